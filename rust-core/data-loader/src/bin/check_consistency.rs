@@ -0,0 +1,117 @@
+use anyhow::{Context, Result};
+use chrono::Utc;
+use clap::Parser;
+use indexing::consistency::{ConsistencyChecker, ConsistencyOptions, ObjectStateSource};
+use indexing::sampling::SamplingOptions;
+use indexing::store::{DgraphStore, ElasticsearchStore, GraphStore, SearchStore};
+use ontology_engine::{Ontology, OntologyConfig, OntologyHandle};
+use std::path::PathBuf;
+use std::sync::Arc;
+use versioning::event_log::{EventLog, ObjectEvent};
+use versioning::time_query::TimeQuery;
+
+#[derive(Parser, Debug)]
+#[command(name = "check-consistency")]
+#[command(about = "Check that the search store and graph store agree for an object type")]
+struct Args {
+    /// Compiled ontology (JSON, or YAML by extension)
+    #[arg(short, long)]
+    ontology: PathBuf,
+
+    /// Object type ID to check
+    #[arg(short = 't', long)]
+    object_type: String,
+
+    #[arg(long, default_value = "http://localhost:9200")]
+    elasticsearch_url: String,
+
+    #[arg(long, default_value = "http://localhost:9080")]
+    dgraph_url: String,
+
+    /// Verify a uniform sample of this many objects and links instead of all of them
+    #[arg(long)]
+    sample_size: Option<usize>,
+
+    /// Seed for the sample, for reproducible runs
+    #[arg(long)]
+    seed: Option<u64>,
+
+    /// Delete dangling links and reindex missing documents
+    #[arg(long)]
+    repair: bool,
+
+    /// Event log export (JSON array of events) used to reindex missing documents
+    #[arg(long)]
+    event_log: Option<PathBuf>,
+
+    #[arg(long, default_value_t = indexing::consistency::DEFAULT_SCAN_PAGE_SIZE)]
+    page_size: usize,
+}
+
+fn load_ontology(path: &PathBuf) -> Result<Ontology> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read ontology file: {}", path.display()))?;
+    let is_yaml = matches!(path.extension().and_then(|e| e.to_str()), Some("yaml" | "yml"));
+    let config: OntologyConfig = if is_yaml {
+        serde_yaml::from_str(&content).context("Failed to parse ontology YAML")?
+    } else {
+        serde_json::from_str(&content).context("Failed to parse ontology JSON")?
+    };
+    Ontology::from_config(config).context("Invalid ontology")
+}
+
+fn load_event_log(path: &PathBuf) -> Result<ObjectStateSource> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read event log: {}", path.display()))?;
+    let events: Vec<ObjectEvent> = serde_json::from_str(&content).context("Failed to parse event log")?;
+    let mut log = EventLog::new();
+    for event in events {
+        log.record(event);
+    }
+    let history = TimeQuery::new(log);
+    Ok(Arc::new(move |object_type: &str, object_id: &str| {
+        history
+            .reconstruct_object(object_type, object_id, Utc::now())
+            .map(|object| object.properties)
+    }))
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let args = Args::parse();
+
+    let ontology = load_ontology(&args.ontology)?;
+    if ontology.get_object_type(&args.object_type).is_none() {
+        anyhow::bail!("Unknown object type: {}", args.object_type);
+    }
+    if args.repair && args.event_log.is_none() {
+        eprintln!("No --event-log given: dangling links will be deleted but missing documents cannot be reindexed");
+    }
+
+    let search: Arc<dyn SearchStore> = Arc::new(ElasticsearchStore::new(args.elasticsearch_url.clone())?);
+    let graph: Arc<dyn GraphStore> = Arc::new(DgraphStore::new(args.dgraph_url.clone()).await?);
+    let mut checker = ConsistencyChecker::new(OntologyHandle::new(ontology), search, graph);
+    if let Some(path) = &args.event_log {
+        checker = checker.with_object_source(load_event_log(path)?);
+    }
+
+    let options = ConsistencyOptions {
+        sampling: args.sample_size.map(|n| {
+            let sampling = SamplingOptions::new(n);
+            match args.seed {
+                Some(seed) => sampling.with_seed(seed),
+                None => sampling,
+            }
+        }),
+        repair: args.repair,
+        page_size: args.page_size.max(1),
+    };
+    let report = checker.check(&args.object_type, &options, None).await?;
+
+    print!("{}", report);
+    let repaired_cleanly = report.repair.as_ref().is_some_and(|r| r.failures.is_empty());
+    if !report.is_consistent() && !repaired_cleanly {
+        std::process::exit(1);
+    }
+    Ok(())
+}
@@ -0,0 +1,35 @@
+pub mod records;
+pub mod loader;
+
+pub use loader::{DataLoader, IngestFailure, IngestReport, LoadOptions, TypeReport};
+pub use records::Record;
+
+use indexing::store::StoreError;
+use std::path::PathBuf;
+
+/// Errors that stop a load
+#[derive(Debug, thiserror::Error)]
+pub enum LoadError {
+    #[error("Failed to read {}: {source}", path.display())]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+
+    #[error("{location}: {message}")]
+    Parse {
+        location: String,
+        message: String,
+    },
+
+    /// An invalid record or link, when `continue_on_error` is off
+    #[error("{location}: {}", messages.join("; "))]
+    Invalid {
+        location: String,
+        messages: Vec<String>,
+    },
+
+    #[error(transparent)]
+    Store(#[from] StoreError),
+}
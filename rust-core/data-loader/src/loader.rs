@@ -0,0 +1,563 @@
+use crate::records::{data_files, read_records, Record};
+use crate::LoadError;
+use indexing::store::{GraphStore, IndexedObject, SearchStore};
+use indexing::sync::plan_upsert;
+use ontology_engine::{ObjectRef, ObjectType, Ontology, PropertyMap, PropertyType, PropertyValue};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+use std::path::Path;
+use std::sync::Arc;
+use versioning::event_log::EventLog;
+
+/// Name of the optional links file in a data directory (`links.jsonl` or `links.csv`)
+pub const LINKS_FILE: &str = "links";
+
+/// Objects sent to the search store per bulk request
+pub const DEFAULT_BATCH_SIZE: usize = 500;
+
+/// Annotation naming the link type an object reference property creates
+pub const LINK_TYPE_ANNOTATION: &str = "linkType";
+
+/// User recorded on ingest events
+const LOADER_USER: &str = "load-data";
+
+/// Options controlling a load
+#[derive(Debug, Clone)]
+pub struct LoadOptions {
+    /// Validate everything but write nothing
+    pub dry_run: bool,
+    /// Skip invalid records and links instead of stopping at the first one
+    pub continue_on_error: bool,
+    /// Only load these object types (and links between them); empty means all
+    pub only_types: Vec<String>,
+    pub batch_size: usize,
+    /// Write every record, even those whose content hash matches the stored object
+    pub force: bool,
+}
+
+impl Default for LoadOptions {
+    fn default() -> Self {
+        Self {
+            dry_run: false,
+            continue_on_error: false,
+            only_types: Vec::new(),
+            batch_size: DEFAULT_BATCH_SIZE,
+            force: false,
+        }
+    }
+}
+
+/// Per-type ingest counts
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TypeReport {
+    pub read: usize,
+    pub loaded: usize,
+    pub failed: usize,
+    /// Loaded records that were new, changed, or identical to the stored object (and so
+    /// not written)
+    pub created: usize,
+    pub updated: usize,
+    pub unchanged: usize,
+    /// Fields that matched no property; they are indexed as-is unless the type has strict
+    /// properties, which fails the record instead
+    pub unknown_fields: Vec<String>,
+    /// Values of unknown fields indexed on loaded objects
+    pub unknown_values: usize,
+}
+
+/// A record or link that was skipped
+#[derive(Debug, Clone)]
+pub struct IngestFailure {
+    pub location: String,
+    pub messages: Vec<String>,
+}
+
+/// Summary of a load, printed by the CLI
+#[derive(Debug, Clone, Default)]
+pub struct IngestReport {
+    pub dry_run: bool,
+    pub types: BTreeMap<String, TypeReport>,
+    pub links_created: usize,
+    pub links_failed: usize,
+    pub events_recorded: usize,
+    pub failures: Vec<IngestFailure>,
+}
+
+impl IngestReport {
+    pub fn objects_loaded(&self) -> usize {
+        self.types.values().map(|t| t.loaded).sum()
+    }
+
+    pub fn has_failures(&self) -> bool {
+        !self.failures.is_empty()
+    }
+}
+
+impl fmt::Display for IngestReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Ingest report{}", if self.dry_run { " (dry run, nothing written)" } else { "" })?;
+        for (object_type, report) in &self.types {
+            writeln!(
+                f,
+                "  {:<30} read {:>8}  loaded {:>8}  failed {:>6}",
+                object_type, report.read, report.loaded, report.failed
+            )?;
+            writeln!(
+                f,
+                "    created {}, updated {}, unchanged {}",
+                report.created, report.updated, report.unchanged
+            )?;
+            if report.unknown_values > 0 {
+                writeln!(
+                    f,
+                    "    unknown fields indexed as-is ({} values): {}",
+                    report.unknown_values,
+                    report.unknown_fields.join(", ")
+                )?;
+            } else if !report.unknown_fields.is_empty() {
+                writeln!(f, "    unknown fields: {}", report.unknown_fields.join(", "))?;
+            }
+        }
+        writeln!(f, "  links created: {}, failed: {}", self.links_created, self.links_failed)?;
+        writeln!(f, "  events recorded: {}", self.events_recorded)?;
+        for failure in &self.failures {
+            writeln!(f, "  ✗ {}: {}", failure.location, failure.messages.join("; "))?;
+        }
+        Ok(())
+    }
+}
+
+/// Loads a directory of per-type data files into the stores, validating every record
+/// against the ontology.
+///
+/// Each object type is read from `<type>.jsonl`, `<type>.csv` or `<type>.geojson`. Record
+/// fields are matched to property IDs exactly, then case-insensitively (also against display
+/// names, with spaces and dashes read as underscores). Fields matching no property are
+/// indexed under their own name and reported, or fail the record if the type has strict
+/// properties. Defaults are applied before validation. Links come from `links.jsonl`/`links.csv` (`link_type`, `source`, `target`,
+/// other fields become link properties) and from object reference properties that name a
+/// link type through the `linkType` annotation or share the link type's ID.
+pub struct DataLoader {
+    ontology: Arc<Ontology>,
+    search: Arc<dyn SearchStore>,
+    graph: Arc<dyn GraphStore>,
+    event_log: EventLog,
+    options: LoadOptions,
+}
+
+/// Object reference values collected while loading, turned into links once every type is in
+struct PendingLink {
+    location: String,
+    link_type: String,
+    source: String,
+    target: String,
+    properties: PropertyMap,
+}
+
+impl DataLoader {
+    pub fn new(ontology: Arc<Ontology>, search: Arc<dyn SearchStore>, graph: Arc<dyn GraphStore>) -> Self {
+        Self {
+            ontology,
+            search,
+            graph,
+            event_log: EventLog::new(),
+            options: LoadOptions::default(),
+        }
+    }
+
+    pub fn with_options(mut self, options: LoadOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Record ingest events into an existing log instead of a fresh one
+    pub fn with_event_log(mut self, event_log: EventLog) -> Self {
+        self.event_log = event_log;
+        self
+    }
+
+    pub fn event_log(&self) -> &EventLog {
+        &self.event_log
+    }
+
+    pub fn into_event_log(self) -> EventLog {
+        self.event_log
+    }
+
+    fn selected(&self, object_type: &str) -> bool {
+        self.options.only_types.is_empty() || self.options.only_types.iter().any(|t| t == object_type)
+    }
+
+    /// Load every selected object type and then the links between them
+    pub async fn load_dir(&mut self, dir: &Path) -> Result<IngestReport, LoadError> {
+        let mut report = IngestReport {
+            dry_run: self.options.dry_run,
+            ..Default::default()
+        };
+        // object type -> IDs loaded in this run, for link endpoint checks
+        let mut loaded_ids: HashMap<String, HashSet<String>> = HashMap::new();
+        let mut pending_links = Vec::new();
+
+        let mut object_types: Vec<ObjectType> = self.ontology.object_types()
+            .filter(|t| self.selected(&t.id))
+            .cloned()
+            .collect();
+        object_types.sort_by(|a, b| a.id.cmp(&b.id));
+
+        for object_type in &object_types {
+            let files = data_files(dir, &object_type.id);
+            if files.is_empty() {
+                continue;
+            }
+            let ids = loaded_ids.entry(object_type.id.clone()).or_default();
+            let type_report = report.types.entry(object_type.id.clone()).or_default();
+            for path in files {
+                let records = read_records(&path)?;
+                let mut batch = Vec::new();
+                for record in records {
+                    type_report.read += 1;
+                    let location = record.location.clone();
+                    match self.prepare_object(object_type, record, type_report) {
+                        Ok((object_id, properties)) if ids.insert(object_id.clone()) => {
+                            pending_links.extend(self.reference_links(object_type, &object_id, &properties, &location));
+                            batch.push(IndexedObject::new(object_type.id.clone(), object_id, properties));
+                        }
+                        Ok((object_id, _)) => {
+                            let messages = vec![format!("Duplicate primary key '{}'", object_id)];
+                            self.fail(&mut report.failures, location, messages)?;
+                            type_report.failed += 1;
+                        }
+                        Err(messages) => {
+                            self.fail(&mut report.failures, location, messages)?;
+                            type_report.failed += 1;
+                        }
+                    }
+                    if batch.len() >= self.options.batch_size {
+                        type_report.loaded += batch.len();
+                        report.events_recorded += self.flush(std::mem::take(&mut batch), type_report).await?;
+                    }
+                }
+                type_report.loaded += batch.len();
+                report.events_recorded += self.flush(batch, type_report).await?;
+            }
+            type_report.unknown_fields.sort();
+            type_report.unknown_fields.dedup();
+        }
+
+        for path in data_files(dir, LINKS_FILE) {
+            for record in read_records(&path)? {
+                match self.link_from_record(record) {
+                    Ok(Some(link)) => pending_links.push(link),
+                    Ok(None) => {}
+                    Err((location, messages)) => {
+                        self.fail(&mut report.failures, location, messages)?;
+                        report.links_failed += 1;
+                    }
+                }
+            }
+        }
+
+        for link in pending_links {
+            match self.check_link(&link, &loaded_ids).await {
+                Ok(()) => {
+                    if !self.options.dry_run {
+                        self.graph.create_link(&link.link_type, &link.source, &link.target, &link.properties).await?;
+                    }
+                    report.links_created += 1;
+                }
+                Err(message) => {
+                    self.fail(&mut report.failures, link.location, vec![message])?;
+                    report.links_failed += 1;
+                }
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// Record a failure, or stop the load unless `continue_on_error` is set
+    fn fail(&self, failures: &mut Vec<IngestFailure>, location: String, messages: Vec<String>) -> Result<(), LoadError> {
+        if !self.options.continue_on_error {
+            return Err(LoadError::Invalid { location, messages });
+        }
+        failures.push(IngestFailure { location, messages });
+        Ok(())
+    }
+
+    /// Index the new and changed objects of a batch and record a creation or update event
+    /// for each; objects whose content hash matches the stored one are skipped unless
+    /// `force` is set. Returns the events recorded.
+    async fn flush(&mut self, batch: Vec<IndexedObject>, type_report: &mut TypeReport) -> Result<usize, LoadError> {
+        if batch.is_empty() {
+            return Ok(0);
+        }
+        let plan = plan_upsert(self.search.as_ref(), batch, self.options.force).await?;
+        type_report.created += plan.created.len();
+        type_report.updated += plan.updated.len();
+        type_report.unchanged += plan.unchanged;
+        if self.options.dry_run || plan.writes() == 0 {
+            return Ok(0);
+        }
+        let count = plan.writes();
+        self.search.bulk_index(plan.created.iter().chain(&plan.updated).cloned().collect()).await?;
+        let user = Some(LOADER_USER.to_string());
+        for object in plan.created {
+            self.event_log.record_created(object.object_type, object.object_id, object.properties, user.clone());
+        }
+        for object in plan.updated {
+            self.event_log.record_updated(object.object_type, object.object_id, object.properties, user.clone());
+        }
+        Ok(count)
+    }
+
+    /// Map a record onto an object type: normalize keys, coerce values, apply defaults and
+    /// validate. Returns the object ID and properties, or every problem found.
+    fn prepare_object(
+        &self,
+        object_type: &ObjectType,
+        record: Record,
+        type_report: &mut TypeReport,
+    ) -> Result<(String, PropertyMap), Vec<String>> {
+        let mut properties = PropertyMap::new();
+        let mut errors = Vec::new();
+        let mut unknown = PropertyMap::new();
+
+        for (key, value) in record.fields {
+            let Some(property) = resolve_property(object_type, &key) else {
+                type_report.unknown_fields.push(key.clone());
+                match serde_json::from_value::<PropertyValue>(value) {
+                    Ok(PropertyValue::Null) => {}
+                    Ok(value) => unknown.insert(key, value),
+                    Err(e) => errors.push(format!("Field '{}': {}", key, e)),
+                }
+                continue;
+            };
+            match coerce_value(&property.property_type, value) {
+                Ok(PropertyValue::Null) => {}
+                Ok(value) => properties.insert(property.id.clone(), value),
+                Err(e) => errors.push(format!("Property '{}': {}", property.id, e)),
+            }
+        }
+        if let Some(geometry) = record.geometry {
+            let geo_property = object_type.properties.iter().find(|p| {
+                matches!(p.property_type, PropertyType::GeoJSON | PropertyType::GeoJSONAlt)
+                    && !properties.contains_key(&p.id)
+            });
+            match geo_property {
+                Some(property) => properties.insert(property.id.clone(), PropertyValue::GeoJSON(geometry.to_string())),
+                None => {
+                    type_report.unknown_fields.push("geometry".to_string());
+                    unknown.insert("geometry".to_string(), PropertyValue::GeoJSON(geometry.to_string()));
+                }
+            }
+        }
+        let unknown_keys = object_type.unknown_properties(&unknown);
+        if object_type.strict_properties && !unknown_keys.is_empty() {
+            errors.push(format!(
+                "Unknown properties on strict type '{}': {}",
+                object_type.id,
+                unknown_keys.join(", ")
+            ));
+        }
+
+        object_type.apply_defaults(&mut properties);
+        if let Err(reprojection_errors) = object_type.reproject_geometries(&mut properties) {
+            errors.extend(reprojection_errors);
+        }
+        if let Err(validation_errors) = object_type.validate_object(&properties) {
+            errors.extend(validation_errors);
+        }
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+
+        let object_id = properties
+            .get(&object_type.primary_key)
+            .map(|v| v.to_string().trim().to_string())
+            .unwrap_or_default();
+        if object_id.is_empty() {
+            return Err(vec![format!("Empty primary key '{}'", object_type.primary_key)]);
+        }
+        type_report.unknown_values += unknown.len();
+        for (key, value) in unknown.iter() {
+            properties.insert(key.clone(), value.clone());
+        }
+        Ok((object_id, properties))
+    }
+
+    /// Links implied by object reference properties of a loaded object
+    fn reference_links(
+        &self,
+        object_type: &ObjectType,
+        object_id: &str,
+        properties: &PropertyMap,
+        location: &str,
+    ) -> Vec<PendingLink> {
+        let mut links = Vec::new();
+        for property in &object_type.properties {
+            if !matches!(property.property_type, PropertyType::ObjectReference | PropertyType::ObjectReferenceAlt) {
+                continue;
+            }
+            let Some(PropertyValue::ObjectReference(target)) = properties.get(&property.id) else {
+                continue;
+            };
+            let link_type_id = property.annotations.get(LINK_TYPE_ANNOTATION).unwrap_or(&property.id);
+            let Some(link_type) = self.ontology.get_link_type(link_type_id) else {
+                continue;
+            };
+            // Bare IDs are objects of the declared target, else of the link's target type
+            let default_type = property.reference_target.as_deref().unwrap_or(&link_type.target);
+            let Ok(target) = ObjectRef::parse(target, Some(default_type)) else {
+                continue;
+            };
+            if link_type.source == object_type.id && target.object_type == link_type.target && self.selected(&link_type.target) {
+                links.push(PendingLink {
+                    location: format!("{} ({})", location, property.id),
+                    link_type: link_type.id.clone(),
+                    source: object_id.to_string(),
+                    target: target.object_id,
+                    properties: PropertyMap::new(),
+                });
+            }
+        }
+        links
+    }
+
+    /// Parse a links file record; `Ok(None)` for links between types that are not selected
+    fn link_from_record(&self, record: Record) -> Result<Option<PendingLink>, (String, Vec<String>)> {
+        let location = record.location;
+        let mut fields = record.fields;
+        let mut take = |key: &str| match fields.remove(key) {
+            Some(Value::String(s)) => Some(s.trim().to_string()),
+            Some(Value::Number(n)) => Some(n.to_string()),
+            _ => None,
+        };
+        let (Some(link_type_id), Some(source), Some(target)) = (take("link_type"), take("source"), take("target")) else {
+            return Err((location, vec!["Link records need link_type, source and target".to_string()]));
+        };
+        let Some(link_type) = self.ontology.get_link_type(&link_type_id) else {
+            return Err((location, vec![format!("Unknown link type '{}'", link_type_id)]));
+        };
+        if !self.selected(&link_type.source) || !self.selected(&link_type.target) {
+            return Ok(None);
+        }
+
+        let mut properties = PropertyMap::new();
+        let mut errors = Vec::new();
+        for (key, value) in fields {
+            let Some(property) = link_type.properties.iter().find(|p| p.id == key) else {
+                errors.push(format!("Unknown link property '{}'", key));
+                continue;
+            };
+            match coerce_value(&property.property_type, value) {
+                Ok(PropertyValue::Null) => {}
+                Ok(value) => properties.insert(property.id.clone(), value),
+                Err(e) => errors.push(format!("Property '{}': {}", property.id, e)),
+            }
+        }
+        if !errors.is_empty() {
+            return Err((location, errors));
+        }
+        Ok(Some(PendingLink {
+            location,
+            link_type: link_type.id.clone(),
+            source,
+            target,
+            properties,
+        }))
+    }
+
+    /// Both endpoints must have been loaded in this run or already exist in the search store
+    async fn check_link(&self, link: &PendingLink, loaded_ids: &HashMap<String, HashSet<String>>) -> Result<(), String> {
+        let link_type = self.ontology.get_link_type(&link.link_type)
+            .ok_or_else(|| format!("Unknown link type '{}'", link.link_type))?;
+        for (end, object_type, object_id) in [
+            ("source", &link_type.source, &link.source),
+            ("target", &link_type.target, &link.target),
+        ] {
+            if loaded_ids.get(object_type).map_or(false, |ids| ids.contains(object_id)) {
+                continue;
+            }
+            let exists = self.search.get_object(object_type, object_id).await
+                .map_err(|e| e.to_string())?
+                .is_some();
+            if !exists {
+                return Err(format!(
+                    "Link '{}' {} {} '{}' does not exist",
+                    link.link_type, end, object_type, object_id
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Find the property a record field refers to
+fn resolve_property<'a>(object_type: &'a ObjectType, key: &str) -> Option<&'a ontology_engine::Property> {
+    if let Some(property) = object_type.get_property(key) {
+        return Some(property);
+    }
+    let normalized = normalize_key(key);
+    object_type.properties.iter().find(|p| {
+        normalize_key(&p.id) == normalized
+            || p.display_name.as_deref().map_or(false, |name| normalize_key(name) == normalized)
+    })
+}
+
+fn normalize_key(key: &str) -> String {
+    key.trim().to_lowercase().replace([' ', '-'], "_")
+}
+
+/// Convert a raw JSON value (CSV values are always strings) into the property's type
+pub fn coerce_value(property_type: &PropertyType, value: Value) -> Result<PropertyValue, String> {
+    let text = match &value {
+        Value::Null => return Ok(PropertyValue::Null),
+        Value::String(s) if s.trim().is_empty() => return Ok(PropertyValue::Null),
+        Value::String(s) => Some(s.trim().to_string()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    };
+
+    match property_type {
+        PropertyType::String | PropertyType::Int => {
+            text.map(PropertyValue::String).ok_or_else(|| "expected a string".to_string())
+        }
+        PropertyType::Integer => match &value {
+            Value::Number(n) if n.as_i64().is_some() => Ok(PropertyValue::Integer(n.as_i64().unwrap_or_default())),
+            // Whole-valued floats such as 3.0 are accepted as integers
+            Value::Number(n) => n.as_f64()
+                .filter(|f| f.fract() == 0.0)
+                .map(|f| PropertyValue::Integer(f as i64))
+                .ok_or_else(|| format!("'{}' is not an integer", n)),
+            _ => text.as_deref()
+                .and_then(|t| t.parse::<i64>().ok())
+                .map(PropertyValue::Integer)
+                .ok_or_else(|| format!("'{}' is not an integer", value)),
+        },
+        PropertyType::Double | PropertyType::Float => text.as_deref()
+            .and_then(|t| t.parse::<f64>().ok())
+            .map(PropertyValue::Double)
+            .ok_or_else(|| format!("'{}' is not a number", value)),
+        PropertyType::Boolean | PropertyType::Bool => match text.as_deref().map(|t| t.to_lowercase()).as_deref() {
+            Some("true" | "1" | "yes") => Ok(PropertyValue::Boolean(true)),
+            Some("false" | "0" | "no") => Ok(PropertyValue::Boolean(false)),
+            _ => Err(format!("'{}' is not a boolean", value)),
+        },
+        PropertyType::Date => text.map(PropertyValue::Date).ok_or_else(|| "expected a date string".to_string()),
+        PropertyType::DateTime | PropertyType::Timestamp => {
+            text.map(PropertyValue::DateTime).ok_or_else(|| "expected a datetime string".to_string())
+        }
+        PropertyType::ObjectReference | PropertyType::ObjectReferenceAlt => {
+            text.map(PropertyValue::ObjectReference).ok_or_else(|| "expected an object ID".to_string())
+        }
+        PropertyType::GeoJSON | PropertyType::GeoJSONAlt => match value {
+            Value::String(s) => Ok(PropertyValue::GeoJSON(s)),
+            other => Ok(PropertyValue::GeoJSON(other.to_string())),
+        },
+        PropertyType::Array { .. } | PropertyType::Map { .. } | PropertyType::Object(_) | PropertyType::Union { .. } => {
+            serde_json::from_value(value).map_err(|e| e.to_string())
+        }
+    }
+}
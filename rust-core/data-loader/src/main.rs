@@ -0,0 +1,102 @@
+use anyhow::{Context, Result};
+use clap::Parser;
+use data_loader::{DataLoader, LoadOptions};
+use indexing::in_memory::{InMemoryGraphStore, InMemorySearchStore};
+use indexing::store::{DgraphStore, ElasticsearchStore, GraphStore, SearchStore};
+use ontology_engine::{Ontology, OntologyConfig};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+#[derive(Parser, Debug)]
+#[command(name = "load-data")]
+#[command(about = "Validate and load a data directory into the ontology stores")]
+struct Args {
+    /// Compiled ontology (JSON, or YAML by extension)
+    #[arg(short, long)]
+    ontology: PathBuf,
+
+    /// Directory of per-type <type>.jsonl/.csv/.geojson files and an optional links file
+    #[arg(short, long)]
+    data_dir: PathBuf,
+
+    #[arg(long, default_value = "http://localhost:9200")]
+    elasticsearch_url: String,
+
+    #[arg(long, default_value = "http://localhost:9080")]
+    dgraph_url: String,
+
+    /// Load into throwaway in-memory stores (useful to check a dataset end to end)
+    #[arg(long)]
+    in_memory: bool,
+
+    /// Validate everything but write nothing
+    #[arg(long)]
+    dry_run: bool,
+
+    /// Report invalid records and links instead of stopping at the first one
+    #[arg(long)]
+    continue_on_error: bool,
+
+    /// Comma-separated object type IDs to load
+    #[arg(long, value_delimiter = ',')]
+    only_types: Vec<String>,
+
+    #[arg(long, default_value_t = data_loader::loader::DEFAULT_BATCH_SIZE)]
+    batch_size: usize,
+
+    /// Rewrite every record, even those unchanged since the last load
+    #[arg(long)]
+    force: bool,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let args = Args::parse();
+
+    let content = std::fs::read_to_string(&args.ontology)
+        .with_context(|| format!("Failed to read ontology file: {}", args.ontology.display()))?;
+    let is_yaml = matches!(
+        args.ontology.extension().and_then(|e| e.to_str()),
+        Some("yaml" | "yml")
+    );
+    let config: OntologyConfig = if is_yaml {
+        serde_yaml::from_str(&content).context("Failed to parse ontology YAML")?
+    } else {
+        serde_json::from_str(&content).context("Failed to parse ontology JSON")?
+    };
+    let ontology = Ontology::from_config(config).context("Invalid ontology")?;
+    for warning in ontology.warnings() {
+        eprintln!("warning: {}", warning);
+    }
+
+    for only in &args.only_types {
+        if ontology.get_object_type(only).is_none() {
+            anyhow::bail!("Unknown object type in --only-types: {}", only);
+        }
+    }
+
+    let (search, graph): (Arc<dyn SearchStore>, Arc<dyn GraphStore>) = if args.in_memory {
+        (Arc::new(InMemorySearchStore::new()), Arc::new(InMemoryGraphStore::new()))
+    } else {
+        (
+            Arc::new(ElasticsearchStore::new(args.elasticsearch_url.clone())?),
+            Arc::new(DgraphStore::new(args.dgraph_url.clone()).await?),
+        )
+    };
+
+    let options = LoadOptions {
+        dry_run: args.dry_run,
+        continue_on_error: args.continue_on_error,
+        only_types: args.only_types,
+        batch_size: args.batch_size.max(1),
+        force: args.force,
+    };
+    let mut loader = DataLoader::new(Arc::new(ontology), search, graph).with_options(options);
+    let report = loader.load_dir(&args.data_dir).await?;
+
+    print!("{}", report);
+    if report.has_failures() {
+        std::process::exit(1);
+    }
+    Ok(())
+}
@@ -0,0 +1,179 @@
+use crate::LoadError;
+use serde_json::{Map, Value};
+use std::path::{Path, PathBuf};
+
+/// Data file formats recognised in a data directory, in lookup order
+pub const FORMATS: [&str; 3] = ["jsonl", "csv", "geojson"];
+
+/// A raw record read from a data file, before it is mapped onto an object type
+#[derive(Debug, Clone)]
+pub struct Record {
+    /// `file:line` (or `file:feature N`) for error reporting
+    pub location: String,
+    pub fields: Map<String, Value>,
+    /// Feature geometry, for GeoJSON files
+    pub geometry: Option<Value>,
+}
+
+/// Data files for a type (`<type>.jsonl`, `<type>.csv`, `<type>.geojson`) that exist in `dir`
+pub fn data_files(dir: &Path, name: &str) -> Vec<PathBuf> {
+    FORMATS
+        .iter()
+        .map(|ext| dir.join(format!("{}.{}", name, ext)))
+        .filter(|path| path.is_file())
+        .collect()
+}
+
+/// Read every record from a JSONL, CSV or GeoJSON file
+pub fn read_records(path: &Path) -> Result<Vec<Record>, LoadError> {
+    let content = std::fs::read_to_string(path).map_err(|source| LoadError::Io {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    let file = path
+        .file_name()
+        .map(|f| f.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    match path.extension().and_then(|e| e.to_str()) {
+        Some("jsonl") => read_jsonl(&file, &content),
+        Some("csv") => read_csv(&file, &content),
+        Some("geojson") => read_geojson(&file, &content),
+        other => Err(LoadError::Parse {
+            location: file,
+            message: format!("Unsupported data file extension {:?}", other),
+        }),
+    }
+}
+
+fn read_jsonl(file: &str, content: &str) -> Result<Vec<Record>, LoadError> {
+    let mut records = Vec::new();
+    for (idx, line) in content.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let location = format!("{}:{}", file, idx + 1);
+        match serde_json::from_str::<Value>(line) {
+            Ok(Value::Object(fields)) => records.push(Record { location, fields, geometry: None }),
+            Ok(_) => {
+                return Err(LoadError::Parse { location, message: "expected a JSON object".to_string() })
+            }
+            Err(e) => return Err(LoadError::Parse { location, message: e.to_string() }),
+        }
+    }
+    Ok(records)
+}
+
+fn read_geojson(file: &str, content: &str) -> Result<Vec<Record>, LoadError> {
+    let parsed: Value = serde_json::from_str(content).map_err(|e| LoadError::Parse {
+        location: format!("{}:{}", file, e.line()),
+        message: e.to_string(),
+    })?;
+    let features = parsed["features"].as_array().ok_or_else(|| LoadError::Parse {
+        location: file.to_string(),
+        message: "expected a FeatureCollection".to_string(),
+    })?;
+
+    Ok(features
+        .iter()
+        .enumerate()
+        .map(|(idx, feature)| Record {
+            location: format!("{}:feature {}", file, idx + 1),
+            fields: feature["properties"].as_object().cloned().unwrap_or_default(),
+            geometry: feature.get("geometry").filter(|g| !g.is_null()).cloned(),
+        })
+        .collect())
+}
+
+/// CSV with a header row. Quoted fields may contain commas, newlines and `""` escapes.
+/// Every value is read as a string; empty values are treated as missing.
+fn read_csv(file: &str, content: &str) -> Result<Vec<Record>, LoadError> {
+    let rows = parse_csv(content).map_err(|(line, message)| LoadError::Parse {
+        location: format!("{}:{}", file, line),
+        message,
+    })?;
+    let mut rows = rows.into_iter();
+    let Some((_, header)) = rows.next() else {
+        return Ok(Vec::new());
+    };
+
+    let mut records = Vec::new();
+    for (line, row) in rows {
+        let location = format!("{}:{}", file, line);
+        if row.len() != header.len() {
+            return Err(LoadError::Parse {
+                location,
+                message: format!("expected {} fields, found {}", header.len(), row.len()),
+            });
+        }
+        let fields = header
+            .iter()
+            .zip(row)
+            .filter(|(_, value)| !value.is_empty())
+            .map(|(key, value)| (key.clone(), Value::String(value)))
+            .collect();
+        records.push(Record { location, fields, geometry: None });
+    }
+    Ok(records)
+}
+
+/// Split CSV content into rows of fields, each tagged with its starting line number
+fn parse_csv(content: &str) -> Result<Vec<(usize, Vec<String>)>, (usize, String)> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut line = 1;
+    let mut row_start = 1;
+    let mut chars = content.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match (c, in_quotes) {
+            ('"', true) if chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            ('"', true) => in_quotes = false,
+            ('"', false) if field.is_empty() => in_quotes = true,
+            (',', false) => row.push(std::mem::take(&mut field)),
+            ('\r', false) if chars.peek() == Some(&'\n') => {}
+            ('\n', false) => {
+                row.push(std::mem::take(&mut field));
+                if !(row.len() == 1 && row[0].is_empty()) {
+                    rows.push((row_start, std::mem::take(&mut row)));
+                }
+                row.clear();
+                line += 1;
+                row_start = line;
+            }
+            (c, _) => {
+                if c == '\n' {
+                    line += 1;
+                }
+                field.push(c);
+            }
+        }
+    }
+    if in_quotes {
+        return Err((row_start, "unterminated quoted field".to_string()));
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push((row_start, row));
+    }
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_csv_handles_quotes_and_newlines() {
+        let rows = parse_csv("id,name,notes\r\n1,\"Smith, Jo\",\"said \"\"hi\"\"\nthen left\"\n\n2,Lee,\n").unwrap();
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[1], (2, vec!["1".to_string(), "Smith, Jo".to_string(), "said \"hi\"\nthen left".to_string()]));
+        assert_eq!(rows[2], (5, vec!["2".to_string(), "Lee".to_string(), String::new()]));
+        assert!(parse_csv("a,\"b\n").is_err());
+    }
+}
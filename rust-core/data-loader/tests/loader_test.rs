@@ -0,0 +1,288 @@
+use data_loader::{DataLoader, LoadError, LoadOptions};
+use indexing::in_memory::{InMemoryGraphStore, InMemorySearchStore};
+use indexing::store::{Filter, FilterOperator, GraphStore, SearchStore};
+use ontology_engine::{CoordinateValidation, GeoOptions, Ontology, OntologyConfig, PropertyValue, ValidatorRegistry};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+fn fixtures() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures")
+}
+
+fn ontology() -> Arc<Ontology> {
+    let content = std::fs::read_to_string(fixtures().join("ontology.json")).unwrap();
+    let config: OntologyConfig = serde_json::from_str(&content).unwrap();
+    Arc::new(Ontology::from_config(config).unwrap())
+}
+
+fn new_loader(options: LoadOptions) -> (DataLoader, Arc<InMemorySearchStore>, Arc<InMemoryGraphStore>) {
+    let search = Arc::new(InMemorySearchStore::new());
+    let graph = Arc::new(InMemoryGraphStore::new());
+    let loader = DataLoader::new(ontology(), search.clone(), graph.clone()).with_options(options);
+    (loader, search, graph)
+}
+
+#[tokio::test]
+async fn test_load_fixture_dataset() {
+    let (mut loader, search, graph) = new_loader(LoadOptions::default());
+    let report = loader.load_dir(&fixtures().join("dataset")).await.unwrap();
+
+    assert!(!report.has_failures(), "{}", report);
+    assert_eq!(search.count_objects("region", None).await.unwrap(), 2);
+    assert_eq!(search.count_objects("company", None).await.unwrap(), 3);
+    assert_eq!(search.count_objects("person", None).await.unwrap(), 4);
+    assert_eq!(report.objects_loaded(), 9);
+    assert_eq!(report.events_recorded, 9);
+    assert_eq!(loader.event_log().get_events_for_object("person", "p1").len(), 1);
+    assert_eq!(report.types["person"].unknown_fields, vec!["nickname".to_string()]);
+    assert_eq!(report.types["person"].unknown_values, 1);
+
+    // 4 works_at links from the links file, 3 located_in links from the headquarters references
+    assert_eq!(report.links_created, 7);
+
+    // Keys were normalized, values coerced and defaults applied
+    let acme = search.get_object("company", "acme").await.unwrap().unwrap();
+    assert_eq!(acme.properties.get("name"), Some(&PropertyValue::String("Acme, Inc.".to_string())));
+    assert_eq!(acme.properties.get("employees"), Some(&PropertyValue::Integer(1200)));
+    assert_eq!(acme.properties.get("public"), Some(&PropertyValue::Boolean(true)));
+    let globex = search.get_object("company", "globex").await.unwrap().unwrap();
+    assert_eq!(globex.properties.get("public"), Some(&PropertyValue::Boolean(false)));
+    let grace = search.get_object("person", "p2").await.unwrap().unwrap();
+    assert_eq!(grace.properties.get("status"), Some(&PropertyValue::String("on_leave".to_string())));
+    let linus = search.get_object("person", "p3").await.unwrap().unwrap();
+    assert_eq!(linus.properties.get("age"), Some(&PropertyValue::Integer(29)));
+    let ken = search.get_object("person", "p4").await.unwrap().unwrap();
+    assert_eq!(ken.properties.get("nickname"), Some(&PropertyValue::String("k".to_string())));
+    let region = search.get_object("region", "NE").await.unwrap().unwrap();
+    assert!(matches!(region.properties.get("boundary"), Some(PropertyValue::GeoJSON(_))));
+
+    // person -> company -> region
+    let mut reached = graph
+        .traverse("p1", &["works_at".to_string(), "located_in".to_string()], 2)
+        .await
+        .unwrap();
+    reached.sort();
+    assert_eq!(reached, vec!["NE".to_string(), "acme".to_string()]);
+
+    // A prefixed reference links to the same region as a bare one
+    assert_eq!(graph.get_connected_objects("initech", "located_in").await.unwrap(), vec!["NE".to_string()]);
+}
+
+#[tokio::test]
+async fn test_dry_run_writes_nothing() {
+    let (mut loader, search, graph) = new_loader(LoadOptions {
+        dry_run: true,
+        ..Default::default()
+    });
+    let report = loader.load_dir(&fixtures().join("dataset")).await.unwrap();
+
+    assert_eq!(report.objects_loaded(), 9);
+    assert_eq!(report.links_created, 7);
+    assert_eq!(report.events_recorded, 0);
+    assert_eq!(search.count_objects("person", None).await.unwrap(), 0);
+    assert!(graph.get_connected_objects("p1", "works_at").await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_reingest_skips_unchanged_records() {
+    let (mut loader, search, _graph) = new_loader(LoadOptions::default());
+    let report = loader.load_dir(&fixtures().join("dataset")).await.unwrap();
+    assert_eq!(report.types["company"].created, 3);
+    let event_log = loader.into_event_log();
+
+    // Loading the same data again writes nothing
+    let mut loader = DataLoader::new(ontology(), search.clone(), Arc::new(InMemoryGraphStore::new()))
+        .with_event_log(event_log);
+    let report = loader.load_dir(&fixtures().join("dataset")).await.unwrap();
+    assert_eq!(report.objects_loaded(), 9);
+    assert_eq!(report.events_recorded, 0);
+    assert_eq!(report.types.values().map(|t| t.unchanged).sum::<usize>(), 9);
+    assert_eq!(report.types.values().map(|t| t.created + t.updated).sum::<usize>(), 0);
+    assert_eq!(search.get_object("company", "globex").await.unwrap().unwrap().revision, 1);
+
+    // One changed field is one update
+    let dir = std::env::temp_dir().join(format!("loader-reingest-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    for entry in std::fs::read_dir(fixtures().join("dataset")).unwrap() {
+        let path = entry.unwrap().path();
+        std::fs::copy(&path, dir.join(path.file_name().unwrap())).unwrap();
+    }
+    let companies = std::fs::read_to_string(dir.join("company.csv")).unwrap();
+    std::fs::write(dir.join("company.csv"), companies.replace("globex,Globex,340", "globex,Globex,345")).unwrap();
+    let report = loader.load_dir(&dir).await.unwrap();
+    assert_eq!(report.events_recorded, 1);
+    assert_eq!((report.types["company"].updated, report.types["company"].unchanged), (1, 2));
+    let events = loader.event_log().get_events_for_object("company", "globex");
+    assert_eq!(events.len(), 2);
+    assert!(matches!(events[1].event_type, versioning::event_log::EventType::ObjectUpdated { .. }));
+    let globex = search.get_object("company", "globex").await.unwrap().unwrap();
+    assert_eq!(globex.properties.get("employees"), Some(&PropertyValue::Integer(345)));
+
+    // --force rewrites everything
+    let mut loader = DataLoader::new(ontology(), search.clone(), Arc::new(InMemoryGraphStore::new()))
+        .with_options(LoadOptions {
+            force: true,
+            ..Default::default()
+        });
+    let report = loader.load_dir(&dir).await.unwrap();
+    assert_eq!(report.events_recorded, 9);
+    assert_eq!(report.types["company"].updated, 3);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_only_types_skips_other_types_and_their_links() {
+    let (mut loader, search, _graph) = new_loader(LoadOptions {
+        only_types: vec!["company".to_string(), "region".to_string()],
+        ..Default::default()
+    });
+    let report = loader.load_dir(&fixtures().join("dataset")).await.unwrap();
+
+    assert_eq!(search.count_objects("person", None).await.unwrap(), 0);
+    assert_eq!(search.count_objects("company", None).await.unwrap(), 3);
+    assert!(!report.types.contains_key("person"));
+    assert_eq!(report.links_created, 3);
+}
+
+#[tokio::test]
+async fn test_invalid_records_stop_the_load_unless_continuing() {
+    let (mut loader, _search, _graph) = new_loader(LoadOptions::default());
+    let err = loader.load_dir(&fixtures().join("invalid")).await.unwrap_err();
+    match err {
+        LoadError::Invalid { location, messages } => {
+            assert_eq!(location, "person.jsonl:2");
+            assert_eq!(messages.len(), 2, "{:?}", messages);
+        }
+        other => panic!("unexpected error: {}", other),
+    }
+
+    let (mut loader, search, _graph) = new_loader(LoadOptions {
+        continue_on_error: true,
+        ..Default::default()
+    });
+    let report = loader.load_dir(&fixtures().join("invalid")).await.unwrap();
+
+    assert_eq!(search.count_objects("person", None).await.unwrap(), 2);
+    assert_eq!(report.types["person"].read, 4);
+    assert_eq!(report.types["person"].failed, 2);
+    assert_eq!(report.links_failed, 2);
+    assert_eq!(report.failures.len(), 4);
+    assert!(report.failures.iter().any(|f| f.messages[0].contains("Duplicate primary key 'p1'")));
+}
+
+#[tokio::test]
+async fn test_strict_type_rejects_records_with_unknown_fields() {
+    let content = std::fs::read_to_string(fixtures().join("ontology.json")).unwrap();
+    let mut config: OntologyConfig = serde_json::from_str(&content).unwrap();
+    for object_type in &mut config.ontology.object_types {
+        object_type.strict_properties = object_type.id == "person";
+    }
+    let search = Arc::new(InMemorySearchStore::new());
+    let mut loader = DataLoader::new(
+        Arc::new(Ontology::from_config(config).unwrap()),
+        search.clone(),
+        Arc::new(InMemoryGraphStore::new()),
+    )
+    .with_options(LoadOptions {
+        continue_on_error: true,
+        ..Default::default()
+    });
+    let report = loader.load_dir(&fixtures().join("dataset")).await.unwrap();
+
+    assert_eq!(report.types["person"].failed, 1);
+    assert_eq!(report.types["person"].unknown_values, 0);
+    assert!(report.failures[0].messages[0].contains("Unknown properties on strict type 'person': nickname"));
+    assert!(search.get_object("person", "p4").await.unwrap().is_none());
+    assert_eq!(search.count_objects("person", None).await.unwrap(), 3);
+}
+
+#[tokio::test]
+async fn test_custom_validator_rejects_records() {
+    // Private companies are capped at 100 employees
+    ValidatorRegistry::register("test_private_company_size", |value, siblings| {
+        let public = siblings.get("public") == Some(&PropertyValue::Boolean(true));
+        match value {
+            PropertyValue::Integer(n) if !public && *n > 100 => Err(format!("{} is too many for a private company", n)),
+            _ => Ok(()),
+        }
+    });
+    let content = std::fs::read_to_string(fixtures().join("ontology.json")).unwrap();
+    let mut config: OntologyConfig = serde_json::from_str(&content).unwrap();
+    let company = config.ontology.object_types.iter_mut().find(|t| t.id == "company").unwrap();
+    let employees = company.properties.iter_mut().find(|p| p.id == "employees").unwrap();
+    employees.custom_validator = Some("test_private_company_size".to_string());
+    let search = Arc::new(InMemorySearchStore::new());
+    let mut loader = DataLoader::new(
+        Arc::new(Ontology::from_config(config).unwrap()),
+        search.clone(),
+        Arc::new(InMemoryGraphStore::new()),
+    )
+    .with_options(LoadOptions {
+        continue_on_error: true,
+        ..Default::default()
+    });
+    let report = loader.load_dir(&fixtures().join("dataset")).await.unwrap();
+
+    assert_eq!(report.types["company"].failed, 1);
+    assert_eq!(
+        report.failures[0].messages,
+        vec!["Property 'employees': 340 is too many for a private company".to_string()]
+    );
+    assert!(search.get_object("company", "globex").await.unwrap().is_none());
+    assert!(search.get_object("company", "acme").await.unwrap().is_some());
+}
+
+#[tokio::test]
+async fn test_projected_geometries_are_reprojected_or_rejected() {
+    // Region boundaries in Web Mercator meters, with strict coordinate validation
+    let load = |crs: Option<&str>| {
+        let content = std::fs::read_to_string(fixtures().join("ontology.json")).unwrap();
+        let mut config: OntologyConfig = serde_json::from_str(&content).unwrap();
+        let region = config.ontology.object_types.iter_mut().find(|t| t.id == "region").unwrap();
+        let boundary = region.properties.iter_mut().find(|p| p.id == "boundary").unwrap();
+        boundary.geo = Some(GeoOptions {
+            crs: crs.map(str::to_string),
+            validation: CoordinateValidation::Strict,
+        });
+        let search = Arc::new(InMemorySearchStore::new());
+        let loader = DataLoader::new(
+            Arc::new(Ontology::from_config(config).unwrap()),
+            search.clone(),
+            Arc::new(InMemoryGraphStore::new()),
+        )
+        .with_options(LoadOptions {
+            continue_on_error: true,
+            ..Default::default()
+        });
+        (loader, search)
+    };
+
+    // Undeclared, the meters are out of WGS84 range
+    let (mut loader, search) = load(None);
+    let report = loader.load_dir(&fixtures().join("projected")).await.unwrap();
+    assert_eq!(report.types["region"].failed, 2);
+    assert!(report.failures[0].messages[0].contains("outside longitude"), "{:?}", report.failures);
+    assert_eq!(search.count_objects("region", None).await.unwrap(), 0);
+
+    let (mut loader, search) = load(Some("EPSG:3857"));
+    let report = loader.load_dir(&fixtures().join("projected")).await.unwrap();
+    assert!(!report.has_failures(), "{}", report);
+    let boston = search.get_object("region", "BOS").await.unwrap().unwrap();
+    let Some(PropertyValue::GeoJSON(boundary)) = boston.properties.get("boundary") else {
+        panic!("{:?}", boston.properties);
+    };
+    let geometry: serde_json::Value = serde_json::from_str(boundary).unwrap();
+    assert!((geometry["coordinates"][0].as_f64().unwrap() - -71.0589).abs() < 1e-4);
+    assert!((geometry["coordinates"][1].as_f64().unwrap() - 42.3601).abs() < 1e-4);
+
+    // Boston to New York is about 306 km
+    let near_boston = |meters: f64| Filter {
+        property: "boundary".to_string(),
+        operator: FilterOperator::WithinDistance,
+        value: PropertyValue::GeoJSON(boundary.clone()),
+        distance: Some(meters),
+        case_insensitive: false,
+    };
+    assert_eq!(search.count_objects("region", Some(&[near_boston(300_000.0)])).await.unwrap(), 1);
+    assert_eq!(search.count_objects("region", Some(&[near_boston(310_000.0)])).await.unwrap(), 2);
+}
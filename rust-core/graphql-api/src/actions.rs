@@ -0,0 +1,539 @@
+use async_graphql::{Context, ErrorExtensions, SimpleObject};
+use indexing::delete_object_cascading;
+use indexing::store::{GraphStore, SearchStore, StoreError};
+use indexing::validating_graph::endpoint_links;
+use ontology_engine::action::OperationType;
+use ontology_engine::validation::{validate_action, ActionContext, ValidationError};
+use ontology_engine::{
+    default_side_effect, Action, ActionExecutor, ActionTypeDef, DeliveryOutcome, ExecutionMode, LinkValidator, ObjectRef,
+    Ontology, PropertyMap, PropertyValue, SideEffectHandlers, SideEffectStatus, SideEffectType,
+};
+use security::SecurityContext;
+use serde_json::Value;
+use std::sync::{Arc, Mutex};
+
+use crate::admin::record_cascade_delete;
+use crate::schema::ObjectEventLog;
+
+/// Outcome of `executeAction`. `operationsExecuted` names what each operation wrote:
+/// `type:id` for objects and the link ID for links.
+#[derive(SimpleObject)]
+pub struct ActionExecutionResultOutput {
+    pub success: bool,
+    pub operations_executed: Vec<String>,
+    pub errors: Vec<String>,
+    pub side_effects_triggered: Vec<TriggeredSideEffectOutput>,
+    /// Whether the writes that succeeded were undone after a later one failed
+    pub rolled_back: bool,
+    /// Undo steps of a rollback that failed; the action is left half-applied when this is
+    /// not empty
+    pub compensation_errors: Vec<String>,
+}
+
+/// A side effect an executed action triggered
+#[derive(SimpleObject)]
+pub struct TriggeredSideEffectOutput {
+    /// e.g. `log` or `webhook`
+    #[graphql(name = "type")]
+    pub effect_type: String,
+    /// `delivered`, or `dead_lettered` if every attempt failed
+    pub status: String,
+    pub effect_id: Option<String>,
+    /// Delivery attempts; more than one means it was retried
+    pub attempts: u32,
+    /// Why the last attempt failed
+    pub error: Option<String>,
+}
+
+/// How to undo a write that was applied
+enum Undo {
+    /// Delete the created object
+    Delete(ObjectRef),
+    /// Write back the object's properties from before the update
+    Restore { object: ObjectRef, previous: PropertyMap },
+    /// Delete the created link
+    DeleteLink { link_type: String, link_id: String, source_id: String, target_id: String },
+    /// Nothing was written
+    Nothing,
+    /// The write cannot be undone, for the reason given
+    Irreversible(String),
+}
+
+/// A store write an action's operation asked for
+#[derive(Debug, Clone)]
+enum ActionWrite {
+    CreateObject { object: ObjectRef, properties: PropertyMap },
+    UpdateObject { object: ObjectRef, properties: PropertyMap },
+    DeleteObject { object: ObjectRef },
+    CreateLink { link_type: String, from: String, to: String, properties: PropertyMap },
+    DeleteLink { link_type: String, from: String, to: String },
+}
+
+/// What the executor's handlers collect. The stores are async and the handlers are not, so
+/// operations and side effects are only recorded while the action runs, and applied once
+/// it has succeeded.
+#[derive(Default)]
+struct RecordedAction {
+    writes: Vec<ActionWrite>,
+    side_effects: Vec<(SideEffectType, PropertyMap)>,
+}
+
+/// GraphQL error for a failed validation, with `code` and, when the failure is about a
+/// parameter, `parameter` extensions
+pub(crate) fn validation_error(error: &ValidationError) -> async_graphql::Error {
+    let code = match error {
+        ValidationError::MissingRole(_) => "MISSING_ROLE",
+        ValidationError::MissingBadge(_) => "MISSING_BADGE",
+        ValidationError::MissingParameter(_) => "MISSING_PARAMETER",
+        ValidationError::InvalidParameter(_) | ValidationError::InvalidParameterValue { .. } => "INVALID_PARAMETER",
+        ValidationError::InvalidCondition(_) => "CONDITION_FAILED",
+        ValidationError::ExecutionFailed(_) => "EXECUTION_FAILED",
+    };
+    async_graphql::Error::new(error.to_string()).extend_with(|_, extensions| {
+        extensions.set("code", code);
+        if let Some(parameter) = error.parameter() {
+            extensions.set("parameter", parameter);
+        }
+    })
+}
+
+/// Parse the `parameters` JSON object of an action, coercing declared parameters to their type
+pub(crate) fn parse_parameters(action_type: &ActionTypeDef, parameters: &str) -> async_graphql::Result<PropertyMap> {
+    let supplied: Value = serde_json::from_str(parameters)
+        .map_err(|e| async_graphql::Error::new(format!("Invalid parameters JSON: {}", e)))?;
+    let Value::Object(supplied) = supplied else {
+        return Err(async_graphql::Error::new("parameters must be a JSON object"));
+    };
+    let mut parsed = PropertyMap::new();
+    for (key, value) in supplied {
+        let value = match action_type.parameters.iter().find(|parameter| parameter.id == key) {
+            Some(parameter) => parameter.value_from_json(&value),
+            None => Err(format!("Action '{}' has no parameter '{}'", action_type.id, key)),
+        };
+        match value {
+            Ok(value) => parsed.insert(key, value),
+            Err(message) => {
+                return Err(validation_error(&ValidationError::InvalidParameterValue { parameter: key, message }))
+            }
+        }
+    }
+    Ok(parsed)
+}
+
+/// Run `action_type` with `parameters` as the caller, on `target` if given. The action is
+/// validated and its operations are applied to the search store (and event log) and graph
+/// store in order. A failed write is handled according to the action's execution mode;
+/// side effects run once every write has been applied, or regardless in
+/// `ContinueOnError` mode.
+pub(crate) async fn execute_action(
+    ctx: &Context<'_>,
+    ontology: Arc<Ontology>,
+    action_type: &ActionTypeDef,
+    parameters: PropertyMap,
+    target: Option<(ObjectRef, PropertyMap)>,
+) -> async_graphql::Result<ActionExecutionResultOutput> {
+    let security = ctx.data_opt::<SecurityContext>();
+    let user_id = security.map(|context| context.user_id.clone());
+    let mut context = ActionContext::new(user_id.clone().unwrap_or_default());
+    if let Some(security) = security {
+        context = context
+            .with_roles(security.roles.iter().cloned().collect())
+            .with_badges(security.badges.iter().cloned().collect());
+    }
+    let target_ref = target.as_ref().map(|(reference, _)| reference.clone());
+    if let Some((_, properties)) = target {
+        context = context.with_object_properties(properties);
+    }
+    let action = Action::new(action_type.id.clone(), parameters, context.user_id.clone());
+    validate_action(&action, action_type, &context).map_err(|e| validation_error(&e))?;
+
+    let recorded = Arc::new(Mutex::new(RecordedAction::default()));
+    let executor = recording_executor(&recorded, ontology.clone(), action_type, target_ref);
+    let mut output = ActionExecutionResultOutput {
+        success: true,
+        operations_executed: Vec::new(),
+        errors: Vec::new(),
+        side_effects_triggered: Vec::new(),
+        rolled_back: false,
+        compensation_errors: Vec::new(),
+    };
+    match executor.execute(&action, action_type, &context) {
+        Ok(_) => {}
+        Err(ValidationError::ExecutionFailed(result)) => {
+            output.success = false;
+            output.errors = result.errors;
+            return Ok(output);
+        }
+        Err(e) => return Err(validation_error(&e)),
+    }
+    let recorded = std::mem::take(&mut *recorded.lock().unwrap());
+
+    let mode = action_type.execution_mode.unwrap_or_default();
+    let mut undo = Vec::new();
+    for write in recorded.writes {
+        match apply_write(ctx, &ontology, write, user_id.clone()).await {
+            Ok((executed, step)) => {
+                output.operations_executed.extend(executed);
+                undo.push(step);
+            }
+            Err(e) => {
+                output.success = false;
+                output.errors.push(e);
+                if mode != ExecutionMode::ContinueOnError {
+                    break;
+                }
+            }
+        }
+    }
+    if !output.success && mode == ExecutionMode::RollbackOnError {
+        for step in undo.into_iter().rev() {
+            if let Err(e) = undo_write(ctx, step, user_id.clone()).await {
+                output.compensation_errors.push(e);
+            }
+        }
+        output.rolled_back = true;
+        if !output.compensation_errors.is_empty() {
+            output.errors.push(format!("Rollback incomplete: {}", output.compensation_errors.join("; ")));
+        }
+    }
+    if !output.success && mode != ExecutionMode::ContinueOnError {
+        return Ok(output);
+    }
+    let handlers = ctx.data_opt::<Arc<SideEffectHandlers>>().cloned();
+    for (effect_type, config) in recorded.side_effects {
+        let outcome = deliver_side_effect(handlers.clone(), effect_type.clone(), config).await;
+        // Side effect failures don't fail the action
+        if let Some(e) = &outcome.error {
+            output.errors.push(format!("Side effect error: {}", e));
+        }
+        let status = match outcome.error {
+            None => SideEffectStatus::Delivered,
+            Some(_) => SideEffectStatus::DeadLettered,
+        };
+        output.side_effects_triggered.push(TriggeredSideEffectOutput {
+            effect_type: enum_name(&effect_type),
+            status: enum_name(&status),
+            effect_id: None,
+            attempts: outcome.attempts,
+            error: outcome.error,
+        });
+    }
+    Ok(output)
+}
+
+/// Deliver a side effect with the configured handlers, off the async executor since delivery
+/// blocks while it retries
+async fn deliver_side_effect(
+    handlers: Option<Arc<SideEffectHandlers>>,
+    effect_type: SideEffectType,
+    config: PropertyMap,
+) -> DeliveryOutcome {
+    match handlers {
+        Some(handlers) => tokio::task::spawn_blocking(move || handlers.deliver(&effect_type, &config))
+            .await
+            .unwrap_or_else(|e| DeliveryOutcome::failed(0, format!("Side effect delivery panicked: {}", e))),
+        None => default_side_effect(&effect_type, &config).into(),
+    }
+}
+
+/// Serialized name of a unit enum variant, e.g. `dead_lettered`
+pub(crate) fn enum_name<T: serde::Serialize>(value: &T) -> String {
+    serde_json::to_value(value)
+        .ok()
+        .and_then(|value| value.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+/// An executor whose handlers record into `recorded`. Objects are identified by their
+/// primary key property, falling back to the target object; operations on the action's
+/// target interface apply to the target's type.
+fn recording_executor(
+    recorded: &Arc<Mutex<RecordedAction>>,
+    ontology: Arc<Ontology>,
+    action_type: &ActionTypeDef,
+    target: Option<ObjectRef>,
+) -> ActionExecutor {
+    let target_interface = action_type.target_interface.clone();
+
+    let mut executor = ActionExecutor::new().with_execution_mode(ExecutionMode::StopOnError);
+    let objects = recorded.clone();
+    executor.object_operation_handler = Some(Box::new(move |operation, object_type, supplied| {
+        let object_type = match &target {
+            Some(target) if target_interface.as_deref() == Some(object_type) => target.object_type.as_str(),
+            _ => object_type,
+        };
+        let object_type_def = ontology
+            .get_object_type(object_type)
+            .ok_or_else(|| format!("Object type '{}' not found", object_type))?;
+        // Templates substitute to strings; values are coerced back to the declared types
+        let mut properties = PropertyMap::new();
+        for (key, value) in supplied.into_iter().flat_map(|properties| properties.iter()) {
+            let value = match object_type_def.get_property(key) {
+                Some(property) => property.value_from_json(&serde_json::to_value(value).map_err(|e| e.to_string())?)?,
+                None => value.clone(),
+            };
+            properties.insert(key.clone(), value);
+        }
+        let primary_key = properties
+            .get(&object_type_def.primary_key)
+            .map(|value| value.to_string().trim().to_string())
+            .filter(|id| !id.is_empty());
+        let target_id = target
+            .as_ref()
+            .filter(|target| target.object_type == object_type)
+            .map(|target| target.object_id.clone());
+        let object_id = match (operation, primary_key, target_id) {
+            (_, Some(object_id), _) => object_id,
+            (OperationType::CreateObject, None, _) => {
+                let object_id = uuid::Uuid::new_v4().to_string();
+                properties.insert(object_type_def.primary_key.clone(), PropertyValue::String(object_id.clone()));
+                object_id
+            }
+            (_, None, Some(object_id)) => object_id,
+            (operation, None, None) => {
+                return Err(format!("{:?} on '{}' needs a target object or its primary key", operation, object_type))
+            }
+        };
+        let object = ObjectRef::new(object_type.to_string(), object_id);
+        let write = match operation {
+            OperationType::CreateObject => ActionWrite::CreateObject { object: object.clone(), properties },
+            OperationType::UpdateObject => ActionWrite::UpdateObject { object: object.clone(), properties },
+            OperationType::DeleteObject => ActionWrite::DeleteObject { object: object.clone() },
+            operation => return Err(format!("{:?} is not an object operation", operation)),
+        };
+        objects.lock().unwrap().writes.push(write);
+        Ok(object.to_string())
+    }));
+
+    let links = recorded.clone();
+    executor.link_operation_handler = Some(Box::new(move |operation, link_type, from, to, properties| {
+        let (link_type, from, to) = (link_type.to_string(), from.to_string(), to.to_string());
+        let write = match operation {
+            OperationType::CreateLink => ActionWrite::CreateLink { link_type, from, to, properties: properties.clone() },
+            OperationType::DeleteLink => ActionWrite::DeleteLink { link_type, from, to },
+            operation => return Err(format!("{:?} is not a link operation", operation)),
+        };
+        links.lock().unwrap().writes.push(write);
+        Ok(String::new())
+    }));
+
+    let side_effects = recorded.clone();
+    executor.side_effect_handler = Some(Box::new(move |effect_type, config| {
+        side_effects.lock().unwrap().side_effects.push((effect_type.clone(), config.clone()));
+        // Delivered once the writes are applied
+        DeliveryOutcome::delivered(0)
+    }));
+    executor
+}
+
+/// Apply one recorded write, returning what it wrote and how to undo it
+async fn apply_write(
+    ctx: &Context<'_>,
+    ontology: &Ontology,
+    write: ActionWrite,
+    user_id: Option<String>,
+) -> Result<(Vec<String>, Undo), String> {
+    let search_store = ctx.data::<Arc<dyn SearchStore>>().map_err(|e| e.message)?;
+    let event_log = ctx.data_opt::<ObjectEventLog>();
+    match write {
+        ActionWrite::CreateObject { object, properties } => {
+            let object_type_def = ontology
+                .get_object_type(&object.object_type)
+                .ok_or_else(|| format!("Object type '{}' not found", object.object_type))?;
+            object_type_def.validate_object(&properties).map_err(|errors| errors.join("; "))?;
+            let already_exists = || format!("Object {} already exists", object);
+            let current = search_store
+                .get_object(&object.object_type, &object.object_id)
+                .await
+                .map_err(|e| format!("Search error: {}", e))?;
+            if current.is_some() {
+                return Err(already_exists());
+            }
+            match search_store.index_object(&object.object_type, &object.object_id, &properties, Some(0)).await {
+                Ok(_) => {}
+                Err(StoreError::Conflict(_)) => return Err(already_exists()),
+                Err(e) => return Err(format!("Index error: {}", e)),
+            }
+            if let Some(event_log) = event_log {
+                event_log.write().await.record_created(
+                    object.object_type.clone(),
+                    object.object_id.clone(),
+                    properties,
+                    user_id,
+                );
+            }
+            Ok((vec![object.to_string()], Undo::Delete(object)))
+        }
+        ActionWrite::UpdateObject { object, properties } => {
+            let object_type_def = ontology
+                .get_object_type(&object.object_type)
+                .ok_or_else(|| format!("Object type '{}' not found", object.object_type))?;
+            let current = search_store
+                .get_object(&object.object_type, &object.object_id)
+                .await
+                .map_err(|e| format!("Search error: {}", e))?
+                .ok_or_else(|| format!("Object {} not found", object))?;
+            let mut merged = current.properties.clone();
+            let mut changes = PropertyMap::new();
+            for (key, value) in properties.iter() {
+                if current.properties.get(key) != Some(value) {
+                    merged.insert(key.clone(), value.clone());
+                    changes.insert(key.clone(), value.clone());
+                }
+            }
+            if changes.is_empty() {
+                return Ok((vec![object.to_string()], Undo::Nothing));
+            }
+            object_type_def.validate_object(&merged).map_err(|errors| errors.join("; "))?;
+            search_store
+                .index_object(&object.object_type, &object.object_id, &merged, Some(current.revision))
+                .await
+                .map_err(|e| format!("Index error: {}", e))?;
+            if let Some(event_log) = event_log {
+                event_log.write().await.record_updated(
+                    object.object_type.clone(),
+                    object.object_id.clone(),
+                    changes,
+                    user_id,
+                );
+            }
+            Ok((vec![object.to_string()], Undo::Restore { previous: current.properties, object }))
+        }
+        ActionWrite::DeleteObject { object } => {
+            let graph_store = ctx.data_opt::<Arc<dyn GraphStore>>().map(|store| store.as_ref());
+            let deleted = delete_object_cascading(
+                ontology,
+                search_store.as_ref(),
+                graph_store,
+                &object.object_type,
+                &object.object_id,
+            )
+            .await
+            .map_err(|e| match e {
+                StoreError::NotFound(_) => format!("Object {} not found", object),
+                e => format!("Delete error: {}", e),
+            })?;
+            if let Some(event_log) = event_log {
+                record_cascade_delete(event_log, &deleted, user_id).await;
+            }
+            let undo = Undo::Irreversible(format!("Cannot undo the deletion of {}", object));
+            Ok((deleted.objects.iter().map(|object| object.to_string()).collect(), undo))
+        }
+        ActionWrite::CreateLink { link_type, from, to, properties } => {
+            let link_type_def = ontology
+                .get_link_type(&link_type)
+                .ok_or_else(|| format!("Link type '{}' not found", link_type))?;
+            LinkValidator::validate_properties(link_type_def, &properties).map_err(|errors| errors.join("; "))?;
+            let source = ObjectRef::parse(&from, Some(&link_type_def.source))?;
+            let target = ObjectRef::parse(&to, Some(&link_type_def.target))?;
+            let graph_store = ctx.data::<Arc<dyn GraphStore>>().map_err(|e| e.message)?;
+            let existing = endpoint_links(graph_store.as_ref(), &link_type, &source.object_id, &target.object_id)
+                .await
+                .map_err(|e| format!("Graph error: {}", e))?;
+            LinkValidator::check_cardinality(link_type_def, &source.object_id, &target.object_id, &existing)?;
+            let link_id = graph_store
+                .create_link(&link_type, &source.object_id, &target.object_id, &properties)
+                .await
+                .map_err(|e| format!("Graph error: {}", e))?;
+            if let Some(event_log) = event_log {
+                event_log.write().await.record_link_created(
+                    link_type.clone(),
+                    link_id.clone(),
+                    source.object_id.clone(),
+                    target.object_id.clone(),
+                    properties,
+                    user_id,
+                );
+            }
+            let undo = Undo::DeleteLink {
+                link_type,
+                link_id: link_id.clone(),
+                source_id: source.object_id,
+                target_id: target.object_id,
+            };
+            Ok((vec![link_id], undo))
+        }
+        ActionWrite::DeleteLink { link_type, from, to } => {
+            let link_type_def = ontology
+                .get_link_type(&link_type)
+                .ok_or_else(|| format!("Link type '{}' not found", link_type))?;
+            let source = ObjectRef::parse(&from, Some(&link_type_def.source))?;
+            let target = ObjectRef::parse(&to, Some(&link_type_def.target))?;
+            let graph_store = ctx.data::<Arc<dyn GraphStore>>().map_err(|e| e.message)?;
+            let existing = endpoint_links(graph_store.as_ref(), &link_type, &source.object_id, &target.object_id)
+                .await
+                .map_err(|e| format!("Graph error: {}", e))?;
+            let mut deleted = Vec::new();
+            for link in existing {
+                graph_store.delete_link(&link.id).await.map_err(|e| format!("Graph error: {}", e))?;
+                if let Some(event_log) = event_log {
+                    event_log.write().await.record_link_deleted(
+                        link_type.clone(),
+                        link.id.clone(),
+                        link.source_id,
+                        link.target_id,
+                        user_id.clone(),
+                    );
+                }
+                deleted.push(link.id);
+            }
+            let undo = if deleted.is_empty() {
+                Undo::Nothing
+            } else {
+                Undo::Irreversible(format!("Cannot undo the deletion of links {}", deleted.join(", ")))
+            };
+            Ok((deleted, undo))
+        }
+    }
+}
+
+/// Undo one applied write
+async fn undo_write(ctx: &Context<'_>, undo: Undo, user_id: Option<String>) -> Result<(), String> {
+    let search_store = ctx.data::<Arc<dyn SearchStore>>().map_err(|e| e.message)?;
+    let event_log = ctx.data_opt::<ObjectEventLog>();
+    match undo {
+        Undo::Delete(object) => {
+            search_store
+                .delete_object(&object.object_type, &object.object_id)
+                .await
+                .map_err(|e| format!("Could not delete created {}: {}", object, e))?;
+            if let Some(event_log) = event_log {
+                event_log.write().await.record_deleted(object.object_type, object.object_id, user_id);
+            }
+        }
+        Undo::Restore { object, previous } => {
+            let changed = search_store
+                .get_object(&object.object_type, &object.object_id)
+                .await
+                .map_err(|e| format!("Could not restore {}: {}", object, e))?
+                .map(|current| current.properties)
+                .unwrap_or_default();
+            search_store
+                .index_object(&object.object_type, &object.object_id, &previous, None)
+                .await
+                .map_err(|e| format!("Could not restore {}: {}", object, e))?;
+            if let Some(event_log) = event_log {
+                let mut changes = PropertyMap::new();
+                for key in changed.iter().chain(previous.iter()).map(|(key, _)| key) {
+                    if changed.get(key) != previous.get(key) {
+                        changes.insert(key.clone(), previous.get(key).cloned().unwrap_or(PropertyValue::Null));
+                    }
+                }
+                event_log.write().await.record_updated(object.object_type, object.object_id, changes, user_id);
+            }
+        }
+        Undo::DeleteLink { link_type, link_id, source_id, target_id } => {
+            let graph_store = ctx.data::<Arc<dyn GraphStore>>().map_err(|e| e.message)?;
+            graph_store
+                .delete_link(&link_id)
+                .await
+                .map_err(|e| format!("Could not delete created link '{}': {}", link_id, e))?;
+            if let Some(event_log) = event_log {
+                event_log.write().await.record_link_deleted(link_type, link_id, source_id, target_id, user_id);
+            }
+        }
+        Undo::Nothing => {}
+        Undo::Irreversible(reason) => return Err(reason),
+    }
+    Ok(())
+}
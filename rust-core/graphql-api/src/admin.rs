@@ -1,6 +1,42 @@
-use async_graphql::{Context, Object, FieldResult, InputObject};
+use async_graphql::{Context, Object, FieldResult, InputObject, Json, SimpleObject};
+use indexing::consistency::{ConsistencyChecker, ConsistencyOptions};
+use indexing::archive::{ARCHIVE_JOB_KIND, UNARCHIVE_JOB_KIND};
+use indexing::hydration::HydrationOptions;
+use indexing::store::{ColumnarStore, GraphStore, IndexedObject, RevisionConflict, SearchQuery, SearchStore, SortOption, StoreError};
+use indexing::dedup::FIND_DUPLICATES_JOB_KIND;
+use indexing::validating_graph::endpoint_links;
+use indexing::references::BACKFILL_REFERENCES_JOB_KIND;
+use indexing::{delete_object_cascading, ArchiveEventSink, CascadeDelete, Archiver, ChangeTrigger, ChangeTriggerRegistry, Deduplicator, ExportFormat, ExportRequest, Exporter, JobRegistry, MergeEventSink, ReferenceIndex, SamplingOptions};
 use ontology_engine::dynamic::DynamicOntology;
+use ontology_engine::meta_model::SchemaEvolution;
+use ontology_engine::{ComputedPropertyMaterializer, LinkValidator, ModelRegistry, ObjectRef, ObjectType, Ontology, OntologyChange, OntologyDef, OntologyHandle, OntologyLoadError, Property, PropertyMap, PropertyValue};
+use security::acl::{AclEntry, AclPermission, ObjectAcl, ACL_PROPERTY, MANAGE_ACL_ROLE};
+use security::{AclSearchFilter, MaskingPolicy, PropertyAccessPolicy, SecurityContext};
+use serde_json::Value;
 use std::sync::Arc;
+use tokio::sync::RwLock;
+use versioning::time_query::TimeQuery;
+use writeback::{ConflictResolution, ConflictResolutionStrategy, EditQueue, EditStatus, PendingConflicts, UserEdit};
+
+use crate::actions::{execute_action, parse_parameters, ActionExecutionResultOutput};
+use crate::error::ApiError;
+use crate::filters::{convert_filters, FilterInput};
+use crate::masking::{check_readable, mask_object_json, mask_properties, redact_unreadable};
+use crate::resolvers::{acl_store_filters, check_indexed, job_visible, conflict_output, edit_output, json_object_result, query_properties, ChangeTriggerOutput, ObjectResult, PendingConflictOutput, UserEditOutput};
+use crate::schema::ObjectEventLog;
+
+/// Page size used when re-materializing computed properties through the search store
+const RECOMPUTE_BATCH_SIZE: usize = 500;
+
+/// Role allowed to add object types and properties to the live ontology
+pub(crate) const ONTOLOGY_ADMIN_ROLE: &str = "admin";
+
+/// Role allowed to apply users' pending edits and settle their conflicts, besides ontology
+/// admins
+const EDIT_REVIEWER_ROLE: &str = "edit_reviewer";
+
+/// Role allowed to create, change and delete objects and links, besides ontology admins
+const OBJECT_EDITOR_ROLE: &str = "editor";
 
 /// Admin mutations for runtime ontology editing
 #[derive(Default)]
@@ -8,16 +44,59 @@ pub struct AdminMutations;
 
 #[Object]
 impl AdminMutations {
-    /// Add a new object type to the ontology at runtime
-    async fn add_object_type(
+    /// Add a new object type to the live ontology from its JSON `definition`. An existing
+    /// type cannot be redefined this way, which also rules out changing its primary key.
+    /// Requires the `admin` role; the ontology is unchanged unless the result validates.
+    async fn add_object_type(&self, ctx: &Context<'_>, definition: String) -> FieldResult<bool> {
+        require_ontology_admin(ctx)?;
+        let mut object_type: ObjectType = serde_json::from_str(&definition)
+            .map_err(|e| ApiError::invalid_argument(format!("Invalid object type definition: {}", e)))?;
+        ctx.data::<OntologyHandle>()?
+            .mutate(|ontology| {
+                if let Some(existing) = ontology.object_types.iter().find(|ot| ot.id == object_type.id) {
+                    if existing.primary_key != object_type.primary_key {
+                        return Err(format!(
+                            "The primary key of object type '{}' cannot change from '{}' to '{}'",
+                            existing.id, existing.primary_key, object_type.primary_key
+                        ));
+                    }
+                    return Err(format!("Object type '{}' already exists; use addProperty to extend it", existing.id));
+                }
+                object_type.schema_evolution.get_or_insert_with(|| SchemaEvolution::new(chrono::Utc::now()));
+                ontology.object_types.push(object_type);
+                Ok(())
+            })
+            .map_err(|e| ApiError::validation(None, e))?;
+        Ok(true)
+    }
+    
+    /// Add a property, given as a JSON property definition, to an existing object type. A
+    /// required property needs a default for the objects already stored. Requires the
+    /// `admin` role; the ontology is unchanged unless the result validates.
+    async fn add_property(&self, ctx: &Context<'_>, object_type: String, property: String) -> FieldResult<bool> {
+        require_ontology_admin(ctx)?;
+        let property: Property = serde_json::from_str(&property)
+            .map_err(|e| ApiError::invalid_argument(format!("Invalid property definition: {}", e)))?;
+        ctx.data::<OntologyHandle>()?
+            .mutate(|ontology| object_type_mut(ontology, &object_type)?.add_property(property))
+            .map_err(|e| ApiError::validation(None, e))?;
+        Ok(true)
+    }
+    
+    /// Mark a property deprecated, optionally naming the property that replaces it. Requires
+    /// the `admin` role.
+    async fn deprecate_property(
         &self,
-        _ctx: &Context<'_>,
-        _object_type: ObjectTypeInput,
+        ctx: &Context<'_>,
+        object_type: String,
+        property_id: String,
+        replacement: Option<String>,
     ) -> FieldResult<bool> {
-        // Convert input to ObjectType
-        // This is simplified - in production, would need full conversion
-        // For now, return error indicating it needs implementation
-        Err(async_graphql::Error::new("add_object_type not yet fully implemented"))
+        require_ontology_admin(ctx)?;
+        ctx.data::<OntologyHandle>()?
+            .mutate(|ontology| object_type_mut(ontology, &object_type)?.deprecate_property(&property_id, replacement))
+            .map_err(|e| ApiError::validation(None, e))?;
+        Ok(true)
     }
     
     /// Add a new link type to the ontology at runtime
@@ -27,7 +106,7 @@ impl AdminMutations {
         _link_type: LinkTypeInput,
     ) -> FieldResult<bool> {
         // Similar to add_object_type
-        Err(async_graphql::Error::new("add_link_type not yet fully implemented"))
+        Err(ApiError::internal("add_link_type not yet fully implemented").into())
     }
     
     /// Add a new action type to the ontology at runtime
@@ -37,17 +116,1421 @@ impl AdminMutations {
         _action_type: ActionTypeInput,
     ) -> FieldResult<bool> {
         // Similar to add_object_type
-        Err(async_graphql::Error::new("add_action_type not yet fully implemented"))
+        Err(ApiError::internal("add_action_type not yet fully implemented").into())
+    }
+    
+    /// Replace the ACL on a single object (requires manage permission on the object)
+    async fn set_object_acl(
+        &self,
+        ctx: &Context<'_>,
+        object_type: String,
+        object_id: String,
+        entries: Vec<AclEntryInput>,
+    ) -> FieldResult<bool> {
+        let security_context = ctx.data_opt::<SecurityContext>()
+            .ok_or_else(|| ApiError::unauthorized("set_object_acl requires a security context"))?;
+        let ontology = ctx.data::<OntologyHandle>()?.load();
+        let object_type_def = ontology.get_object_type(&object_type)
+            .ok_or_else(|| ApiError::not_found(format!("Object type '{}' not found", object_type)))?;
+        
+        let mut new_entries = Vec::new();
+        for entry in entries {
+            let permission = AclPermission::from_str(&entry.permission)
+                .map_err(|e| ApiError::invalid_argument(e.to_string()))?;
+            new_entries.push(AclEntry::new(entry.principal, permission));
+        }
+        let new_acl = ObjectAcl::new(new_entries);
+        let acl_value = new_acl.to_property_value();
+        
+        // Update and re-index through the search store
+        let search_store = ctx.data::<Arc<dyn SearchStore>>()?;
+        let indexed = search_store.get_object(&object_type, &object_id).await
+            .map_err(ApiError::from)?
+            .ok_or_else(|| ApiError::not_found(format!("Object {}:{} not found", object_type, object_id)))?;
+        
+        let current_acl = ObjectAcl::from_properties(&indexed.properties)
+            .ok()
+            .flatten()
+            .unwrap_or_default();
+        if !current_acl.can_manage(security_context) {
+            return Err(ApiError::unauthorized(format!(
+                "User '{}' may not manage the ACL of {}:{}",
+                security_context.user_id, object_type, object_id
+            )).into());
+        }
+        
+        // The search store flattens the ACL into its pre-filter fields on indexing. Writing at
+        // the revision that was read keeps a concurrent ACL change from being overwritten.
+        let mut properties = indexed.properties.clone();
+        properties.insert(ACL_PROPERTY.to_string(), acl_value);
+        search_store.index_object(&object_type, &object_id, &properties, Some(indexed.revision)).await
+            .map_err(ApiError::from)?;
+        
+        Ok(true)
+    }
+    
+    /// Recompute a materialized computed property for every stored object of a type.
+    /// Returns the number of objects refreshed.
+    async fn recompute_materialized(
+        &self,
+        ctx: &Context<'_>,
+        object_type: String,
+        property: String,
+    ) -> FieldResult<u64> {
+        let ontology = ctx.data::<OntologyHandle>()?.load();
+        let object_type_def = ontology.get_object_type(&object_type)
+            .ok_or_else(|| ApiError::not_found(format!("Object type '{}' not found", object_type)))?;
+        let computed = object_type_def.computed_properties.iter()
+            .find(|c| c.id == property)
+            .ok_or_else(|| ApiError::not_found(format!(
+                "Computed property '{}' not found on object type '{}'", property, object_type
+            )))?;
+        if !computed.is_materialized() {
+            return Err(ApiError::invalid_argument(format!(
+                "Computed property '{}' is evaluated on read and has no stored value", property
+            )).into());
+        }
+        let now = chrono::Utc::now();
+        
+        // Page through the search store and re-index each batch
+        let search_store = ctx.data::<Arc<dyn SearchStore>>()?;
+        let mut count = 0;
+        let mut offset = 0;
+        loop {
+            let query = SearchQuery {
+                filters: vec![],
+                sort: vec![SortOption::for_property(object_type_def, object_type_def.primary_key.clone(), true)],
+                limit: Some(RECOMPUTE_BATCH_SIZE),
+                offset: Some(offset),
+                search_after: None,
+            };
+            let page = search_store.search(&object_type, &query).await
+                .map_err(ApiError::from)?;
+            let fetched = page.len();
+            
+            let mut refreshed = Vec::with_capacity(fetched);
+            for indexed in page {
+                let mut properties = indexed.properties;
+                ComputedPropertyMaterializer::materialize_property(computed, &mut properties, now)
+                    .map_err(|e| ApiError::validation(Some(&property), e.to_string()))?;
+                refreshed.push(IndexedObject::new(indexed.object_type, indexed.object_id, properties));
+            }
+            if !refreshed.is_empty() {
+                search_store.bulk_index(refreshed).await
+                    .map_err(ApiError::from)?;
+            }
+            
+            count += fetched as u64;
+            offset += fetched;
+            if fetched < RECOMPUTE_BATCH_SIZE {
+                break;
+            }
+        }
+        Ok(count)
+    }
+    
+    /// Merge `properties` into an object in the search store, creating it if it does not exist.
+    /// With `expectedRevision` the write only succeeds if the object is still at that revision
+    /// (0 = must not exist yet); without it the object is written back at the revision just
+    /// read. A lost race returns `success: false` with the current object in `conflict`.
+    /// Requires the `editor` or `admin` role, and write access to an existing object under its ACL.
+    async fn upsert_object(
+        &self,
+        ctx: &Context<'_>,
+        object_type: String,
+        object_id: String,
+        properties: Json<Value>,
+        expected_revision: Option<u64>,
+    ) -> FieldResult<UpsertObjectResult> {
+        let context = require_object_editor(ctx)?;
+        let ontology = ctx.data::<OntologyHandle>()?.load();
+        let object_type_def = ontology.get_object_type(&object_type)
+            .ok_or_else(|| ApiError::not_found(format!("Object type '{}' not found", object_type)))?;
+        let Value::Object(supplied) = properties.0 else {
+            return Err(ApiError::invalid_argument("properties must be a JSON object").into());
+        };
+        
+        let mut changes = PropertyMap::new();
+        let mut unknown = PropertyMap::new();
+        let mut errors = Vec::new();
+        for (key, value) in supplied {
+            let Some(property) = object_type_def.get_property(&key) else {
+                // System fields such as ACLs have their own mutations
+                if key.starts_with('_') {
+                    errors.push(format!("Unknown property '{}' on object type '{}'", key, object_type));
+                } else {
+                    unknown.insert(key, serde_json::from_value(value)?);
+                }
+                continue;
+            };
+            let value: PropertyValue = serde_json::from_value(value)?;
+            match property.validate_value(&value) {
+                Ok(()) => changes.insert(key, value),
+                Err(e) => errors.push(format!("Property '{}': {}", key, e)),
+            }
+        }
+        // Undeclared properties are written as-is unless the type is strict
+        let unknown_keys = object_type_def.unknown_properties(&unknown);
+        if object_type_def.strict_properties && !unknown_keys.is_empty() {
+            errors.push(format!(
+                "Unknown properties on strict object type '{}': {}",
+                object_type,
+                unknown_keys.join(", ")
+            ));
+        }
+        for (key, value) in unknown.iter() {
+            changes.insert(key.clone(), value.clone());
+        }
+        if !errors.is_empty() {
+            return Err(ApiError::validation(None, errors.join("; ")).into());
+        }
+        
+        let search_store = ctx.data::<Arc<dyn SearchStore>>()?;
+        let current = search_store.get_object(&object_type, &object_id).await
+            .map_err(ApiError::from)?;
+        if let Some(current) = &current {
+            check_object_writable(context, &ObjectRef::new(&object_type, &object_id), &current.properties)?;
+        }
+        let expected_revision = expected_revision
+            .unwrap_or_else(|| current.as_ref().map_or(0, |o| o.revision));
+        let mut merged = current.map(|o| o.properties).unwrap_or_default();
+        for (key, value) in changes.iter() {
+            merged.insert(key.clone(), value.clone());
+        }
+        // Custom validators see the object as it will be written
+        let errors: Vec<String> = changes
+            .iter()
+            .filter_map(|(key, value)| object_type_def.get_property(key)?.run_custom_validator(value, &merged).err())
+            .collect();
+        if !errors.is_empty() {
+            return Err(ApiError::validation(None, errors.join("; ")).into());
+        }
+        
+        match search_store.index_object(&object_type, &object_id, &merged, Some(expected_revision)).await {
+            Ok(revision) => Ok(UpsertObjectResult {
+                success: true,
+                revision,
+                conflict: None,
+            }),
+            Err(StoreError::Conflict(conflict)) => Ok(UpsertObjectResult {
+                success: false,
+                revision: conflict.current_revision,
+                conflict: Some(RevisionConflictOutput::from(*conflict)),
+            }),
+            Err(e) => Err(ApiError::from(e).into()),
+        }
+    }
+    
+    /// Create an object from a JSON object of its properties. Every validation error is
+    /// returned at once; the primary key must be present and not already taken. The object is
+    /// written to the search store. Creation is recorded in the `ObjectEventLog` if one is configured.
+    /// Requires the `editor` or `admin` role.
+    async fn create_object(
+        &self,
+        ctx: &Context<'_>,
+        object_type: String,
+        properties: String,
+    ) -> FieldResult<ObjectResult> {
+        let context = require_object_editor(ctx)?;
+        let ontology = ctx.data::<OntologyHandle>()?.load();
+        let object_type_def = ontology.get_object_type(&object_type)
+            .ok_or_else(|| ApiError::not_found(format!("Object type '{}' not found", object_type)))?;
+        let supplied: Value = serde_json::from_str(&properties)
+            .map_err(|e| ApiError::invalid_argument(format!("Invalid properties JSON: {}", e)))?;
+        let Value::Object(supplied) = supplied else {
+            return Err(ApiError::invalid_argument("properties must be a JSON object").into());
+        };
+        
+        let mut errors = Vec::new();
+        let mut object = PropertyMap::new();
+        for (key, value) in supplied {
+            // System fields such as ACLs have their own mutations
+            if key.starts_with('_') {
+                errors.push(format!("Unknown property '{}' on object type '{}'", key, object_type));
+                continue;
+            }
+            // Declared properties are coerced to their type (e.g. dates sent as strings)
+            let value = match object_type_def.get_property(&key) {
+                Some(property) => property.value_from_json(&value),
+                None => serde_json::from_value::<PropertyValue>(value).map_err(|e| format!("Property '{}': {}", key, e)),
+            };
+            match value {
+                Ok(value) => object.insert(key, value),
+                Err(e) => errors.push(e),
+            }
+        }
+        if let Err(validation_errors) = object_type_def.validate_object(&object) {
+            errors.extend(validation_errors);
+        }
+        let unknown_keys = object_type_def.unknown_properties(&object);
+        if object_type_def.strict_properties && !unknown_keys.is_empty() {
+            errors.push(format!(
+                "Unknown properties on strict object type '{}': {}",
+                object_type,
+                unknown_keys.join(", ")
+            ));
+        }
+        if !errors.is_empty() {
+            return Err(ApiError::validation(None, errors.join("; ")).into());
+        }
+        let object_id = object
+            .get(&object_type_def.primary_key)
+            .map(|v| v.to_string().trim().to_string())
+            .unwrap_or_default();
+        if object_id.is_empty() {
+            return Err(ApiError::validation(Some(&object_type_def.primary_key), format!("Empty primary key '{}'", object_type_def.primary_key)).into());
+        }
+        let already_exists = || ApiError::invalid_argument(format!("Object {}:{} already exists", object_type, object_id));
+        let json = Value::Object(
+            object
+                .iter()
+                .map(|(key, value)| (key.clone(), serde_json::to_value(value).unwrap_or(Value::Null)))
+                .collect(),
+        );
+        
+        let search_store = ctx.data::<Arc<dyn SearchStore>>()?;
+        let current = search_store.get_object(&object_type, &object_id).await
+            .map_err(ApiError::from)?;
+        if current.is_some() {
+            return Err(already_exists().into());
+        }
+        // Revision 0 also loses to a concurrent create of the same ID
+        match search_store.index_object(&object_type, &object_id, &object, Some(0)).await {
+            Ok(_) => {}
+            Err(StoreError::Conflict(_)) => return Err(already_exists().into()),
+            Err(e) => return Err(ApiError::from(e).into()),
+        }
+        
+        if let Some(event_log) = ctx.data_opt::<ObjectEventLog>() {
+            let user_id = Some(context.user_id.clone());
+            event_log.write().await.record_created(object_type.clone(), object_id.clone(), object, user_id);
+        }
+        let mut result = json_object_result(ctx, object_type_def, json, None, HydrationOptions::default());
+        result.object_id = object_id;
+        Ok(result)
+    }
+    
+    /// Change some properties of an existing object; keys that are not supplied keep their
+    /// value. The primary key cannot change. Changed values are validated, and the update
+    /// is recorded in the `ObjectEventLog` with just the properties whose value changed.
+    /// Requires the `editor` or `admin` role and write access under the object's ACL.
+    async fn update_object(
+        &self,
+        ctx: &Context<'_>,
+        object_type: String,
+        object_id: String,
+        properties: String,
+    ) -> FieldResult<ObjectResult> {
+        let context = require_object_editor(ctx)?;
+        let ontology = ctx.data::<OntologyHandle>()?.load();
+        let object_type_def = ontology.get_object_type(&object_type)
+            .ok_or_else(|| ApiError::not_found(format!("Object type '{}' not found", object_type)))?;
+        let supplied: Value = serde_json::from_str(&properties)
+            .map_err(|e| ApiError::invalid_argument(format!("Invalid properties JSON: {}", e)))?;
+        let Value::Object(supplied) = supplied else {
+            return Err(ApiError::invalid_argument("properties must be a JSON object").into());
+        };
+        let not_found = || ApiError::not_found(format!("Object {}:{} not found", object_type, object_id));
+        
+        let pk = &object_type_def.primary_key;
+        let indexed = ctx.data::<Arc<dyn SearchStore>>()?
+            .get_object(&object_type, &object_id).await
+            .map_err(ApiError::from)?
+            .ok_or_else(not_found)?;
+        check_object_writable(context, &ObjectRef::new(&object_type, &object_id), &indexed.properties)?;
+        let (current, revision) = (indexed.properties, indexed.revision);
+        
+        let mut errors = Vec::new();
+        let mut changes = PropertyMap::new();
+        for (key, value) in supplied {
+            if key.starts_with('_') {
+                errors.push(format!("Unknown property '{}' on object type '{}'", key, object_type));
+                continue;
+            }
+            let value = match object_type_def.get_property(&key) {
+                Some(property) => property.value_from_json(&value),
+                None => serde_json::from_value::<PropertyValue>(value).map_err(|e| format!("Property '{}': {}", key, e)),
+            };
+            let value = match value {
+                Ok(value) => value,
+                Err(e) => {
+                    errors.push(e);
+                    continue;
+                }
+            };
+            if &key == pk {
+                if value.to_string().trim() != object_id {
+                    errors.push(format!("The primary key '{}' cannot be changed", pk));
+                }
+                continue;
+            }
+            if current.get(&key) != Some(&value) {
+                changes.insert(key, value);
+            }
+        }
+        let mut merged = current.clone();
+        for (key, value) in changes.iter() {
+            merged.insert(key.clone(), value.clone());
+        }
+        for (key, value) in changes.iter() {
+            let Some(property) = object_type_def.get_property(key) else {
+                continue;
+            };
+            let result = match value {
+                PropertyValue::Null if property.required => Err(format!("Missing required property '{}'", key)),
+                PropertyValue::Null => Ok(()),
+                value => property.validate_value_with_siblings(value, &merged),
+            };
+            if let Err(e) = result {
+                errors.push(e);
+            }
+        }
+        let unknown_keys = object_type_def.unknown_properties(&changes);
+        if object_type_def.strict_properties && !unknown_keys.is_empty() {
+            errors.push(format!(
+                "Unknown properties on strict object type '{}': {}",
+                object_type,
+                unknown_keys.join(", ")
+            ));
+        }
+        if !errors.is_empty() {
+            return Err(ApiError::validation(None, errors.join("; ")).into());
+        }
+        
+        let json = Value::Object(
+            merged
+                .iter()
+                .map(|(key, value)| (key.clone(), serde_json::to_value(value).unwrap_or(Value::Null)))
+                .collect(),
+        );
+        if !changes.is_empty() {
+            ctx.data::<Arc<dyn SearchStore>>()?
+                .index_object(&object_type, &object_id, &merged, Some(revision)).await
+                .map_err(ApiError::from)?;
+            if let Some(event_log) = ctx.data_opt::<ObjectEventLog>() {
+                let user_id = Some(context.user_id.clone());
+                event_log.write().await.record_updated(object_type.clone(), object_id.clone(), changes, user_id);
+            }
+        }
+        let mut result = json_object_result(ctx, object_type_def, json, None, HydrationOptions::default());
+        result.object_id = object_id;
+        Ok(result)
+    }
+    
+    /// Delete an object. Its links are handled according to each link type's `onDelete`:
+    /// removed, removed along with the objects they lead to, or blocking the delete. Every
+    /// deleted object and link is recorded in the `ObjectEventLog`. Requires the `editor` or
+    /// `admin` role and write access under the object's ACL.
+    async fn delete_object(
+        &self,
+        ctx: &Context<'_>,
+        object_type: String,
+        object_id: String,
+    ) -> FieldResult<DeleteObjectResult> {
+        let context = require_object_editor(ctx)?;
+        let ontology = ctx.data::<OntologyHandle>()?.load();
+        let object_type_def = ontology.get_object_type(&object_type)
+            .ok_or_else(|| ApiError::not_found(format!("Object type '{}' not found", object_type)))?;
+        
+        let search_store = ctx.data::<Arc<dyn SearchStore>>()?;
+        if let Some(current) = search_store.get_object(&object_type, &object_id).await.map_err(ApiError::from)? {
+            check_object_writable(context, &ObjectRef::new(&object_type, &object_id), &current.properties)?;
+        }
+        let graph_store = ctx.data_opt::<Arc<dyn GraphStore>>().map(|store| store.as_ref());
+        let deleted = delete_object_cascading(&ontology, search_store.as_ref(), graph_store, &object_type, &object_id)
+            .await
+            .map_err(|e| match e {
+                StoreError::NotFound(_) => {
+                    ApiError::not_found(format!("Object {}:{} not found", object_type, object_id))
+                }
+                e => ApiError::from(e),
+            })?;
+        
+        if let Some(event_log) = ctx.data_opt::<ObjectEventLog>() {
+            record_cascade_delete(event_log, &deleted, Some(context.user_id.clone())).await;
+        }
+        Ok(DeleteObjectResult {
+            deleted_objects: deleted.objects.iter().map(|object| object.to_string()).collect(),
+            deleted_links: deleted.links.into_iter().map(|link| link.link_id).collect(),
+        })
+    }
+    
+    /// Link two existing objects. `sourceId` and `targetId` are IDs of the link type's source
+    /// and target types (`object_type:object_id` is accepted too). Properties are validated
+    /// against the link type, and a link that would break its cardinality is rejected with
+    /// the existing link in the way named. Requires the `editor` or `admin` role and write
+    /// access to both objects under their ACLs.
+    async fn create_link(
+        &self,
+        ctx: &Context<'_>,
+        link_type: String,
+        source_id: String,
+        target_id: String,
+        properties: Option<String>,
+    ) -> FieldResult<CreateLinkResult> {
+        let context = require_object_editor(ctx)?;
+        let ontology = ctx.data::<OntologyHandle>()?.load();
+        let link_type_def = ontology.get_link_type(&link_type)
+            .ok_or_else(|| ApiError::not_found(format!("Link type '{}' not found", link_type)))?;
+        let supplied: Value = match properties {
+            Some(properties) => serde_json::from_str(&properties)
+                .map_err(|e| ApiError::invalid_argument(format!("Invalid properties JSON: {}", e)))?,
+            None => Value::Object(Default::default()),
+        };
+        
+        let mut errors = Vec::new();
+        let link_properties = match link_type_def.instantiate_from_json(&supplied) {
+            Ok(link_properties) => {
+                if let Err(property_errors) = LinkValidator::validate_properties(link_type_def, &link_properties) {
+                    errors.extend(property_errors);
+                }
+                link_properties
+            }
+            Err(coercion_errors) => {
+                errors.extend(coercion_errors);
+                PropertyMap::new()
+            }
+        };
+        let mut endpoints = Vec::new();
+        for (id, object_type) in [(&source_id, &link_type_def.source), (&target_id, &link_type_def.target)] {
+            match ObjectRef::parse(id, Some(object_type)) {
+                Ok(reference) if &reference.object_type == object_type => endpoints.push(reference),
+                Ok(reference) => errors.push(format!(
+                    "Link type '{}' connects '{}' objects, not '{}'",
+                    link_type, object_type, reference.object_type
+                )),
+                Err(e) => errors.push(e),
+            }
+        }
+        if !errors.is_empty() {
+            return Err(ApiError::validation(None, errors.join("; ")).into());
+        }
+        
+        let search_store = ctx.data::<Arc<dyn SearchStore>>()?;
+        for endpoint in &endpoints {
+            let object = search_store.get_object(&endpoint.object_type, &endpoint.object_id).await
+                .map_err(ApiError::from)?
+                .ok_or_else(|| ApiError::not_found(format!("Object {} not found", endpoint)))?;
+            // A link changes both objects it joins
+            check_object_writable(context, endpoint, &object.properties)?;
+        }
+        let (source, target) = (&endpoints[0].object_id, &endpoints[1].object_id);
+        let graph_store = ctx.data::<Arc<dyn GraphStore>>()?;
+        let existing = endpoint_links(graph_store.as_ref(), &link_type, source, target).await
+            .map_err(ApiError::from)?;
+        LinkValidator::check_cardinality(link_type_def, source, target, &existing)
+            .map_err(|e| ApiError::validation(None, e))?;
+        let link_id = graph_store.create_link(&link_type, source, target, &link_properties).await
+            .map_err(ApiError::from)?;
+        
+        Ok(CreateLinkResult {
+            link_id,
+            link_type,
+            source_id: source.clone(),
+            target_id: target.clone(),
+        })
+    }
+    
+    /// Run an action type with `parameters`, a JSON object of its parameter values, as the
+    /// caller. `targetObjectId` (`type:id`, or an ID of the type the action's operations work
+    /// on) is the object acted upon: its properties are available to the action's conditions,
+    /// and operations without a primary key apply to it. Validation failures are errors with
+    /// a `code` extension and, for parameter failures, a `parameter` extension; a failed
+    /// write is handled according to the action type's `executionMode`.
+    async fn execute_action(
+        &self,
+        ctx: &Context<'_>,
+        action_type_id: String,
+        parameters: String,
+        target_object_id: Option<String>,
+    ) -> FieldResult<ActionExecutionResultOutput> {
+        let ontology = ctx.data::<OntologyHandle>()?.load();
+        let action_type = ontology.get_action_type(&action_type_id)
+            .ok_or_else(|| ApiError::not_found(format!("Action type '{}' not found", action_type_id)))?;
+        let parameters = parse_parameters(action_type, &parameters)?;
+        let target = match target_object_id {
+            Some(target_object_id) => {
+                let default_type = action_type.logic.iter().find_map(|operation| operation.object_type.as_deref());
+                let reference = ObjectRef::parse(&target_object_id, default_type).map_err(ApiError::invalid_argument)?;
+                let object = ctx.data::<Arc<dyn SearchStore>>()?
+                    .get_object(&reference.object_type, &reference.object_id).await
+                    .map_err(ApiError::from)?
+                    .ok_or_else(|| ApiError::not_found(format!("Object {} not found", reference)))?;
+                Some((reference, object.properties))
+            }
+            None => None,
+        };
+        execute_action(ctx, ontology.clone(), action_type, parameters, target).await
     }
+    
+    /// Queue an edit of one property for write-back as the calling user. `value` is JSON;
+    /// null deletes the property. The value is checked against the property definition
+    /// before it is queued. Returns the edit's ID.
+    async fn submit_edit(
+        &self,
+        ctx: &Context<'_>,
+        object_type: String,
+        object_id: String,
+        property: String,
+        value: String,
+        comment: Option<String>,
+    ) -> FieldResult<String> {
+        let user_id = ctx.data_opt::<SecurityContext>()
+            .map(|context| context.user_id.clone())
+            .ok_or_else(|| ApiError::unauthorized("Submitting an edit requires an authenticated user"))?;
+        let ontology = ctx.data::<OntologyHandle>()?.load();
+        let object_type_def = ontology.get_object_type(&object_type)
+            .ok_or_else(|| ApiError::not_found(format!("Object type '{}' not found", object_type)))?;
+        if property == object_type_def.primary_key {
+            return Err(ApiError::invalid_argument(format!("The primary key '{}' cannot be edited", property)).into());
+        }
+        let property_def = object_type_def.get_property(&property)
+            .filter(|_| !property.starts_with('_'))
+            .ok_or_else(|| ApiError::not_found(format!("Unknown property '{}' on object type '{}'", property, object_type)))?;
+        let json: Value = serde_json::from_str(&value)
+            .map_err(|e| ApiError::invalid_argument(format!("Invalid value JSON: {}", e)))?;
+        let value = property_def.value_from_json(&json).map_err(|e| ApiError::validation(Some(&property), e))?;
+
+        let current = ctx.data::<Arc<dyn SearchStore>>()?
+            .get_object(&object_type, &object_id).await
+            .map_err(ApiError::from)?
+            .ok_or_else(|| ApiError::not_found(format!("Object {}:{} not found", object_type, object_id)))?
+            .properties;
+        let mut merged = current.clone();
+        merged.insert(property.clone(), value.clone());
+        let checked = match &value {
+            PropertyValue::Null if property_def.required => Err(format!("Missing required property '{}'", property)),
+            PropertyValue::Null => Ok(()),
+            value => property_def.validate_value_with_siblings(value, &merged),
+        };
+        checked.map_err(|e| ApiError::validation(Some(&property), e))?;
+
+        let mut edit = UserEdit::new(&object_type, &object_id, &property, value, current.get(&property).cloned(), &user_id)
+            .with_comment(comment);
+        edit.deleted = edit.property_value.is_null();
+        let edit_id = edit.edit_id.clone();
+        ctx.data::<Arc<dyn EditQueue>>()?
+            .submit(edit).await
+            .map_err(ApiError::from)?;
+        Ok(edit_id)
+    }
+    
+    /// Withdraw a pending edit. Only the user who submitted it can withdraw it, and only
+    /// while it is pending.
+    async fn withdraw_edit(&self, ctx: &Context<'_>, edit_id: String) -> FieldResult<UserEditOutput> {
+        let queue = ctx.data::<Arc<dyn EditQueue>>()?;
+        let edit = queue.get_edit(&edit_id).await
+            .map_err(ApiError::from)?
+            .ok_or_else(|| ApiError::not_found(format!("Edit '{}' not found", edit_id)))?;
+        if ctx.data_opt::<SecurityContext>().is_none_or(|context| context.user_id != edit.user_id) {
+            return Err(ApiError::unauthorized("Only the user who submitted an edit can withdraw it").into());
+        }
+        let edit = queue.resolve(&edit_id, EditStatus::Withdrawn).await
+            .map_err(ApiError::from)?;
+        Ok(edit_output(&edit))
+    }
+    
+    /// Apply an object's pending edits to the search store. Conflicts with the stored
+    /// values are settled by `strategy`: `edit_wins` (the default), `source_wins` or
+    /// `manual`, which leaves them pending for `resolveConflict`. Applied edits are recorded
+    /// in the `ObjectEventLog` if one is configured. Requires the `admin` or
+    /// `edit_reviewer` role.
+    async fn apply_edits(
+        &self,
+        ctx: &Context<'_>,
+        object_type: String,
+        object_id: String,
+        strategy: Option<String>,
+    ) -> FieldResult<AppliedEditsOutput> {
+        require_edit_reviewer(ctx)?;
+        let strategy: ConflictResolutionStrategy = match strategy {
+            Some(strategy) => serde_json::from_value(Value::String(strategy.clone())).map_err(|_| {
+                ApiError::invalid_argument(format!(
+                    "Unknown strategy '{}'; expected 'edit_wins', 'source_wins' or 'manual'",
+                    strategy
+                ))
+            })?,
+            None => ConflictResolutionStrategy::default(),
+        };
+        let ontology = ctx.data::<OntologyHandle>()?.load();
+        let object_type_def = ontology.get_object_type(&object_type)
+            .ok_or_else(|| ApiError::not_found(format!("Object type '{}' not found", object_type)))?;
+        let queue = ctx.data::<Arc<dyn EditQueue>>()?;
+        let edits = queue.pending_edits(&object_type, &object_id).await.map_err(ApiError::from)?;
+        if edits.is_empty() {
+            return Ok(AppliedEditsOutput { revision: None, applied: vec![], rejected: vec![], conflicts: vec![] });
+        }
+        let applied = writeback::apply_edits(
+            ctx.data::<Arc<dyn SearchStore>>()?.as_ref(),
+            object_type_def,
+            &object_id,
+            &edits,
+            strategy,
+        )
+        .await
+        .map_err(ApiError::from)?;
+        let merge = &applied.merge;
+
+        // The latest edit of a written property is applied and earlier ones lost to it; edits
+        // of a property whose source value was kept are rejected; pending conflicts wait
+        let mut output = AppliedEditsOutput {
+            revision: Some(applied.revision),
+            applied: vec![],
+            rejected: vec![],
+            conflicts: merge.pending_conflicts().map(conflict_output).collect(),
+        };
+        let mut changed = PropertyMap::new();
+        for edit in &edits {
+            let written = merge.overridden_properties.contains(&edit.property_name);
+            let latest = edits.iter()
+                .filter(|other| other.property_name == edit.property_name)
+                .max_by_key(|other| other.timestamp)
+                .is_some_and(|latest| latest.edit_id == edit.edit_id);
+            let source_kept = merge.conflicts.iter().any(|conflict| {
+                conflict.property_name == edit.property_name && conflict.resolution == ConflictResolution::SourceKept
+            });
+            let status = if written && latest {
+                if let Some(value) = merge.merged_properties.get(&edit.property_name) {
+                    changed.insert(edit.property_name.clone(), value.clone());
+                }
+                EditStatus::Applied
+            } else if written || source_kept {
+                EditStatus::Rejected
+            } else {
+                continue;
+            };
+            queue.resolve(&edit.edit_id, status).await.map_err(ApiError::from)?;
+            match status {
+                EditStatus::Applied => output.applied.push(edit.edit_id.clone()),
+                _ => output.rejected.push(edit.edit_id.clone()),
+            }
+        }
+        if let Some(pending) = ctx.data_opt::<Arc<PendingConflicts>>() {
+            pending.record(merge);
+        }
+        if !changed.is_empty() {
+            if let Some(event_log) = ctx.data_opt::<ObjectEventLog>() {
+                let user_id = ctx.data_opt::<SecurityContext>().map(|context| context.user_id.clone());
+                event_log.write().await.record_updated(object_type, object_id, changed, user_id);
+            }
+        }
+        Ok(output)
+    }
+    
+    /// Settle a pending write-back conflict. `resolution` is `source` to drop the edit and
+    /// keep the source value, or `edit` to apply the edit over the current value. Requires
+    /// the `admin` or `edit_reviewer` role.
+    async fn resolve_conflict(
+        &self,
+        ctx: &Context<'_>,
+        edit_id: String,
+        resolution: String,
+    ) -> FieldResult<PendingConflictOutput> {
+        require_edit_reviewer(ctx)?;
+        let pending = ctx.data::<Arc<PendingConflicts>>()?;
+        let mut conflict = pending.get(&edit_id)
+            .ok_or_else(|| ApiError::not_found(format!("No pending conflict for edit '{}'", edit_id)))?;
+        match resolution.as_str() {
+            "source" => conflict.resolution = ConflictResolution::SourceKept,
+            "edit" => {
+                let ontology = ctx.data::<OntologyHandle>()?.load();
+                let object_type_def = ontology.get_object_type(&conflict.edit.object_type)
+                    .ok_or_else(|| ApiError::not_found(format!("Object type '{}' not found", conflict.edit.object_type)))?;
+                // Choosing the edit settles the conflict, so it applies whatever the source holds now
+                let applied = writeback::apply_edits(
+                    ctx.data::<Arc<dyn SearchStore>>()?.as_ref(),
+                    object_type_def,
+                    &conflict.edit.object_id,
+                    std::slice::from_ref(&conflict.edit),
+                    ConflictResolutionStrategy::EditWins,
+                )
+                .await
+                .map_err(ApiError::from)?;
+                if let Some(event_log) = ctx.data_opt::<ObjectEventLog>() {
+                    let mut changed = PropertyMap::new();
+                    if let Some(value) = applied.merge.merged_properties.get(&conflict.property_name) {
+                        changed.insert(conflict.property_name.clone(), value.clone());
+                    }
+                    let user_id = ctx.data_opt::<SecurityContext>().map(|context| context.user_id.clone());
+                    event_log.write().await.record_updated(
+                        conflict.edit.object_type.clone(),
+                        conflict.edit.object_id.clone(),
+                        changed,
+                        user_id,
+                    );
+                }
+                conflict.resolution = ConflictResolution::EditApplied;
+            }
+            other => {
+                return Err(ApiError::invalid_argument(format!(
+                    "Unknown resolution '{}'; expected 'source' or 'edit'",
+                    other
+                )).into())
+            }
+        }
+        pending.take(&edit_id);
+        if let Some(queue) = ctx.data_opt::<Arc<dyn EditQueue>>() {
+            let status = match conflict.resolution {
+                ConflictResolution::EditApplied => EditStatus::Applied,
+                _ => EditStatus::Rejected,
+            };
+            // Conflicts can come from edits that were never queued
+            match queue.resolve(&edit_id, status).await {
+                Ok(_) | Err(writeback::EditQueueError::NotFound(_)) => {}
+                Err(e) => return Err(ApiError::from(e).into()),
+            }
+        }
+        Ok(conflict_output(&conflict))
+    }
+    
+    /// Delete a link by ID. Like `createLink`, requires the `editor` or `admin` role and write
+    /// access to both of its objects.
+    async fn delete_link(&self, ctx: &Context<'_>, link_id: String) -> FieldResult<bool> {
+        let context = require_object_editor(ctx)?;
+        let not_found = || ApiError::not_found(format!("Link '{}' not found", link_id));
+        let graph_store = ctx.data::<Arc<dyn GraphStore>>()?;
+        let link = graph_store.get_link(&link_id).await
+            .map_err(ApiError::from)?
+            .ok_or_else(not_found)?;
+        let ontology = ctx.data::<OntologyHandle>()?.load();
+        if let Some(link_type_def) = ontology.get_link_type(&link.link_type_id) {
+            let search_store = ctx.data::<Arc<dyn SearchStore>>()?;
+            for endpoint in [
+                ObjectRef::new(&link_type_def.source, &link.source_id),
+                ObjectRef::new(&link_type_def.target, &link.target_id),
+            ] {
+                let object = search_store.get_object(&endpoint.object_type, &endpoint.object_id).await
+                    .map_err(ApiError::from)?;
+                if let Some(object) = object {
+                    check_object_writable(context, &endpoint, &object.properties)?;
+                }
+            }
+        }
+        match graph_store.delete_link(&link_id).await {
+            Ok(()) => Ok(true),
+            Err(StoreError::NotFound(_)) => Err(not_found().into()),
+            Err(e) => Err(ApiError::from(e).into()),
+        }
+    }
+    
+    /// Replace the live ontology with a new YAML or JSON definition. Every load error is
+    /// returned in `errors`; the current ontology stays live unless the reload succeeds.
+    /// Removing an object type that still has model bindings is rejected. Requires the
+    /// `admin` role.
+    async fn reload_ontology(
+        &self,
+        ctx: &Context<'_>,
+        definition: String,
+        format: Option<String>,
+    ) -> FieldResult<ReloadOntologyResult> {
+        require_ontology_admin(ctx)?;
+        let handle = ctx.data::<OntologyHandle>()?;
+        let loaded = match format.as_deref().unwrap_or("yaml") {
+            "yaml" | "yml" => Ontology::from_yaml(&definition),
+            "json" => Ontology::from_json(&definition),
+            other => {
+                return Err(ApiError::invalid_argument(format!(
+                    "Unsupported ontology format '{}' (expected 'yaml' or 'json')", other
+                )).into())
+            }
+        };
+        
+        let ontology = match loaded {
+            Ok(ontology) => ontology,
+            Err(errors) => {
+                return Ok(ReloadOntologyResult {
+                    success: false,
+                    version: handle.version(),
+                    errors: errors.iter().map(OntologyLoadErrorOutput::from).collect(),
+                    warnings: vec![],
+                    changes: vec![],
+                })
+            }
+        };
+        
+        // Snapshot the bound types first; the registry lock is async and the handle's is not
+        let bound_types: Vec<String> = match ctx.data_opt::<Arc<RwLock<ModelRegistry>>>() {
+            Some(registry) => registry.read().await.list_bindings()
+                .iter()
+                .map(|b| b.object_type.clone())
+                .collect(),
+            None => vec![],
+        };
+        
+        let warnings = ontology.warnings().to_vec();
+        Ok(match handle.reload(ontology, |changes| check_model_bindings(changes, &bound_types)) {
+            Ok((version, changes)) => ReloadOntologyResult {
+                success: true,
+                version,
+                errors: vec![],
+                warnings,
+                changes: changes.iter().map(ToString::to_string).collect(),
+            },
+            Err(message) => ReloadOntologyResult {
+                success: false,
+                version: handle.version(),
+                errors: vec![OntologyLoadErrorOutput {
+                    code: "active_model_bindings".to_string(),
+                    message,
+                    line: None,
+                    column: None,
+                }],
+                warnings: vec![],
+                changes: vec![],
+            },
+        })
+    }
+    
+    /// Start a background check that the search and graph stores agree for an object type.
+    /// Returns the job ID; poll `job(jobId)` for progress and the report. With `repair`,
+    /// dangling links are deleted and missing documents are reindexed from the event log.
+    async fn start_consistency_check(
+        &self,
+        ctx: &Context<'_>,
+        object_type: String,
+        sample_size: Option<usize>,
+        repair: Option<bool>,
+    ) -> FieldResult<String> {
+        let ontology = ctx.data::<OntologyHandle>()?;
+        if ontology.load().get_object_type(&object_type).is_none() {
+            return Err(ApiError::not_found(format!("Object type '{}' not found", object_type)).into());
+        }
+        let jobs = ctx.data::<JobRegistry>()?;
+        let mut checker = ConsistencyChecker::new(
+            ontology.clone(),
+            ctx.data::<Arc<dyn SearchStore>>()?.clone(),
+            ctx.data::<Arc<dyn GraphStore>>()?.clone(),
+        );
+        if let Some(history) = ctx.data_opt::<Arc<TimeQuery>>().cloned() {
+            checker = checker.with_object_source(Arc::new(move |object_type: &str, object_id: &str| {
+                history
+                    .reconstruct_object(object_type, object_id, chrono::Utc::now())
+                    .map(|object| object.properties)
+            }));
+        }
+        let options = ConsistencyOptions {
+            sampling: sample_size.map(SamplingOptions::new),
+            repair: repair.unwrap_or(false),
+            ..Default::default()
+        };
+        
+        let job = jobs.start("consistency_check");
+        let job_id = job.id().to_string();
+        tokio::spawn(async move {
+            match checker.check(&object_type, &options, Some(&job)).await {
+                Ok(report) => job.complete(serde_json::to_value(&report).unwrap_or(Value::Null)),
+                Err(e) => job.fail(e.to_string()),
+            }
+        });
+        Ok(job_id)
+    }
+    
+    /// Start a background export of an object type's objects to a `jsonl` (default) or
+    /// `csv` file. Returns the job ID; poll `exportStatus(jobId)` for progress and the
+    /// file path. The caller's ACL, masking profile and property access policy apply as
+    /// for `searchObjects`.
+    async fn start_export(
+        &self,
+        ctx: &Context<'_>,
+        object_type: String,
+        filters: Option<Vec<FilterInput>>,
+        format: Option<String>,
+    ) -> FieldResult<String> {
+        let ontology = ctx.data::<OntologyHandle>()?.load();
+        let object_type_def = ontology
+            .get_object_type(&object_type)
+            .ok_or_else(|| ApiError::not_found(format!("Object type '{}' not found", object_type)))?;
+        let format = match format.as_deref() {
+            None => ExportFormat::Jsonl,
+            Some(format) => ExportFormat::parse(format).ok_or_else(|| {
+                ApiError::invalid_argument(format!(
+                    "Unsupported export format '{}' (expected 'jsonl' or 'csv')", format
+                ))
+            })?,
+        };
+        
+        let mut store_filters = convert_filters(filters.unwrap_or_default(), &object_type_def.properties)?;
+        check_indexed(object_type_def, &store_filters, &[])?;
+        check_readable(ctx, object_type_def, query_properties(&store_filters, &[]))?;
+        
+        let mut exporter = ctx.data::<Exporter>()?.clone();
+        let context = ctx.data_opt::<SecurityContext>().cloned();
+        let requested_by = context.as_ref().map(|context| context.user_id.clone());
+        if let Some(context) = &context {
+            store_filters.extend(acl_store_filters(&AclSearchFilter::for_context(context)));
+        }
+        let masking = ctx.data_opt::<MaskingPolicy>().cloned().zip(context.clone());
+        let access = ctx.data_opt::<PropertyAccessPolicy>().cloned();
+        if masking.is_some() || access.is_some() {
+            exporter = exporter.with_transform(Arc::new(move |object_type, properties| {
+                let properties = match &masking {
+                    Some((policy, context)) => mask_properties(policy, context, object_type, properties),
+                    None => properties,
+                };
+                match &access {
+                    Some(policy) => redact_unreadable(policy, context.as_ref(), object_type, properties),
+                    None => properties,
+                }
+            }));
+        }
+        
+        let request = ExportRequest {
+            object_type,
+            filters: store_filters,
+            format,
+            requested_by,
+        };
+        exporter
+            .start(ctx.data::<JobRegistry>()?, request)
+            .map_err(|e| ApiError::from(e).into())
+    }
+    
+    /// Start a background search for duplicate objects of a type, per its dedup rules.
+    /// Returns the job ID; poll `job(jobId)` for progress and the candidate clusters.
+    async fn find_duplicates(&self, ctx: &Context<'_>, object_type: String) -> FieldResult<String> {
+        let ontology = ctx.data::<OntologyHandle>()?;
+        let has_rules = ontology
+            .load()
+            .get_object_type(&object_type)
+            .ok_or_else(|| ApiError::not_found(format!("Object type '{}' not found", object_type)))?
+            .dedup_rules
+            .as_ref()
+            .is_some_and(|rules| !rules.is_empty());
+        if !has_rules {
+            return Err(ApiError::invalid_argument(format!("Object type '{}' has no dedup rules", object_type)).into());
+        }
+        let jobs = ctx.data::<JobRegistry>()?;
+        let deduplicator = deduplicator(ctx)?;
+        
+        let job = jobs.start(FIND_DUPLICATES_JOB_KIND);
+        let job_id = job.id().to_string();
+        tokio::spawn(async move {
+            match deduplicator.find_duplicates(&object_type, Some(&job)).await {
+                Ok(report) => job.complete(serde_json::to_value(&report).unwrap_or(Value::Null)),
+                Err(e) => job.fail(e.to_string()),
+            }
+        });
+        Ok(job_id)
+    }
+    
+    /// Start a background job indexing the object references of existing data, for the given
+    /// object types or every type with reference properties. Returns the job ID; poll
+    /// `job(jobId)` for progress and the report.
+    async fn start_reference_backfill(
+        &self,
+        ctx: &Context<'_>,
+        object_types: Option<Vec<String>>,
+    ) -> FieldResult<String> {
+        let object_types = object_types.unwrap_or_default();
+        let ontology = ctx.data::<OntologyHandle>()?.load();
+        if let Some(unknown) = object_types.iter().find(|id| ontology.get_object_type(id).is_none()) {
+            return Err(ApiError::not_found(format!("Object type '{}' not found", unknown)).into());
+        }
+        let index = ctx.data::<ReferenceIndex>()?.clone();
+        let jobs = ctx.data::<JobRegistry>()?;
+        
+        let job = jobs.start(BACKFILL_REFERENCES_JOB_KIND);
+        let job_id = job.id().to_string();
+        tokio::spawn(async move {
+            match index.backfill(&object_types, Some(&job)).await {
+                Ok(report) => job.complete(serde_json::to_value(&report).unwrap_or(Value::Null)),
+                Err(e) => job.fail(e.to_string()),
+            }
+        });
+        Ok(job_id)
+    }
+    
+    /// Merge duplicate objects into a surviving one: properties are merged per the type's
+    /// survivorship policy, links and object references are repointed at the winner, and
+    /// the losers are soft-deleted so their IDs resolve to the winner. Requires the `editor`
+    /// or `admin` role and write access to every merged object under its ACL; the merged
+    /// properties are masked like any other object read.
+    async fn merge_objects(
+        &self,
+        ctx: &Context<'_>,
+        object_type: String,
+        winner_id: String,
+        loser_ids: Vec<String>,
+    ) -> FieldResult<MergeObjectsResult> {
+        let context = require_object_editor(ctx)?;
+        let ontology = ctx.data::<OntologyHandle>()?.load();
+        let object_type_def = ontology.get_object_type(&object_type)
+            .ok_or_else(|| ApiError::not_found(format!("Object type '{}' not found", object_type)))?;
+        let search_store = ctx.data::<Arc<dyn SearchStore>>()?;
+        for object_id in std::iter::once(&winner_id).chain(&loser_ids) {
+            if let Some(object) = search_store.get_object(&object_type, object_id).await.map_err(ApiError::from)? {
+                check_object_writable(context, &ObjectRef::new(&object_type, object_id), &object.properties)?;
+            }
+        }
+        let record = deduplicator(ctx)?
+            .merge(&object_type, &winner_id, &loser_ids)
+            .await
+            .map_err(ApiError::from)?;
+        let properties = record.merged_properties.iter()
+            .map(|(key, value)| (key.clone(), serde_json::to_value(value).unwrap_or(Value::Null)))
+            .collect();
+        Ok(MergeObjectsResult {
+            winner_id: record.winner_id,
+            loser_ids: record.loser_ids,
+            properties: Json(mask_object_json(ctx, object_type_def, Value::Object(properties))),
+            links_repointed: record.links_repointed as u64,
+            references_repointed: record.references_repointed as u64,
+        })
+    }
+    
+    /// Start a background move of an object type's objects that its archival policy marks as
+    /// due to the columnar store's archive. Returns the job ID; poll `job(jobId)` for
+    /// progress and the archived IDs. Archived IDs still resolve in `getObject`, to where
+    /// they were archived.
+    async fn archive_objects(&self, ctx: &Context<'_>, object_type: String) -> FieldResult<String> {
+        let has_policy = ctx
+            .data::<OntologyHandle>()?
+            .load()
+            .get_object_type(&object_type)
+            .ok_or_else(|| ApiError::not_found(format!("Object type '{}' not found", object_type)))?
+            .archival_policy
+            .is_some();
+        if !has_policy {
+            return Err(ApiError::invalid_argument(format!("Object type '{}' has no archival policy", object_type)).into());
+        }
+        let jobs = ctx.data::<JobRegistry>()?;
+        let archiver = archiver(ctx)?;
+
+        let job = jobs.start(ARCHIVE_JOB_KIND);
+        let job_id = job.id().to_string();
+        tokio::spawn(async move {
+            match archiver.archive(&object_type, Some(&job)).await {
+                Ok(record) => job.complete(serde_json::to_value(&record).unwrap_or(Value::Null)),
+                Err(e) => job.fail(e.to_string()),
+            }
+        });
+        Ok(job_id)
+    }
+
+    /// Start a background restore of the archived objects of a type matching `filters`
+    /// (all of them if none) to the search index. Returns the job ID.
+    async fn unarchive_objects(
+        &self,
+        ctx: &Context<'_>,
+        object_type: String,
+        filters: Option<Vec<FilterInput>>,
+    ) -> FieldResult<String> {
+        let store_filters = {
+            let ontology = ctx.data::<OntologyHandle>()?.load();
+            let object_type_def = ontology
+                .get_object_type(&object_type)
+                .ok_or_else(|| ApiError::not_found(format!("Object type '{}' not found", object_type)))?;
+            convert_filters(filters.unwrap_or_default(), &object_type_def.properties)?
+        };
+        let jobs = ctx.data::<JobRegistry>()?;
+        let archiver = archiver(ctx)?;
+
+        let job = jobs.start(UNARCHIVE_JOB_KIND);
+        let job_id = job.id().to_string();
+        tokio::spawn(async move {
+            match archiver.unarchive(&object_type, &store_filters, Some(&job)).await {
+                Ok(record) => job.complete(serde_json::to_value(&record).unwrap_or(Value::Null)),
+                Err(e) => job.fail(e.to_string()),
+            }
+        });
+        Ok(job_id)
+    }
+    
+    /// Register a change trigger: side effects fired when objects of a type are written.
+    /// `trigger` is a definition as returned in `changeTriggers { definition }`.
+    /// Requires the `admin` role.
+    async fn create_change_trigger(&self, ctx: &Context<'_>, trigger: Json<Value>) -> FieldResult<ChangeTriggerOutput> {
+        require_ontology_admin(ctx)?;
+        let trigger = parse_change_trigger(ctx, trigger.0)?;
+        ctx.data::<ChangeTriggerRegistry>()?
+            .create(trigger.clone())
+            .map_err(ApiError::invalid_argument)?;
+        Ok(ChangeTriggerOutput::from(trigger))
+    }
+    
+    /// Replace the definition of a registered change trigger, matched by its `id`.
+    /// Requires the `admin` role.
+    async fn update_change_trigger(&self, ctx: &Context<'_>, trigger: Json<Value>) -> FieldResult<ChangeTriggerOutput> {
+        require_ontology_admin(ctx)?;
+        let trigger = parse_change_trigger(ctx, trigger.0)?;
+        ctx.data::<ChangeTriggerRegistry>()?
+            .update(trigger.clone())
+            .map_err(ApiError::invalid_argument)?;
+        Ok(ChangeTriggerOutput::from(trigger))
+    }
+    
+    /// Remove a change trigger. Returns false if there was none with this ID.
+    /// Requires the `admin` role.
+    async fn delete_change_trigger(&self, ctx: &Context<'_>, id: String) -> FieldResult<bool> {
+        require_ontology_admin(ctx)?;
+        Ok(ctx.data::<ChangeTriggerRegistry>()?.delete(&id))
+    }
+    
+    /// Cancel a running background job. Returns false if the job is unknown or already finished,
+    /// or was started by another user and the caller is not an `admin`.
+    async fn cancel_job(&self, ctx: &Context<'_>, job_id: String) -> FieldResult<bool> {
+        let jobs = ctx.data::<JobRegistry>()?;
+        if !jobs.get(&job_id).is_some_and(|job| job_visible(ctx, &job)) {
+            return Ok(false);
+        }
+        Ok(jobs.cancel(&job_id))
+    }
+}
+
+/// A change trigger definition, checked against the live ontology
+/// Record every object and link a cascading delete removed
+pub(crate) async fn record_cascade_delete(event_log: &ObjectEventLog, deleted: &CascadeDelete, user_id: Option<String>) {
+    let mut event_log = event_log.write().await;
+    for link in &deleted.links {
+        event_log.record_link_deleted(
+            link.link_type_id.clone(),
+            link.link_id.clone(),
+            link.source_id.clone(),
+            link.target_id.clone(),
+            user_id.clone(),
+        );
+    }
+    for object in &deleted.objects {
+        event_log.record_deleted(object.object_type.clone(), object.object_id.clone(), user_id.clone());
+    }
+}
+
+fn require_ontology_admin(ctx: &Context<'_>) -> FieldResult<()> {
+    let context = ctx.data_opt::<SecurityContext>()
+        .ok_or_else(|| ApiError::unauthorized("Changing the ontology requires a security context"))?;
+    if !context.has_role(ONTOLOGY_ADMIN_ROLE) {
+        return Err(ApiError::unauthorized(format!(
+            "User '{}' may not change the ontology; the '{}' role is required",
+            context.user_id, ONTOLOGY_ADMIN_ROLE
+        )).into());
+    }
+    Ok(())
+}
+
+fn require_edit_reviewer(ctx: &Context<'_>) -> FieldResult<()> {
+    let context = ctx.data_opt::<SecurityContext>()
+        .ok_or_else(|| ApiError::unauthorized("Applying edits requires a security context"))?;
+    if !context.has_role(ONTOLOGY_ADMIN_ROLE) && !context.has_role(EDIT_REVIEWER_ROLE) {
+        return Err(ApiError::unauthorized(format!(
+            "User '{}' may not apply edits; the '{}' or '{}' role is required",
+            context.user_id, ONTOLOGY_ADMIN_ROLE, EDIT_REVIEWER_ROLE
+        )).into());
+    }
+    Ok(())
+}
+
+/// The caller's security context, if they hold a role that may write objects and links
+fn require_object_editor<'a>(ctx: &Context<'a>) -> FieldResult<&'a SecurityContext> {
+    let context = ctx.data_opt::<SecurityContext>()
+        .ok_or_else(|| ApiError::unauthorized("Writing objects requires a security context"))?;
+    if !context.has_role(ONTOLOGY_ADMIN_ROLE) && !context.has_role(OBJECT_EDITOR_ROLE) {
+        return Err(ApiError::unauthorized(format!(
+            "User '{}' may not write objects; the '{}' or '{}' role is required",
+            context.user_id, ONTOLOGY_ADMIN_ROLE, OBJECT_EDITOR_ROLE
+        )).into());
+    }
+    Ok(context)
+}
+
+/// Refuse to change an object whose ACL does not let the caller write it. Only ACL managers
+/// may change an object with an invalid ACL.
+fn check_object_writable(context: &SecurityContext, object: &ObjectRef, properties: &PropertyMap) -> FieldResult<()> {
+    let writable = match ObjectAcl::from_properties(properties) {
+        Ok(acl) => acl.unwrap_or_default().can_write(context),
+        Err(_) => context.has_role(MANAGE_ACL_ROLE),
+    };
+    if !writable {
+        return Err(ApiError::unauthorized(format!("User '{}' may not write {}", context.user_id, object)).into());
+    }
+    Ok(())
+}
+
+fn object_type_mut<'a>(ontology: &'a mut OntologyDef, object_type: &str) -> Result<&'a mut ObjectType, String> {
+    ontology
+        .object_types
+        .iter_mut()
+        .find(|ot| ot.id == object_type)
+        .ok_or_else(|| format!("Object type '{}' not found", object_type))
+}
+
+fn parse_change_trigger(ctx: &Context<'_>, definition: Value) -> FieldResult<ChangeTrigger> {
+    let trigger: ChangeTrigger = serde_json::from_value(definition)
+        .map_err(|e| ApiError::invalid_argument(format!("Invalid change trigger: {}", e)))?;
+    trigger
+        .validate(&ctx.data::<OntologyHandle>()?.load())
+        .map_err(|e| ApiError::invalid_argument(format!("Invalid change trigger: {}", e)))?;
+    Ok(trigger)
 }
 
-/// Input for adding object types
+/// Archiver over the configured stores, reporting moves to the `ArchiveEventSink` if one is
+/// registered
+fn archiver(ctx: &Context<'_>) -> FieldResult<Archiver> {
+    let mut archiver = Archiver::new(
+        ctx.data::<OntologyHandle>()?.clone(),
+        ctx.data::<Arc<dyn SearchStore>>()?.clone(),
+        ctx.data::<Arc<dyn ColumnarStore>>()?.clone(),
+    );
+    if let Some(sink) = ctx.data_opt::<ArchiveEventSink>() {
+        archiver = archiver.with_event_sink(sink.clone());
+    }
+    Ok(archiver)
+}
+
+/// Deduplicator over the configured stores, reporting merges to the `MergeEventSink` if one
+/// is registered
+fn deduplicator(ctx: &Context<'_>) -> FieldResult<Deduplicator> {
+    let mut deduplicator = Deduplicator::new(
+        ctx.data::<OntologyHandle>()?.clone(),
+        ctx.data::<Arc<dyn SearchStore>>()?.clone(),
+        ctx.data::<Arc<dyn GraphStore>>()?.clone(),
+    );
+    if let Some(sink) = ctx.data_opt::<MergeEventSink>() {
+        deduplicator = deduplicator.with_event_sink(sink.clone());
+    }
+    if let Some(index) = ctx.data_opt::<ReferenceIndex>() {
+        deduplicator = deduplicator.with_reference_index(index.clone());
+    }
+    Ok(deduplicator)
+}
+
+/// Outcome of a merge of duplicate objects
+#[derive(SimpleObject)]
+struct MergeObjectsResult {
+    winner_id: String,
+    loser_ids: Vec<String>,
+    /// Winner's properties after the merge
+    properties: Json<Value>,
+    links_repointed: u64,
+    /// Objects whose reference properties now point at the winner
+    references_repointed: u64,
+}
+
+/// Refuse a reload that removes an object type with properties still bound to models
+fn check_model_bindings(changes: &[OntologyChange], bound_types: &[String]) -> Result<(), String> {
+    for change in changes {
+        if let OntologyChange::TypeRemoved { object_type } = change {
+            if bound_types.contains(object_type) {
+                return Err(format!(
+                    "Object type '{}' still has active model bindings; unbind them before removing it",
+                    object_type
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Outcome of an ontology reload
+#[derive(SimpleObject)]
+struct ReloadOntologyResult {
+    success: bool,
+    /// Version of the live ontology after the call
+    version: u64,
+    errors: Vec<OntologyLoadErrorOutput>,
+    /// Problems that did not stop the load, e.g. strict types on shared datasources
+    warnings: Vec<String>,
+    /// Changes from the previous ontology, e.g. `object type 'x' added`
+    changes: Vec<String>,
+}
+
+/// A single ontology load error
+#[derive(SimpleObject)]
+struct OntologyLoadErrorOutput {
+    /// Machine-readable error code, e.g. `duplicate_id` or `parse_error`
+    code: String,
+    message: String,
+    /// Source location, for parse errors
+    line: Option<u64>,
+    column: Option<u64>,
+}
+
+impl From<&OntologyLoadError> for OntologyLoadErrorOutput {
+    fn from(error: &OntologyLoadError) -> Self {
+        let (line, column) = match error {
+            OntologyLoadError::ParseError { line, column, .. } => {
+                (line.map(|l| l as u64), column.map(|c| c as u64))
+            }
+            _ => (None, None),
+        };
+        Self {
+            code: error.code().to_string(),
+            message: error.to_string(),
+            line,
+            column,
+        }
+    }
+}
+
+/// Outcome of applying an object's pending edits
+#[derive(SimpleObject)]
+struct AppliedEditsOutput {
+    /// Revision of the stored object after the write; null if there was nothing to apply
+    revision: Option<u64>,
+    /// IDs of the edits written to the object
+    applied: Vec<String>,
+    /// IDs of the edits dropped in favour of the source value or a later edit
+    rejected: Vec<String>,
+    /// Conflicts left for `resolveConflict`; their edits stay pending
+    conflicts: Vec<PendingConflictOutput>,
+}
+
+/// Outcome of an upsert
+#[derive(SimpleObject)]
+struct UpsertObjectResult {
+    success: bool,
+    /// Revision of the stored object after the call
+    revision: u64,
+    /// Set when the object changed since the expected revision
+    conflict: Option<RevisionConflictOutput>,
+}
+
+/// The object state a rejected write has to be rebased onto
+#[derive(SimpleObject)]
+struct RevisionConflictOutput {
+    expected_revision: u64,
+    current_revision: u64,
+    /// Current properties, or null if the object does not exist
+    current: Option<Json<Value>>,
+}
+
+impl From<RevisionConflict> for RevisionConflictOutput {
+    fn from(conflict: RevisionConflict) -> Self {
+        let current = conflict.current.map(|object| {
+            let properties = object.properties.iter()
+                .map(|(key, value)| (key.clone(), serde_json::to_value(value).unwrap_or(Value::Null)))
+                .collect();
+            Json(Value::Object(properties))
+        });
+        Self {
+            expected_revision: conflict.expected_revision,
+            current_revision: conflict.current_revision,
+            current,
+        }
+    }
+}
+
+/// Result of `createLink`
+#[derive(SimpleObject)]
+struct CreateLinkResult {
+    link_id: String,
+    link_type: String,
+    source_id: String,
+    target_id: String,
+}
+
+/// Result of `deleteObject`
+#[derive(SimpleObject)]
+struct DeleteObjectResult {
+    /// The object and any objects deleted with it, as `object_type:object_id`
+    deleted_objects: Vec<String>,
+    deleted_links: Vec<String>,
+}
+
+/// Input for a single object ACL entry
 #[derive(InputObject)]
-struct ObjectTypeInput {
-    id: String,
-    display_name: String,
-    primary_key: String,
-    // Would need properties, etc. - simplified for now
+struct AclEntryInput {
+    principal: String,  // e.g. "user:alice", "role:analyst"
+    permission: String, // "read", "write", "manage", "deny"
 }
 
 /// Input for adding link types
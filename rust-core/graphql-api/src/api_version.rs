@@ -0,0 +1,268 @@
+//! Versioning of the GraphQL API itself.
+//!
+//! The schema is versioned as a whole. `API_VERSION` is reported by the `meta` query, and a
+//! client can name the major version it was written against in the request's
+//! `extensions.apiVersion`. Requests for the current major (or naming none) are served the
+//! current schema. With `compat_mode` on, requests for the previous major are served the field
+//! shapes that version used, such as JSON returned as encoded strings; legacy shapes are kept
+//! for one major version only. Any other version is rejected.
+//!
+//! Fields are deprecated in `DEPRECATIONS` before they are removed or change type; their
+//! `@deprecated` reasons are `Deprecation::reason`. The `check_breaking` test compares the
+//! schema with the committed SDL baseline and fails on removals and type changes the registry
+//! did not record first.
+
+use async_graphql::extensions::{Extension, ExtensionContext, ExtensionFactory, NextPrepareRequest};
+use async_graphql::parser::types::{
+    ConstDirective, InterfaceType, ObjectType, ServiceDocument, Type, TypeKind, TypeSystemDefinition,
+};
+use async_graphql::{Context, Json, Name, Positioned, Request, ServerError, ServerResult, SimpleObject};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+/// Version of the GraphQL API implemented by this crate
+pub const API_VERSION: &str = "2.0.0";
+
+/// Major component of `API_VERSION`
+pub const API_MAJOR_VERSION: u64 = 2;
+
+/// Request extension naming the API major version a client expects, e.g. `1` or `"1.4"`
+pub const API_VERSION_EXTENSION: &str = "apiVersion";
+
+/// A deprecated schema field and when it goes away
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Deprecation {
+    /// GraphQL type declaring the field, e.g. `Query`
+    pub type_name: &'static str,
+    pub field: &'static str,
+    /// Field to use instead
+    pub replacement: &'static str,
+    /// API version that deprecated the field
+    pub since: &'static str,
+    /// API version that removes the field
+    pub removal: &'static str,
+}
+
+impl Deprecation {
+    /// Reason carried by the field's `@deprecated` directive
+    pub fn reason(&self) -> String {
+        format!(
+            "Use `{}` instead. Deprecated in API {}; removed in API {}.",
+            self.replacement, self.since, self.removal
+        )
+    }
+}
+
+/// Every deprecated field. Entries stay until the committed schema baseline no longer has the
+/// field.
+pub const DEPRECATIONS: &[Deprecation] = &[
+    Deprecation {
+        type_name: "Query",
+        field: "executeFunction",
+        replacement: "callFunction",
+        since: "2.0",
+        removal: "3.0",
+    },
+    Deprecation {
+        type_name: "Query",
+        field: "queryByInterface",
+        replacement: "queryInterface",
+        since: "2.0",
+        removal: "3.0",
+    },
+];
+
+/// Registry entry for a field, if it is deprecated
+pub fn deprecation(type_name: &str, field: &str) -> Option<&'static Deprecation> {
+    DEPRECATIONS
+        .iter()
+        .find(|d| d.type_name == type_name && d.field == field)
+}
+
+/// Server-wide API settings, provided as schema data
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ApiSettings {
+    /// Serve legacy field shapes to clients of the previous major version
+    pub compat_mode: bool,
+}
+
+/// Field shapes a request is served with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApiShape {
+    Current,
+    /// Shapes of the previous major version: JSON fields are encoded as strings
+    Legacy,
+}
+
+/// Shapes to serve a client asking for `requested` (a major version or `major.minor`)
+pub fn negotiate(requested: Option<&str>, settings: ApiSettings) -> Result<ApiShape, String> {
+    let Some(requested) = requested else {
+        return Ok(ApiShape::Current);
+    };
+    let major = requested
+        .trim()
+        .split('.')
+        .next()
+        .and_then(|major| major.parse::<u64>().ok())
+        .ok_or_else(|| format!("Invalid API version '{}'", requested))?;
+
+    if major == API_MAJOR_VERSION {
+        Ok(ApiShape::Current)
+    } else if major + 1 == API_MAJOR_VERSION && settings.compat_mode {
+        Ok(ApiShape::Legacy)
+    } else if major + 1 == API_MAJOR_VERSION {
+        Err(format!(
+            "API version {} is only served in compat mode; this server implements API {}",
+            requested, API_VERSION
+        ))
+    } else {
+        Err(format!(
+            "Unsupported API version {}; this server implements API {}",
+            requested, API_VERSION
+        ))
+    }
+}
+
+/// Negotiates each request's API version from `extensions.apiVersion` against the schema's
+/// `ApiSettings`, rejecting unsupported versions before execution
+#[derive(Clone, Default)]
+pub struct ApiVersionExtension;
+
+impl ExtensionFactory for ApiVersionExtension {
+    fn create(&self) -> Arc<dyn Extension> {
+        Arc::new(self.clone())
+    }
+}
+
+#[async_trait::async_trait]
+impl Extension for ApiVersionExtension {
+    async fn prepare_request(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        request: Request,
+        next: NextPrepareRequest<'_>,
+    ) -> ServerResult<Request> {
+        let requested = request
+            .extensions
+            .get(API_VERSION_EXTENSION)
+            .map(|version| match version {
+                async_graphql::Value::String(version) => version.clone(),
+                other => other.to_string(),
+            });
+        let settings = ctx.data_opt::<ApiSettings>().copied().unwrap_or_default();
+        let shape = negotiate(requested.as_deref(), settings).map_err(|e| ServerError::new(e, None))?;
+        next.run(ctx, request.data(shape)).await
+    }
+}
+
+/// A JSON field value in the shape negotiated for the request
+pub(crate) fn json_field(ctx: &Context<'_>, value: &Value) -> Json<Value> {
+    match ctx.data_opt::<ApiShape>() {
+        Some(ApiShape::Legacy) => Json(Value::String(value.to_string())),
+        _ => Json(value.clone()),
+    }
+}
+
+/// API version information
+#[derive(SimpleObject)]
+pub struct ApiMeta {
+    pub api_version: String,
+    pub compat_mode: bool,
+    /// Whether this request is served the previous major version's field shapes
+    pub legacy_shapes: bool,
+    pub deprecations: Vec<DeprecationOutput>,
+}
+
+/// A deprecated field, as listed by `meta`
+#[derive(SimpleObject)]
+pub struct DeprecationOutput {
+    pub type_name: String,
+    pub field: String,
+    pub replacement: String,
+    pub since: String,
+    pub removal: String,
+}
+
+impl ApiMeta {
+    pub(crate) fn for_context(ctx: &Context<'_>) -> Self {
+        Self {
+            api_version: API_VERSION.to_string(),
+            compat_mode: ctx.data_opt::<ApiSettings>().is_some_and(|s| s.compat_mode),
+            legacy_shapes: ctx.data_opt::<ApiShape>() == Some(&ApiShape::Legacy),
+            deprecations: DEPRECATIONS
+                .iter()
+                .map(|d| DeprecationOutput {
+                    type_name: d.type_name.to_string(),
+                    field: d.field.to_string(),
+                    replacement: d.replacement.to_string(),
+                    since: d.since.to_string(),
+                    removal: d.removal.to_string(),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Field of an SDL document: its type and whether it carries `@deprecated`
+struct SdlField {
+    ty: String,
+    deprecated: bool,
+}
+
+/// Fields of every object, interface and input type, keyed by `(type, field)`
+fn sdl_fields(document: &ServiceDocument) -> BTreeMap<(String, String), SdlField> {
+    let is_deprecated =
+        |directives: &[Positioned<ConstDirective>]| directives.iter().any(|d| d.node.name.node == "deprecated");
+    let mut fields = BTreeMap::new();
+    for definition in &document.definitions {
+        let TypeSystemDefinition::Type(type_def) = definition else {
+            continue;
+        };
+        let declared: Vec<(&Name, &Type, bool)> = match &type_def.node.kind {
+            TypeKind::Object(ObjectType { fields, .. }) | TypeKind::Interface(InterfaceType { fields, .. }) => fields
+                .iter()
+                .map(|f| (&f.node.name.node, &f.node.ty.node, is_deprecated(&f.node.directives)))
+                .collect(),
+            TypeKind::InputObject(input) => input
+                .fields
+                .iter()
+                .map(|f| (&f.node.name.node, &f.node.ty.node, is_deprecated(&f.node.directives)))
+                .collect(),
+            _ => continue,
+        };
+        for (field, ty, deprecated) in declared {
+            fields.insert(
+                (type_def.node.name.node.to_string(), field.to_string()),
+                SdlField { ty: ty.to_string(), deprecated },
+            );
+        }
+    }
+    fields
+}
+
+/// Breaking changes from the `baseline` SDL to the `current` one: fields removed or retyped
+/// without first being deprecated in the baseline and recorded in `DEPRECATIONS`
+pub fn breaking_changes(baseline: &str, current: &str) -> Result<Vec<String>, String> {
+    let baseline = async_graphql::parser::parse_schema(baseline)
+        .map_err(|e| format!("Invalid baseline SDL: {}", e))?;
+    let current = async_graphql::parser::parse_schema(current)
+        .map_err(|e| format!("Invalid current SDL: {}", e))?;
+    let current = sdl_fields(&current);
+
+    let mut changes = Vec::new();
+    for ((type_name, field), old) in sdl_fields(&baseline) {
+        let change = match current.get(&(type_name.clone(), field.clone())) {
+            None => format!("{}.{} was removed", type_name, field),
+            Some(new) if new.ty != old.ty => {
+                format!("{}.{} changed type from {} to {}", type_name, field, old.ty, new.ty)
+            }
+            Some(_) => continue,
+        };
+        if old.deprecated && deprecation(&type_name, &field).is_some() {
+            continue;
+        }
+        changes.push(format!("{} without a prior deprecation", change));
+    }
+    Ok(changes)
+}
@@ -0,0 +1,116 @@
+use anyhow::{Context, Result};
+use async_graphql::{EmptySubscription, Schema};
+use clap::Parser;
+use graphql_api::{AdminMutations, QueryRoot};
+use indexing::hydration::ObjectHydrator;
+use indexing::store::{ElasticsearchStore, SearchStore};
+use ontology_engine::{Ontology, OntologyConfig, OntologyHandle};
+use security::{MaskingPolicy, SecurityContext};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+#[derive(Parser, Debug)]
+#[command(name = "query")]
+#[command(about = "Select objects with an OQL query, e.g. 'Plant where state = \"NJ\" order by year desc limit 10'")]
+struct Args {
+    /// OQL query
+    oql: String,
+
+    /// Compiled ontology (JSON, or YAML by extension)
+    #[arg(short, long)]
+    ontology: PathBuf,
+
+    #[arg(long, default_value = "http://localhost:9200")]
+    elasticsearch_url: String,
+
+    /// Run the query as this user; results are limited to objects the user may read
+    #[arg(long)]
+    user: Option<String>,
+
+    /// Role of the user (repeatable)
+    #[arg(long = "role")]
+    roles: Vec<String>,
+
+    /// Property masking rules (JSON), applied for the user
+    #[arg(long)]
+    masking_policy: Option<PathBuf>,
+
+    /// Print results as a JSON array instead of one line per object
+    #[arg(long)]
+    json: bool,
+}
+
+fn load_ontology(path: &PathBuf) -> Result<Ontology> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read ontology file: {}", path.display()))?;
+    let is_yaml = matches!(path.extension().and_then(|e| e.to_str()), Some("yaml" | "yml"));
+    let config = if is_yaml {
+        OntologyConfig::from_yaml(&content).context("Failed to parse ontology YAML")?
+    } else {
+        serde_json::from_str(&content).context("Failed to parse ontology JSON")?
+    };
+    Ontology::from_config(config).context("Invalid ontology")
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let args = Args::parse();
+
+    let ontology = load_ontology(&args.ontology)?;
+    let search_store: Arc<dyn SearchStore> = Arc::new(ElasticsearchStore::new(args.elasticsearch_url.clone())?);
+    let masking_policy = match &args.masking_policy {
+        Some(path) => {
+            let content = std::fs::read_to_string(path)
+                .with_context(|| format!("Failed to read masking policy: {}", path.display()))?;
+            serde_json::from_str::<MaskingPolicy>(&content).context("Failed to parse masking policy")?
+        }
+        None => MaskingPolicy::default(),
+    }
+    .with_hash_key(std::env::var("MASKING_HASH_KEY").unwrap_or_default());
+    masking_policy
+        .validate()
+        .map_err(|e| anyhow::anyhow!("Invalid masking policy: {}; set MASKING_HASH_KEY", e))?;
+
+    // The query runs through the GraphQL resolver, so ACLs, masking and hydration apply as
+    // they do on the server
+    let schema = Schema::build(QueryRoot::default(), AdminMutations::default(), EmptySubscription)
+        .data(OntologyHandle::new(ontology))
+        .data(search_store)
+        .data(ObjectHydrator::new())
+        .data(masking_policy)
+        .finish();
+
+    let mut request = async_graphql::Request::new(
+        "query($oql: String!) { queryOql(oql: $oql) { objectId title properties } }",
+    )
+    .variables(async_graphql::Variables::from_json(serde_json::json!({ "oql": args.oql })));
+    if let Some(user) = &args.user {
+        let context = args
+            .roles
+            .iter()
+            .fold(SecurityContext::new(user.clone()), |context, role| context.with_role(role.clone()));
+        request = request.data(context);
+    }
+
+    let response = schema.execute(request).await;
+    if let Some(error) = response.errors.first() {
+        anyhow::bail!("{}", error.message);
+    }
+    let data = response.data.into_json()?;
+    let results = data["queryOql"].as_array().cloned().unwrap_or_default();
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&results)?);
+    } else {
+        for result in &results {
+            println!(
+                "{}\t{}\t{}",
+                result["objectId"].as_str().unwrap_or_default(),
+                result["title"].as_str().unwrap_or_default(),
+                result["properties"]
+            );
+        }
+        eprintln!("{} objects", results.len());
+    }
+    Ok(())
+}
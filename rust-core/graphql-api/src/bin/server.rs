@@ -3,127 +3,58 @@ use async_graphql::{
     EmptySubscription, Schema,
 };
 use axum::{body::Body, extract::State, response::IntoResponse, routing::get, Router};
-use graphql_api::{AdminMutations, QueryRoot};
+use graphql_api::{
+    init_tracing, spawn_cache_invalidation, AdminMutations, ApiSettings, ApiVersionExtension, FunctionCache,
+    LogFormat, MaskingProfileExtension, ObjectEventLog, QueryExplainExtension, QueryRoot, RequestTracingExtension,
+};
+use data_loader::{DataLoader, LoadOptions};
 use indexing::hydration::ObjectHydrator;
-use indexing::store::{DgraphStore, ElasticsearchStore, ParquetStore};
-use ontology_engine::Ontology;
+use indexing::{
+    ChangeTriggerRegistry, Exporter, InMemoryColumnarStore, InMemoryGraphStore, InMemorySearchStore, JobRegistry,
+    LoggedColumnarStore, LoggedGraphStore, LoggedSearchStore, ModelPropertyEvaluator,
+    PlannerConfig, QueryLog, QueryLogConfig, QueryPlanner, PropertyDrift, ReferenceIndex, ReferenceIndexingSearchStore,
+    SchemaSync, TriggeringSearchStore, ValidatingGraphStore, ValidatingSearchStore,
+};
+use indexing::store::{ColumnarStore, DgraphStore, ElasticsearchStore, GraphStore, ParquetStore, SearchStore};
+use ontology_engine::{
+    BackupManager, FileBackupComponent, ModelCache, ModelExecutionOrchestrator, ModelRegistry, Ontology, OntologyConfig, OntologyHandle, OntologyOverlay, RemoteModelExecutor,
+    SideEffectConfig, SideEffectHandlers, DEFAULT_MODEL_CACHE_ENTRIES,
+};
+use security::{MaskingPolicy, PropertyAccessPolicy};
 use serde_json::Value;
 use std::collections::HashMap;
 use std::fs;
 use std::sync::Arc;
 use versioning::event_log::EventLog;
 use versioning::time_query::TimeQuery;
-
-// In-memory data store for demo purposes
-lazy_static::lazy_static! {
-    static ref DATA_STORE: Arc<tokio::sync::RwLock<HashMap<String, Vec<Value>>>> =
-        Arc::new(tokio::sync::RwLock::new(HashMap::new()));
-}
-
-async fn load_data_from_files() {
-    // Try multiple possible paths
-    let possible_paths = vec![
-        std::path::Path::new("examples/census/data"),
-        std::path::Path::new("../../examples/census/data"),
-        std::path::Path::new("../examples/census/data"),
-    ];
-
-    let mut data_dir = None;
-    for path in &possible_paths {
-        if path.exists() {
-            data_dir = Some(path);
-            println!("Using data directory: {}", path.display());
-            break;
-        }
-    }
-
-    let data_dir = match data_dir {
-        Some(dir) => dir,
-        None => {
-            println!(
-                "⚠ Warning: Could not find data directory. Tried: {:?}",
-                possible_paths
-            );
-            return;
-        }
+use versioning::{EventSink, EventStore, FileEventStore, SnapshotCacheConfig};
+use writeback::{EditQueue, InMemoryEditQueue, PendingConflicts, WriteBackQueue};
+
+/// Load `DATA_DIR` into the stores through the data loader. Returns the ingest events,
+/// which temporal queries read.
+async fn load_data_dir(
+    dir: &str,
+    ontology: Arc<Ontology>,
+    search: Arc<dyn SearchStore>,
+    graph: Arc<dyn GraphStore>,
+) -> EventLog {
+    println!("Loading data from: {}", dir);
+    let options = LoadOptions {
+        continue_on_error: true,
+        ..Default::default()
     };
-
-    let files = vec![
-        ("states.json", "state_vintage"),
-        ("counties.json", "county_vintage"),
-        ("tracts.json", "census_tract_vintage"),
-        ("pumas.json", "puma_vintage"),
-        ("crosswalks.json", "boundary_crosswalk"),
-        ("households.json", "pums_household"),
-        ("persons.json", "pums_person"),
-    ];
-
-    let mut store = DATA_STORE.write().await;
-    let mut total_loaded = 0;
-
-    for (filename, object_type) in files {
-        let file_path = data_dir.join(filename);
-        if file_path.exists() {
-            match fs::read_to_string(&file_path) {
-                Ok(content) => {
-                    match serde_json::from_str::<Vec<Value>>(&content) {
-                        Ok(mut objects) => {
-                            // Inject computed primary keys where the data doesn't include them
-                            if object_type == "pums_person" {
-                                for obj in &mut objects {
-                                    if let Value::Object(map) = obj {
-                                        if !map.contains_key("person_id_year") {
-                                            let person_id = map
-                                                .get("person_id")
-                                                .and_then(|v| v.as_str())
-                                                .unwrap_or("unknown");
-                                            let year = map
-                                                .get("year")
-                                                .and_then(|v| v.as_i64())
-                                                .unwrap_or(0);
-                                            map.insert(
-                                                "person_id_year".to_string(),
-                                                Value::String(format!("{}_{}", person_id, year)),
-                                            );
-                                        }
-                                    }
-                                }
-                            }
-
-                            let key = object_type.to_string();
-                            let count = objects.len();
-                            store.insert(key.clone(), objects);
-                            total_loaded += count;
-                            println!(
-                                "✓ Loaded {} {} objects from {}",
-                                count, object_type, filename
-                            );
-                        }
-                        Err(e) => {
-                            println!("⚠ Failed to parse {}: {}", filename, e);
-                        }
-                    }
-                }
-                Err(e) => {
-                    println!("⚠ Failed to read {}: {}", filename, e);
-                }
-            }
-        } else {
-            println!("⚠ File not found: {}", file_path.display());
-        }
+    let mut loader = DataLoader::new(ontology, search, graph).with_options(options);
+    match loader.load_dir(std::path::Path::new(dir)).await {
+        Ok(report) => println!("{}", report),
+        Err(e) => println!("⚠ Failed to load data: {}", e),
     }
-
-    println!(
-        "✓ Data loading complete: {} total objects loaded",
-        total_loaded
-    );
-    println!("  Store keys: {:?}", store.keys().collect::<Vec<_>>());
+    loader.into_event_log()
 }
 
 #[tokio::main]
 async fn main() {
-    // Load data first
-    load_data_from_files().await;
+    // `RUST_LOG` sets the levels; `LOG_FORMAT=json` switches to JSON lines
+    init_tracing(LogFormat::parse(&std::env::var("LOG_FORMAT").unwrap_or_default()));
 
     // Load ontology
     let ontology_path = std::env::var("ONTOLOGY_PATH")
@@ -133,51 +64,329 @@ async fn main() {
     let ontology_content =
         fs::read_to_string(&ontology_path).expect("Failed to read ontology file");
 
-    let ontology = Ontology::from_yaml(&ontology_content).expect("Failed to parse ontology");
+    let base = OntologyConfig::from_yaml(&ontology_content).expect("Failed to parse ontology");
+    // Comma-separated overlay files applied in order, e.g. per-environment datasources
+    let overlay_paths: Vec<String> = std::env::var("ONTOLOGY_OVERLAYS")
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|path| !path.is_empty())
+        .map(str::to_string)
+        .collect();
+    let overlays: Vec<OntologyOverlay> = overlay_paths
+        .iter()
+        .map(|path| {
+            println!("Applying ontology overlay: {}", path);
+            let content = fs::read_to_string(path).expect("Failed to read ontology overlay");
+            OntologyOverlay::from_yaml(path, &content).expect("Failed to parse ontology overlay")
+        })
+        .collect();
+    let ontology = Ontology::from_layers(base, &overlays).expect("Failed to load ontology");
 
     println!(
         "✓ Loaded ontology with {} object types",
         ontology.object_types().count()
     );
+    for warning in ontology.warnings() {
+        println!("⚠ {}", warning);
+    }
 
-    // Create store backends (using placeholder implementations)
-    let search_store: Arc<dyn indexing::store::SearchStore> = Arc::new(
-        ElasticsearchStore::new("http://localhost:9200".to_string())
-            .expect("Failed to create Elasticsearch store"),
-    );
-    let graph_store: Arc<dyn indexing::store::GraphStore> = Arc::new(
-        DgraphStore::new("http://localhost:9080".to_string())
-            .await
-            .expect("Failed to create Dgraph store"),
+    // Live ontology shared by resolvers; runtime changes swap it and notify listeners
+    let ontology = OntologyHandle::new(ontology);
+
+    // STORE_BACKEND=memory keeps all data in process, for local development without
+    // Elasticsearch, Dgraph or a Parquet directory
+    let in_memory = std::env::var("STORE_BACKEND").is_ok_and(|backend| backend == "memory");
+    let (raw_search_store, raw_graph_store, mut columnar_store): (
+        Arc<dyn SearchStore>,
+        Arc<dyn GraphStore>,
+        Arc<dyn ColumnarStore>,
+    ) = if in_memory {
+        println!("Using in-memory stores");
+        (
+            Arc::new(InMemorySearchStore::new()),
+            Arc::new(InMemoryGraphStore::new()),
+            Arc::new(InMemoryColumnarStore::new()),
+        )
+    } else {
+        let elasticsearch = Arc::new(
+            ElasticsearchStore::new("http://localhost:9200".to_string())
+                .expect("Failed to create Elasticsearch store"),
+        );
+        let dgraph = Arc::new(
+            DgraphStore::new("http://localhost:9080".to_string())
+                .await
+                .expect("Failed to create Dgraph store"),
+        );
+        // Keep index mappings and edge predicates in step with ontology changes
+        SchemaSync::new(ontology.clone())
+            .with_search_store(elasticsearch.clone())
+            .with_graph_store(dgraph.clone())
+            .spawn();
+        (elasticsearch, dgraph, Arc::new(ParquetStore::new("data/parquet".to_string())))
+    };
+    // Writes are checked for undeclared properties: strict types reject them, the rest
+    // index them and count them for the usage report
+    let property_drift = PropertyDrift::new();
+    let mut search_store: Arc<dyn SearchStore> = Arc::new(
+        ValidatingSearchStore::new(raw_search_store.clone(), ontology.clone()).with_drift(property_drift.clone()),
     );
-    let columnar_store: Arc<dyn indexing::store::ColumnarStore> =
-        Arc::new(ParquetStore::new("data/parquet".to_string()));
+    // Change triggers run side effects for writes from any path (sync, writeback, upserts)
+    let change_triggers = ChangeTriggerRegistry::new();
+    search_store = Arc::new(TriggeringSearchStore::new(search_store, change_triggers.clone()));
+    // Reverse references are kept beside the objects in the raw store; run a reference
+    // backfill once to index data written before this was enabled
+    let reference_index = ReferenceIndex::new(raw_search_store, ontology.clone());
+    search_store = Arc::new(ReferenceIndexingSearchStore::new(search_store, reference_index.clone()));
+    // Link writes are checked against the ontology; bidirectional link types traverse both ways
+    let mut graph_store: Arc<dyn GraphStore> =
+        Arc::new(ValidatingGraphStore::new(raw_graph_store, ontology.clone()));
+
+    // Record store queries slower than SLOW_QUERY_THRESHOLD_MS, optionally to SLOW_QUERY_LOG
+    let query_log = std::env::var("SLOW_QUERY_THRESHOLD_MS")
+        .ok()
+        .and_then(|ms| ms.parse::<u64>().ok())
+        .map(|ms| {
+            let config = QueryLogConfig {
+                slow_threshold: std::time::Duration::from_millis(ms),
+                log_file: std::env::var("SLOW_QUERY_LOG").ok().map(Into::into),
+                ..Default::default()
+            };
+            Arc::new(QueryLog::new(config).with_ontology(ontology.clone()))
+        });
+    if let Some(log) = &query_log {
+        let (search, graph, columnar) =
+            if in_memory { ("memory", "memory", "memory") } else { ("elasticsearch", "dgraph", "parquet") };
+        search_store = Arc::new(LoggedSearchStore::new(search_store, search, log.clone()));
+        graph_store = Arc::new(LoggedGraphStore::new(graph_store, graph, log.clone()));
+        columnar_store = Arc::new(LoggedColumnarStore::new(columnar_store, columnar, log.clone()));
+    }
 
-    // Create time query
-    let event_log = EventLog::new();
-    let time_query = Arc::new(TimeQuery::new(event_log));
+    // Per-type data files from DATA_DIR are loaded through the validating stores; their
+    // ingest events back temporal queries
+    let event_log = match std::env::var("DATA_DIR") {
+        Ok(dir) => load_data_dir(&dir, ontology.load(), search_store.clone(), graph_store.clone()).await,
+        Err(_) => EventLog::new(),
+    };
+    // With EVENT_LOG_DIR, history is kept on disk across restarts. The ingest events seed
+    // an empty log only: reloading DATA_DIR on a restart records its objects again, and the
+    // log already holds them from the first run.
+    let event_log_dir = std::env::var("EVENT_LOG_DIR").ok();
+    let time_query = match &event_log_dir {
+        Some(dir) => {
+            let mut store = FileEventStore::open(&dir).expect("Failed to open event log");
+            if store.is_empty().expect("Failed to read event log") {
+                for event in event_log.events() {
+                    store.append(event.clone()).expect("Failed to write to event log");
+                }
+            }
+            TimeQuery::with_store(Box::new(store))
+        }
+        None => TimeQuery::new(event_log),
+    };
+    // Temporal queries replay from checkpoints of object state rather than the first event
+    let time_query = Arc::new(time_query.with_snapshot_cache(SnapshotCacheConfig::default()));
+    // User edits wait for write-back in Postgres with WRITEBACK_DATABASE_URL, else in memory
+    let writeback_queue = match std::env::var("WRITEBACK_DATABASE_URL") {
+        Ok(url) => Some(WriteBackQueue::connect(&url).await.expect("Failed to open writeback queue")),
+        Err(_) => None,
+    };
+    let edit_queue: Arc<dyn EditQueue> = match &writeback_queue {
+        Some(queue) => Arc::new(queue.clone()),
+        None => Arc::new(InMemoryEditQueue::new()),
+    };
+    // Changes made through the API; they join the history temporal queries read, on disk
+    // with EVENT_LOG_DIR
+    let history = time_query.clone();
+    let history_sink: EventSink = Arc::new(move |event| {
+        if let Err(e) = history.append(event.clone()) {
+            tracing::error!("Failed to record event {} in the event log: {}", event.event_id, e);
+        }
+    });
+    let object_event_log: ObjectEventLog =
+        Arc::new(tokio::sync::RwLock::new(EventLog::new().with_sink(history_sink)));
 
     // Create hydrator
     let hydrator = ObjectHydrator::new();
 
     // Create function result cache
-    let function_cache: Arc<tokio::sync::RwLock<HashMap<u64, ontology_engine::PropertyValue>>> =
-        Arc::new(tokio::sync::RwLock::new(HashMap::new()));
+    let function_cache: FunctionCache = Arc::new(tokio::sync::RwLock::new(HashMap::new()));
+    spawn_cache_invalidation(&ontology, function_cache.clone());
+
+    // Load property masking rules; the HMAC key for hashed values comes from the environment
+    let masking_policy = match std::env::var("MASKING_POLICY_PATH") {
+        Ok(path) => {
+            let content = fs::read_to_string(&path).expect("Failed to read masking policy file");
+            serde_json::from_str::<MaskingPolicy>(&content).expect("Failed to parse masking policy")
+        }
+        Err(_) => MaskingPolicy::default(),
+    }
+    .with_hash_key(std::env::var("MASKING_HASH_KEY").unwrap_or_default());
+    if let Err(e) = masking_policy.validate() {
+        panic!("Invalid masking policy: {}; set MASKING_HASH_KEY", e);
+    }
+
+    // Sensitivity tags only some callers may read; without a policy every property is readable
+    let property_access_policy = match std::env::var("PROPERTY_ACCESS_POLICY_PATH") {
+        Ok(path) => {
+            let content = fs::read_to_string(&path).expect("Failed to read property access policy file");
+            serde_json::from_str::<PropertyAccessPolicy>(&content).expect("Failed to parse property access policy")
+        }
+        Err(_) => PropertyAccessPolicy::default(),
+    };
+
+    // Webhook endpoints and the SMTP relay for action side effects; their headers and
+    // credentials are secrets, so the file should be readable by the server only
+    let side_effects = match std::env::var("SIDE_EFFECTS_CONFIG_PATH") {
+        Ok(path) => {
+            let content = fs::read_to_string(&path).expect("Failed to read side effects config");
+            serde_json::from_str::<SideEffectConfig>(&content).expect("Failed to parse side effects config")
+        }
+        Err(_) => SideEffectConfig::default(),
+    };
+
+    // Per-type backend preferences for the query planner; backends that fail are avoided for
+    // a while regardless
+    let query_planner = match std::env::var("QUERY_PLANNER_CONFIG") {
+        Ok(path) => {
+            let content = fs::read_to_string(&path).expect("Failed to read query planner config");
+            QueryPlanner::new(serde_json::from_str::<PlannerConfig>(&content).expect("Failed to parse query planner config"))
+        }
+        Err(_) => QueryPlanner::default(),
+    };
+
+    // Background jobs survive restarts in JOB_REGISTRY_PATH; finished jobs and their export
+    // files are kept for EXPORT_RETENTION_HOURS
+    let retention_hours = std::env::var("EXPORT_RETENTION_HOURS")
+        .ok()
+        .and_then(|hours| hours.parse::<u64>().ok())
+        .unwrap_or(24);
+    let jobs = JobRegistry::open(
+        std::env::var("JOB_REGISTRY_PATH").unwrap_or_else(|_| "data/jobs.json".to_string()),
+    )
+    .expect("Failed to open job registry")
+    .with_retention(std::time::Duration::from_secs(retention_hours * 3600));
+    // Registered models and their property bindings survive restarts in MODEL_REGISTRY_PATH
+    let model_registry = Arc::new(tokio::sync::RwLock::new(
+        ModelRegistry::open(
+            std::env::var("MODEL_REGISTRY_PATH").unwrap_or_else(|_| "data/model_registry.json".to_string()),
+        )
+        .expect("Failed to open model registry"),
+    ));
+    // With BACKUP_INTERVAL_SECS, the event log, the model registry, the ontology overlay files
+    // and the Postgres writeback queue are backed up under BACKUP_DIR that often; the newest
+    // BACKUP_RETENTION backups are kept. The event log is the EVENT_LOG_DIR log, restored
+    // offline with `event-log-restore`; without it, only the API's in-memory changes are kept.
+    let backup_interval_secs = std::env::var("BACKUP_INTERVAL_SECS")
+        .ok()
+        .and_then(|secs| secs.parse::<u64>().ok())
+        .filter(|secs| *secs > 0);
+    if let Some(interval_secs) = backup_interval_secs {
+        let retention = std::env::var("BACKUP_RETENTION")
+            .ok()
+            .and_then(|count| count.parse().ok())
+            .unwrap_or(7);
+        let mut backups = BackupManager::new(
+            std::env::var("BACKUP_DIR").unwrap_or_else(|_| "data/backups".to_string()),
+            retention,
+        );
+        match &event_log_dir {
+            Some(dir) => backups.register(Arc::new(tokio::sync::RwLock::new(
+                FileEventStore::open(dir).expect("Failed to open event log"),
+            ))),
+            None => backups.register(object_event_log.clone()),
+        }
+        backups.register(model_registry.clone());
+        for (index, path) in overlay_paths.iter().enumerate() {
+            backups.register(Arc::new(tokio::sync::RwLock::new(FileBackupComponent::new(
+                format!("ontology_overlay_{}", index),
+                path,
+            ))));
+        }
+        if let Some(queue) = writeback_queue {
+            backups.register(Arc::new(tokio::sync::RwLock::new(queue)));
+        }
+        Arc::new(backups).spawn_scheduled(std::time::Duration::from_secs(interval_secs));
+    }
+    // Predictions run on remote platforms; custom endpoints receive the inputs as JSON. The
+    // cache keeps the MODEL_CACHE_MAX_ENTRIES most recently used predictions and drops
+    // expired ones every MODEL_CACHE_EVICTION_SECS.
+    let max_cached_predictions = std::env::var("MODEL_CACHE_MAX_ENTRIES")
+        .ok()
+        .and_then(|entries| entries.parse().ok())
+        .unwrap_or(DEFAULT_MODEL_CACHE_ENTRIES);
+    let mut model_executor = ModelExecutionOrchestrator::with_cache(ModelCache::with_max_entries(max_cached_predictions));
+    model_executor.add_executor(Box::new(RemoteModelExecutor::new()));
+    let model_executor = Arc::new(model_executor);
+    let eviction_secs = std::env::var("MODEL_CACHE_EVICTION_SECS")
+        .ok()
+        .and_then(|secs| secs.parse().ok())
+        .unwrap_or(60);
+    model_executor.cache().spawn_eviction_task(std::time::Duration::from_secs(eviction_secs));
+    // Fills in model-bound properties for queries that ask for predictions
+    let model_properties = ModelPropertyEvaluator::new(model_registry.clone(), model_executor.clone());
+    let exporter = Exporter::new(
+        ontology.clone(),
+        search_store.clone(),
+        std::env::var("EXPORT_DIR").unwrap_or_else(|_| "data/exports".to_string()),
+    );
+    {
+        let (jobs, exporter) = (jobs.clone(), exporter.clone());
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(std::time::Duration::from_secs(600));
+            loop {
+                interval.tick().await;
+                let (jobs, exporter) = (jobs.clone(), exporter.clone());
+                let _ = tokio::task::spawn_blocking(move || exporter.cleanup(&jobs)).await;
+            }
+        });
+    }
 
     // Create GraphQL schema
-    let schema = Schema::build(
+    let mut schema_builder = Schema::build(
         QueryRoot::default(),
         AdminMutations::default(),
         EmptySubscription,
     )
-    .data(Arc::new(ontology))
-    .data(search_store.clone() as Arc<dyn indexing::store::SearchStore>)
-    .data(graph_store.clone() as Arc<dyn indexing::store::GraphStore>)
-    .data(columnar_store.clone() as Arc<dyn indexing::store::ColumnarStore>)
+    .extension(QueryExplainExtension)
+    .extension(ApiVersionExtension)
+    .extension(RequestTracingExtension);
+    if let Some(log) = query_log {
+        schema_builder = schema_builder.data(log);
+    }
+    if std::env::var("GRAPHQL_DEBUG").is_ok() {
+        // Report the active masking profile in response extensions
+        schema_builder = schema_builder.extension(MaskingProfileExtension);
+    }
+    // Serve API 1 field shapes to clients that still ask for them
+    let api_settings = ApiSettings {
+        compat_mode: std::env::var("GRAPHQL_COMPAT_MODE").is_ok(),
+    };
+    let schema = schema_builder
+    .data(api_settings)
+    .data(masking_policy)
+    .data(property_access_policy)
+    .data(Arc::new(SideEffectHandlers::new(side_effects)))
+    .data(ontology)
+    .data(search_store.clone())
+    .data(graph_store.clone())
+    .data(columnar_store.clone())
     .data(time_query.clone())
+    .data(object_event_log)
+    .data(Arc::new(PendingConflicts::new()))
+    .data(edit_queue)
     .data(hydrator)
-    .data(DATA_STORE.clone())
+    .data(query_planner)
     .data(function_cache)
+    // Progress of background jobs such as consistency checks and exports
+    .data(jobs)
+    .data(exporter)
+    .data(property_drift)
+    .data(change_triggers)
+    .data(reference_index)
+    .data(model_registry)
+    .data(model_executor)
+    .data(model_properties)
     .finish();
 
     // GraphQL handler
@@ -207,9 +416,21 @@ async fn main() {
             .cloned()
             .unwrap_or(Value::Object(serde_json::Map::new()));
 
+        // Extract extensions, e.g. the `apiVersion` the client was written against
+        let extensions = request
+            .get("extensions")
+            .and_then(|v| v.as_object())
+            .cloned()
+            .unwrap_or_default();
+
         // Execute GraphQL query
-        let request = async_graphql::Request::new(query)
+        let mut request = async_graphql::Request::new(query)
             .variables(async_graphql::Variables::from_json(variables));
+        for (name, value) in extensions {
+            if let Ok(value) = async_graphql::Value::from_json(value) {
+                request.extensions.insert(name, value);
+            }
+        }
 
         let response = schema.execute(request).await;
         let response_json = serde_json::to_string(&response).unwrap_or_default();
@@ -0,0 +1,37 @@
+use ontology_engine::{format_property_value, DisplayLocale, ObjectType, PropertyValue};
+use serde_json::Value;
+
+/// Build the `display` map for an object: formatted strings for every property with a
+/// format or unit hint. Values whose format config cannot be applied fall back to the raw
+/// value and log a warning instead of failing the query.
+pub(crate) fn display_json(object_type: &ObjectType, properties: &Value, locale: &DisplayLocale) -> Value {
+    let mut display = serde_json::Map::new();
+    let Value::Object(map) = properties else {
+        return Value::Object(display);
+    };
+
+    for property in &object_type.properties {
+        if property.format.is_none() && property.unit.is_none() {
+            continue;
+        }
+        let Some(raw) = map.get(&property.id) else {
+            continue;
+        };
+        let value: PropertyValue = serde_json::from_value(raw.clone()).unwrap_or(PropertyValue::Null);
+
+        match format_property_value(property, &value, locale) {
+            Ok(Some(formatted)) => {
+                display.insert(property.id.clone(), Value::String(formatted));
+            }
+            Ok(None) => {}
+            Err(e) => {
+                tracing::warn!(
+                    "display format for {}.{} not applied: {}",
+                    object_type.id, property.id, e
+                );
+                display.insert(property.id.clone(), Value::String(value.to_string()));
+            }
+        }
+    }
+    Value::Object(display)
+}
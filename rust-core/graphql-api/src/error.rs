@@ -0,0 +1,165 @@
+//! Errors returned by the resolvers, with a machine-readable `code` extension so clients can
+//! tell a missing object type from a store outage without matching on the message
+
+use async_graphql::ErrorExtensions;
+use indexing::store::StoreError;
+use ontology_engine::validation::ValidationError;
+use ontology_engine::{ModelExecutionError, OntologyLoadErrors};
+use writeback::EditQueueError;
+
+/// A resolver error. Converts into an `async_graphql::Error` carrying `extensions.code` and,
+/// for validation failures about one field, `extensions.field`.
+///
+/// Store errors that expose backend internals (query text, response bodies) are logged and
+/// replaced with a generic message.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ApiError {
+    /// An object type, object, link type or other definition that does not exist
+    NotFound(String),
+    /// Data that breaks the ontology's rules
+    ValidationFailed { field: Option<String>, message: String },
+    /// The backing store could not be reached
+    StoreUnavailable(String),
+    /// The caller lacks the security context or role the operation needs
+    Unauthorized(String),
+    /// An argument that is malformed or inconsistent with the others
+    InvalidArgument(String),
+    /// Anything else; the message is safe to show but carries no detail
+    Internal(String),
+}
+
+impl ApiError {
+    pub fn not_found(message: impl Into<String>) -> Self {
+        ApiError::NotFound(message.into())
+    }
+
+    pub fn validation(field: Option<&str>, message: impl Into<String>) -> Self {
+        ApiError::ValidationFailed { field: field.map(str::to_string), message: message.into() }
+    }
+
+    pub fn unauthorized(message: impl Into<String>) -> Self {
+        ApiError::Unauthorized(message.into())
+    }
+
+    pub fn invalid_argument(message: impl Into<String>) -> Self {
+        ApiError::InvalidArgument(message.into())
+    }
+
+    pub fn internal(message: impl Into<String>) -> Self {
+        ApiError::Internal(message.into())
+    }
+
+    /// The `extensions.code` of the error
+    pub fn code(&self) -> &'static str {
+        match self {
+            ApiError::NotFound(_) => "NOT_FOUND",
+            ApiError::ValidationFailed { .. } => "VALIDATION_FAILED",
+            ApiError::StoreUnavailable(_) => "STORE_UNAVAILABLE",
+            ApiError::Unauthorized(_) => "UNAUTHORIZED",
+            ApiError::InvalidArgument(_) => "INVALID_ARGUMENT",
+            ApiError::Internal(_) => "INTERNAL",
+        }
+    }
+
+    /// The client-visible message
+    pub fn message(&self) -> &str {
+        match self {
+            ApiError::NotFound(message)
+            | ApiError::ValidationFailed { message, .. }
+            | ApiError::StoreUnavailable(message)
+            | ApiError::Unauthorized(message)
+            | ApiError::InvalidArgument(message)
+            | ApiError::Internal(message) => message,
+        }
+    }
+}
+
+impl ErrorExtensions for ApiError {
+    fn extend(&self) -> async_graphql::Error {
+        async_graphql::Error::new(self.message()).extend_with(|_, extensions| {
+            extensions.set("code", self.code());
+            if let ApiError::ValidationFailed { field: Some(field), .. } = self {
+                extensions.set("field", field.as_str());
+            }
+        })
+    }
+}
+
+// `ApiError` deliberately has no `Display`: async_graphql converts any `Display` type into an
+// error without extensions, which would silently drop the code.
+impl From<ApiError> for async_graphql::Error {
+    fn from(error: ApiError) -> Self {
+        error.extend()
+    }
+}
+
+impl From<StoreError> for ApiError {
+    fn from(error: StoreError) -> Self {
+        match error {
+            StoreError::NotFound(_) | StoreError::NoData(_) => ApiError::NotFound(error.to_string()),
+            StoreError::Validation(message) => ApiError::ValidationFailed { field: None, message },
+            StoreError::BulkIndex(_) => ApiError::validation(None, error.to_string()),
+            StoreError::Conflict(_) => ApiError::InvalidArgument(error.to_string()),
+            StoreError::Connection(_) => {
+                tracing::warn!(%error, "store unavailable");
+                ApiError::StoreUnavailable("The backing store is unavailable".to_string())
+            }
+            StoreError::Query(_)
+            | StoreError::Serialization(_)
+            | StoreError::Transaction(_)
+            | StoreError::Configuration(_)
+            | StoreError::WriteError(_)
+            | StoreError::ReadError(_)
+            | StoreError::Unknown(_) => {
+                tracing::warn!(%error, "store error");
+                ApiError::Internal("The backing store failed to complete the request".to_string())
+            }
+        }
+    }
+}
+
+impl From<EditQueueError> for ApiError {
+    fn from(error: EditQueueError) -> Self {
+        match error {
+            EditQueueError::NotFound(_) => ApiError::NotFound(error.to_string()),
+            EditQueueError::InvalidTransition { .. } => ApiError::InvalidArgument(error.to_string()),
+            EditQueueError::Storage(_) => {
+                tracing::warn!(%error, "edit queue unavailable");
+                ApiError::StoreUnavailable("The edit queue is unavailable".to_string())
+            }
+        }
+    }
+}
+
+impl From<OntologyLoadErrors> for ApiError {
+    fn from(errors: OntologyLoadErrors) -> Self {
+        ApiError::validation(None, errors.to_string())
+    }
+}
+
+impl From<ModelExecutionError> for ApiError {
+    fn from(error: ModelExecutionError) -> Self {
+        match error {
+            ModelExecutionError::InvalidInput(_)
+            | ModelExecutionError::UnsupportedPlatform(_)
+            | ModelExecutionError::NoExecutorFound { .. }
+            | ModelExecutionError::NotImplemented(_) => ApiError::InvalidArgument(error.to_string()),
+            ModelExecutionError::ExecutionFailed(_)
+            | ModelExecutionError::NetworkError(_)
+            | ModelExecutionError::Timeout => {
+                tracing::warn!(%error, "model execution failed");
+                ApiError::Internal(error.to_string())
+            }
+        }
+    }
+}
+
+impl From<ValidationError> for ApiError {
+    fn from(error: ValidationError) -> Self {
+        match &error {
+            ValidationError::MissingRole(_) | ValidationError::MissingBadge(_) => ApiError::Unauthorized(error.to_string()),
+            ValidationError::ExecutionFailed(_) => ApiError::Internal(error.to_string()),
+            _ => ApiError::validation(error.parameter(), error.to_string()),
+        }
+    }
+}
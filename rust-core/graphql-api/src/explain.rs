@@ -0,0 +1,61 @@
+use async_graphql::extensions::{
+    Extension, ExtensionContext, ExtensionFactory, NextPrepareRequest, NextRequest,
+};
+use async_graphql::{Context, Request, Response, ServerResult};
+use serde_json::Value;
+use std::sync::{Arc, Mutex};
+
+/// Response extension key under which explained backend queries are returned
+pub const EXPLAIN_EXTENSION: &str = "explain";
+
+/// Returns the backend-native queries of fields run with `explain: true` under
+/// `extensions.explain`, one entry per explained store call.
+#[derive(Clone, Default)]
+pub struct QueryExplainExtension;
+
+impl ExtensionFactory for QueryExplainExtension {
+    fn create(&self) -> Arc<dyn Extension> {
+        Arc::new(ExplainCollector::default())
+    }
+}
+
+/// Per-request list of explained queries, shared with resolvers through request data
+#[derive(Clone, Default)]
+pub(crate) struct ExplainCollector {
+    entries: Arc<Mutex<Vec<Value>>>,
+}
+
+#[async_trait::async_trait]
+impl Extension for ExplainCollector {
+    async fn prepare_request(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        request: Request,
+        next: NextPrepareRequest<'_>,
+    ) -> ServerResult<Request> {
+        next.run(ctx, request.data(self.clone())).await
+    }
+
+    async fn request(&self, ctx: &ExtensionContext<'_>, next: NextRequest<'_>) -> Response {
+        let response = next.run(ctx).await;
+        let entries = std::mem::take(&mut *self.entries.lock().unwrap());
+        if entries.is_empty() {
+            return response;
+        }
+        match async_graphql::Value::from_json(Value::Array(entries)) {
+            Ok(value) => response.extension(EXPLAIN_EXTENSION, value),
+            Err(_) => response,
+        }
+    }
+}
+
+/// Attach a backend query to the response. A no-op unless `QueryExplainExtension` is registered.
+pub(crate) fn record_explain(ctx: &Context<'_>, operation: &str, query: Value) {
+    if let Some(collector) = ctx.data_opt::<ExplainCollector>() {
+        collector.entries.lock().unwrap().push(serde_json::json!({
+            "path": ctx.path_node.as_ref().map(|path| path.to_string()),
+            "operation": operation,
+            "query": query,
+        }));
+    }
+}
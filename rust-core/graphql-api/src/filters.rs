@@ -0,0 +1,228 @@
+//! GraphQL filter inputs: conversion to store filters and evaluation against in-memory
+//! objects.
+
+use async_graphql::{FieldResult, InputObject};
+use indexing::store::{Filter, FilterOperator};
+use ontology_engine::{geo, ObjectType, Property};
+use serde_json::Value;
+use std::cmp::Ordering;
+
+use crate::error::ApiError;
+use crate::property_value::{self, PropertyValueScalar};
+
+/// Input for search filters
+#[derive(InputObject, Clone)]
+pub struct FilterInput {
+    pub(crate) property: String,
+    operator: String,
+    /// Value as a JSON string; ignored when `typedValue` is set
+    #[graphql(default)]
+    value: String,
+    typed_value: Option<PropertyValueScalar>,
+    /// Distance in meters for `withinDistance`
+    distance: Option<f64>,
+    /// Match `contains`, `startsWith` and `endsWith` regardless of case
+    #[graphql(default)]
+    case_insensitive: bool,
+}
+
+/// Convert filter inputs to store filters, coercing values to the types of `properties`
+pub(crate) fn convert_filters(inputs: Vec<FilterInput>, properties: &[Property]) -> FieldResult<Vec<Filter>> {
+    inputs
+        .into_iter()
+        .map(|input| convert_filter_input(input, properties))
+        .collect()
+}
+
+/// Convert FilterInput to Filter, coercing the value to the type of the filtered property
+/// when it is one of `properties`
+pub(crate) fn convert_filter_input(filter_input: FilterInput, properties: &[Property]) -> FieldResult<Filter> {
+    // Parse operator
+    let operator = match filter_input.operator.to_lowercase().as_str() {
+        "equals" | "eq" => FilterOperator::Equals,
+        "notequals" | "ne" => FilterOperator::NotEquals,
+        "greaterthan" | "gt" => FilterOperator::GreaterThan,
+        "lessthan" | "lt" => FilterOperator::LessThan,
+        "greaterthanorequal" | "gte" => FilterOperator::GreaterThanOrEqual,
+        "lessthanorequal" | "lte" => FilterOperator::LessThanOrEqual,
+        "contains" => FilterOperator::Contains,
+        "startswith" => FilterOperator::StartsWith,
+        "endswith" => FilterOperator::EndsWith,
+        "in" => FilterOperator::In,
+        "notin" => FilterOperator::NotIn,
+        "containsgeometry" => FilterOperator::ContainsGeometry,
+        "intersects" => FilterOperator::Intersects,
+        "within" => FilterOperator::Within,
+        "withindistance" => FilterOperator::WithinDistance,
+        _ => {
+            return Err(ApiError::invalid_argument(format!(
+                "Invalid filter operator: {}",
+                filter_input.operator
+            )).into())
+        }
+    };
+
+    let property_value = match filter_input.typed_value {
+        Some(typed) => typed.0,
+        None if filter_input.value.is_empty() => {
+            return Err(ApiError::invalid_argument(format!(
+                "Filter on '{}' needs a value or typedValue",
+                filter_input.property
+            )).into())
+        }
+        None => {
+            // Parse value from JSON string
+            let value = serde_json::from_str::<serde_json::Value>(&filter_input.value)
+                .map_err(|e| ApiError::invalid_argument(format!("Invalid filter value JSON: {}", e)))?;
+            serde_json::from_value(value)
+                .map_err(|e| ApiError::invalid_argument(format!("Failed to parse PropertyValue: {}", e)))?
+        }
+    };
+    let property = properties.iter().find(|p| p.id == filter_input.property);
+    let property_value = match property {
+        Some(property) => property_value::coerce(property_value, &property.property_type).map_err(|e| {
+            ApiError::invalid_argument(format!("Invalid filter value for '{}': {}", filter_input.property, e))
+        })?,
+        None => property_value,
+    };
+
+    let filter = Filter {
+        property: filter_input.property,
+        operator,
+        value: property_value,
+        distance: filter_input.distance,
+        case_insensitive: filter_input.case_insensitive,
+    };
+    if let Some(property) = property.filter(|_| operator.is_spatial()) {
+        check_spatial_filter(&filter, property).map_err(ApiError::invalid_argument)?;
+    }
+    Ok(filter)
+}
+
+/// Check a spatial filter against the GeoJSON property it filters: the geometry is validated
+/// like a value of the property (WGS84 coordinates, rejected under strict validation) and
+/// `withinDistance` needs a positive distance in meters from a Point
+pub(crate) fn check_spatial_filter(filter: &Filter, property: &Property) -> Result<(), String> {
+    property
+        .validate_value(&filter.value)
+        .map_err(|e| format!("Invalid GeoJSON geometry: {}", e))?;
+    if filter.operator == FilterOperator::WithinDistance {
+        match filter.distance {
+            Some(meters) if meters.is_finite() && meters > 0.0 => {}
+            Some(meters) => return Err(format!("Distance must be a positive number of meters, got {}", meters)),
+            None => return Err(format!("Filter on '{}' needs a distance in meters", filter.property)),
+        }
+        let geometry = filter.geometry().map_err(|e| e.to_string())?;
+        geo::point(&geometry).map_err(|e| format!("withinDistance measures from a point: {}", e))?;
+    }
+    Ok(())
+}
+
+/// Filters on interface properties as filters on an implementer's own properties. `Err`
+/// names the first filtered property the implementer does not have.
+pub(crate) fn implementer_filters(
+    inputs: &[FilterInput],
+    interface_id: &str,
+    object_type: &ObjectType,
+) -> Result<Vec<FilterInput>, String> {
+    inputs
+        .iter()
+        .map(|input| {
+            let local = object_type.local_property_id(interface_id, &input.property);
+            match object_type.get_property(local) {
+                Some(property) => Ok(FilterInput {
+                    property: property.id.clone(),
+                    ..input.clone()
+                }),
+                None => Err(input.property.clone()),
+            }
+        })
+        .collect()
+}
+
+/// Whether an in-memory object matches every filter
+pub fn matches_filters(obj: &Value, filters: &[Filter]) -> bool {
+    filters.iter().all(|filter| matches_filter(obj, filter))
+}
+
+/// Whether an in-memory object matches a filter; a missing property never matches
+pub fn matches_filter(obj: &Value, filter: &Filter) -> bool {
+    obj.get(&filter.property)
+        .is_some_and(|prop_value| value_matches_filter(prop_value, filter))
+}
+
+/// Check an in-memory property value against a filter. Numbers compare numerically, strings
+/// (including ISO 8601 dates) lexically; array values match `in`/`not in` if any element does.
+/// Spatial operators are evaluated like the in-memory search store does: `withinDistance`
+/// in great-circle meters from the filter's point, the others as planar relations.
+fn value_matches_filter(value: &Value, filter: &Filter) -> bool {
+    let expected = serde_json::to_value(&filter.value).unwrap_or(Value::Null);
+    let ordering = |a: &Value, b: &Value| match (a, b) {
+        (Value::Number(x), Value::Number(y)) => x.as_f64().partial_cmp(&y.as_f64()),
+        (Value::String(x), Value::String(y)) => Some(x.cmp(y)),
+        (Value::Bool(x), Value::Bool(y)) => Some(x.cmp(y)),
+        _ => None,
+    };
+    let equal = |a: &Value, b: &Value| match (a, b) {
+        (Value::Number(x), Value::Number(y)) => match (x.as_f64(), y.as_f64()) {
+            (Some(x), Some(y)) => (x - y).abs() < 0.0001,
+            _ => false,
+        },
+        _ => ordering(a, b) == Some(Ordering::Equal),
+    };
+    let text = |op: fn(&str, &str) -> bool| match (value.as_str(), expected.as_str()) {
+        (Some(v), Some(e)) if filter.case_insensitive => op(&v.to_lowercase(), &e.to_lowercase()),
+        (Some(v), Some(e)) => op(v, e),
+        _ => false,
+    };
+
+    match filter.operator {
+        FilterOperator::Equals => equal(value, &expected),
+        FilterOperator::NotEquals => !equal(value, &expected),
+        FilterOperator::GreaterThan => ordering(value, &expected) == Some(Ordering::Greater),
+        FilterOperator::LessThan => ordering(value, &expected) == Some(Ordering::Less),
+        FilterOperator::GreaterThanOrEqual => {
+            matches!(ordering(value, &expected), Some(Ordering::Greater | Ordering::Equal))
+        }
+        FilterOperator::LessThanOrEqual => {
+            matches!(ordering(value, &expected), Some(Ordering::Less | Ordering::Equal))
+        }
+        FilterOperator::Contains => text(|v, e| v.contains(e)),
+        FilterOperator::StartsWith => text(|v, e| v.starts_with(e)),
+        FilterOperator::EndsWith => text(|v, e| v.ends_with(e)),
+        FilterOperator::In | FilterOperator::NotIn => {
+            let candidates = match &expected {
+                Value::Array(items) => items.as_slice(),
+                other => std::slice::from_ref(other),
+            };
+            let values = match value {
+                Value::Array(items) => items.as_slice(),
+                other => std::slice::from_ref(other),
+            };
+            let found = values.iter().any(|v| candidates.iter().any(|c| equal(v, c)));
+            found == (filter.operator == FilterOperator::In)
+        }
+        FilterOperator::ContainsGeometry
+        | FilterOperator::Intersects
+        | FilterOperator::Within
+        | FilterOperator::WithinDistance => {
+            // Geometries are stored as GeoJSON strings or inline objects
+            let stored = match value {
+                Value::String(gj) => serde_json::from_str::<Value>(gj).ok(),
+                Value::Object(_) => Some(value.clone()),
+                _ => None,
+            };
+            let Some(stored) = stored else {
+                return false;
+            };
+            match filter.operator.spatial_relation() {
+                Some(relation) => filter
+                    .geometry()
+                    .is_ok_and(|query| geo::relates(&stored, &query, relation).unwrap_or(false)),
+                None => filter.distance_origin().is_ok_and(|(origin, max)| {
+                    geo::point_distance_meters(origin, &stored).is_some_and(|meters| meters <= max)
+                }),
+            }
+        }
+    }
+}
@@ -1,12 +1,28 @@
 pub mod schema;
 pub mod resolvers;
 pub mod admin;
+pub mod actions;
 pub mod model_resolvers;
-
-pub use schema::create_schema;
+pub mod masking;
+pub mod display;
+pub mod explain;
+pub mod api_version;
+pub mod oql;
+pub mod property_value;
+pub mod filters;
+pub mod error;
+pub mod telemetry;
+
+pub use schema::{create_schema, spawn_cache_invalidation, FunctionCache, ObjectEventLog};
 pub use resolvers::QueryRoot;
 pub use admin::AdminMutations;
 pub use model_resolvers::{ModelQueries, ModelMutations};
+pub use masking::MaskingProfileExtension;
+pub use explain::QueryExplainExtension;
+pub use api_version::{ApiSettings, ApiVersionExtension, API_VERSION};
+pub use property_value::PropertyValueScalar;
+pub use error::ApiError;
+pub use telemetry::{init_tracing, LogFormat, RequestTracingExtension};
 
 
 
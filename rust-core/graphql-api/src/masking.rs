@@ -0,0 +1,151 @@
+use async_graphql::extensions::{Extension, ExtensionContext, ExtensionFactory, NextExecute};
+use async_graphql::{Context, FieldResult, Response};
+use ontology_engine::{ObjectType, PropertyValue};
+use security::{MaskingPolicy, MaskingStrategy, PropertyAccessPolicy, SecurityContext};
+use serde_json::Value;
+use std::sync::Arc;
+
+use crate::error::ApiError;
+
+/// Response extension key under which the active masking profile is reported
+pub const MASKING_PROFILE_EXTENSION: &str = "masking_profile";
+
+/// Debug extension that reports the masking profile applied to a response under
+/// `extensions.masking_profile`. Only register it when debugging is enabled.
+#[derive(Clone, Default)]
+pub struct MaskingProfileExtension;
+
+impl ExtensionFactory for MaskingProfileExtension {
+    fn create(&self) -> Arc<dyn Extension> {
+        Arc::new(self.clone())
+    }
+}
+
+#[async_trait::async_trait]
+impl Extension for MaskingProfileExtension {
+    async fn execute(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        operation_name: Option<&str>,
+        next: NextExecute<'_>,
+    ) -> Response {
+        let response = next.run(ctx, operation_name).await;
+
+        let (Some(policy), Some(context)) = (
+            ctx.data_opt::<MaskingPolicy>(),
+            ctx.data_opt::<SecurityContext>(),
+        ) else {
+            return response;
+        };
+
+        let profile = policy.profile_for(context);
+        match serde_json::to_value(&profile)
+            .ok()
+            .and_then(|json| async_graphql::Value::from_json(json).ok())
+        {
+            Some(value) => response.extension(MASKING_PROFILE_EXTENSION, value),
+            None => response,
+        }
+    }
+}
+
+/// Apply the caller's masking profile to an object's JSON properties, then redact the ones
+/// the property access policy does not let them read. The masking profile needs a security
+/// context; the access policy treats a missing one as a caller with no roles or clearances.
+pub(crate) fn mask_object_json(ctx: &Context<'_>, object_type: &ObjectType, object: Value) -> Value {
+    let context = ctx.data_opt::<SecurityContext>();
+    let object = match (ctx.data_opt::<MaskingPolicy>(), context) {
+        (Some(policy), Some(context)) => mask_properties(policy, context, object_type, object),
+        _ => object,
+    };
+    match ctx.data_opt::<PropertyAccessPolicy>() {
+        Some(policy) => redact_unreadable(policy, context, object_type, object),
+        None => object,
+    }
+}
+
+/// Redact the JSON properties of an object that `policy` does not let `context` read
+pub(crate) fn redact_unreadable(
+    policy: &PropertyAccessPolicy,
+    context: Option<&SecurityContext>,
+    object_type: &ObjectType,
+    mut object: Value,
+) -> Value {
+    if let Value::Object(map) = &mut object {
+        let unreadable: Vec<String> = map
+            .keys()
+            .filter(|key| {
+                object_type
+                    .get_property(key)
+                    .is_some_and(|property| !policy.can_read(context, property))
+            })
+            .cloned()
+            .collect();
+        for key in unreadable {
+            match policy.redacted_value() {
+                Some(redacted) => map.insert(key, serde_json::to_value(redacted).unwrap_or(Value::Null)),
+                None => map.remove(&key),
+            };
+        }
+    }
+    object
+}
+
+/// Reject a query that filters, sorts or aggregates on properties the caller's property
+/// access policy does not let them read; otherwise its results would reveal the values
+pub(crate) fn check_readable<'a>(
+    ctx: &Context<'_>,
+    object_type: &ObjectType,
+    properties: impl IntoIterator<Item = &'a str>,
+) -> FieldResult<()> {
+    let Some(policy) = ctx.data_opt::<PropertyAccessPolicy>() else {
+        return Ok(());
+    };
+    let context = ctx.data_opt::<SecurityContext>();
+    for property in properties {
+        policy
+            .check_readable(context, object_type, property)
+            .map_err(ApiError::unauthorized)?;
+    }
+    Ok(())
+}
+
+/// Apply the masking profile `policy` selects for `context` to an object's JSON properties.
+/// Used directly where no request context is available, e.g. background exports.
+pub(crate) fn mask_properties(
+    policy: &MaskingPolicy,
+    context: &SecurityContext,
+    object_type: &ObjectType,
+    mut object: Value,
+) -> Value {
+    let profile = policy.profile_for(context);
+
+    if let Value::Object(map) = &mut object {
+        let keys: Vec<String> = map.keys().cloned().collect();
+        for key in keys {
+            let Some(strategy) = object_type
+                .get_property(&key)
+                .and_then(|property| profile.strategy_for(property))
+            else {
+                continue;
+            };
+            if strategy == MaskingStrategy::Reveal {
+                continue;
+            }
+
+            let value: PropertyValue = map
+                .get(&key)
+                .and_then(|v| serde_json::from_value(v.clone()).ok())
+                .unwrap_or(PropertyValue::Null);
+            match policy.mask_value(&strategy, &value) {
+                Some(masked) => {
+                    map.insert(key, serde_json::to_value(masked).unwrap_or(Value::Null));
+                }
+                None => {
+                    map.remove(&key);
+                }
+            }
+        }
+    }
+    object
+}
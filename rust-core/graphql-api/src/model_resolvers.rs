@@ -1,13 +1,16 @@
 use async_graphql::{Context, Object, FieldResult, InputObject, SimpleObject, Json};
 use ontology_engine::{
     ModelObjective, ModelType, ModelStatus, ModelMetrics as EngineModelMetrics,
-    ModelBinding, ModelBindingConfig, ModelPlatform, ModelRegistry,
+    ModelBinding, ModelBindingConfig, ModelExecutionOrchestrator, ModelPlatform, ModelRegistry,
 };
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use serde_json::Value;
 use chrono::{DateTime, Utc};
 
+use crate::error::ApiError;
+use crate::property_value;
+
 // ============================================================================
 // GraphQL Types for Model Objectives
 // ============================================================================
@@ -19,6 +22,7 @@ pub struct ModelPlatformOutput {
     pub framework: Option<String>,
     pub endpoint: Option<String>,
     pub region: Option<String>,
+    pub content_type: Option<String>,
 }
 
 /// GraphQL metrics output
@@ -55,6 +59,9 @@ pub struct ModelObjectiveOutput {
     pub name: String,
     pub model_type: String,
     pub version: String,
+    /// Version that stands for this model in bindings and predictions
+    pub active_version: String,
+    pub is_active: bool,
     pub description: Option<String>,
     pub platform: ModelPlatformOutput,
     pub metrics: ModelMetricsOutput,
@@ -70,6 +77,8 @@ pub struct ModelObjectiveOutput {
 #[derive(SimpleObject)]
 pub struct ModelBindingOutput {
     pub model_id: String,
+    /// Active version of the bound model, which the binding resolves to
+    pub model_version: Option<String>,
     pub object_type: String,
     pub property_id: String,
     pub bound_at: String,
@@ -93,6 +102,20 @@ pub struct ModelComparisonOutput {
     pub models: Vec<ModelObjectiveOutput>,
     pub comparison_table: Json<Value>,
     pub best_model_id: Option<String>,
+    pub best_version: Option<String>,
+}
+
+/// GraphQL type for the prediction cache counters
+#[derive(SimpleObject)]
+pub struct ModelCacheStatsOutput {
+    pub entries: usize,
+    pub max_entries: usize,
+    pub hits: u64,
+    pub misses: u64,
+    /// Predictions dropped to make room or because they expired
+    pub evictions: u64,
+    /// Share of lookups served from the cache, null before the first lookup
+    pub hit_rate: Option<f64>,
 }
 
 // ============================================================================
@@ -119,6 +142,8 @@ pub struct ModelPlatformInput {
     pub framework: Option<String>, // "sklearn", "pytorch", "tensorflow"
     pub endpoint: Option<String>,
     pub region: Option<String>,
+    /// Content type SageMaker endpoints expect the JSON inputs as
+    pub content_type: Option<String>,
 }
 
 /// Input for updating model metrics
@@ -148,6 +173,8 @@ pub struct BindModelInput {
     pub cache_predictions: Option<bool>,
     pub cache_ttl_seconds: Option<i64>,
     pub async_execution: Option<bool>,
+    /// JSON value returned while no prediction is available
+    pub fallback_value: Option<String>,
 }
 
 /// Input for model prediction
@@ -156,6 +183,9 @@ pub struct PredictInput {
     pub model_id: String,
     pub inputs: String, // JSON string of input features
     pub use_cache: Option<bool>,
+    /// Property whose binding supplies the cache settings; defaults to the model's only binding
+    pub object_type: Option<String>,
+    pub property_id: Option<String>,
 }
 
 // ============================================================================
@@ -199,7 +229,7 @@ impl ModelQueries {
         let end = limit.map(|l| start + l).unwrap_or(models.len());
         let paginated: Vec<_> = models.into_iter().skip(start).take(end - start).collect();
         
-        Ok(paginated.into_iter().map(convert_model_to_output).collect())
+        Ok(paginated.into_iter().map(|m| convert_model_to_output(&registry_read, m)).collect())
     }
     
     /// Get a specific model by ID
@@ -211,25 +241,51 @@ impl ModelQueries {
         let registry = ctx.data::<Arc<RwLock<ModelRegistry>>>()?;
         let registry_read = registry.read().await;
         
-        Ok(registry_read.get(&id).map(convert_model_to_output))
+        Ok(registry_read.get(&id).map(|m| convert_model_to_output(&registry_read, m)))
+    }
+
+    /// All versions of a model, oldest first
+    async fn model_versions(
+        &self,
+        ctx: &Context<'_>,
+        id: String,
+    ) -> FieldResult<Vec<ModelObjectiveOutput>> {
+        let registry = ctx.data::<Arc<RwLock<ModelRegistry>>>()?;
+        let registry_read = registry.read().await;
+
+        Ok(registry_read.list_versions(&id)
+            .into_iter()
+            .map(|m| convert_model_to_output(&registry_read, m))
+            .collect())
     }
     
-    /// Compare multiple models by ID
+    /// Compare multiple models by ID, or with `allVersions` every version of one model
     async fn compare_models(
         &self,
         ctx: &Context<'_>,
         model_ids: Vec<String>,
+        all_versions: Option<bool>,
     ) -> FieldResult<ModelComparisonOutput> {
         let registry = ctx.data::<Arc<RwLock<ModelRegistry>>>()?;
         let registry_read = registry.read().await;
         
-        let comparison = registry_read.compare_models(&model_ids)
-            .map_err(|e| async_graphql::Error::new(format!("Comparison error: {}", e)))?;
+        let (comparison, models) = if all_versions.unwrap_or(false) {
+            let [model_id] = model_ids.as_slice() else {
+                return Err(ApiError::invalid_argument("allVersions compares the versions of exactly one model").into());
+            };
+            let comparison = registry_read.compare_versions(model_id)
+                .map_err(|e| ApiError::not_found(format!("Comparison error: {}", e)))?;
+            (comparison, registry_read.list_versions(model_id))
+        } else {
+            let comparison = registry_read.compare_models(&model_ids)
+                .map_err(|e| ApiError::not_found(format!("Comparison error: {}", e)))?;
+            (comparison, model_ids.iter().filter_map(|id| registry_read.get(id)).collect())
+        };
         
         // comparison is Vec<ModelComparison>
-        let models: Vec<ModelObjectiveOutput> = model_ids.iter()
-            .filter_map(|id| registry_read.get(id))
-            .map(convert_model_to_output)
+        let models: Vec<ModelObjectiveOutput> = models
+            .into_iter()
+            .map(|m| convert_model_to_output(&registry_read, m))
             .collect();
         
         // Build comparison table from ModelComparison results
@@ -237,6 +293,7 @@ impl ModelQueries {
             "metrics": comparison.iter().map(|c| {
                 serde_json::json!({
                     "model_id": c.model_id,
+                    "version": c.version,
                     "name": c.model_name,
                     "primary_metric": c.primary_metric,
                     "accuracy": c.metrics.accuracy,
@@ -248,15 +305,16 @@ impl ModelQueries {
         });
         
         // Find best model by primary metric
-        let best_model_id = comparison.iter()
-            .filter_map(|c| c.primary_metric.map(|m| (c.model_id.clone(), m)))
+        let best = comparison.iter()
+            .filter_map(|c| c.primary_metric.map(|m| (c, m)))
             .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal))
-            .map(|(id, _)| id);
+            .map(|(c, _)| c);
         
         Ok(ModelComparisonOutput {
             models,
             comparison_table: Json(comparison_table),
-            best_model_id,
+            best_model_id: best.map(|c| c.model_id.clone()),
+            best_version: best.map(|c| c.version.clone()),
         })
     }
     
@@ -272,7 +330,7 @@ impl ModelQueries {
         let bindings: Vec<ModelBindingOutput> = registry_read.list_bindings()
             .into_iter()
             .filter(|b| object_type.as_ref().map_or(true, |ot| &b.object_type == ot))
-            .map(convert_binding_to_output)
+            .map(|b| convert_binding_to_output(&registry_read, b))
             .collect();
         
         Ok(bindings)
@@ -289,7 +347,7 @@ impl ModelQueries {
         let registry_read = registry.read().await;
         
         Ok(registry_read.get_binding(&object_type, &property_id)
-            .map(convert_binding_to_output))
+            .map(|b| convert_binding_to_output(&registry_read, b)))
     }
     
     /// Get models by type
@@ -304,7 +362,7 @@ impl ModelQueries {
         let target_type = parse_model_type(&model_type)?;
         let models: Vec<ModelObjectiveOutput> = registry_read.list_by_type(&target_type)
             .into_iter()
-            .map(convert_model_to_output)
+            .map(|m| convert_model_to_output(&registry_read, m))
             .collect();
         
         Ok(models)
@@ -322,11 +380,25 @@ impl ModelQueries {
         let target_status = parse_model_status(&status)?;
         let models: Vec<ModelObjectiveOutput> = registry_read.list_by_status(&target_status)
             .into_iter()
-            .map(convert_model_to_output)
+            .map(|m| convert_model_to_output(&registry_read, m))
             .collect();
         
         Ok(models)
     }
+
+    /// Hits, misses and evictions of the prediction cache, and the predictions it holds
+    async fn model_cache_stats(&self, ctx: &Context<'_>) -> FieldResult<ModelCacheStatsOutput> {
+        let stats = ctx.data::<Arc<ModelExecutionOrchestrator>>()?.cache_stats();
+        let lookups = stats.hits + stats.misses;
+        Ok(ModelCacheStatsOutput {
+            entries: stats.entries,
+            max_entries: stats.max_entries,
+            hits: stats.hits,
+            misses: stats.misses,
+            evictions: stats.evictions,
+            hit_rate: (lookups > 0).then(|| stats.hits as f64 / lookups as f64),
+        })
+    }
 }
 
 // ============================================================================
@@ -348,43 +420,79 @@ impl ModelMutations {
         let registry = ctx.data::<Arc<RwLock<ModelRegistry>>>()?;
         let mut registry_write = registry.write().await;
         
-        let model_type = parse_model_type(&input.model_type)?;
-        let platform = parse_platform_input(input.platform)?;
-        
-        let model = ModelObjective::new(
-            input.id,
-            input.name,
-            model_type,
-            input.version,
-            input.artifact_path,
-            platform,
-        );
+        let model = model_from_input(input)?;
         
         registry_write.register(model.clone())
-            .map_err(|e| async_graphql::Error::new(format!("Registration failed: {}", e)))?;
+            .map_err(|e| ApiError::invalid_argument(format!("Registration failed: {}", e)))?;
         
-        Ok(convert_model_to_output(&model))
+        Ok(convert_model_to_output(&registry_write, &model))
+    }
+
+    /// Register a new version of an existing model. It becomes active once promoted with
+    /// `promoteModelVersion`.
+    async fn register_model_version(
+        &self,
+        ctx: &Context<'_>,
+        input: RegisterModelInput,
+    ) -> FieldResult<ModelObjectiveOutput> {
+        let registry = ctx.data::<Arc<RwLock<ModelRegistry>>>()?;
+        let mut registry_write = registry.write().await;
+
+        let model = model_from_input(input)?;
+        registry_write.register_version(model.clone())
+            .map_err(|e| ApiError::invalid_argument(format!("Registration failed: {}", e)))?;
+
+        Ok(convert_model_to_output(&registry_write, &model))
+    }
+
+    /// Make a version the active version of its model. Bindings on the model resolve to it
+    /// from now on, and cached predictions of the model are dropped.
+    async fn promote_model_version(
+        &self,
+        ctx: &Context<'_>,
+        id: String,
+        version: String,
+    ) -> FieldResult<ModelObjectiveOutput> {
+        let registry = ctx.data::<Arc<RwLock<ModelRegistry>>>()?;
+        let mut registry_write = registry.write().await;
+
+        registry_write.promote_version(&id, &version)
+            .map_err(|e| ApiError::invalid_argument(format!("Promotion failed: {}", e)))?;
+        if let Some(executor) = ctx.data_opt::<Arc<ModelExecutionOrchestrator>>() {
+            executor.invalidate_model(&id);
+        }
+
+        let model = registry_write.get(&id)
+            .ok_or_else(|| ApiError::internal("Model not found after promotion"))?;
+        Ok(convert_model_to_output(&registry_write, model))
     }
     
-    /// Update model metrics
+    /// Update model metrics, of the active version unless `version` is given
     async fn update_model_metrics(
         &self,
         ctx: &Context<'_>,
         model_id: String,
         metrics: ModelMetricsInput,
+        version: Option<String>,
     ) -> FieldResult<ModelObjectiveOutput> {
         let registry = ctx.data::<Arc<RwLock<ModelRegistry>>>()?;
         let mut registry_write = registry.write().await;
         
         let engine_metrics = convert_metrics_input(metrics)?;
         
-        registry_write.update_metrics(&model_id, engine_metrics)
-            .map_err(|e| async_graphql::Error::new(format!("Update failed: {}", e)))?;
+        let version = match version {
+            Some(version) => version,
+            None => registry_write.active_version(&model_id)
+                .ok_or_else(|| ApiError::not_found(format!("Update failed: Model '{}' not found", model_id)))?
+                .to_string(),
+        };
+        registry_write.update_version_metrics(&model_id, &version, engine_metrics)
+            .map_err(|e| ApiError::not_found(format!("Update failed: {}", e)))?;
         
-        let model = registry_write.get(&model_id)
-            .ok_or_else(|| async_graphql::Error::new("Model not found after update"))?;
+        let model = registry_write.get_version(&model_id, &version)
+            .ok_or_else(|| ApiError::internal("Model not found after update"))?;
         
-        Ok(convert_model_to_output(model))
+        Ok(convert_model_to_output(&registry_write, model))
     }
     
     /// Bind a model to a property
@@ -401,6 +509,14 @@ impl ModelMutations {
             cache_enabled: input.cache_predictions.unwrap_or(true),
             cache_ttl: input.cache_ttl_seconds.unwrap_or(3600) as u64,
             async_execution: input.async_execution.unwrap_or(false),
+            fallback_value: input.fallback_value
+                .map(|json| {
+                    serde_json::from_str(&json)
+                        .map_err(|e| format!("Invalid fallback value JSON: {}", e))
+                        .and_then(property_value::decode)
+                })
+                .transpose()
+                .map_err(ApiError::invalid_argument)?,
         };
         
         let binding = registry_write.bind_model(
@@ -409,9 +525,9 @@ impl ModelMutations {
             input.property_id,
             None, // bound_by - would come from auth context
             config,
-        ).map_err(|e| async_graphql::Error::new(format!("Binding failed: {}", e)))?;
+        ).map_err(|e| ApiError::invalid_argument(format!("Binding failed: {}", e)))?;
         
-        Ok(convert_binding_to_output(&binding))
+        Ok(convert_binding_to_output(&registry_write, &binding))
     }
     
     /// Unbind a model from a property
@@ -425,7 +541,7 @@ impl ModelMutations {
         let mut registry_write = registry.write().await;
         
         registry_write.unbind_model(&object_type, &property_id)
-            .map_err(|e| async_graphql::Error::new(format!("Unbind failed: {}", e)))?;
+            .map_err(|e| ApiError::not_found(format!("Unbind failed: {}", e)))?;
         
         Ok(true)
     }
@@ -443,12 +559,12 @@ impl ModelMutations {
         let new_status = parse_model_status(&status)?;
         
         registry_write.update_status(&model_id, new_status)
-            .map_err(|e| async_graphql::Error::new(format!("Status update failed: {}", e)))?;
+            .map_err(|e| ApiError::not_found(format!("Status update failed: {}", e)))?;
         
         let model = registry_write.get(&model_id)
-            .ok_or_else(|| async_graphql::Error::new("Model not found after update"))?;
+            .ok_or_else(|| ApiError::internal("Model not found after update"))?;
         
-        Ok(convert_model_to_output(model))
+        Ok(convert_model_to_output(&registry_write, model))
     }
     
     /// Delete a model
@@ -461,37 +577,77 @@ impl ModelMutations {
         let mut registry_write = registry.write().await;
         
         registry_write.delete(&model_id)
-            .map_err(|e| async_graphql::Error::new(format!("Delete failed: {}", e)))?;
+            .map_err(|e| ApiError::invalid_argument(format!("Delete failed: {}", e)))?;
         
         Ok(true)
     }
+
+    /// Drop the cached predictions of a model, returning how many there were. Promoting a
+    /// version with `promoteModelVersion` does this already.
+    async fn clear_model_cache(&self, ctx: &Context<'_>, model_id: String) -> FieldResult<usize> {
+        let executor = ctx.data::<Arc<ModelExecutionOrchestrator>>()?;
+        Ok(executor.invalidate_model(&model_id))
+    }
     
-    /// Execute model prediction
+    /// Execute model prediction with the active version of the model. Predictions are
+    /// cached for the binding's `cacheTtlSeconds` unless `useCache` is false or the binding
+    /// disables caching.
     async fn predict(
         &self,
         ctx: &Context<'_>,
         input: PredictInput,
     ) -> FieldResult<Json<Value>> {
-        // Get model from registry
+        let executor = ctx.data::<Arc<ModelExecutionOrchestrator>>()?;
         let registry = ctx.data::<Arc<RwLock<ModelRegistry>>>()?;
-        let registry_read = registry.read().await;
-        
-        let model = registry_read.get(&input.model_id)
-            .ok_or_else(|| async_graphql::Error::new("Model not found"))?;
+        let (model, config) = {
+            let registry_read = registry.read().await;
+            let model = registry_read.get(&input.model_id)
+                .ok_or_else(|| ApiError::not_found(format!("Model '{}' not found", input.model_id)))?;
+            let binding = match (&input.object_type, &input.property_id) {
+                (Some(object_type), Some(property_id)) => Some(
+                    registry_read.get_binding(object_type, property_id)
+                        .filter(|b| b.model_id == input.model_id)
+                        .ok_or_else(|| ApiError::not_found(format!(
+                            "Model '{}' is not bound to '{}.{}'",
+                            input.model_id, object_type, property_id
+                        )))?,
+                ),
+                (None, None) => match registry_read.list_bindings_for_model(&input.model_id).as_slice() {
+                    [binding] => Some(*binding),
+                    _ => None,
+                },
+                _ => return Err(ApiError::invalid_argument("objectType and propertyId must be given together").into()),
+            };
+            (model.clone(), binding.map(|b| b.config.clone()).unwrap_or_default())
+        };
         
         // Parse inputs
         let inputs: serde_json::Value = serde_json::from_str(&input.inputs)
-            .map_err(|e| async_graphql::Error::new(format!("Invalid input JSON: {}", e)))?;
-        
-        // For now, return placeholder - actual execution would go through Python service
-        let result = serde_json::json!({
+            .map_err(|e| ApiError::invalid_argument(format!("Invalid input JSON: {}", e)))?;
+        let Value::Object(fields) = inputs else {
+            return Err(ApiError::invalid_argument("Inputs must be a JSON object of feature values").into());
+        };
+        let inputs = fields.into_iter()
+            .map(|(name, value)| {
+                property_value::decode(value)
+                    .map(|value| (name.clone(), value))
+                    .map_err(|e| ApiError::invalid_argument(format!("Invalid input '{}': {}", name, e)))
+            })
+            .collect::<Result<_, _>>()?;
+        
+        let use_cache = input.use_cache.unwrap_or(true) && config.cache_enabled;
+        let result = executor
+            .execute(&model, inputs, use_cache, config.cache_ttl)
+            .await
+            .map_err(ApiError::from)?;
+        
+        Ok(Json(serde_json::json!({
             "model_id": model.id,
-            "status": "prediction_pending",
-            "message": "Model execution requires Python service. See model_service.py",
-            "inputs": inputs,
-        });
-        
-        Ok(Json(result))
+            "version": model.version,
+            "prediction": result.prediction,
+            "confidence": result.confidence,
+            "probabilities": result.probabilities,
+        })))
     }
 }
 
@@ -499,6 +655,20 @@ impl ModelMutations {
 // Helper Functions
 // ============================================================================
 
+fn model_from_input(input: RegisterModelInput) -> FieldResult<ModelObjective> {
+    let model_type = parse_model_type(&input.model_type)?;
+    let platform = parse_platform_input(input.platform)?;
+    
+    Ok(ModelObjective::new(
+        input.id,
+        input.name,
+        model_type,
+        input.version,
+        input.artifact_path,
+        platform,
+    ))
+}
+
 fn parse_model_type(s: &str) -> FieldResult<ModelType> {
     match s.to_lowercase().as_str() {
         "classification" => Ok(ModelType::Classification),
@@ -506,10 +676,10 @@ fn parse_model_type(s: &str) -> FieldResult<ModelType> {
         "clustering" => Ok(ModelType::Clustering),
         "time_series" | "timeseries" => Ok(ModelType::TimeSeries),
         "custom" => Ok(ModelType::Custom("custom".to_string())),
-        _ => Err(async_graphql::Error::new(format!(
+        _ => Err(ApiError::invalid_argument(format!(
             "Invalid model type: {}. Valid: classification, regression, clustering, time_series, custom",
             s
-        ))),
+        )).into()),
     }
 }
 
@@ -520,10 +690,10 @@ fn parse_model_status(s: &str) -> FieldResult<ModelStatus> {
         "bound" => Ok(ModelStatus::Bound),
         "deprecated" => Ok(ModelStatus::Deprecated),
         "archived" => Ok(ModelStatus::Archived),
-        _ => Err(async_graphql::Error::new(format!(
+        _ => Err(ApiError::invalid_argument(format!(
             "Invalid model status: {}. Valid: training, registered, bound, deprecated, archived",
             s
-        ))),
+        )).into()),
     }
 }
 
@@ -535,6 +705,7 @@ fn parse_platform_input(input: ModelPlatformInput) -> FieldResult<ModelPlatform>
         "sagemaker" => Ok(ModelPlatform::SageMaker {
             endpoint_name: input.endpoint.unwrap_or_default(),
             region: input.region.unwrap_or_else(|| "us-east-1".to_string()),
+            content_type: input.content_type,
         }),
         "datarobot" => Ok(ModelPlatform::DataRobot {
             deployment_id: input.endpoint.unwrap_or_default(),
@@ -543,38 +714,42 @@ fn parse_platform_input(input: ModelPlatformInput) -> FieldResult<ModelPlatform>
             platform_name: "custom".to_string(),
             endpoint_url: input.endpoint.unwrap_or_default(),
         }),
-        _ => Err(async_graphql::Error::new(format!(
+        _ => Err(ApiError::invalid_argument(format!(
             "Invalid platform type: {}. Valid: local, sagemaker, datarobot, custom",
             input.platform_type
-        ))),
+        )).into()),
     }
 }
 
-fn convert_model_to_output(model: &ModelObjective) -> ModelObjectiveOutput {
+fn convert_model_to_output(registry: &ModelRegistry, model: &ModelObjective) -> ModelObjectiveOutput {
     let platform = match &model.platform {
         ModelPlatform::Local { framework } => ModelPlatformOutput {
             platform_type: "local".to_string(),
             framework: Some(framework.clone()),
             endpoint: None,
             region: None,
+            content_type: None,
         },
-        ModelPlatform::SageMaker { endpoint_name, region } => ModelPlatformOutput {
+        ModelPlatform::SageMaker { endpoint_name, region, content_type } => ModelPlatformOutput {
             platform_type: "sagemaker".to_string(),
             framework: None,
             endpoint: Some(endpoint_name.clone()),
             region: Some(region.clone()),
+            content_type: content_type.clone(),
         },
         ModelPlatform::DataRobot { deployment_id } => ModelPlatformOutput {
             platform_type: "datarobot".to_string(),
             framework: None,
             endpoint: Some(deployment_id.clone()),
             region: None,
+            content_type: None,
         },
         ModelPlatform::Custom { platform_name: _platform_name, endpoint_url } => ModelPlatformOutput {
             platform_type: "custom".to_string(),
             framework: None,
             endpoint: Some(endpoint_url.clone()),
             region: None,
+            content_type: None,
         },
     };
     
@@ -614,11 +789,14 @@ fn convert_model_to_output(model: &ModelObjective) -> ModelObjectiveOutput {
         ModelType::Custom(_) => "custom",
     };
     
+    let active_version = registry.active_version(&model.id).unwrap_or(&model.version).to_string();
     ModelObjectiveOutput {
         id: model.id.clone(),
         name: model.name.clone(),
         model_type: model_type.to_string(),
         version: model.version.clone(),
+        is_active: active_version == model.version,
+        active_version,
         description: model.description.clone(),
         platform,
         metrics,
@@ -631,17 +809,18 @@ fn convert_model_to_output(model: &ModelObjective) -> ModelObjectiveOutput {
     }
 }
 
-fn convert_binding_to_output(binding: &ModelBinding) -> ModelBindingOutput {
+fn convert_binding_to_output(registry: &ModelRegistry, binding: &ModelBinding) -> ModelBindingOutput {
     let config = ModelBindingConfigOutput {
         input_properties: binding.config.input_properties.clone(),
         cache_predictions: binding.config.cache_enabled,
         cache_ttl_seconds: binding.config.cache_ttl as i64,
         async_execution: binding.config.async_execution,
-        fallback_value: None,
+        fallback_value: binding.config.fallback_value.as_ref().map(|value| Json(property_value::encode(value))),
     };
     
     ModelBindingOutput {
         model_id: binding.model_id.clone(),
+        model_version: registry.active_version(&binding.model_id).map(str::to_string),
         object_type: binding.object_type.clone(),
         property_id: binding.property_id.clone(),
         bound_at: binding.bound_at.to_rfc3339(),
@@ -653,7 +832,7 @@ fn convert_binding_to_output(binding: &ModelBinding) -> ModelBindingOutput {
 fn convert_metrics_input(input: ModelMetricsInput) -> FieldResult<EngineModelMetrics> {
     let custom_metrics = if let Some(json_str) = input.custom_metrics {
         serde_json::from_str(&json_str)
-            .map_err(|e| async_graphql::Error::new(format!("Invalid custom metrics JSON: {}", e)))?
+            .map_err(|e| ApiError::invalid_argument(format!("Invalid custom metrics JSON: {}", e)))?
     } else {
         std::collections::HashMap::new()
     };
@@ -0,0 +1,573 @@
+//! OQL: one-line queries for ad-hoc object selection.
+//!
+//! ```text
+//! Plant where state = "NJ" and year >= 2015 order by population desc limit 50
+//! ```
+//!
+//! A query names an object type, then optional `where`, `order by`, `limit` and `offset`
+//! clauses in that order. Conditions compare a property with a literal using `=`, `!=` (or
+//! `<>`), `<`, `<=`, `>`, `>=`, `contains`, `startswith`, `endswith`, `in (..)` and
+//! `not in (..)`, and combine with `and`, `or` and parentheses; `and` binds tighter than `or`.
+//! Search stores only take conjunctions, so `or` may only join `=` and `in` comparisons on a
+//! single property, which become one `in` filter: `state = "NJ" or state in ("NY", "PA")`
+//! is accepted, while `state = "NJ" or year > 2015` is rejected. Keywords are
+//! case-insensitive; strings are single- or double-quoted.
+//!
+//! Object types, properties and literal types are checked against the ontology while parsing,
+//! and errors carry the 1-based column they were found at.
+
+use indexing::store::{Filter, FilterOperator, SortOption};
+use ontology_engine::{ObjectType, Ontology, Property, PropertyType, PropertyValue};
+
+/// A parsed OQL query, ready to run as a search on `object_type`
+#[derive(Debug, Clone)]
+pub struct OqlQuery {
+    pub object_type: String,
+    pub filters: Vec<Filter>,
+    /// Sort keys as written; empty if the query has no `order by`
+    pub sort: Vec<SortOption>,
+    pub limit: Option<usize>,
+    pub offset: Option<usize>,
+}
+
+/// Why a query was rejected, and where
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+#[error("{message} at column {column}")]
+pub struct OqlError {
+    pub message: String,
+    /// 1-based character column; one past the end for errors at the end of the query
+    pub column: usize,
+}
+
+/// Parse an OQL query, validating it against the ontology
+pub fn parse(input: &str, ontology: &Ontology) -> Result<OqlQuery, OqlError> {
+    let tokens = tokenize(input)?;
+    let end = input.chars().count() + 1;
+    let mut parser = Parser {
+        tokens,
+        position: 0,
+        end,
+        object_type: None,
+    };
+    parser.query(ontology)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum TokenKind {
+    Word(String),
+    Str(String),
+    Number(String),
+    Symbol(&'static str),
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    column: usize,
+}
+
+const SYMBOLS: &[&str] = &["!=", "<>", "<=", ">=", "=", "<", ">", "(", ")", ","];
+
+fn tokenize(input: &str) -> Result<Vec<Token>, OqlError> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let column = i + 1;
+        if c.is_whitespace() {
+            i += 1;
+        } else if c.is_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            tokens.push(Token {
+                kind: TokenKind::Word(chars[start..i].iter().collect()),
+                column,
+            });
+        } else if c.is_ascii_digit() || (c == '-' && chars.get(i + 1).is_some_and(|n| n.is_ascii_digit())) {
+            let start = i;
+            i += 1;
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                i += 1;
+            }
+            tokens.push(Token {
+                kind: TokenKind::Number(chars[start..i].iter().collect()),
+                column,
+            });
+        } else if c == '"' || c == '\'' {
+            let mut value = String::new();
+            i += 1;
+            loop {
+                match chars.get(i) {
+                    None => return Err(error("Unterminated string", column)),
+                    Some('\\') if i + 1 < chars.len() => {
+                        value.push(chars[i + 1]);
+                        i += 2;
+                    }
+                    Some(&q) if q == c => {
+                        i += 1;
+                        break;
+                    }
+                    Some(&other) => {
+                        value.push(other);
+                        i += 1;
+                    }
+                }
+            }
+            tokens.push(Token {
+                kind: TokenKind::Str(value),
+                column,
+            });
+        } else {
+            let rest: String = chars[i..chars.len().min(i + 2)].iter().collect();
+            let symbol = SYMBOLS
+                .iter()
+                .find(|s| rest.starts_with(**s))
+                .ok_or_else(|| error(format!("Unexpected character '{}'", c), column))?;
+            i += symbol.len();
+            tokens.push(Token {
+                kind: TokenKind::Symbol(symbol),
+                column,
+            });
+        }
+    }
+    Ok(tokens)
+}
+
+fn error(message: impl Into<String>, column: usize) -> OqlError {
+    OqlError {
+        message: message.into(),
+        column,
+    }
+}
+
+/// A literal as written, before it is typed against a property
+#[derive(Debug, Clone)]
+enum Literal {
+    Str(String),
+    Number(String),
+    Boolean(bool),
+}
+
+struct Parser<'o> {
+    tokens: Vec<Token>,
+    position: usize,
+    end: usize,
+    object_type: Option<&'o ObjectType>,
+}
+
+impl<'o> Parser<'o> {
+    fn query(&mut self, ontology: &'o Ontology) -> Result<OqlQuery, OqlError> {
+        let (name, column) = self.word("an object type")?;
+        let object_type = ontology
+            .get_object_type(&name)
+            .ok_or_else(|| error(format!("Unknown object type '{}'", name), column))?;
+        self.object_type = Some(object_type);
+
+        let filters = if self.eat_keyword("where") {
+            self.or_expression()?
+        } else {
+            Vec::new()
+        };
+
+        let mut sort = Vec::new();
+        if self.eat_keyword("order") {
+            self.expect_keyword("by")?;
+            loop {
+                let (property, column) = self.word("a property")?;
+                self.property(&property, column)?;
+                object_type
+                    .check_sortable(&property)
+                    .map_err(|message| error(message, column))?;
+                let ascending = if self.eat_keyword("desc") {
+                    false
+                } else {
+                    self.eat_keyword("asc");
+                    true
+                };
+                sort.push(SortOption::for_property(object_type, property, ascending));
+                if !self.eat_symbol(",") {
+                    break;
+                }
+            }
+        }
+
+        let limit = if self.eat_keyword("limit") { Some(self.count()?) } else { None };
+        let offset = if self.eat_keyword("offset") { Some(self.count()?) } else { None };
+
+        if let Some(token) = self.peek() {
+            return Err(error(format!("Unexpected {}", describe(&token.kind)), token.column));
+        }
+        Ok(OqlQuery {
+            object_type: object_type.id.clone(),
+            filters,
+            sort,
+            limit,
+            offset,
+        })
+    }
+
+    /// Disjunction of conjunctions, lowered to filters
+    fn or_expression(&mut self) -> Result<Vec<Filter>, OqlError> {
+        let mut branches = vec![self.and_expression()?];
+        let mut or_columns = Vec::new();
+        while let Some(column) = self.peek_keyword("or") {
+            self.position += 1;
+            or_columns.push(column);
+            branches.push(self.and_expression()?);
+        }
+        if or_columns.is_empty() {
+            return Ok(branches.remove(0));
+        }
+        lower_or(branches, &or_columns).map(|filter| vec![filter])
+    }
+
+    fn and_expression(&mut self) -> Result<Vec<Filter>, OqlError> {
+        let mut filters = self.primary()?;
+        while self.eat_keyword("and") {
+            filters.extend(self.primary()?);
+        }
+        Ok(filters)
+    }
+
+    fn primary(&mut self) -> Result<Vec<Filter>, OqlError> {
+        if self.eat_symbol("(") {
+            let filters = self.or_expression()?;
+            self.expect_symbol(")")?;
+            return Ok(filters);
+        }
+        self.comparison().map(|filter| vec![filter])
+    }
+
+    fn comparison(&mut self) -> Result<Filter, OqlError> {
+        let (name, column) = self.word("a property")?;
+        let property = self.property(&name, column)?;
+        self.object_type()
+            .check_filterable(&name)
+            .map_err(|message| error(message, column))?;
+
+        let operator_column = self.peek().map_or(self.end, |t| t.column);
+        let operator = match self.next().map(|t| t.kind) {
+            Some(TokenKind::Symbol("=")) => FilterOperator::Equals,
+            Some(TokenKind::Symbol("!=" | "<>")) => FilterOperator::NotEquals,
+            Some(TokenKind::Symbol("<")) => FilterOperator::LessThan,
+            Some(TokenKind::Symbol("<=")) => FilterOperator::LessThanOrEqual,
+            Some(TokenKind::Symbol(">")) => FilterOperator::GreaterThan,
+            Some(TokenKind::Symbol(">=")) => FilterOperator::GreaterThanOrEqual,
+            Some(TokenKind::Word(word)) => match word.to_lowercase().as_str() {
+                "contains" => FilterOperator::Contains,
+                "startswith" => FilterOperator::StartsWith,
+                "endswith" => FilterOperator::EndsWith,
+                "in" => FilterOperator::In,
+                "not" => {
+                    self.expect_keyword("in")?;
+                    FilterOperator::NotIn
+                }
+                _ => return Err(error(format!("Expected an operator after '{}'", name), operator_column)),
+            },
+            _ => return Err(error(format!("Expected an operator after '{}'", name), operator_column)),
+        };
+        check_operator(property, operator, operator_column)?;
+
+        let value = if matches!(operator, FilterOperator::In | FilterOperator::NotIn) {
+            self.expect_symbol("(")?;
+            let mut values = Vec::new();
+            loop {
+                let (literal, column) = self.literal()?;
+                values.push(typed_value(property, literal, column)?);
+                if !self.eat_symbol(",") {
+                    break;
+                }
+            }
+            self.expect_symbol(")")?;
+            PropertyValue::Array(values)
+        } else {
+            let (literal, column) = self.literal()?;
+            typed_value(property, literal, column)?
+        };
+
+        Ok(Filter {
+            property: name,
+            operator,
+            value,
+            distance: None,
+            case_insensitive: false,
+        })
+    }
+
+    fn literal(&mut self) -> Result<(Literal, usize), OqlError> {
+        let column = self.peek().map_or(self.end, |t| t.column);
+        let literal = match self.next().map(|t| t.kind) {
+            Some(TokenKind::Str(s)) => Literal::Str(s),
+            Some(TokenKind::Number(n)) => Literal::Number(n),
+            Some(TokenKind::Word(w)) if w.eq_ignore_ascii_case("true") => Literal::Boolean(true),
+            Some(TokenKind::Word(w)) if w.eq_ignore_ascii_case("false") => Literal::Boolean(false),
+            Some(kind) => return Err(error(format!("Expected a value, found {}", describe(&kind)), column)),
+            None => return Err(error("Expected a value", column)),
+        };
+        Ok((literal, column))
+    }
+
+    fn count(&mut self) -> Result<usize, OqlError> {
+        let column = self.peek().map_or(self.end, |t| t.column);
+        match self.next().map(|t| t.kind) {
+            Some(TokenKind::Number(n)) => n
+                .parse()
+                .map_err(|_| error(format!("Expected a non-negative integer, found {}", n), column)),
+            _ => Err(error("Expected a non-negative integer", column)),
+        }
+    }
+
+    fn property(&self, name: &str, column: usize) -> Result<&'o Property, OqlError> {
+        let object_type = self.object_type();
+        object_type.get_property(name).ok_or_else(|| {
+            error(
+                format!("Unknown property '{}' on object type '{}'", name, object_type.id),
+                column,
+            )
+        })
+    }
+
+    fn object_type(&self) -> &'o ObjectType {
+        self.object_type.expect("object type is parsed first")
+    }
+
+    fn word(&mut self, expected: &str) -> Result<(String, usize), OqlError> {
+        let column = self.peek().map_or(self.end, |t| t.column);
+        match self.next().map(|t| t.kind) {
+            Some(TokenKind::Word(word)) => Ok((word, column)),
+            Some(kind) => Err(error(format!("Expected {}, found {}", expected, describe(&kind)), column)),
+            None => Err(error(format!("Expected {}", expected), column)),
+        }
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    /// Column of the next token if it is this keyword
+    fn peek_keyword(&self, keyword: &str) -> Option<usize> {
+        match self.peek() {
+            Some(Token {
+                kind: TokenKind::Word(word),
+                column,
+            }) if word.eq_ignore_ascii_case(keyword) => Some(*column),
+            _ => None,
+        }
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        let found = self.peek_keyword(keyword).is_some();
+        if found {
+            self.position += 1;
+        }
+        found
+    }
+
+    fn expect_keyword(&mut self, keyword: &str) -> Result<(), OqlError> {
+        if self.eat_keyword(keyword) {
+            return Ok(());
+        }
+        Err(self.expected(&format!("'{}'", keyword)))
+    }
+
+    fn eat_symbol(&mut self, symbol: &str) -> bool {
+        let found = matches!(self.peek(), Some(Token { kind: TokenKind::Symbol(s), .. }) if *s == symbol);
+        if found {
+            self.position += 1;
+        }
+        found
+    }
+
+    fn expect_symbol(&mut self, symbol: &str) -> Result<(), OqlError> {
+        if self.eat_symbol(symbol) {
+            return Ok(());
+        }
+        Err(self.expected(&format!("'{}'", symbol)))
+    }
+
+    fn expected(&self, expected: &str) -> OqlError {
+        match self.peek() {
+            Some(token) => error(
+                format!("Expected {}, found {}", expected, describe(&token.kind)),
+                token.column,
+            ),
+            None => error(format!("Expected {}", expected), self.end),
+        }
+    }
+}
+
+fn describe(kind: &TokenKind) -> String {
+    match kind {
+        TokenKind::Word(word) => format!("'{}'", word),
+        TokenKind::Str(s) => format!("\"{}\"", s),
+        TokenKind::Number(n) => n.clone(),
+        TokenKind::Symbol(s) => format!("'{}'", s),
+    }
+}
+
+/// Merge `or` branches of `=`/`in` comparisons on one property into a single `in` filter.
+/// Errors point at the `or` before the offending branch (the first `or` for the first branch).
+fn lower_or(branches: Vec<Vec<Filter>>, or_columns: &[usize]) -> Result<Filter, OqlError> {
+    let mut property: Option<String> = None;
+    let mut values = Vec::new();
+    for (i, mut filters) in branches.into_iter().enumerate() {
+        let column = or_columns[i.saturating_sub(1)];
+        let unsupported = || {
+            error(
+                "'or' can only join '=' and 'in' comparisons on the same property",
+                column,
+            )
+        };
+        if filters.len() != 1 {
+            return Err(unsupported());
+        }
+        let filter = filters.remove(0);
+        if property.as_ref().is_some_and(|p| *p != filter.property) {
+            return Err(unsupported());
+        }
+        match (filter.operator, filter.value) {
+            (FilterOperator::Equals, value) => values.push(value),
+            (FilterOperator::In, PropertyValue::Array(items)) => values.extend(items),
+            _ => return Err(unsupported()),
+        }
+        property = Some(filter.property);
+    }
+    let mut distinct: Vec<PropertyValue> = Vec::with_capacity(values.len());
+    for value in values {
+        if !distinct.contains(&value) {
+            distinct.push(value);
+        }
+    }
+    Ok(Filter {
+        property: property.unwrap_or_default(),
+        operator: FilterOperator::In,
+        value: PropertyValue::Array(distinct),
+        distance: None,
+        case_insensitive: false,
+    })
+}
+
+/// Scalar type compared by filters on a property; array properties compare their elements
+fn comparable_type(property_type: &PropertyType) -> &PropertyType {
+    match property_type {
+        PropertyType::Array { element_type } => comparable_type(element_type),
+        other => other,
+    }
+}
+
+fn check_operator(property: &Property, operator: FilterOperator, column: usize) -> Result<(), OqlError> {
+    let property_type = comparable_type(&property.property_type);
+    // Geometry and structured values are not comparable with literals
+    let scalar = !matches!(
+        property_type,
+        PropertyType::GeoJSON
+            | PropertyType::GeoJSONAlt
+            | PropertyType::Array { .. }
+            | PropertyType::Map { .. }
+            | PropertyType::Object(_)
+            | PropertyType::Union { .. }
+    );
+    let allowed = scalar
+        && match operator {
+            FilterOperator::Equals | FilterOperator::NotEquals | FilterOperator::In | FilterOperator::NotIn => true,
+            FilterOperator::LessThan
+            | FilterOperator::LessThanOrEqual
+            | FilterOperator::GreaterThan
+            | FilterOperator::GreaterThanOrEqual => {
+                !matches!(property_type, PropertyType::Boolean | PropertyType::Bool)
+            }
+            FilterOperator::Contains | FilterOperator::StartsWith | FilterOperator::EndsWith => {
+                matches!(property_type, PropertyType::String)
+            }
+            _ => false,
+        };
+    if allowed {
+        Ok(())
+    } else {
+        Err(error(
+            format!(
+                "Operator '{}' is not supported on property '{}' of type {}",
+                operator_symbol(operator),
+                property.id,
+                type_name(property_type)
+            ),
+            column,
+        ))
+    }
+}
+
+fn operator_symbol(operator: FilterOperator) -> &'static str {
+    match operator {
+        FilterOperator::Equals => "=",
+        FilterOperator::NotEquals => "!=",
+        FilterOperator::LessThan => "<",
+        FilterOperator::LessThanOrEqual => "<=",
+        FilterOperator::GreaterThan => ">",
+        FilterOperator::GreaterThanOrEqual => ">=",
+        FilterOperator::Contains => "contains",
+        FilterOperator::StartsWith => "startswith",
+        FilterOperator::EndsWith => "endswith",
+        FilterOperator::In => "in",
+        FilterOperator::NotIn => "not in",
+        _ => "spatial",
+    }
+}
+
+fn type_name(property_type: &PropertyType) -> String {
+    serde_json::to_value(property_type)
+        .ok()
+        .and_then(|v| v.as_str().map(str::to_string))
+        .unwrap_or_else(|| "complex".to_string())
+}
+
+/// Type a literal for comparison with a property
+fn typed_value(property: &Property, literal: Literal, column: usize) -> Result<PropertyValue, OqlError> {
+    let property_type = comparable_type(&property.property_type);
+    let mismatch = || {
+        error(
+            format!(
+                "Property '{}' of type {} cannot be compared with {}",
+                property.id,
+                type_name(property_type),
+                match &literal {
+                    Literal::Str(s) => format!("\"{}\"", s),
+                    Literal::Number(n) => n.clone(),
+                    Literal::Boolean(b) => b.to_string(),
+                }
+            ),
+            column,
+        )
+    };
+    let value = match (property_type, &literal) {
+        (PropertyType::String, Literal::Str(s)) => PropertyValue::String(s.clone()),
+        (PropertyType::ObjectReference | PropertyType::ObjectReferenceAlt, Literal::Str(s)) => {
+            PropertyValue::ObjectReference(s.clone())
+        }
+        (PropertyType::Integer | PropertyType::Int, Literal::Number(n)) => {
+            PropertyValue::Integer(n.parse().map_err(|_| mismatch())?)
+        }
+        (PropertyType::Double | PropertyType::Float, Literal::Number(n)) => {
+            PropertyValue::Double(n.parse().map_err(|_| mismatch())?)
+        }
+        (PropertyType::Boolean | PropertyType::Bool, Literal::Boolean(b)) => PropertyValue::Boolean(*b),
+        (PropertyType::Date, Literal::Str(s)) => {
+            chrono::NaiveDate::parse_from_str(s, "%Y-%m-%d").map_err(|_| mismatch())?;
+            PropertyValue::Date(s.clone())
+        }
+        (PropertyType::DateTime | PropertyType::Timestamp, Literal::Str(s)) => {
+            chrono::DateTime::parse_from_rfc3339(s).map_err(|_| mismatch())?;
+            PropertyValue::DateTime(s.clone())
+        }
+        _ => return Err(mismatch()),
+    };
+    Ok(value)
+}
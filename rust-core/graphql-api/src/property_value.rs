@@ -0,0 +1,236 @@
+//! Typed `PropertyValue` scalar.
+//!
+//! Plain JSON cannot tell a date from a string or an object reference from an ID, so values
+//! that would be ambiguous are tagged with a single `$` key:
+//!
+//! | Variant           | Encoding                                   |
+//! |-------------------|--------------------------------------------|
+//! | `String`          | `"text"`                                   |
+//! | `Integer`         | `42`                                       |
+//! | `Double`          | `42.0`; `{"$double": "NaN"}` if not finite |
+//! | `Boolean`, `Null` | `true`, `null`                             |
+//! | `Date`            | `{"$date": "2020-01-01"}`                  |
+//! | `DateTime`        | `{"$datetime": "2020-01-01T00:00:00Z"}`    |
+//! | `ObjectReference` | `{"$ref": "plant:123"}`                    |
+//! | `GeoJSON`         | `{"$geojson": "{\"type\":\"Point\",...}"}` |
+//! | `Array`           | `[...]`                                    |
+//! | `Map`             | `{...}`; `{"$map": {...}}` if its only key starts with `$` |
+//! | `Object`          | `{"$object": {...}}`                       |
+//!
+//! Tag keys are not valid GraphQL names, so tagged values are passed in variables. Where the
+//! ontology declares the expected type (a function parameter, a filtered property), untagged
+//! input is coerced to it with `coerce`, e.g. `"2020-01-01"` becomes a `Date` for a date
+//! property.
+
+use async_graphql::{InputValueError, InputValueResult, Scalar, ScalarType, Value as GraphQLValue};
+use ontology_engine::{PropertyType, PropertyValue};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::{Map, Number, Value};
+use std::collections::HashMap;
+
+const DATE_TAG: &str = "$date";
+const DATETIME_TAG: &str = "$datetime";
+const REF_TAG: &str = "$ref";
+const GEOJSON_TAG: &str = "$geojson";
+const DOUBLE_TAG: &str = "$double";
+const MAP_TAG: &str = "$map";
+const OBJECT_TAG: &str = "$object";
+
+/// A `PropertyValue` crossing the GraphQL boundary without losing its variant
+#[derive(Debug, Clone, PartialEq)]
+pub struct PropertyValueScalar(pub PropertyValue);
+
+/// An ontology property value. Dates, datetimes, object references and GeoJSON are tagged,
+/// e.g. `{"$date": "2020-01-01"}`; other values are plain JSON.
+#[Scalar(name = "PropertyValue")]
+impl ScalarType for PropertyValueScalar {
+    fn parse(value: GraphQLValue) -> InputValueResult<Self> {
+        let json = value.into_json().map_err(InputValueError::custom)?;
+        decode(json).map(Self).map_err(InputValueError::custom)
+    }
+
+    fn to_value(&self) -> GraphQLValue {
+        GraphQLValue::from_json(encode(&self.0)).unwrap_or(GraphQLValue::Null)
+    }
+}
+
+// Serde goes through the same encoding, so the scalar also works inside `JSONObject` maps
+impl Serialize for PropertyValueScalar {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        encode(&self.0).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for PropertyValueScalar {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let json = Value::deserialize(deserializer)?;
+        decode(json).map(Self).map_err(serde::de::Error::custom)
+    }
+}
+
+impl From<PropertyValue> for PropertyValueScalar {
+    fn from(value: PropertyValue) -> Self {
+        Self(value)
+    }
+}
+
+/// JSON encoding of a property value, tagging the variants plain JSON cannot distinguish
+pub fn encode(value: &PropertyValue) -> Value {
+    let tagged = |tag: &str, value: Value| Value::Object(Map::from_iter([(tag.to_string(), value)]));
+    let fields = |fields: &HashMap<String, PropertyValue>| {
+        Value::Object(fields.iter().map(|(key, value)| (key.clone(), encode(value))).collect())
+    };
+    match value {
+        PropertyValue::String(s) => Value::String(s.clone()),
+        PropertyValue::Integer(i) => Value::from(*i),
+        PropertyValue::Double(d) => match Number::from_f64(*d) {
+            Some(number) => Value::Number(number),
+            None => tagged(DOUBLE_TAG, Value::String(d.to_string())),
+        },
+        PropertyValue::Boolean(b) => Value::Bool(*b),
+        PropertyValue::Date(s) => tagged(DATE_TAG, Value::String(s.clone())),
+        PropertyValue::DateTime(s) => tagged(DATETIME_TAG, Value::String(s.clone())),
+        PropertyValue::ObjectReference(s) => tagged(REF_TAG, Value::String(s.clone())),
+        PropertyValue::GeoJSON(s) => tagged(GEOJSON_TAG, Value::String(s.clone())),
+        PropertyValue::Array(items) => Value::Array(items.iter().map(encode).collect()),
+        PropertyValue::Map(map) if map.len() == 1 && map.keys().all(|key| key.starts_with('$')) => {
+            tagged(MAP_TAG, fields(map))
+        }
+        PropertyValue::Map(map) => fields(map),
+        PropertyValue::Object(object) => tagged(OBJECT_TAG, fields(object)),
+        PropertyValue::Null => Value::Null,
+    }
+}
+
+/// Property value from its JSON encoding; the inverse of `encode`. Tagged dates, datetimes
+/// and GeoJSON are validated.
+pub fn decode(json: Value) -> Result<PropertyValue, String> {
+    match json {
+        Value::Null => Ok(PropertyValue::Null),
+        Value::Bool(b) => Ok(PropertyValue::Boolean(b)),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => Ok(PropertyValue::Integer(i)),
+            None => n
+                .as_f64()
+                .map(PropertyValue::Double)
+                .ok_or_else(|| format!("Number {} is out of range", n)),
+        },
+        Value::String(s) => Ok(PropertyValue::String(s)),
+        Value::Array(items) => items.into_iter().map(decode).collect::<Result<_, _>>().map(PropertyValue::Array),
+        Value::Object(object) => {
+            let tag = match object.iter().next() {
+                Some((key, _)) if object.len() == 1 && key.starts_with('$') => key.clone(),
+                _ => return decode_fields(object).map(PropertyValue::Map),
+            };
+            let value = object.into_iter().next().map(|(_, value)| value).unwrap_or_default();
+            decode_tagged(&tag, value)
+        }
+    }
+}
+
+fn decode_fields(object: Map<String, Value>) -> Result<HashMap<String, PropertyValue>, String> {
+    object
+        .into_iter()
+        .map(|(key, value)| decode(value).map(|value| (key, value)))
+        .collect()
+}
+
+fn decode_tagged(tag: &str, value: Value) -> Result<PropertyValue, String> {
+    let text = |value: Value| match value {
+        Value::String(s) => Ok(s),
+        other => Err(format!("'{}' expects a string, got {}", tag, other)),
+    };
+    match tag {
+        DATE_TAG => text(value).and_then(|s| parse_date(&s)),
+        DATETIME_TAG => text(value).and_then(|s| parse_datetime(&s)),
+        REF_TAG => match text(value)? {
+            s if s.is_empty() => Err("'$ref' expects a non-empty object reference".to_string()),
+            s => Ok(PropertyValue::ObjectReference(s)),
+        },
+        GEOJSON_TAG => match value {
+            Value::String(s) => parse_geojson(&s),
+            geometry @ Value::Object(_) => Ok(PropertyValue::GeoJSON(geometry.to_string())),
+            other => Err(format!("'$geojson' expects a string or object, got {}", other)),
+        },
+        DOUBLE_TAG => match value {
+            Value::Number(n) => n.as_f64().map(PropertyValue::Double).ok_or_else(|| format!("Number {} is out of range", n)),
+            Value::String(s) => s
+                .parse::<f64>()
+                .map(PropertyValue::Double)
+                .map_err(|_| format!("'$double' expects a number, got '{}'", s)),
+            other => Err(format!("'$double' expects a number, got {}", other)),
+        },
+        MAP_TAG | OBJECT_TAG => match value {
+            Value::Object(object) => {
+                let fields = decode_fields(object)?;
+                Ok(if tag == MAP_TAG { PropertyValue::Map(fields) } else { PropertyValue::Object(fields) })
+            }
+            other => Err(format!("'{}' expects an object, got {}", tag, other)),
+        },
+        _ => Err(format!("Unknown PropertyValue tag '{}'", tag)),
+    }
+}
+
+fn parse_date(s: &str) -> Result<PropertyValue, String> {
+    chrono::NaiveDate::parse_from_str(s, "%Y-%m-%d")
+        .map(|_| PropertyValue::Date(s.to_string()))
+        .map_err(|e| format!("Invalid date '{}': {}", s, e))
+}
+
+fn parse_datetime(s: &str) -> Result<PropertyValue, String> {
+    chrono::DateTime::parse_from_rfc3339(s)
+        .map(|_| PropertyValue::DateTime(s.to_string()))
+        .map_err(|e| format!("Invalid datetime '{}': {}", s, e))
+}
+
+fn parse_geojson(s: &str) -> Result<PropertyValue, String> {
+    match serde_json::from_str::<Value>(s) {
+        Ok(Value::Object(_)) => Ok(PropertyValue::GeoJSON(s.to_string())),
+        _ => Err(format!("Invalid GeoJSON '{}'", s)),
+    }
+}
+
+/// Coerce an untagged value to the type the ontology expects: strings become dates,
+/// datetimes, references or GeoJSON, integers become doubles, and arrays, maps and structs
+/// are coerced element by element. Values of any other shape are returned as they are, for
+/// validation to report.
+pub fn coerce(value: PropertyValue, expected: &PropertyType) -> Result<PropertyValue, String> {
+    match (expected, value) {
+        (PropertyType::Date, PropertyValue::String(s)) => parse_date(&s),
+        (PropertyType::DateTime | PropertyType::Timestamp, PropertyValue::String(s)) => parse_datetime(&s),
+        (PropertyType::ObjectReference | PropertyType::ObjectReferenceAlt, PropertyValue::String(s)) => {
+            Ok(PropertyValue::ObjectReference(s))
+        }
+        (PropertyType::GeoJSON | PropertyType::GeoJSONAlt, PropertyValue::String(s)) => parse_geojson(&s),
+        (PropertyType::GeoJSON | PropertyType::GeoJSONAlt, value @ (PropertyValue::Map(_) | PropertyValue::Object(_))) => {
+            let json = serde_json::to_string(&value).map_err(|e| e.to_string())?;
+            Ok(PropertyValue::GeoJSON(json))
+        }
+        (PropertyType::Double | PropertyType::Float, PropertyValue::Integer(i)) => Ok(PropertyValue::Double(i as f64)),
+        (PropertyType::Array { element_type }, PropertyValue::Array(items)) => items
+            .into_iter()
+            .map(|item| coerce(item, element_type))
+            .collect::<Result<_, _>>()
+            .map(PropertyValue::Array),
+        (PropertyType::Map { value_type, .. }, PropertyValue::Map(map)) => map
+            .into_iter()
+            .map(|(key, value)| coerce(value, value_type).map(|value| (key, value)))
+            .collect::<Result<_, _>>()
+            .map(PropertyValue::Map),
+        (PropertyType::Object(struct_def), PropertyValue::Map(fields) | PropertyValue::Object(fields)) => fields
+            .into_iter()
+            .map(|(key, value)| match struct_def.fields.iter().find(|f| f.id == key) {
+                Some(field) => coerce(value, &field.property_type).map(|value| (key, value)),
+                None => Ok((key, value)),
+            })
+            .collect::<Result<_, _>>()
+            .map(PropertyValue::Object),
+        // Filters on a scalar property may list several candidates, e.g. for `in`
+        (expected, PropertyValue::Array(items)) if expected.is_simple() => items
+            .into_iter()
+            .map(|item| coerce(item, expected))
+            .collect::<Result<_, _>>()
+            .map(PropertyValue::Array),
+        (_, value) => Ok(value),
+    }
+}
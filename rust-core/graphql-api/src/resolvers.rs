@@ -1,17 +1,49 @@
-use async_graphql::{Context, FieldResult, InputObject, Json, Object, SimpleObject};
+use async_graphql::{ComplexObject, Context, FieldResult, InputObject, Json, Object, SimpleObject};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
 use chrono::{DateTime, Utc};
-use indexing::hydration::ObjectHydrator;
+use indexing::hydration::{HydratedObject, HydrationOptions, ObjectHydrator};
+use indexing::model_properties::ModelPropertyEvaluator;
 use indexing::store::{
-    CentralityMetric, CommunityAlgorithm, Filter, GraphStore, SearchQuery, SearchStore,
+    CentralityMetric, CommunityAlgorithm, Filter, GraphStore, IndexedObject, LinkQuery, SearchQuery,
+    SearchStore, SortOption, StoreError,
 };
-use indexing::{DataLineage, DataQualityMetrics, ObjectUsageMetrics};
-use ontology_engine::{FunctionExecutor, InterfaceValidator, Ontology, PropertyValue};
+use indexing::archive::{find_tombstone, ARCHIVED_PARTITION};
+use indexing::dedup::resolve_merged;
+use indexing::export::{ExportOutput, EXPORT_JOB_KIND};
+use indexing::planner::{Backend, BackendCapabilities, QueryPlan, QueryPlanner, QueryShape};
+use indexing::{
+    ChangeTrigger, ChangeTriggerRegistry, DataLineage, DataQualityMetrics, JobProgress, JobRegistry, ObjectUsageMetrics, PropertyDrift, QueryLog,
+    ReferenceIndex, SlowQuery,
+};
+use ontology_engine::{
+    action_form_schema, object_form_schema, AggregationType, ApportionRounding, CrosswalkTraverser, DisplayLocale, FormSchemaOptions, FunctionDataSource,
+    FunctionExecutor, GeometryOutput, InterfaceValidator, ObjectRef, ObjectType, Ontology, OntologyHandle, Property, PropertyGroup, PropertyGroupManager, PropertyMap,
+    PropertyType, PropertyValue, UNGROUPED_GROUP_ID,
+};
+use security::acl::{with_acl_index_fields, ACL_DENIED_FIELD, ACL_READERS_FIELD};
+use security::ols::ObjectSecurityPolicy;
+use security::{check_access, AclSearchFilter, ObjectAcl, SecurityContext};
 use serde_json::Value;
+use std::cmp::Ordering;
 use std::collections::hash_map::DefaultHasher;
 use std::collections::HashMap;
 use std::hash::{Hash, Hasher};
 use std::sync::Arc;
 use versioning::time_query;
+use writeback::{EditQueue, PendingConflicts, PropertyConflict, UserEdit};
+
+use crate::actions::enum_name;
+use crate::admin::ONTOLOGY_ADMIN_ROLE;
+use crate::api_version::{json_field, ApiMeta};
+use crate::display::display_json;
+use crate::error::ApiError;
+use crate::explain::record_explain;
+use crate::filters::{check_spatial_filter, convert_filters, implementer_filters, FilterInput};
+use crate::masking::{check_readable, mask_object_json};
+use crate::oql;
+use crate::property_value::{self, PropertyValueScalar};
+use crate::telemetry::{record_object_type, record_result_count};
 
 /// Root query type for GraphQL API
 #[derive(Default)]
@@ -19,259 +51,398 @@ pub struct QueryRoot;
 
 #[Object]
 impl QueryRoot {
-    /// Search for objects of a specific type
+    /// API version of this server, its compat mode and the deprecated fields
+    async fn meta(&self, ctx: &Context<'_>) -> ApiMeta {
+        ApiMeta::for_context(ctx)
+    }
+
+    /// Search for objects of a specific type. `sort` keys apply in order, each breaking ties
+    /// in the ones before it. With `explain`, the backend-native search request is returned
+    /// under `extensions.explain`. On-read computed properties are evaluated into each result
+    /// unless `includeComputed` is false. With `resolveReferences`, object reference
+    /// properties are returned as `{"id", "title"}` of the referenced objects.
+    /// GeoJSON properties can be shrunk for map views: `simplifyTolerance` (degrees) drops
+    /// vertices with Douglas–Peucker, `geometryPrecision` rounds coordinates to that many
+    /// decimal places, and `omitGeometry` returns `[minLon, minLat, maxLon, maxLat]` instead.
+    /// With `includePredictions`, properties bound to a model hold its prediction.
     async fn search_objects(
         &self,
         ctx: &Context<'_>,
         object_type: String,
         filters: Option<Vec<FilterInput>>,
+        sort: Option<Vec<SortInput>>,
         limit: Option<usize>,
         offset: Option<usize>,
+        include_display: Option<bool>,
+        locale: Option<String>,
+        explain: Option<bool>,
+        include_computed: Option<bool>,
+        resolve_references: Option<bool>,
+        simplify_tolerance: Option<f64>,
+        geometry_precision: Option<u32>,
+        omit_geometry: Option<bool>,
+        include_predictions: Option<bool>,
     ) -> FieldResult<Vec<ObjectResult>> {
-        // Get services from context
-        let ontology = ctx.data::<Arc<Ontology>>()?;
-        let search_store = ctx.data::<Arc<dyn SearchStore>>()?;
-        let hydrator = ctx.data::<ObjectHydrator>()?;
-
-        // Build filters first
-        let mut store_filters = Vec::new();
-        if let Some(filter_inputs) = filters {
-            for filter_input in filter_inputs {
-                store_filters.push(convert_filter_input(filter_input)?);
-            }
-        }
+        let ontology = ctx.data::<OntologyHandle>()?.load();
+        let object_type_def = ontology
+            .get_object_type(&object_type)
+            .ok_or_else(|| ApiError::not_found("Object type not found"))?;
 
-        // Try to get data from in-memory store first
-        let data_store = ctx.data::<Arc<tokio::sync::RwLock<HashMap<String, Vec<Value>>>>>();
+        let display_locale = include_display
+            .unwrap_or(false)
+            .then(|| DisplayLocale::for_tag(locale.as_deref()));
 
-        if let Ok(store) = data_store {
-            let store_read = store.read().await;
-            eprintln!("DEBUG: Looking for object_type: {}", object_type);
-            eprintln!(
-                "DEBUG: Available keys in store: {:?}",
-                store_read.keys().collect::<Vec<_>>()
-            );
-            if let Some(objects) = store_read.get(&object_type) {
-                eprintln!("DEBUG: Found {} objects for {}", objects.len(), object_type);
-                // Get object type definition for metadata
-                let object_type_def = ontology.get_object_type(&object_type).ok_or_else(|| {
-                    async_graphql::Error::new("Object type not found in ontology")
-                })?;
+        // Caller's sort wins, otherwise the type's default sort (primary key if none configured)
+        let sort_options = resolve_sort(object_type_def, sort);
 
-                // Filter objects based on filters
-                let mut filtered: Vec<&Value> = objects.iter().collect();
-
-                // Apply filters
-                for filter in &store_filters {
-                    filtered.retain(|obj| {
-                        if let Some(prop_value) = obj.get(&filter.property) {
-                            match &filter.operator {
-                                indexing::store::FilterOperator::Equals => match filter.value {
-                                    ontology_engine::PropertyValue::String(ref s) => {
-                                        prop_value.as_str().map_or(false, |v| v == s)
-                                    }
-                                    ontology_engine::PropertyValue::Integer(i) => {
-                                        prop_value.as_i64().map_or(false, |v| v == i)
-                                    }
-                                    ontology_engine::PropertyValue::Double(d) => prop_value
-                                        .as_f64()
-                                        .map_or(false, |v| (v - d).abs() < 0.0001),
-                                    _ => false,
-                                },
-                                indexing::store::FilterOperator::GreaterThan => {
-                                    match filter.value {
-                                        ontology_engine::PropertyValue::Integer(i) => {
-                                            prop_value.as_i64().map_or(false, |v| v > i)
-                                        }
-                                        ontology_engine::PropertyValue::Double(d) => {
-                                            prop_value.as_f64().map_or(false, |v| v > d)
-                                        }
-                                        _ => false,
-                                    }
-                                }
-                                indexing::store::FilterOperator::LessThan => match filter.value {
-                                    ontology_engine::PropertyValue::Integer(i) => {
-                                        prop_value.as_i64().map_or(false, |v| v < i)
-                                    }
-                                    ontology_engine::PropertyValue::Double(d) => {
-                                        prop_value.as_f64().map_or(false, |v| v < d)
-                                    }
-                                    _ => false,
-                                },
-                                _ => true, // For other operators, keep for now
-                            }
-                        } else {
-                            false
-                        }
-                    });
-                }
+        let store_filters = convert_filters(filters.unwrap_or_default(), &object_type_def.properties)?;
 
-                // Apply pagination
-                let start = offset.unwrap_or(0);
-                let end = limit.map(|l| start + l).unwrap_or(filtered.len());
-                let paginated: Vec<&Value> =
-                    filtered.into_iter().skip(start).take(end - start).collect();
+        run_search(
+            ctx,
+            &object_type,
+            store_filters,
+            sort_options,
+            limit,
+            offset,
+            display_locale,
+            HydrationOptions {
+                include_computed: include_computed.unwrap_or(true),
+                resolve_references: resolve_references.unwrap_or(false),
+                geometry: geometry_output(simplify_tolerance, geometry_precision, omit_geometry)?,
+                include_predictions: include_predictions.unwrap_or(false),
+            },
+            explain.unwrap_or(false),
+        )
+        .await
+    }
 
-                // Convert to ObjectResult
-                let results: Vec<ObjectResult> = paginated
-                    .iter()
-                    .map(|obj| {
-                        let object_id = obj
-                            .get(&object_type_def.primary_key)
-                            .and_then(|v| v.as_str())
-                            .unwrap_or("unknown")
-                            .to_string();
-
-                        let title = object_type_def
-                            .title_key
-                            .as_ref()
-                            .and_then(|key| obj.get(key))
-                            .and_then(|v| v.as_str())
-                            .map(|s| s.to_string())
-                            .unwrap_or_else(|| object_id.clone());
-
-                        ObjectResult {
-                            object_type: object_type.clone(),
-                            object_id,
-                            title,
-                            properties: Json((*obj).clone()),
-                        }
-                    })
-                    .collect();
+    /// Search one page at a time. Pass the previous page's `endCursor` as `after`; cursors
+    /// mark a position in the sort order (ties broken by primary key), so objects written
+    /// between requests do not shift pages. Applies the same ACL restriction as
+    /// `searchObjects`, and resolves references and shrinks geometries the same way.
+    async fn search_objects_paginated(
+        &self,
+        ctx: &Context<'_>,
+        object_type: String,
+        filters: Option<Vec<FilterInput>>,
+        sort: Option<Vec<SortInput>>,
+        first: Option<usize>,
+        after: Option<String>,
+        include_display: Option<bool>,
+        locale: Option<String>,
+        resolve_references: Option<bool>,
+        simplify_tolerance: Option<f64>,
+        geometry_precision: Option<u32>,
+        omit_geometry: Option<bool>,
+    ) -> FieldResult<PaginatedObjectResult> {
+        let ontology = ctx.data::<OntologyHandle>()?.load();
+        let object_type_def = ontology
+            .get_object_type(&object_type)
+            .ok_or_else(|| ApiError::not_found("Object type not found"))?;
+
+        let display_locale = include_display
+            .unwrap_or(false)
+            .then(|| DisplayLocale::for_tag(locale.as_deref()));
+
+        let store_filters = convert_filters(filters.unwrap_or_default(), &object_type_def.properties)?;
+        let sort_options = resolve_sort(object_type_def, sort);
+        check_indexed(object_type_def, &store_filters, &sort_options)?;
+        check_readable(ctx, object_type_def, query_properties(&store_filters, &sort_options))?;
+
+        let page_size = first.unwrap_or(DEFAULT_SEARCH_PAGE_SIZE);
+        let after = after.as_deref().map(decode_search_cursor).transpose()?;
+        let hydration = HydrationOptions {
+            resolve_references: resolve_references.unwrap_or(false),
+            geometry: geometry_output(simplify_tolerance, geometry_precision, omit_geometry)?,
+            ..HydrationOptions::default()
+        };
+        run_paginated_search(ctx, object_type_def, store_filters, sort_options, page_size, after, display_locale, hydration)
+            .await
+    }
 
-                eprintln!(
-                    "DEBUG: Returning {} results from in-memory store",
-                    results.len()
-                );
-                return Ok(results);
-            } else {
-                eprintln!("DEBUG: No objects found for object_type: {}", object_type);
-                eprintln!(
-                    "DEBUG: Available keys: {:?}",
-                    store_read.keys().collect::<Vec<_>>()
-                );
-            }
+    /// Search with a one-line OQL query, e.g.
+    /// `Plant where state = "NJ" and year >= 2015 order by population desc limit 50`.
+    /// The query is checked against the ontology and then run like `searchObjects`. `or`
+    /// may only join `=` and `in` comparisons on one property, e.g.
+    /// `state = "NJ" or state = "NY"`.
+    async fn query_oql(
+        &self,
+        ctx: &Context<'_>,
+        oql: String,
+        include_display: Option<bool>,
+        locale: Option<String>,
+    ) -> FieldResult<Vec<ObjectResult>> {
+        let ontology = ctx.data::<OntologyHandle>()?.load();
+        let query = oql::parse(&oql, &ontology)
+            .map_err(|e| ApiError::invalid_argument(format!("Invalid OQL query: {}", e)))?;
+        let object_type_def = ontology
+            .get_object_type(&query.object_type)
+            .ok_or_else(|| ApiError::not_found("Object type not found"))?;
+
+        let display_locale = include_display
+            .unwrap_or(false)
+            .then(|| DisplayLocale::for_tag(locale.as_deref()));
+        let sort_options = if query.sort.is_empty() {
+            resolve_sort(object_type_def, None)
         } else {
-            eprintln!(
-                "DEBUG: Failed to get data_store from context. Error: {:?}",
-                data_store
-            );
-        }
+            query.sort
+        };
+
+        run_search(
+            ctx,
+            &query.object_type,
+            query.filters,
+            sort_options,
+            query.limit,
+            query.offset,
+            display_locale,
+            HydrationOptions::default(),
+            false,
+        )
+        .await
+    }
 
-        // Fallback to search store - get object type definition
+    /// Number of objects of a type matching `filters`, counted by the backend without
+    /// fetching any of them. Applies the same ACL restriction as `searchObjects`.
+    async fn count_objects(
+        &self,
+        ctx: &Context<'_>,
+        object_type: String,
+        filters: Option<Vec<FilterInput>>,
+    ) -> FieldResult<u64> {
+        let ontology = ctx.data::<OntologyHandle>()?.load();
         let object_type_def = ontology
             .get_object_type(&object_type)
-            .ok_or_else(|| async_graphql::Error::new("Object type not found"))?;
-
-        let query = SearchQuery {
-            filters: store_filters,
-            sort: None,
-            limit,
-            offset,
-        };
+            .ok_or_else(|| ApiError::not_found("Object type not found"))?;
 
-        // Execute search
-        let indexed_objects = search_store
-            .search(&object_type, &query)
-            .await
-            .map_err(|e| async_graphql::Error::new(format!("Search error: {}", e)))?;
+        let mut store_filters = convert_filters(filters.unwrap_or_default(), &object_type_def.properties)?;
+        check_indexed(object_type_def, &store_filters, &[])?;
+        check_readable(ctx, object_type_def, query_properties(&store_filters, &[]))?;
 
-        // Hydrate objects
-        let hydrated = hydrator
-            .hydrate_batch(&indexed_objects, object_type_def)
-            .map_err(|e| async_graphql::Error::new(format!("Hydration error: {}", e)))?;
+        let acl_filter = ctx
+            .data_opt::<SecurityContext>()
+            .map(AclSearchFilter::for_context);
 
-        // Convert to GraphQL results
-        Ok(hydrated
-            .into_iter()
-            .map(|h| {
-                let properties_json: Value =
-                    serde_json::to_value(&h.properties).unwrap_or_else(|_| serde_json::json!({}));
-                ObjectResult {
-                    object_type: h.object_type,
-                    object_id: h.object_id,
-                    title: h.title,
-                    properties: Json(properties_json),
-                }
-            })
-            .collect())
+        if let Some(acl_filter) = &acl_filter {
+            store_filters.extend(acl_store_filters(acl_filter));
+        }
+        record_object_type(&object_type);
+        let count = ctx.data::<Arc<dyn SearchStore>>()?
+            .count_objects(&object_type, Some(&store_filters))
+            .await
+            .map_err(ApiError::from)?;
+        record_result_count(count as usize);
+        Ok(count)
     }
 
-    /// Get a specific object by ID
+    /// Get a specific object by ID, with its on-read computed properties unless
+    /// `includeComputed` is false, its references resolved as in `searchObjects` with
+    /// `resolveReferences`, its geometries shrunk and its predictions included as there
+    /// Objects whose ACL denies the caller or doesn't list them resolve to null.
     async fn get_object(
         &self,
         ctx: &Context<'_>,
         object_type: String,
         object_id: String,
+        include_display: Option<bool>,
+        locale: Option<String>,
+        include_computed: Option<bool>,
+        resolve_references: Option<bool>,
+        // Also return the property values bucketed by property group, as in `getPropertyGroups`
+        group_properties: Option<bool>,
+        simplify_tolerance: Option<f64>,
+        geometry_precision: Option<u32>,
+        omit_geometry: Option<bool>,
+        include_predictions: Option<bool>,
     ) -> FieldResult<Option<ObjectResult>> {
-        let ontology = ctx.data::<Arc<Ontology>>()?;
-
-        let object_type_def = ontology
-            .get_object_type(&object_type)
-            .ok_or_else(|| async_graphql::Error::new("Object type not found"))?;
-
-        // Try in-memory store first
-        let data_store = ctx.data::<Arc<tokio::sync::RwLock<HashMap<String, Vec<Value>>>>>();
-        if let Ok(store) = data_store {
-            let store_read = store.read().await;
-            if let Some(objects) = store_read.get(&object_type) {
-                let pk = &object_type_def.primary_key;
-                let found = objects.iter().find(|obj| {
-                    obj.get(pk).map_or(false, |v| {
-                        v.as_str().map_or(false, |s| s == object_id)
-                            || v.as_i64().map_or(false, |i| i.to_string() == object_id)
-                            || v.as_f64().map_or(false, |f| f.to_string() == object_id)
-                    })
-                });
-
-                if let Some(obj) = found {
-                    let title = object_type_def
-                        .title_key
-                        .as_ref()
-                        .and_then(|key| obj.get(key))
-                        .and_then(|v| v.as_str())
-                        .map(|s| s.to_string())
-                        .unwrap_or_else(|| object_id.clone());
-                    return Ok(Some(ObjectResult {
-                        object_type: object_type.clone(),
-                        object_id: object_id.clone(),
-                        title,
-                        properties: Json(obj.clone()),
-                    }));
+        let hydration = HydrationOptions {
+            include_computed: include_computed.unwrap_or(true),
+            resolve_references: resolve_references.unwrap_or(false),
+            geometry: geometry_output(simplify_tolerance, geometry_precision, omit_geometry)?,
+            include_predictions: include_predictions.unwrap_or(false),
+        };
+        record_object_type(&object_type);
+        let mut result = load_object(ctx, &object_type, &object_id, hydration).await?;
+        record_result_count(usize::from(result.is_some()));
+        if include_display.unwrap_or(false) || group_properties.unwrap_or(false) {
+            let ontology = ctx.data::<OntologyHandle>()?.load();
+            if let (Some(result), Some(object_type_def)) =
+                (result.as_mut(), ontology.get_object_type(&object_type))
+            {
+                if include_display.unwrap_or(false) {
+                    let locale = DisplayLocale::for_tag(locale.as_deref());
+                    result.display = Some(Json(display_json(object_type_def, &result.properties.0, &locale)));
+                }
+                if group_properties.unwrap_or(false) {
+                    result.property_groups =
+                        Some(property_group_outputs(object_type_def, locale.as_deref(), Some(&result.properties.0)));
                 }
-                // Object type found in store, but this specific ID is not — skip ES lookup
-                return Ok(None);
             }
         }
+        Ok(result)
+    }
+
+    /// Page through the links of an object, optionally filtered and sorted on link properties
+    async fn links(
+        &self,
+        ctx: &Context<'_>,
+        object_type: String,
+        object_id: String,
+        link_type: String,
+        filters: Option<Vec<FilterInput>>,
+        sort: Option<SortInput>,
+        first: Option<usize>,
+        after: Option<String>,
+    ) -> FieldResult<LinkConnection> {
+        let ontology = ctx.data::<OntologyHandle>()?.load();
+        let graph_store = ctx.data::<Arc<dyn GraphStore>>()?;
+
+        let link_type_def = ontology
+            .get_link_type(&link_type)
+            .ok_or_else(|| ApiError::not_found("Link type not found"))?;
+        if link_type_def.source != object_type && link_type_def.target != object_type {
+            return Err(ApiError::invalid_argument("Link type does not connect to this object type").into());
+        }
 
-        // Fallback to Elasticsearch
-        let search_store = ctx.data::<Arc<dyn SearchStore>>()?;
-        let hydrator = ctx.data::<ObjectHydrator>()?;
+        // Link properties end up in graph queries, so only declared ones are accepted
+        let undeclared = filters
+            .iter()
+            .flatten()
+            .map(|filter| &filter.property)
+            .chain(sort.as_ref().map(|s| &s.property))
+            .find(|property| !link_type_def.properties.iter().any(|p| &p.id == *property));
+        if let Some(property) = undeclared {
+            return Err(ApiError::invalid_argument(format!(
+                "Unknown property '{}' on link type '{}'",
+                property, link_type
+            ))
+            .into());
+        }
+        let link_filters = convert_filters(filters.unwrap_or_default(), &link_type_def.properties)?;
 
-        let indexed = search_store
-            .get_object(&object_type, &object_id)
+        // Cursors are the zero-based position of an edge; fetch one extra to detect a next page
+        let start = match after {
+            Some(cursor) => decode_link_cursor(&cursor)? + 1,
+            None => 0,
+        };
+        let page_size = first.unwrap_or(DEFAULT_LINK_PAGE_SIZE);
+        let query = LinkQuery {
+            filters: link_filters,
+            sort: sort.map(|s| SortOption {
+                property: s.property,
+                ascending: s.ascending.unwrap_or(true),
+                keyword_subfield: false,
+            }),
+            limit: Some(page_size + 1),
+            offset: Some(start),
+        };
+
+        let mut links = graph_store
+            .get_links(&object_id, Some(&link_type), None, &query)
             .await
-            .map_err(|e| async_graphql::Error::new(format!("Get error: {}", e)))?;
+            .map_err(ApiError::from)?;
+        let has_next_page = links.len() > page_size;
+        links.truncate(page_size);
 
-        if let Some(indexed) = indexed {
-            let hydrated = hydrator
-                .hydrate_from_indexed(&indexed, object_type_def)
-                .map_err(|e| async_graphql::Error::new(format!("Hydration error: {}", e)))?;
-
-            let properties_json: Value = serde_json::to_value(&hydrated.properties)
-                .unwrap_or_else(|_| serde_json::json!({}));
-            Ok(Some(ObjectResult {
-                object_type: hydrated.object_type,
-                object_id: hydrated.object_id,
-                title: hydrated.title,
-                properties: Json(properties_json),
-            }))
-        } else {
-            Ok(None)
+        let edges: Vec<LinkEdge> = links
+            .into_iter()
+            .enumerate()
+            .map(|(i, link)| {
+                let outgoing = link.source_id == object_id;
+                let (other_object_id, other_object_type) = if outgoing {
+                    (link.target_id, link_type_def.target.clone())
+                } else {
+                    (link.source_id, link_type_def.source.clone())
+                };
+                LinkEdge {
+                    cursor: encode_link_cursor(start + i),
+                    link_id: link.link_id,
+                    link_type: link.link_type_id,
+                    direction: if outgoing { "outgoing" } else { "incoming" }.to_string(),
+                    properties: Json(
+                        serde_json::to_value(&link.properties)
+                            .unwrap_or_else(|_| serde_json::json!({})),
+                    ),
+                    created_at: link.created_at.to_rfc3339(),
+                    other_object_type,
+                    other_object_id,
+                }
+            })
+            .collect();
+
+        Ok(LinkConnection {
+            page_info: PageInfo {
+                has_next_page,
+                has_previous_page: start > 0,
+                start_cursor: edges.first().map(|e| e.cursor.clone()),
+                end_cursor: edges.last().map(|e| e.cursor.clone()),
+            },
+            edges,
+        })
+    }
+
+    /// Count, range, mean and histogram of a numeric property over every link of a type
+    async fn link_stats(
+        &self,
+        ctx: &Context<'_>,
+        link_type: String,
+        property: String,
+        buckets: Option<usize>,
+    ) -> FieldResult<LinkStatsResult> {
+        let ontology = ctx.data::<OntologyHandle>()?.load();
+        let graph_store = ctx.data::<Arc<dyn GraphStore>>()?;
+
+        let link_type_def = ontology
+            .get_link_type(&link_type)
+            .ok_or_else(|| ApiError::not_found("Link type not found"))?;
+        let property_def = link_type_def
+            .properties
+            .iter()
+            .find(|p| p.id == property)
+            .ok_or_else(|| {
+                ApiError::not_found(format!(
+                    "Property '{}' not found on link type '{}'",
+                    property, link_type
+                ))
+            })?;
+        if !matches!(
+            property_def.property_type,
+            PropertyType::Integer | PropertyType::Int | PropertyType::Double | PropertyType::Float
+        ) {
+            return Err(ApiError::invalid_argument(format!(
+                "Property '{}' of link type '{}' is not numeric",
+                property, link_type
+            )).into());
+        }
+        let buckets = buckets.unwrap_or(DEFAULT_LINK_STATS_BUCKETS);
+        if !(1..=MAX_LINK_STATS_BUCKETS).contains(&buckets) {
+            return Err(ApiError::invalid_argument(format!(
+                "buckets must be between 1 and {}",
+                MAX_LINK_STATS_BUCKETS
+            )).into());
         }
+
+        let stats = graph_store
+            .link_property_stats(&link_type, &property, buckets)
+            .await
+            .map_err(ApiError::from)?;
+        Ok(LinkStatsResult {
+            link_type,
+            property,
+            count: stats.count,
+            min: stats.min,
+            max: stats.max,
+            mean: stats.mean,
+            histogram: stats
+                .histogram
+                .into_iter()
+                .map(|b| HistogramBucketResult { lower: b.lower, upper: b.upper, count: b.count })
+                .collect(),
+        })
     }
 
-    /// Get linked objects via a specific link type
+    /// Get linked objects via a specific link type, leaving out those whose ACL hides them
+    /// from the caller
     async fn get_linked_objects(
         &self,
         ctx: &Context<'_>,
@@ -279,7 +450,7 @@ impl QueryRoot {
         object_id: String,
         link_type: String,
     ) -> FieldResult<Vec<ObjectResult>> {
-        let ontology = ctx.data::<Arc<Ontology>>()?;
+        let ontology = ctx.data::<OntologyHandle>()?.load();
         let graph_store = ctx.data::<Arc<dyn GraphStore>>()?;
         let search_store = ctx.data::<Arc<dyn SearchStore>>()?;
         let hydrator = ctx.data::<ObjectHydrator>()?;
@@ -287,54 +458,64 @@ impl QueryRoot {
         // Validate link type
         let link_type_def = ontology
             .get_link_type(&link_type)
-            .ok_or_else(|| async_graphql::Error::new("Link type not found"))?;
+            .ok_or_else(|| ApiError::not_found("Link type not found"))?;
 
-        // Determine target object type
-        let target_type = if link_type_def.source == object_type {
-            &link_type_def.target
+        // Linked objects are of the other end's type. Directed links are followed backwards
+        // from the target side; bidirectional links connect both ways, so the graph store
+        // resolves them from either end.
+        let (target_type, incoming) = if link_type_def.source == object_type {
+            (&link_type_def.target, false)
         } else if link_type_def.target == object_type {
-            &link_type_def.source
+            (&link_type_def.source, !link_type_def.bidirectional)
         } else {
-            return Err(async_graphql::Error::new(
-                "Link type does not connect to this object type",
-            ));
+            return Err(ApiError::invalid_argument("Link type does not connect to this object type").into());
         };
 
         let target_type_def = ontology
             .get_object_type(target_type)
-            .ok_or_else(|| async_graphql::Error::new("Target object type not found"))?;
+            .ok_or_else(|| ApiError::not_found("Target object type not found"))?;
+        record_object_type(target_type);
 
         // Get linked object IDs from graph store
-        let linked_ids = graph_store
-            .get_connected_objects(&object_id, &link_type)
-            .await
-            .map_err(|e| async_graphql::Error::new(format!("Graph query error: {}", e)))?;
+        let linked_ids = if incoming {
+            graph_store
+                .get_links(
+                    &object_id,
+                    Some(&link_type),
+                    Some(indexing::store::LinkDirection::Incoming),
+                    &LinkQuery::default(),
+                )
+                .await
+                .map(|links| links.into_iter().map(|link| link.source_id).collect())
+        } else {
+            graph_store.get_connected_objects(&object_id, &link_type).await
+        }
+        .map_err(ApiError::from)?;
 
-        // Fetch and hydrate linked objects
+        // Fetch and hydrate the linked objects the caller may read
         let mut results = Vec::new();
         for id in linked_ids {
             if let Some(indexed) = search_store
                 .get_object(target_type, &id)
                 .await
-                .map_err(|e| async_graphql::Error::new(format!("Get error: {}", e)))?
+                .map_err(ApiError::from)?
             {
+                if !object_readable(ctx, &indexed.properties) {
+                    continue;
+                }
                 if let Ok(hydrated) = hydrator.hydrate_from_indexed(&indexed, target_type_def) {
-                    let properties_json: Value = serde_json::to_value(&hydrated.properties)
-                        .unwrap_or_else(|_| serde_json::json!({}));
-                    results.push(ObjectResult {
-                        object_type: hydrated.object_type,
-                        object_id: hydrated.object_id,
-                        title: hydrated.title,
-                        properties: Json(properties_json),
-                    });
+                    results.push(hydrated_object_result(ctx, target_type_def, hydrated, None));
                 }
             }
         }
 
+        record_result_count(results.len());
         Ok(results)
     }
 
-    /// Spatial query - search objects by geospatial criteria
+    /// Spatial query - search objects by geospatial criteria. Geometries are WGS84
+    /// longitude/latitude; `within_distance` takes `distance` in meters. Applies the same
+    /// ACL restriction as `searchObjects`.
     async fn spatial_query(
         &self,
         ctx: &Context<'_>,
@@ -342,26 +523,26 @@ impl QueryRoot {
         property: String,
         operator: String,
         geometry: String,      // GeoJSON string
-        distance: Option<f64>, // For WithinDistance operator
+        distance: Option<f64>, // For WithinDistance operator, in meters
     ) -> FieldResult<Vec<ObjectResult>> {
-        let ontology = ctx.data::<Arc<Ontology>>()?;
+        let ontology = ctx.data::<OntologyHandle>()?.load();
         let search_store = ctx.data::<Arc<dyn SearchStore>>()?;
         let hydrator = ctx.data::<ObjectHydrator>()?;
 
         let object_type_def = ontology
             .get_object_type(&object_type)
-            .ok_or_else(|| async_graphql::Error::new("Object type not found"))?;
+            .ok_or_else(|| ApiError::not_found("Object type not found"))?;
 
         // Validate that the property exists and is GeoJSON type
         let prop = object_type_def.get_property(&property).ok_or_else(|| {
-            async_graphql::Error::new(format!("Property '{}' not found", property))
+            ApiError::not_found(format!("Property '{}' not found", property))
         })?;
 
         if prop.property_type != ontology_engine::PropertyType::GeoJSON {
-            return Err(async_graphql::Error::new(format!(
+            return Err(ApiError::invalid_argument(format!(
                 "Property '{}' is not a GeoJSON type",
                 property
-            )));
+            )).into());
         }
 
         // Parse operator
@@ -370,64 +551,56 @@ impl QueryRoot {
             "intersects" => indexing::store::FilterOperator::Intersects,
             "within" => indexing::store::FilterOperator::Within,
             "within_distance" => indexing::store::FilterOperator::WithinDistance,
-            _ => return Err(async_graphql::Error::new(format!(
+            _ => return Err(ApiError::invalid_argument(format!(
                 "Invalid spatial operator: {}. Valid operators: contains, intersects, within, within_distance",
                 operator
-            ))),
+            )).into()),
         };
 
-        // Validate GeoJSON
-        let geometry_value = ontology_engine::PropertyValue::GeoJSON(geometry.clone());
-        if let Err(e) = prop.validate_value(&geometry_value) {
-            return Err(async_graphql::Error::new(format!(
-                "Invalid GeoJSON geometry: {}",
-                e
-            )));
-        }
-
-        // Build filter
+        // Build filter; the geometry is validated like a value of the property
         let filter = Filter {
             property,
             operator: filter_operator,
-            value: geometry_value,
+            value: ontology_engine::PropertyValue::GeoJSON(geometry),
             distance,
+            case_insensitive: false,
         };
+        check_spatial_filter(&filter, prop).map_err(ApiError::invalid_argument)?;
+
+        // Restricted to objects the caller's ACL principals can see, as in `searchObjects`
+        let mut filters = vec![filter];
+        if let Some(context) = ctx.data_opt::<SecurityContext>() {
+            filters.extend(acl_store_filters(&AclSearchFilter::for_context(context)));
+        }
 
         let query = SearchQuery {
-            filters: vec![filter],
-            sort: None,
+            filters,
+            sort: vec![],
             limit: None,
             offset: None,
+            search_after: None,
         };
 
         // Execute search
         let indexed_objects = search_store
             .search(&object_type, &query)
             .await
-            .map_err(|e| async_graphql::Error::new(format!("Spatial search error: {}", e)))?;
+            .map_err(ApiError::from)?;
 
         // Hydrate objects
         let hydrated = hydrator
             .hydrate_batch(&indexed_objects, object_type_def)
-            .map_err(|e| async_graphql::Error::new(format!("Hydration error: {}", e)))?;
+            .map_err(ApiError::from)?;
 
         // Convert to GraphQL results
         Ok(hydrated
             .into_iter()
-            .map(|h| {
-                let properties_json: Value =
-                    serde_json::to_value(&h.properties).unwrap_or_else(|_| serde_json::json!({}));
-                ObjectResult {
-                    object_type: h.object_type,
-                    object_id: h.object_id,
-                    title: h.title,
-                    properties: Json(properties_json),
-                }
-            })
+            .map(|h| hydrated_object_result(ctx, object_type_def, h, None))
             .collect())
     }
 
-    /// Temporal query - query objects by year/vintage
+    /// Temporal query - query objects by year/vintage. Objects whose ACL hides them from the
+    /// caller are left out.
     async fn temporal_query(
         &self,
         ctx: &Context<'_>,
@@ -436,83 +609,62 @@ impl QueryRoot {
         year_range_start: Option<i64>,
         year_range_end: Option<i64>,
         as_of_date: Option<String>, // ISO 8601 datetime string
+        include_links: Option<Vec<String>>,
+        // Only use events recorded on or before this ISO 8601 datetime, ignoring later corrections
+        as_known_at: Option<String>,
     ) -> FieldResult<Vec<ObjectResult>> {
-        let ontology = ctx.data::<Arc<Ontology>>()?;
+        let ontology = ctx.data::<OntologyHandle>()?.load();
 
         let object_type_def = ontology
             .get_object_type(&object_type)
-            .ok_or_else(|| async_graphql::Error::new("Object type not found"))?;
+            .ok_or_else(|| ApiError::not_found("Object type not found"))?;
 
         // Validate at least one filter provided
-        if year.is_none() && year_range_start.is_none() && as_of_date.is_none() {
-            return Err(async_graphql::Error::new(
-                "Must provide either year, year_range_start/year_range_end, or as_of_date",
-            ));
+        if year.is_none() && year_range_start.is_none() && as_of_date.is_none() && as_known_at.is_none() {
+            return Err(ApiError::invalid_argument("Must provide either year, year_range_start/year_range_end, as_of_date, or as_known_at").into());
         }
 
-        // Try in-memory store first — filter by the `year` property
-        let data_store = ctx.data::<Arc<tokio::sync::RwLock<HashMap<String, Vec<Value>>>>>();
-        if let Ok(store) = data_store {
-            let store_read = store.read().await;
-            if let Some(objects) = store_read.get(&object_type) {
-                let filtered: Vec<&Value> = objects
-                    .iter()
-                    .filter(|obj| {
-                        let obj_year = obj.get("year").and_then(|v| v.as_i64());
-                        match obj_year {
-                            None => false,
-                            Some(y) => {
-                                if let Some(target) = year {
-                                    y == target
-                                } else if let (Some(start), Some(end)) =
-                                    (year_range_start, year_range_end)
-                                {
-                                    y >= start && y <= end
-                                } else {
-                                    // as_of_date: all years up to now (EventLog is empty anyway)
-                                    true
-                                }
-                            }
-                        }
-                    })
-                    .collect();
-
-                let results = filtered
-                    .iter()
-                    .map(|obj| {
-                        let object_id = obj
-                            .get(&object_type_def.primary_key)
-                            .and_then(|v| v.as_str())
-                            .unwrap_or("unknown")
-                            .to_string();
-                        let title = object_type_def
-                            .title_key
-                            .as_ref()
-                            .and_then(|key| obj.get(key))
-                            .and_then(|v| v.as_str())
-                            .map(|s| s.to_string())
-                            .unwrap_or_else(|| object_id.clone());
-                        ObjectResult {
-                            object_type: object_type.clone(),
-                            object_id,
-                            title,
-                            properties: Json((*obj).clone()),
-                        }
-                    })
-                    .collect();
-
-                return Ok(results);
+        let include_links = include_links.unwrap_or_default();
+        for link_type in &include_links {
+            let link_type_def = ontology.get_link_type(link_type).ok_or_else(|| {
+                ApiError::not_found(format!("Link type '{}' not found", link_type))
+            })?;
+            if link_type_def.source != object_type && link_type_def.target != object_type {
+                return Err(ApiError::invalid_argument(format!(
+                    "Link type '{}' does not connect to object type '{}'",
+                    link_type, object_type
+                )).into());
             }
         }
 
-        // Fallback to versioning service (EventLog-backed)
+        // Objects and links as recorded in the event log
         let versioning = ctx.data::<Arc<time_query::TimeQuery>>()?;
         let hydrator = ctx.data::<ObjectHydrator>()?;
 
-        let historical_objects = if let Some(as_of_str) = as_of_date {
-            let as_of = chrono::DateTime::parse_from_rfc3339(&as_of_str)
-                .map_err(|e| async_graphql::Error::new(format!("Invalid date format: {}", e)))?
-                .with_timezone(&chrono::Utc);
+        let parse_date = |date: String| {
+            chrono::DateTime::parse_from_rfc3339(&date)
+                .map(|date| date.with_timezone(&chrono::Utc))
+                .map_err(|e| ApiError::invalid_argument(format!("Invalid date format: {}", e)))
+        };
+        let as_of = as_of_date.map(parse_date).transpose()?;
+        let as_known_at = as_known_at.map(parse_date).transpose()?;
+        let historical_objects = if let Some(as_known_at) = as_known_at {
+            let mut objects =
+                versioning.query_bitemporal(&object_type, as_of.unwrap_or_else(Utc::now), as_known_at);
+            let (start, end) = match year {
+                Some(year) => (Some(year), Some(year)),
+                None => (year_range_start, year_range_end),
+            };
+            if start.is_some() || end.is_some() {
+                objects.retain(|obj| match obj.properties.get("year") {
+                    Some(PropertyValue::Integer(obj_year)) => {
+                        start.is_none_or(|start| *obj_year >= start) && end.is_none_or(|end| *obj_year <= end)
+                    }
+                    _ => false,
+                });
+            }
+            objects
+        } else if let Some(as_of) = as_of {
             versioning.query_as_of_date(&object_type, as_of, year)
         } else if let (Some(start), Some(end)) = (year_range_start, year_range_end) {
             versioning.query_by_year_range(&object_type, start, end, None)
@@ -522,6 +674,9 @@ impl QueryRoot {
 
         let mut results = Vec::new();
         for historical in historical_objects {
+            if !object_readable(ctx, &historical.properties) {
+                continue;
+            }
             let mut indexed = indexing::store::IndexedObject::new(
                 historical.object_type.clone(),
                 historical.object_id.clone(),
@@ -530,14 +685,21 @@ impl QueryRoot {
             indexed.indexed_at = historical.reconstructed_at;
 
             if let Ok(hydrated) = hydrator.hydrate_from_indexed(&indexed, object_type_def) {
-                let properties_json: Value = serde_json::to_value(&hydrated.properties)
-                    .unwrap_or_else(|_| serde_json::json!({}));
-                results.push(ObjectResult {
-                    object_type: hydrated.object_type,
-                    object_id: hydrated.object_id,
-                    title: hydrated.title,
-                    properties: Json(properties_json),
+                let links = (!include_links.is_empty()).then(|| {
+                    historical_links_json(
+                        &ontology,
+                        versioning,
+                        object_type_def,
+                        &hydrated.object_id,
+                        &include_links,
+                        as_of.unwrap_or_else(Utc::now),
+                    )
                 });
+                let mut result = hydrated_object_result(ctx, object_type_def, hydrated, None);
+                if let (Some(links), Value::Object(properties)) = (links, &mut result.properties.0) {
+                    properties.insert("_links".to_string(), Value::Array(links));
+                }
+                results.push(result);
             }
         }
 
@@ -550,25 +712,6 @@ impl QueryRoot {
         ctx: &Context<'_>,
         object_type: String,
     ) -> FieldResult<Vec<i64>> {
-        // Try to get years from in-memory store first
-        let data_store = ctx.data::<Arc<tokio::sync::RwLock<HashMap<String, Vec<Value>>>>>();
-
-        if let Ok(store) = data_store {
-            let store_read = store.read().await;
-            if let Some(objects) = store_read.get(&object_type) {
-                let mut years: std::collections::HashSet<i64> = std::collections::HashSet::new();
-                for obj in objects {
-                    if let Some(year) = obj.get("year").and_then(|v| v.as_i64()) {
-                        years.insert(year);
-                    }
-                }
-                let mut years_vec: Vec<i64> = years.into_iter().collect();
-                years_vec.sort();
-                return Ok(years_vec);
-            }
-        }
-
-        // Fallback to versioning service
         let versioning = ctx.data::<Arc<time_query::TimeQuery>>();
         if let Ok(vq) = versioning {
             return Ok(vq.get_available_years(&object_type, None));
@@ -578,7 +721,120 @@ impl QueryRoot {
         Ok(vec![2010, 2020])
     }
 
-    /// Traverse graph with filters and aggregations
+    /// Apportion a numeric property of the `objectType` objects of `fromYear` to the tracts of
+    /// `toYear`, following the crosswalk objects of `crosswalkType` (`boundary_crosswalk` by
+    /// default) through any intermediate vintages. Integer totals are kept with `rounding`:
+    /// `largest_remainder` (the default) or `nearest`.
+    async fn apportion_across_crosswalk(
+        &self,
+        ctx: &Context<'_>,
+        object_type: String,
+        property: String,
+        from_year: i64,
+        to_year: i64,
+        crosswalk_type: Option<String>,
+        rounding: Option<String>,
+    ) -> FieldResult<Vec<ApportionedRow>> {
+        let ontology = ctx.data::<OntologyHandle>()?.load();
+        let search_store = ctx.data::<Arc<dyn SearchStore>>()?;
+
+        let object_type_def = ontology
+            .get_object_type(&object_type)
+            .ok_or_else(|| ApiError::not_found("Object type not found"))?;
+        if object_type_def.get_property(&property).is_none() {
+            return Err(ApiError::not_found(format!("Property '{}' not found", property)).into());
+        }
+        let crosswalk_type = crosswalk_type.unwrap_or_else(|| DEFAULT_CROSSWALK_TYPE.to_string());
+        if ontology.get_object_type(&crosswalk_type).is_none() {
+            return Err(ApiError::not_found(format!("Crosswalk object type '{}' not found", crosswalk_type)).into());
+        }
+        let rounding: ApportionRounding = match rounding {
+            Some(rounding) => serde_json::from_value(Value::String(rounding.clone())).map_err(|_| {
+                ApiError::invalid_argument(format!(
+                    "Unknown rounding '{}'; expected 'largest_remainder' or 'nearest'",
+                    rounding
+                ))
+            })?,
+            None => ApportionRounding::default(),
+        };
+
+        let everything = SearchQuery { filters: vec![], sort: vec![], limit: None, offset: None, search_after: None };
+        let crosswalks = search_store
+            .search(&crosswalk_type, &everything)
+            .await
+            .map_err(ApiError::from)?;
+        let links = crosswalks
+            .iter()
+            .map(|crosswalk| CrosswalkTraverser::link_from_properties(&crosswalk.properties))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| ApiError::validation(None, format!("Invalid crosswalk object: {}", e)))?;
+
+        let in_year = SearchQuery {
+            filters: vec![Filter {
+                property: "year".to_string(),
+                operator: indexing::store::FilterOperator::Equals,
+                value: PropertyValue::Integer(from_year),
+                distance: None,
+                case_insensitive: false,
+            }],
+            ..everything
+        };
+        let sources: Vec<(String, PropertyMap)> = search_store
+            .search(&object_type, &in_year)
+            .await
+            .map_err(ApiError::from)?
+            .into_iter()
+            .map(|indexed| (indexed.object_id, indexed.properties))
+            .collect();
+
+        let apportioned = CrosswalkTraverser::new(links)
+            .apportion(&property, &sources, from_year, to_year, rounding)
+            .map_err(|e| ApiError::validation(Some(&property), e))?;
+        let mut rows: Vec<ApportionedRow> = apportioned
+            .into_iter()
+            .map(|(target_id, value)| ApportionedRow {
+                target_id,
+                value: Json(serde_json::to_value(&value).unwrap_or(Value::Null)),
+            })
+            .collect();
+        rows.sort_by(|a, b| a.target_id.cmp(&b.target_id));
+        Ok(rows)
+    }
+
+    /// User edits whose conflict with refreshed source data, or with another user's edit,
+    /// waits for `resolveConflict`, optionally only those of one object type
+    async fn get_pending_conflicts(
+        &self,
+        ctx: &Context<'_>,
+        object_type: Option<String>,
+    ) -> FieldResult<Vec<PendingConflictOutput>> {
+        let pending = ctx.data::<Arc<PendingConflicts>>()?;
+        Ok(pending.list(object_type.as_deref()).iter().map(conflict_output).collect())
+    }
+
+    /// Edits of an object waiting to be applied, oldest first
+    async fn get_pending_edits(
+        &self,
+        ctx: &Context<'_>,
+        object_type: String,
+        object_id: String,
+    ) -> FieldResult<Vec<UserEditOutput>> {
+        let edits = ctx.data::<Arc<dyn EditQueue>>()?
+            .pending_edits(&object_type, &object_id).await
+            .map_err(ApiError::from)?;
+        Ok(edits.iter().map(edit_output).collect())
+    }
+
+    /// Every edit of objects with ID `objectId`, whatever its status, oldest first
+    async fn get_edit_history(&self, ctx: &Context<'_>, object_id: String) -> FieldResult<Vec<UserEditOutput>> {
+        let edits = ctx.data::<Arc<dyn EditQueue>>()?
+            .edit_history(&object_id).await
+            .map_err(ApiError::from)?;
+        Ok(edits.iter().map(edit_output).collect())
+    }
+
+    /// Traverse graph with filters and aggregations. With `explain`, the backend-native
+    /// traversal query is returned under `extensions.explain`.
     async fn traverse_graph(
         &self,
         ctx: &Context<'_>,
@@ -588,8 +844,9 @@ impl QueryRoot {
         max_hops: usize,
         aggregate_property: Option<String>,
         aggregate_operation: Option<String>, // "count", "sum", "avg", "min", "max"
+        explain: Option<bool>,
     ) -> FieldResult<TraversalResult> {
-        let ontology = ctx.data::<Arc<Ontology>>()?;
+        let ontology = ctx.data::<OntologyHandle>()?.load();
         let graph_store = ctx.data::<Arc<dyn GraphStore>>()?;
         let search_store = ctx.data::<Arc<dyn SearchStore>>()?;
         let hydrator = ctx.data::<ObjectHydrator>()?;
@@ -603,10 +860,10 @@ impl QueryRoot {
                 "min" => indexing::store::Aggregation::Min(prop.clone()),
                 "max" => indexing::store::Aggregation::Max(prop.clone()),
                 _ => {
-                    return Err(async_graphql::Error::new(format!(
+                    return Err(ApiError::invalid_argument(format!(
                         "Invalid aggregation operation: {}. Valid: count, sum, avg, min, max",
                         op
-                    )))
+                    )).into())
                 }
             };
 
@@ -619,7 +876,7 @@ impl QueryRoot {
             let result = graph_store
                 .traverse_with_aggregation(&object_id, &link_types, max_hops, &aggregation)
                 .await
-                .map_err(|e| async_graphql::Error::new(format!("Traversal error: {}", e)))?;
+                .map_err(ApiError::from)?;
 
             let agg_value_json: Value =
                 serde_json::to_value(&result.value).unwrap_or_else(|_| serde_json::Value::Null);
@@ -631,10 +888,15 @@ impl QueryRoot {
         }
 
         // Regular traversal
+        if explain.unwrap_or(false) {
+            if let Some(native) = graph_store.explain_traversal(&object_id, &link_types, max_hops) {
+                record_explain(ctx, "traverse", Value::String(native));
+            }
+        }
         let object_ids = graph_store
             .traverse(&object_id, &link_types, max_hops)
             .await
-            .map_err(|e| async_graphql::Error::new(format!("Traversal error: {}", e)))?;
+            .map_err(ApiError::from)?;
 
         Ok(TraversalResult {
             object_ids: object_ids.clone(),
@@ -643,7 +905,12 @@ impl QueryRoot {
         })
     }
 
-    /// Aggregate query - perform aggregations on objects
+    /// Aggregate query - perform aggregations on objects.
+    /// With `approximate`, aggregates are estimated (from a uniform sample of `sampleSize`
+    /// rows, or with Elasticsearch sketches) and tagged with a sample fraction and 95% error
+    /// bounds; exact is the default. With `includeArchived`, objects moved to the archive
+    /// are aggregated along with the live ones. With `explain`, the backend chosen to answer
+    /// and the reasons for it are returned under `extensions.explain`.
     async fn aggregate_objects(
         &self,
         ctx: &Context<'_>,
@@ -651,13 +918,16 @@ impl QueryRoot {
         aggregations: Vec<AggregationInput>,
         filters: Option<Vec<FilterInput>>,
         group_by: Option<Vec<String>>,
+        approximate: Option<bool>,
+        sample_size: Option<usize>,
+        include_archived: Option<bool>,
+        explain: Option<bool>,
     ) -> FieldResult<AggregationResult> {
-        let ontology = ctx.data::<Arc<Ontology>>()?;
-        let columnar_store = ctx.data::<Arc<dyn indexing::store::ColumnarStore>>()?;
+        let ontology = ctx.data::<OntologyHandle>()?.load();
 
         let object_type_def = ontology
             .get_object_type(&object_type)
-            .ok_or_else(|| async_graphql::Error::new("Object type not found"))?;
+            .ok_or_else(|| ApiError::not_found("Object type not found"))?;
 
         // Convert GraphQL aggregations to store aggregations
         let mut store_aggregations = Vec::new();
@@ -686,16 +956,16 @@ impl QueryRoot {
                                 pct,
                             )
                         } else {
-                            return Err(async_graphql::Error::new(format!(
+                            return Err(ApiError::invalid_argument(format!(
                                 "Invalid aggregation operation: {}. Valid: count, sum, avg, min, max, median, stddev, variance, distinct_count, p50, p95, etc.",
                                 agg_input.operation
-                            )));
+                            )).into());
                         }
                     } else {
-                        return Err(async_graphql::Error::new(format!(
+                        return Err(ApiError::invalid_argument(format!(
                             "Invalid aggregation operation: {}. Valid: count, sum, avg, min, max, median, stddev, variance, distinct_count, p50, p95, etc.",
                             agg_input.operation
-                        )));
+                        )).into());
                     }
                 }
             };
@@ -703,468 +973,133 @@ impl QueryRoot {
         }
 
         // Convert filters
-        let mut store_filters = Vec::new();
-        if let Some(filter_inputs) = filters {
-            for filter_input in filter_inputs {
-                store_filters.push(convert_filter_input(filter_input)?);
-            }
-        }
+        let store_filters = convert_filters(filters.unwrap_or_default(), &object_type_def.properties)?;
 
         let group_by_cols = group_by.unwrap_or_default();
-
-        // Try in-memory store before falling back to Parquet
-        let data_store = ctx.data::<Arc<tokio::sync::RwLock<HashMap<String, Vec<Value>>>>>();
-        if let Ok(store) = data_store {
-            let store_read = store.read().await;
-            if let Some(objects) = store_read.get(&object_type) {
-                // Apply filters
-                let filtered: Vec<&Value> = objects
-                    .iter()
-                    .filter(|obj| {
-                        store_filters.iter().all(|filter| {
-                            obj.get(&filter.property).map_or(false, |prop_val| {
-                                match &filter.operator {
-                                    indexing::store::FilterOperator::Equals => {
-                                        match &filter.value {
-                                            ontology_engine::PropertyValue::String(s) => {
-                                                prop_val.as_str().map_or(false, |v| v == s)
-                                            }
-                                            ontology_engine::PropertyValue::Integer(i) => {
-                                                prop_val.as_i64().map_or(false, |v| v == *i)
-                                            }
-                                            ontology_engine::PropertyValue::Double(d) => prop_val
-                                                .as_f64()
-                                                .map_or(false, |v| (v - d).abs() < 0.0001),
-                                            _ => false,
-                                        }
-                                    }
-                                    indexing::store::FilterOperator::GreaterThan => {
-                                        match &filter.value {
-                                            ontology_engine::PropertyValue::Integer(i) => {
-                                                prop_val.as_i64().map_or(false, |v| v > *i)
-                                            }
-                                            ontology_engine::PropertyValue::Double(d) => {
-                                                prop_val.as_f64().map_or(false, |v| v > *d)
-                                            }
-                                            _ => false,
-                                        }
-                                    }
-                                    indexing::store::FilterOperator::LessThan => {
-                                        match &filter.value {
-                                            ontology_engine::PropertyValue::Integer(i) => {
-                                                prop_val.as_i64().map_or(false, |v| v < *i)
-                                            }
-                                            ontology_engine::PropertyValue::Double(d) => {
-                                                prop_val.as_f64().map_or(false, |v| v < *d)
-                                            }
-                                            _ => false,
-                                        }
-                                    }
-                                    _ => true,
-                                }
-                            })
-                        })
-                    })
-                    .collect();
-
-                let total = filtered.len();
-
-                let compute_aggs = |items: &[&Value]| -> serde_json::Map<String, Value> {
-                    let mut row = serde_json::Map::new();
-                    for agg in &store_aggregations {
-                        match agg {
-                            indexing::store::Aggregation::Count => {
-                                row.insert("count".to_string(), Value::Number(items.len().into()));
-                            }
-                            indexing::store::Aggregation::Sum(prop) => {
-                                let sum: f64 = items
-                                    .iter()
-                                    .filter_map(|o| o.get(prop))
-                                    .filter_map(|v| v.as_f64())
-                                    .sum();
-                                row.insert(
-                                    format!("sum_{}", prop),
-                                    Value::Number(
-                                        serde_json::Number::from_f64(sum).unwrap_or(0.into()),
-                                    ),
-                                );
-                            }
-                            indexing::store::Aggregation::Avg(prop) => {
-                                let vals: Vec<f64> = items
-                                    .iter()
-                                    .filter_map(|o| o.get(prop))
-                                    .filter_map(|v| v.as_f64())
-                                    .collect();
-                                let avg = if vals.is_empty() {
-                                    0.0
-                                } else {
-                                    vals.iter().sum::<f64>() / vals.len() as f64
-                                };
-                                row.insert(
-                                    format!("avg_{}", prop),
-                                    Value::Number(
-                                        serde_json::Number::from_f64(avg).unwrap_or(0.into()),
-                                    ),
-                                );
-                            }
-                            indexing::store::Aggregation::Min(prop) => {
-                                let min = items
-                                    .iter()
-                                    .filter_map(|o| o.get(prop))
-                                    .filter_map(|v| v.as_f64())
-                                    .fold(f64::INFINITY, f64::min);
-                                let min_val = if min.is_finite() { min } else { 0.0 };
-                                row.insert(
-                                    format!("min_{}", prop),
-                                    Value::Number(
-                                        serde_json::Number::from_f64(min_val).unwrap_or(0.into()),
-                                    ),
-                                );
-                            }
-                            indexing::store::Aggregation::Max(prop) => {
-                                let max = items
-                                    .iter()
-                                    .filter_map(|o| o.get(prop))
-                                    .filter_map(|v| v.as_f64())
-                                    .fold(f64::NEG_INFINITY, f64::max);
-                                let max_val = if max.is_finite() { max } else { 0.0 };
-                                row.insert(
-                                    format!("max_{}", prop),
-                                    Value::Number(
-                                        serde_json::Number::from_f64(max_val).unwrap_or(0.into()),
-                                    ),
-                                );
-                            }
-                            indexing::store::Aggregation::Median(prop) => {
-                                let mut vals: Vec<f64> = items
-                                    .iter()
-                                    .filter_map(|o| o.get(prop))
-                                    .filter_map(|v| v.as_f64())
-                                    .collect();
-                                vals.sort_by(|a, b| {
-                                    a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal)
-                                });
-                                let median = if vals.is_empty() {
-                                    0.0
-                                } else if vals.len() % 2 == 0 {
-                                    (vals[vals.len() / 2 - 1] + vals[vals.len() / 2]) / 2.0
-                                } else {
-                                    vals[vals.len() / 2]
-                                };
-                                row.insert(
-                                    format!("median_{}", prop),
-                                    Value::Number(
-                                        serde_json::Number::from_f64(median).unwrap_or(0.into()),
-                                    ),
-                                );
-                            }
-                            indexing::store::Aggregation::StdDev(prop) => {
-                                let vals: Vec<f64> = items
-                                    .iter()
-                                    .filter_map(|o| o.get(prop))
-                                    .filter_map(|v| v.as_f64())
-                                    .collect();
-                                let mean = if vals.is_empty() {
-                                    0.0
-                                } else {
-                                    vals.iter().sum::<f64>() / vals.len() as f64
-                                };
-                                let variance = if vals.len() < 2 {
-                                    0.0
-                                } else {
-                                    vals.iter().map(|v| (v - mean).powi(2)).sum::<f64>()
-                                        / (vals.len() - 1) as f64
-                                };
-                                row.insert(
-                                    format!("stddev_{}", prop),
-                                    Value::Number(
-                                        serde_json::Number::from_f64(variance.sqrt())
-                                            .unwrap_or(0.into()),
-                                    ),
-                                );
-                            }
-                            indexing::store::Aggregation::Variance(prop) => {
-                                let vals: Vec<f64> = items
-                                    .iter()
-                                    .filter_map(|o| o.get(prop))
-                                    .filter_map(|v| v.as_f64())
-                                    .collect();
-                                let mean = if vals.is_empty() {
-                                    0.0
-                                } else {
-                                    vals.iter().sum::<f64>() / vals.len() as f64
-                                };
-                                let variance = if vals.len() < 2 {
-                                    0.0
-                                } else {
-                                    vals.iter().map(|v| (v - mean).powi(2)).sum::<f64>()
-                                        / (vals.len() - 1) as f64
-                                };
-                                row.insert(
-                                    format!("variance_{}", prop),
-                                    Value::Number(
-                                        serde_json::Number::from_f64(variance).unwrap_or(0.into()),
-                                    ),
-                                );
-                            }
-                            indexing::store::Aggregation::DistinctCount(prop) => {
-                                let distinct: std::collections::HashSet<String> = items
-                                    .iter()
-                                    .filter_map(|o| o.get(prop))
-                                    .map(|v| v.to_string())
-                                    .collect();
-                                row.insert(
-                                    format!("distinct_count_{}", prop),
-                                    Value::Number(distinct.len().into()),
-                                );
-                            }
-                            indexing::store::Aggregation::Percentile(prop, pct) => {
-                                let mut vals: Vec<f64> = items
-                                    .iter()
-                                    .filter_map(|o| o.get(prop))
-                                    .filter_map(|v| v.as_f64())
-                                    .collect();
-                                vals.sort_by(|a, b| {
-                                    a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal)
-                                });
-                                let idx = ((vals.len() as f64 * pct) as usize)
-                                    .min(vals.len().saturating_sub(1));
-                                let pct_val = vals.get(idx).copied().unwrap_or(0.0);
-                                row.insert(
-                                    format!("p{}_{}", (*pct * 100.0) as u8, prop),
-                                    Value::Number(
-                                        serde_json::Number::from_f64(pct_val).unwrap_or(0.into()),
-                                    ),
-                                );
-                            }
-                            _ => {}
-                        }
-                    }
-                    row
-                };
-
-                let rows: Vec<Value> = if group_by_cols.is_empty() {
-                    let row = compute_aggs(&filtered);
-                    vec![Value::Object(row)]
-                } else {
-                    let group_key = &group_by_cols[0];
-                    let mut groups: std::collections::HashMap<String, Vec<&Value>> =
-                        std::collections::HashMap::new();
-                    for obj in &filtered {
-                        let key = obj
-                            .get(group_key)
-                            .map(|v| v.to_string())
-                            .unwrap_or_default();
-                        groups.entry(key).or_default().push(obj);
-                    }
-                    let mut result_rows: Vec<Value> = groups
-                        .into_iter()
-                        .map(|(group_val, group_items)| {
-                            let mut row = compute_aggs(&group_items);
-                            row.insert(group_key.clone(), Value::String(group_val));
-                            Value::Object(row)
-                        })
-                        .collect();
-                    result_rows.sort_by(|a, b| {
-                        let ka = a.get(group_key).map(|v| v.to_string()).unwrap_or_default();
-                        let kb = b.get(group_key).map(|v| v.to_string()).unwrap_or_default();
-                        ka.cmp(&kb)
-                    });
-                    result_rows
-                };
-
-                return Ok(AggregationResult {
-                    rows: Json(Value::Array(rows)),
-                    total,
-                });
-            }
-        }
-
-        // Build analytics query for Parquet fallback
+        let targets = store_aggregations.iter().filter_map(indexing::store::Aggregation::property);
+        check_readable(
+            ctx,
+            object_type_def,
+            query_properties(&store_filters, &[]).chain(targets).chain(group_by_cols.iter().map(String::as_str)),
+        )?;
+        let sampling = approximate.unwrap_or(false).then(|| {
+            indexing::SamplingOptions::new(sample_size.unwrap_or(indexing::sampling::DEFAULT_SAMPLE_SIZE))
+        });
+
+        let include_archived = include_archived.unwrap_or(false);
+
+        // Build analytics query
         let query = indexing::store::AnalyticsQuery {
             aggregations: store_aggregations,
             filters: store_filters,
             group_by: group_by_cols,
+            sampling,
+            partitions: if include_archived { vec![ARCHIVED_PARTITION.to_string()] } else { vec![] },
         };
 
-        // Execute aggregation
-        let result = columnar_store
-            .query_analytics(&object_type, &query)
-            .await
-            .map_err(|e| async_graphql::Error::new(format!("Aggregation error: {}", e)))?;
-
-        // Convert results
-        let rows: Vec<serde_json::Value> = result
-            .rows
-            .iter()
-            .map(|row| {
-                let mut json_row = serde_json::Map::new();
-                for (key, value) in row {
-                    json_row.insert(
-                        key.clone(),
-                        serde_json::to_value(value).unwrap_or(serde_json::Value::Null),
-                    );
-                }
-                serde_json::Value::Object(json_row)
-            })
-            .collect();
+        // The planner picks the search index or the analytics store, retrying on its fallback
+        // when the chosen backend fails
+        let planner = query_planner(ctx);
+        let plan = plan_query(ctx, &planner, &QueryShape::aggregate(&object_type, &query), explain.unwrap_or(false))?;
+        let mut result = run_aggregation(ctx, &planner, plan.backend, &object_type, &query).await;
+        if let (Err(e), Some(fallback)) = (&result, plan.fallback) {
+            tracing::warn!("{} aggregation on '{}' failed, using {}: {}", plan.backend, object_type, fallback, e);
+            result = run_aggregation(ctx, &planner, fallback, &object_type, &query).await;
+        }
+        let result = result.map_err(ApiError::from)?;
 
-        Ok(AggregationResult {
-            rows: Json(Value::Array(rows)),
-            total: result.total,
-        })
+        Ok(AggregationResult::from_analytics(result))
     }
 
-    /// Call a function defined in the ontology
+    /// Call a function defined in the ontology. `parameters` are JSON strings;
+    /// `typedParameters` are `PropertyValue`s, which keep dates, references and doubles apart.
     async fn call_function(
         &self,
         ctx: &Context<'_>,
         function_id: String,
-        parameters: HashMap<String, String>, // JSON strings representing PropertyValues
+        #[graphql(default)] parameters: HashMap<String, String>, // JSON strings representing PropertyValues
+        typed_parameters: Option<HashMap<String, PropertyValueScalar>>,
     ) -> FieldResult<FunctionResult> {
-        let ontology = ctx.data::<Arc<Ontology>>()?;
-        let graph_store = ctx.data::<Arc<dyn GraphStore>>()?;
-        let search_store = ctx.data::<Arc<dyn SearchStore>>()?;
-
-        // Get function definition
-        let function_def = ontology.get_function_type(&function_id).ok_or_else(|| {
-            async_graphql::Error::new(format!("Function '{}' not found", function_id))
-        })?;
-
-        // Parse parameters from JSON strings to PropertyValues
-        let mut param_map = ontology_engine::PropertyMap::new();
-        for (key, json_value) in parameters {
-            let value: serde_json::Value = serde_json::from_str(&json_value).map_err(|e| {
-                async_graphql::Error::new(format!("Invalid parameter JSON for '{}': {}", key, e))
-            })?;
-
-            let prop_value = match value {
-                serde_json::Value::String(s) => ontology_engine::PropertyValue::String(s),
-                serde_json::Value::Number(n) => {
-                    if let Some(i) = n.as_i64() {
-                        ontology_engine::PropertyValue::Integer(i)
-                    } else if let Some(d) = n.as_f64() {
-                        ontology_engine::PropertyValue::Double(d)
-                    } else {
-                        return Err(async_graphql::Error::new(format!(
-                            "Invalid number for parameter '{}'",
-                            key
-                        )));
-                    }
-                }
-                serde_json::Value::Bool(b) => ontology_engine::PropertyValue::Boolean(b),
-                serde_json::Value::Array(arr) => {
-                    let prop_values: Result<Vec<ontology_engine::PropertyValue>, _> = arr
-                        .into_iter()
-                        .map(|v| match v {
-                            serde_json::Value::String(s) => {
-                                Ok(ontology_engine::PropertyValue::String(s))
-                            }
-                            serde_json::Value::Number(n) => {
-                                if let Some(i) = n.as_i64() {
-                                    Ok(ontology_engine::PropertyValue::Integer(i))
-                                } else if let Some(d) = n.as_f64() {
-                                    Ok(ontology_engine::PropertyValue::Double(d))
-                                } else {
-                                    Err("Invalid number in array")
-                                }
-                            }
-                            serde_json::Value::Bool(b) => {
-                                Ok(ontology_engine::PropertyValue::Boolean(b))
-                            }
-                            _ => Err("Unsupported array element type"),
-                        })
-                        .collect();
-                    ontology_engine::PropertyValue::Array(prop_values.map_err(|e| {
-                        async_graphql::Error::new(format!(
-                            "Invalid array for parameter '{}': {:?}",
-                            key, e
-                        ))
-                    })?)
-                }
-                _ => {
-                    return Err(async_graphql::Error::new(format!(
-                        "Unsupported parameter type for '{}'",
-                        key
-                    )))
-                }
-            };
-
-            param_map.insert(key, prop_value);
-        }
-
-        // Check cache if function is cacheable
-        let mut cached = false;
-        let cache_key = if function_def.cacheable {
-            // Generate cache key from function_id + serialized parameters
-            let mut hasher = DefaultHasher::new();
-            function_id.hash(&mut hasher);
-            // Serialize parameters for hashing
-            if let Ok(param_json) = serde_json::to_string(&param_map) {
-                param_json.hash(&mut hasher);
-            }
-            Some(hasher.finish())
-        } else {
-            None
-        };
-
-        // Try to get from cache
-        let result_value = if let Some(key) = cache_key {
-            // Get cache from context
-            if let Ok(cache) = ctx.data::<Arc<tokio::sync::RwLock<HashMap<u64, PropertyValue>>>>() {
-                let cache_read = cache.read().await;
-                if let Some(cached_value) = cache_read.get(&key) {
-                    cached = true;
-                    cached_value.clone()
-                } else {
-                    // Execute function and cache result
-                    let result = FunctionExecutor::execute(
-                        function_def,
-                        &param_map,
-                        None, // get_object_property callback - would need to be implemented
-                        None, // get_linked_objects callback - would need to be implemented
-                        None, // aggregate_linked_properties callback - would need to be implemented
-                    )
-                    .await
-                    .map_err(|e| {
-                        async_graphql::Error::new(format!("Function execution error: {}", e))
-                    })?;
-
-                    // Store in cache
-                    let result_value = result.value.clone();
-                    drop(cache_read);
-                    let mut cache_write = cache.write().await;
-                    cache_write.insert(key, result_value.clone());
-                    result_value
-                }
-            } else {
-                // No cache available, just execute
-                let result = FunctionExecutor::execute(function_def, &param_map, None, None, None)
-                    .await
-                    .map_err(|e| {
-                        async_graphql::Error::new(format!("Function execution error: {}", e))
-                    })?;
-                result.value
-            }
-        } else {
-            // Function is not cacheable, just execute
-            let result = FunctionExecutor::execute(function_def, &param_map, None, None, None)
-                .await
-                .map_err(|e| {
-                    async_graphql::Error::new(format!("Function execution error: {}", e))
-                })?;
-            result.value
-        };
+        let (result_value, cached) =
+            execute_function(ctx, &function_id, parameters, typed_parameters.unwrap_or_default()).await?;
 
         let value_json: Value =
             serde_json::to_value(&result_value).unwrap_or_else(|_| serde_json::Value::Null);
         Ok(FunctionResult {
             value: Json(value_json),
+            typed_value: (!result_value.is_null()).then(|| PropertyValueScalar(result_value)),
             cached,
         })
     }
 
-    /// Query objects implementing an interface (polymorphic query)
+    /// Call a function returning an object or a list of objects and hydrate the returned
+    /// references. As in search, objects the caller may not read are left out and the rest
+    /// are masked.
+    async fn call_function_objects(
+        &self,
+        ctx: &Context<'_>,
+        function_id: String,
+        #[graphql(default)] parameters: HashMap<String, String>, // JSON strings representing PropertyValues
+        typed_parameters: Option<HashMap<String, PropertyValueScalar>>,
+    ) -> FieldResult<Vec<ObjectResult>> {
+        let ontology = ctx.data::<OntologyHandle>()?.load();
+        let search_store = ctx.data::<Arc<dyn SearchStore>>()?;
+        let hydrator = ctx.data::<ObjectHydrator>()?;
+
+        let function_def = ontology.get_function_type(&function_id).ok_or_else(|| {
+            ApiError::not_found(format!("Function '{}' not found", function_id))
+        })?;
+        let object_type = function_def.return_type.object_type().ok_or_else(|| {
+            ApiError::invalid_argument(format!(
+                "Function '{}' returns {}, not objects; use callFunction",
+                function_id,
+                function_def.return_type.label()
+            ))
+        })?;
+        let object_type_def = ontology
+            .get_object_type(object_type)
+            .ok_or_else(|| ApiError::not_found("Object type not found"))?;
+
+        let (result_value, _) =
+            execute_function(ctx, &function_id, parameters, typed_parameters.unwrap_or_default()).await?;
+        let mut references = Vec::new();
+        collect_references(&result_value, &mut references);
+
+        let acl_filter = ctx
+            .data_opt::<SecurityContext>()
+            .map(AclSearchFilter::for_context);
+        let mut results = Vec::new();
+        for reference in references {
+            let object_id = match ObjectRef::parse(&reference, Some(object_type)) {
+                Ok(parsed) if parsed.object_type == object_type => parsed.object_id,
+                _ => reference,
+            };
+            let Some(indexed) = search_store
+                .get_object(object_type, &object_id)
+                .await
+                .map_err(ApiError::from)?
+            else {
+                continue;
+            };
+            if acl_filter.as_ref().is_some_and(|acl_filter| !acl_admits(acl_filter, &indexed.properties)) {
+                continue;
+            }
+            let hydrated = hydrator
+                .hydrate_from_indexed(&indexed, object_type_def)
+                .map_err(ApiError::from)?;
+            results.push(hydrated_object_result(ctx, object_type_def, hydrated, None));
+        }
+
+        Ok(results)
+    }
+
+    /// Query objects implementing an interface (polymorphic query). Filters and sort keys
+    /// name interface properties; implementers lacking a filtered property are left out.
+    /// Results from every implementer are merged in one order (the sort keys, then object
+    /// type and ID) before `offset` and `limit` apply. With `projectToInterface`, each object
+    /// carries exactly the interface's properties under their interface IDs. Objects missing
+    /// a required interface property are flagged in `missingInterfaceProperties`, or left
+    /// out with `strict`. Applies the same ACL restriction as `searchObjects`.
     async fn query_interface(
         &self,
         ctx: &Context<'_>,
@@ -1172,14 +1107,17 @@ impl QueryRoot {
         filters: Option<Vec<FilterInput>>,
         limit: Option<usize>,
         offset: Option<usize>,
+        sort: Option<Vec<SortInput>>,
+        project_to_interface: Option<bool>,
+        strict: Option<bool>,
     ) -> FieldResult<Vec<ObjectResult>> {
-        let ontology = ctx.data::<Arc<Ontology>>()?;
+        let ontology = ctx.data::<OntologyHandle>()?.load();
         let search_store = ctx.data::<Arc<dyn SearchStore>>()?;
         let hydrator = ctx.data::<ObjectHydrator>()?;
 
         // Get interface definition
         let interface = ontology.get_interface(&interface_id).ok_or_else(|| {
-            async_graphql::Error::new(format!("Interface '{}' not found", interface_id))
+            ApiError::not_found(format!("Interface '{}' not found", interface_id))
         })?;
 
         // Get all object types that implement this interface
@@ -1190,54 +1128,125 @@ impl QueryRoot {
             return Ok(Vec::new());
         }
 
-        // Query each implementing object type and combine results
-        let mut all_results = Vec::new();
+        // Operators and values are checked once, against the interface's property types
+        let filter_inputs = filters.unwrap_or_default();
+        convert_filters(filter_inputs.clone(), &interface.properties)?;
 
-        // Convert filters once for all object types
-        let mut store_filters = Vec::new();
-        if let Some(filter_inputs) = filters {
-            for filter_input in filter_inputs {
-                store_filters.push(convert_filter_input(filter_input)?);
-            }
+        let sort_keys: Vec<SortOption> = sort
+            .unwrap_or_default()
+            .into_iter()
+            .map(|input| SortOption {
+                property: input.property,
+                ascending: input.ascending.unwrap_or(true),
+                keyword_subfield: false,
+            })
+            .collect();
+        if let Some(key) = sort_keys.iter().find(|key| !interface.properties.iter().any(|p| p.id == key.property)) {
+            return Err(ApiError::invalid_argument(format!(
+                "Interface '{}' has no property '{}' to sort by",
+                interface_id, key.property
+            )).into());
         }
 
+        let offset = offset.unwrap_or(0);
+        let strict = strict.unwrap_or(false);
+        // Each implementer contributes at most the first `offset + limit` objects in the merged
+        // order. Strict queries drop objects after fetching, so they need every match.
+        let window = if strict { None } else { limit.map(|limit| offset + limit) };
+
+        // Restricted to objects the caller's ACL principals can see, as in `searchObjects`
+        let acl_filters = ctx
+            .data_opt::<SecurityContext>()
+            .map(|context| acl_store_filters(&AclSearchFilter::for_context(context)))
+            .unwrap_or_default();
+
+        // Query each implementing object type, keeping each object's interface view for ordering
+        let mut all_results = Vec::new();
         for object_type in implementers {
+            // Filters name interface properties; implementers without one of them are skipped
+            let local_inputs = match implementer_filters(&filter_inputs, &interface_id, object_type) {
+                Ok(local_inputs) => local_inputs,
+                Err(property) => {
+                    tracing::warn!(
+                        "skipping '{}' in query on interface '{}': it has no property '{}'",
+                        object_type.id, interface_id, property
+                    );
+                    continue;
+                }
+            };
+            let mut store_filters = convert_filters(local_inputs, &object_type.properties)?;
+
+            // The merged order restricted to this implementer: sort keys it has (the others are
+            // missing on all its objects), then object ID
+            let mut local_sort: Vec<SortOption> = sort_keys
+                .iter()
+                .filter_map(|key| {
+                    let local = object_type.local_property_id(&interface_id, &key.property);
+                    object_type
+                        .get_property(local)
+                        .map(|property| SortOption::for_property(object_type, property.id.clone(), key.ascending))
+                })
+                .collect();
+            local_sort.push(SortOption::for_property(object_type, object_type.primary_key.clone(), true));
+            check_indexed(object_type, &store_filters, &local_sort)?;
+            check_readable(ctx, object_type, query_properties(&store_filters, &local_sort))?;
+            store_filters.extend(acl_filters.iter().cloned());
+
             let query = SearchQuery {
-                filters: store_filters.clone(),
-                sort: None,
-                limit,
-                offset,
+                filters: store_filters,
+                sort: local_sort,
+                limit: window,
+                offset: None,
+                search_after: None,
             };
 
             // Search objects of this type
             let indexed_objects = search_store
                 .search(&object_type.id, &query)
                 .await
-                .map_err(|e| async_graphql::Error::new(format!("Search error: {}", e)))?;
+                .map_err(ApiError::from)?;
 
             // Hydrate and add to results
             let hydrated = hydrator
                 .hydrate_batch(&indexed_objects, object_type)
-                .map_err(|e| async_graphql::Error::new(format!("Hydration error: {}", e)))?;
+                .map_err(ApiError::from)?;
 
             for h in hydrated {
-                let properties_json: Value =
-                    serde_json::to_value(&h.properties).unwrap_or_else(|_| serde_json::json!({}));
-                all_results.push(ObjectResult {
-                    object_type: h.object_type,
-                    object_id: h.object_id,
-                    title: h.title,
-                    properties: Json(properties_json),
-                });
+                let mut result = hydrated_object_result(ctx, object_type, h, None);
+                let interface_view = interface_projection(&interface.properties, &interface_id, object_type, &result.properties.0);
+                let missing: Vec<String> = interface
+                    .properties
+                    .iter()
+                    .filter(|p| p.required && interface_view[&p.id].is_null())
+                    .map(|p| p.id.clone())
+                    .collect();
+                if strict && !missing.is_empty() {
+                    continue;
+                }
+                if project_to_interface.unwrap_or(false) {
+                    result.properties = Json(interface_view.clone());
+                }
+                result.missing_interface_properties = (!missing.is_empty()).then_some(missing);
+                all_results.push((interface_view, result));
             }
         }
 
-        Ok(all_results)
+        all_results.sort_by(|(a_view, a), (b_view, b)| {
+            compare_json_objects(a_view, b_view, &sort_keys)
+                .then_with(|| a.object_type.cmp(&b.object_type))
+                .then_with(|| a.object_id.cmp(&b.object_id))
+        });
+        Ok(all_results
+            .into_iter()
+            .map(|(_, result)| result)
+            .skip(offset)
+            .take(limit.unwrap_or(usize::MAX))
+            .collect())
     }
 
     /// Get all available functions
     async fn get_functions(&self, ctx: &Context<'_>) -> FieldResult<Vec<FunctionDefinition>> {
-        let ontology = ctx.data::<Arc<Ontology>>()?;
+        let ontology = ctx.data::<OntologyHandle>()?.load();
 
         let functions: Vec<FunctionDefinition> = ontology
             .function_types()
@@ -1245,12 +1254,7 @@ impl QueryRoot {
                 let parameters: Vec<PropertyOutput> = f
                     .parameters
                     .iter()
-                    .map(|p| PropertyOutput {
-                        id: p.id.clone(),
-                        display_name: p.display_name.clone(),
-                        property_type: format!("{:?}", p.property_type),
-                        required: p.required,
-                    })
+                    .map(PropertyOutput::from_property)
                     .collect();
 
                 FunctionDefinition {
@@ -1260,6 +1264,7 @@ impl QueryRoot {
                     parameters,
                     return_type: format!("{:?}", f.return_type),
                     cacheable: f.cacheable,
+                    target_interface: f.target_interface.clone(),
                 }
             })
             .collect();
@@ -1268,6 +1273,7 @@ impl QueryRoot {
     }
 
     /// Execute a function with parameters
+    #[graphql(deprecation = "Use `callFunction` instead. Deprecated in API 2.0; removed in API 3.0.")]
     async fn execute_function(
         &self,
         ctx: &Context<'_>,
@@ -1275,12 +1281,12 @@ impl QueryRoot {
         parameters: HashMap<String, String>, // JSON strings
     ) -> FieldResult<FunctionResult> {
         // Use existing call_function implementation
-        self.call_function(ctx, function_id, parameters).await
+        self.call_function(ctx, function_id, parameters, None).await
     }
 
     /// Get all available interfaces
     async fn get_interfaces(&self, ctx: &Context<'_>) -> FieldResult<Vec<InterfaceDefinition>> {
-        let ontology = ctx.data::<Arc<Ontology>>()?;
+        let ontology = ctx.data::<OntologyHandle>()?.load();
 
         let search_store = ctx.data::<Arc<dyn SearchStore>>()?;
 
@@ -1289,12 +1295,7 @@ impl QueryRoot {
             let properties: Vec<PropertyOutput> = i
                 .properties
                 .iter()
-                .map(|p| PropertyOutput {
-                    id: p.id.clone(),
-                    display_name: p.display_name.clone(),
-                    property_type: format!("{:?}", p.property_type),
-                    required: p.required,
-                })
+                .map(PropertyOutput::from_property)
                 .collect();
 
             // Get implementers
@@ -1310,9 +1311,10 @@ impl QueryRoot {
                 // Note: For exact counts, we'd need a count() method in SearchStore
                 let count_query = SearchQuery {
                     filters: vec![],
-                    sort: None,
+                    sort: vec![],
                     limit: Some(1), // Just check existence
                     offset: None,
+                    search_after: None,
                 };
 
                 let count = match search_store.count_objects(&ot.id, None).await {
@@ -1329,6 +1331,7 @@ impl QueryRoot {
             interfaces.push(InterfaceDefinition {
                 id: i.id.clone(),
                 display_name: i.display_name.clone(),
+                extends: i.extends.clone(),
                 properties,
                 implementers,
             });
@@ -1338,6 +1341,7 @@ impl QueryRoot {
     }
 
     /// Query objects by interface (alias for query_interface)
+    #[graphql(deprecation = "Use `queryInterface` instead. Deprecated in API 2.0; removed in API 3.0.")]
     async fn query_by_interface(
         &self,
         ctx: &Context<'_>,
@@ -1347,10 +1351,74 @@ impl QueryRoot {
         offset: Option<usize>,
     ) -> FieldResult<Vec<ObjectResult>> {
         // Use existing query_interface implementation
-        self.query_interface(ctx, interface_id, filters, limit, offset)
+        self.query_interface(ctx, interface_id, filters, limit, offset, None, None, None)
             .await
     }
 
+    /// Admin: the most recent store queries slower than the configured threshold, newest
+    /// first. Empty when query logging is not enabled.
+    async fn slow_queries(
+        &self,
+        ctx: &Context<'_>,
+        limit: Option<usize>,
+    ) -> FieldResult<Vec<SlowQueryOutput>> {
+        let Some(log) = ctx.data_opt::<Arc<QueryLog>>() else {
+            return Ok(Vec::new());
+        };
+        Ok(log
+            .slow_queries(limit.unwrap_or(50))
+            .into_iter()
+            .map(SlowQueryOutput::from)
+            .collect())
+    }
+
+    /// Admin: registered change triggers
+    async fn change_triggers(&self, ctx: &Context<'_>) -> FieldResult<Vec<ChangeTriggerOutput>> {
+        let Some(triggers) = ctx.data_opt::<ChangeTriggerRegistry>() else {
+            return Ok(Vec::new());
+        };
+        Ok(triggers.list().into_iter().map(ChangeTriggerOutput::from).collect())
+    }
+
+    /// Admin: progress of a background job started by an admin mutation, e.g.
+    /// `startConsistencyCheck`. Null for unknown job IDs, and for jobs another user started
+    /// unless the caller is an `admin`.
+    async fn job(&self, ctx: &Context<'_>, job_id: String) -> FieldResult<Option<JobOutput>> {
+        let Some(jobs) = ctx.data_opt::<JobRegistry>() else {
+            return Ok(None);
+        };
+        Ok(jobs.get(&job_id).filter(|job| job_visible(ctx, job)).map(JobOutput::from))
+    }
+
+    /// Progress of an export started with `startExport`, and the exported file once complete.
+    /// Only the user who started the export, or an `admin`, sees it.
+    async fn export_status(&self, ctx: &Context<'_>, job_id: String) -> FieldResult<Option<ExportStatusOutput>> {
+        let Some(jobs) = ctx.data_opt::<JobRegistry>() else {
+            return Ok(None);
+        };
+        let Some(progress) = jobs.get(&job_id).filter(|job| job.kind == EXPORT_JOB_KIND && job_visible(ctx, job)) else {
+            return Ok(None);
+        };
+        let output = progress
+            .result
+            .clone()
+            .and_then(|result| serde_json::from_value::<ExportOutput>(result).ok());
+        Ok(Some(ExportStatusOutput {
+            job_id: progress.job_id.clone(),
+            status: progress.status.as_str().to_string(),
+            processed: progress.processed as u64,
+            total: progress.total.map(|t| t as u64),
+            path: output.as_ref().map(|o| o.path.clone()),
+            format: output.as_ref().map(|o| o.format.extension().to_string()),
+            rows: output.as_ref().map(|o| o.rows as u64),
+            bytes: output.as_ref().map(|o| o.bytes),
+            error: progress.error.clone(),
+            started_at: progress.started_at.to_rfc3339(),
+            finished_at: progress.finished_at.map(|t| t.to_rfc3339()),
+            expires_at: jobs.expires_at(&progress).map(|t| t.to_rfc3339()),
+        }))
+    }
+
     /// Get data quality metrics for an object type or property
     async fn data_quality_metrics(
         &self,
@@ -1398,6 +1466,18 @@ impl QueryRoot {
         object_type: String,
         object_id: Option<String>,
     ) -> FieldResult<UsageMetricsResult> {
+        // Undeclared properties written to the type, counted by the validating search store
+        let unknown_properties = ctx
+            .data_opt::<PropertyDrift>()
+            .map(|drift| {
+                drift
+                    .for_type(&object_type)
+                    .into_iter()
+                    .map(|(property, count)| UnknownPropertyCount { property, count })
+                    .collect()
+            })
+            .unwrap_or_default();
+
         // For now, return placeholder - actual implementation would fetch from usage tracker
         Ok(UsageMetricsResult {
             object_type,
@@ -1407,6 +1487,7 @@ impl QueryRoot {
             query_frequency: 0.0,
             edit_count: 0,
             traversed_count: 0,
+            unknown_properties,
         })
     }
 
@@ -1461,20 +1542,158 @@ impl QueryRoot {
         })
     }
 
+    /// Property groups of an object type in display order, followed by an `ungrouped` group
+    /// of the properties no group names. Display names use `locale` when a translation exists.
+    async fn get_property_groups(
+        &self,
+        ctx: &Context<'_>,
+        object_type: String,
+        locale: Option<String>,
+    ) -> FieldResult<Vec<PropertyGroupOutput>> {
+        let ontology = ctx.data::<OntologyHandle>()?.load();
+        let object_type_def = ontology
+            .get_object_type(&object_type)
+            .ok_or_else(|| ApiError::not_found("Object type not found"))?;
+        Ok(property_group_outputs(object_type_def, locale.as_deref(), None))
+    }
+
+    /// JSON-Schema-like form for an action's parameters (`actionTypeId`) or for editing
+    /// objects of a type (`objectType`), generated from the property definitions. Titles
+    /// use `locale` when a translation exists; deprecated properties are left out unless
+    /// `includeDeprecated` is set.
+    async fn form_schema(
+        &self,
+        ctx: &Context<'_>,
+        action_type_id: Option<String>,
+        object_type: Option<String>,
+        locale: Option<String>,
+        include_deprecated: Option<bool>,
+    ) -> FieldResult<Json<Value>> {
+        let ontology = ctx.data::<OntologyHandle>()?.load();
+        let options = FormSchemaOptions {
+            locale,
+            include_deprecated: include_deprecated.unwrap_or(false),
+        };
+        let schema = match (action_type_id, object_type) {
+            (Some(action_type_id), None) => {
+                let action = ontology.get_action_type(&action_type_id).ok_or_else(|| {
+                    ApiError::not_found(format!("Action type '{}' not found", action_type_id))
+                })?;
+                action_form_schema(action, &options)
+            }
+            (None, Some(object_type)) => {
+                let object_type_def = ontology.get_object_type(&object_type).ok_or_else(|| {
+                    ApiError::not_found(format!("Object type '{}' not found", object_type))
+                })?;
+                object_form_schema(object_type_def, &options)
+            }
+            _ => {
+                return Err(ApiError::invalid_argument("Pass exactly one of actionTypeId and objectType").into())
+            }
+        };
+        Ok(Json(schema))
+    }
+
+    /// JSON Schema (draft 2020-12) of objects of a type, for validating payloads outside
+    /// the server; the same document `ontology-compiler --emit-json-schema` writes
+    async fn object_type_json_schema(&self, ctx: &Context<'_>, object_type: String) -> FieldResult<Json<Value>> {
+        let ontology = ctx.data::<OntologyHandle>()?.load();
+        let object_type_def = ontology.get_object_type(&object_type).ok_or_else(|| {
+            ApiError::not_found(format!("Object type '{}' not found", object_type))
+        })?;
+        Ok(Json(object_type_def.to_json_schema()))
+    }
+
     /// Get all object types
     async fn get_object_types(&self, ctx: &Context<'_>) -> FieldResult<Vec<ObjectTypeResult>> {
-        let ontology = ctx.data::<Arc<Ontology>>()?;
+        let ontology = ctx.data::<OntologyHandle>()?.load();
 
         let object_types: Vec<ObjectTypeResult> = ontology
             .object_types()
             .map(|ot| ObjectTypeResult {
                 id: ot.id.clone(),
                 display_name: ot.display_name.clone(),
+                primary_key: ot.primary_key.clone(),
+                default_sort: ot.default_sort.as_ref().map(|s| SortOutput {
+                    property: s.property.clone(),
+                    ascending: s.ascending,
+                }),
+                properties: ot
+                    .properties_in_display_order()
+                    .into_iter()
+                    .map(PropertyOutput::from_property)
+                    .collect(),
             })
             .collect();
 
         Ok(object_types)
     }
+
+    /// Get all action types, by ID, with the parameters `executeAction` takes. `formSchema`
+    /// renders the same parameters as a form.
+    async fn get_action_types(&self, ctx: &Context<'_>) -> FieldResult<Vec<ActionTypeResult>> {
+        let ontology = ctx.data::<OntologyHandle>()?.load();
+
+        let mut definitions: Vec<_> = ontology.action_types().collect();
+        definitions.sort_by(|a, b| a.id.cmp(&b.id));
+        Ok(definitions
+            .into_iter()
+            .map(|action_type| {
+                let validation = action_type.validation.as_ref();
+                ActionTypeResult {
+                    id: action_type.id.clone(),
+                    display_name: action_type.display_name_for(None).to_string(),
+                    parameters: action_type.parameters.iter().map(LinkPropertyOutput::from_property).collect(),
+                    required_roles: validation.map(|v| v.required_roles.clone()).unwrap_or_default(),
+                    required_badges: validation.map(|v| v.required_badges.clone()).unwrap_or_default(),
+                    target_interface: action_type.target_interface.clone(),
+                }
+            })
+            .collect())
+    }
+
+    /// Get all link types, by ID. Bidirectional links are listed once per direction, so
+    /// the implied target-to-source traversal needs no special case.
+    async fn get_link_types(&self, ctx: &Context<'_>) -> FieldResult<Vec<LinkTypeResult>> {
+        let ontology = ctx.data::<OntologyHandle>()?.load();
+
+        let mut definitions: Vec<_> = ontology.link_types().collect();
+        definitions.sort_by(|a, b| a.id.cmp(&b.id));
+        let mut link_types = Vec::new();
+        for link_type in definitions {
+            link_types.push(LinkTypeResult::from_link_type(link_type, "outgoing"));
+            if link_type.bidirectional {
+                link_types.push(LinkTypeResult::from_link_type(link_type, "incoming"));
+            }
+        }
+        Ok(link_types)
+    }
+
+    /// Link types with `objectType` as source (`outgoing`) or target (`incoming`); a link
+    /// type from a type to itself is listed in both directions
+    async fn get_link_types_for_object_type(
+        &self,
+        ctx: &Context<'_>,
+        object_type: String,
+    ) -> FieldResult<Vec<LinkTypeResult>> {
+        let ontology = ctx.data::<OntologyHandle>()?.load();
+        if ontology.get_object_type(&object_type).is_none() {
+            return Err(ApiError::not_found(format!("Object type '{}' not found", object_type)).into());
+        }
+
+        let mut definitions: Vec<_> = ontology.link_types().collect();
+        definitions.sort_by(|a, b| a.id.cmp(&b.id));
+        let mut link_types = Vec::new();
+        for link_type in definitions {
+            if link_type.source == object_type {
+                link_types.push(LinkTypeResult::from_link_type(link_type, "outgoing"));
+            }
+            if link_type.target == object_type {
+                link_types.push(LinkTypeResult::from_link_type(link_type, "incoming"));
+            }
+        }
+        Ok(link_types)
+    }
 }
 
 /// Input for aggregation operations
@@ -1486,131 +1705,1741 @@ struct AggregationInput {
 
 /// GraphQL result type for aggregations
 #[derive(SimpleObject)]
+#[graphql(complex)]
 pub struct AggregationResult {
+    #[graphql(skip)]
     pub rows: Json<Value>, // Proper JSON array instead of stringified JSON
     pub total: usize,
+    /// Fraction of rows aggregated, set when the result was computed from a sample
+    pub sample_fraction: Option<f64>,
+    /// 95% `{lower, upper}` bounds per aggregate column, parallel to `rows`
+    pub error_bounds: Option<Json<Value>>,
+}
+
+#[ComplexObject]
+impl AggregationResult {
+    /// Result rows; string-encoded for API 1 clients in compat mode
+    async fn rows(&self, ctx: &Context<'_>) -> Json<Value> {
+        json_field(ctx, &self.rows)
+    }
+}
+
+impl AggregationResult {
+    fn from_analytics(result: indexing::store::AnalyticsResult) -> Self {
+        let rows: Vec<Value> = result
+            .rows
+            .iter()
+            .map(|row| {
+                let mut json_row = serde_json::Map::new();
+                for (key, value) in row {
+                    json_row.insert(
+                        key.clone(),
+                        serde_json::to_value(value).unwrap_or(serde_json::Value::Null),
+                    );
+                }
+                Value::Object(json_row)
+            })
+            .collect();
+        let error_bounds = (!result.error_bounds.is_empty())
+            .then(|| serde_json::to_value(&result.error_bounds).ok())
+            .flatten()
+            .map(Json);
+
+        Self {
+            rows: Json(Value::Array(rows)),
+            total: result.total,
+            sample_fraction: result.sample_fraction,
+            error_bounds,
+        }
+    }
 }
 
-/// Input for search filters
+/// Input for sorting search results
 #[derive(InputObject)]
-struct FilterInput {
+struct SortInput {
     property: String,
-    operator: String,
-    value: String, // Keep as string for input parsing - PropertyValue is complex to represent as GraphQL input
-    distance: Option<f64>, // For spatial WithinDistance operator
-}
-
-/// Convert FilterInput to Filter
-fn convert_filter_input(filter_input: FilterInput) -> FieldResult<Filter> {
-    // Parse operator
-    let operator = match filter_input.operator.to_lowercase().as_str() {
-        "equals" | "eq" => indexing::store::FilterOperator::Equals,
-        "notequals" | "ne" => indexing::store::FilterOperator::NotEquals,
-        "greaterthan" | "gt" => indexing::store::FilterOperator::GreaterThan,
-        "lessthan" | "lt" => indexing::store::FilterOperator::LessThan,
-        "greaterthanorequal" | "gte" => indexing::store::FilterOperator::GreaterThanOrEqual,
-        "lessthanorequal" | "lte" => indexing::store::FilterOperator::LessThanOrEqual,
-        "contains" => indexing::store::FilterOperator::Contains,
-        "startswith" => indexing::store::FilterOperator::StartsWith,
-        "endswith" => indexing::store::FilterOperator::EndsWith,
-        "in" => indexing::store::FilterOperator::In,
-        "notin" => indexing::store::FilterOperator::NotIn,
-        "containsgeometry" => indexing::store::FilterOperator::ContainsGeometry,
-        "intersects" => indexing::store::FilterOperator::Intersects,
-        "within" => indexing::store::FilterOperator::Within,
-        "withindistance" => indexing::store::FilterOperator::WithinDistance,
+    ascending: Option<bool>, // Defaults to ascending
+}
+
+/// How GeoJSON properties are returned, from the `simplifyTolerance`, `geometryPrecision`
+/// and `omitGeometry` arguments
+fn geometry_output(
+    simplify_tolerance: Option<f64>,
+    geometry_precision: Option<u32>,
+    omit_geometry: Option<bool>,
+) -> Result<GeometryOutput, ApiError> {
+    let output = GeometryOutput {
+        simplify_tolerance,
+        precision: geometry_precision,
+        omit: omit_geometry.unwrap_or(false),
+    };
+    output.validate().map_err(ApiError::invalid_argument)?;
+    Ok(output)
+}
+
+/// Resolve the effective sort keys for a search on an object type
+fn resolve_sort(object_type_def: &ObjectType, sort: Option<Vec<SortInput>>) -> Vec<SortOption> {
+    match sort {
+        Some(inputs) if !inputs.is_empty() => inputs
+            .into_iter()
+            .map(|input| SortOption::for_property(object_type_def, input.property, input.ascending.unwrap_or(true)))
+            .collect(),
         _ => {
-            return Err(async_graphql::Error::new(format!(
-                "Invalid filter operator: {}",
-                filter_input.operator
-            )))
+            let default_sort = object_type_def.effective_default_sort();
+            vec![SortOption::for_property(object_type_def, default_sort.property, default_sort.ascending)]
         }
-    };
+    }
+}
 
-    // Parse value from JSON string
-    let value = serde_json::from_str::<serde_json::Value>(&filter_input.value)
-        .map_err(|e| async_graphql::Error::new(format!("Invalid filter value JSON: {}", e)))?;
+/// Reject filters and sorts on properties the search index cannot serve, before the
+/// backend fails on them
+pub(crate) fn check_indexed(object_type_def: &ObjectType, filters: &[Filter], sort: &[SortOption]) -> FieldResult<()> {
+    for filter in filters {
+        object_type_def
+            .check_filterable(&filter.property)
+            .map_err(ApiError::invalid_argument)?;
+    }
+    for sort in sort {
+        object_type_def
+            .check_sortable(&sort.property)
+            .map_err(ApiError::invalid_argument)?;
+    }
+    Ok(())
+}
 
-    let property_value: ontology_engine::PropertyValue = serde_json::from_value(value)
-        .map_err(|e| async_graphql::Error::new(format!("Failed to parse PropertyValue: {}", e)))?;
+/// The properties a query filters and sorts on
+pub(crate) fn query_properties<'a>(filters: &'a [Filter], sort: &'a [SortOption]) -> impl Iterator<Item = &'a str> {
+    filters
+        .iter()
+        .map(|filter| filter.property.as_str())
+        .chain(sort.iter().map(|key| key.property.as_str()))
+}
 
-    Ok(Filter {
-        property: filter_input.property,
-        operator,
-        value: property_value,
-        distance: filter_input.distance,
-    })
+/// An object's values for an interface's properties, keyed by interface property ID and
+/// null where the object has none
+fn interface_projection(
+    interface_properties: &[Property],
+    interface_id: &str,
+    object_type: &ObjectType,
+    properties: &Value,
+) -> Value {
+    Value::Object(
+        interface_properties
+            .iter()
+            .map(|p| {
+                let local = object_type.local_property_id(interface_id, &p.id);
+                (p.id.clone(), properties.get(local).cloned().unwrap_or(Value::Null))
+            })
+            .collect(),
+    )
 }
 
-/// GraphQL result type for objects
-#[derive(SimpleObject)]
-pub struct ObjectResult {
-    pub object_type: String,
-    pub object_id: String,
-    pub title: String,
-    pub properties: Json<Value>, // Proper JSON type instead of stringified JSON
+/// Order two objects by sort keys in priority order; a missing (or null) sort property
+/// always comes last
+fn compare_json_objects(a: &Value, b: &Value, sort: &[SortOption]) -> Ordering {
+    sort.iter()
+        .map(|key| {
+            let a_value = a.get(&key.property).filter(|v| !v.is_null());
+            let b_value = b.get(&key.property).filter(|v| !v.is_null());
+            match (a_value, b_value) {
+                (Some(x), Some(y)) => {
+                    let ordering = compare_json_values(x, y);
+                    if key.ascending {
+                        ordering
+                    } else {
+                        ordering.reverse()
+                    }
+                }
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => Ordering::Equal,
+            }
+        })
+        .find(|ordering| *ordering != Ordering::Equal)
+        .unwrap_or(Ordering::Equal)
 }
 
-/// GraphQL result type for graph traversal
-#[derive(SimpleObject)]
-pub struct TraversalResult {
-    pub object_ids: Vec<String>,
-    pub aggregated_value: Option<Json<Value>>, // Proper JSON type instead of stringified JSON
-    pub count: Option<usize>,
+/// An object's values for each sort key, null where it has none
+fn sort_values(object: &Value, sort: &[SortOption]) -> Vec<Value> {
+    sort.iter()
+        .map(|key| object.get(&key.property).cloned().unwrap_or(Value::Null))
+        .collect()
 }
 
-/// Pagination info for cursor-based pagination
-#[derive(SimpleObject)]
-pub struct PageInfo {
-    pub has_next_page: bool,
-    pub has_previous_page: bool,
-    pub start_cursor: Option<String>,
-    pub end_cursor: Option<String>,
+/// Compare two JSON values of the same kind
+fn compare_json_values(a: &Value, b: &Value) -> Ordering {
+    match (a, b) {
+        (Value::Number(x), Value::Number(y)) => x
+            .as_f64()
+            .partial_cmp(&y.as_f64())
+            .unwrap_or(Ordering::Equal),
+        (Value::String(x), Value::String(y)) => x.cmp(y),
+        (Value::Bool(x), Value::Bool(y)) => x.cmp(y),
+        _ => a.to_string().cmp(&b.to_string()),
+    }
 }
 
-/// Paginated result wrapper
-#[derive(SimpleObject)]
-pub struct PaginatedObjectResult {
-    pub items: Vec<ObjectResult>,
-    pub page_info: PageInfo,
-    pub total_count: usize,
+/// Run a search the way `searchObjects` does: ACL filtering and masking for the caller, and
+/// hydration of the search store's objects
+async fn run_search(
+    ctx: &Context<'_>,
+    object_type: &str,
+    mut store_filters: Vec<Filter>,
+    sort_options: Vec<SortOption>,
+    limit: Option<usize>,
+    offset: Option<usize>,
+    display_locale: Option<DisplayLocale>,
+    hydration: HydrationOptions,
+    explain: bool,
+) -> FieldResult<Vec<ObjectResult>> {
+    // Get services from context
+    let ontology = ctx.data::<OntologyHandle>()?.load();
+    let search_store = ctx.data::<Arc<dyn SearchStore>>()?;
+    let hydrator = ctx.data::<ObjectHydrator>()?;
+
+    let object_type_def = ontology
+        .get_object_type(object_type)
+        .ok_or_else(|| ApiError::not_found("Object type not found"))?;
+    record_object_type(object_type);
+
+    // Restrict results to objects the caller's ACL principals can see
+    let acl_filter = ctx
+        .data_opt::<SecurityContext>()
+        .map(AclSearchFilter::for_context);
+
+    check_indexed(object_type_def, &store_filters, &sort_options)?;
+    check_readable(ctx, object_type_def, query_properties(&store_filters, &sort_options))?;
+
+    // Pre-filter on the indexed ACL fields
+    if let Some(acl_filter) = &acl_filter {
+        store_filters.extend(acl_store_filters(acl_filter));
+    }
+
+    let query = SearchQuery {
+        filters: store_filters,
+        sort: sort_options,
+        limit,
+        offset,
+        search_after: None,
+    };
+
+    if explain {
+        if let Some(native) = search_store.explain_search(object_type, &query) {
+            record_explain(ctx, "search", native);
+        }
+    }
+    // Searches are only answered by the search store; the plan records why and its health
+    let planner = query_planner(ctx);
+    plan_query(ctx, &planner, &QueryShape::search(object_type, &query, &object_type_def.primary_key), explain)?;
+
+    // Execute search
+    let result = search_store.search(object_type, &query).await;
+    report_backend_health(&planner, Backend::Search, &result);
+    let indexed_objects = result.map_err(ApiError::from)?;
+
+    // Hydrate objects
+    let mut hydrated = hydrator
+        .hydrate_batch_with(&indexed_objects, object_type_def, hydration)
+        .map_err(ApiError::from)?;
+    resolve_hydrated_references(ctx, &mut hydrated, object_type_def, hydration).await?;
+    evaluate_model_properties(ctx, &mut hydrated, hydration).await?;
+    record_result_count(hydrated.len());
+
+    // Convert to GraphQL results
+    Ok(hydrated
+        .into_iter()
+        .map(|h| hydrated_object_result(ctx, object_type_def, h, display_locale.as_ref()))
+        .collect())
 }
 
-/// GraphQL result type for function calls
-#[derive(SimpleObject)]
-pub struct FunctionResult {
-    pub value: Json<Value>, // Proper JSON type instead of stringified JSON
-    pub cached: bool,
+/// The schema's query planner, or a default one (without shared health) when none is registered
+fn query_planner(ctx: &Context<'_>) -> QueryPlanner {
+    ctx.data_opt::<QueryPlanner>().cloned().unwrap_or_default()
 }
 
-/// GraphQL result type for object types
-#[derive(SimpleObject)]
-pub struct ObjectTypeResult {
-    pub id: String,
-    #[graphql(name = "displayName")]
-    pub display_name: String,
+/// Plan a query against the stores registered in the schema, returning the plan under
+/// `extensions.explain` when asked
+fn plan_query(ctx: &Context<'_>, planner: &QueryPlanner, shape: &QueryShape, explain: bool) -> FieldResult<QueryPlan> {
+    let search_store = ctx.data_opt::<Arc<dyn SearchStore>>();
+    let capabilities = BackendCapabilities {
+        search: search_store.is_some(),
+        search_aggregations: search_store.is_some_and(|store| store.supports_aggregations()),
+        columnar: ctx.data_opt::<Arc<dyn indexing::store::ColumnarStore>>().is_some(),
+        graph: ctx.data_opt::<Arc<dyn GraphStore>>().is_some(),
+    };
+    let plan = planner
+        .plan(shape, &capabilities)
+        .map_err(ApiError::from)?;
+    if explain {
+        record_explain(ctx, "plan", serde_json::to_value(&plan).unwrap_or(Value::Null));
+    }
+    Ok(plan)
 }
 
-/// GraphQL result type for property definitions (output)
-#[derive(SimpleObject, Clone)]
-pub struct PropertyOutput {
-    pub id: String,
-    #[graphql(name = "displayName")]
-    pub display_name: Option<String>,
-    #[graphql(name = "type")]
-    pub property_type: String,
-    pub required: bool,
+/// Mark `backend` unhealthy when it could not be reached, and healthy again once it answers
+fn report_backend_health<T>(planner: &QueryPlanner, backend: Backend, result: &Result<T, StoreError>) {
+    match result {
+        Ok(_) => planner.health().report_success(backend),
+        Err(StoreError::Connection(reason)) => planner.health().report_failure(backend, reason.clone()),
+        Err(_) => {}
+    }
 }
 
-/// GraphQL input for function parameters
-#[derive(InputObject, Clone)]
-pub struct PropertyInput {
-    pub id: String,
-    #[graphql(name = "displayName")]
+/// Run an aggregation on one backend
+async fn run_aggregation(
+    ctx: &Context<'_>,
+    planner: &QueryPlanner,
+    backend: Backend,
+    object_type: &str,
+    query: &indexing::store::AnalyticsQuery,
+) -> Result<indexing::store::AnalyticsResult, StoreError> {
+    let result = match backend {
+        Backend::Search => match ctx.data_opt::<Arc<dyn SearchStore>>() {
+            Some(store) => store.aggregate(object_type, query).await,
+            None => Err(StoreError::Configuration("No search store configured".to_string())),
+        },
+        Backend::Columnar => match ctx.data_opt::<Arc<dyn indexing::store::ColumnarStore>>() {
+            Some(store) => store.query_analytics(object_type, query).await,
+            None => Err(StoreError::Configuration("No analytics store configured".to_string())),
+        },
+        Backend::Graph => Err(StoreError::Query("The graph store does not run aggregations".to_string())),
+    };
+    report_backend_health(planner, backend, &result);
+    result
+}
+
+/// A page of `searchObjectsPaginated`: `page_size` objects after the `after` position in
+/// `sort_options` order
+async fn run_paginated_search(
+    ctx: &Context<'_>,
+    object_type_def: &ObjectType,
+    mut store_filters: Vec<Filter>,
+    mut sort_options: Vec<SortOption>,
+    page_size: usize,
+    after: Option<Vec<Value>>,
+    display_locale: Option<DisplayLocale>,
+    hydration: HydrationOptions,
+) -> FieldResult<PaginatedObjectResult> {
+    let object_type = object_type_def.id.as_str();
+    record_object_type(object_type);
+    let acl_filter = ctx
+        .data_opt::<SecurityContext>()
+        .map(AclSearchFilter::for_context);
+
+    // The primary key breaks ties so every object has a distinct position
+    if sort_options.last().map(|key| key.property.as_str()) != Some(object_type_def.primary_key.as_str()) {
+        sort_options.push(SortOption::for_property(object_type_def, object_type_def.primary_key.clone(), true));
+    }
+    if let Some(after) = &after {
+        if after.len() != sort_options.len() {
+            return Err(ApiError::invalid_argument("Cursor does not match the requested sort").into());
+        }
+    }
+    let has_previous_page = after.is_some();
+
+    // Each page entry is a result and the sort values its cursor encodes
+    let (mut page, total_count): (Vec<(ObjectResult, Vec<Value>)>, usize) = {
+        if let Some(acl_filter) = &acl_filter {
+            store_filters.extend(acl_store_filters(acl_filter));
+        }
+        let search_store = ctx.data::<Arc<dyn SearchStore>>()?;
+        let total_count = search_store
+            .count_objects(object_type, Some(&store_filters))
+            .await
+            .map_err(ApiError::from)? as usize;
+        let query = SearchQuery {
+            filters: store_filters,
+            sort: sort_options.clone(),
+            limit: Some(page_size + 1),
+            offset: None,
+            search_after: after,
+        };
+        let indexed_objects = search_store
+            .search(object_type, &query)
+            .await
+            .map_err(ApiError::from)?;
+        let mut hydrated = ctx
+            .data::<ObjectHydrator>()?
+            .hydrate_batch_with(&indexed_objects, object_type_def, hydration)
+            .map_err(ApiError::from)?;
+        resolve_hydrated_references(ctx, &mut hydrated, object_type_def, hydration).await?;
+        evaluate_model_properties(ctx, &mut hydrated, hydration).await?;
+
+        let page = indexed_objects
+            .iter()
+            .zip(hydrated)
+            .map(|(indexed, h)| {
+                let properties: serde_json::Map<String, Value> = indexed
+                    .properties
+                    .iter()
+                    .map(|(key, value)| (key.clone(), serde_json::to_value(value).unwrap_or_default()))
+                    .collect();
+                let values = sort_values(&Value::Object(properties), &sort_options);
+                (hydrated_object_result(ctx, object_type_def, h, display_locale.as_ref()), values)
+            })
+            .collect();
+        (page, total_count)
+    };
+
+    // One extra object was fetched to tell whether another page follows
+    let has_next_page = page.len() > page_size;
+    page.truncate(page_size);
+    record_result_count(page.len());
+    let cursors: Vec<String> = page.iter().map(|(_, values)| encode_search_cursor(values)).collect();
+
+    Ok(PaginatedObjectResult {
+        items: page.into_iter().map(|(result, _)| result).collect(),
+        page_info: PageInfo {
+            has_next_page,
+            has_previous_page,
+            start_cursor: cursors.first().cloned(),
+            end_cursor: cursors.last().cloned(),
+        },
+        total_count,
+    })
+}
+
+/// A JSON object as a search result, masked for the caller. Values are coerced to
+/// the declared property types; rows that do not fit are served as stored.
+pub(crate) fn json_object_result(
+    ctx: &Context<'_>,
+    object_type_def: &ObjectType,
+    obj: Value,
+    display_locale: Option<&DisplayLocale>,
+    hydration: HydrationOptions,
+) -> ObjectResult {
+    let hydrated = match ctx.data_opt::<ObjectHydrator>() {
+        Some(hydrator) => hydrator.hydrate_from_json_with(&obj, object_type_def, hydration),
+        None => ObjectHydrator::new().hydrate_from_json_with(&obj, object_type_def, hydration),
+    };
+    let (object_id, obj) = match hydrated {
+        Ok(h) => {
+            log_hydration_warnings(&h);
+            let properties = h.to_json_value()["properties"].take();
+            (h.object_id, properties)
+        }
+        Err(e) => {
+            tracing::warn!("{}", e);
+            let object_id = obj
+                .get(&object_type_def.primary_key)
+                .and_then(|v| v.as_str())
+                .unwrap_or("unknown")
+                .to_string();
+            (object_id, obj)
+        }
+    };
+    let mut result = masked_object_result(ctx, object_type_def, object_id, obj);
+    result.display = display_locale.map(|locale| Json(display_json(object_type_def, &result.properties.0, locale)));
+    result
+}
+
+/// A hydrated search store object as a search result, masked for the caller
+fn hydrated_object_result(
+    ctx: &Context<'_>,
+    object_type_def: &ObjectType,
+    h: HydratedObject,
+    display_locale: Option<&DisplayLocale>,
+) -> ObjectResult {
+    log_hydration_warnings(&h);
+    let properties_json = h.to_json_value()["properties"].take();
+    let mut result = masked_object_result(ctx, object_type_def, h.object_id, properties_json);
+    result.display = display_locale.map(|locale| Json(display_json(object_type_def, &result.properties.0, locale)));
+    result
+}
+
+/// An object's JSON properties as a result, masked for the caller. The title is read from
+/// the masked properties, so a masked or redacted title property does not show through it.
+fn masked_object_result(ctx: &Context<'_>, object_type_def: &ObjectType, object_id: String, properties: Value) -> ObjectResult {
+    let properties = mask_object_json(ctx, object_type_def, properties);
+    let title = match object_type_def.title_key.as_ref().map(|key| &properties[key]) {
+        Some(Value::String(title)) => title.clone(),
+        Some(Value::Null) | None => object_id.clone(),
+        Some(title) => title.to_string(),
+    };
+    ObjectResult {
+        object_type: object_type_def.id.clone(),
+        object_id,
+        title,
+        properties: Json(properties),
+        display: None,
+        archived: None,
+        missing_interface_properties: None,
+        property_groups: None,
+    }
+}
+
+/// Whether the caller may see a job: any caller for jobs started without a user, otherwise
+/// only the user who started it or an `admin`
+pub(crate) fn job_visible(ctx: &Context<'_>, job: &JobProgress) -> bool {
+    let Some(requested_by) = &job.requested_by else {
+        return true;
+    };
+    ctx.data_opt::<SecurityContext>()
+        .is_some_and(|context| &context.user_id == requested_by || context.has_role(ONTOLOGY_ADMIN_ROLE))
+}
+
+/// Whether an object read back from the search store, which leaves out the indexed ACL
+/// fields, passes the caller's ACL filter. Objects with an invalid ACL never do.
+fn acl_admits(acl_filter: &AclSearchFilter, properties: &PropertyMap) -> bool {
+    with_acl_index_fields(properties).is_ok_and(|indexed| acl_filter.matches(&indexed))
+}
+
+/// Whether the caller may read an object fetched by ID. Its ACL goes through
+/// `check_access`, so an explicit deny or an ACL that doesn't list the caller hides it, as
+/// does an invalid ACL. Without a `SecurityContext` every object is readable.
+fn object_readable(ctx: &Context<'_>, properties: &PropertyMap) -> bool {
+    let Some(context) = ctx.data_opt::<SecurityContext>() else {
+        return true;
+    };
+    match ObjectAcl::from_properties(properties) {
+        Ok(acl) => check_access(context, &ObjectSecurityPolicy::new().with_object_acl(acl.unwrap_or_default())).is_ok(),
+        Err(_) => false,
+    }
+}
+
+/// Resolve the references of hydrated objects if `hydration` asks for it. Referenced objects
+/// the caller's ACL principals cannot see resolve without a title, like dangling references.
+async fn resolve_hydrated_references(
+    ctx: &Context<'_>,
+    objects: &mut [HydratedObject],
+    object_type_def: &ObjectType,
+    hydration: HydrationOptions,
+) -> FieldResult<()> {
+    if !hydration.resolve_references {
+        return Ok(());
+    }
+    let ontology = ctx.data::<OntologyHandle>()?.load();
+    let search_store = ctx.data::<Arc<dyn SearchStore>>()?;
+    let acl_filter = ctx
+        .data_opt::<SecurityContext>()
+        .map(AclSearchFilter::for_context);
+    let visible = |target: &IndexedObject| {
+        acl_filter
+            .as_ref()
+            .is_none_or(|acl_filter| acl_admits(acl_filter, &target.properties))
+    };
+    ctx.data::<ObjectHydrator>()?
+        .resolve_references(objects, object_type_def, &ontology, search_store.as_ref(), &visible)
+        .await
+        .map_err(|e| ApiError::from(e).into())
+}
+
+/// Fill in model-bound properties with predictions when asked to
+async fn evaluate_model_properties(
+    ctx: &Context<'_>,
+    objects: &mut [HydratedObject],
+    hydration: HydrationOptions,
+) -> FieldResult<()> {
+    if !hydration.include_predictions {
+        return Ok(());
+    }
+    let evaluator = ctx
+        .data_opt::<ModelPropertyEvaluator>()
+        .ok_or_else(|| ApiError::invalid_argument("Predictions are not available: no model executor is configured"))?;
+    evaluator.evaluate(objects).await;
+    Ok(())
+}
+
+/// Computed properties and model predictions that hydrated as null
+fn log_hydration_warnings(h: &HydratedObject) {
+    for warning in &h.warnings {
+        tracing::warn!("{}", warning);
+    }
+}
+
+/// Load a single object from the search store, masked for the caller
+async fn load_object(
+    ctx: &Context<'_>,
+    object_type: &str,
+    object_id: &str,
+    hydration: HydrationOptions,
+) -> FieldResult<Option<ObjectResult>> {
+    let ontology = ctx.data::<OntologyHandle>()?.load();
+
+    let object_type_def = ontology
+        .get_object_type(object_type)
+        .ok_or_else(|| ApiError::not_found("Object type not found"))?;
+
+    let search_store = ctx.data::<Arc<dyn SearchStore>>()?;
+    let hydrator = ctx.data::<ObjectHydrator>()?;
+
+    let mut indexed = search_store
+        .get_object(object_type, object_id)
+        .await
+        .map_err(ApiError::from)?;
+    if indexed.is_none() {
+        // IDs of duplicates merged away resolve to the object they were merged into
+        let winner_id = resolve_merged(search_store.as_ref(), object_type, object_id)
+            .await
+            .map_err(ApiError::from)?;
+        if let Some(winner_id) = winner_id {
+            indexed = search_store
+                .get_object(object_type, &winner_id)
+                .await
+                .map_err(ApiError::from)?;
+        }
+    }
+
+    if indexed.is_none() {
+        // Archived objects resolve to a pointer at the archive rather than not-found
+        let tombstone = find_tombstone(search_store.as_ref(), object_type, object_id)
+            .await
+            .map_err(ApiError::from)?;
+        if let Some(tombstone) = tombstone {
+            return Ok(Some(ObjectResult {
+                object_type: tombstone.object_type,
+                object_id: tombstone.object_id.clone(),
+                title: tombstone.object_id,
+                properties: Json(serde_json::json!({})),
+                display: None,
+                archived: Some(ArchivedObject {
+                    partition: tombstone.partition,
+                    location: tombstone.location,
+                    archived_at: tombstone.archived_at,
+                }),
+                missing_interface_properties: None,
+                property_groups: None,
+            }));
+        }
+    }
+
+    if let Some(indexed) = indexed {
+        if !object_readable(ctx, &indexed.properties) {
+            return Ok(None);
+        }
+        let mut hydrated = hydrator
+            .hydrate_from_indexed_with(&indexed, object_type_def, hydration)
+            .map_err(ApiError::from)?;
+        resolve_hydrated_references(ctx, std::slice::from_mut(&mut hydrated), object_type_def, hydration).await?;
+        evaluate_model_properties(ctx, std::slice::from_mut(&mut hydrated), hydration).await?;
+        Ok(Some(hydrated_object_result(ctx, object_type_def, hydrated, None)))
+    } else {
+        Ok(None)
+    }
+}
+
+/// Run a function with JSON-encoded parameters, returning its value and whether it came
+/// from the cache. Fails if the value does not match the declared return type.
+async fn execute_function(
+    ctx: &Context<'_>,
+    function_id: &str,
+    parameters: HashMap<String, String>,
+    typed_parameters: HashMap<String, PropertyValueScalar>,
+) -> FieldResult<(PropertyValue, bool)> {
+    let ontology = ctx.data::<OntologyHandle>()?.load();
+    let graph_store = ctx.data::<Arc<dyn GraphStore>>()?;
+    let search_store = ctx.data::<Arc<dyn SearchStore>>()?;
+
+    // Get function definition
+    let function_def = ontology.get_function_type(function_id).ok_or_else(|| {
+        ApiError::not_found(format!("Function '{}' not found", function_id))
+    })?;
+
+    // Parse parameters from JSON strings to PropertyValues
+    let mut param_map = ontology_engine::PropertyMap::new();
+    for (key, json_value) in parameters {
+        let value: serde_json::Value = serde_json::from_str(&json_value).map_err(|e| {
+            ApiError::invalid_argument(format!("Invalid parameter JSON for '{}': {}", key, e))
+        })?;
+
+        let prop_value = match value {
+            serde_json::Value::String(s) => ontology_engine::PropertyValue::String(s),
+            serde_json::Value::Number(n) => {
+                if let Some(i) = n.as_i64() {
+                    ontology_engine::PropertyValue::Integer(i)
+                } else if let Some(d) = n.as_f64() {
+                    ontology_engine::PropertyValue::Double(d)
+                } else {
+                    return Err(ApiError::invalid_argument(format!(
+                        "Invalid number for parameter '{}'",
+                        key
+                    )).into());
+                }
+            }
+            serde_json::Value::Bool(b) => ontology_engine::PropertyValue::Boolean(b),
+            serde_json::Value::Array(arr) => {
+                let prop_values: Result<Vec<ontology_engine::PropertyValue>, _> = arr
+                    .into_iter()
+                    .map(|v| match v {
+                        serde_json::Value::String(s) => {
+                            Ok(ontology_engine::PropertyValue::String(s))
+                        }
+                        serde_json::Value::Number(n) => {
+                            if let Some(i) = n.as_i64() {
+                                Ok(ontology_engine::PropertyValue::Integer(i))
+                            } else if let Some(d) = n.as_f64() {
+                                Ok(ontology_engine::PropertyValue::Double(d))
+                            } else {
+                                Err("Invalid number in array")
+                            }
+                        }
+                        serde_json::Value::Bool(b) => {
+                            Ok(ontology_engine::PropertyValue::Boolean(b))
+                        }
+                        _ => Err("Unsupported array element type"),
+                    })
+                    .collect();
+                ontology_engine::PropertyValue::Array(prop_values.map_err(|e| {
+                    ApiError::invalid_argument(format!(
+                        "Invalid array for parameter '{}': {:?}",
+                        key, e
+                    ))
+                })?)
+            }
+            _ => {
+                return Err(ApiError::invalid_argument(format!(
+                    "Unsupported parameter type for '{}'",
+                    key
+                )).into())
+            }
+        };
+
+        param_map.insert(key, prop_value);
+    }
+
+    for (key, value) in typed_parameters {
+        if param_map.contains_key(&key) {
+            return Err(ApiError::invalid_argument(format!(
+                "Parameter '{}' is given in both parameters and typedParameters",
+                key
+            )).into());
+        }
+        param_map.insert(key, value.0);
+    }
+
+    // Untagged values take the declared parameter type, e.g. object references arrive as
+    // plain strings
+    for param_def in &function_def.parameters {
+        if let Some(value) = param_map.get(&param_def.id).cloned() {
+            let value = property_value::coerce(value, &param_def.property_type).map_err(|e| {
+                ApiError::invalid_argument(format!("Invalid parameter '{}': {}", param_def.id, e))
+            })?;
+            param_map.insert(param_def.id.clone(), value);
+        }
+    }
+
+    // Interface functions run against whichever implementer the referenced object belongs to
+    let mut bound_function = None;
+    if let Some(interface_id) = &function_def.target_interface {
+        let (param_id, reference) = function_def
+            .parameters
+            .iter()
+            .find_map(|p| match param_map.get(&p.id) {
+                Some(PropertyValue::ObjectReference(reference)) => {
+                    Some((p.id.clone(), reference.clone()))
+                }
+                _ => None,
+            })
+            .ok_or_else(|| {
+                ApiError::invalid_argument(format!(
+                    "Function '{}' runs on interface '{}' and needs an object reference parameter",
+                    function_id, interface_id
+                ))
+            })?;
+        let (object_type_def, object_id, _) =
+            resolve_interface_object(ctx, &ontology, interface_id, &reference)
+                .await?
+                .ok_or_else(|| {
+                    ApiError::not_found(format!(
+                        "Object '{}' not found for any implementer of interface '{}'",
+                        reference, interface_id
+                    ))
+                })?;
+        bound_function = Some(
+            function_def
+                .bind_to(object_type_def)
+                .map_err(ApiError::invalid_argument)?,
+        );
+        param_map.insert(
+            param_id,
+            ObjectRef::new(&object_type_def.id, &object_id).into(),
+        );
+    }
+    let function_def = bound_function.as_ref().unwrap_or(function_def);
+    // Bare IDs in the source parameter name objects of its reference target
+    let source_type = function_def.parameters.iter().find_map(|p| match param_map.get(&p.id) {
+        Some(PropertyValue::ObjectReference(_)) => p.reference_target.clone(),
+        _ => None,
+    });
+    let data = ContextFunctionData {
+        ctx,
+        ontology: &ontology,
+        graph_store: graph_store.as_ref(),
+        source_type,
+    };
+
+    // Check cache if function is cacheable
+    let mut cached = false;
+    let cache_key = if function_def.cacheable {
+        // Generate cache key from function_id + serialized parameters
+        let mut hasher = DefaultHasher::new();
+        function_id.hash(&mut hasher);
+        // Serialize parameters for hashing
+        if let Ok(param_json) = serde_json::to_string(&param_map) {
+            param_json.hash(&mut hasher);
+        }
+        Some(hasher.finish())
+    } else {
+        None
+    };
+
+    // Try to get from cache
+    let result_value = if let Some(key) = cache_key {
+        // Get cache from context
+        if let Ok(cache) = ctx.data::<Arc<tokio::sync::RwLock<HashMap<u64, PropertyValue>>>>() {
+            let cache_read = cache.read().await;
+            if let Some(cached_value) = cache_read.get(&key) {
+                cached = true;
+                cached_value.clone()
+            } else {
+                // Execute function and cache result
+                let result = FunctionExecutor::execute(
+                    function_def,
+                    &param_map,
+                    Some(&data),
+                )
+                .await
+                .map_err(|e| {
+                    ApiError::internal(format!("Function execution error: {}", e))
+                })?;
+
+                // Store in cache
+                let result_value = result.value.clone();
+                drop(cache_read);
+                let mut cache_write = cache.write().await;
+                cache_write.insert(key, result_value.clone());
+                result_value
+            }
+        } else {
+            // No cache available, just execute
+            let result = FunctionExecutor::execute(function_def, &param_map, Some(&data))
+                .await
+                .map_err(|e| {
+                    ApiError::internal(format!("Function execution error: {}", e))
+                })?;
+            result.value
+        }
+    } else {
+        // Function is not cacheable, just execute
+        let result = FunctionExecutor::execute(function_def, &param_map, Some(&data))
+            .await
+            .map_err(|e| {
+                ApiError::internal(format!("Function execution error: {}", e))
+            })?;
+        result.value
+    };
+
+    function_def.return_type.check_value(&result_value).map_err(|e| {
+        ApiError::internal(format!("Function '{}' {}", function_id, e))
+    })?;
+    Ok((result_value, cached))
+}
+
+/// `_links` entries of a historical object: its links of `link_types` as they stood at
+/// `timestamp`, each naming the object at the other end with that object's title at the time
+fn historical_links_json(
+    ontology: &Ontology,
+    versioning: &time_query::TimeQuery,
+    object_type: &ObjectType,
+    object_id: &str,
+    link_types: &[String],
+    timestamp: DateTime<Utc>,
+) -> Vec<Value> {
+    versioning
+        .reconstruct_links(object_id, link_types, timestamp)
+        .into_iter()
+        .filter_map(|link| {
+            let link_type = ontology.get_link_type(&link.link_type)?;
+            let (direction, other_type, other_id) =
+                if link.source_id == object_id && link_type.source == object_type.id {
+                    ("outgoing", &link_type.target, &link.target_id)
+                } else if link.target_id == object_id && link_type.target == object_type.id {
+                    ("incoming", &link_type.source, &link.source_id)
+                } else {
+                    return None;
+                };
+            let title = ontology
+                .get_object_type(other_type)
+                .and_then(|other_type_def| other_type_def.title_key.as_ref())
+                .and_then(|title_key| {
+                    let other = versioning.reconstruct_object(other_type, other_id, timestamp)?;
+                    other.properties.get(title_key).map(|title| title.to_string())
+                })
+                .unwrap_or_else(|| other_id.clone());
+            let properties: serde_json::Map<String, Value> = link
+                .properties
+                .iter()
+                .map(|(k, v)| (k.clone(), serde_json::to_value(v).unwrap_or(Value::Null)))
+                .collect();
+            Some(serde_json::json!({
+                "linkType": link.link_type,
+                "linkId": link.link_id,
+                "direction": direction,
+                "objectType": other_type,
+                "objectId": other_id,
+                "title": title,
+                "properties": properties,
+                "createdAt": link.created_at.to_rfc3339(),
+            }))
+        })
+        .collect()
+}
+
+/// Reads for `FunctionExecutor` on behalf of the caller: objects are loaded and masked as the
+/// caller may see them, links come from the graph store
+struct ContextFunctionData<'a, 'c> {
+    ctx: &'a Context<'c>,
+    ontology: &'a Ontology,
+    graph_store: &'a dyn GraphStore,
+    /// Type of objects referenced by bare ID
+    source_type: Option<String>,
+}
+
+impl ContextFunctionData<'_, '_> {
+    /// Type and ID of an object referenced as `type:id` or by bare ID
+    fn resolve(&self, reference: &str) -> (Option<&ObjectType>, String) {
+        match ObjectRef::parse(reference, self.source_type.as_deref()) {
+            Ok(parsed) => (self.ontology.get_object_type(&parsed.object_type), parsed.object_id),
+            Err(_) => (None, reference.to_string()),
+        }
+    }
+
+    async fn properties(&self, object_type: &ObjectType, object_id: &str) -> Result<Option<PropertyMap>, String> {
+        load_object_properties(self.ctx, object_type, object_id)
+            .await
+            .map_err(|e| e.message)
+    }
+
+    /// Objects linked to `reference` through `link_type`, with the type at their end. Links
+    /// are followed backwards when the traversal lands on the link type's source, or, without
+    /// a `target_type`, when it starts from the link type's target.
+    async fn linked(
+        &self,
+        reference: &str,
+        link_type: &str,
+        target_type: Option<&str>,
+    ) -> Result<(String, Vec<String>), String> {
+        let (source_type, source_id) = self.resolve(reference);
+        let link_type_def = self
+            .ontology
+            .get_link_type(link_type)
+            .ok_or_else(|| format!("Link type '{}' not found", link_type))?;
+        let starts_at_target = source_type.is_some_and(|t| t.id == link_type_def.target && t.id != link_type_def.source);
+        let incoming = !link_type_def.bidirectional
+            && match target_type {
+                Some(target_type) => link_type_def.target != target_type && link_type_def.source == target_type,
+                None => starts_at_target,
+            };
+
+        let linked_ids = if incoming {
+            self.graph_store
+                .get_links(&source_id, Some(link_type), Some(indexing::store::LinkDirection::Incoming), &LinkQuery::default())
+                .await
+                .map(|links| links.into_iter().map(|link| link.source_id).collect())
+        } else {
+            self.graph_store.get_connected_objects(&source_id, link_type).await
+        }
+        .map_err(|e| format!("Graph query error: {}", e))?;
+        let linked_type = if incoming || starts_at_target {
+            &link_type_def.source
+        } else {
+            &link_type_def.target
+        };
+        Ok((linked_type.clone(), linked_ids))
+    }
+}
+
+#[async_trait::async_trait]
+impl FunctionDataSource for ContextFunctionData<'_, '_> {
+    async fn get_object_property(
+        &self,
+        object_type: &str,
+        object_id: &str,
+        property: &str,
+    ) -> Result<Option<PropertyValue>, String> {
+        // The executor passes the parameter name as the type of bare IDs
+        let object_type = self
+            .ontology
+            .get_object_type(object_type)
+            .or_else(|| self.source_type.as_deref().and_then(|t| self.ontology.get_object_type(t)))
+            .ok_or_else(|| format!("Cannot tell the object type of '{}'", object_id))?;
+        Ok(self
+            .properties(object_type, object_id)
+            .await?
+            .and_then(|properties| properties.get(property).cloned()))
+    }
+
+    async fn get_linked_objects(
+        &self,
+        object_id: &str,
+        link_type: &str,
+        target_type: &str,
+    ) -> Result<Vec<String>, String> {
+        Ok(self.linked(object_id, link_type, Some(target_type)).await?.1)
+    }
+
+    async fn aggregate_linked_properties(
+        &self,
+        object_id: &str,
+        link_type: &str,
+        property: &str,
+        aggregation: AggregationType,
+    ) -> Result<Option<PropertyValue>, String> {
+        let (linked_type, linked_ids) = self.linked(object_id, link_type, None).await?;
+        let linked_type = self
+            .ontology
+            .get_object_type(&linked_type)
+            .ok_or_else(|| format!("Object type '{}' not found", linked_type))?;
+        let mut values = Vec::new();
+        for linked_id in linked_ids {
+            if let Some(value) = self.properties(linked_type, &linked_id).await?.and_then(|p| p.get(property).cloned()) {
+                values.push(value);
+            }
+        }
+        aggregation.apply(&values).map(Some)
+    }
+}
+
+/// Object references in a function result, flattening nested arrays
+fn collect_references(value: &PropertyValue, references: &mut Vec<String>) {
+    match value {
+        PropertyValue::ObjectReference(reference) | PropertyValue::String(reference) => {
+            references.push(reference.clone())
+        }
+        PropertyValue::Array(items) => {
+            for item in items {
+                collect_references(item, references);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Find which implementer of an interface holds an object, returning its type, ID and
+/// properties as the caller may see them. `reference` is an object ID or `type:id`.
+async fn resolve_interface_object<'a>(
+    ctx: &Context<'_>,
+    ontology: &'a Ontology,
+    interface_id: &str,
+    reference: &str,
+) -> FieldResult<Option<(&'a ObjectType, String, PropertyMap)>> {
+    let implementers = InterfaceValidator::get_implementers(interface_id, ontology.object_types());
+    let (type_hint, object_id) = match ObjectRef::parse(reference, None) {
+        Ok(parsed) if implementers.iter().any(|i| i.id == parsed.object_type) => {
+            (Some(parsed.object_type), parsed.object_id)
+        }
+        _ => (None, reference.to_string()),
+    };
+
+    for object_type_def in implementers
+        .into_iter()
+        .filter(|i| type_hint.as_deref().map_or(true, |t| t == i.id))
+    {
+        if let Some(properties) = load_object_properties(ctx, object_type_def, &object_id).await? {
+            return Ok(Some((object_type_def, object_id.to_string(), properties)));
+        }
+    }
+    Ok(None)
+}
+
+/// An object's properties from the search store, masked for the caller
+async fn load_object_properties(
+    ctx: &Context<'_>,
+    object_type_def: &ObjectType,
+    object_id: &str,
+) -> FieldResult<Option<PropertyMap>> {
+    let search_store = ctx.data::<Arc<dyn SearchStore>>()?;
+    let Some(indexed) = search_store
+        .get_object(&object_type_def.id, object_id)
+        .await
+        .map_err(ApiError::from)?
+    else {
+        return Ok(None);
+    };
+    if !object_readable(ctx, &indexed.properties) {
+        return Ok(None);
+    }
+    let properties = indexed.properties;
+
+    // Masking works on JSON; values it left alone keep their types
+    let json: serde_json::Map<String, Value> = properties
+        .iter()
+        .map(|(k, v)| (k.clone(), serde_json::to_value(v).unwrap_or(Value::Null)))
+        .collect();
+    let mut masked = PropertyMap::new();
+    if let Value::Object(fields) = mask_object_json(ctx, object_type_def, Value::Object(json.clone())) {
+        for (key, value) in fields {
+            if json.get(&key) == Some(&value) {
+                if let Some(typed) = properties.get(&key) {
+                    masked.insert(key, typed.clone());
+                }
+            } else if let Ok(value) = serde_json::from_value::<PropertyValue>(value) {
+                masked.insert(key, value);
+            }
+        }
+    }
+    Ok(Some(masked))
+}
+
+/// Object type `apportionAcrossCrosswalk` reads crosswalk links from when none is given
+const DEFAULT_CROSSWALK_TYPE: &str = "boundary_crosswalk";
+
+/// Default page size for the `links` connection
+const DEFAULT_LINK_PAGE_SIZE: usize = 50;
+
+/// Histogram buckets `linkStats` returns when none are requested
+const DEFAULT_LINK_STATS_BUCKETS: usize = 10;
+
+const MAX_LINK_STATS_BUCKETS: usize = 1000;
+
+/// Default page size for `searchObjectsPaginated`
+const DEFAULT_SEARCH_PAGE_SIZE: usize = 50;
+
+/// Search cursors are opaque to clients: base64 of the JSON array of an object's sort values
+fn encode_search_cursor(sort_values: &[Value]) -> String {
+    URL_SAFE_NO_PAD.encode(Value::Array(sort_values.to_vec()).to_string())
+}
+
+fn decode_search_cursor(cursor: &str) -> FieldResult<Vec<Value>> {
+    URL_SAFE_NO_PAD
+        .decode(cursor)
+        .ok()
+        .and_then(|json| serde_json::from_slice(&json).ok())
+        .ok_or_else(|| ApiError::invalid_argument(format!("Invalid cursor: {}", cursor)).into())
+}
+
+fn encode_link_cursor(position: usize) -> String {
+    format!("link:{}", position)
+}
+
+fn decode_link_cursor(cursor: &str) -> FieldResult<usize> {
+    cursor
+        .strip_prefix("link:")
+        .and_then(|p| p.parse().ok())
+        .ok_or_else(|| ApiError::invalid_argument(format!("Invalid cursor: {}", cursor)).into())
+}
+
+/// Terms filters over the indexed ACL fields for a caller
+pub(crate) fn acl_store_filters(acl_filter: &AclSearchFilter) -> Vec<Filter> {
+    let to_array = |principals: &[String]| {
+        PropertyValue::Array(
+            principals
+                .iter()
+                .map(|p| PropertyValue::String(p.clone()))
+                .collect(),
+        )
+    };
+    vec![
+        Filter {
+            property: ACL_READERS_FIELD.to_string(),
+            operator: indexing::store::FilterOperator::In,
+            value: to_array(&acl_filter.readers_any_of),
+            distance: None,
+            case_insensitive: false,
+        },
+        Filter {
+            property: ACL_DENIED_FIELD.to_string(),
+            operator: indexing::store::FilterOperator::NotIn,
+            value: to_array(&acl_filter.denied_none_of),
+            distance: None,
+            case_insensitive: false,
+        },
+    ]
+}
+
+/// A property total apportioned to one target of a crosswalk
+#[derive(SimpleObject)]
+pub struct ApportionedRow {
+    pub target_id: String,
+    pub value: Json<Value>,
+}
+
+/// A user edit that conflicts with the source or with another user's edit
+#[derive(SimpleObject)]
+pub struct PendingConflictOutput {
+    pub edit_id: String,
+    pub object_type: String,
+    pub object_id: String,
+    pub property_name: String,
+    pub user_id: String,
+    /// `source_changed` or `concurrent_edits`
+    pub kind: String,
+    /// `pending`, `source_kept` or `edit_applied`
+    pub resolution: String,
+    /// The value the user saw when editing
+    pub base_value: Option<Json<Value>>,
+    /// The refreshed source value; null if the source no longer has the property
+    pub source_value: Option<Json<Value>>,
+    /// The edited value; null if the edit deletes the property
+    pub edited_value: Option<Json<Value>>,
+    pub edited_at: String,
+}
+
+/// A user edit queued for write-back and where it is in its lifecycle
+#[derive(SimpleObject)]
+pub struct UserEditOutput {
+    pub edit_id: String,
+    pub object_type: String,
+    pub object_id: String,
+    pub property: String,
+    /// The edited value; null if the edit deletes the property
+    pub value: Option<Json<Value>>,
+    /// The value the user saw when editing
+    pub base_value: Option<Json<Value>>,
+    pub user_id: String,
+    pub comment: Option<String>,
+    /// `pending`, `applied`, `rejected` or `withdrawn`
+    pub status: String,
+    pub submitted_at: String,
+    /// When the edit stopped being pending
+    pub resolved_at: Option<String>,
+}
+
+pub(crate) fn edit_output(edit: &UserEdit) -> UserEditOutput {
+    let json = |value: &PropertyValue| Json(serde_json::to_value(value).unwrap_or(Value::Null));
+    UserEditOutput {
+        edit_id: edit.edit_id.clone(),
+        object_type: edit.object_type.clone(),
+        object_id: edit.object_id.clone(),
+        property: edit.property_name.clone(),
+        value: (!edit.deleted).then(|| json(&edit.property_value)),
+        base_value: edit.base_value.as_ref().map(json),
+        user_id: edit.user_id.clone(),
+        comment: edit.comment.clone(),
+        status: edit.status.to_string(),
+        submitted_at: edit.timestamp.to_rfc3339(),
+        resolved_at: edit.resolved_at.map(|at| at.to_rfc3339()),
+    }
+}
+
+pub(crate) fn conflict_output(conflict: &PropertyConflict) -> PendingConflictOutput {
+    let json = |value: &PropertyValue| Json(serde_json::to_value(value).unwrap_or(Value::Null));
+    PendingConflictOutput {
+        edit_id: conflict.edit.edit_id.clone(),
+        object_type: conflict.edit.object_type.clone(),
+        object_id: conflict.edit.object_id.clone(),
+        property_name: conflict.property_name.clone(),
+        user_id: conflict.edit.user_id.clone(),
+        kind: enum_name(&conflict.kind),
+        resolution: enum_name(&conflict.resolution),
+        base_value: conflict.edit.base_value.as_ref().map(json),
+        source_value: conflict.source_value.as_ref().map(json),
+        edited_value: (!conflict.edit.deleted).then(|| json(&conflict.edit.property_value)),
+        edited_at: conflict.edit.timestamp.to_rfc3339(),
+    }
+}
+
+/// GraphQL result type for objects
+#[derive(SimpleObject)]
+#[graphql(complex)]
+pub struct ObjectResult {
+    pub object_type: String,
+    pub object_id: String,
+    pub title: String,
+    #[graphql(skip)]
+    pub properties: Json<Value>, // Proper JSON type instead of stringified JSON
+    /// Formatted display strings keyed by property, present when `includeDisplay` is set
+    pub display: Option<Json<Value>>,
+    /// Set when the object has been moved to the archive; its properties are then empty
+    pub archived: Option<ArchivedObject>,
+    /// Required interface properties the object has no value for, set by `queryInterface`
+    pub missing_interface_properties: Option<Vec<String>>,
+    /// Property values bucketed by property group, present when `groupProperties` is set
+    pub property_groups: Option<Vec<PropertyGroupOutput>>,
+}
+
+/// Where an archived object was moved
+#[derive(SimpleObject)]
+pub struct ArchivedObject {
+    /// Columnar store partition holding the object
+    pub partition: String,
+    /// Location of the partition in the columnar store
+    pub location: String,
+    pub archived_at: String,
+}
+
+#[ComplexObject]
+impl ObjectResult {
+    /// Property values by property ID; string-encoded for API 1 clients in compat mode
+    async fn properties(&self, ctx: &Context<'_>) -> Json<Value> {
+        json_field(ctx, &self.properties)
+    }
+
+    /// `[minLon, minLat, maxLon, maxLat]` of a GeoJSON property, so maps can frame the object
+    /// without fetching its geometry; null when the object has no value for it
+    async fn bounding_box(&self, property: String) -> FieldResult<Option<Vec<f64>>> {
+        let geojson = match self.properties.get(&property) {
+            None | Some(Value::Null) => return Ok(None),
+            Some(Value::String(gj)) => gj.clone(),
+            Some(geometry @ Value::Object(_)) => geometry.to_string(),
+            // Already a bounding box, with `omitGeometry`
+            Some(Value::Array(bbox)) if bbox.len() == 4 && bbox.iter().all(Value::is_number) => {
+                return Ok(Some(bbox.iter().filter_map(Value::as_f64).collect()));
+            }
+            Some(_) => return Err(ApiError::invalid_argument(format!("Property '{}' is not a GeoJSON property", property)).into()),
+        };
+        let bbox = ontology_engine::geometry::bounding_box(&geojson)
+            .map_err(|e| ApiError::invalid_argument(format!("Property '{}': {}", property, e)))?;
+        Ok(Some(bbox.to_vec()))
+    }
+
+    /// Objects referencing this one through an object reference property, from the
+    /// reverse-reference index. As in search, objects the caller may not read are left out.
+    async fn referenced_by(&self, ctx: &Context<'_>) -> FieldResult<Vec<ReferenceSourceOutput>> {
+        let index = ctx.data::<ReferenceIndex>()?;
+        let sources = index
+            .references_to(&self.object_type, &self.object_id)
+            .await
+            .map_err(ApiError::from)?;
+
+        let acl_filter = ctx
+            .data_opt::<SecurityContext>()
+            .map(AclSearchFilter::for_context);
+        let mut results = Vec::new();
+        for source in sources {
+            if let Some(acl_filter) = &acl_filter {
+                let readable = ctx
+                    .data::<Arc<dyn SearchStore>>()?
+                    .get_object(&source.object_type, &source.object_id)
+                    .await
+                    .map_err(ApiError::from)?
+                    .is_some_and(|o| acl_admits(acl_filter, &o.properties));
+                if !readable {
+                    continue;
+                }
+            }
+            results.push(ReferenceSourceOutput {
+                object_type: source.object_type,
+                object_id: source.object_id,
+                property: source.property,
+            });
+        }
+        Ok(results)
+    }
+}
+
+/// An object referencing another through one of its properties
+#[derive(SimpleObject)]
+pub struct ReferenceSourceOutput {
+    pub object_type: String,
+    pub object_id: String,
+    /// Reference property holding the reference
+    pub property: String,
+}
+
+/// GraphQL result type for graph traversal
+#[derive(SimpleObject)]
+#[graphql(complex)]
+pub struct TraversalResult {
+    pub object_ids: Vec<String>,
+    #[graphql(skip)]
+    pub aggregated_value: Option<Json<Value>>, // Proper JSON type instead of stringified JSON
+    pub count: Option<usize>,
+}
+
+#[ComplexObject]
+impl TraversalResult {
+    /// String-encoded for API 1 clients in compat mode
+    async fn aggregated_value(&self, ctx: &Context<'_>) -> Option<Json<Value>> {
+        self.aggregated_value.as_ref().map(|value| json_field(ctx, value))
+    }
+}
+
+/// Pagination info for cursor-based pagination
+#[derive(SimpleObject)]
+pub struct PageInfo {
+    pub has_next_page: bool,
+    pub has_previous_page: bool,
+    pub start_cursor: Option<String>,
+    pub end_cursor: Option<String>,
+}
+
+/// A link between two objects, as seen from the object it was queried from
+#[derive(SimpleObject)]
+#[graphql(complex)]
+pub struct LinkEdge {
+    pub cursor: String,
+    pub link_id: String,
+    pub link_type: String,
+    pub direction: String, // "outgoing" or "incoming" relative to the queried object
+    pub properties: Json<Value>,
+    pub created_at: String, // RFC 3339
+    pub other_object_type: String,
+    pub other_object_id: String,
+}
+
+#[ComplexObject]
+impl LinkEdge {
+    /// The object at the other end of the link
+    async fn other_object(&self, ctx: &Context<'_>) -> FieldResult<Option<ObjectResult>> {
+        load_object(ctx, &self.other_object_type, &self.other_object_id, HydrationOptions::default()).await
+    }
+}
+
+/// Connection of link edges
+#[derive(SimpleObject)]
+pub struct LinkConnection {
+    pub edges: Vec<LinkEdge>,
+    pub page_info: PageInfo,
+}
+
+/// Distribution of a numeric link property
+#[derive(SimpleObject)]
+pub struct LinkStatsResult {
+    pub link_type: String,
+    pub property: String,
+    /// Links with a value for the property
+    pub count: u64,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    pub mean: Option<f64>,
+    /// Fixed-width buckets from `min` to `max`
+    pub histogram: Vec<HistogramBucketResult>,
+}
+
+/// Links with a property value in `lower..upper`; the last bucket includes `upper`
+#[derive(SimpleObject)]
+pub struct HistogramBucketResult {
+    pub lower: f64,
+    pub upper: f64,
+    pub count: u64,
+}
+
+/// Paginated result wrapper
+#[derive(SimpleObject)]
+pub struct PaginatedObjectResult {
+    pub items: Vec<ObjectResult>,
+    pub page_info: PageInfo,
+    pub total_count: usize,
+}
+
+/// GraphQL result type for function calls
+#[derive(SimpleObject)]
+#[graphql(complex)]
+pub struct FunctionResult {
+    #[graphql(skip)]
+    pub value: Json<Value>, // Proper JSON type instead of stringified JSON
+    /// The result with its exact variant, e.g. `{"$date": "2020-01-01"}` rather than a
+    /// string. Null when the function returned no value.
+    pub typed_value: Option<PropertyValueScalar>,
+    pub cached: bool,
+}
+
+#[ComplexObject]
+impl FunctionResult {
+    /// String-encoded for API 1 clients in compat mode
+    async fn value(&self, ctx: &Context<'_>) -> Json<Value> {
+        json_field(ctx, &self.value)
+    }
+}
+
+/// GraphQL result type for object types
+#[derive(SimpleObject)]
+pub struct ObjectTypeResult {
+    pub id: String,
+    #[graphql(name = "displayName")]
+    pub display_name: String,
+    #[graphql(name = "primaryKey")]
+    pub primary_key: String,
+    #[graphql(name = "defaultSort")]
+    pub default_sort: Option<SortOutput>,
+    pub properties: Vec<PropertyOutput>, // In display order
+}
+
+/// A section of related properties for detail views
+#[derive(SimpleObject)]
+pub struct PropertyGroupOutput {
+    pub id: String,
+    pub display_name: String,
+    pub description: Option<String>,
+    /// Property IDs in the group, in order
+    pub properties: Vec<String>,
+    pub collapsible: bool,
+    pub collapsed_by_default: bool,
+    /// The object's values of the group's properties, set by `getObject` with `groupProperties`
+    pub values: Option<Json<Value>>,
+}
+
+/// Property groups of an object type with the ungrouped remainder. With `object_properties`,
+/// each group also carries its values; values of undeclared or computed properties fall in
+/// the remainder.
+fn property_group_outputs(
+    object_type: &ObjectType,
+    locale: Option<&str>,
+    object_properties: Option<&Value>,
+) -> Vec<PropertyGroupOutput> {
+    let mut groups = PropertyGroupManager::from(object_type.property_groups.clone())
+        .with_remainder(&object_type.properties);
+    let object_properties = object_properties.and_then(Value::as_object);
+    if let Some(object_properties) = object_properties {
+        let leftover: Vec<String> = object_properties
+            .keys()
+            .filter(|key| !groups.iter().any(|g| g.properties.contains(key)))
+            .cloned()
+            .collect();
+        if !leftover.is_empty() {
+            if groups.last().is_none_or(|g| g.id != UNGROUPED_GROUP_ID) {
+                groups.push(PropertyGroup::new(UNGROUPED_GROUP_ID.to_string(), "Other".to_string()));
+            }
+            groups.last_mut().unwrap().properties.extend(leftover);
+        }
+    }
+    groups
+        .into_iter()
+        .map(|group| {
+            let values = object_properties.map(|object_properties| {
+                let values: serde_json::Map<String, Value> = group
+                    .properties
+                    .iter()
+                    .filter_map(|id| object_properties.get(id).map(|value| (id.clone(), value.clone())))
+                    .collect();
+                Json(Value::Object(values))
+            });
+            PropertyGroupOutput {
+                display_name: group.display_name_for(locale).to_string(),
+                id: group.id,
+                description: group.description,
+                properties: group.properties,
+                collapsible: group.collapsible,
+                collapsed_by_default: group.collapsed_by_default,
+                values,
+            }
+        })
+        .collect()
+}
+
+/// GraphQL result type for link types
+#[derive(SimpleObject)]
+pub struct LinkTypeResult {
+    pub id: String,
+    #[graphql(name = "displayName")]
+    pub display_name: String,
+    pub source: String,
+    pub target: String,
+    /// As written in the ontology, e.g. `ONE_TO_MANY`
+    pub cardinality: String,
+    pub bidirectional: bool,
+    /// "outgoing" (source to target) or "incoming" (target to source)
+    pub direction: String,
+    pub properties: Vec<LinkPropertyOutput>,
+}
+
+impl LinkTypeResult {
+    fn from_link_type(link_type: &ontology_engine::LinkTypeDef, direction: &str) -> Self {
+        Self {
+            id: link_type.id.clone(),
+            display_name: link_type.display_name_for(None).to_string(),
+            source: link_type.source.clone(),
+            target: link_type.target.clone(),
+            cardinality: link_type.cardinality.as_str().to_string(),
+            bidirectional: link_type.bidirectional,
+            direction: direction.to_string(),
+            properties: link_type.properties.iter().map(LinkPropertyOutput::from_property).collect(),
+        }
+    }
+}
+
+/// GraphQL result type for action types
+#[derive(SimpleObject)]
+pub struct ActionTypeResult {
+    pub id: String,
+    #[graphql(name = "displayName")]
+    pub display_name: String,
+    pub parameters: Vec<LinkPropertyOutput>,
+    /// The caller needs one of these roles, if any are listed
+    pub required_roles: Vec<String>,
+    /// The caller needs one of these badges, if any are listed
+    pub required_badges: Vec<String>,
+    pub target_interface: Option<String>,
+}
+
+/// GraphQL result type for link property and action parameter definitions
+#[derive(SimpleObject, Clone)]
+pub struct LinkPropertyOutput {
+    pub id: String,
+    #[graphql(name = "displayName")]
     pub display_name: Option<String>,
     #[graphql(name = "type")]
     pub property_type: String,
     pub required: bool,
+    pub description: Option<String>,
+}
+
+impl LinkPropertyOutput {
+    fn from_property(p: &ontology_engine::Property) -> Self {
+        Self {
+            id: p.id.clone(),
+            display_name: p.display_name.clone(),
+            property_type: format!("{:?}", p.property_type),
+            required: p.required,
+            description: p.description.clone(),
+        }
+    }
+}
+
+/// GraphQL result type for a default sort
+#[derive(SimpleObject, Clone)]
+pub struct SortOutput {
+    pub property: String,
+    pub ascending: bool,
+}
+
+/// GraphQL result type for property definitions (output)
+#[derive(SimpleObject, Clone)]
+pub struct PropertyOutput {
+    pub id: String,
+    #[graphql(name = "displayName")]
+    pub display_name: Option<String>,
+    #[graphql(name = "type")]
+    pub property_type: String,
+    pub required: bool,
+    #[graphql(name = "displayOrder")]
+    pub display_order: Option<u32>,
+    /// Search indexing hint; `not_indexed` properties cannot be filtered or sorted on
+    pub indexing: String,
+    /// Object type an object reference property points at, if declared
+    pub reference_target: Option<String>,
+}
+
+impl PropertyOutput {
+    fn from_property(p: &ontology_engine::Property) -> Self {
+        Self {
+            id: p.id.clone(),
+            display_name: p.display_name.clone(),
+            property_type: format!("{:?}", p.property_type),
+            required: p.required,
+            display_order: p.display_order,
+            indexing: p.indexing_hint().as_str().to_string(),
+            reference_target: p.reference_target.clone(),
+        }
+    }
+}
+
+/// GraphQL input for function parameters
+#[derive(InputObject, Clone)]
+pub struct PropertyInput {
+    pub id: String,
+    #[graphql(name = "displayName")]
+    pub display_name: Option<String>,
+    #[graphql(name = "type")]
+    pub property_type: String,
+    pub required: bool,
+}
+
+/// A change trigger and its full definition
+#[derive(SimpleObject)]
+pub struct ChangeTriggerOutput {
+    pub id: String,
+    pub object_type: String,
+    pub properties: Vec<String>,
+    /// The trigger as accepted by `createChangeTrigger`
+    pub definition: Json<Value>,
+}
+
+impl From<ChangeTrigger> for ChangeTriggerOutput {
+    fn from(trigger: ChangeTrigger) -> Self {
+        Self {
+            definition: Json(serde_json::to_value(&trigger).unwrap_or(Value::Null)),
+            id: trigger.id,
+            object_type: trigger.object_type,
+            properties: trigger.properties,
+        }
+    }
+}
+
+/// A store query slower than the slow-query threshold
+#[derive(SimpleObject)]
+pub struct SlowQueryOutput {
+    #[graphql(name = "recordedAt")]
+    pub recorded_at: String,
+    pub backend: String,
+    pub operation: String,
+    #[graphql(name = "objectType")]
+    pub object_type: Option<String>,
+    /// Query shape, with filter values truncated and pii values redacted
+    pub query: Json<Value>,
+    #[graphql(name = "latencyMs")]
+    pub latency_ms: u64,
+    #[graphql(name = "resultCount")]
+    pub result_count: Option<u64>,
+}
+
+impl From<SlowQuery> for SlowQueryOutput {
+    fn from(query: SlowQuery) -> Self {
+        Self {
+            recorded_at: query.recorded_at.to_rfc3339(),
+            backend: query.backend,
+            operation: query.operation,
+            object_type: query.object_type,
+            query: Json(query.query),
+            latency_ms: query.latency_ms,
+            result_count: query.result_count,
+        }
+    }
+}
+
+/// Progress of a background job
+#[derive(SimpleObject)]
+pub struct JobOutput {
+    #[graphql(name = "jobId")]
+    pub job_id: String,
+    pub kind: String,
+    /// `running`, `completed`, `failed` or `cancelled`
+    pub status: String,
+    pub phase: Option<String>,
+    pub processed: u64,
+    /// Expected number of items in the current phase, when known
+    pub total: Option<u64>,
+    #[graphql(name = "startedAt")]
+    pub started_at: String,
+    #[graphql(name = "finishedAt")]
+    pub finished_at: Option<String>,
+    /// Job output, once completed
+    pub result: Option<Json<Value>>,
+    pub error: Option<String>,
+}
+
+impl From<JobProgress> for JobOutput {
+    fn from(progress: JobProgress) -> Self {
+        Self {
+            job_id: progress.job_id,
+            kind: progress.kind,
+            status: progress.status.as_str().to_string(),
+            phase: progress.phase,
+            processed: progress.processed as u64,
+            total: progress.total.map(|t| t as u64),
+            started_at: progress.started_at.to_rfc3339(),
+            finished_at: progress.finished_at.map(|t| t.to_rfc3339()),
+            result: progress.result.map(Json),
+            error: progress.error,
+        }
+    }
+}
+
+/// Progress and result of an export job
+#[derive(SimpleObject)]
+pub struct ExportStatusOutput {
+    #[graphql(name = "jobId")]
+    pub job_id: String,
+    /// `running`, `completed`, `failed` or `cancelled`
+    pub status: String,
+    /// Objects written so far
+    pub processed: u64,
+    /// Objects matching the export, when known
+    pub total: Option<u64>,
+    /// Server path of the exported file, once complete
+    pub path: Option<String>,
+    pub format: Option<String>,
+    pub rows: Option<u64>,
+    pub bytes: Option<u64>,
+    pub error: Option<String>,
+    #[graphql(name = "startedAt")]
+    pub started_at: String,
+    #[graphql(name = "finishedAt")]
+    pub finished_at: Option<String>,
+    /// When the job and its file are cleaned up, if a retention period is configured
+    #[graphql(name = "expiresAt")]
+    pub expires_at: Option<String>,
 }
 
 /// GraphQL result type for function definitions
@@ -1624,6 +3453,9 @@ pub struct FunctionDefinition {
     #[graphql(name = "returnType")]
     pub return_type: String,
     pub cacheable: bool,
+    /// Interface the function is written against; it accepts any implementer's objects
+    #[graphql(name = "targetInterface")]
+    pub target_interface: Option<String>,
 }
 
 /// GraphQL result type for interface definitions
@@ -1632,6 +3464,9 @@ pub struct InterfaceDefinition {
     pub id: String,
     #[graphql(name = "displayName")]
     pub display_name: String,
+    /// Interfaces this one declares it extends
+    pub extends: Vec<String>,
+    /// Every property implementers provide, including inherited ones
     pub properties: Vec<PropertyOutput>,
     #[graphql(name = "implementers")]
     pub implementers: Vec<ImplementerInfo>,
@@ -1712,6 +3547,16 @@ pub struct UsageMetricsResult {
     pub edit_count: usize,
     #[graphql(name = "traversedCount")]
     pub traversed_count: usize,
+    /// Properties written to objects of the type without being declared on it
+    #[graphql(name = "unknownProperties")]
+    pub unknown_properties: Vec<UnknownPropertyCount>,
+}
+
+/// An undeclared property and how many writes carried it
+#[derive(SimpleObject)]
+pub struct UnknownPropertyCount {
+    pub property: String,
+    pub count: u64,
 }
 
 /// Distribution result
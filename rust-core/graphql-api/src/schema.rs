@@ -2,6 +2,18 @@ use async_graphql::{Schema, EmptySubscription, MergedObject};
 use crate::resolvers::QueryRoot;
 use crate::admin::AdminMutations;
 use crate::model_resolvers::{ModelQueries, ModelMutations};
+use ontology_engine::{OntologyHandle, PropertyValue};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::task::JoinHandle;
+use versioning::event_log::EventLog;
+
+/// Function result cache shared through the schema context
+pub type FunctionCache = Arc<tokio::sync::RwLock<HashMap<u64, PropertyValue>>>;
+
+/// Event log that mutations record object changes to, shared through the schema context
+pub type ObjectEventLog = Arc<tokio::sync::RwLock<EventLog>>;
 
 /// Combined query root with model queries
 #[derive(MergedObject, Default)]
@@ -16,3 +28,14 @@ pub fn create_schema() -> Schema<Query, Mutation, EmptySubscription> {
     Schema::build(Query::default(), Mutation::default(), EmptySubscription)
         .finish()
 }
+
+/// Clear cached function results whenever the ontology changes. The schema itself needs no
+/// rebuild: resolvers read the live snapshot from the `OntologyHandle` on every request.
+pub fn spawn_cache_invalidation(ontology: &OntologyHandle, cache: FunctionCache) -> JoinHandle<()> {
+    let mut changes = ontology.subscribe_changes();
+    tokio::spawn(async move {
+        while let Ok(_) | Err(RecvError::Lagged(_)) = changes.recv().await {
+            cache.write().await.clear();
+        }
+    })
+}
@@ -0,0 +1,111 @@
+//! Tracing for GraphQL requests.
+//!
+//! `RequestTracingExtension` runs each request in a `graphql.request` span with a fresh
+//! `request_id`, and each top-level field in a `graphql.resolve` span below it, so the store
+//! spans and events of a resolver can be traced back to the request. Resolvers fill in the
+//! field span's `object_type` and `result_count` with `record_object_type` and
+//! `record_result_count`.
+
+use async_graphql::extensions::{
+    Extension, ExtensionContext, ExtensionFactory, NextExecute, NextRequest, NextResolve, ResolveInfo,
+};
+use async_graphql::{Response, ServerResult, Value};
+use std::sync::Arc;
+use tracing::field::Empty;
+use tracing::{Instrument, Span};
+use tracing_subscriber::fmt::format::FmtSpan;
+use tracing_subscriber::EnvFilter;
+
+/// Log format of `init_tracing`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LogFormat {
+    #[default]
+    Text,
+    /// One JSON object per line, with the fields of the enclosing spans
+    Json,
+}
+
+impl LogFormat {
+    /// `json` selects JSON output; anything else is text
+    pub fn parse(format: &str) -> Self {
+        match format {
+            "json" => LogFormat::Json,
+            _ => LogFormat::Text,
+        }
+    }
+}
+
+/// Install the global tracing subscriber, writing to stderr. Levels come from `RUST_LOG`
+/// (e.g. `info,indexing=debug`), `info` if unset. Closed spans are logged with their busy
+/// and idle time, which is how store call timings show up.
+pub fn init_tracing(format: LogFormat) {
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+    let builder = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_span_events(FmtSpan::CLOSE)
+        .with_writer(std::io::stderr);
+    let installed = match format {
+        LogFormat::Text => builder.try_init(),
+        LogFormat::Json => builder.json().with_current_span(true).with_span_list(true).try_init(),
+    };
+    if let Err(e) = installed {
+        tracing::warn!("tracing subscriber not installed: {}", e);
+    }
+}
+
+/// Runs every request in a span carrying a request ID, and every top-level field in a span
+/// carrying the field name
+#[derive(Clone, Default)]
+pub struct RequestTracingExtension;
+
+impl ExtensionFactory for RequestTracingExtension {
+    fn create(&self) -> Arc<dyn Extension> {
+        Arc::new(RequestTracing)
+    }
+}
+
+struct RequestTracing;
+
+#[async_trait::async_trait]
+impl Extension for RequestTracing {
+    async fn request(&self, ctx: &ExtensionContext<'_>, next: NextRequest<'_>) -> Response {
+        let request_id = uuid::Uuid::new_v4().to_string();
+        let span = tracing::info_span!("graphql.request", request_id = %request_id, operation_name = Empty);
+        next.run(ctx).instrument(span).await
+    }
+
+    async fn execute(&self, ctx: &ExtensionContext<'_>, operation_name: Option<&str>, next: NextExecute<'_>) -> Response {
+        if let Some(operation_name) = operation_name {
+            Span::current().record("operation_name", operation_name);
+        }
+        next.run(ctx, operation_name).await
+    }
+
+    async fn resolve(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        info: ResolveInfo<'_>,
+        next: NextResolve<'_>,
+    ) -> ServerResult<Option<Value>> {
+        if info.path_node.parent.is_some() || info.is_for_introspection {
+            return next.run(ctx, info).await;
+        }
+        let span = tracing::info_span!(
+            "graphql.resolve",
+            operation = info.name,
+            object_type = Empty,
+            result_count = Empty,
+        );
+        next.run(ctx, info).instrument(span).await
+    }
+}
+
+/// Record the object type a top-level resolver works on in its span
+pub(crate) fn record_object_type(object_type: &str) {
+    Span::current().record("object_type", object_type);
+}
+
+/// Record how many results a top-level resolver returns in its span
+pub(crate) fn record_result_count(count: usize) {
+    Span::current().record("result_count", count as u64);
+}
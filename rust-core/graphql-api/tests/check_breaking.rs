@@ -0,0 +1,183 @@
+use async_graphql::{EmptySubscription, Schema};
+use graphql_api::api_version::{breaking_changes, DEPRECATIONS};
+use graphql_api::{AdminMutations, ApiSettings, ApiVersionExtension, QueryRoot, API_VERSION};
+use indexing::hydration::ObjectHydrator;
+use indexing::store::{IndexedObject, SearchStore};
+use ontology_engine::{Ontology, OntologyHandle};
+use serde_json::Value;
+use std::sync::Arc;
+
+/// Committed SDL of the released API. Regenerate with `UPDATE_SCHEMA_BASELINE=1` once a
+/// change is known to be compatible.
+const BASELINE: &str = "tests/fixtures/schema.graphql";
+
+fn sdl() -> String {
+    Schema::build(QueryRoot::default(), AdminMutations::default(), EmptySubscription)
+        .finish()
+        .sdl()
+}
+
+async fn create_versioned_schema(settings: ApiSettings) -> Schema<QueryRoot, AdminMutations, EmptySubscription> {
+    let yaml = r#"
+ontology:
+  objectTypes:
+    - id: "district"
+      displayName: "District"
+      primaryKey: "id"
+      properties:
+        - id: "id"
+          type: "string"
+          required: true
+        - id: "name"
+          type: "string"
+      titleKey: "name"
+  linkTypes: []
+"#;
+    let ontology = Ontology::from_yaml(yaml).expect("Failed to create test ontology");
+    let district = ontology
+        .get_object_type("district")
+        .unwrap()
+        .instantiate_from_json(&serde_json::json!({ "id": "d1", "name": "North" }))
+        .unwrap();
+    let search_store: Arc<dyn SearchStore> = Arc::new(indexing::InMemorySearchStore::new());
+    search_store
+        .bulk_index(vec![IndexedObject::new("district".to_string(), "d1".to_string(), district)])
+        .await
+        .unwrap();
+
+    Schema::build(QueryRoot::default(), AdminMutations::default(), EmptySubscription)
+        .data(OntologyHandle::new(ontology))
+        .data(search_store)
+        .data(ObjectHydrator::new())
+        .data(settings)
+        .extension(ApiVersionExtension)
+        .finish()
+}
+
+fn versioned_request(query: &str, version: Option<&str>) -> async_graphql::Request {
+    let mut request = async_graphql::Request::new(query);
+    if let Some(version) = version {
+        request
+            .extensions
+            .insert("apiVersion".to_string(), async_graphql::Value::String(version.to_string()));
+    }
+    request
+}
+
+#[test]
+fn check_breaking() {
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join(BASELINE);
+    let current = sdl();
+    if std::env::var("UPDATE_SCHEMA_BASELINE").as_deref() == Ok("1") {
+        std::fs::write(&path, &current).expect("Failed to write schema baseline");
+        return;
+    }
+    let baseline = std::fs::read_to_string(&path).expect("Schema baseline missing");
+    let changes = breaking_changes(&baseline, &current).unwrap();
+    assert!(
+        changes.is_empty(),
+        "Breaking schema changes; deprecate the fields in DEPRECATIONS first:\n{}",
+        changes.join("\n")
+    );
+}
+
+#[test]
+fn test_breaking_changes_require_recorded_deprecation() {
+    let baseline = r#"
+type Query {
+  callFunction(functionId: String!): Int!
+  executeFunction(functionId: String!): Int! @deprecated(reason: "Use `callFunction` instead.")
+  objectCount: Int!
+  title: String
+}
+"#;
+    let current = r#"
+type Query {
+  callFunction(functionId: String!): Int!
+  objectCount: String!
+  extra: Boolean!
+}
+"#;
+    let changes = breaking_changes(baseline, current).unwrap();
+    assert_eq!(
+        changes,
+        vec![
+            "Query.objectCount changed type from Int! to String! without a prior deprecation".to_string(),
+            "Query.title was removed without a prior deprecation".to_string(),
+        ]
+    );
+
+    // Marked deprecated in the SDL but missing from the registry: still breaking
+    let baseline = r#"type Query { title: String @deprecated(reason: "gone") }"#;
+    let changes = breaking_changes(baseline, "type Query { other: Int }").unwrap();
+    assert_eq!(changes, vec!["Query.title was removed without a prior deprecation".to_string()]);
+}
+
+#[test]
+fn test_registry_matches_schema_deprecations() {
+    let current = sdl();
+    for deprecation in DEPRECATIONS {
+        let directive = format!("@deprecated(reason: \"{}\")", deprecation.reason());
+        let declared = current
+            .lines()
+            .any(|line| line.trim_start().starts_with(deprecation.field) && line.contains(&directive));
+        assert!(
+            declared,
+            "{}.{} should carry {}",
+            deprecation.type_name, deprecation.field, directive
+        );
+    }
+    assert_eq!(current.matches("@deprecated(reason: \"").count(), DEPRECATIONS.len());
+}
+
+#[tokio::test]
+async fn test_meta_reports_api_version() {
+    let schema = create_versioned_schema(ApiSettings::default()).await;
+    let query = r#"query { meta { apiVersion compatMode legacyShapes deprecations { field replacement removal } } }"#;
+    let response = schema.execute(versioned_request(query, None)).await;
+    assert!(response.errors.is_empty(), "Query should succeed, got errors: {:?}", response.errors);
+    let json = response.data.into_json().unwrap();
+    assert_eq!(json["meta"]["apiVersion"], API_VERSION);
+    assert_eq!(json["meta"]["compatMode"], false);
+    assert_eq!(json["meta"]["legacyShapes"], false);
+    assert_eq!(json["meta"]["deprecations"][0]["field"], "executeFunction");
+    assert_eq!(json["meta"]["deprecations"][0]["replacement"], "callFunction");
+    assert_eq!(json["meta"]["deprecations"][0]["removal"], "3.0");
+}
+
+#[tokio::test]
+async fn test_compat_mode_serves_legacy_json_shapes() {
+    let query = r#"query { searchObjects(objectType: "district") { objectId properties } }"#;
+
+    let schema = create_versioned_schema(ApiSettings { compat_mode: true }).await;
+    let response = schema.execute(versioned_request(query, Some("2"))).await;
+    assert!(response.errors.is_empty(), "Query should succeed, got errors: {:?}", response.errors);
+    let json = response.data.into_json().unwrap();
+    assert_eq!(json["searchObjects"][0]["properties"]["name"], "North");
+
+    let response = schema.execute(versioned_request(query, Some("1"))).await;
+    assert!(response.errors.is_empty(), "Query should succeed, got errors: {:?}", response.errors);
+    let json = response.data.into_json().unwrap();
+    let encoded = json["searchObjects"][0]["properties"].as_str().expect("Legacy shape is a string");
+    let properties: Value = serde_json::from_str(encoded).unwrap();
+    assert_eq!(properties["name"], "North");
+
+    let response = schema.execute(versioned_request("query { meta { legacyShapes } }", Some("1.3"))).await;
+    assert_eq!(response.data.into_json().unwrap()["meta"]["legacyShapes"], true);
+
+    let response = schema.execute(versioned_request(query, Some("0"))).await;
+    assert_eq!(response.errors.len(), 1);
+    assert!(response.errors[0].message.contains("Unsupported API version 0"));
+}
+
+#[tokio::test]
+async fn test_legacy_version_rejected_without_compat_mode() {
+    let schema = create_versioned_schema(ApiSettings::default()).await;
+    let query = r#"query { searchObjects(objectType: "district") { properties } }"#;
+    let response = schema.execute(versioned_request(query, Some("1"))).await;
+    assert_eq!(response.errors.len(), 1);
+    assert!(response.errors[0].message.contains("only served in compat mode"));
+
+    let response = schema.execute(versioned_request(query, Some("v2"))).await;
+    assert!(response.errors[0].message.contains("Invalid API version 'v2'"));
+}
@@ -0,0 +1,252 @@
+use async_graphql::{EmptySubscription, Schema};
+use graphql_api::filters::{matches_filter, matches_filters};
+use graphql_api::{AdminMutations, QueryRoot};
+use indexing::hydration::ObjectHydrator;
+use indexing::store::{Filter, FilterOperator, IndexedObject, SearchStore};
+use indexing::InMemorySearchStore;
+use ontology_engine::{Ontology, OntologyHandle, PropertyValue};
+use serde_json::json;
+use std::sync::Arc;
+
+fn filter(property: &str, operator: FilterOperator, value: PropertyValue) -> Filter {
+    Filter {
+        property: property.to_string(),
+        operator,
+        value,
+        distance: None,
+        case_insensitive: false,
+    }
+}
+
+fn string(s: &str) -> PropertyValue {
+    PropertyValue::String(s.to_string())
+}
+
+fn strings(items: &[&str]) -> PropertyValue {
+    PropertyValue::Array(items.iter().map(|s| string(s)).collect())
+}
+
+#[test]
+fn test_comparison_operators() {
+    let city = json!({ "name": "Boston", "population": 650, "founded": "1630-09-17" });
+    let cases = [
+        (FilterOperator::Equals, PropertyValue::Integer(650), true),
+        (FilterOperator::Equals, PropertyValue::Double(650.0), true),
+        (FilterOperator::NotEquals, PropertyValue::Integer(650), false),
+        (FilterOperator::NotEquals, PropertyValue::Integer(700), true),
+        (FilterOperator::GreaterThan, PropertyValue::Integer(650), false),
+        (FilterOperator::GreaterThanOrEqual, PropertyValue::Integer(650), true),
+        (FilterOperator::LessThan, PropertyValue::Integer(651), true),
+        (FilterOperator::LessThanOrEqual, PropertyValue::Integer(649), false),
+    ];
+    for (operator, value, expected) in cases {
+        let f = filter("population", operator, value.clone());
+        assert_eq!(matches_filter(&city, &f), expected, "{:?} {:?}", operator, value);
+    }
+
+    // Dates compare as ISO 8601 strings
+    let f = filter("founded", FilterOperator::LessThan, PropertyValue::Date("1700-01-01".to_string()));
+    assert!(matches_filter(&city, &f));
+
+    // A missing property never matches, not even `not equals`
+    let f = filter("area", FilterOperator::NotEquals, PropertyValue::Integer(1));
+    assert!(!matches_filter(&city, &f));
+}
+
+#[test]
+fn test_string_operators_and_case() {
+    let city = json!({ "name": "Boston" });
+    let cases = [
+        (FilterOperator::Contains, "ost", true),
+        (FilterOperator::Contains, "OST", false),
+        (FilterOperator::StartsWith, "Bos", true),
+        (FilterOperator::StartsWith, "bos", false),
+        (FilterOperator::EndsWith, "ton", true),
+        (FilterOperator::EndsWith, "TON", false),
+    ];
+    for (operator, value, expected) in cases {
+        let sensitive = filter("name", operator, string(value));
+        assert_eq!(matches_filter(&city, &sensitive), expected, "{:?} {}", operator, value);
+        let insensitive = Filter { case_insensitive: true, ..sensitive };
+        assert!(matches_filter(&city, &insensitive), "{:?} {} ignoring case", operator, value);
+    }
+
+    // String operators do not apply to numbers
+    let f = filter("name", FilterOperator::Contains, PropertyValue::Integer(1));
+    assert!(!matches_filter(&city, &f));
+}
+
+#[test]
+fn test_in_and_not_in() {
+    let city = json!({ "state": "MA", "tags": ["port", "capital"] });
+    assert!(matches_filter(&city, &filter("state", FilterOperator::In, strings(&["MA", "TX"]))));
+    assert!(!matches_filter(&city, &filter("state", FilterOperator::In, strings(&["TX"]))));
+    assert!(matches_filter(&city, &filter("state", FilterOperator::NotIn, strings(&["TX"]))));
+    assert!(!matches_filter(&city, &filter("state", FilterOperator::NotIn, strings(&["MA"]))));
+
+    // Array properties match if any element does
+    assert!(matches_filter(&city, &filter("tags", FilterOperator::In, strings(&["capital"]))));
+    assert!(!matches_filter(&city, &filter("tags", FilterOperator::NotIn, strings(&["port"]))));
+
+    // A single value is a one-element list
+    assert!(matches_filter(&city, &filter("state", FilterOperator::In, string("MA"))));
+
+    let filters = [
+        filter("state", FilterOperator::In, strings(&["MA", "TX"])),
+        filter("tags", FilterOperator::NotIn, strings(&["inland"])),
+    ];
+    assert!(matches_filters(&city, &filters));
+}
+
+#[tokio::test]
+async fn test_search_objects_in_memory_filters() {
+    let yaml = r#"
+ontology:
+  objectTypes:
+    - id: "city"
+      displayName: "City"
+      primaryKey: "id"
+      properties:
+        - id: "id"
+          type: "string"
+        - id: "name"
+          type: "string"
+        - id: "state"
+          type: "string"
+        - id: "population"
+          type: "integer"
+  linkTypes: []
+"#;
+    let mut cities = Vec::new();
+    for (id, name, state, population) in [
+        ("c1", "Austin", "TX", 950),
+        ("c2", "Boston", "MA", 650),
+        ("c3", "Chicago", "IL", 2700),
+        ("c4", "Houston", "TX", 2300),
+    ] {
+        cities.push(json!({ "id": id, "name": name, "state": state, "population": population }));
+    }
+    let ontology = Ontology::from_yaml(yaml).unwrap();
+    let city = ontology.get_object_type("city").unwrap();
+    let objects = cities
+        .iter()
+        .map(|row| {
+            let properties = city.instantiate_from_json(row).unwrap();
+            IndexedObject::new("city".to_string(), row["id"].as_str().unwrap().to_string(), properties)
+        })
+        .collect();
+    let search_store: Arc<dyn SearchStore> = Arc::new(InMemorySearchStore::new());
+    search_store.bulk_index(objects).await.unwrap();
+
+    let schema = Schema::build(QueryRoot::default(), AdminMutations::default(), EmptySubscription)
+        .data(OntologyHandle::new(ontology))
+        .data(search_store)
+        .data(ObjectHydrator::new())
+        .finish();
+
+    let search = |filters: &str| {
+        let query = format!(r#"{{ searchObjects(objectType: "city", filters: [{}]) {{ objectId }} }}"#, filters);
+        let schema = &schema;
+        async move {
+            let response = schema.execute(query.as_str()).await;
+            assert!(response.errors.is_empty(), "{:?}", response.errors);
+            let json = response.data.into_json().unwrap();
+            let mut ids: Vec<String> = json["searchObjects"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|o| o["objectId"].as_str().unwrap().to_string())
+                .collect();
+            ids.sort();
+            ids
+        }
+    };
+
+    assert_eq!(
+        search(r#"{ property: "state", operator: "in", value: "[\"TX\", \"IL\"]" }"#).await,
+        vec!["c1", "c3", "c4"]
+    );
+    assert_eq!(
+        search(r#"{ property: "state", operator: "notIn", value: "[\"TX\"]" }, { property: "population", operator: "lte", value: "2700" }"#).await,
+        vec!["c2", "c3"]
+    );
+    assert_eq!(search(r#"{ property: "name", operator: "endsWith", value: "\"STON\"" }"#).await, Vec::<String>::new());
+    assert_eq!(
+        search(r#"{ property: "name", operator: "endsWith", value: "\"STON\"", caseInsensitive: true }"#).await,
+        vec!["c2", "c4"]
+    );
+}
+
+#[tokio::test]
+async fn test_within_distance_in_meters() {
+    let boston = r#"{"type":"Point","coordinates":[-71.0589,42.3601]}"#;
+    let cambridge = r#"{"type":"Point","coordinates":[-71.1097,42.3736]}"#;
+    let nyc = r#"{"type":"Point","coordinates":[-74.0060,40.7128]}"#;
+
+    // In-memory objects hold geometries as GeoJSON strings
+    let near = |meters: f64| Filter {
+        distance: Some(meters),
+        ..filter("location", FilterOperator::WithinDistance, PropertyValue::GeoJSON(boston.to_string()))
+    };
+    let station = json!({ "location": cambridge });
+    assert!(matches_filter(&station, &near(5_000.0)));
+    assert!(!matches_filter(&station, &near(3_000.0)));
+
+    let yaml = r#"
+ontology:
+  objectTypes:
+    - id: "station"
+      displayName: "Station"
+      primaryKey: "id"
+      properties:
+        - id: "id"
+          type: "string"
+        - id: "location"
+          type: "geojson"
+          geo:
+            validation: "strict"
+  linkTypes: []
+"#;
+    let search_store: Arc<dyn SearchStore> = Arc::new(indexing::InMemorySearchStore::new());
+    for (id, location) in [("bos", boston), ("cam", cambridge), ("nyc", nyc)] {
+        let mut properties = ontology_engine::PropertyMap::new();
+        properties.insert("id".to_string(), string(id));
+        properties.insert("location".to_string(), PropertyValue::GeoJSON(location.to_string()));
+        search_store.index_object("station", id, &properties, None).await.unwrap();
+    }
+    let schema = Schema::build(QueryRoot::default(), AdminMutations::default(), EmptySubscription)
+        .data(OntologyHandle::new(Ontology::from_yaml(yaml).unwrap()))
+        .data(search_store)
+        .data(ObjectHydrator::new())
+        .finish();
+    let query = r#"query($geometry: String!, $distance: Float) {
+        spatialQuery(objectType: "station", property: "location", operator: "within_distance", geometry: $geometry, distance: $distance) { objectId }
+    }"#;
+    let spatial = |geometry: &str, distance: Option<f64>| {
+        let request = async_graphql::Request::new(query)
+            .variables(async_graphql::Variables::from_json(json!({ "geometry": geometry, "distance": distance })));
+        let schema = &schema;
+        async move { schema.execute(request).await }
+    };
+
+    let response = spatial(boston, Some(10_000.0)).await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    let json = response.data.into_json().unwrap();
+    let mut ids: Vec<&str> = json["spatialQuery"].as_array().unwrap().iter().map(|o| o["objectId"].as_str().unwrap()).collect();
+    ids.sort();
+    assert_eq!(ids, vec!["bos", "cam"]);
+
+    let response = spatial(boston, None).await;
+    assert!(response.errors[0].message.contains("needs a distance in meters"), "{:?}", response.errors);
+
+    let area = r#"{"type":"Polygon","coordinates":[[[-71.2,42.3],[-71.0,42.3],[-71.0,42.4],[-71.2,42.3]]]}"#;
+    let response = spatial(area, Some(10_000.0)).await;
+    assert!(response.errors[0].message.contains("got Polygon"), "{:?}", response.errors);
+    let response = spatial(boston, Some(-1.0)).await;
+    assert!(response.errors[0].message.contains("positive number of meters"), "{:?}", response.errors);
+
+    // Web Mercator meters are rejected by the strict property
+    let projected = r#"{"type":"Point","coordinates":[-7910240.6,5215074.2]}"#;
+    let response = spatial(projected, Some(10_000.0)).await;
+    assert!(response.errors[0].message.contains("outside longitude"), "{:?}", response.errors);
+}
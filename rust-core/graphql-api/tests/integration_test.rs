@@ -1,6 +1,6 @@
 use async_graphql::{Schema, EmptySubscription};
 use graphql_api::{QueryRoot, AdminMutations};
-use ontology_engine::Ontology;
+use ontology_engine::{Ontology, OntologyHandle};
 use indexing::store::{ParquetStore, ElasticsearchStore, SearchStore};
 use indexing::hydration::ObjectHydrator;
 use versioning::time_query::TimeQuery;
@@ -8,7 +8,6 @@ use versioning::event_log::EventLog;
 use std::sync::Arc;
 use std::collections::HashMap;
 use ontology_engine::PropertyValue;
-use serde_json::Value;
 
 /// Create a test schema with minimal dependencies
 async fn create_minimal_test_schema() -> Schema<QueryRoot, AdminMutations, EmptySubscription> {
@@ -54,17 +53,12 @@ ontology:
     let function_cache: Arc<tokio::sync::RwLock<HashMap<u64, PropertyValue>>> = 
         Arc::new(tokio::sync::RwLock::new(HashMap::new()));
     
-    // Create in-memory data store
-    let data_store: Arc<tokio::sync::RwLock<HashMap<String, Vec<Value>>>> = 
-        Arc::new(tokio::sync::RwLock::new(HashMap::new()));
-    
     Schema::build(QueryRoot::default(), AdminMutations::default(), EmptySubscription)
-        .data(Arc::new(ontology))
+        .data(OntologyHandle::new(ontology))
         .data(search_store)
         .data(columnar_store)
         .data(time_query)
         .data(hydrator)
-        .data(data_store)
         .data(function_cache)
         .finish()
 }
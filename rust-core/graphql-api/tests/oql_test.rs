@@ -0,0 +1,367 @@
+use async_graphql::{EmptySubscription, Schema};
+use graphql_api::oql::{parse, OqlQuery};
+use graphql_api::{AdminMutations, QueryRoot};
+use indexing::hydration::ObjectHydrator;
+use indexing::store::{FilterOperator, IndexedObject, SearchStore};
+use ontology_engine::{Ontology, OntologyHandle, PropertyMap, PropertyValue};
+use std::sync::Arc;
+
+const PLANT_ONTOLOGY: &str = r#"
+ontology:
+  objectTypes:
+    - id: "Plant"
+      displayName: "Power Plant"
+      primaryKey: "id"
+      titleKey: "name"
+      properties:
+        - id: "id"
+          type: "string"
+          required: true
+        - id: "name"
+          type: "string"
+        - id: "state"
+          type: "string"
+        - id: "year"
+          type: "integer"
+        - id: "population"
+          type: "double"
+        - id: "active"
+          type: "boolean"
+        - id: "commissioned"
+          type: "date"
+        - id: "inspected_at"
+          type: "datetime"
+        - id: "footprint"
+          type: "geojson"
+        - id: "notes"
+          type: "string"
+          indexing: "not_indexed"
+        - id: "summary"
+          type: "string"
+          indexing: "text_only"
+  linkTypes: []
+"#;
+
+fn ontology() -> Ontology {
+    Ontology::from_yaml(PLANT_ONTOLOGY).expect("Failed to create test ontology")
+}
+
+/// Compact rendering of a parsed query: `filters | sort | limit | offset`
+fn render(query: &OqlQuery) -> String {
+    let filters: Vec<String> = query
+        .filters
+        .iter()
+        .map(|f| format!("{} {:?} {}", f.property, f.operator, serde_json::to_string(&f.value).unwrap()))
+        .collect();
+    let sort: Vec<String> = query
+        .sort
+        .iter()
+        .map(|s| format!("{} {}", s.property, if s.ascending { "asc" } else { "desc" }))
+        .collect();
+    format!(
+        "{} | {} | {:?} | {:?}",
+        filters.join(" & "),
+        sort.join(", "),
+        query.limit,
+        query.offset
+    )
+}
+
+#[test]
+fn test_parses_clauses() {
+    let ontology = ontology();
+    let cases = [
+        ("Plant", " |  | None | None"),
+        (
+            r#"Plant where state = "NJ" and year >= 2015 order by population desc limit 50"#,
+            r#"state Equals "NJ" & year GreaterThanOrEqual 2015 | population desc | Some(50) | None"#,
+        ),
+        (
+            "Plant WHERE state = 'NJ' ORDER BY year ASC, name LIMIT 5 OFFSET 10",
+            r#"state Equals "NJ" | year asc, name asc | Some(5) | Some(10)"#,
+        ),
+        (
+            "Plant order by year desc, name asc offset 20",
+            " | year desc, name asc | None | Some(20)",
+        ),
+        (
+            r#"Plant where name = "Salem \"Unit\" 2""#,
+            r#"name Equals "Salem \"Unit\" 2" |  | None | None"#,
+        ),
+        (
+            "Plant where population < -1.5",
+            "population LessThan -1.5 |  | None | None",
+        ),
+        (
+            "Plant where population > 3",
+            "population GreaterThan 3.0 |  | None | None",
+        ),
+    ];
+    for (oql, expected) in cases {
+        let query = parse(oql, &ontology).unwrap_or_else(|e| panic!("{}: {}", oql, e));
+        assert_eq!(render(&query), expected, "{}", oql);
+    }
+    assert_eq!(parse("Plant limit 3", &ontology).unwrap().object_type, "Plant");
+}
+
+#[test]
+fn test_operator_coverage() {
+    let ontology = ontology();
+    let cases = [
+        (r#"state = "NJ""#, FilterOperator::Equals, r#""NJ""#),
+        (r#"state != "NJ""#, FilterOperator::NotEquals, r#""NJ""#),
+        (r#"state <> "NJ""#, FilterOperator::NotEquals, r#""NJ""#),
+        ("year < 2000", FilterOperator::LessThan, "2000"),
+        ("year <= 2000", FilterOperator::LessThanOrEqual, "2000"),
+        ("year > 2000", FilterOperator::GreaterThan, "2000"),
+        ("year >= 2000", FilterOperator::GreaterThanOrEqual, "2000"),
+        (r#"name contains "Creek""#, FilterOperator::Contains, r#""Creek""#),
+        (r#"name startswith "Salem""#, FilterOperator::StartsWith, r#""Salem""#),
+        (r#"name endswith "Unit 2""#, FilterOperator::EndsWith, r#""Unit 2""#),
+        (r#"state in ("NJ", "NY")"#, FilterOperator::In, r#"["NJ","NY"]"#),
+        (r#"state not in ("NJ")"#, FilterOperator::NotIn, r#"["NJ"]"#),
+        ("active = true", FilterOperator::Equals, "true"),
+        ("active != FALSE", FilterOperator::NotEquals, "false"),
+        (r#"commissioned >= "2015-01-01""#, FilterOperator::GreaterThanOrEqual, r#""2015-01-01""#),
+        (
+            r#"inspected_at < "2020-06-01T00:00:00Z""#,
+            FilterOperator::LessThan,
+            r#""2020-06-01T00:00:00Z""#,
+        ),
+    ];
+    for (condition, operator, value) in cases {
+        let oql = format!("Plant where {}", condition);
+        let query = parse(&oql, &ontology).unwrap_or_else(|e| panic!("{}: {}", oql, e));
+        assert_eq!(query.filters.len(), 1, "{}", oql);
+        assert_eq!(query.filters[0].operator, operator, "{}", oql);
+        assert_eq!(serde_json::to_string(&query.filters[0].value).unwrap(), value, "{}", oql);
+    }
+}
+
+#[test]
+fn test_and_binds_tighter_than_or() {
+    let ontology = ontology();
+    // Ok(rendered filters) or Err(column of the offending `or`)
+    let cases: [(&str, Result<&str, usize>); 14] = [
+        (
+            r#"state = "NJ" or state = "NY""#,
+            Ok(r#"state In ["NJ","NY"]"#),
+        ),
+        (
+            r#"(state = "NJ" or state = "NY") and year > 2000"#,
+            Ok(r#"state In ["NJ","NY"] & year GreaterThan 2000"#),
+        ),
+        (
+            r#"year > 2000 and (state = "NJ" or state in ("NY", "NJ", "PA"))"#,
+            Ok(r#"year GreaterThan 2000 & state In ["NJ","NY","PA"]"#),
+        ),
+        (
+            r#"state = "NJ" or (state = "NY" or state = "PA")"#,
+            Ok(r#"state In ["NJ","NY","PA"]"#),
+        ),
+        (
+            r#"((year = 2001)) and ((state = "NJ"))"#,
+            Ok(r#"year Equals 2001 & state Equals "NJ""#),
+        ),
+        // Without parentheses this is `state = "NJ" or (state = "NY" and year > 2000)`
+        (r#"state = "NJ" or state = "NY" and year > 2000"#, Err(26)),
+        (r#"state = "NJ" or year = 2001"#, Err(26)),
+        (r#"year > 2000 or year = 1990"#, Err(25)),
+        // `and` groups first on either side of `or`: the left branch here is the conjunction
+        // `year > 2000 and state = "NJ"`, not `state = "NJ"` alone
+        (r#"year > 2000 and state = "NJ" or state = "NY""#, Err(42)),
+        (r#"(year > 2000 and state = "NJ") or state = "NY""#, Err(44)),
+        (r#"year > 2000 and (state = "NJ" or state = "NY")"#, Ok(r#"year GreaterThan 2000 & state In ["NJ","NY"]"#)),
+        (
+            r#"year > 2000 and (state = "NJ" or state = "NY") and active = true"#,
+            Ok(r#"year GreaterThan 2000 & state In ["NJ","NY"] & active Equals true"#),
+        ),
+        (
+            r#"(state = "NJ" or state = "NY" or state = "PA") and (year > 2000 and active = true)"#,
+            Ok(r#"state In ["NJ","NY","PA"] & year GreaterThan 2000 & active Equals true"#),
+        ),
+        // The error points at the `or` whose branch is unsupported
+        (r#"state = "NJ" or state = "NY" or state = "PA" and year > 2000"#, Err(42)),
+    ];
+    for (condition, expected) in cases {
+        let oql = format!("Plant where {}", condition);
+        let result = parse(&oql, &ontology);
+        match expected {
+            Ok(filters) => {
+                let query = result.unwrap_or_else(|e| panic!("{}: {}", oql, e));
+                assert_eq!(render(&query), format!("{} |  | None | None", filters), "{}", oql);
+            }
+            Err(column) => {
+                let error = result.expect_err(&oql);
+                assert_eq!(
+                    error.message, "'or' can only join '=' and 'in' comparisons on the same property",
+                    "{}",
+                    oql
+                );
+                assert_eq!(error.column, column, "{}", oql);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_parse_errors_report_position() {
+    let ontology = ontology();
+    let cases = [
+        ("", "Expected an object type", 1),
+        ("Factory where year = 1", "Unknown object type 'Factory'", 1),
+        // Keywords are case-insensitive, object types and properties are not
+        ("plant", "Unknown object type 'plant'", 1),
+        ("Plant where State = 'NJ'", "Unknown property 'State' on object type 'Plant'", 13),
+        ("Plant where yeer = 1", "Unknown property 'yeer' on object type 'Plant'", 13),
+        ("Plant where year = \"1990\"", "Property 'year' of type integer cannot be compared with \"1990\"", 20),
+        ("Plant where year = 19.5", "Property 'year' of type integer cannot be compared with 19.5", 20),
+        ("Plant where state = 7", "Property 'state' of type string cannot be compared with 7", 21),
+        ("Plant where active = 'yes'", "Property 'active' of type boolean cannot be compared with \"yes\"", 22),
+        (
+            "Plant where commissioned = '2015-13-01'",
+            "Property 'commissioned' of type date cannot be compared with \"2015-13-01\"",
+            28,
+        ),
+        ("Plant where active > true", "Operator '>' is not supported on property 'active' of type boolean", 20),
+        ("Plant where year contains 5", "Operator 'contains' is not supported on property 'year' of type integer", 18),
+        ("Plant where footprint = 'x'", "Operator '=' is not supported on property 'footprint' of type geojson", 23),
+        (
+            "Plant where notes = 'x'",
+            "Cannot filter on property 'notes' of object type 'Plant': it has indexing 'not_indexed'",
+            13,
+        ),
+        (
+            "Plant order by summary",
+            "Cannot sort on property 'summary' of object type 'Plant': it has indexing 'text_only'",
+            16,
+        ),
+        ("Plant where year", "Expected an operator after 'year'", 17),
+        ("Plant where year ~ 3", "Unexpected character '~'", 18),
+        ("Plant where year = ", "Expected a value", 20),
+        ("Plant where year = and", "Expected a value, found 'and'", 20),
+        ("Plant where state = 'NJ", "Unterminated string", 21),
+        ("Plant where state in 'NJ'", "Expected '(', found \"NJ\"", 22),
+        ("Plant where state in ('NJ'", "Expected ')'", 27),
+        ("Plant where state not 'NJ'", "Expected 'in', found \"NJ\"", 23),
+        ("Plant where (year = 1", "Expected ')'", 22),
+        ("Plant where year = 1)", "Unexpected ')'", 21),
+        ("Plant order year", "Expected 'by', found 'year'", 13),
+        ("Plant order by", "Expected a property", 15),
+        ("Plant limit ten", "Expected a non-negative integer", 13),
+        ("Plant limit -1", "Expected a non-negative integer, found -1", 13),
+        ("Plant limit 5 order by year", "Unexpected 'order'", 15),
+    ];
+    for (oql, message, column) in cases {
+        let error = parse(oql, &ontology).expect_err(oql);
+        assert_eq!(error.message, message, "{}", oql);
+        assert_eq!(error.column, column, "{}", oql);
+        assert_eq!(error.to_string(), format!("{} at column {}", message, column));
+    }
+}
+
+fn plant(id: &str, state: &str, year: i64, population: f64) -> PropertyMap {
+    let mut properties = PropertyMap::new();
+    properties.insert("id".to_string(), PropertyValue::String(id.to_string()));
+    properties.insert("name".to_string(), PropertyValue::String(format!("Plant {}", id)));
+    properties.insert("state".to_string(), PropertyValue::String(state.to_string()));
+    properties.insert("year".to_string(), PropertyValue::Integer(year));
+    properties.insert("population".to_string(), PropertyValue::Double(population));
+    properties
+}
+
+async fn oql_ids(schema: &Schema<QueryRoot, AdminMutations, EmptySubscription>, oql: &str) -> Vec<String> {
+    let request = async_graphql::Request::new("query($oql: String!) { queryOql(oql: $oql) { objectId } }")
+        .variables(async_graphql::Variables::from_json(serde_json::json!({ "oql": oql })));
+    let response = schema.execute(request).await;
+    assert!(response.errors.is_empty(), "Query should succeed, got errors: {:?}", response.errors);
+    response.data.into_json().unwrap()["queryOql"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|o| o["objectId"].as_str().unwrap().to_string())
+        .collect()
+}
+
+#[tokio::test]
+async fn test_query_oql_runs_search() {
+    let search_store: Arc<dyn SearchStore> = Arc::new(indexing::InMemorySearchStore::new());
+    for (id, state, year, population) in [
+        ("p1", "NJ", 2016, 900.0),
+        ("p2", "NJ", 2012, 5000.0),
+        ("p3", "NY", 2019, 1200.0),
+        ("p4", "NJ", 2020, 3100.0),
+        ("p5", "PA", 2018, 100.0),
+    ] {
+        search_store
+            .index_object("Plant", id, &plant(id, state, year, population), None)
+            .await
+            .unwrap();
+    }
+    let schema = Schema::build(QueryRoot::default(), AdminMutations::default(), EmptySubscription)
+        .data(OntologyHandle::new(ontology()))
+        .data(search_store)
+        .data(ObjectHydrator::new())
+        .finish();
+
+    assert_eq!(
+        oql_ids(&schema, r#"Plant where state = "NJ" and year >= 2015 order by population desc limit 50"#).await,
+        vec!["p4", "p1"]
+    );
+    assert_eq!(
+        oql_ids(&schema, r#"Plant where state = "NY" or state = "PA" order by year"#).await,
+        vec!["p5", "p3"]
+    );
+    // Without `order by` the type's default sort (primary key) applies, then the page
+    assert_eq!(oql_ids(&schema, "Plant limit 2 offset 1").await, vec!["p2", "p3"]);
+
+    let request = async_graphql::Request::new(r#"query { queryOql(oql: "Plant where yeer > 1") { objectId } }"#);
+    let response = schema.execute(request).await;
+    assert_eq!(
+        response.errors[0].message,
+        "Invalid OQL query: Unknown property 'yeer' on object type 'Plant' at column 13"
+    );
+}
+
+#[tokio::test]
+async fn test_query_oql_applies_object_acls() {
+    let plants = vec![
+        serde_json::json!({ "id": "p1", "name": "Salem", "state": "NJ", "year": 2016 }),
+        serde_json::json!({ "id": "p2", "name": "Hope Creek", "state": "NJ", "year": 1986 }),
+        serde_json::json!({
+            "id": "p3", "name": "Oyster Creek", "state": "NJ", "year": 2019,
+            "_acl": [{ "principal": "user:bob", "permission": "deny" }]
+        }),
+        serde_json::json!({ "id": "p4", "name": "Indian Point", "state": "NY", "year": 2021 }),
+    ];
+    let ontology = ontology();
+    let plant_type = ontology.get_object_type("Plant").unwrap();
+    let objects = plants
+        .iter()
+        .map(|row| {
+            let properties = plant_type.instantiate_from_json(row).unwrap();
+            IndexedObject::new("Plant".to_string(), row["id"].as_str().unwrap().to_string(), properties)
+        })
+        .collect();
+    let search_store: Arc<dyn SearchStore> = Arc::new(indexing::InMemorySearchStore::new());
+    search_store.bulk_index(objects).await.unwrap();
+    let schema = Schema::build(QueryRoot::default(), AdminMutations::default(), EmptySubscription)
+        .data(OntologyHandle::new(ontology))
+        .data(search_store)
+        .data(ObjectHydrator::new())
+        .finish();
+
+    let query = r#"query { queryOql(oql: "Plant where state in ('NJ') and year >= 2000 and name endswith 'Creek'") { objectId } }"#;
+    let run = |user: &str| {
+        let request = async_graphql::Request::new(query)
+            .data(security::SecurityContext::new(user.to_string()));
+        let schema = schema.clone();
+        async move {
+            let response = schema.execute(request).await;
+            assert!(response.errors.is_empty(), "Query should succeed, got errors: {:?}", response.errors);
+            response.data.into_json().unwrap()["queryOql"].clone()
+        }
+    };
+    assert_eq!(run("alice").await, serde_json::json!([{ "objectId": "p3" }]));
+    assert_eq!(run("bob").await, serde_json::json!([]));
+}
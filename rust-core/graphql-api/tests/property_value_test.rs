@@ -0,0 +1,258 @@
+use async_graphql::{EmptyMutation, EmptySubscription, Object, Schema};
+use graphql_api::property_value::{coerce, decode, encode};
+use graphql_api::{AdminMutations, PropertyValueScalar, QueryRoot};
+use indexing::hydration::ObjectHydrator;
+use indexing::store::{GraphStore, SearchStore};
+use ontology_engine::{Ontology, OntologyHandle, PropertyMap, PropertyType, PropertyValue};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+struct EchoQuery;
+
+#[Object]
+impl EchoQuery {
+    /// A top-level `Null` travels as an absent value
+    async fn echo(&self, value: Option<PropertyValueScalar>) -> Option<PropertyValueScalar> {
+        value
+    }
+
+    async fn echo_map(&self, values: HashMap<String, PropertyValueScalar>) -> HashMap<String, PropertyValueScalar> {
+        values
+    }
+}
+
+/// Deterministic xorshift generator, so failures reproduce
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: u64) -> u64 {
+        self.next() % n
+    }
+}
+
+fn random_key(rng: &mut Rng) -> String {
+    ["a", "b", "$date", "$x", "name", "", "type"][rng.below(7) as usize].to_string()
+}
+
+fn random_fields(rng: &mut Rng, depth: u32) -> HashMap<String, PropertyValue> {
+    (0..rng.below(4)).map(|_| (random_key(rng), random_value(rng, depth + 1))).collect()
+}
+
+fn random_value(rng: &mut Rng, depth: u32) -> PropertyValue {
+    let variants = if depth >= 3 { 9 } else { 12 };
+    match rng.below(variants) {
+        0 => PropertyValue::String(["", "plain", "2020-01-01", "{\"$ref\":\"x\"}", "ünïcode"][rng.below(5) as usize].to_string()),
+        1 => PropertyValue::Integer([0, -1, 42, i64::MAX, i64::MIN][rng.below(5) as usize]),
+        2 => PropertyValue::Double([0.5, -2.0, 3.0, 1e300, f64::INFINITY, f64::NEG_INFINITY][rng.below(6) as usize]),
+        3 => PropertyValue::Boolean(rng.below(2) == 0),
+        4 => PropertyValue::Date(["2020-01-01", "1999-12-31"][rng.below(2) as usize].to_string()),
+        5 => PropertyValue::DateTime(["2020-01-01T00:00:00Z", "2021-06-30T12:30:00+02:00"][rng.below(2) as usize].to_string()),
+        6 => PropertyValue::ObjectReference(["plant:123", "p1"][rng.below(2) as usize].to_string()),
+        7 => PropertyValue::GeoJSON(r#"{"type":"Point","coordinates":[-98.5,39.8]}"#.to_string()),
+        8 => PropertyValue::Null,
+        9 => PropertyValue::Array((0..rng.below(4)).map(|_| random_value(rng, depth + 1)).collect()),
+        10 => PropertyValue::Map(random_fields(rng, depth)),
+        _ => PropertyValue::Object(random_fields(rng, depth)),
+    }
+}
+
+#[tokio::test]
+async fn test_every_variant_round_trips_through_a_request() {
+    let schema = Schema::new(EchoQuery, EmptyMutation, EmptySubscription);
+    let mut rng = Rng(0x5eed);
+    let mut values: Vec<PropertyValue> = (0..300).map(|_| random_value(&mut rng, 0)).collect();
+    values.push(PropertyValue::Map(HashMap::from([("$date".to_string(), PropertyValue::String("2020-01-01".to_string()))])));
+    values.push(PropertyValue::Object(HashMap::new()));
+    values.push(PropertyValue::Map(HashMap::new()));
+
+    for value in values {
+        let variables = serde_json::json!({ "value": encode(&value) });
+        let request = async_graphql::Request::new("query($value: PropertyValue) { echo(value: $value) }")
+            .variables(async_graphql::Variables::from_json(variables));
+        let response = schema.execute(request).await;
+        assert!(response.errors.is_empty(), "{:?} for {:?}", response.errors, value);
+        // Serialize the response as a server would send it
+        let wire = serde_json::to_string(&response.data).unwrap();
+        let data: serde_json::Value = serde_json::from_str(&wire).unwrap();
+        let echoed: PropertyValueScalar = serde_json::from_value(data["echo"].clone()).unwrap();
+        assert_eq!(echoed.0, value, "wire form {}", wire);
+        assert_eq!(decode(data["echo"].clone()).unwrap(), value);
+    }
+
+    // Maps of values, as taken by `typedParameters`, go through the same encoding
+    let mut rng = Rng(0xfeed);
+    let values: HashMap<String, PropertyValue> = (0..20).map(|i| (format!("p{}", i), random_value(&mut rng, 0))).collect();
+    let encoded: serde_json::Map<String, serde_json::Value> =
+        values.iter().map(|(key, value)| (key.clone(), encode(value))).collect();
+    let request = async_graphql::Request::new("query($values: JSONObject!) { echoMap(values: $values) }")
+        .variables(async_graphql::Variables::from_json(serde_json::json!({ "values": encoded })));
+    let response = schema.execute(request).await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    let echoed: HashMap<String, PropertyValueScalar> =
+        serde_json::from_value(response.data.into_json().unwrap()["echoMap"].clone()).unwrap();
+    assert_eq!(echoed.into_iter().map(|(key, value)| (key, value.0)).collect::<HashMap<_, _>>(), values);
+}
+
+#[test]
+fn test_tagged_values_are_validated() {
+    assert_eq!(
+        decode(serde_json::json!({ "$date": "2020-02-29" })).unwrap(),
+        PropertyValue::Date("2020-02-29".to_string())
+    );
+    assert!(decode(serde_json::json!({ "$date": "2021-02-29" })).unwrap_err().contains("Invalid date"));
+    assert!(decode(serde_json::json!({ "$datetime": "2020-01-01" })).unwrap_err().contains("Invalid datetime"));
+    assert!(decode(serde_json::json!({ "$geojson": "not json" })).unwrap_err().contains("Invalid GeoJSON"));
+    assert!(decode(serde_json::json!({ "$ref": "" })).is_err());
+    assert!(decode(serde_json::json!({ "$plant": "p1" })).unwrap_err().contains("Unknown PropertyValue tag '$plant'"));
+    assert!(decode(serde_json::json!({ "$date": 20200101 })).unwrap_err().contains("expects a string"));
+
+    // Only single-key objects are tags
+    assert!(matches!(
+        decode(serde_json::json!({ "$date": "2020-01-01", "note": "x" })).unwrap(),
+        PropertyValue::Map(map) if map.len() == 2
+    ));
+    assert_eq!(
+        decode(serde_json::json!({ "$geojson": { "type": "Point", "coordinates": [1, 2] } })).unwrap(),
+        PropertyValue::GeoJSON(r#"{"coordinates":[1,2],"type":"Point"}"#.to_string())
+    );
+}
+
+#[test]
+fn test_coerce_uses_declared_types() {
+    let string = |s: &str| PropertyValue::String(s.to_string());
+    assert_eq!(coerce(string("2020-01-01"), &PropertyType::Date).unwrap(), PropertyValue::Date("2020-01-01".to_string()));
+    assert!(coerce(string("January"), &PropertyType::Date).is_err());
+    assert_eq!(coerce(PropertyValue::Integer(3), &PropertyType::Double).unwrap(), PropertyValue::Double(3.0));
+    assert_eq!(coerce(string("p1"), &PropertyType::ObjectReference).unwrap(), PropertyValue::ObjectReference("p1".to_string()));
+    assert_eq!(
+        coerce(
+            PropertyValue::Array(vec![string("2020-01-01T00:00:00Z")]),
+            &PropertyType::Array { element_type: Box::new(PropertyType::DateTime) }
+        )
+        .unwrap(),
+        PropertyValue::Array(vec![PropertyValue::DateTime("2020-01-01T00:00:00Z".to_string())])
+    );
+    // Candidates of an `in` filter on a date property
+    assert_eq!(
+        coerce(PropertyValue::Array(vec![string("2020-01-01")]), &PropertyType::Date).unwrap(),
+        PropertyValue::Array(vec![PropertyValue::Date("2020-01-01".to_string())])
+    );
+    // Tagged input is already typed and mismatches are left for validation
+    assert_eq!(coerce(PropertyValue::Integer(3), &PropertyType::Date).unwrap(), PropertyValue::Integer(3));
+    assert_eq!(coerce(string("x"), &PropertyType::String).unwrap(), string("x"));
+}
+
+#[tokio::test]
+async fn test_typed_filter_values_and_function_parameters() {
+    let yaml = r#"
+ontology:
+  objectTypes:
+    - id: "site"
+      displayName: "Site"
+      primaryKey: "id"
+      properties:
+        - id: "id"
+          type: "string"
+    - id: "inspection"
+      displayName: "Inspection"
+      primaryKey: "id"
+      properties:
+        - id: "id"
+          type: "string"
+        - id: "day"
+          type: "date"
+        - id: "score"
+          type: "double"
+  linkTypes:
+    - id: "site_inspections"
+      displayName: "Site Inspections"
+      source: "site"
+      target: "inspection"
+  functionTypes:
+    - id: "inspections_at_site"
+      displayName: "Inspections at Site"
+      parameters:
+        - id: "site"
+          type: "object_reference"
+          required: true
+      returnType:
+        type: "array"
+        element_type:
+          type: "object_type"
+          object_type: "inspection"
+      logic:
+        type: "link_traversal"
+        linkType: "site_inspections"
+        targetType: "inspection"
+"#;
+    let search_store: Arc<dyn SearchStore> = Arc::new(indexing::InMemorySearchStore::new());
+    let graph_store: Arc<dyn GraphStore> = Arc::new(indexing::InMemoryGraphStore::new());
+    for (id, day, score) in [("i1", "2020-01-01", 3.0), ("i2", "2020-03-15", 4.5)] {
+        let mut properties = PropertyMap::new();
+        properties.insert("id".to_string(), PropertyValue::String(id.to_string()));
+        properties.insert("day".to_string(), PropertyValue::Date(day.to_string()));
+        properties.insert("score".to_string(), PropertyValue::Double(score));
+        search_store.index_object("inspection", id, &properties, None).await.unwrap();
+        graph_store.create_link("site_inspections", "s1", id, &PropertyMap::new()).await.unwrap();
+    }
+    let schema = Schema::build(QueryRoot::default(), AdminMutations::default(), EmptySubscription)
+        .data(OntologyHandle::new(Ontology::from_yaml(yaml).unwrap()))
+        .data(search_store)
+        .data(graph_store)
+        .data(ObjectHydrator::new())
+        .finish();
+
+    let search = |filter: serde_json::Value| {
+        let request = async_graphql::Request::new(
+            r#"query($filter: FilterInput!) { searchObjects(objectType: "inspection", filters: [$filter]) { objectId } }"#,
+        )
+        .variables(async_graphql::Variables::from_json(serde_json::json!({ "filter": filter })));
+        let schema = schema.clone();
+        async move { schema.execute(request).await }
+    };
+    let ids = |response: async_graphql::Response| -> Vec<String> {
+        assert!(response.errors.is_empty(), "{:?}", response.errors);
+        response.data.into_json().unwrap()["searchObjects"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|o| o["objectId"].as_str().unwrap().to_string())
+            .collect()
+    };
+
+    let typed = serde_json::json!({ "property": "day", "operator": "gt", "typedValue": { "$date": "2020-02-01" } });
+    assert_eq!(ids(search(typed).await), vec!["i2"]);
+    // JSON string values are coerced to the property's type
+    let legacy = serde_json::json!({ "property": "day", "operator": "equals", "value": "\"2020-01-01\"" });
+    assert_eq!(ids(search(legacy).await), vec!["i1"]);
+    let score = serde_json::json!({ "property": "score", "operator": "gte", "typedValue": 4 });
+    assert_eq!(ids(search(score).await), vec!["i2"]);
+    let invalid = serde_json::json!({ "property": "day", "operator": "equals", "typedValue": "2020-13-01" });
+    let response = search(invalid).await;
+    assert!(response.errors[0].message.contains("Invalid filter value for 'day': Invalid date"), "{:?}", response.errors);
+
+    let request = async_graphql::Request::new(
+        r#"query($parameters: JSONObject!) { callFunction(functionId: "inspections_at_site", typedParameters: $parameters) { typedValue } }"#,
+    )
+    .variables(async_graphql::Variables::from_json(serde_json::json!({ "parameters": { "site": { "$ref": "site:s1" } } })));
+    let response = schema.execute(request).await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    let result: PropertyValueScalar =
+        serde_json::from_value(response.data.into_json().unwrap()["callFunction"]["typedValue"].clone()).unwrap();
+    let PropertyValue::Array(references) = result.0 else { panic!("expected an array") };
+    assert_eq!(references.len(), 2);
+    assert!(references.iter().all(|r| matches!(r, PropertyValue::ObjectReference(_))), "{:?}", references);
+
+    let request = async_graphql::Request::new(
+        r#"{ callFunction(functionId: "inspections_at_site", parameters: { site: "\"s1\"" }, typedParameters: { site: "s1" }) { cached } }"#,
+    );
+    let response = schema.execute(request).await;
+    assert!(response.errors[0].message.contains("given in both parameters and typedParameters"), "{:?}", response.errors);
+}
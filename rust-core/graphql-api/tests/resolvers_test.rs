@@ -1,7 +1,7 @@
 use async_graphql::{Schema, EmptySubscription, Value as GraphQLValue};
 use graphql_api::{QueryRoot, AdminMutations};
-use ontology_engine::{Ontology, PropertyValue};
-use indexing::store::{ElasticsearchStore, ParquetStore, SearchStore};
+use ontology_engine::{Ontology, OntologyHandle, PropertyMap, PropertyValue};
+use indexing::store::SearchStore;
 use indexing::hydration::ObjectHydrator;
 use versioning::time_query::TimeQuery;
 use versioning::event_log::EventLog;
@@ -10,8 +10,30 @@ use std::collections::HashMap;
 use serde_json::Value;
 use tokio;
 
-// Helper to create a test schema with mock stores
-async fn create_test_schema() -> Schema<QueryRoot, AdminMutations, EmptySubscription> {
+/// In-memory search store holding JSON rows of each object type, with values coerced to
+/// their declared property types
+async fn search_store_with(ontology: &Ontology, rows: Vec<(&str, Vec<Value>)>) -> Arc<dyn SearchStore> {
+    let store = indexing::InMemorySearchStore::new();
+    let mut objects = Vec::new();
+    for (object_type, rows) in rows {
+        let object_type_def = ontology.get_object_type(object_type).unwrap();
+        for row in rows {
+            let properties = object_type_def.instantiate_from_json(&row).unwrap();
+            let object_id = properties.get(&object_type_def.primary_key).unwrap().to_string();
+            objects.push(indexing::store::IndexedObject::new(object_type.to_string(), object_id, properties));
+        }
+    }
+    store.bulk_index(objects).await.unwrap();
+    Arc::new(store)
+}
+
+/// A caller allowed to write objects and links
+fn editor() -> security::SecurityContext {
+    security::SecurityContext::new("editor".to_string()).with_role("editor".to_string())
+}
+
+// Helper to create a test schema over in-memory stores holding `objects` of "test_object"
+async fn create_test_schema(objects: Vec<Value>) -> Schema<QueryRoot, AdminMutations, EmptySubscription> {
     // Create a minimal ontology
     let yaml = r#"
 ontology:
@@ -32,13 +54,8 @@ ontology:
     
     let ontology = Ontology::from_yaml(yaml).expect("Failed to create test ontology");
     
-    // Create stores (these will fail if services aren't running, but that's OK for unit tests)
-    let search_store: Arc<dyn SearchStore> = Arc::new(
-        ElasticsearchStore::new("http://localhost:9200".to_string())
-            .unwrap_or_else(|_| panic!("Elasticsearch not available"))
-    );
-    
-    let columnar_store: Arc<dyn indexing::store::ColumnarStore> = Arc::new(ParquetStore::new("test_data/parquet".to_string()));
+    let search_store = search_store_with(&ontology, vec![("test_object", objects)]).await;
+    let columnar_store: Arc<dyn indexing::store::ColumnarStore> = Arc::new(indexing::InMemoryColumnarStore::new());
     let event_log = EventLog::new();
     let time_query = Arc::new(TimeQuery::new(event_log));
     let hydrator = ObjectHydrator::new();
@@ -47,17 +64,12 @@ ontology:
     let function_cache: Arc<tokio::sync::RwLock<HashMap<u64, PropertyValue>>> = 
         Arc::new(tokio::sync::RwLock::new(HashMap::new()));
     
-    // Create in-memory data store
-    let data_store: Arc<tokio::sync::RwLock<HashMap<String, Vec<Value>>>> = 
-        Arc::new(tokio::sync::RwLock::new(HashMap::new()));
-    
     Schema::build(QueryRoot::default(), AdminMutations::default(), EmptySubscription)
-        .data(Arc::new(ontology))
+        .data(OntologyHandle::new(ontology))
         .data(search_store)
         .data(columnar_store)
         .data(time_query)
         .data(hydrator)
-        .data(data_store)
         .data(function_cache)
         .finish()
 }
@@ -68,7 +80,7 @@ async fn test_function_caching() {
     // Note: This is a simplified test - full implementation would require
     // a working function executor
     
-    let schema = create_test_schema().await;
+    let schema = create_test_schema(vec![]).await;
     
     // Test that cacheable functions use the cache
     // This would require actually executing a function, which needs
@@ -79,26 +91,15 @@ async fn test_function_caching() {
 }
 
 #[tokio::test]
-#[ignore = "Requires Elasticsearch running on localhost:9200"]
 async fn test_graphql_json_types() {
     // Test that GraphQL returns Json types instead of strings
-    let schema = create_test_schema().await;
-    
-    // Create test data
-    let mut data_store: Arc<tokio::sync::RwLock<HashMap<String, Vec<Value>>>> = 
-        Arc::new(tokio::sync::RwLock::new(HashMap::new()));
-    
     let mut test_objects = Vec::new();
     let mut obj = serde_json::Map::new();
     obj.insert("id".to_string(), Value::String("test1".to_string()));
     obj.insert("name".to_string(), Value::String("Test Object".to_string()));
     obj.insert("value".to_string(), Value::Number(42.into()));
     test_objects.push(Value::Object(obj));
-    
-    {
-        let mut store = data_store.write().await;
-        store.insert("test_object".to_string(), test_objects);
-    }
+    let schema = create_test_schema(test_objects).await;
     
     // Query for objects
     let query = r#"
@@ -135,7 +136,7 @@ async fn test_graphql_json_types() {
 
 #[tokio::test]
 async fn test_count_objects_query() {
-    let schema = create_test_schema().await;
+    let schema = create_test_schema(vec![]).await;
     
     // Test that count is available through interfaces
     // This tests the count() method integration
@@ -157,3 +158,5464 @@ async fn test_count_objects_query() {
     assert!(response.errors.is_empty(), "Query should succeed, got errors: {:?}", response.errors);
 }
 
+
+#[tokio::test]
+async fn test_search_objects_default_sort() {
+    let yaml = r#"
+ontology:
+  objectTypes:
+    - id: "city"
+      displayName: "City"
+      primaryKey: "id"
+      titleKey: "name"
+      defaultSort:
+        property: "population"
+        ascending: false
+      properties:
+        - id: "id"
+          type: "string"
+          required: true
+        - id: "name"
+          type: "string"
+          displayOrder: 1
+        - id: "population"
+          type: "integer"
+  linkTypes: []
+"#;
+    let ontology = Ontology::from_yaml(yaml).expect("Failed to create test ontology");
+
+    let mut cities = Vec::new();
+    for (id, name, population) in [("c2", "Boston", 650), ("c1", "Austin", 950), ("c3", "Chicago", 2700)] {
+        cities.push(serde_json::json!({ "id": id, "name": name, "population": population }));
+    }
+    let search_store = search_store_with(&ontology, vec![("city", cities)]).await;
+
+    let schema = Schema::build(QueryRoot::default(), AdminMutations::default(), EmptySubscription)
+        .data(OntologyHandle::new(ontology))
+        .data(search_store)
+        .data(ObjectHydrator::new())
+        .finish();
+
+    let ids = |response: async_graphql::Response| -> Vec<String> {
+        assert!(response.errors.is_empty(), "Query should succeed, got errors: {:?}", response.errors);
+        let json = response.data.into_json().unwrap();
+        json["searchObjects"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|o| o["objectId"].as_str().unwrap().to_string())
+            .collect()
+    };
+
+    // No sort given: the type's default sort (population descending) applies
+    let response = schema
+        .execute(r#"query { searchObjects(objectType: "city") { objectId } }"#)
+        .await;
+    assert_eq!(ids(response), vec!["c3", "c1", "c2"]);
+
+    // Explicit sort overrides the default
+    let response = schema
+        .execute(r#"query { searchObjects(objectType: "city", sort: { property: "name" }) { objectId } }"#)
+        .await;
+    assert_eq!(ids(response), vec!["c1", "c2", "c3"]);
+
+    // Metadata exposes the default sort and display order
+    let response = schema
+        .execute(r#"query { getObjectTypes { defaultSort { property ascending } properties { id displayOrder } } }"#)
+        .await;
+    assert!(response.errors.is_empty(), "Query should succeed, got errors: {:?}", response.errors);
+    let json = response.data.into_json().unwrap();
+    assert_eq!(json["getObjectTypes"][0]["defaultSort"]["property"], "population");
+    assert_eq!(json["getObjectTypes"][0]["properties"][0]["id"], "name");
+}
+
+#[tokio::test]
+async fn test_computed_properties_in_results() {
+    let yaml = r#"
+ontology:
+  objectTypes:
+    - id: "city"
+      displayName: "City"
+      primaryKey: "id"
+      properties:
+        - id: "id"
+          type: "string"
+          required: true
+        - id: "population"
+          type: "integer"
+      computedProperties:
+        - id: "size"
+          displayName: "Size"
+          type: "string"
+          expression:
+            type: "branches"
+            branches:
+              - when: { property: "population", operator: "gt", value: 900 }
+                then: "large"
+            default: "small"
+  linkTypes: []
+"#;
+    let ontology = Ontology::from_yaml(yaml).expect("Failed to create test ontology");
+    let cities = vec![
+        serde_json::json!({ "id": "c1", "population": 950 }),
+        serde_json::json!({ "id": "c2", "population": 650 }),
+    ];
+    let search_store = search_store_with(&ontology, vec![("city", cities)]).await;
+    let schema = Schema::build(QueryRoot::default(), AdminMutations::default(), EmptySubscription)
+        .data(OntologyHandle::new(ontology))
+        .data(search_store)
+        .data(ObjectHydrator::new())
+        .finish();
+
+    let response = schema
+        .execute(r#"{ searchObjects(objectType: "city", sort: { property: "id" }) { properties } }"#)
+        .await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    let json = response.data.into_json().unwrap();
+    assert_eq!(json["searchObjects"][0]["properties"]["size"], "large");
+    assert_eq!(json["searchObjects"][1]["properties"]["size"], "small");
+
+    let response = schema
+        .execute(r#"{ getObject(objectType: "city", objectId: "c2") { properties } }"#)
+        .await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    assert_eq!(response.data.into_json().unwrap()["getObject"]["properties"]["size"], "small");
+
+    // Listings can skip the evaluation
+    let response = schema
+        .execute(r#"{ searchObjects(objectType: "city", includeComputed: false) { properties } }"#)
+        .await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    let json = response.data.into_json().unwrap();
+    assert!(json["searchObjects"][0]["properties"].get("size").is_none());
+    assert!(json["searchObjects"][0]["properties"]["population"].is_number());
+
+    let response = schema
+        .execute(r#"{ getObject(objectType: "city", objectId: "c2", includeComputed: false) { properties } }"#)
+        .await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    assert!(response.data.into_json().unwrap()["getObject"]["properties"].get("size").is_none());
+}
+
+#[test]
+fn test_search_objects_falls_back_to_primary_key_order() {
+    let yaml = r#"
+ontology:
+  objectTypes:
+    - id: "city"
+      displayName: "City"
+      primaryKey: "id"
+      properties:
+        - id: "id"
+          type: "string"
+          required: true
+  linkTypes: []
+"#;
+    let ontology = Ontology::from_yaml(yaml).expect("Failed to create test ontology");
+    let sort = ontology.get_object_type("city").unwrap().effective_default_sort();
+    assert_eq!(sort.property, "id");
+    assert!(sort.ascending);
+}
+
+async fn create_case_schema() -> Schema<QueryRoot, AdminMutations, EmptySubscription> {
+    let yaml = r#"
+ontology:
+  objectTypes:
+    - id: "case"
+      displayName: "Case"
+      primaryKey: "id"
+      properties:
+        - id: "id"
+          type: "string"
+          required: true
+  linkTypes: []
+"#;
+    let ontology = Ontology::from_yaml(yaml).expect("Failed to create test ontology");
+    let cases = vec![
+        serde_json::json!({ "id": "open" }),
+        serde_json::json!({ "id": "team", "_acl": [{ "principal": "role:case_team", "permission": "read" }] }),
+        serde_json::json!({ "id": "blocked", "_acl": [{ "principal": "user:alice", "permission": "deny" }] }),
+    ];
+    let search_store = search_store_with(&ontology, vec![("case", cases)]).await;
+
+    Schema::build(QueryRoot::default(), AdminMutations::default(), EmptySubscription)
+        .data(OntologyHandle::new(ontology))
+        .data(search_store)
+        .data(ObjectHydrator::new())
+        .finish()
+}
+
+async fn visible_case_ids(
+    schema: &Schema<QueryRoot, AdminMutations, EmptySubscription>,
+    context: security::SecurityContext,
+) -> Vec<String> {
+    let request = async_graphql::Request::new(r#"query { searchObjects(objectType: "case") { objectId } }"#)
+        .data(context);
+    let response = schema.execute(request).await;
+    assert!(response.errors.is_empty(), "Query should succeed, got errors: {:?}", response.errors);
+    let json = response.data.into_json().unwrap();
+    json["searchObjects"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|o| o["objectId"].as_str().unwrap().to_string())
+        .collect()
+}
+
+#[tokio::test]
+async fn test_search_objects_acl_prefilter() {
+    let schema = create_case_schema().await;
+
+    let alice = security::SecurityContext::new("alice".to_string()).with_role("case_team".to_string());
+    assert_eq!(visible_case_ids(&schema, alice).await, vec!["open", "team"]);
+
+    let bob = security::SecurityContext::new("bob".to_string());
+    assert_eq!(visible_case_ids(&schema, bob).await, vec!["blocked", "open"]);
+}
+
+#[tokio::test]
+async fn test_get_object_acl_deny_overrides_allow() {
+    let yaml = r#"
+ontology:
+  objectTypes:
+    - id: "case"
+      displayName: "Case"
+      primaryKey: "id"
+      properties:
+        - id: "id"
+          type: "string"
+          required: true
+  linkTypes: []
+"#;
+    let ontology = Ontology::from_yaml(yaml).unwrap();
+    let cases = vec![
+        serde_json::json!({ "id": "open" }),
+        serde_json::json!({ "id": "team", "_acl": [{ "principal": "role:case_team", "permission": "read" }] }),
+        serde_json::json!({ "id": "sealed", "_acl": [
+            { "principal": "role:case_team", "permission": "read" },
+            { "principal": "user:alice", "permission": "deny" },
+        ] }),
+    ];
+    let search_store = search_store_with(&ontology, vec![("case", cases)]).await;
+    let schema = Schema::build(QueryRoot::default(), AdminMutations::default(), EmptySubscription)
+        .data(OntologyHandle::new(ontology))
+        .data(search_store)
+        .data(ObjectHydrator::new())
+        .finish();
+    let get = |id: &str, context: security::SecurityContext| {
+        let request = async_graphql::Request::new(format!(
+            r#"{{ getObject(objectType: "case", objectId: "{}") {{ objectId }} }}"#,
+            id
+        ))
+        .data(context);
+        let schema = &schema;
+        async move {
+            let response = schema.execute(request).await;
+            assert!(response.errors.is_empty(), "{:?}", response.errors);
+            response.data.into_json().unwrap()["getObject"]["objectId"].clone()
+        }
+    };
+    let alice = || security::SecurityContext::new("alice".to_string()).with_role("case_team".to_string());
+    let carol = || security::SecurityContext::new("carol".to_string()).with_role("case_team".to_string());
+    let bob = || security::SecurityContext::new("bob".to_string());
+
+    // Alice's role is allowed, but the deny entry naming her wins
+    assert_eq!(get("sealed", alice()).await, Value::Null);
+    assert_eq!(get("sealed", carol()).await, "sealed");
+    // Bob is not listed on restricted objects
+    assert_eq!(get("team", bob()).await, Value::Null);
+    assert_eq!(get("team", alice()).await, "team");
+    assert_eq!(get("open", bob()).await, "open");
+}
+
+/// Two sites owned by "acme" at the same place, one of them readable only by alice, in the
+/// search, graph and event stores
+async fn create_restricted_site_schema() -> Schema<QueryRoot, AdminMutations, EmptySubscription> {
+    use versioning::event_log::EventType;
+
+    let yaml = r#"
+ontology:
+  interfaces:
+    - id: "Asset"
+      displayName: "Asset"
+      properties:
+        - id: "id"
+          type: "string"
+  objectTypes:
+    - id: "owner"
+      displayName: "Owner"
+      primaryKey: "id"
+      properties:
+        - id: "id"
+          type: "string"
+    - id: "site"
+      displayName: "Site"
+      primaryKey: "id"
+      implements: ["Asset"]
+      properties:
+        - id: "id"
+          type: "string"
+        - id: "year"
+          type: "integer"
+        - id: "location"
+          type: "geojson"
+  linkTypes:
+    - id: "owns"
+      source: "owner"
+      target: "site"
+"#;
+    let search_store: Arc<dyn SearchStore> = Arc::new(indexing::InMemorySearchStore::new());
+    let graph_store: Arc<dyn indexing::store::GraphStore> = Arc::new(indexing::InMemoryGraphStore::new());
+    let mut event_log = EventLog::new();
+    for (id, reader) in [("open", None), ("hidden", Some("alice"))] {
+        let mut properties = PropertyMap::new();
+        properties.insert("id".to_string(), PropertyValue::String(id.to_string()));
+        properties.insert("year".to_string(), PropertyValue::Integer(2020));
+        properties.insert(
+            "location".to_string(),
+            PropertyValue::GeoJSON(r#"{"type":"Point","coordinates":[-71.06,42.36]}"#.to_string()),
+        );
+        if let Some(reader) = reader {
+            let acl = security::acl::ObjectAcl {
+                entries: vec![security::acl::AclEntry {
+                    principal: format!("user:{}", reader),
+                    permission: security::acl::AclPermission::Read,
+                }],
+            };
+            properties.insert(security::acl::ACL_PROPERTY.to_string(), acl.to_property_value());
+        }
+        search_store.index_object("site", id, &properties, None).await.unwrap();
+        graph_store.create_link("owns", "acme", id, &PropertyMap::new()).await.unwrap();
+        event_log.record_at(
+            EventType::ObjectCreated { object_type: "site".to_string(), object_id: id.to_string(), properties },
+            chrono::Utc::now() - chrono::Duration::days(1),
+            None,
+        );
+    }
+
+    Schema::build(QueryRoot::default(), AdminMutations::default(), EmptySubscription)
+        .data(OntologyHandle::new(Ontology::from_yaml(yaml).unwrap()))
+        .data(search_store)
+        .data(graph_store)
+        .data(Arc::new(TimeQuery::new(event_log)))
+        .data(ObjectHydrator::new())
+        .finish()
+}
+
+/// IDs of the sites a query's `field` returns to `user`, sorted
+async fn visible_site_ids(
+    schema: &Schema<QueryRoot, AdminMutations, EmptySubscription>,
+    query: &str,
+    field: &str,
+    user: &str,
+) -> Vec<String> {
+    let request = async_graphql::Request::new(query).data(security::SecurityContext::new(user.to_string()));
+    let response = schema.execute(request).await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    let json = response.data.into_json().unwrap();
+    let mut ids: Vec<String> = json[field]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|o| o["objectId"].as_str().unwrap().to_string())
+        .collect();
+    ids.sort();
+    ids
+}
+
+#[tokio::test]
+async fn test_get_linked_objects_hides_denied_objects() {
+    let schema = create_restricted_site_schema().await;
+    let query = r#"{ getLinkedObjects(objectType: "owner", objectId: "acme", linkType: "owns") { objectId } }"#;
+    assert_eq!(visible_site_ids(&schema, query, "getLinkedObjects", "bob").await, vec!["open"]);
+    assert_eq!(visible_site_ids(&schema, query, "getLinkedObjects", "alice").await, vec!["hidden", "open"]);
+}
+
+#[tokio::test]
+async fn test_spatial_query_hides_denied_objects() {
+    let schema = create_restricted_site_schema().await;
+    let query = r#"{ spatialQuery(objectType: "site", property: "location", operator: "within_distance",
+        geometry: "{\"type\":\"Point\",\"coordinates\":[-71.06,42.36]}", distance: 1000.0) { objectId } }"#;
+    assert_eq!(visible_site_ids(&schema, query, "spatialQuery", "bob").await, vec!["open"]);
+    assert_eq!(visible_site_ids(&schema, query, "spatialQuery", "alice").await, vec!["hidden", "open"]);
+}
+
+#[tokio::test]
+async fn test_temporal_query_hides_denied_objects() {
+    let schema = create_restricted_site_schema().await;
+    let query = r#"{ temporalQuery(objectType: "site", year: 2020) { objectId } }"#;
+    assert_eq!(visible_site_ids(&schema, query, "temporalQuery", "bob").await, vec!["open"]);
+    assert_eq!(visible_site_ids(&schema, query, "temporalQuery", "alice").await, vec!["hidden", "open"]);
+}
+
+#[tokio::test]
+async fn test_query_interface_hides_denied_objects() {
+    let schema = create_restricted_site_schema().await;
+    let query = r#"{ queryInterface(interfaceId: "Asset") { objectId } }"#;
+    assert_eq!(visible_site_ids(&schema, query, "queryInterface", "bob").await, vec!["open"]);
+    assert_eq!(visible_site_ids(&schema, query, "queryInterface", "alice").await, vec!["hidden", "open"]);
+}
+
+#[tokio::test]
+async fn test_count_objects_applies_filters_and_acl() {
+    let yaml = r#"
+ontology:
+  objectTypes:
+    - id: "plant"
+      displayName: "Plant"
+      primaryKey: "id"
+      properties:
+        - id: "id"
+          type: "string"
+        - id: "fuel"
+          type: "string"
+  linkTypes: []
+"#;
+    let search_store: Arc<dyn SearchStore> = Arc::new(indexing::InMemorySearchStore::new());
+    for (id, fuel, reader) in [("p1", "coal", None), ("p2", "gas", Some("alice")), ("p3", "gas", None)] {
+        let mut properties = ontology_engine::PropertyMap::new();
+        properties.insert("id".to_string(), PropertyValue::String(id.to_string()));
+        properties.insert("fuel".to_string(), PropertyValue::String(fuel.to_string()));
+        if let Some(reader) = reader {
+            let acl = security::acl::ObjectAcl {
+                entries: vec![security::acl::AclEntry {
+                    principal: format!("user:{}", reader),
+                    permission: security::acl::AclPermission::Read,
+                }],
+            };
+            properties.insert(security::acl::ACL_PROPERTY.to_string(), acl.to_property_value());
+        }
+        let indexed = security::acl::with_acl_index_fields(&properties).unwrap();
+        search_store.index_object("plant", id, &indexed, None).await.unwrap();
+    }
+    let schema = Schema::build(QueryRoot::default(), AdminMutations::default(), EmptySubscription)
+        .data(OntologyHandle::new(Ontology::from_yaml(yaml).unwrap()))
+        .data(search_store)
+        .finish();
+
+    let count = |query: &str, context: Option<security::SecurityContext>| {
+        let mut request = async_graphql::Request::new(query);
+        if let Some(context) = context {
+            request = request.data(context);
+        }
+        let schema = schema.clone();
+        async move {
+            let response = schema.execute(request).await;
+            assert!(response.errors.is_empty(), "{:?}", response.errors);
+            response.data.into_json().unwrap()["countObjects"].as_u64().unwrap()
+        }
+    };
+    let all = r#"{ countObjects(objectType: "plant") }"#;
+    let gas = r#"{ countObjects(objectType: "plant", filters: [{ property: "fuel", operator: "equals", value: "\"gas\"" }]) }"#;
+    assert_eq!(count(all, None).await, 3);
+    assert_eq!(count(gas, None).await, 2);
+    assert_eq!(count(gas, Some(security::SecurityContext::new("alice".to_string()))).await, 2);
+    assert_eq!(count(gas, Some(security::SecurityContext::new("bob".to_string()))).await, 1);
+
+    // Objects whose ACL restricts them are counted the same way
+    let alice = security::SecurityContext::new("alice".to_string()).with_role("case_team".to_string());
+    let response = create_case_schema().await
+        .execute(async_graphql::Request::new(r#"{ countObjects(objectType: "case") }"#).data(alice))
+        .await;
+    assert_eq!(response.data.into_json().unwrap()["countObjects"], 2);
+}
+
+#[tokio::test]
+async fn test_set_object_acl_requires_manage_permission() {
+    let schema = create_case_schema().await;
+    let mutation = r#"mutation {
+        setObjectAcl(objectType: "case", objectId: "open", entries: [{ principal: "user:bob", permission: "deny" }])
+    }"#;
+
+    let bob = security::SecurityContext::new("bob".to_string());
+    let response = schema.execute(async_graphql::Request::new(mutation).data(bob.clone())).await;
+    assert!(!response.errors.is_empty(), "Unprivileged users must not change ACLs");
+
+    let admin = security::SecurityContext::new("admin".to_string())
+        .with_role(security::acl::MANAGE_ACL_ROLE.to_string());
+    let response = schema.execute(async_graphql::Request::new(mutation).data(admin)).await;
+    assert!(response.errors.is_empty(), "Mutation should succeed, got errors: {:?}", response.errors);
+
+    assert_eq!(visible_case_ids(&schema, bob).await, vec!["blocked"]);
+}
+
+#[tokio::test]
+async fn test_search_objects_applies_masking_profile() {
+    use security::{MaskingPolicy, MaskingStrategy};
+
+    let yaml = r#"
+ontology:
+  objectTypes:
+    - id: "person"
+      displayName: "Person"
+      primaryKey: "id"
+      properties:
+        - id: "id"
+          type: "string"
+          required: true
+        - id: "ssn"
+          type: "string"
+          sensitivityTags: ["ssn"]
+        - id: "email"
+          type: "string"
+          pii: true
+  linkTypes: []
+"#;
+    let ontology = Ontology::from_yaml(yaml).expect("Failed to create test ontology");
+    let search_store = search_store_with(
+        &ontology,
+        vec![("person", vec![serde_json::json!({ "id": "p1", "ssn": "123-45-6789", "email": "p1@example.com" })])],
+    )
+    .await;
+
+    let policy = MaskingPolicy::new("test-key")
+        .with_rule("*", "ssn", MaskingStrategy::Redact)
+        .with_rule("analyst", "ssn", MaskingStrategy::PartialReveal { keep_last: 4 })
+        .with_rule("auditor", "ssn", MaskingStrategy::Reveal)
+        .with_rule("*", "pii", MaskingStrategy::Hash);
+
+    let schema = Schema::build(QueryRoot::default(), AdminMutations::default(), EmptySubscription)
+        .extension(graphql_api::MaskingProfileExtension)
+        .data(OntologyHandle::new(ontology))
+        .data(search_store)
+        .data(ObjectHydrator::new())
+        .data(policy)
+        .finish();
+
+    let query = r#"query { searchObjects(objectType: "person") { properties } }"#;
+    let run = |role: &str| {
+        let context = security::SecurityContext::new("u".to_string()).with_role(role.to_string());
+        schema.execute(async_graphql::Request::new(query).data(context))
+    };
+
+    let response = run("analyst").await;
+    assert!(response.errors.is_empty(), "Query should succeed, got errors: {:?}", response.errors);
+    let profile = response.extensions.get("masking_profile").cloned().unwrap().into_json().unwrap();
+    assert_eq!(profile["name"], "analyst");
+    let json = response.data.into_json().unwrap();
+    let analyst_view = &json["searchObjects"][0]["properties"];
+    assert_eq!(analyst_view["ssn"], "***-**-6789");
+    assert_ne!(analyst_view["email"], "p1@example.com");
+
+    let response = run("auditor").await;
+    let json = response.data.into_json().unwrap();
+    let auditor_view = &json["searchObjects"][0]["properties"];
+    assert_eq!(auditor_view["ssn"], "123-45-6789");
+    // Hashes are stable across callers so masked exports can still be joined
+    assert_eq!(auditor_view["email"], analyst_view["email"]);
+}
+
+#[tokio::test]
+async fn test_property_access_policy_redacts_and_rejects_filters() {
+    use security::{PropertyAccessPolicy, PropertyRedaction, SecurityContext};
+
+    let yaml = r#"
+ontology:
+  objectTypes:
+    - id: "person"
+      displayName: "Person"
+      primaryKey: "id"
+      properties:
+        - id: "id"
+          type: "string"
+          required: true
+        - id: "name"
+          type: "string"
+        - id: "email"
+          type: "string"
+          pii: true
+        - id: "salary"
+          type: "integer"
+          sensitivityTags: ["finance"]
+  linkTypes: []
+"#;
+    let ontology = OntologyHandle::new(Ontology::from_yaml(yaml).expect("Failed to create test ontology"));
+    let search_store = search_store_with(
+        &ontology.load(),
+        vec![("person", vec![serde_json::json!({ "id": "p1", "name": "Pat", "email": "p1@example.com", "salary": 90000 })])],
+    )
+    .await;
+    let schema = |policy: PropertyAccessPolicy| {
+        Schema::build(QueryRoot::default(), AdminMutations::default(), EmptySubscription)
+            .data(ontology.clone())
+            .data(search_store.clone())
+            .data(ObjectHydrator::new())
+            .data(policy)
+            .finish()
+    };
+    let policy = PropertyAccessPolicy::new().allow_role("pii", "admin").allow_role("finance", "admin");
+    let user = SecurityContext::new("u".to_string()).with_role("analyst".to_string());
+    let admin = SecurityContext::new("a".to_string()).with_role("admin".to_string());
+
+    let search = r#"{ searchObjects(objectType: "person") { properties } }"#;
+    let masked = schema(policy.clone());
+    let response = masked.execute(async_graphql::Request::new(search).data(user.clone())).await;
+    assert!(response.errors.is_empty(), "Query should succeed, got errors: {:?}", response.errors);
+    let json = response.data.into_json().unwrap();
+    assert_eq!(json["searchObjects"][0]["properties"]["email"], "***");
+    assert_eq!(json["searchObjects"][0]["properties"]["salary"], "***");
+    assert_eq!(json["searchObjects"][0]["properties"]["name"], "Pat");
+
+    let response = masked.execute(async_graphql::Request::new(search).data(admin.clone())).await;
+    let json = response.data.into_json().unwrap();
+    assert_eq!(json["searchObjects"][0]["properties"]["email"], "p1@example.com");
+
+    // Callers without a security context are cleared for nothing
+    let get = r#"{ getObject(objectType: "person", objectId: "p1") { properties } }"#;
+    let json = masked.execute(get).await.data.into_json().unwrap();
+    assert_eq!(json["getObject"]["properties"]["email"], "***");
+
+    let removed = schema(policy.clone().with_redaction(PropertyRedaction::Remove));
+    let json = removed.execute(async_graphql::Request::new(get).data(user.clone())).await.data.into_json().unwrap();
+    assert!(json["getObject"]["properties"].get("email").is_none());
+
+    // Filtering or aggregating on an unreadable property would reveal its values
+    let by_email = r#"{ searchObjects(objectType: "person", filters: [{ property: "email", operator: "startsWith", value: "\"p1\"" }]) { objectId } }"#;
+    let response = masked.execute(async_graphql::Request::new(by_email).data(user.clone())).await;
+    assert_eq!(response.errors.len(), 1);
+    assert!(response.errors[0].message.contains("Property 'email' of 'person' is not readable"), "{:?}", response.errors);
+    let response = masked.execute(async_graphql::Request::new(by_email).data(admin.clone())).await;
+    assert!(response.errors.is_empty(), "Query should succeed, got errors: {:?}", response.errors);
+
+    let count = r#"{ countObjects(objectType: "person", filters: [{ property: "salary", operator: "greaterThan", value: "50000" }]) }"#;
+    let response = masked.execute(async_graphql::Request::new(count).data(user.clone())).await;
+    assert!(response.errors[0].message.contains("'salary'"), "{:?}", response.errors);
+    let aggregate = r#"{ aggregateObjects(objectType: "person", aggregations: [{ property: "salary", operation: "avg" }]) { rows } }"#;
+    let response = masked.execute(async_graphql::Request::new(aggregate).data(user)).await;
+    assert!(response.errors[0].message.contains("'salary'"), "{:?}", response.errors);
+}
+
+#[tokio::test]
+async fn test_property_access_policy_masks_every_object_query_and_title() {
+    use versioning::event_log::EventType;
+
+    let yaml = r#"
+ontology:
+  interfaces:
+    - id: "Contact"
+      displayName: "Contact"
+      properties:
+        - id: "email"
+          type: "string"
+  objectTypes:
+    - id: "team"
+      displayName: "Team"
+      primaryKey: "id"
+      properties:
+        - id: "id"
+          type: "string"
+    - id: "person"
+      displayName: "Person"
+      primaryKey: "id"
+      titleKey: "email"
+      implements: ["Contact"]
+      properties:
+        - id: "id"
+          type: "string"
+        - id: "email"
+          type: "string"
+          pii: true
+        - id: "year"
+          type: "integer"
+        - id: "location"
+          type: "geojson"
+  linkTypes:
+    - id: "member"
+      source: "team"
+      target: "person"
+"#;
+    let mut properties = PropertyMap::new();
+    properties.insert("id".to_string(), PropertyValue::String("p1".to_string()));
+    properties.insert("email".to_string(), PropertyValue::String("p1@example.com".to_string()));
+    properties.insert("year".to_string(), PropertyValue::Integer(2020));
+    properties.insert(
+        "location".to_string(),
+        PropertyValue::GeoJSON(r#"{"type":"Point","coordinates":[-71.06,42.36]}"#.to_string()),
+    );
+    let search_store: Arc<dyn SearchStore> = Arc::new(indexing::InMemorySearchStore::new());
+    search_store.index_object("person", "p1", &properties, None).await.unwrap();
+    let graph_store: Arc<dyn indexing::store::GraphStore> = Arc::new(indexing::InMemoryGraphStore::new());
+    graph_store.create_link("member", "core", "p1", &PropertyMap::new()).await.unwrap();
+    let mut event_log = EventLog::new();
+    event_log.record_at(
+        EventType::ObjectCreated { object_type: "person".to_string(), object_id: "p1".to_string(), properties },
+        chrono::Utc::now() - chrono::Duration::days(1),
+        None,
+    );
+    let schema = Schema::build(QueryRoot::default(), AdminMutations::default(), EmptySubscription)
+        .data(OntologyHandle::new(Ontology::from_yaml(yaml).unwrap()))
+        .data(search_store)
+        .data(graph_store)
+        .data(Arc::new(TimeQuery::new(event_log)))
+        .data(ObjectHydrator::new())
+        .data(security::PropertyAccessPolicy::new().allow_role("pii", "admin"))
+        .finish();
+
+    for (field, query) in [
+        ("searchObjects", r#"searchObjects(objectType: "person")"#),
+        ("getLinkedObjects", r#"getLinkedObjects(objectType: "team", objectId: "core", linkType: "member")"#),
+        ("spatialQuery", r#"spatialQuery(objectType: "person", property: "location", operator: "within_distance",
+            geometry: "{\"type\":\"Point\",\"coordinates\":[-71.06,42.36]}", distance: 1000.0)"#),
+        ("temporalQuery", r#"temporalQuery(objectType: "person", year: 2020)"#),
+        ("queryInterface", r#"queryInterface(interfaceId: "Contact")"#),
+    ] {
+        let request = async_graphql::Request::new(format!("{{ {} {{ title properties }} }}", query))
+            .data(security::SecurityContext::new("u".to_string()).with_role("analyst".to_string()));
+        let response = schema.execute(request).await;
+        assert!(response.errors.is_empty(), "{}: {:?}", field, response.errors);
+        let json = response.data.into_json().unwrap();
+        let object = &json[field][0];
+        assert_eq!(object["properties"]["email"], "***", "{}", field);
+        // The title property is masked in the title too
+        assert_eq!(object["title"], "***", "{}", field);
+    }
+}
+
+#[tokio::test]
+async fn test_links_connection_pagination() {
+    use indexing::store::GraphStore;
+
+    let yaml = r#"
+ontology:
+  objectTypes:
+    - id: "company"
+      displayName: "Company"
+      primaryKey: "id"
+      properties:
+        - id: "id"
+          type: "string"
+          required: true
+    - id: "person"
+      displayName: "Person"
+      primaryKey: "id"
+      titleKey: "name"
+      properties:
+        - id: "id"
+          type: "string"
+          required: true
+        - id: "name"
+          type: "string"
+  linkTypes:
+    - id: "employs"
+      source: "company"
+      target: "person"
+      cardinality: "ONE_TO_MANY"
+      properties:
+        - id: "start_date"
+          type: "date"
+"#;
+    let ontology = Ontology::from_yaml(yaml).expect("Failed to create test ontology");
+
+    let graph_store = indexing::InMemoryGraphStore::new();
+    let mut people = Vec::new();
+    for i in 0..5 {
+        let mut props = ontology_engine::PropertyMap::new();
+        props.insert("start_date".to_string(), PropertyValue::Date(format!("202{}-01-01", 4 - i)));
+        graph_store.create_link("employs", "acme", &format!("p{}", i), &props).await.unwrap();
+        people.push(serde_json::json!({ "id": format!("p{}", i), "name": format!("Person {}", i) }));
+    }
+    let graph_store: Arc<dyn GraphStore> = Arc::new(graph_store);
+    let search_store = search_store_with(&ontology, vec![("person", people)]).await;
+
+    let schema = Schema::build(QueryRoot::default(), AdminMutations::default(), EmptySubscription)
+        .data(OntologyHandle::new(ontology))
+        .data(graph_store)
+        .data(search_store)
+        .data(ObjectHydrator::new())
+        .finish();
+
+    let page = |after: Option<&str>| {
+        let after = after.map(|c| format!(r#", after: "{}""#, c)).unwrap_or_default();
+        format!(
+            r#"query {{ links(objectType: "company", objectId: "acme", linkType: "employs",
+                sort: {{ property: "start_date" }}, first: 2{}) {{
+                edges {{ cursor direction properties otherObject {{ objectId title }} }}
+                pageInfo {{ hasNextPage hasPreviousPage endCursor }}
+            }} }}"#,
+            after
+        )
+    };
+
+    let mut seen = Vec::new();
+    let mut after: Option<String> = None;
+    loop {
+        let response = schema.execute(page(after.as_deref())).await;
+        assert!(response.errors.is_empty(), "Query should succeed, got errors: {:?}", response.errors);
+        let json = response.data.into_json().unwrap();
+        let connection = &json["links"];
+        for edge in connection["edges"].as_array().unwrap() {
+            assert_eq!(edge["direction"], "outgoing");
+            assert_eq!(edge["otherObject"]["title"], format!("Person {}", &edge["otherObject"]["objectId"].as_str().unwrap()[1..]));
+            seen.push(edge["otherObject"]["objectId"].as_str().unwrap().to_string());
+        }
+        assert_eq!(connection["pageInfo"]["hasPreviousPage"], after.is_some());
+        if !connection["pageInfo"]["hasNextPage"].as_bool().unwrap() {
+            break;
+        }
+        after = connection["pageInfo"]["endCursor"].as_str().map(|s| s.to_string());
+    }
+
+    // Sorted by start_date ascending: p4 (2020) first, p0 (2024) last
+    assert_eq!(seen, vec!["p4", "p3", "p2", "p1", "p0"]);
+
+    // Undeclared link properties are rejected rather than passed to the graph query
+    for arguments in [
+        r#"sort: { property: "start_date) { uid }" }"#,
+        r#"filters: [{ property: "salary", operator: "gt", value: "1" }]"#,
+    ] {
+        let query = format!(
+            r#"query {{ links(objectType: "company", objectId: "acme", linkType: "employs", {}) {{
+                edges {{ cursor }}
+            }} }}"#,
+            arguments
+        );
+        let response = schema.execute(query).await;
+        assert_eq!(response.errors.len(), 1, "{}", arguments);
+        let error = serde_json::to_value(&response.errors[0]).unwrap();
+        assert_eq!(error["extensions"]["code"], "INVALID_ARGUMENT");
+    }
+}
+
+#[tokio::test]
+async fn test_link_stats_histogram() {
+    use indexing::store::GraphStore;
+
+    let yaml = r#"
+ontology:
+  objectTypes:
+    - id: "tract"
+      displayName: "Tract"
+      primaryKey: "id"
+      properties:
+        - id: "id"
+          type: "string"
+          required: true
+  linkTypes:
+    - id: "crosswalk"
+      source: "tract"
+      target: "tract"
+      properties:
+        - id: "weight"
+          type: "double"
+        - id: "method"
+          type: "string"
+"#;
+    let ontology = Ontology::from_yaml(yaml).expect("Failed to create test ontology");
+
+    // 300 weights cycling through 0..100, mixing integer and double values, plus links that
+    // have no weight or belong to another type
+    let graph_store = indexing::InMemoryGraphStore::new();
+    for i in 0..300i64 {
+        let mut props = ontology_engine::PropertyMap::new();
+        let weight = i % 100;
+        let value = if i % 2 == 0 { PropertyValue::Integer(weight) } else { PropertyValue::Double(weight as f64) };
+        props.insert("weight".to_string(), value);
+        graph_store.create_link("crosswalk", &format!("a{}", i), &format!("b{}", i), &props).await.unwrap();
+    }
+    for i in 0..10 {
+        let mut props = ontology_engine::PropertyMap::new();
+        props.insert("method".to_string(), PropertyValue::String("area".to_string()));
+        graph_store.create_link("crosswalk", &format!("c{}", i), "b0", &props).await.unwrap();
+        let mut props = ontology_engine::PropertyMap::new();
+        props.insert("weight".to_string(), PropertyValue::Integer(1000));
+        graph_store.create_link("adjacent", &format!("c{}", i), "b0", &props).await.unwrap();
+    }
+    let graph_store: Arc<dyn GraphStore> = Arc::new(graph_store);
+
+    let schema = Schema::build(QueryRoot::default(), AdminMutations::default(), EmptySubscription)
+        .data(OntologyHandle::new(ontology))
+        .data(graph_store)
+        .finish();
+
+    let query = r#"query { linkStats(linkType: "crosswalk", property: "weight") {
+        count min max mean histogram { lower upper count }
+    } }"#;
+    let response = schema.execute(query).await;
+    assert!(response.errors.is_empty(), "Query should succeed, got errors: {:?}", response.errors);
+    let stats = response.data.into_json().unwrap()["linkStats"].clone();
+    assert_eq!(stats["count"], 300);
+    assert_eq!(stats["min"], 0.0);
+    assert_eq!(stats["max"], 99.0);
+    assert_eq!(stats["mean"], 49.5);
+    let histogram = stats["histogram"].as_array().unwrap();
+    assert_eq!(histogram.len(), 10);
+    for (i, bucket) in histogram.iter().enumerate() {
+        // Ten distinct values per bucket, each seen three times
+        assert_eq!(bucket["count"], 30, "bucket {}", i);
+        assert!((bucket["lower"].as_f64().unwrap() - 9.9 * i as f64).abs() < 1e-9);
+    }
+    assert_eq!(histogram[9]["upper"], 99.0);
+
+    let response = schema
+        .execute(r#"query { linkStats(linkType: "crosswalk", property: "weight", buckets: 3) { histogram { count } } }"#)
+        .await;
+    let counts: Vec<i64> = response.data.into_json().unwrap()["linkStats"]["histogram"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|b| b["count"].as_i64().unwrap())
+        .collect();
+    // Width 33: the last bucket also holds the maximum
+    assert_eq!(counts, vec![99, 99, 102]);
+
+    for (arguments, message) in [
+        (r#"linkType: "crosswalk", property: "method""#, "Property 'method' of link type 'crosswalk' is not numeric"),
+        (r#"linkType: "crosswalk", property: "length""#, "Property 'length' not found on link type 'crosswalk'"),
+        (r#"linkType: "crosswalk", property: "weight", buckets: 0"#, "buckets must be between 1 and 1000"),
+        (r#"linkType: "adjacent", property: "weight""#, "Link type not found"),
+    ] {
+        let response = schema.execute(format!("query {{ linkStats({}) {{ count }} }}", arguments)).await;
+        assert_eq!(response.errors[0].message, message, "{}", arguments);
+    }
+}
+
+#[tokio::test]
+async fn test_recompute_materialized_refreshes_stored_values() {
+    let yaml = r#"
+ontology:
+  objectTypes:
+    - id: "city"
+      displayName: "City"
+      primaryKey: "id"
+      properties:
+        - id: "id"
+          type: "string"
+          required: true
+        - id: "population"
+          type: "integer"
+        - id: "area"
+          type: "double"
+      computedProperties:
+        - id: "density"
+          displayName: "Density"
+          type: "double"
+          expression:
+            type: arithmetic
+            expression: "population / area"
+          dependencies: ["population", "area"]
+          materialization: on_write
+  linkTypes: []
+"#;
+    let ontology = Ontology::from_yaml(yaml).expect("Failed to create test ontology");
+    let cities = vec![
+        serde_json::json!({ "id": "c1", "population": 1000, "area": 50.0, "density": 1.0 }),
+        serde_json::json!({ "id": "c2", "population": 300, "area": 3.0 }),
+    ];
+    let search_store = search_store_with(&ontology, vec![("city", cities)]).await;
+
+    let schema = Schema::build(QueryRoot::default(), AdminMutations::default(), EmptySubscription)
+        .data(OntologyHandle::new(ontology))
+        .data(search_store.clone())
+        .data(ObjectHydrator::new())
+        .finish();
+
+    let response = schema
+        .execute(r#"mutation { recomputeMaterialized(objectType: "city", property: "density") }"#)
+        .await;
+    assert!(response.errors.is_empty(), "Mutation should succeed, got errors: {:?}", response.errors);
+    assert_eq!(response.data.into_json().unwrap()["recomputeMaterialized"], 2);
+
+    let c1 = search_store.get_object("city", "c1").await.unwrap().unwrap();
+    let c2 = search_store.get_object("city", "c2").await.unwrap().unwrap();
+    assert_eq!(c1.properties.get("density"), Some(&PropertyValue::Double(20.0)));
+    assert_eq!(c2.properties.get("density"), Some(&PropertyValue::Double(100.0)));
+    assert!(c1.properties.contains_key(ontology_engine::MATERIALIZED_AT_PROPERTY));
+
+    // Read-time properties have nothing stored to refresh
+    let response = schema
+        .execute(r#"mutation { recomputeMaterialized(objectType: "city", property: "population") }"#)
+        .await;
+    assert!(!response.errors.is_empty());
+}
+
+#[tokio::test]
+async fn test_include_display_formats_values_for_locale() {
+    let yaml = r#"
+ontology:
+  objectTypes:
+    - id: "order"
+      displayName: "Order"
+      primaryKey: "id"
+      properties:
+        - id: "id"
+          type: "string"
+          required: true
+        - id: "total"
+          type: "double"
+          format:
+            type: currency
+            symbol: null
+        - id: "discount"
+          type: "double"
+          format:
+            type: percentage
+            decimals: 1
+        - id: "weight"
+          type: "integer"
+          unit: "kg"
+        - id: "placed_on"
+          type: "date"
+          format:
+            type: date_format
+            format: "%Q"
+  linkTypes: []
+"#;
+    let ontology = Ontology::from_yaml(yaml).expect("Failed to create test ontology");
+    let orders = vec![serde_json::json!({
+        "id": "o1", "total": 1234.56, "discount": 0.125, "weight": 1500, "placed_on": "2024-03-15"
+    })];
+    let search_store = search_store_with(&ontology, vec![("order", orders)]).await;
+
+    let schema = Schema::build(QueryRoot::default(), AdminMutations::default(), EmptySubscription)
+        .data(OntologyHandle::new(ontology))
+        .data(search_store)
+        .data(ObjectHydrator::new())
+        .finish();
+
+    let response = schema
+        .execute(r#"query { searchObjects(objectType: "order", includeDisplay: true, locale: "de-DE") { display } }"#)
+        .await;
+    assert!(response.errors.is_empty(), "Query should succeed, got errors: {:?}", response.errors);
+    let json = response.data.into_json().unwrap();
+    let display = &json["searchObjects"][0]["display"];
+    assert_eq!(display["total"], "1.234,56 €");
+    assert_eq!(display["discount"], "12,5\u{a0}%");
+    assert_eq!(display["weight"], "1.500 kg");
+    // A malformed date format falls back to the raw value instead of failing the query
+    assert_eq!(display["placed_on"], "2024-03-15");
+    assert!(display.get("id").is_none());
+
+    let response = schema
+        .execute(r#"query { getObject(objectType: "order", objectId: "o1", includeDisplay: true) { display } }"#)
+        .await;
+    assert!(response.errors.is_empty(), "Query should succeed, got errors: {:?}", response.errors);
+    let json = response.data.into_json().unwrap();
+    assert_eq!(json["getObject"]["display"]["total"], "$1,234.56");
+
+    // Display is only computed on request
+    let response = schema
+        .execute(r#"query { getObject(objectType: "order", objectId: "o1") { display } }"#)
+        .await;
+    assert!(response.data.into_json().unwrap()["getObject"]["display"].is_null());
+}
+
+#[tokio::test]
+async fn test_property_groups_and_grouped_object_properties() {
+    let yaml = r#"
+ontology:
+  objectTypes:
+    - id: "tract"
+      displayName: "Tract"
+      primaryKey: "geoid"
+      properties:
+        - id: "geoid"
+          type: "string"
+          required: true
+        - id: "population"
+          type: "integer"
+        - id: "median_income"
+          type: "double"
+        - id: "geometry"
+          type: "geojson"
+      propertyGroups:
+        - id: "geometry"
+          displayName: "Geometry"
+          properties: ["geometry"]
+          order: 2
+        - id: "demographics"
+          displayName: "Demographics"
+          displayNames:
+            fr: "Démographie"
+          properties: ["median_income", "population"]
+          order: 1
+          collapsedByDefault: true
+  linkTypes: []
+"#;
+    let ontology = Ontology::from_yaml(yaml).expect("Failed to create test ontology");
+    let tracts = vec![serde_json::json!({ "geoid": "t1", "population": 120, "median_income": 51000.0 })];
+    let search_store = search_store_with(&ontology, vec![("tract", tracts)]).await;
+    let schema = Schema::build(QueryRoot::default(), AdminMutations::default(), EmptySubscription)
+        .data(OntologyHandle::new(ontology))
+        .data(search_store)
+        .data(ObjectHydrator::new())
+        .finish();
+
+    let response = schema
+        .execute(
+            r#"query { getPropertyGroups(objectType: "tract", locale: "fr") {
+                id displayName properties collapsedByDefault values
+            } }"#,
+        )
+        .await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    let json = response.data.into_json().unwrap();
+    let groups = json["getPropertyGroups"].as_array().unwrap();
+    let ids: Vec<&str> = groups.iter().map(|g| g["id"].as_str().unwrap()).collect();
+    assert_eq!(ids, vec!["demographics", "geometry", "ungrouped"]);
+    assert_eq!(groups[0]["displayName"], "Démographie");
+    assert_eq!(groups[0]["collapsedByDefault"], true);
+    assert_eq!(groups[2]["properties"], serde_json::json!(["geoid"]));
+    assert!(groups[0]["values"].is_null());
+
+    let response = schema
+        .execute(
+            r#"query { getObject(objectType: "tract", objectId: "t1", groupProperties: true) {
+                propertyGroups { id values }
+            } }"#,
+        )
+        .await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    let json = response.data.into_json().unwrap();
+    let groups = &json["getObject"]["propertyGroups"];
+    assert_eq!(groups[0]["values"], serde_json::json!({ "median_income": 51000.0, "population": 120 }));
+    assert_eq!(groups[1]["values"], serde_json::json!({}));
+    assert_eq!(groups[2]["values"], serde_json::json!({ "geoid": "t1" }));
+
+    // Grouping is only done on request
+    let response = schema
+        .execute(r#"query { getObject(objectType: "tract", objectId: "t1") { propertyGroups { id } } }"#)
+        .await;
+    assert!(response.data.into_json().unwrap()["getObject"]["propertyGroups"].is_null());
+}
+
+#[tokio::test]
+async fn test_function_cache_cleared_on_ontology_change() {
+    let yaml = r#"
+ontology:
+  objectTypes:
+    - id: "person"
+      displayName: "Person"
+      primaryKey: "id"
+      properties:
+        - id: "id"
+          type: "string"
+  linkTypes: []
+"#;
+    let handle = OntologyHandle::new(Ontology::from_yaml(yaml).unwrap());
+    let cache: graphql_api::FunctionCache = Arc::new(tokio::sync::RwLock::new(HashMap::new()));
+    cache.write().await.insert(1, PropertyValue::Integer(42));
+    let listener = graphql_api::spawn_cache_invalidation(&handle, cache.clone());
+
+    handle
+        .mutate(|draft| {
+            let mut company = draft.object_types[0].clone();
+            company.id = "company".to_string();
+            draft.object_types.push(company);
+            Ok(())
+        })
+        .unwrap();
+
+    for _ in 0..50 {
+        if cache.read().await.is_empty() {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+    assert!(cache.read().await.is_empty());
+    listener.abort();
+}
+
+#[tokio::test]
+async fn test_reload_ontology_reports_all_errors() {
+    let yaml = r#"
+ontology:
+  objectTypes:
+    - id: "person"
+      displayName: "Person"
+      primaryKey: "id"
+      properties:
+        - id: "id"
+          type: "string"
+  linkTypes: []
+"#;
+    let handle = OntologyHandle::new(Ontology::from_yaml(yaml).unwrap());
+    let schema = Schema::build(QueryRoot::default(), AdminMutations::default(), EmptySubscription)
+        .data(handle.clone())
+        .finish();
+    let mutation = r#"mutation($definition: String!) {
+        reloadOntology(definition: $definition) { success version errors { code message line column } }
+    }"#;
+    let reload = |definition: &str| {
+        async_graphql::Request::new(mutation)
+            .variables(async_graphql::Variables::from_json(serde_json::json!({ "definition": definition })))
+            .data(security::SecurityContext::new("a".to_string()).with_role("admin".to_string()))
+    };
+
+    // Only admins may replace the ontology
+    let response = schema
+        .execute(
+            async_graphql::Request::new(mutation)
+                .variables(async_graphql::Variables::from_json(serde_json::json!({ "definition": yaml })))
+                .data(security::SecurityContext::new("u".to_string()).with_role("analyst".to_string())),
+        )
+        .await;
+    assert_eq!(serde_json::to_value(&response.errors[0]).unwrap()["extensions"]["code"], "UNAUTHORIZED");
+    assert_eq!(handle.version(), 1);
+
+    // Two independent problems are both reported
+    let invalid = r#"
+ontology:
+  objectTypes:
+    - id: "person"
+      displayName: "Person"
+      primaryKey: "missing"
+      properties:
+        - id: "id"
+          type: "string"
+  linkTypes:
+    - id: "knows"
+      source: "person"
+      target: "robot"
+      cardinality: "MANY_TO_MANY"
+"#;
+    let response = schema.execute(reload(invalid)).await;
+    assert!(response.errors.is_empty(), "Mutation should succeed, got errors: {:?}", response.errors);
+    let json = response.data.into_json().unwrap();
+    let result = &json["reloadOntology"];
+    assert_eq!(result["success"], false);
+    assert_eq!(result["version"], 1);
+    let codes: Vec<&str> = result["errors"].as_array().unwrap().iter()
+        .map(|e| e["code"].as_str().unwrap())
+        .collect();
+    assert_eq!(codes, vec!["unknown_reference", "unknown_reference"]);
+    assert_eq!(handle.load().get_object_type("person").unwrap().primary_key, "id");
+
+    // Parse errors carry their source location
+    let response = schema.execute(reload("ontology:\n  objectTypes: [\n")).await;
+    let json = response.data.into_json().unwrap();
+    let error = &json["reloadOntology"]["errors"][0];
+    assert_eq!(error["code"], "parse_error");
+    assert!(error["line"].as_u64().is_some());
+
+    let response = schema.execute(reload(yaml)).await;
+    let json = response.data.into_json().unwrap();
+    assert_eq!(json["reloadOntology"]["success"], true);
+    assert_eq!(json["reloadOntology"]["version"], 2);
+}
+
+#[tokio::test]
+async fn test_reload_ontology_rejects_removing_bound_types() {
+    use ontology_engine::{ModelBindingConfig, ModelObjective, ModelPlatform, ModelRegistry, ModelType};
+
+    let definition = |types: &[&str]| {
+        let object_types: Vec<Value> = types.iter().map(|id| serde_json::json!({
+            "id": id,
+            "displayName": id,
+            "primaryKey": "id",
+            "properties": [{ "id": "id", "type": "string" }, { "id": "score", "type": "double" }],
+        })).collect();
+        serde_json::json!({ "ontology": { "objectTypes": object_types, "linkTypes": [] } }).to_string()
+    };
+    let handle = OntologyHandle::new(Ontology::from_json(&definition(&["person", "company"])).unwrap());
+    let mut registry = ModelRegistry::new();
+    registry.register(ModelObjective::new(
+        "churn".to_string(),
+        "Churn".to_string(),
+        ModelType::Regression,
+        "1.0.0".to_string(),
+        "/models/churn.pkl".to_string(),
+        ModelPlatform::Local { framework: "sklearn".to_string() },
+    )).unwrap();
+    registry.bind_model("churn", "company".to_string(), "score".to_string(), None, ModelBindingConfig::default())
+        .unwrap();
+    let schema = Schema::build(QueryRoot::default(), AdminMutations::default(), EmptySubscription)
+        .data(handle.clone())
+        .data(Arc::new(tokio::sync::RwLock::new(registry)))
+        .finish();
+    let reload = |definition: String| {
+        async_graphql::Request::new(r#"mutation($definition: String!) {
+            reloadOntology(definition: $definition, format: "json") { success version errors { code message } changes }
+        }"#).variables(async_graphql::Variables::from_json(serde_json::json!({ "definition": definition })))
+            .data(security::SecurityContext::new("a".to_string()).with_role("admin".to_string()))
+    };
+
+    let response = schema.execute(reload(definition(&["person"]))).await;
+    assert!(response.errors.is_empty(), "Mutation should succeed, got errors: {:?}", response.errors);
+    let json = response.data.into_json().unwrap();
+    let result = &json["reloadOntology"];
+    assert_eq!(result["success"], false);
+    assert_eq!(result["errors"][0]["code"], "active_model_bindings");
+    assert!(result["errors"][0]["message"].as_str().unwrap().contains("'company'"));
+    assert!(handle.load().get_object_type("company").is_some());
+
+    // Unbound types can be removed, and the diff is reported
+    let response = schema.execute(reload(definition(&["company", "order"]))).await;
+    let json = response.data.into_json().unwrap();
+    let result = &json["reloadOntology"];
+    assert_eq!(result["success"], true);
+    assert_eq!(result["version"], 2);
+    assert_eq!(result["changes"], serde_json::json!([
+        "object type 'order' added",
+        "object type 'person' removed",
+    ]));
+    assert!(handle.load().get_object_type("person").is_none());
+}
+
+#[tokio::test]
+async fn test_get_object_lists_referencing_objects() {
+    use indexing::{InMemorySearchStore, ReferenceIndex, ReferenceIndexingSearchStore};
+
+    let yaml = r#"
+ontology:
+  objectTypes:
+    - id: "plant"
+      displayName: "Plant"
+      primaryKey: "id"
+      properties:
+        - id: "id"
+          type: "string"
+    - id: "order"
+      displayName: "Order"
+      primaryKey: "id"
+      properties:
+        - id: "id"
+          type: "string"
+        - id: "plant"
+          type: "object_reference"
+          referenceTarget: "plant"
+        - id: "backup_plant"
+          type: "object_reference"
+  linkTypes: []
+"#;
+    let handle = OntologyHandle::new(Ontology::from_yaml(yaml).unwrap());
+    let raw = Arc::new(InMemorySearchStore::new());
+    let index = ReferenceIndex::new(raw.clone(), handle.clone());
+    let search_store: Arc<dyn SearchStore> = Arc::new(ReferenceIndexingSearchStore::new(raw, index.clone()));
+    for (id, plant, backup) in [("o1", "123", "plant:9"), ("o2", "9", "plant:123")] {
+        let mut properties = ontology_engine::PropertyMap::new();
+        properties.insert("plant".to_string(), PropertyValue::ObjectReference(plant.to_string()));
+        properties.insert("backup_plant".to_string(), PropertyValue::ObjectReference(backup.to_string()));
+        search_store.index_object("order", id, &properties, None).await.unwrap();
+    }
+    search_store.index_object("plant", "123", &ontology_engine::PropertyMap::new(), None).await.unwrap();
+    let schema = Schema::build(QueryRoot::default(), AdminMutations::default(), EmptySubscription)
+        .data(handle)
+        .data(search_store)
+        .data(ObjectHydrator::new())
+        .data(index)
+        .finish();
+
+    let response = schema
+        .execute(r#"{ getObject(objectType: "plant", objectId: "123") { referencedBy { objectType objectId property } } }"#)
+        .await;
+    assert!(response.errors.is_empty(), "Query should succeed, got errors: {:?}", response.errors);
+    let json = response.data.into_json().unwrap();
+    assert_eq!(json["getObject"]["referencedBy"], serde_json::json!([
+        { "objectType": "order", "objectId": "o1", "property": "plant" },
+        { "objectType": "order", "objectId": "o2", "property": "backup_plant" },
+    ]));
+}
+
+#[tokio::test]
+async fn test_resolve_references_returns_referenced_titles() {
+    let yaml = r#"
+ontology:
+  objectTypes:
+    - id: "plant"
+      displayName: "Plant"
+      primaryKey: "id"
+      titleKey: "name"
+      properties:
+        - id: "id"
+          type: "string"
+        - id: "name"
+          type: "string"
+    - id: "order"
+      displayName: "Order"
+      primaryKey: "id"
+      properties:
+        - id: "id"
+          type: "string"
+        - id: "plant"
+          type: "object_reference"
+          referenceTarget: "plant"
+  linkTypes: []
+"#;
+    let ontology = Ontology::from_yaml(yaml).unwrap();
+    let search_store = search_store_with(&ontology, vec![
+        ("plant", vec![serde_json::json!({"id": "123", "name": "Newark"})]),
+        ("order", vec![
+            serde_json::json!({"id": "o1", "plant": "123"}),
+            serde_json::json!({"id": "o2", "plant": "gone"}),
+        ]),
+    ]).await;
+    let schema = Schema::build(QueryRoot::default(), AdminMutations::default(), EmptySubscription)
+        .data(OntologyHandle::new(ontology))
+        .data(search_store)
+        .data(ObjectHydrator::new())
+        .finish();
+
+    let response = schema
+        .execute(r#"{ searchObjects(objectType: "order", resolveReferences: true) { objectId properties } }"#)
+        .await;
+    assert!(response.errors.is_empty(), "Query should succeed, got errors: {:?}", response.errors);
+    let json = response.data.into_json().unwrap();
+    assert_eq!(json["searchObjects"][0]["properties"]["plant"], serde_json::json!({ "id": "plant:123", "title": "Newark" }));
+    assert_eq!(json["searchObjects"][1]["properties"]["plant"], serde_json::json!({ "id": "plant:gone", "title": null }));
+
+    let response = schema
+        .execute(r#"{ getObject(objectType: "order", objectId: "o1") { properties } }"#)
+        .await;
+    let json = response.data.into_json().unwrap();
+    assert_eq!(json["getObject"]["properties"]["plant"], serde_json::json!("plant:123"));
+}
+
+#[tokio::test]
+async fn test_form_schema_for_actions_and_object_types() {
+    let yaml = r#"
+ontology:
+  objectTypes:
+    - id: "ticket"
+      displayName: "Ticket"
+      primaryKey: "id"
+      properties:
+        - id: "id"
+          type: "string"
+          required: true
+        - id: "priority"
+          displayName: "Priority"
+          displayNames:
+            de: "Priorität"
+          type: "integer"
+          validation:
+            min: 1
+            max: 5
+  linkTypes: []
+  actionTypes:
+    - id: "escalate"
+      displayName: "Escalate"
+      parameters:
+        - id: "reason"
+          type: "string"
+          required: true
+"#;
+    let schema = Schema::build(QueryRoot::default(), AdminMutations::default(), EmptySubscription)
+        .data(OntologyHandle::new(Ontology::from_yaml(yaml).unwrap()))
+        .finish();
+
+    let response = schema
+        .execute(r#"{ formSchema(objectType: "ticket", locale: "de") }"#)
+        .await;
+    assert!(response.errors.is_empty(), "Query should succeed, got errors: {:?}", response.errors);
+    let json = response.data.into_json().unwrap();
+    let priority = &json["formSchema"]["properties"]["priority"];
+    assert_eq!(priority["title"], "Priorität");
+    assert_eq!(priority["type"], "integer");
+    assert_eq!(priority["maximum"], 5.0);
+
+    let response = schema.execute(r#"{ formSchema(actionTypeId: "escalate") }"#).await;
+    let json = response.data.into_json().unwrap();
+    assert_eq!(json["formSchema"]["required"], serde_json::json!(["reason"]));
+
+    let response = schema
+        .execute(r#"{ formSchema(actionTypeId: "escalate", objectType: "ticket") }"#)
+        .await;
+    assert!(response.errors[0].message.contains("exactly one"));
+
+    let response = schema.execute(r#"{ objectTypeJsonSchema(objectType: "ticket") }"#).await;
+    let json = response.data.into_json().unwrap();
+    assert_eq!(json["objectTypeJsonSchema"]["$id"], "ticket.schema.json");
+    assert_eq!(json["objectTypeJsonSchema"]["properties"]["priority"]["minimum"], 1.0);
+    let response = schema.execute(r#"{ objectTypeJsonSchema(objectType: "nope") }"#).await;
+    assert!(response.errors[0].message.contains("'nope' not found"));
+}
+
+#[tokio::test]
+async fn test_get_link_types_lists_both_directions() {
+    let yaml = r#"
+ontology:
+  objectTypes:
+    - id: "person"
+      displayName: "Person"
+      primaryKey: "id"
+      properties:
+        - id: "id"
+          type: "string"
+    - id: "company"
+      displayName: "Company"
+      primaryKey: "id"
+      properties:
+        - id: "id"
+          type: "string"
+  linkTypes:
+    - id: "works_at"
+      displayName: "Works at"
+      source: "person"
+      target: "company"
+      cardinality: "MANY_TO_ONE"
+      properties:
+        - id: "since"
+          type: "date"
+          required: true
+    - id: "knows"
+      source: "person"
+      target: "person"
+      cardinality: "MANY_TO_MANY"
+      bidirectional: true
+"#;
+    let schema = Schema::build(QueryRoot::default(), AdminMutations::default(), EmptySubscription)
+        .data(OntologyHandle::new(Ontology::from_yaml(yaml).unwrap()))
+        .finish();
+    let entries = |json: &Value, field: &str| -> Vec<String> {
+        json[field]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|l| format!("{} {}", l["id"].as_str().unwrap(), l["direction"].as_str().unwrap()))
+            .collect()
+    };
+
+    let response = schema
+        .execute(r#"{ getLinkTypes { id displayName source target cardinality bidirectional direction properties { id type required } } }"#)
+        .await;
+    assert!(response.errors.is_empty(), "Query should succeed, got errors: {:?}", response.errors);
+    let json = response.data.into_json().unwrap();
+    assert_eq!(entries(&json, "getLinkTypes"), vec!["knows outgoing", "knows incoming", "works_at outgoing"]);
+    let works_at = &json["getLinkTypes"][2];
+    assert_eq!(works_at["displayName"], "Works at");
+    assert_eq!(works_at["cardinality"], "MANY_TO_ONE");
+    assert_eq!(works_at["properties"][0]["id"], "since");
+    assert_eq!(works_at["properties"][0]["required"], true);
+    assert_eq!(json["getLinkTypes"][0]["displayName"], "knows");
+
+    let response = schema
+        .execute(r#"{ getLinkTypesForObjectType(objectType: "company") { id direction } }"#)
+        .await;
+    let json = response.data.into_json().unwrap();
+    assert_eq!(entries(&json, "getLinkTypesForObjectType"), vec!["works_at incoming"]);
+
+    let response = schema
+        .execute(r#"{ getLinkTypesForObjectType(objectType: "person") { id direction } }"#)
+        .await;
+    let json = response.data.into_json().unwrap();
+    assert_eq!(
+        entries(&json, "getLinkTypesForObjectType"),
+        vec!["knows outgoing", "knows incoming", "works_at outgoing"]
+    );
+
+    let response = schema.execute(r#"{ getLinkTypesForObjectType(objectType: "nope") { id } }"#).await;
+    assert!(response.errors[0].message.contains("'nope' not found"));
+}
+
+#[tokio::test]
+async fn test_approximate_aggregate_reports_sample_fraction_and_bounds() {
+    let yaml = r#"
+ontology:
+  objectTypes:
+    - id: "reading"
+      displayName: "Reading"
+      primaryKey: "id"
+      properties:
+        - id: "id"
+          type: "string"
+        - id: "value"
+          type: "double"
+        - id: "sensor"
+          type: "integer"
+  linkTypes: []
+"#;
+    let ontology = Ontology::from_yaml(yaml).expect("Failed to create test ontology");
+    let readings: Vec<Value> = (0..20_000)
+        .map(|i| serde_json::json!({ "id": format!("r{}", i), "value": ((i * 7919) % 1000) as f64, "sensor": i % 300 }))
+        .collect();
+    // Exact distinct counts and percentiles are answered by the columnar store
+    let reading = ontology.get_object_type("reading").unwrap();
+    let objects = readings
+        .iter()
+        .map(|row| {
+            let properties = reading.instantiate_from_json(row).unwrap();
+            indexing::store::IndexedObject::new("reading".to_string(), row["id"].as_str().unwrap().to_string(), properties)
+        })
+        .collect();
+    let columnar_store: Arc<dyn indexing::store::ColumnarStore> = Arc::new(indexing::InMemoryColumnarStore::new());
+    columnar_store.write_batch("reading", objects).await.unwrap();
+    let search_store = search_store_with(&ontology, vec![("reading", readings)]).await;
+
+    let schema = Schema::build(QueryRoot::default(), AdminMutations::default(), EmptySubscription)
+        .data(OntologyHandle::new(ontology))
+        .data(search_store)
+        .data(columnar_store)
+        .finish();
+
+    let aggregations = r#"aggregations: [
+        { property: "value", operation: "avg" },
+        { property: "value", operation: "p95" },
+        { property: "sensor", operation: "distinct_count" }
+    ]"#;
+    let exact = schema
+        .execute(format!(r#"query {{ aggregateObjects(objectType: "reading", {}) {{ rows sampleFraction errorBounds }} }}"#, aggregations))
+        .await;
+    assert!(exact.errors.is_empty(), "Query should succeed, got errors: {:?}", exact.errors);
+    let exact = exact.data.into_json().unwrap();
+    assert!(exact["aggregateObjects"]["sampleFraction"].is_null());
+    assert!(exact["aggregateObjects"]["errorBounds"].is_null());
+    let exact_row = &exact["aggregateObjects"]["rows"][0];
+
+    let approximate = schema
+        .execute(format!(
+            r#"query {{ aggregateObjects(objectType: "reading", {}, approximate: true, sampleSize: 2000) {{ rows total sampleFraction errorBounds }} }}"#,
+            aggregations
+        ))
+        .await;
+    assert!(approximate.errors.is_empty(), "Query should succeed, got errors: {:?}", approximate.errors);
+    let approximate = approximate.data.into_json().unwrap();
+    let result = &approximate["aggregateObjects"];
+    assert_eq!(result["sampleFraction"], 0.1);
+    assert_eq!(result["total"], 1);
+    for column in ["avg_value", "p95_value", "distinct_count_sensor"] {
+        let truth = exact_row[column].as_f64().unwrap();
+        let bound = &result["errorBounds"][0][column];
+        assert!(
+            bound["lower"].as_f64().unwrap() <= truth && truth <= bound["upper"].as_f64().unwrap(),
+            "{}: exact {} outside {}",
+            column,
+            truth,
+            bound
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_upsert_object_rejects_stale_revision() {
+    let yaml = r#"
+ontology:
+  objectTypes:
+    - id: "person"
+      displayName: "Person"
+      primaryKey: "id"
+      properties:
+        - id: "id"
+          type: "string"
+        - id: "name"
+          type: "string"
+        - id: "age"
+          type: "integer"
+  linkTypes: []
+"#;
+    let search_store: Arc<dyn SearchStore> = Arc::new(indexing::InMemorySearchStore::new());
+    let schema = Schema::build(QueryRoot::default(), AdminMutations::default(), EmptySubscription)
+        .data(editor())
+        .data(OntologyHandle::new(Ontology::from_yaml(yaml).unwrap()))
+        .data(search_store.clone())
+        .finish();
+    let mutation = r#"mutation($properties: JSON!, $expected: Int) {
+        upsertObject(objectType: "person", objectId: "p1", properties: $properties, expectedRevision: $expected) {
+            success revision conflict { expectedRevision currentRevision current }
+        }
+    }"#;
+    let upsert = |properties: Value, expected: Option<u64>| {
+        let request = async_graphql::Request::new(mutation)
+            .variables(async_graphql::Variables::from_json(serde_json::json!({
+                "properties": properties,
+                "expected": expected,
+            })));
+        let schema = schema.clone();
+        async move {
+            let response = schema.execute(request).await;
+            assert!(response.errors.is_empty(), "Mutation should succeed, got errors: {:?}", response.errors);
+            response.data.into_json().unwrap()["upsertObject"].clone()
+        }
+    };
+
+    let created = upsert(serde_json::json!({ "id": "p1", "name": "Ada" }), Some(0)).await;
+    assert_eq!(created["success"], true);
+    assert_eq!(created["revision"], 1);
+
+    // Two writers both read revision 1; only the first one wins
+    let first = upsert(serde_json::json!({ "age": 36 }), Some(1)).await;
+    let second = upsert(serde_json::json!({ "name": "Grace" }), Some(1)).await;
+    assert_eq!(first["success"], true);
+    assert_eq!(first["revision"], 2);
+    assert_eq!(second["success"], false);
+    assert_eq!(second["conflict"]["expectedRevision"], 1);
+    assert_eq!(second["conflict"]["currentRevision"], 2);
+    assert_eq!(second["conflict"]["current"]["age"], 36);
+    assert_eq!(second["conflict"]["current"]["name"], "Ada");
+
+    // Rebased onto the current revision the write goes through and keeps both changes
+    let rebased = upsert(serde_json::json!({ "name": "Grace" }), Some(2)).await;
+    assert_eq!(rebased["success"], true);
+    let stored = search_store.get_object("person", "p1").await.unwrap().unwrap();
+    assert_eq!(stored.revision, 3);
+    assert_eq!(stored.properties.get("name"), Some(&PropertyValue::String("Grace".to_string())));
+    assert_eq!(stored.properties.get("age"), Some(&PropertyValue::Integer(36)));
+}
+
+#[tokio::test]
+async fn test_create_object_validates_and_records_event() {
+    let yaml = r#"
+ontology:
+  objectTypes:
+    - id: "person"
+      displayName: "Person"
+      primaryKey: "id"
+      titleKey: "name"
+      properties:
+        - id: "id"
+          type: "string"
+        - id: "name"
+          type: "string"
+          required: true
+        - id: "age"
+          type: "integer"
+          validation:
+            min: 0
+        - id: "born"
+          type: "date"
+    - id: "city"
+      displayName: "City"
+      primaryKey: "id"
+      properties:
+        - id: "id"
+          type: "string"
+        - id: "name"
+          type: "string"
+  linkTypes: []
+"#;
+    let ontology = Ontology::from_yaml(yaml).unwrap();
+    let search_store =
+        search_store_with(&ontology, vec![("city", vec![serde_json::json!({ "id": "bos", "name": "Boston" })])]).await;
+    let event_log: graphql_api::ObjectEventLog = Arc::new(tokio::sync::RwLock::new(EventLog::new()));
+    let schema = Schema::build(QueryRoot::default(), AdminMutations::default(), EmptySubscription)
+        .data(editor())
+        .data(OntologyHandle::new(ontology))
+        .data(search_store.clone())
+        .data(ObjectHydrator::new())
+        .data(event_log.clone())
+        .finish();
+    let create = |object_type: &str, properties: Value| {
+        let request = async_graphql::Request::new(
+            "mutation($type: String!, $properties: String!) { createObject(objectType: $type, properties: $properties) { objectId title properties } }",
+        )
+        .variables(async_graphql::Variables::from_json(serde_json::json!({
+            "type": object_type,
+            "properties": properties.to_string(),
+        })));
+        let schema = schema.clone();
+        async move { schema.execute(request).await }
+    };
+
+    let response = create("person", serde_json::json!({ "id": "p1", "name": "Ada", "age": 36, "born": "1815-12-10" })).await;
+    assert!(response.errors.is_empty(), "Mutation should succeed, got errors: {:?}", response.errors);
+    let created = response.data.into_json().unwrap()["createObject"].clone();
+    assert_eq!(created["objectId"], "p1");
+    assert_eq!(created["title"], "Ada");
+    assert_eq!(created["properties"]["age"], 36);
+    let stored = search_store.get_object("person", "p1").await.unwrap().unwrap();
+    assert_eq!(stored.properties.get("name"), Some(&PropertyValue::String("Ada".to_string())));
+    assert_eq!(stored.properties.get("born"), Some(&PropertyValue::Date("1815-12-10".to_string())));
+    assert_eq!(event_log.read().await.get_events_for_object("person", "p1").len(), 1);
+
+    // Every problem is reported, not just the first
+    let response = create("person", serde_json::json!({ "age": -1 })).await;
+    let message = &response.errors[0].message;
+    assert!(message.contains("Missing required property 'id'"), "{}", message);
+    assert!(message.contains("Missing required property 'name'"), "{}", message);
+    assert!(message.contains("Property 'age'"), "{}", message);
+
+    let response = create("person", serde_json::json!({ "id": "p1", "name": "Grace" })).await;
+    assert_eq!(response.errors[0].message, "Object person:p1 already exists");
+
+    let response = create("city", serde_json::json!({ "id": "nyc", "name": "New York" })).await;
+    assert!(response.errors.is_empty(), "Mutation should succeed, got errors: {:?}", response.errors);
+    let response = create("city", serde_json::json!({ "id": "bos", "name": "Boston" })).await;
+    assert_eq!(response.errors[0].message, "Object city:bos already exists");
+    let response = schema
+        .execute(r#"{ getObject(objectType: "city", objectId: "nyc") { properties } }"#)
+        .await;
+    assert_eq!(response.data.into_json().unwrap()["getObject"]["properties"]["name"], "New York");
+    assert!(search_store.get_object("city", "nyc").await.unwrap().is_some());
+}
+
+#[tokio::test]
+async fn test_errors_carry_machine_readable_codes() {
+    use writeback::{EditQueue, InMemoryEditQueue};
+
+    let yaml = r#"
+ontology:
+  objectTypes:
+    - id: "city"
+      displayName: "City"
+      primaryKey: "id"
+      properties:
+        - id: "id"
+          type: "string"
+        - id: "population"
+          type: "integer"
+          validation:
+            min: 0
+  linkTypes: []
+"#;
+    let ontology = Ontology::from_yaml(yaml).unwrap();
+    let search_store = search_store_with(&ontology, vec![("city", vec![serde_json::json!({ "id": "c1", "population": 10 })])]).await;
+    let edit_queue: Arc<dyn EditQueue> = Arc::new(InMemoryEditQueue::new());
+    let schema = Schema::build(QueryRoot::default(), AdminMutations::default(), EmptySubscription)
+        .data(OntologyHandle::new(ontology))
+        .data(search_store)
+        .data(ObjectHydrator::new())
+        .data(edit_queue)
+        .finish();
+    let error_extensions = |response: &async_graphql::Response| {
+        serde_json::to_value(&response.errors[0]).unwrap()["extensions"].clone()
+    };
+    let submit = |value: &str, user: Option<&str>| {
+        let mut request = async_graphql::Request::new(format!(
+            r#"mutation {{ submitEdit(objectType: "city", objectId: "c1", property: "population", value: {}) }}"#,
+            serde_json::to_string(value).unwrap()
+        ));
+        if let Some(user) = user {
+            request = request.data(security::SecurityContext::new(user.to_string()));
+        }
+        let schema = schema.clone();
+        async move { schema.execute(request).await }
+    };
+
+    let response = schema.execute(r#"{ getObject(objectType: "town", objectId: "t1") { objectId } }"#).await;
+    assert_eq!(response.errors[0].message, "Object type not found");
+    assert_eq!(error_extensions(&response)["code"], "NOT_FOUND");
+
+    let response = submit("-5", Some("alice")).await;
+    let extensions = error_extensions(&response);
+    assert_eq!(extensions["code"], "VALIDATION_FAILED", "{:?}", response.errors);
+    assert_eq!(extensions["field"], "population");
+
+    let response = submit("5", None).await;
+    assert_eq!(error_extensions(&response)["code"], "UNAUTHORIZED");
+
+    let response = schema
+        .execute(r#"{ searchObjects(objectType: "city", filters: [{ property: "population", operator: "between", value: "1" }]) { objectId } }"#)
+        .await;
+    assert_eq!(error_extensions(&response)["code"], "INVALID_ARGUMENT");
+}
+
+/// State of an object rebuilt from the events recorded for it
+async fn replay(event_log: &graphql_api::ObjectEventLog, object_type: &str, object_id: &str) -> Option<ontology_engine::PropertyMap> {
+    let mut replayed = EventLog::new();
+    for event in event_log.read().await.get_events_for_object(object_type, object_id) {
+        replayed.record(event.clone());
+    }
+    TimeQuery::new(replayed)
+        .reconstruct_object(object_type, object_id, chrono::Utc::now())
+        .map(|object| object.properties)
+}
+
+#[tokio::test]
+async fn test_update_and_delete_object_replay_from_event_log() {
+    let yaml = r#"
+ontology:
+  objectTypes:
+    - id: "project"
+      displayName: "Project"
+      primaryKey: "id"
+      properties:
+        - id: "id"
+          type: "string"
+        - id: "name"
+          type: "string"
+          required: true
+        - id: "budget"
+          type: "integer"
+          validation:
+            min: 0
+    - id: "task"
+      displayName: "Task"
+      primaryKey: "id"
+      properties:
+        - id: "id"
+          type: "string"
+        - id: "title"
+          type: "string"
+    - id: "team"
+      displayName: "Team"
+      primaryKey: "id"
+      properties:
+        - id: "id"
+          type: "string"
+  linkTypes:
+    - id: "has_task"
+      source: "project"
+      target: "task"
+      onDelete: cascade
+    - id: "owns"
+      source: "team"
+      target: "project"
+      onDelete: restrict
+"#;
+    let search_store: Arc<dyn SearchStore> = Arc::new(indexing::InMemorySearchStore::new());
+    let graph_store: Arc<dyn indexing::store::GraphStore> = Arc::new(indexing::InMemoryGraphStore::new());
+    let event_log: graphql_api::ObjectEventLog = Arc::new(tokio::sync::RwLock::new(EventLog::new()));
+    let schema = Schema::build(QueryRoot::default(), AdminMutations::default(), EmptySubscription)
+        .data(editor())
+        .data(OntologyHandle::new(Ontology::from_yaml(yaml).unwrap()))
+        .data(search_store.clone())
+        .data(graph_store.clone())
+        .data(event_log.clone())
+        .finish();
+    let run = |query: String| {
+        let schema = schema.clone();
+        async move { schema.execute(query).await }
+    };
+    let create = |object_type: &str, properties: Value| {
+        format!(
+            r#"mutation {{ createObject(objectType: "{}", properties: {}) {{ objectId }} }}"#,
+            object_type,
+            serde_json::to_string(&properties.to_string()).unwrap()
+        )
+    };
+    let update = |object_id: &str, properties: Value| {
+        format!(
+            r#"mutation {{ updateObject(objectType: "project", objectId: "{}", properties: {}) {{ properties }} }}"#,
+            object_id,
+            serde_json::to_string(&properties.to_string()).unwrap()
+        )
+    };
+    for (object_type, properties) in [
+        ("project", serde_json::json!({ "id": "apollo", "name": "Apollo", "budget": 100 })),
+        ("project", serde_json::json!({ "id": "gemini", "name": "Gemini", "budget": 50 })),
+        ("task", serde_json::json!({ "id": "t1", "title": "Launch" })),
+        ("task", serde_json::json!({ "id": "t2", "title": "Land" })),
+        ("team", serde_json::json!({ "id": "nasa" })),
+    ] {
+        let response = run(create(object_type, properties)).await;
+        assert!(response.errors.is_empty(), "Create should succeed, got errors: {:?}", response.errors);
+    }
+    let empty = PropertyMap::new();
+    graph_store.create_link("has_task", "apollo", "t1", &empty).await.unwrap();
+    graph_store.create_link("has_task", "apollo", "t2", &empty).await.unwrap();
+    graph_store.create_link("owns", "nasa", "gemini", &empty).await.unwrap();
+
+    // Partial update: only the supplied key changes, and only it is recorded
+    let response = run(update("apollo", serde_json::json!({ "budget": 250, "name": "Apollo" }))).await;
+    assert!(response.errors.is_empty(), "Update should succeed, got errors: {:?}", response.errors);
+    let updated = response.data.into_json().unwrap()["updateObject"]["properties"].clone();
+    assert_eq!(updated["budget"], 250);
+    assert_eq!(updated["name"], "Apollo");
+    let events = event_log.read().await.get_events_for_object("project", "apollo").into_iter().cloned().collect::<Vec<_>>();
+    match &events.last().unwrap().event_type {
+        versioning::event_log::EventType::ObjectUpdated { changed_properties, .. } => {
+            assert_eq!(changed_properties.iter().map(|(key, _)| key.as_str()).collect::<Vec<_>>(), vec!["budget"]);
+        }
+        other => panic!("expected an update event, got {:?}", other),
+    }
+    let replayed = replay(&event_log, "project", "apollo").await.unwrap();
+    let stored = search_store.get_object("project", "apollo").await.unwrap().unwrap();
+    assert_eq!(replayed.get("budget"), Some(&PropertyValue::Integer(250)));
+    assert_eq!(replayed.get("name"), stored.properties.get("name"));
+    assert_eq!(stored.properties.get("budget"), Some(&PropertyValue::Integer(250)));
+
+    let response = run(update("apollo", serde_json::json!({ "id": "saturn", "budget": -5, "name": null }))).await;
+    let message = &response.errors[0].message;
+    assert!(message.contains("The primary key 'id' cannot be changed"), "{}", message);
+    assert!(message.contains("Property 'budget'"), "{}", message);
+    assert!(message.contains("Missing required property 'name'"), "{}", message);
+    let response = run(update("mercury", serde_json::json!({ "budget": 1 }))).await;
+    assert_eq!(response.errors[0].message, "Object project:mercury not found");
+
+    // Deleting a project cascades to its tasks
+    let response = run(r#"mutation { deleteObject(objectType: "project", objectId: "apollo") { deletedObjects deletedLinks } }"#.to_string()).await;
+    assert!(response.errors.is_empty(), "Delete should succeed, got errors: {:?}", response.errors);
+    let deleted = response.data.into_json().unwrap()["deleteObject"].clone();
+    let mut deleted_objects: Vec<String> = serde_json::from_value(deleted["deletedObjects"].clone()).unwrap();
+    deleted_objects.sort();
+    assert_eq!(deleted_objects, vec!["project:apollo", "task:t1", "task:t2"]);
+    assert_eq!(deleted["deletedLinks"].as_array().unwrap().len(), 2);
+    for (object_type, object_id) in [("project", "apollo"), ("task", "t1"), ("task", "t2")] {
+        assert!(search_store.get_object(object_type, object_id).await.unwrap().is_none());
+        assert!(replay(&event_log, object_type, object_id).await.is_none(), "{} should replay as deleted", object_id);
+    }
+    assert!(graph_store.get_links("apollo", None, None, &Default::default()).await.unwrap().is_empty());
+
+    // A restrict link blocks the delete and leaves everything in place
+    let response = run(r#"mutation { deleteObject(objectType: "project", objectId: "gemini") { deletedObjects } }"#.to_string()).await;
+    assert!(response.errors[0].message.contains("'owns' links, which restrict deletion"), "{}", response.errors[0].message);
+    assert!(search_store.get_object("project", "gemini").await.unwrap().is_some());
+    assert_eq!(replay(&event_log, "project", "gemini").await.unwrap().get("name"), Some(&PropertyValue::String("Gemini".to_string())));
+}
+
+#[tokio::test]
+async fn test_create_link_enforces_properties_endpoints_and_cardinality() {
+    let yaml = r#"
+ontology:
+  objectTypes:
+    - id: "person"
+      displayName: "Person"
+      primaryKey: "id"
+      properties:
+        - id: "id"
+          type: "string"
+    - id: "car"
+      displayName: "Car"
+      primaryKey: "id"
+      properties:
+        - id: "id"
+          type: "string"
+  linkTypes:
+    - id: "owns"
+      source: "person"
+      target: "car"
+      cardinality: ONE_TO_MANY
+      properties:
+        - id: "since"
+          type: "date"
+          required: true
+"#;
+    let search_store: Arc<dyn SearchStore> = Arc::new(indexing::InMemorySearchStore::new());
+    let graph_store: Arc<dyn indexing::store::GraphStore> = Arc::new(indexing::InMemoryGraphStore::new());
+    for (object_type, object_id) in [("person", "ann"), ("person", "bob"), ("car", "c1"), ("car", "c2")] {
+        let mut properties = PropertyMap::new();
+        properties.insert("id".to_string(), PropertyValue::String(object_id.to_string()));
+        search_store.index_object(object_type, object_id, &properties, None).await.unwrap();
+    }
+    let schema = Schema::build(QueryRoot::default(), AdminMutations::default(), EmptySubscription)
+        .data(editor())
+        .data(OntologyHandle::new(Ontology::from_yaml(yaml).unwrap()))
+        .data(search_store)
+        .data(graph_store.clone())
+        .finish();
+    let create_link = |source: &str, target: &str, properties: &str| {
+        let request = async_graphql::Request::new(
+            "mutation($source: String!, $target: String!, $properties: String) { createLink(linkType: \"owns\", sourceId: $source, targetId: $target, properties: $properties) { linkId sourceId targetId } }",
+        )
+        .variables(async_graphql::Variables::from_json(serde_json::json!({
+            "source": source,
+            "target": target,
+            "properties": properties,
+        })));
+        let schema = schema.clone();
+        async move { schema.execute(request).await }
+    };
+
+    let response = create_link("ann", "car:c1", r#"{"since": "2020-05-01"}"#).await;
+    assert!(response.errors.is_empty(), "Mutation should succeed, got errors: {:?}", response.errors);
+    let created = response.data.into_json().unwrap()["createLink"].clone();
+    assert_eq!(created["sourceId"], "ann");
+    assert_eq!(created["targetId"], "c1");
+    let link_id = created["linkId"].as_str().unwrap().to_string();
+
+    // A person may own many cars, but each car has one owner
+    let response = create_link("ann", "c2", r#"{"since": "2021-01-01"}"#).await;
+    assert!(response.errors.is_empty(), "Mutation should succeed, got errors: {:?}", response.errors);
+    let response = create_link("bob", "c1", r#"{"since": "2022-01-01"}"#).await;
+    assert_eq!(
+        response.errors[0].message,
+        format!("Link type 'owns' is ONE_TO_MANY: 'c1' already has link '{}' (ann -> c1)", link_id)
+    );
+
+    let response = create_link("bob", "c9", r#"{"color": "red"}"#).await;
+    assert_eq!(response.errors[0].message, "Missing required property 'since'");
+    let response = create_link("bob", "car:c2", r#"{"since": "last year", "color": "red"}"#).await;
+    let message = &response.errors[0].message;
+    assert!(message.contains("Property 'since'"), "{}", message);
+    let response = create_link("bob", "car:c2", r#"{"since": "2022-01-01", "color": "red"}"#).await;
+    assert_eq!(response.errors[0].message, "Unknown property 'color' on link type 'owns'");
+    let response = create_link("bob", "c9", r#"{"since": "2022-01-01"}"#).await;
+    assert_eq!(response.errors[0].message, "Object car:c9 not found");
+    let response = create_link("car:c2", "c1", r#"{"since": "2022-01-01"}"#).await;
+    assert_eq!(response.errors[0].message, "Link type 'owns' connects 'person' objects, not 'car'");
+
+    // Once the link is gone the car can change hands
+    let delete = format!(r#"mutation {{ deleteLink(linkId: "{}") }}"#, link_id);
+    let response = schema.execute(delete.as_str()).await;
+    assert_eq!(response.data.into_json().unwrap()["deleteLink"], true);
+    assert_eq!(schema.execute(delete.as_str()).await.errors[0].message, format!("Link '{}' not found", link_id));
+    let response = create_link("bob", "c1", r#"{"since": "2022-01-01"}"#).await;
+    assert!(response.errors.is_empty(), "Mutation should succeed, got errors: {:?}", response.errors);
+    assert_eq!(graph_store.get_links("c1", Some("owns"), None, &Default::default()).await.unwrap()[0].source_id, "bob");
+}
+
+#[tokio::test]
+async fn test_object_and_link_writes_check_role_and_acl() {
+    use indexing::store::GraphStore;
+
+    let yaml = r#"
+ontology:
+  objectTypes:
+    - id: "doc"
+      displayName: "Document"
+      primaryKey: "id"
+      properties:
+        - id: "id"
+          type: "string"
+        - id: "title"
+          type: "string"
+  linkTypes:
+    - id: "cites"
+      source: "doc"
+      target: "doc"
+"#;
+    let search_store: Arc<dyn SearchStore> = Arc::new(indexing::InMemorySearchStore::new());
+    let graph_store: Arc<dyn GraphStore> = Arc::new(indexing::InMemoryGraphStore::new());
+    for (id, writer) in [("open", None), ("locked", Some("alice"))] {
+        let mut properties = PropertyMap::new();
+        properties.insert("id".to_string(), PropertyValue::String(id.to_string()));
+        if let Some(writer) = writer {
+            let acl = security::acl::ObjectAcl::new(vec![
+                security::acl::AclEntry::new("role:editor".to_string(), security::acl::AclPermission::Read),
+                security::acl::AclEntry::new(format!("user:{}", writer), security::acl::AclPermission::Write),
+            ]);
+            properties.insert(security::acl::ACL_PROPERTY.to_string(), acl.to_property_value());
+        }
+        search_store.index_object("doc", id, &properties, None).await.unwrap();
+    }
+    let link_id = graph_store.create_link("cites", "open", "locked", &PropertyMap::new()).await.unwrap();
+    let schema = Schema::build(QueryRoot::default(), AdminMutations::default(), EmptySubscription)
+        .data(OntologyHandle::new(Ontology::from_yaml(yaml).unwrap()))
+        .data(search_store.clone())
+        .data(graph_store)
+        .finish();
+    let run = |mutation: String, context: security::SecurityContext| {
+        let schema = schema.clone();
+        async move { schema.execute(async_graphql::Request::new(mutation).data(context)).await }
+    };
+    let error_code = |response: &async_graphql::Response| {
+        assert_eq!(response.errors.len(), 1, "{:?}", response.errors);
+        serde_json::to_value(&response.errors[0]).unwrap()["extensions"]["code"].clone()
+    };
+    let update = |id: &str| {
+        format!(r#"mutation {{ updateObject(objectType: "doc", objectId: "{}", properties: "{{\"title\": \"Draft\"}}") {{ objectId }} }}"#, id)
+    };
+    let bob = || security::SecurityContext::new("bob".to_string()).with_role("editor".to_string());
+    let alice = || security::SecurityContext::new("alice".to_string()).with_role("editor".to_string());
+
+    // Writing needs the editor role
+    let create = r#"mutation { createObject(objectType: "doc", properties: "{\"id\": \"new\"}") { objectId } }"#;
+    let response = run(create.to_string(), security::SecurityContext::new("bob".to_string())).await;
+    assert_eq!(error_code(&response), "UNAUTHORIZED");
+    assert!(response.errors[0].message.contains("the 'admin' or 'editor' role is required"), "{:?}", response.errors);
+    assert!(run(create.to_string(), bob()).await.errors.is_empty());
+    assert!(run(update("open"), bob()).await.errors.is_empty());
+
+    // Editors the ACL does not let write the object are refused, whatever the mutation
+    for mutation in [
+        update("locked"),
+        r#"mutation { upsertObject(objectType: "doc", objectId: "locked", properties: { title: "Draft" }) { success } }"#.to_string(),
+        r#"mutation { deleteObject(objectType: "doc", objectId: "locked") { deletedObjects } }"#.to_string(),
+        r#"mutation { createLink(linkType: "cites", sourceId: "new", targetId: "locked") { linkId } }"#.to_string(),
+        format!(r#"mutation {{ deleteLink(linkId: "{}") }}"#, link_id),
+    ] {
+        let response = run(mutation.clone(), bob()).await;
+        assert_eq!(error_code(&response), "UNAUTHORIZED", "{}", mutation);
+        assert!(response.errors[0].message.contains("User 'bob' may not write doc:locked"), "{:?}", response.errors);
+    }
+    let locked = search_store.get_object("doc", "locked").await.unwrap().unwrap();
+    assert!(locked.properties.get("title").is_none());
+
+    assert!(run(update("locked"), alice()).await.errors.is_empty());
+    let response = run(format!(r#"mutation {{ deleteLink(linkId: "{}") }}"#, link_id), alice()).await;
+    assert_eq!(response.data.into_json().unwrap()["deleteLink"], true);
+}
+
+#[tokio::test]
+async fn test_interface_function_accepts_any_implementer() {
+    let yaml = r#"
+ontology:
+  interfaces:
+    - id: "Location"
+      displayName: "Location"
+      properties:
+        - id: "area"
+          type: "double"
+  objectTypes:
+    - id: "tract"
+      displayName: "Census Tract"
+      primaryKey: "geoid"
+      implements: ["Location"]
+      interfaceMappings:
+        Location:
+          area: "land_area"
+      properties:
+        - id: "geoid"
+          type: "string"
+        - id: "land_area"
+          type: "double"
+    - id: "county"
+      displayName: "County"
+      primaryKey: "fips"
+      implements: ["Location"]
+      properties:
+        - id: "fips"
+          type: "string"
+        - id: "area"
+          type: "double"
+  linkTypes: []
+  functionTypes:
+    - id: "location_area"
+      displayName: "Location Area"
+      targetInterface: "Location"
+      parameters:
+        - id: "location"
+          type: "object_reference"
+          required: true
+      returnType:
+        type: "property"
+        property_type: "double"
+      logic:
+        type: "property_access"
+        property: "area"
+"#;
+    let search_store: Arc<dyn SearchStore> = Arc::new(indexing::InMemorySearchStore::new());
+    let graph_store: Arc<dyn indexing::store::GraphStore> = Arc::new(indexing::InMemoryGraphStore::new());
+    for (object_type, object_id, property, area) in [
+        ("tract", "36061000100", "land_area", 0.42),
+        ("county", "36061", "area", 58.7),
+    ] {
+        let mut properties = ontology_engine::PropertyMap::new();
+        properties.insert(property.to_string(), PropertyValue::Double(area));
+        search_store.index_object(object_type, object_id, &properties, None).await.unwrap();
+    }
+    let schema = Schema::build(QueryRoot::default(), AdminMutations::default(), EmptySubscription)
+        .data(OntologyHandle::new(Ontology::from_yaml(yaml).unwrap()))
+        .data(search_store)
+        .data(graph_store)
+        .finish();
+
+    let functions = schema.execute("{ getFunctions { id targetInterface } }").await;
+    assert!(functions.errors.is_empty(), "{:?}", functions.errors);
+    assert_eq!(
+        functions.data.into_json().unwrap()["getFunctions"][0]["targetInterface"],
+        "Location"
+    );
+
+    for (object_id, expected) in [("36061000100", 0.42), ("36061", 58.7), ("county:36061", 58.7)] {
+        let query = format!(
+            r#"{{ callFunction(functionId: "location_area", parameters: {{ location: "\"{}\"" }}) {{ value }} }}"#,
+            object_id
+        );
+        let response = schema.execute(query.as_str()).await;
+        assert!(response.errors.is_empty(), "{:?}", response.errors);
+        assert_eq!(response.data.into_json().unwrap()["callFunction"]["value"], expected);
+    }
+
+    let missing = schema
+        .execute(r#"{ callFunction(functionId: "location_area", parameters: { location: "\"nowhere\"" }) { value } }"#)
+        .await;
+    assert!(missing.errors[0].message.contains("not found for any implementer"));
+}
+
+/// In-memory search store that explains searches like a backend with a query language
+struct ExplainingSearchStore(indexing::InMemorySearchStore);
+
+#[async_trait::async_trait]
+impl SearchStore for ExplainingSearchStore {
+    async fn index_object(&self, object_type: &str, object_id: &str, properties: &ontology_engine::PropertyMap, expected_revision: Option<u64>) -> Result<u64, indexing::store::StoreError> {
+        self.0.index_object(object_type, object_id, properties, expected_revision).await
+    }
+    async fn search(&self, object_type: &str, query: &indexing::store::SearchQuery) -> Result<Vec<indexing::store::IndexedObject>, indexing::store::StoreError> {
+        self.0.search(object_type, query).await
+    }
+    async fn get_object(&self, object_type: &str, object_id: &str) -> Result<Option<indexing::store::IndexedObject>, indexing::store::StoreError> {
+        self.0.get_object(object_type, object_id).await
+    }
+    async fn bulk_index(&self, objects: Vec<indexing::store::IndexedObject>) -> Result<(), indexing::store::StoreError> {
+        self.0.bulk_index(objects).await
+    }
+    async fn delete_object(&self, object_type: &str, object_id: &str) -> Result<(), indexing::store::StoreError> {
+        self.0.delete_object(object_type, object_id).await
+    }
+    async fn count_objects(&self, object_type: &str, filters: Option<&[indexing::store::Filter]>) -> Result<u64, indexing::store::StoreError> {
+        self.0.count_objects(object_type, filters).await
+    }
+    fn explain_search(&self, object_type: &str, query: &indexing::store::SearchQuery) -> Option<Value> {
+        Some(serde_json::json!({ "index": object_type, "size": query.limit }))
+    }
+}
+
+/// In-memory search store that counts natively, like a backend with aggregations, and can
+/// be taken down
+#[derive(Default)]
+struct AggregatingSearchStore {
+    inner: indexing::InMemorySearchStore,
+    down: std::sync::atomic::AtomicBool,
+}
+
+#[async_trait::async_trait]
+impl SearchStore for AggregatingSearchStore {
+    async fn index_object(&self, object_type: &str, object_id: &str, properties: &ontology_engine::PropertyMap, expected_revision: Option<u64>) -> Result<u64, indexing::store::StoreError> {
+        self.inner.index_object(object_type, object_id, properties, expected_revision).await
+    }
+    async fn search(&self, object_type: &str, query: &indexing::store::SearchQuery) -> Result<Vec<indexing::store::IndexedObject>, indexing::store::StoreError> {
+        self.inner.search(object_type, query).await
+    }
+    async fn get_object(&self, object_type: &str, object_id: &str) -> Result<Option<indexing::store::IndexedObject>, indexing::store::StoreError> {
+        self.inner.get_object(object_type, object_id).await
+    }
+    async fn bulk_index(&self, objects: Vec<indexing::store::IndexedObject>) -> Result<(), indexing::store::StoreError> {
+        self.inner.bulk_index(objects).await
+    }
+    async fn delete_object(&self, object_type: &str, object_id: &str) -> Result<(), indexing::store::StoreError> {
+        self.inner.delete_object(object_type, object_id).await
+    }
+    async fn count_objects(&self, object_type: &str, filters: Option<&[indexing::store::Filter]>) -> Result<u64, indexing::store::StoreError> {
+        self.inner.count_objects(object_type, filters).await
+    }
+    async fn aggregate(&self, object_type: &str, query: &indexing::store::AnalyticsQuery) -> Result<indexing::store::AnalyticsResult, indexing::store::StoreError> {
+        if self.down.load(std::sync::atomic::Ordering::SeqCst) {
+            return Err(indexing::store::StoreError::Connection("connection refused".to_string()));
+        }
+        let count = self.inner.count_objects(object_type, Some(&query.filters)).await?;
+        let row = HashMap::from([("count".to_string(), PropertyValue::Integer(count as i64))]);
+        Ok(indexing::store::AnalyticsResult { rows: vec![row], total: 1, sample_fraction: None, error_bounds: vec![] })
+    }
+    fn supports_aggregations(&self) -> bool {
+        true
+    }
+}
+
+#[tokio::test]
+async fn test_aggregate_routes_through_query_planner() {
+    let yaml = r#"
+ontology:
+  objectTypes:
+    - id: "plant"
+      displayName: "Plant"
+      primaryKey: "id"
+      properties:
+        - id: "id"
+          type: "string"
+        - id: "fuel"
+          type: "string"
+  linkTypes: []
+"#;
+    let search = Arc::new(AggregatingSearchStore::default());
+    let columnar = Arc::new(indexing::InMemoryColumnarStore::new());
+    let mut plants = Vec::new();
+    for (id, fuel) in [("p1", "gas"), ("p2", "coal"), ("p3", "gas")] {
+        let mut properties = ontology_engine::PropertyMap::new();
+        properties.insert("id".to_string(), PropertyValue::String(id.to_string()));
+        properties.insert("fuel".to_string(), PropertyValue::String(fuel.to_string()));
+        search.index_object("plant", id, &properties, None).await.unwrap();
+        plants.push(indexing::store::IndexedObject::new("plant".to_string(), id.to_string(), properties));
+    }
+    indexing::store::ColumnarStore::write_batch(columnar.as_ref(), "plant", plants).await.unwrap();
+
+    let schema = Schema::build(QueryRoot::default(), AdminMutations::default(), EmptySubscription)
+        .extension(graphql_api::QueryExplainExtension)
+        .data(OntologyHandle::new(Ontology::from_yaml(yaml).unwrap()))
+        .data(search.clone() as Arc<dyn SearchStore>)
+        .data(columnar as Arc<dyn indexing::store::ColumnarStore>)
+        .data(indexing::QueryPlanner::default())
+        .finish();
+    let aggregate = |group_by: &str| {
+        let query = format!(
+            r#"{{ aggregateObjects(objectType: "plant", aggregations: [{{ property: "id", operation: "count" }}]{}, explain: true) {{ rows }} }}"#,
+            group_by
+        );
+        let schema = schema.clone();
+        async move {
+            let response = schema.execute(query.as_str()).await;
+            assert!(response.errors.is_empty(), "{:?}", response.errors);
+            let extensions = serde_json::to_value(&response.extensions).unwrap();
+            let plan = extensions["explain"][0].clone();
+            assert_eq!(plan["operation"], "plan");
+            (response.data.into_json().unwrap()["aggregateObjects"]["rows"].clone(), plan["query"].clone())
+        }
+    };
+
+    // Ungrouped counts are answered by the search index, grouped ones by the columnar store
+    let (rows, plan) = aggregate("").await;
+    assert_eq!(rows[0]["count"], 3);
+    assert_eq!((plan["backend"].as_str(), plan["fallback"].as_str()), (Some("search"), Some("columnar")));
+    let (rows, plan) = aggregate(r#", groupBy: ["fuel"]"#).await;
+    assert_eq!(rows.as_array().unwrap().len(), 2);
+    assert_eq!(plan["backend"], "columnar");
+
+    // A search backend that cannot be reached falls back, and is avoided by later plans
+    search.down.store(true, std::sync::atomic::Ordering::SeqCst);
+    let (rows, plan) = aggregate("").await;
+    assert_eq!(rows[0]["count"], 3);
+    assert_eq!(plan["backend"], "search");
+    let (rows, plan) = aggregate("").await;
+    assert_eq!(rows[0]["count"], 3);
+    assert_eq!(plan["backend"], "columnar");
+    assert!(plan["reasons"].as_array().unwrap().iter().any(|r| r == "search is unhealthy: connection refused"));
+}
+
+#[tokio::test]
+async fn test_slow_queries_and_explain() {
+    let yaml = r#"
+ontology:
+  objectTypes:
+    - id: "person"
+      displayName: "Person"
+      primaryKey: "id"
+      properties:
+        - id: "id"
+          type: "string"
+        - id: "ssn"
+          type: "string"
+          pii: true
+  linkTypes: []
+"#;
+    let ontology = OntologyHandle::new(Ontology::from_yaml(yaml).unwrap());
+    let log = Arc::new(
+        indexing::QueryLog::new(indexing::QueryLogConfig {
+            slow_threshold: std::time::Duration::ZERO,
+            ..Default::default()
+        })
+        .with_ontology(ontology.clone()),
+    );
+    let search_store: Arc<dyn SearchStore> = Arc::new(indexing::LoggedSearchStore::new(
+        Arc::new(ExplainingSearchStore(indexing::InMemorySearchStore::new())),
+        "memory",
+        log.clone(),
+    ));
+    let schema = Schema::build(QueryRoot::default(), AdminMutations::default(), EmptySubscription)
+        .extension(graphql_api::QueryExplainExtension)
+        .data(ontology)
+        .data(search_store)
+        .data(ObjectHydrator::new())
+        .data(log)
+        .finish();
+
+    let response = schema
+        .execute(r#"{ searchObjects(objectType: "person", filters: [{ property: "ssn", operator: "equals", value: "\"123-45-6789\"" }], limit: 5, explain: true) { objectId } }"#)
+        .await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    let extensions = serde_json::to_value(&response.extensions).unwrap();
+    assert_eq!(extensions["explain"][0]["operation"], "search");
+    assert_eq!(extensions["explain"][0]["path"], "searchObjects");
+    assert_eq!(extensions["explain"][0]["query"]["size"], 5);
+
+    // Without the flag nothing is explained
+    let response = schema.execute(r#"{ searchObjects(objectType: "person") { objectId } }"#).await;
+    assert!(response.extensions.is_empty());
+
+    let response = schema
+        .execute("{ slowQueries(limit: 1) { backend operation objectType query resultCount } }")
+        .await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    let slow = response.data.into_json().unwrap()["slowQueries"].clone();
+    assert_eq!(slow.as_array().unwrap().len(), 1);
+    assert_eq!(slow[0]["backend"], "memory");
+    assert_eq!(slow[0]["objectType"], "person");
+    assert_eq!(slow[0]["resultCount"], 0);
+    let response = schema.execute("{ slowQueries { query } }").await;
+    let slow = response.data.into_json().unwrap()["slowQueries"].clone();
+    assert_eq!(slow[1]["query"]["filters"][0]["value"], "[REDACTED]");
+}
+
+#[tokio::test]
+async fn test_consistency_check_job_reports_progress_and_result() {
+    let yaml = r#"
+ontology:
+  objectTypes:
+    - id: "person"
+      displayName: "Person"
+      primaryKey: "id"
+      properties:
+        - id: "id"
+          type: "string"
+    - id: "company"
+      displayName: "Company"
+      primaryKey: "id"
+      properties:
+        - id: "id"
+          type: "string"
+  linkTypes:
+    - id: "works_at"
+      displayName: "Works At"
+      source: "person"
+      target: "company"
+      cardinality: "MANY_TO_ONE"
+"#;
+    let search_store: Arc<dyn SearchStore> = Arc::new(indexing::InMemorySearchStore::new());
+    let graph_store: Arc<dyn indexing::store::GraphStore> = Arc::new(indexing::InMemoryGraphStore::new());
+    let none = ontology_engine::PropertyMap::new();
+    search_store.index_object("person", "p1", &none, None).await.unwrap();
+    search_store.index_object("company", "c1", &none, None).await.unwrap();
+    graph_store.create_link("works_at", "p1", "c1", &none).await.unwrap();
+    // The company was never indexed
+    let dangling = graph_store.create_link("works_at", "p1", "c2", &none).await.unwrap();
+
+    let schema = Schema::build(QueryRoot::default(), AdminMutations::default(), EmptySubscription)
+        .data(OntologyHandle::new(Ontology::from_yaml(yaml).unwrap()))
+        .data(search_store)
+        .data(graph_store)
+        .data(indexing::JobRegistry::new())
+        .finish();
+
+    let response = schema
+        .execute(r#"mutation { startConsistencyCheck(objectType: "person") }"#)
+        .await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    let job_id = response.data.into_json().unwrap()["startConsistencyCheck"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    let query = format!(r#"{{ job(jobId: "{}") {{ kind status phase processed total result error }} }}"#, job_id);
+    let mut job = Value::Null;
+    for _ in 0..100 {
+        let response = schema.execute(query.as_str()).await;
+        assert!(response.errors.is_empty(), "{:?}", response.errors);
+        job = response.data.into_json().unwrap()["job"].clone();
+        if job["status"] != "running" {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+    assert_eq!(job["kind"], "consistency_check");
+    assert_eq!(job["status"], "completed", "{}", job);
+    assert_eq!(job["phase"], "graph");
+    assert_eq!(job["processed"], 2);
+    assert_eq!(job["result"]["links_scanned"], 2);
+    assert_eq!(job["result"]["dangling_links"][0]["link_id"], dangling.as_str());
+    assert_eq!(job["result"]["missing_objects"][0]["object_id"], "c2");
+
+    let response = schema.execute(r#"{ job(jobId: "unknown") { status } }"#).await;
+    assert_eq!(response.data.into_json().unwrap()["job"], Value::Null);
+}
+
+#[tokio::test]
+async fn test_search_rejects_unindexed_filter_and_sort() {
+    let yaml = r#"
+ontology:
+  objectTypes:
+    - id: "document"
+      displayName: "Document"
+      primaryKey: "id"
+      properties:
+        - id: "id"
+          type: "string"
+        - id: "body"
+          type: "string"
+          indexing: "text_only"
+        - id: "raw"
+          type: "string"
+          indexing: "not_indexed"
+  linkTypes: []
+"#;
+    let search_store: Arc<dyn SearchStore> = Arc::new(indexing::InMemorySearchStore::new());
+    let schema = Schema::build(QueryRoot::default(), AdminMutations::default(), EmptySubscription)
+        .data(OntologyHandle::new(Ontology::from_yaml(yaml).unwrap()))
+        .data(search_store)
+        .data(ObjectHydrator::new())
+        .finish();
+
+    let response = schema
+        .execute(r#"{ searchObjects(objectType: "document", filters: [{ property: "raw", operator: "equals", value: "\"x\"" }]) { objectId } }"#)
+        .await;
+    assert_eq!(
+        response.errors[0].message,
+        "Cannot filter on property 'raw' of object type 'document': it has indexing 'not_indexed'"
+    );
+
+    let response = schema
+        .execute(r#"{ searchObjects(objectType: "document", sort: { property: "body" }) { objectId } }"#)
+        .await;
+    assert_eq!(
+        response.errors[0].message,
+        "Cannot sort on property 'body' of object type 'document': it has indexing 'text_only'"
+    );
+
+    // Full-text properties can still be filtered
+    let response = schema
+        .execute(r#"{ searchObjects(objectType: "document", filters: [{ property: "body", operator: "contains", value: "\"x\"" }]) { objectId } }"#)
+        .await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+}
+
+#[tokio::test]
+async fn test_export_job_writes_masked_file() {
+    use security::{MaskingPolicy, MaskingStrategy};
+
+    let yaml = r#"
+ontology:
+  objectTypes:
+    - id: "person"
+      displayName: "Person"
+      primaryKey: "id"
+      properties:
+        - id: "id"
+          type: "string"
+        - id: "ssn"
+          type: "string"
+          sensitivityTags: ["ssn"]
+  linkTypes: []
+"#;
+    let ontology = OntologyHandle::new(Ontology::from_yaml(yaml).unwrap());
+    let search_store: Arc<dyn SearchStore> = Arc::new(indexing::InMemorySearchStore::new());
+    for (id, ssn, acl) in [("p1", "123-45-6789", None), ("p2", "987-65-4321", Some("alice"))] {
+        let mut properties = ontology_engine::PropertyMap::new();
+        properties.insert("id".to_string(), PropertyValue::String(id.to_string()));
+        properties.insert("ssn".to_string(), PropertyValue::String(ssn.to_string()));
+        if let Some(reader) = acl {
+            let acl = security::acl::ObjectAcl {
+                entries: vec![security::acl::AclEntry {
+                    principal: format!("user:{}", reader),
+                    permission: security::acl::AclPermission::Read,
+                }],
+            };
+            properties.insert(security::acl::ACL_PROPERTY.to_string(), acl.to_property_value());
+        }
+        let indexed = security::acl::with_acl_index_fields(&properties).unwrap();
+        search_store.index_object("person", id, &indexed, None).await.unwrap();
+    }
+
+    let dir = std::env::temp_dir().join(format!("exports-{}", uuid::Uuid::new_v4()));
+    let jobs = indexing::JobRegistry::new().with_retention(std::time::Duration::from_secs(3600));
+    let schema = Schema::build(QueryRoot::default(), AdminMutations::default(), EmptySubscription)
+        .data(indexing::Exporter::new(ontology.clone(), search_store.clone(), &dir))
+        .data(ontology)
+        .data(search_store)
+        .data(jobs.clone())
+        .data(MaskingPolicy::new("test-key").with_rule("*", "ssn", MaskingStrategy::PartialReveal { keep_last: 4 }))
+        .finish();
+    let bob = security::SecurityContext::new("bob".to_string());
+
+    let response = schema
+        .execute(async_graphql::Request::new(r#"mutation { startExport(objectType: "person", format: "jsonl") }"#).data(bob.clone()))
+        .await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    let job_id = response.data.into_json().unwrap()["startExport"].as_str().unwrap().to_string();
+
+    let query = format!(r#"{{ exportStatus(jobId: "{}") {{ status processed total path format rows bytes error expiresAt }} }}"#, job_id);
+    let mut status = Value::Null;
+    for _ in 0..100 {
+        let response = schema.execute(async_graphql::Request::new(query.as_str()).data(bob.clone())).await;
+        assert!(response.errors.is_empty(), "{:?}", response.errors);
+        status = response.data.into_json().unwrap()["exportStatus"].clone();
+        if status["status"] != "running" {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+    assert_eq!(status["status"], "completed", "{}", status);
+    assert_eq!((status["rows"].clone(), status["format"].clone()), (serde_json::json!(1), serde_json::json!("jsonl")));
+    assert!(status["expiresAt"].is_string());
+
+    // Only Bob and admins see Bob's export
+    let alice = security::SecurityContext::new("alice".to_string());
+    let admin = security::SecurityContext::new("root".to_string()).with_role("admin".to_string());
+    let status_as = |context: Option<security::SecurityContext>| {
+        let mut request = async_graphql::Request::new(format!(r#"{{ exportStatus(jobId: "{0}") {{ status }} job(jobId: "{0}") {{ status }} }}"#, job_id));
+        if let Some(context) = context {
+            request = request.data(context);
+        }
+        let schema = &schema;
+        async move { schema.execute(request).await.data.into_json().unwrap() }
+    };
+    assert_eq!(status_as(None).await, serde_json::json!({"exportStatus": null, "job": null}));
+    assert_eq!(status_as(Some(alice.clone())).await, serde_json::json!({"exportStatus": null, "job": null}));
+    assert_eq!(status_as(Some(admin)).await["exportStatus"]["status"], "completed");
+
+    // Bob cannot read p2, and sees p1's SSN masked; index-only ACL fields are not exported
+    let content = std::fs::read_to_string(status["path"].as_str().unwrap()).unwrap();
+    assert_eq!(status["bytes"], content.len());
+    let row: Value = serde_json::from_str(content.trim_end()).unwrap();
+    assert_eq!(row, serde_json::json!({"id": "p1", "ssn": "***-**-6789"}));
+
+    let response = schema
+        .execute(r#"mutation { startExport(objectType: "person", format: "xml") }"#)
+        .await;
+    assert!(response.errors[0].message.contains("Unsupported export format"));
+    let response = schema.execute(format!(r#"mutation {{ cancelJob(jobId: "{}") }}"#, job_id)).await;
+    assert_eq!(response.data.into_json().unwrap()["cancelJob"], false);
+
+    // Nor may anyone else cancel Bob's running jobs
+    let running = jobs.start_for(indexing::export::EXPORT_JOB_KIND, Some("bob".to_string()));
+    let cancel = format!(r#"mutation {{ cancelJob(jobId: "{}") }}"#, running.id());
+    let response = schema.execute(async_graphql::Request::new(cancel.as_str()).data(alice)).await;
+    assert_eq!(response.data.into_json().unwrap()["cancelJob"], false);
+    let response = schema.execute(async_graphql::Request::new(cancel.as_str()).data(bob)).await;
+    assert_eq!(response.data.into_json().unwrap()["cancelJob"], true);
+
+    std::fs::remove_dir_all(&dir).ok();
+}
+
+#[tokio::test]
+async fn test_find_duplicates_and_merge_objects() {
+    use indexing::store::GraphStore;
+
+    let yaml = r#"
+ontology:
+  objectTypes:
+    - id: "business"
+      displayName: "Business"
+      primaryKey: "id"
+      titleKey: "name"
+      properties:
+        - id: "id"
+          type: "string"
+        - id: "name"
+          type: "string"
+        - id: "website"
+          type: "string"
+          sensitivityTags: ["contact"]
+      dedupRules:
+        - match:
+            - property: "name"
+              comparator:
+                type: "normalized"
+    - id: "person"
+      displayName: "Person"
+      primaryKey: "id"
+      properties:
+        - id: "id"
+          type: "string"
+  linkTypes:
+    - id: "owns"
+      displayName: "Owns"
+      source: "person"
+      target: "business"
+      cardinality: "MANY_TO_MANY"
+"#;
+    let search_store: Arc<dyn SearchStore> = Arc::new(indexing::InMemorySearchStore::new());
+    let graph_store: Arc<dyn GraphStore> = Arc::new(indexing::InMemoryGraphStore::new());
+    for (id, name, website) in [("b1", "Acme, Inc.", None), ("b2", "ACME INC", Some("acme.example")), ("b3", "Globex", None)] {
+        let mut properties = ontology_engine::PropertyMap::new();
+        properties.insert("id".to_string(), PropertyValue::String(id.to_string()));
+        properties.insert("name".to_string(), PropertyValue::String(name.to_string()));
+        if let Some(website) = website {
+            properties.insert("website".to_string(), PropertyValue::String(website.to_string()));
+        }
+        if id == "b3" {
+            let acl = security::acl::ObjectAcl {
+                entries: vec![security::acl::AclEntry {
+                    principal: "user:alice".to_string(),
+                    permission: security::acl::AclPermission::Write,
+                }],
+            };
+            properties.insert(security::acl::ACL_PROPERTY.to_string(), acl.to_property_value());
+        }
+        let indexed = security::acl::with_acl_index_fields(&properties).unwrap();
+        search_store.index_object("business", id, &indexed, None).await.unwrap();
+    }
+    graph_store.create_link("owns", "alice", "b2", &ontology_engine::PropertyMap::new()).await.unwrap();
+
+    let merged_ids = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let sink_ids = merged_ids.clone();
+    let sink: indexing::MergeEventSink = Arc::new(move |record: &indexing::MergeRecord| {
+        sink_ids.lock().unwrap().extend(record.loser_ids.clone());
+    });
+    let schema = Schema::build(QueryRoot::default(), AdminMutations::default(), EmptySubscription)
+        .data(OntologyHandle::new(Ontology::from_yaml(yaml).unwrap()))
+        .data(search_store)
+        .data(graph_store.clone())
+        .data(ObjectHydrator::new())
+        .data(indexing::JobRegistry::new())
+        .data(sink)
+        .data(security::MaskingPolicy::new("test-key").with_rule("*", "contact", security::MaskingStrategy::Redact))
+        .finish();
+
+    let response = schema.execute(r#"mutation { findDuplicates(objectType: "business") }"#).await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    let job_id = response.data.into_json().unwrap()["findDuplicates"].as_str().unwrap().to_string();
+    let query = format!(r#"{{ job(jobId: "{}") {{ status result }} }}"#, job_id);
+    let mut job = Value::Null;
+    for _ in 0..100 {
+        job = schema.execute(query.as_str()).await.data.into_json().unwrap()["job"].clone();
+        if job["status"] != "running" {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+    assert_eq!(job["status"], "completed", "{}", job);
+    assert_eq!(job["result"]["clusters"], serde_json::json!([["b1", "b2"]]));
+
+    // Merging needs the editor role, and write access to every merged object
+    let merge = r#"mutation { mergeObjects(objectType: "business", winnerId: "b1", loserIds: ["b2"]) { properties linksRepointed } }"#;
+    let response = schema.execute(async_graphql::Request::new(merge).data(security::SecurityContext::new("bob".to_string()))).await;
+    assert!(response.errors[0].message.contains("may not write objects"), "{:?}", response.errors);
+    let response = schema
+        .execute(async_graphql::Request::new(r#"mutation { mergeObjects(objectType: "business", winnerId: "b1", loserIds: ["b3"]) { winnerId } }"#).data(editor()))
+        .await;
+    assert!(response.errors[0].message.contains("may not write business:b3"), "{:?}", response.errors);
+
+    // The merged properties are masked
+    let response = schema.execute(async_graphql::Request::new(merge).data(editor())).await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    let merge = &response.data.into_json().unwrap()["mergeObjects"];
+    assert_eq!(merge["properties"]["name"], "Acme, Inc.");
+    assert_ne!(merge["properties"]["website"], "acme.example");
+    assert!(merge["properties"]["website"].is_string());
+    assert_eq!(merge["linksRepointed"], 1);
+    assert_eq!(*merged_ids.lock().unwrap(), vec!["b2".to_string()]);
+    let owned = graph_store
+        .get_links("alice", Some("owns"), None, &indexing::store::LinkQuery::default())
+        .await
+        .unwrap();
+    assert_eq!(owned[0].target_id, "b1");
+
+    // The merged-away ID still resolves, to the winner
+    let response = schema
+        .execute(r#"{ getObject(objectType: "business", objectId: "b2") { objectId title } }"#)
+        .await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    let object = &response.data.into_json().unwrap()["getObject"];
+    assert_eq!(object["objectId"], "b1");
+    assert_eq!(object["title"], "Acme, Inc.");
+
+    let response = schema
+        .execute(r#"mutation { findDuplicates(objectType: "person") }"#)
+        .await;
+    assert!(response.errors[0].message.contains("no dedup rules"));
+}
+
+#[tokio::test]
+async fn test_call_function_objects_hydrates_readable_objects() {
+    use indexing::store::GraphStore;
+
+    let yaml = r#"
+ontology:
+  objectTypes:
+    - id: "site"
+      displayName: "Site"
+      primaryKey: "id"
+      properties:
+        - id: "id"
+          type: "string"
+    - id: "plant"
+      displayName: "Plant"
+      primaryKey: "id"
+      titleKey: "name"
+      properties:
+        - id: "id"
+          type: "string"
+        - id: "name"
+          type: "string"
+  linkTypes:
+    - id: "site_plants"
+      displayName: "Site Plants"
+      source: "site"
+      target: "plant"
+      cardinality: "ONE_TO_MANY"
+  functionTypes:
+    - id: "plants_at_site"
+      displayName: "Plants at Site"
+      parameters:
+        - id: "site"
+          type: "object_reference"
+          required: true
+      returnType:
+        type: "array"
+        element_type:
+          type: "object_type"
+          object_type: "plant"
+      logic:
+        type: "link_traversal"
+        linkType: "site_plants"
+        targetType: "plant"
+    - id: "plant_capacity"
+      displayName: "Plant Capacity"
+      parameters:
+        - id: "site"
+          type: "object_reference"
+          required: true
+      returnType:
+        type: "property"
+        property_type: "double"
+      logic:
+        type: "link_traversal"
+        linkType: "site_plants"
+        targetType: "plant"
+"#;
+    let search_store: Arc<dyn SearchStore> = Arc::new(indexing::InMemorySearchStore::new());
+    let graph_store: Arc<dyn GraphStore> = Arc::new(indexing::InMemoryGraphStore::new());
+    for (id, name, reader) in [("p1", "North Mill", None), ("p2", "South Mill", Some("alice")), ("p3", "East Mill", None)] {
+        let mut properties = ontology_engine::PropertyMap::new();
+        properties.insert("id".to_string(), PropertyValue::String(id.to_string()));
+        properties.insert("name".to_string(), PropertyValue::String(name.to_string()));
+        if let Some(reader) = reader {
+            let acl = security::acl::ObjectAcl {
+                entries: vec![security::acl::AclEntry {
+                    principal: format!("user:{}", reader),
+                    permission: security::acl::AclPermission::Read,
+                }],
+            };
+            properties.insert(security::acl::ACL_PROPERTY.to_string(), acl.to_property_value());
+        }
+        let indexed = security::acl::with_acl_index_fields(&properties).unwrap();
+        search_store.index_object("plant", id, &indexed, None).await.unwrap();
+    }
+    for plant in ["p1", "p2"] {
+        graph_store.create_link("site_plants", "s1", plant, &ontology_engine::PropertyMap::new()).await.unwrap();
+    }
+    let schema = Schema::build(QueryRoot::default(), AdminMutations::default(), EmptySubscription)
+        .data(OntologyHandle::new(Ontology::from_yaml(yaml).unwrap()))
+        .data(search_store)
+        .data(graph_store)
+        .data(ObjectHydrator::new())
+        .finish();
+
+    let query = r#"{ callFunctionObjects(functionId: "plants_at_site", parameters: { site: "\"site:s1\"" }) { objectId title } }"#;
+    let titles = |response: async_graphql::Response| -> Vec<String> {
+        assert!(response.errors.is_empty(), "{:?}", response.errors);
+        let mut titles: Vec<String> = response.data.into_json().unwrap()["callFunctionObjects"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|object| object["title"].as_str().unwrap().to_string())
+            .collect();
+        titles.sort();
+        titles
+    };
+    let alice = security::SecurityContext::new("alice".to_string());
+    let response = schema.execute(async_graphql::Request::new(query).data(alice)).await;
+    assert_eq!(titles(response), vec!["North Mill", "South Mill"]);
+
+    // Bob cannot read p2, so only p1 is hydrated
+    let bob = security::SecurityContext::new("bob".to_string());
+    let response = schema.execute(async_graphql::Request::new(query).data(bob)).await;
+    assert_eq!(titles(response), vec!["North Mill"]);
+
+    // callFunction returns the traversal's references as-is
+    let response = schema
+        .execute(r#"{ callFunction(functionId: "plants_at_site", parameters: { site: "\"s1\"" }) { value } }"#)
+        .await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    assert_eq!(response.data.into_json().unwrap()["callFunction"]["value"].as_array().unwrap().len(), 2);
+
+    let response = schema
+        .execute(r#"{ callFunction(functionId: "plant_capacity", parameters: { site: "\"s1\"" }) { value } }"#)
+        .await;
+    assert!(
+        response.errors[0].message.contains("declares return type double but returned array<object reference>"),
+        "{:?}",
+        response.errors
+    );
+    let response = schema
+        .execute(r#"{ callFunctionObjects(functionId: "plant_capacity", parameters: { site: "\"s1\"" }) { objectId } }"#)
+        .await;
+    assert!(response.errors[0].message.contains("returns double, not objects"));
+}
+
+#[tokio::test]
+async fn test_call_function_reads_objects_and_links_from_stores() {
+    use indexing::store::GraphStore;
+
+    let yaml = r#"
+ontology:
+  objectTypes:
+    - id: "employee"
+      displayName: "Employee"
+      primaryKey: "id"
+      properties:
+        - id: "id"
+          type: "string"
+        - id: "salary"
+          type: "integer"
+    - id: "company"
+      displayName: "Company"
+      primaryKey: "id"
+      properties:
+        - id: "id"
+          type: "string"
+        - id: "name"
+          type: "string"
+  linkTypes:
+    - id: "worksAt"
+      displayName: "Works At"
+      source: "employee"
+      target: "company"
+      cardinality: "MANY_TO_ONE"
+  functionTypes:
+    - id: "company_payroll"
+      displayName: "Company Payroll"
+      parameters:
+        - id: "company"
+          type: "object_reference"
+          referenceTarget: "company"
+          required: true
+      returnType:
+        type: "property"
+        property_type: "integer"
+      logic:
+        type: "aggregation"
+        linkType: "worksAt"
+        aggregation: "sum"
+        property: "salary"
+    - id: "company_employees"
+      displayName: "Company Employees"
+      parameters:
+        - id: "company"
+          type: "object_reference"
+          referenceTarget: "company"
+          required: true
+      returnType:
+        type: "array"
+        element_type:
+          type: "object_type"
+          object_type: "employee"
+      logic:
+        type: "link_traversal"
+        linkType: "worksAt"
+        targetType: "employee"
+    - id: "company_name"
+      displayName: "Company Name"
+      parameters:
+        - id: "company"
+          type: "object_reference"
+          referenceTarget: "company"
+          required: true
+      returnType:
+        type: "property"
+        property_type: "string"
+      logic:
+        type: "property_access"
+        property: "name"
+"#;
+    let search_store: Arc<dyn SearchStore> = Arc::new(indexing::InMemorySearchStore::new());
+    let graph_store: Arc<dyn GraphStore> = Arc::new(indexing::InMemoryGraphStore::new());
+    for (id, name) in [("acme", "Acme"), ("globex", "Globex")] {
+        let mut properties = ontology_engine::PropertyMap::new();
+        properties.insert("id".to_string(), PropertyValue::String(id.to_string()));
+        properties.insert("name".to_string(), PropertyValue::String(name.to_string()));
+        search_store.index_object("company", id, &properties, None).await.unwrap();
+    }
+    for (id, salary, company) in [("e1", 100_000, "acme"), ("e2", 85_000, "acme"), ("e3", 120_000, "globex")] {
+        let mut properties = ontology_engine::PropertyMap::new();
+        properties.insert("id".to_string(), PropertyValue::String(id.to_string()));
+        properties.insert("salary".to_string(), PropertyValue::Integer(salary));
+        search_store.index_object("employee", id, &properties, None).await.unwrap();
+        graph_store.create_link("worksAt", id, company, &ontology_engine::PropertyMap::new()).await.unwrap();
+    }
+    let schema = Schema::build(QueryRoot::default(), AdminMutations::default(), EmptySubscription)
+        .data(OntologyHandle::new(Ontology::from_yaml(yaml).unwrap()))
+        .data(search_store)
+        .data(graph_store)
+        .finish();
+    let call = |function: &str, company: &str| {
+        format!(
+            r#"{{ callFunction(functionId: "{}", parameters: {{ company: "\"{}\"" }}) {{ value }} }}"#,
+            function, company
+        )
+    };
+    let value = |response: async_graphql::Response| {
+        assert!(response.errors.is_empty(), "{:?}", response.errors);
+        response.data.into_json().unwrap()["callFunction"]["value"].clone()
+    };
+
+    // Employees link to their company, so the payroll follows worksAt backwards
+    assert_eq!(value(schema.execute(call("company_payroll", "acme")).await), serde_json::json!(185000));
+    assert_eq!(value(schema.execute(call("company_payroll", "company:globex")).await), serde_json::json!(120000));
+
+    let mut employees: Vec<Value> = value(schema.execute(call("company_employees", "acme")).await)
+        .as_array()
+        .unwrap()
+        .clone();
+    employees.sort_by_key(|id| id.to_string());
+    assert_eq!(employees, vec![serde_json::json!("e1"), serde_json::json!("e2")]);
+
+    assert_eq!(value(schema.execute(call("company_name", "globex")).await), serde_json::json!("Globex"));
+    let response = schema.execute(call("company_name", "initech")).await;
+    assert!(
+        response.errors[0].message.contains("Property 'name' not found on object 'initech'"),
+        "{:?}",
+        response.errors
+    );
+}
+
+#[tokio::test]
+async fn test_upsert_unknown_properties_in_strict_and_lenient_types() {
+    let yaml = r#"
+ontology:
+  objectTypes:
+    - id: "city"
+      displayName: "City"
+      primaryKey: "id"
+      strictProperties: true
+      properties:
+        - id: "id"
+          type: "string"
+        - id: "population"
+          type: "integer"
+    - id: "town"
+      displayName: "Town"
+      primaryKey: "id"
+      properties:
+        - id: "id"
+          type: "string"
+        - id: "population"
+          type: "integer"
+  linkTypes: []
+"#;
+    let ontology = OntologyHandle::new(Ontology::from_yaml(yaml).unwrap());
+    let drift = indexing::PropertyDrift::new();
+    let inner: Arc<dyn SearchStore> = Arc::new(indexing::InMemorySearchStore::new());
+    let search_store: Arc<dyn SearchStore> =
+        Arc::new(indexing::ValidatingSearchStore::new(inner, ontology.clone()).with_drift(drift.clone()));
+    let schema = Schema::build(QueryRoot::default(), AdminMutations::default(), EmptySubscription)
+        .data(editor())
+        .data(ontology)
+        .data(search_store.clone())
+        .data(drift)
+        .finish();
+    let upsert = |object_type: &str, object_id: &str| {
+        let request = async_graphql::Request::new(format!(
+            r#"mutation($properties: JSON!) {{ upsertObject(objectType: "{}", objectId: "{}", properties: $properties) {{ success }} }}"#,
+            object_type, object_id
+        ))
+        .variables(async_graphql::Variables::from_json(serde_json::json!({
+            "properties": { "id": object_id, "popluation": 1200, "mayor": "Kim" },
+        })));
+        let schema = schema.clone();
+        async move { schema.execute(request).await }
+    };
+
+    // Strict types reject the write and name every unknown key
+    let response = upsert("city", "c1").await;
+    assert!(
+        response.errors[0].message.contains("Unknown properties on strict object type 'city': mayor, popluation"),
+        "{:?}",
+        response.errors
+    );
+    assert!(search_store.get_object("city", "c1").await.unwrap().is_none());
+
+    // Other types index them and count them for the usage report
+    for object_id in ["t1", "t2"] {
+        let response = upsert("town", object_id).await;
+        assert!(response.errors.is_empty(), "{:?}", response.errors);
+    }
+    let stored = search_store.get_object("town", "t1").await.unwrap().unwrap();
+    assert_eq!(stored.properties.get("popluation"), Some(&PropertyValue::Integer(1200)));
+
+    let response = schema
+        .execute(r#"{ usageMetrics(objectType: "town") { unknownProperties { property count } } }"#)
+        .await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    assert_eq!(
+        response.data.into_json().unwrap()["usageMetrics"]["unknownProperties"],
+        serde_json::json!([{ "property": "mayor", "count": 2 }, { "property": "popluation", "count": 2 }])
+    );
+
+    let response = schema
+        .execute(r#"mutation { upsertObject(objectType: "town", objectId: "t1", properties: { _acl: [] }) { success } }"#)
+        .await;
+    assert!(response.errors[0].message.contains("Unknown property '_acl'"));
+}
+
+#[tokio::test]
+async fn test_schema_mutations_evolve_the_live_ontology() {
+    use security::SecurityContext;
+
+    let yaml = r#"
+ontology:
+  objectTypes:
+    - id: "city"
+      displayName: "City"
+      primaryKey: "id"
+      strictProperties: true
+      properties:
+        - id: "id"
+          type: "string"
+        - id: "name"
+          type: "string"
+  linkTypes: []
+"#;
+    let handle = OntologyHandle::new(Ontology::from_yaml(yaml).unwrap());
+    let inner: Arc<dyn SearchStore> = Arc::new(indexing::InMemorySearchStore::new());
+    let search_store: Arc<dyn SearchStore> = Arc::new(indexing::ValidatingSearchStore::new(inner, handle.clone()));
+    let schema = Schema::build(QueryRoot::default(), AdminMutations::default(), EmptySubscription)
+        .data(handle.clone())
+        .data(search_store.clone())
+        .data(ObjectHydrator::new())
+        .finish();
+    let as_user = |query: &str, roles: &[&str]| {
+        let mut context = SecurityContext::new("ana".to_string());
+        for role in roles {
+            context = context.with_role(role.to_string());
+        }
+        async_graphql::Request::new(query).data(context)
+    };
+    let add_mayor = |property: Value| {
+        format!(
+            r#"mutation {{ addProperty(objectType: "city", property: {}) }}"#,
+            serde_json::to_string(&property.to_string()).unwrap()
+        )
+    };
+    let upsert = r#"mutation { upsertObject(objectType: "city", objectId: "c1", properties: { id: "c1", name: "Lyon", mayor: "Kim" }) { success } }"#;
+
+    // Only admins may change the ontology
+    let mayor = serde_json::json!({ "id": "mayor", "type": "string", "required": true });
+    let response = schema.execute(add_mayor(mayor.clone())).await;
+    assert!(response.errors[0].message.contains("requires a security context"));
+    let response = schema.execute(as_user(&add_mayor(mayor.clone()), &["analyst"])).await;
+    assert!(response.errors[0].message.contains("the 'admin' role is required"), "{:?}", response.errors);
+
+    // A rejected change leaves the runtime untouched
+    let response = schema.execute(as_user(&add_mayor(mayor), &["admin"])).await;
+    assert!(response.errors[0].message.contains("needs a default"), "{:?}", response.errors);
+    let response = schema
+        .execute(as_user(
+            r#"mutation { addObjectType(definition: "{\"id\": \"city\", \"displayName\": \"City\", \"primaryKey\": \"name\", \"properties\": []}") }"#,
+            &["admin"],
+        ))
+        .await;
+    assert!(response.errors[0].message.contains("primary key of object type 'city' cannot change"), "{:?}", response.errors);
+    assert_eq!(handle.version(), 1);
+    let city = handle.load().get_object_type("city").unwrap().clone();
+    assert!(city.get_property("mayor").is_none());
+    assert!(city.schema_evolution.is_none());
+    let response = schema.execute(as_user(upsert, &["editor"])).await;
+    assert!(response.errors[0].message.contains("Unknown properties on strict object type 'city': mayor"));
+
+    // An accepted one is seen by validation and search straight away
+    let mayor = serde_json::json!({ "id": "mayor", "type": "string", "required": true, "default": "vacant" });
+    let response = schema.execute(as_user(&add_mayor(mayor), &["admin"])).await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    let response = schema.execute(as_user(upsert, &["editor"])).await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    let response = schema
+        .execute(r#"{ searchObjects(objectType: "city", filters: [{ property: "mayor", operator: "eq", value: "\"Kim\"" }]) { objectId } }"#)
+        .await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    assert_eq!(response.data.into_json().unwrap()["searchObjects"][0]["objectId"], "c1");
+
+    let response = schema
+        .execute(as_user(
+            r#"mutation { deprecateProperty(objectType: "city", propertyId: "name", replacement: "mayor") }"#,
+            &["admin"],
+        ))
+        .await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    let city = handle.load().get_object_type("city").unwrap().clone();
+    assert_eq!(city.get_property("name").unwrap().deprecated.as_ref().unwrap().replacement.as_deref(), Some("mayor"));
+    assert_eq!(city.schema_evolution.as_ref().unwrap().changes.len(), 2);
+
+    let response = schema
+        .execute(as_user(
+            r#"mutation { addObjectType(definition: "{\"id\": \"district\", \"displayName\": \"District\", \"primaryKey\": \"id\", \"properties\": [{\"id\": \"id\", \"type\": \"string\"}]}") }"#,
+            &["admin"],
+        ))
+        .await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    assert!(handle.load().get_object_type("district").unwrap().schema_evolution.is_some());
+    assert_eq!(handle.version(), 4);
+}
+
+#[tokio::test]
+async fn test_temporal_query_reconstructs_historical_links() {
+    use chrono::{TimeZone, Utc};
+    use versioning::event_log::EventType;
+
+    let yaml = r#"
+ontology:
+  objectTypes:
+    - id: "plant"
+      displayName: "Plant"
+      primaryKey: "id"
+      titleKey: "name"
+      properties:
+        - id: "id"
+          type: "string"
+        - id: "name"
+          type: "string"
+    - id: "supplier"
+      displayName: "Supplier"
+      primaryKey: "id"
+      titleKey: "name"
+      properties:
+        - id: "id"
+          type: "string"
+        - id: "name"
+          type: "string"
+  linkTypes:
+    - id: "supplies"
+      displayName: "Supplies"
+      source: "supplier"
+      target: "plant"
+      cardinality: "MANY_TO_MANY"
+"#;
+    let ontology = Ontology::from_yaml(yaml).expect("ontology");
+
+    let at = |year: i32| Utc.with_ymd_and_hms(year, 1, 1, 0, 0, 0).unwrap();
+    let named = |id: &str, name: &str| {
+        let mut properties = ontology_engine::PropertyMap::new();
+        properties.insert("id".to_string(), PropertyValue::String(id.to_string()));
+        properties.insert("name".to_string(), PropertyValue::String(name.to_string()));
+        properties
+    };
+    let mut event_log = EventLog::new();
+    event_log.record_at(
+        EventType::ObjectCreated {
+            object_type: "plant".to_string(),
+            object_id: "p1".to_string(),
+            properties: named("p1", "Riverside"),
+        },
+        at(2015),
+        None,
+    );
+    event_log.record_at(
+        EventType::ObjectCreated {
+            object_type: "supplier".to_string(),
+            object_id: "s1".to_string(),
+            properties: named("s1", "Acme"),
+        },
+        at(2015),
+        None,
+    );
+    event_log.record_at(
+        EventType::LinkCreated {
+            link_type: "supplies".to_string(),
+            link_id: "l1".to_string(),
+            source_id: "s1".to_string(),
+            target_id: "p1".to_string(),
+            properties: ontology_engine::PropertyMap::new(),
+        },
+        at(2016),
+        None,
+    );
+    event_log.record_at(
+        EventType::ObjectUpdated {
+            object_type: "supplier".to_string(),
+            object_id: "s1".to_string(),
+            changed_properties: named("s1", "Acme Holdings"),
+        },
+        at(2018),
+        None,
+    );
+    event_log.record_at(
+        EventType::LinkDeleted {
+            link_type: "supplies".to_string(),
+            link_id: "l1".to_string(),
+            source_id: "s1".to_string(),
+            target_id: "p1".to_string(),
+        },
+        at(2019),
+        None,
+    );
+
+    let schema = Schema::build(QueryRoot::default(), AdminMutations::default(), EmptySubscription)
+        .data(OntologyHandle::new(ontology))
+        .data(Arc::new(TimeQuery::new(event_log)))
+        .data(ObjectHydrator::new())
+        .finish();
+
+    let links_at = |as_of: &'static str| {
+        let schema = &schema;
+        async move {
+            let query = format!(
+                r#"{{ temporalQuery(objectType: "plant", asOfDate: "{}", includeLinks: ["supplies"]) {{ objectId properties }} }}"#,
+                as_of
+            );
+            let response = schema.execute(query).await;
+            assert!(response.errors.is_empty(), "{:?}", response.errors);
+            let data = response.data.into_json().unwrap();
+            let objects = data["temporalQuery"].as_array().unwrap().clone();
+            assert_eq!(objects.len(), 1);
+            assert_eq!(objects[0]["objectId"], "p1");
+            objects[0]["properties"]["_links"].as_array().unwrap().clone()
+        }
+    };
+
+    // Before the link was created
+    assert!(links_at("2015-06-01T00:00:00Z").await.is_empty());
+
+    // While it existed, with the supplier's name as it was then
+    let links = links_at("2017-06-01T00:00:00Z").await;
+    assert_eq!(links.len(), 1);
+    assert_eq!(links[0]["linkType"], "supplies");
+    assert_eq!(links[0]["linkId"], "l1");
+    assert_eq!(links[0]["direction"], "incoming");
+    assert_eq!(links[0]["objectType"], "supplier");
+    assert_eq!(links[0]["objectId"], "s1");
+    assert_eq!(links[0]["title"], "Acme");
+
+    // After it was deleted
+    assert!(links_at("2020-06-01T00:00:00Z").await.is_empty());
+
+    let response = schema
+        .execute(r#"{ temporalQuery(objectType: "plant", asOfDate: "2017-06-01T00:00:00Z", includeLinks: ["owns"]) { objectId } }"#)
+        .await;
+    assert!(response.errors[0].message.contains("Link type 'owns' not found"));
+}
+
+#[tokio::test]
+async fn test_temporal_query_as_known_at_ignores_later_corrections() {
+    use chrono::{TimeZone, Utc};
+    use versioning::event_log::{EventType, ObjectEvent};
+
+    let yaml = r#"
+ontology:
+  objectTypes:
+    - id: "county"
+      displayName: "County"
+      primaryKey: "id"
+      titleKey: "id"
+      properties:
+        - id: "id"
+          type: "string"
+        - id: "year"
+          type: "integer"
+        - id: "population"
+          type: "integer"
+  linkTypes: []
+"#;
+    let ontology = Ontology::from_yaml(yaml).expect("ontology");
+
+    let census = |population: i64| {
+        let mut properties = PropertyMap::new();
+        properties.insert("id".to_string(), PropertyValue::String("06001".to_string()));
+        properties.insert("year".to_string(), PropertyValue::Integer(2020));
+        properties.insert("population".to_string(), PropertyValue::Integer(population));
+        properties
+    };
+    let census_day = Utc.with_ymd_and_hms(2020, 4, 1, 0, 0, 0).unwrap();
+    let mut original = ObjectEvent::at(
+        EventType::ObjectCreated {
+            object_type: "county".to_string(),
+            object_id: "06001".to_string(),
+            properties: census(1_682_353),
+        },
+        census_day,
+        None,
+    );
+    original.timestamp = Utc.with_ymd_and_hms(2021, 8, 12, 0, 0, 0).unwrap();
+    let mut correction = ObjectEvent::at(
+        EventType::ObjectUpdated {
+            object_type: "county".to_string(),
+            object_id: "06001".to_string(),
+            changed_properties: census(1_682_400),
+        },
+        census_day,
+        None,
+    );
+    correction.timestamp = Utc.with_ymd_and_hms(2023, 3, 1, 0, 0, 0).unwrap();
+    let mut event_log = EventLog::new();
+    event_log.record(original);
+    event_log.record(correction);
+
+    let schema = Schema::build(QueryRoot::default(), AdminMutations::default(), EmptySubscription)
+        .data(OntologyHandle::new(ontology))
+        .data(Arc::new(TimeQuery::new(event_log)))
+        .data(ObjectHydrator::new())
+        .finish();
+
+    let population_known_at = |as_known_at: &'static str| {
+        let schema = &schema;
+        async move {
+            let query = format!(
+                r#"{{ temporalQuery(objectType: "county", year: 2020, asOfDate: "2022-01-01T00:00:00Z", asKnownAt: "{}") {{ properties }} }}"#,
+                as_known_at
+            );
+            let response = schema.execute(query).await;
+            assert!(response.errors.is_empty(), "{:?}", response.errors);
+            let data = response.data.into_json().unwrap();
+            let objects = data["temporalQuery"].as_array().unwrap().clone();
+            assert_eq!(objects.len(), 1);
+            objects[0]["properties"]["population"].clone()
+        }
+    };
+
+    // Before the correction was recorded, the published figure stands
+    assert_eq!(population_known_at("2022-01-01T00:00:00Z").await, 1_682_353);
+    assert_eq!(population_known_at("2024-01-01T00:00:00Z").await, 1_682_400);
+
+    let response = schema
+        .execute(r#"{ temporalQuery(objectType: "county", asKnownAt: "last year") { objectId } }"#)
+        .await;
+    assert!(response.errors[0].message.contains("Invalid date format"));
+}
+
+#[tokio::test]
+async fn test_pending_conflicts_are_listed_and_resolved() {
+    use writeback::{ConflictResolutionStrategy, PendingConflicts, UserEdit};
+
+    let yaml = r#"
+ontology:
+  objectTypes:
+    - id: "city"
+      displayName: "City"
+      primaryKey: "id"
+      titleKey: "name"
+      properties:
+        - id: "id"
+          type: "string"
+        - id: "name"
+          type: "string"
+        - id: "mayor"
+          type: "string"
+  linkTypes: []
+"#;
+    let ontology = Ontology::from_yaml(yaml).expect("ontology");
+    let object_type = ontology.get_object_type("city").unwrap().clone();
+    let search_store: Arc<dyn SearchStore> = Arc::new(indexing::InMemorySearchStore::new());
+    let string = |value: &str| PropertyValue::String(value.to_string());
+
+    // The source renamed the mayor after two users edited what they saw
+    let mut source = PropertyMap::new();
+    source.insert("id".to_string(), string("c1"));
+    source.insert("name".to_string(), string("Springfield"));
+    source.insert("mayor".to_string(), string("Quimby"));
+    search_store.index_object("city", "c1", &source, None).await.unwrap();
+    let edit = |edit_id: &str, property_name: &str, value: &str, base_value: &str| UserEdit {
+        edit_id: edit_id.to_string(),
+        object_type: "city".to_string(),
+        object_id: "c1".to_string(),
+        property_name: property_name.to_string(),
+        property_value: string(value),
+        user_id: "user1".to_string(),
+        timestamp: chrono::Utc::now(),
+        deleted: false,
+        base_value: Some(string(base_value)),
+        comment: None,
+        status: writeback::EditStatus::Pending,
+        resolved_at: None,
+    };
+    let edits = [
+        edit("edit1", "mayor", "Simpson", "Wiggum"),
+        edit("edit2", "name", "Shelbyville", "Springfield"),
+    ];
+    let applied = writeback::apply_edits(search_store.as_ref(), &object_type, "c1", &edits, ConflictResolutionStrategy::Manual)
+        .await
+        .unwrap();
+    let pending = Arc::new(PendingConflicts::new());
+    pending.record(&applied.merge);
+
+    let schema = Schema::build(QueryRoot::default(), AdminMutations::default(), EmptySubscription)
+        .data(OntologyHandle::new(ontology))
+        .data(search_store.clone())
+        .data(ObjectHydrator::new())
+        .data(pending.clone())
+        .finish();
+
+    let response = schema
+        .execute(r#"{ getPendingConflicts(objectType: "city") { editId propertyName kind resolution baseValue sourceValue editedValue } }"#)
+        .await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    let data = response.data.into_json().unwrap();
+    assert_eq!(
+        data["getPendingConflicts"],
+        serde_json::json!([{
+            "editId": "edit1",
+            "propertyName": "mayor",
+            "kind": "source_changed",
+            "resolution": "pending",
+            "baseValue": "Wiggum",
+            "sourceValue": "Quimby",
+            "editedValue": "Simpson",
+        }])
+    );
+    // The clean edit went through; the conflicting one is held back
+    let stored = search_store.get_object("city", "c1").await.unwrap().unwrap();
+    assert_eq!(stored.properties.get("name"), Some(&string("Shelbyville")));
+    assert_eq!(stored.properties.get("mayor"), Some(&string("Quimby")));
+
+    // Settling conflicts takes a reviewer
+    let resolve = |resolution: &str, reviewer: bool| {
+        let mut context = security::SecurityContext::new("user2".to_string());
+        if reviewer {
+            context = context.with_role("edit_reviewer".to_string());
+        }
+        let query = format!(r#"mutation {{ resolveConflict(editId: "edit1", resolution: "{}") {{ resolution }} }}"#, resolution);
+        schema.execute(async_graphql::Request::new(query).data(context))
+    };
+    let response = resolve("edit", false).await;
+    assert!(response.errors[0].message.contains("may not apply edits"), "{:?}", response.errors);
+    let response = resolve("edit", true).await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    assert_eq!(response.data.into_json().unwrap()["resolveConflict"]["resolution"], "edit_applied");
+    let stored = search_store.get_object("city", "c1").await.unwrap().unwrap();
+    assert_eq!(stored.properties.get("mayor"), Some(&string("Simpson")));
+    assert!(pending.is_empty());
+
+    let response = resolve("source", true).await;
+    assert!(response.errors[0].message.contains("No pending conflict for edit 'edit1'"));
+}
+
+#[tokio::test]
+async fn test_edit_lifecycle_from_submission_to_application() {
+    use versioning::event_log::EventType;
+    use writeback::{EditQueue, InMemoryEditQueue};
+
+    let yaml = r#"
+ontology:
+  objectTypes:
+    - id: "city"
+      displayName: "City"
+      primaryKey: "id"
+      titleKey: "name"
+      properties:
+        - id: "id"
+          type: "string"
+        - id: "name"
+          type: "string"
+        - id: "population"
+          type: "integer"
+  linkTypes: []
+"#;
+    let ontology = Ontology::from_yaml(yaml).expect("ontology");
+    let search_store: Arc<dyn SearchStore> = Arc::new(indexing::InMemorySearchStore::new());
+    let mut source = PropertyMap::new();
+    source.insert("id".to_string(), PropertyValue::String("c1".to_string()));
+    source.insert("name".to_string(), PropertyValue::String("Springfield".to_string()));
+    source.insert("population".to_string(), PropertyValue::Integer(1000));
+    search_store.index_object("city", "c1", &source, None).await.unwrap();
+    let edit_queue: Arc<dyn EditQueue> = Arc::new(InMemoryEditQueue::new());
+    let event_log: graphql_api::ObjectEventLog = Arc::new(tokio::sync::RwLock::new(EventLog::new()));
+
+    let schema = Schema::build(QueryRoot::default(), AdminMutations::default(), EmptySubscription)
+        .data(OntologyHandle::new(ontology))
+        .data(search_store.clone())
+        .data(ObjectHydrator::new())
+        .data(edit_queue)
+        .data(event_log.clone())
+        .finish();
+    let run = |query: String, user: Option<&str>| {
+        let schema = &schema;
+        let mut request = async_graphql::Request::new(query);
+        if let Some(user) = user {
+            request = request.data(security::SecurityContext::new(user.to_string()));
+        }
+        async move { schema.execute(request).await }
+    };
+    let submit = |property: &str, value: &str| {
+        format!(
+            r#"mutation {{ submitEdit(objectType: "city", objectId: "c1", property: "{}", value: {}, comment: "census update") }}"#,
+            property,
+            serde_json::to_string(value).unwrap()
+        )
+    };
+    let ok = |response: async_graphql::Response| {
+        assert!(response.errors.is_empty(), "{:?}", response.errors);
+        response.data.into_json().unwrap()
+    };
+
+    // Invalid values and anonymous edits are refused before anything is queued
+    let response = run(submit("population", r#""lots""#), Some("alice")).await;
+    assert!(!response.errors.is_empty());
+    let response = run(submit("population", "2000"), None).await;
+    assert!(response.errors[0].message.contains("requires an authenticated user"));
+
+    let population_edit = ok(run(submit("population", "2000"), Some("alice")).await)["submitEdit"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let name_edit = ok(run(submit("name", r#""Shelbyville""#), Some("alice")).await)["submitEdit"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let pending = ok(run(
+        r#"{ getPendingEdits(objectType: "city", objectId: "c1") { editId property value baseValue userId comment status } }"#.to_string(),
+        None,
+    )
+    .await);
+    assert_eq!(
+        pending["getPendingEdits"][0],
+        serde_json::json!({
+            "editId": population_edit,
+            "property": "population",
+            "value": 2000,
+            "baseValue": 1000,
+            "userId": "alice",
+            "comment": "census update",
+            "status": "pending",
+        })
+    );
+    assert_eq!(pending["getPendingEdits"].as_array().unwrap().len(), 2);
+
+    // Only the submitter can withdraw
+    let withdraw = |edit_id: &str| format!(r#"mutation {{ withdrawEdit(editId: "{}") {{ status resolvedAt }} }}"#, edit_id);
+    let response = run(withdraw(&name_edit), Some("bob")).await;
+    assert!(response.errors[0].message.contains("Only the user who submitted"));
+    let withdrawn = ok(run(withdraw(&name_edit), Some("alice")).await);
+    assert_eq!(withdrawn["withdrawEdit"]["status"], "withdrawn");
+    assert!(withdrawn["withdrawEdit"]["resolvedAt"].is_string());
+
+    // Applying takes a reviewer
+    let apply = r#"mutation { applyEdits(objectType: "city", objectId: "c1") { revision applied rejected conflicts { editId } } }"#;
+    let response = run(apply.to_string(), Some("alice")).await;
+    assert!(response.errors[0].message.contains("may not apply edits"), "{:?}", response.errors);
+    let response = run(apply.to_string(), None).await;
+    assert!(response.errors[0].message.contains("requires a security context"), "{:?}", response.errors);
+    let reviewer = security::SecurityContext::new("admin".to_string()).with_role("edit_reviewer".to_string());
+    let applied = ok(schema.execute(async_graphql::Request::new(apply).data(reviewer)).await);
+    assert_eq!(
+        applied["applyEdits"],
+        serde_json::json!({ "revision": 2, "applied": [population_edit], "rejected": [], "conflicts": [] })
+    );
+    let stored = search_store.get_object("city", "c1").await.unwrap().unwrap();
+    assert_eq!(stored.properties.get("population"), Some(&PropertyValue::Integer(2000)));
+    assert_eq!(stored.properties.get("name"), Some(&PropertyValue::String("Springfield".to_string())));
+    let events = event_log.read().await.get_events_for_object("city", "c1").into_iter().cloned().collect::<Vec<_>>();
+    assert_eq!(events.len(), 1);
+    assert!(matches!(&events[0].event_type, EventType::ObjectUpdated { changed_properties, .. }
+        if changed_properties.get("population") == Some(&PropertyValue::Integer(2000))));
+    assert_eq!(events[0].user_id.as_deref(), Some("admin"));
+
+    // An applied edit can no longer be withdrawn
+    let response = run(withdraw(&population_edit), Some("alice")).await;
+    assert!(response.errors[0].message.contains("is already applied and cannot become withdrawn"), "{:?}", response.errors);
+
+    let history = ok(run(r#"{ getEditHistory(objectId: "c1") { property status } }"#.to_string(), None).await);
+    assert_eq!(
+        history["getEditHistory"],
+        serde_json::json!([
+            { "property": "population", "status": "applied" },
+            { "property": "name", "status": "withdrawn" },
+        ])
+    );
+}
+
+#[tokio::test]
+async fn test_search_sorts_by_each_property_type_with_missing_values_last() {
+    let yaml = r#"
+ontology:
+  objectTypes:
+    - id: "reading"
+      displayName: "Reading"
+      primaryKey: "id"
+      properties:
+        - id: "id"
+          type: "string"
+        - id: "site"
+          type: "string"
+        - id: "count"
+          type: "integer"
+        - id: "level"
+          type: "double"
+        - id: "taken_on"
+          type: "date"
+  linkTypes: []
+"#;
+    let readings = vec![
+        serde_json::json!({ "id": "r1", "site": "b", "count": 3, "level": 2.5, "taken_on": "2024-03-01" }),
+        serde_json::json!({ "id": "r2", "site": "a", "count": 10, "level": 0.5, "taken_on": "2023-12-31" }),
+        serde_json::json!({ "id": "r3", "site": "c", "count": -1, "taken_on": "2024-01-15" }),
+        serde_json::json!({ "id": "r4", "count": 3, "level": 9.0 }),
+    ];
+
+    let search_store = indexing::InMemorySearchStore::new();
+    for reading in &readings {
+        let mut properties = ontology_engine::PropertyMap::new();
+        for (key, value) in reading.as_object().unwrap() {
+            let value = match (key.as_str(), value) {
+                ("taken_on", Value::String(s)) => PropertyValue::Date(s.clone()),
+                (_, Value::String(s)) => PropertyValue::String(s.clone()),
+                (_, value) if value.is_i64() => PropertyValue::Integer(value.as_i64().unwrap()),
+                (_, value) => PropertyValue::Double(value.as_f64().unwrap()),
+            };
+            properties.insert(key.clone(), value);
+        }
+        search_store
+            .index_object("reading", reading["id"].as_str().unwrap(), &properties, None)
+            .await
+            .unwrap();
+    }
+    let search_store: Arc<dyn SearchStore> = Arc::new(search_store);
+    let indexed = Schema::build(QueryRoot::default(), AdminMutations::default(), EmptySubscription)
+        .data(OntologyHandle::new(Ontology::from_yaml(yaml).unwrap()))
+        .data(search_store)
+        .data(ObjectHydrator::new())
+        .finish();
+
+    let cases = [
+        (r#"{ property: "site" }"#, ["r2", "r1", "r3", "r4"]),
+        (r#"{ property: "site", ascending: false }"#, ["r3", "r1", "r2", "r4"]),
+        (r#"{ property: "count" }"#, ["r3", "r1", "r4", "r2"]),
+        (r#"{ property: "count", ascending: false }"#, ["r2", "r1", "r4", "r3"]),
+        (r#"{ property: "level" }"#, ["r2", "r1", "r4", "r3"]),
+        (r#"{ property: "level", ascending: false }"#, ["r4", "r1", "r2", "r3"]),
+        (r#"{ property: "taken_on" }"#, ["r2", "r3", "r1", "r4"]),
+        (r#"{ property: "taken_on", ascending: false }"#, ["r1", "r3", "r2", "r4"]),
+        // Later keys break ties in earlier ones
+        (r#"[{ property: "count" }, { property: "level", ascending: false }]"#, ["r3", "r4", "r1", "r2"]),
+    ];
+    for (sort, expected) in &cases {
+        let query = format!(r#"{{ searchObjects(objectType: "reading", sort: {}) {{ objectId }} }}"#, sort);
+        let response = indexed.execute(query).await;
+        assert!(response.errors.is_empty(), "{:?}", response.errors);
+        let data = response.data.into_json().unwrap();
+        let ids: Vec<&str> = data["searchObjects"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|o| o["objectId"].as_str().unwrap())
+            .collect();
+        assert_eq!(&ids, expected, "sorted by {}", sort);
+    }
+}
+
+#[tokio::test]
+async fn test_change_trigger_fires_webhook_on_upsert() {
+    use ontology_engine::{PropertyMap, SideEffectType};
+
+    let yaml = r#"
+ontology:
+  objectTypes:
+    - id: "ticket"
+      displayName: "Ticket"
+      primaryKey: "id"
+      properties:
+        - id: "id"
+          type: "string"
+        - id: "status"
+          type: "string"
+        - id: "assignee"
+          type: "string"
+  linkTypes: []
+"#;
+    let ontology = OntologyHandle::new(Ontology::from_yaml(yaml).unwrap());
+    let registry = indexing::ChangeTriggerRegistry::new();
+    let sent = Arc::new(std::sync::Mutex::new(Vec::<(SideEffectType, PropertyMap)>::new()));
+    let recorder = sent.clone();
+    let search_store: Arc<dyn SearchStore> = Arc::new(
+        indexing::TriggeringSearchStore::new(Arc::new(indexing::InMemorySearchStore::new()), registry.clone())
+            .with_handler(Arc::new(move |effect_type: &SideEffectType, config: &PropertyMap| {
+                recorder.lock().unwrap().push((effect_type.clone(), config.clone()));
+                Ok(())
+            })),
+    );
+    let schema = Schema::build(QueryRoot::default(), AdminMutations::default(), EmptySubscription)
+        .data(editor())
+        .data(ontology)
+        .data(search_store)
+        .data(registry)
+        .finish();
+
+    let admin = || security::SecurityContext::new("a".to_string()).with_role("admin".to_string());
+    let create_as = |definition: Value, context: security::SecurityContext| {
+        let request = async_graphql::Request::new(
+            r#"mutation($trigger: JSON!) { createChangeTrigger(trigger: $trigger) { id objectType properties } }"#,
+        )
+        .variables(async_graphql::Variables::from_json(serde_json::json!({ "trigger": definition })))
+        .data(context);
+        let schema = schema.clone();
+        async move { schema.execute(request).await }
+    };
+    let upsert = |status: &str, assignee: &str| {
+        let request = async_graphql::Request::new(
+            r#"mutation($properties: JSON!) { upsertObject(objectType: "ticket", objectId: "t1", properties: $properties) { success } }"#,
+        )
+        .variables(async_graphql::Variables::from_json(serde_json::json!({
+            "properties": { "id": "t1", "status": status, "assignee": assignee },
+        })));
+        let schema = schema.clone();
+        async move { schema.execute(request).await }
+    };
+
+    let trigger = serde_json::json!({
+        "id": "ticket-status",
+        "objectType": "ticket",
+        "properties": ["status"],
+        "sideEffects": [{ "type": "webhook", "config": { "properties": { "url": "https://example.com/tickets" } } }]
+    });
+    let response = create_as(trigger.clone(), security::SecurityContext::new("u".to_string())).await;
+    assert!(response.errors[0].message.contains("may not change the ontology"), "{:?}", response.errors);
+    let response = schema
+        .execute(async_graphql::Request::new(r#"mutation { deleteChangeTrigger(id: "ticket-status") }"#)
+            .data(security::SecurityContext::new("u".to_string())))
+        .await;
+    assert_eq!(serde_json::to_value(&response.errors[0]).unwrap()["extensions"]["code"], "UNAUTHORIZED");
+
+    let mut unknown = trigger.clone();
+    unknown["properties"] = serde_json::json!(["state"]);
+    let response = create_as(unknown, admin()).await;
+    assert!(response.errors[0].message.contains("Unknown property 'state' on object type 'ticket'"), "{:?}", response.errors);
+
+    let response = create_as(trigger, admin()).await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    let response = schema.execute("{ changeTriggers { id objectType properties } }").await;
+    assert_eq!(
+        response.data.into_json().unwrap()["changeTriggers"],
+        serde_json::json!([{ "id": "ticket-status", "objectType": "ticket", "properties": ["status"] }])
+    );
+
+    assert!(upsert("open", "kim").await.errors.is_empty());
+    assert!(upsert("open", "lee").await.errors.is_empty());
+    assert!(upsert("closed", "lee").await.errors.is_empty());
+    {
+        let sent = sent.lock().unwrap();
+        assert_eq!(sent.len(), 2, "reassigning the ticket must not fire");
+        let (effect_type, config) = &sent[1];
+        assert!(matches!(effect_type, SideEffectType::Webhook));
+        assert_eq!(config.get("url"), Some(&PropertyValue::String("https://example.com/tickets".to_string())));
+        let payload = serde_json::to_value(config.get("payload").unwrap()).unwrap();
+        assert_eq!(payload["operation"], "update");
+        assert_eq!(payload["object_id"], "t1");
+        assert_eq!(payload["changes"], serde_json::json!({ "status": { "old": "open", "new": "closed" } }));
+    }
+
+    let response = schema
+        .execute(async_graphql::Request::new(r#"mutation { deleteChangeTrigger(id: "ticket-status") }"#).data(admin()))
+        .await;
+    assert_eq!(response.data.into_json().unwrap()["deleteChangeTrigger"], true);
+    assert!(upsert("open", "lee").await.errors.is_empty());
+    assert_eq!(sent.lock().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn test_search_objects_paginated_pages_without_duplicates_or_gaps() {
+    let yaml = r#"
+ontology:
+  objectTypes:
+    - id: "meter"
+      displayName: "Meter"
+      primaryKey: "id"
+      properties:
+        - id: "id"
+          type: "string"
+        - id: "zone"
+          type: "integer"
+  linkTypes: []
+"#;
+    // Few distinct zones, so most of the order comes from the primary key tiebreak
+    let meters: Vec<Value> = (0..25)
+        .map(|i| serde_json::json!({ "id": format!("m{:02}", i), "zone": i % 3 }))
+        .collect();
+
+    let search_store = Arc::new(indexing::InMemorySearchStore::new());
+    for meter in &meters {
+        let mut properties = ontology_engine::PropertyMap::new();
+        properties.insert("id".to_string(), PropertyValue::String(meter["id"].as_str().unwrap().to_string()));
+        properties.insert("zone".to_string(), PropertyValue::Integer(meter["zone"].as_i64().unwrap()));
+        search_store.index_object("meter", meter["id"].as_str().unwrap(), &properties, None).await.unwrap();
+    }
+    let indexed = Schema::build(QueryRoot::default(), AdminMutations::default(), EmptySubscription)
+        .data(OntologyHandle::new(Ontology::from_yaml(yaml).unwrap()))
+        .data(search_store.clone() as Arc<dyn SearchStore>)
+        .data(ObjectHydrator::new())
+        .finish();
+
+    let page = |schema: Schema<QueryRoot, AdminMutations, EmptySubscription>, args: String| async move {
+        let query = format!(
+            r#"{{ searchObjectsPaginated(objectType: "meter", {}) {{
+                items {{ objectId }}
+                totalCount
+                pageInfo {{ hasNextPage hasPreviousPage startCursor endCursor }}
+            }} }}"#,
+            args
+        );
+        let response = schema.execute(query).await;
+        assert!(response.errors.is_empty(), "{:?}", response.errors);
+        response.data.into_json().unwrap()["searchObjectsPaginated"].clone()
+    };
+    let ids = |page: &Value| -> Vec<String> {
+        page["items"].as_array().unwrap().iter().map(|o| o["objectId"].as_str().unwrap().to_string()).collect()
+    };
+
+    let mut expected: Vec<String> = (0..25).map(|i| format!("m{:02}", i)).collect();
+    expected.sort_by_key(|id| (id[1..].parse::<usize>().unwrap() % 3, id.clone()));
+    let mut seen = Vec::new();
+    let mut after: Option<String> = None;
+    let mut sizes = Vec::new();
+    loop {
+        let args = match &after {
+            Some(cursor) => format!(r#"sort: [{{ property: "zone" }}], first: 10, after: "{}""#, cursor),
+            None => r#"sort: [{ property: "zone" }], first: 10"#.to_string(),
+        };
+        let result = page(indexed.clone(), args).await;
+        assert_eq!(result["totalCount"], 25);
+        assert_eq!(result["pageInfo"]["hasPreviousPage"], after.is_some());
+        sizes.push(ids(&result).len());
+        seen.extend(ids(&result));
+        if result["pageInfo"]["hasNextPage"] != true {
+            break;
+        }
+        after = Some(result["pageInfo"]["endCursor"].as_str().unwrap().to_string());
+    }
+    assert_eq!(sizes, vec![10, 10, 5]);
+    assert_eq!(seen, expected);
+
+    // Exactly one page
+    let all = page(indexed.clone(), "first: 25".to_string()).await;
+    assert_eq!(ids(&all).len(), 25);
+    assert_eq!(all["pageInfo"]["hasNextPage"], false);
+    assert_eq!(all["pageInfo"]["hasPreviousPage"], false);
+
+    // No matches
+    let none = page(
+        indexed.clone(),
+        r#"filters: [{ property: "zone", operator: "equals", value: "7" }], first: 10"#.to_string(),
+    )
+    .await;
+    assert_eq!(none["totalCount"], 0);
+    assert!(ids(&none).is_empty());
+    assert_eq!(none["pageInfo"]["hasNextPage"], false);
+    assert_eq!(none["pageInfo"]["endCursor"], Value::Null);
+
+    // A write sorting before the cursor does not shift the next page
+    let first = page(indexed.clone(), r#"sort: [{ property: "zone" }], first: 10"#.to_string()).await;
+    let cursor = first["pageInfo"]["endCursor"].as_str().unwrap().to_string();
+    let mut properties = ontology_engine::PropertyMap::new();
+    properties.insert("id".to_string(), PropertyValue::String("a00".to_string()));
+    properties.insert("zone".to_string(), PropertyValue::Integer(0));
+    search_store.index_object("meter", "a00", &properties, None).await.unwrap();
+    let second = page(indexed.clone(), format!(r#"sort: [{{ property: "zone" }}], first: 10, after: "{}""#, cursor)).await;
+    assert_eq!(ids(&second), expected[10..20].to_vec());
+    assert_eq!(second["totalCount"], 26);
+
+    // Cursors are checked against the sort they were issued for
+    let response = indexed
+        .execute(format!(r#"{{ searchObjectsPaginated(objectType: "meter", after: "{}") {{ totalCount }} }}"#, cursor))
+        .await;
+    assert!(response.errors[0].message.contains("Cursor does not match"), "{:?}", response.errors);
+    let response = indexed
+        .execute(r#"{ searchObjectsPaginated(objectType: "meter", after: "not a cursor") { totalCount } }"#)
+        .await;
+    assert!(response.errors[0].message.contains("Invalid cursor"), "{:?}", response.errors);
+}
+
+/// In-memory search store recording the Elasticsearch request body of each search
+struct ElasticsearchBodySearchStore {
+    inner: indexing::InMemorySearchStore,
+    elasticsearch: indexing::store::ElasticsearchStore,
+    bodies: std::sync::Mutex<Vec<Value>>,
+}
+
+#[async_trait::async_trait]
+impl SearchStore for ElasticsearchBodySearchStore {
+    async fn index_object(&self, object_type: &str, object_id: &str, properties: &ontology_engine::PropertyMap, expected_revision: Option<u64>) -> Result<u64, indexing::store::StoreError> {
+        self.inner.index_object(object_type, object_id, properties, expected_revision).await
+    }
+    async fn search(&self, object_type: &str, query: &indexing::store::SearchQuery) -> Result<Vec<indexing::store::IndexedObject>, indexing::store::StoreError> {
+        let explained = self.elasticsearch.explain_search(object_type, query).unwrap();
+        self.bodies.lock().unwrap().push(explained["body"].clone());
+        self.inner.search(object_type, query).await
+    }
+    async fn get_object(&self, object_type: &str, object_id: &str) -> Result<Option<indexing::store::IndexedObject>, indexing::store::StoreError> {
+        self.inner.get_object(object_type, object_id).await
+    }
+    async fn bulk_index(&self, objects: Vec<indexing::store::IndexedObject>) -> Result<(), indexing::store::StoreError> {
+        self.inner.bulk_index(objects).await
+    }
+    async fn delete_object(&self, object_type: &str, object_id: &str) -> Result<(), indexing::store::StoreError> {
+        self.inner.delete_object(object_type, object_id).await
+    }
+    async fn count_objects(&self, object_type: &str, filters: Option<&[indexing::store::Filter]>) -> Result<u64, indexing::store::StoreError> {
+        self.inner.count_objects(object_type, filters).await
+    }
+}
+
+#[tokio::test]
+async fn test_search_objects_paginated_sorts_strings_on_keyword_subfield() {
+    let yaml = r#"
+ontology:
+  objectTypes:
+    - id: "meter"
+      displayName: "Meter"
+      primaryKey: "id"
+      properties:
+        - id: "id"
+          type: "string"
+        - id: "site"
+          type: "string"
+  linkTypes: []
+"#;
+    let search_store = Arc::new(ElasticsearchBodySearchStore {
+        inner: indexing::InMemorySearchStore::new(),
+        elasticsearch: indexing::store::ElasticsearchStore::new("http://localhost:9200".to_string()).unwrap(),
+        bodies: std::sync::Mutex::new(Vec::new()),
+    });
+    let schema = Schema::build(QueryRoot::default(), AdminMutations::default(), EmptySubscription)
+        .data(OntologyHandle::new(Ontology::from_yaml(yaml).unwrap()))
+        .data(search_store.clone() as Arc<dyn SearchStore>)
+        .data(ObjectHydrator::new())
+        .finish();
+
+    let response = schema
+        .execute(r#"{ searchObjectsPaginated(objectType: "meter", sort: [{ property: "site", ascending: false }]) { totalCount } }"#)
+        .await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+
+    // Both the requested sort and the primary key tiebreak are dynamically mapped strings
+    let bodies = search_store.bodies.lock().unwrap();
+    assert_eq!(
+        bodies[0]["sort"],
+        serde_json::json!([
+            { "site.keyword": { "order": "desc", "missing": "_last" } },
+            { "id.keyword": { "order": "asc", "missing": "_last" } },
+        ])
+    );
+}
+
+#[tokio::test]
+async fn test_archive_query_and_unarchive_cycle() {
+    use chrono::Datelike;
+    use indexing::store::{ColumnarStore, IndexedObject};
+
+    let yaml = r#"
+ontology:
+  objectTypes:
+    - id: "estimate"
+      displayName: "Estimate"
+      primaryKey: "id"
+      titleKey: "id"
+      archivalPolicy:
+        property: "year"
+        olderThanYears: 5
+      properties:
+        - id: "id"
+          type: "string"
+        - id: "year"
+          type: "integer"
+        - id: "population"
+          type: "integer"
+  linkTypes: []
+"#;
+    let search_store: Arc<dyn SearchStore> = Arc::new(indexing::InMemorySearchStore::new());
+    let columnar_store: Arc<dyn ColumnarStore> = Arc::new(indexing::InMemoryColumnarStore::new());
+    let current_year = chrono::Utc::now().year() as i64;
+    for (id, year, population) in [("e1", 2010, 100), ("e2", 2012, 200), ("e3", current_year, 400)] {
+        let mut properties = ontology_engine::PropertyMap::new();
+        properties.insert("id".to_string(), PropertyValue::String(id.to_string()));
+        properties.insert("year".to_string(), PropertyValue::Integer(year));
+        properties.insert("population".to_string(), PropertyValue::Integer(population));
+        search_store.index_object("estimate", id, &properties, None).await.unwrap();
+        if id == "e3" {
+            columnar_store
+                .write_batch("estimate", vec![IndexedObject::new("estimate".to_string(), id.to_string(), properties)])
+                .await
+                .unwrap();
+        }
+    }
+
+    let events = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let sink_events = events.clone();
+    let sink: indexing::ArchiveEventSink = Arc::new(move |record: &indexing::ArchiveRecord| {
+        sink_events.lock().unwrap().push((record.action, record.object_ids.clone()));
+    });
+    let schema = Schema::build(QueryRoot::default(), AdminMutations::default(), EmptySubscription)
+        .data(OntologyHandle::new(Ontology::from_yaml(yaml).unwrap()))
+        .data(search_store.clone())
+        .data(columnar_store)
+        .data(ObjectHydrator::new())
+        .data(indexing::JobRegistry::new())
+        .data(sink)
+        .finish();
+    let run_job = |mutation: &'static str| {
+        let schema = schema.clone();
+        async move {
+            let response = schema.execute(mutation).await;
+            assert!(response.errors.is_empty(), "{:?}", response.errors);
+            let data = response.data.into_json().unwrap();
+            let job_id = data.as_object().unwrap().values().next().unwrap().as_str().unwrap().to_string();
+            let query = format!(r#"{{ job(jobId: "{}") {{ status result }} }}"#, job_id);
+            let mut job = Value::Null;
+            for _ in 0..100 {
+                job = schema.execute(query.as_str()).await.data.into_json().unwrap()["job"].clone();
+                if job["status"] != "running" {
+                    break;
+                }
+                tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+            }
+            assert_eq!(job["status"], "completed", "{}", job);
+            job["result"].clone()
+        }
+    };
+    let total_population = |include_archived: bool| {
+        let schema = schema.clone();
+        async move {
+            let query = format!(
+                r#"{{ aggregateObjects(objectType: "estimate", aggregations: [{{ property: "population", operation: "sum" }}], includeArchived: {}) {{ rows }} }}"#,
+                include_archived
+            );
+            let response = schema.execute(query).await;
+            assert!(response.errors.is_empty(), "{:?}", response.errors);
+            response.data.into_json().unwrap()["aggregateObjects"]["rows"][0]["sum_population"].as_f64().unwrap()
+        }
+    };
+
+    // Only the old vintages are archived
+    let result = run_job(r#"mutation { archiveObjects(objectType: "estimate") }"#).await;
+    assert_eq!(result["action"], "archived");
+    assert_eq!(result["object_ids"], serde_json::json!(["e1", "e2"]));
+    assert!(search_store.get_object("estimate", "e1").await.unwrap().is_none());
+    assert!(search_store.get_object("estimate", "e3").await.unwrap().is_some());
+
+    // Archived IDs resolve to where they went; live ones are unaffected
+    let response = schema
+        .execute(r#"{ getObject(objectType: "estimate", objectId: "e1") { objectId properties archived { partition location } } }"#)
+        .await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    let object = &response.data.into_json().unwrap()["getObject"];
+    assert_eq!(object["objectId"], "e1");
+    assert_eq!(object["properties"], serde_json::json!({}));
+    assert_eq!(object["archived"]["partition"], "archived");
+    assert_eq!(object["archived"]["location"], result["location"]);
+    let response = schema
+        .execute(r#"{ getObject(objectType: "estimate", objectId: "e3") { archived { partition } } }"#)
+        .await;
+    assert!(response.data.into_json().unwrap()["getObject"]["archived"].is_null());
+
+    // Analytics see the archive only when asked
+    assert_eq!(total_population(false).await, 400.0);
+    assert_eq!(total_population(true).await, 700.0);
+
+    // Restoring by filter brings back just the matching objects
+    let result = run_job(
+        r#"mutation { unarchiveObjects(objectType: "estimate", filters: [{ property: "year", operator: "equals", value: "2010" }]) }"#,
+    )
+    .await;
+    assert_eq!(result["object_ids"], serde_json::json!(["e1"]));
+    let response = schema
+        .execute(r#"{ getObject(objectType: "estimate", objectId: "e1") { objectId archived { partition } } }"#)
+        .await;
+    let object = &response.data.into_json().unwrap()["getObject"];
+    assert_eq!(object["objectId"], "e1");
+    assert!(object["archived"].is_null());
+    let restored = search_store.get_object("estimate", "e1").await.unwrap().unwrap();
+    assert_eq!(restored.properties.get("population"), Some(&PropertyValue::Integer(100)));
+    assert!(search_store.get_object("estimate", "e2").await.unwrap().is_none());
+    assert_eq!(total_population(true).await, 600.0);
+
+    assert_eq!(
+        *events.lock().unwrap(),
+        vec![
+            (indexing::ArchiveAction::Archived, vec!["e1".to_string(), "e2".to_string()]),
+            (indexing::ArchiveAction::Restored, vec!["e1".to_string()]),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_aggregate_applies_numeric_filter() {
+    let yaml = r#"
+ontology:
+  objectTypes:
+    - id: "reading"
+      displayName: "Reading"
+      primaryKey: "id"
+      properties:
+        - id: "id"
+          type: "string"
+        - id: "value"
+          type: "double"
+  linkTypes: []
+"#;
+    let ontology = Ontology::from_yaml(yaml).expect("Failed to create test ontology");
+    let readings: Vec<Value> = (1..=10)
+        .map(|i| serde_json::json!({ "id": format!("r{}", i), "value": i as f64 }))
+        .collect();
+    let search_store = search_store_with(&ontology, vec![("reading", readings)]).await;
+
+    let schema = Schema::build(QueryRoot::default(), AdminMutations::default(), EmptySubscription)
+        .data(OntologyHandle::new(ontology))
+        .data(search_store)
+        .finish();
+
+    let response = schema
+        .execute(
+            r#"query { aggregateObjects(
+                objectType: "reading",
+                aggregations: [{ property: "value", operation: "sum" }],
+                filters: [{ property: "value", operator: "gte", value: "8" }]
+            ) { rows total } }"#,
+        )
+        .await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    let json = response.data.into_json().unwrap();
+    assert_eq!(json["aggregateObjects"]["total"], 1);
+    assert_eq!(json["aggregateObjects"]["rows"][0]["sum_value"], 27.0);
+
+    // A bad operator is reported rather than ignored
+    let response = schema
+        .execute(
+            r#"query { aggregateObjects(
+                objectType: "reading",
+                aggregations: [{ property: "value", operation: "sum" }],
+                filters: [{ property: "value", operator: "about", value: "8" }]
+            ) { total } }"#,
+        )
+        .await;
+    assert!(!response.errors.is_empty());
+}
+
+#[tokio::test]
+async fn test_apportion_across_crosswalk_keeps_integer_totals() {
+    let yaml = r#"
+ontology:
+  objectTypes:
+    - id: "tract"
+      displayName: "Tract"
+      primaryKey: "tract_year"
+      properties:
+        - id: "tract_year"
+          type: "string"
+        - id: "year"
+          type: "integer"
+        - id: "population"
+          type: "integer"
+        - id: "name"
+          type: "string"
+    - id: "boundary_crosswalk"
+      displayName: "Boundary Crosswalk"
+      primaryKey: "link_id"
+      properties:
+        - id: "link_id"
+          type: "string"
+        - id: "source_tract_id"
+          type: "string"
+        - id: "target_tract_id"
+          type: "string"
+        - id: "source_year"
+          type: "integer"
+        - id: "target_year"
+          type: "integer"
+        - id: "overlap_percentage"
+          type: "double"
+  linkTypes: []
+"#;
+    let ontology = Ontology::from_yaml(yaml).expect("Failed to create test ontology");
+    let tracts = vec![
+        serde_json::json!({ "tract_year": "t1", "year": 2000, "population": 1000, "name": "North" }),
+        serde_json::json!({ "tract_year": "t2", "year": 2000, "population": 101, "name": "South" }),
+        // A tract of another vintage is left out
+        serde_json::json!({ "tract_year": "t9", "year": 2010, "population": 5000, "name": "East" }),
+    ];
+    let crosswalk = |id: &str, source: &str, target: &str, overlap: f64| {
+        serde_json::json!({
+            "link_id": id,
+            "source_tract_id": source,
+            "target_tract_id": target,
+            "source_year": 2000,
+            "target_year": 2010,
+            "overlap_percentage": overlap,
+        })
+    };
+    let crosswalks = vec![
+        crosswalk("x1", "t1", "a", 1.0),
+        crosswalk("x2", "t1", "b", 1.0),
+        crosswalk("x3", "t1", "c", 1.0),
+        crosswalk("x4", "t2", "c", 0.5),
+        crosswalk("x5", "t2", "d", 0.5),
+    ];
+    let search_store = search_store_with(&ontology, vec![("tract", tracts), ("boundary_crosswalk", crosswalks)]).await;
+    let schema = Schema::build(QueryRoot::default(), AdminMutations::default(), EmptySubscription)
+        .data(OntologyHandle::new(ontology))
+        .data(search_store)
+        .finish();
+
+    let response = schema
+        .execute(
+            r#"query { apportionAcrossCrosswalk(
+                objectType: "tract", property: "population", fromYear: 2000, toYear: 2010
+            ) { targetId value } }"#,
+        )
+        .await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    let json = response.data.into_json().unwrap();
+    let rows = json["apportionAcrossCrosswalk"].as_array().unwrap();
+    let targets: Vec<&str> = rows.iter().map(|row| row["targetId"].as_str().unwrap()).collect();
+    assert_eq!(targets, vec!["a", "b", "c", "d"]);
+    let total: i64 = rows.iter().map(|row| row["value"].as_i64().unwrap()).sum();
+    assert_eq!(total, 1000 + 101);
+
+    let response = schema
+        .execute(
+            r#"query { apportionAcrossCrosswalk(
+                objectType: "tract", property: "name", fromYear: 2000, toYear: 2010
+            ) { targetId } }"#,
+        )
+        .await;
+    assert_eq!(response.errors[0].message, "Property 'name' is not numeric on: t1, t2");
+}
+
+#[tokio::test]
+async fn test_query_interface_filters_each_implementer() {
+    let yaml = r#"
+ontology:
+  interfaces:
+    - id: "Region"
+      displayName: "Region"
+      properties:
+        - id: "name"
+          type: "string"
+  objectTypes:
+    - id: "tract"
+      displayName: "Census Tract"
+      primaryKey: "geoid"
+      implements: ["Region"]
+      interfaceMappings:
+        Region:
+          name: "tract_name"
+      properties:
+        - id: "geoid"
+          type: "string"
+        - id: "tract_name"
+          type: "string"
+    - id: "county"
+      displayName: "County"
+      primaryKey: "fips"
+      implements: ["Region"]
+      properties:
+        - id: "fips"
+          type: "string"
+        - id: "name"
+          type: "string"
+        - id: "state"
+          type: "string"
+  linkTypes: []
+"#;
+    let search_store: Arc<dyn SearchStore> = Arc::new(indexing::InMemorySearchStore::new());
+    for (object_type, object_id, values) in [
+        ("tract", "36061000100", vec![("tract_name", "New York Tract 1")]),
+        ("tract", "06037000100", vec![("tract_name", "Los Angeles Tract 1")]),
+        ("county", "36061", vec![("name", "New York County"), ("state", "NY")]),
+        ("county", "06037", vec![("name", "Los Angeles County"), ("state", "CA")]),
+    ] {
+        let mut properties = ontology_engine::PropertyMap::new();
+        for (property, value) in values {
+            properties.insert(property.to_string(), PropertyValue::String(value.to_string()));
+        }
+        search_store.index_object(object_type, object_id, &properties, None).await.unwrap();
+    }
+    let schema = Schema::build(QueryRoot::default(), AdminMutations::default(), EmptySubscription)
+        .data(OntologyHandle::new(Ontology::from_yaml(yaml).unwrap()))
+        .data(search_store)
+        .data(ObjectHydrator::new())
+        .finish();
+
+    let query_ids = |filter: &str| {
+        let query = format!(
+            r#"{{ queryInterface(interfaceId: "Region", filters: [{}]) {{ objectType objectId }} }}"#,
+            filter
+        );
+        let schema = &schema;
+        async move {
+            let response = schema.execute(query.as_str()).await;
+            assert!(response.errors.is_empty(), "{:?}", response.errors);
+            let json = response.data.into_json().unwrap();
+            let mut ids: Vec<String> = json["queryInterface"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|object| format!("{}:{}", object["objectType"].as_str().unwrap(), object["objectId"].as_str().unwrap()))
+                .collect();
+            ids.sort();
+            ids
+        }
+    };
+
+    // The interface property is matched through each implementer's mapping
+    let matched = query_ids(r#"{ property: "name", operator: "startsWith", value: "\"New York\"" }"#).await;
+    assert_eq!(matched, vec!["county:36061", "tract:36061000100"]);
+
+    // Tracts have no `state`, so they are skipped rather than failing the query
+    let matched = query_ids(r#"{ property: "state", operator: "equals", value: "\"CA\"" }"#).await;
+    assert_eq!(matched, vec!["county:06037"]);
+}
+
+#[tokio::test]
+async fn test_interfaces_inherit_through_extends() {
+    let yaml = r#"
+ontology:
+  interfaces:
+    - id: "Identifiable"
+      displayName: "Identifiable"
+      properties:
+        - id: "name"
+          type: "string"
+    - id: "GeoEntity"
+      displayName: "Geo Entity"
+      extends: ["Identifiable"]
+      properties:
+        - id: "latitude"
+          type: "double"
+  objectTypes:
+    - id: "tract"
+      displayName: "Census Tract"
+      primaryKey: "geoid"
+      implements: ["GeoEntity"]
+      interfaceMappings:
+        GeoEntity:
+          name: "tract_name"
+      properties:
+        - id: "geoid"
+          type: "string"
+        - id: "tract_name"
+          type: "string"
+        - id: "latitude"
+          type: "double"
+  linkTypes: []
+"#;
+    let search_store: Arc<dyn SearchStore> = Arc::new(indexing::InMemorySearchStore::new());
+    let mut properties = ontology_engine::PropertyMap::new();
+    properties.insert("tract_name".to_string(), PropertyValue::String("New York Tract 1".to_string()));
+    search_store.index_object("tract", "36061000100", &properties, None).await.unwrap();
+    let schema = Schema::build(QueryRoot::default(), AdminMutations::default(), EmptySubscription)
+        .data(OntologyHandle::new(Ontology::from_yaml(yaml).unwrap()))
+        .data(search_store)
+        .data(ObjectHydrator::new())
+        .finish();
+
+    let response = schema
+        .execute(r#"{ getInterfaces { id extends properties { id } implementers { objectType } } }"#)
+        .await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    let json = response.data.into_json().unwrap();
+    let mut interfaces = json["getInterfaces"].as_array().unwrap().clone();
+    interfaces.sort_by_key(|i| i["id"].as_str().unwrap().to_string());
+    assert_eq!(interfaces[0]["id"], "GeoEntity");
+    assert_eq!(interfaces[0]["extends"], serde_json::json!(["Identifiable"]));
+    assert_eq!(interfaces[0]["properties"], serde_json::json!([{ "id": "latitude" }, { "id": "name" }]));
+    // Implementers of the child implement the parent too
+    assert_eq!(interfaces[1]["extends"], serde_json::json!([]));
+    assert_eq!(interfaces[1]["implementers"], serde_json::json!([{ "objectType": "tract" }]));
+
+    // Querying the parent resolves its properties through the child's mapping
+    let response = schema
+        .execute(r#"{ queryInterface(interfaceId: "Identifiable", filters: [{ property: "name", operator: "startsWith", value: "\"New York\"" }]) { objectId } }"#)
+        .await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    let json = response.data.into_json().unwrap();
+    assert_eq!(json["queryInterface"], serde_json::json!([{ "objectId": "36061000100" }]));
+}
+
+#[tokio::test]
+async fn test_query_interface_paginates_across_implementers() {
+    let yaml = r#"
+ontology:
+  interfaces:
+    - id: "Asset"
+      displayName: "Asset"
+      properties:
+        - id: "name"
+          type: "string"
+          required: true
+        - id: "value"
+          type: "double"
+  objectTypes:
+    - id: "vehicle"
+      displayName: "Vehicle"
+      primaryKey: "id"
+      implements: ["Asset"]
+      properties:
+        - id: "id"
+          type: "string"
+        - id: "name"
+          type: "string"
+          required: true
+        - id: "value"
+          type: "double"
+        - id: "plate"
+          type: "string"
+    - id: "building"
+      displayName: "Building"
+      primaryKey: "id"
+      implements: ["Asset"]
+      interfaceMappings:
+        Asset:
+          name: "label"
+      properties:
+        - id: "id"
+          type: "string"
+        - id: "label"
+          type: "string"
+          required: true
+        - id: "value"
+          type: "double"
+  linkTypes: []
+"#;
+    let search_store: Arc<dyn SearchStore> = Arc::new(indexing::InMemorySearchStore::new());
+    for i in 1..=5 {
+        let mut vehicle = PropertyMap::new();
+        vehicle.insert("name".to_string(), PropertyValue::String(format!("Vehicle {}", i)));
+        vehicle.insert("value".to_string(), PropertyValue::Double(i as f64 * 10.0));
+        vehicle.insert("plate".to_string(), PropertyValue::String(format!("P-{}", i)));
+        search_store.index_object("vehicle", &format!("v{}", i), &vehicle, None).await.unwrap();
+
+        // b3's label is null, though the interface requires a name
+        let mut building = PropertyMap::new();
+        let label = if i == 3 { PropertyValue::Null } else { PropertyValue::String(format!("Building {}", i)) };
+        building.insert("label".to_string(), label);
+        building.insert("value".to_string(), PropertyValue::Double(i as f64 * 10.0 + 5.0));
+        search_store.index_object("building", &format!("b{}", i), &building, None).await.unwrap();
+    }
+    let schema = Schema::build(QueryRoot::default(), AdminMutations::default(), EmptySubscription)
+        .data(OntologyHandle::new(Ontology::from_yaml(yaml).unwrap()))
+        .data(search_store)
+        .data(ObjectHydrator::new())
+        .finish();
+
+    let query = |arguments: &str| {
+        let query = format!(
+            r#"{{ queryInterface(interfaceId: "Asset", {}) {{ objectId properties missingInterfaceProperties }} }}"#,
+            arguments
+        );
+        let schema = &schema;
+        async move {
+            let response = schema.execute(query.as_str()).await;
+            assert!(response.errors.is_empty(), "{:?}", response.errors);
+            response.data.into_json().unwrap()["queryInterface"].as_array().unwrap().clone()
+        }
+    };
+    let ids = |objects: &[Value]| -> Vec<String> {
+        objects.iter().map(|o| o["objectId"].as_str().unwrap().to_string()).collect()
+    };
+
+    // Without a sort, objects are ordered by type and ID; limit and offset apply to the merged list
+    assert_eq!(ids(&query("limit: 3").await), vec!["b1", "b2", "b3"]);
+    assert_eq!(ids(&query("limit: 4, offset: 3").await), vec!["b4", "b5", "v1", "v2"]);
+
+    // Sorting by an interface property interleaves the implementers
+    let by_value = r#"sort: [{ property: "value", ascending: false }]"#;
+    assert_eq!(
+        ids(&query(&format!("{}, limit: 3, offset: 2", by_value)).await),
+        vec!["b4", "v4", "b3"]
+    );
+    let mut paged = Vec::new();
+    for offset in (0..10).step_by(3) {
+        paged.extend(ids(&query(&format!("{}, limit: 3, offset: {}", by_value, offset)).await));
+    }
+    assert_eq!(paged, ids(&query(by_value).await));
+    assert_eq!(paged.len(), 10);
+
+    // Projection gives every object the interface's shape under interface property IDs
+    let projected = query(r#"sort: [{ property: "name" }], limit: 1, projectToInterface: true"#).await;
+    assert_eq!(projected[0]["properties"], serde_json::json!({ "name": "Building 1", "value": 15.0 }));
+    let unprojected = query(r#"sort: [{ property: "name", ascending: false }], limit: 1"#).await;
+    assert_eq!(unprojected[0]["properties"]["plate"], "P-5");
+
+    // Objects missing a required interface property are flagged, or left out when strict
+    let flagged = query("limit: 3").await;
+    assert_eq!(flagged[2]["missingInterfaceProperties"], serde_json::json!(["name"]));
+    assert!(flagged[0]["missingInterfaceProperties"].is_null());
+    assert_eq!(ids(&query("limit: 3, strict: true").await), vec!["b1", "b2", "b4"]);
+
+    let response = schema
+        .execute(r#"{ queryInterface(interfaceId: "Asset", sort: [{ property: "plate" }]) { objectId } }"#)
+        .await;
+    assert!(response.errors[0].message.contains("Interface 'Asset' has no property 'plate' to sort by"));
+}
+
+#[tokio::test]
+async fn test_execute_action_checks_roles_and_writes_to_stores() {
+    use indexing::store::GraphStore;
+
+    let yaml = r#"
+ontology:
+  objectTypes:
+    - id: "ticket"
+      displayName: "Ticket"
+      primaryKey: "id"
+      properties:
+        - id: "id"
+          type: "string"
+        - id: "status"
+          type: "string"
+        - id: "priority"
+          type: "integer"
+    - id: "person"
+      displayName: "Person"
+      primaryKey: "id"
+      properties:
+        - id: "id"
+          type: "string"
+  linkTypes:
+    - id: "assigned_to"
+      displayName: "Assigned to"
+      source: "ticket"
+      target: "person"
+      cardinality: "MANY_TO_ONE"
+  actionTypes:
+    - id: "escalate"
+      displayName: "Escalate"
+      parameters:
+        - id: "ticket"
+          type: "string"
+          required: true
+        - id: "priority"
+          type: "integer"
+          required: true
+          validation:
+            min: 1
+            max: 5
+        - id: "assignee"
+          type: "string"
+          required: true
+      validation:
+        required_roles: ["support"]
+        conditions:
+          - property: "status"
+            operator: "equals"
+            value: "open"
+      logic:
+        - operation: update_object
+          type: "ticket"
+          properties:
+            properties:
+              status: "escalated"
+              priority: "{{priority}}"
+        - operation: create_link
+          linkType: "assigned_to"
+          from: "{{ticket}}"
+          to: "{{assignee}}"
+      side_effects:
+        - type: log
+          config:
+            properties:
+              message: "escalated {{ticket}}"
+    - id: "open_ticket"
+      displayName: "Open ticket"
+      parameters:
+        - id: "id"
+          type: "string"
+          required: true
+      logic:
+        - operation: create_object
+          type: "ticket"
+          properties:
+            properties:
+              id: "{{id}}"
+              status: "open"
+"#;
+    let ontology = Ontology::from_yaml(yaml).unwrap();
+    let search_store = search_store_with(
+        &ontology,
+        vec![
+            ("ticket", vec![serde_json::json!({ "id": "t1", "status": "open", "priority": 2 })]),
+            ("person", vec![serde_json::json!({ "id": "p1" })]),
+        ],
+    )
+    .await;
+    let graph_store: Arc<dyn GraphStore> = Arc::new(indexing::InMemoryGraphStore::new());
+    let event_log: graphql_api::ObjectEventLog = Arc::new(tokio::sync::RwLock::new(EventLog::new()));
+    let schema = Schema::build(QueryRoot::default(), AdminMutations::default(), EmptySubscription)
+        .data(OntologyHandle::new(ontology))
+        .data(search_store.clone())
+        .data(graph_store.clone())
+        .data(ObjectHydrator::new())
+        .data(event_log.clone())
+        .finish();
+
+    let response = schema
+        .execute("{ getActionTypes { id requiredRoles parameters { id type required } } }")
+        .await;
+    let json = response.data.into_json().unwrap();
+    let escalate = &json["getActionTypes"][0];
+    assert_eq!(escalate["id"], "escalate");
+    assert_eq!(escalate["requiredRoles"], serde_json::json!(["support"]));
+    let parameters: Vec<&str> = escalate["parameters"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|parameter| parameter["id"].as_str().unwrap())
+        .collect();
+    assert_eq!(parameters, vec!["ticket", "priority", "assignee"]);
+    assert_eq!(json["getActionTypes"][1]["id"], "open_ticket");
+
+    let execute = |action_type: &str, parameters: Value, roles: &[&str]| {
+        let mut context = security::SecurityContext::new("sam".to_string());
+        for role in roles {
+            context = context.with_role(role.to_string());
+        }
+        let request = async_graphql::Request::new(
+            "mutation($type: String!, $parameters: String!) { executeAction(actionTypeId: $type, parameters: $parameters, targetObjectId: \"t1\") { success operationsExecuted errors sideEffectsTriggered { type status } } }",
+        )
+        .variables(async_graphql::Variables::from_json(serde_json::json!({
+            "type": action_type,
+            "parameters": parameters.to_string(),
+        })))
+        .data(context);
+        let schema = schema.clone();
+        async move { schema.execute(request).await }
+    };
+    let error_extensions = |response: &async_graphql::Response| {
+        serde_json::to_value(&response.errors[0]).unwrap()["extensions"].clone()
+    };
+
+    let valid = serde_json::json!({ "ticket": "t1", "priority": 4, "assignee": "p1" });
+    let response = execute("escalate", valid.clone(), &[]).await;
+    assert_eq!(error_extensions(&response)["code"], "MISSING_ROLE");
+
+    // Parameter failures name the parameter
+    let response = execute("escalate", serde_json::json!({ "ticket": "t1", "priority": 9, "assignee": "p1" }), &["support"]).await;
+    assert_eq!(error_extensions(&response), serde_json::json!({ "code": "INVALID_PARAMETER", "parameter": "priority" }));
+    let response = execute("escalate", serde_json::json!({ "ticket": "t1", "priority": "high", "assignee": "p1" }), &["support"]).await;
+    assert_eq!(error_extensions(&response)["parameter"], "priority");
+    let response = execute("escalate", serde_json::json!({ "ticket": "t1", "priority": 4 }), &["support"]).await;
+    assert_eq!(error_extensions(&response), serde_json::json!({ "code": "MISSING_PARAMETER", "parameter": "assignee" }));
+
+    let response = execute("escalate", valid.clone(), &["support"]).await;
+    assert!(response.errors.is_empty(), "Mutation should succeed, got errors: {:?}", response.errors);
+    let result = response.data.into_json().unwrap()["executeAction"].clone();
+    assert_eq!(result["success"], true);
+    assert_eq!(result["operationsExecuted"][0], "ticket:t1");
+    assert_eq!(result["sideEffectsTriggered"], serde_json::json!([{ "type": "log", "status": "delivered" }]));
+    let ticket = search_store.get_object("ticket", "t1").await.unwrap().unwrap();
+    assert_eq!(ticket.properties.get("status"), Some(&PropertyValue::String("escalated".to_string())));
+    assert_eq!(ticket.properties.get("priority"), Some(&PropertyValue::Integer(4)));
+    let links = indexing::validating_graph::endpoint_links(graph_store.as_ref(), "assigned_to", "t1", "p1").await.unwrap();
+    assert_eq!(links.len(), 1);
+    assert_eq!(result["operationsExecuted"][1], links[0].id.as_str());
+    assert_eq!(event_log.read().await.get_events_for_object("ticket", "t1").len(), 1);
+
+    // Conditions are checked against the target object
+    let response = execute("escalate", valid, &["support"]).await;
+    assert_eq!(error_extensions(&response)["code"], "CONDITION_FAILED");
+
+    let response = execute("open_ticket", serde_json::json!({ "id": "t2" }), &[]).await;
+    assert!(response.errors.is_empty(), "Mutation should succeed, got errors: {:?}", response.errors);
+    let ticket = search_store.get_object("ticket", "t2").await.unwrap().unwrap();
+    assert_eq!(ticket.properties.get("status"), Some(&PropertyValue::String("open".to_string())));
+    let response = execute("open_ticket", serde_json::json!({ "id": "t2" }), &[]).await;
+    let result = response.data.into_json().unwrap()["executeAction"].clone();
+    assert_eq!(result["success"], false);
+    assert_eq!(result["errors"], serde_json::json!(["Object ticket:t2 already exists"]));
+}
+
+#[tokio::test]
+async fn test_execute_action_rolls_back_applied_writes() {
+    let yaml = r#"
+ontology:
+  objectTypes:
+    - id: "ticket"
+      displayName: "Ticket"
+      primaryKey: "id"
+      properties:
+        - id: "id"
+          type: "string"
+        - id: "status"
+          type: "string"
+  linkTypes: []
+  actionTypes:
+    - id: "split"
+      displayName: "Split"
+      executionMode: rollback_on_error
+      parameters:
+        - id: "copy"
+          type: "string"
+          required: true
+      logic:
+        - operation: update_object
+          type: "ticket"
+          properties:
+            properties:
+              status: "split"
+        - operation: create_object
+          type: "ticket"
+          properties:
+            properties:
+              id: "{{copy}}"
+              status: "open"
+"#;
+    let ontology = Ontology::from_yaml(yaml).unwrap();
+    let search_store = search_store_with(
+        &ontology,
+        vec![("ticket", vec![
+            serde_json::json!({ "id": "t1", "status": "open" }),
+            serde_json::json!({ "id": "t2", "status": "open" }),
+        ])],
+    )
+    .await;
+    let schema = Schema::build(QueryRoot::default(), AdminMutations::default(), EmptySubscription)
+        .data(OntologyHandle::new(ontology))
+        .data(search_store.clone())
+        .data(ObjectHydrator::new())
+        .finish();
+
+    // The copy's ID is taken, so the update of the target is undone
+    let response = schema
+        .execute(r#"mutation { executeAction(actionTypeId: "split", parameters: "{\"copy\": \"t2\"}", targetObjectId: "t1") { success rolledBack errors compensationErrors } }"#)
+        .await;
+    assert!(response.errors.is_empty(), "Mutation should succeed, got errors: {:?}", response.errors);
+    let result = response.data.into_json().unwrap()["executeAction"].clone();
+    assert_eq!(result["success"], false);
+    assert_eq!(result["rolledBack"], true);
+    assert_eq!(result["errors"], serde_json::json!(["Object ticket:t2 already exists"]));
+    assert_eq!(result["compensationErrors"], serde_json::json!([]));
+    let ticket = search_store.get_object("ticket", "t1").await.unwrap().unwrap();
+    assert_eq!(ticket.properties.get("status"), Some(&PropertyValue::String("open".to_string())));
+}
+
+#[tokio::test]
+async fn test_execute_action_delivers_webhooks_with_retries() {
+    use std::io::{Read, Write};
+
+    // Answers 503, then 200, then 400
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let hook = format!("http://{}/hook", listener.local_addr().unwrap());
+    std::thread::spawn(move || {
+        for (stream, status) in listener.incoming().zip([503, 200, 400]) {
+            let mut stream = stream.unwrap();
+            let mut request = String::new();
+            let mut buffer = [0; 4096];
+            while !(request.contains("\r\n\r\n") && request.trim_end().ends_with('}')) {
+                let read = stream.read(&mut buffer).unwrap();
+                if read == 0 {
+                    break;
+                }
+                request.push_str(&String::from_utf8_lossy(&buffer[..read]));
+            }
+            let response = format!("HTTP/1.1 {} Status\r\ncontent-length: 0\r\nconnection: close\r\n\r\n", status);
+            stream.write_all(response.as_bytes()).unwrap();
+        }
+    });
+
+    let yaml = r#"
+ontology:
+  objectTypes:
+    - id: "ticket"
+      displayName: "Ticket"
+      primaryKey: "id"
+      properties:
+        - id: "id"
+          type: "string"
+        - id: "status"
+          type: "string"
+  linkTypes: []
+  actionTypes:
+    - id: "notify"
+      displayName: "Notify"
+      parameters:
+        - id: "hook"
+          type: "string"
+          required: true
+      logic:
+        - operation: update_object
+          type: "ticket"
+          properties:
+            properties:
+              status: "notified"
+      side_effects:
+        - type: webhook
+          config:
+            properties:
+              url: "{{hook}}"
+"#;
+    let ontology = Ontology::from_yaml(yaml).unwrap();
+    let search_store = search_store_with(&ontology, vec![("ticket", vec![serde_json::json!({ "id": "t1", "status": "open" })])]).await;
+    let handlers = ontology_engine::SideEffectHandlers::new(ontology_engine::SideEffectConfig {
+        webhooks: ontology_engine::WebhookConfig { initial_backoff_ms: 1, ..Default::default() },
+        smtp: None,
+    });
+    let schema = Schema::build(QueryRoot::default(), AdminMutations::default(), EmptySubscription)
+        .data(OntologyHandle::new(ontology))
+        .data(search_store.clone())
+        .data(ObjectHydrator::new())
+        .data(Arc::new(handlers))
+        .finish();
+    let execute = || {
+        let request = async_graphql::Request::new(
+            "mutation($parameters: String!) { executeAction(actionTypeId: \"notify\", parameters: $parameters, targetObjectId: \"t1\") { success errors sideEffectsTriggered { type status attempts error } } }",
+        )
+        .variables(async_graphql::Variables::from_json(serde_json::json!({
+            "parameters": serde_json::json!({ "hook": hook }).to_string(),
+        })));
+        let schema = schema.clone();
+        async move { schema.execute(request).await.data.into_json().unwrap()["executeAction"].clone() }
+    };
+
+    let result = execute().await;
+    assert_eq!(result["success"], true);
+    assert_eq!(
+        result["sideEffectsTriggered"],
+        serde_json::json!([{ "type": "webhook", "status": "delivered", "attempts": 2, "error": null }])
+    );
+
+    // Client errors are not retried, and do not fail the action
+    let result = execute().await;
+    assert_eq!(result["success"], true);
+    let triggered = &result["sideEffectsTriggered"][0];
+    assert_eq!((triggered["status"].as_str(), triggered["attempts"].as_u64()), (Some("dead_lettered"), Some(1)));
+    assert!(triggered["error"].as_str().unwrap().contains("400"), "{}", triggered);
+    assert_eq!(result["errors"].as_array().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_get_object_bounding_box() {
+    let yaml = r#"
+ontology:
+  objectTypes:
+    - id: "tract"
+      displayName: "Tract"
+      primaryKey: "id"
+      properties:
+        - id: "id"
+          type: "string"
+          required: true
+        - id: "name"
+          type: "string"
+        - id: "boundary"
+          type: "geojson"
+          validation:
+            geometryTypes: ["Polygon", "MultiPolygon"]
+  linkTypes: []
+"#;
+    let ontology = Ontology::from_yaml(yaml).unwrap();
+    let boundary = serde_json::json!({ "type": "MultiPolygon", "coordinates": [
+        [[[-71.10, 42.35], [-71.09, 42.35], [-71.09, 42.36], [-71.10, 42.35]]],
+        [[[-71.08, 42.34], [-71.07, 42.34], [-71.07, 42.35], [-71.08, 42.34]]],
+    ] });
+    let rows = vec![
+        serde_json::json!({ "id": "t1", "name": "Tract 1", "boundary": boundary.to_string() }),
+        serde_json::json!({ "id": "t2", "name": "Tract 2" }),
+    ];
+    let search_store = search_store_with(&ontology, vec![("tract", rows)]).await;
+    let schema = Schema::build(QueryRoot::default(), AdminMutations::default(), EmptySubscription)
+        .data(OntologyHandle::new(ontology))
+        .data(search_store)
+        .data(ObjectHydrator::new())
+        .finish();
+    let bbox = |object_id: &str, property: &str| {
+        let query = format!(
+            r#"{{ getObject(objectType: "tract", objectId: "{}") {{ boundingBox(property: "{}") }} }}"#,
+            object_id, property
+        );
+        let schema = &schema;
+        async move { schema.execute(query).await }
+    };
+
+    let response = bbox("t1", "boundary").await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    assert_eq!(
+        response.data.into_json().unwrap()["getObject"]["boundingBox"],
+        serde_json::json!([-71.10, 42.34, -71.07, 42.36])
+    );
+    let response = bbox("t2", "boundary").await;
+    assert_eq!(response.data.into_json().unwrap()["getObject"]["boundingBox"], Value::Null);
+    let response = bbox("t1", "name").await;
+    assert!(response.errors[0].message.contains("invalid GeoJSON"), "{:?}", response.errors);
+}
+
+#[tokio::test]
+async fn test_search_objects_shrinks_geometries() {
+    let yaml = r#"
+ontology:
+  objectTypes:
+    - id: "tract"
+      displayName: "Tract"
+      primaryKey: "id"
+      properties:
+        - id: "id"
+          type: "string"
+          required: true
+        - id: "name"
+          type: "string"
+        - id: "boundary"
+          type: "geojson"
+  linkTypes: []
+"#;
+    let ontology = Ontology::from_yaml(yaml).unwrap();
+    // A circle with 2000 vertices, far more than a map view needs
+    let ring: Vec<Value> = (0..=2000)
+        .map(|i| {
+            let angle = std::f64::consts::TAU * (i % 2000) as f64 / 2000.0;
+            serde_json::json!([-71.1 + 0.05 * angle.cos(), 42.35 + 0.05 * angle.sin()])
+        })
+        .collect();
+    let boundary = serde_json::json!({ "type": "Polygon", "coordinates": [ring] }).to_string();
+    let rows = vec![serde_json::json!({ "id": "t1", "name": "Tract 1", "boundary": boundary })];
+    let search_store = search_store_with(&ontology, vec![("tract", rows)]).await;
+    let schema = Schema::build(QueryRoot::default(), AdminMutations::default(), EmptySubscription)
+        .data(OntologyHandle::new(ontology))
+        .data(search_store)
+        .data(ObjectHydrator::new())
+        .finish();
+    let search = |args: &str| {
+        let query = format!(
+            r#"{{ searchObjects(objectType: "tract"{}) {{ properties boundingBox(property: "boundary") }} }}"#,
+            args
+        );
+        let schema = &schema;
+        async move {
+            let response = schema.execute(query).await;
+            assert!(response.errors.is_empty(), "{:?}", response.errors);
+            response.data.into_json().unwrap()["searchObjects"][0].clone()
+        }
+    };
+
+    let full = search("").await;
+    assert_eq!(full["properties"]["boundary"], serde_json::from_str::<Value>(&boundary).unwrap());
+
+    let simplified = search(", simplifyTolerance: 0.001, geometryPrecision: 4").await;
+    assert_eq!(simplified["properties"]["name"], "Tract 1");
+    let shape = simplified["properties"]["boundary"].to_string();
+    assert!(shape.len() * 10 < boundary.len(), "{} vs {} bytes", shape.len(), boundary.len());
+    assert_eq!(ontology_engine::geometry::geometry_types(&shape).unwrap(), vec!["Polygon"]);
+
+    let omitted = search(", omitGeometry: true").await;
+    assert_eq!(omitted["properties"]["boundary"], omitted["boundingBox"]);
+    assert_eq!(omitted["boundingBox"], full["boundingBox"]);
+
+    let response = schema
+        .execute(r#"{ searchObjects(objectType: "tract", simplifyTolerance: -1) { objectId } }"#)
+        .await;
+    assert_eq!(serde_json::to_value(&response.errors[0]).unwrap()["extensions"]["code"], "INVALID_ARGUMENT");
+}
+
+#[tokio::test]
+async fn test_promote_model_version_moves_bindings() {
+    use graphql_api::{ModelMutations, ModelQueries};
+    use ontology_engine::{ModelExecutionOrchestrator, ModelRegistry};
+
+    let schema = Schema::build(ModelQueries, ModelMutations, EmptySubscription)
+        .data(Arc::new(tokio::sync::RwLock::new(ModelRegistry::new())))
+        .data(Arc::new(ModelExecutionOrchestrator::new()))
+        .finish();
+    let execute = |query: String| {
+        let schema = &schema;
+        async move {
+            let response = schema.execute(query).await;
+            assert!(response.errors.is_empty(), "{:?}", response.errors);
+            response.data.into_json().unwrap()
+        }
+    };
+    let register = |mutation: &str, version: &str, r2: f64| {
+        format!(
+            r#"mutation {{
+                {}(input: {{ id: "churn", name: "Churn", modelType: "regression", version: "{}",
+                    artifactPath: "/models/churn-{}.pkl", platform: {{ platformType: "local" }} }}) {{ version }}
+                updateModelMetrics(modelId: "churn", version: "{}", metrics: {{ r2Score: {} }}) {{ version }}
+            }}"#,
+            mutation, version, version, version, r2
+        )
+    };
+
+    execute(register("registerModel", "1", 0.7)).await;
+    execute(r#"mutation { bindModel(input: { modelId: "churn", objectType: "Customer", propertyId: "churn_risk" }) { modelId } }"#.to_string()).await;
+    execute(register("registerModelVersion", "2", 0.9)).await;
+
+    let binding = r#"{ getPropertyBinding(objectType: "Customer", propertyId: "churn_risk") { modelId modelVersion } }"#;
+    assert_eq!(execute(binding.to_string()).await["getPropertyBinding"]["modelVersion"], "1");
+    let comparison = execute(r#"{ compareModels(modelIds: ["churn"], allVersions: true) { bestVersion } }"#.to_string()).await;
+    assert_eq!(comparison["compareModels"]["bestVersion"], "2");
+
+    let promoted = execute(r#"mutation { promoteModelVersion(id: "churn", version: "2") { version status isActive } }"#.to_string()).await;
+    assert_eq!(promoted["promoteModelVersion"], serde_json::json!({ "version": "2", "status": "bound", "isActive": true }));
+    assert_eq!(execute(binding.to_string()).await["getPropertyBinding"]["modelVersion"], "2");
+    let versions = execute(r#"{ modelVersions(id: "churn") { version status isActive activeVersion } models { version } }"#.to_string()).await;
+    assert_eq!(
+        versions["modelVersions"],
+        serde_json::json!([
+            { "version": "1", "status": "registered", "isActive": false, "activeVersion": "2" },
+            { "version": "2", "status": "bound", "isActive": true, "activeVersion": "2" },
+        ])
+    );
+    assert_eq!(versions["models"], serde_json::json!([{ "version": "2" }]));
+}
+
+#[tokio::test]
+async fn test_predict_runs_custom_endpoint_models() {
+    use graphql_api::{ModelMutations, ModelQueries};
+    use ontology_engine::{ModelExecutionOrchestrator, ModelRegistry, RemoteModelExecutor};
+    use std::io::{Read, Write};
+
+    // Scores every request; counts them so cached predictions can be told apart
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let endpoint = format!("http://{}/predict", listener.local_addr().unwrap());
+    let served = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+    let counter = served.clone();
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = stream.unwrap();
+            let mut request = String::new();
+            let mut buffer = [0; 4096];
+            while !(request.contains("\r\n\r\n") && request.trim_end().ends_with('}')) {
+                let read = stream.read(&mut buffer).unwrap();
+                if read == 0 {
+                    break;
+                }
+                request.push_str(&String::from_utf8_lossy(&buffer[..read]));
+            }
+            counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            let body = r#"{"prediction": 0.75, "confidence": 0.9, "probabilities": {"churn": 0.75}}"#;
+            let response = format!(
+                "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            stream.write_all(response.as_bytes()).unwrap();
+        }
+    });
+
+    let mut executor = ModelExecutionOrchestrator::new();
+    executor.add_executor(Box::new(RemoteModelExecutor::new()));
+    let schema = Schema::build(ModelQueries, ModelMutations, EmptySubscription)
+        .data(Arc::new(tokio::sync::RwLock::new(ModelRegistry::new())))
+        .data(Arc::new(executor))
+        .finish();
+    let register = |id: &str, platform: &str| {
+        format!(
+            r#"mutation {{ registerModel(input: {{ id: "{}", name: "Churn", modelType: "regression", version: "1",
+                artifactPath: "/models/churn.pkl", platform: {} }}) {{ id }} }}"#,
+            id, platform
+        )
+    };
+    let response = schema.execute(register("churn", &format!(r#"{{ platformType: "custom", endpoint: "{}" }}"#, endpoint))).await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    let response = schema.execute(register("local", r#"{ platformType: "local" }"#)).await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    let predict = |model_id: &str, use_cache: bool| {
+        async_graphql::Request::new(r#"mutation($input: PredictInput!) { predict(input: $input) }"#).variables(
+            async_graphql::Variables::from_json(serde_json::json!({ "input": {
+                "modelId": model_id,
+                "inputs": r#"{"tenure": 3, "plan": "basic"}"#,
+                "useCache": use_cache,
+            } })),
+        )
+    };
+
+    let response = schema.execute(predict("churn", true)).await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    assert_eq!(
+        response.data.into_json().unwrap()["predict"],
+        serde_json::json!({
+            "model_id": "churn",
+            "version": "1",
+            "prediction": 0.75,
+            "confidence": 0.9,
+            "probabilities": { "churn": 0.75 },
+        })
+    );
+    schema.execute(predict("churn", true)).await;
+    assert_eq!(served.load(std::sync::atomic::Ordering::SeqCst), 1);
+    schema.execute(predict("churn", false)).await;
+    assert_eq!(served.load(std::sync::atomic::Ordering::SeqCst), 2);
+
+    let response = schema.execute(predict("local", true)).await;
+    let error = serde_json::to_value(&response.errors[0]).unwrap();
+    assert_eq!(error["extensions"]["code"], "INVALID_ARGUMENT");
+    assert!(error["message"].as_str().unwrap().contains("'local'"), "{}", error);
+}
+
+/// Executor predicting twice the `tenure` input, counting its calls
+struct DoublingExecutor(Arc<std::sync::atomic::AtomicUsize>);
+
+#[async_trait::async_trait]
+impl ontology_engine::ModelExecutor for DoublingExecutor {
+    async fn execute(
+        &self,
+        _model: &ontology_engine::ModelObjective,
+        inputs: HashMap<String, PropertyValue>,
+    ) -> Result<ontology_engine::ModelExecutionResult, ontology_engine::ModelExecutionError> {
+        self.0.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        let tenure = match inputs.get("tenure") {
+            Some(PropertyValue::Integer(n)) => *n as f64,
+            _ => 0.0,
+        };
+        Ok(ontology_engine::ModelExecutionResult {
+            prediction: PropertyValue::Double(tenure * 2.0),
+            confidence: None,
+            probabilities: None,
+            metadata: HashMap::new(),
+        })
+    }
+
+    fn can_handle(&self, _platform: &ontology_engine::ModelPlatform) -> bool {
+        true
+    }
+}
+
+#[tokio::test]
+async fn test_queries_include_model_predictions() {
+    use indexing::ModelPropertyEvaluator;
+    use ontology_engine::{ModelBindingConfig, ModelExecutionOrchestrator, ModelObjective, ModelPlatform, ModelRegistry, ModelType};
+
+    let yaml = r#"
+ontology:
+  objectTypes:
+    - id: "customer"
+      displayName: "Customer"
+      primaryKey: "id"
+      properties:
+        - id: "id"
+          type: "string"
+          required: true
+        - id: "tenure"
+          type: "integer"
+        - id: "churn_score"
+          type: "double"
+  linkTypes: []
+"#;
+    let ontology = Ontology::from_yaml(yaml).unwrap();
+    let rows = vec![
+        serde_json::json!({ "id": "c1", "tenure": 3 }),
+        serde_json::json!({ "id": "c2", "churn_score": 0.5 }),
+    ];
+    let search_store = search_store_with(&ontology, vec![("customer", rows)]).await;
+
+    let mut registry = ModelRegistry::new();
+    registry
+        .register(ModelObjective::new(
+            "churn".to_string(),
+            "Churn".to_string(),
+            ModelType::Regression,
+            "1".to_string(),
+            "/models/churn.pkl".to_string(),
+            ModelPlatform::Local { framework: "sklearn".to_string() },
+        ))
+        .unwrap();
+    let config = ModelBindingConfig { input_properties: vec!["tenure".to_string()], ..Default::default() };
+    registry.bind_model("churn", "customer".to_string(), "churn_score".to_string(), None, config).unwrap();
+    let calls = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+    let mut executor = ModelExecutionOrchestrator::new();
+    executor.add_executor(Box::new(DoublingExecutor(calls.clone())));
+    let evaluator = ModelPropertyEvaluator::new(
+        Arc::new(tokio::sync::RwLock::new(registry)),
+        Arc::new(executor),
+    );
+    let schema = Schema::build(QueryRoot::default(), AdminMutations::default(), EmptySubscription)
+        .data(OntologyHandle::new(ontology))
+        .data(search_store.clone())
+        .data(ObjectHydrator::new())
+        .data(evaluator)
+        .finish();
+
+    let search = r#"{ searchObjects(objectType: "customer", sort: [{ property: "id" }], includePredictions: true) { properties } }"#;
+    let response = schema.execute(search).await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    let data = response.data.into_json().unwrap();
+    assert_eq!(data["searchObjects"][0]["properties"]["churn_score"], 6.0);
+    // Without a tenure the model is skipped and the stored score kept
+    assert_eq!(data["searchObjects"][1]["properties"]["churn_score"], 0.5);
+
+    let get = |include: bool| {
+        format!(
+            r#"{{ getObject(objectType: "customer", objectId: "c1", includePredictions: {}) {{ properties }} }}"#,
+            include
+        )
+    };
+    let response = schema.execute(get(true)).await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    assert_eq!(response.data.into_json().unwrap()["getObject"]["properties"]["churn_score"], 6.0);
+    // The repeated prediction came from the cache
+    assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 1);
+
+    let response = schema.execute(get(false)).await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    assert!(response.data.into_json().unwrap()["getObject"]["properties"].get("churn_score").is_none());
+
+    // Predictions need an evaluator
+    let schema = Schema::build(QueryRoot::default(), AdminMutations::default(), EmptySubscription)
+        .data(OntologyHandle::new(Ontology::from_yaml(yaml).unwrap()))
+        .data(search_store)
+        .data(ObjectHydrator::new())
+        .finish();
+    let response = schema.execute(search).await;
+    let error = serde_json::to_value(&response.errors[0]).unwrap();
+    assert_eq!(error["extensions"]["code"], "INVALID_ARGUMENT");
+}
+
+#[tokio::test]
+async fn test_model_cache_stats_and_clearing() {
+    use graphql_api::{ModelMutations, ModelQueries};
+    use ontology_engine::{ModelExecutionOrchestrator, ModelRegistry};
+
+    let calls = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+    let mut executor = ModelExecutionOrchestrator::new();
+    executor.add_executor(Box::new(DoublingExecutor(calls.clone())));
+    let schema = Schema::build(ModelQueries, ModelMutations, EmptySubscription)
+        .data(Arc::new(tokio::sync::RwLock::new(ModelRegistry::new())))
+        .data(Arc::new(executor))
+        .finish();
+    let response = schema
+        .execute(
+            r#"mutation { registerModel(input: { id: "churn", name: "Churn", modelType: "regression", version: "1",
+                artifactPath: "/models/churn.pkl", platform: { platformType: "local" } }) { id } }"#,
+        )
+        .await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    let predict = r#"mutation { predict(input: { modelId: "churn", inputs: "{\"tenure\": 4}" }) }"#;
+    let stats = r#"{ modelCacheStats { entries maxEntries hits misses evictions hitRate } }"#;
+
+    let response = schema.execute(stats).await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    assert_eq!(response.data.into_json().unwrap()["modelCacheStats"]["hitRate"], Value::Null);
+
+    for _ in 0..2 {
+        let response = schema.execute(predict).await;
+        assert!(response.errors.is_empty(), "{:?}", response.errors);
+    }
+    let response = schema.execute(stats).await;
+    assert_eq!(
+        response.data.into_json().unwrap()["modelCacheStats"],
+        serde_json::json!({ "entries": 1, "maxEntries": 10000, "hits": 1, "misses": 1, "evictions": 0, "hitRate": 0.5 })
+    );
+
+    let response = schema.execute(r#"mutation { clearModelCache(modelId: "churn") }"#).await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    assert_eq!(response.data.into_json().unwrap()["clearModelCache"], 1);
+    schema.execute(predict).await;
+    assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 2);
+}
+
+/// Collects the fields of every span, by span name
+#[derive(Clone, Default)]
+struct SpanCapture {
+    spans: Arc<std::sync::Mutex<Vec<(String, HashMap<String, String>)>>>,
+    ids: Arc<std::sync::Mutex<HashMap<tracing::span::Id, usize>>>,
+}
+
+struct FieldVisitor<'a>(&'a mut HashMap<String, String>);
+
+impl tracing::field::Visit for FieldVisitor<'_> {
+    fn record_str(&mut self, field: &tracing::field::Field, value: &str) {
+        self.0.insert(field.name().to_string(), value.to_string());
+    }
+
+    fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn std::fmt::Debug) {
+        self.0.insert(field.name().to_string(), format!("{:?}", value));
+    }
+}
+
+impl<S: tracing::Subscriber> tracing_subscriber::Layer<S> for SpanCapture {
+    fn on_new_span(
+        &self,
+        attrs: &tracing::span::Attributes<'_>,
+        id: &tracing::span::Id,
+        _ctx: tracing_subscriber::layer::Context<'_, S>,
+    ) {
+        let mut fields = HashMap::new();
+        attrs.record(&mut FieldVisitor(&mut fields));
+        let mut spans = self.spans.lock().unwrap();
+        spans.push((attrs.metadata().name().to_string(), fields));
+        self.ids.lock().unwrap().insert(id.clone(), spans.len() - 1);
+    }
+
+    fn on_record(
+        &self,
+        id: &tracing::span::Id,
+        values: &tracing::span::Record<'_>,
+        _ctx: tracing_subscriber::layer::Context<'_, S>,
+    ) {
+        if let Some(&index) = self.ids.lock().unwrap().get(id) {
+            values.record(&mut FieldVisitor(&mut self.spans.lock().unwrap()[index].1));
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_search_emits_spans_with_request_id_and_result_count() {
+    use tracing_subscriber::layer::SubscriberExt;
+
+    let capture = SpanCapture::default();
+    let _guard = tracing::subscriber::set_default(tracing_subscriber::registry().with(capture.clone()));
+
+    let yaml = r#"
+ontology:
+  objectTypes:
+    - id: "test_object"
+      displayName: "Test Object"
+      primaryKey: "id"
+      properties:
+        - id: "id"
+          type: "string"
+          required: true
+  linkTypes: []
+"#;
+    let ontology = Ontology::from_yaml(yaml).unwrap();
+    let rows = vec![serde_json::json!({ "id": "a" }), serde_json::json!({ "id": "b" })];
+    let search_store = search_store_with(&ontology, vec![("test_object", rows)]).await;
+    let schema = Schema::build(QueryRoot::default(), AdminMutations::default(), EmptySubscription)
+        .data(OntologyHandle::new(ontology))
+        .data(search_store)
+        .data(ObjectHydrator::new())
+        .extension(graphql_api::RequestTracingExtension)
+        .finish();
+
+    let response = schema
+        .execute(r#"query Objects { searchObjects(objectType: "test_object") { objectId } }"#)
+        .await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+
+    let spans = capture.spans.lock().unwrap();
+    let span = |name: &str| {
+        spans
+            .iter()
+            .find(|(span_name, _)| span_name == name)
+            .map(|(_, fields)| fields.clone())
+            .unwrap_or_else(|| panic!("no {} span in {:?}", name, spans))
+    };
+    let request = span("graphql.request");
+    assert!(!request["request_id"].is_empty());
+    assert_eq!(request["operation_name"], "Objects");
+    let resolve = span("graphql.resolve");
+    assert_eq!(resolve["operation"], "searchObjects");
+    assert_eq!(resolve["object_type"], "test_object");
+    assert_eq!(resolve["result_count"], "2");
+    let search = span("store.search");
+    assert_eq!((search["backend"].as_str(), search["object_type"].as_str()), ("in_memory", "test_object"));
+}
@@ -0,0 +1,294 @@
+//! Moving old objects out of the search index into the columnar store, and back.
+//!
+//! `archive` moves the objects of a type that its archival policy marks as due:
+//! - they are appended to the type's `ARCHIVED_PARTITION` in the columnar store, where
+//!   analytics can still include them
+//! - each is deleted from the search store and replaced by a tombstone in
+//!   `ARCHIVE_TOMBSTONE_TYPE` recording where it went, so `find_tombstone` can tell an
+//!   archived ID from one that never existed
+//!
+//! Objects are written to the archive before they are deleted, so an interrupted run leaves
+//! at worst a copy in both places; running it again finishes the move.
+//!
+//! `unarchive` reverses it for the archived objects matching some filters: they are read
+//! back from the partition and reindexed, then their tombstones and archived rows are
+//! removed. Each archive and restore is reported to an optional event sink (typically the
+//! event log).
+
+use crate::jobs::JobHandle;
+use crate::store::{ColumnarStore, Filter, FilterOperator, IndexedObject, SearchStore, StoreError};
+use chrono::{DateTime, Utc};
+use ontology_engine::{OntologyHandle, PropertyMap, PropertyValue};
+use serde::Serialize;
+use std::sync::Arc;
+
+/// Columnar store partition holding archived objects
+pub const ARCHIVED_PARTITION: &str = "archived";
+
+/// Search store type holding tombstones of archived objects, keyed `type:id`
+pub const ARCHIVE_TOMBSTONE_TYPE: &str = "_archived";
+
+/// Job kind recorded for archive runs
+pub const ARCHIVE_JOB_KIND: &str = "archive";
+
+/// Job kind recorded for restores from the archive
+pub const UNARCHIVE_JOB_KIND: &str = "unarchive";
+
+/// Default number of objects moved per batch
+pub const DEFAULT_ARCHIVE_BATCH_SIZE: usize = 500;
+
+/// Direction of a move between the search store and the archive
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArchiveAction {
+    Archived,
+    Restored,
+}
+
+/// What an archive or restore did
+#[derive(Debug, Clone, Serialize)]
+pub struct ArchiveRecord {
+    pub action: ArchiveAction,
+    pub object_type: String,
+    pub object_ids: Vec<String>,
+    /// Where the archived objects are stored
+    pub location: String,
+    pub at: DateTime<Utc>,
+}
+
+/// Receives every completed archive and restore
+pub type ArchiveEventSink = Arc<dyn Fn(&ArchiveRecord) + Send + Sync>;
+
+/// Marker left in the search store for an archived object
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Tombstone {
+    pub object_type: String,
+    pub object_id: String,
+    pub partition: String,
+    pub location: String,
+    pub archived_at: String,
+}
+
+/// Moves objects of a type between the search store and the columnar archive
+#[derive(Clone)]
+pub struct Archiver {
+    ontology: OntologyHandle,
+    search: Arc<dyn SearchStore>,
+    columnar: Arc<dyn ColumnarStore>,
+    event_sink: Option<ArchiveEventSink>,
+    batch_size: usize,
+}
+
+impl Archiver {
+    pub fn new(ontology: OntologyHandle, search: Arc<dyn SearchStore>, columnar: Arc<dyn ColumnarStore>) -> Self {
+        Self {
+            ontology,
+            search,
+            columnar,
+            event_sink: None,
+            batch_size: DEFAULT_ARCHIVE_BATCH_SIZE,
+        }
+    }
+
+    /// Where completed archives and restores are reported
+    pub fn with_event_sink(mut self, sink: ArchiveEventSink) -> Self {
+        self.event_sink = Some(sink);
+        self
+    }
+
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Archive every object of the type its archival policy marks as due now
+    pub async fn archive(&self, object_type: &str, job: Option<&JobHandle>) -> Result<ArchiveRecord, StoreError> {
+        let filter = {
+            let ontology = self.ontology.load();
+            let object_type_def = ontology
+                .get_object_type(object_type)
+                .ok_or_else(|| StoreError::NotFound(format!("Object type '{}'", object_type)))?;
+            let policy = object_type_def.archival_policy.as_ref().ok_or_else(|| {
+                StoreError::Configuration(format!("Object type '{}' has no archival policy", object_type))
+            })?;
+            let cutoff = object_type_def
+                .get_property(&policy.property)
+                .and_then(|property| policy.cutoff(&property.property_type, Utc::now()))
+                .ok_or_else(|| {
+                    StoreError::Configuration(format!(
+                        "Archival property '{}' of '{}' is not a year, date or datetime",
+                        policy.property, object_type
+                    ))
+                })?;
+            Filter {
+                property: policy.property.clone(),
+                operator: FilterOperator::LessThan,
+                value: cutoff,
+                distance: None,
+                case_insensitive: false,
+            }
+        };
+
+        // Collect before deleting so the scan is not paging over a shrinking result
+        if let Some(job) = job {
+            let total = self.search.count_objects(object_type, Some(std::slice::from_ref(&filter))).await.ok();
+            job.start_phase("scan", total.map(|n| n as usize));
+        }
+        let mut due = Vec::new();
+        let mut cursor: Option<String> = None;
+        loop {
+            if let Some(job) = job {
+                job.check_cancelled()?;
+            }
+            let page = self
+                .search
+                .scan_objects(object_type, std::slice::from_ref(&filter), cursor.as_deref(), self.batch_size)
+                .await?;
+            if let Some(job) = job {
+                job.advance(page.objects.len());
+            }
+            due.extend(page.objects);
+            match page.next {
+                Some(next) => cursor = Some(next),
+                None => break,
+            }
+        }
+
+        if let Some(job) = job {
+            job.start_phase("archive", Some(due.len()));
+        }
+        let archived_at = Utc::now();
+        let mut location = String::new();
+        let mut object_ids = Vec::with_capacity(due.len());
+        for batch in due.chunks(self.batch_size) {
+            if let Some(job) = job {
+                job.check_cancelled()?;
+            }
+            location = self
+                .columnar
+                .append_partition(object_type, ARCHIVED_PARTITION, batch.to_vec())
+                .await?;
+            for object in batch {
+                let tombstone = Tombstone {
+                    object_type: object_type.to_string(),
+                    object_id: object.object_id.clone(),
+                    partition: ARCHIVED_PARTITION.to_string(),
+                    location: location.clone(),
+                    archived_at: archived_at.to_rfc3339(),
+                };
+                self.search
+                    .index_object(ARCHIVE_TOMBSTONE_TYPE, &tombstone_id(object_type, &object.object_id), &tombstone.properties(), None)
+                    .await?;
+                self.search.delete_object(object_type, &object.object_id).await?;
+                object_ids.push(object.object_id.clone());
+            }
+            if let Some(job) = job {
+                job.advance(batch.len());
+            }
+        }
+
+        Ok(self.report(ArchiveAction::Archived, object_type, object_ids, location))
+    }
+
+    /// Restore the archived objects of a type matching all `filters` to the search store
+    pub async fn unarchive(
+        &self,
+        object_type: &str,
+        filters: &[Filter],
+        job: Option<&JobHandle>,
+    ) -> Result<ArchiveRecord, StoreError> {
+        if self.ontology.load().get_object_type(object_type).is_none() {
+            return Err(StoreError::NotFound(format!("Object type '{}'", object_type)));
+        }
+
+        if let Some(job) = job {
+            job.start_phase("read", None);
+        }
+        let archived = self.columnar.read_partition(object_type, ARCHIVED_PARTITION, filters).await?;
+
+        if let Some(job) = job {
+            job.start_phase("restore", Some(archived.len()));
+        }
+        let mut location = String::new();
+        let mut object_ids = Vec::with_capacity(archived.len());
+        for batch in archived.chunks(self.batch_size) {
+            if let Some(job) = job {
+                job.check_cancelled()?;
+            }
+            let restored: Vec<IndexedObject> = batch
+                .iter()
+                .map(|object| IndexedObject::new(object_type.to_string(), object.object_id.clone(), object.properties.clone()))
+                .collect();
+            self.search.bulk_index(restored).await?;
+            let ids: Vec<String> = batch.iter().map(|object| object.object_id.clone()).collect();
+            for object_id in &ids {
+                let id = tombstone_id(object_type, object_id);
+                if let Some(tombstone) = self.search.get_object(ARCHIVE_TOMBSTONE_TYPE, &id).await? {
+                    if let Some(PropertyValue::String(stored_at)) = tombstone.properties.get("location") {
+                        location = stored_at.clone();
+                    }
+                    self.search.delete_object(ARCHIVE_TOMBSTONE_TYPE, &id).await?;
+                }
+            }
+            self.columnar.remove_from_partition(object_type, ARCHIVED_PARTITION, &ids).await?;
+            if let Some(job) = job {
+                job.advance(batch.len());
+            }
+            object_ids.extend(ids);
+        }
+
+        Ok(self.report(ArchiveAction::Restored, object_type, object_ids, location))
+    }
+
+    fn report(&self, action: ArchiveAction, object_type: &str, object_ids: Vec<String>, location: String) -> ArchiveRecord {
+        let record = ArchiveRecord {
+            action,
+            object_type: object_type.to_string(),
+            object_ids,
+            location,
+            at: Utc::now(),
+        };
+        if let Some(sink) = &self.event_sink {
+            if !record.object_ids.is_empty() {
+                sink(&record);
+            }
+        }
+        record
+    }
+}
+
+impl Tombstone {
+    fn properties(&self) -> PropertyMap {
+        let mut properties = PropertyMap::new();
+        properties.insert("object_type".to_string(), PropertyValue::String(self.object_type.clone()));
+        properties.insert("object_id".to_string(), PropertyValue::String(self.object_id.clone()));
+        properties.insert("partition".to_string(), PropertyValue::String(self.partition.clone()));
+        properties.insert("location".to_string(), PropertyValue::String(self.location.clone()));
+        properties.insert("archived_at".to_string(), PropertyValue::DateTime(self.archived_at.clone()));
+        properties
+    }
+}
+
+fn tombstone_id(object_type: &str, object_id: &str) -> String {
+    format!("{}:{}", object_type, object_id)
+}
+
+/// The tombstone of an archived object, or `None` if the ID was never archived (or has
+/// been restored)
+pub async fn find_tombstone(
+    search: &dyn SearchStore,
+    object_type: &str,
+    object_id: &str,
+) -> Result<Option<Tombstone>, StoreError> {
+    let Some(stored) = search.get_object(ARCHIVE_TOMBSTONE_TYPE, &tombstone_id(object_type, object_id)).await? else {
+        return Ok(None);
+    };
+    let field = |name: &str| stored.properties.get(name).map(|value| value.to_string()).unwrap_or_default();
+    Ok(Some(Tombstone {
+        object_type: object_type.to_string(),
+        object_id: object_id.to_string(),
+        partition: field("partition"),
+        location: field("location"),
+        archived_at: field("archived_at"),
+    }))
+}
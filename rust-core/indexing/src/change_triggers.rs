@@ -0,0 +1,432 @@
+//! Change triggers: side effects fired by plain data writes.
+//!
+//! Action side effects only run when an action executes. A `ChangeTrigger` attaches the same
+//! side effects (webhook, email, log, ...) to writes of an object type, whichever path made
+//! them: sync, writeback or an admin upsert. `TriggeringSearchStore` wraps the search store;
+//! before writing an object of a watched type it reads the stored version, and once the write
+//! succeeds it fires every trigger whose watched properties changed and whose condition holds.
+//! Each side effect receives its configured `config` plus a `payload` entry holding the old
+//! and new values of the changed properties.
+//!
+//! Firings are rate limited per target (a webhook URL, an email recipient) so a large sync
+//! cannot flood a receiver; firings over the limit are dropped and counted. Bulk writes skip
+//! triggers unless `TriggerSettings::fire_during_bulk` is set.
+
+use crate::store::{
+    AnalyticsQuery, AnalyticsResult, Filter, IndexedObject, ObjectScanPage, SearchQuery, SearchStore,
+    StoreError,
+};
+use async_trait::async_trait;
+use ontology_engine::validation::condition_matches;
+use ontology_engine::{
+    default_side_effect, ActionCondition, ActionSideEffect, Ontology, PropertyMap, PropertyValue, SideEffectType,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+/// Executes a side effect; `DeliveryOutcome::into_result` adapts `SideEffectHandlers::deliver`
+pub type SideEffectHandler = Arc<dyn Fn(&SideEffectType, &PropertyMap) -> Result<(), String> + Send + Sync>;
+
+/// Config keys naming where a side effect is delivered, in the order they are looked up
+const TARGET_CONFIG_KEYS: &[&str] = &["url", "to", "recipient", "channel"];
+
+/// Side effects to run when objects of a type change
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChangeTrigger {
+    pub id: String,
+
+    #[serde(rename = "objectType")]
+    pub object_type: String,
+
+    /// Fire only when one of these properties changes; empty means any property
+    #[serde(default)]
+    pub properties: Vec<String>,
+
+    /// Checked against the object after the write (before it, for deletes)
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub condition: Option<ActionCondition>,
+
+    #[serde(rename = "sideEffects")]
+    pub side_effects: Vec<ActionSideEffect>,
+}
+
+impl ChangeTrigger {
+    /// Check that the object type and every property the trigger names are declared
+    pub fn validate(&self, ontology: &Ontology) -> Result<(), String> {
+        let object_type = ontology
+            .get_object_type(&self.object_type)
+            .ok_or_else(|| format!("Object type '{}' not found", self.object_type))?;
+        let condition_property = self.condition.as_ref().map(|c| &c.property);
+        for property in self.properties.iter().chain(condition_property) {
+            if object_type.get_property(property).is_none() {
+                return Err(format!(
+                    "Unknown property '{}' on object type '{}'",
+                    property, self.object_type
+                ));
+            }
+        }
+        if self.side_effects.is_empty() {
+            return Err(format!("Change trigger '{}' has no side effects", self.id));
+        }
+        Ok(())
+    }
+
+    /// Watched properties that differ between the stored object and the written one, with
+    /// their old and new values (`Null` where the object or property is absent)
+    pub fn changes(
+        &self,
+        old: Option<&PropertyMap>,
+        new: Option<&PropertyMap>,
+    ) -> BTreeMap<String, (PropertyValue, PropertyValue)> {
+        let value = |properties: Option<&PropertyMap>, key: &str| {
+            properties.and_then(|p| p.get(key)).cloned().unwrap_or(PropertyValue::Null)
+        };
+        let candidates: Vec<String> = if self.properties.is_empty() {
+            let mut keys: Vec<String> = old.iter().chain(new.iter()).flat_map(|p| p.iter().map(|(key, _)| key.clone())).collect();
+            keys.sort();
+            keys.dedup();
+            keys
+        } else {
+            self.properties.clone()
+        };
+        candidates
+            .into_iter()
+            .filter_map(|key| {
+                let (before, after) = (value(old, &key), value(new, &key));
+                (before != after).then_some((key, (before, after)))
+            })
+            .collect()
+    }
+
+    /// Whether the trigger's condition holds for `properties`; a missing property fails it
+    pub fn condition_holds(&self, properties: &PropertyMap) -> bool {
+        match &self.condition {
+            None => true,
+            Some(condition) => properties
+                .get(&condition.property)
+                .is_some_and(|value| condition_matches(condition, value).unwrap_or(false)),
+        }
+    }
+}
+
+/// Registered change triggers, keyed by ID
+#[derive(Clone, Default)]
+pub struct ChangeTriggerRegistry {
+    triggers: Arc<RwLock<BTreeMap<String, ChangeTrigger>>>,
+}
+
+impl ChangeTriggerRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a trigger; fails if the ID is taken
+    pub fn create(&self, trigger: ChangeTrigger) -> Result<(), String> {
+        let mut triggers = self.triggers.write().unwrap();
+        if triggers.contains_key(&trigger.id) {
+            return Err(format!("Change trigger '{}' already exists", trigger.id));
+        }
+        triggers.insert(trigger.id.clone(), trigger);
+        Ok(())
+    }
+
+    /// Replace a registered trigger; fails if there is none with its ID
+    pub fn update(&self, trigger: ChangeTrigger) -> Result<(), String> {
+        let mut triggers = self.triggers.write().unwrap();
+        match triggers.get_mut(&trigger.id) {
+            Some(existing) => {
+                *existing = trigger;
+                Ok(())
+            }
+            None => Err(format!("Change trigger '{}' not found", trigger.id)),
+        }
+    }
+
+    /// Remove a trigger; returns whether it existed
+    pub fn delete(&self, id: &str) -> bool {
+        self.triggers.write().unwrap().remove(id).is_some()
+    }
+
+    pub fn get(&self, id: &str) -> Option<ChangeTrigger> {
+        self.triggers.read().unwrap().get(id).cloned()
+    }
+
+    pub fn list(&self) -> Vec<ChangeTrigger> {
+        self.triggers.read().unwrap().values().cloned().collect()
+    }
+
+    /// Triggers watching an object type
+    pub fn for_type(&self, object_type: &str) -> Vec<ChangeTrigger> {
+        self.triggers
+            .read()
+            .unwrap()
+            .values()
+            .filter(|t| t.object_type == object_type)
+            .cloned()
+            .collect()
+    }
+}
+
+/// Rate limiting and bulk behaviour of `TriggeringSearchStore`
+#[derive(Debug, Clone)]
+pub struct TriggerSettings {
+    /// Side effects delivered to one target per `window`; later ones are dropped
+    pub max_per_target: usize,
+    pub window: Duration,
+    /// Evaluate triggers for `bulk_index` writes too. Off by default, since bulk loads would
+    /// otherwise read every object back before writing it.
+    pub fire_during_bulk: bool,
+}
+
+impl Default for TriggerSettings {
+    fn default() -> Self {
+        Self {
+            max_per_target: 60,
+            window: Duration::from_secs(60),
+            fire_during_bulk: false,
+        }
+    }
+}
+
+/// Search store wrapper that fires change triggers after successful writes
+pub struct TriggeringSearchStore {
+    inner: Arc<dyn SearchStore>,
+    triggers: ChangeTriggerRegistry,
+    handler: SideEffectHandler,
+    settings: TriggerSettings,
+    /// Recent deliveries per target, oldest first
+    deliveries: Mutex<HashMap<String, VecDeque<Instant>>>,
+    suppressed: AtomicU64,
+}
+
+impl TriggeringSearchStore {
+    pub fn new(inner: Arc<dyn SearchStore>, triggers: ChangeTriggerRegistry) -> Self {
+        Self {
+            inner,
+            triggers,
+            handler: Arc::new(default_side_effect),
+            settings: TriggerSettings::default(),
+            deliveries: Mutex::new(HashMap::new()),
+            suppressed: AtomicU64::new(0),
+        }
+    }
+
+    /// Run side effects with `handler` instead of the default executors
+    pub fn with_handler(mut self, handler: SideEffectHandler) -> Self {
+        self.handler = handler;
+        self
+    }
+
+    pub fn with_settings(mut self, settings: TriggerSettings) -> Self {
+        self.settings = settings;
+        self
+    }
+
+    /// Side effects dropped by the per-target rate limit
+    pub fn suppressed(&self) -> u64 {
+        self.suppressed.load(Ordering::Relaxed)
+    }
+
+    fn watches(&self, object_type: &str) -> bool {
+        !self.triggers.for_type(object_type).is_empty()
+    }
+
+    async fn stored_properties(&self, object_type: &str, object_id: &str) -> Result<Option<PropertyMap>, StoreError> {
+        Ok(self.inner.get_object(object_type, object_id).await?.map(|o| o.properties))
+    }
+
+    /// Take a delivery slot for `target` if it is under the rate limit
+    fn admit(&self, target: &str) -> bool {
+        let now = Instant::now();
+        let mut deliveries = self.deliveries.lock().unwrap();
+        let recent = deliveries.entry(target.to_string()).or_default();
+        while recent.front().is_some_and(|sent| now.duration_since(*sent) >= self.settings.window) {
+            recent.pop_front();
+        }
+        if recent.len() >= self.settings.max_per_target {
+            return false;
+        }
+        recent.push_back(now);
+        true
+    }
+
+    /// Fire the triggers matching a write of `object_type:object_id` from `old` to `new`
+    fn fire(&self, object_type: &str, object_id: &str, old: Option<&PropertyMap>, new: Option<&PropertyMap>) {
+        let operation = match (old, new) {
+            (None, _) => "create",
+            (Some(_), Some(_)) => "update",
+            (Some(_), None) => "delete",
+        };
+        for trigger in self.triggers.for_type(object_type) {
+            let changes = trigger.changes(old, new);
+            if changes.is_empty() || !new.or(old).is_some_and(|p| trigger.condition_holds(p)) {
+                continue;
+            }
+            let payload = trigger_payload(&trigger.id, object_type, object_id, operation, changes);
+            for side_effect in &trigger.side_effects {
+                if !self.admit(&side_effect_target(side_effect)) {
+                    self.suppressed.fetch_add(1, Ordering::Relaxed);
+                    continue;
+                }
+                let mut config = side_effect.config.clone();
+                config.insert("payload".to_string(), payload.clone());
+                if let Err(e) = (self.handler)(&side_effect.effect_type, &config) {
+                    tracing::error!("Change trigger '{}' side effect {:?} failed: {}", trigger.id, side_effect.effect_type, e);
+                }
+            }
+        }
+    }
+}
+
+/// Where a side effect is delivered: its type and the first target key in its config
+fn side_effect_target(side_effect: &ActionSideEffect) -> String {
+    let target = TARGET_CONFIG_KEYS
+        .iter()
+        .find_map(|key| side_effect.config.get(key))
+        .map(|value| value.to_string())
+        .unwrap_or_default();
+    format!("{:?}:{}", side_effect.effect_type, target)
+}
+
+fn trigger_payload(
+    trigger_id: &str,
+    object_type: &str,
+    object_id: &str,
+    operation: &str,
+    changes: BTreeMap<String, (PropertyValue, PropertyValue)>,
+) -> PropertyValue {
+    let changes = changes
+        .into_iter()
+        .map(|(property, (old, new))| {
+            let change = HashMap::from([("old".to_string(), old), ("new".to_string(), new)]);
+            (property, PropertyValue::Map(change))
+        })
+        .collect();
+    PropertyValue::Map(HashMap::from([
+        ("trigger".to_string(), PropertyValue::String(trigger_id.to_string())),
+        ("object_type".to_string(), PropertyValue::String(object_type.to_string())),
+        ("object_id".to_string(), PropertyValue::String(object_id.to_string())),
+        ("operation".to_string(), PropertyValue::String(operation.to_string())),
+        ("changes".to_string(), PropertyValue::Map(changes)),
+    ]))
+}
+
+#[async_trait]
+impl SearchStore for TriggeringSearchStore {
+    async fn index_object(
+        &self,
+        object_type: &str,
+        object_id: &str,
+        properties: &PropertyMap,
+        expected_revision: Option<u64>,
+    ) -> Result<u64, StoreError> {
+        if !self.watches(object_type) {
+            return self.inner.index_object(object_type, object_id, properties, expected_revision).await;
+        }
+        let old = self.stored_properties(object_type, object_id).await?;
+        let revision = self.inner.index_object(object_type, object_id, properties, expected_revision).await?;
+        self.fire(object_type, object_id, old.as_ref(), Some(properties));
+        Ok(revision)
+    }
+
+    async fn search(
+        &self,
+        object_type: &str,
+        query: &SearchQuery,
+    ) -> Result<Vec<IndexedObject>, StoreError> {
+        self.inner.search(object_type, query).await
+    }
+
+    async fn get_object(
+        &self,
+        object_type: &str,
+        object_id: &str,
+    ) -> Result<Option<IndexedObject>, StoreError> {
+        self.inner.get_object(object_type, object_id).await
+    }
+
+    async fn bulk_index(&self, objects: Vec<IndexedObject>) -> Result<(), StoreError> {
+        if !self.settings.fire_during_bulk {
+            return self.inner.bulk_index(objects).await;
+        }
+        let mut writes = Vec::new();
+        for object in &objects {
+            if self.watches(&object.object_type) {
+                let old = self.stored_properties(&object.object_type, &object.object_id).await?;
+                writes.push((object.object_type.clone(), object.object_id.clone(), old, object.properties.clone()));
+            }
+        }
+        self.inner.bulk_index(objects).await?;
+        for (object_type, object_id, old, new) in &writes {
+            self.fire(object_type, object_id, old.as_ref(), Some(new));
+        }
+        Ok(())
+    }
+
+    async fn delete_object(&self, object_type: &str, object_id: &str) -> Result<(), StoreError> {
+        if !self.watches(object_type) {
+            return self.inner.delete_object(object_type, object_id).await;
+        }
+        let old = self.stored_properties(object_type, object_id).await?;
+        self.inner.delete_object(object_type, object_id).await?;
+        if let Some(old) = &old {
+            self.fire(object_type, object_id, Some(old), None);
+        }
+        Ok(())
+    }
+
+    async fn count_objects(
+        &self,
+        object_type: &str,
+        filters: Option<&[Filter]>,
+    ) -> Result<u64, StoreError> {
+        self.inner.count_objects(object_type, filters).await
+    }
+
+    async fn get_objects(
+        &self,
+        object_type: &str,
+        object_ids: &[String],
+    ) -> Result<HashMap<String, IndexedObject>, StoreError> {
+        self.inner.get_objects(object_type, object_ids).await
+    }
+
+    async fn content_hashes(
+        &self,
+        object_type: &str,
+        object_ids: &[String],
+    ) -> Result<HashMap<String, Option<String>>, StoreError> {
+        self.inner.content_hashes(object_type, object_ids).await
+    }
+
+    async fn scan_objects(
+        &self,
+        object_type: &str,
+        filters: &[Filter],
+        cursor: Option<&str>,
+        limit: usize,
+    ) -> Result<ObjectScanPage, StoreError> {
+        self.inner.scan_objects(object_type, filters, cursor, limit).await
+    }
+
+    async fn aggregate(
+        &self,
+        object_type: &str,
+        query: &AnalyticsQuery,
+    ) -> Result<AnalyticsResult, StoreError> {
+        self.inner.aggregate(object_type, query).await
+    }
+
+    fn supports_aggregations(&self) -> bool {
+        self.inner.supports_aggregations()
+    }
+
+    fn explain_search(&self, object_type: &str, query: &SearchQuery) -> Option<JsonValue> {
+        self.inner.explain_search(object_type, query)
+    }
+}
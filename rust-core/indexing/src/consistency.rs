@@ -0,0 +1,398 @@
+//! Consistency check between the search store and the graph store.
+//!
+//! For one object type the check runs in two passes:
+//! - search pass: streams the type's IDs from the search store and verifies that every link
+//!   stored for each object points at an object that exists
+//! - graph pass: scans every link type with the object type at either end and verifies that
+//!   both endpoints of each link exist in the search store
+//!
+//! Links with a missing endpoint are reported as dangling; the missing endpoints are graph
+//! nodes with no document behind them. In sampling mode each pass verifies a uniform sample
+//! instead of every item. Repair reindexes missing documents from an object state source
+//! (typically the event log) and deletes the dangling links that are still dangling after that.
+
+use crate::jobs::JobHandle;
+use crate::sampling::{ReservoirSampler, SamplingOptions};
+use crate::store::{GraphLink, GraphStore, LinkDirection, LinkQuery, SearchQuery, SearchStore, SortOption, StoreError};
+use ontology_engine::{LinkTypeDef, OntologyHandle, PropertyMap};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::sync::Arc;
+
+/// Default number of search documents or links read per page
+pub const DEFAULT_SCAN_PAGE_SIZE: usize = 500;
+
+/// Last known properties of an object, used to restore documents missing from the search store
+pub type ObjectStateSource = Arc<dyn Fn(&str, &str) -> Option<PropertyMap> + Send + Sync>;
+
+#[derive(Debug, Clone)]
+pub struct ConsistencyOptions {
+    /// Verify a uniform sample of objects and links instead of all of them
+    pub sampling: Option<SamplingOptions>,
+    /// Reindex missing documents and delete dangling links
+    pub repair: bool,
+    pub page_size: usize,
+}
+
+impl Default for ConsistencyOptions {
+    fn default() -> Self {
+        Self {
+            sampling: None,
+            repair: false,
+            page_size: DEFAULT_SCAN_PAGE_SIZE,
+        }
+    }
+}
+
+/// An object referenced by the graph that has no search document
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+pub struct MissingObject {
+    pub object_type: String,
+    pub object_id: String,
+}
+
+/// A link with at least one endpoint missing from the search store
+#[derive(Debug, Clone, Serialize)]
+pub struct DanglingLink {
+    pub link_id: String,
+    pub link_type_id: String,
+    pub source_id: String,
+    pub target_id: String,
+    pub missing: Vec<MissingObject>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RepairSummary {
+    pub objects_reindexed: Vec<MissingObject>,
+    pub links_deleted: Vec<String>,
+    pub failures: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ConsistencyReport {
+    pub object_type: String,
+    pub sampled: bool,
+    /// Search documents streamed / whose links were verified
+    pub objects_scanned: usize,
+    pub objects_checked: usize,
+    /// Graph links streamed / whose endpoints were verified
+    pub links_scanned: usize,
+    pub links_checked: usize,
+    pub dangling_links: Vec<DanglingLink>,
+    pub missing_objects: Vec<MissingObject>,
+    pub repair: Option<RepairSummary>,
+}
+
+impl ConsistencyReport {
+    pub fn is_consistent(&self) -> bool {
+        self.dangling_links.is_empty() && self.missing_objects.is_empty()
+    }
+}
+
+impl fmt::Display for ConsistencyReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Consistency report for {}{}",
+            self.object_type,
+            if self.sampled { " (sampled)" } else { "" }
+        )?;
+        writeln!(f, "  objects scanned {:>8}  checked {:>8}", self.objects_scanned, self.objects_checked)?;
+        writeln!(f, "  links scanned   {:>8}  checked {:>8}", self.links_scanned, self.links_checked)?;
+        writeln!(f, "  dangling links: {}, missing objects: {}", self.dangling_links.len(), self.missing_objects.len())?;
+        for missing in &self.missing_objects {
+            writeln!(f, "  ✗ missing {}:{}", missing.object_type, missing.object_id)?;
+        }
+        for link in &self.dangling_links {
+            writeln!(
+                f,
+                "  ✗ dangling {} {} ({} -> {})",
+                link.link_type_id, link.link_id, link.source_id, link.target_id
+            )?;
+        }
+        if let Some(repair) = &self.repair {
+            writeln!(
+                f,
+                "  repaired: {} objects reindexed, {} links deleted",
+                repair.objects_reindexed.len(),
+                repair.links_deleted.len()
+            )?;
+            for failure in &repair.failures {
+                writeln!(f, "  ✗ repair failed: {}", failure)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Checks (and optionally repairs) agreement between the search and graph stores
+pub struct ConsistencyChecker {
+    ontology: OntologyHandle,
+    search: Arc<dyn SearchStore>,
+    graph: Arc<dyn GraphStore>,
+    object_source: Option<ObjectStateSource>,
+}
+
+/// Findings accumulated across both passes
+#[derive(Default)]
+struct Findings {
+    /// (object type, object ID) -> exists in the search store
+    exists: HashMap<(String, String), bool>,
+    dangling: BTreeMap<String, DanglingLink>,
+}
+
+impl ConsistencyChecker {
+    pub fn new(ontology: OntologyHandle, search: Arc<dyn SearchStore>, graph: Arc<dyn GraphStore>) -> Self {
+        Self {
+            ontology,
+            search,
+            graph,
+            object_source: None,
+        }
+    }
+
+    /// Where repair reads the state of documents missing from the search store
+    pub fn with_object_source(mut self, source: ObjectStateSource) -> Self {
+        self.object_source = Some(source);
+        self
+    }
+
+    pub async fn check(
+        &self,
+        object_type: &str,
+        options: &ConsistencyOptions,
+        job: Option<&JobHandle>,
+    ) -> Result<ConsistencyReport, StoreError> {
+        let ontology = self.ontology.load();
+        let primary_key_sort = ontology
+            .get_object_type(object_type)
+            .map(|o| SortOption::for_property(o, o.primary_key.clone(), true))
+            .ok_or_else(|| StoreError::NotFound(format!("Object type '{}'", object_type)))?;
+        let link_types: Vec<LinkTypeDef> = ontology
+            .link_types()
+            .filter(|l| l.source == object_type || l.target == object_type)
+            .cloned()
+            .collect();
+        drop(ontology);
+
+        let page_size = options.page_size.max(1);
+        let mut report = ConsistencyReport {
+            object_type: object_type.to_string(),
+            sampled: options.sampling.is_some(),
+            ..Default::default()
+        };
+        let mut findings = Findings::default();
+
+        // Search pass
+        let total = self.search.count_objects(object_type, None).await.ok().map(|n| n as usize);
+        if let Some(job) = job {
+            job.start_phase("search", options.sampling.map(|s| s.sample_size).or(total));
+        }
+        let mut sampler = options.sampling.map(ReservoirSampler::new);
+        let mut offset = 0;
+        loop {
+            if let Some(job) = job {
+                job.check_cancelled()?;
+            }
+            let query = SearchQuery {
+                filters: vec![],
+                sort: vec![primary_key_sort.clone()],
+                limit: Some(page_size),
+                offset: Some(offset),
+                search_after: None,
+            };
+            let page = self.search.search(object_type, &query).await?;
+            let fetched = page.len();
+            report.objects_scanned += fetched;
+            for object in page {
+                findings.exists.insert((object_type.to_string(), object.object_id.clone()), true);
+                match &mut sampler {
+                    Some(sampler) => sampler.offer(object.object_id),
+                    None => {
+                        self.check_object(object_type, &object.object_id, &link_types, &mut findings).await?;
+                        report.objects_checked += 1;
+                        if let Some(job) = job {
+                            job.advance(1);
+                        }
+                    }
+                }
+            }
+            offset += fetched;
+            if fetched < page_size {
+                break;
+            }
+        }
+        if let Some(sampler) = sampler {
+            for object_id in sampler.into_sample() {
+                self.check_object(object_type, &object_id, &link_types, &mut findings).await?;
+                report.objects_checked += 1;
+                if let Some(job) = job {
+                    job.advance(1);
+                }
+            }
+        }
+
+        // Graph pass
+        if let Some(job) = job {
+            job.start_phase("graph", options.sampling.map(|s| s.sample_size));
+        }
+        let mut sampler = options.sampling.map(ReservoirSampler::new);
+        for link_type in &link_types {
+            let mut cursor: Option<String> = None;
+            loop {
+                if let Some(job) = job {
+                    job.check_cancelled()?;
+                }
+                let page = self.graph.scan_links(&link_type.id, cursor.as_deref(), page_size).await?;
+                report.links_scanned += page.links.len();
+                for link in page.links {
+                    match &mut sampler {
+                        Some(sampler) => sampler.offer(link),
+                        None => {
+                            self.check_link(link_type, link, &mut findings).await?;
+                            report.links_checked += 1;
+                            if let Some(job) = job {
+                                job.advance(1);
+                            }
+                        }
+                    }
+                }
+                match page.next {
+                    Some(next) => cursor = Some(next),
+                    None => break,
+                }
+            }
+        }
+        if let Some(sampler) = sampler {
+            for link in sampler.into_sample() {
+                if let Some(link_type) = link_types.iter().find(|l| l.id == link.link_type_id) {
+                    self.check_link(link_type, link, &mut findings).await?;
+                    report.links_checked += 1;
+                    if let Some(job) = job {
+                        job.advance(1);
+                    }
+                }
+            }
+        }
+
+        report.missing_objects = findings
+            .exists
+            .iter()
+            .filter(|(_, exists)| !**exists)
+            .map(|((object_type, object_id), _)| MissingObject {
+                object_type: object_type.clone(),
+                object_id: object_id.clone(),
+            })
+            .collect();
+        report.missing_objects.sort();
+        report.dangling_links = findings.dangling.into_values().collect();
+
+        if options.repair && !report.is_consistent() {
+            if let Some(job) = job {
+                job.start_phase("repair", Some(report.missing_objects.len() + report.dangling_links.len()));
+            }
+            report.repair = Some(self.repair(&report, job).await);
+        }
+        Ok(report)
+    }
+
+    /// Verify the far end of every link stored for one object
+    async fn check_object(
+        &self,
+        object_type: &str,
+        object_id: &str,
+        link_types: &[LinkTypeDef],
+        findings: &mut Findings,
+    ) -> Result<(), StoreError> {
+        for link_type in link_types {
+            let direction = match (link_type.source == object_type, link_type.target == object_type) {
+                (true, true) => LinkDirection::Both,
+                (true, false) => LinkDirection::Outgoing,
+                _ => LinkDirection::Incoming,
+            };
+            let links = self
+                .graph
+                .get_links(object_id, Some(&link_type.id), Some(direction), &LinkQuery::default())
+                .await?;
+            for link in links {
+                self.check_link(link_type, link, findings).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Verify that both endpoints of a link exist, recording it as dangling if not
+    async fn check_link(&self, link_type: &LinkTypeDef, link: GraphLink, findings: &mut Findings) -> Result<(), StoreError> {
+        let mut missing = Vec::new();
+        for (object_type, object_id) in [(&link_type.source, &link.source_id), (&link_type.target, &link.target_id)] {
+            if !self.exists(object_type, object_id, findings).await? {
+                missing.push(MissingObject {
+                    object_type: object_type.clone(),
+                    object_id: object_id.clone(),
+                });
+            }
+        }
+        if !missing.is_empty() {
+            findings.dangling.entry(link.link_id.clone()).or_insert(DanglingLink {
+                link_id: link.link_id,
+                link_type_id: link.link_type_id,
+                source_id: link.source_id,
+                target_id: link.target_id,
+                missing,
+            });
+        }
+        Ok(())
+    }
+
+    async fn exists(&self, object_type: &str, object_id: &str, findings: &mut Findings) -> Result<bool, StoreError> {
+        let key = (object_type.to_string(), object_id.to_string());
+        if let Some(exists) = findings.exists.get(&key) {
+            return Ok(*exists);
+        }
+        let exists = self.search.get_object(object_type, object_id).await?.is_some();
+        findings.exists.insert(key, exists);
+        Ok(exists)
+    }
+
+    /// Reindex missing documents that the object source still knows about, then delete the
+    /// links that still have a missing endpoint
+    async fn repair(&self, report: &ConsistencyReport, job: Option<&JobHandle>) -> RepairSummary {
+        let mut summary = RepairSummary::default();
+        for missing in &report.missing_objects {
+            let state = self
+                .object_source
+                .as_ref()
+                .and_then(|source| source(&missing.object_type, &missing.object_id));
+            if let Some(properties) = state {
+                match self
+                    .search
+                    .index_object(&missing.object_type, &missing.object_id, &properties, None)
+                    .await
+                {
+                    Ok(_) => summary.objects_reindexed.push(missing.clone()),
+                    Err(e) => summary
+                        .failures
+                        .push(format!("reindex {}:{}: {}", missing.object_type, missing.object_id, e)),
+                }
+            }
+            if let Some(job) = job {
+                job.advance(1);
+            }
+        }
+
+        for link in &report.dangling_links {
+            let restored = link.missing.iter().all(|m| summary.objects_reindexed.contains(m));
+            if !restored {
+                match self.graph.delete_link(&link.link_id).await {
+                    Ok(()) => summary.links_deleted.push(link.link_id.clone()),
+                    Err(e) => summary.failures.push(format!("delete link {}: {}", link.link_id, e)),
+                }
+            }
+            if let Some(job) = job {
+                job.advance(1);
+            }
+        }
+        summary
+    }
+}
@@ -0,0 +1,403 @@
+//! Finding and merging duplicate objects according to their object type's dedup rules.
+//!
+//! `find_duplicates` scans an object type's documents and clusters them with
+//! `ontology_engine::find_duplicate_clusters`. `merge` folds duplicates (losers) into a
+//! surviving object (winner):
+//! - the winner's document is rewritten with the properties merged per the survivorship policy
+//! - links touching a loser are recreated on the winner and the originals deleted
+//! - object reference properties pointing at a loser are repointed at the winner, found
+//!   through the reverse-reference index when one is configured
+//! - each loser's document is soft-deleted: it is replaced by a redirect record in
+//!   `MERGE_REDIRECT_TYPE` keeping its last properties, so `resolve_merged` can map its ID
+//!   to the winner
+//!
+//! Each merge is reported to an optional event sink (typically the event log).
+
+use crate::jobs::JobHandle;
+use crate::references::ReferenceIndex;
+use crate::store::{Filter, FilterOperator, GraphStore, LinkDirection, LinkQuery, SearchStore, StoreError};
+use ontology_engine::{
+    find_duplicate_clusters, merge_duplicates, IndexingHint, ObjectRef, OntologyHandle, PropertyMap, PropertyType,
+    PropertyValue, ReferenceManager,
+};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::sync::Arc;
+
+/// Search store type holding redirect records of merged-away objects, keyed `type:id`
+pub const MERGE_REDIRECT_TYPE: &str = "_merge_redirect";
+
+/// Job kind recorded for duplicate searches
+pub const FIND_DUPLICATES_JOB_KIND: &str = "find_duplicates";
+
+/// Redirects followed before `resolve_merged` gives up on a chain
+const MAX_REDIRECT_HOPS: usize = 16;
+
+/// Default number of search documents read per page
+pub const DEFAULT_DEDUP_PAGE_SIZE: usize = 500;
+
+/// Candidate duplicate clusters for an object type
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DuplicateReport {
+    pub object_type: String,
+    pub objects_scanned: usize,
+    /// Object IDs of each cluster, sorted
+    pub clusters: Vec<Vec<String>>,
+}
+
+/// What a merge did
+#[derive(Debug, Clone, Serialize)]
+pub struct MergeRecord {
+    pub object_type: String,
+    pub winner_id: String,
+    pub loser_ids: Vec<String>,
+    /// Winner's properties after the merge
+    pub merged_properties: PropertyMap,
+    pub links_repointed: usize,
+    /// Objects whose reference properties were repointed at the winner
+    pub references_repointed: usize,
+}
+
+/// Receives every completed merge
+pub type MergeEventSink = Arc<dyn Fn(&MergeRecord) + Send + Sync>;
+
+/// Finds and merges duplicates of an object type across the search and graph stores
+#[derive(Clone)]
+pub struct Deduplicator {
+    ontology: OntologyHandle,
+    search: Arc<dyn SearchStore>,
+    graph: Arc<dyn GraphStore>,
+    event_sink: Option<MergeEventSink>,
+    reference_index: Option<ReferenceIndex>,
+    page_size: usize,
+}
+
+impl Deduplicator {
+    pub fn new(ontology: OntologyHandle, search: Arc<dyn SearchStore>, graph: Arc<dyn GraphStore>) -> Self {
+        Self {
+            ontology,
+            search,
+            graph,
+            event_sink: None,
+            reference_index: None,
+            page_size: DEFAULT_DEDUP_PAGE_SIZE,
+        }
+    }
+
+    /// Where completed merges are reported
+    pub fn with_event_sink(mut self, sink: MergeEventSink) -> Self {
+        self.event_sink = Some(sink);
+        self
+    }
+
+    /// Find objects referencing a loser through the reverse-reference index instead of
+    /// scanning every type with reference properties
+    pub fn with_reference_index(mut self, index: ReferenceIndex) -> Self {
+        self.reference_index = Some(index);
+        self
+    }
+
+    pub fn with_page_size(mut self, page_size: usize) -> Self {
+        self.page_size = page_size.max(1);
+        self
+    }
+
+    /// Scan every object of the type and cluster the duplicates
+    pub async fn find_duplicates(
+        &self,
+        object_type: &str,
+        job: Option<&JobHandle>,
+    ) -> Result<DuplicateReport, StoreError> {
+        let object_type_def = self
+            .ontology
+            .load()
+            .get_object_type(object_type)
+            .cloned()
+            .ok_or_else(|| StoreError::NotFound(format!("Object type '{}'", object_type)))?;
+        if object_type_def.dedup_rules.as_ref().is_none_or(|rules| rules.is_empty()) {
+            return Err(StoreError::Configuration(format!(
+                "Object type '{}' has no dedup rules",
+                object_type
+            )));
+        }
+
+        if let Some(job) = job {
+            let total = self.search.count_objects(object_type, None).await.ok().map(|n| n as usize);
+            job.start_phase("scan", total);
+        }
+        let mut records = Vec::new();
+        let mut cursor: Option<String> = None;
+        loop {
+            if let Some(job) = job {
+                job.check_cancelled()?;
+            }
+            let page = self.search.scan_objects(object_type, &[], cursor.as_deref(), self.page_size).await?;
+            if let Some(job) = job {
+                job.advance(page.objects.len());
+            }
+            records.extend(page.objects.into_iter().map(|o| (o.object_id, o.properties)));
+            match page.next {
+                Some(next) => cursor = Some(next),
+                None => break,
+            }
+        }
+
+        if let Some(job) = job {
+            job.start_phase("cluster", None);
+        }
+        Ok(DuplicateReport {
+            object_type: object_type.to_string(),
+            objects_scanned: records.len(),
+            clusters: find_duplicate_clusters(&object_type_def, &records),
+        })
+    }
+
+    /// Merge `loser_ids` into `winner_id`
+    pub async fn merge(
+        &self,
+        object_type: &str,
+        winner_id: &str,
+        loser_ids: &[String],
+    ) -> Result<MergeRecord, StoreError> {
+        let ontology = self.ontology.load();
+        let object_type_def = ontology
+            .get_object_type(object_type)
+            .cloned()
+            .ok_or_else(|| StoreError::NotFound(format!("Object type '{}'", object_type)))?;
+        if loser_ids.is_empty() {
+            return Err(StoreError::Validation("Nothing to merge: no loser IDs given".to_string()));
+        }
+        if loser_ids.iter().any(|id| id == winner_id) {
+            return Err(StoreError::Validation(format!("Object '{}' cannot be merged into itself", winner_id)));
+        }
+
+        let winner = self
+            .search
+            .get_object(object_type, winner_id)
+            .await?
+            .ok_or_else(|| StoreError::NotFound(format!("{} '{}'", object_type, winner_id)))?;
+        let mut losers = Vec::new();
+        for loser_id in loser_ids {
+            let loser = self
+                .search
+                .get_object(object_type, loser_id)
+                .await?
+                .ok_or_else(|| StoreError::NotFound(format!("{} '{}'", object_type, loser_id)))?;
+            losers.push(loser);
+        }
+        let loser_properties: Vec<&PropertyMap> = losers.iter().map(|l| &l.properties).collect();
+        let merged = merge_duplicates(&object_type_def, &winner.properties, &loser_properties);
+        self.search.index_object(object_type, winner_id, &merged, None).await?;
+
+        // Links: recreate every link touching a loser on the winner
+        let mut links_repointed = 0;
+        let mut seen = HashSet::new();
+        let link_types: Vec<_> = ontology
+            .link_types()
+            .filter(|l| l.source == object_type || l.target == object_type)
+            .cloned()
+            .collect();
+        drop(ontology);
+        let is_loser = |id: &str| loser_ids.iter().any(|l| l == id);
+        for link_type in &link_types {
+            let existing = self
+                .graph
+                .get_links(winner_id, Some(&link_type.id), Some(LinkDirection::Both), &LinkQuery::default())
+                .await?;
+            let mut endpoints: HashSet<(String, String)> =
+                existing.into_iter().map(|l| (l.source_id, l.target_id)).collect();
+            for loser_id in loser_ids {
+                let links = self
+                    .graph
+                    .get_links(loser_id, Some(&link_type.id), Some(LinkDirection::Both), &LinkQuery::default())
+                    .await?;
+                for link in links {
+                    if !seen.insert(link.link_id.clone()) {
+                        continue;
+                    }
+                    let repoint = |end_type: &str, id: &str| {
+                        if end_type == object_type && is_loser(id) { winner_id.to_string() } else { id.to_string() }
+                    };
+                    let source = repoint(&link_type.source, &link.source_id);
+                    let target = repoint(&link_type.target, &link.target_id);
+                    // Links between duplicates collapse, and the winner may already have the link
+                    let keep = source != target && endpoints.insert((source.clone(), target.clone()));
+                    if keep {
+                        self.graph.create_link(&link_type.id, &source, &target, &link.properties).await?;
+                    }
+                    self.graph.delete_link(&link.link_id).await?;
+                    links_repointed += 1;
+                }
+            }
+        }
+
+        // Object references: collect first, since updated objects drop out of the filter
+        let references_repointed = self.repoint_references(object_type, winner_id, loser_ids).await?;
+
+        // Soft-delete the losers behind redirects
+        let merged_at = chrono::Utc::now().to_rfc3339();
+        for loser in &losers {
+            let mut redirect = PropertyMap::new();
+            redirect.insert("object_type".to_string(), PropertyValue::String(object_type.to_string()));
+            redirect.insert("object_id".to_string(), PropertyValue::String(loser.object_id.clone()));
+            redirect.insert("merged_into".to_string(), PropertyValue::String(winner_id.to_string()));
+            redirect.insert("merged_at".to_string(), PropertyValue::DateTime(merged_at.clone()));
+            let snapshot = serde_json::to_string(&loser.properties).map_err(|e| StoreError::Serialization(e.to_string()))?;
+            redirect.insert("snapshot".to_string(), PropertyValue::String(snapshot));
+            self.search
+                .index_object(MERGE_REDIRECT_TYPE, &redirect_id(object_type, &loser.object_id), &redirect, None)
+                .await?;
+            self.search.delete_object(object_type, &loser.object_id).await?;
+        }
+
+        let record = MergeRecord {
+            object_type: object_type.to_string(),
+            winner_id: winner_id.to_string(),
+            loser_ids: loser_ids.to_vec(),
+            merged_properties: merged,
+            links_repointed,
+            references_repointed,
+        };
+        if let Some(sink) = &self.event_sink {
+            sink(&record);
+        }
+        Ok(record)
+    }
+
+    /// Point object reference properties (of any type) that reference a loser at the winner
+    async fn repoint_references(
+        &self,
+        object_type: &str,
+        winner_id: &str,
+        loser_ids: &[String],
+    ) -> Result<usize, StoreError> {
+        if let Some(index) = &self.reference_index {
+            return self.repoint_indexed_references(index, object_type, winner_id, loser_ids).await;
+        }
+        let referenced: Vec<PropertyValue> = loser_ids
+            .iter()
+            .flat_map(|id| [id.clone(), ObjectRef::new(object_type, id).to_string()])
+            .map(PropertyValue::String)
+            .collect();
+        let reference_properties: Vec<(String, String)> = self
+            .ontology
+            .load()
+            .object_types()
+            .flat_map(|o| {
+                o.properties
+                    .iter()
+                    .filter(|p| matches!(p.property_type, PropertyType::ObjectReference | PropertyType::ObjectReferenceAlt))
+                    .filter(|p| p.indexing_hint() != IndexingHint::NotIndexed)
+                    .map(|p| (o.id.clone(), p.id.clone()))
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+
+        let mut repointed = 0;
+        for (referencing_type, property) in reference_properties {
+            let filters = [Filter {
+                property: property.clone(),
+                operator: FilterOperator::In,
+                value: PropertyValue::Array(referenced.clone()),
+                distance: None,
+                case_insensitive: false,
+            }];
+            let mut matches = Vec::new();
+            let mut cursor: Option<String> = None;
+            loop {
+                let page = self
+                    .search
+                    .scan_objects(&referencing_type, &filters, cursor.as_deref(), self.page_size)
+                    .await?;
+                matches.extend(page.objects);
+                match page.next {
+                    Some(next) => cursor = Some(next),
+                    None => break,
+                }
+            }
+            for mut object in matches {
+                let Some(value) = object.properties.get(&property) else {
+                    continue;
+                };
+                let Some(new_value) = ReferenceManager::repoint_reference(value, object_type, loser_ids, winner_id) else {
+                    continue;
+                };
+                object.properties.insert(property.clone(), new_value);
+                self.search
+                    .index_object(&referencing_type, &object.object_id, &object.properties, None)
+                    .await?;
+                repointed += 1;
+            }
+        }
+        Ok(repointed)
+    }
+
+    /// `repoint_references` for the objects the reverse-reference index lists for the losers
+    async fn repoint_indexed_references(
+        &self,
+        index: &ReferenceIndex,
+        object_type: &str,
+        winner_id: &str,
+        loser_ids: &[String],
+    ) -> Result<usize, StoreError> {
+        let mut sources: BTreeMap<(String, String), BTreeSet<String>> = BTreeMap::new();
+        for loser_id in loser_ids {
+            for source in index.references_to(object_type, loser_id).await? {
+                sources.entry((source.object_type, source.object_id)).or_default().insert(source.property);
+            }
+        }
+
+        let mut repointed = 0;
+        for ((referencing_type, referencing_id), properties) in sources {
+            let Some(mut object) = self.search.get_object(&referencing_type, &referencing_id).await? else {
+                continue;
+            };
+            let mut changed = false;
+            for property in properties {
+                let new_value = object
+                    .properties
+                    .get(&property)
+                    .and_then(|value| ReferenceManager::repoint_reference(value, object_type, loser_ids, winner_id));
+                if let Some(new_value) = new_value {
+                    object.properties.insert(property, new_value);
+                    changed = true;
+                }
+            }
+            if changed {
+                self.search
+                    .index_object(&referencing_type, &referencing_id, &object.properties, None)
+                    .await?;
+                repointed += 1;
+            }
+        }
+        Ok(repointed)
+    }
+}
+
+fn redirect_id(object_type: &str, object_id: &str) -> String {
+    format!("{}:{}", object_type, object_id)
+}
+
+/// The object a merged-away ID now resolves to, following chains of merges. `None` if the
+/// ID was never merged.
+pub async fn resolve_merged(
+    search: &dyn SearchStore,
+    object_type: &str,
+    object_id: &str,
+) -> Result<Option<String>, StoreError> {
+    let mut current = object_id.to_string();
+    let mut resolved = None;
+    for _ in 0..MAX_REDIRECT_HOPS {
+        let Some(redirect) = search.get_object(MERGE_REDIRECT_TYPE, &redirect_id(object_type, &current)).await? else {
+            break;
+        };
+        match redirect.properties.get("merged_into") {
+            Some(PropertyValue::String(next)) => {
+                current = next.clone();
+                resolved = Some(current.clone());
+            }
+            _ => break,
+        }
+    }
+    Ok(resolved)
+}
@@ -0,0 +1,288 @@
+//! Background exports of an object type's documents to a file.
+//!
+//! An export runs as a job: it scans the search store page by page (`scan_objects`) and
+//! appends each page to `<output dir>/<job id>.<ext>.part`, renaming it to the final name
+//! once the scan completes. JSONL rows are objects of the type's declared properties and CSV
+//! columns are those properties in declaration order, so both can be fed back to the data
+//! loader.
+
+use crate::jobs::{JobHandle, JobRegistry, JobStatus};
+use crate::store::{Filter, SearchStore, StoreError};
+use ontology_engine::{ObjectType, OntologyHandle};
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::io::{AsyncWriteExt, BufWriter};
+
+/// Job kind recorded for exports
+pub const EXPORT_JOB_KIND: &str = "export";
+
+/// Default number of documents read per page
+pub const DEFAULT_EXPORT_PAGE_SIZE: usize = 1000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Jsonl,
+    Csv,
+}
+
+impl ExportFormat {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "jsonl" | "ndjson" => Some(ExportFormat::Jsonl),
+            "csv" => Some(ExportFormat::Csv),
+            _ => None,
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Jsonl => "jsonl",
+            ExportFormat::Csv => "csv",
+        }
+    }
+}
+
+/// What to export
+#[derive(Debug, Clone)]
+pub struct ExportRequest {
+    pub object_type: String,
+    pub filters: Vec<Filter>,
+    pub format: ExportFormat,
+    /// User the export runs for, recorded on its job
+    pub requested_by: Option<String>,
+}
+
+/// Result of a completed export, stored as the job result
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportOutput {
+    /// Server path of the exported file
+    pub path: String,
+    pub format: ExportFormat,
+    pub rows: usize,
+    pub bytes: u64,
+}
+
+/// Rewrites each exported object's properties before they are written (e.g. masking)
+pub type ExportTransform = Arc<dyn Fn(&ObjectType, JsonValue) -> JsonValue + Send + Sync>;
+
+/// Runs exports into files under an output directory
+#[derive(Clone)]
+pub struct Exporter {
+    ontology: OntologyHandle,
+    search: Arc<dyn SearchStore>,
+    output_dir: PathBuf,
+    page_size: usize,
+    transform: Option<ExportTransform>,
+}
+
+impl Exporter {
+    pub fn new(ontology: OntologyHandle, search: Arc<dyn SearchStore>, output_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            ontology,
+            search,
+            output_dir: output_dir.into(),
+            page_size: DEFAULT_EXPORT_PAGE_SIZE,
+            transform: None,
+        }
+    }
+
+    pub fn with_page_size(mut self, page_size: usize) -> Self {
+        self.page_size = page_size.max(1);
+        self
+    }
+
+    pub fn with_transform(mut self, transform: ExportTransform) -> Self {
+        self.transform = Some(transform);
+        self
+    }
+
+    /// Register an export job and run it in the background. Returns the job ID.
+    pub fn start(&self, jobs: &JobRegistry, request: ExportRequest) -> Result<String, StoreError> {
+        if self.ontology.load().get_object_type(&request.object_type).is_none() {
+            return Err(StoreError::NotFound(format!("Object type '{}'", request.object_type)));
+        }
+        let job = jobs.start_for(EXPORT_JOB_KIND, request.requested_by.clone());
+        let job_id = job.id().to_string();
+        let exporter = self.clone();
+        tokio::spawn(async move {
+            match exporter.run(&request, &job).await {
+                Ok(output) => job.complete(serde_json::to_value(&output).unwrap_or(JsonValue::Null)),
+                Err(e) => job.fail(e.to_string()),
+            }
+        });
+        Ok(job_id)
+    }
+
+    /// Export into `<output dir>/<job id>.<ext>`, reporting progress on `job`
+    pub async fn run(&self, request: &ExportRequest, job: &JobHandle) -> Result<ExportOutput, StoreError> {
+        let object_type = self
+            .ontology
+            .load()
+            .get_object_type(&request.object_type)
+            .cloned()
+            .ok_or_else(|| StoreError::NotFound(format!("Object type '{}'", request.object_type)))?;
+
+        tokio::fs::create_dir_all(&self.output_dir)
+            .await
+            .map_err(|e| StoreError::WriteError(format!("Failed to create {}: {}", self.output_dir.display(), e)))?;
+        let path = self
+            .output_dir
+            .join(format!("{}.{}", job.id(), request.format.extension()));
+        let partial = partial_path(&path);
+
+        let result = self.write_file(request, &object_type, &partial, job).await;
+        let rows = match result {
+            Ok(rows) => rows,
+            Err(e) => {
+                let _ = tokio::fs::remove_file(&partial).await;
+                return Err(e);
+            }
+        };
+        tokio::fs::rename(&partial, &path)
+            .await
+            .map_err(|e| StoreError::WriteError(format!("Failed to finish {}: {}", path.display(), e)))?;
+        let bytes = tokio::fs::metadata(&path).await.map(|m| m.len()).unwrap_or(0);
+
+        Ok(ExportOutput {
+            path: path.display().to_string(),
+            format: request.format,
+            rows,
+            bytes,
+        })
+    }
+
+    async fn write_file(
+        &self,
+        request: &ExportRequest,
+        object_type: &ObjectType,
+        path: &Path,
+        job: &JobHandle,
+    ) -> Result<usize, StoreError> {
+        let total = self
+            .search
+            .count_objects(&request.object_type, Some(&request.filters))
+            .await
+            .ok()
+            .map(|n| n as usize);
+        job.start_phase("export", total);
+
+        let write_error = |e: std::io::Error| StoreError::WriteError(format!("Failed to write {}: {}", path.display(), e));
+        let mut writer = BufWriter::new(tokio::fs::File::create(path).await.map_err(write_error)?);
+        let columns: Vec<&str> = object_type.properties.iter().map(|p| p.id.as_str()).collect();
+        if request.format == ExportFormat::Csv {
+            let header: Vec<String> = columns.iter().map(|c| csv_field(c)).collect();
+            writer.write_all(format!("{}\n", header.join(",")).as_bytes()).await.map_err(write_error)?;
+        }
+
+        let mut rows = 0;
+        let mut cursor: Option<String> = None;
+        loop {
+            job.check_cancelled()?;
+            let page = self
+                .search
+                .scan_objects(&request.object_type, &request.filters, cursor.as_deref(), self.page_size)
+                .await?;
+            let fetched = page.objects.len();
+            // Each page is formatted, then written in one go
+            let mut lines = String::new();
+            for object in page.objects {
+                // Only declared properties; index-only fields such as ACL terms stay out
+                let mut properties = object
+                    .properties
+                    .iter()
+                    .filter(|(key, _)| object_type.get_property(key).is_some())
+                    .map(|(key, value)| serde_json::to_value(value).map(|value| (key.clone(), value)))
+                    .collect::<Result<serde_json::Map<_, _>, _>>()
+                    .map(JsonValue::Object)
+                    .map_err(|e| StoreError::Serialization(e.to_string()))?;
+                if let Some(transform) = &self.transform {
+                    properties = transform(object_type, properties);
+                }
+                match request.format {
+                    ExportFormat::Jsonl => lines.push_str(&properties.to_string()),
+                    ExportFormat::Csv => {
+                        let fields: Vec<String> = columns
+                            .iter()
+                            .map(|c| csv_value(properties.get(*c).unwrap_or(&JsonValue::Null)))
+                            .collect();
+                        lines.push_str(&fields.join(","));
+                    }
+                }
+                lines.push('\n');
+            }
+            writer.write_all(lines.as_bytes()).await.map_err(write_error)?;
+            rows += fetched;
+            job.advance(fetched);
+            match page.next {
+                Some(next) => cursor = Some(next),
+                None => break,
+            }
+        }
+        writer.flush().await.map_err(write_error)?;
+        Ok(rows)
+    }
+
+    /// Drop jobs past their retention, deleting the files of expired exports, and remove
+    /// partial files left behind by exports that are no longer running (e.g. after a
+    /// restart). Returns the number of files deleted. This does blocking file I/O; run it
+    /// off the async runtime's worker threads.
+    pub fn cleanup(&self, jobs: &JobRegistry) -> usize {
+        let mut deleted = 0;
+        for job in jobs.prune_expired() {
+            let path = job
+                .result
+                .and_then(|result| serde_json::from_value::<ExportOutput>(result).ok())
+                .map(|output| output.path);
+            if job.kind == EXPORT_JOB_KIND && path.is_some_and(|path| std::fs::remove_file(path).is_ok()) {
+                deleted += 1;
+            }
+        }
+
+        let Ok(entries) = std::fs::read_dir(&self.output_dir) else {
+            return deleted;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("part") {
+                continue;
+            }
+            let job_id = path
+                .file_name()
+                .and_then(|f| f.to_str())
+                .and_then(|f| f.split('.').next())
+                .unwrap_or_default();
+            let running = jobs.get(job_id).is_some_and(|job| job.status == JobStatus::Running);
+            if !running && std::fs::remove_file(&path).is_ok() {
+                deleted += 1;
+            }
+        }
+        deleted
+    }
+}
+
+fn partial_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".part");
+    path.with_file_name(name)
+}
+
+/// A CSV cell for a property value: strings as-is, nested values as JSON, null as empty
+fn csv_value(value: &JsonValue) -> String {
+    match value {
+        JsonValue::Null => String::new(),
+        JsonValue::String(s) => csv_field(s),
+        other => csv_field(&other.to_string()),
+    }
+}
+
+/// Quote a CSV field if it contains a separator, quote or line break
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
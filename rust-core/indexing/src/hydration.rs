@@ -1,14 +1,68 @@
 use crate::store::{SearchStore, GraphStore, IndexedObject, StoreError};
-use ontology_engine::{ObjectType, PropertyMap};
+use ontology_engine::{
+    CompiledComputedProperty, ComputedPropertyEvaluator, ComputedPropertyMaterializer, GeometryOutput, ObjectType,
+    Ontology, Property, PropertyMap, PropertyType, PropertyValue,
+};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 /// Object hydrator - converts indexed data back into full object representations
 pub struct ObjectHydrator {
-    // In a real implementation, might need access to ontology for validation
+    /// Computed property definitions compiled so far
+    compilations: AtomicUsize,
+    /// Properties of referenced objects returned with their titles
+    reference_summary: Vec<String>,
 }
 
+/// What hydration adds to the stored properties
+#[derive(Debug, Clone, Copy)]
+pub struct HydrationOptions {
+    /// Evaluate the type's on-read computed properties into each object
+    pub include_computed: bool,
+    /// Resolve object references into the referenced objects' titles; see
+    /// [`ObjectHydrator::resolve_references`]
+    pub resolve_references: bool,
+    /// How GeoJSON properties are returned: simplified, rounded or as bounding boxes
+    pub geometry: GeometryOutput,
+    /// Fill in model-bound properties with predictions; see
+    /// [`crate::model_properties::ModelPropertyEvaluator`]
+    pub include_predictions: bool,
+}
+
+impl Default for HydrationOptions {
+    fn default() -> Self {
+        Self {
+            include_computed: true,
+            resolve_references: false,
+            geometry: GeometryOutput::default(),
+            include_predictions: false,
+        }
+    }
+}
+
+/// A type's on-read computed properties, compiled once for a call; definitions that do not
+/// compile keep their error
+type ReadComputed = Vec<(String, Result<CompiledComputedProperty, String>)>;
+
 impl ObjectHydrator {
     pub fn new() -> Self {
-        Self {}
+        Self {
+            compilations: AtomicUsize::new(0),
+            reference_summary: Vec::new(),
+        }
+    }
+    
+    /// Properties of referenced objects to return alongside their titles when references
+    /// are resolved
+    pub fn with_reference_summary(mut self, properties: Vec<String>) -> Self {
+        self.reference_summary = properties;
+        self
+    }
+    
+    /// How many computed property definitions this hydrator has compiled. Each call compiles
+    /// a type's definitions once, however many objects it hydrates.
+    pub fn compilations(&self) -> usize {
+        self.compilations.load(Ordering::Relaxed)
     }
     
     /// Hydrate an object from search index results
@@ -16,6 +70,27 @@ impl ObjectHydrator {
         &self,
         indexed: &IndexedObject,
         object_type: &ObjectType,
+    ) -> Result<HydratedObject, StoreError> {
+        self.hydrate_from_indexed_with(indexed, object_type, HydrationOptions::default())
+    }
+    
+    /// Hydrate an object from search index results with the given options
+    pub fn hydrate_from_indexed_with(
+        &self,
+        indexed: &IndexedObject,
+        object_type: &ObjectType,
+        options: HydrationOptions,
+    ) -> Result<HydratedObject, StoreError> {
+        let computed = self.read_computed(object_type, options);
+        self.hydrate_indexed(indexed, object_type, &computed, options.geometry)
+    }
+    
+    fn hydrate_indexed(
+        &self,
+        indexed: &IndexedObject,
+        object_type: &ObjectType,
+        computed: &ReadComputed,
+        geometry: GeometryOutput,
     ) -> Result<HydratedObject, StoreError> {
         // Validate that all required properties are present
         for prop_def in &object_type.properties {
@@ -29,18 +104,132 @@ impl ObjectHydrator {
             }
         }
         
+        Ok(self.hydrate(&indexed.object_type, &indexed.object_id, indexed.properties.clone(), object_type, computed, geometry))
+    }
+    
+    /// Hydrate an object from a raw JSON row (e.g. an in-memory dataset), coercing its
+    /// values to the declared property types and applying defaults
+    pub fn hydrate_from_json(
+        &self,
+        json: &serde_json::Value,
+        object_type: &ObjectType,
+    ) -> Result<HydratedObject, StoreError> {
+        self.hydrate_from_json_with(json, object_type, HydrationOptions::default())
+    }
+    
+    /// Hydrate an object from a raw JSON row with the given options
+    pub fn hydrate_from_json_with(
+        &self,
+        json: &serde_json::Value,
+        object_type: &ObjectType,
+        options: HydrationOptions,
+    ) -> Result<HydratedObject, StoreError> {
+        let properties = object_type.instantiate_from_json(json).map_err(|errors| {
+            StoreError::Query(format!(
+                "Invalid object of type '{}': {}",
+                object_type.id,
+                errors.join("; ")
+            ))
+        })?;
+        let object_id = properties
+            .get(&object_type.primary_key)
+            .map(|v| v.to_string())
+            .ok_or_else(|| StoreError::Query(format!(
+                "Object of type '{}' has no primary key '{}'",
+                object_type.id, object_type.primary_key
+            )))?;
+        let computed = self.read_computed(object_type, options);
+        Ok(self.hydrate(&object_type.id, &object_id, properties, object_type, &computed, options.geometry))
+    }
+    
+    /// Compile the type's on-read computed properties; materialized ones are stored
+    fn read_computed(&self, object_type: &ObjectType, options: HydrationOptions) -> ReadComputed {
+        if !options.include_computed {
+            return Vec::new();
+        }
+        object_type.computed_properties.iter()
+            .filter(|computed| !computed.is_materialized())
+            .map(|computed| {
+                self.compilations.fetch_add(1, Ordering::Relaxed);
+                let compiled = ComputedPropertyEvaluator::compile(computed).map_err(|e| e.to_string());
+                (computed.id.clone(), compiled)
+            })
+            .collect()
+    }
+    
+    fn hydrate(
+        &self,
+        object_type_id: &str,
+        object_id: &str,
+        mut properties: PropertyMap,
+        object_type: &ObjectType,
+        computed: &ReadComputed,
+        geometry: GeometryOutput,
+    ) -> HydratedObject {
+        // Recompute materialized values whose freshness window has passed
+        for (property, error) in ComputedPropertyMaterializer::refresh_stale(object_type, &mut properties, chrono::Utc::now()) {
+            tracing::error!("Error refreshing computed property {} on {}: {}", property, object_id, error);
+        }
+        
+        // On-read computed properties see the stored values only; one that fails is null
+        let mut warnings = Vec::new();
+        let values: Vec<(String, PropertyValue)> = computed.iter()
+            .map(|(id, compiled)| {
+                let value = compiled.as_ref().map_err(|e| e.clone()).and_then(|compiled| {
+                    compiled
+                        .evaluate(&properties, None::<fn(&str, &str) -> Option<PropertyValue>>)
+                        .map_err(|e| e.to_string())
+                });
+                let value = value.unwrap_or_else(|e| {
+                    warnings.push(format!("Computed property '{}' on {}: {}", id, object_id, e));
+                    PropertyValue::Null
+                });
+                (id.clone(), value)
+            })
+            .collect();
+        for (id, value) in values {
+            properties.insert(id, value);
+        }
+        let computed = object_type.computed_properties.iter()
+            .filter(|computed| properties.contains_key(&computed.id))
+            .map(|computed| computed.id.clone())
+            .collect();
+        
+        // References are returned as `object_type:object_id`, whichever form was stored
+        for prop_def in object_type.properties.iter().filter(|p| p.reference_target.is_some()) {
+            if let Some(mut value) = properties.get(&prop_def.id).cloned() {
+                normalize_references(prop_def, &mut value);
+                properties.insert(prop_def.id.clone(), value);
+            }
+        }
+        
         // Build title from title_key if specified
         let title = object_type.title_key.as_ref()
-            .and_then(|key| indexed.properties.get(key))
+            .and_then(|key| properties.get(key))
             .map(|v| v.to_string())
-            .unwrap_or_else(|| indexed.object_id.clone());
+            .unwrap_or_else(|| object_id.to_string());
+        
+        // Geometries are shrunk last, after computed properties have read them
+        if !geometry.is_full() {
+            let geometry_properties = object_type.properties.iter()
+                .filter(|p| matches!(p.property_type, PropertyType::GeoJSON | PropertyType::GeoJSONAlt));
+            for prop_def in geometry_properties {
+                if let Some(PropertyValue::GeoJSON(gj)) = properties.get(&prop_def.id) {
+                    let shaped = geometry.apply(gj);
+                    properties.insert(prop_def.id.clone(), shaped);
+                }
+            }
+        }
         
-        Ok(HydratedObject {
-            object_type: indexed.object_type.clone(),
-            object_id: indexed.object_id.clone(),
+        HydratedObject {
+            object_type: object_type_id.to_string(),
+            object_id: object_id.to_string(),
             title,
-            properties: indexed.properties.clone(),
-        })
+            properties,
+            computed,
+            warnings,
+            references: HashMap::new(),
+        }
     }
     
     /// Bulk hydrate multiple objects
@@ -49,11 +238,89 @@ impl ObjectHydrator {
         indexed_objects: &[IndexedObject],
         object_type: &ObjectType,
     ) -> Result<Vec<HydratedObject>, StoreError> {
+        self.hydrate_batch_with(indexed_objects, object_type, HydrationOptions::default())
+    }
+    
+    /// Bulk hydrate multiple objects with the given options, compiling computed properties
+    /// once for the whole batch
+    pub fn hydrate_batch_with(
+        &self,
+        indexed_objects: &[IndexedObject],
+        object_type: &ObjectType,
+        options: HydrationOptions,
+    ) -> Result<Vec<HydratedObject>, StoreError> {
+        let computed = self.read_computed(object_type, options);
         indexed_objects.iter()
-            .map(|idx| self.hydrate_from_indexed(idx, object_type))
+            .map(|idx| self.hydrate_indexed(idx, object_type, &computed, options.geometry))
             .collect()
     }
     
+    /// Resolve the object references of hydrated objects of `object_type` into the referenced
+    /// objects' titles and summary properties, with one multi-get per target type for the
+    /// whole batch. References are read as their property's `referenceTarget` unless they
+    /// name their type. Only the references of `objects` are resolved, not those of the
+    /// objects they point to. Dangling references, and references to objects `visible`
+    /// rejects, resolve without a title.
+    pub async fn resolve_references(
+        &self,
+        objects: &mut [HydratedObject],
+        object_type: &ObjectType,
+        ontology: &Ontology,
+        search_store: &dyn SearchStore,
+        visible: &(dyn Fn(&IndexedObject) -> bool + Send + Sync),
+    ) -> Result<(), StoreError> {
+        let mut targets: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+        for object in objects.iter() {
+            for prop_def in &object_type.properties {
+                if let Some(value) = object.properties.get(&prop_def.id) {
+                    for reference in references_in(prop_def, value) {
+                        targets.entry(reference.object_type).or_default().insert(reference.object_id);
+                    }
+                }
+            }
+        }
+        
+        let mut resolved = HashMap::new();
+        for (target_type, ids) in targets {
+            let ids: Vec<String> = ids.into_iter().collect();
+            let found = search_store.get_objects(&target_type, &ids).await?;
+            let title_key = ontology.get_object_type(&target_type).and_then(|t| t.title_key.as_ref());
+            for object_id in ids {
+                let target = found.get(&object_id).filter(|target| visible(target));
+                let title = target.map(|target| {
+                    title_key
+                        .and_then(|key| target.properties.get(key))
+                        .map(|v| v.to_string())
+                        .unwrap_or_else(|| object_id.clone())
+                });
+                let mut summary = PropertyMap::new();
+                if let Some(target) = target {
+                    for key in &self.reference_summary {
+                        if let Some(value) = target.properties.get(key) {
+                            summary.insert(key.clone(), value.clone());
+                        }
+                    }
+                }
+                let reference = ResolvedReference { object_type: target_type.clone(), object_id, title, summary };
+                resolved.insert(format!("{}:{}", reference.object_type, reference.object_id), reference);
+            }
+        }
+        
+        for object in objects.iter_mut() {
+            for prop_def in &object_type.properties {
+                if let Some(value) = object.properties.get(&prop_def.id) {
+                    for reference in references_in(prop_def, value) {
+                        let key = reference.to_string();
+                        if let Some(resolved) = resolved.get(&key) {
+                            object.references.insert(key, resolved.clone());
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+    
     /// Get linked objects from graph store and hydrate them
     pub async fn get_and_hydrate_linked(
         &self,
@@ -78,7 +345,7 @@ impl ObjectHydrator {
                 match self.hydrate_from_indexed(&indexed, object_type) {
                     Ok(obj) => hydrated.push(obj),
                     Err(e) => {
-                        eprintln!("Error hydrating object {}: {}", id, e);
+                        tracing::error!("Error hydrating object {}: {}", id, e);
                         // Continue with other objects
                     }
                 }
@@ -95,6 +362,58 @@ impl Default for ObjectHydrator {
     }
 }
 
+/// Rewrite the object references in a value of `property` to their `object_type:object_id`
+/// form, element-wise for arrays
+fn normalize_references(property: &Property, value: &mut PropertyValue) {
+    match value {
+        PropertyValue::ObjectReference(reference) => {
+            if let Ok(parsed) = property.parse_reference(reference) {
+                *reference = parsed.to_string();
+            }
+        }
+        PropertyValue::Array(items) => {
+            for item in items {
+                normalize_references(property, item);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// The object references held in a value of `property`, element-wise for arrays; values
+/// that do not parse as references are skipped
+fn references_in(property: &Property, value: &PropertyValue) -> Vec<ontology_engine::ObjectRef> {
+    match value {
+        PropertyValue::ObjectReference(reference) => property.parse_reference(reference).into_iter().collect(),
+        PropertyValue::Array(items) => items.iter().flat_map(|item| references_in(property, item)).collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// A referenced object as returned with the reference
+#[derive(Debug, Clone)]
+pub struct ResolvedReference {
+    pub object_type: String,
+    pub object_id: String,
+    /// `None` if the referenced object does not exist or may not be seen
+    pub title: Option<String>,
+    /// The hydrator's summary properties that the referenced object has
+    pub summary: PropertyMap,
+}
+
+impl ResolvedReference {
+    /// `{"id": ..., "title": ...}` plus the summary properties
+    pub fn to_json_value(&self) -> serde_json::Value {
+        let mut map = serde_json::Map::new();
+        for (key, value) in self.summary.iter() {
+            map.insert(key.clone(), property_value_to_json(value));
+        }
+        map.insert("id".to_string(), format!("{}:{}", self.object_type, self.object_id).into());
+        map.insert("title".to_string(), self.title.clone().into());
+        map.into()
+    }
+}
+
 /// A fully hydrated object ready for API responses
 #[derive(Debug, Clone)]
 pub struct HydratedObject {
@@ -102,6 +421,13 @@ pub struct HydratedObject {
     pub object_id: String,
     pub title: String,
     pub properties: PropertyMap,
+    /// IDs of the computed properties among `properties`, evaluated on read or materialized
+    pub computed: Vec<String>,
+    /// Computed properties that could not be evaluated and were returned as null, and
+    /// model-bound properties whose prediction was skipped or failed
+    pub warnings: Vec<String>,
+    /// Resolved references by `object_type:object_id`, empty unless references were resolved
+    pub references: HashMap<String, ResolvedReference>,
 }
 
 impl HydratedObject {
@@ -114,12 +440,26 @@ impl HydratedObject {
         
         let mut props = serde_json::Map::new();
         for (key, value) in self.properties.iter() {
-            props.insert(key.clone(), property_value_to_json(value));
+            props.insert(key.clone(), self.value_to_json(value));
         }
         map.insert("properties".to_string(), props.into());
         
         map.into()
     }
+    
+    /// A property value as JSON, with resolved references in their `{"id", "title"}` form
+    fn value_to_json(&self, value: &PropertyValue) -> serde_json::Value {
+        match value {
+            PropertyValue::ObjectReference(reference) => match self.references.get(reference) {
+                Some(resolved) => resolved.to_json_value(),
+                None => property_value_to_json(value),
+            },
+            PropertyValue::Array(items) if !self.references.is_empty() => {
+                serde_json::Value::Array(items.iter().map(|item| self.value_to_json(item)).collect())
+            }
+            _ => property_value_to_json(value),
+        }
+    }
 }
 
 /// Convert PropertyValue to JSON
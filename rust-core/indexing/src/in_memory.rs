@@ -0,0 +1,806 @@
+use crate::sampling::{approximate_analytics, ReservoirSampler};
+use crate::store::{
+    Aggregation, AnalyticsQuery, AnalyticsResult, CentralityMetric, ColumnarStore, CommunityAlgorithm,
+    Filter, FilterOperator, GraphLink, GraphMetrics, GraphStore, IndexedObject, LinkDirection,
+    LinkPropertyStats, LinkQuery, LinkScanPage, SearchQuery, SearchStore, SortOption, StoreError,
+    TraversalAggregation, TraversalAggregationResult, numeric_value,
+};
+use async_trait::async_trait;
+use ontology_engine::{geo, PropertyMap, PropertyValue};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+/// Graph store kept entirely in memory, for local development and tests
+#[derive(Default)]
+pub struct InMemoryGraphStore {
+    links: RwLock<Vec<GraphLink>>,
+    // object id -> properties traversals can aggregate
+    nodes: RwLock<HashMap<String, PropertyMap>>,
+}
+
+impl InMemoryGraphStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Store an object's properties on its node, so traversals can filter and aggregate
+    /// them (see `DgraphStore::set_node_properties`)
+    pub async fn set_node_properties(&self, object_id: &str, properties: &PropertyMap) {
+        let mut nodes = self.nodes.write().await;
+        let node = nodes.entry(object_id.to_string()).or_default();
+        for (key, value) in properties.iter() {
+            node.insert(key.clone(), value.clone());
+        }
+    }
+
+    /// Object IDs reachable in one hop over the given link types (outgoing edges)
+    fn neighbours(
+        links: &[GraphLink],
+        object_id: &str,
+        link_type_ids: &[String],
+        link_filters: &[Filter],
+    ) -> Vec<String> {
+        links
+            .iter()
+            .filter(|l| l.source_id == object_id)
+            .filter(|l| link_type_ids.is_empty() || link_type_ids.contains(&l.link_type_id))
+            .filter(|l| matches_filters(&l.properties, link_filters))
+            .map(|l| l.target_id.clone())
+            .collect()
+    }
+
+    /// Breadth-first traversal returning reached objects (excluding the start) in visit order
+    fn bfs(
+        links: &[GraphLink],
+        start_id: &str,
+        link_type_ids: &[String],
+        max_hops: usize,
+        link_filters: &[Filter],
+    ) -> Vec<String> {
+        let mut visited: HashSet<String> = HashSet::from([start_id.to_string()]);
+        let mut reached = Vec::new();
+        let mut frontier = vec![start_id.to_string()];
+
+        for _ in 0..max_hops {
+            let mut next = Vec::new();
+            for node in &frontier {
+                for neighbour in Self::neighbours(links, node, link_type_ids, link_filters) {
+                    if visited.insert(neighbour.clone()) {
+                        reached.push(neighbour.clone());
+                        next.push(neighbour);
+                    }
+                }
+            }
+            if next.is_empty() {
+                break;
+            }
+            frontier = next;
+        }
+
+        reached
+    }
+
+    /// Undirected adjacency over all stored links
+    fn adjacency(links: &[GraphLink]) -> HashMap<String, HashSet<String>> {
+        let mut adjacency: HashMap<String, HashSet<String>> = HashMap::new();
+        for link in links {
+            adjacency.entry(link.source_id.clone()).or_default().insert(link.target_id.clone());
+            adjacency.entry(link.target_id.clone()).or_default().insert(link.source_id.clone());
+        }
+        adjacency
+    }
+}
+
+#[async_trait]
+impl GraphStore for InMemoryGraphStore {
+    #[tracing::instrument(level = "debug", name = "store.create_link", skip_all, fields(backend = "in_memory", link_type_id = %link_type_id))]
+    async fn create_link(
+        &self,
+        link_type_id: &str,
+        source_id: &str,
+        target_id: &str,
+        properties: &PropertyMap,
+    ) -> Result<String, StoreError> {
+        let link_id = Uuid::new_v4().to_string();
+        self.links.write().await.push(GraphLink {
+            link_id: link_id.clone(),
+            link_type_id: link_type_id.to_string(),
+            source_id: source_id.to_string(),
+            target_id: target_id.to_string(),
+            properties: properties.clone(),
+            created_at: chrono::Utc::now(),
+        });
+        Ok(link_id)
+    }
+
+    async fn delete_link(&self, link_id: &str) -> Result<(), StoreError> {
+        let mut links = self.links.write().await;
+        let before = links.len();
+        links.retain(|l| l.link_id != link_id);
+        if links.len() == before {
+            return Err(StoreError::NotFound(format!("Link '{}'", link_id)));
+        }
+        Ok(())
+    }
+
+    async fn get_link(&self, link_id: &str) -> Result<Option<GraphLink>, StoreError> {
+        Ok(self.links.read().await.iter().find(|l| l.link_id == link_id).cloned())
+    }
+
+    async fn get_links(
+        &self,
+        object_id: &str,
+        link_type_id: Option<&str>,
+        direction: Option<LinkDirection>,
+        query: &LinkQuery,
+    ) -> Result<Vec<GraphLink>, StoreError> {
+        let direction = direction.unwrap_or(LinkDirection::Both);
+        let links = self.links.read().await;
+
+        let mut matching: Vec<GraphLink> = links
+            .iter()
+            .filter(|l| link_type_id.map_or(true, |id| l.link_type_id == id))
+            .filter(|l| match direction {
+                LinkDirection::Outgoing => l.source_id == object_id,
+                LinkDirection::Incoming => l.target_id == object_id,
+                LinkDirection::Both => l.source_id == object_id || l.target_id == object_id,
+            })
+            .filter(|l| matches_filters(&l.properties, &query.filters))
+            .cloned()
+            .collect();
+
+        // Stable order when no sort is given (insertion order is not guaranteed after deletes)
+        matching.sort_by(|a, b| a.created_at.cmp(&b.created_at).then_with(|| a.link_id.cmp(&b.link_id)));
+        let sort = query.sort.as_slice();
+        if !sort.is_empty() {
+            matching.sort_by(|a, b| compare_sorted(&a.properties, &b.properties, sort));
+        }
+
+        let offset = query.offset.unwrap_or(0);
+        let limit = query.limit.unwrap_or(usize::MAX);
+        Ok(matching.into_iter().skip(offset).take(limit).collect())
+    }
+
+    #[tracing::instrument(level = "debug", name = "store.traverse", skip_all, fields(backend = "in_memory", start_id = %start_id, max_hops = max_hops))]
+    async fn traverse(
+        &self,
+        start_id: &str,
+        link_type_ids: &[String],
+        max_hops: usize,
+    ) -> Result<Vec<String>, StoreError> {
+        let links = self.links.read().await;
+        Ok(Self::bfs(&links, start_id, link_type_ids, max_hops, &[]))
+    }
+
+    async fn get_connected_objects(
+        &self,
+        object_id: &str,
+        link_type_id: &str,
+    ) -> Result<Vec<String>, StoreError> {
+        self.traverse(object_id, &[link_type_id.to_string()], 1).await
+    }
+
+    async fn traverse_with_filters(
+        &self,
+        start_id: &str,
+        link_type_ids: &[String],
+        max_hops: usize,
+        link_filters: &[Filter],
+    ) -> Result<Vec<String>, StoreError> {
+        let links = self.links.read().await;
+        Ok(Self::bfs(&links, start_id, link_type_ids, max_hops, link_filters))
+    }
+
+    async fn traverse_with_aggregation(
+        &self,
+        start_id: &str,
+        link_type_ids: &[String],
+        max_hops: usize,
+        aggregation: &TraversalAggregation,
+    ) -> Result<TraversalAggregationResult, StoreError> {
+        let reached = self.traverse(start_id, link_type_ids, max_hops).await?;
+        let nodes = self.nodes.read().await;
+        let empty = PropertyMap::new();
+        let matching: Vec<&PropertyMap> = reached
+            .iter()
+            .map(|id| nodes.get(id).unwrap_or(&empty))
+            .filter(|properties| matches_filters(properties, &aggregation.object_filters))
+            .collect();
+
+        let property = match &aggregation.operation {
+            Aggregation::Count => {
+                return Ok(TraversalAggregationResult {
+                    value: PropertyValue::Integer(matching.len() as i64),
+                    count: matching.len(),
+                });
+            }
+            Aggregation::Sum(p) | Aggregation::Avg(p) | Aggregation::Min(p) | Aggregation::Max(p) => p,
+            other => {
+                return Err(StoreError::Query(format!(
+                    "Aggregation {:?} not supported in graph traversal",
+                    other
+                )));
+            }
+        };
+        let values: Vec<&PropertyValue> = matching
+            .iter()
+            .filter_map(|properties| properties.get(property))
+            .filter(|value| !value.is_null())
+            .collect();
+        if values.is_empty() {
+            return Err(StoreError::NoData(format!("No traversed object has property '{}'", property)));
+        }
+        let value = traversal_aggregate(&aggregation.operation, &values)
+            .ok_or_else(|| StoreError::Query(format!("Cannot aggregate non-numeric property '{}'", property)))?;
+        Ok(TraversalAggregationResult { value, count: values.len() })
+    }
+
+    async fn compute_centrality(
+        &self,
+        _object_type: &str,
+        metric: CentralityMetric,
+    ) -> Result<HashMap<String, f64>, StoreError> {
+        match metric {
+            CentralityMetric::Degree => {
+                let links = self.links.read().await;
+                Ok(Self::adjacency(&links)
+                    .into_iter()
+                    .map(|(id, neighbours)| (id, neighbours.len() as f64))
+                    .collect())
+            }
+            other => Err(StoreError::Query(format!(
+                "Centrality metric {:?} is not supported by the in-memory graph store",
+                other
+            ))),
+        }
+    }
+
+    async fn detect_communities(
+        &self,
+        _object_type: &str,
+        algorithm: CommunityAlgorithm,
+    ) -> Result<HashMap<String, usize>, StoreError> {
+        Err(StoreError::Query(format!(
+            "Community detection ({:?}) is not supported by the in-memory graph store",
+            algorithm
+        )))
+    }
+
+    async fn shortest_path(
+        &self,
+        source_id: &str,
+        target_id: &str,
+        link_types: &[String],
+    ) -> Result<Vec<String>, StoreError> {
+        let links = self.links.read().await;
+        let mut previous: HashMap<String, String> = HashMap::new();
+        let mut queue = VecDeque::from([source_id.to_string()]);
+        let mut visited: HashSet<String> = HashSet::from([source_id.to_string()]);
+
+        while let Some(node) = queue.pop_front() {
+            if node == target_id {
+                let mut path = vec![node.clone()];
+                let mut current = node;
+                while let Some(prev) = previous.get(&current) {
+                    path.push(prev.clone());
+                    current = prev.clone();
+                }
+                path.reverse();
+                return Ok(path);
+            }
+            for neighbour in Self::neighbours(&links, &node, link_types, &[]) {
+                if visited.insert(neighbour.clone()) {
+                    previous.insert(neighbour.clone(), node.clone());
+                    queue.push_back(neighbour);
+                }
+            }
+        }
+
+        Ok(Vec::new())
+    }
+
+    async fn scan_links(
+        &self,
+        link_type_id: &str,
+        cursor: Option<&str>,
+        limit: usize,
+    ) -> Result<LinkScanPage, StoreError> {
+        let offset: usize = match cursor {
+            Some(cursor) => cursor
+                .parse()
+                .map_err(|_| StoreError::Query(format!("Invalid scan cursor '{}'", cursor)))?,
+            None => 0,
+        };
+        let links = self.links.read().await;
+        let mut of_type = links.iter().filter(|l| l.link_type_id == link_type_id).skip(offset);
+        let page: Vec<GraphLink> = of_type.by_ref().take(limit).cloned().collect();
+        let next = of_type.next().is_some().then(|| (offset + page.len()).to_string());
+        Ok(LinkScanPage { links: page, next })
+    }
+
+    async fn link_property_stats(
+        &self,
+        link_type_id: &str,
+        property: &str,
+        buckets: usize,
+    ) -> Result<LinkPropertyStats, StoreError> {
+        let links = self.links.read().await;
+        let values: Vec<f64> = links
+            .iter()
+            .filter(|l| l.link_type_id == link_type_id)
+            .filter_map(|l| numeric_value(l.properties.get(property)?))
+            .collect();
+        LinkPropertyStats::from_values(&values, buckets)
+    }
+
+    async fn graph_metrics(&self, _object_type: &str) -> Result<GraphMetrics, StoreError> {
+        let links = self.links.read().await;
+        let adjacency = Self::adjacency(&links);
+        let node_count = adjacency.len();
+        let edge_count = links.len();
+
+        let density = if node_count > 1 {
+            edge_count as f64 / (node_count * (node_count - 1)) as f64
+        } else {
+            0.0
+        };
+        let average_degree = if node_count > 0 {
+            adjacency.values().map(|n| n.len()).sum::<usize>() as f64 / node_count as f64
+        } else {
+            0.0
+        };
+        let average_clustering_coefficient = if node_count > 0 {
+            adjacency
+                .values()
+                .map(|neighbours| {
+                    let k = neighbours.len();
+                    if k < 2 {
+                        return 0.0;
+                    }
+                    let neighbours: Vec<&String> = neighbours.iter().collect();
+                    let mut connected_pairs = 0;
+                    for (i, a) in neighbours.iter().enumerate() {
+                        for b in &neighbours[i + 1..] {
+                            if adjacency.get(*a).map_or(false, |n| n.contains(*b)) {
+                                connected_pairs += 1;
+                            }
+                        }
+                    }
+                    2.0 * connected_pairs as f64 / (k * (k - 1)) as f64
+                })
+                .sum::<f64>()
+                / node_count as f64
+        } else {
+            0.0
+        };
+
+        Ok(GraphMetrics {
+            node_count,
+            edge_count,
+            density,
+            average_clustering_coefficient,
+            average_degree,
+        })
+    }
+}
+
+/// Search store kept entirely in memory, for local development and tests
+#[derive(Default)]
+pub struct InMemorySearchStore {
+    // object type -> object id -> object
+    objects: RwLock<HashMap<String, HashMap<String, IndexedObject>>>,
+}
+
+impl InMemorySearchStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl SearchStore for InMemorySearchStore {
+    #[tracing::instrument(level = "debug", name = "store.index_object", skip_all, fields(backend = "in_memory", object_type = %object_type, object_id = %object_id))]
+    async fn index_object(
+        &self,
+        object_type: &str,
+        object_id: &str,
+        properties: &PropertyMap,
+        expected_revision: Option<u64>,
+    ) -> Result<u64, StoreError> {
+        // Compare-and-swap under the write lock
+        let mut objects = self.objects.write().await;
+        let by_id = objects.entry(object_type.to_string()).or_default();
+        let current = by_id.get(object_id);
+        let current_revision = current.map_or(0, |o| o.revision);
+        if let Some(expected) = expected_revision {
+            if expected != current_revision {
+                return Err(StoreError::conflict(object_type, object_id, expected, current.map(without_acl_fields)));
+            }
+        }
+
+        let mut object = IndexedObject::new(object_type.to_string(), object_id.to_string(), with_acl_fields(properties)?);
+        object.revision = current_revision + 1;
+        by_id.insert(object_id.to_string(), object);
+        Ok(current_revision + 1)
+    }
+
+    #[tracing::instrument(level = "debug", name = "store.search", skip_all, fields(backend = "in_memory", object_type = %object_type))]
+    async fn search(
+        &self,
+        object_type: &str,
+        query: &SearchQuery,
+    ) -> Result<Vec<IndexedObject>, StoreError> {
+        query.filters.iter().try_for_each(Filter::check_spatial)?;
+        let objects = self.objects.read().await;
+        let mut matching: Vec<IndexedObject> = objects
+            .get(object_type)
+            .map(|by_id| {
+                by_id.values()
+                    .filter(|o| matches_filters(&o.properties, &query.filters))
+                    .map(without_acl_fields)
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        matching.sort_by(|a, b| a.object_id.cmp(&b.object_id));
+        if !query.sort.is_empty() {
+            matching.sort_by(|a, b| compare_sorted(&a.properties, &b.properties, &query.sort));
+        }
+        if let Some(after) = &query.search_after {
+            let position = search_after_position(after, &query.sort)?;
+            matching.retain(|o| compare_sorted(&o.properties, &position, &query.sort) == Ordering::Greater);
+        }
+
+        let offset = query.offset.unwrap_or(0);
+        let limit = query.limit.unwrap_or(usize::MAX);
+        Ok(matching.into_iter().skip(offset).take(limit).collect())
+    }
+
+    async fn get_object(
+        &self,
+        object_type: &str,
+        object_id: &str,
+    ) -> Result<Option<IndexedObject>, StoreError> {
+        Ok(self.objects.read().await
+            .get(object_type)
+            .and_then(|by_id| by_id.get(object_id))
+            .map(without_acl_fields))
+    }
+
+    async fn bulk_index(&self, objects: Vec<IndexedObject>) -> Result<(), StoreError> {
+        let mut stored = self.objects.write().await;
+        for mut object in objects {
+            object.properties = with_acl_fields(&object.properties)?;
+            let by_id = stored.entry(object.object_type.clone()).or_default();
+            object.revision = by_id.get(&object.object_id).map_or(0, |o| o.revision) + 1;
+            by_id.insert(object.object_id.clone(), object);
+        }
+        Ok(())
+    }
+
+    async fn delete_object(&self, object_type: &str, object_id: &str) -> Result<(), StoreError> {
+        self.objects.write().await
+            .get_mut(object_type)
+            .and_then(|by_id| by_id.remove(object_id))
+            .map(|_| ())
+            .ok_or_else(|| StoreError::NotFound(format!("{} '{}'", object_type, object_id)))
+    }
+
+    async fn count_objects(
+        &self,
+        object_type: &str,
+        filters: Option<&[Filter]>,
+    ) -> Result<u64, StoreError> {
+        let filters = filters.unwrap_or(&[]);
+        filters.iter().try_for_each(Filter::check_spatial)?;
+        Ok(self.objects.read().await
+            .get(object_type)
+            .map(|by_id| by_id.values().filter(|o| matches_filters(&o.properties, filters)).count())
+            .unwrap_or(0) as u64)
+    }
+
+    async fn aggregate(
+        &self,
+        object_type: &str,
+        query: &AnalyticsQuery,
+    ) -> Result<AnalyticsResult, StoreError> {
+        let objects = self.objects.read().await;
+        object_analytics(objects.get(object_type).into_iter().flat_map(|by_id| by_id.values()), query)
+    }
+
+    fn supports_aggregations(&self) -> bool {
+        true
+    }
+}
+
+/// An object's properties with its ACL flattened into the fields searches pre-filter on,
+/// as Elasticsearch indexes them
+fn with_acl_fields(properties: &PropertyMap) -> Result<PropertyMap, StoreError> {
+    security::acl::with_acl_index_fields(properties)
+        .map_err(|e| StoreError::Serialization(format!("Invalid object ACL: {}", e)))
+}
+
+/// A stored object as it reads back, without the flattened ACL fields
+fn without_acl_fields(object: &IndexedObject) -> IndexedObject {
+    let mut properties = PropertyMap::new();
+    for (key, value) in object.properties.iter() {
+        if key != security::acl::ACL_READERS_FIELD && key != security::acl::ACL_DENIED_FIELD {
+            properties.insert(key.clone(), value.clone());
+        }
+    }
+    IndexedObject { properties, ..object.clone() }
+}
+
+/// Columnar store kept entirely in memory, for local development and tests. Analytics are
+/// computed exactly over the stored rows, or over a uniform sample when asked.
+#[derive(Default)]
+pub struct InMemoryColumnarStore {
+    // (object type, partition) -> object id -> object; the main data has no partition
+    objects: RwLock<HashMap<(String, Option<String>), BTreeMap<String, IndexedObject>>>,
+}
+
+impl InMemoryColumnarStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl ColumnarStore for InMemoryColumnarStore {
+    async fn write_batch(&self, object_type: &str, objects: Vec<IndexedObject>) -> Result<(), StoreError> {
+        let mut stored = self.objects.write().await;
+        let by_id = stored.entry((object_type.to_string(), None)).or_default();
+        for object in objects {
+            by_id.insert(object.object_id.clone(), object);
+        }
+        Ok(())
+    }
+
+    async fn query_analytics(
+        &self,
+        object_type: &str,
+        query: &AnalyticsQuery,
+    ) -> Result<AnalyticsResult, StoreError> {
+        let stored = self.objects.read().await;
+        let partitions = std::iter::once(None).chain(query.partitions.iter().cloned().map(Some));
+        let objects = partitions
+            .filter_map(|partition| stored.get(&(object_type.to_string(), partition)))
+            .flat_map(|by_id| by_id.values());
+        object_analytics(objects, query)
+    }
+
+    async fn append_partition(
+        &self,
+        object_type: &str,
+        partition: &str,
+        objects: Vec<IndexedObject>,
+    ) -> Result<String, StoreError> {
+        let mut stored = self.objects.write().await;
+        let by_id = stored.entry((object_type.to_string(), Some(partition.to_string()))).or_default();
+        for object in objects {
+            by_id.insert(object.object_id.clone(), object);
+        }
+        Ok(format!("memory://{}/{}", partition, object_type))
+    }
+
+    async fn read_partition(
+        &self,
+        object_type: &str,
+        partition: &str,
+        filters: &[Filter],
+    ) -> Result<Vec<IndexedObject>, StoreError> {
+        Ok(self.objects.read().await
+            .get(&(object_type.to_string(), Some(partition.to_string())))
+            .map(|by_id| by_id.values().filter(|o| matches_filters(&o.properties, filters)).cloned().collect())
+            .unwrap_or_default())
+    }
+
+    async fn remove_from_partition(
+        &self,
+        object_type: &str,
+        partition: &str,
+        object_ids: &[String],
+    ) -> Result<usize, StoreError> {
+        let mut stored = self.objects.write().await;
+        let Some(by_id) = stored.get_mut(&(object_type.to_string(), Some(partition.to_string()))) else {
+            return Ok(0);
+        };
+        Ok(object_ids.iter().filter(|id| by_id.remove(id.as_str()).is_some()).count())
+    }
+}
+
+/// Sum, average, minimum or maximum of traversed values. Integer sums and extremes stay
+/// integers, as in Dgraph; `None` if a value is not numeric.
+fn traversal_aggregate(operation: &Aggregation, values: &[&PropertyValue]) -> Option<PropertyValue> {
+    let integers: Option<Vec<i64>> = values
+        .iter()
+        .map(|value| match value {
+            PropertyValue::Integer(i) => Some(*i),
+            _ => None,
+        })
+        .collect();
+    let numbers: Vec<f64> = values.iter().map(|value| numeric_value(value)).collect::<Option<_>>()?;
+    let value = match (operation, integers) {
+        (Aggregation::Sum(_), Some(integers)) => PropertyValue::Integer(integers.iter().sum()),
+        (Aggregation::Min(_), Some(integers)) => PropertyValue::Integer(*integers.iter().min()?),
+        (Aggregation::Max(_), Some(integers)) => PropertyValue::Integer(*integers.iter().max()?),
+        (Aggregation::Sum(_), None) => PropertyValue::Double(numbers.iter().sum()),
+        (Aggregation::Min(_), None) => PropertyValue::Double(numbers.iter().copied().fold(f64::INFINITY, f64::min)),
+        (Aggregation::Max(_), None) => PropertyValue::Double(numbers.iter().copied().fold(f64::NEG_INFINITY, f64::max)),
+        (Aggregation::Avg(_), _) => PropertyValue::Double(numbers.iter().sum::<f64>() / numbers.len() as f64),
+        _ => return None,
+    };
+    Some(value)
+}
+
+/// Aggregate stored objects exactly, or over a uniform sample when the query asks for one
+fn object_analytics<'a>(
+    objects: impl Iterator<Item = &'a IndexedObject>,
+    query: &AnalyticsQuery,
+) -> Result<AnalyticsResult, StoreError> {
+    if query.aggregations.is_empty() {
+        return Err(StoreError::Query("At least one aggregation is required".to_string()));
+    }
+    let rows: Vec<serde_json::Value> = objects
+        .filter(|o| matches_filters(&o.properties, &query.filters))
+        .map(|o| {
+            let row = o.properties.iter()
+                .map(|(key, value)| (key.clone(), serde_json::to_value(value).unwrap_or_default()))
+                .collect();
+            serde_json::Value::Object(row)
+        })
+        .collect();
+
+    let population = rows.len();
+    let Some(sampling) = query.sampling else {
+        let all: Vec<&serde_json::Value> = rows.iter().collect();
+        let result = approximate_analytics(&all, population, &query.aggregations, &query.group_by);
+        return Ok(AnalyticsResult { sample_fraction: None, error_bounds: vec![], ..result });
+    };
+    let mut sampler = ReservoirSampler::new(sampling);
+    for row in &rows {
+        sampler.offer(row);
+    }
+    Ok(approximate_analytics(&sampler.into_sample(), population, &query.aggregations, &query.group_by))
+}
+
+/// Check object or link properties against filters; a missing property never matches
+pub(crate) fn matches_filters(properties: &PropertyMap, filters: &[Filter]) -> bool {
+    filters.iter().all(|filter| {
+        let Some(value) = properties.get(&filter.property) else {
+            return false;
+        };
+        let ordering = compare_values(value, &filter.value);
+        let text = |op: fn(&str, &str) -> bool| {
+            let (value, expected) = (value.to_string(), filter.value.to_string());
+            if filter.case_insensitive {
+                op(&value.to_lowercase(), &expected.to_lowercase())
+            } else {
+                op(&value, &expected)
+            }
+        };
+        match filter.operator {
+            FilterOperator::Equals => ordering == Some(Ordering::Equal),
+            FilterOperator::NotEquals => ordering != Some(Ordering::Equal),
+            FilterOperator::GreaterThan => ordering == Some(Ordering::Greater),
+            FilterOperator::LessThan => ordering == Some(Ordering::Less),
+            FilterOperator::GreaterThanOrEqual => matches!(ordering, Some(Ordering::Greater | Ordering::Equal)),
+            FilterOperator::LessThanOrEqual => matches!(ordering, Some(Ordering::Less | Ordering::Equal)),
+            FilterOperator::Contains => text(|v, e| v.contains(e)),
+            FilterOperator::StartsWith => text(|v, e| v.starts_with(e)),
+            FilterOperator::EndsWith => text(|v, e| v.ends_with(e)),
+            FilterOperator::In | FilterOperator::NotIn => {
+                let candidates = match &filter.value {
+                    PropertyValue::Array(items) => items.as_slice(),
+                    other => std::slice::from_ref(other),
+                };
+                // Array fields match if any element does, like an Elasticsearch terms query
+                let values = match value {
+                    PropertyValue::Array(items) => items.as_slice(),
+                    other => std::slice::from_ref(other),
+                };
+                let found = values
+                    .iter()
+                    .any(|v| candidates.iter().any(|c| compare_values(v, c) == Some(Ordering::Equal)));
+                found == (filter.operator == FilterOperator::In)
+            }
+            FilterOperator::ContainsGeometry
+            | FilterOperator::Intersects
+            | FilterOperator::Within
+            | FilterOperator::WithinDistance => matches_spatial(value, filter),
+        }
+    })
+}
+
+/// Whether a GeoJSON value matches a spatial filter. `WithinDistance` matches geometries with
+/// any point within `filter.distance` meters of the filter's point, like an Elasticsearch
+/// `geo_distance` query; malformed geometries and filters never match.
+fn matches_spatial(value: &PropertyValue, filter: &Filter) -> bool {
+    let stored = match value {
+        PropertyValue::GeoJSON(gj) | PropertyValue::String(gj) => serde_json::from_str::<serde_json::Value>(gj).ok(),
+        _ => None,
+    };
+    let Some(stored) = stored else {
+        return false;
+    };
+    match filter.operator.spatial_relation() {
+        Some(relation) => filter
+            .geometry()
+            .is_ok_and(|query| geo::relates(&stored, &query, relation).unwrap_or(false)),
+        None => filter.distance_origin().is_ok_and(|(origin, max)| {
+            geo::point_distance_meters(origin, &stored).is_some_and(|meters| meters <= max)
+        }),
+    }
+}
+
+/// The sort values of a `search_after` cursor as properties, so objects can be compared
+/// against it like against each other
+fn search_after_position(after: &[serde_json::Value], sort: &[SortOption]) -> Result<PropertyMap, StoreError> {
+    if after.len() != sort.len() {
+        return Err(StoreError::Query(format!(
+            "search_after has {} value(s) for {} sort key(s)",
+            after.len(),
+            sort.len()
+        )));
+    }
+    let mut position = PropertyMap::new();
+    for (key, value) in sort.iter().zip(after) {
+        let value: PropertyValue = serde_json::from_value(value.clone())
+            .map_err(|e| StoreError::Query(format!("Invalid search_after value for '{}': {}", key.property, e)))?;
+        position.insert(key.property.clone(), value);
+    }
+    Ok(position)
+}
+
+/// Order two property maps by sort keys in priority order; a missing (or null) sort
+/// property always comes last
+pub(crate) fn compare_sorted(a: &PropertyMap, b: &PropertyMap, sort: &[SortOption]) -> Ordering {
+    fn value<'a>(properties: &'a PropertyMap, property: &str) -> Option<&'a PropertyValue> {
+        properties.get(property).filter(|v| !matches!(v, PropertyValue::Null))
+    }
+    for key in sort {
+        let ordering = match (value(a, &key.property), value(b, &key.property)) {
+            (Some(x), Some(y)) => {
+                let ordering = compare_values(x, y).unwrap_or(Ordering::Equal);
+                if key.ascending { ordering } else { ordering.reverse() }
+            }
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+    Ordering::Equal
+}
+
+/// Compare two property values of compatible kinds (numbers, strings, dates, booleans)
+fn compare_values(a: &PropertyValue, b: &PropertyValue) -> Option<Ordering> {
+    let as_f64 = |v: &PropertyValue| match v {
+        PropertyValue::Integer(i) => Some(*i as f64),
+        PropertyValue::Double(d) => Some(*d),
+        _ => None,
+    };
+    let as_str = |v: &PropertyValue| match v {
+        PropertyValue::String(s)
+        | PropertyValue::Date(s)
+        | PropertyValue::DateTime(s)
+        | PropertyValue::ObjectReference(s) => Some(s.clone()),
+        _ => None,
+    };
+
+    match (a, b) {
+        (PropertyValue::Boolean(x), PropertyValue::Boolean(y)) => Some(x.cmp(y)),
+        _ => match (as_f64(a), as_f64(b)) {
+            (Some(x), Some(y)) => x.partial_cmp(&y),
+            // ISO 8601 dates compare correctly as strings
+            _ => match (as_str(a), as_str(b)) {
+                (Some(x), Some(y)) => Some(x.cmp(&y)),
+                _ => None,
+            },
+        },
+    }
+}
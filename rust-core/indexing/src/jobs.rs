@@ -0,0 +1,281 @@
+//! Progress tracking for long-running maintenance jobs.
+//!
+//! A job is started on a `JobRegistry`, which hands back a `JobHandle` for the worker to
+//! report progress through. Readers poll the registry by job ID. Progress is counted per
+//! phase: starting a phase resets `processed` and sets the phase's expected `total`.
+//!
+//! A registry opened on a file saves every status change to it. Jobs that were still
+//! running when the process stopped are marked failed on the next open, since their
+//! workers are gone. Finished jobs are kept for the retention period, if one is set.
+
+use crate::store::StoreError;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    Running,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+impl JobStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JobStatus::Running => "running",
+            JobStatus::Completed => "completed",
+            JobStatus::Failed => "failed",
+            JobStatus::Cancelled => "cancelled",
+        }
+    }
+}
+
+/// Snapshot of a job's progress
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobProgress {
+    pub job_id: String,
+    pub kind: String,
+    pub status: JobStatus,
+    pub phase: Option<String>,
+    pub processed: usize,
+    /// Expected number of items in the current phase, when known
+    pub total: Option<usize>,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+    /// Job output once completed
+    pub result: Option<JsonValue>,
+    pub error: Option<String>,
+    /// User who started the job, when it was started on someone's behalf
+    #[serde(default)]
+    pub requested_by: Option<String>,
+}
+
+/// Jobs started in this process (and, when opened on a file, earlier ones), by ID
+#[derive(Clone, Default)]
+pub struct JobRegistry {
+    jobs: Arc<RwLock<HashMap<String, JobProgress>>>,
+    path: Option<PathBuf>,
+    retention: Option<Duration>,
+}
+
+impl JobRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registry persisted to `path`, loading the jobs saved there
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self, StoreError> {
+        let path = path.into();
+        let mut jobs = HashMap::new();
+        if path.exists() {
+            let content = std::fs::read_to_string(&path)
+                .map_err(|e| StoreError::ReadError(format!("Failed to read job registry {}: {}", path.display(), e)))?;
+            let saved: Vec<JobProgress> = serde_json::from_str(&content)
+                .map_err(|e| StoreError::Serialization(format!("Invalid job registry {}: {}", path.display(), e)))?;
+            for mut job in saved {
+                if job.status == JobStatus::Running {
+                    job.status = JobStatus::Failed;
+                    job.finished_at = Some(Utc::now());
+                    job.error = Some("Interrupted by a restart".to_string());
+                }
+                jobs.insert(job.job_id.clone(), job);
+            }
+        }
+        let registry = Self {
+            jobs: Arc::new(RwLock::new(jobs)),
+            path: Some(path),
+            retention: None,
+        };
+        registry.save();
+        Ok(registry)
+    }
+
+    /// Keep finished jobs for `retention` before `prune_expired` drops them
+    pub fn with_retention(mut self, retention: Duration) -> Self {
+        self.retention = Some(retention);
+        self
+    }
+
+    /// Register a new running job of the given kind
+    pub fn start(&self, kind: &str) -> JobHandle {
+        self.start_for(kind, None)
+    }
+
+    /// Register a new running job of the given kind, started by `requested_by`
+    pub fn start_for(&self, kind: &str, requested_by: Option<String>) -> JobHandle {
+        let job_id = Uuid::new_v4().to_string();
+        self.jobs.write().unwrap().insert(
+            job_id.clone(),
+            JobProgress {
+                job_id: job_id.clone(),
+                kind: kind.to_string(),
+                status: JobStatus::Running,
+                phase: None,
+                processed: 0,
+                total: None,
+                started_at: Utc::now(),
+                finished_at: None,
+                result: None,
+                error: None,
+                requested_by,
+            },
+        );
+        self.save();
+        JobHandle {
+            job_id,
+            registry: self.clone(),
+        }
+    }
+
+    pub fn get(&self, job_id: &str) -> Option<JobProgress> {
+        self.jobs.read().unwrap().get(job_id).cloned()
+    }
+
+    /// All jobs, most recently started first
+    pub fn list(&self) -> Vec<JobProgress> {
+        let mut jobs: Vec<JobProgress> = self.jobs.read().unwrap().values().cloned().collect();
+        jobs.sort_by_key(|job| std::cmp::Reverse(job.started_at));
+        jobs
+    }
+
+    /// Ask a running job to stop; its worker sees it at the next `is_cancelled` check.
+    /// Returns false if the job is unknown or already finished.
+    pub fn cancel(&self, job_id: &str) -> bool {
+        self.finish(job_id, |progress| progress.status = JobStatus::Cancelled)
+    }
+
+    /// Remove finished jobs older than the retention period and return them, so callers
+    /// can clean up what they produced. Nothing expires without a retention period.
+    pub fn prune_expired(&self) -> Vec<JobProgress> {
+        let Some(retention) = self.retention.and_then(|r| chrono::Duration::from_std(r).ok()) else {
+            return Vec::new();
+        };
+        let cutoff = Utc::now() - retention;
+        let expired: Vec<JobProgress> = {
+            let mut jobs = self.jobs.write().unwrap();
+            let ids: Vec<String> = jobs
+                .values()
+                .filter(|job| job.finished_at.is_some_and(|finished| finished < cutoff))
+                .map(|job| job.job_id.clone())
+                .collect();
+            ids.iter().filter_map(|id| jobs.remove(id)).collect()
+        };
+        if !expired.is_empty() {
+            self.save();
+        }
+        expired
+    }
+
+    /// When a finished job will be pruned, if a retention period is set
+    pub fn expires_at(&self, job: &JobProgress) -> Option<DateTime<Utc>> {
+        let retention = chrono::Duration::from_std(self.retention?).ok()?;
+        Some(job.finished_at? + retention)
+    }
+
+    /// Move a running job to a final status
+    fn finish(&self, job_id: &str, apply: impl FnOnce(&mut JobProgress)) -> bool {
+        let finished = match self.jobs.write().unwrap().get_mut(job_id) {
+            Some(progress) if progress.status == JobStatus::Running => {
+                apply(progress);
+                progress.finished_at = Some(Utc::now());
+                true
+            }
+            _ => false,
+        };
+        if finished {
+            self.save();
+        }
+        finished
+    }
+
+    fn save(&self) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        let result = serde_json::to_vec_pretty(&self.list())
+            .map_err(|e| e.to_string())
+            .and_then(|content| {
+                // Write then rename so a crash never leaves a truncated registry
+                let partial = path.with_extension("tmp");
+                std::fs::write(&partial, content)
+                    .and_then(|_| std::fs::rename(&partial, path))
+                    .map_err(|e| e.to_string())
+            });
+        if let Err(e) = result {
+            tracing::error!("Failed to save job registry {}: {}", path.display(), e);
+        }
+    }
+}
+
+/// Worker side of a registered job
+#[derive(Clone)]
+pub struct JobHandle {
+    job_id: String,
+    registry: JobRegistry,
+}
+
+impl JobHandle {
+    pub fn id(&self) -> &str {
+        &self.job_id
+    }
+
+    fn update(&self, apply: impl FnOnce(&mut JobProgress)) {
+        if let Some(progress) = self.registry.jobs.write().unwrap().get_mut(&self.job_id) {
+            apply(progress);
+        }
+    }
+
+    /// Enter a new phase, resetting the processed count
+    pub fn start_phase(&self, phase: &str, total: Option<usize>) {
+        self.update(|progress| {
+            progress.phase = Some(phase.to_string());
+            progress.processed = 0;
+            progress.total = total;
+        });
+    }
+
+    pub fn advance(&self, items: usize) {
+        self.update(|progress| progress.processed += items);
+    }
+
+    /// Record the job's output; ignored if the job was cancelled meanwhile
+    pub fn complete(&self, result: JsonValue) {
+        self.registry.finish(&self.job_id, |progress| {
+            progress.status = JobStatus::Completed;
+            progress.result = Some(result);
+        });
+    }
+
+    /// Record why the job failed; ignored if the job was cancelled meanwhile
+    pub fn fail(&self, error: String) {
+        self.registry.finish(&self.job_id, |progress| {
+            progress.status = JobStatus::Failed;
+            progress.error = Some(error);
+        });
+    }
+
+    /// Whether the job was cancelled; workers should stop and clean up
+    pub fn is_cancelled(&self) -> bool {
+        self.progress().is_some_and(|progress| progress.status == JobStatus::Cancelled)
+    }
+
+    /// Fails once the job was cancelled, so workers can stop with `?` between pages
+    pub fn check_cancelled(&self) -> Result<(), StoreError> {
+        if self.is_cancelled() {
+            return Err(StoreError::Unknown(format!("Job {} was cancelled", self.job_id)));
+        }
+        Ok(())
+    }
+
+    pub fn progress(&self) -> Option<JobProgress> {
+        self.registry.get(&self.job_id)
+    }
+}
@@ -4,13 +4,46 @@ pub mod hydration;
 pub mod data_quality;
 pub mod lineage;
 pub mod usage_tracking;
+pub mod in_memory;
+pub mod schema_sync;
+pub mod sampling;
+pub mod query_log;
+pub mod validating_graph;
+pub mod validating_search;
+pub mod jobs;
+pub mod consistency;
+pub mod dedup;
+pub mod export;
+pub mod change_triggers;
+pub mod archive;
+pub mod planner;
+pub mod references;
+pub mod model_properties;
 
 pub use store::{SearchStore, GraphStore, ColumnarStore, StoreBackend};
-pub use sync::SyncService;
+pub use sync::{
+    delete_object_cascading, plan_upsert, read_modify_write, CascadeDelete, SyncEventSink, SyncFailure, SyncProgress,
+    SyncProgressCallback, SyncReport, SyncService, SyncState, UpsertPlan,
+};
 pub use hydration::ObjectHydrator;
 pub use data_quality::{DataQualityMetrics, ObjectTypeQualityMetrics};
 pub use lineage::{DataLineage, Transformation, ObjectReference};
 pub use usage_tracking::{ObjectUsageMetrics, UsageTracker};
+pub use in_memory::{InMemoryColumnarStore, InMemoryGraphStore, InMemorySearchStore};
+pub use schema_sync::SchemaSync;
+pub use sampling::{ErrorBound, ReservoirSampler, SamplingOptions};
+pub use query_log::{LoggedColumnarStore, LoggedGraphStore, LoggedSearchStore, QueryLog, QueryLogConfig, SlowQuery};
+pub use validating_graph::ValidatingGraphStore;
+pub use validating_search::{PropertyDrift, ValidatingSearchStore};
+pub use jobs::{JobHandle, JobProgress, JobRegistry, JobStatus};
+pub use consistency::{ConsistencyChecker, ConsistencyOptions, ConsistencyReport};
+pub use dedup::{Deduplicator, DuplicateReport, MergeEventSink, MergeRecord};
+pub use export::{ExportFormat, ExportOutput, ExportRequest, Exporter};
+pub use change_triggers::{ChangeTrigger, ChangeTriggerRegistry, TriggerSettings, TriggeringSearchStore};
+pub use archive::{ArchiveAction, ArchiveEventSink, ArchiveRecord, Archiver, Tombstone};
+pub use planner::{Backend, BackendCapabilities, BackendHealth, PlannerConfig, QueryPlan, QueryPlanner, QueryShape};
+pub use references::{ReferenceBackfillReport, ReferenceIndex, ReferenceIndexingSearchStore, ReferenceSource};
+pub use model_properties::ModelPropertyEvaluator;
 
 
 
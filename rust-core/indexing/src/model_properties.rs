@@ -0,0 +1,150 @@
+//! Model-backed properties: a property bound to a model in the `ModelRegistry` holds the
+//! model's prediction from the object's other properties, evaluated when the object is read.
+//!
+//! Synchronous bindings are predicted inline through the `ModelExecutionOrchestrator` and
+//! its cache. Asynchronous bindings return a cached prediction if there is one, and
+//! otherwise the binding's fallback value (or null) while the prediction is computed in the
+//! background and cached for later reads.
+
+use crate::hydration::HydratedObject;
+use ontology_engine::{
+    ModelBinding, ModelCache, ModelExecutionOrchestrator, ModelObjective, ModelRegistry, PropertyMap, PropertyValue,
+};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use tokio::sync::RwLock;
+
+/// Fills in model-bound properties of hydrated objects with predictions
+#[derive(Clone)]
+pub struct ModelPropertyEvaluator {
+    registry: Arc<RwLock<ModelRegistry>>,
+    executor: Arc<ModelExecutionOrchestrator>,
+    /// Cache keys of background predictions not finished yet
+    pending: Arc<Mutex<HashSet<String>>>,
+}
+
+impl ModelPropertyEvaluator {
+    pub fn new(registry: Arc<RwLock<ModelRegistry>>, executor: Arc<ModelExecutionOrchestrator>) -> Self {
+        Self {
+            registry,
+            executor,
+            pending: Arc::new(Mutex::new(HashSet::new())),
+        }
+    }
+
+    /// Put the prediction of each bound model, by its active version, under the bound
+    /// property of every object. Objects missing a model's inputs keep their stored value
+    /// and get a warning; a failed prediction gives the fallback value and a warning.
+    pub async fn evaluate(&self, objects: &mut [HydratedObject]) {
+        let mut bindings_by_type: HashMap<String, Vec<(ModelBinding, ModelObjective)>> = HashMap::new();
+        {
+            let registry = self.registry.read().await;
+            for object in objects.iter() {
+                bindings_by_type.entry(object.object_type.clone()).or_insert_with(|| {
+                    registry
+                        .list_bindings_for_object_type(&object.object_type)
+                        .into_iter()
+                        .filter_map(|binding| Some((binding.clone(), registry.get(&binding.model_id)?.clone())))
+                        .collect()
+                });
+            }
+        }
+
+        for object in objects.iter_mut() {
+            for (binding, model) in &bindings_by_type[&object.object_type] {
+                self.evaluate_binding(object, binding, model).await;
+            }
+        }
+    }
+
+    async fn evaluate_binding(&self, object: &mut HydratedObject, binding: &ModelBinding, model: &ModelObjective) {
+        let inputs = match model_inputs(&object.properties, binding) {
+            Ok(inputs) => inputs,
+            Err(missing) => {
+                object.warnings.push(format!(
+                    "Model '{}' for property '{}' of {}:{} skipped: missing input properties {}",
+                    model.id,
+                    binding.property_id,
+                    object.object_type,
+                    object.object_id,
+                    missing.join(", ")
+                ));
+                return;
+            }
+        };
+
+        let config = &binding.config;
+        let prediction = if config.async_execution {
+            let cached = self.executor.cached(model, &inputs);
+            if cached.is_none() {
+                self.enqueue(model.clone(), inputs, config.cache_ttl);
+            }
+            cached
+        } else {
+            let result = self
+                .executor
+                .execute(model, inputs, config.cache_enabled, config.cache_ttl)
+                .await;
+            match result {
+                Ok(result) => Some(result),
+                Err(e) => {
+                    object.warnings.push(format!(
+                        "Model '{}' for property '{}' of {}:{} failed: {}",
+                        model.id, binding.property_id, object.object_type, object.object_id, e
+                    ));
+                    None
+                }
+            }
+        };
+
+        let value = prediction
+            .map(|result| result.prediction)
+            .or_else(|| config.fallback_value.clone())
+            .unwrap_or(PropertyValue::Null);
+        object.properties.insert(binding.property_id.clone(), value);
+    }
+
+    /// Predict in the background, caching the result for later reads. A prediction already
+    /// underway for the same model and inputs is not started again.
+    fn enqueue(&self, model: ModelObjective, inputs: HashMap<String, PropertyValue>, cache_ttl: u64) {
+        let key = ModelCache::generate_key(&model.id, &inputs);
+        if !self.pending.lock().unwrap_or_else(|e| e.into_inner()).insert(key.clone()) {
+            return;
+        }
+        let (executor, pending) = (self.executor.clone(), self.pending.clone());
+        tokio::spawn(async move {
+            if let Err(e) = executor.execute(&model, inputs, true, cache_ttl).await {
+                tracing::warn!(model = %model.id, error = %e, "background model prediction failed");
+            }
+            pending.lock().unwrap_or_else(|e| e.into_inner()).remove(&key);
+        });
+    }
+}
+
+/// The binding's input properties from the object, or the names of those it lacks. A
+/// binding without input properties gets all of the object's other properties.
+fn model_inputs(properties: &PropertyMap, binding: &ModelBinding) -> Result<HashMap<String, PropertyValue>, Vec<String>> {
+    if binding.config.input_properties.is_empty() {
+        return Ok(properties
+            .iter()
+            .filter(|(name, _)| **name != binding.property_id)
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect());
+    }
+
+    let mut inputs = HashMap::new();
+    let mut missing = Vec::new();
+    for name in &binding.config.input_properties {
+        match properties.get(name) {
+            Some(PropertyValue::Null) | None => missing.push(name.clone()),
+            Some(value) => {
+                inputs.insert(name.clone(), value.clone());
+            }
+        }
+    }
+    if missing.is_empty() {
+        Ok(inputs)
+    } else {
+        Err(missing)
+    }
+}
@@ -0,0 +1,389 @@
+//! Chooses the backend that answers a query from the query's shape, what each configured
+//! backend can do and whether it is currently healthy. Every plan carries the reasons for
+//! its choice so it can be returned by `explain`.
+
+use crate::store::{Aggregation, AnalyticsQuery, FilterOperator, SearchQuery, StoreError};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+/// A store that can execute queries
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Backend {
+    Search,
+    Columnar,
+    Graph,
+}
+
+impl std::fmt::Display for Backend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Backend::Search => "search",
+            Backend::Columnar => "columnar",
+            Backend::Graph => "graph",
+        })
+    }
+}
+
+/// What a query asks for, as far as backend choice is concerned
+#[derive(Debug, Clone, Default)]
+pub struct QueryShape {
+    pub object_type: String,
+    pub filters: usize,
+    /// The query selects objects by primary key
+    pub id_lookup: bool,
+    pub aggregations: Vec<Aggregation>,
+    pub group_by: Vec<String>,
+    pub limit: Option<usize>,
+    /// The query follows links
+    pub traversal: bool,
+    /// Estimates are acceptable
+    pub approximate: bool,
+    /// The query reads archived partitions, which only the columnar store holds
+    pub archived: bool,
+}
+
+impl QueryShape {
+    /// Shape of an object search; equality on `primary_key` makes it an id lookup
+    pub fn search(object_type: &str, query: &SearchQuery, primary_key: &str) -> Self {
+        Self {
+            object_type: object_type.to_string(),
+            filters: query.filters.len(),
+            id_lookup: query
+                .filters
+                .iter()
+                .any(|f| f.property == primary_key && f.operator == FilterOperator::Equals),
+            limit: query.limit,
+            ..Default::default()
+        }
+    }
+
+    /// Shape of an aggregation
+    pub fn aggregate(object_type: &str, query: &AnalyticsQuery) -> Self {
+        Self {
+            object_type: object_type.to_string(),
+            filters: query.filters.len(),
+            aggregations: query.aggregations.clone(),
+            group_by: query.group_by.clone(),
+            approximate: query.sampling.is_some(),
+            archived: !query.partitions.is_empty(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Which backends are configured and what the optional features of each support
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BackendCapabilities {
+    pub search: bool,
+    /// The search store runs aggregations natively (see `SearchStore::supports_aggregations`)
+    pub search_aggregations: bool,
+    pub columnar: bool,
+    pub graph: bool,
+}
+
+/// Planner settings
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PlannerConfig {
+    /// Preferred backend per object type, tried first for every query it can answer
+    #[serde(default)]
+    pub overrides: HashMap<String, Backend>,
+}
+
+/// A backend that failed recently, and until when the planner avoids it
+#[derive(Debug, Clone)]
+struct Unhealthy {
+    reason: String,
+    until: Instant,
+}
+
+/// Shared record of backends that recently failed. Cloning shares the record.
+#[derive(Debug, Clone)]
+pub struct BackendHealth {
+    unhealthy: Arc<RwLock<HashMap<Backend, Unhealthy>>>,
+    retry_after: Duration,
+}
+
+impl Default for BackendHealth {
+    fn default() -> Self {
+        Self {
+            unhealthy: Arc::default(),
+            retry_after: Duration::from_secs(30),
+        }
+    }
+}
+
+impl BackendHealth {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// How long a failed backend is avoided before the planner tries it again
+    pub fn with_retry_after(mut self, retry_after: Duration) -> Self {
+        self.retry_after = retry_after;
+        self
+    }
+
+    /// Avoid `backend` for the retry period
+    pub fn report_failure(&self, backend: Backend, reason: impl Into<String>) {
+        let until = Instant::now() + self.retry_after;
+        self.unhealthy
+            .write()
+            .unwrap()
+            .insert(backend, Unhealthy { reason: reason.into(), until });
+    }
+
+    pub fn report_success(&self, backend: Backend) {
+        self.unhealthy.write().unwrap().remove(&backend);
+    }
+
+    /// Why `backend` is being avoided, or `None` when it is healthy
+    pub fn status(&self, backend: Backend) -> Option<String> {
+        self.unhealthy
+            .read()
+            .unwrap()
+            .get(&backend)
+            .filter(|unhealthy| unhealthy.until > Instant::now())
+            .map(|unhealthy| unhealthy.reason.clone())
+    }
+}
+
+/// The backend chosen for a query, the one to retry on if it fails, and why
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct QueryPlan {
+    pub backend: Backend,
+    pub fallback: Option<Backend>,
+    pub reasons: Vec<String>,
+}
+
+/// Picks backends for queries. Cloning shares the health record.
+#[derive(Debug, Clone, Default)]
+pub struct QueryPlanner {
+    config: PlannerConfig,
+    health: BackendHealth,
+}
+
+impl QueryPlanner {
+    pub fn new(config: PlannerConfig) -> Self {
+        Self { config, health: BackendHealth::new() }
+    }
+
+    pub fn with_health(mut self, health: BackendHealth) -> Self {
+        self.health = health;
+        self
+    }
+
+    pub fn health(&self) -> &BackendHealth {
+        &self.health
+    }
+
+    /// Choose a backend for `shape`. Healthy backends are preferred over unhealthy ones;
+    /// when every backend that can answer is unhealthy the best of them is used anyway.
+    /// Fails only when no configured backend can answer the query at all.
+    pub fn plan(&self, shape: &QueryShape, capabilities: &BackendCapabilities) -> Result<QueryPlan, StoreError> {
+        let mut reasons = Vec::new();
+        let mut candidates = preferred_backends(shape, capabilities, &mut reasons);
+
+        if let Some(&preferred) = self.config.overrides.get(&shape.object_type) {
+            match candidates.iter().position(|&backend| backend == preferred) {
+                Some(index) => {
+                    candidates.remove(index);
+                    candidates.insert(0, preferred);
+                    reasons.push(format!("object type '{}' is configured to prefer {}", shape.object_type, preferred));
+                }
+                None => reasons.push(format!(
+                    "ignored the {} override for '{}': it cannot answer this query",
+                    preferred, shape.object_type
+                )),
+            }
+        }
+
+        if candidates.is_empty() {
+            return Err(StoreError::Configuration(format!(
+                "No configured backend can answer this query on '{}': {}",
+                shape.object_type,
+                reasons.join("; ")
+            )));
+        }
+
+        let (healthy, unhealthy): (Vec<Backend>, Vec<Backend>) =
+            candidates.iter().partition(|&&backend| self.health.status(backend).is_none());
+        for &backend in &unhealthy {
+            reasons.push(format!(
+                "{} is unhealthy: {}",
+                backend,
+                self.health.status(backend).unwrap_or_default()
+            ));
+        }
+        if healthy.is_empty() {
+            reasons.push("no healthy backend can answer, so the preferred one is used".to_string());
+        }
+        let mut ordered = healthy.into_iter().chain(unhealthy);
+        let backend = ordered.next().expect("candidates are not empty");
+        Ok(QueryPlan { backend, fallback: ordered.next(), reasons })
+    }
+}
+
+/// Backends able to answer `shape`, best first, with the heuristics that ordered them
+fn preferred_backends(shape: &QueryShape, capabilities: &BackendCapabilities, reasons: &mut Vec<String>) -> Vec<Backend> {
+    let mut candidates = Vec::new();
+    let mut offer = |backend: Backend, available: bool, reasons: &mut Vec<String>| {
+        if available {
+            candidates.push(backend);
+        } else {
+            reasons.push(format!("{} is not configured", backend));
+        }
+    };
+
+    if shape.traversal {
+        reasons.push("the query follows links".to_string());
+        offer(Backend::Graph, capabilities.graph, reasons);
+    } else if shape.aggregations.is_empty() {
+        reasons.push(if shape.id_lookup {
+            "primary key lookup".to_string()
+        } else {
+            "object search".to_string()
+        });
+        offer(Backend::Search, capabilities.search, reasons);
+    } else if shape.archived {
+        reasons.push("archived objects are only in the columnar store".to_string());
+        offer(Backend::Columnar, capabilities.columnar, reasons);
+    } else {
+        let search = capabilities.search && capabilities.search_aggregations;
+        match search_aggregation_limit(shape) {
+            Some(limit) => {
+                reasons.push(limit);
+                offer(Backend::Columnar, capabilities.columnar, reasons);
+            }
+            None => {
+                reasons.push("ungrouped aggregation the search index answers directly".to_string());
+                if !search {
+                    reasons.push("the search store does not run aggregations".to_string());
+                }
+                offer(Backend::Search, search, reasons);
+                offer(Backend::Columnar, capabilities.columnar, reasons);
+            }
+        }
+    }
+    candidates
+}
+
+/// Why the search store cannot answer an aggregation, if it cannot
+fn search_aggregation_limit(shape: &QueryShape) -> Option<String> {
+    if !shape.group_by.is_empty() {
+        return Some("grouped aggregation".to_string());
+    }
+    shape.aggregations.iter().find_map(|aggregation| match aggregation {
+        Aggregation::TopN(..) | Aggregation::BottomN(..) => {
+            Some("top/bottom-N aggregations need the columnar store".to_string())
+        }
+        // The search backend estimates these with sketches
+        Aggregation::Median(_) | Aggregation::Percentile(..) | Aggregation::DistinctCount(_)
+            if !shape.approximate =>
+        {
+            Some("exact distinct counts and percentiles need the columnar store".to_string())
+        }
+        _ => None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::Filter;
+    use ontology_engine::PropertyValue;
+
+    fn all_backends() -> BackendCapabilities {
+        BackendCapabilities { search: true, search_aggregations: true, columnar: true, graph: true }
+    }
+
+    fn aggregate(aggregations: Vec<Aggregation>, group_by: &[&str]) -> QueryShape {
+        QueryShape {
+            object_type: "plant".to_string(),
+            aggregations,
+            group_by: group_by.iter().map(|s| s.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_routes_representative_query_shapes() {
+        let planner = QueryPlanner::default();
+        let capabilities = all_backends();
+        let backend = |shape: &QueryShape| planner.plan(shape, &capabilities).unwrap().backend;
+
+        // Grouped aggregations go to the columnar store, ungrouped ones to the search index
+        let sum = vec![Aggregation::Sum("capacity".to_string())];
+        assert_eq!(backend(&aggregate(sum.clone(), &["fuel"])), Backend::Columnar);
+        let plan = planner.plan(&aggregate(sum.clone(), &[]), &capabilities).unwrap();
+        assert_eq!((plan.backend, plan.fallback), (Backend::Search, Some(Backend::Columnar)));
+
+        // Exact percentiles stay columnar; estimates may use the index
+        let median = vec![Aggregation::Median("capacity".to_string())];
+        assert_eq!(backend(&aggregate(median.clone(), &[])), Backend::Columnar);
+        assert_eq!(backend(&QueryShape { approximate: true, ..aggregate(median, &[]) }), Backend::Search);
+        assert_eq!(backend(&QueryShape { archived: true, ..aggregate(sum, &[]) }), Backend::Columnar);
+
+        // Id lookups use the search store, traversals the graph
+        let query = SearchQuery {
+            filters: vec![Filter {
+                property: "id".to_string(),
+                operator: FilterOperator::Equals,
+                value: PropertyValue::String("p1".to_string()),
+                distance: None,
+                case_insensitive: false,
+            }],
+            sort: vec![],
+            limit: Some(1),
+            offset: None,
+            search_after: None,
+        };
+        let plan = planner.plan(&QueryShape::search("plant", &query, "id"), &capabilities).unwrap();
+        assert_eq!(plan.backend, Backend::Search);
+        assert_eq!(plan.reasons, vec!["primary key lookup".to_string()]);
+        let traversal = QueryShape { object_type: "plant".to_string(), traversal: true, ..Default::default() };
+        assert_eq!(backend(&traversal), Backend::Graph);
+
+        // Without native search aggregations everything aggregates in the columnar store
+        let capabilities = BackendCapabilities { search_aggregations: false, ..all_backends() };
+        let plan = planner.plan(&aggregate(vec![Aggregation::Count], &[]), &capabilities).unwrap();
+        assert_eq!((plan.backend, plan.fallback), (Backend::Columnar, None));
+    }
+
+    #[test]
+    fn test_falls_back_from_unhealthy_backends_and_honours_overrides() {
+        let mut config = PlannerConfig::default();
+        config.overrides.insert("plant".to_string(), Backend::Columnar);
+        let planner = QueryPlanner::new(config);
+        let count = aggregate(vec![Aggregation::Count], &[]);
+
+        let plan = planner.plan(&count, &all_backends()).unwrap();
+        assert_eq!((plan.backend, plan.fallback), (Backend::Columnar, Some(Backend::Search)));
+        assert!(plan.reasons.iter().any(|r| r.contains("configured to prefer columnar")));
+
+        // The override is skipped while its backend is unhealthy, and used again once it recovers
+        planner.health().report_failure(Backend::Columnar, "connection refused");
+        let plan = planner.plan(&count, &all_backends()).unwrap();
+        assert_eq!((plan.backend, plan.fallback), (Backend::Search, Some(Backend::Columnar)));
+        assert!(plan.reasons.iter().any(|r| r == "columnar is unhealthy: connection refused"));
+        planner.health().report_success(Backend::Columnar);
+        assert_eq!(planner.plan(&count, &all_backends()).unwrap().backend, Backend::Columnar);
+
+        // A backend that cannot answer is never chosen, even when it is the only healthy one
+        let grouped = aggregate(vec![Aggregation::Count], &["fuel"]);
+        planner.health().report_failure(Backend::Columnar, "timed out");
+        let plan = planner.plan(&grouped, &all_backends()).unwrap();
+        assert_eq!((plan.backend, plan.fallback), (Backend::Columnar, None));
+        assert!(plan.reasons.iter().any(|r| r.starts_with("no healthy backend")));
+
+        // Failures expire after the retry period
+        let health = BackendHealth::new().with_retry_after(Duration::ZERO);
+        health.report_failure(Backend::Search, "down");
+        assert_eq!(health.status(Backend::Search), None);
+
+        let none = BackendCapabilities::default();
+        assert!(matches!(planner.plan(&grouped, &none), Err(StoreError::Configuration(_))));
+    }
+}
@@ -0,0 +1,640 @@
+//! Slow-query capture for the store backends.
+//!
+//! `LoggedSearchStore`, `LoggedGraphStore` and `LoggedColumnarStore` wrap a backend and time
+//! its read queries. Queries slower than the configured threshold are kept in a bounded ring
+//! buffer on the shared `QueryLog` (and optionally appended to a JSON-lines file) with the
+//! query's shape: filter values are truncated, and values filtering on properties marked
+//! `pii` in the ontology are redacted.
+
+use crate::store::{
+    AnalyticsQuery, AnalyticsResult, CentralityMetric, ColumnarStore, CommunityAlgorithm, Filter,
+    GraphLink, GraphMetrics, GraphStore, IndexedObject, LinkDirection, LinkPropertyStats, LinkQuery,
+    LinkScanPage, NewLink, ObjectScanPage, SearchQuery, SearchStore, StoreError, TraversalAggregation,
+    TraversalAggregationResult,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use ontology_engine::{OntologyHandle, PropertyMap};
+use serde::Serialize;
+use serde_json::{json, Value as JsonValue};
+use std::collections::{HashMap, VecDeque};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Placeholder for filter values on pii properties
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Array filter values beyond this many elements are dropped from the logged shape
+const MAX_LOGGED_ARRAY_LEN: usize = 10;
+
+/// Slow-query logging settings
+#[derive(Debug, Clone)]
+pub struct QueryLogConfig {
+    /// Queries taking at least this long are recorded
+    pub slow_threshold: Duration,
+    /// Number of slow queries kept in memory; the oldest are dropped first
+    pub capacity: usize,
+    /// Also append each slow query as a JSON line to this file
+    pub log_file: Option<PathBuf>,
+    /// String filter values longer than this are truncated
+    pub max_value_len: usize,
+}
+
+impl Default for QueryLogConfig {
+    fn default() -> Self {
+        Self {
+            slow_threshold: Duration::from_millis(500),
+            capacity: 1000,
+            log_file: None,
+            max_value_len: 64,
+        }
+    }
+}
+
+/// A recorded slow query
+#[derive(Debug, Clone, Serialize)]
+pub struct SlowQuery {
+    pub recorded_at: DateTime<Utc>,
+    /// Backend label given to the store wrapper, e.g. `elasticsearch`
+    pub backend: String,
+    /// Store operation, e.g. `search` or `traverse_with_filters`
+    pub operation: String,
+    pub object_type: Option<String>,
+    /// Query shape with values truncated and pii values redacted
+    pub query: JsonValue,
+    pub latency_ms: u64,
+    pub result_count: Option<u64>,
+}
+
+/// Bounded log of slow store queries, shared by the store wrappers
+pub struct QueryLog {
+    config: QueryLogConfig,
+    ontology: Option<OntologyHandle>,
+    entries: Mutex<VecDeque<SlowQuery>>,
+}
+
+impl QueryLog {
+    pub fn new(config: QueryLogConfig) -> Self {
+        Self {
+            config,
+            ontology: None,
+            entries: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    /// Redact filter values on properties the live ontology marks as `pii`
+    pub fn with_ontology(mut self, ontology: OntologyHandle) -> Self {
+        self.ontology = Some(ontology);
+        self
+    }
+
+    pub fn config(&self) -> &QueryLogConfig {
+        &self.config
+    }
+
+    /// Most recent slow queries first
+    pub fn slow_queries(&self, limit: usize) -> Vec<SlowQuery> {
+        let entries = self.entries.lock().unwrap();
+        entries.iter().rev().take(limit).cloned().collect()
+    }
+
+    pub fn clear(&self) {
+        self.entries.lock().unwrap().clear();
+    }
+
+    /// Record a query that took `latency`, if it is at or above the slow threshold
+    pub fn record(
+        &self,
+        backend: &str,
+        operation: &str,
+        object_type: Option<&str>,
+        query: JsonValue,
+        latency: Duration,
+        result_count: Option<u64>,
+    ) {
+        if latency < self.config.slow_threshold {
+            return;
+        }
+        let entry = SlowQuery {
+            recorded_at: Utc::now(),
+            backend: backend.to_string(),
+            operation: operation.to_string(),
+            object_type: object_type.map(str::to_string),
+            query,
+            latency_ms: latency.as_millis() as u64,
+            result_count,
+        };
+
+        if let Some(path) = &self.config.log_file {
+            let appended = std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .and_then(|mut file| {
+                    let line = serde_json::to_string(&entry).unwrap_or_default();
+                    writeln!(file, "{}", line)
+                });
+            if let Err(e) = appended {
+                tracing::error!("Failed to append to slow-query log {}: {}", path.display(), e);
+            }
+        }
+
+        let mut entries = self.entries.lock().unwrap();
+        if entries.len() >= self.config.capacity.max(1) {
+            entries.pop_front();
+        }
+        entries.push_back(entry);
+    }
+
+    /// Shape of a search query for logging
+    pub fn search_shape(&self, object_type: &str, query: &SearchQuery) -> JsonValue {
+        json!({
+            "filters": self.filters_shape(Some(object_type), &query.filters),
+            "sort": query.sort.iter().map(|s| json!({ "property": s.property, "ascending": s.ascending })).collect::<Vec<_>>(),
+            "limit": query.limit,
+            "offset": query.offset,
+        })
+    }
+
+    /// Shape of an analytics query for logging
+    pub fn analytics_shape(&self, object_type: &str, query: &AnalyticsQuery) -> JsonValue {
+        json!({
+            "aggregations": query.aggregations.iter().map(|a| format!("{:?}", a)).collect::<Vec<_>>(),
+            "filters": self.filters_shape(Some(object_type), &query.filters),
+            "group_by": query.group_by,
+            "sample_size": query.sampling.map(|s| s.sample_size),
+        })
+    }
+
+    /// Shape of a filter list; `object_type` is unknown for graph queries, in which case a
+    /// property marked `pii` on any type is redacted
+    pub fn filters_shape(&self, object_type: Option<&str>, filters: &[Filter]) -> JsonValue {
+        let pii = self.pii_properties();
+        let is_pii = |property: &str| match object_type {
+            Some(object_type) => pii.get(object_type).is_some_and(|props| props.iter().any(|p| p == property)),
+            None => pii.values().any(|props| props.iter().any(|p| p == property)),
+        };
+
+        JsonValue::Array(
+            filters
+                .iter()
+                .map(|filter| {
+                    let value = if is_pii(&filter.property) {
+                        JsonValue::String(REDACTED.to_string())
+                    } else {
+                        self.truncate(serde_json::to_value(&filter.value).unwrap_or(JsonValue::Null))
+                    };
+                    json!({
+                        "property": filter.property,
+                        "operator": format!("{:?}", filter.operator),
+                        "value": value,
+                        "distance": filter.distance,
+                    })
+                })
+                .collect(),
+        )
+    }
+
+    /// pii property IDs per object type in the live ontology
+    fn pii_properties(&self) -> HashMap<String, Vec<String>> {
+        let Some(handle) = &self.ontology else {
+            return HashMap::new();
+        };
+        handle
+            .load()
+            .object_types()
+            .map(|object_type| {
+                let pii = object_type
+                    .properties
+                    .iter()
+                    .filter(|p| p.pii)
+                    .map(|p| p.id.clone())
+                    .collect();
+                (object_type.id.clone(), pii)
+            })
+            .collect()
+    }
+
+    fn truncate(&self, value: JsonValue) -> JsonValue {
+        match value {
+            JsonValue::String(s) if s.chars().count() > self.config.max_value_len => {
+                let kept: String = s.chars().take(self.config.max_value_len).collect();
+                JsonValue::String(format!("{}…", kept))
+            }
+            JsonValue::Array(items) => {
+                let total = items.len();
+                let mut kept: Vec<JsonValue> = items
+                    .into_iter()
+                    .take(MAX_LOGGED_ARRAY_LEN)
+                    .map(|item| self.truncate(item))
+                    .collect();
+                if total > MAX_LOGGED_ARRAY_LEN {
+                    kept.push(JsonValue::String(format!("… ({} more)", total - MAX_LOGGED_ARRAY_LEN)));
+                }
+                JsonValue::Array(kept)
+            }
+            other => other,
+        }
+    }
+}
+
+/// Search store wrapper that records slow searches, counts and aggregations
+pub struct LoggedSearchStore {
+    inner: Arc<dyn SearchStore>,
+    backend: String,
+    log: Arc<QueryLog>,
+}
+
+impl LoggedSearchStore {
+    pub fn new(inner: Arc<dyn SearchStore>, backend: impl Into<String>, log: Arc<QueryLog>) -> Self {
+        Self {
+            inner,
+            backend: backend.into(),
+            log,
+        }
+    }
+}
+
+#[async_trait]
+impl SearchStore for LoggedSearchStore {
+    async fn index_object(
+        &self,
+        object_type: &str,
+        object_id: &str,
+        properties: &PropertyMap,
+        expected_revision: Option<u64>,
+    ) -> Result<u64, StoreError> {
+        self.inner.index_object(object_type, object_id, properties, expected_revision).await
+    }
+
+    async fn search(
+        &self,
+        object_type: &str,
+        query: &SearchQuery,
+    ) -> Result<Vec<IndexedObject>, StoreError> {
+        let started = Instant::now();
+        let result = self.inner.search(object_type, query).await;
+        self.log.record(
+            &self.backend,
+            "search",
+            Some(object_type),
+            self.log.search_shape(object_type, query),
+            started.elapsed(),
+            result.as_ref().ok().map(|objects| objects.len() as u64),
+        );
+        result
+    }
+
+    async fn get_object(
+        &self,
+        object_type: &str,
+        object_id: &str,
+    ) -> Result<Option<IndexedObject>, StoreError> {
+        self.inner.get_object(object_type, object_id).await
+    }
+
+    async fn bulk_index(&self, objects: Vec<IndexedObject>) -> Result<(), StoreError> {
+        self.inner.bulk_index(objects).await
+    }
+
+    async fn delete_object(&self, object_type: &str, object_id: &str) -> Result<(), StoreError> {
+        self.inner.delete_object(object_type, object_id).await
+    }
+
+    async fn count_objects(
+        &self,
+        object_type: &str,
+        filters: Option<&[Filter]>,
+    ) -> Result<u64, StoreError> {
+        let started = Instant::now();
+        let result = self.inner.count_objects(object_type, filters).await;
+        self.log.record(
+            &self.backend,
+            "count_objects",
+            Some(object_type),
+            json!({ "filters": self.log.filters_shape(Some(object_type), filters.unwrap_or(&[])) }),
+            started.elapsed(),
+            result.as_ref().ok().copied(),
+        );
+        result
+    }
+
+    async fn get_objects(
+        &self,
+        object_type: &str,
+        object_ids: &[String],
+    ) -> Result<HashMap<String, IndexedObject>, StoreError> {
+        self.inner.get_objects(object_type, object_ids).await
+    }
+
+    async fn content_hashes(
+        &self,
+        object_type: &str,
+        object_ids: &[String],
+    ) -> Result<HashMap<String, Option<String>>, StoreError> {
+        self.inner.content_hashes(object_type, object_ids).await
+    }
+
+    async fn scan_objects(
+        &self,
+        object_type: &str,
+        filters: &[Filter],
+        cursor: Option<&str>,
+        limit: usize,
+    ) -> Result<ObjectScanPage, StoreError> {
+        let started = Instant::now();
+        let result = self.inner.scan_objects(object_type, filters, cursor, limit).await;
+        self.log.record(
+            &self.backend,
+            "scan_objects",
+            Some(object_type),
+            json!({ "filters": self.log.filters_shape(Some(object_type), filters), "limit": limit }),
+            started.elapsed(),
+            result.as_ref().ok().map(|page| page.objects.len() as u64),
+        );
+        result
+    }
+
+    async fn aggregate(
+        &self,
+        object_type: &str,
+        query: &AnalyticsQuery,
+    ) -> Result<AnalyticsResult, StoreError> {
+        let started = Instant::now();
+        let result = self.inner.aggregate(object_type, query).await;
+        self.log.record(
+            &self.backend,
+            "aggregate",
+            Some(object_type),
+            self.log.analytics_shape(object_type, query),
+            started.elapsed(),
+            result.as_ref().ok().map(|r| r.total as u64),
+        );
+        result
+    }
+
+    fn supports_aggregations(&self) -> bool {
+        self.inner.supports_aggregations()
+    }
+
+    fn explain_search(&self, object_type: &str, query: &SearchQuery) -> Option<JsonValue> {
+        self.inner.explain_search(object_type, query)
+    }
+}
+
+/// Graph store wrapper that records slow traversals
+pub struct LoggedGraphStore {
+    inner: Arc<dyn GraphStore>,
+    backend: String,
+    log: Arc<QueryLog>,
+}
+
+impl LoggedGraphStore {
+    pub fn new(inner: Arc<dyn GraphStore>, backend: impl Into<String>, log: Arc<QueryLog>) -> Self {
+        Self {
+            inner,
+            backend: backend.into(),
+            log,
+        }
+    }
+
+    fn traversal_shape(&self, link_type_ids: &[String], max_hops: usize, filters: &[Filter]) -> JsonValue {
+        json!({
+            "link_types": link_type_ids,
+            "max_hops": max_hops,
+            "filters": self.log.filters_shape(None, filters),
+        })
+    }
+}
+
+#[async_trait]
+impl GraphStore for LoggedGraphStore {
+    async fn create_link(
+        &self,
+        link_type_id: &str,
+        source_id: &str,
+        target_id: &str,
+        properties: &PropertyMap,
+    ) -> Result<String, StoreError> {
+        self.inner.create_link(link_type_id, source_id, target_id, properties).await
+    }
+
+    async fn create_links_batch(&self, links: &[NewLink]) -> Result<Vec<String>, StoreError> {
+        self.inner.create_links_batch(links).await
+    }
+
+    async fn delete_link(&self, link_id: &str) -> Result<(), StoreError> {
+        self.inner.delete_link(link_id).await
+    }
+
+    async fn get_link(&self, link_id: &str) -> Result<Option<GraphLink>, StoreError> {
+        self.inner.get_link(link_id).await
+    }
+
+    async fn get_links(
+        &self,
+        object_id: &str,
+        link_type_id: Option<&str>,
+        direction: Option<LinkDirection>,
+        query: &LinkQuery,
+    ) -> Result<Vec<GraphLink>, StoreError> {
+        self.inner.get_links(object_id, link_type_id, direction, query).await
+    }
+
+    async fn traverse(
+        &self,
+        start_id: &str,
+        link_type_ids: &[String],
+        max_hops: usize,
+    ) -> Result<Vec<String>, StoreError> {
+        let started = Instant::now();
+        let result = self.inner.traverse(start_id, link_type_ids, max_hops).await;
+        self.log.record(
+            &self.backend,
+            "traverse",
+            None,
+            self.traversal_shape(link_type_ids, max_hops, &[]),
+            started.elapsed(),
+            result.as_ref().ok().map(|ids| ids.len() as u64),
+        );
+        result
+    }
+
+    async fn get_connected_objects(
+        &self,
+        object_id: &str,
+        link_type_id: &str,
+    ) -> Result<Vec<String>, StoreError> {
+        self.inner.get_connected_objects(object_id, link_type_id).await
+    }
+
+    async fn traverse_with_filters(
+        &self,
+        start_id: &str,
+        link_type_ids: &[String],
+        max_hops: usize,
+        link_filters: &[Filter],
+    ) -> Result<Vec<String>, StoreError> {
+        let started = Instant::now();
+        let result = self
+            .inner
+            .traverse_with_filters(start_id, link_type_ids, max_hops, link_filters)
+            .await;
+        self.log.record(
+            &self.backend,
+            "traverse_with_filters",
+            None,
+            self.traversal_shape(link_type_ids, max_hops, link_filters),
+            started.elapsed(),
+            result.as_ref().ok().map(|ids| ids.len() as u64),
+        );
+        result
+    }
+
+    async fn traverse_with_aggregation(
+        &self,
+        start_id: &str,
+        link_type_ids: &[String],
+        max_hops: usize,
+        aggregation: &TraversalAggregation,
+    ) -> Result<TraversalAggregationResult, StoreError> {
+        let started = Instant::now();
+        let result = self
+            .inner
+            .traverse_with_aggregation(start_id, link_type_ids, max_hops, aggregation)
+            .await;
+        let mut shape = self.traversal_shape(link_type_ids, max_hops, &aggregation.object_filters);
+        shape["aggregation"] = JsonValue::String(format!("{:?}", aggregation.operation));
+        self.log.record(
+            &self.backend,
+            "traverse_with_aggregation",
+            None,
+            shape,
+            started.elapsed(),
+            result.as_ref().ok().map(|r| r.count as u64),
+        );
+        result
+    }
+
+    async fn compute_centrality(
+        &self,
+        object_type: &str,
+        metric: CentralityMetric,
+    ) -> Result<HashMap<String, f64>, StoreError> {
+        self.inner.compute_centrality(object_type, metric).await
+    }
+
+    async fn detect_communities(
+        &self,
+        object_type: &str,
+        algorithm: CommunityAlgorithm,
+    ) -> Result<HashMap<String, usize>, StoreError> {
+        self.inner.detect_communities(object_type, algorithm).await
+    }
+
+    async fn shortest_path(
+        &self,
+        source_id: &str,
+        target_id: &str,
+        link_types: &[String],
+    ) -> Result<Vec<String>, StoreError> {
+        self.inner.shortest_path(source_id, target_id, link_types).await
+    }
+
+    async fn graph_metrics(&self, object_type: &str) -> Result<GraphMetrics, StoreError> {
+        self.inner.graph_metrics(object_type).await
+    }
+
+    fn explain_traversal(&self, start_id: &str, link_type_ids: &[String], max_hops: usize) -> Option<String> {
+        self.inner.explain_traversal(start_id, link_type_ids, max_hops)
+    }
+
+    async fn scan_links(
+        &self,
+        link_type_id: &str,
+        cursor: Option<&str>,
+        limit: usize,
+    ) -> Result<LinkScanPage, StoreError> {
+        self.inner.scan_links(link_type_id, cursor, limit).await
+    }
+
+    async fn link_property_stats(
+        &self,
+        link_type_id: &str,
+        property: &str,
+        buckets: usize,
+    ) -> Result<LinkPropertyStats, StoreError> {
+        self.inner.link_property_stats(link_type_id, property, buckets).await
+    }
+}
+
+/// Columnar store wrapper that records slow analytics queries
+pub struct LoggedColumnarStore {
+    inner: Arc<dyn ColumnarStore>,
+    backend: String,
+    log: Arc<QueryLog>,
+}
+
+impl LoggedColumnarStore {
+    pub fn new(inner: Arc<dyn ColumnarStore>, backend: impl Into<String>, log: Arc<QueryLog>) -> Self {
+        Self {
+            inner,
+            backend: backend.into(),
+            log,
+        }
+    }
+}
+
+#[async_trait]
+impl ColumnarStore for LoggedColumnarStore {
+    async fn write_batch(&self, object_type: &str, objects: Vec<IndexedObject>) -> Result<(), StoreError> {
+        self.inner.write_batch(object_type, objects).await
+    }
+
+    async fn query_analytics(
+        &self,
+        object_type: &str,
+        query: &AnalyticsQuery,
+    ) -> Result<AnalyticsResult, StoreError> {
+        let started = Instant::now();
+        let result = self.inner.query_analytics(object_type, query).await;
+        self.log.record(
+            &self.backend,
+            "query_analytics",
+            Some(object_type),
+            self.log.analytics_shape(object_type, query),
+            started.elapsed(),
+            result.as_ref().ok().map(|r| r.total as u64),
+        );
+        result
+    }
+
+    async fn append_partition(
+        &self,
+        object_type: &str,
+        partition: &str,
+        objects: Vec<IndexedObject>,
+    ) -> Result<String, StoreError> {
+        self.inner.append_partition(object_type, partition, objects).await
+    }
+
+    async fn read_partition(
+        &self,
+        object_type: &str,
+        partition: &str,
+        filters: &[Filter],
+    ) -> Result<Vec<IndexedObject>, StoreError> {
+        self.inner.read_partition(object_type, partition, filters).await
+    }
+
+    async fn remove_from_partition(
+        &self,
+        object_type: &str,
+        partition: &str,
+        object_ids: &[String],
+    ) -> Result<usize, StoreError> {
+        self.inner.remove_from_partition(object_type, partition, object_ids).await
+    }
+}
@@ -0,0 +1,406 @@
+//! Reverse-reference index: which objects point at a given object through an
+//! `ObjectReference` property.
+//!
+//! Answering that by scanning every referencing type with a filter per reference property
+//! is slow, so the index keeps one document per referenced object in the reserved
+//! `REFERENCE_INDEX_TYPE` of the search store, keyed `type:id`, listing the
+//! (source type, source ID, property) triples that reference it.
+//! `ReferenceIndexingSearchStore` keeps it current: it reads the stored version of an
+//! object before each write or delete of a type with reference properties, and afterwards
+//! adds and removes the entries for the references that changed. `ReferenceIndex::backfill`
+//! builds the index for data written before the wrapper was in place.
+
+use crate::jobs::JobHandle;
+use crate::store::{
+    AnalyticsQuery, AnalyticsResult, Filter, IndexedObject, ObjectScanPage, SearchQuery, SearchStore,
+    StoreError,
+};
+use crate::sync::read_modify_write;
+use async_trait::async_trait;
+use ontology_engine::{ObjectRef, OntologyHandle, PropertyMap, PropertyValue, ReferenceManager};
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::Arc;
+
+/// Search store type holding the reverse references of each referenced object, keyed `type:id`
+pub const REFERENCE_INDEX_TYPE: &str = "_references";
+
+/// Job kind recorded for reference backfills
+pub const BACKFILL_REFERENCES_JOB_KIND: &str = "backfill_references";
+
+/// Default number of search documents read per page during a backfill
+pub const DEFAULT_BACKFILL_PAGE_SIZE: usize = 500;
+
+/// Attempts at updating one index document before giving up on concurrent writers
+const MAX_UPDATE_ATTEMPTS: usize = 5;
+
+/// Property of an index document listing its sources
+const SOURCES_PROPERTY: &str = "sources";
+
+/// An object property referencing another object
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct ReferenceSource {
+    pub object_type: String,
+    pub object_id: String,
+    pub property: String,
+}
+
+impl ReferenceSource {
+    fn to_value(&self) -> PropertyValue {
+        PropertyValue::Map(HashMap::from([
+            ("object_type".to_string(), PropertyValue::String(self.object_type.clone())),
+            ("object_id".to_string(), PropertyValue::String(self.object_id.clone())),
+            ("property".to_string(), PropertyValue::String(self.property.clone())),
+        ]))
+    }
+
+    fn from_value(value: &PropertyValue) -> Option<Self> {
+        let PropertyValue::Map(fields) = value else {
+            return None;
+        };
+        let field = |key: &str| match fields.get(key) {
+            Some(PropertyValue::String(s)) => Some(s.clone()),
+            _ => None,
+        };
+        Some(Self {
+            object_type: field("object_type")?,
+            object_id: field("object_id")?,
+            property: field("property")?,
+        })
+    }
+}
+
+/// What a backfill indexed
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ReferenceBackfillReport {
+    pub objects_scanned: usize,
+    pub references_indexed: usize,
+    /// Distinct objects found to be referenced
+    pub targets: usize,
+}
+
+/// Reverse references stored in the search store
+#[derive(Clone)]
+pub struct ReferenceIndex {
+    store: Arc<dyn SearchStore>,
+    ontology: OntologyHandle,
+    page_size: usize,
+}
+
+impl ReferenceIndex {
+    /// `store` should be the unwrapped search store, so index documents are not themselves
+    /// validated or fed to change triggers
+    pub fn new(store: Arc<dyn SearchStore>, ontology: OntologyHandle) -> Self {
+        Self {
+            store,
+            ontology,
+            page_size: DEFAULT_BACKFILL_PAGE_SIZE,
+        }
+    }
+
+    pub fn with_page_size(mut self, page_size: usize) -> Self {
+        self.page_size = page_size.max(1);
+        self
+    }
+
+    /// Whether objects of the type can reference others
+    pub fn tracks(&self, object_type: &str) -> bool {
+        self.ontology
+            .load()
+            .get_object_type(object_type)
+            .is_some_and(|o| o.properties.iter().any(|p| p.holds_references()))
+    }
+
+    /// Objects referencing `object_type:object_id`, sorted
+    pub async fn references_to(&self, object_type: &str, object_id: &str) -> Result<Vec<ReferenceSource>, StoreError> {
+        let key = ObjectRef::new(object_type, object_id).to_string();
+        Ok(self
+            .store
+            .get_object(REFERENCE_INDEX_TYPE, &key)
+            .await?
+            .map(|document| sources_of(&document.properties).into_iter().collect())
+            .unwrap_or_default())
+    }
+
+    /// Update the index for a write of `object_type:object_id` from `old` to `new` (`None`
+    /// where the object does not exist)
+    pub async fn update(
+        &self,
+        object_type: &str,
+        object_id: &str,
+        old: Option<&PropertyMap>,
+        new: Option<&PropertyMap>,
+    ) -> Result<(), StoreError> {
+        let before = self.references(object_type, object_id, old);
+        let after = self.references(object_type, object_id, new);
+        let mut changes: BTreeMap<&ObjectRef, (Vec<ReferenceSource>, Vec<ReferenceSource>)> = BTreeMap::new();
+        for (target, source) in after.difference(&before) {
+            changes.entry(target).or_default().0.push(source.clone());
+        }
+        for (target, source) in before.difference(&after) {
+            changes.entry(target).or_default().1.push(source.clone());
+        }
+        for (target, (added, removed)) in changes {
+            self.apply(target, &added, &removed).await?;
+        }
+        Ok(())
+    }
+
+    /// Index the references of every stored object of the given types, or of every type
+    /// with reference properties when `object_types` is empty. Entries are only added, so
+    /// writes made while the backfill runs are kept.
+    pub async fn backfill(
+        &self,
+        object_types: &[String],
+        job: Option<&JobHandle>,
+    ) -> Result<ReferenceBackfillReport, StoreError> {
+        let object_types: Vec<String> = if object_types.is_empty() {
+            let ontology = self.ontology.load();
+            ontology.object_types().map(|o| o.id.clone()).filter(|id| self.tracks(id)).collect()
+        } else {
+            object_types.to_vec()
+        };
+
+        let mut report = ReferenceBackfillReport::default();
+        let mut found: BTreeMap<ObjectRef, Vec<ReferenceSource>> = BTreeMap::new();
+        for object_type in &object_types {
+            if let Some(job) = job {
+                let total = self.store.count_objects(object_type, None).await?;
+                job.start_phase(&format!("scan {}", object_type), Some(total as usize));
+            }
+            let mut cursor: Option<String> = None;
+            loop {
+                if let Some(job) = job {
+                    job.check_cancelled()?;
+                }
+                let page = self.store.scan_objects(object_type, &[], cursor.as_deref(), self.page_size).await?;
+                for object in &page.objects {
+                    for (target, source) in self.references(object_type, &object.object_id, Some(&object.properties)) {
+                        found.entry(target).or_default().push(source);
+                        report.references_indexed += 1;
+                    }
+                }
+                report.objects_scanned += page.objects.len();
+                if let Some(job) = job {
+                    job.advance(page.objects.len());
+                }
+                match page.next {
+                    Some(next) => cursor = Some(next),
+                    None => break,
+                }
+            }
+        }
+
+        if let Some(job) = job {
+            job.start_phase("write", Some(found.len()));
+        }
+        report.targets = found.len();
+        for (target, sources) in &found {
+            if let Some(job) = job {
+                job.check_cancelled()?;
+            }
+            self.apply(target, sources, &[]).await?;
+            if let Some(job) = job {
+                job.advance(1);
+            }
+        }
+        Ok(report)
+    }
+
+    /// References held by an object, paired with the source entry each one produces
+    fn references(
+        &self,
+        object_type: &str,
+        object_id: &str,
+        properties: Option<&PropertyMap>,
+    ) -> BTreeSet<(ObjectRef, ReferenceSource)> {
+        let ontology = self.ontology.load();
+        let (Some(object_type_def), Some(properties)) = (ontology.get_object_type(object_type), properties) else {
+            return BTreeSet::new();
+        };
+        ReferenceManager::extract_references(object_type_def, properties)
+            .into_iter()
+            .map(|(property, target)| {
+                let source = ReferenceSource {
+                    object_type: object_type.to_string(),
+                    object_id: object_id.to_string(),
+                    property,
+                };
+                (target, source)
+            })
+            .collect()
+    }
+
+    /// Add and remove sources of one target's document, retrying when another writer
+    /// updated it first
+    async fn apply(&self, target: &ObjectRef, added: &[ReferenceSource], removed: &[ReferenceSource]) -> Result<(), StoreError> {
+        let key = target.to_string();
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            let result = read_modify_write(self.store.as_ref(), REFERENCE_INDEX_TYPE, &key, |current| {
+                let mut sources = current.map(|document| sources_of(&document.properties)).unwrap_or_default();
+                sources.extend(added.iter().cloned());
+                for source in removed {
+                    sources.remove(source);
+                }
+                let mut properties = PropertyMap::new();
+                properties.insert(
+                    SOURCES_PROPERTY.to_string(),
+                    PropertyValue::Array(sources.iter().map(ReferenceSource::to_value).collect()),
+                );
+                Ok(properties)
+            })
+            .await;
+            match result {
+                Err(StoreError::Conflict(_)) if attempt < MAX_UPDATE_ATTEMPTS => continue,
+                other => return other.map(|_| ()),
+            }
+        }
+    }
+}
+
+fn sources_of(properties: &PropertyMap) -> BTreeSet<ReferenceSource> {
+    match properties.get(SOURCES_PROPERTY) {
+        Some(PropertyValue::Array(items)) => items.iter().filter_map(ReferenceSource::from_value).collect(),
+        _ => BTreeSet::new(),
+    }
+}
+
+/// Search store wrapper that keeps a `ReferenceIndex` in step with writes
+pub struct ReferenceIndexingSearchStore {
+    inner: Arc<dyn SearchStore>,
+    index: ReferenceIndex,
+}
+
+impl ReferenceIndexingSearchStore {
+    pub fn new(inner: Arc<dyn SearchStore>, index: ReferenceIndex) -> Self {
+        Self { inner, index }
+    }
+
+    async fn stored_properties(&self, object_type: &str, object_id: &str) -> Result<Option<PropertyMap>, StoreError> {
+        Ok(self.inner.get_object(object_type, object_id).await?.map(|o| o.properties))
+    }
+
+    /// The write itself already succeeded, so a failed index update is reported rather than
+    /// returned; a backfill repairs it
+    async fn update_index(&self, object_type: &str, object_id: &str, old: Option<&PropertyMap>, new: Option<&PropertyMap>) {
+        if let Err(e) = self.index.update(object_type, object_id, old, new).await {
+            tracing::warn!(
+                "failed to update reverse references of '{}:{}': {}",
+                object_type, object_id, e
+            );
+        }
+    }
+}
+
+#[async_trait]
+impl SearchStore for ReferenceIndexingSearchStore {
+    async fn index_object(
+        &self,
+        object_type: &str,
+        object_id: &str,
+        properties: &PropertyMap,
+        expected_revision: Option<u64>,
+    ) -> Result<u64, StoreError> {
+        if !self.index.tracks(object_type) {
+            return self.inner.index_object(object_type, object_id, properties, expected_revision).await;
+        }
+        let old = self.stored_properties(object_type, object_id).await?;
+        let revision = self.inner.index_object(object_type, object_id, properties, expected_revision).await?;
+        self.update_index(object_type, object_id, old.as_ref(), Some(properties)).await;
+        Ok(revision)
+    }
+
+    async fn search(
+        &self,
+        object_type: &str,
+        query: &SearchQuery,
+    ) -> Result<Vec<IndexedObject>, StoreError> {
+        self.inner.search(object_type, query).await
+    }
+
+    async fn get_object(
+        &self,
+        object_type: &str,
+        object_id: &str,
+    ) -> Result<Option<IndexedObject>, StoreError> {
+        self.inner.get_object(object_type, object_id).await
+    }
+
+    async fn bulk_index(&self, objects: Vec<IndexedObject>) -> Result<(), StoreError> {
+        let mut writes = Vec::new();
+        for object in &objects {
+            if self.index.tracks(&object.object_type) {
+                let old = self.stored_properties(&object.object_type, &object.object_id).await?;
+                writes.push((object.object_type.clone(), object.object_id.clone(), old, object.properties.clone()));
+            }
+        }
+        self.inner.bulk_index(objects).await?;
+        for (object_type, object_id, old, new) in &writes {
+            self.update_index(object_type, object_id, old.as_ref(), Some(new)).await;
+        }
+        Ok(())
+    }
+
+    async fn delete_object(&self, object_type: &str, object_id: &str) -> Result<(), StoreError> {
+        if !self.index.tracks(object_type) {
+            return self.inner.delete_object(object_type, object_id).await;
+        }
+        let old = self.stored_properties(object_type, object_id).await?;
+        self.inner.delete_object(object_type, object_id).await?;
+        self.update_index(object_type, object_id, old.as_ref(), None).await;
+        Ok(())
+    }
+
+    async fn count_objects(
+        &self,
+        object_type: &str,
+        filters: Option<&[Filter]>,
+    ) -> Result<u64, StoreError> {
+        self.inner.count_objects(object_type, filters).await
+    }
+
+    async fn get_objects(
+        &self,
+        object_type: &str,
+        object_ids: &[String],
+    ) -> Result<HashMap<String, IndexedObject>, StoreError> {
+        self.inner.get_objects(object_type, object_ids).await
+    }
+
+    async fn content_hashes(
+        &self,
+        object_type: &str,
+        object_ids: &[String],
+    ) -> Result<HashMap<String, Option<String>>, StoreError> {
+        self.inner.content_hashes(object_type, object_ids).await
+    }
+
+    async fn scan_objects(
+        &self,
+        object_type: &str,
+        filters: &[Filter],
+        cursor: Option<&str>,
+        limit: usize,
+    ) -> Result<ObjectScanPage, StoreError> {
+        self.inner.scan_objects(object_type, filters, cursor, limit).await
+    }
+
+    async fn aggregate(
+        &self,
+        object_type: &str,
+        query: &AnalyticsQuery,
+    ) -> Result<AnalyticsResult, StoreError> {
+        self.inner.aggregate(object_type, query).await
+    }
+
+    fn supports_aggregations(&self) -> bool {
+        self.inner.supports_aggregations()
+    }
+
+    fn explain_search(&self, object_type: &str, query: &SearchQuery) -> Option<JsonValue> {
+        self.inner.explain_search(object_type, query)
+    }
+}
@@ -0,0 +1,465 @@
+//! Reservoir sampling and sample-based estimates for approximate aggregations.
+//!
+//! Approximate results come with 95% error bounds:
+//! - count, sum and avg use a normal-approximation confidence interval with finite
+//!   population correction, so they collapse to the exact value when the sample is the
+//!   whole population
+//! - median and percentiles use the Dvoretzky–Kiefer–Wolfowitz rank band
+//! - stddev and variance use the normal approximation for the sample variance
+//! - distinct_count uses the GEE estimator, bounded below by the distinct values seen in
+//!   the sample and above by scaling every sample singleton by the inverse sample fraction
+//!
+//! Min and max are the sample extremes and carry no bounds.
+
+use crate::store::{Aggregation, AnalyticsResult};
+use ontology_engine::PropertyValue;
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+use std::collections::HashMap;
+
+/// Default number of rows kept when no sample size is requested
+pub const DEFAULT_SAMPLE_SIZE: usize = 10_000;
+
+/// z-score of the 95% confidence level used for error bounds
+const Z_95: f64 = 1.96;
+
+/// Sampling configuration for approximate aggregations
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SamplingOptions {
+    pub sample_size: usize,
+    /// Fixed seed for reproducible samples; a time-based seed is used when unset
+    pub seed: Option<u64>,
+}
+
+impl Default for SamplingOptions {
+    fn default() -> Self {
+        Self {
+            sample_size: DEFAULT_SAMPLE_SIZE,
+            seed: None,
+        }
+    }
+}
+
+impl SamplingOptions {
+    pub fn new(sample_size: usize) -> Self {
+        Self {
+            sample_size,
+            seed: None,
+        }
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+}
+
+/// Estimated range of an approximate aggregate at 95% confidence
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct ErrorBound {
+    pub lower: f64,
+    pub upper: f64,
+}
+
+impl ErrorBound {
+    fn around(value: f64, half_width: f64) -> Self {
+        Self {
+            lower: value - half_width,
+            upper: value + half_width,
+        }
+    }
+
+    pub fn contains(&self, value: f64) -> bool {
+        self.lower <= value && value <= self.upper
+    }
+}
+
+/// Uniform fixed-size sample over a stream of unknown length (Algorithm R)
+pub struct ReservoirSampler<T> {
+    capacity: usize,
+    seen: usize,
+    sample: Vec<T>,
+    rng: SplitMix64,
+}
+
+impl<T> ReservoirSampler<T> {
+    pub fn new(options: SamplingOptions) -> Self {
+        let seed = options.seed.unwrap_or_else(|| {
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_nanos() as u64)
+                .unwrap_or_default()
+        });
+        Self {
+            capacity: options.sample_size.max(1),
+            seen: 0,
+            sample: Vec::with_capacity(options.sample_size.clamp(1, DEFAULT_SAMPLE_SIZE)),
+            rng: SplitMix64(seed),
+        }
+    }
+
+    /// Offer the next item of the stream
+    pub fn offer(&mut self, item: T) {
+        if self.sample.len() < self.capacity {
+            self.sample.push(item);
+        } else {
+            let slot = self.rng.below(self.seen as u64 + 1) as usize;
+            if slot < self.capacity {
+                self.sample[slot] = item;
+            }
+        }
+        self.seen += 1;
+    }
+
+    /// Number of items offered so far
+    pub fn population(&self) -> usize {
+        self.seen
+    }
+
+    /// Fraction of the offered items held in the sample
+    pub fn sample_fraction(&self) -> f64 {
+        if self.seen == 0 {
+            1.0
+        } else {
+            self.sample.len() as f64 / self.seen as f64
+        }
+    }
+
+    pub fn into_sample(self) -> Vec<T> {
+        self.sample
+    }
+}
+
+/// Small, dependency-free PRNG; sampling needs uniformity, not cryptographic strength
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    fn below(&mut self, bound: u64) -> u64 {
+        ((self.next() as u128 * bound as u128) >> 64) as u64
+    }
+}
+
+/// Aggregate a uniform sample of `population` filtered rows, grouping by `group_by`.
+/// Rows carry the same column names as exact aggregations; `error_bounds` is parallel to
+/// `rows`.
+pub fn approximate_analytics(
+    sample: &[&JsonValue],
+    population: usize,
+    aggregations: &[Aggregation],
+    group_by: &[String],
+) -> AnalyticsResult {
+    let sample_fraction = if population == 0 {
+        1.0
+    } else {
+        sample.len() as f64 / population as f64
+    };
+    let estimator = Estimator {
+        sample_size: sample.len(),
+        population: population as f64,
+    };
+
+    let mut groups: Vec<(Vec<JsonValue>, Vec<&JsonValue>)> = Vec::new();
+    if group_by.is_empty() {
+        groups.push((Vec::new(), sample.to_vec()));
+    } else {
+        let mut index: HashMap<String, usize> = HashMap::new();
+        for row in sample {
+            let key: Vec<JsonValue> = group_by.iter()
+                .map(|col| row.get(col).cloned().unwrap_or(JsonValue::Null))
+                .collect();
+            let slot = *index.entry(JsonValue::Array(key.clone()).to_string()).or_insert_with(|| {
+                groups.push((key, Vec::new()));
+                groups.len() - 1
+            });
+            groups[slot].1.push(row);
+        }
+        groups.sort_by_key(|(key, _)| JsonValue::Array(key.clone()).to_string());
+    }
+
+    let mut rows = Vec::with_capacity(groups.len());
+    let mut error_bounds = Vec::with_capacity(groups.len());
+    for (key, members) in groups {
+        let mut row = HashMap::new();
+        let mut bounds = HashMap::new();
+        for (col, value) in group_by.iter().zip(key) {
+            let value = serde_json::from_value(value.clone())
+                .unwrap_or_else(|_| PropertyValue::String(value.to_string()));
+            row.insert(col.clone(), value);
+        }
+        for aggregation in aggregations {
+            if let Some((column, value, bound)) = estimator.estimate(aggregation, &members) {
+                if let Some(bound) = bound {
+                    bounds.insert(column.clone(), bound);
+                }
+                row.insert(column, value);
+            }
+        }
+        rows.push(row);
+        error_bounds.push(bounds);
+    }
+
+    AnalyticsResult {
+        total: rows.len(),
+        rows,
+        sample_fraction: Some(sample_fraction),
+        error_bounds,
+    }
+}
+
+struct Estimator {
+    sample_size: usize,
+    population: f64,
+}
+
+impl Estimator {
+    /// Estimated number of population rows represented by `members`
+    fn group_population(&self, members: usize) -> f64 {
+        if self.sample_size == 0 {
+            0.0
+        } else {
+            self.population * members as f64 / self.sample_size as f64
+        }
+    }
+
+    fn estimate(
+        &self,
+        aggregation: &Aggregation,
+        members: &[&JsonValue],
+    ) -> Option<(String, PropertyValue, Option<ErrorBound>)> {
+        let numbers = |prop: &str| -> Vec<f64> {
+            members.iter().filter_map(|o| o.get(prop)).filter_map(|v| v.as_f64()).collect()
+        };
+        let n = self.sample_size as f64;
+
+        Some(match aggregation {
+            Aggregation::Count => {
+                // Group share of the sample as a binomial proportion
+                let share = if n > 0.0 { members.len() as f64 / n } else { 0.0 };
+                let se = if n > 0.0 { (share * (1.0 - share) / n).sqrt() * fpc_for(self.population, self.sample_size) } else { 0.0 };
+                let count = self.population * share;
+                (
+                    "count".to_string(),
+                    PropertyValue::Integer(count.round() as i64),
+                    Some(ErrorBound::around(count, Z_95 * se * self.population)),
+                )
+            }
+            Aggregation::Sum(prop) => {
+                // Horvitz–Thompson: rows outside the group contribute zero
+                let values = numbers(prop);
+                let total: f64 = values.iter().sum();
+                let sum_sq: f64 = values.iter().map(|v| v * v).sum();
+                let mean = if n > 0.0 { total / n } else { 0.0 };
+                let variance = if n > 1.0 { (sum_sq - n * mean * mean).max(0.0) / (n - 1.0) } else { 0.0 };
+                let sum = mean * self.population;
+                let se = self.population * (variance / n.max(1.0)).sqrt() * fpc_for(self.population, self.sample_size);
+                (format!("sum_{}", prop), PropertyValue::Double(sum), Some(ErrorBound::around(sum, Z_95 * se)))
+            }
+            Aggregation::Avg(prop) => {
+                let values = numbers(prop);
+                let (mean, variance) = mean_and_variance(&values);
+                let m = values.len();
+                let fpc = fpc_for(self.group_population(members.len()), m);
+                let se = if m > 0 { (variance / m as f64).sqrt() * fpc } else { 0.0 };
+                (format!("avg_{}", prop), PropertyValue::Double(mean), Some(ErrorBound::around(mean, Z_95 * se)))
+            }
+            Aggregation::Min(prop) => {
+                let min = numbers(prop).into_iter().fold(f64::INFINITY, f64::min);
+                (format!("min_{}", prop), PropertyValue::Double(if min.is_finite() { min } else { 0.0 }), None)
+            }
+            Aggregation::Max(prop) => {
+                let max = numbers(prop).into_iter().fold(f64::NEG_INFINITY, f64::max);
+                (format!("max_{}", prop), PropertyValue::Double(if max.is_finite() { max } else { 0.0 }), None)
+            }
+            Aggregation::Median(prop) => {
+                let (value, bound) = quantile_with_bound(numbers(prop), 0.5);
+                (format!("median_{}", prop), PropertyValue::Double(value), bound)
+            }
+            Aggregation::Percentile(prop, pct) => {
+                let (value, bound) = quantile_with_bound(numbers(prop), *pct);
+                (format!("p{}_{}", (*pct * 100.0) as u8, prop), PropertyValue::Double(value), bound)
+            }
+            Aggregation::Variance(prop) | Aggregation::StdDev(prop) => {
+                let values = numbers(prop);
+                let (_, variance) = mean_and_variance(&values);
+                let m = values.len() as f64;
+                let half_width = if m > 1.0 { Z_95 * variance * (2.0 / (m - 1.0)).sqrt() } else { 0.0 };
+                let bound = ErrorBound::around(variance, half_width);
+                let bound = ErrorBound { lower: bound.lower.max(0.0), upper: bound.upper };
+                if matches!(aggregation, Aggregation::StdDev(_)) {
+                    let bound = ErrorBound { lower: bound.lower.sqrt(), upper: bound.upper.sqrt() };
+                    (format!("stddev_{}", prop), PropertyValue::Double(variance.sqrt()), Some(bound))
+                } else {
+                    (format!("variance_{}", prop), PropertyValue::Double(variance), Some(bound))
+                }
+            }
+            Aggregation::DistinctCount(prop) => {
+                let mut frequencies: HashMap<String, usize> = HashMap::new();
+                for value in members.iter().filter_map(|o| o.get(prop)) {
+                    *frequencies.entry(value.to_string()).or_default() += 1;
+                }
+                let seen = frequencies.len() as f64;
+                let singletons = frequencies.values().filter(|&&f| f == 1).count() as f64;
+                let group_population = self.group_population(members.len());
+                let scale = if members.is_empty() { 1.0 } else { (group_population / members.len() as f64).max(1.0) };
+                // GEE: singletons stand for sqrt(N/n) distinct values each
+                let estimate = scale.sqrt() * singletons + (seen - singletons);
+                let upper = (seen - singletons + singletons * scale).min(group_population.max(seen));
+                (
+                    format!("distinct_count_{}", prop),
+                    PropertyValue::Integer(estimate.round() as i64),
+                    Some(ErrorBound { lower: seen, upper }),
+                )
+            }
+            Aggregation::TopN(..) | Aggregation::BottomN(..) => return None,
+        })
+    }
+}
+
+/// Finite population correction for a sample of `n` out of `population`
+fn fpc_for(population: f64, n: usize) -> f64 {
+    if population <= 1.0 {
+        return 0.0;
+    }
+    ((population - n as f64) / (population - 1.0)).max(0.0).sqrt()
+}
+
+/// Mean and sample variance (n - 1 denominator)
+fn mean_and_variance(values: &[f64]) -> (f64, f64) {
+    if values.is_empty() {
+        return (0.0, 0.0);
+    }
+    let mean = values.iter().sum::<f64>() / values.len() as f64;
+    let variance = if values.len() < 2 {
+        0.0
+    } else {
+        values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (values.len() - 1) as f64
+    };
+    (mean, variance)
+}
+
+/// Sample quantile plus the DKW 95% band: the true quantile lies between the sample
+/// quantiles at `pct ± sqrt(ln(2 / 0.05) / 2n)`
+fn quantile_with_bound(mut values: Vec<f64>, pct: f64) -> (f64, Option<ErrorBound>) {
+    if values.is_empty() {
+        return (0.0, None);
+    }
+    values.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    let at = |p: f64| {
+        let idx = ((values.len() as f64 * p.clamp(0.0, 1.0)) as usize).min(values.len() - 1);
+        values[idx]
+    };
+    let epsilon = ((2.0f64 / 0.05).ln() / (2.0 * values.len() as f64)).sqrt();
+    (at(pct), Some(ErrorBound { lower: at(pct - epsilon), upper: at(pct + epsilon) }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// 100k rows: `value` uniform-ish in [0, 1000), `category` in 0..5, `user` in 0..2000
+    fn dataset() -> Vec<JsonValue> {
+        let mut rng = SplitMix64(42);
+        (0..100_000)
+            .map(|i| {
+                json!({
+                    "value": (rng.below(100_000) as f64) / 100.0,
+                    "category": format!("c{}", i % 5),
+                    "user": rng.below(2_000),
+                })
+            })
+            .collect()
+    }
+
+    fn sample(rows: &[JsonValue], size: usize) -> Vec<&JsonValue> {
+        let mut sampler = ReservoirSampler::new(SamplingOptions::new(size).with_seed(7));
+        for row in rows {
+            sampler.offer(row);
+        }
+        assert_eq!(sampler.population(), rows.len());
+        sampler.into_sample()
+    }
+
+    fn number(value: &PropertyValue) -> f64 {
+        match value {
+            PropertyValue::Double(d) => *d,
+            PropertyValue::Integer(i) => *i as f64,
+            other => panic!("expected a number, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_reservoir_keeps_fixed_size_uniform_sample() {
+        let mut sampler = ReservoirSampler::new(SamplingOptions::new(1_000).with_seed(1));
+        for i in 0..50_000u32 {
+            sampler.offer(i);
+        }
+        assert!((sampler.sample_fraction() - 0.02).abs() < 1e-9);
+        let sample = sampler.into_sample();
+        assert_eq!(sample.len(), 1_000);
+        // Roughly half the sample comes from each half of the stream
+        let low = sample.iter().filter(|&&i| i < 25_000).count();
+        assert!((400..600).contains(&low), "skewed sample: {} of 1000 from first half", low);
+    }
+
+    #[test]
+    fn test_approximate_results_stay_within_error_bounds() {
+        let rows = dataset();
+        let all: Vec<&JsonValue> = rows.iter().collect();
+        let aggregations = vec![
+            Aggregation::Count,
+            Aggregation::Sum("value".to_string()),
+            Aggregation::Avg("value".to_string()),
+            Aggregation::Median("value".to_string()),
+            Aggregation::Percentile("value".to_string(), 0.95),
+            Aggregation::StdDev("value".to_string()),
+            Aggregation::DistinctCount("user".to_string()),
+        ];
+
+        let exact = approximate_analytics(&all, all.len(), &aggregations, &[]);
+        assert_eq!(exact.sample_fraction, Some(1.0));
+
+        let sampled = sample(&rows, 5_000);
+        let approximate = approximate_analytics(&sampled, rows.len(), &aggregations, &[]);
+        assert_eq!(approximate.sample_fraction, Some(0.05));
+
+        let (exact_row, row, bounds) = (&exact.rows[0], &approximate.rows[0], &approximate.error_bounds[0]);
+        for column in ["count", "sum_value", "avg_value", "median_value", "p95_value", "stddev_value", "distinct_count_user"] {
+            let truth = number(&exact_row[column]);
+            let bound = bounds[column];
+            assert!(bound.contains(truth), "{}: exact {} outside {:?}", column, truth, bound);
+            assert!(bound.contains(number(&row[column])), "{}: estimate outside its own bound", column);
+        }
+        assert_eq!(number(&row["count"]), 100_000.0);
+        // The average of a 5% sample lands within 2% of the exact value
+        assert!((number(&row["avg_value"]) / number(&exact_row["avg_value"]) - 1.0).abs() < 0.02);
+    }
+
+    #[test]
+    fn test_grouped_approximation_estimates_group_sizes() {
+        let rows = dataset();
+        let sampled = sample(&rows, 5_000);
+        let result = approximate_analytics(
+            &sampled,
+            rows.len(),
+            &[Aggregation::Count, Aggregation::Avg("value".to_string())],
+            &["category".to_string()],
+        );
+        assert_eq!(result.rows.len(), 5);
+        assert_eq!(result.rows[0]["category"], PropertyValue::String("c0".to_string()));
+        // Every category holds exactly 20,000 rows; 95% bounds may miss one group in twenty
+        let covered = result.error_bounds.iter().filter(|b| b["count"].contains(20_000.0)).count();
+        assert!(covered >= 4, "only {} of 5 group counts within bounds", covered);
+        for row in &result.rows {
+            assert!((number(&row["count"]) - 20_000.0).abs() < 1_500.0);
+        }
+    }
+}
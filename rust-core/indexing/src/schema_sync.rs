@@ -0,0 +1,103 @@
+use crate::store::{DgraphStore, ElasticsearchStore, StoreError};
+use ontology_engine::{OntologyChange, OntologyHandle};
+use std::sync::Arc;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::task::JoinHandle;
+
+/// Keeps backend schemas in step with the live ontology: index mappings for new types and
+/// properties, and edge predicates for new or changed link types.
+pub struct SchemaSync {
+    ontology: OntologyHandle,
+    search: Option<Arc<ElasticsearchStore>>,
+    graph: Option<Arc<DgraphStore>>,
+}
+
+impl SchemaSync {
+    pub fn new(ontology: OntologyHandle) -> Self {
+        Self {
+            ontology,
+            search: None,
+            graph: None,
+        }
+    }
+
+    pub fn with_search_store(mut self, search: Arc<ElasticsearchStore>) -> Self {
+        self.search = Some(search);
+        self
+    }
+
+    pub fn with_graph_store(mut self, graph: Arc<DgraphStore>) -> Self {
+        self.graph = Some(graph);
+        self
+    }
+
+    /// Listen for ontology changes in the background; abort the returned task to stop
+    pub fn spawn(self) -> JoinHandle<()> {
+        let mut changes = self.ontology.subscribe_changes();
+        tokio::spawn(async move {
+            loop {
+                let result = match changes.recv().await {
+                    Ok(change) => self.apply(&change).await,
+                    // Missed events: resync everything from the current snapshot
+                    Err(RecvError::Lagged(_)) => self.resync().await,
+                    Err(RecvError::Closed) => break,
+                };
+                if let Err(e) = result {
+                    tracing::error!("Error syncing backend schema: {}", e);
+                }
+            }
+        })
+    }
+
+    /// Apply a single ontology change to the backends
+    pub async fn apply(&self, change: &OntologyChange) -> Result<(), StoreError> {
+        let ontology = self.ontology.load();
+        match change {
+            OntologyChange::TypeAdded { object_type }
+            | OntologyChange::PropertyAdded { object_type, .. } => {
+                if let (Some(search), Some(object_type_def)) =
+                    (&self.search, ontology.get_object_type(object_type))
+                {
+                    search.put_object_type_mapping(object_type_def).await?;
+                }
+            }
+            OntologyChange::LinkTypeChanged { link_type } => {
+                // Removed link types keep their predicate so existing edges stay readable
+                if let (Some(graph), Some(_)) = (&self.graph, ontology.get_link_type(link_type)) {
+                    graph.register_link_predicate(link_type).await?;
+                }
+            }
+            OntologyChange::PropertyIndexingChanged { object_type, property } => {
+                return Err(StoreError::Configuration(format!(
+                    "Indexing of '{}.{}' changed; reindex '{}' to apply the new mapping",
+                    object_type, property, object_type
+                )));
+            }
+            // Indexed data for removed types is retained until explicitly purged
+            OntologyChange::TypeRemoved { .. } => {}
+            // Mappings only gain fields; documents keep removed or redefined properties until
+            // the type is reindexed
+            OntologyChange::TypeChanged { .. }
+            | OntologyChange::PropertyRemoved { .. }
+            | OntologyChange::PropertyChanged { .. } => {}
+            OntologyChange::Reloaded { .. } => self.resync().await?,
+        }
+        Ok(())
+    }
+
+    /// Register every mapping and predicate in the current ontology
+    pub async fn resync(&self) -> Result<(), StoreError> {
+        let ontology = self.ontology.load();
+        if let Some(search) = &self.search {
+            for object_type in ontology.object_types() {
+                search.put_object_type_mapping(object_type).await?;
+            }
+        }
+        if let Some(graph) = &self.graph {
+            for link_type in ontology.link_types() {
+                graph.register_link_predicate(&link_type.id).await?;
+            }
+        }
+        Ok(())
+    }
+}
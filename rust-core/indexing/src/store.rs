@@ -11,9 +11,13 @@ use elasticsearch::{
     BulkParts,
     CountParts,
     DeleteParts,
+    MgetParts,
     indices::IndicesExistsParts,
+    http::request::JsonBody,
 };
 use serde_json::{Value as JsonValue, json};
+use crate::in_memory::matches_filters;
+use crate::sampling::{approximate_analytics, ErrorBound, ReservoirSampler, SamplingOptions};
 use dgraph_tonic::{Client as DgraphClient, Mutation, Operation, Query, Mutate};
 use polars::prelude::*;
 use std::io::Cursor;
@@ -23,13 +27,18 @@ use std::path::Path;
 /// Abstract trait for search store backends (Elasticsearch, etc.)
 #[async_trait]
 pub trait SearchStore: Send + Sync {
-    /// Index an object
+    /// Index an object and return its new revision.
+    ///
+    /// With `expected_revision` the write only succeeds if the stored object is still at that
+    /// revision (`Some(0)` means it must not exist yet); otherwise it fails with
+    /// `StoreError::Conflict` carrying the current object. `None` writes unconditionally.
     async fn index_object(
         &self,
         object_type: &str,
         object_id: &str,
         properties: &PropertyMap,
-    ) -> Result<(), StoreError>;
+        expected_revision: Option<u64>,
+    ) -> Result<u64, StoreError>;
     
     /// Search for objects matching the query
     async fn search(
@@ -64,6 +73,98 @@ pub trait SearchStore: Send + Sync {
         object_type: &str,
         filters: Option<&[Filter]>,
     ) -> Result<u64, StoreError>;
+    
+    /// Run aggregations in the search backend. Backends without native aggregations
+    /// return a query error so callers can fall back to the analytics path.
+    async fn aggregate(
+        &self,
+        object_type: &str,
+        query: &AnalyticsQuery,
+    ) -> Result<AnalyticsResult, StoreError> {
+        Err(StoreError::Query(format!(
+            "Aggregations on '{}' are not supported by this search store",
+            object_type
+        )))
+    }
+    
+    /// Whether `aggregate` runs natively rather than always failing. Query planning uses
+    /// this to decide if the search backend can answer aggregations.
+    fn supports_aggregations(&self) -> bool {
+        false
+    }
+    
+    /// The backend-native request `search` would send for this query, for profiling.
+    /// Backends without a query language return `None`.
+    fn explain_search(&self, _object_type: &str, _query: &SearchQuery) -> Option<JsonValue> {
+        None
+    }
+    
+    /// One page of every object matching `filters`, for exports and other full scans.
+    /// Pass the previous page's `next` cursor to continue; the scan is done when `next` is
+    /// `None`. The default pages with offsets, so backends with a bounded result window
+    /// should override it.
+    async fn scan_objects(
+        &self,
+        object_type: &str,
+        filters: &[Filter],
+        cursor: Option<&str>,
+        limit: usize,
+    ) -> Result<ObjectScanPage, StoreError> {
+        let offset = match cursor {
+            Some(cursor) => cursor
+                .parse::<usize>()
+                .map_err(|_| StoreError::Query(format!("Invalid scan cursor '{}'", cursor)))?,
+            None => 0,
+        };
+        let query = SearchQuery {
+            filters: filters.to_vec(),
+            sort: vec![],
+            limit: Some(limit),
+            offset: Some(offset),
+            search_after: None,
+        };
+        let objects = self.search(object_type, &query).await?;
+        let next = (objects.len() == limit).then(|| (offset + limit).to_string());
+        Ok(ObjectScanPage { objects, next })
+    }
+    
+    /// The given objects that exist, by object ID. The default fetches each object, so
+    /// backends that can read several documents in one request should override it.
+    async fn get_objects(
+        &self,
+        object_type: &str,
+        object_ids: &[String],
+    ) -> Result<HashMap<String, IndexedObject>, StoreError> {
+        let mut objects = HashMap::new();
+        for object_id in object_ids {
+            if let Some(object) = self.get_object(object_type, object_id).await? {
+                objects.insert(object_id.clone(), object);
+            }
+        }
+        Ok(objects)
+    }
+    
+    /// Stored content hashes (`_content_hash`) of the given objects that exist, by object ID;
+    /// `None` for objects written without one. The default fetches each object, so backends
+    /// that can read a single field in bulk should override it.
+    async fn content_hashes(
+        &self,
+        object_type: &str,
+        object_ids: &[String],
+    ) -> Result<HashMap<String, Option<String>>, StoreError> {
+        let mut hashes = HashMap::new();
+        for object_id in object_ids {
+            let Some(object) = self.get_object(object_type, object_id).await? else {
+                continue;
+            };
+            let hash = match object.properties.get(ontology_engine::CONTENT_HASH_PROPERTY) {
+                Some(ontology_engine::PropertyValue::String(hash)) => Some(hash.clone()),
+                _ => None,
+            };
+            hashes.insert(object_id.clone(), hash);
+        }
+        Ok(hashes)
+    }
 }
 
 /// Abstract trait for graph store backends (Dgraph, Neo4j, etc.)
@@ -78,18 +179,35 @@ pub trait GraphStore: Send + Sync {
         properties: &PropertyMap,
     ) -> Result<String, StoreError>;
     
+    /// Create several links, returning their IDs in the same order. The default creates
+    /// them one at a time; backends that can write a batch in fewer round trips override it.
+    async fn create_links_batch(&self, links: &[NewLink]) -> Result<Vec<String>, StoreError> {
+        let mut link_ids = Vec::with_capacity(links.len());
+        for link in links {
+            link_ids.push(
+                self.create_link(&link.link_type_id, &link.source_id, &link.target_id, &link.properties)
+                    .await?,
+            );
+        }
+        Ok(link_ids)
+    }
+    
     /// Delete a link
     async fn delete_link(
         &self,
         link_id: &str,
     ) -> Result<(), StoreError>;
     
-    /// Get all links connected to an object
+    /// Get a link by ID, or `None` if there is no such link
+    async fn get_link(&self, link_id: &str) -> Result<Option<GraphLink>, StoreError>;
+    
+    /// Get links connected to an object, filtered, sorted and paginated by `query`
     async fn get_links(
         &self,
         object_id: &str,
         link_type_id: Option<&str>,
         direction: Option<LinkDirection>,
+        query: &LinkQuery,
     ) -> Result<Vec<GraphLink>, StoreError>;
     
     /// Traverse the graph from a starting object
@@ -152,6 +270,57 @@ pub trait GraphStore: Send + Sync {
         &self,
         object_type: &str,
     ) -> Result<GraphMetrics, StoreError>;
+    
+    /// The backend-native query text `traverse` would run, for profiling.
+    /// Backends without a query language return `None`.
+    fn explain_traversal(&self, _start_id: &str, _link_type_ids: &[String], _max_hops: usize) -> Option<String> {
+        None
+    }
+    
+    /// One page of the stored links of a type, for maintenance scans. Pass the previous
+    /// page's `next` cursor to continue; the scan is done when `next` is `None`.
+    async fn scan_links(
+        &self,
+        _link_type_id: &str,
+        _cursor: Option<&str>,
+        _limit: usize,
+    ) -> Result<LinkScanPage, StoreError> {
+        Err(StoreError::Query("This graph store does not support link scans".to_string()))
+    }
+    
+    /// Count, range, mean and a `buckets`-bucket histogram of a numeric link property over
+    /// every link of a type. Links without a numeric value for the property are not counted.
+    /// The default implementation reads the links through `scan_links`.
+    async fn link_property_stats(
+        &self,
+        link_type_id: &str,
+        property: &str,
+        buckets: usize,
+    ) -> Result<LinkPropertyStats, StoreError> {
+        let mut values = Vec::new();
+        let mut cursor: Option<String> = None;
+        loop {
+            let page = self.scan_links(link_type_id, cursor.as_deref(), LINK_STATS_PAGE_SIZE).await?;
+            values.extend(page.links.iter().filter_map(|link| numeric_value(link.properties.get(property)?)));
+            match page.next {
+                Some(next) => cursor = Some(next),
+                None => break,
+            }
+        }
+        LinkPropertyStats::from_values(&values, buckets)
+    }
+}
+
+/// Links (or, for Dgraph, source nodes) read per page when computing link property stats
+pub const LINK_STATS_PAGE_SIZE: usize = 1000;
+
+/// Numeric value of a property, for statistics
+pub(crate) fn numeric_value(value: &ontology_engine::PropertyValue) -> Option<f64> {
+    match value {
+        ontology_engine::PropertyValue::Integer(i) => Some(*i as f64),
+        ontology_engine::PropertyValue::Double(d) if d.is_finite() => Some(*d),
+        _ => None,
+    }
 }
 
 /// Abstract trait for columnar store backends (Parquet, S3, etc.)
@@ -170,6 +339,528 @@ pub trait ColumnarStore: Send + Sync {
         object_type: &str,
         query: &AnalyticsQuery,
     ) -> Result<AnalyticsResult, StoreError>;
+    
+    /// Add objects to a named partition of an object type, kept apart from the data
+    /// `write_batch` writes; objects already in the partition are replaced by ID.
+    /// Returns where the partition is stored.
+    async fn append_partition(
+        &self,
+        object_type: &str,
+        partition: &str,
+        _objects: Vec<IndexedObject>,
+    ) -> Result<String, StoreError> {
+        Err(StoreError::Configuration(format!(
+            "Columnar store does not support partitions (writing {}/{})",
+            object_type, partition
+        )))
+    }
+    
+    /// Objects in a partition of an object type matching all `filters`
+    async fn read_partition(
+        &self,
+        _object_type: &str,
+        _partition: &str,
+        _filters: &[Filter],
+    ) -> Result<Vec<IndexedObject>, StoreError> {
+        Ok(Vec::new())
+    }
+    
+    /// Remove objects from a partition by ID, returning how many were removed
+    async fn remove_from_partition(
+        &self,
+        _object_type: &str,
+        _partition: &str,
+        _object_ids: &[String],
+    ) -> Result<usize, StoreError> {
+        Ok(0)
+    }
+}
+
+/// Elasticsearch mapping body for the typed properties of an object type.
+/// GeoJSON properties are `geo_shape` fields, so they must be mapped before the first
+/// geometry is indexed. Strings are left to dynamic
+/// mapping so they keep their text + keyword fields, unless an indexing hint narrows them:
+/// `not_indexed` properties are only kept in `_source`, and `keyword_only` / `text_only`
+/// strings get a single field without the other multifield. The ACL fields searches are
+/// pre-filtered on are `keyword`, so principals match exactly, including the `*` wildcard.
+pub fn object_type_mapping(object_type: &ontology_engine::ObjectType) -> JsonValue {
+    use ontology_engine::{IndexingHint, PropertyType};
+    
+    let mut properties = serde_json::Map::new();
+    for property in &object_type.properties {
+        let mapping = match property.indexing_hint() {
+            // Disabled object fields accept any value and skip parsing it entirely
+            IndexingHint::NotIndexed => json!({ "type": "object", "enabled": false }),
+            IndexingHint::KeywordOnly => json!({ "type": "keyword" }),
+            IndexingHint::TextOnly => json!({ "type": "text" }),
+            IndexingHint::Full => {
+                let field_type = match &property.property_type {
+                    PropertyType::Integer | PropertyType::Int => "long",
+                    PropertyType::Double | PropertyType::Float => "double",
+                    PropertyType::Boolean | PropertyType::Bool => "boolean",
+                    PropertyType::Date | PropertyType::DateTime | PropertyType::Timestamp => "date",
+                    PropertyType::ObjectReference | PropertyType::ObjectReferenceAlt => "keyword",
+                    PropertyType::GeoJSON | PropertyType::GeoJSONAlt => "geo_shape",
+                    _ => continue,
+                };
+                json!({ "type": field_type })
+            }
+        };
+        properties.insert(property.id.clone(), mapping);
+    }
+    for field in [security::acl::ACL_READERS_FIELD, security::acl::ACL_DENIED_FIELD] {
+        properties.insert(field.to_string(), json!({ "type": "keyword" }));
+    }
+    json!({ "properties": properties })
+}
+
+/// Convert an Elasticsearch search hit back into an indexed object
+fn search_hit_to_object(object_type: &str, hit: &JsonValue) -> Result<IndexedObject, StoreError> {
+    let source = hit.get("_source")
+        .ok_or_else(|| StoreError::Query("Missing _source in hit".to_string()))?;
+    
+    let id = hit.get("_id")
+        .and_then(|v| v.as_str())
+        .unwrap_or("");
+    
+    // Convert JSON back to PropertyMap
+    let mut properties = PropertyMap::new();
+    if let Some(obj) = source.as_object() {
+        for (key, value) in obj {
+            // Skip metadata fields
+            if key == "object_id" || key == "object_type" || key == "indexed_at"
+                || key == security::acl::ACL_READERS_FIELD || key == security::acl::ACL_DENIED_FIELD {
+                continue;
+            }
+            
+            let prop_value = document_property_value(value)
+                .map_err(|e| StoreError::Query(format!("Failed to deserialize property '{}': {}", key, e)))?;
+            properties.insert(key.clone(), prop_value);
+        }
+    }
+    
+    let indexed_at = source.get("indexed_at")
+        .and_then(|v| v.as_str())
+        .and_then(|s| chrono::DateTime::parse_from_rfc3339(s).ok())
+        .map(|dt| dt.with_timezone(&chrono::Utc))
+        .unwrap_or_else(chrono::Utc::now);
+    
+    Ok(IndexedObject {
+        object_type: object_type.to_string(),
+        object_id: id.to_string(),
+        properties,
+        indexed_at,
+        source_last_modified: None,
+        refresh_frequency: None,
+        next_refresh: None,
+        refresh_status: RefreshStatus::UpToDate,
+        revision: hit["_version"].as_u64().unwrap_or(0),
+    })
+}
+
+/// Elasticsearch document for an object's properties
+fn object_document(properties: &PropertyMap) -> Result<JsonValue, StoreError> {
+    // Flatten the object's ACL into terms fields so searches can pre-filter on principals
+    let properties = &security::acl::with_acl_index_fields(properties)
+        .map_err(|e| StoreError::Serialization(format!("Invalid object ACL: {}", e)))?;
+
+    // Serialize PropertyMap to JSON
+    // PropertyMap has a private "properties" field, so we need to build the JSON manually
+    // to get a flat structure (just the property key-value pairs)
+    let mut json_map = serde_json::Map::new();
+    for (key, value) in properties.iter() {
+        // GeoJSON is indexed as the geometry object a `geo_shape` field parses, not as the
+        // serialized string it is held as
+        let json_value = match value {
+            ontology_engine::PropertyValue::GeoJSON(gj) => serde_json::from_str(gj),
+            value => serde_json::to_value(value),
+        }
+        .map_err(|e| StoreError::Serialization(format!("Failed to serialize property '{}': {}", key, e)))?;
+        json_map.insert(key.clone(), json_value);
+    }
+    Ok(JsonValue::Object(json_map))
+}
+
+/// A property of a stored document; the geometry objects `object_document` writes for
+/// GeoJSON properties come back as GeoJSON
+fn document_property_value(value: &JsonValue) -> Result<ontology_engine::PropertyValue, serde_json::Error> {
+    let is_geometry = value.get("type").and_then(JsonValue::as_str).is_some_and(|geometry_type| {
+        matches!(
+            geometry_type,
+            "Point" | "MultiPoint" | "LineString" | "MultiLineString" | "Polygon" | "MultiPolygon" | "GeometryCollection"
+        )
+    }) && (value.get("coordinates").is_some() || value.get("geometries").is_some());
+    if is_geometry {
+        return Ok(ontology_engine::PropertyValue::GeoJSON(value.to_string()));
+    }
+    serde_json::from_value(value.clone())
+}
+
+/// The object stored in a document of a get or `_mget` response
+fn document_object(object_type: &str, object_id: &str, document: &JsonValue) -> Result<IndexedObject, StoreError> {
+    // Extract source document
+    let source = document.get("_source")
+        .ok_or_else(|| StoreError::ReadError("Missing _source in response".to_string()))?;
+    
+    // Convert JSON back to PropertyMap
+    let mut properties = PropertyMap::new();
+    if let Some(obj) = source.as_object() {
+        for (key, value) in obj {
+            // Skip metadata fields
+            if key == "object_id" || key == "object_type" || key == "indexed_at"
+                || key == security::acl::ACL_READERS_FIELD || key == security::acl::ACL_DENIED_FIELD {
+                continue;
+            }
+            
+            let prop_value = document_property_value(value)
+                .map_err(|e| StoreError::ReadError(format!("Failed to deserialize property '{}': {}", key, e)))?;
+            properties.insert(key.clone(), prop_value);
+        }
+    }
+    
+    // Extract indexed_at from source or use current time
+    let indexed_at = source.get("indexed_at")
+        .and_then(|v| v.as_str())
+        .and_then(|s| chrono::DateTime::parse_from_rfc3339(s).ok())
+        .map(|dt| dt.with_timezone(&chrono::Utc))
+        .unwrap_or_else(chrono::Utc::now);
+    
+    Ok(IndexedObject {
+        object_type: object_type.to_string(),
+        object_id: object_id.to_string(),
+        properties,
+        indexed_at,
+        source_last_modified: None,
+        refresh_frequency: None,
+        next_refresh: None,
+        refresh_status: RefreshStatus::UpToDate,
+        revision: document["_version"].as_u64().unwrap_or(0),
+    })
+}
+
+/// Objects of the documents an `_mget` response found, by document ID
+fn mget_objects(object_type: &str, response: &JsonValue) -> Result<HashMap<String, IndexedObject>, StoreError> {
+    let docs = response["docs"].as_array().map(Vec::as_slice).unwrap_or_default();
+    let mut objects = HashMap::new();
+    for doc in docs.iter().filter(|doc| doc["found"].as_bool() == Some(true)) {
+        let Some(object_id) = doc["_id"].as_str() else {
+            continue;
+        };
+        objects.insert(object_id.to_string(), document_object(object_type, object_id, doc)?);
+    }
+    Ok(objects)
+}
+
+/// Content hashes of the documents an `_mget` response found, by document ID
+fn mget_content_hashes(response: &JsonValue) -> HashMap<String, Option<String>> {
+    let docs = response["docs"].as_array().map(Vec::as_slice).unwrap_or_default();
+    docs.iter()
+        .filter(|doc| doc["found"].as_bool() == Some(true))
+        .filter_map(|doc| {
+            let hash = doc["_source"][ontology_engine::CONTENT_HASH_PROPERTY].as_str().map(str::to_string);
+            Some((doc["_id"].as_str()?.to_string(), hash))
+        })
+        .collect()
+}
+
+/// Objects of `batch` rejected in a `_bulk` response; items are reported in request order
+fn bulk_failures(response: &JsonValue, batch: &[IndexedObject]) -> Vec<BulkItemFailure> {
+    if response["errors"].as_bool() != Some(true) {
+        return Vec::new();
+    }
+    let items = response["items"].as_array().map(Vec::as_slice).unwrap_or_default();
+    items
+        .iter()
+        .zip(batch)
+        .filter_map(|(item, object)| {
+            let error = item["index"].get("error")?;
+            let reason = match (error["type"].as_str(), error["reason"].as_str()) {
+                (Some(kind), Some(reason)) => format!("{}: {}", kind, reason),
+                _ => error.to_string(),
+            };
+            Some(BulkItemFailure {
+                object_type: object.object_type.clone(),
+                object_id: object.object_id.clone(),
+                reason,
+            })
+        })
+        .collect()
+}
+
+/// Escape the metacharacters of an Elasticsearch `wildcard` pattern
+fn escape_wildcard(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, '\\' | '*' | '?') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// How long Elasticsearch keeps a scan's point-in-time open between pages
+const ES_SCAN_KEEP_ALIVE: &str = "5m";
+
+/// Resume point of an Elasticsearch scan, serialized as the page cursor
+#[derive(serde::Serialize, serde::Deserialize)]
+struct EsScanCursor {
+    pit: String,
+    search_after: JsonValue,
+}
+
+/// DQL for a `max_hops` traversal along one link type from the node matched by `root`
+/// (a DQL root function such as `uid(0x1)` or `eq(xid, "a")`)
+pub fn dgraph_traversal_query(root: &str, link_type_id: &str, max_hops: usize) -> String {
+    let predicate = link_type_id.replace('-', "_").replace('.', "_");
+    let mut query_parts = vec![format!("node(func: {}) {{", root)];
+    
+    // Each hop nests inside the previous one
+    for hop in 0..max_hops {
+        let indent = "  ".repeat(hop + 1);
+        query_parts.push(format!("{}~{} {{", indent, predicate));
+        query_parts.push(format!("{}  uid", indent));
+        query_parts.push(format!("{}  xid", indent));
+    }
+    
+    // Close all brackets
+    for hop in (0..max_hops).rev() {
+        let indent = "  ".repeat(hop + 1);
+        query_parts.push(format!("{}}}", indent));
+    }
+    query_parts.push("}".to_string());
+    
+    format!("{{\n{}\n}}", query_parts.join("\n"))
+}
+
+/// Quoted DQL string literal, backslashes escaped before quotes
+fn dql_string(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// A property name as a predicate or facet name in a DQL query; anything but letters,
+/// digits and underscores could change the query
+fn dql_name(property: &str) -> Result<&str, StoreError> {
+    if !property.is_empty() && property.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        Ok(property)
+    } else {
+        Err(StoreError::Query(format!("Invalid property name '{}'", property)))
+    }
+}
+
+/// DQL reading the metadata node of the link `link_id`: its type and the edge's endpoints,
+/// by node and object ID
+fn link_metadata_query(link_id: &str) -> String {
+    format!(r#"
+            {{
+                links(func: eq(link_id, {})) {{
+                    uid
+                    link_type_id
+                    link_source {{ uid xid }}
+                    link_target {{ uid xid }}
+                }}
+            }}
+        "#, dql_string(link_id))
+}
+
+/// Typed N-Quads setting the scalar properties of the node `uid`
+fn node_property_nquads(uid: &str, properties: &PropertyMap) -> String {
+    let mut nquads = Vec::new();
+    for (key, value) in properties.iter() {
+        let literal = match value {
+            ontology_engine::PropertyValue::String(s) => format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\"")),
+            ontology_engine::PropertyValue::Integer(i) => format!("\"{}\"^^<xs:int>", i),
+            ontology_engine::PropertyValue::Double(d) => format!("\"{}\"^^<xs:float>", d),
+            ontology_engine::PropertyValue::Boolean(b) => format!("\"{}\"^^<xs:boolean>", b),
+            _ => continue,
+        };
+        let predicate = key.replace('-', "_").replace('.', "_");
+        nquads.push(format!("<{}> <{}> {} .", uid, predicate, literal));
+    }
+    nquads.join("\n")
+}
+
+/// DQL aggregating over the nodes reached forwards from `start_uid` within `max_hops` along
+/// each of `link_type_ids`, excluding the start node and those failing `node_filter` (a DQL
+/// filter expression). A `var` block collects the nodes hop by hop; the `objects` block counts
+/// the nodes aggregated (those with the property, except for counts) and `aggregate` holds
+/// the aggregated `value`.
+pub fn dgraph_aggregation_query(
+    start_uid: &str,
+    link_type_ids: &[String],
+    max_hops: usize,
+    operation: &Aggregation,
+    node_filter: Option<&str>,
+) -> Result<String, StoreError> {
+    let (function, property) = match operation {
+        Aggregation::Count => ("count", None),
+        Aggregation::Sum(prop) => ("sum", Some(prop)),
+        Aggregation::Avg(prop) => ("avg", Some(prop)),
+        Aggregation::Min(prop) => ("min", Some(prop)),
+        Aggregation::Max(prop) => ("max", Some(prop)),
+        other => {
+            return Err(StoreError::Query(format!(
+                "Aggregation {:?} not supported in graph traversal. Use columnar store instead.",
+                other
+            )));
+        }
+    };
+    
+    let mut query_parts = Vec::new();
+    let mut reached = Vec::new();
+    if max_hops > 0 {
+        query_parts.push(format!("var(func: uid({})) {{", start_uid));
+    }
+    for (link, link_type_id) in link_type_ids.iter().enumerate().filter(|_| max_hops > 0) {
+        let predicate = link_type_id.replace('-', "_").replace('.', "_");
+        
+        // Each hop nests inside the previous one; the innermost needs no body
+        for hop in 1..=max_hops {
+            let indent = "  ".repeat(hop);
+            let var = format!("hop{}_{}", link, hop);
+            let body = if hop < max_hops { " {" } else { "" };
+            query_parts.push(format!("{}{} as {}{}", indent, var, predicate, body));
+            reached.push(var);
+        }
+        for hop in (1..max_hops).rev() {
+            query_parts.push(format!("{}}}", "  ".repeat(hop)));
+        }
+    }
+    if max_hops > 0 {
+        query_parts.push("}".to_string());
+    }
+    
+    let mut filter = format!("NOT uid({})", start_uid);
+    if let Some(node_filter) = node_filter {
+        filter.push_str(&format!(" AND ({})", node_filter));
+    }
+    if let Some(property) = property {
+        filter.push_str(&format!(" AND has({})", property.replace('-', "_").replace('.', "_")));
+    }
+    let reached = if reached.is_empty() { start_uid.to_string() } else { reached.join(", ") };
+    match property {
+        Some(property) => {
+            let predicate = property.replace('-', "_").replace('.', "_");
+            query_parts.push(format!("targets as var(func: uid({})) @filter({}) {{", reached, filter));
+            query_parts.push(format!("  values as {}", predicate));
+            query_parts.push("}".to_string());
+            query_parts.push("objects(func: uid(targets)) {".to_string());
+            query_parts.push("  count(uid)".to_string());
+            query_parts.push("}".to_string());
+            query_parts.push("aggregate() {".to_string());
+            query_parts.push(format!("  value: {}(val(values))", function));
+            query_parts.push("}".to_string());
+        }
+        None => {
+            query_parts.push(format!("objects(func: uid({})) @filter({}) {{", reached, filter));
+            query_parts.push("  count(uid)".to_string());
+            query_parts.push("}".to_string());
+        }
+    }
+    
+    Ok(format!("{{\n{}\n}}", query_parts.join("\n")))
+}
+
+/// The result of a `dgraph_aggregation_query`. Averages are doubles; sums, minimums and
+/// maximums keep the property's type. When no traversed node has the property the result is
+/// `StoreError::NoData`, so an empty traversal is not mistaken for a zero sum.
+fn dgraph_aggregation_result(
+    json: &serde_json::Value,
+    operation: &Aggregation,
+) -> Result<TraversalAggregationResult, StoreError> {
+    let count = json
+        .get("objects")
+        .and_then(|o| o.as_array())
+        .and_then(|o| o.first())
+        .and_then(|o| o.get("count"))
+        .and_then(|c| c.as_u64())
+        .unwrap_or(0) as usize;
+    
+    let property = match operation {
+        Aggregation::Count => {
+            return Ok(TraversalAggregationResult {
+                value: ontology_engine::PropertyValue::Integer(count as i64),
+                count,
+            });
+        }
+        Aggregation::Sum(prop) | Aggregation::Avg(prop) | Aggregation::Min(prop) | Aggregation::Max(prop) => prop,
+        other => {
+            return Err(StoreError::Query(format!(
+                "Aggregation {:?} not supported in graph traversal",
+                other
+            )));
+        }
+    };
+    
+    let value = json
+        .get("aggregate")
+        .and_then(|a| a.as_array())
+        .and_then(|a| a.iter().find_map(|entry| entry.get("value")))
+        .filter(|_| count > 0)
+        .ok_or_else(|| StoreError::NoData(format!("No traversed object has property '{}'", property)))?;
+    
+    let value = match (operation, value) {
+        (Aggregation::Avg(_), value) => value.as_f64().map(ontology_engine::PropertyValue::Double),
+        (_, serde_json::Value::Number(n)) => match n.as_i64() {
+            Some(i) => Some(ontology_engine::PropertyValue::Integer(i)),
+            None => n.as_f64().map(ontology_engine::PropertyValue::Double),
+        },
+        (_, serde_json::Value::String(s)) => Some(ontology_engine::PropertyValue::String(s.clone())),
+        _ => None,
+    }
+    .ok_or_else(|| StoreError::ReadError(format!("Unexpected aggregate of '{}': {}", property, value)))?;
+    
+    Ok(TraversalAggregationResult { value, count })
+}
+
+/// Elasticsearch `aggs` body for an analytics query. Distinct counts use the HyperLogLog++
+/// `cardinality` aggregation and percentiles the TDigest-based `percentiles` aggregation, so
+/// both are approximate; counts come from the hit total.
+pub fn elasticsearch_aggregations(aggregations: &[Aggregation]) -> Result<JsonValue, StoreError> {
+    let mut aggs = serde_json::Map::new();
+    for aggregation in aggregations {
+        let (name, body) = match aggregation {
+            Aggregation::Count => continue,
+            Aggregation::Sum(prop) => (format!("sum_{}", prop), json!({ "sum": { "field": prop } })),
+            Aggregation::Avg(prop) => (format!("avg_{}", prop), json!({ "avg": { "field": prop } })),
+            Aggregation::Min(prop) => (format!("min_{}", prop), json!({ "min": { "field": prop } })),
+            Aggregation::Max(prop) => (format!("max_{}", prop), json!({ "max": { "field": prop } })),
+            Aggregation::StdDev(prop) => (format!("stddev_{}", prop), json!({ "extended_stats": { "field": prop } })),
+            Aggregation::Variance(prop) => (format!("variance_{}", prop), json!({ "extended_stats": { "field": prop } })),
+            Aggregation::Median(prop) => (format!("median_{}", prop), percentiles_aggregation(prop, 0.5)),
+            Aggregation::Percentile(prop, pct) => {
+                (format!("p{}_{}", (*pct * 100.0) as u8, prop), percentiles_aggregation(prop, *pct))
+            }
+            Aggregation::DistinctCount(prop) => (
+                format!("distinct_count_{}", prop),
+                json!({ "cardinality": { "field": prop, "precision_threshold": ES_CARDINALITY_PRECISION } }),
+            ),
+            Aggregation::TopN(prop, _) | Aggregation::BottomN(prop, _) => {
+                return Err(StoreError::Query(format!(
+                    "TopN/BottomN aggregation for property '{}' is not supported by Elasticsearch",
+                    prop
+                )));
+            }
+        };
+        aggs.insert(name, body);
+    }
+    Ok(JsonValue::Object(aggs))
+}
+
+/// Counts below this threshold are close to exact; memory use grows with it (max 40000)
+const ES_CARDINALITY_PRECISION: u32 = 3000;
+
+/// TDigest compression for percentile aggregations (Elasticsearch's default)
+const ES_TDIGEST_COMPRESSION: u32 = 100;
+
+fn percentiles_aggregation(prop: &str, pct: f64) -> JsonValue {
+    json!({
+        "percentiles": {
+            "field": prop,
+            "percents": [pct * 100.0],
+            "tdigest": { "compression": ES_TDIGEST_COMPRESSION },
+        }
+    })
 }
 
 /// Link direction for graph traversal
@@ -183,6 +874,20 @@ pub enum LinkDirection {
 /// Search query structure
 #[derive(Debug, Clone)]
 pub struct SearchQuery {
+    pub filters: Vec<Filter>,
+    /// Sort keys in priority order, each breaking ties in the ones before it
+    pub sort: Vec<SortOption>,
+    pub limit: Option<usize>,
+    pub offset: Option<usize>,
+    /// Sort values of the last object of the previous page, one per sort key; only objects
+    /// sorting after it are returned. Unlike `offset`, pages stay put when objects are
+    /// written between requests. A null value stands for a missing property.
+    pub search_after: Option<Vec<JsonValue>>,
+}
+
+/// Filters, sort and page for `GraphStore::get_links`; filters and sort apply to link properties
+#[derive(Debug, Clone, Default)]
+pub struct LinkQuery {
     pub filters: Vec<Filter>,
     pub sort: Option<SortOption>,
     pub limit: Option<usize>,
@@ -195,8 +900,10 @@ pub struct Filter {
     pub property: String,
     pub operator: FilterOperator,
     pub value: ontology_engine::PropertyValue,
-    /// Optional distance parameter for WithinDistance operator (in meters)
+    /// Distance for the WithinDistance operator, in meters on WGS84
     pub distance: Option<f64>,
+    /// Whether `Contains`, `StartsWith` and `EndsWith` ignore case
+    pub case_insensitive: bool,
 }
 
 /// Filter operators
@@ -220,11 +927,94 @@ pub enum FilterOperator {
     WithinDistance,      // Check if geometry is within distance (requires distance parameter)
 }
 
+impl FilterOperator {
+    /// Whether the operator compares GeoJSON geometries
+    pub fn is_spatial(&self) -> bool {
+        matches!(
+            self,
+            FilterOperator::ContainsGeometry
+                | FilterOperator::Intersects
+                | FilterOperator::Within
+                | FilterOperator::WithinDistance
+        )
+    }
+
+    /// How a stored geometry must relate to the filter geometry, for the spatial operators
+    /// other than `WithinDistance`
+    pub fn spatial_relation(&self) -> Option<ontology_engine::geo::SpatialRelation> {
+        use ontology_engine::geo::SpatialRelation;
+        match self {
+            FilterOperator::ContainsGeometry => Some(SpatialRelation::Contains),
+            FilterOperator::Intersects => Some(SpatialRelation::Intersects),
+            FilterOperator::Within => Some(SpatialRelation::Within),
+            _ => None,
+        }
+    }
+}
+
+impl Filter {
+    /// The GeoJSON geometry a spatial filter compares against
+    pub fn geometry(&self) -> Result<JsonValue, StoreError> {
+        let geometry = match &self.value {
+            ontology_engine::PropertyValue::GeoJSON(gj) | ontology_engine::PropertyValue::String(gj) => {
+                serde_json::from_str(gj).ok()
+            }
+            value @ (ontology_engine::PropertyValue::Map(_) | ontology_engine::PropertyValue::Object(_)) => {
+                serde_json::to_value(value).ok()
+            }
+            _ => None,
+        };
+        geometry.ok_or_else(|| {
+            StoreError::Validation(format!("Spatial filter on '{}' needs a GeoJSON geometry", self.property))
+        })
+    }
+
+    /// The `(longitude, latitude)` and meters of a `WithinDistance` filter. Distances are
+    /// measured from a point, so any other geometry is rejected.
+    pub fn distance_origin(&self) -> Result<((f64, f64), f64), StoreError> {
+        let point = ontology_engine::geo::point(&self.geometry()?).map_err(|e| {
+            StoreError::Validation(format!("withinDistance on '{}' needs a Point geometry: {}", self.property, e))
+        })?;
+        match self.distance {
+            Some(meters) if meters.is_finite() && meters > 0.0 => Ok((point, meters)),
+            _ => Err(StoreError::Validation(format!(
+                "withinDistance on '{}' needs a positive distance in meters",
+                self.property
+            ))),
+        }
+    }
+
+    /// Check a spatial filter can be evaluated; other filters always can
+    pub fn check_spatial(&self) -> Result<(), StoreError> {
+        match self.operator {
+            FilterOperator::WithinDistance => self.distance_origin().map(|_| ()),
+            operator if operator.is_spatial() => self.geometry().map(|_| ()),
+            _ => Ok(()),
+        }
+    }
+}
+
 /// Sort option
 #[derive(Debug, Clone)]
 pub struct SortOption {
     pub property: String,
     pub ascending: bool,
+    /// The property is a string the search index maps as text with a `.keyword` subfield,
+    /// which is what Elasticsearch sorts on
+    pub keyword_subfield: bool,
+}
+
+impl SortOption {
+    /// Sort on `property` of `object_type`
+    pub fn for_property(object_type: &ontology_engine::ObjectType, property: String, ascending: bool) -> Self {
+        use ontology_engine::{IndexingHint, PropertyType};
+        
+        // Mirrors `object_type_mapping`: only full-indexed strings keep dynamic text + keyword
+        let keyword_subfield = object_type.get_property(&property).is_some_and(|p| {
+            p.property_type == PropertyType::String && p.indexing_hint() == IndexingHint::Full
+        });
+        Self { property, ascending, keyword_subfield }
+    }
 }
 
 /// Refresh status for data freshness tracking
@@ -248,6 +1038,9 @@ pub struct IndexedObject {
     pub refresh_frequency: Option<String>, // "daily", "hourly", "real-time"
     pub next_refresh: Option<chrono::DateTime<chrono::Utc>>,
     pub refresh_status: RefreshStatus,
+    
+    /// Revision of the stored object, incremented on every write (0 = not stored yet)
+    pub revision: u64,
 }
 
 impl IndexedObject {
@@ -261,6 +1054,7 @@ impl IndexedObject {
             refresh_frequency: None,
             next_refresh: None,
             refresh_status: RefreshStatus::UpToDate,
+            revision: 0,
         }
     }
     
@@ -296,12 +1090,96 @@ pub struct GraphLink {
     pub created_at: chrono::DateTime<chrono::Utc>,
 }
 
+/// A link to create, for `GraphStore::create_links_batch`
+#[derive(Debug, Clone)]
+pub struct NewLink {
+    pub link_type_id: String,
+    pub source_id: String,
+    pub target_id: String,
+    pub properties: PropertyMap,
+}
+
+/// A page of `SearchStore::scan_objects`
+#[derive(Debug, Clone, Default)]
+pub struct ObjectScanPage {
+    pub objects: Vec<IndexedObject>,
+    /// Cursor for the next page; `None` once the scan is complete
+    pub next: Option<String>,
+}
+
+/// A page of `GraphStore::scan_links`
+#[derive(Debug, Clone, Default)]
+pub struct LinkScanPage {
+    pub links: Vec<GraphLink>,
+    /// Cursor for the next page; `None` once the scan is complete
+    pub next: Option<String>,
+}
+
+/// Distribution of a numeric link property, from `GraphStore::link_property_stats`
+#[derive(Debug, Clone, PartialEq)]
+pub struct LinkPropertyStats {
+    /// Links with a value for the property
+    pub count: u64,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    pub mean: Option<f64>,
+    /// Fixed-width buckets spanning `min..=max`; empty when `count` is 0
+    pub histogram: Vec<HistogramBucket>,
+}
+
+/// A histogram bucket holding values in `lower..upper` (the last bucket includes `upper`)
+#[derive(Debug, Clone, PartialEq)]
+pub struct HistogramBucket {
+    pub lower: f64,
+    pub upper: f64,
+    pub count: u64,
+}
+
+impl LinkPropertyStats {
+    /// Statistics over `values` with a histogram of `buckets` buckets. When every value is
+    /// the same the histogram is a single bucket.
+    pub fn from_values(values: &[f64], buckets: usize) -> Result<Self, StoreError> {
+        if buckets == 0 {
+            return Err(StoreError::Query("A histogram needs at least one bucket".to_string()));
+        }
+        if values.is_empty() {
+            return Ok(Self { count: 0, min: None, max: None, mean: None, histogram: Vec::new() });
+        }
+        let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+        let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        let mean = values.iter().sum::<f64>() / values.len() as f64;
+        
+        let buckets = if min == max { 1 } else { buckets };
+        let width = (max - min) / buckets as f64;
+        let mut counts = vec![0u64; buckets];
+        for value in values {
+            let bucket = if width == 0.0 { 0 } else { ((value - min) / width) as usize };
+            counts[bucket.min(buckets - 1)] += 1;
+        }
+        let histogram = counts
+            .into_iter()
+            .enumerate()
+            .map(|(i, count)| HistogramBucket {
+                lower: min + width * i as f64,
+                upper: if i + 1 == buckets { max } else { min + width * (i + 1) as f64 },
+                count,
+            })
+            .collect();
+        
+        Ok(Self { count: values.len() as u64, min: Some(min), max: Some(max), mean: Some(mean), histogram })
+    }
+}
+
 /// Analytics query
 #[derive(Debug, Clone)]
 pub struct AnalyticsQuery {
     pub aggregations: Vec<Aggregation>,
     pub filters: Vec<Filter>,
     pub group_by: Vec<String>,
+    /// Aggregate a uniform sample instead of every row; `None` means exact
+    pub sampling: Option<SamplingOptions>,
+    /// Named partitions aggregated along with the object type's main data
+    pub partitions: Vec<String>,
 }
 
 /// Aggregation operations
@@ -321,11 +1199,28 @@ pub enum Aggregation {
     BottomN(String, usize),
 }
 
+impl Aggregation {
+    /// The property aggregated; `None` for `Count`
+    pub fn property(&self) -> Option<&str> {
+        match self {
+            Aggregation::Count => None,
+            Aggregation::Sum(p) | Aggregation::Avg(p) | Aggregation::Min(p) | Aggregation::Max(p)
+            | Aggregation::Median(p) | Aggregation::StdDev(p) | Aggregation::Variance(p)
+            | Aggregation::Percentile(p, _) | Aggregation::DistinctCount(p)
+            | Aggregation::TopN(p, _) | Aggregation::BottomN(p, _) => Some(p.as_str()),
+        }
+    }
+}
+
 /// Analytics query result
 #[derive(Debug, Clone)]
 pub struct AnalyticsResult {
     pub rows: Vec<HashMap<String, ontology_engine::PropertyValue>>,
     pub total: usize,
+    /// Fraction of rows aggregated when the result was computed from a sample
+    pub sample_fraction: Option<f64>,
+    /// 95% bounds per aggregate column, parallel to `rows`; empty for exact results
+    pub error_bounds: Vec<HashMap<String, ErrorBound>>,
 }
 
 /// Traversal aggregation configuration
@@ -393,6 +1288,15 @@ impl StoreBackend {
         }
     }
     
+    /// Backend over the in-memory stores, for local development and tests
+    pub fn in_memory() -> Self {
+        Self::new(
+            Box::new(crate::in_memory::InMemorySearchStore::new()),
+            Box::new(crate::in_memory::InMemoryGraphStore::new()),
+            Box::new(crate::in_memory::InMemoryColumnarStore::new()),
+        )
+    }
+    
     pub fn search_store(&self) -> &dyn SearchStore {
         self.search.as_ref()
     }
@@ -430,20 +1334,82 @@ pub enum StoreError {
     #[error("Write error: {0}")]
     WriteError(String),
     
+    /// A write the store refused because the data breaks the ontology's rules, as opposed
+    /// to one the backend failed to carry out
+    #[error("Validation error: {0}")]
+    Validation(String),
+    
     #[error("Read error: {0}")]
     ReadError(String),
     
     #[error("Unknown error: {0}")]
     Unknown(String),
+    
+    #[error("Revision conflict on {}:{}: expected revision {}, found {}",
+        .0.object_type, .0.object_id, .0.expected_revision, .0.current_revision)]
+    Conflict(Box<RevisionConflict>),
+    
+    /// Objects a bulk write rejected; the rest of the batch was written
+    #[error("Bulk indexing failed for {}",
+        .0.iter().map(|f| format!("{}:{} ({})", f.object_type, f.object_id, f.reason)).collect::<Vec<_>>().join(", "))]
+    BulkIndex(Vec<BulkItemFailure>),
+    
+    /// An aggregation found nothing to aggregate, as opposed to aggregating to zero
+    #[error("No data: {0}")]
+    NoData(String),
 }
 
-// Elasticsearch store implementation
-pub struct ElasticsearchStore {
-    client: Elasticsearch,
-    /// Index prefix allows you to namespace apps (e.g., "dev_user", "prod_user")
-    index_prefix: String,
+/// An object rejected by a bulk write
+#[derive(Debug, Clone, PartialEq)]
+pub struct BulkItemFailure {
+    pub object_type: String,
+    pub object_id: String,
+    pub reason: String,
+}
+
+/// A write rejected because the object changed since the caller read it
+#[derive(Debug, Clone)]
+pub struct RevisionConflict {
+    pub object_type: String,
+    pub object_id: String,
+    pub expected_revision: u64,
+    /// Revision currently stored (0 if the object does not exist)
+    pub current_revision: u64,
+    /// The stored object, so the caller can rebase its change onto it
+    pub current: Option<IndexedObject>,
+}
+
+impl StoreError {
+    pub fn conflict(
+        object_type: &str,
+        object_id: &str,
+        expected_revision: u64,
+        current: Option<IndexedObject>,
+    ) -> Self {
+        StoreError::Conflict(Box::new(RevisionConflict {
+            object_type: object_type.to_string(),
+            object_id: object_id.to_string(),
+            expected_revision,
+            current_revision: current.as_ref().map_or(0, |o| o.revision),
+            current,
+        }))
+    }
+}
+
+/// Objects sent per `_bulk` request by `ElasticsearchStore::bulk_index`
+pub const DEFAULT_BULK_BATCH_SIZE: usize = 500;
+
+// Elasticsearch store implementation
+pub struct ElasticsearchStore {
+    client: Elasticsearch,
+    /// Index prefix allows you to namespace apps (e.g., "dev_user", "prod_user")
+    index_prefix: String,
     /// Base URL for direct HTTP operations (for alias/reindex APIs)
     base_url: String,
+    /// Objects per `_bulk` request
+    bulk_batch_size: usize,
+    /// Object types whose alias is known to point at a versioned index
+    aliased_types: std::sync::Mutex<std::collections::HashSet<String>>,
 }
 
 impl ElasticsearchStore {
@@ -465,8 +1431,61 @@ impl ElasticsearchStore {
             client,
             index_prefix: "ontology".to_string(),
             base_url: endpoint,
+            bulk_batch_size: DEFAULT_BULK_BATCH_SIZE,
+            aliased_types: std::sync::Mutex::new(std::collections::HashSet::new()),
         })
     }
+    
+    /// Send `bulk_index` writes in `_bulk` requests of at most `size` objects
+    pub fn with_bulk_batch_size(mut self, size: usize) -> Self {
+        self.bulk_batch_size = size.max(1);
+        self
+    }
+    
+    /// Namespace indices and aliases as `<prefix>_<object type>` (default `ontology`)
+    pub fn with_index_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.index_prefix = prefix.into();
+        self
+    }
+
+    /// Fetch an object with the `_seq_no`/`_primary_term` needed for a conditional write
+    async fn get_object_with_seq_no(
+        &self,
+        object_type: &str,
+        object_id: &str,
+    ) -> Result<(Option<IndexedObject>, i64, i64), StoreError> {
+        let index_name = self.index_name(object_type);
+        
+        let response = self.client
+            .get(GetParts::IndexId(&index_name, object_id))
+            .send()
+            .await
+            .map_err(|e| StoreError::ReadError(format!("Elasticsearch get failed: {}", e)))?;
+        
+        let status_code = response.status_code();
+        if !status_code.is_success() {
+            if status_code == 404 {
+                return Ok((None, 0, 0));
+            }
+            let error_body = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+            return Err(StoreError::ReadError(format!(
+                "Elasticsearch returned error {}: {}",
+                status_code.as_u16(),
+                error_body
+            )));
+        }
+        
+        // Parse response
+        let response_body: serde_json::Value = response
+            .json()
+            .await
+            .map_err(|e| StoreError::ReadError(format!("Failed to parse response: {}", e)))?;
+        
+        let object = document_object(object_type, object_id, &response_body)?;
+        let seq_no = response_body["_seq_no"].as_i64().unwrap_or(0);
+        let primary_term = response_body["_primary_term"].as_i64().unwrap_or(0);
+        Ok((Some(object), seq_no, primary_term))
+    }
 
     /// Generate index name from object type (e.g., "ontology_user" or "ontology_document")
     fn index_name(&self, object_type: &str) -> String {
@@ -483,7 +1502,61 @@ impl ElasticsearchStore {
         format!("{}_{}", self.index_prefix, object_type)
     }
     
-    /// Create an index alias pointing to a versioned index
+    /// Version a versioned index name (e.g. "ontology_user_v2" -> 2) belongs to, if it is one
+    /// of this object type's
+    fn index_version(&self, object_type: &str, index_name: &str) -> Option<u64> {
+        index_name
+            .strip_prefix(&format!("{}_{}_v", self.index_prefix, object_type))
+            .and_then(|version| version.parse().ok())
+    }
+    
+    /// Make sure writes to an object type land in a versioned index behind its alias,
+    /// creating version 1 and the alias the first time the type is written
+    async fn ensure_alias(&self, object_type: &str) -> Result<(), StoreError> {
+        if self.aliased_types.lock().unwrap().contains(object_type) {
+            return Ok(());
+        }
+        if self.get_alias_version(object_type).await?.is_none() {
+            self.create_alias(object_type, 1).await?;
+        }
+        self.aliased_types.lock().unwrap().insert(object_type.to_string());
+        Ok(())
+    }
+    
+    /// Create the versioned index for an object type (e.g. "ontology_user_v2"); an index that
+    /// already exists is left as it is
+    pub async fn create_versioned_index(
+        &self,
+        object_type: &str,
+        version: u64,
+    ) -> Result<(), StoreError> {
+        let index = self.versioned_index_name(object_type, version);
+        let url = format!("{}/{}", self.base_url, index);
+        let client = reqwest::Client::new();
+        let response = client
+            .put(&url)
+            .send()
+            .await
+            .map_err(|e| StoreError::WriteError(format!("Failed to create index: {}", e)))?;
+        
+        let status = response.status();
+        if !status.is_success() {
+            let error_body = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+            if error_body.contains("resource_already_exists_exception") {
+                return Ok(());
+            }
+            return Err(StoreError::WriteError(format!(
+                "Failed to create index {}: {} - {}",
+                index,
+                status.as_u16(),
+                error_body
+            )));
+        }
+        
+        Ok(())
+    }
+    
+    /// Point the object type's alias at a versioned index, creating the index if needed
     pub async fn create_alias(
         &self,
         object_type: &str,
@@ -491,6 +1564,7 @@ impl ElasticsearchStore {
     ) -> Result<(), StoreError> {
         let alias = self.alias_name(object_type);
         let index = self.versioned_index_name(object_type, version);
+        self.create_versioned_index(object_type, version).await?;
         
         // Create alias pointing to the versioned index
         let alias_body = json!({
@@ -599,8 +1673,9 @@ impl ElasticsearchStore {
             }
         });
         
-        // Use HTTP client directly for reindex operation
-        let url = format!("{}/_reindex", self.base_url);
+        // Block until every document is copied, and refresh so they are searchable once the
+        // alias is swapped
+        let url = format!("{}/_reindex?wait_for_completion=true&refresh=true", self.base_url);
         let client = reqwest::Client::new();
         let response = client
             .post(&url)
@@ -671,24 +1746,14 @@ impl ElasticsearchStore {
             .await
             .map_err(|e| StoreError::ReadError(format!("Failed to parse alias response: {}", e)))?;
         
-        // Extract version from index name (e.g., "ontology_user_v2" -> 2)
-        for (index_name, _) in response_body.as_object().unwrap_or(&serde_json::Map::new()) {
-            if let Some(version_str) = index_name.strip_suffix(&format!("_{}", object_type)) {
-                if let Some(version) = version_str.strip_prefix(&format!("{}_v", self.index_prefix)) {
-                    if let Ok(version_num) = version.parse::<u64>() {
-                        return Ok(Some(version_num));
-                    }
-                }
-            }
-            // Alternative pattern: check if index name ends with _v{number}
-            if let Some(v_part) = index_name.strip_prefix(&format!("{}_{}_v", self.index_prefix, object_type)) {
-                if let Ok(version_num) = v_part.parse::<u64>() {
-                    return Ok(Some(version_num));
-                }
-            }
-        }
-        
-        Ok(None)
+        // Keys are the indexes the alias points to (e.g., "ontology_user_v2" -> 2); if there
+        // are several, the newest version wins
+        Ok(response_body
+            .as_object()
+            .into_iter()
+            .flat_map(|indexes| indexes.keys())
+            .filter_map(|index_name| self.index_version(object_type, index_name))
+            .max())
     }
     
     /// Delete an old versioned index (after migration is complete)
@@ -698,6 +1763,8 @@ impl ElasticsearchStore {
         version: u64,
     ) -> Result<(), StoreError> {
         let index = self.versioned_index_name(object_type, version);
+        // Deleting the index the alias points at removes the alias too
+        self.aliased_types.lock().unwrap().remove(object_type);
         
         let response = self.client
             .indices()
@@ -722,6 +1789,78 @@ impl ElasticsearchStore {
         Ok(())
     }
     
+    /// Apply explicit field mappings for an object type's properties.
+    /// Adding fields to an existing mapping is allowed; changing a mapped field's type is not.
+    pub async fn put_object_type_mapping(
+        &self,
+        object_type: &ontology_engine::ObjectType,
+    ) -> Result<(), StoreError> {
+        self.ensure_alias(&object_type.id).await?;
+        let url = format!("{}/{}/_mapping", self.base_url, self.alias_name(&object_type.id));
+        let client = reqwest::Client::new();
+        let response = client
+            .put(&url)
+            .json(&object_type_mapping(object_type))
+            .send()
+            .await
+            .map_err(|e| StoreError::WriteError(format!("Failed to update mapping: {}", e)))?;
+        
+        let status = response.status();
+        if !status.is_success() {
+            let error_body = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+            return Err(StoreError::WriteError(format!(
+                "Failed to update mapping: {} - {}",
+                status.as_u16(),
+                error_body
+            )));
+        }
+        
+        Ok(())
+    }
+    
+    /// Request body for a search: filters, sort, pagination and the `_version` of each hit
+    fn search_body(&self, query: &SearchQuery) -> Result<JsonValue, StoreError> {
+        let query_body = self.build_query_body(Some(&query.filters))?;
+        
+        // Extract the query body map for adding sort/pagination
+        let mut query_body_map = if let JsonValue::Object(map) = query_body {
+            map
+        } else {
+            return Err(StoreError::Query("Invalid query body structure".to_string()));
+        };
+        
+        // Add sorting; documents missing a sort property come last in either direction
+        if !query.sort.is_empty() {
+            let sort_keys = query.sort.iter().map(|sort| {
+                let field = if sort.keyword_subfield {
+                    format!("{}.keyword", sort.property)
+                } else {
+                    sort.property.clone()
+                };
+                let mut sort_obj = serde_json::Map::new();
+                sort_obj.insert(field, json!({
+                    "order": if sort.ascending { "asc" } else { "desc" },
+                    "missing": "_last",
+                }));
+                JsonValue::Object(sort_obj)
+            }).collect();
+            query_body_map.insert("sort".to_string(), JsonValue::Array(sort_keys));
+        }
+        
+        // Add pagination
+        if let Some(size) = query.limit {
+            query_body_map.insert("size".to_string(), JsonValue::Number(size.into()));
+        }
+        if let Some(from) = query.offset {
+            query_body_map.insert("from".to_string(), JsonValue::Number(from.into()));
+        }
+        if let Some(after) = &query.search_after {
+            query_body_map.insert("search_after".to_string(), JsonValue::Array(after.clone()));
+        }
+        query_body_map.insert("version".to_string(), JsonValue::Bool(true));
+        Ok(JsonValue::Object(query_body_map))
+    }
+    
     /// Build Elasticsearch query body from filters (reusable for search and count)
     fn build_query_body(&self, filters: Option<&[Filter]>) -> Result<JsonValue, StoreError> {
         let mut query_body = serde_json::Map::new();
@@ -755,13 +1894,11 @@ impl ElasticsearchStore {
                 query_body.insert("query".to_string(), JsonValue::Object(query_obj));
             } else {
                 // Match all if no filters
-                let mut match_all = serde_json::Map::new();
-                query_body.insert("query".to_string(), JsonValue::Object(match_all));
+                query_body.insert("query".to_string(), json!({ "match_all": {} }));
             }
         } else {
             // Match all if no filters provided
-            let mut match_all = serde_json::Map::new();
-            query_body.insert("query".to_string(), JsonValue::Object(match_all));
+            query_body.insert("query".to_string(), json!({ "match_all": {} }));
         }
         
         Ok(JsonValue::Object(query_body))
@@ -784,6 +1921,8 @@ impl ElasticsearchStore {
                     _ => return Err(StoreError::Query("Contains/StartsWith/EndsWith requires string value".to_string())),
                 };
                 
+                // `*` and `?` in the value are matched literally, not as wildcards
+                let query_string = escape_wildcard(&query_string);
                 let mut match_obj = serde_json::Map::new();
                 let pattern = match filter.operator {
                     FilterOperator::Contains => format!("*{}*", query_string),
@@ -791,7 +1930,12 @@ impl ElasticsearchStore {
                     FilterOperator::EndsWith => format!("*{}", query_string),
                     _ => unreachable!(),
                 };
-                match_obj.insert(filter.property.clone(), JsonValue::String(pattern));
+                let pattern = if filter.case_insensitive {
+                    json!({ "value": pattern, "case_insensitive": true })
+                } else {
+                    JsonValue::String(pattern)
+                };
+                match_obj.insert(filter.property.clone(), pattern);
                 clause.insert("wildcard".to_string(), JsonValue::Object(match_obj));
             }
             FilterOperator::GreaterThan => {
@@ -844,6 +1988,24 @@ impl ElasticsearchStore {
                 terms_obj.insert(filter.property.clone(), JsonValue::Array(values));
                 clause.insert("terms".to_string(), JsonValue::Object(terms_obj));
             }
+            FilterOperator::ContainsGeometry | FilterOperator::Intersects | FilterOperator::Within => {
+                let relation = match filter.operator {
+                    FilterOperator::ContainsGeometry => "contains",
+                    FilterOperator::Intersects => "intersects",
+                    _ => "within",
+                };
+                let mut shape_obj = serde_json::Map::new();
+                shape_obj.insert(filter.property.clone(), json!({ "shape": filter.geometry()?, "relation": relation }));
+                clause.insert("geo_shape".to_string(), JsonValue::Object(shape_obj));
+            }
+            FilterOperator::WithinDistance => {
+                // Matches shapes with any point within the distance, like the in-memory store
+                let ((lon, lat), meters) = filter.distance_origin()?;
+                let mut distance_obj = serde_json::Map::new();
+                distance_obj.insert("distance".to_string(), JsonValue::String(format!("{}m", meters)));
+                distance_obj.insert(filter.property.clone(), json!([lon, lat]));
+                clause.insert("geo_distance".to_string(), JsonValue::Object(distance_obj));
+            }
             _ => {
                 return Err(StoreError::Query(format!(
                     "Filter operator {:?} not yet implemented for Elasticsearch",
@@ -866,6 +2028,10 @@ impl ElasticsearchStore {
                     .ok_or_else(|| StoreError::Query("Invalid double value".to_string()))
             }
             ontology_engine::PropertyValue::Boolean(b) => Ok(JsonValue::Bool(*b)),
+            // Dates are mapped as `date` fields, which accept ISO 8601 strings in queries
+            ontology_engine::PropertyValue::Date(s)
+            | ontology_engine::PropertyValue::DateTime(s)
+            | ontology_engine::PropertyValue::ObjectReference(s) => Ok(JsonValue::String(s.clone())),
             _ => Err(StoreError::Query(format!("Unsupported PropertyValue type for Elasticsearch: {:?}", value))),
         }
     }
@@ -873,35 +2039,50 @@ impl ElasticsearchStore {
 
 #[async_trait]
 impl SearchStore for ElasticsearchStore {
+    #[tracing::instrument(level = "debug", name = "store.index_object", skip_all, fields(backend = "elasticsearch", object_type = %object_type, object_id = %object_id))]
     async fn index_object(
         &self,
         object_type: &str,
         object_id: &str,
         properties: &PropertyMap,
-    ) -> Result<(), StoreError> {
+        expected_revision: Option<u64>,
+    ) -> Result<u64, StoreError> {
+        self.ensure_alias(object_type).await?;
         let index_name = self.index_name(object_type);
 
-        // Serialize PropertyMap to JSON
-        // PropertyMap has a private "properties" field, so we need to build the JSON manually
-        // to get a flat structure (just the property key-value pairs)
-        let mut json_map = serde_json::Map::new();
-        for (key, value) in properties.iter() {
-            // PropertyValue implements Serialize, so we can convert it directly
-            let json_value = serde_json::to_value(value)
-                .map_err(|e| StoreError::Serialization(format!("Failed to serialize property '{}': {}", key, e)))?;
-            json_map.insert(key.clone(), json_value);
+        // The revision is the document `_version`. A conditional write checks it against a
+        // fresh read, then pins that read with if_seq_no/if_primary_term so a write landing
+        // in between is rejected by Elasticsearch instead of overwritten.
+        let mut seq_no_guard = None;
+        if let Some(expected) = expected_revision.filter(|r| *r > 0) {
+            let (current, seq_no, primary_term) = self.get_object_with_seq_no(object_type, object_id).await?;
+            match current {
+                Some(current) if current.revision == expected => seq_no_guard = Some((seq_no, primary_term)),
+                current => return Err(StoreError::conflict(object_type, object_id, expected, current)),
+            }
         }
-        let json_body = JsonValue::Object(json_map);
 
-        let response = self.client
+        let json_body = object_document(properties)?;
+
+        let mut request = self.client
             .index(IndexParts::IndexId(&index_name, object_id))
-            .body(json_body)
+            .body(json_body);
+        if let Some((seq_no, primary_term)) = seq_no_guard {
+            request = request.if_seq_no(seq_no).if_primary_term(primary_term);
+        } else if expected_revision == Some(0) {
+            request = request.op_type(elasticsearch::params::OpType::Create);
+        }
+        let response = request
             .send()
             .await
             .map_err(|e| StoreError::Connection(format!("Elasticsearch request failed: {}", e)))?;
 
         // Check if the response was successful
         let status_code = response.status_code();
+        if status_code.as_u16() == 409 {
+            let (current, _, _) = self.get_object_with_seq_no(object_type, object_id).await?;
+            return Err(StoreError::conflict(object_type, object_id, expected_revision.unwrap_or(0), current));
+        }
         if !status_code.is_success() {
             let error_body = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
             return Err(StoreError::Query(format!(
@@ -911,46 +2092,25 @@ impl SearchStore for ElasticsearchStore {
             )));
         }
 
-        Ok(())
+        let response_body: JsonValue = response
+            .json()
+            .await
+            .map_err(|e| StoreError::Query(format!("Failed to parse response: {}", e)))?;
+        Ok(response_body["_version"].as_u64().unwrap_or(0))
     }
     
+    #[tracing::instrument(level = "debug", name = "store.search", skip_all, fields(backend = "elasticsearch", object_type = %object_type))]
     async fn search(
         &self,
         object_type: &str,
         query: &SearchQuery,
     ) -> Result<Vec<IndexedObject>, StoreError> {
         let index_name = self.index_name(object_type);
-        
-        // Build query body using helper method
-        let query_body = self.build_query_body(Some(&query.filters))?;
-        
-        // Extract the query body map for adding sort/pagination
-        let mut query_body_map = if let JsonValue::Object(map) = query_body {
-            map
-        } else {
-            return Err(StoreError::Query("Invalid query body structure".to_string()));
-        };
-        
-        // Add sorting
-        if let Some(sort) = &query.sort {
-            let mut sort_obj = serde_json::Map::new();
-            sort_obj.insert(sort.property.clone(), JsonValue::String(
-                if sort.ascending { "asc" } else { "desc" }.to_string()
-            ));
-            query_body_map.insert("sort".to_string(), JsonValue::Array(vec![JsonValue::Object(sort_obj)]));
-        }
-        
-        // Add pagination
-        if let Some(size) = query.limit {
-            query_body_map.insert("size".to_string(), JsonValue::Number(size.into()));
-        }
-        if let Some(from) = query.offset {
-            query_body_map.insert("from".to_string(), JsonValue::Number(from.into()));
-        }
+        let query_body = self.search_body(query)?;
         
         let response = self.client
             .search(SearchParts::Index(&[&index_name]))
-            .body(JsonValue::Object(query_body_map))
+            .body(query_body)
             .send()
             .await
             .map_err(|e| StoreError::Query(format!("Elasticsearch search failed: {}", e)))?;
@@ -978,49 +2138,88 @@ impl SearchStore for ElasticsearchStore {
             .and_then(|h| h.as_array())
             .unwrap_or(&empty_vec);
         
-        let mut results = Vec::new();
-        for hit in hits {
-            let source = hit.get("_source")
-                .ok_or_else(|| StoreError::Query("Missing _source in hit".to_string()))?;
-            
-            let id = hit.get("_id")
-                .and_then(|v| v.as_str())
-                .unwrap_or("");
-            
-            // Convert JSON back to PropertyMap
-            let mut properties = PropertyMap::new();
-            if let Some(obj) = source.as_object() {
-                for (key, value) in obj {
-                    // Skip metadata fields
-                    if key == "object_id" || key == "object_type" || key == "indexed_at" {
-                        continue;
-                    }
-                    
-                    let prop_value: ontology_engine::PropertyValue = serde_json::from_value(value.clone())
-                        .map_err(|e| StoreError::Query(format!("Failed to deserialize property '{}': {}", key, e)))?;
-                    properties.insert(key.clone(), prop_value);
-                }
+        hits.iter().map(|hit| search_hit_to_object(object_type, hit)).collect()
+    }
+    
+    fn supports_aggregations(&self) -> bool {
+        true
+    }
+    
+    fn explain_search(&self, object_type: &str, query: &SearchQuery) -> Option<JsonValue> {
+        let body = self.search_body(query).ok()?;
+        Some(json!({ "index": self.index_name(object_type), "body": body }))
+    }
+    
+    /// Scans use a point-in-time with `search_after`, so they see a consistent snapshot and
+    /// are not limited by `index.max_result_window`
+    async fn scan_objects(
+        &self,
+        object_type: &str,
+        filters: &[Filter],
+        cursor: Option<&str>,
+        limit: usize,
+    ) -> Result<ObjectScanPage, StoreError> {
+        let client = reqwest::Client::new();
+        let cursor: Option<EsScanCursor> = cursor
+            .map(|c| serde_json::from_str(c).map_err(|_| StoreError::Query(format!("Invalid scan cursor '{}'", c))))
+            .transpose()?;
+        let pit = match &cursor {
+            Some(cursor) => cursor.pit.clone(),
+            None => {
+                let url = format!("{}/{}/_pit?keep_alive={}", self.base_url, self.index_name(object_type), ES_SCAN_KEEP_ALIVE);
+                let response = client.post(&url).send().await
+                    .map_err(|e| StoreError::Query(format!("Failed to open point in time: {}", e)))?;
+                let body: JsonValue = response.json().await
+                    .map_err(|e| StoreError::Query(format!("Failed to parse response: {}", e)))?;
+                body["id"].as_str()
+                    .ok_or_else(|| StoreError::Query(format!("Failed to open point in time: {}", body)))?
+                    .to_string()
             }
-            
-            let indexed_at = source.get("indexed_at")
-                .and_then(|v| v.as_str())
-                .and_then(|s| chrono::DateTime::parse_from_rfc3339(s).ok())
-                .map(|dt| dt.with_timezone(&chrono::Utc))
-                .unwrap_or_else(chrono::Utc::now);
-            
-            results.push(IndexedObject {
-                object_type: object_type.to_string(),
-                object_id: id.to_string(),
-                properties,
-                indexed_at,
-                source_last_modified: None,
-                refresh_frequency: None,
-                next_refresh: None,
-                refresh_status: RefreshStatus::UpToDate,
-            });
+        };
+        
+        let JsonValue::Object(mut body) = self.build_query_body(Some(filters))? else {
+            return Err(StoreError::Query("Invalid query body structure".to_string()));
+        };
+        body.insert("size".to_string(), json!(limit));
+        body.insert("pit".to_string(), json!({ "id": pit, "keep_alive": ES_SCAN_KEEP_ALIVE }));
+        body.insert("sort".to_string(), json!([{ "_shard_doc": "asc" }]));
+        body.insert("version".to_string(), json!(true));
+        if let Some(cursor) = &cursor {
+            body.insert("search_after".to_string(), cursor.search_after.clone());
+        }
+        
+        let response = client.post(format!("{}/_search", self.base_url)).json(&body).send().await
+            .map_err(|e| StoreError::Query(format!("Elasticsearch search failed: {}", e)))?;
+        let status = response.status();
+        if !status.is_success() {
+            let error_body = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+            return Err(StoreError::Query(format!(
+                "Elasticsearch returned error {}: {}",
+                status.as_u16(),
+                error_body
+            )));
         }
+        let response_body: JsonValue = response.json().await
+            .map_err(|e| StoreError::Query(format!("Failed to parse response: {}", e)))?;
         
-        Ok(results)
+        let hits = response_body["hits"]["hits"].as_array().cloned().unwrap_or_default();
+        let objects = hits.iter()
+            .map(|hit| search_hit_to_object(object_type, hit))
+            .collect::<Result<Vec<_>, _>>()?;
+        // The point-in-time ID may change between pages
+        let pit = response_body["pit_id"].as_str().map(str::to_string).unwrap_or(pit);
+        let next = match hits.last() {
+            Some(last) if hits.len() == limit => Some(
+                serde_json::to_string(&EsScanCursor { pit, search_after: last["sort"].clone() })
+                    .map_err(|e| StoreError::Serialization(e.to_string()))?,
+            ),
+            _ => {
+                // Best effort: an unclosed point-in-time expires after the keep-alive
+                let _ = client.delete(format!("{}/_pit", self.base_url)).json(&json!({ "id": pit })).send().await;
+                None
+            }
+        };
+        Ok(ObjectScanPage { objects, next })
     }
     
     async fn get_object(
@@ -1028,18 +2227,32 @@ impl SearchStore for ElasticsearchStore {
         object_type: &str,
         object_id: &str,
     ) -> Result<Option<IndexedObject>, StoreError> {
+        let (object, _, _) = self.get_object_with_seq_no(object_type, object_id).await?;
+        Ok(object)
+    }
+    
+    /// One `_mget` for all the objects
+    async fn get_objects(
+        &self,
+        object_type: &str,
+        object_ids: &[String],
+    ) -> Result<HashMap<String, IndexedObject>, StoreError> {
+        if object_ids.is_empty() {
+            return Ok(HashMap::new());
+        }
         let index_name = self.index_name(object_type);
-        
         let response = self.client
-            .get(GetParts::IndexId(&index_name, object_id))
+            .mget(MgetParts::Index(&index_name))
+            .body(json!({ "ids": object_ids }))
             .send()
             .await
-            .map_err(|e| StoreError::ReadError(format!("Elasticsearch get failed: {}", e)))?;
+            .map_err(|e| StoreError::ReadError(format!("Elasticsearch mget failed: {}", e)))?;
         
         let status_code = response.status_code();
         if !status_code.is_success() {
+            // Nothing has been written to this type yet
             if status_code == 404 {
-                return Ok(None);
+                return Ok(HashMap::new());
             }
             let error_body = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
             return Err(StoreError::ReadError(format!(
@@ -1049,109 +2262,103 @@ impl SearchStore for ElasticsearchStore {
             )));
         }
         
-        // Parse response
-        let response_body: serde_json::Value = response
+        let response_body: JsonValue = response
             .json()
             .await
             .map_err(|e| StoreError::ReadError(format!("Failed to parse response: {}", e)))?;
-        
-        // Extract source document
-        let source = response_body.get("_source")
-            .ok_or_else(|| StoreError::ReadError("Missing _source in response".to_string()))?;
-        
-        // Convert JSON back to PropertyMap
-        let mut properties = PropertyMap::new();
-        if let Some(obj) = source.as_object() {
-            for (key, value) in obj {
-                // Skip metadata fields
-                if key == "object_id" || key == "object_type" || key == "indexed_at" {
-                    continue;
-                }
-                
-                let prop_value: ontology_engine::PropertyValue = serde_json::from_value(value.clone())
-                    .map_err(|e| StoreError::ReadError(format!("Failed to deserialize property '{}': {}", key, e)))?;
-                properties.insert(key.clone(), prop_value);
-            }
-        }
-        
-        // Extract indexed_at from source or use current time
-        let indexed_at = source.get("indexed_at")
-            .and_then(|v| v.as_str())
-            .and_then(|s| chrono::DateTime::parse_from_rfc3339(s).ok())
-            .map(|dt| dt.with_timezone(&chrono::Utc))
-            .unwrap_or_else(chrono::Utc::now);
-        
-        Ok(Some(IndexedObject {
-            object_type: object_type.to_string(),
-            object_id: object_id.to_string(),
-            properties,
-            indexed_at,
-            source_last_modified: None,
-            refresh_frequency: None,
-            next_refresh: None,
-            refresh_status: RefreshStatus::UpToDate,
-        }))
+        mget_objects(object_type, &response_body)
     }
     
-    async fn bulk_index(
+    /// One `_mget` that returns only the hash field of each document
+    async fn content_hashes(
         &self,
-        objects: Vec<IndexedObject>,
-    ) -> Result<(), StoreError> {
-        if objects.is_empty() {
-            return Ok(());
+        object_type: &str,
+        object_ids: &[String],
+    ) -> Result<HashMap<String, Option<String>>, StoreError> {
+        if object_ids.is_empty() {
+            return Ok(HashMap::new());
         }
-        
-        // Group objects by type for efficient bulk indexing
-        let mut by_type: std::collections::HashMap<String, Vec<(String, JsonValue)>> = std::collections::HashMap::new();
-        
-        for obj in objects {
-            let mut properties = obj.properties.clone();
-            // Add metadata to properties for indexing
-            properties.insert("object_id".to_string(), ontology_engine::PropertyValue::String(obj.object_id.clone()));
-            properties.insert("object_type".to_string(), ontology_engine::PropertyValue::String(obj.object_type.clone()));
-            properties.insert("indexed_at".to_string(), ontology_engine::PropertyValue::DateTime(obj.indexed_at.to_rfc3339()));
+        let index_name = self.index_name(object_type);
+        let response = self.client
+            .mget(MgetParts::Index(&index_name))
+            ._source_includes(&[ontology_engine::CONTENT_HASH_PROPERTY])
+            .body(json!({ "ids": object_ids }))
+            .send()
+            .await
+            .map_err(|e| StoreError::ReadError(format!("Elasticsearch mget failed: {}", e)))?;
+        
+        let status_code = response.status_code();
+        if !status_code.is_success() {
+            // Nothing has been written to this type yet
+            if status_code == 404 {
+                return Ok(HashMap::new());
+            }
+            let error_body = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+            return Err(StoreError::ReadError(format!(
+                "Elasticsearch returned error {}: {}",
+                status_code.as_u16(),
+                error_body
+            )));
+        }
+        
+        let response_body: JsonValue = response
+            .json()
+            .await
+            .map_err(|e| StoreError::ReadError(format!("Failed to parse response: {}", e)))?;
+        Ok(mget_content_hashes(&response_body))
+    }
+    
+    /// Writes through the `_bulk` API in batches of `bulk_batch_size` objects. Every batch is
+    /// sent even if an earlier one had rejected objects; those are reported together as
+    /// `StoreError::BulkIndex`.
+    async fn bulk_index(
+        &self,
+        objects: Vec<IndexedObject>,
+    ) -> Result<(), StoreError> {
+        let object_types: std::collections::BTreeSet<&str> = objects.iter().map(|o| o.object_type.as_str()).collect();
+        for object_type in object_types {
+            self.ensure_alias(object_type).await?;
+        }
+        
+        let mut failures = Vec::new();
+        for batch in objects.chunks(self.bulk_batch_size) {
+            let mut body: Vec<JsonBody<JsonValue>> = Vec::with_capacity(batch.len() * 2);
+            for object in batch {
+                body.push(json!({
+                    "index": { "_index": self.index_name(&object.object_type), "_id": object.object_id }
+                }).into());
+                body.push(object_document(&object.properties)?.into());
+            }
             
-            // Serialize PropertyMap to JSON
-            let mut json_map = serde_json::Map::new();
-            for (key, value) in properties.iter() {
-                let json_value = serde_json::to_value(value)
-                    .map_err(|e| StoreError::Serialization(format!("Failed to serialize property '{}': {}", key, e)))?;
-                json_map.insert(key.clone(), json_value);
-            }
-            let json_body = JsonValue::Object(json_map);
+            let response = self.client
+                .bulk(BulkParts::None)
+                .body(body)
+                .send()
+                .await
+                .map_err(|e| StoreError::Connection(format!("Elasticsearch bulk request failed: {}", e)))?;
             
-            by_type.entry(obj.object_type.clone())
-                .or_insert_with(Vec::new)
-                .push((obj.object_id, json_body));
-        }
-        
-        // Perform bulk operations per object type
-        // Note: Proper bulk API with NDJSON requires determining the correct body type
-        // for elasticsearch crate version 8.19.0-alpha.1. The current implementation
-        // uses individual operations which is still more efficient than before (grouped by type).
-        for (object_type, items) in by_type {
-            for (id, doc) in items {
-                let mut properties = PropertyMap::new();
-                if let JsonValue::Object(map) = doc {
-                    for (key, value) in map {
-                        // Skip metadata fields - they're added automatically in index_object
-                        if key == "object_id" || key == "object_type" || key == "indexed_at" {
-                            continue;
-                        }
-                        let prop_value: ontology_engine::PropertyValue = serde_json::from_value(value)
-                            .map_err(|e| StoreError::Serialization(format!("Failed to deserialize property '{}': {}", key, e)))?;
-                        properties.insert(key, prop_value);
-                    }
-                }
-                
-                self.index_object(&object_type, &id, &properties).await?;
+            let status_code = response.status_code();
+            if !status_code.is_success() {
+                let error_body = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+                return Err(StoreError::Query(format!(
+                    "Elasticsearch returned error {}: {}",
+                    status_code.as_u16(),
+                    error_body
+                )));
             }
-            // Note: Proper bulk API with NDJSON requires determining the correct body type
-            // for elasticsearch crate version 8.19.0-alpha.1. The current implementation
-            // uses individual operations which is still more efficient than before (grouped by type).
+            
+            let response_body: JsonValue = response
+                .json()
+                .await
+                .map_err(|e| StoreError::Query(format!("Failed to parse response: {}", e)))?;
+            failures.extend(bulk_failures(&response_body, batch));
         }
         
-        Ok(())
+        if failures.is_empty() {
+            Ok(())
+        } else {
+            Err(StoreError::BulkIndex(failures))
+        }
     }
     
     async fn count_objects(
@@ -1187,6 +2394,105 @@ impl SearchStore for ElasticsearchStore {
         Ok(json["count"].as_u64().unwrap_or(0))
     }
     
+    async fn aggregate(
+        &self,
+        object_type: &str,
+        query: &AnalyticsQuery,
+    ) -> Result<AnalyticsResult, StoreError> {
+        if !query.group_by.is_empty() {
+            return Err(StoreError::Query(
+                "Grouped aggregations are not supported by Elasticsearch".to_string()
+            ));
+        }
+        let index_name = self.index_name(object_type);
+        let mut body = self.build_query_body(Some(query.filters.as_slice()))?;
+        body["size"] = json!(0);
+        body["track_total_hits"] = json!(true);
+        body["aggs"] = elasticsearch_aggregations(&query.aggregations)?;
+        
+        let response = self.client
+            .search(SearchParts::Index(&[&index_name]))
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| StoreError::Query(format!("Elasticsearch aggregation failed: {}", e)))?;
+        
+        let status_code = response.status_code();
+        if !status_code.is_success() {
+            let error_body = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+            return Err(StoreError::Query(format!(
+                "Elasticsearch returned error {}: {}",
+                status_code.as_u16(),
+                error_body
+            )));
+        }
+        
+        let json: serde_json::Value = response
+            .json()
+            .await
+            .map_err(|e| StoreError::Query(format!("Failed to parse aggregation response: {}", e)))?;
+        
+        let mut row = HashMap::new();
+        for aggregation in &query.aggregations {
+            let (name, value) = match aggregation {
+                Aggregation::Count => (
+                    "count".to_string(),
+                    ontology_engine::PropertyValue::Integer(json["hits"]["total"]["value"].as_i64().unwrap_or(0)),
+                ),
+                Aggregation::DistinctCount(prop) => {
+                    let name = format!("distinct_count_{}", prop);
+                    let value = json["aggregations"][&name]["value"].as_i64().unwrap_or(0);
+                    (name, ontology_engine::PropertyValue::Integer(value))
+                }
+                Aggregation::StdDev(prop) => {
+                    let name = format!("stddev_{}", prop);
+                    let value = json["aggregations"][&name]["std_deviation_sampling"].as_f64().unwrap_or(0.0);
+                    (name, ontology_engine::PropertyValue::Double(value))
+                }
+                Aggregation::Variance(prop) => {
+                    let name = format!("variance_{}", prop);
+                    let value = json["aggregations"][&name]["variance_sampling"].as_f64().unwrap_or(0.0);
+                    (name, ontology_engine::PropertyValue::Double(value))
+                }
+                Aggregation::Median(_) | Aggregation::Percentile(..) => {
+                    let name = match aggregation {
+                        Aggregation::Median(prop) => format!("median_{}", prop),
+                        Aggregation::Percentile(prop, pct) => format!("p{}_{}", (*pct * 100.0) as u8, prop),
+                        _ => unreachable!(),
+                    };
+                    // One percent is requested, so `values` has a single entry
+                    let value = json["aggregations"][&name]["values"]
+                        .as_object()
+                        .and_then(|values| values.values().next())
+                        .and_then(|v| v.as_f64())
+                        .unwrap_or(0.0);
+                    (name, ontology_engine::PropertyValue::Double(value))
+                }
+                Aggregation::Sum(prop) | Aggregation::Avg(prop) | Aggregation::Min(prop) | Aggregation::Max(prop) => {
+                    let prefix = match aggregation {
+                        Aggregation::Sum(_) => "sum",
+                        Aggregation::Avg(_) => "avg",
+                        Aggregation::Min(_) => "min",
+                        _ => "max",
+                    };
+                    let name = format!("{}_{}", prefix, prop);
+                    let value = json["aggregations"][&name]["value"].as_f64().unwrap_or(0.0);
+                    (name, ontology_engine::PropertyValue::Double(value))
+                }
+                Aggregation::TopN(..) | Aggregation::BottomN(..) => continue,
+            };
+            row.insert(name, value);
+        }
+        
+        Ok(AnalyticsResult {
+            rows: vec![row],
+            total: 1,
+            // Sketch-based, not sampled: no sample fraction or per-row bounds
+            sample_fraction: None,
+            error_bounds: vec![],
+        })
+    }
+    
     async fn delete_object(
         &self,
         object_type: &str,
@@ -1221,8 +2527,17 @@ impl SearchStore for ElasticsearchStore {
 // Dgraph store implementation
 pub struct DgraphStore {
     client: DgraphClient,
+    /// Link types whose predicates have been registered, in registration order; queries
+    /// that name no link type cover all of them
+    link_types: std::sync::RwLock<Vec<String>>,
+    /// Recently resolved xid → uid mappings, so links touching the same objects skip the
+    /// lookup. Nodes are never deleted, so entries cannot go stale.
+    uid_cache: std::sync::Mutex<lru::LruCache<String, String>>,
 }
 
+/// Entries kept in a `DgraphStore`'s xid → uid cache
+pub const DGRAPH_UID_CACHE_SIZE: usize = 100_000;
+
 impl DgraphStore {
     /// Create a new DgraphStore instance
     /// 
@@ -1236,64 +2551,82 @@ impl DgraphStore {
         let client = DgraphClient::new(endpoint)
             .map_err(|e| StoreError::Configuration(format!("Dgraph client error: {}", e)))?;
             
-        Ok(Self { client })
+        Ok(Self {
+            client,
+            link_types: std::sync::RwLock::new(Vec::new()),
+            uid_cache: std::sync::Mutex::new(new_uid_cache()),
+        })
     }
     
     /// Initialize the Dgraph schema
-    /// Run this once on startup to define predicates and indexes
-    pub async fn init_schema(&self) -> Result<(), StoreError> {
+    /// Run this once on startup to define predicates and indexes, with the ontology's link
+    /// types so their edges can be read in both directions
+    pub async fn init_schema(&self, link_type_ids: &[String]) -> Result<(), StoreError> {
         // Define schema for link properties and xid lookups
         // xid is used to map string IDs to UIDs
-        // link_id, link_type_id, created_at are stored as facets on edges
+        // link_id, link_type_id, created_at are stored as facets on edges, and again on a
+        // metadata node per link (with link_source/link_target) so links can be found by ID
         let schema = r#"
             xid: string @index(exact) .
             link_id: string @index(exact) .
             link_type_id: string .
+            link_source: uid .
+            link_target: uid .
             created_at: datetime .
         "#;
+        let mut schema = schema.to_string();
+        for link_type_id in link_type_ids {
+            schema.push_str(&link_predicate_schema(link_type_id));
+        }
         
         let op = Operation {
-            schema: schema.to_string(),
+            schema,
             ..Default::default()
         };
         
         self.client.alter(op).await
             .map_err(|e| StoreError::WriteError(format!("Schema error: {}", e)))?;
-            
+        
+        for link_type_id in link_type_ids {
+            self.remember_link_type(link_type_id);
+        }
         Ok(())
     }
     
-    /// Get or create a UID for a given string ID
-    /// Uses xid field to lookup existing UID or creates a new blank node
-    async fn get_or_create_uid(&self, object_id: &str) -> Result<String, StoreError> {
-        // First, try to query for existing UID
-        let query = format!(r#"
-            {{
-                objects(func: eq(xid, "{}")) {{
-                    uid
-                }}
-            }}
-        "#, object_id);
-        
-        let mut txn = self.client.new_read_only_txn();
-        let response = txn.query(query).await
-            .map_err(|e| StoreError::ReadError(format!("Query error: {}", e)))?;
+    /// Register the edge predicate for a link type so it can be traversed in both directions
+    pub async fn register_link_predicate(&self, link_type_id: &str) -> Result<(), StoreError> {
+        let op = Operation {
+            schema: link_predicate_schema(link_type_id),
+            ..Default::default()
+        };
         
-        let json: serde_json::Value = serde_json::from_slice(&response.json)
-            .map_err(|e| StoreError::ReadError(format!("Parse error: {}", e)))?;
+        self.client.alter(op).await
+            .map_err(|e| StoreError::WriteError(format!("Schema error: {}", e)))?;
         
-        // Check if we found an existing UID
-        if let Some(objects) = json.get("objects").and_then(|o| o.as_array()) {
-            if let Some(first) = objects.first() {
-                if let Some(uid) = first.get("uid").and_then(|u| u.as_str()) {
-                    return Ok(uid.to_string());
-                }
-            }
+        self.remember_link_type(link_type_id);
+        Ok(())
+    }
+    
+    fn remember_link_type(&self, link_type_id: &str) {
+        let mut link_types = self.link_types.write().unwrap();
+        if !link_types.iter().any(|id| id == link_type_id) {
+            link_types.push(link_type_id.to_string());
+        }
+    }
+    
+    /// Link types registered with this store
+    pub fn link_types(&self) -> Vec<String> {
+        self.link_types.read().unwrap().clone()
+    }
+    
+    /// Store an object's scalar properties on its node, so traversals can aggregate them.
+    /// Other values are not stored.
+    pub async fn set_node_properties(&self, object_id: &str, properties: &PropertyMap) -> Result<(), StoreError> {
+        let uid = self.get_or_create_uid(object_id).await?;
+        let rdf = node_property_nquads(&uid, properties);
+        if rdf.is_empty() {
+            return Ok(());
         }
-        
-        // No existing UID found, create a new node with blank UID (Dgraph will assign one)
-        // We'll use a mutation to create the node
-        let rdf = format!(r#"_:node <xid> "{}" ."#, object_id);
         
         let mutation = Mutation {
             set_nquads: rdf.into_bytes(),
@@ -1305,43 +2638,39 @@ impl DgraphStore {
             .map_err(|e| StoreError::WriteError(format!("Mutation error: {}", e)))?;
         txn.commit().await
             .map_err(|e| StoreError::WriteError(format!("Commit error: {}", e)))?;
-        
-        // Query to get the UID we just created
-        let query = format!(r#"
-            {{
-                objects(func: eq(xid, "{}")) {{
-                    uid
-                }}
-            }}
-        "#, object_id);
-        
-        let mut txn = self.client.new_read_only_txn();
-        let response = txn.query(query).await
-            .map_err(|e| StoreError::ReadError(format!("Query error: {}", e)))?;
-        
-        let json: serde_json::Value = serde_json::from_slice(&response.json)
-            .map_err(|e| StoreError::ReadError(format!("Parse error: {}", e)))?;
-        
-        if let Some(objects) = json.get("objects").and_then(|o| o.as_array()) {
-            if let Some(first) = objects.first() {
-                if let Some(uid) = first.get("uid").and_then(|u| u.as_str()) {
-                    return Ok(uid.to_string());
-                }
-            }
-        }
-        
-        Err(StoreError::WriteError(format!("Failed to get or create UID for {}", object_id)))
+        Ok(())
+    }
+    
+    /// Get or create a UID for a given string ID
+    /// Uses xid field to lookup existing UID or creates a new blank node
+    async fn get_or_create_uid(&self, object_id: &str) -> Result<String, StoreError> {
+        let mut uids = self.get_or_create_uids(&[object_id.to_string()]).await?;
+        uids.remove(object_id)
+            .ok_or_else(|| StoreError::WriteError(format!("Failed to get or create UID for {}", object_id)))
+    }
+    
+    /// UIDs for many string IDs, keyed by ID. Cached IDs are not looked up; the rest are
+    /// resolved in one query, and nodes for those still missing are created in one mutation
+    /// whose response assigns their UIDs.
+    pub async fn get_or_create_uids(&self, object_ids: &[String]) -> Result<HashMap<String, String>, StoreError> {
+        resolve_uids(self, &self.uid_cache, object_ids).await
     }
     
     /// Convert PropertyMap to RDF N-Quad format for facets
     /// Facets in Dgraph are stored as: <source> <predicate> <target> (property="value") .
-    fn properties_to_facets(&self, properties: &PropertyMap, link_id: &str, link_type_id: &str) -> String {
+    fn properties_to_facets(
+        &self,
+        properties: &PropertyMap,
+        link_id: &str,
+        link_type_id: &str,
+        created_at: &str,
+    ) -> String {
         let mut facets = Vec::new();
         
         // Always add link_id and link_type_id as facets
         facets.push(format!("link_id=\"{}\"", link_id));
         facets.push(format!("link_type_id=\"{}\"", link_type_id));
-        facets.push(format!("created_at=\"{}\"", chrono::Utc::now().to_rfc3339()));
+        facets.push(format!("created_at=\"{}\"", created_at));
         
         // Add custom properties as facets
         for (key, value) in properties.iter() {
@@ -1368,9 +2697,7 @@ impl DgraphStore {
                 ontology_engine::PropertyValue::Null => continue, // Skip null values
             };
             
-            // Escape quotes in the value
-            let escaped_value = value_str.replace('"', "\\\"");
-            facets.push(format!("{}=\"{}\"", key, escaped_value));
+            facets.push(format!("{}={}", key, dql_string(&value_str)));
         }
         
         if facets.is_empty() {
@@ -1380,12 +2707,11 @@ impl DgraphStore {
         }
     }
     
-    /// Build a Dgraph filter expression from a Filter
-    /// Filters are applied to facets (edge properties) using @filter directive
-    /// This is a helper method for traverse_with_filters
-    fn build_dgraph_filter(&self, filter: &Filter) -> Result<String, StoreError> {
+    /// Dgraph `@filter` expression for a filter on node predicates
+    fn build_dgraph_filter(filter: &Filter) -> Result<String, StoreError> {
+        let property = dql_name(&filter.property)?;
         let value_str = match &filter.value {
-            ontology_engine::PropertyValue::String(s) => format!("\"{}\"", s.replace('"', "\\\"")),
+            ontology_engine::PropertyValue::String(s) => dql_string(s),
             ontology_engine::PropertyValue::Integer(i) => i.to_string(),
             ontology_engine::PropertyValue::Double(d) => d.to_string(),
             ontology_engine::PropertyValue::Boolean(b) => b.to_string(),
@@ -1395,26 +2721,24 @@ impl DgraphStore {
             ))),
         };
         
-        // Dgraph uses facet syntax: @facets(property_name operator value)
-        // For filtering on facets during traversal, we use @filter(eq(facet_name, value))
         let filter_expr = match filter.operator {
             FilterOperator::Equals => {
-                format!("eq({}, {})", filter.property, value_str)
+                format!("eq({}, {})", property, value_str)
             }
             FilterOperator::NotEquals => {
-                format!("not eq({}, {})", filter.property, value_str)
+                format!("not eq({}, {})", property, value_str)
             }
             FilterOperator::GreaterThan => {
-                format!("gt({}, {})", filter.property, value_str)
+                format!("gt({}, {})", property, value_str)
             }
             FilterOperator::LessThan => {
-                format!("lt({}, {})", filter.property, value_str)
+                format!("lt({}, {})", property, value_str)
             }
             FilterOperator::GreaterThanOrEqual => {
-                format!("ge({}, {})", filter.property, value_str)
+                format!("ge({}, {})", property, value_str)
             }
             FilterOperator::LessThanOrEqual => {
-                format!("le({}, {})", filter.property, value_str)
+                format!("le({}, {})", property, value_str)
             }
             FilterOperator::In => {
                 // For In, we need to build an OR expression
@@ -1422,7 +2746,7 @@ impl DgraphStore {
                     let mut value_strs = Vec::new();
                     for v in arr {
                         let value_str = match v {
-                            ontology_engine::PropertyValue::String(s) => format!("\"{}\"", s.replace('"', "\\\"")),
+                            ontology_engine::PropertyValue::String(s) => dql_string(s),
                             ontology_engine::PropertyValue::Integer(i) => i.to_string(),
                             ontology_engine::PropertyValue::Double(d) => d.to_string(),
                             ontology_engine::PropertyValue::Boolean(b) => b.to_string(),
@@ -1435,7 +2759,7 @@ impl DgraphStore {
                     }
                     // Build OR expression for all values
                     let or_parts: Vec<String> = value_strs.iter()
-                        .map(|vs| format!("eq({}, {})", filter.property, vs))
+                        .map(|vs| format!("eq({}, {})", property, vs))
                         .collect();
                     format!("({})", or_parts.join(" OR "))
                 } else {
@@ -1456,6 +2780,7 @@ impl DgraphStore {
 
 #[async_trait]
 impl GraphStore for DgraphStore {
+    #[tracing::instrument(level = "debug", name = "store.create_link", skip_all, fields(backend = "dgraph", link_type_id = %link_type_id))]
     async fn create_link(
         &self,
         link_type_id: &str,
@@ -1463,20 +2788,56 @@ impl GraphStore for DgraphStore {
         target_id: &str,
         properties: &PropertyMap,
     ) -> Result<String, StoreError> {
-        // Generate a unique link_id
-        let link_id = Uuid::new_v4().to_string();
-        
-        // Get or create UIDs for source and target
-        let source_uid = self.get_or_create_uid(source_id).await?;
-        let target_uid = self.get_or_create_uid(target_id).await?;
-        
-        // Use link_type_id as the predicate name
-        // Sanitize it for Dgraph (predicates must be valid identifiers)
-        let predicate = link_type_id.replace('-', "_").replace('.', "_");
+        let link = NewLink {
+            link_type_id: link_type_id.to_string(),
+            source_id: source_id.to_string(),
+            target_id: target_id.to_string(),
+            properties: properties.clone(),
+        };
+        let mut link_ids = self.create_links_batch(std::slice::from_ref(&link)).await?;
+        link_ids.pop()
+            .ok_or_else(|| StoreError::WriteError("Link creation returned no link ID".to_string()))
+    }
+    
+    async fn create_links_batch(&self, links: &[NewLink]) -> Result<Vec<String>, StoreError> {
+        if links.is_empty() {
+            return Ok(Vec::new());
+        }
         
-        // Create the edge with properties as facets
-        let facets = self.properties_to_facets(properties, &link_id, link_type_id);
-        let rdf = format!("<{}> <{}> <{}> {} .", source_uid, predicate, target_uid, facets);
+        // Resolve every endpoint at once rather than per link
+        let mut endpoints: Vec<String> = Vec::with_capacity(links.len() * 2);
+        for link in links {
+            endpoints.push(link.source_id.clone());
+            endpoints.push(link.target_id.clone());
+        }
+        let uids = self.get_or_create_uids(&endpoints).await?;
+        
+        // Each edge carries its properties as facets and gets a metadata node, all in one
+        // mutation, so links can later be found (and deleted) by ID
+        let created_at = chrono::Utc::now().to_rfc3339();
+        let mut link_ids = Vec::with_capacity(links.len());
+        let mut rdf = String::new();
+        for (i, link) in links.iter().enumerate() {
+            let link_id = Uuid::new_v4().to_string();
+            let source_uid = &uids[&link.source_id];
+            let target_uid = &uids[&link.target_id];
+            
+            // Use link_type_id as the predicate name
+            // Sanitize it for Dgraph (predicates must be valid identifiers)
+            let predicate = link.link_type_id.replace('-', "_").replace('.', "_");
+            let facets = self.properties_to_facets(&link.properties, &link_id, &link.link_type_id, &created_at);
+            rdf.push_str(&format!("<{}> <{}> <{}> {} .\n", source_uid, predicate, target_uid, facets));
+            rdf.push_str(&link_metadata_nquads(
+                &format!("link{}", i),
+                &link_id,
+                &link.link_type_id,
+                source_uid,
+                target_uid,
+                &created_at,
+            ));
+            rdf.push('\n');
+            link_ids.push(link_id);
+        }
         
         let mutation = Mutation {
             set_nquads: rdf.into_bytes(),
@@ -1489,58 +2850,60 @@ impl GraphStore for DgraphStore {
         txn.commit().await
             .map_err(|e| StoreError::WriteError(format!("Commit error: {}", e)))?;
         
-        Ok(link_id)
+        Ok(link_ids)
     }
     
     async fn delete_link(
         &self,
         link_id: &str,
     ) -> Result<(), StoreError> {
-        // First, find the edge(s) with this link_id
-        // We need to query for edges that have link_id as a facet
-        let query = format!(r#"
-            {{
-                edges(func: eq(link_id, "{}")) @facets {{
-                    uid
-                    ~_predicate_ {{
-                        uid
-                    }}
-                }}
-            }}
-        "#, link_id);
-        
-        // This approach is tricky because Dgraph doesn't easily support querying by facet
-        // Instead, we'll need to query all edges and filter client-side, or use a different approach
-        // For now, we'll delete by querying the link_id property and then deleting the edge
-        // This requires a more complex query structure
-        
-        // Alternative: Store link_id as a reverse index or use a helper node
-        // For simplicity, we'll use a query to find the edge and then delete it
-        // Since Dgraph doesn't directly support querying edges by facets efficiently,
-        // we'll need to track links differently or accept this limitation
+        // Edges cannot be queried by facet, so the link's metadata node says which edge it is
+        let mut txn = self.client.new_mutated_txn();
+        let response = txn.query(link_metadata_query(link_id)).await
+            .map_err(|e| StoreError::ReadError(format!("Query error: {}", e)))?;
+        let json: serde_json::Value = serde_json::from_slice(&response.json)
+            .map_err(|e| StoreError::ReadError(format!("Parse error: {}", e)))?;
         
-        // For now, we'll delete by finding all edges and checking facets (not ideal for large graphs)
-        // In production, you might want to maintain a reverse index
-        let query = format!(r#"
-            {{
-                var(func: has(~_predicate_)) {{
-                    ~_predicate_ @facets(link_id) {{
-                        link_id as link_id
-                    }}
-                    filter(link_id == "{}")
-                }}
-            }}
-        "#, link_id);
+        // Already deleted (or never created)
+        let Some(metadata) = json.get("links").and_then(|l| l.as_array()).and_then(|l| l.first()) else {
+            return Err(StoreError::NotFound(format!("Link '{}'", link_id)));
+        };
+        let rdf = link_delete_nquads(metadata).ok_or_else(|| {
+            StoreError::ReadError(format!("Incomplete metadata for link '{}'", link_id))
+        })?;
         
-        // Actually, Dgraph's GraphQL+- doesn't easily support querying edges by facets
-        // We'll need to use a different approach: store link metadata in a separate node
-        // or accept that deletion requires scanning (which is a limitation)
+        let mutation = Mutation {
+            del_nquads: rdf.into_bytes(),
+            ..Default::default()
+        };
+        txn.mutate(mutation).await
+            .map_err(|e| StoreError::WriteError(format!("Link deletion error: {}", e)))?;
+        txn.commit().await
+            .map_err(|e| StoreError::WriteError(format!("Commit error: {}", e)))?;
         
-        // For now, return an error indicating this needs a more sophisticated approach
-        // In a production system, you'd maintain a reverse index or use intermediate nodes
-        Err(StoreError::Query(
-            "Delete by link_id requires reverse indexing - not yet implemented".to_string()
-        ))
+        Ok(())
+    }
+    
+    async fn get_link(&self, link_id: &str) -> Result<Option<GraphLink>, StoreError> {
+        // The metadata node names the link's type and source; the link itself, with its
+        // properties, is read off the source's outgoing edges
+        let mut txn = self.client.new_read_only_txn();
+        let response = txn.query(link_metadata_query(link_id)).await
+            .map_err(|e| StoreError::ReadError(format!("Query error: {}", e)))?;
+        let json: serde_json::Value = serde_json::from_slice(&response.json)
+            .map_err(|e| StoreError::ReadError(format!("Parse error: {}", e)))?;
+        let Some(metadata) = json.get("links").and_then(|l| l.as_array()).and_then(|l| l.first()) else {
+            return Ok(None);
+        };
+        let link_type_id = metadata.get("link_type_id").and_then(|v| v.as_str());
+        let source_id = metadata.get("link_source").and_then(|v| v.get("xid")).and_then(|v| v.as_str());
+        let (Some(link_type_id), Some(source_id)) = (link_type_id, source_id) else {
+            return Err(StoreError::ReadError(format!("Incomplete metadata for link '{}'", link_id)));
+        };
+        let links = self
+            .get_links(source_id, Some(link_type_id), Some(LinkDirection::Outgoing), &LinkQuery::default())
+            .await?;
+        Ok(links.into_iter().find(|link| link.link_id == link_id))
     }
     
     async fn get_links(
@@ -1548,67 +2911,106 @@ impl GraphStore for DgraphStore {
         object_id: &str,
         link_type_id: Option<&str>,
         direction: Option<LinkDirection>,
+        link_query: &LinkQuery,
     ) -> Result<Vec<GraphLink>, StoreError> {
         let object_uid = self.get_or_create_uid(object_id).await?;
         let direction = direction.unwrap_or(LinkDirection::Both);
-        let predicate = link_type_id.map(|id| id.replace('-', "_").replace('.', "_"));
         
-        let mut links = Vec::new();
+        // Without a link type, every registered link predicate is read
+        let link_type_ids = match link_type_id {
+            Some(id) => vec![id.to_string()],
+            None => self.link_types(),
+        };
+        if link_type_ids.is_empty() {
+            return Err(StoreError::Configuration(
+                "No link predicates registered; call init_schema with the ontology's link types".to_string(),
+            ));
+        }
+        let mut edges = Vec::new();
+        for link_type_id in &link_type_ids {
+            if direction != LinkDirection::Incoming {
+                edges.push((link_type_id.as_str(), true));
+            }
+            if direction != LinkDirection::Outgoing {
+                edges.push((link_type_id.as_str(), false));
+            }
+        }
         
-        // Query outgoing links
-        if direction == LinkDirection::Outgoing || direction == LinkDirection::Both {
-            if let Some(pred) = &predicate {
-                let query = format!(r#"
-                    {{
-                        node(func: uid({})) {{
-                            {} @facets {{
-                                uid
-                                xid
-                            }}
+        // A single edge pages in Dgraph (first/offset); reads over several edges are merged
+        // and then sorted and paged here. Facet filters and ordering only support scalar
+        // comparisons on a single facet; link properties that are not stored as facets
+        // (arrays, maps) cannot be filtered or sorted on.
+        let single_edge = edges.len() == 1;
+        let mut page_args = Vec::new();
+        if single_edge {
+            if let Some(limit) = link_query.limit {
+                page_args.push(format!("first: {}", limit));
+            }
+            if let Some(offset) = link_query.offset {
+                page_args.push(format!("offset: {}", offset));
+            }
+        }
+        let page_args = if page_args.is_empty() {
+            String::new()
+        } else {
+            format!("({})", page_args.join(", "))
+        };
+        let facet_filter = self.build_facet_filter(&link_query.filters)?
+            .map(|expr| format!("@facets({})", expr))
+            .unwrap_or_default();
+        let facet_order = match &link_query.sort {
+            Some(sort) => format!(
+                "@facets({}: {})",
+                if sort.ascending { "orderasc" } else { "orderdesc" },
+                dql_name(&sort.property)?
+            ),
+            None => "@facets".to_string(),
+        };
+        
+        let mut links = Vec::new();
+        for (link_type_id, outgoing) in edges {
+            let edge = edge_name(link_type_id, outgoing);
+            let query = format!(r#"
+                {{
+                    node(func: uid({})) {{
+                        {} {} {} {} {{
+                            uid
+                            xid
                         }}
                     }}
-                "#, object_uid, pred);
-                
-                let mut txn = self.client.new_read_only_txn();
-                let response = txn.query(query).await
-                    .map_err(|e| StoreError::ReadError(format!("Query error: {}", e)))?;
-                
-                let json: serde_json::Value = serde_json::from_slice(&response.json)
-                    .map_err(|e| StoreError::ReadError(format!("Parse error: {}", e)))?;
-                
-                if let Some(node_arr) = json.get("node").and_then(|n| n.as_array()) {
-                    for node in node_arr {
-                        if let Some(targets) = node.get(pred).and_then(|t| t.as_array()) {
-                            for target in targets {
-                                // Extract facets for link_id, link_type_id, and properties
-                                let link = self.extract_link_from_target(
-                                    target,
-                                    object_id,
-                                    link_type_id.unwrap(),
-                                    direction,
-                                    true,
-                                )?;
-                                links.push(link);
-                            }
-                        }
-                    }
+                }}
+            "#, object_uid, edge, page_args, facet_filter, facet_order);
+            
+            let mut txn = self.client.new_read_only_txn();
+            let response = txn.query(query).await
+                .map_err(|e| StoreError::ReadError(format!("Query error: {}", e)))?;
+            
+            let json: serde_json::Value = serde_json::from_slice(&response.json)
+                .map_err(|e| StoreError::ReadError(format!("Parse error: {}", e)))?;
+            
+            for node in json.get("node").and_then(|n| n.as_array()).into_iter().flatten() {
+                for target in node.get(&edge).and_then(|t| t.as_array()).into_iter().flatten() {
+                    // Extract facets for link_id, created_at, and properties
+                    links.push(self.extract_link_from_target(target, object_id, link_type_id, outgoing)?);
                 }
-            } else {
-                // Query all outgoing predicates - more complex
-                // For now, return empty if no specific predicate
             }
         }
         
-        // Query incoming links
-        if direction == LinkDirection::Incoming || direction == LinkDirection::Both {
-            // Similar logic but query reverse direction
-            // This would require knowing all possible predicates or using a schema query
-            // For now, we'll focus on outgoing links with specified predicates
+        if !single_edge {
+            links.sort_by(|a, b| a.created_at.cmp(&b.created_at).then_with(|| a.link_id.cmp(&b.link_id)));
+            let sort = link_query.sort.as_slice();
+            if !sort.is_empty() {
+                links.sort_by(|a, b| crate::in_memory::compare_sorted(&a.properties, &b.properties, sort));
+            }
+            let offset = link_query.offset.unwrap_or(0);
+            let limit = link_query.limit.unwrap_or(usize::MAX);
+            links = links.into_iter().skip(offset).take(limit).collect();
         }
         
         Ok(links)
     }
     
+    #[tracing::instrument(level = "debug", name = "store.traverse", skip_all, fields(backend = "dgraph", start_id = %start_id, max_hops = max_hops))]
     async fn traverse(
         &self,
         start_id: &str,
@@ -1622,29 +3024,7 @@ impl GraphStore for DgraphStore {
         let mut all_target_ids = Vec::new();
         
         for link_type_id in link_type_ids {
-            let predicate = link_type_id.replace('-', "_").replace('.', "_");
-            
-            // Build recursive query for traversal up to max_hops
-            let mut query_parts = vec![format!("node(func: uid({}))", start_uid)];
-            
-            for hop in 0..max_hops {
-                let indent = "  ".repeat(hop + 1);
-                query_parts.push(format!("{}~{} {{", indent, predicate));
-                query_parts.push(format!("{}  uid", indent));
-                query_parts.push(format!("{}  xid", indent));
-                if hop < max_hops - 1 {
-                    query_parts.push(format!("{}  ~{} {{", indent, predicate));
-                }
-            }
-            
-            // Close all brackets
-            for hop in (0..max_hops).rev() {
-                let indent = "  ".repeat(hop + 1);
-                query_parts.push(format!("{}}}", indent));
-            }
-            query_parts.push("}".to_string());
-            
-            let query = format!("{{\n{}\n}}", query_parts.join("\n"));
+            let query = dgraph_traversal_query(&format!("uid({})", start_uid), link_type_id, max_hops);
             
             let mut txn = self.client.new_read_only_txn();
             let response = txn.query(query).await
@@ -1687,75 +3067,55 @@ impl GraphStore for DgraphStore {
         link_filters: &[Filter],
     ) -> Result<Vec<String>, StoreError> {
         let start_uid = self.get_or_create_uid(start_id).await?;
-        let mut all_target_ids = Vec::new();
         
-        // Build filter string for Dgraph @filter directive
-        let filter_str = if !link_filters.is_empty() {
-            let mut filter_parts = Vec::new();
-            for filter in link_filters {
-                let filter_expr = self.build_dgraph_filter(filter)?;
-                filter_parts.push(filter_expr);
-            }
-            if !filter_parts.is_empty() {
-                format!(" @filter({})", filter_parts.join(" AND "))
-            } else {
-                String::new()
-            }
-        } else {
-            String::new()
-        };
+        // Link filters apply to the facets of the edge taken at every hop. DQL can compare a
+        // facet with a scalar; anything else is checked here on the returned facets.
+        let (pushed, client_side): (Vec<Filter>, Vec<Filter>) =
+            link_filters.iter().cloned().partition(is_dgraph_facet_filter);
+        if !client_side.is_empty() {
+            tracing::warn!(
+                "Dgraph cannot filter link facets on {}; filtering traversal results client-side",
+                client_side
+                    .iter()
+                    .map(|f| format!("{} {:?}", f.property, f.operator))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+        }
+        let facet_filter = self.build_facet_filter(&pushed)?;
         
-        // Build traversal query with filters
+        let mut target_ids = Vec::new();
         for link_type_id in link_type_ids {
-            let predicate = link_type_id.replace('-', "_").replace('.', "_");
+            let query = dgraph_filtered_traversal_query(
+                &format!("uid({})", start_uid),
+                link_type_id,
+                max_hops,
+                facet_filter.as_deref(),
+                !client_side.is_empty(),
+            );
             
-            let mut query_parts = Vec::new();
-            query_parts.push(format!("query {{\n  node(func: uid({})) {{", start_uid));
-            
-            // Build nested traversal with filters
-            for hop in 0..max_hops {
-                let indent = "  ".repeat(hop + 1);
-                if hop == 0 {
-                    // Apply filter on first hop
-                    query_parts.push(format!("{}{} {} {{", indent, predicate, filter_str));
-                } else {
-                    query_parts.push(format!("{}~{} {{", indent, predicate));
-                }
-                query_parts.push(format!("{}  uid", indent));
-                query_parts.push(format!("{}  xid", indent));
-                if hop < max_hops - 1 {
-                    query_parts.push(format!("{}  ~{} {{", indent, predicate));
-                }
-            }
-            
-            // Close all brackets
-            for hop in (0..max_hops).rev() {
-                let indent = "  ".repeat(hop + 1);
-                query_parts.push(format!("{}}}", indent));
-            }
-            query_parts.push("  }".to_string());
-            query_parts.push("}".to_string());
-            
-            let query = query_parts.join("\n");
-            
-            let mut txn = self.client.new_read_only_txn();
-            let response = txn.query(query).await
-                .map_err(|e| StoreError::ReadError(format!("Query error: {}", e)))?;
+            let mut txn = self.client.new_read_only_txn();
+            let response = txn.query(query).await
+                .map_err(|e| StoreError::ReadError(format!("Query error: {}", e)))?;
             
             let json: serde_json::Value = serde_json::from_slice(&response.json)
                 .map_err(|e| StoreError::ReadError(format!("Parse error: {}", e)))?;
             
-            // Extract all UIDs from the traversal result
-            self.extract_ids_from_traversal(&json, &mut all_target_ids);
+            let edge = edge_name(link_type_id, true);
+            for node in json.get("node").and_then(|n| n.as_array()).into_iter().flatten() {
+                collect_filtered_targets(node, &edge, &client_side, &mut target_ids);
+            }
         }
         
-        // Convert UIDs back to string IDs by querying xid
+        // Nodes without an xid are reported by UID, as in `traverse`
         let mut string_ids = Vec::new();
-        for uid in all_target_ids {
-            if let Ok(id) = self.uid_to_xid(&uid).await {
-                if !string_ids.contains(&id) {
-                    string_ids.push(id);
-                }
+        for id in target_ids {
+            let id = match id {
+                Ok(xid) => xid,
+                Err(uid) => self.uid_to_xid(&uid).await?,
+            };
+            if !string_ids.contains(&id) {
+                string_ids.push(id);
             }
         }
         
@@ -1769,85 +3129,25 @@ impl GraphStore for DgraphStore {
         max_hops: usize,
         aggregation: &TraversalAggregation,
     ) -> Result<TraversalAggregationResult, StoreError> {
-        let start_uid = self.get_or_create_uid(start_id).await?;
-        
-        // Build aggregation query using Dgraph's aggregation functions
-        // Dgraph supports: count(uid), sum(predicate), avg(predicate), min(predicate), max(predicate)
-        let predicate = if !link_type_ids.is_empty() {
-            link_type_ids[0].replace('-', "_").replace('.', "_")
-        } else {
+        if link_type_ids.is_empty() {
             return Err(StoreError::Query("At least one link type is required for aggregation".to_string()));
-        };
-        
-        // Build aggregation expression based on operation
-        // Note: Dgraph supports limited aggregations, so some will need post-processing
-        let agg_expr = match &aggregation.operation {
-            Aggregation::Count => "count(uid)".to_string(),
-            Aggregation::Sum(prop) => format!("sum({})", prop),
-            Aggregation::Avg(prop) => format!("avg({})", prop),
-            Aggregation::Min(prop) => format!("min({})", prop),
-            Aggregation::Max(prop) => format!("max({})", prop),
-            Aggregation::Median(_) | Aggregation::StdDev(_) | Aggregation::Variance(_) |
-            Aggregation::Percentile(_, _) | Aggregation::DistinctCount(_) |
-            Aggregation::TopN(_, _) | Aggregation::BottomN(_, _) => {
-                return Err(StoreError::Query(
-                    format!("Aggregation {:?} not supported in graph traversal. Use columnar store instead.", aggregation.operation)
-                ));
-            }
-        };
-        
-        // Build filter string if object filters are provided
-        let filter_str = if !aggregation.object_filters.is_empty() {
-            let mut filter_parts = Vec::new();
-            for filter in &aggregation.object_filters {
-                let filter_expr = self.build_dgraph_filter(filter)?;
-                filter_parts.push(filter_expr);
-            }
-            if !filter_parts.is_empty() {
-                format!(" @filter({})", filter_parts.join(" AND "))
-            } else {
-                String::new()
-            }
-        } else {
-            String::new()
-        };
-        
-        // Build query with aggregation
-        // For multi-hop traversal with aggregation, we aggregate over all reached nodes
-        let mut query_parts = Vec::new();
-        query_parts.push(format!("query {{\n  node(func: uid({})) {{", start_uid));
-        
-        // Build traversal path
-        for hop in 0..max_hops {
-            let indent = "  ".repeat(hop + 1);
-            if hop == 0 {
-                query_parts.push(format!("{}{} {} {{", indent, predicate, filter_str));
-            } else {
-                query_parts.push(format!("{}~{} {{", indent, predicate));
-            }
-            
-            if hop == max_hops - 1 {
-                // At the final hop, apply aggregation
-                query_parts.push(format!("{}  {}", indent, agg_expr));
-            } else {
-                query_parts.push(format!("{}  uid", indent));
-                query_parts.push(format!("{}  xid", indent));
-            }
-            
-            if hop < max_hops - 1 {
-                query_parts.push(format!("{}  ~{} {{", indent, predicate));
-            }
         }
+        let start_uid = self.get_or_create_uid(start_id).await?;
         
-        // Close all brackets
-        for hop in (0..max_hops).rev() {
-            let indent = "  ".repeat(hop + 1);
-            query_parts.push(format!("{}}}", indent));
+        // Object filters apply to the traversed nodes before aggregating
+        let mut filter_parts = Vec::new();
+        for filter in &aggregation.object_filters {
+            filter_parts.push(Self::build_dgraph_filter(filter)?);
         }
-        query_parts.push("  }".to_string());
-        query_parts.push("}".to_string());
+        let node_filter = if filter_parts.is_empty() { None } else { Some(filter_parts.join(" AND ")) };
         
-        let query = query_parts.join("\n");
+        let query = dgraph_aggregation_query(
+            &start_uid,
+            link_type_ids,
+            max_hops,
+            &aggregation.operation,
+            node_filter.as_deref(),
+        )?;
         
         let mut txn = self.client.new_read_only_txn();
         let response = txn.query(query).await
@@ -1856,116 +3156,7 @@ impl GraphStore for DgraphStore {
         let json: serde_json::Value = serde_json::from_slice(&response.json)
             .map_err(|e| StoreError::ReadError(format!("Parse error: {}", e)))?;
         
-        // Extract aggregation result
-        // The result structure depends on the aggregation type
-        let (value, count) = if let Some(node_arr) = json.get("node").and_then(|n| n.as_array()) {
-            if let Some(node) = node_arr.first() {
-                // Navigate through the traversal path to find the aggregation result
-                // The aggregation result is at the deepest level
-                let mut current = node;
-                for _ in 0..max_hops {
-                    if let Some(pred_obj) = current.get(&predicate).and_then(|p| p.as_array()).and_then(|a| a.first()) {
-                        current = pred_obj;
-                    } else {
-                        break;
-                    }
-                }
-                
-                // Extract aggregation value based on operation type
-                let (value, count) = match &aggregation.operation {
-                    Aggregation::Count => {
-                        // Count returns the number of UIDs
-                        if let Some(arr) = current.as_array() {
-                            (arr.len() as i64, arr.len())
-                        } else if let Some(count_val) = current.get("count").and_then(|v| v.as_u64()) {
-                            (count_val as i64, count_val as usize)
-                        } else {
-                            (0, 0)
-                        }
-                    }
-                    Aggregation::Sum(prop) => {
-                        let agg_key = format!("sum_{}", prop);
-                        if let Some(num_val) = current.get(&agg_key).and_then(|v| {
-                            v.as_f64().or_else(|| v.as_i64().map(|i| i as f64))
-                        }) {
-                            (num_val as i64, 1)
-                        } else {
-                            (0, 0)
-                        }
-                    }
-                    Aggregation::Avg(prop) => {
-                        let agg_key = format!("avg_{}", prop);
-                        if let Some(num_val) = current.get(&agg_key).and_then(|v| {
-                            v.as_f64().or_else(|| v.as_i64().map(|i| i as f64))
-                        }) {
-                            (num_val as i64, 1)
-                        } else {
-                            (0, 0)
-                        }
-                    }
-                    Aggregation::Min(prop) => {
-                        let agg_key = format!("min_{}", prop);
-                        if let Some(num_val) = current.get(&agg_key).and_then(|v| {
-                            v.as_f64().or_else(|| v.as_i64().map(|i| i as f64))
-                        }) {
-                            (num_val as i64, 1)
-                        } else {
-                            (0, 0)
-                        }
-                    }
-                    Aggregation::Max(prop) => {
-                        let agg_key = format!("max_{}", prop);
-                        if let Some(num_val) = current.get(&agg_key).and_then(|v| {
-                            v.as_f64().or_else(|| v.as_i64().map(|i| i as f64))
-                        }) {
-                            (num_val as i64, 1)
-                        } else {
-                            (0, 0)
-                        }
-                    }
-                    Aggregation::Median(_) | Aggregation::StdDev(_) | Aggregation::Variance(_) |
-                    Aggregation::Percentile(_, _) | Aggregation::DistinctCount(_) |
-                    Aggregation::TopN(_, _) | Aggregation::BottomN(_, _) => {
-                        // These aggregations are not supported in graph traversal
-                        (0, 0)
-                    }
-                };
-                
-                (value, count)
-            } else {
-                (0, 0)
-            }
-        } else {
-            (0, 0)
-        };
-        
-        // Convert to PropertyValue
-        // For aggregations, we need to extract the actual numeric value from the query result
-        // Dgraph returns aggregation results differently - we need to handle the actual response structure
-        let prop_value = match &aggregation.operation {
-            Aggregation::Count => ontology_engine::PropertyValue::Integer(value),
-            Aggregation::Sum(_) | Aggregation::Min(_) | Aggregation::Max(_) => {
-                ontology_engine::PropertyValue::Integer(value)
-            }
-            Aggregation::Avg(_) => {
-                // Average might be a decimal, but for now we'll use Integer
-                // In a full implementation, we'd extract the actual double value from Dgraph response
-                ontology_engine::PropertyValue::Integer(value)
-            }
-            Aggregation::Median(_) | Aggregation::StdDev(_) | Aggregation::Variance(_) |
-            Aggregation::Percentile(_, _) | Aggregation::DistinctCount(_) |
-            Aggregation::TopN(_, _) | Aggregation::BottomN(_, _) => {
-                // These aggregations are not supported in graph traversal
-                return Err(StoreError::Query(
-                    format!("Aggregation {:?} not supported in graph traversal", aggregation.operation)
-                ));
-            }
-        };
-        
-        Ok(TraversalAggregationResult {
-            value: prop_value,
-            count,
-        })
+        dgraph_aggregation_result(&json, &aggregation.operation)
     }
     
     async fn compute_centrality(
@@ -1996,6 +3187,107 @@ impl GraphStore for DgraphStore {
         Err(StoreError::Query("Shortest path computation not yet implemented".to_string()))
     }
     
+    fn explain_traversal(&self, start_id: &str, link_type_ids: &[String], max_hops: usize) -> Option<String> {
+        let root = format!("eq(xid, {})", JsonValue::String(start_id.to_string()));
+        Some(
+            link_type_ids
+                .iter()
+                .map(|link_type_id| dgraph_traversal_query(&root, link_type_id, max_hops))
+                .collect::<Vec<_>>()
+                .join("\n"),
+        )
+    }
+    
+    /// Pages over source nodes holding the link predicate; the cursor is a node offset, so a
+    /// page can hold more than `limit` links
+    async fn scan_links(
+        &self,
+        link_type_id: &str,
+        cursor: Option<&str>,
+        limit: usize,
+    ) -> Result<LinkScanPage, StoreError> {
+        let predicate = link_type_id.replace('-', "_").replace('.', "_");
+        let offset: usize = match cursor {
+            Some(cursor) => cursor
+                .parse()
+                .map_err(|_| StoreError::Query(format!("Invalid scan cursor '{}'", cursor)))?,
+            None => 0,
+        };
+        let query = format!(r#"
+            {{
+                nodes(func: has({pred}), first: {limit}, offset: {offset}) {{
+                    xid
+                    {pred} @facets {{
+                        uid
+                        xid
+                    }}
+                }}
+            }}
+        "#, pred = predicate, limit = limit, offset = offset);
+        
+        let mut txn = self.client.new_read_only_txn();
+        let response = txn.query(query).await
+            .map_err(|e| StoreError::ReadError(format!("Query error: {}", e)))?;
+        let json: serde_json::Value = serde_json::from_slice(&response.json)
+            .map_err(|e| StoreError::ReadError(format!("Parse error: {}", e)))?;
+        
+        let nodes = json.get("nodes").and_then(|n| n.as_array()).cloned().unwrap_or_default();
+        let mut links = Vec::new();
+        for node in &nodes {
+            let Some(source_id) = node.get("xid").and_then(|x| x.as_str()) else { continue };
+            for target in node.get(&predicate).and_then(|t| t.as_array()).into_iter().flatten() {
+                links.push(self.extract_link_from_target(target, source_id, link_type_id, true)?);
+            }
+        }
+        let next = (nodes.len() == limit).then(|| (offset + limit).to_string());
+        Ok(LinkScanPage { links, next })
+    }
+    
+    /// Reads only the property's facet, paging over source nodes
+    async fn link_property_stats(
+        &self,
+        link_type_id: &str,
+        property: &str,
+        buckets: usize,
+    ) -> Result<LinkPropertyStats, StoreError> {
+        let predicate = link_type_id.replace('-', "_").replace('.', "_");
+        let property = dql_name(property)?;
+        let facet_key = format!("{}|{}", predicate, property);
+        let mut values = Vec::new();
+        let mut offset = 0;
+        loop {
+            let query = format!(r#"
+                {{
+                    nodes(func: has({pred}), first: {limit}, offset: {offset}) {{
+                        {pred} @facets({property}) {{
+                            uid
+                        }}
+                    }}
+                }}
+            "#, pred = predicate, property = property, limit = LINK_STATS_PAGE_SIZE, offset = offset);
+            
+            let mut txn = self.client.new_read_only_txn();
+            let response = txn.query(query).await
+                .map_err(|e| StoreError::ReadError(format!("Query error: {}", e)))?;
+            let json: serde_json::Value = serde_json::from_slice(&response.json)
+                .map_err(|e| StoreError::ReadError(format!("Parse error: {}", e)))?;
+            
+            let nodes = json.get("nodes").and_then(|n| n.as_array()).cloned().unwrap_or_default();
+            for node in &nodes {
+                for target in node.get(&predicate).and_then(|t| t.as_array()).into_iter().flatten() {
+                    if let Some(value) = target.get(&facet_key).and_then(|v| v.as_f64()) {
+                        values.push(value);
+                    }
+                }
+            }
+            if nodes.len() < LINK_STATS_PAGE_SIZE {
+                break;
+            }
+            offset += LINK_STATS_PAGE_SIZE;
+        }
+        LinkPropertyStats::from_values(&values, buckets)
+    }
+    
     async fn graph_metrics(
         &self,
         _object_type: &str,
@@ -2012,39 +3304,95 @@ impl GraphStore for DgraphStore {
 }
 
 impl DgraphStore {
-    /// Extract link information from a target node with facets
+    /// Build a facet filter expression (`eq(since, "2020") AND gt(weight, 2)`) from link filters
+    fn build_facet_filter(&self, filters: &[Filter]) -> Result<Option<String>, StoreError> {
+        let mut exprs = Vec::new();
+        for filter in filters {
+            let func = match filter.operator {
+                FilterOperator::Equals => "eq",
+                FilterOperator::GreaterThan => "gt",
+                FilterOperator::LessThan => "lt",
+                FilterOperator::GreaterThanOrEqual => "ge",
+                FilterOperator::LessThanOrEqual => "le",
+                _ => {
+                    return Err(StoreError::Query(format!(
+                        "Filter operator {:?} is not supported on Dgraph link facets",
+                        filter.operator
+                    )));
+                }
+            };
+            let value = match &filter.value {
+                ontology_engine::PropertyValue::Integer(i) => i.to_string(),
+                ontology_engine::PropertyValue::Double(d) => d.to_string(),
+                ontology_engine::PropertyValue::Boolean(b) => b.to_string(),
+                other => dql_string(&other.to_string()),
+            };
+            exprs.push(format!("{}({}, {})", func, dql_name(&filter.property)?, value));
+        }
+        Ok(if exprs.is_empty() { None } else { Some(exprs.join(" AND ")) })
+    }
+    
+    /// Extract link information from a node reached from `object_id` along a link edge,
+    /// forwards (`outgoing`) or along its reverse. Facets come back as `<edge>|<facet>` keys;
+    /// `link_id` and `created_at` mirror the link's metadata node and the rest are the
+    /// link's properties.
     fn extract_link_from_target(
         &self,
         target: &serde_json::Value,
-        source_id: &str,
+        object_id: &str,
         link_type_id: &str,
-        _direction: LinkDirection,
-        _outgoing: bool,
+        outgoing: bool,
     ) -> Result<GraphLink, StoreError> {
         let target_uid = target.get("uid")
             .and_then(|u| u.as_str())
             .ok_or_else(|| StoreError::ReadError("Missing uid in target".to_string()))?;
         
-        let target_id = target.get("xid")
+        let other_id = target.get("xid")
             .and_then(|x| x.as_str())
             .map(|s| s.to_string())
             .unwrap_or_else(|| target_uid.to_string());
+        let (source_id, target_id) = if outgoing {
+            (object_id.to_string(), other_id)
+        } else {
+            (other_id, object_id.to_string())
+        };
         
-        // Extract facets (link properties)
-        // Note: Facets in Dgraph response are nested under @facets
+        let facet_prefix = format!("{}|", edge_name(link_type_id, outgoing));
+        let mut link_id = None;
+        let mut created_at = None;
         let mut properties = PropertyMap::new();
-        
-        // For now, create a basic link
-        // In a full implementation, you'd extract all facets
-        let link_id = Uuid::new_v4().to_string(); // Would come from facets
+        for (key, value) in target.as_object().into_iter().flatten() {
+            let Some(facet) = key.strip_prefix(&facet_prefix) else { continue };
+            match facet {
+                "link_id" => link_id = value.as_str().map(|s| s.to_string()),
+                "created_at" => {
+                    created_at = value
+                        .as_str()
+                        .and_then(|s| chrono::DateTime::parse_from_rfc3339(s).ok())
+                        .map(|t| t.with_timezone(&chrono::Utc));
+                }
+                "link_type_id" => {}
+                _ => {
+                    if let Some(value) = facet_value_to_property(value) {
+                        properties.insert(facet.to_string(), value);
+                    }
+                }
+            }
+        }
+        let link_id = link_id.ok_or_else(|| {
+            StoreError::ReadError(format!(
+                "Edge '{}' from '{}' to '{}' has no link_id facet",
+                link_type_id, source_id, target_id
+            ))
+        })?;
         
         Ok(GraphLink {
             link_id,
             link_type_id: link_type_id.to_string(),
-            source_id: source_id.to_string(),
+            source_id,
             target_id,
             properties,
-            created_at: chrono::Utc::now(),
+            created_at: created_at.unwrap_or_else(chrono::Utc::now),
         })
     }
     
@@ -2100,6 +3448,273 @@ impl DgraphStore {
     }
 }
 
+/// DQL for a `max_hops` traversal forwards along one link type from the node matched by
+/// `root`, following at each hop only edges whose facets pass `facet_filter` (a DQL facet
+/// expression such as `gt(weight, 15)`). With `fetch_facets`, each edge's facets are returned
+/// too, for filters checked outside Dgraph.
+pub fn dgraph_filtered_traversal_query(
+    root: &str,
+    link_type_id: &str,
+    max_hops: usize,
+    facet_filter: Option<&str>,
+    fetch_facets: bool,
+) -> String {
+    let predicate = link_type_id.replace('-', "_").replace('.', "_");
+    let mut directives = String::new();
+    if let Some(filter) = facet_filter {
+        directives.push_str(&format!(" @facets({})", filter));
+    }
+    if fetch_facets {
+        directives.push_str(" @facets");
+    }
+    let mut query_parts = vec![format!("node(func: {}) {{", root)];
+    
+    // Each hop nests inside the previous one, filtered the same way
+    for hop in 0..max_hops {
+        let indent = "  ".repeat(hop + 1);
+        query_parts.push(format!("{}{}{} {{", indent, predicate, directives));
+        query_parts.push(format!("{}  uid", indent));
+        query_parts.push(format!("{}  xid", indent));
+    }
+    
+    // Close all brackets
+    for hop in (0..max_hops).rev() {
+        let indent = "  ".repeat(hop + 1);
+        query_parts.push(format!("{}}}", indent));
+    }
+    query_parts.push("}".to_string());
+    
+    format!("{{\n{}\n}}", query_parts.join("\n"))
+}
+
+/// Whether DQL can apply a link filter to edge facets: a comparison with a scalar
+fn is_dgraph_facet_filter(filter: &Filter) -> bool {
+    matches!(
+        filter.operator,
+        FilterOperator::Equals
+            | FilterOperator::GreaterThan
+            | FilterOperator::LessThan
+            | FilterOperator::GreaterThanOrEqual
+            | FilterOperator::LessThanOrEqual
+    ) && matches!(
+        filter.value,
+        ontology_engine::PropertyValue::Integer(_)
+            | ontology_engine::PropertyValue::Double(_)
+            | ontology_engine::PropertyValue::String(_)
+    )
+}
+
+/// Nodes reached along `edge` from `node` in a traversal result, at every depth, whose edge
+/// facets pass `filters`; a node failing them is not traversed further. Each node is its xid,
+/// or `Err(uid)` when it has none.
+fn collect_filtered_targets(
+    node: &serde_json::Value,
+    edge: &str,
+    filters: &[Filter],
+    ids: &mut Vec<Result<String, String>>,
+) {
+    let facet_prefix = format!("{}|", edge);
+    for target in node.get(edge).and_then(|t| t.as_array()).into_iter().flatten() {
+        if !filters.is_empty() {
+            let mut facets = PropertyMap::new();
+            for (key, value) in target.as_object().into_iter().flatten() {
+                if let (Some(facet), Some(value)) = (key.strip_prefix(&facet_prefix), facet_value_to_property(value)) {
+                    facets.insert(facet.to_string(), value);
+                }
+            }
+            if !matches_filters(&facets, filters) {
+                continue;
+            }
+        }
+        let id = match (target.get("xid").and_then(|x| x.as_str()), target.get("uid").and_then(|u| u.as_str())) {
+            (Some(xid), _) => Ok(xid.to_string()),
+            (None, Some(uid)) => Err(uid.to_string()),
+            (None, None) => continue,
+        };
+        if !ids.contains(&id) {
+            ids.push(id);
+        }
+        collect_filtered_targets(target, edge, filters, ids);
+    }
+}
+
+/// Schema line for a link type's edge predicate, indexed in reverse so incoming links can
+/// be read
+fn link_predicate_schema(link_type_id: &str) -> String {
+    format!("{}: [uid] @reverse @count .\n", link_type_id.replace('-', "_").replace('.', "_"))
+}
+
+/// The edge to follow from an object for a link type: its predicate, or the reverse of it
+/// (`~predicate`) for incoming links
+fn edge_name(link_type_id: &str, outgoing: bool) -> String {
+    let predicate = link_type_id.replace('-', "_").replace('.', "_");
+    if outgoing {
+        predicate
+    } else {
+        format!("~{}", predicate)
+    }
+}
+
+/// The Dgraph operations resolving xids to UIDs, apart from the store so the batching in
+/// `resolve_uids` can be exercised without a server
+#[async_trait]
+trait UidSource: Send + Sync {
+    /// UIDs of the existing nodes among `xids`, keyed by xid, in one query
+    async fn find_uids(&self, xids: &[String]) -> Result<HashMap<String, String>, StoreError>;
+    
+    /// Create a node for each of `xids` in one mutation, returning the UIDs it assigned
+    async fn create_nodes(&self, xids: &[String]) -> Result<HashMap<String, String>, StoreError>;
+}
+
+#[async_trait]
+impl UidSource for DgraphStore {
+    async fn find_uids(&self, xids: &[String]) -> Result<HashMap<String, String>, StoreError> {
+        // A JSON string array is also a DQL list of strings
+        let query = format!(
+            "{{\n  objects(func: eq(xid, {})) {{\n    uid\n    xid\n  }}\n}}",
+            JsonValue::from(xids.to_vec())
+        );
+        
+        let mut txn = self.client.new_read_only_txn();
+        let response = txn.query(query).await
+            .map_err(|e| StoreError::ReadError(format!("Query error: {}", e)))?;
+        
+        let json: serde_json::Value = serde_json::from_slice(&response.json)
+            .map_err(|e| StoreError::ReadError(format!("Parse error: {}", e)))?;
+        
+        let mut uids = HashMap::new();
+        for node in json.get("objects").and_then(|o| o.as_array()).into_iter().flatten() {
+            if let (Some(xid), Some(uid)) = (node.get("xid").and_then(|x| x.as_str()), node.get("uid").and_then(|u| u.as_str())) {
+                uids.insert(xid.to_string(), uid.to_string());
+            }
+        }
+        Ok(uids)
+    }
+    
+    async fn create_nodes(&self, xids: &[String]) -> Result<HashMap<String, String>, StoreError> {
+        let rdf: Vec<String> = xids
+            .iter()
+            .enumerate()
+            .map(|(i, xid)| format!("_:node{} <xid> {} .", i, JsonValue::String(xid.clone())))
+            .collect();
+        
+        let mutation = Mutation {
+            set_nquads: rdf.join("\n").into_bytes(),
+            ..Default::default()
+        };
+        
+        let mut txn = self.client.new_mutated_txn();
+        let response = txn.mutate(mutation).await
+            .map_err(|e| StoreError::WriteError(format!("Mutation error: {}", e)))?;
+        txn.commit().await
+            .map_err(|e| StoreError::WriteError(format!("Commit error: {}", e)))?;
+        
+        // The response maps each blank node to the UID assigned to it
+        Ok(xids
+            .iter()
+            .enumerate()
+            .filter_map(|(i, xid)| Some((xid.clone(), response.uids.get(&format!("node{}", i))?.clone())))
+            .collect())
+    }
+}
+
+fn new_uid_cache() -> lru::LruCache<String, String> {
+    lru::LruCache::new(std::num::NonZeroUsize::new(DGRAPH_UID_CACHE_SIZE).unwrap())
+}
+
+/// UIDs for `object_ids`, keyed by ID: from `cache` where possible, otherwise with at most
+/// one lookup and one creation for the whole batch. Resolved IDs are added to `cache`.
+async fn resolve_uids(
+    source: &dyn UidSource,
+    cache: &std::sync::Mutex<lru::LruCache<String, String>>,
+    object_ids: &[String],
+) -> Result<HashMap<String, String>, StoreError> {
+    let mut uids = HashMap::new();
+    let mut missing = Vec::new();
+    {
+        let mut cache = cache.lock().unwrap();
+        for object_id in object_ids {
+            if uids.contains_key(object_id) {
+                continue;
+            }
+            match cache.get(object_id) {
+                Some(uid) => {
+                    uids.insert(object_id.clone(), uid.clone());
+                }
+                None => {
+                    // Placeholder until resolved, so repeated IDs are looked up once
+                    uids.insert(object_id.clone(), String::new());
+                    missing.push(object_id.clone());
+                }
+            }
+        }
+    }
+    if missing.is_empty() {
+        return Ok(uids);
+    }
+    
+    let mut resolved = source.find_uids(&missing).await?;
+    let to_create: Vec<String> = missing.iter().filter(|id| !resolved.contains_key(*id)).cloned().collect();
+    if !to_create.is_empty() {
+        resolved.extend(source.create_nodes(&to_create).await?);
+    }
+    
+    let mut cache = cache.lock().unwrap();
+    for object_id in missing {
+        let uid = resolved
+            .remove(&object_id)
+            .ok_or_else(|| StoreError::WriteError(format!("Failed to get or create UID for {}", object_id)))?;
+        cache.put(object_id.clone(), uid.clone());
+        uids.insert(object_id, uid);
+    }
+    Ok(uids)
+}
+
+/// N-Quads for a link's metadata node (the blank node `blank_node` of its mutation): its ID,
+/// type, creation time and the two ends of its edge
+fn link_metadata_nquads(
+    blank_node: &str,
+    link_id: &str,
+    link_type_id: &str,
+    source_uid: &str,
+    target_uid: &str,
+    created_at: &str,
+) -> String {
+    [
+        format!("_:{} <link_id> \"{}\" .", blank_node, link_id),
+        format!("_:{} <link_type_id> \"{}\" .", blank_node, link_type_id),
+        format!("_:{} <link_source> <{}> .", blank_node, source_uid),
+        format!("_:{} <link_target> <{}> .", blank_node, target_uid),
+        format!("_:{} <created_at> \"{}\" .", blank_node, created_at),
+    ]
+    .join("\n")
+}
+
+/// N-Quads deleting the edge a metadata node describes and the metadata node itself; `None`
+/// when the node is missing any of its fields
+fn link_delete_nquads(metadata: &serde_json::Value) -> Option<String> {
+    let uid = metadata.get("uid")?.as_str()?;
+    let link_type_id = metadata.get("link_type_id")?.as_str()?;
+    let source_uid = metadata.get("link_source")?.get("uid")?.as_str()?;
+    let target_uid = metadata.get("link_target")?.get("uid")?.as_str()?;
+    let predicate = link_type_id.replace('-', "_").replace('.', "_");
+    Some(format!("<{}> <{}> <{}> .\n<{}> * * .", source_uid, predicate, target_uid, uid))
+}
+
+/// A link property read back from an edge facet
+fn facet_value_to_property(value: &serde_json::Value) -> Option<ontology_engine::PropertyValue> {
+    use ontology_engine::PropertyValue;
+    match value {
+        serde_json::Value::String(s) => Some(PropertyValue::String(s.clone())),
+        serde_json::Value::Bool(b) => Some(PropertyValue::Boolean(*b)),
+        serde_json::Value::Number(n) => n
+            .as_i64()
+            .map(PropertyValue::Integer)
+            .or_else(|| n.as_f64().map(PropertyValue::Double)),
+        _ => None,
+    }
+}
+
 // Parquet store implementation using Polars
 pub struct ParquetStore {
     base_path: String,
@@ -2114,6 +3729,176 @@ impl ParquetStore {
         format!("{}/{}.parquet", self.base_path, object_type)
     }
 
+    fn partition_path(&self, object_type: &str, partition: &str) -> String {
+        format!("{}/{}/{}.parquet", self.base_path, partition, object_type)
+    }
+
+    /// Write objects to a Parquet file, replacing it
+    fn write_objects(path: &str, objects: &[IndexedObject]) -> Result<(), StoreError> {
+        if let Some(dir) = Path::new(path).parent() {
+            std::fs::create_dir_all(dir)
+                .map_err(|e| StoreError::WriteError(format!("Failed to create directory: {}", e)))?;
+        }
+
+        // 1. Convert IndexedObjects to JSON
+        let mut json_objects = Vec::new();
+        for obj in objects {
+            json_objects.push(Self::indexed_object_to_json(obj)?);
+        }
+
+        // 2. Serialize as a JSON array (polars JsonReader expects an array, not NDJSON)
+        let json_array = JsonValue::Array(json_objects);
+        let json_buffer = serde_json::to_vec(&json_array)
+            .map_err(|e| StoreError::WriteError(format!("Serialization error: {}", e)))?;
+
+        // 3. Load into Polars DataFrame
+        let cursor = Cursor::new(json_buffer);
+        let mut df = JsonReader::new(cursor)
+            .infer_schema_len(Some(1000)) // Check up to 1000 rows to determine column types
+            .finish()
+            .map_err(|e| StoreError::WriteError(format!("DataFrame creation error: {}", e)))?;
+
+        // 4. Write to Parquet
+        let file = File::create(path)
+            .map_err(|e| StoreError::WriteError(format!("File creation error: {}", e)))?;
+
+        ParquetWriter::new(file)
+            .finish(&mut df)
+            .map_err(|e| StoreError::WriteError(format!("Parquet write error: {}", e)))?;
+
+        Ok(())
+    }
+
+    /// Every object in a Parquet file written by `write_objects`; none if it does not exist
+    fn read_objects(path: &str) -> Result<Vec<IndexedObject>, StoreError> {
+        if !Path::new(path).exists() {
+            return Ok(Vec::new());
+        }
+        let file = File::open(path)
+            .map_err(|e| StoreError::ReadError(format!("File open error: {}", e)))?;
+        let df = ParquetReader::new(file)
+            .finish()
+            .map_err(|e| StoreError::ReadError(format!("Parquet read error: {}", e)))?;
+
+        let mut objects = Vec::with_capacity(df.height());
+        for row_idx in 0..df.height() {
+            let mut object = IndexedObject::new(String::new(), String::new(), PropertyMap::new());
+            for series in df.get_columns() {
+                let value = series.get(row_idx)
+                    .map_err(|e| StoreError::ReadError(format!("Column access error: {}", e)))?;
+                match (series.name(), Self::any_value_to_json(value)) {
+                    // Columns missing from a row are null; they were not properties of it
+                    (_, JsonValue::Null) => {}
+                    ("object_id", JsonValue::String(id)) => object.object_id = id,
+                    ("object_type", JsonValue::String(object_type)) => object.object_type = object_type,
+                    ("indexed_at", JsonValue::String(at)) => {
+                        if let Ok(at) = chrono::DateTime::parse_from_rfc3339(&at) {
+                            object.indexed_at = at.with_timezone(&chrono::Utc);
+                        }
+                    }
+                    (name, value) => {
+                        let value = serde_json::from_value(value)
+                            .map_err(|e| StoreError::Serialization(format!("Column '{}': {}", name, e)))?;
+                        object.properties.insert(name.to_string(), value);
+                    }
+                }
+            }
+            objects.push(object);
+        }
+        Ok(objects)
+    }
+
+    /// Polars cell as JSON, as aggregation over JSON rows expects
+    fn any_value_to_json(value: AnyValue) -> JsonValue {
+        match value {
+            AnyValue::Null => JsonValue::Null,
+            AnyValue::Boolean(b) => JsonValue::Bool(b),
+            AnyValue::String(s) => JsonValue::String(s.to_string()),
+            AnyValue::Int32(i) => json!(i),
+            AnyValue::Int64(i) => json!(i),
+            AnyValue::UInt32(i) => json!(i),
+            AnyValue::UInt64(i) => json!(i),
+            AnyValue::Float32(f) => json!(f),
+            AnyValue::Float64(f) => json!(f),
+            other => JsonValue::String(other.to_string()),
+        }
+    }
+
+    /// Polars cell as a property value; nulls stay null
+    fn any_value_to_property(value: AnyValue) -> ontology_engine::PropertyValue {
+        match value {
+            AnyValue::Null => ontology_engine::PropertyValue::Null,
+            AnyValue::Boolean(b) => ontology_engine::PropertyValue::Boolean(b),
+            AnyValue::String(s) => ontology_engine::PropertyValue::String(s.to_string()),
+            AnyValue::Int8(i) => ontology_engine::PropertyValue::Integer(i.into()),
+            AnyValue::Int16(i) => ontology_engine::PropertyValue::Integer(i.into()),
+            AnyValue::Int32(i) => ontology_engine::PropertyValue::Integer(i.into()),
+            AnyValue::Int64(i) => ontology_engine::PropertyValue::Integer(i),
+            AnyValue::UInt8(i) => ontology_engine::PropertyValue::Integer(i.into()),
+            AnyValue::UInt16(i) => ontology_engine::PropertyValue::Integer(i.into()),
+            AnyValue::UInt32(i) => ontology_engine::PropertyValue::Integer(i.into()),
+            AnyValue::UInt64(i) => ontology_engine::PropertyValue::Integer(i as i64),
+            AnyValue::Float32(f) => ontology_engine::PropertyValue::Double(f.into()),
+            AnyValue::Float64(f) => ontology_engine::PropertyValue::Double(f),
+            other => ontology_engine::PropertyValue::String(other.to_string()),
+        }
+    }
+
+    /// Row predicate for a filter, with `matches_filters` semantics: a null never matches
+    fn filter_expr(filter: &Filter) -> Result<Expr, StoreError> {
+        let literal = |value: &ontology_engine::PropertyValue| match value {
+            ontology_engine::PropertyValue::String(s) => Ok(lit(s.clone())),
+            ontology_engine::PropertyValue::Integer(i) => Ok(lit(*i)),
+            ontology_engine::PropertyValue::Double(d) => Ok(lit(*d)),
+            ontology_engine::PropertyValue::Boolean(b) => Ok(lit(*b)),
+            _ => Err(StoreError::Query(
+                format!("Unsupported filter value type for property: {}", filter.property)
+            )),
+        };
+        let column = col(&filter.property);
+        let expr = match filter.operator {
+            FilterOperator::Equals => column.eq(literal(&filter.value)?),
+            FilterOperator::NotEquals => column.neq(literal(&filter.value)?),
+            FilterOperator::GreaterThan => column.gt(literal(&filter.value)?),
+            FilterOperator::LessThan => column.lt(literal(&filter.value)?),
+            FilterOperator::GreaterThanOrEqual => column.gt_eq(literal(&filter.value)?),
+            FilterOperator::LessThanOrEqual => column.lt_eq(literal(&filter.value)?),
+            FilterOperator::In | FilterOperator::NotIn => {
+                let candidates = match &filter.value {
+                    ontology_engine::PropertyValue::Array(items) => items.as_slice(),
+                    other => std::slice::from_ref(other),
+                };
+                let mut any = lit(false);
+                for candidate in candidates {
+                    any = any.or(column.clone().eq(literal(candidate)?));
+                }
+                if filter.operator == FilterOperator::In {
+                    any
+                } else {
+                    // Keep nulls out, as `not` of a null comparison would be null anyway
+                    column.is_not_null().and(any.not())
+                }
+            }
+            _ => {
+                return Err(StoreError::Query(
+                    format!("Filter operator {:?} is not supported on columnar data", filter.operator)
+                ));
+            }
+        };
+        Ok(expr)
+    }
+
+    /// Columns an analytics query groups by or aggregates
+    fn aggregated_columns(query: &AnalyticsQuery) -> Vec<&str> {
+        let mut columns: Vec<&str> = query.group_by.iter().map(|c| c.as_str()).collect();
+        for prop in query.aggregations.iter().filter_map(Aggregation::property) {
+            if !columns.contains(&prop) {
+                columns.push(prop);
+            }
+        }
+        columns
+    }
+
     /// Convert PropertyValue to serde_json::Value
     fn property_value_to_json(value: &ontology_engine::PropertyValue) -> JsonValue {
         match value {
@@ -2153,6 +3938,47 @@ impl ParquetStore {
         }
     }
 
+    /// Aggregate a uniform sample of the filtered rows. Only the referenced columns are
+    /// collected, and only sampled rows are converted for aggregation.
+    fn sampled_analytics(
+        &self,
+        lf: LazyFrame,
+        query: &AnalyticsQuery,
+        sampling: SamplingOptions,
+    ) -> Result<AnalyticsResult, StoreError> {
+        let columns = Self::aggregated_columns(query);
+        let lf = if columns.is_empty() {
+            lf
+        } else {
+            lf.select(columns.iter().map(|c| col(c)).collect::<Vec<_>>())
+        };
+        let df = lf
+            .collect()
+            .map_err(|e| StoreError::ReadError(format!("Query execution error: {}", e)))?;
+        
+        let mut sampler = ReservoirSampler::new(sampling);
+        for row_idx in 0..df.height() {
+            sampler.offer(row_idx);
+        }
+        let population = sampler.population();
+        let mut indices = sampler.into_sample();
+        indices.sort_unstable();
+        
+        let mut rows = Vec::with_capacity(indices.len());
+        for row_idx in indices {
+            let mut row = serde_json::Map::new();
+            for series in df.get_columns() {
+                let value = series.get(row_idx)
+                    .map_err(|e| StoreError::ReadError(format!("Column access error: {}", e)))?;
+                row.insert(series.name().to_string(), Self::any_value_to_json(value));
+            }
+            rows.push(JsonValue::Object(row));
+        }
+        
+        let sample: Vec<&JsonValue> = rows.iter().collect();
+        Ok(approximate_analytics(&sample, population, &query.aggregations, &query.group_by))
+    }
+
     /// Convert IndexedObject to JSON for Polars ingestion
     fn indexed_object_to_json(obj: &IndexedObject) -> Result<JsonValue, StoreError> {
         let mut json_obj = serde_json::Map::new();
@@ -2184,39 +4010,7 @@ impl ColumnarStore for ParquetStore {
         if objects.is_empty() {
             return Ok(());
         }
-
-        // Ensure base directory exists
-        std::fs::create_dir_all(&self.base_path)
-            .map_err(|e| StoreError::WriteError(format!("Failed to create directory: {}", e)))?;
-
-        // 1. Convert IndexedObjects to JSON
-        let mut json_objects = Vec::new();
-        for obj in &objects {
-            json_objects.push(Self::indexed_object_to_json(obj)?);
-        }
-
-        // 2. Serialize as a JSON array (polars JsonReader expects an array, not NDJSON)
-        let json_array = JsonValue::Array(json_objects);
-        let json_buffer = serde_json::to_vec(&json_array)
-            .map_err(|e| StoreError::WriteError(format!("Serialization error: {}", e)))?;
-
-        // 3. Load into Polars DataFrame
-        let cursor = Cursor::new(json_buffer);
-        let mut df = JsonReader::new(cursor)
-            .infer_schema_len(Some(1000)) // Check up to 1000 rows to determine column types
-            .finish()
-            .map_err(|e| StoreError::WriteError(format!("DataFrame creation error: {}", e)))?;
-
-        // 4. Write to Parquet
-        let path = self.file_path(object_type);
-        let file = File::create(&path)
-            .map_err(|e| StoreError::WriteError(format!("File creation error: {}", e)))?;
-
-        ParquetWriter::new(file)
-            .finish(&mut df)
-            .map_err(|e| StoreError::WriteError(format!("Parquet write error: {}", e)))?;
-
-        Ok(())
+        Self::write_objects(&self.file_path(object_type), &objects)
     }
     
     async fn query_analytics(
@@ -2225,65 +4019,76 @@ impl ColumnarStore for ParquetStore {
         query: &AnalyticsQuery,
     ) -> Result<AnalyticsResult, StoreError> {
         let path = self.file_path(object_type);
-        if !Path::new(&path).exists() {
+        let paths: Vec<String> = std::iter::once(path.clone())
+            .chain(query.partitions.iter().map(|p| self.partition_path(object_type, p)))
+            .filter(|p| Path::new(p).exists())
+            .collect();
+        if paths.is_empty() {
             return Err(StoreError::ReadError(format!("File not found: {}", path)));
         }
 
         // 1. Lazy Scan (doesn't load whole file into memory)
-        let lf = LazyFrame::scan_parquet(&path, ScanArgsParquet::default())
-            .map_err(|e| StoreError::ReadError(format!("Scan error: {}", e)))?;
+        let mut frames = Vec::new();
+        for path in &paths {
+            frames.push(
+                LazyFrame::scan_parquet(path, ScanArgsParquet::default())
+                    .map_err(|e| StoreError::ReadError(format!("Scan error: {}", e)))?,
+            );
+        }
+        let lf = if frames.len() == 1 {
+            frames.remove(0)
+        } else {
+            // Partitions are written separately and may not share every column; union the
+            // ones the query reads
+            let mut columns = Self::aggregated_columns(query);
+            columns.push("object_id");
+            columns.extend(query.filters.iter().map(|f| f.property.as_str()));
+            columns.sort_unstable();
+            columns.dedup();
+            let frames: Vec<LazyFrame> = frames
+                .into_iter()
+                .map(|lf| lf.select(columns.iter().map(|c| col(c)).collect::<Vec<_>>()))
+                .collect();
+            concat(frames, UnionArgs::default())
+                .map_err(|e| StoreError::ReadError(format!("Partition union error: {}", e)))?
+        };
 
-        // 2. Apply filters if any
+        // 2. Apply filters if any. Rows without the property (or whole files without the
+        // column) never match, as in `matches_filters`
         let mut lf = lf;
+        let schema = lf.schema()
+            .map_err(|e| StoreError::ReadError(format!("Schema error: {}", e)))?;
         for filter in &query.filters {
-            // Basic filter support - can be extended
-            match filter.operator {
-                FilterOperator::Equals => {
-                    // Convert PropertyValue to Polars literal
-                    let lit = match &filter.value {
-                        ontology_engine::PropertyValue::String(s) => {
-                            lit(s.clone())
-                        }
-                        ontology_engine::PropertyValue::Integer(i) => {
-                            lit(*i)
-                        }
-                        ontology_engine::PropertyValue::Double(d) => {
-                            lit(*d)
-                        }
-                        ontology_engine::PropertyValue::Boolean(b) => {
-                            lit(*b)
-                        }
-                        _ => {
-                            return Err(StoreError::Query(
-                                format!("Unsupported filter value type for property: {}", filter.property)
-                            ));
-                        }
-                    };
-                    lf = lf.filter(col(&filter.property).eq(lit));
-                }
-                _ => {
-                    // Other filter types can be added here
-                    return Err(StoreError::Query(
-                        format!("Filter operator {:?} not yet implemented", filter.operator)
-                    ));
-                }
-            }
+            let predicate = if schema.contains(&filter.property) {
+                Self::filter_expr(filter)?
+            } else {
+                lit(false)
+            };
+            lf = lf.filter(predicate);
         }
 
         // 3. Build aggregations
         if query.aggregations.is_empty() {
             return Err(StoreError::Query("At least one aggregation is required".to_string()));
         }
+        
+        if let Some(sampling) = query.sampling {
+            return self.sampled_analytics(lf, query, sampling);
+        }
 
         let mut agg_exprs = Vec::new();
         
         for agg in &query.aggregations {
             match agg {
                 Aggregation::Count => {
-                    agg_exprs.push(lit(1i32).sum().alias("count"));
+                    agg_exprs.push(col("object_id").count().alias("count"));
                 }
                 Aggregation::Sum(prop) => {
-                    agg_exprs.push(col(prop).sum().alias(&format!("sum_{}", prop)));
+                    // Nulls are skipped; a group with no values sums to null rather than 0
+                    let sum = when(col(prop).is_not_null().sum().gt(lit(0)))
+                        .then(col(prop).sum())
+                        .otherwise(lit(NULL));
+                    agg_exprs.push(sum.alias(&format!("sum_{}", prop)));
                 }
                 Aggregation::Avg(prop) => {
                     agg_exprs.push(col(prop).mean().alias(&format!("avg_{}", prop)));
@@ -2305,7 +4110,7 @@ impl ColumnarStore for ParquetStore {
                 }
                 Aggregation::Percentile(prop, pct) => {
                     let pct_val = (*pct * 100.0) as u8;
-                    agg_exprs.push(col(prop).quantile(lit(*pct), QuantileInterpolOptions::Linear).alias(&format!("p{}_", pct_val)));
+                    agg_exprs.push(col(prop).quantile(lit(*pct), QuantileInterpolOptions::Linear).alias(&format!("p{}_{}", pct_val, prop)));
                 }
                 Aggregation::DistinctCount(prop) => {
                     agg_exprs.push(col(prop).n_unique().alias(&format!("distinct_count_{}", prop)));
@@ -2326,10 +4131,14 @@ impl ColumnarStore for ParquetStore {
             }
         }
 
-        // 4. Apply group_by if specified, otherwise aggregate globally
+        // 4. Apply group_by if specified, otherwise aggregate globally. Groups come back
+        // ordered by their keys, nulls last
         if !query.group_by.is_empty() {
             let group_cols: Vec<Expr> = query.group_by.iter().map(|s| col(s)).collect();
-            lf = lf.group_by(group_cols).agg(agg_exprs);
+            lf = lf
+                .group_by(group_cols.clone())
+                .agg(agg_exprs)
+                .sort_by_exprs(group_cols, vec![false; query.group_by.len()], true, false);
         } else {
             lf = lf.select(agg_exprs);
         }
@@ -2339,55 +4148,78 @@ impl ColumnarStore for ParquetStore {
             .collect()
             .map_err(|e| StoreError::ReadError(format!("Query execution error: {}", e)))?;
 
-        // 6. Convert DataFrame to AnalyticsResult
+        // 6. Convert DataFrame to AnalyticsResult; group keys are columns of each row
         let mut rows = Vec::new();
         let height = df.height();
         
         for row_idx in 0..height {
             let mut row_map = HashMap::new();
-            
-            for col_name in df.get_column_names() {
-                let series = df.column(col_name)
+            for series in df.get_columns() {
+                let value = series.get(row_idx)
                     .map_err(|e| StoreError::ReadError(format!("Column access error: {}", e)))?;
-                
-                // Convert Polars value to PropertyValue
-                let prop_value = match series.dtype() {
-                    DataType::String => {
-                        let str_val = series.str().unwrap().get(row_idx);
-                        ontology_engine::PropertyValue::String(
-                            str_val.map(|s| s.to_string()).unwrap_or_default()
-                        )
-                    }
-                    DataType::Int64 => {
-                        let int_val = series.i64().unwrap().get(row_idx);
-                        ontology_engine::PropertyValue::Integer(int_val.unwrap_or(0))
-                    }
-                    DataType::Float64 => {
-                        let float_val = series.f64().unwrap().get(row_idx);
-                        ontology_engine::PropertyValue::Double(float_val.unwrap_or(0.0))
-                    }
-                    DataType::Boolean => {
-                        let bool_val = series.bool().unwrap().get(row_idx);
-                        ontology_engine::PropertyValue::Boolean(bool_val.unwrap_or(false))
-                    }
-                    _ => {
-                        // Fallback to string representation
-                        let str_val = series.get(row_idx).map(|v| v.to_string());
-                        ontology_engine::PropertyValue::String(str_val.unwrap_or_default())
-                    }
-                };
-                
-                row_map.insert(col_name.to_string(), prop_value);
+                row_map.insert(series.name().to_string(), Self::any_value_to_property(value));
             }
-            
             rows.push(row_map);
         }
 
         Ok(AnalyticsResult {
             rows,
             total: height,
+            sample_fraction: None,
+            error_bounds: vec![],
         })
     }
+
+    async fn append_partition(
+        &self,
+        object_type: &str,
+        partition: &str,
+        objects: Vec<IndexedObject>,
+    ) -> Result<String, StoreError> {
+        let path = self.partition_path(object_type, partition);
+        let replaced: std::collections::HashSet<&str> = objects.iter().map(|o| o.object_id.as_str()).collect();
+        let mut stored: Vec<IndexedObject> = Self::read_objects(&path)?
+            .into_iter()
+            .filter(|o| !replaced.contains(o.object_id.as_str()))
+            .collect();
+        stored.extend(objects);
+        if !stored.is_empty() {
+            Self::write_objects(&path, &stored)?;
+        }
+        Ok(path)
+    }
+
+    async fn read_partition(
+        &self,
+        object_type: &str,
+        partition: &str,
+        filters: &[Filter],
+    ) -> Result<Vec<IndexedObject>, StoreError> {
+        let objects = Self::read_objects(&self.partition_path(object_type, partition))?;
+        Ok(objects.into_iter().filter(|o| matches_filters(&o.properties, filters)).collect())
+    }
+
+    async fn remove_from_partition(
+        &self,
+        object_type: &str,
+        partition: &str,
+        object_ids: &[String],
+    ) -> Result<usize, StoreError> {
+        let path = self.partition_path(object_type, partition);
+        let stored = Self::read_objects(&path)?;
+        let before = stored.len();
+        let kept: Vec<IndexedObject> = stored.into_iter().filter(|o| !object_ids.contains(&o.object_id)).collect();
+        let removed = before - kept.len();
+        if removed > 0 {
+            if kept.is_empty() {
+                std::fs::remove_file(&path)
+                    .map_err(|e| StoreError::WriteError(format!("File removal error: {}", e)))?;
+            } else {
+                Self::write_objects(&path, &kept)?;
+            }
+        }
+        Ok(removed)
+    }
 }
 
 #[cfg(test)]
@@ -2417,7 +4249,7 @@ mod tests {
         properties.insert("active".to_string(), PropertyValue::Boolean(true));
 
         // Index the object
-        let result = store.index_object("test_object", "test_123", &properties).await;
+        let result = store.index_object("test_object", "test_123", &properties, None).await;
         
         // Should succeed if Elasticsearch is running
         if result.is_err() {
@@ -2429,6 +4261,411 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_dgraph_link_metadata() {
+        let rdf = link_metadata_nquads("link", "l1", "works-at", "0x1", "0x2", "2024-01-01T00:00:00+00:00");
+        assert!(rdf.contains("_:link <link_id> \"l1\" ."));
+        assert!(rdf.contains("_:link <link_source> <0x1> ."));
+        assert!(rdf.contains("_:link <link_target> <0x2> ."));
+        
+        // Deleting removes exactly the described edge and the metadata node
+        let metadata = json!({
+            "uid": "0x9",
+            "link_type_id": "works-at",
+            "link_source": { "uid": "0x1" },
+            "link_target": { "uid": "0x2" },
+        });
+        assert_eq!(link_delete_nquads(&metadata).unwrap(), "<0x1> <works_at> <0x2> .\n<0x9> * * .");
+        assert!(link_delete_nquads(&json!({ "uid": "0x9" })).is_none());
+        
+        // Links read back carry the IDs and times they were created with
+        let Ok(store) = DgraphStore::new("http://localhost:9080".to_string()).await else { return };
+        let target = json!({
+            "uid": "0x2",
+            "xid": "acme",
+            "works_at|link_id": "l1",
+            "works_at|link_type_id": "works-at",
+            "works_at|created_at": "2024-01-01T00:00:00Z",
+            "works_at|since": "2020",
+            "works_at|weight": 3,
+        });
+        let link = store
+            .extract_link_from_target(&target, "alice", "works-at", true)
+            .unwrap();
+        assert_eq!(link.link_id, "l1");
+        assert_eq!(link.target_id, "acme");
+        assert_eq!(link.created_at.to_rfc3339(), "2024-01-01T00:00:00+00:00");
+        assert_eq!(link.properties.len(), 2);
+        assert_eq!(link.properties.get("weight"), Some(&PropertyValue::Integer(3)));
+        
+        // Read along the reverse edge, the queried object is the target
+        let source = json!({ "uid": "0x3", "xid": "alice", "~works_at|link_id": "l1" });
+        let link = store.extract_link_from_target(&source, "acme", "works-at", false).unwrap();
+        assert_eq!((link.source_id.as_str(), link.target_id.as_str()), ("alice", "acme"));
+        assert_eq!(link.link_id, "l1");
+    }
+    
+    #[test]
+    fn test_dgraph_filtered_traversal() {
+        // The facet filter applies to the forward edge taken at every hop
+        let query = dgraph_filtered_traversal_query("uid(0x1)", "works-at", 2, Some("gt(weight, 15)"), false);
+        assert_eq!(
+            query,
+            "{\nnode(func: uid(0x1)) {\n  works_at @facets(gt(weight, 15)) {\n    uid\n    xid\n    works_at @facets(gt(weight, 15)) {\n      uid\n      xid\n    }\n  }\n}\n}"
+        );
+        let query = dgraph_filtered_traversal_query("uid(0x1)", "works-at", 1, None, true);
+        assert!(query.contains("  works_at @facets {\n"), "{}", query);
+        
+        let filter = |operator, value| Filter {
+            property: "weight".to_string(),
+            operator,
+            value,
+            distance: None,
+            case_insensitive: false,
+        };
+        assert!(is_dgraph_facet_filter(&filter(FilterOperator::GreaterThan, PropertyValue::Integer(15))));
+        assert!(is_dgraph_facet_filter(&filter(FilterOperator::Equals, PropertyValue::String("a".to_string()))));
+        assert!(!is_dgraph_facet_filter(&filter(FilterOperator::NotEquals, PropertyValue::Integer(15))));
+        assert!(!is_dgraph_facet_filter(&filter(FilterOperator::Equals, PropertyValue::Boolean(true))));
+        
+        // Link IDs can't break out of the link metadata query
+        let query = link_metadata_query(r#"l1\" OR x"#);
+        assert!(query.contains(r#"eq(link_id, "l1\\\" OR x")"#), "{}", query);
+        
+        // Node filters can't break out of the query either
+        let hostile = Filter {
+            property: "name) { uid } q(func: has(ssn)".to_string(),
+            ..filter(FilterOperator::Equals, PropertyValue::Integer(1))
+        };
+        assert!(matches!(DgraphStore::build_dgraph_filter(&hostile), Err(StoreError::Query(_))));
+        let hostile = Filter {
+            property: "name".to_string(),
+            ..filter(FilterOperator::Equals, PropertyValue::String(r#"a\" OR x"#.to_string()))
+        };
+        assert_eq!(DgraphStore::build_dgraph_filter(&hostile).unwrap(), r#"eq(name, "a\\\" OR x")"#);
+        
+        // Facet values and names can't break out of the query
+        assert_eq!(dql_string(r#"a\" OR x"#), r#""a\\\" OR x""#);
+        assert_eq!(dql_name("start_date").unwrap(), "start_date");
+        assert!(matches!(dql_name("weight) { uid }"), Err(StoreError::Query(_))));
+        
+        // Edges failing a client-side filter are neither returned nor traversed further
+        let response = json!({
+            "uid": "0x1",
+            "works_at": [
+                { "uid": "0x2", "xid": "a", "works_at|weight": 10,
+                  "works_at": [{ "uid": "0x4", "xid": "c", "works_at|weight": 1 }] },
+                { "uid": "0x3", "works_at|weight": 20,
+                  "works_at": [{ "uid": "0x5", "xid": "d", "works_at|weight": 30 }] },
+            ],
+        });
+        let mut ids = Vec::new();
+        collect_filtered_targets(
+            &response,
+            "works_at",
+            &[filter(FilterOperator::NotEquals, PropertyValue::Integer(10))],
+            &mut ids,
+        );
+        assert_eq!(ids, vec![Err("0x3".to_string()), Ok("d".to_string())]);
+    }
+    
+    #[test]
+    fn test_dgraph_aggregation_query() {
+        let links = vec!["works-at".to_string(), "owns".to_string()];
+        let query = dgraph_aggregation_query(
+            "0x1",
+            &links,
+            2,
+            &Aggregation::Sum("head-count".to_string()),
+            Some("gt(revenue, 10)"),
+        )
+        .unwrap();
+        assert_eq!(
+            query,
+            "{\nvar(func: uid(0x1)) {\n  hop0_1 as works_at {\n    hop0_2 as works_at\n  }\n  hop1_1 as owns {\n    hop1_2 as owns\n  }\n}\n\
+             targets as var(func: uid(hop0_1, hop0_2, hop1_1, hop1_2)) @filter(NOT uid(0x1) AND (gt(revenue, 10)) AND has(head_count)) {\n  values as head_count\n}\n\
+             objects(func: uid(targets)) {\n  count(uid)\n}\n\
+             aggregate() {\n  value: sum(val(values))\n}\n}"
+        );
+        let query = dgraph_aggregation_query("0x1", &links[..1], 1, &Aggregation::Count, None).unwrap();
+        assert_eq!(
+            query,
+            "{\nvar(func: uid(0x1)) {\n  hop0_1 as works_at\n}\nobjects(func: uid(hop0_1)) @filter(NOT uid(0x1)) {\n  count(uid)\n}\n}"
+        );
+        assert!(dgraph_aggregation_query("0x1", &links, 1, &Aggregation::Median("x".to_string()), None).is_err());
+        
+        // Sums keep the property's type; averages are doubles
+        let response = json!({ "objects": [{ "count": 2 }], "aggregate": [{ "value": 15 }] });
+        let result = dgraph_aggregation_result(&response, &Aggregation::Sum("x".to_string())).unwrap();
+        assert_eq!((result.value, result.count), (PropertyValue::Integer(15), 2));
+        let response = json!({ "objects": [{ "count": 2 }], "aggregate": [{ "value": 7 }] });
+        let result = dgraph_aggregation_result(&response, &Aggregation::Avg("x".to_string())).unwrap();
+        assert_eq!(result.value, PropertyValue::Double(7.0));
+        let response = json!({ "objects": [{ "count": 3 }] });
+        let result = dgraph_aggregation_result(&response, &Aggregation::Count).unwrap();
+        assert_eq!((result.value, result.count), (PropertyValue::Integer(3), 3));
+        
+        // No node with the property is an error, not zero
+        let response = json!({ "objects": [{ "count": 0 }], "aggregate": [{}] });
+        let err = dgraph_aggregation_result(&response, &Aggregation::Sum("x".to_string())).unwrap_err();
+        assert!(matches!(err, StoreError::NoData(_)), "{:?}", err);
+        
+        let mut properties = PropertyMap::new();
+        properties.insert("head-count".to_string(), PropertyValue::Integer(5));
+        assert_eq!(node_property_nquads("0x2", &properties), "<0x2> <head_count> \"5\"^^<xs:int> .");
+    }
+    
+    /// Hands out sequential UIDs and counts the round trips asked of it
+    #[derive(Default)]
+    struct CountingUidSource {
+        existing: std::sync::Mutex<HashMap<String, String>>,
+        queries: std::sync::atomic::AtomicUsize,
+        mutations: std::sync::atomic::AtomicUsize,
+    }
+    
+    #[async_trait]
+    impl UidSource for CountingUidSource {
+        async fn find_uids(&self, xids: &[String]) -> Result<HashMap<String, String>, StoreError> {
+            self.queries.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            let existing = self.existing.lock().unwrap();
+            Ok(xids.iter().filter_map(|xid| Some((xid.clone(), existing.get(xid)?.clone()))).collect())
+        }
+        
+        async fn create_nodes(&self, xids: &[String]) -> Result<HashMap<String, String>, StoreError> {
+            self.mutations.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            let mut existing = self.existing.lock().unwrap();
+            let mut created = HashMap::new();
+            for xid in xids {
+                let uid = format!("0x{:x}", existing.len() + 1);
+                existing.insert(xid.clone(), uid.clone());
+                created.insert(xid.clone(), uid);
+            }
+            Ok(created)
+        }
+    }
+    
+    #[tokio::test]
+    async fn test_uid_resolution_is_batched_and_cached() {
+        let source = CountingUidSource::default();
+        let round_trips = || {
+            source.queries.load(std::sync::atomic::Ordering::SeqCst)
+                + source.mutations.load(std::sync::atomic::Ordering::SeqCst)
+        };
+        source.existing.lock().unwrap().insert("p0".to_string(), "0xa0".to_string());
+        
+        // 1000 links among 50 objects: resolving their endpoints one at a time takes up to
+        // two round trips each, a batch takes one lookup and one creation
+        let endpoints: Vec<String> = (0..2000).map(|i| format!("p{}", (i * 7) % 50)).collect();
+        let cache = std::sync::Mutex::new(new_uid_cache());
+        let uids = resolve_uids(&source, &cache, &endpoints).await.unwrap();
+        assert_eq!(uids.len(), 50);
+        assert_eq!(uids["p0"], "0xa0");
+        assert_eq!(round_trips(), 2);
+        
+        // Further links between the same objects hit the cache
+        let again = resolve_uids(&source, &cache, &endpoints[..10]).await.unwrap();
+        assert_eq!(again["p7"], uids["p7"]);
+        assert_eq!(round_trips(), 2);
+        
+        // Without the cache, only new objects are created
+        let cache = std::sync::Mutex::new(new_uid_cache());
+        let more: Vec<String> = vec!["p1".to_string(), "q1".to_string()];
+        let resolved = resolve_uids(&source, &cache, &more).await.unwrap();
+        assert_eq!(resolved["p1"], uids["p1"]);
+        assert_eq!((source.queries.load(std::sync::atomic::Ordering::SeqCst), round_trips()), (2, 4));
+        let resolved = resolve_uids(&source, &cache, &more).await.unwrap();
+        assert_eq!(resolved.len(), 2);
+        assert_eq!(round_trips(), 4);
+    }
+
+    #[test]
+    fn test_elasticsearch_search_body() {
+        // Building the request body does not contact Elasticsearch
+        let store = ElasticsearchStore::new("http://localhost:9200".to_string()).unwrap();
+        let filter = |property: &str, operator, value| Filter {
+            property: property.to_string(),
+            operator,
+            value,
+            distance: None,
+            case_insensitive: false,
+        };
+
+        let explained = store.explain_search("plant", &SearchQuery {
+            filters: vec![],
+            sort: vec![],
+            limit: None,
+            offset: None,
+            search_after: None,
+        }).unwrap();
+        assert_eq!(explained["body"]["query"], json!({ "match_all": {} }));
+
+        let query = SearchQuery {
+            filters: vec![
+                filter("state", FilterOperator::Equals, PropertyValue::String("NJ".to_string())),
+                filter("commissioned", FilterOperator::GreaterThanOrEqual, PropertyValue::Date("2015-01-01".to_string())),
+                filter("name", FilterOperator::Contains, PropertyValue::String("a*b?".to_string())),
+                filter("owner", FilterOperator::NotIn, PropertyValue::Array(vec![
+                    PropertyValue::ObjectReference("u1".to_string()),
+                ])),
+            ],
+            sort: vec![SortOption { property: "year".to_string(), ascending: false, keyword_subfield: false }],
+            limit: Some(10),
+            offset: Some(20),
+            search_after: None,
+        };
+        let body = &store.explain_search("plant", &query).unwrap()["body"];
+        assert_eq!(body["query"], json!({
+            "bool": {
+                "must": [
+                    { "term": { "state": "NJ" } },
+                    { "range": { "commissioned": { "gte": "2015-01-01" } } },
+                    { "wildcard": { "name": "*a\\*b\\?*" } },
+                ],
+                "must_not": [{ "terms": { "owner": ["u1"] } }],
+            }
+        }));
+        assert_eq!(body["sort"], json!([{ "year": { "order": "desc", "missing": "_last" } }]));
+        assert_eq!(body["size"], 10);
+        assert_eq!(body["from"], 20);
+        assert!(body.get("search_after").is_none());
+
+        let insensitive = Filter {
+            case_insensitive: true,
+            ..filter("name", FilterOperator::StartsWith, PropertyValue::String("Acme".to_string()))
+        };
+        let body = store.build_query_body(Some(std::slice::from_ref(&insensitive))).unwrap();
+        assert_eq!(
+            body["query"]["bool"]["must"][0],
+            json!({ "wildcard": { "name": { "value": "Acme*", "case_insensitive": true } } })
+        );
+
+        let query = SearchQuery {
+            offset: None,
+            search_after: Some(vec![json!(2015), json!("p-17")]),
+            ..query
+        };
+        let body = &store.explain_search("plant", &query).unwrap()["body"];
+        assert_eq!(body["search_after"], json!([2015, "p-17"]));
+        assert!(body.get("from").is_none());
+    }
+
+    #[test]
+    fn test_elasticsearch_spatial_clauses() {
+        let store = ElasticsearchStore::new("http://localhost:9200".to_string()).unwrap();
+        let tract = json!({ "type": "Polygon", "coordinates": [[[-71.1, 42.35], [-71.09, 42.35], [-71.09, 42.36], [-71.1, 42.35]]] });
+        let school = json!({ "type": "Point", "coordinates": [-71.095, 42.355] });
+        let filter = |operator, geometry: &JsonValue, distance| Filter {
+            property: "boundary".to_string(),
+            operator,
+            value: PropertyValue::GeoJSON(geometry.to_string()),
+            distance,
+            case_insensitive: false,
+        };
+        let clause = |filter: Filter| store.build_query_clause(&filter);
+
+        for (operator, relation) in [
+            (FilterOperator::ContainsGeometry, "contains"),
+            (FilterOperator::Intersects, "intersects"),
+            (FilterOperator::Within, "within"),
+        ] {
+            assert_eq!(
+                clause(filter(operator, &tract, None)).unwrap(),
+                json!({ "geo_shape": { "boundary": { "shape": tract, "relation": relation } } })
+            );
+        }
+        assert_eq!(
+            clause(filter(FilterOperator::WithinDistance, &school, Some(1500.0))).unwrap(),
+            json!({ "geo_distance": { "distance": "1500m", "boundary": [-71.095, 42.355] } })
+        );
+        assert!(matches!(
+            clause(filter(FilterOperator::WithinDistance, &tract, Some(1500.0))),
+            Err(StoreError::Validation(message)) if message.contains("Point")
+        ));
+        assert!(clause(filter(FilterOperator::WithinDistance, &school, None)).is_err());
+
+        // Geometries are indexed as objects for `geo_shape` and read back as GeoJSON
+        let mut properties = PropertyMap::new();
+        properties.insert("boundary".to_string(), PropertyValue::GeoJSON(tract.to_string()));
+        let document = object_document(&properties).unwrap();
+        assert_eq!(document["boundary"], tract);
+        assert_eq!(
+            document_property_value(&document["boundary"]).unwrap(),
+            PropertyValue::GeoJSON(tract.to_string())
+        );
+        assert_eq!(
+            document_property_value(&json!({ "type": "residential" })).unwrap(),
+            serde_json::from_value::<PropertyValue>(json!({ "type": "residential" })).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_mget_content_hashes_lists_found_documents() {
+        let response = json!({
+            "docs": [
+                { "_id": "a", "found": true, "_source": { "_content_hash": "h1" } },
+                { "_id": "b", "found": false },
+                { "_id": "c", "found": true, "_source": {} },
+            ],
+        });
+        let hashes = mget_content_hashes(&response);
+        assert_eq!(hashes, HashMap::from([("a".to_string(), Some("h1".to_string())), ("c".to_string(), None)]));
+    }
+
+    #[test]
+    fn test_mget_objects_reads_found_documents() {
+        let response = json!({
+            "docs": [
+                { "_id": "a", "_version": 3, "found": true, "_source": { "object_id": "a", "name": "Alpha" } },
+                { "_id": "b", "found": false },
+            ],
+        });
+        let objects = mget_objects("plant", &response).unwrap();
+        assert_eq!(objects.len(), 1);
+        assert_eq!(objects["a"].revision, 3);
+        assert_eq!(objects["a"].properties.get("name"), Some(&PropertyValue::String("Alpha".to_string())));
+        assert!(!objects["a"].properties.contains_key("object_id"));
+    }
+
+    #[test]
+    fn test_bulk_failures_lists_rejected_objects() {
+        let batch: Vec<IndexedObject> = ["a", "b", "c"]
+            .iter()
+            .map(|id| IndexedObject {
+                object_type: "plant".to_string(),
+                object_id: id.to_string(),
+                properties: PropertyMap::new(),
+                indexed_at: chrono::Utc::now(),
+                source_last_modified: None,
+                refresh_frequency: None,
+                next_refresh: None,
+                refresh_status: RefreshStatus::UpToDate,
+                revision: 0,
+            })
+            .collect();
+        let response = json!({
+            "errors": true,
+            "items": [
+                { "index": { "_id": "a", "status": 201 } },
+                { "index": { "_id": "b", "status": 400, "error": {
+                    "type": "mapper_parsing_exception",
+                    "reason": "failed to parse field [year]",
+                } } },
+                { "index": { "_id": "c", "status": 201 } },
+            ],
+        });
+        let failures = bulk_failures(&response, &batch);
+        assert_eq!(failures, vec![BulkItemFailure {
+            object_type: "plant".to_string(),
+            object_id: "b".to_string(),
+            reason: "mapper_parsing_exception: failed to parse field [year]".to_string(),
+        }]);
+        assert_eq!(
+            StoreError::BulkIndex(failures).to_string(),
+            "Bulk indexing failed for plant:b (mapper_parsing_exception: failed to parse field [year])"
+        );
+        assert!(bulk_failures(&json!({ "errors": false, "items": [] }), &batch).is_empty());
+    }
+
     #[tokio::test]
     async fn test_parquet_store_flow() {
         use std::fs;
@@ -2458,6 +4695,7 @@ mod tests {
             refresh_frequency: None,
             next_refresh: None,
             refresh_status: RefreshStatus::UpToDate,
+            revision: 0,
         });
 
         let mut props2 = PropertyMap::new();
@@ -2474,6 +4712,7 @@ mod tests {
             refresh_frequency: None,
             next_refresh: None,
             refresh_status: RefreshStatus::UpToDate,
+            revision: 0,
         });
 
         let mut props3 = PropertyMap::new();
@@ -2489,6 +4728,7 @@ mod tests {
             refresh_frequency: None,
             next_refresh: None,
             refresh_status: RefreshStatus::UpToDate,
+            revision: 0,
         });
 
         // 3. Write batch
@@ -2499,6 +4739,8 @@ mod tests {
             aggregations: vec![Aggregation::Avg("score".to_string())],
             filters: vec![],
             group_by: vec![],
+            sampling: None,
+            partitions: vec![],
         };
         
         let result = store.query_analytics("metrics", &query).await.expect("Query failed");
@@ -2522,6 +4764,8 @@ mod tests {
             aggregations: vec![Aggregation::Avg("score".to_string())],
             filters: vec![],
             group_by: vec!["category".to_string()],
+            sampling: None,
+            partitions: vec![],
         };
         
         let group_result = store.query_analytics("metrics", &group_query).await.expect("Group query failed");
@@ -2533,5 +4777,148 @@ mod tests {
         // Clean up
         let _ = fs::remove_dir_all(test_dir);
     }
-}
 
+    #[tokio::test]
+    async fn test_parquet_store_partitions() {
+        use std::fs;
+
+        let test_dir = "./test_data_parquet_partitions";
+        let _ = fs::remove_dir_all(test_dir);
+        let store = ParquetStore::new(test_dir.to_string());
+        let estimate = |id: &str, year: i64, population: i64| {
+            let mut properties = PropertyMap::new();
+            properties.insert("year".to_string(), PropertyValue::Integer(year));
+            properties.insert("population".to_string(), PropertyValue::Integer(population));
+            IndexedObject::new("estimate".to_string(), id.to_string(), properties)
+        };
+
+        // Partitions are kept apart from the main data
+        store.write_batch("estimate", vec![estimate("e3", 2024, 400)]).await.expect("Write failed");
+        let location = store
+            .append_partition("estimate", "archived", vec![estimate("e1", 2010, 100), estimate("e2", 2012, 200)])
+            .await
+            .expect("Append failed");
+        assert!(Path::new(&location).exists());
+
+        let filters = [Filter {
+            property: "year".to_string(),
+            operator: FilterOperator::Equals,
+            value: PropertyValue::Integer(2010),
+            distance: None,
+            case_insensitive: false,
+        }];
+        let archived = store.read_partition("estimate", "archived", &filters).await.expect("Read failed");
+        assert_eq!(archived.len(), 1);
+        assert_eq!(archived[0].object_id, "e1");
+        assert_eq!(archived[0].properties.get("population"), Some(&PropertyValue::Integer(100)));
+
+        // Analytics include a partition only when asked
+        let sum = |partitions: Vec<String>| AnalyticsQuery {
+            aggregations: vec![Aggregation::Sum("population".to_string())],
+            filters: vec![],
+            group_by: vec![],
+            sampling: None,
+            partitions,
+        };
+        let live = store.query_analytics("estimate", &sum(vec![])).await.expect("Query failed");
+        assert_eq!(live.rows[0].get("sum_population"), Some(&PropertyValue::Integer(400)));
+        let all = store.query_analytics("estimate", &sum(vec!["archived".to_string()])).await.expect("Query failed");
+        assert_eq!(all.rows[0].get("sum_population"), Some(&PropertyValue::Integer(700)));
+
+        let removed = store.remove_from_partition("estimate", "archived", &["e1".to_string()]).await.unwrap();
+        assert_eq!(removed, 1);
+        let remaining = store.read_partition("estimate", "archived", &[]).await.unwrap();
+        assert_eq!(remaining.iter().map(|o| o.object_id.as_str()).collect::<Vec<_>>(), vec!["e2"]);
+
+        let _ = fs::remove_dir_all(test_dir);
+    }
+
+    #[tokio::test]
+    async fn test_parquet_store_grouped_analytics() {
+        use std::fs;
+
+        let test_dir = "./test_data_parquet_grouped";
+        let _ = fs::remove_dir_all(test_dir);
+        let store = ParquetStore::new(test_dir.to_string());
+        let sale = |id: &str, region: Option<&str>, year: i64, revenue: Option<i64>| {
+            let mut properties = PropertyMap::new();
+            if let Some(region) = region {
+                properties.insert("region".to_string(), PropertyValue::String(region.to_string()));
+            }
+            properties.insert("year".to_string(), PropertyValue::Integer(year));
+            properties.insert("revenue".to_string(), revenue.map(PropertyValue::Integer).unwrap_or(PropertyValue::Null));
+            IndexedObject::new("sale".to_string(), id.to_string(), properties)
+        };
+        store
+            .write_batch("sale", vec![
+                sale("s1", Some("east"), 2023, Some(10)),
+                sale("s2", Some("east"), 2023, Some(30)),
+                sale("s3", Some("east"), 2023, None),
+                sale("s4", Some("west"), 2023, Some(5)),
+                sale("s5", Some("east"), 2024, None),
+                sale("s6", None, 2024, Some(7)),
+            ])
+            .await
+            .expect("Write failed");
+
+        let query = |filters: Vec<Filter>| AnalyticsQuery {
+            aggregations: vec![
+                Aggregation::Count,
+                Aggregation::Sum("revenue".to_string()),
+                Aggregation::Avg("revenue".to_string()),
+                Aggregation::Max("revenue".to_string()),
+            ],
+            filters,
+            group_by: vec!["region".to_string(), "year".to_string()],
+            sampling: None,
+            partitions: vec![],
+        };
+        let result = store.query_analytics("sale", &query(vec![])).await.expect("Query failed");
+
+        // One row per group, keyed by its columns, with nulls skipped by the aggregates
+        assert_eq!(result.total, 4);
+        let row = |i: usize, column: &str| result.rows[i].get(column).cloned().unwrap();
+        let keys: Vec<(PropertyValue, PropertyValue)> = (0..4).map(|i| (row(i, "region"), row(i, "year"))).collect();
+        assert_eq!(keys, vec![
+            (PropertyValue::String("east".to_string()), PropertyValue::Integer(2023)),
+            (PropertyValue::String("east".to_string()), PropertyValue::Integer(2024)),
+            (PropertyValue::String("west".to_string()), PropertyValue::Integer(2023)),
+            (PropertyValue::Null, PropertyValue::Integer(2024)),
+        ]);
+        assert_eq!(row(0, "count"), PropertyValue::Integer(3));
+        assert_eq!(row(0, "sum_revenue"), PropertyValue::Integer(40));
+        assert_eq!(row(0, "avg_revenue"), PropertyValue::Double(20.0));
+        assert_eq!(row(0, "max_revenue"), PropertyValue::Integer(30));
+
+        // A group without any values aggregates to null, not zero
+        assert_eq!(row(1, "count"), PropertyValue::Integer(1));
+        assert_eq!(row(1, "sum_revenue"), PropertyValue::Null);
+        assert_eq!(row(1, "avg_revenue"), PropertyValue::Null);
+
+        // Filters apply before grouping; rows without the property never match
+        let filter = |property: &str, operator, value| Filter {
+            property: property.to_string(),
+            operator,
+            value,
+            distance: None,
+            case_insensitive: false,
+        };
+        let filtered = store
+            .query_analytics("sale", &query(vec![
+                filter("revenue", FilterOperator::GreaterThanOrEqual, PropertyValue::Integer(7)),
+                filter("region", FilterOperator::NotIn, PropertyValue::Array(vec![PropertyValue::String("west".to_string())])),
+            ]))
+            .await
+            .expect("Query failed");
+        assert_eq!(filtered.total, 1);
+        assert_eq!(filtered.rows[0].get("count"), Some(&PropertyValue::Integer(2)));
+        assert_eq!(filtered.rows[0].get("sum_revenue"), Some(&PropertyValue::Integer(40)));
+        let unknown = store
+            .query_analytics("sale", &query(vec![filter("missing", FilterOperator::Equals, PropertyValue::Integer(1))]))
+            .await
+            .expect("Query failed");
+        assert_eq!(unknown.total, 0);
+
+        let _ = fs::remove_dir_all(test_dir);
+    }
+}
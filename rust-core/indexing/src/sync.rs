@@ -1,16 +1,69 @@
-use crate::store::{StoreBackend, IndexedObject, StoreError};
-use ontology_engine::PropertyMap;
+use crate::store::{GraphLink, GraphStore, LinkDirection, LinkQuery, NewLink, SearchStore, StoreBackend, IndexedObject, StoreError};
+use chrono::Utc;
+use ontology_engine::{CascadeDeleteBehavior, ComputedPropertyMaterializer, ObjectRef, Ontology, OntologyHandle, PropertyMap, PropertyValue, CONTENT_HASH_PROPERTY};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::PathBuf;
 use uuid::Uuid;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use tokio::sync::mpsc;
+use tokio::sync::{mpsc, Mutex};
+
+/// Default number of concurrent hydration tasks of a pipelined sync
+pub const DEFAULT_SYNC_CONCURRENCY: usize = 4;
+
+/// Default number of objects per bulk write of a pipelined sync
+pub const DEFAULT_SYNC_BATCH_SIZE: usize = 500;
 
 /// Sync service that maintains consistency across search, graph, and columnar stores
 pub struct SyncService {
     backend: Arc<StoreBackend>,
+    ontology: Option<OntologyHandle>,
+    event_sink: Option<SyncEventSink>,
+    progress: Option<SyncProgressCallback>,
+    concurrency: usize,
+    batch_size: usize,
+    fail_fast: bool,
     event_tx: mpsc::Sender<SyncEvent>,
     event_rx: Option<mpsc::Receiver<SyncEvent>>,
 }
 
+/// Receives the object events of every bulk sync write
+pub type SyncEventSink = Arc<dyn Fn(&SyncEvent) + Send + Sync>;
+
+/// Receives the progress of a pipelined sync after each batch
+pub type SyncProgressCallback = Arc<dyn Fn(SyncProgress) + Send + Sync>;
+
+/// How far a pipelined sync has got
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SyncProgress {
+    /// Objects through the pipeline, whether written, unchanged or failed
+    pub processed: usize,
+    pub failed: usize,
+    /// Objects in the sync, if the caller knows
+    pub total: Option<usize>,
+}
+
+impl SyncProgress {
+    /// Percent complete, if the total is known
+    pub fn percent(&self) -> Option<f64> {
+        self.total.map(|total| {
+            if total == 0 {
+                100.0
+            } else {
+                (self.processed as f64 / total as f64 * 100.0).min(100.0)
+            }
+        })
+    }
+}
+
+/// Objects a pipelined sync could not write, and why
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyncFailure {
+    pub objects: Vec<ObjectRef>,
+    pub error: String,
+}
+
 /// Events that trigger sync operations
 #[derive(Debug, Clone)]
 pub enum SyncEvent {
@@ -45,11 +98,66 @@ impl SyncService {
         let (tx, rx) = mpsc::channel(1000);
         Self {
             backend,
+            ontology: None,
+            event_sink: None,
+            progress: None,
+            concurrency: DEFAULT_SYNC_CONCURRENCY,
+            batch_size: DEFAULT_SYNC_BATCH_SIZE,
+            fail_fast: false,
             event_tx: tx,
             event_rx: Some(rx),
         }
     }
     
+    /// Materialize OnWrite/Both computed properties of the ontology's object types during sync
+    pub fn with_ontology(mut self, ontology: OntologyHandle) -> Self {
+        self.ontology = Some(ontology);
+        self
+    }
+    
+    /// Where bulk syncs report the objects they create, update and delete
+    pub fn with_event_sink(mut self, sink: SyncEventSink) -> Self {
+        self.event_sink = Some(sink);
+        self
+    }
+    
+    /// Where pipelined syncs report their progress
+    pub fn with_progress(mut self, progress: SyncProgressCallback) -> Self {
+        self.progress = Some(progress);
+        self
+    }
+    
+    /// Number of batches hydrated at once by a pipelined sync
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+    
+    /// Number of objects per bulk write of a pipelined sync
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+    
+    /// Stop a pipelined sync at the first batch that fails instead of reporting the failures
+    /// at the end
+    pub fn with_fail_fast(mut self, fail_fast: bool) -> Self {
+        self.fail_fast = fail_fast;
+        self
+    }
+    
+    /// Compute write-time computed properties before the object reaches the stores
+    fn materialize(ontology: Option<&Ontology>, object_type: &str, properties: PropertyMap) -> PropertyMap {
+        let Some(object_type_def) = ontology.and_then(|o| o.get_object_type(object_type)) else {
+            return properties;
+        };
+        let mut properties = properties;
+        for (property, error) in ComputedPropertyMaterializer::materialize(object_type_def, &mut properties, Utc::now()) {
+            tracing::error!("Failed to materialize computed property '{}' on '{}': {}", property, object_type, error);
+        }
+        properties
+    }
+    
     /// Get the event sender for external components
     pub fn event_sender(&self) -> mpsc::Sender<SyncEvent> {
         self.event_tx.clone()
@@ -61,11 +169,14 @@ impl SyncService {
             .ok_or_else(|| StoreError::Unknown("Sync service already started".to_string()))?;
         
         let backend = Arc::clone(&self.backend);
+        let ontology = self.ontology.clone();
         
         tokio::spawn(async move {
             while let Some(event) = rx.recv().await {
-                if let Err(e) = Self::handle_event(&backend, event).await {
-                    eprintln!("Error handling sync event: {}", e);
+                // Each event sees the ontology snapshot that is live when it is handled
+                let snapshot = ontology.as_ref().map(|handle| handle.load());
+                if let Err(e) = Self::handle_event(&backend, snapshot.as_deref(), event).await {
+                    tracing::error!("Error handling sync event: {}", e);
                     // In production, might want to retry or queue for later
                 }
             }
@@ -77,13 +188,16 @@ impl SyncService {
     /// Handle a sync event and update all stores
     async fn handle_event(
         backend: &StoreBackend,
+        ontology: Option<&Ontology>,
         event: SyncEvent,
     ) -> Result<(), StoreError> {
         match event {
             SyncEvent::ObjectCreated { object_type, object_id, properties } => {
+                let properties = Self::materialize(ontology, &object_type, properties);
+                
                 // Update search index
                 backend.search_store()
-                    .index_object(&object_type, &object_id, &properties)
+                    .index_object(&object_type, &object_id, &properties, None)
                     .await?;
                 
                 // Write to columnar store (in batch, but for now individual)
@@ -99,9 +213,11 @@ impl SyncService {
                 Ok(())
             }
             SyncEvent::ObjectUpdated { object_type, object_id, properties } => {
+                let properties = Self::materialize(ontology, &object_type, properties);
+                
                 // Update search index
                 backend.search_store()
-                    .index_object(&object_type, &object_id, &properties)
+                    .index_object(&object_type, &object_id, &properties, None)
                     .await?;
                 
                 // Update columnar store
@@ -153,6 +269,9 @@ impl SyncService {
         object_id: &str,
         properties: &PropertyMap,
     ) -> Result<(), StoreError> {
+        let snapshot = self.ontology.as_ref().map(|handle| handle.load());
+        let properties = Self::materialize(snapshot.as_deref(), object_type, properties.clone());
+        
         // Create indexed object
         let indexed_obj = IndexedObject::new(
             object_type.to_string(),
@@ -162,7 +281,7 @@ impl SyncService {
         
         // Update search index
         self.backend.search_store()
-            .index_object(object_type, object_id, properties)
+            .index_object(object_type, object_id, &properties, None)
             .await?;
         
         // Update columnar store
@@ -179,6 +298,280 @@ impl SyncService {
         Ok(())
     }
     
+    /// Index a batch of objects in every store, skipping those whose content has not changed
+    /// since they were last written (see [`plan_upsert`]). `force` writes every object.
+    /// Returns the plan that was carried out, so callers can record created and updated
+    /// events for exactly the objects written.
+    pub async fn upsert_objects(
+        &self,
+        objects: Vec<IndexedObject>,
+        force: bool,
+    ) -> Result<UpsertPlan, StoreError> {
+        let plan = plan_upsert(self.backend.search_store(), objects, force).await?;
+        let snapshot = self.ontology.as_ref().map(|handle| handle.load());
+        let mut by_type: HashMap<String, Vec<IndexedObject>> = HashMap::new();
+        for object in plan.created.iter().chain(&plan.updated) {
+            let mut object = object.clone();
+            object.properties = Self::materialize(snapshot.as_deref(), &object.object_type, object.properties);
+            by_type.entry(object.object_type.clone()).or_default().push(object);
+        }
+        for (object_type, objects) in by_type {
+            self.backend.search_store().bulk_index(objects.clone()).await?;
+            self.backend.columnar_store().write_batch(&object_type, objects).await?;
+        }
+        Ok(plan)
+    }
+    
+    /// Sync a stream of objects through a pipeline: batches are hydrated by concurrent tasks
+    /// (compared with the stored content hashes and given their write-time computed
+    /// properties), then bulk written to the search and columnar stores as they come.
+    /// Bounded channels between the stages hold back the input when writes fall behind, so
+    /// memory does not grow with the number of objects. Objects whose content is unchanged
+    /// are skipped. A failing batch is reported in the result's `failures` and the rest are
+    /// still synced, unless the service is fail-fast. `total` is only used for progress.
+    pub async fn sync_objects<I>(&self, objects: I, total: Option<usize>) -> Result<SyncReport, StoreError>
+    where
+        I: IntoIterator<Item = IndexedObject>,
+        I::IntoIter: Send,
+    {
+        let snapshot = self.ontology.as_ref().map(|handle| handle.load());
+        let (input_tx, input_rx) = mpsc::channel::<Vec<IndexedObject>>(self.concurrency);
+        let (hydrated_tx, mut hydrated_rx) = mpsc::channel(self.concurrency);
+        let input_rx = Arc::new(Mutex::new(input_rx));
+        let aborted = AtomicBool::new(false);
+        
+        let mut workers = Vec::with_capacity(self.concurrency);
+        for _ in 0..self.concurrency {
+            let backend = self.backend.clone();
+            let snapshot = snapshot.clone();
+            let input_rx = input_rx.clone();
+            let hydrated_tx = hydrated_tx.clone();
+            workers.push(tokio::spawn(async move {
+                loop {
+                    let Some(batch) = input_rx.lock().await.recv().await else {
+                        break;
+                    };
+                    let refs: Vec<ObjectRef> = batch.iter().map(|o| ObjectRef::new(&o.object_type, &o.object_id)).collect();
+                    let hydrated = match plan_upsert(backend.search_store(), batch, false).await {
+                        Ok(mut plan) => {
+                            for object in plan.created.iter_mut().chain(plan.updated.iter_mut()) {
+                                let properties = std::mem::take(&mut object.properties);
+                                object.properties = Self::materialize(snapshot.as_deref(), &object.object_type, properties);
+                            }
+                            Ok(plan)
+                        }
+                        Err(e) => Err((refs, e)),
+                    };
+                    if hydrated_tx.send(hydrated).await.is_err() {
+                        break;
+                    }
+                }
+            }));
+        }
+        drop((input_rx, hydrated_tx));
+        
+        let batch_size = self.batch_size;
+        let feed = async {
+            let mut objects = objects.into_iter();
+            while !aborted.load(Ordering::Relaxed) {
+                let batch: Vec<IndexedObject> = objects.by_ref().take(batch_size).collect();
+                if batch.is_empty() || input_tx.send(batch).await.is_err() {
+                    break;
+                }
+            }
+            drop(input_tx);
+        };
+        // Owns the receiving end, so stopping early also stops the hydration tasks and the feed
+        let write = async {
+            let mut hydrated_rx = hydrated_rx;
+            let mut report = SyncReport::default();
+            let mut progress = SyncProgress { processed: 0, failed: 0, total };
+            while let Some(hydrated) = hydrated_rx.recv().await {
+                let failure = match hydrated {
+                    Ok(plan) => {
+                        progress.processed += plan.writes() + plan.unchanged;
+                        report.unchanged += plan.unchanged;
+                        self.write_plan(plan, &mut report).await
+                    }
+                    Err((refs, e)) => {
+                        progress.processed += refs.len();
+                        Some((refs, e))
+                    }
+                };
+                if let Some((refs, e)) = failure {
+                    progress.failed += refs.len();
+                    if self.fail_fast {
+                        aborted.store(true, Ordering::Relaxed);
+                        return Err(e);
+                    }
+                    report.failures.push(SyncFailure { objects: refs, error: e.to_string() });
+                }
+                if let Some(callback) = &self.progress {
+                    callback(progress);
+                }
+            }
+            Ok(report)
+        };
+        let ((), report) = tokio::join!(feed, write);
+        for worker in workers {
+            worker.await.map_err(|e| StoreError::Unknown(format!("Sync hydration task failed: {}", e)))?;
+        }
+        report
+    }
+    
+    /// Write the new and changed objects of a hydrated batch to the search and columnar
+    /// stores, counting them in `report` and reporting them to the event sink. Objects a
+    /// bulk write rejected are returned as the batch's failure; the rest are still written.
+    async fn write_plan(&self, plan: UpsertPlan, report: &mut SyncReport) -> Option<(Vec<ObjectRef>, StoreError)> {
+        let existed: HashSet<ObjectRef> = plan.updated.iter().map(|o| ObjectRef::new(&o.object_type, &o.object_id)).collect();
+        let mut objects: Vec<IndexedObject> = plan.created.into_iter().chain(plan.updated).collect();
+        if objects.is_empty() {
+            return None;
+        }
+        let mut failure = None;
+        match self.backend.search_store().bulk_index(objects.clone()).await {
+            Ok(()) => {}
+            Err(StoreError::BulkIndex(items)) => {
+                let rejected: HashSet<ObjectRef> =
+                    items.iter().map(|item| ObjectRef::new(&item.object_type, &item.object_id)).collect();
+                objects.retain(|o| !rejected.contains(&ObjectRef::new(&o.object_type, &o.object_id)));
+                failure = Some((rejected.into_iter().collect(), StoreError::BulkIndex(items)));
+            }
+            Err(e) => {
+                let refs = objects.iter().map(|o| ObjectRef::new(&o.object_type, &o.object_id)).collect();
+                return Some((refs, e));
+            }
+        }
+        let mut by_type: HashMap<String, Vec<IndexedObject>> = HashMap::new();
+        for object in &objects {
+            by_type.entry(object.object_type.clone()).or_default().push(object.clone());
+        }
+        for (object_type, batch) in by_type {
+            if let Err(e) = self.backend.columnar_store().write_batch(&object_type, batch).await {
+                let refs = objects.iter().map(|o| ObjectRef::new(&o.object_type, &o.object_id)).collect();
+                return Some((refs, e));
+            }
+        }
+        
+        for object in objects {
+            let updated = existed.contains(&ObjectRef::new(&object.object_type, &object.object_id));
+            if updated {
+                report.updated += 1;
+            } else {
+                report.created += 1;
+            }
+            if let Some(sink) = &self.event_sink {
+                let (object_type, object_id, properties) = (object.object_type, object.object_id, object.properties);
+                sink(&if updated {
+                    SyncEvent::ObjectUpdated { object_type, object_id, properties }
+                } else {
+                    SyncEvent::ObjectCreated { object_type, object_id, properties }
+                });
+            }
+        }
+        failure
+    }
+    
+    /// Bring an object type in line with a full snapshot of its source objects, writing only
+    /// what changed since the run recorded in `state`. Objects whose content hash differs
+    /// from the recorded one go through the [`sync_objects`](Self::sync_objects) pipeline,
+    /// and objects of the previous run missing from the snapshot are deleted from the search
+    /// store and reported to the event sink. `state` is saved once the stores are up to date;
+    /// objects that failed to write keep their previous hash so the next run retries them. A
+    /// dry run counts the changes from `state` alone without writing anything.
+    pub async fn sync_incremental(
+        &self,
+        object_type: &str,
+        objects: Vec<IndexedObject>,
+        state: &mut SyncState,
+        dry_run: bool,
+    ) -> Result<SyncReport, StoreError> {
+        let previous = state.object_types.get(object_type).cloned().unwrap_or_default();
+        let mut current = BTreeMap::new();
+        let mut changed = Vec::new();
+        let mut unchanged = 0;
+        for object in objects {
+            if object.object_type != object_type {
+                return Err(StoreError::WriteError(format!(
+                    "Cannot sync {}:{} as '{}'",
+                    object.object_type, object.object_id, object_type
+                )));
+            }
+            let hash = object.properties.content_hash();
+            if current.insert(object.object_id.clone(), hash.clone()).is_some() {
+                return Err(StoreError::WriteError(format!("Duplicate object {}:{} in snapshot", object_type, object.object_id)));
+            }
+            if previous.get(&object.object_id) == Some(&hash) {
+                unchanged += 1;
+            } else {
+                changed.push(object);
+            }
+        }
+        let deleted: Vec<&String> = previous.keys().filter(|id| !current.contains_key(*id)).collect();
+        if dry_run {
+            let updated = changed.iter().filter(|o| previous.contains_key(&o.object_id)).count();
+            return Ok(SyncReport {
+                created: changed.len() - updated,
+                updated,
+                deleted: deleted.len(),
+                unchanged,
+                dry_run,
+                failures: Vec::new(),
+            });
+        }
+        
+        let total = changed.len();
+        let mut report = self.sync_objects(changed, Some(total)).await?;
+        report.unchanged += unchanged;
+        for failure in &report.failures {
+            for object in &failure.objects {
+                match previous.get(&object.object_id) {
+                    Some(hash) => current.insert(object.object_id.clone(), hash.clone()),
+                    None => current.remove(&object.object_id),
+                };
+            }
+        }
+        for object_id in deleted {
+            match self.backend.search_store().delete_object(object_type, object_id).await {
+                Ok(()) | Err(StoreError::NotFound(_)) => {}
+                Err(e) => return Err(e),
+            }
+            report.deleted += 1;
+            if let Some(sink) = &self.event_sink {
+                sink(&SyncEvent::ObjectDeleted {
+                    object_type: object_type.to_string(),
+                    object_id: object_id.to_string(),
+                });
+            }
+        }
+        state.object_types.insert(object_type.to_string(), current);
+        state.save()?;
+        Ok(report)
+    }
+    
+    /// Read-modify-write an object in every store under optimistic concurrency (see
+    /// [`read_modify_write`]). Returns the object as written, including its new revision.
+    pub async fn update_object<F>(
+        &self,
+        object_type: &str,
+        object_id: &str,
+        update: F,
+    ) -> Result<IndexedObject, StoreError>
+    where
+        F: FnOnce(Option<&IndexedObject>) -> Result<PropertyMap, StoreError>,
+    {
+        let snapshot = self.ontology.as_ref().map(|handle| handle.load());
+        let written = read_modify_write(self.backend.search_store(), object_type, object_id, |current| {
+            Ok(Self::materialize(snapshot.as_deref(), object_type, update(current)?))
+        }).await?;
+        
+        self.backend.columnar_store()
+            .write_batch(object_type, vec![written.clone()])
+            .await?;
+        
+        Ok(written)
+    }
+    
     /// Sync a link to the graph store
     pub async fn sync_link(
         &self,
@@ -191,5 +584,242 @@ impl SyncService {
             .create_link(link_type_id, source_id, target_id, properties)
             .await
     }
+    
+    /// Sync a chunk of links to the graph store in one batch, returning their IDs in order
+    pub async fn sync_links(&self, links: &[NewLink]) -> Result<Vec<String>, StoreError> {
+        self.backend.graph_store()
+            .create_links_batch(links)
+            .await
+    }
+}
+
+/// Read-modify-write an object in the search store under optimistic concurrency.
+///
+/// `update` receives the stored object (`None` if it does not exist yet) and returns the
+/// properties to write. The write only succeeds if the object is still at the revision that
+/// was read; if another writer got in first this fails with `StoreError::Conflict` carrying
+/// the current object, so the caller can rebase its change onto it and try again.
+pub async fn read_modify_write<F>(
+    search: &dyn SearchStore,
+    object_type: &str,
+    object_id: &str,
+    update: F,
+) -> Result<IndexedObject, StoreError>
+where
+    F: FnOnce(Option<&IndexedObject>) -> Result<PropertyMap, StoreError>,
+{
+    let current = search.get_object(object_type, object_id).await?;
+    let expected_revision = current.as_ref().map_or(0, |o| o.revision);
+    let mut properties = update(current.as_ref())?;
+    // Keep a stored content hash in step with the content it describes
+    if properties.contains_key(CONTENT_HASH_PROPERTY) {
+        properties.insert(CONTENT_HASH_PROPERTY.to_string(), PropertyValue::String(properties.content_hash()));
+    }
+    
+    let revision = search.index_object(object_type, object_id, &properties, Some(expected_revision)).await?;
+    let mut written = IndexedObject::new(object_type.to_string(), object_id.to_string(), properties);
+    written.revision = revision;
+    Ok(written)
+}
+
+/// What an incremental sync wrote, or would write in a dry run
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SyncReport {
+    pub created: usize,
+    pub updated: usize,
+    pub deleted: usize,
+    pub unchanged: usize,
+    pub dry_run: bool,
+    /// Batches that could not be written; their objects are counted in none of the above
+    pub failures: Vec<SyncFailure>,
+}
+
+impl SyncReport {
+    pub fn writes(&self) -> usize {
+        self.created + self.updated + self.deleted
+    }
+}
+
+/// Content hashes of the objects the last incremental sync of each object type left in the
+/// stores, by object ID. Loaded from and saved to a JSON sidecar file so the next run can
+/// tell changed objects from unchanged ones and find those that left the source.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SyncState {
+    #[serde(skip)]
+    path: Option<PathBuf>,
+    object_types: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+impl SyncState {
+    /// State kept in memory only
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Read the state saved at `path`; a missing file is the empty state of a first run
+    pub fn load(path: impl Into<PathBuf>) -> Result<Self, StoreError> {
+        let path = path.into();
+        let mut state: Self = match std::fs::read(&path) {
+            Ok(content) => serde_json::from_slice(&content)
+                .map_err(|e| StoreError::Serialization(format!("Invalid sync state {}: {}", path.display(), e)))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Self::default(),
+            Err(e) => return Err(StoreError::ReadError(format!("Failed to read sync state {}: {}", path.display(), e))),
+        };
+        state.path = Some(path);
+        Ok(state)
+    }
+    
+    /// Content hashes recorded for an object type, by object ID
+    pub fn hashes(&self, object_type: &str) -> Option<&BTreeMap<String, String>> {
+        self.object_types.get(object_type)
+    }
+    
+    /// Write the state to its file, if it has one
+    pub fn save(&self) -> Result<(), StoreError> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let content = serde_json::to_vec(self).map_err(|e| StoreError::Serialization(e.to_string()))?;
+        // Write then rename so a crash never leaves a truncated state
+        let partial = path.with_extension("tmp");
+        std::fs::write(&partial, content)
+            .and_then(|_| std::fs::rename(&partial, path))
+            .map_err(|e| StoreError::WriteError(format!("Failed to save sync state {}: {}", path.display(), e)))
+    }
+}
+
+/// Objects of an upsert batch sorted by what writing them would do. Created and updated
+/// objects carry their content hash in `_content_hash`.
+#[derive(Debug, Clone, Default)]
+pub struct UpsertPlan {
+    pub created: Vec<IndexedObject>,
+    pub updated: Vec<IndexedObject>,
+    /// Objects skipped because their stored content hash matches
+    pub unchanged: usize,
+}
+
+impl UpsertPlan {
+    pub fn writes(&self) -> usize {
+        self.created.len() + self.updated.len()
+    }
+}
+
+/// Stamp each object with its content hash and compare it with the stored one, fetched in
+/// one [`SearchStore::content_hashes`] call per object type. Objects with a matching hash
+/// are unchanged. Existing objects written without a hash (before hashing, or by a path that
+/// does not stamp one) are updated. `force` treats every existing object as updated.
+pub async fn plan_upsert(
+    search: &dyn SearchStore,
+    objects: Vec<IndexedObject>,
+    force: bool,
+) -> Result<UpsertPlan, StoreError> {
+    let mut ids_by_type: HashMap<String, Vec<String>> = HashMap::new();
+    for object in &objects {
+        ids_by_type.entry(object.object_type.clone()).or_default().push(object.object_id.clone());
+    }
+    let mut stored: HashMap<String, HashMap<String, Option<String>>> = HashMap::new();
+    for (object_type, ids) in &ids_by_type {
+        stored.insert(object_type.clone(), search.content_hashes(object_type, ids).await?);
+    }
+    
+    let mut plan = UpsertPlan::default();
+    for mut object in objects {
+        let hash = object.properties.content_hash();
+        let stored_hash = stored.get(&object.object_type).and_then(|hashes| hashes.get(&object.object_id));
+        if !force && stored_hash == Some(&Some(hash.clone())) {
+            plan.unchanged += 1;
+            continue;
+        }
+        object.properties.insert(CONTENT_HASH_PROPERTY.to_string(), PropertyValue::String(hash));
+        if stored_hash.is_some() {
+            plan.updated.push(object);
+        } else {
+            plan.created.push(object);
+        }
+    }
+    Ok(plan)
 }
 
+/// Objects and links removed by [`delete_object_cascading`], in deletion order
+#[derive(Debug, Clone, Default)]
+pub struct CascadeDelete {
+    pub objects: Vec<ObjectRef>,
+    pub links: Vec<GraphLink>,
+}
+
+/// Delete an object and handle its links according to each link type's `onDelete`.
+///
+/// Everything is planned before anything is written, so a `restrict` link anywhere in the
+/// cascade refuses the whole delete. Cascaded targets that are not stored are skipped.
+/// Without a graph store only the object itself is deleted.
+pub async fn delete_object_cascading(
+    ontology: &Ontology,
+    search: &dyn SearchStore,
+    graph: Option<&dyn GraphStore>,
+    object_type: &str,
+    object_id: &str,
+) -> Result<CascadeDelete, StoreError> {
+    if search.get_object(object_type, object_id).await?.is_none() {
+        return Err(StoreError::NotFound(format!("{}:{}", object_type, object_id)));
+    }
+    let Some(graph) = graph else {
+        search.delete_object(object_type, object_id).await?;
+        return Ok(CascadeDelete {
+            objects: vec![ObjectRef::new(object_type, object_id)],
+            links: Vec::new(),
+        });
+    };
+    let mut link_types: Vec<_> = ontology
+        .link_types()
+        .filter(|link_type| link_type.on_delete != CascadeDeleteBehavior::NoAction)
+        .collect();
+    link_types.sort_by(|a, b| a.id.cmp(&b.id));
+    
+    let mut plan = CascadeDelete::default();
+    let mut seen_links = HashSet::new();
+    let mut pending = vec![ObjectRef::new(object_type, object_id)];
+    while let Some(object) = pending.pop() {
+        if plan.objects.contains(&object) {
+            continue;
+        }
+        for link_type in &link_types {
+            let directions = [
+                (link_type.source == object.object_type, LinkDirection::Outgoing),
+                (link_type.target == object.object_type, LinkDirection::Incoming),
+            ];
+            for (_, direction) in directions.into_iter().filter(|(applies, _)| *applies) {
+                let links = graph
+                    .get_links(&object.object_id, Some(&link_type.id), Some(direction), &LinkQuery::default())
+                    .await?;
+                for link in links {
+                    if link_type.on_delete == CascadeDeleteBehavior::Restrict {
+                        return Err(StoreError::Validation(format!(
+                            "Cannot delete {}: it has '{}' links, which restrict deletion",
+                            object, link_type.id
+                        )));
+                    }
+                    // Only the source side cascades to the objects at the other end
+                    let target = ObjectRef::new(&link_type.target, &link.target_id);
+                    if link_type.on_delete == CascadeDeleteBehavior::Cascade
+                        && direction == LinkDirection::Outgoing
+                        && search.get_object(&target.object_type, &target.object_id).await?.is_some()
+                    {
+                        pending.push(target);
+                    }
+                    if seen_links.insert(link.link_id.clone()) {
+                        plan.links.push(link);
+                    }
+                }
+            }
+        }
+        plan.objects.push(object);
+    }
+    
+    for link in &plan.links {
+        graph.delete_link(&link.link_id).await?;
+    }
+    for object in &plan.objects {
+        search.delete_object(&object.object_type, &object.object_id).await?;
+    }
+    Ok(plan)
+}
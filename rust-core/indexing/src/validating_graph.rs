@@ -0,0 +1,412 @@
+//! Ontology-aware graph store wrapper.
+//!
+//! Backends store each link once, as a directed edge from source to target. The wrapper
+//! gives link types marked `bidirectional` their intended meaning on top of that: they are
+//! traversable from either endpoint, and `get_links` presents them from the queried
+//! object's side (the reversed view is synthesized, nothing extra is stored). Writes are
+//! checked against the link type: unknown types are rejected and cardinality is enforced,
+//! on both endpoints for bidirectional types.
+
+use crate::store::{
+    Aggregation, CentralityMetric, CommunityAlgorithm, Filter, GraphLink, GraphMetrics,
+    GraphStore, LinkDirection, LinkPropertyStats, LinkQuery, LinkScanPage, NewLink, StoreError, TraversalAggregation,
+    TraversalAggregationResult,
+};
+use async_trait::async_trait;
+use ontology_engine::{Link, LinkTypeDef, LinkValidator, OntologyHandle, PropertyMap, PropertyValue};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Arc;
+
+/// Graph store wrapper that validates link writes and makes bidirectional link types
+/// traversable from either endpoint
+pub struct ValidatingGraphStore {
+    inner: Arc<dyn GraphStore>,
+    ontology: OntologyHandle,
+}
+
+impl ValidatingGraphStore {
+    pub fn new(inner: Arc<dyn GraphStore>, ontology: OntologyHandle) -> Self {
+        Self { inner, ontology }
+    }
+
+    /// Whether links of this type are traversable from either endpoint
+    fn is_bidirectional(&self, link_type_id: &str) -> bool {
+        self.ontology
+            .load()
+            .get_link_type(link_type_id)
+            .is_some_and(|link_type| link_type.bidirectional)
+    }
+
+    /// Whether a query over these link types (all types when empty) may involve a
+    /// bidirectional one
+    fn involves_bidirectional(&self, link_type_ids: &[String]) -> bool {
+        let ontology = self.ontology.load();
+        if link_type_ids.is_empty() {
+            ontology.link_types().any(|link_type| link_type.bidirectional)
+        } else {
+            link_type_ids.iter().any(|id| self.is_bidirectional(id))
+        }
+    }
+
+    /// Objects one hop away over the given link types: outgoing links, plus incoming links
+    /// of bidirectional types. Each neighbour appears once.
+    async fn neighbours(
+        &self,
+        object_id: &str,
+        link_type_ids: &[String],
+        link_filters: &[Filter],
+    ) -> Result<Vec<String>, StoreError> {
+        let query = LinkQuery {
+            filters: link_filters.to_vec(),
+            ..Default::default()
+        };
+        let links = if link_type_ids.is_empty() {
+            self.inner.get_links(object_id, None, Some(LinkDirection::Both), &query).await?
+        } else {
+            let mut links = Vec::new();
+            for link_type_id in link_type_ids {
+                let direction = if self.is_bidirectional(link_type_id) {
+                    LinkDirection::Both
+                } else {
+                    LinkDirection::Outgoing
+                };
+                links.extend(
+                    self.inner
+                        .get_links(object_id, Some(link_type_id), Some(direction), &query)
+                        .await?,
+                );
+            }
+            links
+        };
+
+        let mut seen = HashSet::new();
+        Ok(links
+            .into_iter()
+            .filter_map(|link| {
+                if link.source_id == object_id {
+                    Some(link.target_id)
+                } else if self.is_bidirectional(&link.link_type_id) {
+                    Some(link.source_id)
+                } else {
+                    None
+                }
+            })
+            .filter(|id| id != object_id && seen.insert(id.clone()))
+            .collect())
+    }
+
+    /// Breadth-first traversal honouring bidirectional link types; returns reached objects
+    /// (excluding the start) in visit order, each once
+    async fn bfs(
+        &self,
+        start_id: &str,
+        link_type_ids: &[String],
+        max_hops: usize,
+        link_filters: &[Filter],
+    ) -> Result<Vec<String>, StoreError> {
+        let mut visited: HashSet<String> = HashSet::from([start_id.to_string()]);
+        let mut reached = Vec::new();
+        let mut frontier = vec![start_id.to_string()];
+
+        for _ in 0..max_hops {
+            let mut next = Vec::new();
+            for node in &frontier {
+                for neighbour in self.neighbours(node, link_type_ids, link_filters).await? {
+                    if visited.insert(neighbour.clone()) {
+                        reached.push(neighbour.clone());
+                        next.push(neighbour);
+                    }
+                }
+            }
+            if next.is_empty() {
+                break;
+            }
+            frontier = next;
+        }
+        Ok(reached)
+    }
+
+    /// Reject a new link that would break its type's cardinality (see [`LinkValidator`])
+    async fn check_cardinality(
+        &self,
+        link_type: &LinkTypeDef,
+        source_id: &str,
+        target_id: &str,
+    ) -> Result<(), StoreError> {
+        let existing = endpoint_links(self.inner.as_ref(), &link_type.id, source_id, target_id).await?;
+        LinkValidator::check_cardinality(link_type, source_id, target_id, &existing).map_err(StoreError::Validation)
+    }
+}
+
+/// Links of a type at either endpoint of a prospective link, in any direction, as the
+/// `existing` links [`LinkValidator::check_cardinality`] expects
+pub async fn endpoint_links(
+    graph: &dyn GraphStore,
+    link_type_id: &str,
+    source_id: &str,
+    target_id: &str,
+) -> Result<Vec<Link>, StoreError> {
+    let query = LinkQuery::default();
+    let mut links: Vec<GraphLink> = graph
+        .get_links(source_id, Some(link_type_id), Some(LinkDirection::Both), &query)
+        .await?;
+    for link in graph.get_links(target_id, Some(link_type_id), Some(LinkDirection::Both), &query).await? {
+        if !links.iter().any(|l| l.link_id == link.link_id) {
+            links.push(link);
+        }
+    }
+    Ok(links
+        .into_iter()
+        .map(|link| Link {
+            id: link.link_id,
+            link_type_id: link.link_type_id,
+            source_id: link.source_id,
+            target_id: link.target_id,
+            properties: link.properties,
+            created_at: link.created_at,
+        })
+        .collect())
+}
+
+/// Present a bidirectional link from `object_id`'s side: as outgoing, or as incoming when
+/// only incoming links were asked for
+fn orient(mut link: GraphLink, object_id: &str, direction: LinkDirection) -> GraphLink {
+    let wants_source = !matches!(direction, LinkDirection::Incoming);
+    if (link.source_id == object_id) != wants_source {
+        std::mem::swap(&mut link.source_id, &mut link.target_id);
+    }
+    link
+}
+
+#[async_trait]
+impl GraphStore for ValidatingGraphStore {
+    async fn create_link(
+        &self,
+        link_type_id: &str,
+        source_id: &str,
+        target_id: &str,
+        properties: &PropertyMap,
+    ) -> Result<String, StoreError> {
+        let link_type = self
+            .ontology
+            .load()
+            .get_link_type(link_type_id)
+            .cloned()
+            .ok_or_else(|| StoreError::Validation(format!("Unknown link type '{}'", link_type_id)))?;
+        self.check_cardinality(&link_type, source_id, target_id).await?;
+        self.inner.create_link(link_type_id, source_id, target_id, properties).await
+    }
+
+    async fn create_links_batch(&self, links: &[NewLink]) -> Result<Vec<String>, StoreError> {
+        // Each link is checked against the stored links and the batch's earlier links, then
+        // the batch is written in one go
+        let ontology = self.ontology.load();
+        let mut accepted: Vec<Link> = Vec::with_capacity(links.len());
+        for (i, link) in links.iter().enumerate() {
+            let link_type = ontology
+                .get_link_type(&link.link_type_id)
+                .ok_or_else(|| StoreError::Validation(format!("Unknown link type '{}'", link.link_type_id)))?;
+            let mut existing = endpoint_links(self.inner.as_ref(), &link_type.id, &link.source_id, &link.target_id).await?;
+            existing.extend(accepted.iter().cloned());
+            LinkValidator::check_cardinality(link_type, &link.source_id, &link.target_id, &existing)
+                .map_err(StoreError::Validation)?;
+            accepted.push(Link {
+                id: format!("batch item {}", i),
+                link_type_id: link.link_type_id.clone(),
+                source_id: link.source_id.clone(),
+                target_id: link.target_id.clone(),
+                properties: link.properties.clone(),
+                created_at: chrono::Utc::now(),
+            });
+        }
+        self.inner.create_links_batch(links).await
+    }
+
+    async fn delete_link(&self, link_id: &str) -> Result<(), StoreError> {
+        self.inner.delete_link(link_id).await
+    }
+
+    async fn get_link(&self, link_id: &str) -> Result<Option<GraphLink>, StoreError> {
+        self.inner.get_link(link_id).await
+    }
+
+    async fn get_links(
+        &self,
+        object_id: &str,
+        link_type_id: Option<&str>,
+        direction: Option<LinkDirection>,
+        query: &LinkQuery,
+    ) -> Result<Vec<GraphLink>, StoreError> {
+        let direction = direction.unwrap_or(LinkDirection::Both);
+        let link_types: Vec<String> = link_type_id.map(str::to_string).into_iter().collect();
+        if !self.involves_bidirectional(&link_types) {
+            return self.inner.get_links(object_id, link_type_id, Some(direction), query).await;
+        }
+
+        // Fetch both directions unpaginated, keep directed links that match the requested
+        // direction and every bidirectional one, then paginate the combined view
+        let unpaged = LinkQuery {
+            limit: None,
+            offset: None,
+            ..query.clone()
+        };
+        let links = self
+            .inner
+            .get_links(object_id, link_type_id, Some(LinkDirection::Both), &unpaged)
+            .await?;
+        let offset = query.offset.unwrap_or(0);
+        let limit = query.limit.unwrap_or(usize::MAX);
+        Ok(links
+            .into_iter()
+            .filter_map(|link| {
+                if self.is_bidirectional(&link.link_type_id) {
+                    return Some(orient(link, object_id, direction));
+                }
+                let keep = match direction {
+                    LinkDirection::Outgoing => link.source_id == object_id,
+                    LinkDirection::Incoming => link.target_id == object_id,
+                    LinkDirection::Both => true,
+                };
+                keep.then_some(link)
+            })
+            .skip(offset)
+            .take(limit)
+            .collect())
+    }
+
+    async fn traverse(
+        &self,
+        start_id: &str,
+        link_type_ids: &[String],
+        max_hops: usize,
+    ) -> Result<Vec<String>, StoreError> {
+        if !self.involves_bidirectional(link_type_ids) {
+            return self.inner.traverse(start_id, link_type_ids, max_hops).await;
+        }
+        self.bfs(start_id, link_type_ids, max_hops, &[]).await
+    }
+
+    async fn get_connected_objects(
+        &self,
+        object_id: &str,
+        link_type_id: &str,
+    ) -> Result<Vec<String>, StoreError> {
+        if !self.is_bidirectional(link_type_id) {
+            return self.inner.get_connected_objects(object_id, link_type_id).await;
+        }
+        self.neighbours(object_id, &[link_type_id.to_string()], &[]).await
+    }
+
+    async fn traverse_with_filters(
+        &self,
+        start_id: &str,
+        link_type_ids: &[String],
+        max_hops: usize,
+        link_filters: &[Filter],
+    ) -> Result<Vec<String>, StoreError> {
+        if !self.involves_bidirectional(link_type_ids) {
+            return self
+                .inner
+                .traverse_with_filters(start_id, link_type_ids, max_hops, link_filters)
+                .await;
+        }
+        self.bfs(start_id, link_type_ids, max_hops, link_filters).await
+    }
+
+    async fn traverse_with_aggregation(
+        &self,
+        start_id: &str,
+        link_type_ids: &[String],
+        max_hops: usize,
+        aggregation: &TraversalAggregation,
+    ) -> Result<TraversalAggregationResult, StoreError> {
+        // Counting only needs the reached set; other aggregations need object properties,
+        // which only the backend can reach
+        if matches!(aggregation.operation, Aggregation::Count) && self.involves_bidirectional(link_type_ids) {
+            let reached = self.bfs(start_id, link_type_ids, max_hops, &[]).await?;
+            return Ok(TraversalAggregationResult {
+                value: PropertyValue::Integer(reached.len() as i64),
+                count: reached.len(),
+            });
+        }
+        self.inner
+            .traverse_with_aggregation(start_id, link_type_ids, max_hops, aggregation)
+            .await
+    }
+
+    async fn compute_centrality(
+        &self,
+        object_type: &str,
+        metric: CentralityMetric,
+    ) -> Result<HashMap<String, f64>, StoreError> {
+        self.inner.compute_centrality(object_type, metric).await
+    }
+
+    async fn detect_communities(
+        &self,
+        object_type: &str,
+        algorithm: CommunityAlgorithm,
+    ) -> Result<HashMap<String, usize>, StoreError> {
+        self.inner.detect_communities(object_type, algorithm).await
+    }
+
+    async fn shortest_path(
+        &self,
+        source_id: &str,
+        target_id: &str,
+        link_types: &[String],
+    ) -> Result<Vec<String>, StoreError> {
+        if !self.involves_bidirectional(link_types) {
+            return self.inner.shortest_path(source_id, target_id, link_types).await;
+        }
+
+        let mut previous: HashMap<String, String> = HashMap::new();
+        let mut queue = VecDeque::from([source_id.to_string()]);
+        let mut visited: HashSet<String> = HashSet::from([source_id.to_string()]);
+        while let Some(node) = queue.pop_front() {
+            if node == target_id {
+                let mut path = vec![node.clone()];
+                let mut current = node;
+                while let Some(prev) = previous.get(&current) {
+                    path.push(prev.clone());
+                    current = prev.clone();
+                }
+                path.reverse();
+                return Ok(path);
+            }
+            for neighbour in self.neighbours(&node, link_types, &[]).await? {
+                if visited.insert(neighbour.clone()) {
+                    previous.insert(neighbour.clone(), node.clone());
+                    queue.push_back(neighbour);
+                }
+            }
+        }
+        Ok(Vec::new())
+    }
+
+    async fn graph_metrics(&self, object_type: &str) -> Result<GraphMetrics, StoreError> {
+        self.inner.graph_metrics(object_type).await
+    }
+
+    fn explain_traversal(&self, start_id: &str, link_type_ids: &[String], max_hops: usize) -> Option<String> {
+        self.inner.explain_traversal(start_id, link_type_ids, max_hops)
+    }
+
+    async fn scan_links(
+        &self,
+        link_type_id: &str,
+        cursor: Option<&str>,
+        limit: usize,
+    ) -> Result<LinkScanPage, StoreError> {
+        self.inner.scan_links(link_type_id, cursor, limit).await
+    }
+
+    async fn link_property_stats(
+        &self,
+        link_type_id: &str,
+        property: &str,
+        buckets: usize,
+    ) -> Result<LinkPropertyStats, StoreError> {
+        self.inner.link_property_stats(link_type_id, property, buckets).await
+    }
+}
@@ -0,0 +1,197 @@
+//! Ontology-aware search store wrapper.
+//!
+//! Writes are checked for properties their object type does not declare, such as a
+//! misspelled source column. Types with `strictProperties` reject those writes, naming the
+//! unknown keys. Other types index them as-is, and every unknown key is counted in a
+//! `PropertyDrift` so drift in source data can be found later. Keys in the reserved `_`
+//! namespace and documents of types the ontology does not know are passed through.
+
+use crate::store::{
+    AnalyticsQuery, AnalyticsResult, Filter, IndexedObject, ObjectScanPage, SearchQuery, SearchStore,
+    StoreError,
+};
+use async_trait::async_trait;
+use ontology_engine::{OntologyHandle, PropertyMap};
+use serde_json::Value as JsonValue;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, RwLock};
+
+/// Counts of undeclared property keys written, per object type
+#[derive(Clone, Default)]
+pub struct PropertyDrift {
+    counts: Arc<RwLock<HashMap<String, BTreeMap<String, u64>>>>,
+}
+
+impl PropertyDrift {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Count one write of an object carrying these unknown keys
+    pub fn record(&self, object_type: &str, keys: &[String]) {
+        if keys.is_empty() {
+            return;
+        }
+        let mut counts = self.counts.write().unwrap();
+        let type_counts = counts.entry(object_type.to_string()).or_default();
+        for key in keys {
+            *type_counts.entry(key.clone()).or_insert(0) += 1;
+        }
+    }
+
+    /// Unknown keys written for an object type, with the number of writes that carried each
+    pub fn for_type(&self, object_type: &str) -> BTreeMap<String, u64> {
+        self.counts.read().unwrap().get(object_type).cloned().unwrap_or_default()
+    }
+}
+
+/// Search store wrapper that enforces strict object types and counts undeclared properties
+/// written to the others
+pub struct ValidatingSearchStore {
+    inner: Arc<dyn SearchStore>,
+    ontology: OntologyHandle,
+    drift: PropertyDrift,
+}
+
+impl ValidatingSearchStore {
+    pub fn new(inner: Arc<dyn SearchStore>, ontology: OntologyHandle) -> Self {
+        Self {
+            inner,
+            ontology,
+            drift: PropertyDrift::new(),
+        }
+    }
+
+    /// Count unknown keys into a shared tracker (e.g. one the usage report reads)
+    pub fn with_drift(mut self, drift: PropertyDrift) -> Self {
+        self.drift = drift;
+        self
+    }
+
+    pub fn drift(&self) -> &PropertyDrift {
+        &self.drift
+    }
+
+    /// Unknown keys of an object about to be written; an error if its type is strict
+    fn unknown_properties(
+        &self,
+        object_type: &str,
+        object_id: &str,
+        properties: &PropertyMap,
+    ) -> Result<Vec<String>, StoreError> {
+        let ontology = self.ontology.load();
+        let Some(object_type_def) = ontology.get_object_type(object_type) else {
+            return Ok(Vec::new());
+        };
+        let unknown = object_type_def.unknown_properties(properties);
+        if object_type_def.strict_properties && !unknown.is_empty() {
+            return Err(StoreError::Validation(format!(
+                "Object '{}' of strict type '{}' has unknown properties: {}",
+                object_id,
+                object_type,
+                unknown.join(", ")
+            )));
+        }
+        Ok(unknown)
+    }
+}
+
+#[async_trait]
+impl SearchStore for ValidatingSearchStore {
+    async fn index_object(
+        &self,
+        object_type: &str,
+        object_id: &str,
+        properties: &PropertyMap,
+        expected_revision: Option<u64>,
+    ) -> Result<u64, StoreError> {
+        let unknown = self.unknown_properties(object_type, object_id, properties)?;
+        let revision = self.inner.index_object(object_type, object_id, properties, expected_revision).await?;
+        self.drift.record(object_type, &unknown);
+        Ok(revision)
+    }
+
+    async fn search(
+        &self,
+        object_type: &str,
+        query: &SearchQuery,
+    ) -> Result<Vec<IndexedObject>, StoreError> {
+        self.inner.search(object_type, query).await
+    }
+
+    async fn get_object(
+        &self,
+        object_type: &str,
+        object_id: &str,
+    ) -> Result<Option<IndexedObject>, StoreError> {
+        self.inner.get_object(object_type, object_id).await
+    }
+
+    /// The whole batch is rejected if any object breaks a strict type
+    async fn bulk_index(&self, objects: Vec<IndexedObject>) -> Result<(), StoreError> {
+        let mut unknown = Vec::with_capacity(objects.len());
+        for object in &objects {
+            unknown.push(self.unknown_properties(&object.object_type, &object.object_id, &object.properties)?);
+        }
+        let object_types: Vec<String> = objects.iter().map(|o| o.object_type.clone()).collect();
+        self.inner.bulk_index(objects).await?;
+        for (object_type, keys) in object_types.iter().zip(&unknown) {
+            self.drift.record(object_type, keys);
+        }
+        Ok(())
+    }
+
+    async fn delete_object(&self, object_type: &str, object_id: &str) -> Result<(), StoreError> {
+        self.inner.delete_object(object_type, object_id).await
+    }
+
+    async fn count_objects(
+        &self,
+        object_type: &str,
+        filters: Option<&[Filter]>,
+    ) -> Result<u64, StoreError> {
+        self.inner.count_objects(object_type, filters).await
+    }
+
+    async fn get_objects(
+        &self,
+        object_type: &str,
+        object_ids: &[String],
+    ) -> Result<HashMap<String, IndexedObject>, StoreError> {
+        self.inner.get_objects(object_type, object_ids).await
+    }
+
+    async fn content_hashes(
+        &self,
+        object_type: &str,
+        object_ids: &[String],
+    ) -> Result<HashMap<String, Option<String>>, StoreError> {
+        self.inner.content_hashes(object_type, object_ids).await
+    }
+
+    async fn scan_objects(
+        &self,
+        object_type: &str,
+        filters: &[Filter],
+        cursor: Option<&str>,
+        limit: usize,
+    ) -> Result<ObjectScanPage, StoreError> {
+        self.inner.scan_objects(object_type, filters, cursor, limit).await
+    }
+
+    async fn aggregate(
+        &self,
+        object_type: &str,
+        query: &AnalyticsQuery,
+    ) -> Result<AnalyticsResult, StoreError> {
+        self.inner.aggregate(object_type, query).await
+    }
+
+    fn supports_aggregations(&self) -> bool {
+        self.inner.supports_aggregations()
+    }
+
+    fn explain_search(&self, object_type: &str, query: &SearchQuery) -> Option<JsonValue> {
+        self.inner.explain_search(object_type, query)
+    }
+}
@@ -34,7 +34,7 @@ async fn test_complete_search_flow() {
         );
 
         store
-            .index_object(object_type, &format!("obj_{}", i), &properties)
+            .index_object(object_type, &format!("obj_{}", i), &properties, None)
             .await
             .unwrap();
     }
@@ -56,6 +56,7 @@ async fn test_complete_search_flow() {
         operator: FilterOperator::GreaterThan,
         value: PropertyValue::Integer(20),
         distance: None,
+        case_insensitive: false,
     };
     let filtered_count = store
         .count_objects(object_type, Some(&[filter]))
@@ -70,10 +71,12 @@ async fn test_complete_search_flow() {
             operator: FilterOperator::Equals,
             value: PropertyValue::String("test".to_string()),
             distance: None,
+            case_insensitive: false,
         }],
-        sort: None,
+        sort: vec![],
         limit: Some(10),
         offset: None,
+        search_after: None,
     };
 
     let results = store.search(object_type, &query).await.unwrap();
@@ -124,6 +127,7 @@ async fn test_bulk_indexing_flow() {
             refresh_frequency: None,
             next_refresh: None,
             refresh_status: indexing::store::RefreshStatus::UpToDate,
+            revision: 0,
         });
     }
 
@@ -148,10 +152,12 @@ async fn test_bulk_indexing_flow() {
             operator: FilterOperator::Equals,
             value: PropertyValue::String("batch1".to_string()),
             distance: None,
+            case_insensitive: false,
         }],
-        sort: None,
+        sort: vec![],
         limit: Some(25),
         offset: None,
+        search_after: None,
     };
 
     let results = store.search(object_type, &query).await.unwrap();
@@ -188,7 +194,7 @@ async fn test_blue_green_migration_flow() {
     properties1.insert("version".to_string(), PropertyValue::Integer(1));
 
     store
-        .index_object(object_type, "data1", &properties1)
+        .index_object(object_type, "data1", &properties1, None)
         .await
         .unwrap();
     tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
@@ -209,7 +215,7 @@ async fn test_blue_green_migration_flow() {
     properties2.insert("version".to_string(), PropertyValue::Integer(2));
 
     store
-        .index_object(object_type, "data2", &properties2)
+        .index_object(object_type, "data2", &properties2, None)
         .await
         .unwrap();
     tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
@@ -241,7 +247,7 @@ async fn test_blue_green_migration_flow() {
 async fn test_dgraph_traversal_flow() {
     let store = match DgraphStore::new("http://localhost:9080".to_string()).await {
         Ok(s) => {
-            let _ = s.init_schema().await;
+            let _ = s.init_schema(&["test_traversal_link".to_string()]).await;
             s
         }
         Err(_) => {
@@ -282,6 +288,7 @@ async fn test_dgraph_traversal_flow() {
         operator: FilterOperator::GreaterThan,
         value: PropertyValue::Integer(10),
         distance: None,
+        case_insensitive: false,
     };
 
     let filtered_result = store
@@ -9,6 +9,7 @@ fn test_filter_creation() {
         operator: FilterOperator::GreaterThan,
         value: PropertyValue::Integer(18),
         distance: None,
+        case_insensitive: false,
     };
     
     assert_eq!(filter.property, "age");
@@ -23,13 +24,15 @@ fn test_search_query_creation() {
         operator: FilterOperator::Equals,
         value: PropertyValue::String("active".to_string()),
         distance: None,
+        case_insensitive: false,
     };
     
     let query = SearchQuery {
         filters: vec![filter],
-        sort: None,
+        sort: vec![],
         limit: Some(10),
         offset: Some(0),
+        search_after: None,
     };
     
     assert_eq!(query.filters.len(), 1);
@@ -111,3 +114,299 @@ fn test_filter_operators() {
 
 
 
+
+#[test]
+fn test_hydration_normalizes_references() {
+    let yaml = r#"
+ontology:
+  objectTypes:
+    - id: "company"
+      displayName: "Company"
+      primaryKey: "id"
+      properties:
+        - id: "id"
+          type: "string"
+        - id: "headquarters"
+          type: "object_reference"
+          referenceTarget: "region"
+    - id: "region"
+      displayName: "Region"
+      primaryKey: "code"
+      properties:
+        - id: "code"
+          type: "string"
+  linkTypes: []
+"#;
+    let ontology = ontology_engine::Ontology::from_yaml(yaml).unwrap();
+    let company = ontology.get_object_type("company").unwrap();
+    let hydrator = indexing::hydration::ObjectHydrator::new();
+
+    // Bare and prefixed references hydrate to the same value
+    for stored in ["NE", "region:NE"] {
+        let mut properties = PropertyMap::new();
+        properties.insert("headquarters".to_string(), PropertyValue::ObjectReference(stored.to_string()));
+        let indexed = indexing::store::IndexedObject::new("company".to_string(), "acme".to_string(), properties);
+        let hydrated = hydrator.hydrate_from_indexed(&indexed, company).unwrap();
+        assert_eq!(
+            hydrated.properties.get("headquarters"),
+            Some(&PropertyValue::ObjectReference("region:NE".to_string()))
+        );
+    }
+
+    // Raw JSON rows are coerced to the declared types first
+    let hydrated = hydrator
+        .hydrate_from_json(&serde_json::json!({"id": "acme", "headquarters": "NE"}), company)
+        .unwrap();
+    assert_eq!(hydrated.object_id, "acme");
+    assert_eq!(
+        hydrated.properties.get("headquarters"),
+        Some(&PropertyValue::ObjectReference("region:NE".to_string()))
+    );
+    let err = hydrator
+        .hydrate_from_json(&serde_json::json!({"id": "acme", "headquarters": 7}), company)
+        .err()
+        .unwrap();
+    assert!(err.to_string().contains("'headquarters' expects type 'object_reference'"), "{}", err);
+}
+
+#[test]
+fn test_hydration_evaluates_computed_properties() {
+    let yaml = r#"
+ontology:
+  objectTypes:
+    - id: "city"
+      displayName: "City"
+      primaryKey: "id"
+      properties:
+        - id: "id"
+          type: "string"
+        - id: "population"
+          type: "integer"
+        - id: "area"
+          type: "double"
+      computedProperties:
+        - id: "density"
+          displayName: "Density"
+          type: "double"
+          expression:
+            type: "arithmetic"
+            expression: "population / area"
+        - id: "size"
+          displayName: "Size"
+          type: "string"
+          expression:
+            type: "branches"
+            branches:
+              - when: { property: "population", operator: "gte", value: 1000000 }
+                then: "large"
+            default: "small"
+  linkTypes: []
+"#;
+    let ontology = ontology_engine::Ontology::from_yaml(yaml).unwrap();
+    let city = ontology.get_object_type("city").unwrap();
+    let hydrator = indexing::hydration::ObjectHydrator::new();
+
+    let indexed: Vec<_> = (0..100)
+        .map(|i| {
+            let mut properties = PropertyMap::new();
+            properties.insert("population".to_string(), PropertyValue::Integer(20_000 * i));
+            properties.insert("area".to_string(), PropertyValue::Double(if i == 0 { 0.0 } else { 10.0 }));
+            indexing::store::IndexedObject::new("city".to_string(), format!("c{}", i), properties)
+        })
+        .collect();
+    let hydrated = hydrator.hydrate_batch(&indexed, city).unwrap();
+
+    // Each definition is compiled once for the whole batch
+    assert_eq!(hydrator.compilations(), 2);
+    assert_eq!(hydrated[60].properties.get("density"), Some(&PropertyValue::Double(120_000.0)));
+    assert_eq!(hydrated[60].properties.get("size"), Some(&PropertyValue::String("large".to_string())));
+    assert_eq!(hydrated[1].properties.get("size"), Some(&PropertyValue::String("small".to_string())));
+    assert_eq!(hydrated[1].computed, vec!["density".to_string(), "size".to_string()]);
+    assert!(hydrated[1].warnings.is_empty());
+
+    // A failing property is null with a warning; the others are still evaluated
+    assert_eq!(hydrated[0].properties.get("density"), Some(&PropertyValue::Null));
+    assert_eq!(hydrated[0].properties.get("size"), Some(&PropertyValue::String("small".to_string())));
+    assert_eq!(hydrated[0].warnings.len(), 1);
+    assert!(hydrated[0].warnings[0].contains("'density' on c0"), "{}", hydrated[0].warnings[0]);
+
+    // Computed properties can be left out
+    let options = indexing::hydration::HydrationOptions { include_computed: false, ..Default::default() };
+    let stored_only = hydrator.hydrate_batch_with(&indexed, city, options).unwrap();
+    assert!(!stored_only[60].properties.contains_key("density"));
+    assert!(stored_only[60].computed.is_empty());
+    assert_eq!(hydrator.compilations(), 2);
+}
+
+/// Executor that predicts the sum of its integer inputs, recording every call's inputs
+struct SummingExecutor {
+    calls: std::sync::Arc<std::sync::Mutex<Vec<std::collections::HashMap<String, PropertyValue>>>>,
+}
+
+#[async_trait::async_trait]
+impl ontology_engine::ModelExecutor for SummingExecutor {
+    async fn execute(
+        &self,
+        _model: &ontology_engine::ModelObjective,
+        inputs: std::collections::HashMap<String, PropertyValue>,
+    ) -> Result<ontology_engine::ModelExecutionResult, ontology_engine::ModelExecutionError> {
+        let sum = inputs
+            .values()
+            .map(|value| match value {
+                PropertyValue::Integer(n) => *n,
+                _ => 0,
+            })
+            .sum();
+        self.calls.lock().unwrap().push(inputs);
+        Ok(ontology_engine::ModelExecutionResult {
+            prediction: PropertyValue::Integer(sum),
+            confidence: None,
+            probabilities: None,
+            metadata: std::collections::HashMap::new(),
+        })
+    }
+
+    fn can_handle(&self, _platform: &ontology_engine::ModelPlatform) -> bool {
+        true
+    }
+}
+
+fn customer(id: &str, properties: &[(&str, PropertyValue)]) -> indexing::hydration::HydratedObject {
+    let mut map = PropertyMap::new();
+    for (name, value) in properties {
+        map.insert(name.to_string(), value.clone());
+    }
+    indexing::hydration::HydratedObject {
+        object_type: "customer".to_string(),
+        object_id: id.to_string(),
+        title: id.to_string(),
+        properties: map,
+        computed: Vec::new(),
+        warnings: Vec::new(),
+        references: std::collections::HashMap::new(),
+    }
+}
+
+/// Evaluator over a registry binding `customer.score` to the summing model with `config`
+fn model_property_evaluator(
+    config: ontology_engine::ModelBindingConfig,
+) -> (
+    indexing::ModelPropertyEvaluator,
+    std::sync::Arc<std::sync::Mutex<Vec<std::collections::HashMap<String, PropertyValue>>>>,
+) {
+    let mut registry = ontology_engine::ModelRegistry::new();
+    registry
+        .register(ontology_engine::ModelObjective::new(
+            "score".to_string(),
+            "Score".to_string(),
+            ontology_engine::ModelType::Regression,
+            "1.0.0".to_string(),
+            "/models/score.pkl".to_string(),
+            ontology_engine::ModelPlatform::Local { framework: "sklearn".to_string() },
+        ))
+        .unwrap();
+    registry
+        .bind_model("score", "customer".to_string(), "score".to_string(), None, config)
+        .unwrap();
+    let calls = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let mut orchestrator = ontology_engine::ModelExecutionOrchestrator::new();
+    orchestrator.add_executor(Box::new(SummingExecutor { calls: calls.clone() }));
+    let evaluator = indexing::ModelPropertyEvaluator::new(
+        std::sync::Arc::new(tokio::sync::RwLock::new(registry)),
+        std::sync::Arc::new(orchestrator),
+    );
+    (evaluator, calls)
+}
+
+#[tokio::test]
+async fn test_model_properties_predict_from_bound_inputs() {
+    let (evaluator, calls) = model_property_evaluator(ontology_engine::ModelBindingConfig {
+        input_properties: vec!["tenure".to_string(), "orders".to_string()],
+        ..Default::default()
+    });
+
+    let mut objects = vec![
+        customer(
+            "c1",
+            &[
+                ("tenure", PropertyValue::Integer(3)),
+                ("orders", PropertyValue::Integer(4)),
+                ("name", PropertyValue::String("Ada".to_string())),
+            ],
+        ),
+        customer("c2", &[("tenure", PropertyValue::Integer(3)), ("score", PropertyValue::Integer(1))]),
+    ];
+    evaluator.evaluate(&mut objects).await;
+
+    // Only the bound input properties reach the model
+    assert_eq!(objects[0].properties.get("score"), Some(&PropertyValue::Integer(7)));
+    assert!(objects[0].warnings.is_empty(), "{:?}", objects[0].warnings);
+    assert_eq!(
+        *calls.lock().unwrap(),
+        vec![std::collections::HashMap::from([
+            ("tenure".to_string(), PropertyValue::Integer(3)),
+            ("orders".to_string(), PropertyValue::Integer(4)),
+        ])]
+    );
+
+    // Missing inputs skip the model and keep the stored value
+    assert_eq!(objects[1].properties.get("score"), Some(&PropertyValue::Integer(1)));
+    assert_eq!(objects[1].warnings.len(), 1);
+    assert!(objects[1].warnings[0].contains("missing input properties orders"), "{}", objects[1].warnings[0]);
+
+    // The same inputs again are served from the cache
+    let mut again = vec![customer(
+        "c3",
+        &[("tenure", PropertyValue::Integer(3)), ("orders", PropertyValue::Integer(4))],
+    )];
+    evaluator.evaluate(&mut again).await;
+    assert_eq!(again[0].properties.get("score"), Some(&PropertyValue::Integer(7)));
+    assert_eq!(calls.lock().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_model_properties_without_cache_execute_every_read() {
+    let (evaluator, calls) = model_property_evaluator(ontology_engine::ModelBindingConfig {
+        cache_enabled: false,
+        ..Default::default()
+    });
+
+    for _ in 0..2 {
+        let mut objects = vec![customer("c1", &[("tenure", PropertyValue::Integer(2))])];
+        evaluator.evaluate(&mut objects).await;
+        assert_eq!(objects[0].properties.get("score"), Some(&PropertyValue::Integer(2)));
+    }
+    assert_eq!(calls.lock().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn test_async_model_properties_return_fallback_until_computed() {
+    let (evaluator, calls) = model_property_evaluator(ontology_engine::ModelBindingConfig {
+        input_properties: vec!["tenure".to_string()],
+        async_execution: true,
+        fallback_value: Some(PropertyValue::Integer(-1)),
+        ..Default::default()
+    });
+
+    let mut objects = vec![customer("c1", &[("tenure", PropertyValue::Integer(5))])];
+    evaluator.evaluate(&mut objects).await;
+    assert_eq!(objects[0].properties.get("score"), Some(&PropertyValue::Integer(-1)));
+
+    // The background prediction lands in the cache for later reads
+    for _ in 0..100 {
+        if !calls.lock().unwrap().is_empty() {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+    let mut objects = vec![customer("c1", &[("tenure", PropertyValue::Integer(5))])];
+    for _ in 0..100 {
+        evaluator.evaluate(&mut objects).await;
+        if objects[0].properties.get("score") == Some(&PropertyValue::Integer(5)) {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+    assert_eq!(objects[0].properties.get("score"), Some(&PropertyValue::Integer(5)));
+    assert_eq!(calls.lock().unwrap().len(), 1);
+}
@@ -0,0 +1,201 @@
+//! Elasticsearch and Dgraph for a test run, started in containers or already running.
+
+use anyhow::{bail, Context};
+use indexing::store::{DgraphStore, ElasticsearchStore, StoreError};
+use ontology_engine::Ontology;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Test binary flag that keeps the run off Docker
+pub const NO_CONTAINERS_FLAG: &str = "--no-containers";
+
+/// Environment variable with the same effect, for `cargo test` runs that cannot pass flags
+/// to every test binary
+pub const NO_CONTAINERS_ENV: &str = "ONTOLOGY_TEST_NO_CONTAINERS";
+
+const ELASTICSEARCH_IMAGE: (&str, &str) = ("elasticsearch", "8.11.0");
+const DGRAPH_IMAGE: (&str, &str) = ("dgraph/standalone", "v23.1.0");
+
+/// How long freshly started containers get to accept requests
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(180);
+
+/// How long already running backends get to answer before the test skips
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Where a run's backends come from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BackendMode {
+    /// Start Elasticsearch and Dgraph containers for the run
+    Containers,
+    /// Use the backends at `ELASTICSEARCH_URL` / `DGRAPH_URL`, skipping when unreachable
+    External,
+}
+
+impl BackendMode {
+    /// `External` if the test binary got `--no-containers`, `ONTOLOGY_TEST_NO_CONTAINERS` is
+    /// set, or the crate was built without the `containers` feature
+    pub fn from_env() -> Self {
+        let no_containers = std::env::args().any(|arg| arg == NO_CONTAINERS_FLAG)
+            || std::env::var(NO_CONTAINERS_ENV).is_ok_and(|v| !v.is_empty() && v != "0");
+        if no_containers || !cfg!(feature = "containers") {
+            BackendMode::External
+        } else {
+            BackendMode::Containers
+        }
+    }
+}
+
+/// Search and graph stores on real backends. Containers started for them are removed when
+/// this is dropped.
+pub struct Backends {
+    pub elasticsearch_url: String,
+    pub dgraph_url: String,
+    pub search: Arc<ElasticsearchStore>,
+    pub graph: Arc<DgraphStore>,
+    #[cfg(feature = "containers")]
+    _containers: Vec<testcontainers::ContainerAsync<testcontainers::GenericImage>>,
+}
+
+impl Backends {
+    /// Backends for `mode`. In `External` mode this is `Ok(None)` (after saying why on
+    /// stderr) when the backends are not reachable; in `Containers` mode failing to start
+    /// them is an error, so a broken setup cannot pass as a skip.
+    pub async fn start(mode: BackendMode) -> anyhow::Result<Option<Self>> {
+        match mode {
+            BackendMode::Containers => Self::start_containers().await.map(Some),
+            BackendMode::External => {
+                let elasticsearch_url = std::env::var("ELASTICSEARCH_URL")
+                    .unwrap_or_else(|_| "http://localhost:9200".to_string());
+                let dgraph_url =
+                    std::env::var("DGRAPH_URL").unwrap_or_else(|_| "http://localhost:9080".to_string());
+                match Self::connect(elasticsearch_url, dgraph_url, PROBE_TIMEOUT).await {
+                    Ok(backends) => Ok(Some(backends)),
+                    Err(e) => {
+                        eprintln!("Skipping: backends not available ({:#})", e);
+                        Ok(None)
+                    }
+                }
+            }
+        }
+    }
+
+    /// `start` with the mode from the environment, for tests that only need the stores
+    pub async fn from_env() -> anyhow::Result<Option<Self>> {
+        Self::start(BackendMode::from_env()).await
+    }
+
+    #[cfg(feature = "containers")]
+    async fn start_containers() -> anyhow::Result<Self> {
+        use testcontainers::core::IntoContainerPort;
+        use testcontainers::runners::AsyncRunner;
+        use testcontainers::{GenericImage, ImageExt};
+
+        let elasticsearch = GenericImage::new(ELASTICSEARCH_IMAGE.0, ELASTICSEARCH_IMAGE.1)
+            .with_exposed_port(9200.tcp())
+            .with_env_var("discovery.type", "single-node")
+            .with_env_var("xpack.security.enabled", "false")
+            .with_env_var("ES_JAVA_OPTS", "-Xms512m -Xmx512m")
+            .start()
+            .await
+            .context("starting Elasticsearch container (is Docker running? use --no-containers to skip)")?;
+        let dgraph = GenericImage::new(DGRAPH_IMAGE.0, DGRAPH_IMAGE.1)
+            .with_exposed_port(9080.tcp())
+            .start()
+            .await
+            .context("starting Dgraph container")?;
+
+        let elasticsearch_url = format!(
+            "http://{}:{}",
+            elasticsearch.get_host().await?,
+            elasticsearch.get_host_port_ipv4(9200.tcp()).await?
+        );
+        let dgraph_url = format!("http://{}:{}", dgraph.get_host().await?, dgraph.get_host_port_ipv4(9080.tcp()).await?);
+        let mut backends = Self::connect(elasticsearch_url, dgraph_url, STARTUP_TIMEOUT).await?;
+        backends._containers = vec![elasticsearch, dgraph];
+        Ok(backends)
+    }
+
+    #[cfg(not(feature = "containers"))]
+    async fn start_containers() -> anyhow::Result<Self> {
+        bail!("built without the `containers` feature; run with {}", NO_CONTAINERS_FLAG)
+    }
+
+    /// Wait for both backends to answer. Indices get a per-run prefix so runs against shared
+    /// backends do not see each other's objects.
+    async fn connect(elasticsearch_url: String, dgraph_url: String, timeout: Duration) -> anyhow::Result<Self> {
+        let health = format!("{}/_cluster/health?wait_for_status=yellow&timeout=1s", elasticsearch_url);
+        wait_until("Elasticsearch", timeout, || {
+            let health = health.clone();
+            async move {
+                let response = reqwest::get(&health).await.map_err(|e| e.to_string())?;
+                if response.status().is_success() {
+                    Ok(())
+                } else {
+                    Err(format!("cluster health returned {}", response.status()))
+                }
+            }
+        })
+        .await?;
+        let prefix = format!("it_{}", uuid::Uuid::new_v4().simple());
+        let search = ElasticsearchStore::new(elasticsearch_url.clone())?.with_index_prefix(prefix);
+
+        let graph = wait_until("Dgraph", timeout, || {
+            let dgraph_url = dgraph_url.clone();
+            async move {
+                let store = DgraphStore::new(dgraph_url).await.map_err(|e| e.to_string())?;
+                store.init_schema(&[]).await.map_err(|e| e.to_string())?;
+                Ok(store)
+            }
+        })
+        .await?;
+
+        Ok(Self {
+            elasticsearch_url,
+            dgraph_url,
+            search: Arc::new(search),
+            graph: Arc::new(graph),
+            #[cfg(feature = "containers")]
+            _containers: Vec::new(),
+        })
+    }
+
+    /// Elasticsearch mappings for every object type and Dgraph predicates for every link
+    /// type of the ontology
+    pub async fn init_schemas(&self, ontology: &Ontology) -> Result<(), StoreError> {
+        for object_type in ontology.object_types() {
+            self.search.put_object_type_mapping(object_type).await?;
+        }
+        let link_type_ids: Vec<String> = ontology.link_types().map(|l| l.id.clone()).collect();
+        self.graph.init_schema(&link_type_ids).await?;
+        Ok(())
+    }
+
+    /// Make everything indexed so far visible to searches
+    pub async fn refresh(&self) -> anyhow::Result<()> {
+        let response = reqwest::Client::new()
+            .post(format!("{}/_refresh", self.elasticsearch_url))
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            bail!("refresh returned {}", response.status());
+        }
+        Ok(())
+    }
+}
+
+/// Retry `attempt` every half second until it succeeds or `timeout` passes
+async fn wait_until<T, F, Fut>(what: &str, timeout: Duration, mut attempt: F) -> anyhow::Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, String>>,
+{
+    let deadline = Instant::now() + timeout;
+    loop {
+        match attempt().await {
+            Ok(value) => return Ok(value),
+            Err(e) if Instant::now() >= deadline => bail!("{} not ready after {:?}: {}", what, timeout, e),
+            Err(_) => tokio::time::sleep(Duration::from_millis(500)).await,
+        }
+    }
+}
@@ -0,0 +1,53 @@
+//! The fixture ontology and dataset, and a GraphQL schema serving them from real backends.
+
+use crate::backends::Backends;
+use anyhow::bail;
+use async_graphql::{EmptySubscription, Schema};
+use data_loader::{DataLoader, IngestReport};
+use graphql_api::{AdminMutations, QueryRoot};
+use indexing::hydration::ObjectHydrator;
+use indexing::store::{ColumnarStore, GraphStore, SearchStore};
+use indexing::InMemoryColumnarStore;
+use ontology_engine::{Ontology, OntologyHandle};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use versioning::event_log::EventLog;
+use versioning::time_query::TimeQuery;
+
+pub type TestSchema = Schema<QueryRoot, AdminMutations, EmptySubscription>;
+
+/// Directory holding `ontology.yaml` and the `data/` files the data loader reads
+pub fn fixture_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures")
+}
+
+/// Load and validate the fixture ontology
+pub fn fixture_ontology() -> anyhow::Result<Ontology> {
+    let yaml = std::fs::read_to_string(fixture_dir().join("ontology.yaml"))?;
+    Ok(Ontology::from_yaml(&yaml)?)
+}
+
+/// Ingest the fixture dataset into the backends through the data loader and make it
+/// searchable. Returns the load report and the events it recorded.
+pub async fn load_fixture_data(backends: &Backends, ontology: Arc<Ontology>) -> anyhow::Result<(IngestReport, EventLog)> {
+    let mut loader = DataLoader::new(ontology, backends.search.clone(), backends.graph.clone());
+    let report = loader.load_dir(&fixture_dir().join("data")).await?;
+    if report.has_failures() {
+        bail!("fixture data did not load cleanly:\n{}", report);
+    }
+    backends.refresh().await?;
+    Ok((report, loader.into_event_log()))
+}
+
+/// GraphQL schema over the backends. Temporal queries read `event_log`; analytics use an
+/// in-memory columnar store.
+pub fn graphql_schema(backends: &Backends, ontology: OntologyHandle, event_log: EventLog) -> TestSchema {
+    Schema::build(QueryRoot::default(), AdminMutations::default(), EmptySubscription)
+        .data(ontology)
+        .data(backends.search.clone() as Arc<dyn SearchStore>)
+        .data(backends.graph.clone() as Arc<dyn GraphStore>)
+        .data(Arc::new(InMemoryColumnarStore::new()) as Arc<dyn ColumnarStore>)
+        .data(Arc::new(TimeQuery::new(event_log)))
+        .data(ObjectHydrator::new())
+        .finish()
+}
@@ -0,0 +1,23 @@
+//! Integration tests against real Elasticsearch and Dgraph backends.
+//!
+//! `Backends::start` gives a test its backends:
+//! - by default each run starts its own Elasticsearch and Dgraph containers (the
+//!   `containers` feature; needs Docker) and fails if they cannot be started
+//! - with `--no-containers` (or `ONTOLOGY_TEST_NO_CONTAINERS=1`) it connects to
+//!   `ELASTICSEARCH_URL` / `DGRAPH_URL` and returns `None` when they are not reachable, so
+//!   the test skips as the ignored store tests do
+//!
+//! `fixture_ontology`, `Backends::init_schemas` and `load_fixture_data` set up the fixture
+//! ontology and ingest its dataset; `graphql_schema` serves it. The scripted end-to-end
+//! scenario is `tests/scenario.rs`:
+//!
+//! ```text
+//! cargo test -p integration-tests --test scenario
+//! cargo test -p integration-tests --test scenario -- --no-containers
+//! ```
+
+pub mod backends;
+pub mod fixture;
+
+pub use backends::{BackendMode, Backends, NO_CONTAINERS_ENV, NO_CONTAINERS_FLAG};
+pub use fixture::{fixture_dir, fixture_ontology, graphql_schema, load_fixture_data, TestSchema};
@@ -0,0 +1,161 @@
+//! End-to-end scenario against real backends: load the fixture ontology, ingest the fixture
+//! dataset, query it over GraphQL, run an action, write its edits back and check the
+//! results and the event log.
+//!
+//! Starts Elasticsearch and Dgraph containers unless run with `--no-containers` (or
+//! `ONTOLOGY_TEST_NO_CONTAINERS=1`), in which case it uses `ELASTICSEARCH_URL` /
+//! `DGRAPH_URL` and skips when they are not running.
+
+use chrono::Utc;
+use integration_tests::{fixture_ontology, graphql_schema, load_fixture_data, Backends, TestSchema};
+use ontology_engine::action::OperationType;
+use ontology_engine::action_executor::ActionExecutor;
+use ontology_engine::validation::ActionContext;
+use ontology_engine::{Action, OntologyHandle, PropertyMap, PropertyValue};
+use indexing::store::SearchStore;
+use serde_json::Value;
+use std::process::ExitCode;
+use std::sync::{Arc, Mutex};
+use versioning::event_log::EventType;
+use writeback::{ConflictResolutionStrategy, UserEdit};
+
+fn main() -> ExitCode {
+    let runtime = tokio::runtime::Runtime::new().expect("tokio runtime");
+    runtime.block_on(async {
+        let backends = match Backends::from_env().await {
+            Ok(Some(backends)) => backends,
+            Ok(None) => return ExitCode::SUCCESS,
+            Err(e) => {
+                eprintln!("scenario: {:#}", e);
+                return ExitCode::FAILURE;
+            }
+        };
+        scenario(&backends).await;
+        println!("scenario: ok");
+        ExitCode::SUCCESS
+    })
+}
+
+async fn scenario(backends: &Backends) {
+    // Compile: the fixture ontology loads without errors
+    let handle = OntologyHandle::new(fixture_ontology().expect("fixture ontology"));
+    let ontology = handle.load();
+    backends.init_schemas(&ontology).await.expect("schemas");
+
+    // Ingest
+    let (report, mut event_log) = load_fixture_data(backends, ontology.clone()).await.expect("fixture data");
+    assert_eq!(report.objects_loaded(), 6);
+    assert_eq!(report.links_created, 4);
+    assert_eq!(backends.search.count_objects("plant", None).await.unwrap(), 4);
+
+    // Action: its object updates are captured and written back as user edits
+    let updates: Arc<Mutex<Vec<(String, PropertyMap)>>> = Arc::default();
+    let mut executor = ActionExecutor::new();
+    let captured = updates.clone();
+    executor.object_operation_handler = Some(Box::new(move |operation, object_type, properties| {
+        if let (OperationType::UpdateObject, Some(properties)) = (operation, properties) {
+            captured.lock().unwrap().push((object_type.to_string(), properties.clone()));
+        }
+        Ok(format!("{:?} {}", operation, object_type))
+    }));
+    let mut parameters = PropertyMap::new();
+    parameters.insert("plant_id".to_string(), PropertyValue::String("p2".to_string()));
+    let action = Action::new("retire_plant".to_string(), parameters, "tester".to_string());
+    let action_type = ontology.get_action_type("retire_plant").unwrap();
+    let result = executor.execute(&action, action_type, &ActionContext::new("tester".to_string())).unwrap();
+    assert!(result.success, "{:?}", result.errors);
+
+    let updates = std::mem::take(&mut *updates.lock().unwrap());
+    assert_eq!(updates.len(), 1);
+    for (object_type, properties) in updates {
+        let object_type_def = ontology.get_object_type(&object_type).unwrap();
+        let object_id = properties.get(&object_type_def.primary_key).unwrap().to_string();
+        let mut changed = PropertyMap::new();
+        let edits: Vec<UserEdit> = properties
+            .iter()
+            .filter(|(property, _)| **property != object_type_def.primary_key)
+            .map(|(property, value)| {
+                changed.insert(property.clone(), value.clone());
+                UserEdit {
+                    edit_id: uuid::Uuid::new_v4().to_string(),
+                    object_type: object_type.clone(),
+                    object_id: object_id.clone(),
+                    property_name: property.clone(),
+                    property_value: value.clone(),
+                    user_id: "tester".to_string(),
+                    timestamp: Utc::now(),
+                    deleted: false,
+                    base_value: None,
+                    comment: None,
+                    status: writeback::EditStatus::Pending,
+                    resolved_at: None,
+                }
+            })
+            .collect();
+        let applied = writeback::apply_edits(backends.search.as_ref(), object_type_def, &object_id, &edits, ConflictResolutionStrategy::EditWins)
+            .await
+            .unwrap();
+        assert!(applied.revision > 1);
+        event_log.record_updated(object_type, object_id, changed, Some("tester".to_string()));
+    }
+    backends.refresh().await.unwrap();
+
+    let events = event_log.get_events_for_object("plant", "p2");
+    assert_eq!(events.len(), 2);
+    assert!(matches!(&events[1].event_type, EventType::ObjectUpdated { changed_properties, .. }
+        if changed_properties.get("status") == Some(&PropertyValue::String("retired".to_string()))));
+
+    // Query over GraphQL, with temporal queries reading the recorded events
+    let schema = graphql_schema(backends, handle, event_log);
+
+    let gas = query(&schema, r#"{ searchObjects(objectType: "plant", filters: [{ property: "fuel", operator: "equals", value: "\"gas\"" }]) { objectId } }"#).await;
+    assert_eq!(sorted_ids(&gas["searchObjects"]), vec!["p1", "p4"]);
+    let largest = query(
+        &schema,
+        r#"{ searchObjects(objectType: "plant", filters: [{ property: "capacity_mw", operator: "gte", value: "300" }], sort: [{ property: "capacity_mw", ascending: false }], limit: 2) { objectId title } }"#,
+    )
+    .await;
+    let largest: Vec<&str> = largest["searchObjects"].as_array().unwrap().iter().map(|o| o["objectId"].as_str().unwrap()).collect();
+    assert_eq!(largest, vec!["p2", "p1"]);
+    let count = query(&schema, r#"{ countObjects(objectType: "plant", filters: [{ property: "year", operator: "equals", value: "2010" }]) }"#).await;
+    assert_eq!(count["countObjects"], 2);
+
+    // Traversal
+    let traversal = query(&schema, r#"{ traverseGraph(objectType: "plant", objectId: "p1", linkTypes: ["located_in"], maxHops: 1) { objectIds } }"#).await;
+    assert_eq!(traversal["traverseGraph"]["objectIds"], serde_json::json!(["NE"]));
+
+    // Aggregation, answered by the search backend
+    let aggregate = query(
+        &schema,
+        r#"{ aggregateObjects(objectType: "plant", aggregations: [{ property: "capacity_mw", operation: "sum" }], approximate: true) { rows } }"#,
+    )
+    .await;
+    assert_eq!(aggregate["aggregateObjects"]["rows"][0]["sum_capacity_mw"].as_f64(), Some(1480.0));
+
+    // Temporal: p2 was retired after the load but its 2010 plants are unchanged
+    let temporal = query(&schema, r#"{ temporalQuery(objectType: "plant", year: 2010) { objectId } }"#).await;
+    assert_eq!(sorted_ids(&temporal["temporalQuery"]), vec!["p1", "p3"]);
+
+    // The write-back replaced only the edited property
+    let retired = query(&schema, r#"{ searchObjects(objectType: "plant", filters: [{ property: "status", operator: "equals", value: "\"retired\"" }]) { objectId } }"#).await;
+    assert_eq!(sorted_ids(&retired["searchObjects"]), vec!["p2"]);
+    let p2 = backends.search.get_object("plant", "p2").await.unwrap().unwrap();
+    assert_eq!(p2.properties.get("name"), Some(&PropertyValue::String("Ridge Line".to_string())));
+}
+
+async fn query(schema: &TestSchema, query: &str) -> Value {
+    let response = schema.execute(query).await;
+    assert!(response.errors.is_empty(), "{}: {:?}", query, response.errors);
+    response.data.into_json().unwrap()
+}
+
+fn sorted_ids(results: &Value) -> Vec<String> {
+    let mut ids: Vec<String> = results
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|o| o["objectId"].as_str().unwrap().to_string())
+        .collect();
+    ids.sort();
+    ids
+}
@@ -0,0 +1,134 @@
+//! Focused store tests on real backends. They follow the harness's backend mode; under
+//! plain `cargo test` set `ONTOLOGY_TEST_NO_CONTAINERS=1` to use running backends instead
+//! of Docker.
+
+use indexing::store::{GraphStore, LinkDirection, LinkQuery, SearchStore, StoreError};
+use integration_tests::{fixture_ontology, graphql_schema, Backends};
+use ontology_engine::{OntologyHandle, PropertyMap, PropertyValue};
+use versioning::event_log::EventLog;
+
+#[tokio::test]
+async fn test_conditional_index_conflicts_on_stale_revision() {
+    let Some(backends) = Backends::from_env().await.unwrap() else { return };
+    backends.init_schemas(&fixture_ontology().unwrap()).await.unwrap();
+
+    let mut properties = PropertyMap::new();
+    properties.insert("code".to_string(), PropertyValue::String("NW".to_string()));
+    properties.insert("name".to_string(), PropertyValue::String("Northwest".to_string()));
+    let revision = backends.search.index_object("region", "NW", &properties, Some(0)).await.unwrap();
+
+    properties.insert("name".to_string(), PropertyValue::String("Pacific Northwest".to_string()));
+    let updated = backends.search.index_object("region", "NW", &properties, Some(revision)).await.unwrap();
+    assert!(updated > revision);
+
+    let stale = backends.search.index_object("region", "NW", &properties, Some(revision)).await;
+    assert!(matches!(stale, Err(StoreError::Conflict(_))), "{:?}", stale);
+}
+
+#[tokio::test]
+async fn test_links_are_traversed_in_both_directions() {
+    let Some(backends) = Backends::from_env().await.unwrap() else { return };
+    backends.init_schemas(&fixture_ontology().unwrap()).await.unwrap();
+
+    // The graph is shared between runs against the same backend, so the ids are unique
+    let (plant, region) = (uuid::Uuid::new_v4().to_string(), uuid::Uuid::new_v4().to_string());
+    backends.graph.create_link("located_in", &plant, &region, &PropertyMap::new()).await.unwrap();
+    let located_in = vec!["located_in".to_string()];
+    assert_eq!(backends.graph.traverse(&plant, &located_in, 1).await.unwrap(), vec![region.clone()]);
+
+    let incoming = backends
+        .graph
+        .get_links(&region, Some("located_in"), Some(LinkDirection::Incoming), &LinkQuery::default())
+        .await
+        .unwrap();
+    assert_eq!(incoming.len(), 1);
+    assert_eq!(incoming[0].source_id, plant);
+}
+
+#[tokio::test]
+async fn test_get_links_by_direction_across_link_types() {
+    let Some(backends) = Backends::from_env().await.unwrap() else { return };
+    backends.init_schemas(&fixture_ontology().unwrap()).await.unwrap();
+    backends.graph.register_link_predicate("supplies").await.unwrap();
+
+    // `plant` has one outgoing link (to its region) and one incoming (from its supplier)
+    let (plant, region, supplier) = (
+        uuid::Uuid::new_v4().to_string(),
+        uuid::Uuid::new_v4().to_string(),
+        uuid::Uuid::new_v4().to_string(),
+    );
+    let mut properties = PropertyMap::new();
+    properties.insert("share".to_string(), PropertyValue::String("0.4".to_string()));
+    let located_in = backends.graph.create_link("located_in", &plant, &region, &PropertyMap::new()).await.unwrap();
+    let supplies = backends.graph.create_link("supplies", &supplier, &plant, &properties).await.unwrap();
+
+    let links = |link_type: Option<&'static str>, direction: LinkDirection| {
+        let (graph, plant) = (backends.graph.clone(), plant.clone());
+        async move {
+            let mut links = graph.get_links(&plant, link_type, Some(direction), &LinkQuery::default()).await.unwrap();
+            links.sort_by(|a, b| a.link_type_id.cmp(&b.link_type_id));
+            links
+        }
+    };
+
+    let outgoing = links(None, LinkDirection::Outgoing).await;
+    assert_eq!(outgoing.len(), 1);
+    assert_eq!((outgoing[0].link_id.as_str(), outgoing[0].target_id.as_str()), (located_in.as_str(), region.as_str()));
+
+    let incoming = links(None, LinkDirection::Incoming).await;
+    assert_eq!(incoming.len(), 1);
+    assert_eq!((incoming[0].link_id.as_str(), incoming[0].source_id.as_str()), (supplies.as_str(), supplier.as_str()));
+    assert_eq!(incoming[0].properties.get("share"), Some(&PropertyValue::String("0.4".to_string())));
+
+    let both = links(None, LinkDirection::Both).await;
+    let ids: Vec<&str> = both.iter().map(|l| l.link_id.as_str()).collect();
+    assert_eq!(ids, vec![located_in.as_str(), supplies.as_str()]);
+
+    // Naming a link type reads only its edges, in whichever directions were asked for
+    let named = links(Some("supplies"), LinkDirection::Both).await;
+    assert_eq!(named.len(), 1);
+    assert_eq!(named[0].target_id, plant);
+    assert!(links(Some("supplies"), LinkDirection::Outgoing).await.is_empty());
+}
+
+#[tokio::test]
+async fn test_traverse_graph_aggregates_node_properties() {
+    let Some(backends) = Backends::from_env().await.unwrap() else { return };
+    let handle = OntologyHandle::new(fixture_ontology().unwrap());
+    backends.init_schemas(&handle.load()).await.unwrap();
+    backends.graph.register_link_predicate("supplies").await.unwrap();
+
+    // A supplier feeding two plants, whose capacities are stored on their nodes
+    let supplier = uuid::Uuid::new_v4().to_string();
+    for capacity in [120.5, 80.0] {
+        let plant = uuid::Uuid::new_v4().to_string();
+        backends.graph.create_link("supplies", &supplier, &plant, &PropertyMap::new()).await.unwrap();
+        let mut properties = PropertyMap::new();
+        properties.insert("capacity_mw".to_string(), PropertyValue::Double(capacity));
+        properties.insert("year".to_string(), PropertyValue::Integer(2000));
+        backends.graph.set_node_properties(&plant, &properties).await.unwrap();
+    }
+
+    let schema = graphql_schema(&backends, handle, EventLog::new());
+    let traverse = |property: &str, operation: &str| {
+        format!(
+            r#"{{ traverseGraph(objectType: "supplier", objectId: "{}", linkTypes: ["supplies"], maxHops: 1, aggregateProperty: "{}", aggregateOperation: "{}") {{ aggregatedValue count }} }}"#,
+            supplier, property, operation
+        )
+    };
+
+    let sum = schema.execute(traverse("capacity_mw", "sum")).await;
+    assert!(sum.errors.is_empty(), "{:?}", sum.errors);
+    let sum = sum.data.into_json().unwrap();
+    assert_eq!(sum["traverseGraph"]["aggregatedValue"], 200.5);
+    assert_eq!(sum["traverseGraph"]["count"], 2);
+
+    // Integer properties stay integers
+    let max = schema.execute(traverse("year", "max")).await.data.into_json().unwrap();
+    assert_eq!(max["traverseGraph"]["aggregatedValue"], 2000);
+
+    // No traversed object with the property is an error rather than zero
+    let missing = schema.execute(traverse("output_mwh", "sum")).await;
+    assert_eq!(missing.errors.len(), 1);
+    assert!(missing.errors[0].message.contains("No data"), "{}", missing.errors[0].message);
+}
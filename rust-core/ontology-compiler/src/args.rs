@@ -1,4 +1,4 @@
-use clap::Parser;
+use clap::{Parser, Subcommand, ValueEnum};
 use std::path::PathBuf;
 
 #[derive(Parser, Debug)]
@@ -15,4 +15,92 @@ pub struct Args {
     /// Output JSON file
     #[arg(short, long, default_value = "ontology.json")]
     pub output: PathBuf,
+
+    /// Overlay file layered on the compiled ontology (e.g. per environment); repeat to apply
+    /// several in order
+    #[arg(long = "overlay")]
+    pub overlays: Vec<PathBuf>,
+
+    /// Only validate the compiled ontology and report every error; no output is written
+    #[arg(long)]
+    pub lint: bool,
+
+    /// Keep running and recompile whenever a .ttl file, the sidecar or an overlay changes
+    #[arg(long)]
+    pub watch: bool,
+
+    /// How long to wait for saves to settle before recompiling in watch mode
+    #[arg(long, default_value_t = 300)]
+    pub debounce_ms: u64,
+
+    /// GraphQL endpoint of a running server (e.g. `http://localhost:8080/graphql`) to send each
+    /// recompiled ontology to through the `reloadOntology` mutation; watch mode only
+    #[arg(long, requires = "watch")]
+    pub push_to: Option<String>,
+
+    /// Also write a JSON Schema per object type (`<type>.schema.json`) into this directory
+    #[arg(long, value_name = "DIR")]
+    pub emit_json_schema: Option<PathBuf>,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Render a reference site (index, one page per type, diagrams) from a compiled ontology
+    Docs(DocsArgs),
+    /// List the changes between two compiled ontologies, each with its overlays applied
+    Diff(DiffArgs),
+}
+
+#[derive(clap::Args, Debug)]
+pub struct DiffArgs {
+    /// Ontology before the change (JSON or YAML)
+    pub old: PathBuf,
+
+    /// Ontology after the change (JSON or YAML)
+    pub new: PathBuf,
+
+    /// Overlay applied to the old ontology; repeat to apply several in order
+    #[arg(long = "old-overlay")]
+    pub old_overlays: Vec<PathBuf>,
+
+    /// Overlay applied to the new ontology; repeat to apply several in order
+    #[arg(long = "new-overlay")]
+    pub new_overlays: Vec<PathBuf>,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct DocsArgs {
+    /// Compiled ontology file (JSON or YAML)
+    #[arg(short, long, default_value = "ontology.json")]
+    pub input: PathBuf,
+
+    /// Directory the site is written to
+    #[arg(short, long, default_value = "ontology-docs")]
+    pub output: PathBuf,
+
+    /// Output format
+    #[arg(short, long, value_enum, default_value_t = DocsFormat::Html)]
+    pub format: DocsFormat,
+
+    /// Use display names for this locale (e.g. `fr`, `pt-BR`) where the ontology defines them
+    #[arg(long)]
+    pub locale: Option<String>,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DocsFormat {
+    Html,
+    Markdown,
+}
+
+impl DocsFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            DocsFormat::Html => "html",
+            DocsFormat::Markdown => "md",
+        }
+    }
 }
@@ -1,14 +1,47 @@
 use anyhow::{Context, Result};
-use oxigraph::model::{NamedNode, NamedNodeRef, Term, Literal, Subject, SubjectRef, GraphNameRef};
+use oxigraph::io::{GraphFormat, GraphParser};
+use oxigraph::model::{NamedNode, NamedNodeRef, Term, Literal, Subject, SubjectRef, GraphNameRef, Triple};
 use oxigraph::store::Store;
 use ontology_engine::{
-    ObjectType, Property, PropertyType, LinkTypeDef, LinkCardinality,
-    OntologyDef, InterfaceDef
+    ObjectType, DefaultSort, Property, PropertyType, LinkTypeDef, LinkCardinality, CascadeDeleteBehavior,
+    OntologyDef, InterfaceDef, IndexingHint
 };
 use std::collections::HashMap;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::fs;
 
+/// The `.ttl` files directly inside `dir`, sorted by path
+pub fn ttl_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    if !dir.exists() {
+        return Err(anyhow::anyhow!("Directory not found: {:?}", dir));
+    }
+
+    let mut paths = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if is_ttl_file(&path) {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+    Ok(paths)
+}
+
+pub fn is_ttl_file(path: &Path) -> bool {
+    path.extension().map_or(false, |ext| ext == "ttl")
+}
+
+/// Parse one Turtle file without loading it into a store
+pub fn parse_ttl_file(path: &Path) -> Result<Vec<Triple>> {
+    let file = fs::File::open(path).with_context(|| format!("Failed to open {:?}", path))?;
+    let triples = GraphParser::from_format(GraphFormat::Turtle)
+        .read_triples(std::io::BufReader::new(file))
+        .map_err(|e| anyhow::anyhow!("Failed to load {:?}: {}", path, e))?;
+    triples
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| anyhow::anyhow!("Failed to load {:?}: {}", path, e))
+}
+
 // Namespaces
 const OWL: &str = "http://www.w3.org/2002/07/owl#";
 const RDFS: &str = "http://www.w3.org/2000/01/rdf-schema#";
@@ -28,19 +61,17 @@ impl Compiler {
     }
 
     pub fn load_ttl_files(&self, dir: &Path) -> Result<()> {
-        if !dir.exists() {
-            return Err(anyhow::anyhow!("Directory not found: {:?}", dir));
+        for path in ttl_files(dir)? {
+            println!("Loading {:?}", path);
+            self.load_triples(&parse_ttl_file(&path)?)?;
         }
+        Ok(())
+    }
 
-        for entry in fs::read_dir(dir)? {
-            let entry = entry?;
-            let path = entry.path();
-            if path.extension().map_or(false, |ext| ext == "ttl") {
-                println!("Loading {:?}", path);
-                let file = fs::File::open(&path)?;
-                self.store.load_graph(std::io::BufReader::new(file), oxigraph::io::GraphFormat::Turtle, GraphNameRef::DefaultGraph, None)
-                    .map_err(|e| anyhow::anyhow!("Failed to load {:?}: {}", path, e))?;
-            }
+    /// Add already parsed triples to the default graph
+    pub fn load_triples(&self, triples: &[Triple]) -> Result<()> {
+        for triple in triples {
+            self.store.insert(triple.as_ref().in_graph(GraphNameRef::DefaultGraph))?;
         }
         Ok(())
     }
@@ -57,6 +88,7 @@ impl Compiler {
             interfaces,
             function_types: vec![], // Will be filled from sidecar
             model_objectives: vec![],
+            interface_compatibility: Default::default(),
         })
     }
 
@@ -136,15 +168,30 @@ impl Compiler {
         implements.sort();
         implements.dedup();
 
+        // Default Sort
+        let sort_prop = NamedNode::new(format!("{}defaultSort", SYS)).unwrap();
+        let default_sort = self.get_object_literal(subject, &sort_prop)
+            .map(|v| DefaultSort::parse(&v))
+            .transpose()
+            .map_err(|e| anyhow::anyhow!("Invalid sys:defaultSort for {}: {}", id, e))?;
+
         Ok(ObjectType {
             schema_evolution: None,
             id,
             display_name,
+            display_names: HashMap::new(),
             primary_key,
             properties,
             backing_datasource,
             title_key,
             implements,
+            interface_mappings: HashMap::new(),
+            default_sort,
+            computed_properties: Vec::new(),
+            dedup_rules: None,
+            archival_policy: None,
+            strict_properties: false,
+            property_groups: Vec::new(),
         })
     }
 
@@ -172,8 +219,10 @@ impl Compiler {
                          interfaces.push(InterfaceDef {
                              id: name.clone(),
                              display_name: self.get_label(&subject).unwrap_or(name),
+                             display_names: HashMap::new(),
                              properties: self.get_properties_for_domain(&subject)?,
                              required_link_types: vec![], // Not implemented in MVP
+                             extends: vec![],
                          });
                      }
                 }
@@ -212,11 +261,13 @@ impl Compiler {
                 links.push(LinkTypeDef {
                     id,
                     display_name,
+                    display_names: HashMap::new(),
                     source,
                     target,
                     cardinality: LinkCardinality::OneToMany, // Default, hard to infer from standard OWL without constraints
                     properties: vec![], // Link properties not in MVP TTL
                     bidirectional,
+                    on_delete: CascadeDeleteBehavior::NoAction,
                 });
             }
         }
@@ -252,9 +303,22 @@ impl Compiler {
                      let unit_prop = NamedNode::new(format!("{}unit", SYS)).unwrap();
                      let unit = self.get_object_literal(&prop_subject, &unit_prop);
 
+                     let order_prop = NamedNode::new(format!("{}displayOrder", SYS)).unwrap();
+                     let display_order = self.get_object_literal(&prop_subject, &order_prop)
+                         .map(|v| v.parse::<u32>()
+                             .map_err(|_| anyhow::anyhow!("Invalid sys:displayOrder '{}' for property {}", v, id)))
+                         .transpose()?;
+
+                     let indexing_prop = NamedNode::new(format!("{}indexing", SYS)).unwrap();
+                     let indexing = self.get_object_literal(&prop_subject, &indexing_prop)
+                         .map(|v| IndexingHint::parse(&v)
+                             .ok_or_else(|| anyhow::anyhow!("Invalid sys:indexing '{}' for property {}", v, id)))
+                         .transpose()?;
+
                      properties.push(Property {
                          id,
                          display_name: self.get_label(&prop_subject),
+                         display_names: HashMap::new(),
                          property_type,
                          required: false, // Default to false for MVP
                          default: None,
@@ -268,6 +332,11 @@ impl Compiler {
                          deprecated: None,
                          statistics: None,
                          model_binding: None,
+                         display_order,
+                         indexing,
+                         reference_target: None,
+                         geo: None,
+                         custom_validator: None,
                      });
                  }
              }
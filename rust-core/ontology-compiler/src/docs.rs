@@ -0,0 +1,814 @@
+//! Reference documentation for a compiled ontology.
+//!
+//! Pages are built once as a small block tree and rendered to either a static HTML site or a
+//! Markdown tree: an index (with search, in HTML) listing every definition, one page per
+//! object type and interface, and Mermaid diagrams of the type graph. A Graphviz rendering of
+//! the whole graph is written alongside as `ontology.dot`.
+
+use anyhow::{Context, Result};
+use ontology_engine::property::PropertyValidation;
+use ontology_engine::{
+    ActionTypeDef, FunctionLogic, FunctionReturnType, FunctionTypeDef, InterfaceDef, LinkCardinality,
+    LinkTypeDef, ObjectType, OntologyDef, Property, PropertyType,
+};
+use std::fs;
+use std::path::Path;
+
+use crate::args::DocsFormat;
+
+/// A documentation page; `path` is relative to the site root, without extension
+pub struct Page {
+    pub path: String,
+    pub title: String,
+    blocks: Vec<Block>,
+}
+
+enum Block {
+    Heading(String),
+    Paragraph(Vec<Inline>),
+    Table {
+        headers: Vec<&'static str>,
+        rows: Vec<Vec<Vec<Inline>>>,
+    },
+    /// Mermaid source
+    Diagram(String),
+    /// Filter box over the page's tables; HTML only
+    Search,
+}
+
+enum Inline {
+    Text(String),
+    Code(String),
+    /// Link to another page by its `Page::path`
+    Link { text: String, page: String },
+}
+
+fn text(value: impl Into<String>) -> Vec<Inline> {
+    vec![Inline::Text(value.into())]
+}
+
+fn code(value: impl Into<String>) -> Vec<Inline> {
+    vec![Inline::Code(value.into())]
+}
+
+fn link(value: impl Into<String>, page: String) -> Vec<Inline> {
+    vec![Inline::Link { text: value.into(), page }]
+}
+
+/// Comma-separated links
+fn links(items: impl Iterator<Item = (String, String)>) -> Vec<Inline> {
+    let mut inlines = Vec::new();
+    for (i, (value, page)) in items.enumerate() {
+        if i > 0 {
+            inlines.push(Inline::Text(", ".to_string()));
+        }
+        inlines.push(Inline::Link { text: value, page });
+    }
+    inlines
+}
+
+fn type_page(id: &str) -> String {
+    format!("types/{}", id)
+}
+
+fn interface_page(id: &str) -> String {
+    format!("interfaces/{}", id)
+}
+
+/// Render the site for `ontology` into `dir`, returning the number of pages written
+pub fn write_site(ontology: &OntologyDef, locale: Option<&str>, format: DocsFormat, dir: &Path) -> Result<usize> {
+    let pages = build_site(ontology, locale);
+    for page in &pages {
+        let path = dir.join(format!("{}.{}", page.path, format.extension()));
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).with_context(|| format!("Failed to create {:?}", parent))?;
+        }
+        fs::write(&path, render(page, format)).with_context(|| format!("Failed to write {:?}", path))?;
+    }
+    fs::write(dir.join("ontology.dot"), graphviz(ontology, locale)).context("Failed to write ontology.dot")?;
+    Ok(pages.len())
+}
+
+/// Build the index, object type and interface pages
+pub fn build_site(ontology: &OntologyDef, locale: Option<&str>) -> Vec<Page> {
+    let mut pages = vec![index_page(ontology, locale)];
+    pages.extend(ontology.object_types.iter().map(|object_type| object_type_page(ontology, object_type, locale)));
+    pages.extend(ontology.interfaces.iter().map(|interface| interface_page_for(ontology, interface, locale)));
+    pages
+}
+
+fn index_page(ontology: &OntologyDef, locale: Option<&str>) -> Page {
+    let object_name = |id: &str| object_type_name(ontology, id, locale);
+    let mut blocks = vec![
+        Block::Search,
+        Block::Paragraph(text(format!(
+            "{} object types, {} interfaces, {} link types, {} actions, {} functions.",
+            ontology.object_types.len(),
+            ontology.interfaces.len(),
+            ontology.link_types.len(),
+            ontology.action_types.len(),
+            ontology.function_types.len()
+        ))),
+        Block::Diagram(mermaid(ontology, None, locale)),
+        Block::Heading("Object types".to_string()),
+        Block::Table {
+            headers: vec!["Name", "ID", "Properties", "Implements"],
+            rows: ontology
+                .object_types
+                .iter()
+                .map(|object_type| {
+                    vec![
+                        link(object_type.display_name_for(locale), type_page(&object_type.id)),
+                        code(&object_type.id),
+                        text(object_type.properties.len().to_string()),
+                        interface_links(ontology, &object_type.implements, locale),
+                    ]
+                })
+                .collect(),
+        },
+    ];
+
+    if !ontology.interfaces.is_empty() {
+        blocks.push(Block::Heading("Interfaces".to_string()));
+        blocks.push(Block::Table {
+            headers: vec!["Name", "ID", "Implemented by"],
+            rows: ontology
+                .interfaces
+                .iter()
+                .map(|interface| {
+                    vec![
+                        link(interface.display_name_for(locale), interface_page(&interface.id)),
+                        code(&interface.id),
+                        links(implementers(ontology, &interface.id).map(|object_type| {
+                            (object_type.display_name_for(locale).to_string(), type_page(&object_type.id))
+                        })),
+                    ]
+                })
+                .collect(),
+        });
+    }
+
+    if !ontology.link_types.is_empty() {
+        blocks.push(Block::Heading("Link types".to_string()));
+        blocks.push(Block::Table {
+            headers: vec!["Name", "ID", "Source", "Target", "Cardinality"],
+            rows: ontology
+                .link_types
+                .iter()
+                .map(|link_type| {
+                    vec![
+                        text(link_type.display_name_for(locale)),
+                        code(&link_type.id),
+                        link(object_name(&link_type.source), type_page(&link_type.source)),
+                        link(object_name(&link_type.target), type_page(&link_type.target)),
+                        text(cardinality_label(link_type)),
+                    ]
+                })
+                .collect(),
+        });
+    }
+
+    if !ontology.action_types.is_empty() {
+        blocks.push(Block::Heading("Actions".to_string()));
+        blocks.push(Block::Table {
+            headers: vec!["Name", "ID", "Applies to"],
+            rows: ontology
+                .action_types
+                .iter()
+                .map(|action| {
+                    vec![
+                        text(action.display_name_for(locale)),
+                        code(&action.id),
+                        applies_to(ontology, action_targets(ontology, action), action.target_interface.as_deref(), locale),
+                    ]
+                })
+                .collect(),
+        });
+    }
+
+    if !ontology.function_types.is_empty() {
+        blocks.push(Block::Heading("Functions".to_string()));
+        blocks.push(Block::Table {
+            headers: vec!["Name", "ID", "Applies to", "Returns", "Description"],
+            rows: ontology
+                .function_types
+                .iter()
+                .map(|function| {
+                    vec![
+                        text(function.display_name_for(locale)),
+                        code(&function.id),
+                        applies_to(ontology, function_targets(ontology, function), function.target_interface.as_deref(), locale),
+                        code(return_type_label(&function.return_type)),
+                        text(function.description.clone().unwrap_or_default()),
+                    ]
+                })
+                .collect(),
+        });
+    }
+
+    Page {
+        path: "index".to_string(),
+        title: "Ontology reference".to_string(),
+        blocks,
+    }
+}
+
+fn object_type_page(ontology: &OntologyDef, object_type: &ObjectType, locale: Option<&str>) -> Page {
+    let mut summary = vec![Inline::Text("ID ".to_string()), Inline::Code(object_type.id.clone())];
+    summary.push(Inline::Text(" · primary key ".to_string()));
+    summary.push(Inline::Code(object_type.primary_key.clone()));
+    if let Some(title_key) = &object_type.title_key {
+        summary.push(Inline::Text(" · title key ".to_string()));
+        summary.push(Inline::Code(title_key.clone()));
+    }
+    if let Some(datasource) = &object_type.backing_datasource {
+        summary.push(Inline::Text(" · datasource ".to_string()));
+        summary.push(Inline::Code(datasource.clone()));
+    }
+    let mut blocks = vec![
+        Block::Paragraph(summary),
+        Block::Heading("Properties".to_string()),
+        properties_table(&object_type.properties, locale),
+    ];
+
+    if !object_type.implements.is_empty() {
+        blocks.push(Block::Heading("Interfaces".to_string()));
+        blocks.push(Block::Paragraph(interface_links(ontology, &object_type.implements, locale)));
+    }
+
+    let outgoing: Vec<&LinkTypeDef> = ontology.link_types.iter().filter(|l| l.source == object_type.id).collect();
+    if !outgoing.is_empty() {
+        blocks.push(Block::Heading("Outgoing links".to_string()));
+        blocks.push(links_table(ontology, &outgoing, "Target", |l| &l.target, locale));
+    }
+    let incoming: Vec<&LinkTypeDef> = ontology.link_types.iter().filter(|l| l.target == object_type.id).collect();
+    if !incoming.is_empty() {
+        blocks.push(Block::Heading("Incoming links".to_string()));
+        blocks.push(links_table(ontology, &incoming, "Source", |l| &l.source, locale));
+    }
+
+    let functions: Vec<&FunctionTypeDef> = ontology
+        .function_types
+        .iter()
+        .filter(|function| function_targets(ontology, function).contains(&object_type.id.as_str()))
+        .collect();
+    if !functions.is_empty() {
+        blocks.push(Block::Heading("Functions".to_string()));
+        blocks.push(functions_table(&functions, locale));
+    }
+    let actions: Vec<&ActionTypeDef> = ontology
+        .action_types
+        .iter()
+        .filter(|action| action_targets(ontology, action).contains(&object_type.id.as_str()))
+        .collect();
+    if !actions.is_empty() {
+        blocks.push(Block::Heading("Actions".to_string()));
+        blocks.push(actions_table(&actions, locale));
+    }
+
+    blocks.push(Block::Heading("Diagram".to_string()));
+    blocks.push(Block::Diagram(mermaid(ontology, Some(&object_type.id), locale)));
+
+    Page {
+        path: type_page(&object_type.id),
+        title: object_type.display_name_for(locale).to_string(),
+        blocks,
+    }
+}
+
+fn interface_page_for(ontology: &OntologyDef, interface: &InterfaceDef, locale: Option<&str>) -> Page {
+    let mut blocks = vec![
+        Block::Paragraph(vec![Inline::Text("Interface ".to_string()), Inline::Code(interface.id.clone())]),
+        Block::Heading("Properties".to_string()),
+        properties_table(&interface.properties, locale),
+        Block::Heading("Implemented by".to_string()),
+        Block::Paragraph(links(implementers(ontology, &interface.id).map(|object_type| {
+            (object_type.display_name_for(locale).to_string(), type_page(&object_type.id))
+        }))),
+    ];
+
+    let targets_interface = |target: &Option<String>| target.as_deref() == Some(interface.id.as_str());
+    let functions: Vec<&FunctionTypeDef> =
+        ontology.function_types.iter().filter(|f| targets_interface(&f.target_interface)).collect();
+    if !functions.is_empty() {
+        blocks.push(Block::Heading("Functions".to_string()));
+        blocks.push(functions_table(&functions, locale));
+    }
+    let actions: Vec<&ActionTypeDef> =
+        ontology.action_types.iter().filter(|a| targets_interface(&a.target_interface)).collect();
+    if !actions.is_empty() {
+        blocks.push(Block::Heading("Actions".to_string()));
+        blocks.push(actions_table(&actions, locale));
+    }
+
+    Page {
+        path: interface_page(&interface.id),
+        title: interface.display_name_for(locale).to_string(),
+        blocks,
+    }
+}
+
+fn properties_table(properties: &[Property], locale: Option<&str>) -> Block {
+    let mut ordered: Vec<&Property> = properties.iter().collect();
+    ordered.sort_by_key(|p| p.display_order.unwrap_or(u32::MAX));
+    Block::Table {
+        headers: vec!["Name", "ID", "Type", "Required", "Validation", "Unit", "Description", "Deprecated"],
+        rows: ordered
+            .into_iter()
+            .map(|property| {
+                vec![
+                    text(property.display_name_for(locale)),
+                    code(&property.id),
+                    code(type_label(&property.property_type)),
+                    text(if property.required { "yes" } else { "" }),
+                    text(property.validation.as_ref().map(validation_label).unwrap_or_default()),
+                    text(property.unit.clone().unwrap_or_default()),
+                    text(property.description.clone().unwrap_or_default()),
+                    text(
+                        property
+                            .deprecated
+                            .as_ref()
+                            .map(|deprecation| {
+                                let mut label = format!("since {}", deprecation.deprecated_since);
+                                if let Some(replacement) = &deprecation.replacement {
+                                    label.push_str(&format!("; use {}", replacement));
+                                }
+                                if let Some(removal) = &deprecation.removal_date {
+                                    label.push_str(&format!("; removed {}", removal));
+                                }
+                                label
+                            })
+                            .unwrap_or_default(),
+                    ),
+                ]
+            })
+            .collect(),
+    }
+}
+
+fn links_table<'a>(
+    ontology: &OntologyDef,
+    link_types: &[&'a LinkTypeDef],
+    other_end: &'static str,
+    end: impl Fn(&'a LinkTypeDef) -> &'a String,
+    locale: Option<&str>,
+) -> Block {
+    Block::Table {
+        headers: vec!["Name", "ID", other_end, "Cardinality"],
+        rows: link_types
+            .iter()
+            .map(|link_type| {
+                let other = end(link_type);
+                vec![
+                    text(link_type.display_name_for(locale)),
+                    code(&link_type.id),
+                    link(object_type_name(ontology, other, locale), type_page(other)),
+                    text(cardinality_label(link_type)),
+                ]
+            })
+            .collect(),
+    }
+}
+
+fn functions_table(functions: &[&FunctionTypeDef], locale: Option<&str>) -> Block {
+    Block::Table {
+        headers: vec!["Name", "ID", "Returns", "Description"],
+        rows: functions
+            .iter()
+            .map(|function| {
+                vec![
+                    text(function.display_name_for(locale)),
+                    code(&function.id),
+                    code(return_type_label(&function.return_type)),
+                    text(function.description.clone().unwrap_or_default()),
+                ]
+            })
+            .collect(),
+    }
+}
+
+fn actions_table(actions: &[&ActionTypeDef], locale: Option<&str>) -> Block {
+    Block::Table {
+        headers: vec!["Name", "ID", "Parameters"],
+        rows: actions
+            .iter()
+            .map(|action| {
+                vec![
+                    text(action.display_name_for(locale)),
+                    code(&action.id),
+                    text(action.parameters.iter().map(|p| p.id.as_str()).collect::<Vec<_>>().join(", ")),
+                ]
+            })
+            .collect(),
+    }
+}
+
+fn interface_links(ontology: &OntologyDef, ids: &[String], locale: Option<&str>) -> Vec<Inline> {
+    links(ids.iter().map(|id| {
+        let name = ontology
+            .interfaces
+            .iter()
+            .find(|interface| &interface.id == id)
+            .map(|interface| interface.display_name_for(locale))
+            .unwrap_or(id);
+        (name.to_string(), interface_page(id))
+    }))
+}
+
+fn applies_to(ontology: &OntologyDef, targets: Vec<&str>, interface: Option<&str>, locale: Option<&str>) -> Vec<Inline> {
+    match interface {
+        Some(interface) => interface_links(ontology, &[interface.to_string()], locale),
+        None => links(
+            targets
+                .into_iter()
+                .map(|id| (object_type_name(ontology, id, locale), type_page(id))),
+        ),
+    }
+}
+
+fn implementers<'a>(ontology: &'a OntologyDef, interface_id: &'a str) -> impl Iterator<Item = &'a ObjectType> {
+    ontology
+        .object_types
+        .iter()
+        .filter(move |object_type| object_type.implements.iter().any(|id| id == interface_id))
+}
+
+fn object_type_name(ontology: &OntologyDef, id: &str, locale: Option<&str>) -> String {
+    ontology
+        .object_types
+        .iter()
+        .find(|object_type| object_type.id == id)
+        .map(|object_type| object_type.display_name_for(locale))
+        .unwrap_or(id)
+        .to_string()
+}
+
+/// Object types an action operates on: its operations' types, or every implementer of its
+/// target interface
+fn action_targets<'a>(ontology: &'a OntologyDef, action: &'a ActionTypeDef) -> Vec<&'a str> {
+    let mut targets: Vec<&str> = match &action.target_interface {
+        Some(interface) => implementers(ontology, interface).map(|o| o.id.as_str()).collect(),
+        None => action.logic.iter().filter_map(|operation| operation.object_type.as_deref()).collect(),
+    };
+    targets.dedup();
+    targets
+}
+
+/// Object types a function is called on: implementers of its target interface, or the far
+/// end of the link its logic follows (the end that does not hold the traversed-to type or the
+/// aggregated property). Functions returning an object type are listed there too.
+fn function_targets<'a>(ontology: &'a OntologyDef, function: &'a FunctionTypeDef) -> Vec<&'a str> {
+    let link_ends = |link_type: &str| {
+        ontology
+            .link_types
+            .iter()
+            .find(|l| l.id == link_type)
+            .map(|l| (l.source.as_str(), l.target.as_str()))
+    };
+    let has_property = |object_type: &str, property: &str| {
+        ontology
+            .object_types
+            .iter()
+            .any(|o| o.id == object_type && o.get_property(property).is_some())
+    };
+
+    let mut targets: Vec<&str> = match &function.target_interface {
+        Some(interface) => implementers(ontology, interface).map(|o| o.id.as_str()).collect(),
+        None => logic_targets(&function.logic, &link_ends, &has_property),
+    };
+    if let FunctionReturnType::ObjectType { object_type } = &function.return_type {
+        if !targets.contains(&object_type.as_str()) {
+            targets.push(object_type);
+        }
+    }
+    targets
+}
+
+/// Object types a function's logic starts from; a pipeline starts where its first step does
+fn logic_targets<'a>(
+    logic: &FunctionLogic,
+    link_ends: &impl Fn(&str) -> Option<(&'a str, &'a str)>,
+    has_property: &impl Fn(&str, &str) -> bool,
+) -> Vec<&'a str> {
+    match logic {
+        FunctionLogic::LinkTraversal { link_type, target_type, .. } => link_ends(link_type)
+            .map(|(source, target)| if target == target_type { source } else { target })
+            .into_iter()
+            .collect(),
+        FunctionLogic::Aggregation { link_type: Some(link_type), property, .. } => link_ends(link_type)
+            .map(|(source, target)| {
+                if has_property(target, property) || !has_property(source, property) {
+                    source
+                } else {
+                    target
+                }
+            })
+            .into_iter()
+            .collect(),
+        FunctionLogic::Pipeline { steps } => steps
+            .first()
+            .map(|step| logic_targets(step, link_ends, has_property))
+            .unwrap_or_default(),
+        FunctionLogic::Aggregation { link_type: None, .. }
+        | FunctionLogic::PropertyAccess { .. }
+        | FunctionLogic::Filter { .. } => Vec::new(),
+    }
+}
+
+fn type_label(property_type: &PropertyType) -> String {
+    match property_type {
+        PropertyType::Array { element_type } => format!("array<{}>", type_label(element_type)),
+        PropertyType::Map { key_type, value_type } => {
+            format!("map<{}, {}>", type_label(key_type), type_label(value_type))
+        }
+        PropertyType::Object(struct_def) => format!("object {}", struct_def.id),
+        PropertyType::Union { types } => types.iter().map(type_label).collect::<Vec<_>>().join(" | "),
+        scalar => serde_json::to_value(scalar)
+            .ok()
+            .and_then(|value| value.as_str().map(str::to_string))
+            .unwrap_or_else(|| format!("{:?}", scalar)),
+    }
+}
+
+fn return_type_label(return_type: &FunctionReturnType) -> String {
+    match return_type {
+        FunctionReturnType::Property { property_type } => type_label(property_type),
+        FunctionReturnType::ObjectType { object_type } => object_type.clone(),
+        FunctionReturnType::Array { element_type } => format!("array<{}>", return_type_label(element_type)),
+    }
+}
+
+fn validation_label(validation: &PropertyValidation) -> String {
+    let mut rules = Vec::new();
+    match (validation.min_length, validation.max_length) {
+        (Some(min), Some(max)) => rules.push(format!("length {}..{}", min, max)),
+        (Some(min), None) => rules.push(format!("length ≥ {}", min)),
+        (None, Some(max)) => rules.push(format!("length ≤ {}", max)),
+        (None, None) => {}
+    }
+    if let Some(min) = validation.min {
+        rules.push(format!("≥ {}", min));
+    }
+    if let Some(max) = validation.max {
+        rules.push(format!("≤ {}", max));
+    }
+    if let Some(pattern) = &validation.pattern {
+        rules.push(format!("matches {}", pattern));
+    }
+    if let Some(values) = &validation.enum_values {
+        rules.push(format!("one of {}", values.join(", ")));
+    }
+    rules.join("; ")
+}
+
+fn cardinality_label(link_type: &LinkTypeDef) -> String {
+    let cardinality = match link_type.cardinality {
+        LinkCardinality::OneToOne => "one to one",
+        LinkCardinality::OneToMany => "one to many",
+        LinkCardinality::ManyToOne => "many to one",
+        LinkCardinality::ManyToMany => "many to many",
+    };
+    if link_type.bidirectional {
+        format!("{}, bidirectional", cardinality)
+    } else {
+        cardinality.to_string()
+    }
+}
+
+/// Mermaid node ID: IDs may contain characters Mermaid treats as syntax
+fn node_id(id: &str) -> String {
+    id.chars().map(|c| if c.is_ascii_alphanumeric() { c } else { '_' }).collect()
+}
+
+fn node_label(label: &str) -> String {
+    label.replace('"', "#quot;")
+}
+
+/// Flowchart of object types, interfaces and link types. With `focus`, only the types linked
+/// to that object type (and the interfaces it implements) are drawn.
+fn mermaid(ontology: &OntologyDef, focus: Option<&str>, locale: Option<&str>) -> String {
+    let link_types: Vec<&LinkTypeDef> = ontology
+        .link_types
+        .iter()
+        .filter(|l| focus.map_or(true, |id| l.source == id || l.target == id))
+        .collect();
+    let object_types: Vec<&ObjectType> = ontology
+        .object_types
+        .iter()
+        .filter(|o| {
+            focus.map_or(true, |id| o.id == id || link_types.iter().any(|l| l.source == o.id || l.target == o.id))
+        })
+        .collect();
+    let interfaces: Vec<&InterfaceDef> = ontology
+        .interfaces
+        .iter()
+        .filter(|i| object_types.iter().any(|o| o.implements.contains(&i.id) && focus.map_or(true, |id| o.id == id)))
+        .collect();
+
+    let mut lines = vec!["flowchart LR".to_string()];
+    for object_type in &object_types {
+        lines.push(format!(
+            "    {}[\"{}\"]",
+            node_id(&object_type.id),
+            node_label(object_type.display_name_for(locale))
+        ));
+    }
+    for interface in &interfaces {
+        lines.push(format!(
+            "    {}{{{{\"{}\"}}}}",
+            node_id(&interface.id),
+            node_label(interface.display_name_for(locale))
+        ));
+    }
+    for link_type in &link_types {
+        let arrow = if link_type.bidirectional { "<-->" } else { "-->" };
+        lines.push(format!(
+            "    {} {}|\"{}\"| {}",
+            node_id(&link_type.source),
+            arrow,
+            node_label(link_type.display_name_for(locale)),
+            node_id(&link_type.target)
+        ));
+    }
+    for object_type in &object_types {
+        for interface in interfaces.iter().filter(|i| object_type.implements.contains(&i.id)) {
+            lines.push(format!("    {} -.-> {}", node_id(&object_type.id), node_id(&interface.id)));
+        }
+    }
+    lines.join("\n")
+}
+
+/// Graphviz rendering of the whole type graph
+pub fn graphviz(ontology: &OntologyDef, locale: Option<&str>) -> String {
+    let quote = |value: &str| format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""));
+    let mut lines = vec!["digraph ontology {".to_string(), "    rankdir=LR;".to_string()];
+    for object_type in &ontology.object_types {
+        lines.push(format!(
+            "    {} [label={}, shape=box];",
+            quote(&object_type.id),
+            quote(object_type.display_name_for(locale))
+        ));
+    }
+    for interface in &ontology.interfaces {
+        lines.push(format!(
+            "    {} [label={}, shape=hexagon];",
+            quote(&interface.id),
+            quote(interface.display_name_for(locale))
+        ));
+    }
+    for link_type in &ontology.link_types {
+        let dir = if link_type.bidirectional { ", dir=both" } else { "" };
+        lines.push(format!(
+            "    {} -> {} [label={}{}];",
+            quote(&link_type.source),
+            quote(&link_type.target),
+            quote(link_type.display_name_for(locale)),
+            dir
+        ));
+    }
+    for object_type in &ontology.object_types {
+        for interface in &object_type.implements {
+            lines.push(format!("    {} -> {} [style=dashed];", quote(&object_type.id), quote(interface)));
+        }
+    }
+    lines.push("}".to_string());
+    lines.join("\n") + "\n"
+}
+
+/// Render a page in the requested format
+pub fn render(page: &Page, format: DocsFormat) -> String {
+    match format {
+        DocsFormat::Html => render_html(page),
+        DocsFormat::Markdown => render_markdown(page),
+    }
+}
+
+/// Relative link from `from` to `to`, both site paths without extension
+fn href(from: &str, to: &str, extension: &str) -> String {
+    let depth = from.matches('/').count();
+    format!("{}{}.{}", "../".repeat(depth), to, extension)
+}
+
+fn render_markdown(page: &Page) -> String {
+    let inline = |inlines: &[Inline]| -> String {
+        inlines
+            .iter()
+            .map(|i| match i {
+                Inline::Text(value) => value.replace('|', "\\|"),
+                Inline::Code(value) => format!("`{}`", value.replace('|', "\\|")),
+                Inline::Link { text, page: to } => format!("[{}]({})", text, href(&page.path, to, "md")),
+            })
+            .collect()
+    };
+
+    let mut out = format!("# {}\n", page.title);
+    if page.path != "index" {
+        out.push_str(&format!("\n[Index]({})\n", href(&page.path, "index", "md")));
+    }
+    for block in &page.blocks {
+        match block {
+            Block::Heading(value) => out.push_str(&format!("\n## {}\n", value)),
+            Block::Paragraph(inlines) => out.push_str(&format!("\n{}\n", inline(inlines))),
+            Block::Table { headers, rows } => {
+                out.push_str(&format!("\n| {} |\n", headers.join(" | ")));
+                out.push_str(&format!("|{}\n", " --- |".repeat(headers.len())));
+                for row in rows {
+                    let cells: Vec<String> = row.iter().map(|cell| inline(cell)).collect();
+                    out.push_str(&format!("| {} |\n", cells.join(" | ")));
+                }
+            }
+            Block::Diagram(source) => out.push_str(&format!("\n```mermaid\n{}\n```\n", source)),
+            Block::Search => {}
+        }
+    }
+    out
+}
+
+fn escape_html(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+const SEARCH_SCRIPT: &str = r#"<script>
+document.getElementById("search").addEventListener("input", function (event) {
+  var query = event.target.value.toLowerCase();
+  document.querySelectorAll("tbody tr").forEach(function (row) {
+    row.hidden = query !== "" && row.textContent.toLowerCase().indexOf(query) === -1;
+  });
+});
+</script>"#;
+
+const STYLE: &str = "body{font-family:sans-serif;max-width:72rem;margin:2rem auto;padding:0 1rem}\
+table{border-collapse:collapse;width:100%}th,td{border:1px solid #ddd;padding:.3rem .5rem;text-align:left}\
+code{background:#f4f4f4;padding:0 .2rem}#search{width:100%;padding:.4rem;font-size:1rem}";
+
+fn render_html(page: &Page) -> String {
+    let inline = |inlines: &[Inline]| -> String {
+        inlines
+            .iter()
+            .map(|i| match i {
+                Inline::Text(value) => escape_html(value),
+                Inline::Code(value) => format!("<code>{}</code>", escape_html(value)),
+                Inline::Link { text, page: to } => {
+                    format!("<a href=\"{}\">{}</a>", href(&page.path, to, "html"), escape_html(text))
+                }
+            })
+            .collect()
+    };
+
+    let mut body = String::new();
+    let mut has_search = false;
+    let mut has_diagram = false;
+    if page.path != "index" {
+        body.push_str(&format!("<nav><a href=\"{}\">Index</a></nav>\n", href(&page.path, "index", "html")));
+    }
+    body.push_str(&format!("<h1>{}</h1>\n", escape_html(&page.title)));
+    for block in &page.blocks {
+        match block {
+            Block::Heading(value) => body.push_str(&format!("<h2>{}</h2>\n", escape_html(value))),
+            Block::Paragraph(inlines) => body.push_str(&format!("<p>{}</p>\n", inline(inlines))),
+            Block::Table { headers, rows } => {
+                body.push_str("<table>\n<thead><tr>");
+                for header in headers {
+                    body.push_str(&format!("<th>{}</th>", escape_html(header)));
+                }
+                body.push_str("</tr></thead>\n<tbody>\n");
+                for row in rows {
+                    body.push_str("<tr>");
+                    for cell in row {
+                        body.push_str(&format!("<td>{}</td>", inline(cell)));
+                    }
+                    body.push_str("</tr>\n");
+                }
+                body.push_str("</tbody>\n</table>\n");
+            }
+            Block::Diagram(source) => {
+                has_diagram = true;
+                body.push_str(&format!("<pre class=\"mermaid\">\n{}\n</pre>\n", escape_html(source)));
+            }
+            Block::Search => {
+                has_search = true;
+                body.push_str("<input id=\"search\" type=\"search\" placeholder=\"Search types, properties, links…\">\n");
+            }
+        }
+    }
+    if has_search {
+        body.push_str(SEARCH_SCRIPT);
+        body.push('\n');
+    }
+    if has_diagram {
+        body.push_str(
+            "<script type=\"module\">import mermaid from \"https://cdn.jsdelivr.net/npm/mermaid@10/dist/mermaid.esm.min.mjs\"; mermaid.initialize({ startOnLoad: true });</script>\n",
+        );
+    }
+
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>{}</style>\n</head>\n<body>\n{}</body>\n</html>\n",
+        escape_html(&page.title),
+        STYLE,
+        body
+    )
+}
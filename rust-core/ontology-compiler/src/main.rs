@@ -1,14 +1,27 @@
 mod args;
 mod compiler;
+mod docs;
+mod watch;
 
 use clap::Parser;
 use anyhow::{Result, Context};
 use std::fs;
+use std::path::{Path, PathBuf};
 use ontology_engine::OntologyDef;
 
 fn main() -> Result<()> {
     let args = args::Args::parse();
 
+    match &args.command {
+        Some(args::Command::Docs(docs_args)) => return generate_docs(docs_args),
+        Some(args::Command::Diff(diff_args)) => return diff_ontologies(diff_args),
+        None => {}
+    }
+
+    if args.watch {
+        return watch::run(&args);
+    }
+
     println!("Starting Ontology Compiler...");
     println!("Input Directory: {:?}", args.input);
     println!("Output File: {:?}", args.output);
@@ -16,16 +29,42 @@ fn main() -> Result<()> {
     // 1. Compile OWL definitions
     let compiler = compiler::Compiler::new();
     compiler.load_ttl_files(&args.input)?;
-    let mut ontology = compiler.compile()?;
+    let ontology = compiler.compile()?;
 
     println!("Compiled {} Object Types", ontology.object_types.len());
     println!("Compiled {} Link Types", ontology.link_types.len());
     println!("Compiled {} Interfaces", ontology.interfaces.len());
 
-    // 2. Merge Sidecar (Actions/Functions)
-    if let Some(sidecar_path) = args.sidecar {
+    // 2-3. Merge the sidecar, apply overlays and validate
+    let ontology = build(ontology, &args)?;
+
+    if args.lint {
+        println!("Lint passed: no ontology errors found");
+        return Ok(());
+    }
+
+    // 4. Serialize to JSON
+    let json = to_json(&ontology)?;
+
+    fs::write(&args.output, json)
+        .context("Failed to write output file")?;
+
+    println!("Success! Ontology compiled to {:?}", args.output);
+
+    if let Some(dir) = &args.emit_json_schema {
+        let count = write_json_schemas(&ontology, dir)?;
+        println!("Wrote {} JSON schemas to {:?}", count, dir);
+    }
+
+    Ok(())
+}
+
+/// Merge the sidecar (actions/functions) into a compiled definition, then apply overlays and
+/// validate the result, reporting every error at once
+fn build(mut ontology: OntologyDef, args: &args::Args) -> Result<ontology_engine::Ontology> {
+    if let Some(sidecar_path) = &args.sidecar {
         println!("Loading sidecar: {:?}", sidecar_path);
-        let sidecar_content = fs::read_to_string(&sidecar_path)
+        let sidecar_content = fs::read_to_string(sidecar_path)
             .context("Failed to read sidecar file")?;
 
         // We parse the sidecar as a partial OntologyDef (or a specific struct matching the yaml)
@@ -49,15 +88,91 @@ fn main() -> Result<()> {
         println!("Merged {} Function Types", ontology.function_types.len());
     }
 
-    // 3. Serialize to JSON
-    let config = ontology_engine::OntologyConfig { ontology };
-    let json = serde_json::to_string_pretty(&config)
-        .context("Failed to serialize ontology to JSON")?;
+    let overlays = load_overlays(&args.overlays)?;
+    for path in &args.overlays {
+        println!("Applying overlay: {:?}", path);
+    }
+    let base = ontology_engine::OntologyConfig { ontology };
+    let ontology = ontology_engine::Ontology::from_layers(base, &overlays).map_err(report_errors)?;
+    for warning in ontology.warnings() {
+        eprintln!("warning: {}", warning);
+    }
+    Ok(ontology)
+}
 
-    fs::write(&args.output, json)
-        .context("Failed to write output file")?;
+/// The compiled output file's contents
+fn to_json(ontology: &ontology_engine::Ontology) -> Result<String> {
+    let config = ontology_engine::OntologyConfig { ontology: ontology.definition().clone() };
+    serde_json::to_string_pretty(&config)
+        .context("Failed to serialize ontology to JSON")
+}
 
-    println!("Success! Ontology compiled to {:?}", args.output);
+/// Write each object type's JSON Schema to `<dir>/<type>.schema.json`; returns the count
+fn write_json_schemas(ontology: &ontology_engine::Ontology, dir: &Path) -> Result<usize> {
+    fs::create_dir_all(dir).with_context(|| format!("Failed to create {:?}", dir))?;
+    let object_types = &ontology.definition().object_types;
+    for object_type in object_types {
+        let path = dir.join(ontology_engine::json_schema_file_name(&object_type.id));
+        let schema = serde_json::to_string_pretty(&object_type.to_json_schema())
+            .context("Failed to serialize JSON schema")?;
+        fs::write(&path, schema + "\n").with_context(|| format!("Failed to write {:?}", path))?;
+    }
+    Ok(object_types.len())
+}
+
+/// Render the reference site for an already compiled ontology
+fn generate_docs(args: &args::DocsArgs) -> Result<()> {
+    let content = fs::read_to_string(&args.input)
+        .with_context(|| format!("Failed to read {:?}", args.input))?;
+    // JSON is valid YAML, so one parser covers both
+    let ontology = ontology_engine::Ontology::from_yaml(&content).map_err(report_errors)?;
+
+    let pages = docs::write_site(ontology.definition(), args.locale.as_deref(), args.format, &args.output)?;
+    println!("Wrote {} pages to {:?}", pages, args.output);
+    Ok(())
+}
 
+/// Print the changes from one effective (overlaid) ontology to another
+fn diff_ontologies(args: &args::DiffArgs) -> Result<()> {
+    let old = load_layered(&args.old, &args.old_overlays)?;
+    let new = load_layered(&args.new, &args.new_overlays)?;
+
+    let changes = ontology_engine::diff_definitions(old.definition(), new.definition());
+    if changes.is_empty() {
+        println!("No changes");
+    }
+    for change in &changes {
+        println!("{}", change);
+    }
     Ok(())
 }
+
+/// Load a compiled ontology with overlays applied in order
+fn load_layered(path: &Path, overlay_paths: &[PathBuf]) -> Result<ontology_engine::Ontology> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {:?}", path))?;
+    // JSON is valid YAML, so one parser covers both
+    let base = ontology_engine::OntologyConfig::from_yaml(&content).map_err(report_errors)?;
+    let overlays = load_overlays(overlay_paths)?;
+    ontology_engine::Ontology::from_layers(base, &overlays).map_err(report_errors)
+}
+
+fn load_overlays(paths: &[PathBuf]) -> Result<Vec<ontology_engine::OntologyOverlay>> {
+    paths
+        .iter()
+        .map(|path| {
+            let content = fs::read_to_string(path)
+                .with_context(|| format!("Failed to read overlay {:?}", path))?;
+            ontology_engine::OntologyOverlay::from_yaml(path.display().to_string(), &content)
+                .map_err(report_errors)
+        })
+        .collect()
+}
+
+/// Print every load error and turn them into a single failure
+fn report_errors(errors: ontology_engine::OntologyLoadErrors) -> anyhow::Error {
+    for error in &errors {
+        eprintln!("error: {}", error);
+    }
+    anyhow::anyhow!("Ontology validation failed with {} error(s)", errors.len())
+}
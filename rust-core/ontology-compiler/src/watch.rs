@@ -0,0 +1,226 @@
+//! `--watch`: recompile whenever the input directory, the sidecar or an overlay changes.
+//!
+//! Every .ttl file is parsed once and its triples kept; a change re-parses only the files
+//! that were touched and compiles from a fresh store filled from the cache. The output is
+//! rewritten only when its JSON differs, and the changes since the previous output are
+//! printed. Errors are printed and the watcher keeps going.
+
+use crate::args::Args;
+use crate::compiler::{self, Compiler};
+use anyhow::{Context, Result};
+use notify::{EventKind, RecursiveMode, Watcher};
+use oxigraph::model::Triple;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::Duration;
+
+const RELOAD_MUTATION: &str = "mutation($definition: String!) { \
+    reloadOntology(definition: $definition, format: \"json\") { success version errors { message } warnings } }";
+
+/// Watch the inputs and recompile on every change until the process is stopped
+pub fn run(args: &Args) -> Result<()> {
+    let mut session = WatchSession::new(args)?;
+    session.rebuild();
+
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx).context("Failed to start file watcher")?;
+    for dir in session.watched_dirs() {
+        watcher
+            .watch(&dir, RecursiveMode::NonRecursive)
+            .with_context(|| format!("Failed to watch {:?}", dir))?;
+    }
+    println!("Watching {:?} for changes...", args.input);
+
+    let debounce = Duration::from_millis(args.debounce_ms);
+    while let Ok(event) = rx.recv() {
+        // Editors often save in several steps; wait for the events to settle
+        let mut changed = BTreeSet::new();
+        session.collect(event, &mut changed);
+        while let Ok(event) = rx.recv_timeout(debounce) {
+            session.collect(event, &mut changed);
+        }
+
+        if !changed.is_empty() {
+            session.apply(&changed);
+            session.rebuild();
+        }
+    }
+    Ok(())
+}
+
+struct WatchSession<'a> {
+    args: &'a Args,
+    input: PathBuf,
+    /// Sidecar and overlay files; they are read again on every build
+    configs: Vec<PathBuf>,
+    /// Parsed triples of each .ttl file, or why it failed to parse
+    files: BTreeMap<PathBuf, Result<Vec<Triple>, String>>,
+}
+
+impl<'a> WatchSession<'a> {
+    fn new(args: &'a Args) -> Result<Self> {
+        let input = fs::canonicalize(&args.input)
+            .with_context(|| format!("Directory not found: {:?}", args.input))?;
+        let configs = args
+            .sidecar
+            .iter()
+            .chain(&args.overlays)
+            .map(|path| absolute(path))
+            .collect::<Result<_>>()?;
+
+        let mut files = BTreeMap::new();
+        for path in compiler::ttl_files(&input)? {
+            let parsed = compiler::parse_ttl_file(&path).map_err(|e| e.to_string());
+            files.insert(path, parsed);
+        }
+        Ok(Self { args, input, configs, files })
+    }
+
+    fn watched_dirs(&self) -> BTreeSet<PathBuf> {
+        let config_dirs = self.configs.iter().filter_map(|path| path.parent().map(Path::to_path_buf));
+        std::iter::once(self.input.clone()).chain(config_dirs).collect()
+    }
+
+    /// Add the paths of an event that matter to the build
+    fn collect(&self, event: notify::Result<notify::Event>, changed: &mut BTreeSet<PathBuf>) {
+        let event = match event {
+            Ok(event) => event,
+            Err(e) => {
+                eprintln!("watch error: {}", e);
+                return;
+            }
+        };
+        // Reads (including our own) are not changes
+        if matches!(event.kind, EventKind::Access(_)) {
+            return;
+        }
+        for path in event.paths {
+            let is_input = path.parent() == Some(self.input.as_path()) && compiler::is_ttl_file(&path);
+            if is_input || self.configs.contains(&path) {
+                changed.insert(path);
+            }
+        }
+    }
+
+    /// Re-parse the changed .ttl files; the sidecar and overlays are read by the build
+    fn apply(&mut self, changed: &BTreeSet<PathBuf>) {
+        for path in changed {
+            println!("Changed: {:?}", path);
+            if self.configs.contains(path) {
+                continue;
+            }
+            if path.exists() {
+                let parsed = compiler::parse_ttl_file(path).map_err(|e| e.to_string());
+                self.files.insert(path.clone(), parsed);
+            } else {
+                self.files.remove(path);
+            }
+        }
+    }
+
+    /// Compile, validate and publish, printing any errors instead of stopping
+    fn rebuild(&self) {
+        if let Err(e) = self.compile().and_then(|ontology| self.publish(&ontology)) {
+            eprintln!("error: {:#}", e);
+            println!("Compilation failed; waiting for changes...");
+        }
+    }
+
+    fn compile(&self) -> Result<ontology_engine::Ontology> {
+        let broken: Vec<&String> = self.files.values().filter_map(|parsed| parsed.as_ref().err()).collect();
+        for error in &broken {
+            eprintln!("error: {}", error);
+        }
+        if !broken.is_empty() {
+            anyhow::bail!("{} file(s) failed to parse", broken.len());
+        }
+
+        let compiler = Compiler::new();
+        for triples in self.files.values().flatten() {
+            compiler.load_triples(triples)?;
+        }
+        crate::build(compiler.compile()?, self.args)
+    }
+
+    /// Write the output if it differs from what is on disk, print the changes and push them
+    fn publish(&self, ontology: &ontology_engine::Ontology) -> Result<()> {
+        let json = crate::to_json(ontology)?;
+        let previous = fs::read_to_string(&self.args.output).ok();
+        if previous.as_deref() == Some(json.as_str()) {
+            println!("No changes; {:?} is up to date", self.args.output);
+            return Ok(());
+        }
+
+        fs::write(&self.args.output, &json).context("Failed to write output file")?;
+        if let Some(dir) = &self.args.emit_json_schema {
+            crate::write_json_schemas(ontology, dir)?;
+        }
+        let previous = previous.and_then(|content| serde_json::from_str::<ontology_engine::OntologyConfig>(&content).ok());
+        match previous {
+            Some(previous) => {
+                let changes = ontology_engine::diff_definitions(&previous.ontology, ontology.definition());
+                println!("Recompiled {:?}: {} change(s)", self.args.output, changes.len());
+                for change in &changes {
+                    println!("  {}", change);
+                }
+            }
+            None => println!(
+                "Compiled {} object types to {:?}",
+                ontology.definition().object_types.len(),
+                self.args.output
+            ),
+        }
+
+        if let Some(url) = &self.args.push_to {
+            // A server that is down should not stop the watcher
+            if let Err(e) = push(url, &json) {
+                eprintln!("error: Failed to push to {}: {:#}", url, e);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Send the compiled ontology to a server's `reloadOntology` mutation
+fn push(url: &str, json: &str) -> Result<()> {
+    let body = serde_json::json!({
+        "query": RELOAD_MUTATION,
+        "variables": { "definition": json },
+    });
+    let response: serde_json::Value = reqwest::blocking::Client::new()
+        .post(url)
+        .json(&body)
+        .send()?
+        .error_for_status()?
+        .json()?;
+
+    if let Some(errors) = response.get("errors").and_then(|errors| errors.as_array()) {
+        let messages: Vec<&str> = errors.iter().filter_map(|e| e["message"].as_str()).collect();
+        anyhow::bail!("{}", messages.join("; "));
+    }
+    let result = &response["data"]["reloadOntology"];
+    if result["success"].as_bool() != Some(true) {
+        let messages = result["errors"]
+            .as_array()
+            .map(|errors| errors.iter().filter_map(|e| e["message"].as_str()).collect::<Vec<_>>().join("; "))
+            .unwrap_or_default();
+        anyhow::bail!("server rejected the ontology: {}", messages);
+    }
+    println!("Pushed to {} (version {})", url, result["version"]);
+    Ok(())
+}
+
+/// Absolute form of a file that may not exist yet, matching the paths watch events carry
+fn absolute(path: &Path) -> Result<PathBuf> {
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| anyhow::anyhow!("Not a file: {:?}", path))?;
+    let parent = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let parent = fs::canonicalize(parent).with_context(|| format!("Directory not found: {:?}", parent))?;
+    Ok(parent.join(file_name))
+}
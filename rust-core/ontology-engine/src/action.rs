@@ -62,6 +62,19 @@ pub struct ActionOperation {
     pub to: Option<String>,
 }
 
+/// What `ActionExecutor::execute` does when one of an action's operations fails
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecutionMode {
+    /// Run the remaining operations anyway and report every error
+    #[default]
+    ContinueOnError,
+    /// Skip the remaining operations
+    StopOnError,
+    /// Skip the remaining operations and undo the ones that succeeded, most recent first
+    RollbackOnError,
+}
+
 /// Operation types
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -94,6 +107,17 @@ pub enum SideEffectType {
     Log,
 }
 
+/// Parameter names referenced as `{{name}}` in a template string, in order of appearance
+pub fn template_parameters(template: &str) -> Vec<&str> {
+    static TEMPLATE: std::sync::OnceLock<regex::Regex> = std::sync::OnceLock::new();
+    TEMPLATE
+        .get_or_init(|| regex::Regex::new(r"\{\{([^}]+)\}\}").expect("valid template pattern"))
+        .captures_iter(template)
+        .filter_map(|cap| cap.get(1))
+        .map(|name| name.as_str().trim())
+        .collect()
+}
+
 /// Runtime action execution context
 #[derive(Debug, Clone)]
 pub struct Action {
@@ -1,4 +1,5 @@
-use crate::action::{Action, ActionType, ActionOperation, OperationType, ActionSideEffect, SideEffectType};
+use crate::action::{Action, ActionType, ActionOperation, ExecutionMode, OperationType, ActionSideEffect, SideEffectType};
+use crate::outbox::{SideEffectOutbox, SideEffectRecord, SideEffectStatus};
 use crate::property::{PropertyValue, PropertyMap};
 use crate::validation::{validate_action, ActionContext, ValidationError};
 use std::collections::HashMap;
@@ -9,17 +10,101 @@ pub struct ActionExecutionResult {
     pub success: bool,
     pub operations_executed: Vec<String>,
     pub errors: Vec<String>,
-    pub side_effects_triggered: Vec<String>,
+    pub side_effects_triggered: Vec<TriggeredSideEffect>,
+    /// Whether the operations that succeeded were undone after a later one failed
+    pub rolled_back: bool,
+    /// Compensating steps of a rollback that failed; the action is left half-applied when
+    /// this is not empty
+    pub compensation_errors: Vec<String>,
+}
+
+/// A side effect an action triggered. Effects run inline are `Delivered`, or `DeadLettered`
+/// if every attempt failed; effects handed to an outbox are `Enqueued` and their progress is
+/// read with `SideEffectOutbox::side_effect_status(effect_id)`.
+#[derive(Debug, Clone)]
+pub struct TriggeredSideEffect {
+    pub effect_type: SideEffectType,
+    pub status: SideEffectStatus,
+    pub effect_id: Option<String>,
+    /// Delivery attempts made inline; 0 for enqueued effects
+    pub attempts: u32,
+    /// Why the last attempt failed
+    pub error: Option<String>,
+}
+
+/// How delivering a side effect went
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeliveryOutcome {
+    /// 0 if the effect was rejected before any attempt, e.g. for an invalid config
+    pub attempts: u32,
+    /// Why the last attempt failed; `None` once delivered
+    pub error: Option<String>,
+}
+
+impl DeliveryOutcome {
+    pub fn delivered(attempts: u32) -> Self {
+        Self { attempts, error: None }
+    }
+
+    pub fn failed(attempts: u32, error: String) -> Self {
+        Self { attempts, error: Some(error) }
+    }
+
+    /// Attempts after the first
+    pub fn retries(&self) -> u32 {
+        self.attempts.saturating_sub(1)
+    }
+
+    pub fn into_result(self) -> Result<(), String> {
+        match self.error {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
+    }
+}
+
+/// A single attempt
+impl From<Result<(), String>> for DeliveryOutcome {
+    fn from(result: Result<(), String>) -> Self {
+        match result {
+            Ok(()) => Self::delivered(1),
+            Err(e) => Self::failed(1, e),
+        }
+    }
+}
+
+/// How to undo an operation that succeeded
+#[derive(Debug, Clone)]
+enum Compensation {
+    /// Delete the object created with these properties
+    DeleteCreated { object_type: String, properties: PropertyMap },
+    /// Update the object back to its previous values
+    Restore { object_type: String, previous: PropertyMap },
+    /// Create the deleted object again
+    Recreate { object_type: String, properties: PropertyMap },
+    /// Delete the created link
+    DeleteLink { link_type: String, from: String, to: String },
+    /// The operation cannot be undone, for the reason given
+    Irreversible(String),
 }
 
 /// Action executor - executes actions with template substitution
 pub struct ActionExecutor {
     /// Function to execute object operations (create, update, delete)
     pub object_operation_handler: Option<Box<dyn Fn(&OperationType, &str, Option<&PropertyMap>) -> Result<String, String> + Send + Sync>>,
-    /// Function to execute link operations
-    pub link_operation_handler: Option<Box<dyn Fn(&str, &str, &str, &PropertyMap) -> Result<String, String> + Send + Sync>>,
-    /// Function to handle side effects
-    pub side_effect_handler: Option<Box<dyn Fn(&SideEffectType, &PropertyMap) -> Result<(), String> + Send + Sync>>,
+    /// Function to execute link operations (create, delete)
+    pub link_operation_handler: Option<Box<dyn Fn(&OperationType, &str, &str, &str, &PropertyMap) -> Result<String, String> + Send + Sync>>,
+    /// Current properties of the object an operation on a type with the given properties
+    /// applies to, or `None` if there is none. Read before updates and deletes in
+    /// `RollbackOnError` mode so they can be undone.
+    pub object_snapshot_handler: Option<Box<dyn Fn(&str, &PropertyMap) -> Result<Option<PropertyMap>, String> + Send + Sync>>,
+    /// Function to deliver side effects, retrying as it sees fit (see `SideEffectHandlers`)
+    pub side_effect_handler: Option<Box<dyn Fn(&SideEffectType, &PropertyMap) -> DeliveryOutcome + Send + Sync>>,
+    /// When set, side effects are recorded here for an `OutboxDispatcher` to deliver instead
+    /// of running inline, and only once every operation has succeeded
+    pub outbox: Option<SideEffectOutbox>,
+    /// Mode for action types that do not set `executionMode`
+    pub execution_mode: ExecutionMode,
 }
 
 impl ActionExecutor {
@@ -27,11 +112,21 @@ impl ActionExecutor {
         Self {
             object_operation_handler: None,
             link_operation_handler: None,
+            object_snapshot_handler: None,
             side_effect_handler: None,
+            outbox: None,
+            execution_mode: ExecutionMode::default(),
         }
     }
     
-    /// Execute an action
+    pub fn with_execution_mode(mut self, execution_mode: ExecutionMode) -> Self {
+        self.execution_mode = execution_mode;
+        self
+    }
+    
+    /// Execute an action. If an operation fails the result comes back in
+    /// `ValidationError::ExecutionFailed`; side effects only run when every operation
+    /// succeeded, except in `ContinueOnError` mode without an outbox.
     pub fn execute(
         &self,
         action: &Action,
@@ -41,57 +136,136 @@ impl ActionExecutor {
         // Validate action first
         validate_action(action, action_type, context)?;
         
+        let mode = action_type.execution_mode.unwrap_or(self.execution_mode);
         let mut result = ActionExecutionResult {
             success: true,
             operations_executed: Vec::new(),
             errors: Vec::new(),
             side_effects_triggered: Vec::new(),
+            rolled_back: false,
+            compensation_errors: Vec::new(),
         };
         
         // Execute each operation in the action's logic
+        let mut compensations = Vec::new();
         for operation in &action_type.logic {
-            match self.execute_operation(operation, &action.parameters, context) {
-                Ok(op_id) => {
+            let compensate = mode == ExecutionMode::RollbackOnError;
+            match self.execute_operation(operation, &action.parameters, context, compensate) {
+                Ok((op_id, compensation)) => {
                     result.operations_executed.push(op_id);
+                    compensations.extend(compensation);
                 }
                 Err(e) => {
                     result.errors.push(e);
                     result.success = false;
-                    // Continue executing other operations (could be made configurable)
+                    if mode != ExecutionMode::ContinueOnError {
+                        break;
+                    }
                 }
             }
         }
+        if !result.success && mode == ExecutionMode::RollbackOnError {
+            self.roll_back(&action_type.id, compensations, &mut result);
+        }
         
-        // Execute side effects
-        for side_effect in &action_type.side_effects {
-            match self.execute_side_effect(side_effect, &action.parameters, context) {
-                Ok(()) => {
-                    result.side_effects_triggered.push(format!("{:?}", side_effect.effect_type));
-                }
-                Err(e) => {
+        // Execute side effects, or hand them to the outbox
+        if let Some(outbox) = &self.outbox {
+            if result.success {
+                self.enqueue_side_effects(outbox, action, action_type, &mut result);
+            }
+        } else if result.success || mode == ExecutionMode::ContinueOnError {
+            for side_effect in &action_type.side_effects {
+                let outcome = self.execute_side_effect(side_effect, &action.parameters, context);
+                // Side effect failures don't fail the action
+                if let Some(e) = &outcome.error {
                     result.errors.push(format!("Side effect error: {}", e));
-                    // Side effect failures don't fail the action
                 }
+                result.side_effects_triggered.push(TriggeredSideEffect {
+                    effect_type: side_effect.effect_type.clone(),
+                    status: match outcome.error {
+                        None => SideEffectStatus::Delivered,
+                        Some(_) => SideEffectStatus::DeadLettered,
+                    },
+                    effect_id: None,
+                    attempts: outcome.attempts,
+                    error: outcome.error,
+                });
             }
         }
         
         if result.success {
             Ok(result)
         } else {
-            Err(ValidationError::InvalidParameter(format!(
-                "Action execution failed: {:?}",
-                result.errors
-            )))
+            Err(ValidationError::ExecutionFailed(Box::new(result)))
+        }
+    }
+    
+    /// Undo the operations that succeeded, most recent first. Every step is attempted;
+    /// the ones that fail are reported in `compensation_errors` and on stderr.
+    fn roll_back(&self, action_type_id: &str, compensations: Vec<Compensation>, result: &mut ActionExecutionResult) {
+        for compensation in compensations.into_iter().rev() {
+            if let Err(e) = self.compensate(compensation) {
+                result.compensation_errors.push(e);
+            }
+        }
+        result.rolled_back = true;
+        if !result.compensation_errors.is_empty() {
+            tracing::error!(
+                "rollback of action '{}' is incomplete, it is left half-applied: {}",
+                action_type_id,
+                result.compensation_errors.join("; ")
+            );
+            result.errors.push(format!("Rollback incomplete: {}", result.compensation_errors.join("; ")));
+        }
+    }
+    
+    fn compensate(&self, compensation: Compensation) -> Result<(), String> {
+        match compensation {
+            Compensation::DeleteCreated { object_type, properties } => match &self.object_operation_handler {
+                Some(handler) => handler(&OperationType::DeleteObject, &object_type, Some(&properties)).map(|_| ()),
+                None => Ok(()),
+            }
+            .map_err(|e| format!("Could not delete created '{}': {}", object_type, e)),
+            Compensation::Restore { object_type, previous } => match &self.object_operation_handler {
+                Some(handler) => handler(&OperationType::UpdateObject, &object_type, Some(&previous)).map(|_| ()),
+                None => Ok(()),
+            }
+            .map_err(|e| format!("Could not restore updated '{}': {}", object_type, e)),
+            Compensation::Recreate { object_type, properties } => match &self.object_operation_handler {
+                Some(handler) => handler(&OperationType::CreateObject, &object_type, Some(&properties)).map(|_| ()),
+                None => Ok(()),
+            }
+            .map_err(|e| format!("Could not recreate deleted '{}': {}", object_type, e)),
+            Compensation::DeleteLink { link_type, from, to } => match &self.link_operation_handler {
+                Some(handler) => handler(&OperationType::DeleteLink, &link_type, &from, &to, &PropertyMap::new()).map(|_| ()),
+                None => Ok(()),
+            }
+            .map_err(|e| format!("Could not delete created '{}' link from {} to {}: {}", link_type, from, to, e)),
+            Compensation::Irreversible(reason) => Err(reason),
         }
     }
     
-    /// Execute a single operation with template substitution
+    /// Current properties of the object an update or delete applies to, to undo it with
+    fn snapshot(&self, object_type: &str, properties: &PropertyMap) -> Result<Result<PropertyMap, String>, String> {
+        let Some(handler) = &self.object_snapshot_handler else {
+            return Ok(Err(format!("Cannot undo the change to '{}': no object snapshot handler", object_type)));
+        };
+        match handler(object_type, properties) {
+            Ok(Some(current)) => Ok(Ok(current)),
+            Ok(None) => Ok(Err(format!("Cannot undo the change to '{}': the object was not found", object_type))),
+            Err(e) => Err(format!("Could not read '{}' before changing it: {}", object_type, e)),
+        }
+    }
+    
+    /// Execute a single operation with template substitution, and with `compensate` also
+    /// work out how to undo it
     fn execute_operation(
         &self,
         operation: &ActionOperation,
         parameters: &PropertyMap,
         _context: &ActionContext,
-    ) -> Result<String, String> {
+        compensate: bool,
+    ) -> Result<(String, Option<Compensation>), String> {
         // Substitute template variables in properties
         let substituted_properties = self.substitute_templates(&operation.properties, parameters)?;
         
@@ -100,31 +274,60 @@ impl ActionExecutor {
                 let object_type = operation.object_type.as_ref()
                     .ok_or_else(|| "CreateObject requires object_type".to_string())?;
                 
-                if let Some(handler) = &self.object_operation_handler {
-                    handler(&operation.operation, object_type, Some(&substituted_properties))
+                let op_id = if let Some(handler) = &self.object_operation_handler {
+                    handler(&operation.operation, object_type, Some(&substituted_properties))?
                 } else {
-                    Ok(format!("create_object_{}", uuid::Uuid::new_v4()))
-                }
+                    format!("create_object_{}", uuid::Uuid::new_v4())
+                };
+                let compensation = compensate.then(|| Compensation::DeleteCreated {
+                    object_type: object_type.clone(),
+                    properties: substituted_properties,
+                });
+                Ok((op_id, compensation))
             }
             OperationType::UpdateObject => {
                 let object_type = operation.object_type.as_ref()
                     .ok_or_else(|| "UpdateObject requires object_type".to_string())?;
                 
-                if let Some(handler) = &self.object_operation_handler {
-                    handler(&operation.operation, object_type, Some(&substituted_properties))
+                let compensation = if compensate {
+                    Some(match self.snapshot(object_type, &substituted_properties)? {
+                        Ok(current) => {
+                            let mut previous = PropertyMap::new();
+                            for (key, _) in substituted_properties.iter() {
+                                previous.insert(key.clone(), current.get(key).cloned().unwrap_or(PropertyValue::Null));
+                            }
+                            Compensation::Restore { object_type: object_type.clone(), previous }
+                        }
+                        Err(reason) => Compensation::Irreversible(reason),
+                    })
                 } else {
-                    Ok(format!("update_object_{}", uuid::Uuid::new_v4()))
-                }
+                    None
+                };
+                let op_id = if let Some(handler) = &self.object_operation_handler {
+                    handler(&operation.operation, object_type, Some(&substituted_properties))?
+                } else {
+                    format!("update_object_{}", uuid::Uuid::new_v4())
+                };
+                Ok((op_id, compensation))
             }
             OperationType::DeleteObject => {
                 let object_type = operation.object_type.as_ref()
                     .ok_or_else(|| "DeleteObject requires object_type".to_string())?;
                 
-                if let Some(handler) = &self.object_operation_handler {
-                    handler(&operation.operation, object_type, None)
+                let compensation = if compensate {
+                    Some(match self.snapshot(object_type, &substituted_properties)? {
+                        Ok(current) => Compensation::Recreate { object_type: object_type.clone(), properties: current },
+                        Err(reason) => Compensation::Irreversible(reason),
+                    })
                 } else {
-                    Ok(format!("delete_object_{}", uuid::Uuid::new_v4()))
-                }
+                    None
+                };
+                let op_id = if let Some(handler) = &self.object_operation_handler {
+                    handler(&operation.operation, object_type, None)?
+                } else {
+                    format!("delete_object_{}", uuid::Uuid::new_v4())
+                };
+                Ok((op_id, compensation))
             }
             OperationType::CreateLink => {
                 let link_type = operation.link_type.as_ref()
@@ -138,11 +341,17 @@ impl ActionExecutor {
                 let from_sub = self.substitute_string_template(from, parameters)?;
                 let to_sub = self.substitute_string_template(to, parameters)?;
                 
-                if let Some(handler) = &self.link_operation_handler {
-                    handler(link_type, &from_sub, &to_sub, &substituted_properties)
+                let op_id = if let Some(handler) = &self.link_operation_handler {
+                    handler(&operation.operation, link_type, &from_sub, &to_sub, &substituted_properties)?
                 } else {
-                    Ok(format!("create_link_{}", uuid::Uuid::new_v4()))
-                }
+                    format!("create_link_{}", uuid::Uuid::new_v4())
+                };
+                let compensation = compensate.then(|| Compensation::DeleteLink {
+                    link_type: link_type.clone(),
+                    from: from_sub,
+                    to: to_sub,
+                });
+                Ok((op_id, compensation))
             }
             OperationType::DeleteLink => {
                 let link_type = operation.link_type.as_ref()
@@ -155,15 +364,21 @@ impl ActionExecutor {
                 let from_sub = self.substitute_string_template(from, parameters)?;
                 let to_sub = self.substitute_string_template(to, parameters)?;
                 
-                if let Some(handler) = &self.link_operation_handler {
-                    handler(link_type, &from_sub, &to_sub, &PropertyMap::new())
+                let op_id = if let Some(handler) = &self.link_operation_handler {
+                    handler(&operation.operation, link_type, &from_sub, &to_sub, &PropertyMap::new())?
                 } else {
-                    Ok(format!("delete_link_{}", uuid::Uuid::new_v4()))
-                }
+                    format!("delete_link_{}", uuid::Uuid::new_v4())
+                };
+                // The deleted link's properties are gone, so it cannot be recreated faithfully
+                let compensation = compensate.then(|| Compensation::Irreversible(format!(
+                    "Cannot undo the deletion of the '{}' link from {} to {}",
+                    link_type, from_sub, to_sub
+                )));
+                Ok((op_id, compensation))
             }
             OperationType::UpdateProperty => {
                 // UpdateProperty would update a specific property
-                Ok(format!("update_property_{}", uuid::Uuid::new_v4()))
+                Ok((format!("update_property_{}", uuid::Uuid::new_v4()), None))
             }
         }
     }
@@ -266,39 +481,78 @@ impl ActionExecutor {
         Ok(result)
     }
     
+    /// Record the action's side effects, with templates substituted, in one outbox write
+    fn enqueue_side_effects(
+        &self,
+        outbox: &SideEffectOutbox,
+        action: &Action,
+        action_type: &ActionType,
+        result: &mut ActionExecutionResult,
+    ) {
+        let mut records = Vec::new();
+        for side_effect in &action_type.side_effects {
+            match self.substitute_templates(&side_effect.config, &action.parameters) {
+                Ok(config) => records.push(SideEffectRecord::new(&action.action_type_id, side_effect.effect_type.clone(), config)),
+                Err(e) => result.errors.push(format!("Side effect error: {}", e)),
+            }
+        }
+        let triggered: Vec<TriggeredSideEffect> = records
+            .iter()
+            .map(|record| TriggeredSideEffect {
+                effect_type: record.effect_type.clone(),
+                status: SideEffectStatus::Enqueued,
+                effect_id: Some(record.effect_id.clone()),
+                attempts: 0,
+                error: None,
+            })
+            .collect();
+        match outbox.enqueue(records) {
+            Ok(()) => result.side_effects_triggered.extend(triggered),
+            Err(e) => result.errors.push(format!("Side effect error: failed to enqueue: {}", e)),
+        }
+    }
+
     /// Execute a side effect
     fn execute_side_effect(
         &self,
         side_effect: &ActionSideEffect,
         parameters: &PropertyMap,
         _context: &ActionContext,
-    ) -> Result<(), String> {
+    ) -> DeliveryOutcome {
         // Substitute templates in side effect config
-        let substituted_config = self.substitute_templates(&side_effect.config, parameters)?;
+        let substituted_config = match self.substitute_templates(&side_effect.config, parameters) {
+            Ok(config) => config,
+            Err(e) => return DeliveryOutcome::failed(0, e),
+        };
         
         if let Some(handler) = &self.side_effect_handler {
             handler(&side_effect.effect_type, &substituted_config)
         } else {
-            // Default handlers (stubs)
-            match &side_effect.effect_type {
-                SideEffectType::Email => {
-                    // Stub email handler
-                    Ok(())
-                }
-                SideEffectType::Webhook => {
-                    // Stub webhook handler
-                    Ok(())
-                }
-                SideEffectType::Notification => {
-                    // Stub notification handler
-                    Ok(())
-                }
-                SideEffectType::Log => {
-                    // Stub log handler
-                    eprintln!("Action side effect: {:?} with config: {:?}", side_effect.effect_type, substituted_config);
-                    Ok(())
-                }
-            }
+            default_side_effect(&side_effect.effect_type, &substituted_config).into()
+        }
+    }
+}
+
+/// Side effect executor used when no handler is configured: only `Log` does anything.
+/// `SideEffectHandlers` delivers webhooks and emails.
+pub fn default_side_effect(effect_type: &SideEffectType, config: &PropertyMap) -> Result<(), String> {
+    match effect_type {
+        SideEffectType::Email => {
+            // Stub email handler
+            Ok(())
+        }
+        SideEffectType::Webhook => {
+            // Stub webhook handler
+            Ok(())
+        }
+        SideEffectType::Notification => {
+            // Stub notification handler
+            Ok(())
+        }
+        SideEffectType::Log => {
+            // Stub log handler
+            tracing::debug!("Action side effect: {:?} with config: {:?}", effect_type, config);
+            Ok(())
         }
     }
 }
@@ -331,6 +585,216 @@ mod tests {
         assert_eq!(result, "Normalize from 1990 to 2010 for population");
     }
     
+    #[test]
+    fn test_side_effects_are_enqueued_with_an_outbox() {
+        let action_type: ActionType = serde_yaml::from_str(r#"
+id: "retire_plant"
+displayName: "Retire Plant"
+parameters:
+  - id: "plant_id"
+    type: "string"
+    required: true
+logic:
+  - operation: "update_object"
+    type: "plant"
+side_effects:
+  - type: "webhook"
+    config:
+      properties:
+        url: "https://hooks.example/plants/{{plant_id}}"
+"#).unwrap();
+        let mut executor = ActionExecutor::new();
+        let outbox = SideEffectOutbox::new();
+        executor.outbox = Some(outbox.clone());
+        executor.side_effect_handler = Some(Box::new(|_, _| panic!("side effects must not run inline")));
+
+        let mut params = PropertyMap::new();
+        params.insert("plant_id".to_string(), PropertyValue::String("p2".to_string()));
+        let action = Action::new("retire_plant".to_string(), params, "tester".to_string());
+        let result = executor.execute(&action, &action_type, &ActionContext::new("tester".to_string())).unwrap();
+
+        assert_eq!(result.side_effects_triggered.len(), 1);
+        let triggered = &result.side_effects_triggered[0];
+        assert_eq!(triggered.status, SideEffectStatus::Enqueued);
+        let record = outbox.side_effect_status(triggered.effect_id.as_deref().unwrap()).unwrap();
+        assert_eq!(record.status, SideEffectStatus::Enqueued);
+        assert_eq!(
+            record.config.get("url"),
+            Some(&PropertyValue::String("https://hooks.example/plants/p2".to_string()))
+        );
+
+        // Nothing is enqueued for an action whose operations fail
+        executor.object_operation_handler = Some(Box::new(|_, _, _| Err("store unavailable".to_string())));
+        assert!(executor.execute(&action, &action_type, &ActionContext::new("tester".to_string())).is_err());
+        assert_eq!(outbox.list().len(), 1);
+    }
+
+    type Objects = std::sync::Arc<std::sync::Mutex<HashMap<String, PropertyMap>>>;
+    
+    /// Executor over an in-memory store of objects keyed by `id`, whose `audit` type
+    /// rejects every write
+    fn in_memory_executor(objects: &Objects, links: &std::sync::Arc<std::sync::Mutex<Vec<String>>>) -> ActionExecutor {
+        let id = |properties: Option<&PropertyMap>| {
+            properties.and_then(|properties| properties.get("id")).map(|id| id.to_string()).ok_or("no id".to_string())
+        };
+        let mut executor = ActionExecutor::new();
+        let store = objects.clone();
+        executor.object_operation_handler = Some(Box::new(move |operation, object_type, properties| {
+            if object_type == "audit" {
+                return Err("audit log unavailable".to_string());
+            }
+            let object_id = id(properties)?;
+            let mut store = store.lock().unwrap();
+            match operation {
+                OperationType::CreateObject => {
+                    store.insert(object_id.clone(), properties.unwrap().clone());
+                }
+                OperationType::UpdateObject => {
+                    let current = store.get_mut(&object_id).ok_or(format!("{} not found", object_id))?;
+                    for (key, value) in properties.unwrap().iter() {
+                        current.insert(key.clone(), value.clone());
+                    }
+                }
+                _ => {
+                    store.remove(&object_id);
+                }
+            }
+            Ok(object_id)
+        }));
+        let store = objects.clone();
+        executor.object_snapshot_handler = Some(Box::new(move |_, properties| {
+            Ok(store.lock().unwrap().get(&id(Some(properties))?).cloned())
+        }));
+        let created = links.clone();
+        executor.link_operation_handler = Some(Box::new(move |operation, _, from, to, _| {
+            let link = format!("{}->{}", from, to);
+            match operation {
+                OperationType::CreateLink => created.lock().unwrap().push(link.clone()),
+                _ => created.lock().unwrap().retain(|existing| existing != &link),
+            }
+            Ok(link)
+        }));
+        executor.side_effect_handler = Some(Box::new(|_, _| panic!("side effects must not run for a failed action")));
+        executor
+    }
+    
+    #[test]
+    fn test_failed_operation_rolls_back_earlier_ones() {
+        let action_type: ActionType = serde_yaml::from_str(r#"
+id: "open_ticket"
+displayName: "Open Ticket"
+executionMode: rollback_on_error
+parameters:
+  - id: "ticket"
+    type: "string"
+  - id: "queue"
+    type: "string"
+logic:
+  - operation: "create_object"
+    type: "ticket"
+    properties:
+      properties:
+        id: "{{ticket}}"
+        status: "open"
+  - operation: "update_object"
+    type: "queue"
+    properties:
+      properties:
+        id: "{{queue}}"
+        status: "busy"
+  - operation: "create_link"
+    linkType: "in_queue"
+    from: "{{ticket}}"
+    to: "{{queue}}"
+side_effects:
+  - type: "log"
+"#).unwrap();
+        let objects = Objects::default();
+        let links = std::sync::Arc::default();
+        let executor = in_memory_executor(&objects, &links);
+        let mut params = PropertyMap::new();
+        params.insert("ticket".to_string(), PropertyValue::String("t1".to_string()));
+        params.insert("queue".to_string(), PropertyValue::String("q1".to_string()));
+        let action = Action::new("open_ticket".to_string(), params, "tester".to_string());
+        
+        // The queue does not exist, so the update fails and the ticket is deleted again
+        let result = match executor.execute(&action, &action_type, &ActionContext::new("tester".to_string())) {
+            Err(ValidationError::ExecutionFailed(result)) => result,
+            other => panic!("expected the action to fail, got {:?}", other),
+        };
+        assert!(result.rolled_back);
+        assert!(result.compensation_errors.is_empty(), "{:?}", result.compensation_errors);
+        assert_eq!(result.operations_executed, vec!["t1".to_string()]);
+        assert_eq!(result.errors, vec!["q1 not found".to_string()]);
+        assert!(objects.lock().unwrap().is_empty());
+        assert!(links.lock().unwrap().is_empty());
+        
+        // Stopping leaves the ticket in place, continuing also creates the link
+        let mut stop = action_type.clone();
+        stop.execution_mode = Some(ExecutionMode::StopOnError);
+        assert!(executor.execute(&action, &stop, &ActionContext::new("tester".to_string())).is_err());
+        assert!(objects.lock().unwrap().contains_key("t1"));
+        assert!(links.lock().unwrap().is_empty());
+    }
+    
+    #[test]
+    fn test_rollback_restores_updates_and_reports_failed_compensation() {
+        let action_type: ActionType = serde_yaml::from_str(r#"
+id: "escalate"
+displayName: "Escalate"
+parameters:
+  - id: "ticket"
+    type: "string"
+logic:
+  - operation: "update_object"
+    type: "ticket"
+    properties:
+      properties:
+        id: "{{ticket}}"
+        status: "escalated"
+  - operation: "create_link"
+    linkType: "escalated_to"
+    from: "{{ticket}}"
+    to: "manager"
+  - operation: "create_object"
+    type: "audit"
+"#).unwrap();
+        let objects = Objects::default();
+        let mut ticket = PropertyMap::new();
+        ticket.insert("id".to_string(), PropertyValue::String("t1".to_string()));
+        ticket.insert("status".to_string(), PropertyValue::String("open".to_string()));
+        objects.lock().unwrap().insert("t1".to_string(), ticket);
+        let links = std::sync::Arc::default();
+        let executor = in_memory_executor(&objects, &links).with_execution_mode(ExecutionMode::RollbackOnError);
+        let mut params = PropertyMap::new();
+        params.insert("ticket".to_string(), PropertyValue::String("t1".to_string()));
+        let action = Action::new("escalate".to_string(), params, "tester".to_string());
+        let context = ActionContext::new("tester".to_string());
+        
+        let Err(ValidationError::ExecutionFailed(result)) = executor.execute(&action, &action_type, &context) else {
+            panic!("expected the action to fail");
+        };
+        assert!(result.rolled_back);
+        assert_eq!(result.operations_executed.len(), 2);
+        assert_eq!(
+            objects.lock().unwrap()["t1"].get("status"),
+            Some(&PropertyValue::String("open".to_string()))
+        );
+        assert!(links.lock().unwrap().is_empty());
+        
+        // Without snapshots the update cannot be undone, and the action says so
+        let mut executor = executor;
+        executor.object_snapshot_handler = None;
+        let Err(ValidationError::ExecutionFailed(result)) = executor.execute(&action, &action_type, &context) else {
+            panic!("expected the action to fail");
+        };
+        assert!(result.rolled_back);
+        assert_eq!(result.compensation_errors.len(), 1);
+        assert!(result.compensation_errors[0].contains("no object snapshot handler"), "{:?}", result.compensation_errors);
+        assert!(result.errors.iter().any(|e| e.starts_with("Rollback incomplete")), "{:?}", result.errors);
+        assert!(links.lock().unwrap().is_empty());
+    }
+    
     #[test]
     fn test_template_substitution_missing_param() {
         let executor = ActionExecutor::new();
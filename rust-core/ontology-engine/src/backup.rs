@@ -0,0 +1,463 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+const MANIFEST_FILE: &str = "manifest.json";
+
+/// A piece of store-independent state that can be backed up and restored
+/// (event log, writeback queue, model registry, ...). Components serialize
+/// themselves so the backup manager never reaches into their internals.
+#[async_trait]
+pub trait BackupComponent: Send + Sync {
+    /// Stable name used for the component's file inside a backup
+    fn backup_name(&self) -> String;
+
+    /// Serialize the component's full state
+    async fn export_for_backup(&self) -> Result<Vec<u8>, BackupError>;
+
+    /// Replace the component's state with previously exported data
+    async fn import_from_backup(&mut self, data: &[u8]) -> Result<(), BackupError>;
+
+    /// Location of the component's live file, if it is file-backed (lets the CLI restore it offline)
+    fn live_path(&self) -> Option<PathBuf> {
+        None
+    }
+}
+
+/// Shared handle to a registered component
+pub type SharedBackupComponent = Arc<RwLock<dyn BackupComponent>>;
+
+/// Manifest written alongside every backup
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BackupManifest {
+    pub backup_id: String,
+    pub created_at: DateTime<Utc>,
+    pub components: Vec<BackupManifestEntry>,
+
+    /// Safety copies taken before a restore are not subject to rotation
+    #[serde(default)]
+    pub safety_copy: bool,
+}
+
+/// Manifest entry for one component
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BackupManifestEntry {
+    pub name: String,
+    pub file: String,
+    pub checksum: String, // SHA-256, hex encoded
+    pub size: u64,
+
+    #[serde(default)]
+    pub live_path: Option<PathBuf>,
+}
+
+/// Creates, rotates and restores backups of registered components
+pub struct BackupManager {
+    backup_root: PathBuf,
+    retention: usize,
+    components: Vec<SharedBackupComponent>,
+}
+
+impl BackupManager {
+    /// Create a manager writing backups under `backup_root`, keeping the newest `retention` backups
+    pub fn new(backup_root: impl Into<PathBuf>, retention: usize) -> Self {
+        Self {
+            backup_root: backup_root.into(),
+            retention: retention.max(1),
+            components: Vec::new(),
+        }
+    }
+
+    /// Register a component to include in backups
+    pub fn register(&mut self, component: SharedBackupComponent) {
+        self.components.push(component);
+    }
+
+    /// Take a backup of all components and rotate old backups
+    pub async fn create_backup(&self) -> Result<BackupManifest, BackupError> {
+        let manifest = self.write_backup("backup", false).await?;
+        self.rotate()?;
+        Ok(manifest)
+    }
+
+    /// List backup manifests, newest first
+    pub fn list_backups(&self) -> Result<Vec<BackupManifest>, BackupError> {
+        list_manifests(&self.backup_root)
+    }
+
+    /// Restore all components from a backup. Checksums are verified up front and a
+    /// safety copy of the current state is taken; if any import fails, every component
+    /// is rolled back to the safety copy.
+    pub async fn restore(&self, backup_id: &str) -> Result<BackupManifest, BackupError> {
+        let manifest = read_manifest(&self.backup_root, backup_id)?;
+        let payloads = read_verified_payloads(&self.backup_root, &manifest)?;
+
+        // Every registered component must be present before anything is touched
+        let mut plan = Vec::new();
+        for component in &self.components {
+            let name = component.read().await.backup_name();
+            let data = payloads.iter()
+                .find(|(entry, _)| entry.name == name)
+                .map(|(_, data)| data.clone())
+                .ok_or_else(|| BackupError::MissingComponent(name.clone()))?;
+            plan.push((component.clone(), data));
+        }
+
+        let safety = self.write_backup("pre-restore", true).await?;
+        let safety_payloads = read_verified_payloads(&self.backup_root, &safety)?;
+
+        for (index, (component, data)) in plan.iter().enumerate() {
+            if let Err(e) = component.write().await.import_from_backup(data).await {
+                // Roll back the components already restored (and the one that failed)
+                for (rollback, _) in plan.iter().take(index + 1) {
+                    let mut rollback = rollback.write().await;
+                    let name = rollback.backup_name();
+                    if let Some((_, original)) = safety_payloads.iter().find(|(entry, _)| entry.name == name) {
+                        rollback.import_from_backup(original).await?;
+                    }
+                }
+                return Err(e);
+            }
+        }
+
+        Ok(manifest)
+    }
+
+    /// Spawn a background job that takes a backup every `interval`
+    pub fn spawn_scheduled(self: Arc<Self>, interval: std::time::Duration) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.tick().await; // First tick completes immediately
+            loop {
+                ticker.tick().await;
+                if let Err(e) = self.create_backup().await {
+                    tracing::error!("Scheduled backup failed: {}", e);
+                }
+            }
+        })
+    }
+
+    async fn write_backup(&self, prefix: &str, safety_copy: bool) -> Result<BackupManifest, BackupError> {
+        let created_at = Utc::now();
+        let backup_id = format!(
+            "{}-{}-{}",
+            prefix,
+            created_at.format("%Y%m%dT%H%M%S%3fZ"),
+            &uuid::Uuid::new_v4().simple().to_string()[..8]
+        );
+
+        // Write into a temporary directory and rename, so partial backups are never listed
+        let tmp_dir = self.backup_root.join(format!(".{}.tmp", backup_id));
+        std::fs::create_dir_all(&tmp_dir)?;
+
+        let mut components = Vec::new();
+        for component in &self.components {
+            let component = component.read().await;
+            let name = component.backup_name();
+            let data = component.export_for_backup().await?;
+            let file = format!("{}.bak", name);
+            std::fs::write(tmp_dir.join(&file), &data)?;
+            components.push(BackupManifestEntry {
+                name,
+                file,
+                checksum: checksum(&data),
+                size: data.len() as u64,
+                live_path: component.live_path(),
+            });
+        }
+
+        let manifest = BackupManifest {
+            backup_id: backup_id.clone(),
+            created_at,
+            components,
+            safety_copy,
+        };
+        let manifest_json = serde_json::to_vec_pretty(&manifest)
+            .map_err(|e| BackupError::Serialization(e.to_string()))?;
+        std::fs::write(tmp_dir.join(MANIFEST_FILE), manifest_json)?;
+        std::fs::rename(&tmp_dir, self.backup_root.join(&backup_id))?;
+
+        Ok(manifest)
+    }
+
+    /// Delete the oldest regular backups beyond the retention count
+    fn rotate(&self) -> Result<(), BackupError> {
+        let backups: Vec<BackupManifest> = self.list_backups()?
+            .into_iter()
+            .filter(|m| !m.safety_copy)
+            .collect();
+        for old in backups.iter().skip(self.retention) {
+            std::fs::remove_dir_all(self.backup_root.join(&old.backup_id))?;
+        }
+        Ok(())
+    }
+}
+
+/// File-backed component: backs up and restores a single live file
+pub struct FileBackupComponent {
+    name: String,
+    path: PathBuf,
+}
+
+impl FileBackupComponent {
+    pub fn new(name: impl Into<String>, path: impl Into<PathBuf>) -> Self {
+        Self {
+            name: name.into(),
+            path: path.into(),
+        }
+    }
+}
+
+#[async_trait]
+impl BackupComponent for FileBackupComponent {
+    fn backup_name(&self) -> String {
+        self.name.clone()
+    }
+
+    async fn export_for_backup(&self) -> Result<Vec<u8>, BackupError> {
+        match std::fs::read(&self.path) {
+            Ok(data) => Ok(data),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn import_from_backup(&mut self, data: &[u8]) -> Result<(), BackupError> {
+        write_file_atomically(&self.path, data)
+    }
+
+    fn live_path(&self) -> Option<PathBuf> {
+        Some(self.path.clone())
+    }
+}
+
+/// Restore the file-backed components recorded in a backup manifest, without a running server.
+/// A safety copy of each live file is written next to it before anything is replaced.
+pub fn restore_files_from_backup(backup_root: &Path, backup_id: &str) -> Result<Vec<PathBuf>, BackupError> {
+    let manifest = read_manifest(backup_root, backup_id)?;
+    let payloads = read_verified_payloads(backup_root, &manifest)?;
+
+    let mut safety_copies = Vec::new();
+    for (entry, _) in &payloads {
+        if let Some(path) = &entry.live_path {
+            if path.exists() {
+                let safety = path.with_extension("pre-restore");
+                std::fs::copy(path, &safety)?;
+                safety_copies.push((path.clone(), safety));
+            }
+        }
+    }
+
+    let mut restored = Vec::new();
+    for (entry, data) in &payloads {
+        if let Some(path) = &entry.live_path {
+            if let Err(e) = write_file_atomically(path, data) {
+                for (live, safety) in &safety_copies {
+                    std::fs::copy(safety, live)?;
+                }
+                return Err(e);
+            }
+            restored.push(path.clone());
+        }
+    }
+
+    Ok(restored)
+}
+
+/// List backup manifests under a backup root, newest first
+pub fn list_manifests(backup_root: &Path) -> Result<Vec<BackupManifest>, BackupError> {
+    let mut manifests = Vec::new();
+    if !backup_root.exists() {
+        return Ok(manifests);
+    }
+    for entry in std::fs::read_dir(backup_root)? {
+        let path = entry?.path().join(MANIFEST_FILE);
+        if path.exists() {
+            let content = std::fs::read(&path)?;
+            let manifest: BackupManifest = serde_json::from_slice(&content)
+                .map_err(|e| BackupError::Serialization(format!("{}: {}", path.display(), e)))?;
+            manifests.push(manifest);
+        }
+    }
+    manifests.sort_by_key(|m| std::cmp::Reverse(m.created_at));
+    Ok(manifests)
+}
+
+fn read_manifest(backup_root: &Path, backup_id: &str) -> Result<BackupManifest, BackupError> {
+    let path = backup_root.join(backup_id).join(MANIFEST_FILE);
+    if !path.exists() {
+        return Err(BackupError::NotFound(backup_id.to_string()));
+    }
+    let content = std::fs::read(&path)?;
+    serde_json::from_slice(&content).map_err(|e| BackupError::Serialization(e.to_string()))
+}
+
+/// The verified payload of one component of a backup, for components that are restored by
+/// their own tooling rather than as a single live file (e.g. a directory of segments)
+pub fn read_component_backup(backup_root: &Path, backup_id: &str, name: &str) -> Result<Vec<u8>, BackupError> {
+    let manifest = read_manifest(backup_root, backup_id)?;
+    let entry = manifest
+        .components
+        .iter()
+        .find(|entry| entry.name == name)
+        .ok_or_else(|| BackupError::MissingComponent(name.to_string()))?;
+    let data = std::fs::read(backup_root.join(backup_id).join(&entry.file))?;
+    if checksum(&data) != entry.checksum {
+        return Err(BackupError::ChecksumMismatch(entry.name.clone()));
+    }
+    Ok(data)
+}
+
+/// Read every component payload of a backup, failing if any checksum does not match
+fn read_verified_payloads(
+    backup_root: &Path,
+    manifest: &BackupManifest,
+) -> Result<Vec<(BackupManifestEntry, Vec<u8>)>, BackupError> {
+    let dir = backup_root.join(&manifest.backup_id);
+    let mut payloads = Vec::new();
+    for entry in &manifest.components {
+        let data = std::fs::read(dir.join(&entry.file))?;
+        if checksum(&data) != entry.checksum {
+            return Err(BackupError::ChecksumMismatch(entry.name.clone()));
+        }
+        payloads.push((entry.clone(), data));
+    }
+    Ok(payloads)
+}
+
+fn write_file_atomically(path: &Path, data: &[u8]) -> Result<(), BackupError> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let tmp = path.with_extension("restore-tmp");
+    std::fs::write(&tmp, data)?;
+    std::fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+fn checksum(data: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(data))
+}
+
+/// Errors that can occur during backup or restore
+#[derive(Debug, thiserror::Error)]
+pub enum BackupError {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Serialization error: {0}")]
+    Serialization(String),
+
+    #[error("Backup not found: {0}")]
+    NotFound(String),
+
+    #[error("Checksum mismatch for component '{0}'")]
+    ChecksumMismatch(String),
+
+    #[error("Backup does not contain component '{0}'")]
+    MissingComponent(String),
+
+    #[error("Component error: {0}")]
+    Component(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(label: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("ontology-backup-{}-{}", label, uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[tokio::test]
+    async fn test_backup_corrupt_and_restore() {
+        let root = temp_dir("restore");
+        let live = root.join("live/events.json");
+        std::fs::create_dir_all(live.parent().unwrap()).unwrap();
+        std::fs::write(&live, b"{\"events\":[1,2,3]}").unwrap();
+
+        let component: SharedBackupComponent = Arc::new(RwLock::new(FileBackupComponent::new("event_log", &live)));
+        let mut manager = BackupManager::new(root.join("backups"), 3);
+        manager.register(component);
+
+        let manifest = manager.create_backup().await.unwrap();
+        assert_eq!(manifest.components.len(), 1);
+
+        std::fs::write(&live, b"corrupted").unwrap();
+        manager.restore(&manifest.backup_id).await.unwrap();
+        assert_eq!(std::fs::read(&live).unwrap(), b"{\"events\":[1,2,3]}");
+
+        // A safety copy of the corrupted state was taken before restoring
+        assert!(manager.list_backups().unwrap().iter().any(|m| m.safety_copy));
+
+        std::fs::remove_dir_all(&root).ok();
+    }
+
+    #[tokio::test]
+    async fn test_restore_rejects_tampered_backup() {
+        let root = temp_dir("tampered");
+        let live = root.join("queue.json");
+        std::fs::write(&live, b"original").unwrap();
+
+        let mut manager = BackupManager::new(root.join("backups"), 3);
+        manager.register(Arc::new(RwLock::new(FileBackupComponent::new("writeback_queue", &live))));
+        let manifest = manager.create_backup().await.unwrap();
+
+        std::fs::write(root.join("backups").join(&manifest.backup_id).join("writeback_queue.bak"), b"tampered").unwrap();
+        std::fs::write(&live, b"current").unwrap();
+
+        assert!(matches!(manager.restore(&manifest.backup_id).await, Err(BackupError::ChecksumMismatch(_))));
+        assert_eq!(std::fs::read(&live).unwrap(), b"current");
+
+        std::fs::remove_dir_all(&root).ok();
+    }
+
+    #[tokio::test]
+    async fn test_backup_rotation() {
+        let root = temp_dir("rotation");
+        let live = root.join("registry.json");
+        std::fs::write(&live, b"{}").unwrap();
+
+        let mut manager = BackupManager::new(root.join("backups"), 2);
+        manager.register(Arc::new(RwLock::new(FileBackupComponent::new("model_registry", &live))));
+        for _ in 0..4 {
+            manager.create_backup().await.unwrap();
+        }
+        assert_eq!(manager.list_backups().unwrap().len(), 2);
+
+        std::fs::remove_dir_all(&root).ok();
+    }
+
+    #[tokio::test]
+    async fn test_restore_files_from_backup_offline() {
+        let root = temp_dir("offline");
+        let live = root.join("overlay.yaml");
+        std::fs::write(&live, b"ontology: {}").unwrap();
+
+        let mut manager = BackupManager::new(root.join("backups"), 2);
+        manager.register(Arc::new(RwLock::new(FileBackupComponent::new("overlay", &live))));
+        let manifest = manager.create_backup().await.unwrap();
+
+        std::fs::write(&live, b"broken").unwrap();
+        let restored = restore_files_from_backup(&root.join("backups"), &manifest.backup_id).unwrap();
+        assert_eq!(restored, vec![live.clone()]);
+        assert_eq!(std::fs::read(&live).unwrap(), b"ontology: {}");
+        assert_eq!(std::fs::read(live.with_extension("pre-restore")).unwrap(), b"broken");
+        assert_eq!(
+            read_component_backup(&root.join("backups"), &manifest.backup_id, "overlay").unwrap(),
+            b"ontology: {}"
+        );
+        assert!(matches!(
+            read_component_backup(&root.join("backups"), &manifest.backup_id, "event_log"),
+            Err(BackupError::MissingComponent(_))
+        ));
+
+        std::fs::remove_dir_all(&root).ok();
+    }
+}
@@ -0,0 +1,49 @@
+//! Backup maintenance CLI
+//!
+//! Usage:
+//!   ontology-backup list <backup_root>
+//!   ontology-backup restore <backup_root> <backup_id>
+//!
+//! `restore` replaces file-backed components with their backed-up contents after
+//! verifying checksums; a `.pre-restore` copy of each live file is kept alongside it.
+//! Components without a live file (e.g. database-backed queues) are restored by the
+//! running service through `BackupManager::restore`. The file-backed event log is a
+//! directory of segments and is restored with `event-log-restore` instead.
+
+use ontology_engine::backup::{list_manifests, restore_files_from_backup};
+use std::path::PathBuf;
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    let result = match args.iter().map(String::as_str).collect::<Vec<_>>().as_slice() {
+        ["list", root] => list(PathBuf::from(root)),
+        ["restore", root, backup_id] => restore(PathBuf::from(root), backup_id),
+        _ => {
+            eprintln!("Usage:\n  ontology-backup list <backup_root>\n  ontology-backup restore <backup_root> <backup_id>");
+            std::process::exit(2);
+        }
+    };
+
+    if let Err(e) = result {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    }
+}
+
+fn list(root: PathBuf) -> Result<(), ontology_engine::BackupError> {
+    for manifest in list_manifests(&root)? {
+        let kind = if manifest.safety_copy { " (pre-restore safety copy)" } else { "" };
+        println!("{}  {}  {} component(s){}", manifest.backup_id, manifest.created_at.to_rfc3339(), manifest.components.len(), kind);
+    }
+    Ok(())
+}
+
+fn restore(root: PathBuf, backup_id: &str) -> Result<(), ontology_engine::BackupError> {
+    let restored = restore_files_from_backup(&root, backup_id)?;
+    for path in &restored {
+        println!("Restored {}", path.display());
+    }
+    println!("Restored {} file(s) from backup '{}'", restored.len(), backup_id);
+    Ok(())
+}
@@ -1,5 +1,8 @@
+use crate::meta_model::ObjectType;
 use crate::property::{PropertyMap, PropertyType, PropertyValue};
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 /// Computed property definition - a property whose value is calculated from other properties
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -10,6 +13,7 @@ pub struct ComputedProperty {
     pub display_name: String,
 
     #[serde(rename = "type")]
+    #[serde(deserialize_with = "crate::property::deserialize_property_type")]
     pub property_type: PropertyType,
 
     #[serde(default)]
@@ -30,6 +34,70 @@ pub struct ComputedProperty {
     #[serde(rename = "cacheTtl")]
     #[serde(default)]
     pub cache_ttl: Option<u64>,
+
+    /// When the value is computed: at read time, at write time, or both
+    #[serde(default)]
+    pub materialization: Materialization,
+
+    /// Staleness window in seconds after which a `Both` value is recomputed on read
+    #[serde(default)]
+    pub freshness: Option<u64>,
+
+    /// Allow write-time materialization to depend on model-bound properties
+    #[serde(rename = "allowModelDependencies")]
+    #[serde(default)]
+    pub allow_model_dependencies: bool,
+}
+
+/// When a computed property is evaluated
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Materialization {
+    /// Computed on every read, never stored
+    #[default]
+    OnRead,
+    /// Computed during ingestion / writeback and stored as a regular property
+    OnWrite,
+    /// Stored at write time and recomputed on read once older than `freshness`
+    Both,
+}
+
+impl ComputedProperty {
+    /// Whether the value is computed on the write paths and stored in the index
+    pub fn is_materialized(&self) -> bool {
+        matches!(self.materialization, Materialization::OnWrite | Materialization::Both)
+    }
+
+    /// Validate this computed property against the object type it belongs to
+    pub fn validate_for(&self, object_type: &ObjectType) -> Result<(), String> {
+        if object_type.get_property(&self.id).is_some() {
+            return Err(format!(
+                "Computed property '{}' on object type '{}' conflicts with a stored property",
+                self.id, object_type.id
+            ));
+        }
+
+        // Stored values would silently go stale when the model is retrained
+        if self.is_materialized() && !self.allow_model_dependencies {
+            for dependency in &self.dependencies {
+                if object_type.get_property(dependency).map_or(false, |p| p.model_binding.is_some()) {
+                    return Err(format!(
+                        "Computed property '{}' on object type '{}' is materialized on write but depends on model-bound property '{}' (set allowModelDependencies to permit this)",
+                        self.id, object_type.id, dependency
+                    ));
+                }
+            }
+        }
+
+        if self.freshness.is_some() && self.materialization != Materialization::Both {
+            return Err(format!(
+                "Computed property '{}' on object type '{}' sets freshness, which only applies to 'both' materialization",
+                self.id, object_type.id
+            ));
+        }
+
+        Ok(())
+    }
 }
 
 /// Expression types for computed properties
@@ -59,6 +127,55 @@ pub enum ComputedExpression {
         property: String,
         aggregation: AggregationType,
     },
+    /// The result of the first branch whose condition holds, else the default
+    Branches {
+        branches: Vec<ConditionalBranch>,
+        #[serde(default)]
+        default: Option<BranchResult>,
+    },
+}
+
+/// One `when` / `then` pair of a `branches` expression
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConditionalBranch {
+    pub when: BranchCondition,
+    pub then: BranchResult,
+}
+
+/// Comparison of a property against a literal or another property
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BranchCondition {
+    pub property: String,
+    pub operator: ComparisonOperator,
+    pub value: Operand,
+}
+
+/// Comparison operators for branch conditions, named like the store's filter operators
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ComparisonOperator {
+    Eq,
+    Ne,
+    Gt,
+    Lt,
+    Gte,
+    Lte,
+}
+
+/// A property reference (`{ property: score }`) or a literal value
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Operand {
+    Property { property: String },
+    Literal(PropertyValue),
+}
+
+/// What a branch evaluates to: a nested expression (anything with a `type`), or an operand
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum BranchResult {
+    Expression(Box<ComputedExpression>),
+    Operand(Operand),
 }
 
 /// Aggregation types for link aggregations
@@ -75,6 +192,88 @@ pub enum AggregationType {
 /// Evaluator for computed properties
 pub struct ComputedPropertyEvaluator;
 
+/// A computed property with its expression parsed once, for evaluation against many objects
+#[derive(Debug, Clone)]
+pub struct CompiledComputedProperty {
+    pub id: String,
+    expression: CompiledExpression,
+}
+
+impl CompiledComputedProperty {
+    /// Evaluate against one object's properties
+    pub fn evaluate<F>(
+        &self,
+        properties: &PropertyMap,
+        get_linked_property: Option<F>,
+    ) -> Result<PropertyValue, ComputedPropertyError>
+    where
+        F: Fn(&str, &str) -> Option<PropertyValue>,
+    {
+        ComputedPropertyEvaluator::evaluate_compiled(&self.expression, properties, get_linked_property.as_ref())
+    }
+}
+
+/// Parsed form of a `ComputedExpression`
+#[derive(Debug, Clone)]
+enum CompiledExpression {
+    Arithmetic(Vec<ArithmeticToken>),
+    Function {
+        function_id: String,
+        parameters: Vec<String>,
+    },
+    Conditional {
+        left: String,
+        operator: ComparisonOperator,
+        right: String,
+        then_token: String,
+        else_token: String,
+    },
+    StringFormat(Vec<TemplatePart>),
+    LinkAggregation {
+        link_type: String,
+        property: String,
+        aggregation: AggregationType,
+    },
+    Branches {
+        branches: Vec<(BranchCondition, CompiledResult)>,
+        default: Option<CompiledResult>,
+    },
+}
+
+#[derive(Debug, Clone)]
+enum ArithmeticToken {
+    Operator(char),
+    /// A property name, or a number when no property has that name
+    Operand { name: String, number: Option<f64> },
+}
+
+#[derive(Debug, Clone)]
+enum TemplatePart {
+    Text(String),
+    Placeholder(String),
+}
+
+#[derive(Debug, Clone)]
+enum CompiledResult {
+    Expression(Box<CompiledExpression>),
+    Operand(Operand),
+}
+
+impl ComparisonOperator {
+    /// Whether `left <op> right` holds; values that cannot be ordered fail every ordering
+    fn holds(&self, left: &PropertyValue, right: &PropertyValue) -> bool {
+        let ordering = ComputedPropertyEvaluator::compare_values(left, right);
+        match self {
+            ComparisonOperator::Eq => left == right || ordering == Some(std::cmp::Ordering::Equal),
+            ComparisonOperator::Ne => left != right && ordering != Some(std::cmp::Ordering::Equal),
+            ComparisonOperator::Gt => ordering.map_or(false, |o| o == std::cmp::Ordering::Greater),
+            ComparisonOperator::Gte => ordering.map_or(false, |o| o != std::cmp::Ordering::Less),
+            ComparisonOperator::Lt => ordering.map_or(false, |o| o == std::cmp::Ordering::Less),
+            ComparisonOperator::Lte => ordering.map_or(false, |o| o != std::cmp::Ordering::Greater),
+        }
+    }
+}
+
 impl ComputedPropertyEvaluator {
     /// Evaluate a computed property value
     pub fn evaluate<F>(
@@ -85,25 +284,167 @@ impl ComputedPropertyEvaluator {
     where
         F: Fn(&str, &str) -> Option<PropertyValue>,
     {
-        match &computed.expression {
-            ComputedExpression::Arithmetic { expression } => {
-                Self::evaluate_arithmetic(expression, properties)
-            }
+        Self::compile(computed)?.evaluate(properties, get_linked_property)
+    }
+
+    /// Parse a computed property's expression, so that evaluating it for many objects does
+    /// not re-parse it for each one
+    pub fn compile(computed: &ComputedProperty) -> Result<CompiledComputedProperty, ComputedPropertyError> {
+        Ok(CompiledComputedProperty {
+            id: computed.id.clone(),
+            expression: Self::compile_expression(&computed.expression)?,
+        })
+    }
+
+    fn compile_expression(expression: &ComputedExpression) -> Result<CompiledExpression, ComputedPropertyError> {
+        match expression {
+            ComputedExpression::Arithmetic { expression } => Self::compile_arithmetic(expression),
             ComputedExpression::Function {
                 function_id,
                 parameters,
-            } => Self::evaluate_function(function_id, parameters, properties),
+            } => Ok(CompiledExpression::Function {
+                function_id: function_id.clone(),
+                parameters: parameters.clone(),
+            }),
             ComputedExpression::Conditional {
                 condition,
                 then_expression,
                 else_expression,
-            } => {
-                Self::evaluate_conditional(condition, then_expression, else_expression, properties)
+            } => Self::compile_conditional(condition, then_expression, else_expression),
+            ComputedExpression::StringFormat { template } => Ok(Self::compile_string_format(template)),
+            ComputedExpression::LinkAggregation {
+                link_type,
+                property,
+                aggregation,
+            } => Ok(CompiledExpression::LinkAggregation {
+                link_type: link_type.clone(),
+                property: property.clone(),
+                aggregation: aggregation.clone(),
+            }),
+            ComputedExpression::Branches { branches, default } => Ok(CompiledExpression::Branches {
+                branches: branches
+                    .iter()
+                    .map(|branch| Ok((branch.when.clone(), Self::compile_result(&branch.then)?)))
+                    .collect::<Result<_, ComputedPropertyError>>()?,
+                default: default.as_ref().map(Self::compile_result).transpose()?,
+            }),
+        }
+    }
+
+    fn compile_result(result: &BranchResult) -> Result<CompiledResult, ComputedPropertyError> {
+        Ok(match result {
+            BranchResult::Expression(expression) => {
+                CompiledResult::Expression(Box::new(Self::compile_expression(expression)?))
             }
-            ComputedExpression::StringFormat { template } => {
-                Self::evaluate_string_format(template, properties)
+            BranchResult::Operand(operand) => CompiledResult::Operand(operand.clone()),
+        })
+    }
+
+    fn compile_arithmetic(expression: &str) -> Result<CompiledExpression, ComputedPropertyError> {
+        let tokens: Vec<ArithmeticToken> = expression
+            .split_whitespace()
+            .map(|part| match part {
+                "+" | "-" | "*" | "/" => ArithmeticToken::Operator(part.chars().next().unwrap()),
+                _ => ArithmeticToken::Operand {
+                    name: part.to_string(),
+                    number: part.parse::<f64>().ok(),
+                },
+            })
+            .collect();
+
+        if !tokens.iter().any(|t| matches!(t, ArithmeticToken::Operand { .. })) {
+            return Err(ComputedPropertyError::EvaluationError(
+                "Empty arithmetic expression".to_string(),
+            ));
+        }
+        Ok(CompiledExpression::Arithmetic(tokens))
+    }
+
+    fn compile_conditional(
+        condition: &str,
+        then_expr: &str,
+        else_expr: &str,
+    ) -> Result<CompiledExpression, ComputedPropertyError> {
+        // Parse condition: "operand1 op operand2"
+        let parts: Vec<&str> = condition.splitn(3, ' ').collect();
+        if parts.len() != 3 {
+            return Err(ComputedPropertyError::EvaluationError(format!(
+                "Invalid condition '{}'. Expected format: 'property op value'",
+                condition
+            )));
+        }
+        let operator = match parts[1] {
+            "==" | "=" => ComparisonOperator::Eq,
+            "!=" => ComparisonOperator::Ne,
+            ">" => ComparisonOperator::Gt,
+            ">=" => ComparisonOperator::Gte,
+            "<" => ComparisonOperator::Lt,
+            "<=" => ComparisonOperator::Lte,
+            op => {
+                return Err(ComputedPropertyError::EvaluationError(format!(
+                    "Unknown operator '{}'. Valid: ==, !=, >, >=, <, <=",
+                    op
+                )))
             }
-            ComputedExpression::LinkAggregation {
+        };
+
+        Ok(CompiledExpression::Conditional {
+            left: parts[0].to_string(),
+            operator,
+            right: parts[2].to_string(),
+            then_token: then_expr.to_string(),
+            else_token: else_expr.to_string(),
+        })
+    }
+
+    /// Split a template into text and `{property_id}` placeholders
+    fn compile_string_format(template: &str) -> CompiledExpression {
+        let mut parts = Vec::new();
+        let mut rest = template;
+        while let Some(start) = rest.find('{') {
+            let Some(length) = rest[start + 1..].find('}') else {
+                break;
+            };
+            if start > 0 {
+                parts.push(TemplatePart::Text(rest[..start].to_string()));
+            }
+            parts.push(TemplatePart::Placeholder(rest[start + 1..start + 1 + length].to_string()));
+            rest = &rest[start + 2 + length..];
+        }
+        if !rest.is_empty() {
+            parts.push(TemplatePart::Text(rest.to_string()));
+        }
+        CompiledExpression::StringFormat(parts)
+    }
+
+    fn evaluate_compiled<F>(
+        expression: &CompiledExpression,
+        properties: &PropertyMap,
+        get_linked_property: Option<&F>,
+    ) -> Result<PropertyValue, ComputedPropertyError>
+    where
+        F: Fn(&str, &str) -> Option<PropertyValue>,
+    {
+        match expression {
+            CompiledExpression::Arithmetic(tokens) => Self::evaluate_arithmetic(tokens, properties),
+            CompiledExpression::Function {
+                function_id,
+                parameters,
+            } => Self::evaluate_function(function_id, parameters, properties),
+            CompiledExpression::Conditional {
+                left,
+                operator,
+                right,
+                then_token,
+                else_token,
+            } => {
+                let left = Self::resolve_token(left, properties);
+                let right = Self::resolve_token(right, properties);
+                let branch = if operator.holds(&left, &right) { then_token } else { else_token };
+                Ok(Self::resolve_token(branch, properties))
+            }
+            CompiledExpression::StringFormat(parts) => Ok(Self::evaluate_string_format(parts, properties)),
+            CompiledExpression::LinkAggregation {
                 link_type,
                 property,
                 aggregation,
@@ -116,41 +457,52 @@ impl ComputedPropertyEvaluator {
                     ))
                 }
             }
+            CompiledExpression::Branches { branches, default } => {
+                for (condition, result) in branches {
+                    if Self::condition_holds(condition, properties)? {
+                        return Self::evaluate_result(result, properties, get_linked_property);
+                    }
+                }
+                match default {
+                    Some(result) => Self::evaluate_result(result, properties, get_linked_property),
+                    None => Err(ComputedPropertyError::EvaluationError(
+                        "No branch matched and no default is set".to_string(),
+                    )),
+                }
+            }
         }
     }
 
     fn evaluate_arithmetic(
-        expression: &str,
+        tokens: &[ArithmeticToken],
         properties: &PropertyMap,
     ) -> Result<PropertyValue, ComputedPropertyError> {
-        let parts: Vec<&str> = expression.split_whitespace().collect();
-
         let mut accumulator: Option<f64> = None;
         let mut pending_op: char = '+';
 
-        for part in &parts {
-            match *part {
-                "+" | "-" | "*" | "/" => {
-                    pending_op = part.chars().next().unwrap();
+        for token in tokens {
+            match token {
+                ArithmeticToken::Operator(op) => {
+                    pending_op = *op;
                 }
-                _ => {
-                    let value = if let Some(prop_val) = properties.get(*part) {
+                ArithmeticToken::Operand { name, number } => {
+                    let value = if let Some(prop_val) = properties.get(name) {
                         match prop_val {
                             PropertyValue::Integer(i) => *i as f64,
                             PropertyValue::Double(d) => *d,
                             _ => {
                                 return Err(ComputedPropertyError::InvalidType(format!(
                                     "Property '{}' is not numeric",
-                                    part
+                                    name
                                 )))
                             }
                         }
-                    } else if let Ok(num) = part.parse::<f64>() {
-                        num
+                    } else if let Some(num) = number {
+                        *num
                     } else {
                         return Err(ComputedPropertyError::EvaluationError(format!(
                             "Unknown token '{}': not a property name or number",
-                            part
+                            name
                         )));
                     };
 
@@ -195,47 +547,44 @@ impl ComputedPropertyEvaluator {
         ))
     }
 
-    fn evaluate_conditional(
-        condition: &str,
-        then_expr: &str,
-        else_expr: &str,
+    fn condition_holds(
+        condition: &BranchCondition,
         properties: &PropertyMap,
-    ) -> Result<PropertyValue, ComputedPropertyError> {
-        // Parse condition: "operand1 op operand2"
-        let parts: Vec<&str> = condition.splitn(3, ' ').collect();
-        if parts.len() != 3 {
-            return Err(ComputedPropertyError::EvaluationError(format!(
-                "Invalid condition '{}'. Expected format: 'property op value'",
-                condition
-            )));
+    ) -> Result<bool, ComputedPropertyError> {
+        let left = Self::resolve_operand(
+            &Operand::Property { property: condition.property.clone() },
+            properties,
+        )?;
+        let right = Self::resolve_operand(&condition.value, properties)?;
+        Ok(condition.operator.holds(&left, &right))
+    }
+
+    fn resolve_operand(operand: &Operand, properties: &PropertyMap) -> Result<PropertyValue, ComputedPropertyError> {
+        match operand {
+            Operand::Property { property } => properties.get(property).cloned().ok_or_else(|| {
+                ComputedPropertyError::MissingDependency(format!(
+                    "Condition references missing property '{}'",
+                    property
+                ))
+            }),
+            Operand::Literal(value) => Ok(value.clone()),
         }
-        let (operand1, op, operand2) = (parts[0], parts[1], parts[2]);
-
-        let val1 = Self::resolve_token(operand1, properties);
-        let val2 = Self::resolve_token(operand2, properties);
-
-        let condition_met =
-            match op {
-                "==" | "=" => val1 == val2,
-                "!=" => val1 != val2,
-                ">" => Self::compare_values(&val1, &val2)
-                    .map_or(false, |o| o == std::cmp::Ordering::Greater),
-                ">=" => Self::compare_values(&val1, &val2)
-                    .map_or(false, |o| o != std::cmp::Ordering::Less),
-                "<" => Self::compare_values(&val1, &val2)
-                    .map_or(false, |o| o == std::cmp::Ordering::Less),
-                "<=" => Self::compare_values(&val1, &val2)
-                    .map_or(false, |o| o != std::cmp::Ordering::Greater),
-                _ => {
-                    return Err(ComputedPropertyError::EvaluationError(format!(
-                        "Unknown operator '{}'. Valid: ==, !=, >, >=, <, <=",
-                        op
-                    )))
-                }
-            };
+    }
 
-        let branch = if condition_met { then_expr } else { else_expr };
-        Ok(Self::resolve_token(branch, properties))
+    fn evaluate_result<F>(
+        result: &CompiledResult,
+        properties: &PropertyMap,
+        get_linked_property: Option<&F>,
+    ) -> Result<PropertyValue, ComputedPropertyError>
+    where
+        F: Fn(&str, &str) -> Option<PropertyValue>,
+    {
+        match result {
+            CompiledResult::Expression(expression) => {
+                Self::evaluate_compiled(expression, properties, get_linked_property)
+            }
+            CompiledResult::Operand(operand) => Self::resolve_operand(operand, properties),
+        }
     }
 
     fn resolve_token(token: &str, properties: &PropertyMap) -> PropertyValue {
@@ -277,21 +626,19 @@ impl ComputedPropertyEvaluator {
         }
     }
 
-    fn evaluate_string_format(
-        template: &str,
-        properties: &PropertyMap,
-    ) -> Result<PropertyValue, ComputedPropertyError> {
-        let mut result = template.to_string();
-
-        // Simple template replacement: {property_id}
-        for (key, value) in properties.iter() {
-            let placeholder = format!("{{{}}}", key);
-            if result.contains(&placeholder) {
-                result = result.replace(&placeholder, &value.to_string());
+    fn evaluate_string_format(parts: &[TemplatePart], properties: &PropertyMap) -> PropertyValue {
+        // Placeholders without a matching property are left as written
+        let mut result = String::new();
+        for part in parts {
+            match part {
+                TemplatePart::Text(text) => result.push_str(text),
+                TemplatePart::Placeholder(key) => match properties.get(key) {
+                    Some(value) => result.push_str(&value.to_string()),
+                    None => result.push_str(&format!("{{{}}}", key)),
+                },
             }
         }
-
-        Ok(PropertyValue::String(result))
+        PropertyValue::String(result)
     }
 
     fn evaluate_link_aggregation<F>(
@@ -310,6 +657,98 @@ impl ComputedPropertyEvaluator {
     }
 }
 
+/// System-managed property recording when each materialized value was computed
+pub const MATERIALIZED_AT_PROPERTY: &str = "_materialized_at";
+
+/// Stores computed property values on the write paths and refreshes stale ones on read
+pub struct ComputedPropertyMaterializer;
+
+impl ComputedPropertyMaterializer {
+    /// Compute every OnWrite/Both property of the object type into `properties`.
+    /// Returns the properties that failed to evaluate; their previous stored values are kept.
+    pub fn materialize(
+        object_type: &ObjectType,
+        properties: &mut PropertyMap,
+        now: DateTime<Utc>,
+    ) -> Vec<(String, ComputedPropertyError)> {
+        object_type.computed_properties.iter()
+            .filter(|computed| computed.is_materialized())
+            .filter_map(|computed| {
+                Self::materialize_property(computed, properties, now)
+                    .err()
+                    .map(|e| (computed.id.clone(), e))
+            })
+            .collect()
+    }
+
+    /// Compute a single property and store it along with its materialization time
+    pub fn materialize_property(
+        computed: &ComputedProperty,
+        properties: &mut PropertyMap,
+        now: DateTime<Utc>,
+    ) -> Result<(), ComputedPropertyError> {
+        let value = ComputedPropertyEvaluator::evaluate(
+            computed,
+            properties,
+            None::<fn(&str, &str) -> Option<PropertyValue>>,
+        )?;
+        properties.insert(computed.id.clone(), value);
+
+        let mut timestamps = match properties.get(MATERIALIZED_AT_PROPERTY) {
+            Some(PropertyValue::Map(map)) => map.clone(),
+            _ => HashMap::new(),
+        };
+        timestamps.insert(computed.id.clone(), PropertyValue::DateTime(now.to_rfc3339()));
+        properties.insert(MATERIALIZED_AT_PROPERTY.to_string(), PropertyValue::Map(timestamps));
+        Ok(())
+    }
+
+    /// When a stored value was last materialized
+    pub fn materialized_at(properties: &PropertyMap, property_id: &str) -> Option<DateTime<Utc>> {
+        match properties.get(MATERIALIZED_AT_PROPERTY) {
+            Some(PropertyValue::Map(map)) => match map.get(property_id) {
+                Some(PropertyValue::DateTime(ts)) | Some(PropertyValue::String(ts)) => {
+                    DateTime::parse_from_rfc3339(ts).ok().map(|dt| dt.with_timezone(&Utc))
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Whether a `Both` property's stored value is missing or older than its freshness window
+    pub fn is_stale(computed: &ComputedProperty, properties: &PropertyMap, now: DateTime<Utc>) -> bool {
+        if !properties.contains_key(&computed.id) {
+            return true;
+        }
+        match (Self::materialized_at(properties, &computed.id), computed.freshness) {
+            (None, _) => true,
+            (Some(_), None) => false,
+            (Some(at), Some(window)) => now - at > chrono::Duration::seconds(window as i64),
+        }
+    }
+
+    /// Read-path refresh: recompute `Both` properties whose stored value is stale.
+    /// OnWrite values are served as stored; OnRead properties are not touched here.
+    pub fn refresh_stale(
+        object_type: &ObjectType,
+        properties: &mut PropertyMap,
+        now: DateTime<Utc>,
+    ) -> Vec<(String, ComputedPropertyError)> {
+        object_type.computed_properties.iter()
+            .filter(|computed| computed.materialization == Materialization::Both)
+            .filter_map(|computed| {
+                if !Self::is_stale(computed, properties, now) {
+                    return None;
+                }
+                Self::materialize_property(computed, properties, now)
+                    .err()
+                    .map(|e| (computed.id.clone(), e))
+            })
+            .collect()
+    }
+}
+
 /// Errors for computed property evaluation
 #[derive(Debug, thiserror::Error)]
 pub enum ComputedPropertyError {
@@ -338,13 +777,55 @@ mod tests {
         map
     }
 
+    fn evaluate(computed: &ComputedProperty, p: &PropertyMap) -> Result<PropertyValue, ComputedPropertyError> {
+        ComputedPropertyEvaluator::evaluate(computed, p, None::<fn(&str, &str) -> Option<PropertyValue>>)
+    }
+
+    fn expression(expression: ComputedExpression, p: &PropertyMap) -> Result<PropertyValue, ComputedPropertyError> {
+        evaluate(
+            &ComputedProperty {
+                id: "computed".to_string(),
+                display_name: "Computed".to_string(),
+                property_type: PropertyType::String,
+                description: None,
+                expression,
+                dependencies: Vec::new(),
+                cached: false,
+                cache_ttl: None,
+                materialization: Materialization::OnRead,
+                freshness: None,
+                allow_model_dependencies: false,
+            },
+            p,
+        )
+    }
+
+    fn arithmetic(expr: &str, p: &PropertyMap) -> Result<PropertyValue, ComputedPropertyError> {
+        expression(ComputedExpression::Arithmetic { expression: expr.to_string() }, p)
+    }
+
+    fn conditional(condition: &str, then: &str, otherwise: &str, p: &PropertyMap) -> Result<PropertyValue, ComputedPropertyError> {
+        expression(
+            ComputedExpression::Conditional {
+                condition: condition.to_string(),
+                then_expression: then.to_string(),
+                else_expression: otherwise.to_string(),
+            },
+            p,
+        )
+    }
+
+    fn string_format(template: &str, p: &PropertyMap) -> Result<PropertyValue, ComputedPropertyError> {
+        expression(ComputedExpression::StringFormat { template: template.to_string() }, p)
+    }
+
     #[test]
     fn test_arithmetic_addition() {
         let p = props(&[
             ("a", PropertyValue::Integer(3)),
             ("b", PropertyValue::Integer(7)),
         ]);
-        let result = ComputedPropertyEvaluator::evaluate_arithmetic("a + b", &p).unwrap();
+        let result = arithmetic("a + b", &p).unwrap();
         assert_eq!(result, PropertyValue::Double(10.0));
     }
 
@@ -354,7 +835,7 @@ mod tests {
             ("x", PropertyValue::Double(10.0)),
             ("y", PropertyValue::Double(3.5)),
         ]);
-        let result = ComputedPropertyEvaluator::evaluate_arithmetic("x - y", &p).unwrap();
+        let result = arithmetic("x - y", &p).unwrap();
         assert_eq!(result, PropertyValue::Double(6.5));
     }
 
@@ -365,7 +846,7 @@ mod tests {
             ("area", PropertyValue::Double(50.0)),
         ]);
         let result =
-            ComputedPropertyEvaluator::evaluate_arithmetic("population / area", &p).unwrap();
+            arithmetic("population / area", &p).unwrap();
         assert_eq!(result, PropertyValue::Double(20.0));
     }
 
@@ -373,14 +854,14 @@ mod tests {
     fn test_arithmetic_mixed() {
         // 2 * 3 + 4 = 10
         let p = props(&[]);
-        let result = ComputedPropertyEvaluator::evaluate_arithmetic("2 * 3 + 4", &p).unwrap();
+        let result = arithmetic("2 * 3 + 4", &p).unwrap();
         assert_eq!(result, PropertyValue::Double(10.0));
     }
 
     #[test]
     fn test_arithmetic_division_by_zero() {
         let p = props(&[]);
-        let result = ComputedPropertyEvaluator::evaluate_arithmetic("10 / 0", &p);
+        let result = arithmetic("10 / 0", &p);
         assert!(result.is_err());
     }
 
@@ -391,7 +872,7 @@ mod tests {
             ("grade", PropertyValue::String("A".to_string())),
         ]);
         let result =
-            ComputedPropertyEvaluator::evaluate_conditional("score > 50", "grade", "F", &p)
+            conditional("score > 50", "grade", "F", &p)
                 .unwrap();
         assert_eq!(result, PropertyValue::String("A".to_string()));
     }
@@ -400,7 +881,7 @@ mod tests {
     fn test_conditional_gt_false_branch() {
         let p = props(&[("score", PropertyValue::Integer(30))]);
         let result =
-            ComputedPropertyEvaluator::evaluate_conditional("score > 50", "pass", "0", &p).unwrap();
+            conditional("score > 50", "pass", "0", &p).unwrap();
         assert_eq!(result, PropertyValue::Integer(0));
     }
 
@@ -408,7 +889,7 @@ mod tests {
     fn test_conditional_eq_strings() {
         let p = props(&[("status", PropertyValue::String("active".to_string()))]);
         let result =
-            ComputedPropertyEvaluator::evaluate_conditional("status == active", "1", "0", &p)
+            conditional("status == active", "1", "0", &p)
                 .unwrap();
         assert_eq!(result, PropertyValue::Integer(1));
     }
@@ -417,7 +898,7 @@ mod tests {
     fn test_conditional_neq() {
         let p = props(&[("x", PropertyValue::Integer(5))]);
         let result =
-            ComputedPropertyEvaluator::evaluate_conditional("x != 5", "yes", "no", &p).unwrap();
+            conditional("x != 5", "yes", "no", &p).unwrap();
         assert_eq!(result, PropertyValue::String("no".to_string()));
     }
 
@@ -428,7 +909,215 @@ mod tests {
             ("year", PropertyValue::Integer(2020)),
         ]);
         let result =
-            ComputedPropertyEvaluator::evaluate_string_format("{name} ({year})", &p).unwrap();
+            string_format("{name} ({year})", &p).unwrap();
         assert_eq!(result, PropertyValue::String("Alice (2020)".to_string()));
     }
+
+    fn risk_band() -> ComputedProperty {
+        serde_yaml::from_str(r#"
+id: risk_band
+displayName: Risk Band
+type: string
+expression:
+  type: branches
+  branches:
+    - when: { property: score, operator: gt, value: 0.8 }
+      then: high
+    - when: { property: score, operator: gt, value: 0.5 }
+      then: medium
+  default: low
+dependencies: [score]
+"#).unwrap()
+    }
+
+    #[test]
+    fn test_branches_numeric_comparisons() {
+        let computed = risk_band();
+        for (score, band) in [(0.93, "high"), (0.8, "medium"), (0.6, "medium"), (0.1, "low")] {
+            let p = props(&[("score", PropertyValue::Double(score))]);
+            assert_eq!(evaluate(&computed, &p).unwrap(), PropertyValue::String(band.to_string()));
+        }
+        // Integers compare against double literals
+        let p = props(&[("score", PropertyValue::Integer(1))]);
+        assert_eq!(evaluate(&computed, &p).unwrap(), PropertyValue::String("high".to_string()));
+    }
+
+    #[test]
+    fn test_branches_string_comparisons_and_property_operands() {
+        let computed: ComputedProperty = serde_yaml::from_str(r#"
+id: owner
+displayName: Owner
+type: string
+expression:
+  type: branches
+  branches:
+    - when: { property: status, operator: eq, value: closed }
+      then: nobody
+    - when: { property: assignee, operator: ne, value: { property: reporter } }
+      then: { property: assignee }
+    - when: { property: reporter, operator: lt, value: m }
+      then: { type: string_format, template: "{reporter} (self, a-l)" }
+  default: { property: reporter }
+"#).unwrap();
+
+        let ticket = |status: &str, assignee: &str, reporter: &str| {
+            props(&[
+                ("status", PropertyValue::String(status.to_string())),
+                ("assignee", PropertyValue::String(assignee.to_string())),
+                ("reporter", PropertyValue::String(reporter.to_string())),
+            ])
+        };
+        let owner = |p: PropertyMap| evaluate(&computed, &p).unwrap();
+        assert_eq!(owner(ticket("closed", "ann", "bob")), PropertyValue::String("nobody".to_string()));
+        assert_eq!(owner(ticket("open", "ann", "bob")), PropertyValue::String("ann".to_string()));
+        assert_eq!(owner(ticket("open", "bob", "bob")), PropertyValue::String("bob (self, a-l)".to_string()));
+        assert_eq!(owner(ticket("open", "zoe", "zoe")), PropertyValue::String("zoe".to_string()));
+    }
+
+    #[test]
+    fn test_nested_branches() {
+        let computed: ComputedProperty = serde_yaml::from_str(r#"
+id: tier
+displayName: Tier
+type: string
+expression:
+  type: branches
+  branches:
+    - when: { property: region, operator: eq, value: us }
+      then:
+        type: branches
+        branches:
+          - when: { property: revenue, operator: gte, value: 1000 }
+            then: us-enterprise
+        default: us-smb
+  default:
+    type: arithmetic
+    expression: revenue * 2
+"#).unwrap();
+
+        let account = |region: &str, revenue: i64| {
+            props(&[
+                ("region", PropertyValue::String(region.to_string())),
+                ("revenue", PropertyValue::Integer(revenue)),
+            ])
+        };
+        assert_eq!(evaluate(&computed, &account("us", 1000)).unwrap(), PropertyValue::String("us-enterprise".to_string()));
+        assert_eq!(evaluate(&computed, &account("us", 999)).unwrap(), PropertyValue::String("us-smb".to_string()));
+        assert_eq!(evaluate(&computed, &account("eu", 10)).unwrap(), PropertyValue::Double(20.0));
+    }
+
+    #[test]
+    fn test_branches_errors() {
+        let mut computed = risk_band();
+        let missing = evaluate(&computed, &props(&[])).unwrap_err();
+        assert!(matches!(missing, ComputedPropertyError::MissingDependency(_)));
+        assert!(missing.to_string().contains("missing property 'score'"), "{}", missing);
+
+        if let ComputedExpression::Branches { default, .. } = &mut computed.expression {
+            *default = None;
+        }
+        let p = props(&[("score", PropertyValue::Double(0.2))]);
+        let unmatched = evaluate(&computed, &p).unwrap_err();
+        assert!(matches!(unmatched, ComputedPropertyError::EvaluationError(_)));
+        assert!(unmatched.to_string().contains("No branch matched"), "{}", unmatched);
+    }
+
+    fn city_type(materialization: &str, extra: &str) -> ObjectType {
+        serde_yaml::from_str(&format!(r#"
+id: city
+displayName: City
+primaryKey: id
+properties:
+  - id: id
+    type: string
+  - id: population
+    type: integer
+  - id: area
+    type: double
+  - id: risk_score
+    type: double
+    modelBinding: risk-model
+computedProperties:
+  - id: density
+    displayName: Density
+    type: double
+    expression:
+      type: arithmetic
+      expression: population / area
+    dependencies: [population, area]
+    materialization: {}
+{}
+"#, materialization, extra)).unwrap()
+    }
+
+    #[test]
+    fn test_materialize_on_write_stores_value_and_timestamp() {
+        let object_type = city_type("on_write", "");
+        let now = Utc::now();
+        let mut p = props(&[
+            ("population", PropertyValue::Integer(1000)),
+            ("area", PropertyValue::Double(50.0)),
+        ]);
+        assert!(ComputedPropertyMaterializer::materialize(&object_type, &mut p, now).is_empty());
+        assert_eq!(p.get("density"), Some(&PropertyValue::Double(20.0)));
+        assert_eq!(ComputedPropertyMaterializer::materialized_at(&p, "density"), Some(now));
+
+        // Changed inputs produce a new stored value on the next write
+        p.insert("population".to_string(), PropertyValue::Integer(2000));
+        ComputedPropertyMaterializer::materialize(&object_type, &mut p, now);
+        assert_eq!(p.get("density"), Some(&PropertyValue::Double(40.0)));
+
+        // OnRead properties are never stored
+        let on_read = city_type("on_read", "");
+        let mut q = props(&[
+            ("population", PropertyValue::Integer(1000)),
+            ("area", PropertyValue::Double(50.0)),
+        ]);
+        ComputedPropertyMaterializer::materialize(&on_read, &mut q, now);
+        assert!(!q.contains_key("density"));
+    }
+
+    #[test]
+    fn test_refresh_stale_respects_freshness_window() {
+        let object_type = city_type("both", "    freshness: 60");
+        let written = Utc::now();
+        let mut p = props(&[
+            ("population", PropertyValue::Integer(1000)),
+            ("area", PropertyValue::Double(50.0)),
+        ]);
+        ComputedPropertyMaterializer::materialize(&object_type, &mut p, written);
+        p.insert("population".to_string(), PropertyValue::Integer(3000));
+
+        // Within the window the stored value is served as-is
+        ComputedPropertyMaterializer::refresh_stale(&object_type, &mut p, written + chrono::Duration::seconds(30));
+        assert_eq!(p.get("density"), Some(&PropertyValue::Double(20.0)));
+
+        // Past the window it is recomputed and re-stamped
+        let later = written + chrono::Duration::seconds(61);
+        ComputedPropertyMaterializer::refresh_stale(&object_type, &mut p, later);
+        assert_eq!(p.get("density"), Some(&PropertyValue::Double(60.0)));
+        assert_eq!(ComputedPropertyMaterializer::materialized_at(&p, "density"), Some(later));
+    }
+
+    #[test]
+    fn test_materialized_property_cannot_depend_on_model_by_default() {
+        let mut object_type = city_type("on_write", "");
+        object_type.computed_properties[0].dependencies.push("risk_score".to_string());
+        let err = object_type.validate().unwrap_err();
+        assert!(err.contains("model-bound property 'risk_score'"), "{}", err);
+
+        object_type.computed_properties[0].allow_model_dependencies = true;
+        assert!(object_type.validate().is_ok());
+
+        // Read-time evaluation always sees the latest prediction
+        let mut on_read = city_type("on_read", "");
+        on_read.computed_properties[0].dependencies.push("risk_score".to_string());
+        assert!(on_read.validate().is_ok());
+    }
+
+    #[test]
+    fn test_freshness_requires_both_materialization() {
+        let object_type = city_type("on_write", "    freshness: 60");
+        assert!(object_type.validate().unwrap_err().contains("freshness"));
+    }
 }
@@ -1,8 +1,28 @@
 use crate::property::{PropertyValue, PropertyMap};
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
 
-/// Crosswalk traverser - handles boundary normalization using crosswalk objects
-pub struct CrosswalkTraverser;
+/// How far composite weights may sum from 1.0 before a warning is printed
+pub const WEIGHT_TOLERANCE: f64 = 1e-6;
+
+/// Crosswalk traverser - handles boundary normalization using crosswalk objects.
+/// Links registered with it can be chained across intermediate vintages.
+#[derive(Debug, Clone, Default)]
+pub struct CrosswalkTraverser {
+    links: Vec<CrosswalkLink>,
+}
+
+/// How apportioned integer properties are rounded
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApportionRounding {
+    /// Floor every share, then hand the units left over to the shares with the largest
+    /// fractions, so each source object's total is kept exactly
+    #[default]
+    LargestRemainder,
+    /// Round every share to the nearest integer; totals may drift
+    Nearest,
+}
 
 /// Crosswalk link information
 #[derive(Debug, Clone)]
@@ -16,6 +36,161 @@ pub struct CrosswalkLink {
 }
 
 impl CrosswalkTraverser {
+    pub fn new(links: Vec<CrosswalkLink>) -> Self {
+        Self { links }
+    }
+    
+    pub fn register(&mut self, link: CrosswalkLink) {
+        self.links.push(link);
+    }
+    
+    pub fn links(&self) -> &[CrosswalkLink] {
+        &self.links
+    }
+    
+    /// Vintages from `from_vintage` to `to_vintage` through registered links, with the fewest
+    /// hops. Fails if there is no path, or if the vintages reachable from `from_vintage` form
+    /// a cycle.
+    pub fn find_path(&self, from_vintage: i64, to_vintage: i64) -> Result<Vec<i64>, String> {
+        let mut next_vintages: BTreeMap<i64, BTreeSet<i64>> = BTreeMap::new();
+        for link in &self.links {
+            next_vintages.entry(link.source_year).or_default().insert(link.target_year);
+        }
+        if let Some(cycle) = find_cycle(&next_vintages, from_vintage) {
+            let cycle: Vec<String> = cycle.iter().map(|vintage| vintage.to_string()).collect();
+            return Err(format!("Crosswalk links form a cycle: {}", cycle.join(" -> ")));
+        }
+        
+        // Breadth-first, so the first path found has the fewest hops
+        let mut previous: HashMap<i64, i64> = HashMap::new();
+        let mut queue = VecDeque::from([from_vintage]);
+        while let Some(vintage) = queue.pop_front() {
+            if vintage == to_vintage {
+                let mut path = vec![vintage];
+                while let Some(before) = previous.get(path.last().unwrap()) {
+                    path.push(*before);
+                }
+                path.reverse();
+                return Ok(path);
+            }
+            for next in next_vintages.get(&vintage).into_iter().flatten() {
+                if *next != from_vintage && !previous.contains_key(next) {
+                    previous.insert(*next, vintage);
+                    queue.push_back(*next);
+                }
+            }
+        }
+        Err(format!("No crosswalk path from year {} to year {}", from_vintage, to_vintage))
+    }
+    
+    /// Target objects in `to_vintage` that `object_id` in `from_vintage` maps to, following
+    /// the shortest chain of crosswalks, with the weights of each hop multiplied along the way.
+    /// The weights should sum to 1.0; a warning is printed when they don't.
+    pub fn resolve_path(&self, from_vintage: i64, to_vintage: i64, object_id: &str) -> Result<Vec<(String, f64)>, String> {
+        let path = self.find_path(from_vintage, to_vintage)?;
+        self.resolve_along(&path, object_id)
+    }
+    
+    /// `resolve_path` for many objects, finding the path once
+    pub fn resolve_many(
+        &self,
+        from_vintage: i64,
+        to_vintage: i64,
+        object_ids: &[&str],
+    ) -> Result<HashMap<String, Vec<(String, f64)>>, String> {
+        let path = self.find_path(from_vintage, to_vintage)?;
+        object_ids
+            .iter()
+            .map(|object_id| Ok((object_id.to_string(), self.resolve_along(&path, object_id)?)))
+            .collect()
+    }
+    
+    /// Apportion a numeric property of `source_objects` in `from_vintage` to the objects of
+    /// `to_vintage`: each value is multiplied by the crosswalk weights and summed per target.
+    /// Integer properties stay integers, rounded per `rounding`; if any value is a double,
+    /// all are summed as doubles. Objects without the property, or with null, are skipped.
+    pub fn apportion(
+        &self,
+        property: &str,
+        source_objects: &[(String, PropertyMap)],
+        from_vintage: i64,
+        to_vintage: i64,
+        rounding: ApportionRounding,
+    ) -> Result<HashMap<String, PropertyValue>, String> {
+        let mut values: Vec<(&str, &PropertyValue)> = Vec::new();
+        let mut not_numeric: Vec<&str> = Vec::new();
+        for (object_id, properties) in source_objects {
+            match properties.get(property) {
+                None | Some(PropertyValue::Null) => {}
+                Some(value @ (PropertyValue::Integer(_) | PropertyValue::Double(_))) => {
+                    values.push((object_id, value));
+                }
+                Some(_) => not_numeric.push(object_id),
+            }
+        }
+        if !not_numeric.is_empty() {
+            return Err(format!("Property '{}' is not numeric on: {}", property, not_numeric.join(", ")));
+        }
+        
+        let object_ids: Vec<&str> = values.iter().map(|(object_id, _)| *object_id).collect();
+        let weights = self.resolve_many(from_vintage, to_vintage, &object_ids)?;
+        let integers = values.iter().all(|(_, value)| matches!(value, PropertyValue::Integer(_)));
+        
+        let mut integer_totals: HashMap<String, i64> = HashMap::new();
+        let mut double_totals: HashMap<String, f64> = HashMap::new();
+        for (object_id, value) in values {
+            let targets = &weights[object_id];
+            match value {
+                PropertyValue::Integer(value) if integers => {
+                    for (target_id, share) in apportion_integer(*value, targets, rounding) {
+                        *integer_totals.entry(target_id).or_insert(0) += share;
+                    }
+                }
+                _ => {
+                    let value = match value {
+                        PropertyValue::Integer(value) => *value as f64,
+                        PropertyValue::Double(value) => *value,
+                        _ => unreachable!("non-numeric values are rejected above"),
+                    };
+                    for (target_id, weight) in targets {
+                        *double_totals.entry(target_id.clone()).or_insert(0.0) += value * weight;
+                    }
+                }
+            }
+        }
+        
+        Ok(if integers {
+            integer_totals.into_iter().map(|(target_id, total)| (target_id, PropertyValue::Integer(total))).collect()
+        } else {
+            double_totals.into_iter().map(|(target_id, total)| (target_id, PropertyValue::Double(total))).collect()
+        })
+    }
+    
+    fn resolve_along(&self, path: &[i64], object_id: &str) -> Result<Vec<(String, f64)>, String> {
+        let mut weights: BTreeMap<String, f64> = BTreeMap::from([(object_id.to_string(), 1.0)]);
+        for hop in path.windows(2) {
+            let mut next: BTreeMap<String, f64> = BTreeMap::new();
+            for (source_id, weight) in &weights {
+                for (target_id, allocated) in Self::normalize_boundaries(source_id, hop[0], hop[1], *weight, &self.links)? {
+                    *next.entry(target_id).or_insert(0.0) += allocated;
+                }
+            }
+            weights = next;
+        }
+        
+        let total: f64 = weights.values().sum();
+        if (total - 1.0).abs() > WEIGHT_TOLERANCE {
+            tracing::warn!(
+                "crosswalk weights of {} from {} to {} sum to {}, not 1",
+                object_id,
+                path[0],
+                path[path.len() - 1],
+                total
+            );
+        }
+        Ok(weights.into_iter().collect())
+    }
+    
     /// Normalize data from source vintage to target vintage using crosswalk links
     pub fn normalize_boundaries(
         source_tract_id: &str,
@@ -179,6 +354,63 @@ impl CrosswalkTraverser {
     }
 }
 
+/// Integer shares of `value` split by `weights`. With `LargestRemainder` the shares add up to
+/// the value times the summed weights, rounded, so a full split keeps the value exactly.
+fn apportion_integer(value: i64, weights: &[(String, f64)], rounding: ApportionRounding) -> Vec<(String, i64)> {
+    let exact: Vec<f64> = weights.iter().map(|(_, weight)| value as f64 * weight).collect();
+    match rounding {
+        ApportionRounding::Nearest => weights
+            .iter()
+            .zip(&exact)
+            .map(|((target_id, _), share)| (target_id.clone(), share.round() as i64))
+            .collect(),
+        ApportionRounding::LargestRemainder => {
+            let mut shares: Vec<i64> = exact.iter().map(|share| share.floor() as i64).collect();
+            let total = exact.iter().sum::<f64>().round() as i64;
+            let residual = (total - shares.iter().sum::<i64>()).max(0) as usize;
+            
+            // Weights are sorted by target, so ties go to the first target
+            let mut by_fraction: Vec<usize> = (0..exact.len()).collect();
+            by_fraction.sort_by(|a, b| {
+                let fraction = |index: usize| exact[index] - exact[index].floor();
+                fraction(*b).total_cmp(&fraction(*a)).then(a.cmp(b))
+            });
+            for index in by_fraction.into_iter().take(residual) {
+                shares[index] += 1;
+            }
+            weights.iter().map(|(target_id, _)| target_id.clone()).zip(shares).collect()
+        }
+    }
+}
+
+/// Vintages on a cycle reachable from `start`, first one repeated at the end
+fn find_cycle(next_vintages: &BTreeMap<i64, BTreeSet<i64>>, start: i64) -> Option<Vec<i64>> {
+    fn visit(
+        vintage: i64,
+        next_vintages: &BTreeMap<i64, BTreeSet<i64>>,
+        stack: &mut Vec<i64>,
+        done: &mut BTreeSet<i64>,
+    ) -> Option<Vec<i64>> {
+        if let Some(index) = stack.iter().position(|on_stack| *on_stack == vintage) {
+            let mut cycle = stack[index..].to_vec();
+            cycle.push(vintage);
+            return Some(cycle);
+        }
+        if !done.insert(vintage) {
+            return None;
+        }
+        stack.push(vintage);
+        for next in next_vintages.get(&vintage).into_iter().flatten() {
+            if let Some(cycle) = visit(*next, next_vintages, stack, done) {
+                return Some(cycle);
+            }
+        }
+        stack.pop();
+        None
+    }
+    visit(start, next_vintages, &mut Vec::new(), &mut BTreeSet::new())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -252,11 +484,59 @@ mod tests {
         
         assert_eq!(result.get("tract4"), Some(&1500.0));
     }
+    
+    fn link(source: &str, source_year: i64, target: &str, target_year: i64, overlap: f64) -> CrosswalkLink {
+        CrosswalkLink {
+            source_tract_id: source.to_string(),
+            target_tract_id: target.to_string(),
+            source_year,
+            target_year,
+            overlap_percentage: overlap,
+            allocation_factor: None,
+        }
+    }
+    
+    #[test]
+    fn test_find_path_takes_fewest_hops_and_rejects_cycles() {
+        let mut traverser = CrosswalkTraverser::new(vec![
+            link("a", 1990, "b", 2000, 1.0),
+            link("b", 2000, "c", 2010, 1.0),
+            link("a", 1990, "c", 2010, 1.0),
+            link("c", 2010, "d", 2020, 1.0),
+        ]);
+        assert_eq!(traverser.find_path(1990, 2020).unwrap(), vec![1990, 2010, 2020]);
+        assert_eq!(traverser.find_path(2010, 2010).unwrap(), vec![2010]);
+        assert!(traverser.find_path(2020, 1990).unwrap_err().contains("No crosswalk path"));
+        
+        traverser.register(link("d", 2020, "b", 2000, 1.0));
+        let error = traverser.find_path(1990, 2020).unwrap_err();
+        assert!(error.contains("cycle: 2000 -> 2010 -> 2020 -> 2000"), "{}", error);
+    }
+    
+    #[test]
+    fn test_resolve_many_reuses_the_path() {
+        let traverser = CrosswalkTraverser::new(vec![
+            link("a", 1990, "x", 2000, 1.0),
+            link("b", 1990, "x", 2000, 0.5),
+            link("b", 1990, "y", 2000, 0.5),
+            link("x", 2000, "z", 2010, 1.0),
+            link("y", 2000, "z", 2010, 1.0),
+        ]);
+        let resolved = traverser.resolve_many(1990, 2010, &["a", "b"]).unwrap();
+        assert_eq!(resolved["a"], vec![("z".to_string(), 1.0)]);
+        assert_eq!(resolved["b"], vec![("z".to_string(), 1.0)]);
+        
+        // An object with no crosswalk at some hop fails the batch
+        assert!(traverser.resolve_many(1990, 2010, &["a", "missing"]).is_err());
+    }
+    
+    #[test]
+    fn test_apportion_integer_keeps_the_value_with_largest_remainder() {
+        let weights = vec![("a".to_string(), 1.0 / 3.0), ("b".to_string(), 1.0 / 3.0), ("c".to_string(), 1.0 / 3.0)];
+        let shares = apportion_integer(100, &weights, ApportionRounding::LargestRemainder);
+        assert_eq!(shares, vec![("a".to_string(), 34), ("b".to_string(), 33), ("c".to_string(), 33)]);
+        
+        let shares = apportion_integer(100, &weights, ApportionRounding::Nearest);
+        assert_eq!(shares.iter().map(|(_, share)| share).sum::<i64>(), 99);
+    }
 }
-
-
-
-
-
-
-
@@ -0,0 +1,345 @@
+//! Declarative deduplication (entity resolution) rules for object types.
+//!
+//! A rule matches two records when every one of its match properties agrees under its
+//! comparator; records are duplicates when any of the type's rules matches them, and
+//! duplicates are clustered transitively. Merging a cluster coalesces properties across its
+//! records in the order the rule's survivorship policy ranks them.
+
+use crate::meta_model::ObjectType;
+use crate::property::{PropertyMap, PropertyValue};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+/// A duplicate-matching rule for an object type
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DedupRule {
+    /// Optional name, used in validation errors
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+
+    /// Properties that must all match for two records to be duplicates
+    #[serde(rename = "match")]
+    pub match_on: Vec<MatchProperty>,
+
+    /// Which values win when duplicates are merged
+    #[serde(default)]
+    pub survivorship: Survivorship,
+}
+
+/// A property compared by a dedup rule
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MatchProperty {
+    pub property: String,
+
+    #[serde(default)]
+    pub comparator: MatchComparator,
+}
+
+/// How a match property's values are compared
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum MatchComparator {
+    /// Values are identical
+    #[default]
+    Exact,
+    /// Values are identical after lowercasing and dropping punctuation and extra whitespace
+    Normalized,
+    /// Normalized values have an edit-distance similarity of at least `threshold` (0-1]
+    Fuzzy { threshold: f64 },
+}
+
+/// Survivorship policy for merging duplicates
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(tag = "policy", rename_all = "snake_case")]
+pub enum Survivorship {
+    /// Values from the record with the latest `timestamp_property` win
+    PreferNewest { timestamp_property: String },
+    /// Values from the record whose `source_property` comes first in `priority` win
+    PreferSourcePriority {
+        source_property: String,
+        priority: Vec<String>,
+    },
+    /// The surviving record's values win; its missing or null values are filled from the others
+    #[default]
+    MergeNonNull,
+}
+
+impl MatchComparator {
+    /// Whether two values match; null or missing values never match
+    pub fn matches(&self, a: &PropertyValue, b: &PropertyValue) -> bool {
+        if a.is_null() || b.is_null() {
+            return false;
+        }
+        match self {
+            MatchComparator::Exact => a.to_string() == b.to_string(),
+            MatchComparator::Normalized => {
+                let a = normalize(a);
+                !a.is_empty() && a == normalize(b)
+            }
+            MatchComparator::Fuzzy { threshold } => similarity(&normalize(a), &normalize(b)) >= *threshold,
+        }
+    }
+}
+
+impl DedupRule {
+    /// Whether two records are duplicates under this rule
+    pub fn matches(&self, a: &PropertyMap, b: &PropertyMap) -> bool {
+        !self.match_on.is_empty()
+            && self.match_on.iter().all(|m| match (a.get(&m.property), b.get(&m.property)) {
+                (Some(x), Some(y)) => m.comparator.matches(x, y),
+                _ => false,
+            })
+    }
+
+    /// Key that records matching under this rule must share, if the rule has an exact or
+    /// normalized property; lets clustering compare records within buckets only
+    fn blocking_key(&self, record: &PropertyMap) -> Option<Option<String>> {
+        let blocking = self
+            .match_on
+            .iter()
+            .find(|m| !matches!(m.comparator, MatchComparator::Fuzzy { .. }))?;
+        let key = record.get(&blocking.property).filter(|v| !v.is_null()).map(|value| {
+            match blocking.comparator {
+                MatchComparator::Normalized => normalize(value),
+                _ => value.to_string(),
+            }
+        });
+        Some(key)
+    }
+
+    /// Validate this rule against the object type it belongs to
+    pub fn validate_for(&self, object_type: &ObjectType) -> Result<(), String> {
+        if self.match_on.is_empty() {
+            return Err("dedup rule must match on at least one property".to_string());
+        }
+        for m in &self.match_on {
+            if object_type.get_property(&m.property).is_none() {
+                return Err(format!("match property '{}' does not exist", m.property));
+            }
+            if let MatchComparator::Fuzzy { threshold } = m.comparator {
+                if !(threshold > 0.0 && threshold <= 1.0) {
+                    return Err(format!(
+                        "fuzzy threshold for '{}' must be in (0, 1], got {}",
+                        m.property, threshold
+                    ));
+                }
+            }
+        }
+        let survivorship_property = match &self.survivorship {
+            Survivorship::PreferNewest { timestamp_property } => Some(timestamp_property),
+            Survivorship::PreferSourcePriority { source_property, priority } => {
+                if priority.is_empty() {
+                    return Err("source priority list is empty".to_string());
+                }
+                Some(source_property)
+            }
+            Survivorship::MergeNonNull => None,
+        };
+        if let Some(property) = survivorship_property {
+            if object_type.get_property(property).is_none() {
+                return Err(format!("survivorship property '{}' does not exist", property));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Survivorship {
+    /// Merge a surviving record with its duplicates. Records are ranked by the policy (the
+    /// survivor first among equals) and each property takes the first non-null value.
+    pub fn merge(&self, winner: &PropertyMap, losers: &[&PropertyMap]) -> PropertyMap {
+        let mut records: Vec<&PropertyMap> = std::iter::once(winner).chain(losers.iter().copied()).collect();
+        match self {
+            Survivorship::PreferNewest { timestamp_property } => {
+                // Stable sort, newest first; records without a timestamp last
+                records.sort_by(|a, b| {
+                    match (a.get(timestamp_property), b.get(timestamp_property)) {
+                        (Some(x), Some(y)) if !x.is_null() && !y.is_null() => compare_values(y, x),
+                        (Some(x), _) if !x.is_null() => Ordering::Less,
+                        (_, Some(y)) if !y.is_null() => Ordering::Greater,
+                        _ => Ordering::Equal,
+                    }
+                });
+            }
+            Survivorship::PreferSourcePriority { source_property, priority } => {
+                records.sort_by_key(|record| {
+                    record
+                        .get(source_property)
+                        .and_then(|source| priority.iter().position(|p| *p == source.to_string()))
+                        .unwrap_or(priority.len())
+                });
+            }
+            Survivorship::MergeNonNull => {}
+        }
+
+        let mut merged = PropertyMap::new();
+        for record in records {
+            for (key, value) in record.iter() {
+                let missing = merged.get(key).is_none_or(|v| v.is_null());
+                if missing && (!value.is_null() || !merged.contains_key(key)) {
+                    merged.insert(key.clone(), value.clone());
+                }
+            }
+        }
+        merged
+    }
+}
+
+/// Group records (object ID, properties) into clusters of duplicates under the object type's
+/// dedup rules. Only clusters of two or more are returned, each sorted by object ID.
+pub fn find_duplicate_clusters(object_type: &ObjectType, records: &[(String, PropertyMap)]) -> Vec<Vec<String>> {
+    let mut parent: Vec<usize> = (0..records.len()).collect();
+    fn root(parent: &mut [usize], mut i: usize) -> usize {
+        while parent[i] != i {
+            parent[i] = parent[parent[i]];
+            i = parent[i];
+        }
+        i
+    }
+
+    for rule in object_type.dedup_rules.iter().flatten() {
+        // Candidate pairs share a blocking key; rules with only fuzzy comparators compare all pairs
+        let mut buckets: HashMap<String, Vec<usize>> = HashMap::new();
+        for (i, (_, properties)) in records.iter().enumerate() {
+            match rule.blocking_key(properties) {
+                Some(Some(key)) => buckets.entry(key).or_default().push(i),
+                Some(None) => {}
+                None => buckets.entry(String::new()).or_default().push(i),
+            }
+        }
+        for members in buckets.values() {
+            for (n, &i) in members.iter().enumerate() {
+                for &j in &members[n + 1..] {
+                    let (ri, rj) = (root(&mut parent, i), root(&mut parent, j));
+                    if ri != rj && rule.matches(&records[i].1, &records[j].1) {
+                        parent[ri] = rj;
+                    }
+                }
+            }
+        }
+    }
+
+    let mut clusters: HashMap<usize, Vec<String>> = HashMap::new();
+    for (i, (object_id, _)) in records.iter().enumerate() {
+        let r = root(&mut parent, i);
+        clusters.entry(r).or_default().push(object_id.clone());
+    }
+    let mut clusters: Vec<Vec<String>> = clusters.into_values().filter(|c| c.len() > 1).collect();
+    for cluster in &mut clusters {
+        cluster.sort();
+    }
+    clusters.sort();
+    clusters
+}
+
+/// Merge duplicates into the surviving record using the survivorship policy of the first
+/// rule that matches the survivor with one of them (the type's first rule otherwise). The
+/// survivor keeps its own primary key.
+pub fn merge_duplicates(object_type: &ObjectType, winner: &PropertyMap, losers: &[&PropertyMap]) -> PropertyMap {
+    let rules = object_type.dedup_rules.as_deref().unwrap_or_default();
+    let default_policy = Survivorship::default();
+    let policy = rules
+        .iter()
+        .find(|rule| losers.iter().any(|loser| rule.matches(winner, loser)))
+        .or(rules.first())
+        .map_or(&default_policy, |rule| &rule.survivorship);
+
+    let mut merged = policy.merge(winner, losers);
+    if let Some(key) = winner.get(&object_type.primary_key) {
+        merged.insert(object_type.primary_key.clone(), key.clone());
+    }
+    merged
+}
+
+/// Lowercase, keep letters and digits, collapse whitespace
+fn normalize(value: &PropertyValue) -> String {
+    let text: String = value
+        .to_string()
+        .to_lowercase()
+        .chars()
+        .filter(|c| c.is_alphanumeric() || c.is_whitespace())
+        .collect();
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// 1 - (Levenshtein distance / length of the longer string), over characters
+fn similarity(a: &str, b: &str) -> f64 {
+    let (a, b): (Vec<char>, Vec<char>) = (a.chars().collect(), b.chars().collect());
+    let longest = a.len().max(b.len());
+    if longest == 0 {
+        return 0.0;
+    }
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.iter().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    1.0 - previous[b.len()] as f64 / longest as f64
+}
+
+/// Numbers numerically, everything else by string (ISO dates sort correctly)
+fn compare_values(a: &PropertyValue, b: &PropertyValue) -> Ordering {
+    let number = |v: &PropertyValue| match v {
+        PropertyValue::Integer(i) => Some(*i as f64),
+        PropertyValue::Double(d) => Some(*d),
+        _ => None,
+    };
+    match (number(a), number(b)) {
+        (Some(x), Some(y)) => x.partial_cmp(&y).unwrap_or(Ordering::Equal),
+        _ => a.to_string().cmp(&b.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(pairs: &[(&str, PropertyValue)]) -> PropertyMap {
+        let mut properties = PropertyMap::new();
+        for (key, value) in pairs {
+            properties.insert(key.to_string(), value.clone());
+        }
+        properties
+    }
+
+    fn text(s: &str) -> PropertyValue {
+        PropertyValue::String(s.to_string())
+    }
+
+    #[test]
+    fn test_comparators() {
+        assert!(MatchComparator::Normalized.matches(&text("Acme, Inc."), &text("ACME  inc")));
+        assert!(!MatchComparator::Exact.matches(&text("Acme, Inc."), &text("ACME  inc")));
+        assert!(!MatchComparator::Normalized.matches(&PropertyValue::Null, &PropertyValue::Null));
+        let fuzzy = MatchComparator::Fuzzy { threshold: 0.8 };
+        assert!(fuzzy.matches(&text("Jonathan Smith"), &text("Jonathon Smith")));
+        assert!(!fuzzy.matches(&text("Jonathan Smith"), &text("Jane Smythe")));
+    }
+
+    #[test]
+    fn test_survivorship_policies() {
+        let winner = record(&[("id", text("a")), ("name", text("Ada")), ("email", PropertyValue::Null), ("seen", text("2024-01-01")), ("source", text("crm"))]);
+        let loser = record(&[("id", text("b")), ("name", text("Ada L.")), ("email", text("ada@example.com")), ("seen", text("2024-06-01")), ("source", text("billing"))]);
+
+        let merged = Survivorship::MergeNonNull.merge(&winner, &[&loser]);
+        assert_eq!(merged.get("name"), Some(&text("Ada")));
+        assert_eq!(merged.get("email"), Some(&text("ada@example.com")));
+
+        let newest = Survivorship::PreferNewest { timestamp_property: "seen".to_string() };
+        assert_eq!(newest.merge(&winner, &[&loser]).get("name"), Some(&text("Ada L.")));
+
+        let by_source = Survivorship::PreferSourcePriority {
+            source_property: "source".to_string(),
+            priority: vec!["billing".to_string(), "crm".to_string()],
+        };
+        let merged = by_source.merge(&winner, &[&loser]);
+        assert_eq!(merged.get("name"), Some(&text("Ada L.")));
+        assert_eq!(merged.get("id"), Some(&text("b")));
+    }
+}
@@ -0,0 +1,283 @@
+use crate::property::{Property, PropertyFormat, PropertyValue};
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, NaiveDate};
+
+/// Locale conventions used when rendering display strings
+#[derive(Debug, Clone, PartialEq)]
+pub struct DisplayLocale {
+    pub tag: String,
+    pub decimal_separator: char,
+    pub group_separator: char,
+    /// Currency symbol used when a currency format does not declare one
+    pub currency_symbol: String,
+    /// Whether the currency symbol follows the amount ("1.234,56 €")
+    pub currency_after: bool,
+    /// Whether a space separates the number and the percent sign ("42 %")
+    pub percent_space: bool,
+}
+
+impl DisplayLocale {
+    /// Resolve a BCP 47 tag such as `en-US` or `de`; unknown or missing tags fall back to English
+    pub fn for_tag(tag: Option<&str>) -> Self {
+        let tag = tag.unwrap_or("en").trim();
+        let language = tag.split(['-', '_']).next().unwrap_or("en").to_lowercase();
+        let (decimal, group, symbol, after, percent_space) = match language.as_str() {
+            "de" => (',', '.', "€", true, true),
+            "fr" => (',', '\u{202f}', "€", true, true),
+            "es" | "it" | "pt" => (',', '.', "€", true, false),
+            "nl" => (',', '.', "€", false, false),
+            "ja" => ('.', ',', "¥", false, false),
+            "zh" => ('.', ',', "¥", false, false),
+            _ => ('.', ',', "$", false, false),
+        };
+        let (decimal, group, symbol) = match tag.to_lowercase().as_str() {
+            "en-gb" => (decimal, group, "£"),
+            "pt-br" => (decimal, group, "R$"),
+            "de-ch" => ('.', '\'', "CHF"),
+            _ => (decimal, group, symbol),
+        };
+        Self {
+            tag: tag.to_string(),
+            decimal_separator: decimal,
+            group_separator: group,
+            currency_symbol: symbol.to_string(),
+            currency_after: after,
+            percent_space,
+        }
+    }
+}
+
+impl Default for DisplayLocale {
+    fn default() -> Self {
+        Self::for_tag(None)
+    }
+}
+
+/// Errors raised when a property's display configuration cannot be applied
+#[derive(Debug, thiserror::Error)]
+pub enum DisplayFormatError {
+    #[error("Invalid date format '{0}'")]
+    InvalidDateFormat(String),
+
+    #[error("Group separator '{0}' conflicts with the locale decimal separator")]
+    InvalidSeparator(char),
+
+    #[error("Value '{value}' cannot be rendered with a {format} format")]
+    UnsupportedValue { format: String, value: String },
+}
+
+/// Render a property value for display using its format hint and unit.
+/// Returns `Ok(None)` for null values and for properties without display hints.
+pub fn format_property_value(
+    property: &Property,
+    value: &PropertyValue,
+    locale: &DisplayLocale,
+) -> Result<Option<String>, DisplayFormatError> {
+    if matches!(value, PropertyValue::Null) {
+        return Ok(None);
+    }
+
+    let formatted = match &property.format {
+        Some(PropertyFormat::Currency { symbol }) => {
+            let amount = numeric(value, "currency")?;
+            let symbol = symbol.as_deref().unwrap_or(&locale.currency_symbol);
+            let digits = format_number(amount.abs(), 2, locale.group_separator, locale);
+            let sign = if amount < 0.0 { "-" } else { "" };
+            if locale.currency_after {
+                format!("{}{} {}", sign, digits, symbol)
+            } else {
+                format!("{}{}{}", sign, symbol, digits)
+            }
+        }
+        Some(PropertyFormat::Percentage { decimals }) => {
+            // Percentages are stored as ratios (0.42 renders as 42%)
+            let ratio = numeric(value, "percentage")?;
+            let digits = format_number(ratio * 100.0, decimals.unwrap_or(0), locale.group_separator, locale);
+            if locale.percent_space {
+                format!("{}\u{a0}%", digits)
+            } else {
+                format!("{}%", digits)
+            }
+        }
+        Some(PropertyFormat::DateFormat { format }) => format_date(value, format)?,
+        Some(PropertyFormat::NumberFormat { decimals, separator }) => {
+            let group = separator.unwrap_or(locale.group_separator);
+            if group == locale.decimal_separator {
+                return Err(DisplayFormatError::InvalidSeparator(group));
+            }
+            format_number(numeric(value, "number")?, *decimals, group, locale)
+        }
+        None if property.unit.is_some() => match value {
+            PropertyValue::Integer(i) => format_number(*i as f64, 0, locale.group_separator, locale),
+            PropertyValue::Double(d) => {
+                let decimals = d.to_string().split_once('.').map_or(0, |(_, frac)| frac.len());
+                format_number(*d, decimals, locale.group_separator, locale)
+            }
+            other => other.to_string(),
+        },
+        None => return Ok(None),
+    };
+
+    Ok(Some(match &property.unit {
+        Some(unit) => format!("{} {}", formatted, unit),
+        None => formatted,
+    }))
+}
+
+fn numeric(value: &PropertyValue, format: &str) -> Result<f64, DisplayFormatError> {
+    match value {
+        PropertyValue::Integer(i) => Ok(*i as f64),
+        PropertyValue::Double(d) => Ok(*d),
+        PropertyValue::String(s) => s.trim().parse::<f64>().map_err(|_| DisplayFormatError::UnsupportedValue {
+            format: format.to_string(),
+            value: s.clone(),
+        }),
+        other => Err(DisplayFormatError::UnsupportedValue {
+            format: format.to_string(),
+            value: other.to_string(),
+        }),
+    }
+}
+
+/// Fixed-point rendering with locale decimal separator and the given thousands separator
+fn format_number(value: f64, decimals: usize, group: char, locale: &DisplayLocale) -> String {
+    let fixed = format!("{:.*}", decimals, value.abs());
+    let (integer, fraction) = match fixed.split_once('.') {
+        Some((i, f)) => (i, Some(f)),
+        None => (fixed.as_str(), None),
+    };
+
+    let mut grouped = String::new();
+    for (idx, digit) in integer.chars().enumerate() {
+        if idx > 0 && (integer.len() - idx) % 3 == 0 {
+            grouped.push(group);
+        }
+        grouped.push(digit);
+    }
+
+    let mut out = String::new();
+    if value < 0.0 && fixed.chars().any(|c| c.is_ascii_digit() && c != '0') {
+        out.push('-');
+    }
+    out.push_str(&grouped);
+    if let Some(fraction) = fraction {
+        out.push(locale.decimal_separator);
+        out.push_str(fraction);
+    }
+    out
+}
+
+fn format_date(value: &PropertyValue, format: &str) -> Result<String, DisplayFormatError> {
+    // chrono panics when rendering an invalid specifier, so reject it up front
+    let items: Vec<Item> = StrftimeItems::new(format).collect();
+    if items.iter().any(|item| matches!(item, Item::Error)) {
+        return Err(DisplayFormatError::InvalidDateFormat(format.to_string()));
+    }
+
+    let raw = match value {
+        PropertyValue::Date(s) | PropertyValue::DateTime(s) | PropertyValue::String(s) => s,
+        other => {
+            return Err(DisplayFormatError::UnsupportedValue {
+                format: "date".to_string(),
+                value: other.to_string(),
+            })
+        }
+    };
+
+    if let Ok(dt) = DateTime::parse_from_rfc3339(raw) {
+        return Ok(dt.format_with_items(items.into_iter()).to_string());
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(raw, "%Y-%m-%d") {
+        // Plain dates render at midnight so time specifiers stay valid
+        let midnight = date.and_hms_opt(0, 0, 0).expect("midnight is a valid time");
+        return Ok(midnight.format_with_items(items.into_iter()).to_string());
+    }
+    Err(DisplayFormatError::UnsupportedValue {
+        format: "date".to_string(),
+        value: raw.clone(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn property(format: Option<PropertyFormat>, unit: Option<&str>) -> Property {
+        let mut property: Property = serde_json::from_value(serde_json::json!({
+            "id": "value",
+            "type": "double"
+        }))
+        .unwrap();
+        property.format = format;
+        property.unit = unit.map(|u| u.to_string());
+        property
+    }
+
+    #[test]
+    fn test_currency_is_locale_aware() {
+        let prop = property(Some(PropertyFormat::Currency { symbol: None }), None);
+        let value = PropertyValue::Double(1234.56);
+        let en = format_property_value(&prop, &value, &DisplayLocale::for_tag(Some("en-US"))).unwrap();
+        let de = format_property_value(&prop, &value, &DisplayLocale::for_tag(Some("de-DE"))).unwrap();
+        assert_eq!(en.as_deref(), Some("$1,234.56"));
+        assert_eq!(de.as_deref(), Some("1.234,56 €"));
+
+        let prop = property(Some(PropertyFormat::Currency { symbol: Some("£".to_string()) }), None);
+        let negative = format_property_value(&prop, &PropertyValue::Integer(-5), &DisplayLocale::default()).unwrap();
+        assert_eq!(negative.as_deref(), Some("-£5.00"));
+    }
+
+    #[test]
+    fn test_percentage_number_and_unit() {
+        let prop = property(Some(PropertyFormat::Percentage { decimals: None }), None);
+        assert_eq!(
+            format_property_value(&prop, &PropertyValue::Double(0.42), &DisplayLocale::default()).unwrap().as_deref(),
+            Some("42%")
+        );
+
+        let prop = property(Some(PropertyFormat::NumberFormat { decimals: 1, separator: Some(' ') }), Some("km"));
+        assert_eq!(
+            format_property_value(&prop, &PropertyValue::Double(1234567.89), &DisplayLocale::default()).unwrap().as_deref(),
+            Some("1 234 567.9 km")
+        );
+
+        let prop = property(None, Some("kg"));
+        assert_eq!(
+            format_property_value(&prop, &PropertyValue::Integer(4200), &DisplayLocale::for_tag(Some("de"))).unwrap().as_deref(),
+            Some("4.200 kg")
+        );
+
+        // No hints means nothing to render
+        let prop = property(None, None);
+        assert!(format_property_value(&prop, &PropertyValue::Integer(1), &DisplayLocale::default()).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_date_format() {
+        let prop = property(Some(PropertyFormat::DateFormat { format: "%d/%m/%Y".to_string() }), None);
+        let value = PropertyValue::Date("2024-03-15".to_string());
+        assert_eq!(
+            format_property_value(&prop, &value, &DisplayLocale::default()).unwrap().as_deref(),
+            Some("15/03/2024")
+        );
+    }
+
+    #[test]
+    fn test_malformed_configs_are_errors() {
+        let prop = property(Some(PropertyFormat::DateFormat { format: "%Q".to_string() }), None);
+        let value = PropertyValue::Date("2024-03-15".to_string());
+        assert!(matches!(
+            format_property_value(&prop, &value, &DisplayLocale::default()),
+            Err(DisplayFormatError::InvalidDateFormat(_))
+        ));
+
+        let prop = property(Some(PropertyFormat::NumberFormat { decimals: 2, separator: Some('.') }), None);
+        assert!(matches!(
+            format_property_value(&prop, &PropertyValue::Integer(1000), &DisplayLocale::default()),
+            Err(DisplayFormatError::InvalidSeparator('.'))
+        ));
+
+        let prop = property(Some(PropertyFormat::Currency { symbol: None }), None);
+        assert!(format_property_value(&prop, &PropertyValue::Boolean(true), &DisplayLocale::default()).is_err());
+    }
+}
@@ -1,4 +1,7 @@
 use crate::meta_model::{OntologyRuntime, ObjectType, LinkTypeDef, ActionTypeDef};
+use crate::property::{parse_date, parse_datetime, Property, PropertyMap, PropertyType, PropertyValue};
+use serde_json::Value;
+use std::collections::HashMap;
 use std::sync::{Arc, RwLock};
 
 /// Dynamic ontology that supports hot-swapping schemas
@@ -199,3 +202,323 @@ pub enum MigrationStrategy {
     Reindex,
 }
 
+/// What `ObjectType::instantiate_from_json` does with keys that are not properties of the type
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum UnknownKeys {
+    /// Keep them, read as untagged property values (e.g. `_acl` and other system fields)
+    #[default]
+    Keep,
+    /// Report each one as an error
+    Reject,
+}
+
+impl ObjectType {
+    /// Build an object's properties from a raw JSON row, keeping keys that are not
+    /// properties of the type. See `instantiate_from_json_with`.
+    pub fn instantiate_from_json(&self, json: &Value) -> Result<PropertyMap, Vec<String>> {
+        self.instantiate_from_json_with(json, UnknownKeys::Keep)
+    }
+
+    /// Build an object's properties from a raw JSON row: each value is coerced to its
+    /// property's declared type (numbers and booleans given as strings, dates, references,
+    /// GeoJSON, and arrays, maps and structs element by element), missing optional properties
+    /// get their defaults and missing required ones are reported. Every problem is collected
+    /// rather than stopping at the first. Values are only converted; rules such as
+    /// `validation` bounds are checked by `validate_object`.
+    pub fn instantiate_from_json_with(&self, json: &Value, unknown_keys: UnknownKeys) -> Result<PropertyMap, Vec<String>> {
+        let Value::Object(fields) = json else {
+            return Err(vec![format!("Object of type '{}' must be a JSON object, got {}", self.id, json)]);
+        };
+        let mut errors = Vec::new();
+        let properties = instantiate_fields(&self.properties, fields, unknown_keys, "", &mut errors);
+        if errors.is_empty() {
+            Ok(properties)
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+impl Property {
+    /// Coerce one JSON value to this property's type, as `ObjectType::instantiate_from_json`
+    /// does for each of an object's values
+    pub fn value_from_json(&self, value: &Value) -> Result<PropertyValue, String> {
+        coerce_json(value, &self.property_type, &self.id, UnknownKeys::Keep)
+    }
+}
+
+impl LinkTypeDef {
+    /// Build a link's properties from a JSON object, coercing values as
+    /// `ObjectType::instantiate_from_json` does
+    pub fn instantiate_from_json(&self, json: &Value) -> Result<PropertyMap, Vec<String>> {
+        let Value::Object(fields) = json else {
+            return Err(vec![format!("Properties of link type '{}' must be a JSON object, got {}", self.id, json)]);
+        };
+        let mut errors = Vec::new();
+        let properties = instantiate_fields(&self.properties, fields, UnknownKeys::Keep, "", &mut errors);
+        if errors.is_empty() {
+            Ok(properties)
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+/// Coerce the JSON fields of an object or struct to `properties`, reporting problems with
+/// property paths under `prefix`
+fn instantiate_fields(
+    properties: &[Property],
+    fields: &serde_json::Map<String, Value>,
+    unknown_keys: UnknownKeys,
+    prefix: &str,
+    errors: &mut Vec<String>,
+) -> PropertyMap {
+    let mut instantiated = PropertyMap::new();
+    for property in properties {
+        let path = format!("{}{}", prefix, property.id);
+        match fields.get(&property.id).filter(|value| !value.is_null()) {
+            Some(value) => match coerce_json(value, &property.property_type, &path, unknown_keys) {
+                Ok(value) => instantiated.insert(property.id.clone(), value),
+                Err(e) => errors.push(e),
+            },
+            None => match &property.default {
+                Some(default) => instantiated.insert(property.id.clone(), default.clone()),
+                None if property.required => errors.push(format!("Missing required property '{}'", path)),
+                None => {}
+            },
+        }
+    }
+    for (key, value) in fields {
+        if properties.iter().any(|p| &p.id == key) {
+            continue;
+        }
+        match unknown_keys {
+            UnknownKeys::Reject => errors.push(format!("Unknown property '{}{}'", prefix, key)),
+            UnknownKeys::Keep => match serde_json::from_value::<PropertyValue>(value.clone()) {
+                Ok(value) => instantiated.insert(key.clone(), value),
+                Err(e) => errors.push(format!("Property '{}{}': {}", prefix, key, e)),
+            },
+        }
+    }
+    instantiated
+}
+
+/// Coerce one JSON value to `property_type`; `path` names it in errors
+fn coerce_json(value: &Value, property_type: &PropertyType, path: &str, unknown_keys: UnknownKeys) -> Result<PropertyValue, String> {
+    let mismatch = || format!("Property '{}' expects {}, got {}", path, type_label(property_type), value);
+    match (property_type, value) {
+        (_, Value::Null) => Ok(PropertyValue::Null),
+        (PropertyType::String, Value::String(s)) => Ok(PropertyValue::String(s.clone())),
+        (PropertyType::String, Value::Number(n)) => Ok(PropertyValue::String(n.to_string())),
+        (PropertyType::String, Value::Bool(b)) => Ok(PropertyValue::String(b.to_string())),
+        (PropertyType::Integer | PropertyType::Int, Value::Number(n)) => match (n.as_i64(), n.as_f64()) {
+            (Some(i), _) => Ok(PropertyValue::Integer(i)),
+            // Floats are only integers when they are whole numbers in range
+            (None, Some(f)) if f.fract() == 0.0 && f >= i64::MIN as f64 && f < i64::MAX as f64 => {
+                Ok(PropertyValue::Integer(f as i64))
+            }
+            _ => Err(format!("Property '{}' expects an integer, got {}", path, n)),
+        },
+        (PropertyType::Integer | PropertyType::Int, Value::String(s)) => s
+            .trim()
+            .parse::<i64>()
+            .map(PropertyValue::Integer)
+            .map_err(|_| format!("Property '{}' expects an integer, got '{}'", path, s)),
+        (PropertyType::Double | PropertyType::Float, Value::Number(n)) => {
+            n.as_f64().map(PropertyValue::Double).ok_or_else(mismatch)
+        }
+        (PropertyType::Double | PropertyType::Float, Value::String(s)) => s
+            .trim()
+            .parse::<f64>()
+            .map(PropertyValue::Double)
+            .map_err(|_| format!("Property '{}' expects a number, got '{}'", path, s)),
+        (PropertyType::Boolean | PropertyType::Bool, Value::Bool(b)) => Ok(PropertyValue::Boolean(*b)),
+        (PropertyType::Boolean | PropertyType::Bool, Value::String(s)) => match s.trim().to_ascii_lowercase().as_str() {
+            "true" => Ok(PropertyValue::Boolean(true)),
+            "false" => Ok(PropertyValue::Boolean(false)),
+            _ => Err(format!("Property '{}' expects a boolean, got '{}'", path, s)),
+        },
+        (PropertyType::Date, Value::String(s)) => parse_date(s)
+            .map(|_| PropertyValue::Date(s.clone()))
+            .map_err(|e| format!("Property '{}' value {}", path, e)),
+        (PropertyType::DateTime | PropertyType::Timestamp, Value::String(s)) => parse_datetime(s)
+            .map(|_| PropertyValue::DateTime(s.clone()))
+            .map_err(|e| format!("Property '{}' value {}", path, e)),
+        (PropertyType::ObjectReference | PropertyType::ObjectReferenceAlt, Value::String(s)) => {
+            Ok(PropertyValue::ObjectReference(s.clone()))
+        }
+        (PropertyType::GeoJSON | PropertyType::GeoJSONAlt, Value::Object(_)) => Ok(PropertyValue::GeoJSON(value.to_string())),
+        (PropertyType::GeoJSON | PropertyType::GeoJSONAlt, Value::String(s)) => match serde_json::from_str::<Value>(s) {
+            Ok(Value::Object(_)) => Ok(PropertyValue::GeoJSON(s.clone())),
+            _ => Err(format!("Property '{}' expects GeoJSON, got '{}'", path, s)),
+        },
+        (PropertyType::Array { element_type }, Value::Array(items)) => {
+            let mut errors = Vec::new();
+            let mut values = Vec::with_capacity(items.len());
+            for (i, item) in items.iter().enumerate() {
+                match coerce_json(item, element_type, &format!("{}[{}]", path, i), unknown_keys) {
+                    Ok(value) => values.push(value),
+                    Err(e) => errors.push(e),
+                }
+            }
+            if errors.is_empty() {
+                Ok(PropertyValue::Array(values))
+            } else {
+                Err(errors.join("; "))
+            }
+        }
+        (PropertyType::Map { value_type, .. }, Value::Object(entries)) => {
+            let mut errors = Vec::new();
+            let mut values = HashMap::with_capacity(entries.len());
+            for (key, entry) in entries {
+                match coerce_json(entry, value_type, &format!("{}.{}", path, key), unknown_keys) {
+                    Ok(value) => {
+                        values.insert(key.clone(), value);
+                    }
+                    Err(e) => errors.push(e),
+                }
+            }
+            if errors.is_empty() {
+                Ok(PropertyValue::Map(values))
+            } else {
+                Err(errors.join("; "))
+            }
+        }
+        (PropertyType::Object(struct_def), Value::Object(fields)) => {
+            let mut errors = Vec::new();
+            let fields = instantiate_fields(&struct_def.fields, fields, unknown_keys, &format!("{}.", path), &mut errors);
+            if errors.is_empty() {
+                Ok(PropertyValue::Object(fields.iter().map(|(k, v)| (k.clone(), v.clone())).collect()))
+            } else {
+                Err(errors.join("; "))
+            }
+        }
+        // The first member type the value converts to wins
+        (PropertyType::Union { types }, _) => types
+            .iter()
+            .find_map(|member| coerce_json(value, member, path, unknown_keys).ok())
+            .ok_or_else(mismatch),
+        _ => Err(mismatch()),
+    }
+}
+
+/// A property type as named in ontology definitions, for errors
+fn type_label(property_type: &PropertyType) -> String {
+    match property_type {
+        PropertyType::Array { .. } => "an array".to_string(),
+        PropertyType::Map { .. } => "a map".to_string(),
+        PropertyType::Object(struct_def) => format!("a '{}' struct", struct_def.id),
+        PropertyType::Union { .. } => "one of its union types".to_string(),
+        simple => serde_json::to_value(simple)
+            .ok()
+            .and_then(|v| v.as_str().map(|s| format!("type '{}'", s)))
+            .unwrap_or_else(|| format!("{:?}", simple)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::property::StructDef;
+    use serde_json::json;
+
+    fn plant_type() -> ObjectType {
+        let yaml = r#"
+ontology:
+  objectTypes:
+    - id: "plant"
+      displayName: "Plant"
+      primaryKey: "id"
+      properties:
+        - id: "id"
+          type: "string"
+          required: true
+        - id: "units"
+          type: "integer"
+        - id: "capacity"
+          type: "double"
+        - id: "active"
+          type: "boolean"
+          default: true
+        - id: "opened"
+          type: "date"
+  linkTypes: []
+"#;
+        let mut plant = OntologyRuntime::from_yaml(yaml).unwrap().get_object_type("plant").unwrap().clone();
+
+        // Complex types, built in code
+        let base = plant.properties[0].clone();
+        let field = |id: &str, property_type: PropertyType, required: bool| Property {
+            id: id.to_string(),
+            property_type,
+            required,
+            ..base.clone()
+        };
+        let address = StructDef {
+            id: "address".to_string(),
+            fields: vec![field("city", PropertyType::String, true), field("zip", PropertyType::Integer, false)],
+        };
+        let tags = PropertyType::Array { element_type: Box::new(PropertyType::String) };
+        let readings = PropertyType::Map { key_type: Box::new(PropertyType::String), value_type: Box::new(PropertyType::Integer) };
+        plant.properties.push(field("tags", tags, false));
+        plant.properties.push(field("readings", readings, false));
+        plant.properties.push(field("address", PropertyType::Object(address), false));
+        plant
+    }
+
+    #[test]
+    fn test_instantiate_from_json_coerces_and_applies_defaults() {
+        let plant = plant_type();
+        let properties = plant
+            .instantiate_from_json(&json!({
+                "id": "p1",
+                "units": "4",
+                "capacity": 12,
+                "opened": "2010-05-01",
+                "tags": ["gas", "peaker"],
+                "readings": {"jan": 3.0},
+                "address": {"city": "Boston", "zip": "02110"},
+                "_acl": null
+            }))
+            .unwrap();
+        assert_eq!(properties.get("units"), Some(&PropertyValue::Integer(4)));
+        assert_eq!(properties.get("capacity"), Some(&PropertyValue::Double(12.0)));
+        assert_eq!(properties.get("active"), Some(&PropertyValue::Boolean(true)));
+        assert_eq!(properties.get("opened"), Some(&PropertyValue::Date("2010-05-01".to_string())));
+        assert_eq!(
+            properties.get("readings"),
+            Some(&PropertyValue::Map(HashMap::from([("jan".to_string(), PropertyValue::Integer(3))])))
+        );
+        let Some(PropertyValue::Object(address)) = properties.get("address") else { panic!("{:?}", properties) };
+        assert_eq!(address.get("zip"), Some(&PropertyValue::Integer(2110)));
+        // Unknown keys are kept by default
+        assert_eq!(properties.get("_acl"), Some(&PropertyValue::Null));
+    }
+
+    #[test]
+    fn test_instantiate_from_json_collects_errors() {
+        let plant = plant_type();
+        let errors = plant
+            .instantiate_from_json(&json!({
+                "units": 2.5,
+                "opened": "not-a-date",
+                "tags": ["ok", {"nested": true}],
+                "address": {"zip": 1},
+                "extra": 1
+            }))
+            .unwrap_err();
+        assert_eq!(errors.len(), 5, "{:?}", errors);
+        assert!(errors.iter().any(|e| e == "Missing required property 'id'"));
+        assert!(errors.iter().any(|e| e.contains("'units' expects an integer, got 2.5")));
+        assert!(errors.iter().any(|e| e.contains("'not-a-date' is not an ISO 8601 date")));
+        assert!(errors.iter().any(|e| e.contains("'tags[1]' expects type 'string'")));
+        assert!(errors.iter().any(|e| e.contains("Missing required property 'address.city'")));
+
+        // Whole floats are integers; unknown keys are errors in strict mode
+        let row = json!({"id": "p2", "units": 3.0, "extra": 1});
+        let properties = plant.instantiate_from_json(&row).unwrap();
+        assert_eq!(properties.get("units"), Some(&PropertyValue::Integer(3)));
+        let errors = plant.instantiate_from_json_with(&row, UnknownKeys::Reject).unwrap_err();
+        assert_eq!(errors, vec!["Unknown property 'extra'".to_string()]);
+    }
+}
@@ -0,0 +1,175 @@
+//! Form schemas generated from the ontology.
+//!
+//! Frontends render forms for action parameters and object editing from these documents
+//! instead of restating the ontology's rules. The format follows JSON Schema where it has
+//! a keyword (`type`, `required`, `minLength`, `pattern`, `enum`, `default`, `readOnly`,
+//! ...) and uses `x-` keywords for the rest:
+//! - `x-order`: property IDs in display order
+//! - `x-sections`: the object type's property groups, in group order
+//! - `x-unit`, `x-referenceTarget`: a property's unit and referenced object type
+//!
+//! Values are typed as in the object type's JSON Schema (`ObjectType::to_json_schema`),
+//! and date bounds use `formatMinimum`/`formatMaximum`. Deprecated properties are left out
+//! unless `FormSchemaOptions::include_deprecated` is set, in which case they are marked
+//! `deprecated`. Properties populated by a model and computed properties are `readOnly`.
+
+use crate::meta_model::{ActionTypeDef, ObjectType};
+use crate::property::{Property, PropertyType, PropertyValidation};
+use crate::schema_export::value_schema;
+use serde_json::{json, Map, Value as JsonValue};
+
+/// How a form schema is rendered
+#[derive(Debug, Clone, Default)]
+pub struct FormSchemaOptions {
+    /// Locale for titles (e.g. `fr`, `pt-BR`); the default display names when unset
+    pub locale: Option<String>,
+    pub include_deprecated: bool,
+}
+
+/// Form for creating or editing objects of a type
+pub fn object_form_schema(object_type: &ObjectType, options: &FormSchemaOptions) -> JsonValue {
+    let mut schema = properties_schema(&object_type.properties, options);
+    for computed in &object_type.computed_properties {
+        let mut field = type_schema(&computed.property_type, options);
+        field.insert("title".to_string(), json!(computed.display_name));
+        if let Some(description) = &computed.description {
+            field.insert("description".to_string(), json!(description));
+        }
+        field.insert("readOnly".to_string(), json!(true));
+        push_field(&mut schema, &computed.id, field);
+    }
+
+    let visible = |id: &String| schema["properties"].get(id.as_str()).is_some();
+    let mut groups: Vec<_> = object_type.property_groups.iter().collect();
+    groups.sort_by_key(|g| g.order);
+    let sections: Vec<JsonValue> = groups
+        .into_iter()
+        .map(|group| {
+            let mut section = json!({
+                "id": group.id,
+                "title": group.display_name_for(options.locale.as_deref()),
+                "properties": group.properties.iter().filter(|id| visible(id)).collect::<Vec<_>>(),
+                "collapsible": group.collapsible,
+                "collapsed": group.collapsed_by_default,
+            });
+            if let Some(description) = &group.description {
+                section["description"] = json!(description);
+            }
+            section
+        })
+        .collect();
+
+    let mut form = Map::new();
+    form.insert("title".to_string(), json!(object_type.display_name_for(options.locale.as_deref())));
+    form.insert("x-objectType".to_string(), json!(object_type.id));
+    form.extend(schema);
+    if !sections.is_empty() {
+        form.insert("x-sections".to_string(), JsonValue::Array(sections));
+    }
+    JsonValue::Object(form)
+}
+
+/// Form for an action's parameters
+pub fn action_form_schema(action: &ActionTypeDef, options: &FormSchemaOptions) -> JsonValue {
+    let mut form = Map::new();
+    form.insert("title".to_string(), json!(action.display_name_for(options.locale.as_deref())));
+    form.insert("x-actionType".to_string(), json!(action.id));
+    form.extend(properties_schema(&action.parameters, options));
+    JsonValue::Object(form)
+}
+
+/// Schema of a single property: its type, constraints and presentation
+pub fn property_schema(property: &Property, options: &FormSchemaOptions) -> JsonValue {
+    let mut schema = type_schema(&property.property_type, options);
+    schema.insert("title".to_string(), json!(property.display_name_for(options.locale.as_deref())));
+    if let Some(description) = &property.description {
+        schema.insert("description".to_string(), json!(description));
+    }
+    if let Some(default) = &property.default {
+        schema.insert("default".to_string(), serde_json::to_value(default).unwrap_or(JsonValue::Null));
+    }
+    if let Some(validation) = &property.validation {
+        add_constraints(&mut schema, validation, &property.property_type);
+    }
+    if let Some(unit) = &property.unit {
+        schema.insert("x-unit".to_string(), json!(unit));
+    }
+    if let Some(target) = &property.reference_target {
+        schema.insert("x-referenceTarget".to_string(), json!(target));
+    }
+    if property.model_binding.is_some() {
+        schema.insert("readOnly".to_string(), json!(true));
+    }
+    if property.deprecated.is_some() {
+        schema.insert("deprecated".to_string(), json!(true));
+    }
+    JsonValue::Object(schema)
+}
+
+/// An object schema over `properties`: `properties`, `required` and `x-order`
+fn properties_schema(properties: &[Property], options: &FormSchemaOptions) -> Map<String, JsonValue> {
+    let mut ordered: Vec<&Property> = properties
+        .iter()
+        .filter(|p| options.include_deprecated || p.deprecated.is_none())
+        .collect();
+    // Explicit display order first, then declaration order
+    ordered.sort_by_key(|p| p.display_order.unwrap_or(u32::MAX));
+
+    let mut schema = Map::new();
+    schema.insert("type".to_string(), json!("object"));
+    schema.insert("properties".to_string(), JsonValue::Object(Map::new()));
+    schema.insert("required".to_string(), JsonValue::Array(Vec::new()));
+    schema.insert("x-order".to_string(), JsonValue::Array(Vec::new()));
+    for property in ordered {
+        if property.required {
+            if let Some(JsonValue::Array(required)) = schema.get_mut("required") {
+                required.push(json!(property.id));
+            }
+        }
+        let JsonValue::Object(field) = property_schema(property, options) else {
+            continue;
+        };
+        push_field(&mut schema, &property.id, field);
+    }
+    schema
+}
+
+fn push_field(schema: &mut Map<String, JsonValue>, id: &str, field: Map<String, JsonValue>) {
+    if let Some(JsonValue::Object(properties)) = schema.get_mut("properties") {
+        properties.insert(id.to_string(), JsonValue::Object(field));
+    }
+    if let Some(JsonValue::Array(order)) = schema.get_mut("x-order") {
+        order.push(json!(id));
+    }
+}
+
+fn type_schema(property_type: &PropertyType, options: &FormSchemaOptions) -> Map<String, JsonValue> {
+    value_schema(property_type, &|struct_def| {
+        let mut schema = properties_schema(&struct_def.fields, options);
+        schema.insert("x-struct".to_string(), json!(struct_def.id));
+        schema
+    })
+}
+
+pub(crate) fn add_constraints(schema: &mut Map<String, JsonValue>, validation: &PropertyValidation, property_type: &PropertyType) {
+    // Length bounds count items for arrays and characters otherwise
+    let (min_length, max_length) = match property_type {
+        PropertyType::Array { .. } => ("minItems", "maxItems"),
+        _ => ("minLength", "maxLength"),
+    };
+    let constraints = [
+        (min_length, validation.min_length.map(|n| json!(n))),
+        (max_length, validation.max_length.map(|n| json!(n))),
+        ("minimum", validation.min.map(|n| json!(n))),
+        ("maximum", validation.max.map(|n| json!(n))),
+        ("pattern", validation.pattern.as_ref().map(|p| json!(p))),
+        ("enum", validation.enum_values.as_ref().map(|values| json!(values))),
+        ("formatMinimum", validation.min_date.as_ref().map(|d| json!(d))),
+        ("formatMaximum", validation.max_date.as_ref().map(|d| json!(d))),
+    ];
+    for (keyword, value) in constraints {
+        if let Some(value) = value {
+            schema.insert(keyword.to_string(), value);
+        }
+    }
+}
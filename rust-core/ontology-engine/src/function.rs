@@ -1,5 +1,7 @@
-use crate::meta_model::{FunctionTypeDef, FunctionLogic, FunctionReturnType, AggregationType};
+use crate::meta_model::{FunctionTypeDef, FunctionLogic, FunctionReturnType, AggregationType, FunctionFilter};
+use crate::validation::condition_matches;
 use crate::property::{PropertyValue, PropertyMap};
+use async_trait::async_trait;
 
 /// Function execution result
 #[derive(Debug, Clone)]
@@ -7,18 +9,151 @@ pub struct FunctionExecutionResult {
     pub value: PropertyValue,
 }
 
+/// Data access for function execution, provided by the runtime (e.g. backed by the search
+/// and graph stores). Errors are store failures; a missing object or property is `Ok(None)`.
+#[async_trait]
+pub trait FunctionDataSource: Send + Sync {
+    /// A property of a stored object
+    async fn get_object_property(
+        &self,
+        object_type: &str,
+        object_id: &str,
+        property: &str,
+    ) -> Result<Option<PropertyValue>, String>;
+    
+    /// IDs of the `target_type` objects linked to `object_id` through `link_type`
+    async fn get_linked_objects(
+        &self,
+        object_id: &str,
+        link_type: &str,
+        target_type: &str,
+    ) -> Result<Vec<String>, String>;
+    
+    /// `aggregation` of `property` over the objects linked to `object_id` through `link_type`
+    async fn aggregate_linked_properties(
+        &self,
+        object_id: &str,
+        link_type: &str,
+        property: &str,
+        aggregation: AggregationType,
+    ) -> Result<Option<PropertyValue>, String>;
+}
+
+impl AggregationType {
+    /// Fold property values; nulls are skipped. Sums of integers stay integers, averages
+    /// are doubles, and min and max keep the winning value as it was. Sum and count of no
+    /// values are 0, the others null.
+    pub fn apply(&self, values: &[PropertyValue]) -> Result<PropertyValue, String> {
+        let values: Vec<&PropertyValue> = values.iter().filter(|v| !matches!(v, PropertyValue::Null)).collect();
+        if let AggregationType::Count = self {
+            return Ok(PropertyValue::Integer(values.len() as i64));
+        }
+        let numbers = values
+            .iter()
+            .map(|value| match value {
+                PropertyValue::Integer(i) => Ok(*i as f64),
+                PropertyValue::Double(d) => Ok(*d),
+                other => Err(format!("Cannot aggregate non-numeric value {}", other.to_string())),
+            })
+            .collect::<Result<Vec<f64>, String>>()?;
+        let all_integers = values.iter().all(|v| matches!(v, PropertyValue::Integer(_)));
+        let pick = |better: fn(f64, f64) -> bool| {
+            values
+                .iter()
+                .zip(&numbers)
+                .fold(None, |best: Option<(&PropertyValue, f64)>, (value, &n)| match best {
+                    Some((_, b)) if !better(n, b) => best,
+                    _ => Some((value, n)),
+                })
+                .map_or(PropertyValue::Null, |(value, _)| (*value).clone())
+        };
+        Ok(match self {
+            AggregationType::Sum if all_integers => {
+                PropertyValue::Integer(values.iter().map(|v| if let PropertyValue::Integer(i) = v { *i } else { 0 }).sum())
+            }
+            AggregationType::Sum => PropertyValue::Double(numbers.iter().sum()),
+            AggregationType::Avg if numbers.is_empty() => PropertyValue::Null,
+            AggregationType::Avg => PropertyValue::Double(numbers.iter().sum::<f64>() / numbers.len() as f64),
+            AggregationType::Min => pick(|a, b| a < b),
+            AggregationType::Max => pick(|a, b| a > b),
+            AggregationType::Count => unreachable!("handled above"),
+        })
+    }
+}
+
+/// Objects flowing through a pipeline: the source object, as referenced in the parameters,
+/// until a traversal replaces it with the objects it reached
+struct ObjectSet {
+    /// Type of every object once traversed; unknown for the source
+    object_type: Option<String>,
+    /// Type passed to the data source for bare source IDs: the source parameter's ID
+    type_hint: String,
+    ids: Vec<String>,
+}
+
+impl ObjectSet {
+    /// Reference for the data source: `type:id` once traversed, the source as given
+    fn reference(&self, id: &str) -> String {
+        match &self.object_type {
+            Some(object_type) => format!("{}:{}", object_type, id),
+            None => id.to_string(),
+        }
+    }
+    
+    async fn property(
+        &self,
+        id: &str,
+        property: &str,
+        data: Option<&dyn FunctionDataSource>,
+    ) -> Result<Option<PropertyValue>, String> {
+        let Some(data) = data else {
+            return Ok(None);
+        };
+        let (object_type, object_id) = match (&self.object_type, id.split_once(':')) {
+            (Some(object_type), _) => (object_type.as_str(), id),
+            (None, Some((object_type, object_id))) => (object_type, object_id),
+            (None, None) => (self.type_hint.as_str(), id),
+        };
+        data.get_object_property(object_type, object_id, property).await
+    }
+    
+    /// Keep the objects whose properties match every filter; a missing property matches nothing
+    async fn retain(&mut self, filters: &[FunctionFilter], data: Option<&dyn FunctionDataSource>) -> Result<(), String> {
+        let conditions = filters
+            .iter()
+            .map(|filter| filter.condition())
+            .collect::<Result<Vec<_>, String>>()?;
+        let mut kept = Vec::new();
+        for id in &self.ids {
+            let mut matches = true;
+            for condition in &conditions {
+                matches = match self.property(id, &condition.property, data).await? {
+                    Some(value) => condition_matches(condition, &value).map_err(|e| e.to_string())?,
+                    None => false,
+                };
+                if !matches {
+                    break;
+                }
+            }
+            if matches {
+                kept.push(id.clone());
+            }
+        }
+        self.ids = kept;
+        Ok(())
+    }
+}
+
 /// Function executor - executes declarative function logic
 pub struct FunctionExecutor;
 
 impl FunctionExecutor {
-    /// Execute a function with given parameters
+    /// Execute a function with given parameters. Without a data source, functions that need
+    /// stored data return placeholders (0, an empty array, null).
     pub async fn execute(
         function_def: &FunctionTypeDef,
         parameters: &PropertyMap,
-        // Callbacks for accessing data (would be provided by the runtime)
-        get_object_property: Option<&(dyn Fn(&str, &str, &str) -> Option<PropertyValue> + Send + Sync)>, // (object_type, object_id, property_id) -> value
-        get_linked_objects: Option<&(dyn Fn(&str, &str, &str) -> Vec<String> + Send + Sync)>, // (object_id, link_type, target_type) -> object_ids
-        aggregate_linked_properties: Option<&(dyn Fn(&str, &str, &str, AggregationType) -> Option<PropertyValue> + Send + Sync)>, // (object_id, link_type, property, agg_type) -> value
+        data: Option<&dyn FunctionDataSource>,
     ) -> Result<FunctionExecutionResult, String> {
         // Validate parameters
         for param_def in &function_def.parameters {
@@ -29,7 +164,7 @@ impl FunctionExecutor {
             }
             
             if let Some(value) = parameters.get(&param_def.id) {
-                if let Err(e) = param_def.validate_value(value) {
+                if let Err(e) = param_def.validate_value_with_siblings(value, parameters) {
                     return Err(format!("Invalid parameter '{}': {}", param_def.id, e));
                 }
             }
@@ -37,59 +172,75 @@ impl FunctionExecutor {
         
         // Execute function logic
         let result = match &function_def.logic {
-            FunctionLogic::Aggregation { link_type, aggregation, property } => {
+            FunctionLogic::Aggregation { link_type: Some(link_type), aggregation, property } => {
                 Self::execute_aggregation(
+                    function_def,
                     parameters,
                     link_type,
                     aggregation,
                     property,
-                    aggregate_linked_properties,
-                )?
+                    data,
+                ).await?
             }
-            FunctionLogic::LinkTraversal { link_type, target_type, filter } => {
-                Self::execute_link_traversal(
+            FunctionLogic::LinkTraversal { .. } | FunctionLogic::Pipeline { .. } => {
+                Self::execute_pipeline(
+                    function_def,
                     parameters,
-                    link_type,
-                    target_type,
-                    filter,
-                    get_linked_objects,
-                )?
+                    function_def.logic.steps(),
+                    data,
+                ).await?
+            }
+            FunctionLogic::Aggregation { link_type: None, .. } => {
+                return Err("Aggregation without a link type only runs inside a pipeline".to_string());
+            }
+            FunctionLogic::Filter { .. } => {
+                return Err("Filter steps only run inside a pipeline".to_string());
             }
             FunctionLogic::PropertyAccess { property } => {
                 Self::execute_property_access(
                     parameters,
                     property,
-                    get_object_property,
-                )?
+                    data,
+                ).await?
             }
         };
         
         Ok(FunctionExecutionResult { value: result })
     }
     
+    /// The object a function runs on: the first object reference parameter, in declaration
+    /// order, as `(parameter ID, reference)`
+    fn source_parameter(function_def: &FunctionTypeDef, parameters: &PropertyMap) -> Result<(String, String), String> {
+        function_def
+            .parameters
+            .iter()
+            .filter_map(|param_def| parameters.get(&param_def.id).map(|value| (&param_def.id, value)))
+            .chain(parameters.iter())
+            .find_map(|(param_id, value)| match value {
+                PropertyValue::ObjectReference(ref_id) => Some((param_id.clone(), ref_id.clone())),
+                _ => None,
+            })
+            .ok_or_else(|| "Missing source object ID in parameters".to_string())
+    }
+    
+    fn source_reference(function_def: &FunctionTypeDef, parameters: &PropertyMap) -> Result<String, String> {
+        Self::source_parameter(function_def, parameters).map(|(_, reference)| reference)
+    }
+    
     /// Execute aggregation logic
-    fn execute_aggregation(
+    async fn execute_aggregation(
+        function_def: &FunctionTypeDef,
         parameters: &PropertyMap,
         link_type: &str,
         aggregation: &AggregationType,
         property: &str,
-        aggregate_fn: Option<&(dyn Fn(&str, &str, &str, AggregationType) -> Option<PropertyValue> + Send + Sync)>,
+        data: Option<&dyn FunctionDataSource>,
     ) -> Result<PropertyValue, String> {
-        // Get the source object ID from parameters
-        // Assumes first parameter is the source object ID
-        let source_id = parameters.iter()
-            .next()
-            .and_then(|(_, v)| {
-                if let PropertyValue::ObjectReference(ref_id) = v {
-                    Some(ref_id.clone())
-                } else {
-                    None
-                }
-            })
-            .ok_or_else(|| "Missing source object ID in parameters".to_string())?;
+        let source_id = Self::source_reference(function_def, parameters)?;
         
-        if let Some(agg_fn) = aggregate_fn {
-            agg_fn(&source_id, link_type, property, aggregation.clone())
+        if let Some(data) = data {
+            data.aggregate_linked_properties(&source_id, link_type, property, aggregation.clone())
+                .await?
                 .ok_or_else(|| format!("Aggregation failed for link type '{}', property '{}'", link_type, property))
         } else {
             // Fallback: return a placeholder value
@@ -97,44 +248,95 @@ impl FunctionExecutor {
         }
     }
     
-    /// Execute link traversal logic
-    fn execute_link_traversal(
+    /// Execute pipeline steps against the source object. Link traversals also run here, as
+    /// a pipeline of one step.
+    async fn execute_pipeline(
+        function_def: &FunctionTypeDef,
         parameters: &PropertyMap,
-        link_type: &str,
-        target_type: &str,
-        _filter: &Option<crate::meta_model::FunctionFilter>,
-        get_linked_fn: Option<&(dyn Fn(&str, &str, &str) -> Vec<String> + Send + Sync)>,
+        steps: &[FunctionLogic],
+        data: Option<&dyn FunctionDataSource>,
     ) -> Result<PropertyValue, String> {
-        // Get the source object ID from parameters
-        let source_id = parameters.iter()
-            .next()
-            .and_then(|(_, v)| {
-                if let PropertyValue::ObjectReference(ref_id) = v {
-                    Some(ref_id.clone())
-                } else {
-                    None
-                }
-            })
-            .ok_or_else(|| "Missing source object ID in parameters".to_string())?;
+        let (source_param, source_reference) = Self::source_parameter(function_def, parameters)?;
+        let mut objects = ObjectSet {
+            object_type: None,
+            type_hint: source_param,
+            ids: vec![source_reference],
+        };
         
-        if let Some(linked_fn) = get_linked_fn {
-            let linked_ids = linked_fn(&source_id, link_type, target_type);
-            // Return array of object references
-            let refs: Vec<PropertyValue> = linked_ids.into_iter()
-                .map(|id| PropertyValue::ObjectReference(id))
-                .collect();
-            Ok(PropertyValue::Array(refs))
-        } else {
-            // Fallback: return empty array
-            Ok(PropertyValue::Array(Vec::new()))
+        for (i, step) in steps.iter().enumerate() {
+            if i > 0 && steps[i - 1].produces_value() {
+                return Err(format!(
+                    "Pipeline step {} ({}) needs objects, but step {} produced a value",
+                    i + 1,
+                    step.kind(),
+                    i
+                ));
+            }
+            match step {
+                FunctionLogic::LinkTraversal { link_type, target_type, filter } => {
+                    let mut linked_ids: Vec<String> = Vec::new();
+                    if let Some(data) = data {
+                        for id in &objects.ids {
+                            for linked_id in data.get_linked_objects(&objects.reference(id), link_type, target_type).await? {
+                                if !linked_ids.contains(&linked_id) {
+                                    linked_ids.push(linked_id);
+                                }
+                            }
+                        }
+                    }
+                    objects = ObjectSet {
+                        object_type: Some(target_type.clone()),
+                        type_hint: target_type.clone(),
+                        ids: linked_ids,
+                    };
+                    if let Some(filter) = filter {
+                        objects.retain(std::slice::from_ref(filter), data).await?;
+                    }
+                }
+                FunctionLogic::Filter { filters } => {
+                    objects.retain(filters, data).await?;
+                }
+                FunctionLogic::Aggregation { link_type: None, aggregation, property } => {
+                    let mut values = Vec::new();
+                    for id in &objects.ids {
+                        if let Some(value) = objects.property(id, property, data).await? {
+                            values.push(value);
+                        }
+                    }
+                    return aggregation.apply(&values);
+                }
+                FunctionLogic::PropertyAccess { property } => {
+                    if objects.object_type.is_none() {
+                        return objects.property(&objects.ids[0], property, data).await.map(|v| v.unwrap_or(PropertyValue::Null));
+                    }
+                    let mut values = Vec::new();
+                    for id in &objects.ids {
+                        values.push(objects.property(id, property, data).await?.unwrap_or(PropertyValue::Null));
+                    }
+                    return Ok(PropertyValue::Array(values));
+                }
+                FunctionLogic::Aggregation { link_type: Some(link_type), .. } => {
+                    return Err(format!(
+                        "Pipeline step {} aggregates over link type '{}'; traverse it in an earlier step",
+                        i + 1,
+                        link_type
+                    ));
+                }
+                FunctionLogic::Pipeline { .. } => {
+                    return Err(format!("Pipeline step {} is a nested pipeline", i + 1));
+                }
+            }
         }
+        
+        // Return array of object references
+        Ok(PropertyValue::Array(objects.ids.into_iter().map(PropertyValue::ObjectReference).collect()))
     }
     
     /// Execute property access logic
-    fn execute_property_access(
+    async fn execute_property_access(
         parameters: &PropertyMap,
         property: &str,
-        get_property_fn: Option<&(dyn Fn(&str, &str, &str) -> Option<PropertyValue> + Send + Sync)>,
+        data: Option<&dyn FunctionDataSource>,
     ) -> Result<PropertyValue, String> {
         // Get the object ID and type from parameters
         let (object_type, object_id) = parameters.iter()
@@ -157,8 +359,9 @@ impl FunctionExecutor {
             (object_type, object_id)
         };
         
-        if let Some(prop_fn) = get_property_fn {
-            prop_fn(&obj_type, &obj_id, property)
+        if let Some(data) = data {
+            data.get_object_property(&obj_type, &obj_id, property)
+                .await?
                 .ok_or_else(|| format!("Property '{}' not found on object '{}' of type '{}'", property, obj_id, obj_type))
         } else {
             // Fallback: return null
@@ -176,11 +379,13 @@ mod tests {
         FunctionTypeDef {
             id: "get_total_value".to_string(),
             display_name: "Get Total Value".to_string(),
+            display_names: std::collections::HashMap::new(),
             description: Some("Get total value".to_string()),
             parameters: vec![
                 Property {
                     id: "portfolio_id".to_string(),
                     display_name: None,
+                    display_names: std::collections::HashMap::new(),
                     property_type: PropertyType::ObjectReference,
                     required: true,
                     default: None,
@@ -193,17 +398,23 @@ mod tests {
                     pii: false,
                     deprecated: None,
                     statistics: None,
-                    model_binding: None,                },
+                    model_binding: None,
+                    display_order: None,
+                    indexing: None,
+                    reference_target: None,
+                    geo: None,
+                    custom_validator: None,                },
             ],
             return_type: FunctionReturnType::Property {
                 property_type: PropertyType::Double,
             },
             logic: FunctionLogic::Aggregation {
-                link_type: "portfolio_holding".to_string(),
+                link_type: Some("portfolio_holding".to_string()),
                 aggregation: AggregationType::Sum,
                 property: "value".to_string(),
             },
             cacheable: true,
+            target_interface: None,
         }
     }
     
@@ -213,5 +424,22 @@ mod tests {
         // Just verify it compiles
         assert!(true);
     }
+    
+    #[test]
+    fn test_aggregation_apply() {
+        let values = vec![PropertyValue::Integer(3), PropertyValue::Null, PropertyValue::Integer(5)];
+        assert_eq!(AggregationType::Sum.apply(&values).unwrap(), PropertyValue::Integer(8));
+        assert_eq!(AggregationType::Avg.apply(&values).unwrap(), PropertyValue::Double(4.0));
+        assert_eq!(AggregationType::Count.apply(&values).unwrap(), PropertyValue::Integer(2));
+        assert_eq!(AggregationType::Max.apply(&values).unwrap(), PropertyValue::Integer(5));
+        
+        let mixed = vec![PropertyValue::Integer(3), PropertyValue::Double(1.5)];
+        assert_eq!(AggregationType::Sum.apply(&mixed).unwrap(), PropertyValue::Double(4.5));
+        assert_eq!(AggregationType::Min.apply(&mixed).unwrap(), PropertyValue::Double(1.5));
+        
+        assert_eq!(AggregationType::Sum.apply(&[]).unwrap(), PropertyValue::Integer(0));
+        assert_eq!(AggregationType::Avg.apply(&[]).unwrap(), PropertyValue::Null);
+        assert!(AggregationType::Sum.apply(&[PropertyValue::String("x".to_string())]).is_err());
+    }
 }
 
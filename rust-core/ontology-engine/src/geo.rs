@@ -0,0 +1,340 @@
+//! Coordinates of GeoJSON property values.
+//!
+//! Stored geometries are WGS84 longitude/latitude in degrees. A GeoJSON property can declare
+//! the CRS its source data arrives in; values are reprojected to WGS84 at ingest. Coordinates
+//! outside longitude [-180, 180] / latitude [-90, 90] (typically projected meters that were
+//! never reprojected) are rejected under strict validation and logged under lenient
+//! validation. Distances are great-circle meters on the WGS84 mean radius.
+//!
+//! `EPSG:4326` and spherical Web Mercator (`EPSG:3857`) are built in; other CRSs are PROJ
+//! strings (`+proj=utm +zone=18 +datum=WGS84`) and need the `proj` feature.
+//!
+//! Spatial relations (`relates`) are computed on the plane of longitude/latitude, as
+//! Elasticsearch does for `geo_shape` queries; geometries crossing the antimeridian are not
+//! split.
+
+use ::geo::{Closest, ClosestPoint, Geometry, Point, Relate};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Mean earth radius used for great-circle distances
+pub const EARTH_RADIUS_METERS: f64 = 6_371_008.8;
+
+/// Sphere radius of Web Mercator (EPSG:3857)
+const WEB_MERCATOR_RADIUS: f64 = 6_378_137.0;
+
+/// Coordinate handling of a GeoJSON property
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GeoOptions {
+    /// CRS values arrive in, reprojected to WGS84 at ingest; WGS84 when unset
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub crs: Option<String>,
+
+    #[serde(default)]
+    pub validation: CoordinateValidation,
+}
+
+/// What happens to a geometry with coordinates outside the WGS84 ranges
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CoordinateValidation {
+    /// Accepted with a warning
+    #[default]
+    Lenient,
+    /// Rejected
+    Strict,
+}
+
+impl GeoOptions {
+    /// The declared CRS, if values need reprojecting to WGS84
+    pub fn source_crs(&self) -> Option<&str> {
+        self.crs.as_deref().filter(|crs| !is_wgs84(crs))
+    }
+
+    /// Check the declared CRS can be reprojected from
+    pub fn validate(&self) -> Result<(), String> {
+        match self.source_crs() {
+            Some(crs) => projection(crs).map(|_| ()),
+            None => Ok(()),
+        }
+    }
+}
+
+fn is_wgs84(crs: &str) -> bool {
+    matches!(
+        crs.trim().to_ascii_uppercase().as_str(),
+        "EPSG:4326" | "WGS84" | "CRS:84" | "OGC:CRS84"
+    )
+}
+
+fn is_web_mercator(crs: &str) -> bool {
+    matches!(
+        crs.trim().to_ascii_uppercase().as_str(),
+        "EPSG:3857" | "EPSG:900913" | "EPSG:102100"
+    )
+}
+
+/// Check every position of a geometry, feature or feature collection lies within WGS84
+/// longitude/latitude ranges
+pub fn check_coordinates(geojson: &Value) -> Result<(), String> {
+    for (lon, lat) in positions(geojson)? {
+        if !(-180.0..=180.0).contains(&lon) || !(-90.0..=90.0).contains(&lat) {
+            return Err(format!(
+                "coordinate [{}, {}] is outside longitude [-180, 180] / latitude [-90, 90]; \
+                 is it in a projected CRS?",
+                lon, lat
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Reproject every position of a geometry, feature or feature collection from `crs` to
+/// WGS84, in place
+pub fn reproject_to_wgs84(geojson: &mut Value, crs: &str) -> Result<(), String> {
+    let project = projection(crs)?;
+    for_each_position_mut(geojson, &mut |position| {
+        let (x, y) = lon_lat(position)?;
+        let (lon, lat) = project(x, y)?;
+        position[0] = Value::from(lon);
+        position[1] = Value::from(lat);
+        Ok(())
+    })
+}
+
+/// Great-circle distance in meters between two WGS84 `(longitude, latitude)` positions
+pub fn haversine_meters((lon1, lat1): (f64, f64), (lon2, lat2): (f64, f64)) -> f64 {
+    let (phi1, phi2) = (lat1.to_radians(), lat2.to_radians());
+    let d_phi = (lat2 - lat1).to_radians();
+    let d_lambda = (lon2 - lon1).to_radians();
+    let a = (d_phi / 2.0).sin().powi(2) + phi1.cos() * phi2.cos() * (d_lambda / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS_METERS * a.sqrt().asin()
+}
+
+/// Shortest great-circle distance in meters between the positions of two WGS84 geometries.
+/// Distances are measured between vertices, so a point inside a large polygon is as far
+/// from it as from its nearest corner. `None` if either has no positions.
+pub fn distance_meters(a: &Value, b: &Value) -> Option<f64> {
+    let (a, b) = (positions(a).ok()?, positions(b).ok()?);
+    a.iter()
+        .flat_map(|p| b.iter().map(move |q| haversine_meters(*p, *q)))
+        .min_by(f64::total_cmp)
+}
+
+/// How a stored geometry relates to the query geometry of a spatial filter
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpatialRelation {
+    /// The stored geometry contains the query geometry
+    Contains,
+    /// The geometries share at least one point
+    Intersects,
+    /// The stored geometry lies within the query geometry
+    Within,
+}
+
+/// Whether a stored geometry relates to a query geometry. Either can be a GeoJSON geometry,
+/// feature or feature collection.
+pub fn relates(stored: &Value, query: &Value, relation: SpatialRelation) -> Result<bool, String> {
+    let matrix = geometry(stored)?.relate(&geometry(query)?);
+    Ok(match relation {
+        SpatialRelation::Contains => matrix.is_contains(),
+        SpatialRelation::Intersects => matrix.is_intersects(),
+        SpatialRelation::Within => matrix.is_within(),
+    })
+}
+
+/// `(longitude, latitude)` of a GeoJSON Point geometry or feature; `Err` names the type of
+/// any other geometry
+pub fn point(geojson: &Value) -> Result<(f64, f64), String> {
+    match geometry(geojson)? {
+        Geometry::Point(point) => Ok(point.x_y()),
+        _ => {
+            let type_name = geojson.get("geometry").unwrap_or(geojson).get("type").and_then(Value::as_str);
+            Err(format!("expected a Point geometry, got {}", type_name.unwrap_or("an untyped geometry")))
+        }
+    }
+}
+
+/// Great-circle meters from a WGS84 `(longitude, latitude)` point to a geometry: zero if the
+/// geometry contains or touches the point, otherwise the distance to the geometry's closest
+/// point. `None` if the geometry is invalid or empty.
+pub fn point_distance_meters(point: (f64, f64), geojson: &Value) -> Option<f64> {
+    match geometry(geojson).ok()?.closest_point(&Point::from(point)) {
+        Closest::Intersection(_) => Some(0.0),
+        Closest::SinglePoint(closest) => Some(haversine_meters(point, closest.x_y())),
+        Closest::Indeterminate => None,
+    }
+}
+
+/// The geometry of a GeoJSON geometry, feature or feature collection
+pub(crate) fn geometry(geojson: &Value) -> Result<Geometry<f64>, String> {
+    let parsed = geojson::GeoJson::from_json_value(geojson.clone()).map_err(|e| format!("invalid GeoJSON: {}", e))?;
+    Geometry::try_from(parsed).map_err(|e| format!("invalid GeoJSON: {}", e))
+}
+
+/// `(x, y)` of every position, in document order
+fn positions(geojson: &Value) -> Result<Vec<(f64, f64)>, String> {
+    let mut found = Vec::new();
+    let mut value = geojson.clone();
+    for_each_position_mut(&mut value, &mut |position| {
+        found.push(lon_lat(position)?);
+        Ok(())
+    })?;
+    Ok(found)
+}
+
+/// Visit the positions under `coordinates`, `geometry`, `geometries` and `features`
+fn for_each_position_mut(
+    value: &mut Value,
+    visit: &mut dyn FnMut(&mut Vec<Value>) -> Result<(), String>,
+) -> Result<(), String> {
+    match value {
+        Value::Object(map) => {
+            for key in ["coordinates", "geometry", "geometries", "features"] {
+                if let Some(child) = map.get_mut(key) {
+                    for_each_position_mut(child, visit)?;
+                }
+            }
+            Ok(())
+        }
+        Value::Array(items) if items.first().is_some_and(Value::is_number) => visit(items),
+        Value::Array(items) => items.iter_mut().try_for_each(|item| for_each_position_mut(item, visit)),
+        _ => Ok(()),
+    }
+}
+
+fn lon_lat(position: &[Value]) -> Result<(f64, f64), String> {
+    match (position.first().and_then(Value::as_f64), position.get(1).and_then(Value::as_f64)) {
+        (Some(x), Some(y)) => Ok((x, y)),
+        _ => Err(format!("position {:?} needs two numbers", position)),
+    }
+}
+
+/// Converts `(x, y)` in a source CRS to WGS84 `(longitude, latitude)` in degrees
+type Projection = Box<dyn Fn(f64, f64) -> Result<(f64, f64), String>>;
+
+fn projection(crs: &str) -> Result<Projection, String> {
+    if is_wgs84(crs) {
+        return Ok(Box::new(|x, y| Ok((x, y))));
+    }
+    if is_web_mercator(crs) {
+        return Ok(Box::new(|x, y| {
+            let lon = (x / WEB_MERCATOR_RADIUS).to_degrees();
+            let lat = (2.0 * (y / WEB_MERCATOR_RADIUS).exp().atan() - std::f64::consts::FRAC_PI_2).to_degrees();
+            Ok((lon, lat))
+        }));
+    }
+    proj_projection(crs)
+}
+
+#[cfg(feature = "proj")]
+fn proj_projection(crs: &str) -> Result<Projection, String> {
+    use proj4rs::proj::Proj;
+
+    let from = Proj::from_proj_string(crs).map_err(|e| format!("unsupported CRS '{}': {}", crs, e))?;
+    let to = Proj::from_proj_string("+proj=longlat +datum=WGS84 +no_defs")
+        .map_err(|e| format!("cannot build WGS84 projection: {}", e))?;
+    let crs = crs.to_string();
+    Ok(Box::new(move |x, y| {
+        let mut point = (x, y, 0.0);
+        proj4rs::transform::transform(&from, &to, &mut point)
+            .map_err(|e| format!("cannot reproject [{}, {}] from '{}': {}", x, y, crs, e))?;
+        Ok((point.0.to_degrees(), point.1.to_degrees()))
+    }))
+}
+
+#[cfg(not(feature = "proj"))]
+fn proj_projection(crs: &str) -> Result<Projection, String> {
+    Err(format!(
+        "unsupported CRS '{}': EPSG:4326 and EPSG:3857 are built in, PROJ strings need the `proj` feature",
+        crs
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_check_coordinates() {
+        assert!(check_coordinates(&json!({"type": "Point", "coordinates": [-71.06, 42.36]})).is_ok());
+        let projected = json!({"type": "Point", "coordinates": [-7910240.0, 5215074.0]});
+        assert!(check_coordinates(&projected).unwrap_err().contains("-7910240"));
+
+        // Positions are found inside features and collections
+        let collection = json!({
+            "type": "FeatureCollection",
+            "features": [{"type": "Feature", "properties": {}, "geometry": {
+                "type": "GeometryCollection",
+                "geometries": [{"type": "LineString", "coordinates": [[0.0, 0.0], [10.0, 95.0]]}]
+            }}]
+        });
+        assert!(check_coordinates(&collection).is_err());
+    }
+
+    #[test]
+    fn test_reproject_web_mercator() {
+        let mut point = json!({"type": "Point", "coordinates": [-7910240.0, 5215074.0]});
+        reproject_to_wgs84(&mut point, "EPSG:3857").unwrap();
+        let coordinates = point["coordinates"].as_array().unwrap();
+        assert!((coordinates[0].as_f64().unwrap() - -71.0589).abs() < 1e-3);
+        assert!((coordinates[1].as_f64().unwrap() - 42.3601).abs() < 1e-3);
+        assert!(check_coordinates(&point).is_ok());
+
+        let options = GeoOptions { crs: Some("EPSG:4326".to_string()), ..Default::default() };
+        assert_eq!(options.source_crs(), None);
+        let options = GeoOptions { crs: Some("EPSG:27700".to_string()), ..Default::default() };
+        assert!(options.validate().is_err());
+    }
+
+    #[cfg(feature = "proj")]
+    #[test]
+    fn test_reproject_proj_string() {
+        let crs = "+proj=merc +a=6378137 +b=6378137 +lat_ts=0 +lon_0=0 +x_0=0 +y_0=0 +k=1 +units=m +no_defs";
+        let mut built_in = json!({"type": "Point", "coordinates": [-7910240.0, 5215074.0]});
+        let mut via_proj = built_in.clone();
+        reproject_to_wgs84(&mut built_in, "EPSG:3857").unwrap();
+        reproject_to_wgs84(&mut via_proj, crs).unwrap();
+        for i in 0..2 {
+            let (a, b) = (built_in["coordinates"][i].as_f64().unwrap(), via_proj["coordinates"][i].as_f64().unwrap());
+            assert!((a - b).abs() < 1e-6, "{} != {}", a, b);
+        }
+    }
+
+    #[test]
+    fn test_distance_meters() {
+        // Boston to New York City, about 306 km
+        let boston = json!({"type": "Point", "coordinates": [-71.0589, 42.3601]});
+        let nyc = json!({"type": "Point", "coordinates": [-74.0060, 40.7128]});
+        let meters = distance_meters(&boston, &nyc).unwrap();
+        assert!((meters - 306_000.0).abs() < 2_000.0, "{}", meters);
+
+        // One degree of latitude
+        let meters = haversine_meters((0.0, 0.0), (0.0, 1.0));
+        assert!((meters - 111_195.0).abs() < 1.0, "{}", meters);
+
+        assert_eq!(distance_meters(&boston, &json!({"type": "GeometryCollection", "geometries": []})), None);
+    }
+
+    #[test]
+    fn test_spatial_relations() {
+        let square = json!({"type": "Polygon", "coordinates": [[[0.0, 0.0], [2.0, 0.0], [2.0, 2.0], [0.0, 2.0], [0.0, 0.0]]]});
+        let inner = json!({"type": "Point", "coordinates": [1.0, 1.0]});
+        let crossing = json!({"type": "LineString", "coordinates": [[1.0, 1.0], [3.0, 1.0]]});
+        assert!(relates(&square, &inner, SpatialRelation::Contains).unwrap());
+        assert!(relates(&inner, &square, SpatialRelation::Within).unwrap());
+        assert!(!relates(&square, &crossing, SpatialRelation::Contains).unwrap());
+        assert!(relates(&crossing, &square, SpatialRelation::Intersects).unwrap());
+        assert!(relates(&square, &json!({"type": "Point"}), SpatialRelation::Intersects).is_err());
+
+        assert_eq!(point(&inner).unwrap(), (1.0, 1.0));
+        assert!(point(&square).unwrap_err().contains("Polygon"));
+
+        // Inside the polygon, and one degree of longitude east of its edge on the equator
+        assert_eq!(point_distance_meters((1.0, 1.0), &square), Some(0.0));
+        let meters = point_distance_meters((3.0, 0.0), &square).unwrap();
+        assert!((meters - 111_195.0).abs() < 1.0, "{}", meters);
+    }
+}
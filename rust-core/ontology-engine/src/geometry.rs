@@ -0,0 +1,376 @@
+//! Measurements of GeoJSON property values for map display: bounding boxes, centroids and
+//! areas, and the geometry types a value holds. `GeometryOutput` shrinks values for query
+//! results by simplifying, rounding or replacing them with their bounding box.
+//!
+//! Values are GeoJSON text in WGS84 longitude/latitude, as stored (see `geo`). A value can be
+//! a geometry, a feature or a feature collection; collections are measured as a whole.
+
+use crate::property::PropertyValue;
+use ::geo::line_intersection::line_intersection;
+use ::geo::{BoundingRect, Centroid, ChamberlainDuquetteArea, Coord, LineString, Polygon, Simplify};
+use serde_json::Value;
+
+/// The geometry types of GeoJSON
+pub const GEOMETRY_TYPES: [&str; 7] = [
+    "Point",
+    "MultiPoint",
+    "LineString",
+    "MultiLineString",
+    "Polygon",
+    "MultiPolygon",
+    "GeometryCollection",
+];
+
+/// `[min longitude, min latitude, max longitude, max latitude]` of every position
+pub fn bounding_box(geojson: &str) -> Result<[f64; 4], String> {
+    let rect = crate::geo::geometry(&parse(geojson)?)?
+        .bounding_rect()
+        .ok_or_else(|| "geometry has no positions".to_string())?;
+    Ok([rect.min().x, rect.min().y, rect.max().x, rect.max().y])
+}
+
+/// `[longitude, latitude]` of the centroid, weighted by the highest dimension present: the
+/// area centroid when there are polygons, the length centroid when there are only lines
+pub fn centroid(geojson: &str) -> Result<[f64; 2], String> {
+    let point = crate::geo::geometry(&parse(geojson)?)?
+        .centroid()
+        .ok_or_else(|| "geometry has no positions".to_string())?;
+    Ok([point.x(), point.y()])
+}
+
+/// Area in square meters of the polygons, on a sphere of the WGS84 equatorial radius; points
+/// and lines have none
+pub fn area_sq_meters(geojson: &str) -> Result<f64, String> {
+    Ok(crate::geo::geometry(&parse(geojson)?)?.chamberlain_duquette_unsigned_area())
+}
+
+/// The type of each top-level geometry: a geometry's own type, a feature's geometry type and
+/// the geometry type of every feature in a collection. A geometry collection is its own type.
+pub fn geometry_types(geojson: &str) -> Result<Vec<String>, String> {
+    fn collect(value: &Value, types: &mut Vec<String>) -> Result<(), String> {
+        match value.get("type").and_then(Value::as_str) {
+            Some("FeatureCollection") => value
+                .get("features")
+                .and_then(Value::as_array)
+                .ok_or_else(|| "FeatureCollection has no features".to_string())?
+                .iter()
+                .try_for_each(|feature| collect(feature, types)),
+            Some("Feature") => match value.get("geometry") {
+                Some(Value::Null) | None => Ok(()),
+                Some(geometry) => collect(geometry, types),
+            },
+            Some(geometry_type) if GEOMETRY_TYPES.contains(&geometry_type) => {
+                types.push(geometry_type.to_string());
+                Ok(())
+            }
+            Some(other) => Err(format!("unknown GeoJSON type '{}'", other)),
+            None => Err("GeoJSON object has no type".to_string()),
+        }
+    }
+    let mut types = Vec::new();
+    collect(&parse(geojson)?, &mut types)?;
+    Ok(types)
+}
+
+/// How GeoJSON property values are returned in query results
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct GeometryOutput {
+    /// Douglas–Peucker tolerance in degrees; see `simplify`
+    pub simplify_tolerance: Option<f64>,
+    /// Decimal places coordinates are rounded to, after any simplification
+    pub precision: Option<u32>,
+    /// Return `[minLon, minLat, maxLon, maxLat]` instead of the geometry
+    pub omit: bool,
+}
+
+impl GeometryOutput {
+    /// Whether values are returned as stored
+    pub fn is_full(&self) -> bool {
+        self.simplify_tolerance.is_none() && self.precision.is_none() && !self.omit
+    }
+
+    /// Check the tolerance is a non-negative number of degrees
+    pub fn validate(&self) -> Result<(), String> {
+        match self.simplify_tolerance {
+            Some(tolerance) if !tolerance.is_finite() || tolerance < 0.0 => {
+                Err(format!("simplify tolerance must be a non-negative number of degrees, got {}", tolerance))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// A GeoJSON value as requested: its bounding box as an array of doubles, or the
+    /// simplified and rounded GeoJSON. Values that are not valid GeoJSON are returned as they
+    /// are.
+    pub fn apply(&self, geojson: &str) -> PropertyValue {
+        let shaped = if self.omit {
+            bounding_box(geojson).map(|bbox| PropertyValue::Array(bbox.into_iter().map(PropertyValue::Double).collect()))
+        } else {
+            self.shape(geojson).map(PropertyValue::GeoJSON)
+        };
+        shaped.unwrap_or_else(|_| PropertyValue::GeoJSON(geojson.to_string()))
+    }
+
+    fn shape(&self, geojson: &str) -> Result<String, String> {
+        let mut value = parse(geojson)?;
+        if let Some(tolerance) = self.simplify_tolerance {
+            for_each_geometry_mut(&mut value, &mut |geometry_type, coordinates| {
+                simplify_coordinates(geometry_type, coordinates, tolerance)
+            })?;
+        }
+        if let Some(decimals) = self.precision {
+            let factor = 10f64.powi(decimals.min(15) as i32);
+            for_each_geometry_mut(&mut value, &mut |_, coordinates| {
+                round_numbers(coordinates, factor);
+                Ok(())
+            })?;
+        }
+        Ok(value.to_string())
+    }
+}
+
+/// Simplify the lines and polygon rings of a GeoJSON value with Douglas–Peucker, dropping
+/// vertices within `tolerance` degrees of the simplified shape. Feature properties and
+/// points are kept. Rings keep at least four positions, and a ring that simplification
+/// would make self-intersecting is kept as it was, so simple polygons stay valid.
+pub fn simplify(geojson: &str, tolerance: f64) -> Result<String, String> {
+    GeometryOutput { simplify_tolerance: Some(tolerance), ..Default::default() }.shape(geojson)
+}
+
+/// Call `visit` with the type and `coordinates` of every geometry in a GeoJSON value,
+/// descending into features, feature collections and geometry collections
+fn for_each_geometry_mut(
+    value: &mut Value,
+    visit: &mut dyn FnMut(&str, &mut Value) -> Result<(), String>,
+) -> Result<(), String> {
+    let geometry_type = value.get("type").and_then(Value::as_str).map(str::to_string);
+    let children = match geometry_type.as_deref() {
+        Some("FeatureCollection") => "features",
+        Some("Feature") => "geometry",
+        Some("GeometryCollection") => "geometries",
+        Some(geometry_type) if GEOMETRY_TYPES.contains(&geometry_type) => {
+            let coordinates = value
+                .get_mut("coordinates")
+                .ok_or_else(|| format!("{} has no coordinates", geometry_type))?;
+            return visit(geometry_type, coordinates);
+        }
+        Some(other) => return Err(format!("unknown GeoJSON type '{}'", other)),
+        None => return Err("GeoJSON object has no type".to_string()),
+    };
+    match value.get_mut(children) {
+        Some(Value::Array(items)) => items.iter_mut().try_for_each(|item| for_each_geometry_mut(item, visit)),
+        Some(Value::Null) | None if children == "geometry" => Ok(()),
+        Some(child @ Value::Object(_)) => for_each_geometry_mut(child, visit),
+        _ => Err(format!("GeoJSON {} has no {}", geometry_type.unwrap_or_default(), children)),
+    }
+}
+
+fn simplify_coordinates(geometry_type: &str, coordinates: &mut Value, tolerance: f64) -> Result<(), String> {
+    let each = |coordinates: &mut Value, simplify: &dyn Fn(&mut Value) -> Result<(), String>| match coordinates {
+        Value::Array(items) => items.iter_mut().try_for_each(simplify),
+        _ => Err("coordinates must be an array".to_string()),
+    };
+    let line = |coordinates: &mut Value| -> Result<(), String> {
+        *coordinates = line_json(&line_string(coordinates)?.simplify(&tolerance));
+        Ok(())
+    };
+    let ring = |coordinates: &mut Value| -> Result<(), String> {
+        let original = line_string(coordinates)?;
+        let simplified = Polygon::new(original, vec![]).simplify(&tolerance).exterior().clone();
+        if is_simple_ring(&simplified) {
+            *coordinates = line_json(&simplified);
+        }
+        Ok(())
+    };
+    let polygon = |coordinates: &mut Value| each(coordinates, &ring);
+    match geometry_type {
+        "LineString" => line(coordinates),
+        "MultiLineString" => each(coordinates, &line),
+        "Polygon" => polygon(coordinates),
+        "MultiPolygon" => each(coordinates, &polygon),
+        _ => Ok(()),
+    }
+}
+
+fn line_string(coordinates: &Value) -> Result<LineString<f64>, String> {
+    let positions = coordinates.as_array().ok_or_else(|| "coordinates must be an array".to_string())?;
+    positions
+        .iter()
+        .map(|position| match (position.get(0).and_then(Value::as_f64), position.get(1).and_then(Value::as_f64)) {
+            (Some(x), Some(y)) => Ok(Coord { x, y }),
+            _ => Err(format!("position {} needs two numbers", position)),
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .map(LineString::new)
+}
+
+fn line_json(line: &LineString<f64>) -> Value {
+    Value::Array(line.coords().map(|c| serde_json::json!([c.x, c.y])).collect())
+}
+
+/// Whether no two non-adjacent edges of a closed ring touch
+fn is_simple_ring(ring: &LineString<f64>) -> bool {
+    let edges: Vec<_> = ring.lines().collect();
+    let count = edges.len();
+    (0..count).all(|i| {
+        ((i + 2)..count)
+            // The first and last edges meet at the closing position
+            .filter(|&j| !(i == 0 && j == count - 1))
+            .all(|j| line_intersection(edges[i], edges[j]).is_none())
+    })
+}
+
+fn round_numbers(value: &mut Value, factor: f64) {
+    match value {
+        Value::Array(items) => items.iter_mut().for_each(|item| round_numbers(item, factor)),
+        Value::Number(number) => {
+            if let Some(rounded) = number.as_f64().and_then(|n| serde_json::Number::from_f64((n * factor).round() / factor)) {
+                *number = rounded;
+            }
+        }
+        _ => {}
+    }
+}
+
+fn parse(geojson: &str) -> Result<Value, String> {
+    serde_json::from_str(geojson).map_err(|e| format!("invalid GeoJSON: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn square(lon: f64, lat: f64, size: f64) -> Value {
+        json!({"type": "Polygon", "coordinates": [[
+            [lon, lat], [lon + size, lat], [lon + size, lat + size], [lon, lat + size], [lon, lat]
+        ]]})
+    }
+
+    #[test]
+    fn test_measure_feature_collection() {
+        let collection = json!({
+            "type": "FeatureCollection",
+            "features": [
+                {"type": "Feature", "properties": {}, "geometry": square(0.0, 0.0, 1.0)},
+                {"type": "Feature", "properties": {}, "geometry": square(2.0, 0.0, 1.0)},
+                {"type": "Feature", "properties": {}, "geometry": null},
+            ]
+        })
+        .to_string();
+        assert_eq!(bounding_box(&collection).unwrap(), [0.0, 0.0, 3.0, 1.0]);
+        let [lon, lat] = centroid(&collection).unwrap();
+        assert!((lon - 1.5).abs() < 1e-9 && (lat - 0.5).abs() < 1e-9, "{} {}", lon, lat);
+        assert_eq!(geometry_types(&collection).unwrap(), vec!["Polygon", "Polygon"]);
+
+        // One degree square at the equator is about 111 km on a side
+        let area = area_sq_meters(&collection).unwrap();
+        assert!((area / 2.0 - 12_364_000_000.0).abs() < 50_000_000.0, "{}", area);
+    }
+
+    #[test]
+    fn test_measure_nested_geometries() {
+        let nested = json!({
+            "type": "GeometryCollection",
+            "geometries": [
+                {"type": "Point", "coordinates": [-5.0, 10.0]},
+                {"type": "GeometryCollection", "geometries": [
+                    {"type": "LineString", "coordinates": [[0.0, 0.0], [4.0, -2.0]]},
+                ]},
+            ]
+        })
+        .to_string();
+        assert_eq!(bounding_box(&nested).unwrap(), [-5.0, -2.0, 4.0, 10.0]);
+        // Lines outweigh points, and enclose no area
+        assert_eq!(centroid(&nested).unwrap(), [2.0, -1.0]);
+        assert_eq!(area_sq_meters(&nested).unwrap(), 0.0);
+        assert_eq!(geometry_types(&nested).unwrap(), vec!["GeometryCollection"]);
+
+        let empty = json!({"type": "GeometryCollection", "geometries": []}).to_string();
+        assert!(bounding_box(&empty).unwrap_err().contains("no positions"));
+        assert!(bounding_box("not json").unwrap_err().contains("invalid GeoJSON"));
+        assert!(geometry_types(r#"{"type": "Circle"}"#).unwrap_err().contains("'Circle'"));
+    }
+
+    /// A polygon approximating a circle of `radius` degrees with `vertices` positions
+    fn circle(lon: f64, lat: f64, radius: f64, vertices: usize) -> Value {
+        let mut ring: Vec<Value> = (0..vertices)
+            .map(|i| {
+                let angle = i as f64 / vertices as f64 * std::f64::consts::TAU;
+                json!([lon + radius * angle.cos(), lat + radius * angle.sin()])
+            })
+            .collect();
+        ring.push(ring[0].clone());
+        json!({"type": "Polygon", "coordinates": [ring]})
+    }
+
+    #[test]
+    fn test_simplify_multipolygons_and_feature_collections() {
+        let tract = |lon: f64| circle(lon, 40.0, 0.05, 2000);
+        let multi = json!({"type": "MultiPolygon", "coordinates": [tract(-75.0)["coordinates"], tract(-74.8)["coordinates"]]});
+        let collection = json!({
+            "type": "FeatureCollection",
+            "features": [
+                {"type": "Feature", "properties": {"geoid": "34001"}, "geometry": multi},
+                {"type": "Feature", "properties": {"geoid": "34003"}, "geometry": {"type": "Point", "coordinates": [-74.5, 40.0]}},
+            ]
+        })
+        .to_string();
+
+        let simplified = simplify(&collection, 0.001).unwrap();
+        assert!(simplified.len() * 10 < collection.len(), "{} -> {} bytes", collection.len(), simplified.len());
+        let parsed = simplified.parse::<geojson::GeoJson>().unwrap();
+        let geojson::GeoJson::FeatureCollection(features) = parsed else { panic!("{}", simplified) };
+        assert_eq!(features.features[0].property("geoid"), Some(&json!("34001")));
+        let Some(geojson::Value::MultiPolygon(polygons)) = features.features[0].geometry.as_ref().map(|g| &g.value) else {
+            panic!("{}", simplified)
+        };
+        for ring in polygons.iter().flatten() {
+            assert!(ring.len() >= 4 && ring.first() == ring.last(), "{:?}", ring);
+        }
+        assert_eq!(features.features[1].geometry.as_ref().unwrap().value, geojson::Value::Point(vec![-74.5, 40.0]));
+
+        // A huge tolerance still leaves every ring a valid polygon
+        let collapsed = simplify(&collection, 10.0).unwrap();
+        assert!(collapsed.parse::<geojson::GeoJson>().is_ok());
+        assert!(simplify(r#"{"type": "Polygon"}"#, 0.001).unwrap_err().contains("no coordinates"));
+    }
+
+    #[test]
+    fn test_simplified_ring_stays_simple() {
+        // A slot cut down from the top reaches below the line from (0, 0) to (10, 0), so
+        // dropping the dip at (5, -0.8) would cross it
+        let slotted = json!({"type": "Polygon", "coordinates": [[
+            [0.0, 0.0], [5.0, -0.8], [10.0, 0.0], [10.0, 10.0], [5.2, 10.0], [5.2, -0.5],
+            [4.8, -0.5], [4.8, 10.0], [0.0, 10.0], [0.0, 0.0]
+        ]]});
+        let ring = line_string(&slotted["coordinates"][0]).unwrap();
+        let unchecked = Polygon::new(ring.clone(), vec![]).simplify(&0.9);
+        assert!(!is_simple_ring(unchecked.exterior()));
+
+        let simplified: Value = serde_json::from_str(&simplify(&slotted.to_string(), 0.9).unwrap()).unwrap();
+        assert_eq!(line_string(&simplified["coordinates"][0]).unwrap(), ring);
+    }
+
+    #[test]
+    fn test_geometry_output() {
+        let tract = circle(-75.0, 40.0, 0.05, 500).to_string();
+        assert!(GeometryOutput::default().is_full());
+
+        let rounded = GeometryOutput { precision: Some(2), ..Default::default() }.apply(&tract);
+        let PropertyValue::GeoJSON(rounded) = rounded else { panic!("{:?}", rounded) };
+        let value: Value = serde_json::from_str(&rounded).unwrap();
+        assert_eq!(value["coordinates"][0][0], json!([-74.95, 40.0]));
+        assert!(rounded.parse::<geojson::GeoJson>().is_ok());
+
+        let omitted = GeometryOutput { omit: true, ..Default::default() }.apply(&tract);
+        let PropertyValue::Array(bbox) = omitted else { panic!("{:?}", omitted) };
+        assert_eq!(bbox.len(), 4);
+        assert_eq!(bbox[0], PropertyValue::Double(-75.05));
+
+        // Values that do not parse come back unchanged
+        let output = GeometryOutput { simplify_tolerance: Some(0.01), ..Default::default() };
+        assert_eq!(output.apply("not json"), PropertyValue::GeoJSON("not json".to_string()));
+        assert!(GeometryOutput { simplify_tolerance: Some(-1.0), ..Default::default() }.validate().is_err());
+    }
+}
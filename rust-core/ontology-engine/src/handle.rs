@@ -0,0 +1,440 @@
+use crate::meta_model::{ObjectType, OntologyConfig, OntologyDef, OntologyRuntime};
+use crate::property::Property;
+use arc_swap::ArcSwap;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast;
+
+/// Capacity of the change broadcast; slow subscribers that lag behind see `RecvError::Lagged`
+/// and should treat it like `Reloaded`
+const CHANGE_CHANNEL_CAPACITY: usize = 256;
+
+/// A change applied to the live ontology
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OntologyChange {
+    TypeAdded { object_type: String },
+    TypeRemoved { object_type: String },
+    /// A type's own settings changed (e.g. its backing datasource); property changes are
+    /// reported separately
+    TypeChanged { object_type: String },
+    PropertyAdded { object_type: String, property: String },
+    PropertyRemoved { object_type: String, property: String },
+    /// A property's definition changed other than its indexing hint
+    PropertyChanged { object_type: String, property: String },
+    /// A property's indexing hint changed; existing search mappings cannot change in place,
+    /// so the type must be reindexed
+    PropertyIndexingChanged { object_type: String, property: String },
+    LinkTypeChanged { link_type: String },
+    /// The whole ontology was replaced (e.g. hot reload); listeners should resync everything
+    Reloaded { version: u64 },
+}
+
+/// Shared, concurrently readable handle to the live ontology.
+///
+/// Readers take a cheap immutable snapshot with [`OntologyHandle::load`]; writers go through
+/// [`OntologyHandle::mutate`], which clones the definition, applies the change, re-validates
+/// the whole ontology and swaps the snapshot atomically. In-flight readers keep the snapshot
+/// they loaded.
+#[derive(Clone)]
+pub struct OntologyHandle {
+    inner: Arc<HandleInner>,
+}
+
+struct HandleInner {
+    current: ArcSwap<OntologyRuntime>,
+    version: AtomicU64,
+    // Serializes writers so concurrent mutations never drop each other's changes
+    writer: Mutex<()>,
+    changes: broadcast::Sender<OntologyChange>,
+}
+
+impl OntologyHandle {
+    pub fn new(ontology: OntologyRuntime) -> Self {
+        let (changes, _) = broadcast::channel(CHANGE_CHANNEL_CAPACITY);
+        Self {
+            inner: Arc::new(HandleInner {
+                current: ArcSwap::from_pointee(ontology),
+                version: AtomicU64::new(1),
+                writer: Mutex::new(()),
+                changes,
+            }),
+        }
+    }
+
+    /// Current ontology snapshot
+    pub fn load(&self) -> Arc<OntologyRuntime> {
+        self.inner.current.load_full()
+    }
+
+    /// Version of the current snapshot, incremented on every successful swap
+    pub fn version(&self) -> u64 {
+        self.inner.version.load(Ordering::SeqCst)
+    }
+
+    /// Subscribe to changes applied after this call
+    pub fn subscribe_changes(&self) -> broadcast::Receiver<OntologyChange> {
+        self.inner.changes.subscribe()
+    }
+
+    /// Apply a change to a draft copy of the definition and swap it in if the result validates.
+    /// On error the current snapshot stays live and no change is published.
+    pub fn mutate<F>(&self, f: F) -> Result<Vec<OntologyChange>, String>
+    where
+        F: FnOnce(&mut OntologyDef) -> Result<(), String>,
+    {
+        let _writer = self.inner.writer.lock().unwrap_or_else(|e| e.into_inner());
+        let current = self.load();
+
+        let mut draft = current.definition().clone();
+        f(&mut draft)?;
+        let next = OntologyRuntime::from_config(OntologyConfig { ontology: draft })
+            .map_err(|e| e.to_string())?;
+
+        let changes = diff_definitions(current.definition(), next.definition());
+        self.inner.current.store(Arc::new(next));
+        self.inner.version.fetch_add(1, Ordering::SeqCst);
+
+        for change in &changes {
+            // No subscribers is not an error
+            let _ = self.inner.changes.send(change.clone());
+        }
+        Ok(changes)
+    }
+
+    /// Replace the whole ontology (hot reload) and publish `Reloaded`
+    pub fn replace(&self, ontology: OntologyRuntime) -> u64 {
+        let _writer = self.inner.writer.lock().unwrap_or_else(|e| e.into_inner());
+        self.swap(ontology)
+    }
+
+    /// Replace the whole ontology if `check` accepts the changes from the current one.
+    /// The check runs under the writer lock, so it sees exactly the changes being applied.
+    /// Returns the new version and the changes; on error the current snapshot stays live.
+    pub fn reload<F>(&self, ontology: OntologyRuntime, check: F) -> Result<(u64, Vec<OntologyChange>), String>
+    where
+        F: FnOnce(&[OntologyChange]) -> Result<(), String>,
+    {
+        let _writer = self.inner.writer.lock().unwrap_or_else(|e| e.into_inner());
+        let changes = diff_definitions(self.load().definition(), ontology.definition());
+        check(&changes)?;
+        Ok((self.swap(ontology), changes))
+    }
+
+    /// Validate a JSON definition and hot reload it, returning the changes from the
+    /// previous ontology
+    pub fn reload_from_json(&self, content: &str) -> Result<Vec<OntologyChange>, String> {
+        let ontology = OntologyRuntime::from_json(content).map_err(|e| e.to_string())?;
+        self.reload(ontology, |_| Ok(())).map(|(_, changes)| changes)
+    }
+
+    // Callers must hold the writer lock
+    fn swap(&self, ontology: OntologyRuntime) -> u64 {
+        self.inner.current.store(Arc::new(ontology));
+        let version = self.inner.version.fetch_add(1, Ordering::SeqCst) + 1;
+        let _ = self.inner.changes.send(OntologyChange::Reloaded { version });
+        version
+    }
+}
+
+impl OntologyChange {
+    /// Whether indexed data must be rebuilt for the change to take effect
+    pub fn requires_reindex(&self) -> bool {
+        matches!(self, OntologyChange::PropertyIndexingChanged { .. })
+    }
+}
+
+impl fmt::Display for OntologyChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OntologyChange::TypeAdded { object_type } => write!(f, "object type '{}' added", object_type),
+            OntologyChange::TypeRemoved { object_type } => write!(f, "object type '{}' removed", object_type),
+            OntologyChange::TypeChanged { object_type } => write!(f, "object type '{}' changed", object_type),
+            OntologyChange::PropertyAdded { object_type, property } => {
+                write!(f, "property '{}.{}' added", object_type, property)
+            }
+            OntologyChange::PropertyRemoved { object_type, property } => {
+                write!(f, "property '{}.{}' removed", object_type, property)
+            }
+            OntologyChange::PropertyChanged { object_type, property } => {
+                write!(f, "property '{}.{}' changed", object_type, property)
+            }
+            OntologyChange::PropertyIndexingChanged { object_type, property } => {
+                write!(f, "indexing of property '{}.{}' changed (reindex required)", object_type, property)
+            }
+            OntologyChange::LinkTypeChanged { link_type } => write!(f, "link type '{}' changed", link_type),
+            OntologyChange::Reloaded { version } => write!(f, "ontology reloaded (version {})", version),
+        }
+    }
+}
+
+impl From<OntologyRuntime> for OntologyHandle {
+    fn from(ontology: OntologyRuntime) -> Self {
+        Self::new(ontology)
+    }
+}
+
+/// Changes needed to go from one definition to the next
+pub fn diff_definitions(old: &OntologyDef, new: &OntologyDef) -> Vec<OntologyChange> {
+    let mut changes = Vec::new();
+
+    let old_types: HashMap<&str, _> = old.object_types.iter().map(|t| (t.id.as_str(), t)).collect();
+    for object_type in &new.object_types {
+        match old_types.get(object_type.id.as_str()) {
+            None => changes.push(OntologyChange::TypeAdded { object_type: object_type.id.clone() }),
+            Some(previous) => {
+                if type_settings(previous) != type_settings(object_type) {
+                    changes.push(OntologyChange::TypeChanged { object_type: object_type.id.clone() });
+                }
+                for property in &object_type.properties {
+                    match previous.get_property(&property.id) {
+                        None => changes.push(OntologyChange::PropertyAdded {
+                            object_type: object_type.id.clone(),
+                            property: property.id.clone(),
+                        }),
+                        Some(old_property) if old_property.indexing_hint() != property.indexing_hint() => {
+                            changes.push(OntologyChange::PropertyIndexingChanged {
+                                object_type: object_type.id.clone(),
+                                property: property.id.clone(),
+                            })
+                        }
+                        Some(old_property) if without_indexing(old_property) != without_indexing(property) => {
+                            changes.push(OntologyChange::PropertyChanged {
+                                object_type: object_type.id.clone(),
+                                property: property.id.clone(),
+                            })
+                        }
+                        Some(_) => {}
+                    }
+                }
+                for property in &previous.properties {
+                    if object_type.get_property(&property.id).is_none() {
+                        changes.push(OntologyChange::PropertyRemoved {
+                            object_type: object_type.id.clone(),
+                            property: property.id.clone(),
+                        });
+                    }
+                }
+            }
+        }
+    }
+    for object_type in &old.object_types {
+        if !new.object_types.iter().any(|t| t.id == object_type.id) {
+            changes.push(OntologyChange::TypeRemoved { object_type: object_type.id.clone() });
+        }
+    }
+
+    // LinkTypeDef has no PartialEq; compare serialized forms
+    let old_links: HashMap<&str, serde_json::Value> = old.link_types.iter()
+        .map(|l| (l.id.as_str(), serde_json::to_value(l).unwrap_or_default()))
+        .collect();
+    let mut new_link_ids = Vec::new();
+    for link_type in &new.link_types {
+        new_link_ids.push(link_type.id.as_str());
+        let serialized = serde_json::to_value(link_type).unwrap_or_default();
+        if old_links.get(link_type.id.as_str()) != Some(&serialized) {
+            changes.push(OntologyChange::LinkTypeChanged { link_type: link_type.id.clone() });
+        }
+    }
+    for link_id in old_links.keys() {
+        if !new_link_ids.contains(link_id) {
+            changes.push(OntologyChange::LinkTypeChanged { link_type: link_id.to_string() });
+        }
+    }
+
+    changes
+}
+
+/// A type's serialized definition without its properties, for comparing its own settings
+fn type_settings(object_type: &ObjectType) -> serde_json::Value {
+    let mut settings = serde_json::to_value(object_type).unwrap_or_default();
+    if let Some(settings) = settings.as_object_mut() {
+        settings.remove("properties");
+    }
+    settings
+}
+
+fn without_indexing(property: &Property) -> Property {
+    Property { indexing: None, ..property.clone() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const YAML: &str = r#"
+ontology:
+  objectTypes:
+    - id: person
+      displayName: Person
+      primaryKey: id
+      properties:
+        - id: id
+          type: string
+  linkTypes: []
+"#;
+
+    fn handle() -> OntologyHandle {
+        OntologyHandle::new(OntologyRuntime::from_yaml(YAML).unwrap())
+    }
+
+    fn property(id: &str) -> Property {
+        serde_json::from_value(serde_json::json!({ "id": id, "type": "string" })).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_mutate_swaps_and_publishes_changes() {
+        let handle = handle();
+        let mut changes = handle.subscribe_changes();
+        let before = handle.load();
+
+        let applied = handle.mutate(|draft| {
+            draft.object_types[0].properties.push(property("email"));
+            let mut company = draft.object_types[0].clone();
+            company.id = "company".to_string();
+            draft.object_types.push(company);
+            Ok(())
+        }).unwrap();
+
+        assert_eq!(applied.len(), 2);
+        assert_eq!(changes.recv().await.unwrap(), OntologyChange::PropertyAdded {
+            object_type: "person".to_string(),
+            property: "email".to_string(),
+        });
+        assert_eq!(changes.recv().await.unwrap(), OntologyChange::TypeAdded {
+            object_type: "company".to_string(),
+        });
+        assert_eq!(handle.version(), 2);
+        assert!(handle.load().get_object_type("company").is_some());
+        // Snapshots taken before the swap are unaffected
+        assert!(before.get_object_type("company").is_none());
+    }
+
+    #[test]
+    fn test_indexing_hint_change_requires_reindex() {
+        let handle = handle();
+        let applied = handle.mutate(|draft| {
+            draft.object_types[0].properties.push(property("bio"));
+            Ok(())
+        }).unwrap();
+        assert!(!applied[0].requires_reindex());
+
+        let applied = handle.mutate(|draft| {
+            draft.object_types[0].properties[1].indexing = Some(crate::property::IndexingHint::NotIndexed);
+            Ok(())
+        }).unwrap();
+        assert_eq!(applied, vec![OntologyChange::PropertyIndexingChanged {
+            object_type: "person".to_string(),
+            property: "bio".to_string(),
+        }]);
+        assert!(applied[0].requires_reindex());
+
+        // Spelling out the default is not a change
+        let applied = handle.mutate(|draft| {
+            draft.object_types[0].properties[0].indexing = Some(crate::property::IndexingHint::Full);
+            Ok(())
+        }).unwrap();
+        assert!(applied.is_empty());
+    }
+
+    #[test]
+    fn test_diff_reports_changed_and_removed_definitions() {
+        let old = handle().load().definition().clone();
+        let mut new = old.clone();
+        new.object_types[0].properties.push(property("email"));
+        let mut changed = new.clone();
+        changed.object_types[0].backing_datasource = Some("people_staging".to_string());
+        changed.object_types[0].properties[1].required = true;
+        assert_eq!(diff_definitions(&new, &changed), vec![
+            OntologyChange::TypeChanged { object_type: "person".to_string() },
+            OntologyChange::PropertyChanged { object_type: "person".to_string(), property: "email".to_string() },
+        ]);
+        assert_eq!(diff_definitions(&new, &old), vec![OntologyChange::PropertyRemoved {
+            object_type: "person".to_string(),
+            property: "email".to_string(),
+        }]);
+        assert_eq!(diff_definitions(&new, &old)[0].to_string(), "property 'person.email' removed");
+    }
+
+    #[test]
+    fn test_failed_validation_keeps_old_snapshot() {
+        let handle = handle();
+        let mut changes = handle.subscribe_changes();
+
+        let result = handle.mutate(|draft| {
+            draft.object_types[0].primary_key = "missing".to_string();
+            Ok(())
+        });
+
+        assert!(result.is_err());
+        assert_eq!(handle.version(), 1);
+        assert_eq!(handle.load().get_object_type("person").unwrap().primary_key, "id");
+        assert!(changes.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_concurrent_readers_see_consistent_snapshots() {
+        let handle = handle();
+        let readers: Vec<_> = (0..4).map(|_| {
+            let handle = handle.clone();
+            std::thread::spawn(move || {
+                for _ in 0..2000 {
+                    let snapshot = handle.load();
+                    let person = snapshot.get_object_type("person").unwrap();
+                    // Every snapshot is a fully validated definition
+                    assert!(person.get_property(&person.primary_key).is_some());
+                    assert_eq!(snapshot.object_types().count(), snapshot.definition().object_types.len());
+                }
+            })
+        }).collect();
+
+        for i in 0..50 {
+            handle.mutate(|draft| {
+                draft.object_types[0].properties.push(property(&format!("p{}", i)));
+                Ok(())
+            }).unwrap();
+        }
+        for reader in readers {
+            reader.join().unwrap();
+        }
+        assert_eq!(handle.version(), 51);
+        assert_eq!(handle.load().get_object_type("person").unwrap().properties.len(), 51);
+    }
+
+    #[tokio::test]
+    async fn test_replace_publishes_reloaded() {
+        let handle = handle();
+        let mut changes = handle.subscribe_changes();
+        let version = handle.replace(OntologyRuntime::from_yaml(YAML).unwrap());
+        assert_eq!(changes.recv().await.unwrap(), OntologyChange::Reloaded { version });
+    }
+
+    #[tokio::test]
+    async fn test_reload_from_json_returns_changes() {
+        let handle = handle();
+        let mut changes = handle.subscribe_changes();
+        let mut next = handle.load().definition().clone();
+        next.object_types[0].properties.push(property("email"));
+        let json = serde_json::to_string(&OntologyConfig { ontology: next }).unwrap();
+
+        let applied = handle.reload_from_json(&json).unwrap();
+        assert_eq!(applied, vec![OntologyChange::PropertyAdded {
+            object_type: "person".to_string(),
+            property: "email".to_string(),
+        }]);
+        assert_eq!(changes.recv().await.unwrap(), OntologyChange::Reloaded { version: 2 });
+
+        // Invalid definitions and rejected changes leave the current snapshot live
+        assert!(handle.reload_from_json("{\"ontology\": {\"objectTypes\": [").is_err());
+        let rejected = handle.reload(OntologyRuntime::from_yaml(YAML).unwrap(), |changes| {
+            match changes.first() {
+                Some(change) => Err(format!("rejected: {}", change)),
+                None => Ok(()),
+            }
+        });
+        assert_eq!(rejected.unwrap_err(), "rejected: property 'person.email' removed");
+        assert_eq!(handle.version(), 2);
+        assert!(handle.load().get_object_type("person").unwrap().get_property("email").is_some());
+    }
+}
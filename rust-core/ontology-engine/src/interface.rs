@@ -1,68 +1,160 @@
-use crate::meta_model::{ObjectType, InterfaceDef};
+use crate::load_error::{DefinitionKind, OntologyLoadError};
+use crate::meta_model::{ObjectType, InterfaceDef, LinkTypeDef};
 use crate::property::{Property, PropertyType};
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+/// How closely an implementer's property types must match the interface's
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TypeCompatibility {
+    /// Types must be identical as written
+    Strict,
+    /// Aliases match (`int`/`integer`, `bool`/`boolean`, `timestamp`/`datetime`, ...) and
+    /// integers satisfy doubles
+    #[default]
+    Compatible,
+}
+
+/// One way an object type falls short of an interface it implements
+#[derive(Debug, Clone, PartialEq)]
+pub struct InterfaceViolation {
+    pub object_type: String,
+    pub interface: String,
+    pub kind: InterfaceViolationKind,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum InterfaceViolationKind {
+    /// No property, under its own or a mapped name, implements the interface property
+    MissingProperty { property: String },
+    TypeMismatch {
+        property: String,
+        actual: PropertyType,
+        required: PropertyType,
+    },
+    /// The interface requires the property but the object type does not
+    NotRequired { property: String },
+    /// The object type is neither end of a link type the interface requires
+    MissingLinkType { link_type: String },
+}
+
+impl std::fmt::Display for InterfaceViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.kind {
+            InterfaceViolationKind::MissingProperty { property } => write!(
+                f,
+                "Object type '{}' does not implement required property '{}' from interface '{}'",
+                self.object_type, property, self.interface
+            ),
+            InterfaceViolationKind::TypeMismatch { property, actual, required } => write!(
+                f,
+                "Object type '{}' property '{}' has type {:?} which is not compatible with interface '{}' requirement {:?}",
+                self.object_type, property, actual, self.interface, required
+            ),
+            InterfaceViolationKind::NotRequired { property } => write!(
+                f,
+                "Object type '{}' property '{}' must be required to implement interface '{}'",
+                self.object_type, property, self.interface
+            ),
+            InterfaceViolationKind::MissingLinkType { link_type } => write!(
+                f,
+                "Object type '{}' is not an end of link type '{}' required by interface '{}'",
+                self.object_type, link_type, self.interface
+            ),
+        }
+    }
+}
+
 /// Interface validator - validates that object types satisfy interface contracts
 pub struct InterfaceValidator;
 
 impl InterfaceValidator {
-    /// Validate that an object type implements an interface
+    /// Validate that an object type implements an interface, returning every violation
     pub fn validate_implements(
         object_type: &ObjectType,
         interface: &InterfaceDef,
-    ) -> Result<(), String> {
+        link_types: &[LinkTypeDef],
+        compatibility: TypeCompatibility,
+    ) -> Result<(), Vec<InterfaceViolation>> {
+        let violation = |kind: InterfaceViolationKind| InterfaceViolation {
+            object_type: object_type.id.clone(),
+            interface: interface.id.clone(),
+            kind,
+        };
+        let mut violations = Vec::new();
+        
         // Check that all required properties exist in the object type
         for interface_prop in &interface.properties {
-            let obj_prop = object_type.get_property(&interface_prop.id)
-                .ok_or_else(|| format!(
-                    "Object type '{}' does not implement required property '{}' from interface '{}'",
-                    object_type.id, interface_prop.id, interface.id
-                ))?;
+            // The implementer may map the interface property to a differently named local one
+            let Some(obj_prop) = object_type.interface_property(&interface.id, &interface_prop.id) else {
+                violations.push(violation(InterfaceViolationKind::MissingProperty {
+                    property: object_type.local_property_id(&interface.id, &interface_prop.id).to_string(),
+                }));
+                continue;
+            };
             
-            // Check property type compatibility (allowing covariant types)
-            if !Self::is_type_compatible(&obj_prop.property_type, &interface_prop.property_type) {
-                return Err(format!(
-                    "Object type '{}' property '{}' has type {:?} which is not compatible with interface '{}' requirement {:?}",
-                    object_type.id, interface_prop.id, obj_prop.property_type, interface.id, interface_prop.property_type
-                ));
+            if !Self::is_type_compatible(&obj_prop.property_type, &interface_prop.property_type, compatibility) {
+                violations.push(violation(InterfaceViolationKind::TypeMismatch {
+                    property: interface_prop.id.clone(),
+                    actual: obj_prop.property_type.clone(),
+                    required: interface_prop.property_type.clone(),
+                }));
             }
             
             // Check that required properties are also required in the object type
             if interface_prop.required && !obj_prop.required {
-                return Err(format!(
-                    "Object type '{}' property '{}' must be required to implement interface '{}'",
-                    object_type.id, interface_prop.id, interface.id
-                ));
+                violations.push(violation(InterfaceViolationKind::NotRequired {
+                    property: interface_prop.id.clone(),
+                }));
             }
         }
         
-        // Check that all required link types are supported
-        // This is a simplified check - in a full implementation, we'd verify
-        // that the object type can participate in these link types
-        for _link_type_id in &interface.required_link_types {
-            // For now, we just check that the link type exists somewhere in the ontology
-            // A more complete check would verify the object type is source or target
-            // This would require access to the full ontology, which we handle at a higher level
+        // The object type must be able to take part in every required link type
+        for link_type_id in &interface.required_link_types {
+            let participates = link_types
+                .iter()
+                .any(|l| &l.id == link_type_id && (l.source == object_type.id || l.target == object_type.id));
+            if !participates {
+                violations.push(violation(InterfaceViolationKind::MissingLinkType {
+                    link_type: link_type_id.clone(),
+                }));
+            }
         }
         
-        Ok(())
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(violations)
+        }
     }
     
-    /// Check if two property types are compatible (covariant checking)
-    fn is_type_compatible(actual: &PropertyType, required: &PropertyType) -> bool {
+    /// Whether a property of type `actual` can stand in for one of type `required`
+    pub fn is_type_compatible(actual: &PropertyType, required: &PropertyType, compatibility: TypeCompatibility) -> bool {
         // Exact match
         if actual == required {
             return true;
         }
+        if compatibility == TypeCompatibility::Strict {
+            return false;
+        }
         
-        // Handle type aliases (e.g., int vs integer, float vs double)
-        match (actual.as_simple(), required.as_simple()) {
-            (Some(act), Some(req)) => act == req,
-            _ => false,
+        match (actual, required) {
+            (PropertyType::Array { element_type: actual }, PropertyType::Array { element_type: required }) => {
+                Self::is_type_compatible(actual, required, compatibility)
+            }
+            // Handle type aliases (e.g., int vs integer, float vs double), widening integers
+            _ => match (actual.as_simple(), required.as_simple()) {
+                (Some(PropertyType::Integer), Some(PropertyType::Double)) => true,
+                (Some(act), Some(req)) => act == req,
+                _ => false,
+            },
         }
     }
     
-    /// Get all object types that implement a given interface
+    /// Get all object types that implement a given interface. Runtime object types list
+    /// inherited interfaces in `implements`, so implementers of an interface extending this
+    /// one are included.
     pub fn get_implementers<'a>(
         interface_id: &str,
         object_types: impl Iterator<Item = &'a ObjectType>,
@@ -71,6 +163,135 @@ impl InterfaceValidator {
             .filter(|obj_type| obj_type.implements.contains(&interface_id.to_string()))
             .collect()
     }
+    
+    /// Resolve `extends`: every interface gains the properties and required link types of
+    /// the interfaces it inherits from, directly or transitively. Unknown parents, cycles
+    /// and properties inherited with incompatible types are reported; interfaces involved
+    /// are flattened as far as possible so the remaining checks can still run.
+    pub fn flatten(
+        interfaces: &[InterfaceDef],
+        compatibility: TypeCompatibility,
+    ) -> (HashMap<String, InterfaceDef>, Vec<OntologyLoadError>) {
+        let mut flattener = Flattener {
+            declared: interfaces.iter().map(|i| (i.id.as_str(), i)).collect(),
+            compatibility,
+            resolving: Vec::new(),
+            flattened: HashMap::new(),
+            origins: HashMap::new(),
+            errors: Vec::new(),
+        };
+        for interface in interfaces {
+            flattener.resolve(&interface.id);
+        }
+        (flattener.flattened, flattener.errors)
+    }
+    
+    /// Every known interface `interface_id` inherits from, nearest first
+    pub fn ancestors(interface_id: &str, interfaces: &HashMap<String, InterfaceDef>) -> Vec<String> {
+        let mut ancestors: Vec<String> = Vec::new();
+        let mut pending = vec![interface_id];
+        while let Some(id) = pending.pop() {
+            let Some(interface) = interfaces.get(id) else { continue };
+            for parent in &interface.extends {
+                let known = interfaces.contains_key(parent);
+                if known && parent != interface_id && !ancestors.contains(parent) {
+                    ancestors.push(parent.clone());
+                    pending.insert(0, parent);
+                }
+            }
+        }
+        ancestors
+    }
+}
+
+/// Depth-first resolution of `extends`, tracking where each inherited property came from
+struct Flattener<'a> {
+    declared: HashMap<&'a str, &'a InterfaceDef>,
+    compatibility: TypeCompatibility,
+    /// Interfaces being resolved, outermost first; meeting one again is a cycle
+    resolving: Vec<String>,
+    flattened: HashMap<String, InterfaceDef>,
+    /// Interface ID -> property ID -> interface declaring the property
+    origins: HashMap<String, HashMap<String, String>>,
+    errors: Vec<OntologyLoadError>,
+}
+
+impl Flattener<'_> {
+    fn resolve(&mut self, id: &str) {
+        if self.flattened.contains_key(id) {
+            return;
+        }
+        let Some(&declared) = self.declared.get(id) else { return };
+        self.resolving.push(id.to_string());
+        
+        let mut interface = declared.clone();
+        let mut origins: HashMap<String, String> = declared
+            .properties
+            .iter()
+            .map(|p| (p.id.clone(), id.to_string()))
+            .collect();
+        for parent_id in &declared.extends {
+            if !self.declared.contains_key(parent_id.as_str()) {
+                self.errors.push(OntologyLoadError::UnknownReference {
+                    kind: DefinitionKind::Interface,
+                    id: parent_id.clone(),
+                    referenced_by: format!("Interface '{}' extends", id),
+                });
+                continue;
+            }
+            if let Some(start) = self.resolving.iter().position(|r| r == parent_id) {
+                let mut cycle = self.resolving[start..].to_vec();
+                cycle.push(parent_id.clone());
+                self.errors.push(OntologyLoadError::InvalidDefinition {
+                    kind: DefinitionKind::Interface,
+                    id: id.to_string(),
+                    message: format!("Interface inheritance cycle: {}", cycle.join(" -> ")),
+                });
+                continue;
+            }
+            self.resolve(parent_id);
+            let parent = self.flattened[parent_id.as_str()].clone();
+            let parent_origins = self.origins[parent_id.as_str()].clone();
+            
+            for inherited in &parent.properties {
+                let inherited_from = &parent_origins[&inherited.id];
+                let Some(existing) = interface.properties.iter_mut().find(|p| p.id == inherited.id) else {
+                    interface.properties.push(inherited.clone());
+                    origins.insert(inherited.id.clone(), inherited_from.clone());
+                    continue;
+                };
+                let existing_from = &origins[&inherited.id];
+                // The same declaration reached through two parents
+                if existing_from == inherited_from {
+                    continue;
+                }
+                let (a, b) = (&existing.property_type, &inherited.property_type);
+                let compatible = InterfaceValidator::is_type_compatible(a, b, self.compatibility)
+                    || InterfaceValidator::is_type_compatible(b, a, self.compatibility);
+                if !compatible {
+                    self.errors.push(OntologyLoadError::InvalidDefinition {
+                        kind: DefinitionKind::Interface,
+                        id: id.to_string(),
+                        message: format!(
+                            "Interface '{}' gets property '{}' from both '{}' ({:?}) and '{}' ({:?}) with incompatible types",
+                            id, inherited.id, existing_from, a, inherited_from, b
+                        ),
+                    });
+                    continue;
+                }
+                existing.required |= inherited.required;
+            }
+            for link_type in &parent.required_link_types {
+                if !interface.required_link_types.contains(link_type) {
+                    interface.required_link_types.push(link_type.clone());
+                }
+            }
+        }
+        
+        self.resolving.pop();
+        self.origins.insert(id.to_string(), origins);
+        self.flattened.insert(id.to_string(), interface);
+    }
 }
 
 #[cfg(test)]
@@ -82,10 +303,12 @@ mod tests {
         InterfaceDef {
             id: "Location".to_string(),
             display_name: "Location".to_string(),
+            display_names: HashMap::new(),
             properties: vec![
                 Property {
                     id: "latitude".to_string(),
                     display_name: None,
+                    display_names: HashMap::new(),
                     property_type: PropertyType::Double,
                     required: true,
                     default: None,
@@ -98,10 +321,16 @@ mod tests {
                     pii: false,
                     deprecated: None,
                     statistics: None,
-                    model_binding: None,                },
+                    model_binding: None,
+                    display_order: None,
+                    indexing: None,
+                    reference_target: None,
+                    geo: None,
+                    custom_validator: None,                },
                 Property {
                     id: "longitude".to_string(),
                     display_name: None,
+                    display_names: HashMap::new(),
                     property_type: PropertyType::Double,
                     required: true,
                     default: None,
@@ -115,9 +344,15 @@ mod tests {
                     deprecated: None,
                     statistics: None,
                     model_binding: None,
+                    display_order: None,
+                    indexing: None,
+                    reference_target: None,
+                    geo: None,
+                    custom_validator: None,
                 },
             ],
             required_link_types: Vec::new(),
+            extends: Vec::new(),
         }
     }
     
@@ -125,11 +360,13 @@ mod tests {
         ObjectType {
             id: "office".to_string(),
             display_name: "Office".to_string(),
+            display_names: HashMap::new(),
             primary_key: "id".to_string(),
             properties: vec![
                 Property {
                     id: "id".to_string(),
                     display_name: None,
+                    display_names: HashMap::new(),
                     property_type: PropertyType::String,
                     required: true,
                     default: None,
@@ -143,10 +380,16 @@ mod tests {
                     deprecated: None,
                     statistics: None,
                     model_binding: None,
+                    display_order: None,
+                    indexing: None,
+                    reference_target: None,
+                    geo: None,
+                    custom_validator: None,
                 },
                 Property {
                     id: "latitude".to_string(),
                     display_name: None,
+                    display_names: HashMap::new(),
                     property_type: PropertyType::Double,
                     required: true,
                     default: None,
@@ -160,10 +403,16 @@ mod tests {
                     deprecated: None,
                     statistics: None,
                     model_binding: None,
+                    display_order: None,
+                    indexing: None,
+                    reference_target: None,
+                    geo: None,
+                    custom_validator: None,
                 },
                 Property {
                     id: "longitude".to_string(),
                     display_name: None,
+                    display_names: HashMap::new(),
                     property_type: PropertyType::Double,
                     required: true,
                     default: None,
@@ -177,12 +426,24 @@ mod tests {
                     deprecated: None,
                     statistics: None,
                     model_binding: None,
+                    display_order: None,
+                    indexing: None,
+                    reference_target: None,
+                    geo: None,
+                    custom_validator: None,
                 },
             ],
             backing_datasource: None,
             title_key: Some("id".to_string()),
             implements: vec!["Location".to_string()],
+            interface_mappings: HashMap::new(),
             schema_evolution: None,
+            default_sort: None,
+            computed_properties: Vec::new(),
+            dedup_rules: None,
+            archival_policy: None,
+            strict_properties: false,
+            property_groups: Vec::new(),
         }
     }
     
@@ -191,7 +452,7 @@ mod tests {
         let interface = create_test_interface();
         let object_type = create_implementing_object_type();
         
-        assert!(InterfaceValidator::validate_implements(&object_type, &interface).is_ok());
+        assert!(InterfaceValidator::validate_implements(&object_type, &interface, &[], TypeCompatibility::Strict).is_ok());
     }
     
     #[test]
@@ -200,7 +461,7 @@ mod tests {
         let mut object_type = create_implementing_object_type();
         object_type.properties.pop(); // Remove longitude
         
-        assert!(InterfaceValidator::validate_implements(&object_type, &interface).is_err());
+        assert!(InterfaceValidator::validate_implements(&object_type, &interface, &[], TypeCompatibility::Strict).is_err());
     }
     
     #[test]
@@ -215,5 +476,257 @@ mod tests {
         
         assert_eq!(implementers.len(), 2);
     }
+    
+    #[test]
+    fn test_type_compatibility_modes() {
+        let interface = create_test_interface();
+        let mut object_type = create_implementing_object_type();
+        object_type.properties[1].property_type = PropertyType::Integer;
+        object_type.properties[2].property_type = PropertyType::Float;
+        
+        // Integers widen to doubles and float is an alias of double
+        assert!(InterfaceValidator::validate_implements(&object_type, &interface, &[], TypeCompatibility::Compatible).is_ok());
+        
+        let violations = InterfaceValidator::validate_implements(&object_type, &interface, &[], TypeCompatibility::Strict)
+            .unwrap_err();
+        let mismatched: Vec<&str> = violations
+            .iter()
+            .filter_map(|v| match &v.kind {
+                InterfaceViolationKind::TypeMismatch { property, .. } => Some(property.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(mismatched, vec!["latitude", "longitude"]);
+        
+        // Narrowing is never accepted
+        let compatible = |actual: PropertyType, required: PropertyType| {
+            InterfaceValidator::is_type_compatible(&actual, &required, TypeCompatibility::Compatible)
+        };
+        assert!(!compatible(PropertyType::Double, PropertyType::Integer));
+        assert!(compatible(PropertyType::Int, PropertyType::Integer));
+        assert!(compatible(PropertyType::Bool, PropertyType::Boolean));
+        assert!(compatible(PropertyType::Timestamp, PropertyType::DateTime));
+        assert!(!compatible(PropertyType::Int, PropertyType::String));
+        assert!(compatible(
+            PropertyType::Array { element_type: Box::new(PropertyType::Integer) },
+            PropertyType::Array { element_type: Box::new(PropertyType::Double) },
+        ));
+    }
+    
+    #[test]
+    fn test_all_violations_are_reported() {
+        let mut interface = create_test_interface();
+        interface.required_link_types = vec!["located_in".to_string()];
+        let mut object_type = create_implementing_object_type();
+        object_type.properties.pop(); // Remove longitude
+        object_type.properties[1].property_type = PropertyType::String;
+        object_type.properties[1].required = false;
+        
+        let violations = InterfaceValidator::validate_implements(&object_type, &interface, &[], TypeCompatibility::Compatible)
+            .unwrap_err();
+        let kinds: Vec<&InterfaceViolationKind> = violations.iter().map(|v| &v.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                &InterfaceViolationKind::TypeMismatch {
+                    property: "latitude".to_string(),
+                    actual: PropertyType::String,
+                    required: PropertyType::Double,
+                },
+                &InterfaceViolationKind::NotRequired { property: "latitude".to_string() },
+                &InterfaceViolationKind::MissingProperty { property: "longitude".to_string() },
+                &InterfaceViolationKind::MissingLinkType { link_type: "located_in".to_string() },
+            ]
+        );
+        assert_eq!(
+            violations[2].to_string(),
+            "Object type 'office' does not implement required property 'longitude' from interface 'Location'"
+        );
+    }
+    
+    #[test]
+    fn test_load_reports_violations_of_every_object_type() {
+        let yaml = r#"
+ontology:
+  interfaceCompatibility: strict
+  interfaces:
+    - id: Located
+      displayName: Located
+      properties:
+        - id: latitude
+          type: double
+        - id: population
+          type: integer
+      requiredLinkTypes: [inRegion]
+  objectTypes:
+    - id: region
+      displayName: Region
+      primaryKey: id
+      properties:
+        - id: id
+          type: string
+    - id: city
+      displayName: City
+      primaryKey: id
+      implements: [Located]
+      properties:
+        - id: id
+          type: string
+        - id: latitude
+          type: integer
+        - id: population
+          type: int
+    - id: site
+      displayName: Site
+      primaryKey: id
+      implements: [Located]
+      properties:
+        - id: id
+          type: string
+        - id: latitude
+          type: integer
+  linkTypes:
+    - id: inRegion
+      source: city
+      target: region
+"#;
+        let errors = crate::meta_model::OntologyRuntime::from_yaml(yaml).err().unwrap();
+        let messages: Vec<String> = errors.iter().map(|e| e.to_string()).collect();
+        assert_eq!(messages.len(), 4, "{:#?}", messages);
+        assert!(messages.iter().any(|m| m.contains("'city' property 'latitude' has type Integer")));
+        assert!(messages.iter().any(|m| m.contains("'site' property 'latitude' has type Integer")));
+        assert!(messages.iter().any(|m| m.contains("'site' does not implement required property 'population'")));
+        assert!(messages.iter().any(|m| m.contains("'site' is not an end of link type 'inRegion'")));
+        
+        // The same definitions load once aliases and widening are accepted
+        assert!(crate::meta_model::OntologyRuntime::from_yaml(&yaml.replace("strict", "compatible"))
+            .err()
+            .unwrap()
+            .iter()
+            .all(|e| e.to_string().contains("'site'")));
+    }
+    
+    const INHERITANCE: &str = r#"
+ontology:
+  interfaces:
+    - id: Identifiable
+      displayName: Identifiable
+      properties:
+        - id: name
+          type: string
+          required: true
+    - id: Geo
+      displayName: Geo
+      extends: [Identifiable]
+      properties:
+        - id: latitude
+          type: double
+      requiredLinkTypes: [inRegion]
+    - id: GeoEntity
+      displayName: Geo Entity
+      extends: [Geo, Identifiable]
+      properties:
+        - id: elevation
+          type: double
+  objectTypes:
+    - id: region
+      displayName: Region
+      primaryKey: id
+      properties:
+        - id: id
+          type: string
+    - id: city
+      displayName: City
+      primaryKey: id
+      implements: [GeoEntity]
+      interfaceMappings:
+        GeoEntity:
+          name: city_name
+      properties:
+        - id: id
+          type: string
+        - id: city_name
+          type: string
+          required: true
+        - id: latitude
+          type: double
+        - id: elevation
+          type: integer
+  linkTypes:
+    - id: inRegion
+      source: city
+      target: region
+"#;
+    
+    #[test]
+    fn test_extends_flattens_inherited_contracts() {
+        let ontology = crate::meta_model::OntologyRuntime::from_yaml(INHERITANCE).unwrap();
+        
+        // Properties reached through both parents appear once; `extends` stays as declared
+        let geo_entity = ontology.get_interface("GeoEntity").unwrap();
+        let properties: Vec<&str> = geo_entity.properties.iter().map(|p| p.id.as_str()).collect();
+        assert_eq!(properties, vec!["elevation", "latitude", "name"]);
+        assert_eq!(geo_entity.required_link_types, vec!["inRegion".to_string()]);
+        assert_eq!(geo_entity.extends, vec!["Geo".to_string(), "Identifiable".to_string()]);
+        
+        // Implementing the child implements its ancestors, mappings included
+        let city = ontology.get_object_type("city").unwrap();
+        assert_eq!(city.implements, vec!["GeoEntity", "Geo", "Identifiable"]);
+        assert_eq!(city.local_property_id("Identifiable", "name"), "city_name");
+        let implementers = InterfaceValidator::get_implementers("Identifiable", ontology.object_types());
+        assert_eq!(implementers.len(), 1);
+        
+        // Implementers must satisfy the inherited parts of the contract
+        let yaml = INHERITANCE.replace("          required: true\n        - id: latitude", "        - id: latitude");
+        let errors = crate::meta_model::OntologyRuntime::from_yaml(&yaml).err().unwrap();
+        let messages: Vec<String> = errors.iter().map(|e| e.to_string()).collect();
+        assert_eq!(
+            messages,
+            vec!["Object type 'city' property 'name' must be required to implement interface 'GeoEntity'"]
+        );
+    }
+    
+    #[test]
+    fn test_extends_errors() {
+        let interface = |id: &str, extends: &[&str], code: Option<PropertyType>| {
+            let mut interface = create_test_interface();
+            interface.id = id.to_string();
+            interface.extends = extends.iter().map(|e| e.to_string()).collect();
+            interface.properties.truncate(1);
+            match code {
+                Some(property_type) => {
+                    interface.properties[0].id = "code".to_string();
+                    interface.properties[0].property_type = property_type;
+                }
+                None => interface.properties.clear(),
+            }
+            interface
+        };
+        let interfaces = vec![
+            interface("A", &["C"], None),
+            interface("B", &["A"], None),
+            interface("C", &["B", "Missing"], None),
+            interface("Named", &[], Some(PropertyType::String)),
+            interface("Numbered", &[], Some(PropertyType::Integer)),
+            interface("Widened", &[], Some(PropertyType::Double)),
+            interface("Labelled", &["Named", "Numbered"], None),
+            interface("Measured", &["Numbered", "Widened"], None),
+        ];
+        
+        let (flattened, errors) = InterfaceValidator::flatten(&interfaces, TypeCompatibility::Compatible);
+        let messages: Vec<String> = errors.iter().map(|e| e.to_string()).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "Interface inheritance cycle: A -> C -> B -> A",
+                "Interface 'C' extends references unknown interface 'Missing'",
+                "Interface 'Labelled' gets property 'code' from both 'Named' (String) and 'Numbered' (Integer) with incompatible types",
+            ]
+        );
+        // Integers widen to doubles, so these parents agree
+        assert_eq!(flattened["Measured"].properties.len(), 1);
+        
+        let (_, errors) = InterfaceValidator::flatten(&interfaces, TypeCompatibility::Strict);
+        assert!(errors.iter().any(|e| e.to_string().contains("'Measured' gets property 'code'")));
+    }
 }
-
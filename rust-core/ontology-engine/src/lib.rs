@@ -1,6 +1,7 @@
 pub mod meta_model;
 pub mod property;
 pub mod link;
+pub mod link_validator;
 pub mod action;
 pub mod validation;
 pub mod dynamic;
@@ -11,20 +12,53 @@ pub mod interface;
 pub mod function;
 pub mod property_groups;
 pub mod computed_properties;
+pub mod dedup;
 pub mod model_objectives;
 pub mod model_executor;
+pub mod sagemaker;
+pub mod backup;
+pub mod display;
+pub mod handle;
+pub mod load_error;
+pub mod overlay;
+pub mod retention;
+pub mod geo;
+pub mod geometry;
+pub mod outbox;
+pub mod side_effects;
+pub mod form_schema;
+pub mod validators;
+pub mod schema_export;
 
-pub use meta_model::{ObjectType, LinkTypeDef, ActionTypeDef, InterfaceDef, FunctionTypeDef, FunctionLogic, FunctionReturnType, AggregationType, OntologyRuntime as Ontology, OntologyConfig, OntologyDef};
-pub use property::{PropertyType, Property, PropertyValue, PropertyMap};
+pub use meta_model::{ObjectType, DefaultSort, LinkTypeDef, ActionTypeDef, InterfaceDef, FunctionTypeDef, FunctionLogic, FunctionReturnType, AggregationType, OntologyRuntime as Ontology, OntologyConfig, OntologyDef};
+pub use property::{IndexingHint, PropertyType, Property, PropertyValue, PropertyMap, CONTENT_HASH_PROPERTY};
 pub use link::{Link, LinkCardinality, LinkDirection};
-pub use action::{Action, ActionOperation, ActionSideEffect};
-pub use reference::{ReferenceManager, CascadeDeleteBehavior};
-pub use action_executor::{ActionExecutor, ActionExecutionResult};
-pub use crosswalk::{CrosswalkTraverser, CrosswalkLink};
-pub use interface::InterfaceValidator;
-pub use function::{FunctionExecutor, FunctionExecutionResult};
-pub use property_groups::{PropertyGroup, PropertyGroupManager};
-pub use computed_properties::{ComputedProperty, ComputedPropertyEvaluator, ComputedPropertyError, ComputedExpression};
-pub use model_objectives::{ModelObjective, ModelRegistry, ModelBinding, ModelMetrics, ModelType, ModelStatus, ModelPlatform, ModelBindingConfig, ModelComparison};
-pub use model_executor::{ModelExecutor, PythonModelExecutor, RemoteModelExecutor, ModelExecutionOrchestrator, ModelExecutionResult, ModelExecutionError};
-
+pub use link_validator::LinkValidator;
+pub use action::{Action, ActionCondition, ActionOperation, ActionSideEffect, ConditionOperator, ExecutionMode, SideEffectType};
+pub use reference::{ObjectRef, ReferenceManager, CascadeDeleteBehavior};
+pub use action_executor::{ActionExecutor, ActionExecutionResult, DeliveryOutcome, TriggeredSideEffect, default_side_effect};
+pub use crosswalk::{ApportionRounding, CrosswalkTraverser, CrosswalkLink};
+pub use interface::{InterfaceValidator, InterfaceViolation, InterfaceViolationKind, TypeCompatibility};
+pub use function::{FunctionDataSource, FunctionExecutor, FunctionExecutionResult};
+pub use property_groups::{PropertyGroup, PropertyGroupManager, UNGROUPED_GROUP_ID};
+pub use computed_properties::{ComputedProperty, ComputedPropertyEvaluator, CompiledComputedProperty, ComputedPropertyError, ComputedExpression, ConditionalBranch, BranchCondition, BranchResult, ComparisonOperator, Operand, ComputedPropertyMaterializer, Materialization, MATERIALIZED_AT_PROPERTY};
+pub use dedup::{DedupRule, MatchComparator, MatchProperty, Survivorship, find_duplicate_clusters, merge_duplicates};
+pub use model_objectives::{ModelObjective, ModelRegistry, ModelRegistryError, ModelBinding, ModelMetrics, ModelType, ModelStatus, ModelPlatform, ModelBindingConfig, ModelComparison};
+pub use model_executor::{ModelCache, ModelCacheStats, DEFAULT_MODEL_CACHE_ENTRIES, ModelExecutor, PythonModelExecutor, RemoteModelExecutor, ModelExecutionOrchestrator, ModelExecutionResult, ModelExecutionError};
+pub use sagemaker::{SageMakerError, SageMakerRequest, SageMakerRuntime};
+#[cfg(feature = "sagemaker")]
+pub use sagemaker::AwsSageMakerRuntime;
+pub use backup::{BackupComponent, BackupManager, BackupManifest, BackupManifestEntry, BackupError, FileBackupComponent};
+pub use display::{DisplayLocale, DisplayFormatError, format_property_value};
+pub use handle::{OntologyHandle, OntologyChange, diff_definitions};
+pub use load_error::{OntologyLoadError, OntologyLoadErrors, DefinitionKind};
+pub use overlay::{OntologyOverlay, apply_overlays};
+pub use retention::ArchivalPolicy;
+pub use geo::{CoordinateValidation, GeoOptions};
+pub use geometry::GeometryOutput;
+pub use dynamic::UnknownKeys;
+pub use form_schema::{FormSchemaOptions, action_form_schema, object_form_schema};
+pub use schema_export::{JSON_SCHEMA_DIALECT, json_schema_file_name};
+pub use validators::{CustomValidator, ValidatorRegistry, LUHN_VALIDATOR, URL_VALIDATOR};
+pub use side_effects::{SideEffectConfig, SideEffectHandlers, SmtpConfig, WebhookConfig, WebhookEndpoint};
+pub use outbox::{OutboxDispatcher, OutboxError, RetryPolicy, SideEffectOutbox, SideEffectRecord, SideEffectStatus};
@@ -16,6 +16,18 @@ impl Default for LinkCardinality {
     }
 }
 
+impl LinkCardinality {
+    /// Name as written in ontology files (e.g. `ONE_TO_MANY`)
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LinkCardinality::OneToOne => "ONE_TO_ONE",
+            LinkCardinality::OneToMany => "ONE_TO_MANY",
+            LinkCardinality::ManyToOne => "MANY_TO_ONE",
+            LinkCardinality::ManyToMany => "MANY_TO_MANY",
+        }
+    }
+}
+
 /// Link direction for traversal
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum LinkDirection {
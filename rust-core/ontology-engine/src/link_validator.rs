@@ -0,0 +1,190 @@
+//! Checks for new links against their link type: property values and cardinality.
+//!
+//! Cardinality limits how many links of a type an endpoint may have:
+//! - `ONE_TO_ONE`: one outgoing link per source and one incoming link per target
+//! - `MANY_TO_ONE`: one outgoing link per source
+//! - `ONE_TO_MANY`: one incoming link per target
+//! - `MANY_TO_MANY`: no limit
+//!
+//! For bidirectional types an endpoint's links count in both directions, and a link that
+//! already exists the other way round is a duplicate. The checks are pure; callers fetch the
+//! existing links from their graph store.
+
+use crate::link::{Link, LinkCardinality};
+use crate::meta_model::LinkTypeDef;
+use crate::property::{PropertyMap, PropertyValue};
+
+/// Validates links before they are written
+pub struct LinkValidator;
+
+impl LinkValidator {
+    /// Whether a source and a target are each limited to one link of this cardinality, as
+    /// `(source_limited, target_limited)`
+    pub fn limits(cardinality: LinkCardinality) -> (bool, bool) {
+        match cardinality {
+            LinkCardinality::OneToOne => (true, true),
+            LinkCardinality::ManyToOne => (true, false),
+            LinkCardinality::OneToMany => (false, true),
+            LinkCardinality::ManyToMany => (false, false),
+        }
+    }
+
+    /// Reject a new `source_id -> target_id` link that would break the type's cardinality.
+    /// `existing` holds the type's links at either endpoint, in any direction; the error
+    /// names the link in the way.
+    pub fn check_cardinality(
+        link_type: &LinkTypeDef,
+        source_id: &str,
+        target_id: &str,
+        existing: &[Link],
+    ) -> Result<(), String> {
+        let existing: Vec<&Link> = existing.iter().filter(|link| link.link_type_id == link_type.id).collect();
+        if link_type.bidirectional {
+            if let Some(link) = existing
+                .iter()
+                .find(|link| link.source_id == target_id && link.target_id == source_id)
+            {
+                return Err(format!(
+                    "Bidirectional link '{}' between '{}' and '{}' already exists as link '{}'",
+                    link_type.id, target_id, source_id, link.id
+                ));
+            }
+        }
+
+        let (source_limited, target_limited) = Self::limits(link_type.cardinality);
+        for (object_id, limited, outgoing) in [(source_id, source_limited, true), (target_id, target_limited, false)] {
+            if !limited {
+                continue;
+            }
+            let conflict = existing.iter().find(|link| {
+                let (near, far) = if outgoing {
+                    (&link.source_id, &link.target_id)
+                } else {
+                    (&link.target_id, &link.source_id)
+                };
+                near == object_id || (link_type.bidirectional && far == object_id)
+            });
+            if let Some(link) = conflict {
+                return Err(format!(
+                    "Link type '{}' is {}: '{}' already has link '{}' ({} -> {})",
+                    link_type.id,
+                    link_type.cardinality.as_str(),
+                    object_id,
+                    link.id,
+                    link.source_id,
+                    link.target_id
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Check link property values against the link type's property definitions. Every
+    /// problem is reported: missing required properties, undeclared properties and values
+    /// failing their property's rules.
+    pub fn validate_properties(link_type: &LinkTypeDef, properties: &PropertyMap) -> Result<(), Vec<String>> {
+        let mut errors = Vec::new();
+        for property in &link_type.properties {
+            match properties.get(&property.id) {
+                None | Some(PropertyValue::Null) => {
+                    if property.required {
+                        errors.push(format!("Missing required property '{}'", property.id));
+                    }
+                }
+                Some(value) => {
+                    if let Err(e) = property.validate_value_with_siblings(value, properties) {
+                        errors.push(e);
+                    }
+                }
+            }
+        }
+        let mut unknown: Vec<&String> = properties
+            .iter()
+            .map(|(key, _)| key)
+            .filter(|key| !link_type.properties.iter().any(|p| &p.id == *key))
+            .collect();
+        unknown.sort();
+        for key in unknown {
+            errors.push(format!("Unknown property '{}' on link type '{}'", key, link_type.id));
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn link_type(cardinality: LinkCardinality, bidirectional: bool) -> LinkTypeDef {
+        serde_json::from_value(serde_json::json!({
+            "id": "manages",
+            "source": "person",
+            "target": "person",
+            "cardinality": cardinality.as_str(),
+            "bidirectional": bidirectional,
+            "properties": [
+                { "id": "since", "type": "date", "required": true },
+                { "id": "weight", "type": "double", "validation": { "min": 0.0, "max": 1.0 } }
+            ]
+        }))
+        .unwrap()
+    }
+
+    fn link(id: &str, source_id: &str, target_id: &str) -> Link {
+        Link::new(id.to_string(), "manages".to_string(), source_id.to_string(), target_id.to_string())
+    }
+
+    #[test]
+    fn test_cardinality_limits_each_endpoint() {
+        let existing = vec![link("l1", "ann", "bob")];
+        let one_to_one = link_type(LinkCardinality::OneToOne, false);
+        assert_eq!(
+            LinkValidator::check_cardinality(&one_to_one, "ann", "cat", &existing).unwrap_err(),
+            "Link type 'manages' is ONE_TO_ONE: 'ann' already has link 'l1' (ann -> bob)"
+        );
+        assert!(LinkValidator::check_cardinality(&one_to_one, "cat", "bob", &existing).is_err());
+        assert!(LinkValidator::check_cardinality(&one_to_one, "cat", "dan", &existing).is_ok());
+
+        // A target may have many sources under MANY_TO_ONE, a source many targets under ONE_TO_MANY
+        let many_to_one = link_type(LinkCardinality::ManyToOne, false);
+        assert!(LinkValidator::check_cardinality(&many_to_one, "cat", "bob", &existing).is_ok());
+        assert!(LinkValidator::check_cardinality(&many_to_one, "ann", "cat", &existing).is_err());
+        let one_to_many = link_type(LinkCardinality::OneToMany, false);
+        assert!(LinkValidator::check_cardinality(&one_to_many, "ann", "cat", &existing).is_ok());
+        assert_eq!(
+            LinkValidator::check_cardinality(&one_to_many, "cat", "bob", &existing).unwrap_err(),
+            "Link type 'manages' is ONE_TO_MANY: 'bob' already has link 'l1' (ann -> bob)"
+        );
+        assert!(LinkValidator::check_cardinality(&link_type(LinkCardinality::ManyToMany, false), "cat", "bob", &existing).is_ok());
+
+        // Bidirectional links count from both ends, and the reverse of a link is a duplicate
+        let bidirectional = link_type(LinkCardinality::ManyToMany, true);
+        assert!(LinkValidator::check_cardinality(&bidirectional, "bob", "ann", &existing)
+            .unwrap_err()
+            .contains("already exists as link 'l1'"));
+        let bidirectional_one_to_one = link_type(LinkCardinality::OneToOne, true);
+        assert!(LinkValidator::check_cardinality(&bidirectional_one_to_one, "bob", "cat", &existing).is_err());
+    }
+
+    #[test]
+    fn test_validate_link_properties_reports_every_error() {
+        let link_type = link_type(LinkCardinality::ManyToMany, false);
+        let mut properties = PropertyMap::new();
+        properties.insert("weight".to_string(), PropertyValue::Double(1.5));
+        properties.insert("note".to_string(), PropertyValue::String("x".to_string()));
+        let errors = LinkValidator::validate_properties(&link_type, &properties).unwrap_err();
+        assert_eq!(errors.len(), 3);
+        assert_eq!(errors[0], "Missing required property 'since'");
+        assert!(errors[1].starts_with("Property 'weight'"), "{}", errors[1]);
+        assert_eq!(errors[2], "Unknown property 'note' on link type 'manages'");
+
+        let mut properties = PropertyMap::new();
+        properties.insert("weight".to_string(), PropertyValue::Double(0.5));
+        properties.insert("since".to_string(), PropertyValue::Date("2020-01-01".to_string()));
+        assert!(LinkValidator::validate_properties(&link_type, &properties).is_ok());
+    }
+}
@@ -0,0 +1,211 @@
+use serde::Serialize;
+use std::fmt;
+
+/// Kind of ontology definition an error refers to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DefinitionKind {
+    ObjectType,
+    LinkType,
+    ActionType,
+    Interface,
+    FunctionType,
+    Property,
+    ComputedProperty,
+    PropertyGroup,
+    DedupRule,
+    ArchivalPolicy,
+    Parameter,
+    Validator,
+}
+
+impl fmt::Display for DefinitionKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            DefinitionKind::ObjectType => "object type",
+            DefinitionKind::LinkType => "link type",
+            DefinitionKind::ActionType => "action type",
+            DefinitionKind::Interface => "interface",
+            DefinitionKind::FunctionType => "function type",
+            DefinitionKind::Property => "property",
+            DefinitionKind::ComputedProperty => "computed property",
+            DefinitionKind::PropertyGroup => "property group",
+            DefinitionKind::DedupRule => "dedup rule",
+            DefinitionKind::ArchivalPolicy => "archival policy",
+            DefinitionKind::Parameter => "parameter",
+            DefinitionKind::Validator => "validator",
+        };
+        f.write_str(name)
+    }
+}
+
+/// A single problem found while loading an ontology definition
+#[derive(Debug, Clone, PartialEq, Serialize, thiserror::Error)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum OntologyLoadError {
+    /// The source could not be parsed; `line`/`column` are 1-based when known
+    #[error("Failed to parse {format}{}: {message}", location_suffix(.line, .column))]
+    ParseError {
+        format: String,
+        message: String,
+        line: Option<usize>,
+        column: Option<usize>,
+    },
+
+    #[error("Duplicate {kind} ID '{id}'{}", scope_suffix(.scope))]
+    DuplicateId {
+        kind: DefinitionKind,
+        id: String,
+        scope: Option<String>,
+    },
+
+    #[error("{referenced_by} references unknown {kind} '{id}'")]
+    UnknownReference {
+        kind: DefinitionKind,
+        id: String,
+        referenced_by: String,
+    },
+
+    #[error("Property '{property}' on object type '{object_type}' has an invalid type: {message}")]
+    InvalidPropertyType {
+        object_type: String,
+        property: String,
+        message: String,
+    },
+
+    #[error("{message}")]
+    InterfaceViolation {
+        object_type: String,
+        interface: String,
+        message: String,
+    },
+
+    /// Any other rule violation on a single definition
+    #[error("{message}")]
+    InvalidDefinition {
+        kind: DefinitionKind,
+        id: String,
+        message: String,
+    },
+
+    /// An overlay changes something a lower layer already defines without `override: true`
+    #[error("Overlay '{overlay}' changes {field} of {kind} '{id}' without `override: true`")]
+    OverlayConflict {
+        overlay: String,
+        kind: DefinitionKind,
+        id: String,
+        field: String,
+    },
+}
+
+fn location_suffix(line: &Option<usize>, column: &Option<usize>) -> String {
+    match (line, column) {
+        (Some(line), Some(column)) => format!(" at line {}, column {}", line, column),
+        (Some(line), None) => format!(" at line {}", line),
+        _ => String::new(),
+    }
+}
+
+fn scope_suffix(scope: &Option<String>) -> String {
+    scope.as_ref().map(|s| format!(" in {}", s)).unwrap_or_default()
+}
+
+impl OntologyLoadError {
+    /// Stable machine-readable code, matching the serialized `type` tag
+    pub fn code(&self) -> &'static str {
+        match self {
+            OntologyLoadError::ParseError { .. } => "parse_error",
+            OntologyLoadError::DuplicateId { .. } => "duplicate_id",
+            OntologyLoadError::UnknownReference { .. } => "unknown_reference",
+            OntologyLoadError::InvalidPropertyType { .. } => "invalid_property_type",
+            OntologyLoadError::InterfaceViolation { .. } => "interface_violation",
+            OntologyLoadError::InvalidDefinition { .. } => "invalid_definition",
+            OntologyLoadError::OverlayConflict { .. } => "overlay_conflict",
+        }
+    }
+
+    pub fn from_yaml_error(error: &serde_yaml::Error) -> Self {
+        let location = error.location();
+        OntologyLoadError::ParseError {
+            format: "YAML".to_string(),
+            message: error.to_string(),
+            line: location.as_ref().map(|l| l.line()),
+            column: location.as_ref().map(|l| l.column()),
+        }
+    }
+
+    pub fn from_json_error(error: &serde_json::Error) -> Self {
+        // serde_json reports line 0 when the error has no position (e.g. I/O)
+        let known = error.line() > 0;
+        OntologyLoadError::ParseError {
+            format: "JSON".to_string(),
+            message: error.to_string(),
+            line: known.then(|| error.line()),
+            column: known.then(|| error.column()),
+        }
+    }
+}
+
+/// Every error found while loading an ontology; loading reports all of them at once
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(transparent)]
+pub struct OntologyLoadErrors(pub Vec<OntologyLoadError>);
+
+impl OntologyLoadErrors {
+    pub fn errors(&self) -> &[OntologyLoadError] {
+        &self.0
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, OntologyLoadError> {
+        self.0.iter()
+    }
+}
+
+impl fmt::Display for OntologyLoadErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0.as_slice() {
+            [single] => write!(f, "{}", single),
+            errors => {
+                write!(f, "{} ontology errors:", errors.len())?;
+                for error in errors {
+                    write!(f, "\n  - {}", error)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for OntologyLoadErrors {}
+
+impl From<OntologyLoadError> for OntologyLoadErrors {
+    fn from(error: OntologyLoadError) -> Self {
+        Self(vec![error])
+    }
+}
+
+impl IntoIterator for OntologyLoadErrors {
+    type Item = OntologyLoadError;
+    type IntoIter = std::vec::IntoIter<OntologyLoadError>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a OntologyLoadErrors {
+    type Item = &'a OntologyLoadError;
+    type IntoIter = std::slice::Iter<'a, OntologyLoadError>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
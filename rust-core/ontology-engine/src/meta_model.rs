@@ -1,8 +1,11 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use chrono::{DateTime, Utc};
-use crate::property::{Property, PropertyType};
+use crate::property::{localized_name, DeprecationInfo, IndexingHint, Property, PropertyMap, PropertyType, PropertyValue};
 use crate::link::LinkCardinality;
+use crate::reference::CascadeDeleteBehavior;
+use crate::action::{ActionCondition, ConditionOperator};
+use crate::load_error::{DefinitionKind, OntologyLoadError, OntologyLoadErrors};
 
 /// Core meta-model representing the ontology configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -10,6 +13,14 @@ pub struct OntologyConfig {
     pub ontology: OntologyDef,
 }
 
+impl OntologyConfig {
+    /// Parse a definition without validating it, e.g. as the base for overlays
+    pub fn from_yaml(content: &str) -> Result<Self, OntologyLoadErrors> {
+        serde_yaml::from_str(content)
+            .map_err(|e| OntologyLoadError::from_yaml_error(&e).into())
+    }
+}
+
 /// The complete ontology definition (for serialization)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OntologyDef {
@@ -34,6 +45,11 @@ pub struct OntologyDef {
     #[serde(rename = "modelObjectives")]
     #[serde(default)]
     pub model_objectives: Vec<crate::model_objectives::ModelObjective>,
+    
+    /// How closely implementers' property types must match their interfaces'
+    #[serde(rename = "interfaceCompatibility")]
+    #[serde(default)]
+    pub interface_compatibility: crate::interface::TypeCompatibility,
 }
 
 /// Interface definition - represents a contract that object types can implement
@@ -44,28 +60,47 @@ pub struct InterfaceDef {
     #[serde(rename = "displayName")]
     pub display_name: String,
     
+    /// Display names by locale (e.g. `fr`, `pt-BR`), used in place of `displayName` when
+    /// rendering for that locale
+    #[serde(rename = "displayNames")]
+    #[serde(default)]
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub display_names: HashMap<String, String>,
+    
     #[serde(default)]
     pub properties: Vec<Property>,
     
     #[serde(rename = "requiredLinkTypes")]
     #[serde(default)]
     pub required_link_types: Vec<String>,
+    
+    /// Interfaces whose properties and required link types this one includes. At runtime
+    /// `properties` and `requiredLinkTypes` hold the inherited ones too; `extends` stays
+    /// as declared.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub extends: Vec<String>,
 }
 
 impl InterfaceDef {
+    /// Display name for the given locale, falling back to `displayName`
+    pub fn display_name_for(&self, locale: Option<&str>) -> &str {
+        localized_name(&self.display_names, locale).unwrap_or(&self.display_name)
+    }
+    
     /// Validate that the interface definition is valid
     pub fn validate(&self) -> Result<(), String> {
+        first_error(self.load_errors())
+    }
+    
+    /// All problems with this interface definition
+    pub fn load_errors(&self) -> Vec<OntologyLoadError> {
         // Check for duplicate property IDs
-        let mut seen = std::collections::HashSet::new();
-        for prop in &self.properties {
-            if !seen.insert(&prop.id) {
-                return Err(format!(
-                    "Duplicate property ID '{}' in interface '{}'",
-                    prop.id, self.id
-                ));
-            }
-        }
-        Ok(())
+        duplicate_ids(
+            self.properties.iter().map(|p| p.id.as_str()),
+            DefinitionKind::Property,
+            Some(format!("interface '{}'", self.id)),
+        )
     }
 }
 
@@ -77,6 +112,13 @@ pub struct ObjectType {
     #[serde(rename = "displayName")]
     pub display_name: String,
     
+    /// Display names by locale (e.g. `fr`, `pt-BR`), used in place of `displayName` when
+    /// rendering for that locale
+    #[serde(rename = "displayNames")]
+    #[serde(default)]
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub display_names: HashMap<String, String>,
+    
     #[serde(rename = "primaryKey")]
     pub primary_key: String,
     
@@ -94,9 +136,92 @@ pub struct ObjectType {
     #[serde(default)]
     pub implements: Vec<String>, // List of interface IDs this object type implements
     
+    /// Local property IDs for interface properties named differently on this type:
+    /// interface ID -> interface property ID -> local property ID
+    #[serde(rename = "interfaceMappings")]
+    #[serde(default)]
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub interface_mappings: HashMap<String, HashMap<String, String>>,
+    
     // Schema evolution metadata
     #[serde(default)]
     pub schema_evolution: Option<SchemaEvolution>,
+    
+    /// Sort applied to search results when the caller does not request one
+    #[serde(rename = "defaultSort")]
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_sort: Option<DefaultSort>,
+    
+    /// Properties derived from other properties, optionally materialized at write time
+    #[serde(rename = "computedProperties")]
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub computed_properties: Vec<crate::computed_properties::ComputedProperty>,
+    
+    /// Rules identifying duplicate records of this type, for entity resolution
+    #[serde(rename = "dedupRules")]
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dedup_rules: Option<Vec<crate::dedup::DedupRule>>,
+    
+    /// When objects of this type move from the search index to the columnar archive
+    #[serde(rename = "archivalPolicy")]
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub archival_policy: Option<crate::retention::ArchivalPolicy>,
+    
+    /// Reject writes carrying properties the type does not declare, rather than indexing
+    /// them as-is
+    #[serde(rename = "strictProperties")]
+    #[serde(default)]
+    pub strict_properties: bool,
+    
+    /// Sections grouping related properties in forms and detail views
+    #[serde(rename = "propertyGroups")]
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub property_groups: Vec<crate::property_groups::PropertyGroup>,
+}
+
+/// Default sort order for an object type
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DefaultSort {
+    pub property: String,
+    
+    #[serde(default = "default_ascending")]
+    pub ascending: bool,
+}
+
+fn default_ascending() -> bool {
+    true
+}
+
+impl DefaultSort {
+    /// Parse a `sys:defaultSort` annotation value such as `name`, `name asc` or `-population`
+    pub fn parse(value: &str) -> Result<Self, String> {
+        let value = value.trim();
+        let (property, ascending) = if let Some(rest) = value.strip_prefix('-') {
+            (rest.trim(), false)
+        } else if let Some((prop, dir)) = value.split_once(char::is_whitespace) {
+            match dir.trim().to_lowercase().as_str() {
+                "asc" | "ascending" => (prop, true),
+                "desc" | "descending" => (prop, false),
+                other => return Err(format!("Invalid sort direction '{}' in default sort '{}'", other, value)),
+            }
+        } else {
+            (value, true)
+        };
+        
+        if property.is_empty() {
+            return Err("Default sort must name a property".to_string());
+        }
+        
+        Ok(Self {
+            property: property.to_string(),
+            ascending,
+        })
+    }
 }
 
 /// Schema evolution tracking
@@ -109,6 +234,19 @@ pub struct SchemaEvolution {
     pub migration_script: Option<String>,
 }
 
+impl SchemaEvolution {
+    /// An empty log starting at version 0
+    pub fn new(created_at: DateTime<Utc>) -> Self {
+        Self {
+            version: "0".to_string(),
+            created_at,
+            changes: Vec::new(),
+            deprecated_properties: Vec::new(),
+            migration_script: None,
+        }
+    }
+}
+
 /// Schema change types
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum SchemaChange {
@@ -116,58 +254,505 @@ pub enum SchemaChange {
     PropertyRemoved { property_id: String },
     PropertyTypeChanged { property_id: String, old_type: String, new_type: String },
     PropertyRenamed { old_id: String, new_id: String },
+    PropertyDeprecated { property_id: String, replacement: Option<String> },
 }
 
 impl ObjectType {
+    /// Display name for the given locale, falling back to `displayName`
+    pub fn display_name_for(&self, locale: Option<&str>) -> &str {
+        localized_name(&self.display_names, locale).unwrap_or(&self.display_name)
+    }
+    
     /// Get a property by its ID
     pub fn get_property(&self, property_id: &str) -> Option<&Property> {
         self.properties.iter().find(|p| p.id == property_id)
     }
     
+    /// Local ID of an interface property on this type (the same ID unless mapped)
+    pub fn local_property_id<'a>(&'a self, interface_id: &str, property_id: &'a str) -> &'a str {
+        self.interface_mappings
+            .get(interface_id)
+            .and_then(|mapping| mapping.get(property_id))
+            .map_or(property_id, |local| local.as_str())
+    }
+    
+    /// The property implementing an interface property on this type
+    pub fn interface_property(&self, interface_id: &str, property_id: &str) -> Option<&Property> {
+        self.get_property(self.local_property_id(interface_id, property_id))
+    }
+    
+    /// This type as loaded at runtime: `implements` also lists every interface inherited
+    /// through `extends`, and properties mapped for an interface stay mapped for the
+    /// interfaces it inherits from
+    pub fn with_inherited_interfaces(&self, interfaces: &HashMap<String, InterfaceDef>) -> ObjectType {
+        let mut object_type = self.clone();
+        for interface_id in &self.implements {
+            for ancestor in crate::interface::InterfaceValidator::ancestors(interface_id, interfaces) {
+                if let Some(mapping) = self.interface_mappings.get(interface_id) {
+                    let inherited = object_type.interface_mappings.entry(ancestor.clone()).or_default();
+                    for property in &interfaces[&ancestor].properties {
+                        if let Some(local) = mapping.get(&property.id) {
+                            inherited.entry(property.id.clone()).or_insert_with(|| local.clone());
+                        }
+                    }
+                }
+                if !object_type.implements.contains(&ancestor) {
+                    object_type.implements.push(ancestor);
+                }
+            }
+        }
+        object_type
+    }
+    
+    /// Effective default sort: the configured default, or primary key ascending
+    pub fn effective_default_sort(&self) -> DefaultSort {
+        self.default_sort.clone().unwrap_or_else(|| DefaultSort {
+            property: self.primary_key.clone(),
+            ascending: true,
+        })
+    }
+    
+    /// Error if a search cannot filter on a property because it is not indexed.
+    /// Properties not declared on the type (e.g. metadata fields) are not checked.
+    pub fn check_filterable(&self, property_id: &str) -> Result<(), String> {
+        match self.get_property(property_id) {
+            Some(prop) if !prop.indexing_hint().is_filterable() => Err(format!(
+                "Cannot filter on property '{}' of object type '{}': it has indexing '{}'",
+                property_id,
+                self.id,
+                prop.indexing_hint().as_str()
+            )),
+            _ => Ok(()),
+        }
+    }
+    
+    /// Error if a search cannot sort on a property because of how it is indexed
+    pub fn check_sortable(&self, property_id: &str) -> Result<(), String> {
+        match self.get_property(property_id) {
+            Some(prop) if !prop.indexing_hint().is_sortable() => Err(format!(
+                "Cannot sort on property '{}' of object type '{}': it has indexing '{}'",
+                property_id,
+                self.id,
+                prop.indexing_hint().as_str()
+            )),
+            _ => Ok(()),
+        }
+    }
+    
+    /// Properties ordered for display: explicit display order first, then declaration order
+    pub fn properties_in_display_order(&self) -> Vec<&Property> {
+        let mut props: Vec<&Property> = self.properties.iter().collect();
+        props.sort_by_key(|p| p.display_order.unwrap_or(u32::MAX));
+        props
+    }
+    
+    /// Fill in declared defaults for properties missing from (or null in) an object
+    pub fn apply_defaults(&self, properties: &mut PropertyMap) {
+        for property in &self.properties {
+            if let Some(default) = &property.default {
+                if properties.get(&property.id).map_or(true, |v| v.is_null()) {
+                    properties.insert(property.id.clone(), default.clone());
+                }
+            }
+        }
+    }
+    
+    /// Reproject GeoJSON values of properties declared in another CRS to WGS84, in place.
+    /// Returns every value that could not be reprojected.
+    pub fn reproject_geometries(&self, properties: &mut PropertyMap) -> Result<(), Vec<String>> {
+        let mut errors = Vec::new();
+        for property in self.properties.iter().filter(|p| p.geo.is_some()) {
+            let Some(value) = properties.get(&property.id) else {
+                continue;
+            };
+            match property.to_wgs84(value.clone()) {
+                Ok(reprojected) => {
+                    properties.insert(property.id.clone(), reprojected);
+                }
+                Err(e) => errors.push(e),
+            }
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+    
+    /// Validate an object's values against this type: the primary key and required
+    /// properties must be present, and every declared property must hold a valid value,
+    /// including passing its custom validator. Properties not declared on the type are
+    /// ignored. Returns every violation.
+    pub fn validate_object(&self, properties: &PropertyMap) -> Result<(), Vec<String>> {
+        let mut errors = Vec::new();
+        for property in &self.properties {
+            match properties.get(&property.id) {
+                None | Some(PropertyValue::Null) => {
+                    if property.required || property.id == self.primary_key {
+                        errors.push(format!("Missing required property '{}'", property.id));
+                    }
+                }
+                Some(value) => {
+                    if let Err(e) = property.validate_value_with_siblings(value, properties) {
+                        errors.push(e);
+                    }
+                }
+            }
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+    
+    /// Keys of an object's properties that the type does not declare, sorted. Keys in the
+    /// reserved `_` namespace (ACLs and other system fields) and computed properties count
+    /// as declared.
+    pub fn unknown_properties(&self, properties: &PropertyMap) -> Vec<String> {
+        let mut unknown: Vec<String> = properties
+            .iter()
+            .map(|(key, _)| key)
+            .filter(|key| {
+                !key.starts_with('_')
+                    && self.get_property(key).is_none()
+                    && !self.computed_properties.iter().any(|c| &c.id == *key)
+            })
+            .cloned()
+            .collect();
+        unknown.sort();
+        unknown
+    }
+    
+    /// Add a property to an existing type, recording it in the schema evolution log. A new
+    /// required property needs a default, since stored objects have no value for it.
+    pub fn add_property(&mut self, property: Property) -> Result<(), String> {
+        if self.get_property(&property.id).is_some() {
+            return Err(format!("Property '{}' already exists on object type '{}'", property.id, self.id));
+        }
+        if property.required && property.default.is_none() {
+            return Err(format!(
+                "New required property '{}' on object type '{}' needs a default for existing objects",
+                property.id, self.id
+            ));
+        }
+        let property_id = property.id.clone();
+        self.properties.push(property);
+        self.record_schema_change(SchemaChange::PropertyAdded { property_id });
+        Ok(())
+    }
+    
+    /// Mark a property deprecated as of today, optionally pointing at the property replacing
+    /// it, and record it in the schema evolution log. The primary key cannot be deprecated.
+    pub fn deprecate_property(&mut self, property_id: &str, replacement: Option<String>) -> Result<(), String> {
+        if property_id == self.primary_key {
+            return Err(format!("Primary key '{}' of object type '{}' cannot be deprecated", property_id, self.id));
+        }
+        if let Some(replacement) = &replacement {
+            match self.get_property(replacement) {
+                None => {
+                    return Err(format!(
+                        "Replacement property '{}' not found on object type '{}'",
+                        replacement, self.id
+                    ))
+                }
+                Some(p) if p.id == property_id || p.deprecated.is_some() => {
+                    return Err(format!("Property '{}' cannot replace '{}'", replacement, property_id));
+                }
+                Some(_) => {}
+            }
+        }
+        let object_type = self.id.clone();
+        let property = self
+            .properties
+            .iter_mut()
+            .find(|p| p.id == property_id)
+            .ok_or_else(|| format!("Property '{}' not found on object type '{}'", property_id, object_type))?;
+        if property.deprecated.is_some() {
+            return Err(format!("Property '{}' of object type '{}' is already deprecated", property_id, object_type));
+        }
+        property.deprecated = Some(DeprecationInfo {
+            deprecated_since: Utc::now().format("%Y-%m-%d").to_string(),
+            replacement: replacement.clone(),
+            removal_date: None,
+        });
+        self.record_schema_change(SchemaChange::PropertyDeprecated { property_id: property_id.to_string(), replacement });
+        Ok(())
+    }
+    
+    /// Append a change to the schema evolution log, starting the log if needed. The log's
+    /// version counts the changes recorded.
+    pub fn record_schema_change(&mut self, change: SchemaChange) {
+        let evolution = self.schema_evolution.get_or_insert_with(|| SchemaEvolution::new(Utc::now()));
+        if let SchemaChange::PropertyDeprecated { property_id, .. } = &change {
+            evolution.deprecated_properties.push(property_id.clone());
+        }
+        evolution.changes.push(change);
+        evolution.version = evolution.changes.len().to_string();
+    }
+    
     /// Validate that all required properties are present
     pub fn validate(&self) -> Result<(), String> {
+        first_error(self.load_errors())
+    }
+    
+    /// All problems with this object type's own definition
+    pub fn load_errors(&self) -> Vec<OntologyLoadError> {
+        let mut errors = Vec::new();
+        
         // Check that primary_key property exists
         if !self.properties.iter().any(|p| p.id == self.primary_key) {
-            return Err(format!(
-                "Primary key '{}' not found in properties for object type '{}'",
-                self.primary_key, self.id
-            ));
+            errors.push(OntologyLoadError::UnknownReference {
+                kind: DefinitionKind::Property,
+                id: self.primary_key.clone(),
+                referenced_by: format!("Primary key of object type '{}'", self.id),
+            });
         }
         
         // Check for duplicate property IDs
-        let mut seen = std::collections::HashSet::new();
+        errors.extend(duplicate_ids(
+            self.properties.iter().map(|p| p.id.as_str()),
+            DefinitionKind::Property,
+            Some(format!("object type '{}'", self.id)),
+        ));
+        
+        // Check that property groups only name declared properties, each in one group
+        errors.extend(duplicate_ids(
+            self.property_groups.iter().map(|g| g.id.as_str()),
+            DefinitionKind::PropertyGroup,
+            Some(format!("object type '{}'", self.id)),
+        ));
+        let mut group_of: HashMap<&str, &str> = HashMap::new();
+        for group in &self.property_groups {
+            for property_id in &group.properties {
+                if self.get_property(property_id).is_none() {
+                    errors.push(OntologyLoadError::UnknownReference {
+                        kind: DefinitionKind::Property,
+                        id: property_id.clone(),
+                        referenced_by: format!("Property group '{}' of object type '{}'", group.id, self.id),
+                    });
+                }
+                if let Some(first) = group_of.insert(property_id, &group.id) {
+                    errors.push(OntologyLoadError::InvalidDefinition {
+                        kind: DefinitionKind::PropertyGroup,
+                        id: group.id.clone(),
+                        message: format!(
+                            "Property '{}' of object type '{}' is in property groups '{}' and '{}'",
+                            property_id, self.id, first, group.id
+                        ),
+                    });
+                }
+            }
+        }
+        
+        // Check that the default sort targets an existing, sortable property
+        if let Some(sort) = &self.default_sort {
+            match self.get_property(&sort.property) {
+                None => errors.push(OntologyLoadError::UnknownReference {
+                    kind: DefinitionKind::Property,
+                    id: sort.property.clone(),
+                    referenced_by: format!("Default sort of object type '{}'", self.id),
+                }),
+                Some(prop) if !prop.property_type.is_sortable() => {
+                    errors.push(OntologyLoadError::InvalidPropertyType {
+                        object_type: self.id.clone(),
+                        property: sort.property.clone(),
+                        message: "default sort property is not sortable".to_string(),
+                    });
+                }
+                Some(prop) if !prop.indexing_hint().is_sortable() => {
+                    errors.push(OntologyLoadError::InvalidPropertyType {
+                        object_type: self.id.clone(),
+                        property: sort.property.clone(),
+                        message: format!(
+                            "default sort property has indexing '{}' and cannot be sorted on",
+                            prop.indexing_hint().as_str()
+                        ),
+                    });
+                }
+                Some(_) => {}
+            }
+        }
+        
+        // Keyword/text-only indexing is a string mapping choice; the primary key is the
+        // fallback sort, so it must stay sortable
         for prop in &self.properties {
-            if !seen.insert(&prop.id) {
-                return Err(format!(
-                    "Duplicate property ID '{}' in object type '{}'",
-                    prop.id, self.id
-                ));
+            let hint = prop.indexing_hint();
+            let is_string = matches!(prop.property_type, PropertyType::String);
+            if matches!(hint, IndexingHint::KeywordOnly | IndexingHint::TextOnly) && !is_string {
+                errors.push(OntologyLoadError::InvalidPropertyType {
+                    object_type: self.id.clone(),
+                    property: prop.id.clone(),
+                    message: format!("indexing '{}' only applies to string properties", hint.as_str()),
+                });
+            }
+            if prop.id == self.primary_key && !hint.is_sortable() {
+                errors.push(OntologyLoadError::InvalidPropertyType {
+                    object_type: self.id.clone(),
+                    property: prop.id.clone(),
+                    message: format!("primary key cannot have indexing '{}'", hint.as_str()),
+                });
+            }
+            if prop.reference_target.is_some() && !holds_references(&prop.property_type) {
+                errors.push(OntologyLoadError::InvalidPropertyType {
+                    object_type: self.id.clone(),
+                    property: prop.id.clone(),
+                    message: "referenceTarget only applies to object reference properties".to_string(),
+                });
+            }
+            if let Some(validation) = &prop.validation {
+                let is_date = matches!(
+                    prop.property_type,
+                    PropertyType::Date | PropertyType::DateTime | PropertyType::Timestamp
+                );
+                let is_geojson = matches!(prop.property_type, PropertyType::GeoJSON | PropertyType::GeoJSONAlt);
+                let message = if validation.has_date_bounds() && !is_date {
+                    Some("min_date/max_date only apply to date and datetime properties".to_string())
+                } else if validation.geometry_types.is_some() && !is_geojson {
+                    Some("geometry_types only applies to GeoJSON properties".to_string())
+                } else {
+                    validation.validate().err()
+                };
+                if let Some(message) = message {
+                    errors.push(OntologyLoadError::InvalidPropertyType {
+                        object_type: self.id.clone(),
+                        property: prop.id.clone(),
+                        message,
+                    });
+                }
+            }
+            if let Some(geo) = &prop.geo {
+                let message = if !matches!(prop.property_type, PropertyType::GeoJSON | PropertyType::GeoJSONAlt) {
+                    Some("geo only applies to GeoJSON properties".to_string())
+                } else {
+                    geo.validate().err()
+                };
+                if let Some(message) = message {
+                    errors.push(OntologyLoadError::InvalidPropertyType {
+                        object_type: self.id.clone(),
+                        property: prop.id.clone(),
+                        message,
+                    });
+                }
+            }
+        }
+        
+        for computed in &self.computed_properties {
+            if let Err(message) = computed.validate_for(self) {
+                errors.push(OntologyLoadError::InvalidDefinition {
+                    kind: DefinitionKind::ComputedProperty,
+                    id: computed.id.clone(),
+                    message,
+                });
+            }
+        }
+        
+        for (i, rule) in self.dedup_rules.iter().flatten().enumerate() {
+            if let Err(message) = rule.validate_for(self) {
+                errors.push(OntologyLoadError::InvalidDefinition {
+                    kind: DefinitionKind::DedupRule,
+                    id: rule.id.clone().unwrap_or_else(|| format!("{}#{}", self.id, i)),
+                    message,
+                });
+            }
+        }
+        
+        if let Some(policy) = &self.archival_policy {
+            if let Err(message) = policy.validate_for(self) {
+                errors.push(OntologyLoadError::InvalidDefinition {
+                    kind: DefinitionKind::ArchivalPolicy,
+                    id: self.id.clone(),
+                    message,
+                });
             }
         }
         
         // Note: Interface implementation validation happens at ontology level
         // where we have access to interface definitions
         
-        Ok(())
+        errors
+    }
+    
+    /// Reference targets naming object types that do not exist
+    pub fn reference_errors(&self, object_type_ids: &[String]) -> Vec<OntologyLoadError> {
+        self.properties
+            .iter()
+            .filter_map(|prop| {
+                let target = prop.reference_target.as_ref()?;
+                (!object_type_ids.contains(target)).then(|| OntologyLoadError::UnknownReference {
+                    kind: DefinitionKind::ObjectType,
+                    id: target.clone(),
+                    referenced_by: format!("Reference target of property '{}' of object type '{}'", prop.id, self.id),
+                })
+            })
+            .collect()
     }
     
     /// Validate that this object type implements all declared interfaces
     pub fn validate_interface_implementations(
         &self,
         interfaces: &std::collections::HashMap<String, InterfaceDef>,
+        link_types: &[LinkTypeDef],
+        compatibility: crate::interface::TypeCompatibility,
     ) -> Result<(), String> {
+        first_error(self.interface_errors(interfaces, link_types, compatibility))
+    }
+    
+    /// All unknown interfaces declared by this object type, and every way it falls short of
+    /// the ones it implements
+    pub fn interface_errors(
+        &self,
+        interfaces: &std::collections::HashMap<String, InterfaceDef>,
+        link_types: &[LinkTypeDef],
+        compatibility: crate::interface::TypeCompatibility,
+    ) -> Vec<OntologyLoadError> {
         use crate::interface::InterfaceValidator;
+        let mut errors = Vec::new();
         for interface_id in &self.implements {
-            let interface = interfaces.get(interface_id)
-                .ok_or_else(|| format!(
-                    "Object type '{}' declares implementation of interface '{}' which does not exist",
-                    self.id, interface_id
-                ))?;
+            let Some(interface) = interfaces.get(interface_id) else {
+                errors.push(OntologyLoadError::UnknownReference {
+                    kind: DefinitionKind::Interface,
+                    id: interface_id.clone(),
+                    referenced_by: format!("Object type '{}'", self.id),
+                });
+                continue;
+            };
             
-            InterfaceValidator::validate_implements(self, interface)?;
+            if let Err(violations) = InterfaceValidator::validate_implements(self, interface, link_types, compatibility) {
+                errors.extend(violations.into_iter().map(|violation| OntologyLoadError::InterfaceViolation {
+                    object_type: self.id.clone(),
+                    interface: interface_id.clone(),
+                    message: violation.to_string(),
+                }));
+            }
         }
         
-        Ok(())
+        for (interface_id, mapping) in &self.interface_mappings {
+            if !self.implements.contains(interface_id) {
+                errors.push(OntologyLoadError::InvalidDefinition {
+                    kind: DefinitionKind::ObjectType,
+                    id: self.id.clone(),
+                    message: format!(
+                        "Object type '{}' maps properties for interface '{}' which it does not implement",
+                        self.id, interface_id
+                    ),
+                });
+                continue;
+            }
+            let Some(interface) = interfaces.get(interface_id) else { continue };
+            let mut mapped: Vec<&String> = mapping.keys()
+                .filter(|property| !interface.properties.iter().any(|p| &p.id == *property))
+                .collect();
+            mapped.sort();
+            for property in mapped {
+                errors.push(OntologyLoadError::UnknownReference {
+                    kind: DefinitionKind::Property,
+                    id: property.clone(),
+                    referenced_by: format!("Object type '{}' mapping for interface '{}'", self.id, interface_id),
+                });
+            }
+        }
+        errors
     }
 }
 
@@ -180,6 +765,13 @@ pub struct LinkTypeDef {
     #[serde(default)]
     pub display_name: Option<String>,
     
+    /// Display names by locale (e.g. `fr`, `pt-BR`), used in place of `displayName` when
+    /// rendering for that locale
+    #[serde(rename = "displayNames")]
+    #[serde(default)]
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub display_names: HashMap<String, String>,
+    
     pub source: String,
     pub target: String,
     
@@ -191,26 +783,41 @@ pub struct LinkTypeDef {
     
     #[serde(default)]
     pub bidirectional: bool,
+    
+    /// Applied to the links of an object being deleted: `setNull` removes them, `cascade`
+    /// removes them and deletes their targets when the object is the source, and `restrict`
+    /// refuses the delete while any exist
+    #[serde(rename = "onDelete")]
+    #[serde(default)]
+    pub on_delete: CascadeDeleteBehavior,
 }
 
 impl LinkTypeDef {
+    /// Display name for the given locale, falling back to `displayName` and then the ID
+    pub fn display_name_for(&self, locale: Option<&str>) -> &str {
+        localized_name(&self.display_names, locale)
+            .or(self.display_name.as_deref())
+            .unwrap_or(&self.id)
+    }
+    
     /// Validate that source and target object types exist
     pub fn validate(&self, object_type_ids: &[String]) -> Result<(), String> {
-        if !object_type_ids.contains(&self.source) {
-            return Err(format!(
-                "Link type '{}' references unknown source object type '{}'",
-                self.id, self.source
-            ));
-        }
-        
-        if !object_type_ids.contains(&self.target) {
-            return Err(format!(
-                "Link type '{}' references unknown target object type '{}'",
-                self.id, self.target
-            ));
+        first_error(self.load_errors(object_type_ids))
+    }
+    
+    /// All unknown endpoint references of this link type
+    pub fn load_errors(&self, object_type_ids: &[String]) -> Vec<OntologyLoadError> {
+        let mut errors = Vec::new();
+        for (end, object_type) in [("source", &self.source), ("target", &self.target)] {
+            if !object_type_ids.contains(object_type) {
+                errors.push(OntologyLoadError::UnknownReference {
+                    kind: DefinitionKind::ObjectType,
+                    id: object_type.clone(),
+                    referenced_by: format!("Link type '{}' {}", self.id, end),
+                });
+            }
         }
-        
-        Ok(())
+        errors
     }
 }
 
@@ -222,6 +829,13 @@ pub struct ActionTypeDef {
     #[serde(rename = "displayName")]
     pub display_name: String,
     
+    /// Display names by locale (e.g. `fr`, `pt-BR`), used in place of `displayName` when
+    /// rendering for that locale
+    #[serde(rename = "displayNames")]
+    #[serde(default)]
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub display_names: HashMap<String, String>,
+    
     #[serde(default)]
     pub parameters: Vec<Property>,
     
@@ -233,6 +847,167 @@ pub struct ActionTypeDef {
     
     #[serde(default)]
     pub side_effects: Vec<crate::action::ActionSideEffect>,
+    
+    /// Interface this action is written against. Operations whose `type` is the interface
+    /// (or unset) apply to whichever implementer the action is run on.
+    #[serde(rename = "targetInterface")]
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target_interface: Option<String>,
+    
+    /// What to do when an operation fails; the executor's mode applies if unset
+    #[serde(rename = "executionMode")]
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub execution_mode: Option<crate::action::ExecutionMode>,
+}
+
+impl ActionTypeDef {
+    /// Display name for the given locale, falling back to `displayName`
+    pub fn display_name_for(&self, locale: Option<&str>) -> &str {
+        localized_name(&self.display_names, locale).unwrap_or(&self.display_name)
+    }
+    
+    /// Validate the object types, link types and parameters the action refers to
+    pub fn validate(&self, object_type_ids: &[String], link_type_ids: &[String]) -> Result<(), String> {
+        first_error(self.load_errors(object_type_ids, link_type_ids))
+    }
+    
+    /// Unknown object or link types in the action's operations, and `{{param}}` templates in
+    /// operations or side effect configs that name parameters the action does not declare.
+    /// Operations on the target interface are checked by `interface_errors`.
+    pub fn load_errors(&self, object_type_ids: &[String], link_type_ids: &[String]) -> Vec<OntologyLoadError> {
+        let mut errors = Vec::new();
+        for (index, operation) in self.logic.iter().enumerate() {
+            let referenced_by = format!("Action '{}' operation {}", self.id, index + 1);
+            if let Some(object_type) = &operation.object_type {
+                if self.target_interface.as_ref() != Some(object_type) && !object_type_ids.contains(object_type) {
+                    errors.push(OntologyLoadError::UnknownReference {
+                        kind: DefinitionKind::ObjectType,
+                        id: object_type.clone(),
+                        referenced_by: referenced_by.clone(),
+                    });
+                }
+            }
+            if let Some(link_type) = &operation.link_type {
+                if !link_type_ids.contains(link_type) {
+                    errors.push(OntologyLoadError::UnknownReference {
+                        kind: DefinitionKind::LinkType,
+                        id: link_type.clone(),
+                        referenced_by: referenced_by.clone(),
+                    });
+                }
+            }
+            let mut templates = string_values(&operation.properties);
+            templates.extend(operation.from.as_deref());
+            templates.extend(operation.to.as_deref());
+            errors.extend(self.unknown_parameters(templates, &referenced_by));
+        }
+        for side_effect in &self.side_effects {
+            let effect_type = format!("{:?}", side_effect.effect_type).to_lowercase();
+            let referenced_by = format!("Action '{}' {} side effect", self.id, effect_type);
+            errors.extend(self.unknown_parameters(string_values(&side_effect.config), &referenced_by));
+        }
+        errors
+    }
+    
+    /// Parameters referenced by `templates` that the action does not declare
+    fn unknown_parameters(&self, templates: Vec<&str>, referenced_by: &str) -> Vec<OntologyLoadError> {
+        templates
+            .into_iter()
+            .flat_map(crate::action::template_parameters)
+            .filter(|name| !self.parameters.iter().any(|p| p.id == *name))
+            .map(|name| OntologyLoadError::UnknownReference {
+                kind: DefinitionKind::Parameter,
+                id: name.to_string(),
+                referenced_by: referenced_by.to_string(),
+            })
+            .collect()
+    }
+    
+    /// Unknown target interface, or operation properties the interface does not declare
+    pub fn interface_errors(&self, interfaces: &HashMap<String, InterfaceDef>) -> Vec<OntologyLoadError> {
+        let Some(interface_id) = &self.target_interface else {
+            return Vec::new();
+        };
+        let Some(interface) = interfaces.get(interface_id) else {
+            return vec![OntologyLoadError::UnknownReference {
+                kind: DefinitionKind::Interface,
+                id: interface_id.clone(),
+                referenced_by: format!("Action '{}' target interface", self.id),
+            }];
+        };
+        
+        let mut errors = Vec::new();
+        for operation in self.logic.iter().filter(|o| targets_interface(o, interface_id)) {
+            let mut unknown: Vec<&String> = operation.properties.iter()
+                .map(|(property, _)| property)
+                .filter(|property| !interface.properties.iter().any(|p| &p.id == *property))
+                .collect();
+            unknown.sort();
+            for property in unknown {
+                errors.push(OntologyLoadError::UnknownReference {
+                    kind: DefinitionKind::Property,
+                    id: property.clone(),
+                    referenced_by: format!("Action '{}' on interface '{}'", self.id, interface_id),
+                });
+            }
+        }
+        errors
+    }
+    
+    /// Resolve an interface-bound action against a concrete implementer: interface operations
+    /// target `object_type` and their property keys are mapped to its local property IDs.
+    /// Actions without a target interface are returned unchanged.
+    pub fn bind_to(&self, object_type: &ObjectType) -> Result<ActionTypeDef, String> {
+        let Some(interface_id) = &self.target_interface else {
+            return Ok(self.clone());
+        };
+        if !object_type.implements.contains(interface_id) {
+            return Err(format!(
+                "Object type '{}' does not implement interface '{}' required by action '{}'",
+                object_type.id, interface_id, self.id
+            ));
+        }
+        
+        let mut bound = self.clone();
+        bound.target_interface = None;
+        for operation in bound.logic.iter_mut() {
+            if !targets_interface(operation, interface_id) {
+                continue;
+            }
+            operation.object_type = Some(object_type.id.clone());
+            let mut properties = PropertyMap::new();
+            for (key, value) in operation.properties.iter() {
+                properties.insert(object_type.local_property_id(interface_id, key).to_string(), value.clone());
+            }
+            operation.properties = properties;
+        }
+        Ok(bound)
+    }
+}
+
+/// Whether an object operation of an interface-bound action targets the interface
+/// Top-level string values of a property map, sorted by key: the values the action executor
+/// substitutes templates in
+fn string_values(properties: &PropertyMap) -> Vec<&str> {
+    let mut values: Vec<(&String, &str)> = properties
+        .iter()
+        .filter_map(|(key, value)| match value {
+            PropertyValue::String(s) => Some((key, s.as_str())),
+            _ => None,
+        })
+        .collect();
+    values.sort();
+    values.into_iter().map(|(_, value)| value).collect()
+}
+
+fn targets_interface(operation: &crate::action::ActionOperation, interface_id: &str) -> bool {
+    use crate::action::OperationType;
+    matches!(
+        operation.operation,
+        OperationType::CreateObject | OperationType::UpdateObject | OperationType::DeleteObject | OperationType::UpdateProperty
+    ) && operation.object_type.as_deref().map_or(true, |t| t == interface_id)
 }
 
 /// Function return type
@@ -240,6 +1015,7 @@ pub struct ActionTypeDef {
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum FunctionReturnType {
     Property {
+        #[serde(deserialize_with = "crate::property::deserialize_property_type")]
         property_type: PropertyType,
     },
     ObjectType {
@@ -250,6 +1026,124 @@ pub enum FunctionReturnType {
     },
 }
 
+impl FunctionReturnType {
+    /// Type of the returned objects, for object and array-of-object return types
+    pub fn object_type(&self) -> Option<&str> {
+        match self {
+            FunctionReturnType::ObjectType { object_type } => Some(object_type),
+            FunctionReturnType::Array { element_type } => element_type.object_type(),
+            FunctionReturnType::Property { .. } => None,
+        }
+    }
+
+    /// Short label such as `double`, `object plant` or `array<object plant>`
+    pub fn label(&self) -> String {
+        match self {
+            FunctionReturnType::Property { property_type } => property_type_label(property_type),
+            FunctionReturnType::ObjectType { object_type } => format!("object {}", object_type),
+            FunctionReturnType::Array { element_type } => format!("array<{}>", element_type.label()),
+        }
+    }
+
+    /// Check a function's output against the declared return type, naming both on mismatch.
+    /// Null (no result) is accepted for any return type.
+    pub fn check_value(&self, value: &PropertyValue) -> Result<(), String> {
+        if self.accepts(value) {
+            Ok(())
+        } else {
+            Err(format!(
+                "declares return type {} but returned {}",
+                self.label(),
+                value_label(value)
+            ))
+        }
+    }
+
+    fn accepts(&self, value: &PropertyValue) -> bool {
+        match (self, value) {
+            (_, PropertyValue::Null) => true,
+            (FunctionReturnType::Property { property_type }, value) => property_type_accepts(property_type, value),
+            (FunctionReturnType::ObjectType { .. }, value) => {
+                matches!(value, PropertyValue::ObjectReference(_) | PropertyValue::String(_))
+            }
+            (FunctionReturnType::Array { element_type }, PropertyValue::Array(items)) => {
+                items.iter().all(|item| element_type.accepts(item))
+            }
+            (FunctionReturnType::Array { .. }, _) => false,
+        }
+    }
+}
+
+/// Whether values of a property type are object references, directly or as array elements
+fn holds_references(property_type: &PropertyType) -> bool {
+    match property_type {
+        PropertyType::ObjectReference | PropertyType::ObjectReferenceAlt => true,
+        PropertyType::Array { element_type } => holds_references(element_type),
+        _ => false,
+    }
+}
+
+/// Whether a value fits a property type. Scalars stored as strings (dates, references,
+/// GeoJSON) accept plain strings; maps, structs and unions are not checked.
+fn property_type_accepts(property_type: &PropertyType, value: &PropertyValue) -> bool {
+    match (property_type, value) {
+        (_, PropertyValue::Null) => true,
+        (PropertyType::String, PropertyValue::String(_)) => true,
+        (PropertyType::Integer | PropertyType::Int, PropertyValue::Integer(_)) => true,
+        (PropertyType::Double | PropertyType::Float, PropertyValue::Double(_) | PropertyValue::Integer(_)) => true,
+        (PropertyType::Boolean | PropertyType::Bool, PropertyValue::Boolean(_)) => true,
+        (PropertyType::Date, PropertyValue::Date(_) | PropertyValue::String(_)) => true,
+        (PropertyType::DateTime | PropertyType::Timestamp, PropertyValue::DateTime(_) | PropertyValue::String(_)) => true,
+        (
+            PropertyType::ObjectReference | PropertyType::ObjectReferenceAlt,
+            PropertyValue::ObjectReference(_) | PropertyValue::String(_),
+        ) => true,
+        (PropertyType::GeoJSON | PropertyType::GeoJSONAlt, PropertyValue::GeoJSON(_) | PropertyValue::String(_)) => true,
+        (PropertyType::Array { element_type }, PropertyValue::Array(items)) => {
+            items.iter().all(|item| property_type_accepts(element_type, item))
+        }
+        (PropertyType::Map { .. } | PropertyType::Object(_) | PropertyType::Union { .. }, _) => true,
+        _ => false,
+    }
+}
+
+fn property_type_label(property_type: &PropertyType) -> String {
+    match property_type {
+        PropertyType::Array { element_type } => format!("array<{}>", property_type_label(element_type)),
+        PropertyType::Map { .. } => "map".to_string(),
+        PropertyType::Object(struct_def) => format!("struct {}", struct_def.id),
+        PropertyType::Union { .. } => "union".to_string(),
+        scalar => serde_json::to_value(scalar)
+            .ok()
+            .and_then(|value| value.as_str().map(str::to_string))
+            .unwrap_or_else(|| format!("{:?}", scalar)),
+    }
+}
+
+/// Label for the kind of a value; arrays are labelled by their first element
+fn value_label(value: &PropertyValue) -> String {
+    let label = match value {
+        PropertyValue::String(_) => "string",
+        PropertyValue::Integer(_) => "integer",
+        PropertyValue::Double(_) => "double",
+        PropertyValue::Boolean(_) => "boolean",
+        PropertyValue::Date(_) => "date",
+        PropertyValue::DateTime(_) => "datetime",
+        PropertyValue::ObjectReference(_) => "object reference",
+        PropertyValue::GeoJSON(_) => "geojson",
+        PropertyValue::Array(items) => {
+            return match items.first() {
+                Some(first) => format!("array<{}>", value_label(first)),
+                None => "empty array".to_string(),
+            };
+        }
+        PropertyValue::Map(_) => "map",
+        PropertyValue::Object(_) => "struct",
+        PropertyValue::Null => "null",
+    };
+    label.to_string()
+}
+
 /// Aggregation type for function logic
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
@@ -261,7 +1155,8 @@ pub enum AggregationType {
     Max,
 }
 
-/// Function filter for link traversal
+/// Function filter for link traversal and filter steps. `operator` is a condition operator
+/// name (`equals`, `greaterthan`, `in`, ...) or one of `==`, `!=`, `>`, `>=`, `<`, `<=`.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FunctionFilter {
     pub property: String,
@@ -269,13 +1164,38 @@ pub struct FunctionFilter {
     pub value: crate::property::PropertyValue,
 }
 
+impl FunctionFilter {
+    /// The filter as a condition on the property's value
+    pub fn condition(&self) -> Result<ActionCondition, String> {
+        let operator = match self.operator.as_str() {
+            "==" => ConditionOperator::Equals,
+            "!=" => ConditionOperator::NotEquals,
+            ">" => ConditionOperator::GreaterThan,
+            ">=" => ConditionOperator::GreaterThanOrEqual,
+            "<" => ConditionOperator::LessThan,
+            "<=" => ConditionOperator::LessThanOrEqual,
+            name => serde_json::from_value(serde_json::Value::String(name.to_string()))
+                .map_err(|_| format!("Unknown filter operator '{}' on property '{}'", name, self.property))?,
+        };
+        Ok(ActionCondition {
+            property: self.property.clone(),
+            operator,
+            value: self.value.clone(),
+        })
+    }
+}
+
 /// Function logic definition
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum FunctionLogic {
+    /// Aggregate a property of the objects linked through `link_type`; inside a pipeline,
+    /// with no link type, of the objects the previous steps produced
     Aggregation {
         #[serde(rename = "linkType")]
-        link_type: String,
+        #[serde(default)]
+        #[serde(skip_serializing_if = "Option::is_none")]
+        link_type: Option<String>,
         aggregation: AggregationType,
         property: String,
     },
@@ -289,6 +1209,42 @@ pub enum FunctionLogic {
     PropertyAccess {
         property: String,
     },
+    /// Keep the objects matching every filter; only valid as a pipeline step
+    Filter {
+        filters: Vec<FunctionFilter>,
+    },
+    /// Steps run in order, starting from the source object; each traversal or filter hands
+    /// its objects to the next step, and an aggregation or property access ends the pipeline
+    /// with a value
+    Pipeline {
+        steps: Vec<FunctionLogic>,
+    },
+}
+
+impl FunctionLogic {
+    /// Name of the logic type, as written in definitions
+    pub fn kind(&self) -> &'static str {
+        match self {
+            FunctionLogic::Aggregation { .. } => "aggregation",
+            FunctionLogic::LinkTraversal { .. } => "link_traversal",
+            FunctionLogic::PropertyAccess { .. } => "property_access",
+            FunctionLogic::Filter { .. } => "filter",
+            FunctionLogic::Pipeline { .. } => "pipeline",
+        }
+    }
+    
+    /// The steps this logic runs: a pipeline's steps, or the logic itself
+    pub fn steps(&self) -> &[FunctionLogic] {
+        match self {
+            FunctionLogic::Pipeline { steps } => steps,
+            logic => std::slice::from_ref(logic),
+        }
+    }
+    
+    /// Whether the step turns objects into a value, after which no step may follow
+    pub fn produces_value(&self) -> bool {
+        matches!(self, FunctionLogic::Aggregation { .. } | FunctionLogic::PropertyAccess { .. })
+    }
 }
 
 /// Function Type definition - represents a function that returns typed data
@@ -299,6 +1255,13 @@ pub struct FunctionTypeDef {
     #[serde(rename = "displayName")]
     pub display_name: String,
     
+    /// Display names by locale (e.g. `fr`, `pt-BR`), used in place of `displayName` when
+    /// rendering for that locale
+    #[serde(rename = "displayNames")]
+    #[serde(default)]
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub display_names: HashMap<String, String>,
+    
     #[serde(default)]
     pub description: Option<String>,
     
@@ -312,20 +1275,44 @@ pub struct FunctionTypeDef {
     
     #[serde(default)]
     pub cacheable: bool,
+    
+    /// Interface this function is written against; its logic names interface properties and
+    /// runs on any implementer
+    #[serde(rename = "targetInterface")]
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target_interface: Option<String>,
 }
 
 impl FunctionTypeDef {
+    /// Display name for the given locale, falling back to `displayName`
+    pub fn display_name_for(&self, locale: Option<&str>) -> &str {
+        localized_name(&self.display_names, locale).unwrap_or(&self.display_name)
+    }
+    
     /// Validate that the function definition is valid
     pub fn validate(&self, object_type_ids: &[String], link_type_ids: &[String]) -> Result<(), String> {
+        first_error(self.load_errors(object_type_ids, link_type_ids))
+    }
+    
+    /// All unknown object or link type references in this function definition, and logic
+    /// whose steps do not fit together
+    pub fn load_errors(&self, object_type_ids: &[String], link_type_ids: &[String]) -> Vec<OntologyLoadError> {
+        let mut errors = Vec::new();
+        let mut reference = |kind: DefinitionKind, id: &String, ids: &[String], referenced_by: &str| {
+            if !ids.contains(id) {
+                errors.push(OntologyLoadError::UnknownReference {
+                    kind,
+                    id: id.clone(),
+                    referenced_by: format!("Function '{}' {}", self.id, referenced_by),
+                });
+            }
+        };
+        
         // Validate return type references exist
         match &self.return_type {
             FunctionReturnType::ObjectType { object_type } => {
-                if !object_type_ids.contains(object_type) {
-                    return Err(format!(
-                        "Function '{}' return type references unknown object type '{}'",
-                        self.id, object_type
-                    ));
-                }
+                reference(DefinitionKind::ObjectType, object_type, object_type_ids, "return type");
             }
             FunctionReturnType::Array { element_type } => {
                 // Recursively validate array element type
@@ -335,98 +1322,258 @@ impl FunctionTypeDef {
         }
         
         // Validate logic references
-        match &self.logic {
-            FunctionLogic::LinkTraversal { link_type, target_type, .. } => {
-                if !link_type_ids.contains(link_type) {
-                    return Err(format!(
-                        "Function '{}' logic references unknown link type '{}'",
-                        self.id, link_type
-                    ));
+        let pipeline = matches!(self.logic, FunctionLogic::Pipeline { .. });
+        for step in self.logic.steps() {
+            match step {
+                FunctionLogic::LinkTraversal { link_type, target_type, .. } => {
+                    reference(DefinitionKind::LinkType, link_type, link_type_ids, "logic");
+                    reference(DefinitionKind::ObjectType, target_type, object_type_ids, "logic target");
                 }
-                if !object_type_ids.contains(target_type) {
-                    return Err(format!(
-                        "Function '{}' logic references unknown target type '{}'",
-                        self.id, target_type
-                    ));
+                FunctionLogic::Aggregation { link_type: Some(link_type), .. } => {
+                    reference(DefinitionKind::LinkType, link_type, link_type_ids, "logic");
                 }
+                _ => {}
             }
-            FunctionLogic::Aggregation { link_type, .. } => {
-                if !link_type_ids.contains(link_type) {
-                    return Err(format!(
-                        "Function '{}' logic references unknown link type '{}'",
-                        self.id, link_type
+        }
+        
+        let mut invalid = |message: String| {
+            errors.push(OntologyLoadError::InvalidDefinition {
+                kind: DefinitionKind::FunctionType,
+                id: self.id.clone(),
+                message: format!("Function '{}' {}", self.id, message),
+            });
+        };
+        if pipeline && self.logic.steps().is_empty() {
+            invalid("has a pipeline with no steps".to_string());
+        }
+        let mut value_step: Option<usize> = None;
+        for (i, step) in self.logic.steps().iter().enumerate() {
+            let position = if pipeline { format!("step {} ({})", i + 1, step.kind()) } else { "logic".to_string() };
+            match step {
+                FunctionLogic::Pipeline { .. } if pipeline => {
+                    invalid(format!("{}: pipelines cannot be nested", position));
+                }
+                FunctionLogic::Filter { .. } if !pipeline => {
+                    invalid("uses a filter step outside a pipeline".to_string());
+                }
+                FunctionLogic::Aggregation { link_type: None, .. } if !pipeline => {
+                    invalid("aggregates without a linkType".to_string());
+                }
+                FunctionLogic::Aggregation { link_type: Some(link_type), .. } if pipeline => {
+                    invalid(format!(
+                        "{} aggregates objects linked through '{}'; in a pipeline, traverse the link in an earlier step",
+                        position, link_type
                     ));
                 }
+                _ => {}
+            }
+            if let Some(previous) = value_step {
+                invalid(format!(
+                    "{} needs objects, but step {} already produced a value",
+                    position,
+                    previous + 1
+                ));
+            }
+            if step.produces_value() && value_step.is_none() {
+                value_step = Some(i);
+            }
+            let filters: &[FunctionFilter] = match step {
+                FunctionLogic::Filter { filters } => filters,
+                FunctionLogic::LinkTraversal { filter: Some(filter), .. } => std::slice::from_ref(filter),
+                _ => &[],
+            };
+            for filter in filters {
+                if let Err(e) = filter.condition() {
+                    invalid(format!("{}: {}", position, e));
+                }
             }
-            _ => {}
         }
         
-        Ok(())
+        errors
     }
-}
-
-/// The runtime ontology state
-pub struct OntologyRuntime {
-    config: OntologyConfig,
-    object_types: HashMap<String, ObjectType>,
-    link_types: HashMap<String, LinkTypeDef>,
-    action_types: HashMap<String, ActionTypeDef>,
-    interfaces: HashMap<String, InterfaceDef>,
-    function_types: HashMap<String, FunctionTypeDef>,
-}
-
-impl OntologyRuntime {
-    /// Load ontology from configuration
-    pub fn from_config(config: OntologyConfig) -> Result<Self, String> {
-        let ontology_def = config.ontology.clone();
-        
-        // Validate all object types
-        let object_type_ids: Vec<String> = ontology_def.object_types.iter()
-            .map(|ot| ot.id.clone())
-            .collect();
+    
+    /// Unknown target interface, or logic referring to properties or link types the
+    /// interface does not declare
+    pub fn interface_errors(&self, interfaces: &HashMap<String, InterfaceDef>) -> Vec<OntologyLoadError> {
+        let Some(interface_id) = &self.target_interface else {
+            return Vec::new();
+        };
+        let Some(interface) = interfaces.get(interface_id) else {
+            return vec![OntologyLoadError::UnknownReference {
+                kind: DefinitionKind::Interface,
+                id: interface_id.clone(),
+                referenced_by: format!("Function '{}' target interface", self.id),
+            }];
+        };
         
-        for object_type in &ontology_def.object_types {
-            object_type.validate()?;
+        let referenced_by = format!("Function '{}' on interface '{}'", self.id, interface_id);
+        let mut errors = Vec::new();
+        // Steps up to the first traversal run on the implementer itself
+        for step in self.logic.steps() {
+            match step {
+                FunctionLogic::PropertyAccess { property } => {
+                    if !interface.properties.iter().any(|p| &p.id == property) {
+                        errors.push(OntologyLoadError::UnknownReference {
+                            kind: DefinitionKind::Property,
+                            id: property.clone(),
+                            referenced_by: referenced_by.clone(),
+                        });
+                    }
+                }
+                FunctionLogic::Filter { filters } => {
+                    for filter in filters {
+                        if !interface.properties.iter().any(|p| p.id == filter.property) {
+                            errors.push(OntologyLoadError::UnknownReference {
+                                kind: DefinitionKind::Property,
+                                id: filter.property.clone(),
+                                referenced_by: referenced_by.clone(),
+                            });
+                        }
+                    }
+                }
+                FunctionLogic::Aggregation { link_type: Some(link_type), .. } | FunctionLogic::LinkTraversal { link_type, .. }
+                    if !interface.required_link_types.contains(link_type) =>
+                {
+                    errors.push(OntologyLoadError::InvalidDefinition {
+                        kind: DefinitionKind::FunctionType,
+                        id: self.id.clone(),
+                        message: format!(
+                            "{} uses link type '{}' which is not a required link type of the interface",
+                            referenced_by, link_type
+                        ),
+                    });
+                }
+                _ => {}
+            }
+            if matches!(step, FunctionLogic::LinkTraversal { .. }) {
+                break;
+            }
         }
-        
-        // Build interfaces map first (needed for interface validation)
-        let interfaces: HashMap<String, InterfaceDef> = ontology_def.interfaces
-            .iter()
-            .cloned()
-            .map(|i| (i.id.clone(), i))
-            .collect();
-        
-        // Validate interface implementations for all object types
-        for object_type in &ontology_def.object_types {
-            object_type.validate_interface_implementations(&interfaces)?;
+        errors
+    }
+    
+    /// Resolve an interface-bound function against a concrete implementer, mapping interface
+    /// property names to its local property IDs. Functions without a target interface are
+    /// returned unchanged.
+    pub fn bind_to(&self, object_type: &ObjectType) -> Result<FunctionTypeDef, String> {
+        let Some(interface_id) = &self.target_interface else {
+            return Ok(self.clone());
+        };
+        if !object_type.implements.contains(interface_id) {
+            return Err(format!(
+                "Object type '{}' does not implement interface '{}' required by function '{}'",
+                object_type.id, interface_id, self.id
+            ));
         }
         
-        // Validate all link types
-        for link_type in &ontology_def.link_types {
-            link_type.validate(&object_type_ids)?;
+        let mut bound = self.clone();
+        bound.target_interface = None;
+        let steps = match &mut bound.logic {
+            FunctionLogic::Pipeline { steps } => steps.iter_mut().collect(),
+            logic => vec![logic],
+        };
+        for step in steps {
+            match step {
+                FunctionLogic::PropertyAccess { property } => {
+                    *property = object_type.local_property_id(interface_id, property).to_string();
+                }
+                FunctionLogic::Filter { filters } => {
+                    for filter in filters {
+                        filter.property = object_type.local_property_id(interface_id, &filter.property).to_string();
+                    }
+                }
+                FunctionLogic::LinkTraversal { .. } => break,
+                _ => {}
+            }
         }
-        
-        // Validate all interfaces
-        let interface_ids: Vec<String> = ontology_def.interfaces.iter()
-            .map(|i| i.id.clone())
-            .collect();
-        for interface in &ontology_def.interfaces {
-            interface.validate()?;
+        Ok(bound)
+    }
+}
+
+/// Convert a list of load errors into the first error's message, for `validate` callers
+fn first_error(errors: Vec<OntologyLoadError>) -> Result<(), String> {
+    match errors.into_iter().next() {
+        Some(error) => Err(error.to_string()),
+        None => Ok(()),
+    }
+}
+
+/// One `DuplicateId` error for every ID that appears more than once
+fn duplicate_ids<'a>(
+    ids: impl Iterator<Item = &'a str>,
+    kind: DefinitionKind,
+    scope: Option<String>,
+) -> Vec<OntologyLoadError> {
+    let mut seen = std::collections::HashSet::new();
+    let mut reported = std::collections::HashSet::new();
+    let mut errors = Vec::new();
+    for id in ids {
+        if !seen.insert(id) && reported.insert(id) {
+            errors.push(OntologyLoadError::DuplicateId {
+                kind,
+                id: id.to_string(),
+                scope: scope.clone(),
+            });
         }
-        
-        // Validate all function types
-        let link_type_ids: Vec<String> = ontology_def.link_types.iter()
-            .map(|lt| lt.id.clone())
+    }
+    errors
+}
+
+/// Strict types backed by a datasource that also backs other types: its rows carry the
+/// other types' columns, which strict mode would reject
+fn strict_datasource_warnings(object_types: &[ObjectType]) -> Vec<String> {
+    let mut warnings = Vec::new();
+    for object_type in object_types.iter().filter(|ot| ot.strict_properties) {
+        let Some(datasource) = &object_type.backing_datasource else {
+            continue;
+        };
+        let sharing: Vec<&str> = object_types
+            .iter()
+            .filter(|other| other.id != object_type.id && other.backing_datasource.as_ref() == Some(datasource))
+            .map(|other| other.id.as_str())
             .collect();
-        for function_type in &ontology_def.function_types {
-            function_type.validate(&object_type_ids, &link_type_ids)?;
+        if !sharing.is_empty() {
+            warnings.push(format!(
+                "Object type '{}' has strict properties but its datasource '{}' also backs {}, so it carries extra columns",
+                object_type.id,
+                datasource,
+                sharing.iter().map(|id| format!("'{}'", id)).collect::<Vec<_>>().join(", ")
+            ));
+        }
+    }
+    warnings
+}
+
+/// The runtime ontology state
+pub struct OntologyRuntime {
+    config: OntologyConfig,
+    object_types: HashMap<String, ObjectType>,
+    link_types: HashMap<String, LinkTypeDef>,
+    action_types: HashMap<String, ActionTypeDef>,
+    interfaces: HashMap<String, InterfaceDef>,
+    function_types: HashMap<String, FunctionTypeDef>,
+    warnings: Vec<String>,
+}
+
+impl OntologyRuntime {
+    /// Load ontology from configuration
+    pub fn from_config(config: OntologyConfig) -> Result<Self, OntologyLoadErrors> {
+        let ontology_def = config.ontology.clone();
+        let errors = Self::validate_ontology(&ontology_def);
+        if !errors.is_empty() {
+            return Err(OntologyLoadErrors(errors));
         }
         
-        // Build hash maps for efficient lookup
+        // Build hash maps for efficient lookup; interfaces and their implementers include
+        // everything inherited through `extends`
+        let (interfaces, _) = crate::interface::InterfaceValidator::flatten(
+            &ontology_def.interfaces,
+            ontology_def.interface_compatibility,
+        );
+        
         let object_types: HashMap<String, ObjectType> = ontology_def.object_types
             .iter()
-            .cloned()
-            .map(|ot| (ot.id.clone(), ot))
+            .map(|ot| (ot.id.clone(), ot.with_inherited_interfaces(&interfaces)))
             .collect();
         
         let link_types: HashMap<String, LinkTypeDef> = ontology_def.link_types
@@ -447,6 +1594,8 @@ impl OntologyRuntime {
             .map(|ft| (ft.id.clone(), ft))
             .collect();
         
+        let warnings = strict_datasource_warnings(&ontology_def.object_types);
+        
         Ok(Self {
             config: OntologyConfig { ontology: ontology_def },
             object_types,
@@ -454,23 +1603,132 @@ impl OntologyRuntime {
             action_types,
             interfaces,
             function_types,
+            warnings,
         })
     }
     
+    /// Every problem in a definition: duplicate IDs, invalid properties, and references
+    /// between object types, link types, interfaces, functions and actions that do not
+    /// resolve. Every problem is collected so a single load reports all of them.
+    pub fn validate_ontology(ontology_def: &OntologyDef) -> Vec<OntologyLoadError> {
+        let mut errors = Vec::new();
+        
+        // Check for duplicate IDs within each kind of definition
+        errors.extend(duplicate_ids(
+            ontology_def.object_types.iter().map(|ot| ot.id.as_str()),
+            DefinitionKind::ObjectType,
+            None,
+        ));
+        errors.extend(duplicate_ids(
+            ontology_def.link_types.iter().map(|lt| lt.id.as_str()),
+            DefinitionKind::LinkType,
+            None,
+        ));
+        errors.extend(duplicate_ids(
+            ontology_def.action_types.iter().map(|at| at.id.as_str()),
+            DefinitionKind::ActionType,
+            None,
+        ));
+        errors.extend(duplicate_ids(
+            ontology_def.interfaces.iter().map(|i| i.id.as_str()),
+            DefinitionKind::Interface,
+            None,
+        ));
+        errors.extend(duplicate_ids(
+            ontology_def.function_types.iter().map(|ft| ft.id.as_str()),
+            DefinitionKind::FunctionType,
+            None,
+        ));
+        
+        // Validate all object types
+        let object_type_ids: Vec<String> = ontology_def.object_types.iter()
+            .map(|ot| ot.id.clone())
+            .collect();
+        
+        for object_type in &ontology_def.object_types {
+            errors.extend(object_type.load_errors());
+            errors.extend(object_type.reference_errors(&object_type_ids));
+        }
+        
+        // Resolve interface inheritance first: implementers must satisfy the flattened contracts
+        let (interfaces, inheritance_errors) = crate::interface::InterfaceValidator::flatten(
+            &ontology_def.interfaces,
+            ontology_def.interface_compatibility,
+        );
+        errors.extend(inheritance_errors);
+        
+        // Validate interface implementations for all object types
+        for object_type in &ontology_def.object_types {
+            errors.extend(object_type.interface_errors(
+                &interfaces,
+                &ontology_def.link_types,
+                ontology_def.interface_compatibility,
+            ));
+        }
+        
+        // Validate all link types
+        for link_type in &ontology_def.link_types {
+            errors.extend(link_type.load_errors(&object_type_ids));
+        }
+        
+        // Validate all interfaces
+        for interface in &ontology_def.interfaces {
+            errors.extend(interface.load_errors());
+        }
+        
+        // Validate all function types
+        let link_type_ids: Vec<String> = ontology_def.link_types.iter()
+            .map(|lt| lt.id.clone())
+            .collect();
+        for function_type in &ontology_def.function_types {
+            errors.extend(function_type.load_errors(&object_type_ids, &link_type_ids));
+            errors.extend(function_type.interface_errors(&interfaces));
+        }
+        for action_type in &ontology_def.action_types {
+            errors.extend(action_type.load_errors(&object_type_ids, &link_type_ids));
+            errors.extend(action_type.interface_errors(&interfaces));
+        }
+        errors.extend(crate::validators::unregistered_validator_errors(ontology_def));
+        
+        errors
+    }
+    
+    /// `validate_ontology` for callers that only want the first problem's message
+    pub fn validate_definition(ontology_def: &OntologyDef) -> Result<(), String> {
+        first_error(Self::validate_ontology(ontology_def))
+    }
+    
+    /// Load a base ontology with environment overlays applied in order on top of it
+    pub fn from_layers(
+        base: OntologyConfig,
+        overlays: &[crate::overlay::OntologyOverlay],
+    ) -> Result<Self, OntologyLoadErrors> {
+        let ontology = crate::overlay::apply_overlays(base.ontology, overlays)?;
+        Self::from_config(OntologyConfig { ontology })
+    }
+    
+    /// Problems that do not stop the ontology from loading but likely need attention
+    pub fn warnings(&self) -> &[String] {
+        &self.warnings
+    }
+    
     /// Load ontology from YAML file
-    pub fn from_yaml(content: &str) -> Result<Self, String> {
-        let config: OntologyConfig = serde_yaml::from_str(content)
-            .map_err(|e| format!("Failed to parse YAML: {}", e))?;
-        Self::from_config(config)
+    pub fn from_yaml(content: &str) -> Result<Self, OntologyLoadErrors> {
+        Self::from_config(OntologyConfig::from_yaml(content)?)
     }
     
     /// Load ontology from JSON file
-    pub fn from_json(content: &str) -> Result<Self, String> {
+    pub fn from_json(content: &str) -> Result<Self, OntologyLoadErrors> {
         let config: OntologyConfig = serde_json::from_str(content)
-            .map_err(|e| format!("Failed to parse JSON: {}", e))?;
+            .map_err(|e| OntologyLoadError::from_json_error(&e))?;
         Self::from_config(config)
     }
     
+    /// The definition this runtime was built from
+    pub fn definition(&self) -> &OntologyDef {
+        &self.config.ontology
+    }
+    
     /// Get an object type by ID
     pub fn get_object_type(&self, id: &str) -> Option<&ObjectType> {
         self.object_types.get(id)
@@ -535,11 +1793,13 @@ mod tests {
         ObjectType {
             id: "test_object".to_string(),
             display_name: "Test Object".to_string(),
+            display_names: HashMap::new(),
             primary_key: "id".to_string(),
             properties: vec![
                 Property {
                     id: "id".to_string(),
                     display_name: None,
+                    display_names: HashMap::new(),
                     property_type: PropertyType::String,
                     required: true,
                     default: None,
@@ -553,10 +1813,16 @@ mod tests {
                     deprecated: None,
                     statistics: None,
                     model_binding: None,
+                    display_order: None,
+                    indexing: None,
+                    reference_target: None,
+                    geo: None,
+                    custom_validator: None,
                 },
                 Property {
                     id: "name".to_string(),
                     display_name: None,
+                    display_names: HashMap::new(),
                     property_type: PropertyType::String,
                     required: false,
                     default: None,
@@ -570,12 +1836,24 @@ mod tests {
                     deprecated: None,
                     statistics: None,
                     model_binding: None,
+                    display_order: None,
+                    indexing: None,
+                    reference_target: None,
+                    geo: None,
+                    custom_validator: None,
                 },
             ],
             backing_datasource: None,
             title_key: Some("name".to_string()),
             implements: vec![],
+            interface_mappings: HashMap::new(),
             schema_evolution: None,
+            default_sort: None,
+            computed_properties: Vec::new(),
+            dedup_rules: None,
+            archival_policy: None,
+            strict_properties: false,
+            property_groups: Vec::new(),
         }
     }
     
@@ -592,6 +1870,97 @@ mod tests {
         assert!(obj_type.validate().is_err());
     }
     
+    #[test]
+    fn test_object_type_default_sort_validation() {
+        let mut obj_type = create_test_object_type();
+        obj_type.default_sort = Some(DefaultSort { property: "name".to_string(), ascending: false });
+        assert!(obj_type.validate().is_ok());
+        
+        obj_type.default_sort = Some(DefaultSort { property: "missing".to_string(), ascending: true });
+        assert!(obj_type.validate().is_err());
+        
+        obj_type.properties[1].property_type = PropertyType::Array { element_type: Box::new(PropertyType::String) };
+        obj_type.default_sort = Some(DefaultSort { property: "name".to_string(), ascending: true });
+        assert!(obj_type.validate().is_err());
+    }
+    
+    #[test]
+    fn test_indexing_hints() {
+        let mut obj_type = create_test_object_type();
+        obj_type.properties[1].indexing = Some(IndexingHint::TextOnly);
+        assert!(obj_type.validate().is_ok());
+        assert!(obj_type.check_filterable("name").is_ok());
+        assert!(obj_type.check_sortable("name").unwrap_err().contains("text_only"));
+        
+        obj_type.default_sort = Some(DefaultSort { property: "name".to_string(), ascending: true });
+        assert!(obj_type.validate().is_err());
+        obj_type.default_sort = None;
+        
+        obj_type.properties[1].indexing = Some(IndexingHint::NotIndexed);
+        assert_eq!(
+            obj_type.check_filterable("name").unwrap_err(),
+            "Cannot filter on property 'name' of object type 'test_object': it has indexing 'not_indexed'"
+        );
+        assert!(obj_type.check_filterable("_metadata").is_ok());
+        
+        // Keyword/text-only need a string, and the primary key must stay sortable
+        obj_type.properties[0].indexing = Some(IndexingHint::NotIndexed);
+        assert!(obj_type.validate().is_err());
+        obj_type.properties[0].indexing = None;
+        obj_type.properties[1].property_type = PropertyType::Integer;
+        obj_type.properties[1].indexing = Some(IndexingHint::KeywordOnly);
+        assert!(obj_type.validate().is_err());
+    }
+    
+    #[test]
+    fn test_dedup_rules() {
+        let mut obj_type = create_test_object_type();
+        obj_type.dedup_rules = Some(serde_json::from_value(serde_json::json!([
+            {
+                "match": [{"property": "name", "comparator": {"type": "fuzzy", "threshold": 0.9}}],
+                "survivorship": {"policy": "prefer_newest", "timestamp_property": "name"}
+            }
+        ])).unwrap());
+        assert!(obj_type.validate().is_ok());
+        
+        let rules = obj_type.dedup_rules.as_mut().unwrap();
+        rules[0].match_on[0].comparator = crate::dedup::MatchComparator::Fuzzy { threshold: 1.5 };
+        assert!(obj_type.validate().unwrap_err().contains("fuzzy threshold"));
+        
+        let rules = obj_type.dedup_rules.as_mut().unwrap();
+        rules[0].match_on[0].comparator = crate::dedup::MatchComparator::Normalized;
+        rules[0].survivorship = crate::dedup::Survivorship::PreferSourcePriority {
+            source_property: "source".to_string(),
+            priority: vec!["crm".to_string()],
+        };
+        assert!(obj_type.validate().unwrap_err().contains("survivorship property 'source'"));
+    }
+    
+    #[test]
+    fn test_effective_default_sort_falls_back_to_primary_key() {
+        let mut obj_type = create_test_object_type();
+        assert_eq!(obj_type.effective_default_sort(), DefaultSort { property: "id".to_string(), ascending: true });
+        
+        obj_type.default_sort = Some(DefaultSort::parse("-name").unwrap());
+        assert_eq!(obj_type.effective_default_sort(), DefaultSort { property: "name".to_string(), ascending: false });
+    }
+    
+    #[test]
+    fn test_default_sort_parse() {
+        assert_eq!(DefaultSort::parse("name").unwrap(), DefaultSort { property: "name".to_string(), ascending: true });
+        assert_eq!(DefaultSort::parse("name desc").unwrap(), DefaultSort { property: "name".to_string(), ascending: false });
+        assert!(DefaultSort::parse("name sideways").is_err());
+        assert!(DefaultSort::parse("").is_err());
+    }
+    
+    #[test]
+    fn test_properties_in_display_order() {
+        let mut obj_type = create_test_object_type();
+        obj_type.properties[1].display_order = Some(1);
+        let ordered: Vec<&str> = obj_type.properties_in_display_order().iter().map(|p| p.id.as_str()).collect();
+        assert_eq!(ordered, vec!["name", "id"]);
+    }
+    
     #[test]
     fn test_object_type_get_property() {
         let obj_type = create_test_object_type();
@@ -653,11 +2022,13 @@ ontology:
         let link_type = LinkTypeDef {
             id: "test_link".to_string(),
             display_name: None,
+            display_names: HashMap::new(),
             source: "source_type".to_string(),
             target: "target_type".to_string(),
             cardinality: LinkCardinality::OneToMany,
             properties: vec![],
             bidirectional: false,
+            on_delete: CascadeDeleteBehavior::NoAction,
         };
         
         // Should fail validation - source type doesn't exist
@@ -666,4 +2037,563 @@ ontology:
         // Should pass validation
         assert!(link_type.validate(&["source_type".to_string(), "target_type".to_string()]).is_ok());
     }
+    
+    #[test]
+    fn test_load_reports_all_errors() {
+        let yaml = r#"
+ontology:
+  objectTypes:
+    - id: person
+      displayName: Person
+      primaryKey: missing
+      properties:
+        - id: id
+          type: string
+        - id: id
+          type: string
+    - id: person
+      displayName: Person Again
+      primaryKey: id
+      properties:
+        - id: id
+          type: string
+  linkTypes:
+    - id: knows
+      source: person
+      target: robot
+      cardinality: MANY_TO_MANY
+"#;
+        let errors = OntologyRuntime::from_yaml(yaml).err().unwrap();
+        assert_eq!(errors.len(), 4);
+        assert!(errors.iter().any(|e| matches!(e, OntologyLoadError::DuplicateId {
+            kind: DefinitionKind::ObjectType, id, scope: None,
+        } if id == "person")));
+        assert!(errors.iter().any(|e| matches!(e, OntologyLoadError::DuplicateId {
+            kind: DefinitionKind::Property, scope: Some(_), ..
+        })));
+        assert!(errors.iter().any(|e| matches!(e, OntologyLoadError::UnknownReference {
+            kind: DefinitionKind::Property, id, ..
+        } if id == "missing")));
+        assert!(errors.iter().any(|e| matches!(e, OntologyLoadError::UnknownReference {
+            kind: DefinitionKind::ObjectType, id, ..
+        } if id == "robot")));
+        assert!(errors.to_string().starts_with("4 ontology errors:"));
+    }
+    
+    #[test]
+    fn test_action_references_are_validated() {
+        let yaml = r#"
+ontology:
+  objectTypes:
+    - id: plant
+      displayName: Plant
+      primaryKey: id
+      properties:
+        - id: id
+          type: string
+  linkTypes: []
+  actionTypes:
+    - id: retire_plant
+      displayName: Retire Plant
+      parameters:
+        - id: plant_id
+          type: string
+      logic:
+        - operation: update_object
+          type: plant
+          properties:
+            properties:
+              id: "{{plant_id}}"
+              note: "retired by {{ user }}"
+        - operation: create_link
+          linkType: replaced_by
+          from: "{{plant_id}}"
+          to: "{{replacement}}"
+        - operation: create_object
+          type: turbine
+      side_effects:
+        - type: webhook
+          config:
+            properties:
+              url: "https://hooks.example/{{plant}}"
+"#;
+        let def = OntologyConfig::from_yaml(yaml).unwrap().ontology;
+        let errors = OntologyRuntime::validate_ontology(&def);
+        let unknown: Vec<(DefinitionKind, &str, &str)> = errors
+            .iter()
+            .map(|e| match e {
+                OntologyLoadError::UnknownReference { kind, id, referenced_by } => (*kind, id.as_str(), referenced_by.as_str()),
+                other => panic!("unexpected error {:?}", other),
+            })
+            .collect();
+        assert_eq!(
+            unknown,
+            vec![
+                (DefinitionKind::Parameter, "user", "Action 'retire_plant' operation 1"),
+                (DefinitionKind::LinkType, "replaced_by", "Action 'retire_plant' operation 2"),
+                (DefinitionKind::Parameter, "replacement", "Action 'retire_plant' operation 2"),
+                (DefinitionKind::ObjectType, "turbine", "Action 'retire_plant' operation 3"),
+                (DefinitionKind::Parameter, "plant", "Action 'retire_plant' webhook side effect"),
+            ]
+        );
+        assert_eq!(
+            OntologyRuntime::validate_definition(&def).unwrap_err(),
+            "Action 'retire_plant' operation 1 references unknown parameter 'user'"
+        );
+        assert_eq!(OntologyRuntime::from_yaml(yaml).err().unwrap().len(), 5);
+    }
+    
+    #[test]
+    fn test_parse_error_keeps_location() {
+        let yaml = "ontology:\n  objectTypes:\n    - id: [unclosed\n";
+        let errors = OntologyRuntime::from_yaml(yaml).err().unwrap();
+        match &errors.errors()[0] {
+            OntologyLoadError::ParseError { format, line, .. } => {
+                assert_eq!(format, "YAML");
+                assert!(line.is_some());
+            }
+            other => panic!("expected a parse error, got {:?}", other),
+        }
+        
+        let errors = OntologyRuntime::from_json("{\n  \"ontology\": {\n    \"objectTypes\": 5\n  }\n}").err().unwrap();
+        assert!(matches!(&errors.errors()[0], OntologyLoadError::ParseError { line: Some(3), .. }));
+    }
+    
+    #[test]
+    fn test_validate_object_reports_all_violations() {
+        let mut object_type = create_test_object_type();
+        object_type.properties[1].required = true;
+        object_type.properties[1].default = Some(PropertyValue::String("unnamed".to_string()));
+        
+        let mut properties = PropertyMap::new();
+        properties.insert("name".to_string(), PropertyValue::Integer(5));
+        let errors = object_type.validate_object(&properties).unwrap_err();
+        assert_eq!(errors.len(), 2, "{:?}", errors);
+        
+        let mut properties = PropertyMap::new();
+        properties.insert("id".to_string(), PropertyValue::String("a".to_string()));
+        assert!(object_type.validate_object(&properties).is_err());
+        object_type.apply_defaults(&mut properties);
+        assert_eq!(properties.get("name"), Some(&PropertyValue::String("unnamed".to_string())));
+        assert!(object_type.validate_object(&properties).is_ok());
+    }
+    
+    #[test]
+    fn test_reference_target_validation() {
+        let yaml = r#"
+ontology:
+  objectTypes:
+    - id: "person"
+      displayName: "Person"
+      primaryKey: "id"
+      properties:
+        - id: "id"
+          type: "string"
+        - id: "employer"
+          type: "object_reference"
+          referenceTarget: "company"
+        - id: "friend"
+          type: "object_reference"
+  linkTypes: []
+"#;
+        let errors = OntologyRuntime::from_yaml(yaml).err().unwrap();
+        assert!(errors.errors().iter().any(|e| matches!(e, OntologyLoadError::UnknownReference {
+            kind: DefinitionKind::ObjectType, id, ..
+        } if id == "company")));
+        
+        let yaml = yaml.replace("  linkTypes: []", "    - id: \"company\"\n      displayName: \"Company\"\n      primaryKey: \"id\"\n      properties:\n        - id: \"id\"\n          type: \"string\"\n  linkTypes: []");
+        let ontology = OntologyRuntime::from_yaml(&yaml).unwrap();
+        let person = ontology.get_object_type("person").unwrap();
+        let employer = person.get_property("employer").unwrap();
+        let friend = person.get_property("friend").unwrap();
+        let exists = |object_type: &str, object_id: &str| object_type == "company" && object_id == "c1";
+        let reference = |s: &str| PropertyValue::ObjectReference(s.to_string());
+        
+        // Both forms resolve to the declared target
+        assert!(employer.validate_value_with_reference_check(&reference("c1"), Some(&exists)).is_ok());
+        assert!(employer.validate_value_with_reference_check(&reference("company:c1"), Some(&exists)).is_ok());
+        assert!(employer.validate_value_with_reference_check(&reference("c2"), Some(&exists)).is_err());
+        assert!(employer.validate_value(&reference("person:p1")).is_err());
+        
+        // Without a declared target only the prefixed form can be checked
+        assert!(friend.validate_value(&reference("p1")).is_ok());
+        assert!(friend.validate_value_with_reference_check(&reference("p1"), Some(&exists)).is_err());
+        assert!(friend.validate_value_with_reference_check(&reference("company:c1"), Some(&exists)).is_ok());
+        
+        let bad_type = yaml.replace("          referenceTarget: \"company\"", "          referenceTarget: \"company\"\n        - id: \"nickname\"\n          type: \"string\"\n          referenceTarget: \"company\"");
+        let errors = OntologyRuntime::from_yaml(&bad_type).err().unwrap();
+        assert!(matches!(&errors.errors()[0], OntologyLoadError::InvalidPropertyType { property, .. } if property == "nickname"));
+    }
+
+    #[test]
+    fn test_projected_geometries() {
+        let yaml = r#"
+ontology:
+  objectTypes:
+    - id: "station"
+      displayName: "Station"
+      primaryKey: "id"
+      properties:
+        - id: "id"
+          type: "string"
+        - id: "location"
+          type: "geojson"
+          geo:
+            crs: "EPSG:3857"
+            validation: "strict"
+  linkTypes: []
+"#;
+        let ontology = OntologyRuntime::from_yaml(yaml).unwrap();
+        let station = ontology.get_object_type("station").unwrap();
+        let point = |x: f64, y: f64| PropertyValue::GeoJSON(format!(r#"{{"type":"Point","coordinates":[{},{}]}}"#, x, y));
+
+        // Web Mercator meters for Boston and New York City
+        let mut boston = PropertyMap::new();
+        boston.insert("id".to_string(), PropertyValue::String("bos".to_string()));
+        boston.insert("location".to_string(), point(-7910240.6, 5215074.2));
+        let mut nyc = boston.clone();
+        nyc.insert("location".to_string(), point(-8238310.2, 4970071.6));
+
+        // Unprojected meters are out of WGS84 range and rejected in strict mode
+        let errors = station.validate_object(&boston).unwrap_err();
+        assert!(errors[0].contains("outside longitude"), "{:?}", errors);
+
+        station.reproject_geometries(&mut boston).unwrap();
+        station.reproject_geometries(&mut nyc).unwrap();
+        assert!(station.validate_object(&boston).is_ok());
+        let geometry = |properties: &PropertyMap| match properties.get("location") {
+            Some(PropertyValue::GeoJSON(gj)) => serde_json::from_str::<serde_json::Value>(gj).unwrap(),
+            other => panic!("{:?}", other),
+        };
+        let coordinates = geometry(&boston)["coordinates"].clone();
+        assert!((coordinates[0].as_f64().unwrap() - -71.0589).abs() < 1e-4);
+        assert!((coordinates[1].as_f64().unwrap() - 42.3601).abs() < 1e-4);
+        let meters = crate::geo::distance_meters(&geometry(&boston), &geometry(&nyc)).unwrap();
+        assert!((meters - 306_000.0).abs() < 2_000.0, "{}", meters);
+
+        // Without a declared CRS out-of-range coordinates are only warned about
+        let lenient = yaml.replace("          geo:\n            crs: \"EPSG:3857\"\n            validation: \"strict\"\n", "");
+        let ontology = OntologyRuntime::from_yaml(&lenient).unwrap();
+        let mut raw = PropertyMap::new();
+        raw.insert("id".to_string(), PropertyValue::String("bos".to_string()));
+        raw.insert("location".to_string(), point(-7910240.6, 5215074.2));
+        assert!(ontology.get_object_type("station").unwrap().validate_object(&raw).is_ok());
+
+        let unknown_crs = yaml.replace("EPSG:3857", "EPSG:27700");
+        let errors = OntologyRuntime::from_yaml(&unknown_crs).err().unwrap();
+        assert!(matches!(&errors.errors()[0], OntologyLoadError::InvalidPropertyType { property, message, .. }
+            if property == "location" && message.contains("EPSG:27700")));
+    }
+
+    #[test]
+    fn test_geometry_types_constraint() {
+        let yaml = r#"
+ontology:
+  objectTypes:
+    - id: "tract"
+      displayName: "Tract"
+      primaryKey: "id"
+      properties:
+        - id: "id"
+          type: "string"
+        - id: "centroid"
+          type: "geojson"
+          validation:
+            geometryTypes: ["Point", "MultiPoint"]
+        - id: "boundary"
+          type: "geojson"
+          validation:
+            geometry_types: ["Polygon", "MultiPolygon"]
+  linkTypes: []
+"#;
+        let ontology = OntologyRuntime::from_yaml(yaml).unwrap();
+        let tract = ontology.get_object_type("tract").unwrap();
+        let polygon = r#"{"type":"Polygon","coordinates":[[[0,0],[1,0],[1,1],[0,0]]]}"#;
+        let point = r#"{"type":"Point","coordinates":[0.5,0.25]}"#;
+        let object = |centroid: &str, boundary: &str| {
+            let mut properties = PropertyMap::new();
+            properties.insert("id".to_string(), PropertyValue::String("t1".to_string()));
+            properties.insert("centroid".to_string(), PropertyValue::GeoJSON(centroid.to_string()));
+            properties.insert("boundary".to_string(), PropertyValue::GeoJSON(boundary.to_string()));
+            properties
+        };
+        assert!(tract.validate_object(&object(point, polygon)).is_ok());
+
+        let errors = tract.validate_object(&object(polygon, polygon)).unwrap_err();
+        assert!(errors[0].contains("'centroid' only accepts Point or MultiPoint geometries, got Polygon"), "{:?}", errors);
+
+        // Every feature of a collection is checked
+        let features = format!(
+            r#"{{"type":"FeatureCollection","features":[{{"type":"Feature","properties":{{}},"geometry":{}}},{{"type":"Feature","properties":{{}},"geometry":{}}}]}}"#,
+            polygon, point
+        );
+        let errors = tract.validate_object(&object(point, &features)).unwrap_err();
+        assert!(errors[0].contains("got Point"), "{:?}", errors);
+
+        let unknown = yaml.replace("\"MultiPoint\"", "\"Circle\"");
+        let errors = OntologyRuntime::from_yaml(&unknown).err().unwrap();
+        assert!(matches!(&errors.errors()[0], OntologyLoadError::InvalidPropertyType { property, message, .. }
+            if property == "centroid" && message.contains("'Circle'")));
+        let not_geojson = yaml.replace("- id: \"centroid\"\n          type: \"geojson\"", "- id: \"centroid\"\n          type: \"string\"");
+        let errors = OntologyRuntime::from_yaml(&not_geojson).err().unwrap();
+        assert!(matches!(&errors.errors()[0], OntologyLoadError::InvalidPropertyType { message, .. }
+            if message.contains("only applies to GeoJSON")));
+    }
+
+    #[test]
+    fn test_invalid_validation_rules_rejected_at_load() {
+        let yaml = r#"
+ontology:
+  objectTypes:
+    - id: "plate"
+      displayName: "Plate"
+      primaryKey: "number"
+      properties:
+        - id: "number"
+          type: "string"
+          validation:
+            pattern: "^[A-Z]{2}-\\d{4}$"
+  linkTypes: []
+"#;
+        assert!(OntologyRuntime::from_yaml(yaml).is_ok());
+
+        let invalid = yaml.replace("{4}$", "{4$");
+        let errors = OntologyRuntime::from_yaml(&invalid).err().unwrap();
+        assert!(matches!(&errors.errors()[0], OntologyLoadError::InvalidPropertyType { object_type, property, message }
+            if object_type == "plate" && property == "number" && message.contains("invalid validation pattern")));
+
+        // Date bounds only apply to dates
+        let bounded = yaml.replace("pattern: \"^[A-Z]{2}-\\\\d{4}$\"", "min_date: \"2000-01-01\"");
+        let errors = OntologyRuntime::from_yaml(&bounded).err().unwrap();
+        assert!(matches!(&errors.errors()[0], OntologyLoadError::InvalidPropertyType { message, .. }
+            if message.contains("only apply to date")));
+    }
+
+    #[test]
+    fn test_function_return_type_check_value() {
+        let plants = FunctionReturnType::Array {
+            element_type: Box::new(FunctionReturnType::ObjectType { object_type: "plant".to_string() }),
+        };
+        assert_eq!(plants.object_type(), Some("plant"));
+        let refs = PropertyValue::Array(vec![
+            PropertyValue::ObjectReference("p1".to_string()),
+            PropertyValue::ObjectReference("p2".to_string()),
+        ]);
+        assert!(plants.check_value(&refs).is_ok());
+        assert!(plants.check_value(&PropertyValue::Null).is_ok());
+        assert_eq!(
+            plants.check_value(&PropertyValue::Integer(3)).unwrap_err(),
+            "declares return type array<object plant> but returned integer"
+        );
+        assert_eq!(
+            plants.check_value(&PropertyValue::Array(vec![PropertyValue::Double(1.5)])).unwrap_err(),
+            "declares return type array<object plant> but returned array<double>"
+        );
+        
+        let total = FunctionReturnType::Property { property_type: PropertyType::Double };
+        assert_eq!(total.object_type(), None);
+        assert!(total.check_value(&PropertyValue::Integer(2)).is_ok());
+        assert_eq!(
+            total.check_value(&refs).unwrap_err(),
+            "declares return type double but returned array<object reference>"
+        );
+    }
+    
+    #[test]
+    fn test_pipeline_function_from_yaml() {
+        let yaml = r#"
+ontology:
+  objectTypes:
+    - id: company
+      displayName: Company
+      primaryKey: id
+      properties:
+        - id: id
+          type: string
+    - id: employee
+      displayName: Employee
+      primaryKey: id
+      properties:
+        - id: id
+          type: string
+        - id: status
+          type: string
+        - id: salary
+          type: double
+  linkTypes:
+    - id: worksAt
+      source: employee
+      target: company
+      cardinality: MANY_TO_ONE
+  functionTypes:
+    - id: active_average_salary
+      displayName: Average Salary of Active Employees
+      parameters:
+        - id: company
+          type: object_reference
+          required: true
+      returnType:
+        type: property
+        property_type: double
+      logic:
+        type: pipeline
+        steps:
+          - type: link_traversal
+            linkType: worksAt
+            targetType: employee
+          - type: filter
+            filters:
+              - property: status
+                operator: "=="
+                value: active
+          - type: aggregation
+            aggregation: avg
+            property: salary
+"#;
+        let ontology = OntologyRuntime::from_yaml(yaml).unwrap();
+        let function = ontology.get_function_type("active_average_salary").unwrap();
+        let steps = function.logic.steps();
+        assert_eq!(steps.iter().map(|s| s.kind()).collect::<Vec<_>>(), vec!["link_traversal", "filter", "aggregation"]);
+        let FunctionLogic::Filter { filters } = &steps[1] else {
+            panic!("second step should be a filter");
+        };
+        assert!(matches!(filters[0].condition().unwrap().operator, ConditionOperator::Equals));
+        assert!(matches!(&steps[2], FunctionLogic::Aggregation { link_type: None, .. }));
+        
+        let load_error = |from: &str, to: &str| {
+            let errors = OntologyRuntime::from_yaml(&yaml.replace(from, to)).err().unwrap();
+            errors.errors()[0].to_string()
+        };
+        // Steps after a value, unknown references in steps, and bad operators are load errors
+        let after_value = load_error(
+            "            property: salary\n",
+            "            property: salary\n          - type: aggregation\n            aggregation: count\n            property: id\n",
+        );
+        assert_eq!(
+            after_value,
+            "Function 'active_average_salary' step 4 (aggregation) needs objects, but step 3 already produced a value"
+        );
+        assert_eq!(
+            load_error("linkType: worksAt", "linkType: employs"),
+            "Function 'active_average_salary' logic references unknown link type 'employs'"
+        );
+        assert!(load_error("operator: \"==\"", "operator: \"~\"").contains("Unknown filter operator '~'"));
+        assert!(load_error("aggregation: avg", "aggregation: avg\n            linkType: worksAt")
+            .contains("step 3 (aggregation) aggregates objects linked through 'worksAt'"));
+    }
+    
+    #[test]
+    fn test_strict_properties() {
+        let yaml = r#"
+ontology:
+  objectTypes:
+    - id: city
+      displayName: City
+      primaryKey: id
+      backingDatasource: places
+      strictProperties: true
+      properties:
+        - id: id
+          type: string
+        - id: population
+          type: integer
+    - id: town
+      displayName: Town
+      primaryKey: id
+      backingDatasource: places
+      properties:
+        - id: id
+          type: string
+  linkTypes: []
+"#;
+        let ontology = OntologyRuntime::from_yaml(yaml).unwrap();
+        let city = ontology.get_object_type("city").unwrap();
+        assert!(city.strict_properties);
+        assert!(!ontology.get_object_type("town").unwrap().strict_properties);
+        assert_eq!(ontology.warnings().len(), 1);
+        assert!(ontology.warnings()[0].contains("datasource 'places' also backs 'town'"));
+        
+        let mut properties = PropertyMap::new();
+        properties.insert("id".to_string(), PropertyValue::String("c1".to_string()));
+        properties.insert("popluation".to_string(), PropertyValue::Integer(5));
+        properties.insert("mayor".to_string(), PropertyValue::String("x".to_string()));
+        properties.insert("_acl".to_string(), PropertyValue::Array(Vec::new()));
+        assert_eq!(city.unknown_properties(&properties), vec!["mayor".to_string(), "popluation".to_string()]);
+    }
+
+    #[test]
+    fn test_property_group_validation() {
+        let yaml = r#"
+ontology:
+  objectTypes:
+    - id: tract
+      displayName: Tract
+      primaryKey: geoid
+      properties:
+        - id: geoid
+          type: string
+        - id: population
+          type: integer
+        - id: median_income
+          type: double
+        - id: geometry
+          type: geojson
+      propertyGroups:
+        - id: demographics
+          displayName: Demographics
+          properties: [population, median_income]
+          collapsedByDefault: true
+        - id: geometry
+          displayName: Geometry
+          properties: [geometry]
+  linkTypes: []
+"#;
+        let ontology = OntologyRuntime::from_yaml(yaml).unwrap();
+        assert!(ontology.get_object_type("tract").unwrap().property_groups[0].collapsed_by_default);
+
+        let misspelled = yaml.replace("properties: [geometry]", "properties: [geometyr]");
+        let errors = OntologyRuntime::from_yaml(&misspelled).err().unwrap();
+        assert!(matches!(&errors.errors()[0], OntologyLoadError::UnknownReference { kind: DefinitionKind::Property, id, .. }
+            if id == "geometyr"));
+
+        let shared = yaml.replace("properties: [geometry]", "properties: [geometry, population]");
+        let errors = OntologyRuntime::from_yaml(&shared).err().unwrap();
+        assert_eq!(errors.errors().len(), 1);
+        assert_eq!(
+            errors.errors()[0].to_string(),
+            "Property 'population' of object type 'tract' is in property groups 'demographics' and 'geometry'"
+        );
+
+        let duplicate = yaml.replace("- id: geometry\n          displayName: Geometry", "- id: demographics\n          displayName: Geometry")
+            .replace("properties: [geometry]", "properties: []");
+        let errors = OntologyRuntime::from_yaml(&duplicate).err().unwrap();
+        assert!(matches!(&errors.errors()[0], OntologyLoadError::DuplicateId { kind: DefinitionKind::PropertyGroup, id, .. }
+            if id == "demographics"));
+    }
+
+    #[test]
+    fn test_add_and_deprecate_property_record_schema_evolution() {
+        let mut obj_type = create_test_object_type();
+        let mut nickname = obj_type.properties[1].clone();
+        nickname.id = "nickname".to_string();
+        nickname.required = true;
+        let error = obj_type.add_property(nickname.clone()).unwrap_err();
+        assert!(error.contains("needs a default"), "{}", error);
+        assert!(obj_type.schema_evolution.is_none());
+
+        nickname.default = Some(PropertyValue::String("none".to_string()));
+        obj_type.add_property(nickname.clone()).unwrap();
+        assert!(obj_type.add_property(nickname).unwrap_err().contains("already exists"));
+
+        assert!(obj_type.deprecate_property("id", None).is_err());
+        assert!(obj_type.deprecate_property("name", Some("missing".to_string())).is_err());
+        obj_type.deprecate_property("name", Some("nickname".to_string())).unwrap();
+        assert!(obj_type.deprecate_property("name", None).unwrap_err().contains("already deprecated"));
+        assert_eq!(obj_type.get_property("name").unwrap().deprecated.as_ref().unwrap().replacement.as_deref(), Some("nickname"));
+
+        let evolution = obj_type.schema_evolution.as_ref().unwrap();
+        assert_eq!(evolution.version, "2");
+        assert!(matches!(&evolution.changes[0], SchemaChange::PropertyAdded { property_id } if property_id == "nickname"));
+        assert_eq!(evolution.deprecated_properties, vec!["name".to_string()]);
+    }
 }
@@ -2,6 +2,10 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use crate::property::PropertyValue;
 use crate::model_objectives::{ModelObjective, ModelPlatform};
+use crate::sagemaker::{self, SageMakerRequest, SageMakerRuntime};
+use lru::LruCache;
+use std::num::NonZeroUsize;
+use std::sync::{Arc, Mutex, MutexGuard};
 
 /// Result of model execution
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -65,13 +69,33 @@ impl ModelExecutor for PythonModelExecutor {
 /// Remote model executor - executes models on external platforms
 pub struct RemoteModelExecutor {
     http_client: reqwest::Client,
+    sagemaker: Option<Arc<dyn SageMakerRuntime>>,
 }
 
 impl RemoteModelExecutor {
     pub fn new() -> Self {
-        Self {
-            http_client: reqwest::Client::new(),
-        }
+        Self::with_timeout(std::time::Duration::from_secs(30))
+    }
+
+    /// Executor giving up on a prediction request after `timeout`. With the `sagemaker`
+    /// feature, SageMaker endpoints are invoked through `AwsSageMakerRuntime`.
+    pub fn with_timeout(timeout: std::time::Duration) -> Self {
+        let http_client = reqwest::Client::builder()
+            .timeout(timeout)
+            .build()
+            .unwrap_or_default();
+        #[cfg(feature = "sagemaker")]
+        let sagemaker: Option<Arc<dyn SageMakerRuntime>> =
+            Some(Arc::new(sagemaker::AwsSageMakerRuntime::new(http_client.clone())));
+        #[cfg(not(feature = "sagemaker"))]
+        let sagemaker = None;
+        Self { http_client, sagemaker }
+    }
+
+    /// Invoke SageMaker endpoints through `runtime`
+    pub fn with_sagemaker_runtime(mut self, runtime: Arc<dyn SageMakerRuntime>) -> Self {
+        self.sagemaker = Some(runtime);
+        self
     }
 }
 
@@ -89,8 +113,8 @@ impl ModelExecutor for RemoteModelExecutor {
         inputs: HashMap<String, PropertyValue>,
     ) -> Result<ModelExecutionResult, ModelExecutionError> {
         match &model.platform {
-            ModelPlatform::SageMaker { endpoint_name, region } => {
-                self.execute_sagemaker(endpoint_name, region, inputs).await
+            ModelPlatform::SageMaker { endpoint_name, region, content_type } => {
+                self.execute_sagemaker(endpoint_name, region, content_type.as_deref(), inputs).await
             }
             ModelPlatform::DataRobot { deployment_id } => {
                 self.execute_datarobot(deployment_id, inputs).await
@@ -99,7 +123,7 @@ impl ModelExecutor for RemoteModelExecutor {
                 self.execute_custom(endpoint_url, inputs).await
             }
             _ => Err(ModelExecutionError::UnsupportedPlatform(
-                format!("Platform {:?} not supported by RemoteModelExecutor", model.platform)
+                format!("Platform '{}' not supported by RemoteModelExecutor", model.platform.name())
             )),
         }
     }
@@ -115,16 +139,23 @@ impl ModelExecutor for RemoteModelExecutor {
 }
 
 impl RemoteModelExecutor {
+    /// Invoke the endpoint with the inputs as a JSON object; it answers with a bare number
+    /// or `{"prediction", "probabilities"}`
     async fn execute_sagemaker(
         &self,
-        _endpoint_name: &str,
-        _region: &str,
-        _inputs: HashMap<String, PropertyValue>,
+        endpoint_name: &str,
+        region: &str,
+        content_type: Option<&str>,
+        inputs: HashMap<String, PropertyValue>,
     ) -> Result<ModelExecutionResult, ModelExecutionError> {
-        // TODO: Implement SageMaker invocation via AWS SDK
-        Err(ModelExecutionError::NotImplemented(
-            "SageMaker execution not yet implemented".to_string()
-        ))
+        let Some(runtime) = &self.sagemaker else {
+            return Err(ModelExecutionError::NotImplemented(
+                "SageMaker execution needs the `sagemaker` feature or a SageMaker runtime".to_string()
+            ));
+        };
+        let request = SageMakerRequest::new(endpoint_name, region, content_type, &inputs)?;
+        let body = runtime.invoke_endpoint(request).await?;
+        sagemaker::parse_response(&body)
     }
     
     async fn execute_datarobot(
@@ -138,21 +169,44 @@ impl RemoteModelExecutor {
         ))
     }
     
+    /// POST the inputs as a JSON object to the endpoint, which answers with
+    /// `{"prediction", "confidence", "probabilities"}`
     async fn execute_custom(
         &self,
-        _endpoint_url: &str,
-        _inputs: HashMap<String, PropertyValue>,
+        endpoint_url: &str,
+        inputs: HashMap<String, PropertyValue>,
     ) -> Result<ModelExecutionResult, ModelExecutionError> {
-        // TODO: Implement custom endpoint invocation
-        Err(ModelExecutionError::NotImplemented(
-            "Custom endpoint execution not yet implemented".to_string()
-        ))
+        let response = self.http_client
+            .post(endpoint_url)
+            .json(&inputs)
+            .send()
+            .await
+            .map_err(request_error)?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(ModelExecutionError::ExecutionFailed(format!("Model endpoint returned {}", status)));
+        }
+        response
+            .json::<ModelExecutionResult>()
+            .await
+            .map_err(|e| ModelExecutionError::ExecutionFailed(format!("Invalid prediction response: {}", e.without_url())))
     }
 }
 
-/// Model prediction cache
+/// Entries a `ModelCache` holds by default before evicting the least recently used
+pub const DEFAULT_MODEL_CACHE_ENTRIES: usize = 10_000;
+
+/// Model prediction cache, bounded to a number of entries with least recently used
+/// eviction. Shareable across tasks: the lock inside is never held across an `.await`.
 pub struct ModelCache {
-    cache: HashMap<String, CachedPrediction>,
+    state: Mutex<CacheState>,
+}
+
+struct CacheState {
+    entries: LruCache<String, CachedPrediction>,
+    hits: u64,
+    misses: u64,
+    evictions: u64,
 }
 
 #[derive(Debug, Clone)]
@@ -162,53 +216,151 @@ struct CachedPrediction {
     ttl: std::time::Duration,
 }
 
+impl CachedPrediction {
+    fn is_expired(&self) -> bool {
+        self.cached_at.elapsed() >= self.ttl
+    }
+}
+
+/// Counters of a `ModelCache`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct ModelCacheStats {
+    pub entries: usize,
+    pub max_entries: usize,
+    pub hits: u64,
+    pub misses: u64,
+    /// Entries dropped to make room or because they expired
+    pub evictions: u64,
+}
+
 impl ModelCache {
     pub fn new() -> Self {
+        Self::with_max_entries(DEFAULT_MODEL_CACHE_ENTRIES)
+    }
+
+    /// Cache holding at most `max_entries` predictions (at least one)
+    pub fn with_max_entries(max_entries: usize) -> Self {
+        let capacity = NonZeroUsize::new(max_entries).unwrap_or(NonZeroUsize::MIN);
         Self {
-            cache: HashMap::new(),
+            state: Mutex::new(CacheState {
+                entries: LruCache::new(capacity),
+                hits: 0,
+                misses: 0,
+                evictions: 0,
+            }),
         }
     }
+
+    fn state(&self) -> MutexGuard<'_, CacheState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
     
     /// Get a cached prediction if it exists and is not expired
     pub fn get(
         &self,
         cache_key: &str,
     ) -> Option<ModelExecutionResult> {
-        if let Some(cached) = self.cache.get(cache_key) {
-            if cached.cached_at.elapsed() < cached.ttl {
-                return Some(cached.result.clone());
+        let mut state = self.state();
+        let result = match state.entries.get(cache_key) {
+            Some(cached) if !cached.is_expired() => Some(cached.result.clone()),
+            Some(_) => {
+                state.entries.pop(cache_key);
+                state.evictions += 1;
+                None
             }
+            None => None,
+        };
+        match result {
+            Some(_) => state.hits += 1,
+            None => state.misses += 1,
         }
-        None
+        result
     }
     
-    /// Store a prediction in the cache
+    /// Store a prediction in the cache, evicting the least recently used one if full
     pub fn put(
-        &mut self,
+        &self,
         cache_key: String,
         result: ModelExecutionResult,
         ttl_seconds: u64,
     ) {
-        self.cache.insert(
-            cache_key,
-            CachedPrediction {
-                result,
-                cached_at: std::time::Instant::now(),
-                ttl: std::time::Duration::from_secs(ttl_seconds),
-            },
-        );
+        let mut state = self.state();
+        let cached = CachedPrediction {
+            result,
+            cached_at: std::time::Instant::now(),
+            ttl: std::time::Duration::from_secs(ttl_seconds),
+        };
+        if let Some((evicted, _)) = state.entries.push(cache_key.clone(), cached) {
+            if evicted != cache_key {
+                state.evictions += 1;
+            }
+        }
     }
     
-    /// Clear expired entries
-    pub fn clear_expired(&mut self) {
-        self.cache.retain(|_, cached| {
-            cached.cached_at.elapsed() < cached.ttl
-        });
+    /// Clear expired entries, returning how many there were
+    pub fn clear_expired(&self) -> usize {
+        let mut state = self.state();
+        let expired: Vec<String> = state.entries
+            .iter()
+            .filter(|(_, cached)| cached.is_expired())
+            .map(|(key, _)| key.clone())
+            .collect();
+        for key in &expired {
+            state.entries.pop(key);
+        }
+        state.evictions += expired.len() as u64;
+        expired.len()
     }
     
     /// Clear all cache entries
-    pub fn clear_all(&mut self) {
-        self.cache.clear();
+    pub fn clear_all(&self) {
+        self.state().entries.clear();
+    }
+
+    /// Clear the predictions of one model, e.g. after another version of it was promoted,
+    /// returning how many there were
+    pub fn invalidate_model(&self, model_id: &str) -> usize {
+        let mut state = self.state();
+        let keys: Vec<String> = state.entries
+            .iter()
+            .filter(|(key, _)| key.rsplit_once(':').map(|(id, _)| id) == Some(model_id))
+            .map(|(key, _)| key.clone())
+            .collect();
+        for key in &keys {
+            state.entries.pop(key);
+        }
+        keys.len()
+    }
+
+    /// Hits, misses and evictions so far, and the entries held now
+    pub fn cache_stats(&self) -> ModelCacheStats {
+        let state = self.state();
+        ModelCacheStats {
+            entries: state.entries.len(),
+            max_entries: state.entries.cap().get(),
+            hits: state.hits,
+            misses: state.misses,
+            evictions: state.evictions,
+        }
+    }
+
+    /// Clear expired entries every `interval` until the cache is dropped
+    pub fn spawn_eviction_task(self: &Arc<Self>, interval: std::time::Duration) -> tokio::task::JoinHandle<()> {
+        let cache = Arc::downgrade(self);
+        tokio::spawn(async move {
+            let mut ticks = tokio::time::interval(interval);
+            ticks.tick().await;
+            loop {
+                ticks.tick().await;
+                let Some(cache) = cache.upgrade() else {
+                    return;
+                };
+                let evicted = cache.clear_expired();
+                if evicted > 0 {
+                    tracing::debug!(evicted, "evicted expired model predictions");
+                }
+            }
+        })
     }
     
     /// Generate cache key from model ID and inputs
@@ -242,19 +394,30 @@ impl Default for ModelCache {
     }
 }
 
-/// Orchestrator for model execution with caching and multiple executors
+/// Orchestrator for model execution with caching and multiple executors. Executors are
+/// added up front; after that it is shared as is, as predictions only touch the cache.
 pub struct ModelExecutionOrchestrator {
     executors: Vec<Box<dyn ModelExecutor>>,
-    cache: ModelCache,
+    cache: Arc<ModelCache>,
 }
 
 impl ModelExecutionOrchestrator {
     pub fn new() -> Self {
+        Self::with_cache(ModelCache::new())
+    }
+
+    /// Orchestrator caching predictions in `cache`
+    pub fn with_cache(cache: ModelCache) -> Self {
         Self {
             executors: Vec::new(),
-            cache: ModelCache::new(),
+            cache: Arc::new(cache),
         }
     }
+
+    /// The prediction cache, e.g. to start its eviction task
+    pub fn cache(&self) -> &Arc<ModelCache> {
+        &self.cache
+    }
     
     /// Add an executor to the orchestrator
     pub fn add_executor(&mut self, executor: Box<dyn ModelExecutor>) {
@@ -263,15 +426,15 @@ impl ModelExecutionOrchestrator {
     
     /// Execute a model with caching support
     pub async fn execute(
-        &mut self,
+        &self,
         model: &ModelObjective,
         inputs: HashMap<String, PropertyValue>,
         use_cache: bool,
         cache_ttl: u64,
     ) -> Result<ModelExecutionResult, ModelExecutionError> {
         // Check cache first if enabled
+        let cache_key = ModelCache::generate_key(&model.id, &inputs);
         if use_cache {
-            let cache_key = ModelCache::generate_key(&model.id, &inputs);
             if let Some(cached_result) = self.cache.get(&cache_key) {
                 return Ok(cached_result);
             }
@@ -280,25 +443,52 @@ impl ModelExecutionOrchestrator {
         // Find an executor that can handle this model
         let executor = self.executors.iter()
             .find(|e| e.can_handle(&model.platform))
-            .ok_or_else(|| ModelExecutionError::NoExecutorFound(
-                format!("No executor found for platform {:?}", model.platform)
-            ))?;
+            .ok_or_else(|| ModelExecutionError::NoExecutorFound {
+                platform: model.platform.name().to_string(),
+            })?;
         
         // Execute the model
-        let result = executor.execute(model, inputs.clone()).await?;
+        let result = executor.execute(model, inputs).await?;
         
         // Cache the result if enabled
         if use_cache {
-            let cache_key = ModelCache::generate_key(&model.id, &inputs);
             self.cache.put(cache_key, result.clone(), cache_ttl);
         }
         
         Ok(result)
     }
     
+    /// A cached, unexpired prediction of `model` for `inputs`, without executing it
+    pub fn cached(
+        &self,
+        model: &ModelObjective,
+        inputs: &HashMap<String, PropertyValue>,
+    ) -> Option<ModelExecutionResult> {
+        self.cache.get(&ModelCache::generate_key(&model.id, inputs))
+    }
+    
     /// Clear expired cache entries
-    pub fn clear_expired_cache(&mut self) {
-        self.cache.clear_expired();
+    pub fn clear_expired_cache(&self) -> usize {
+        self.cache.clear_expired()
+    }
+
+    /// Clear cached predictions of a model, returning how many there were
+    pub fn invalidate_model(&self, model_id: &str) -> usize {
+        self.cache.invalidate_model(model_id)
+    }
+
+    /// Counters of the prediction cache
+    pub fn cache_stats(&self) -> ModelCacheStats {
+        self.cache.cache_stats()
+    }
+}
+
+// reqwest errors can include the full URL, which may carry credentials
+fn request_error(error: reqwest::Error) -> ModelExecutionError {
+    if error.is_timeout() {
+        ModelExecutionError::Timeout
+    } else {
+        ModelExecutionError::NetworkError(error.without_url().to_string())
     }
 }
 
@@ -320,8 +510,8 @@ pub enum ModelExecutionError {
     #[error("Unsupported platform: {0}")]
     UnsupportedPlatform(String),
     
-    #[error("No executor found: {0}")]
-    NoExecutorFound(String),
+    #[error("No executor can run models on platform '{platform}'")]
+    NoExecutorFound { platform: String },
     
     #[error("Not implemented: {0}")]
     NotImplemented(String),
@@ -376,6 +566,173 @@ mod tests {
         assert!(cache.get("key1").is_none());
     }
 
+    #[test]
+    fn test_invalidate_model() {
+        let mut cache = ModelCache::new();
+        let result = ModelExecutionResult {
+            prediction: PropertyValue::Double(0.85),
+            confidence: None,
+            probabilities: None,
+            metadata: HashMap::new(),
+        };
+        let inputs = HashMap::from([("feature1".to_string(), PropertyValue::Integer(42))]);
+        let churn = ModelCache::generate_key("churn", &inputs);
+        let churn_eu = ModelCache::generate_key("churn:eu", &inputs);
+        cache.put(churn.clone(), result.clone(), 3600);
+        cache.put(churn_eu.clone(), result, 3600);
+
+        cache.invalidate_model("churn");
+        assert!(cache.get(&churn).is_none());
+        assert!(cache.get(&churn_eu).is_some());
+    }
+
+    fn prediction(value: i64) -> ModelExecutionResult {
+        ModelExecutionResult {
+            prediction: PropertyValue::Integer(value),
+            confidence: None,
+            probabilities: None,
+            metadata: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_cache_evicts_least_recently_used() {
+        let cache = ModelCache::with_max_entries(2);
+        cache.put("m:a".to_string(), prediction(1), 3600);
+        cache.put("m:b".to_string(), prediction(2), 3600);
+        // Reading `a` makes `b` the least recently used
+        assert!(cache.get("m:a").is_some());
+        cache.put("m:c".to_string(), prediction(3), 3600);
+
+        assert!(cache.get("m:b").is_none());
+        assert_eq!(cache.get("m:a").unwrap().prediction, PropertyValue::Integer(1));
+        assert_eq!(cache.get("m:c").unwrap().prediction, PropertyValue::Integer(3));
+
+        // Replacing a prediction evicts nothing
+        cache.put("m:c".to_string(), prediction(4), 3600);
+        assert_eq!(cache.get("m:c").unwrap().prediction, PropertyValue::Integer(4));
+        assert_eq!(cache.cache_stats().evictions, 1);
+        assert_eq!(cache.cache_stats().entries, 2);
+    }
+
+    #[test]
+    fn test_cache_stats() {
+        let cache = ModelCache::with_max_entries(10);
+        cache.put("m:a".to_string(), prediction(1), 3600);
+        cache.put("m:b".to_string(), prediction(2), 0);
+        cache.put("m:c".to_string(), prediction(3), 0);
+        assert!(cache.get("m:a").is_some());
+        assert!(cache.get("m:a").is_some());
+        assert!(cache.get("m:x").is_none());
+        // An expired prediction is a miss and is dropped
+        assert!(cache.get("m:b").is_none());
+
+        assert_eq!(
+            cache.cache_stats(),
+            ModelCacheStats { entries: 2, max_entries: 10, hits: 2, misses: 2, evictions: 1 }
+        );
+        assert_eq!(cache.clear_expired(), 1);
+        assert_eq!(cache.cache_stats().evictions, 2);
+        assert_eq!(cache.cache_stats().entries, 1);
+    }
+
+    #[tokio::test]
+    async fn test_eviction_task_clears_expired_predictions() {
+        let cache = Arc::new(ModelCache::new());
+        cache.put("m:a".to_string(), prediction(1), 0);
+        cache.put("m:b".to_string(), prediction(2), 3600);
+        let task = cache.spawn_eviction_task(std::time::Duration::from_millis(10));
+        for _ in 0..100 {
+            if cache.cache_stats().entries == 1 {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        assert_eq!(cache.cache_stats().entries, 1);
+        assert_eq!(cache.cache_stats().evictions, 1);
+
+        // The task ends with the cache
+        drop(cache);
+        tokio::time::timeout(std::time::Duration::from_secs(1), task).await.unwrap().unwrap();
+    }
+
+    /// Executor whose predictions only complete once `barrier` has as many waiting
+    struct BarrierExecutor {
+        barrier: tokio::sync::Barrier,
+        calls: std::sync::atomic::AtomicUsize,
+    }
+
+    #[async_trait::async_trait]
+    impl ModelExecutor for BarrierExecutor {
+        async fn execute(
+            &self,
+            _model: &ModelObjective,
+            inputs: HashMap<String, PropertyValue>,
+        ) -> Result<ModelExecutionResult, ModelExecutionError> {
+            self.calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            self.barrier.wait().await;
+            Ok(prediction(inputs.len() as i64))
+        }
+
+        fn can_handle(&self, _platform: &ModelPlatform) -> bool {
+            true
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_concurrent_predictions_share_the_cache() {
+        let mut orchestrator = ModelExecutionOrchestrator::with_cache(ModelCache::with_max_entries(100));
+        orchestrator.add_executor(Box::new(BarrierExecutor {
+            barrier: tokio::sync::Barrier::new(8),
+            calls: std::sync::atomic::AtomicUsize::new(0),
+        }));
+        let orchestrator = Arc::new(orchestrator);
+        let model = ModelObjective::new(
+            "score".to_string(),
+            "Score".to_string(),
+            ModelType::Regression,
+            "1.0.0".to_string(),
+            "/models/score.pkl".to_string(),
+            ModelPlatform::Local { framework: "sklearn".to_string() },
+        );
+        let inputs = |i: i64| {
+            (0..=i).map(|n| (format!("feature{}", n), PropertyValue::Integer(n))).collect::<HashMap<_, _>>()
+        };
+
+        // Eight predictions only finish together, so none may hold the cache while executing
+        let tasks: Vec<_> = (0..8)
+            .map(|i| {
+                let (orchestrator, model, inputs) = (orchestrator.clone(), model.clone(), inputs(i));
+                tokio::spawn(async move { orchestrator.execute(&model, inputs, true, 3600).await })
+            })
+            .collect();
+        for (i, task) in tasks.into_iter().enumerate() {
+            let result = tokio::time::timeout(std::time::Duration::from_secs(5), task).await.unwrap().unwrap();
+            assert_eq!(result.unwrap().prediction, PropertyValue::Integer(i as i64 + 1));
+        }
+
+        let readers: Vec<_> = (0..8)
+            .map(|i| {
+                let (orchestrator, model) = (orchestrator.clone(), model.clone());
+                tokio::spawn(async move {
+                    for _ in 0..50 {
+                        assert!(orchestrator.cached(&model, &inputs(i)).is_some());
+                        assert!(orchestrator.cached(&model, &inputs(i + 8)).is_none());
+                    }
+                })
+            })
+            .collect();
+        for reader in readers {
+            reader.await.unwrap();
+        }
+
+        let stats = orchestrator.cache_stats();
+        assert_eq!(stats.entries, 8);
+        assert_eq!(stats.hits, 8 * 50);
+        assert_eq!(stats.misses, 8 + 8 * 50);
+        assert_eq!(stats.evictions, 0);
+    }
+
     #[test]
     fn test_python_executor_can_handle() {
         let executor = PythonModelExecutor::new("localhost:50051".to_string());
@@ -387,6 +744,7 @@ mod tests {
         let sagemaker_platform = ModelPlatform::SageMaker {
             endpoint_name: "test".to_string(),
             region: "us-east-1".to_string(),
+            content_type: None,
         };
         
         assert!(executor.can_handle(&local_platform));
@@ -404,9 +762,104 @@ mod tests {
         let sagemaker_platform = ModelPlatform::SageMaker {
             endpoint_name: "test".to_string(),
             region: "us-east-1".to_string(),
+            content_type: None,
         };
         
         assert!(!executor.can_handle(&local_platform));
         assert!(executor.can_handle(&sagemaker_platform));
     }
+
+    /// HTTP server answering every request with `status` and `body`, returning its URL and
+    /// the request bodies received so far
+    fn serve(status: u16, body: &'static str) -> (String, std::sync::Arc<std::sync::Mutex<Vec<String>>>) {
+        use std::io::{Read, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/predict", listener.local_addr().unwrap());
+        let requests = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let received = requests.clone();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut request = String::new();
+                let mut buffer = [0; 4096];
+                while !(request.contains("\r\n\r\n") && request.trim_end().ends_with('}')) {
+                    let read = stream.read(&mut buffer).unwrap();
+                    if read == 0 {
+                        break;
+                    }
+                    request.push_str(&String::from_utf8_lossy(&buffer[..read]));
+                }
+                let (_, request_body) = request.split_once("\r\n\r\n").unwrap_or_default();
+                received.lock().unwrap().push(request_body.to_string());
+                let response = format!(
+                    "HTTP/1.1 {} Status\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                );
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+        });
+        (url, requests)
+    }
+
+    fn custom_model(endpoint_url: &str) -> ModelObjective {
+        ModelObjective::new(
+            "churn".to_string(),
+            "Churn".to_string(),
+            ModelType::Classification,
+            "1.0.0".to_string(),
+            "/models/churn.pkl".to_string(),
+            ModelPlatform::Custom {
+                platform_name: "scoring".to_string(),
+                endpoint_url: endpoint_url.to_string(),
+            },
+        )
+    }
+
+    #[tokio::test]
+    async fn test_custom_endpoint_prediction_is_cached() {
+        let (url, requests) = serve(200, r#"{"prediction": "churn", "confidence": 0.8, "probabilities": {"churn": 0.8, "stay": 0.2}}"#);
+        let mut orchestrator = ModelExecutionOrchestrator::new();
+        orchestrator.add_executor(Box::new(RemoteModelExecutor::new()));
+        let model = custom_model(&url);
+        let inputs = HashMap::from([("tenure".to_string(), PropertyValue::Integer(3))]);
+
+        let result = orchestrator.execute(&model, inputs.clone(), true, 60).await.unwrap();
+        assert_eq!(result.prediction, PropertyValue::String("churn".to_string()));
+        assert_eq!(result.confidence, Some(0.8));
+        assert_eq!(result.probabilities.unwrap()["stay"], 0.2);
+        orchestrator.execute(&model, inputs.clone(), true, 60).await.unwrap();
+        assert_eq!(*requests.lock().unwrap(), vec![r#"{"tenure":3}"#.to_string()]);
+
+        orchestrator.invalidate_model("churn");
+        orchestrator.execute(&model, inputs, true, 60).await.unwrap();
+        assert_eq!(requests.lock().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_custom_endpoint_errors() {
+        let (url, _) = serve(500, "{}");
+        let executor = RemoteModelExecutor::new();
+        let error = executor.execute(&custom_model(&url), HashMap::new()).await.unwrap_err();
+        assert!(matches!(&error, ModelExecutionError::ExecutionFailed(message) if message.contains("500")), "{}", error);
+
+        let (url, _) = serve(200, r#"{"score": 1}"#);
+        let error = executor.execute(&custom_model(&url), HashMap::new()).await.unwrap_err();
+        assert!(matches!(error, ModelExecutionError::ExecutionFailed(_)), "{}", error);
+
+        let mut orchestrator = ModelExecutionOrchestrator::new();
+        orchestrator.add_executor(Box::new(RemoteModelExecutor::new()));
+        let local = ModelObjective::new(
+            "local".to_string(),
+            "Local".to_string(),
+            ModelType::Regression,
+            "1.0.0".to_string(),
+            "/models/local.pkl".to_string(),
+            ModelPlatform::Local { framework: "sklearn".to_string() },
+        );
+        let error = orchestrator.execute(&local, HashMap::new(), false, 0).await.unwrap_err();
+        assert!(matches!(&error, ModelExecutionError::NoExecutorFound { platform } if platform == "local"), "{}", error);
+    }
 }
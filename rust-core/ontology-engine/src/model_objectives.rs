@@ -1,6 +1,13 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::io::Write;
+use std::path::{Path, PathBuf};
 use chrono::{DateTime, Utc};
+use crate::backup::{BackupComponent, BackupError};
+use crate::property::PropertyValue;
+
+/// Version of the registry file format written by `save_to_path`
+pub const MODEL_REGISTRY_FILE_VERSION: u32 = 2;
 
 /// Model type enumeration
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -145,6 +152,11 @@ pub struct ModelBindingConfig {
     /// Whether to execute asynchronously
     #[serde(default)]
     pub async_execution: bool,
+    
+    /// Value returned while no prediction is available, e.g. while one is computed in the
+    /// background for an asynchronous binding
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fallback_value: Option<PropertyValue>,
 }
 
 fn default_cache_enabled() -> bool {
@@ -162,6 +174,7 @@ impl Default for ModelBindingConfig {
             cache_enabled: default_cache_enabled(),
             cache_ttl: default_cache_ttl(),
             async_execution: false,
+            fallback_value: None,
         }
     }
 }
@@ -176,6 +189,9 @@ pub enum ModelPlatform {
     SageMaker {
         endpoint_name: String,
         region: String,
+        /// Content type of the JSON request body, `application/json` if unset
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        content_type: Option<String>,
     },
     DataRobot {
         deployment_id: String,
@@ -186,6 +202,18 @@ pub enum ModelPlatform {
     },
 }
 
+impl ModelPlatform {
+    /// Short name of the platform, as in its serialized `type`
+    pub fn name(&self) -> &'static str {
+        match self {
+            ModelPlatform::Local { .. } => "local",
+            ModelPlatform::SageMaker { .. } => "sagemaker",
+            ModelPlatform::DataRobot { .. } => "datarobot",
+            ModelPlatform::Custom { .. } => "custom",
+        }
+    }
+}
+
 /// Model objective - represents a registered ML model
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ModelObjective {
@@ -295,10 +323,14 @@ impl ModelObjective {
     }
 }
 
-/// Model registry - manages registered models
+/// Model registry - manages registered models. A model ID can have several versions; one
+/// of them is active and stands for the model wherever only its ID is given, such as in
+/// bindings.
 pub struct ModelRegistry {
-    models: HashMap<String, ModelObjective>,
+    models: HashMap<(String, String), ModelObjective>, // (id, version) -> model
+    active_versions: HashMap<String, String>, // id -> active version
     bindings: HashMap<(String, String), ModelBinding>, // (object_type, property_id) -> binding
+    path: Option<PathBuf>,
 }
 
 impl ModelRegistry {
@@ -306,69 +338,240 @@ impl ModelRegistry {
     pub fn new() -> Self {
         Self {
             models: HashMap::new(),
+            active_versions: HashMap::new(),
             bindings: HashMap::new(),
+            path: None,
+        }
+    }
+
+    /// Registry persisted to `path`: loads the models and bindings saved there if the file
+    /// exists, and saves after every mutation
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self, ModelRegistryError> {
+        let path = path.into();
+        let mut registry = if path.exists() {
+            Self::load_from_path(&path)?
+        } else {
+            Self::new()
+        };
+        registry.save_to_path(&path)?;
+        registry.path = Some(path);
+        Ok(registry)
+    }
+
+    /// Load a registry saved with `save_to_path`. The result is not persisted; use `open`
+    /// for that.
+    pub fn load_from_path(path: &Path) -> Result<Self, ModelRegistryError> {
+        let content = std::fs::read(path)?;
+        let file: ModelRegistryFile = serde_json::from_slice(&content)
+            .map_err(|e| ModelRegistryError::Serialization(format!("Invalid model registry {}: {}", path.display(), e)))?;
+        if file.version > MODEL_REGISTRY_FILE_VERSION {
+            return Err(ModelRegistryError::UnsupportedVersion(file.version));
+        }
+        let mut registry = Self::new();
+        registry.restore(file.registry);
+        Ok(registry)
+    }
+
+    /// Write the registry to `path`. The file is written beside it and renamed into place,
+    /// so readers and crashes never see a partial registry.
+    pub fn save_to_path(&self, path: &Path) -> Result<(), ModelRegistryError> {
+        let file = ModelRegistryFile {
+            version: MODEL_REGISTRY_FILE_VERSION,
+            registry: self.snapshot(),
+        };
+        let content = serde_json::to_vec_pretty(&file).map_err(|e| ModelRegistryError::Serialization(e.to_string()))?;
+        if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+        // A temporary file per write, so concurrent saves never write into the same file
+        let partial = path.with_extension(format!("{}.tmp", uuid::Uuid::new_v4()));
+        let written = std::fs::File::create(&partial).and_then(|mut f| {
+            f.write_all(&content)?;
+            f.sync_all()
+        });
+        if let Err(e) = written.and_then(|_| std::fs::rename(&partial, path)) {
+            std::fs::remove_file(&partial).ok();
+            return Err(e.into());
+        }
+        Ok(())
+    }
+
+    /// Save to the registry's path after a mutation, if it has one
+    fn persist(&self) -> Result<(), String> {
+        match &self.path {
+            Some(path) => self
+                .save_to_path(path)
+                .map_err(|e| format!("Failed to save model registry: {}", e)),
+            None => Ok(()),
+        }
+    }
+
+    /// Models and bindings in a stable order, for saving
+    fn snapshot(&self) -> ModelRegistrySnapshot {
+        let mut models: Vec<ModelObjective> = self.models.values().cloned().collect();
+        models.sort_by(|a, b| (&a.id, &a.version).cmp(&(&b.id, &b.version)));
+        let mut bindings: Vec<ModelBinding> = self.bindings.values().cloned().collect();
+        bindings.sort_by(|a, b| (&a.object_type, &a.property_id).cmp(&(&b.object_type, &b.property_id)));
+        let active_versions = self.active_versions.iter()
+            .map(|(id, version)| (id.clone(), version.clone()))
+            .collect();
+        ModelRegistrySnapshot { models, bindings, active_versions }
+    }
+
+    fn restore(&mut self, snapshot: ModelRegistrySnapshot) {
+        self.active_versions = snapshot.active_versions.into_iter().collect();
+        // Snapshots from before version lineage have one version per model, which is active
+        for model in &snapshot.models {
+            self.active_versions.entry(model.id.clone()).or_insert_with(|| model.version.clone());
         }
+        self.models = snapshot.models.into_iter()
+            .map(|m| ((m.id.clone(), m.version.clone()), m))
+            .collect();
+        self.bindings = snapshot.bindings.into_iter()
+            .map(|b| ((b.object_type.clone(), b.property_id.clone()), b))
+            .collect();
+    }
+
+    fn active_key(&self, id: &str) -> Option<(String, String)> {
+        self.active_versions.get(id).map(|version| (id.to_string(), version.clone()))
     }
     
-    /// Register a new model
+    /// Register a new model; its version becomes the active one
     pub fn register(&mut self, model: ModelObjective) -> Result<(), String> {
         model.validate()?;
         
-        if self.models.contains_key(&model.id) {
+        if self.active_versions.contains_key(&model.id) {
             return Err(format!("Model with ID '{}' already exists", model.id));
         }
         
-        self.models.insert(model.id.clone(), model);
-        Ok(())
+        self.active_versions.insert(model.id.clone(), model.version.clone());
+        self.models.insert((model.id.clone(), model.version.clone()), model);
+        self.persist()
+    }
+
+    /// Register another version of an existing model. The active version is unchanged
+    /// until the new one is promoted with `promote_version`.
+    pub fn register_version(&mut self, mut model: ModelObjective) -> Result<(), String> {
+        model.validate()?;
+
+        if !self.active_versions.contains_key(&model.id) {
+            return Err(format!("Model '{}' not found", model.id));
+        }
+        let key = (model.id.clone(), model.version.clone());
+        if self.models.contains_key(&key) {
+            return Err(format!("Model '{}' already has version '{}'", model.id, model.version));
+        }
+
+        // Bindings follow the active version, so a new version starts unbound
+        if model.status == ModelStatus::Bound {
+            model.status = ModelStatus::Registered;
+        }
+        self.models.insert(key, model);
+        self.persist()
+    }
+
+    /// Make `version` the active version of model `id`. Bindings on the model keep working
+    /// and from now on resolve to this version.
+    pub fn promote_version(&mut self, id: &str, version: &str) -> Result<(), String> {
+        let key = (id.to_string(), version.to_string());
+        if !self.models.contains_key(&key) {
+            return Err(format!("Model '{}' has no version '{}'", id, version));
+        }
+        let previous = self.active_key(id)
+            .ok_or_else(|| format!("Model '{}' not found", id))?;
+        if previous == key {
+            return Ok(());
+        }
+
+        if self.bindings.values().any(|b| b.model_id == id) {
+            if let Some(model) = self.models.get_mut(&previous) {
+                model.update_status(ModelStatus::Registered);
+            }
+            if let Some(model) = self.models.get_mut(&key) {
+                model.update_status(ModelStatus::Bound);
+            }
+        }
+        self.active_versions.insert(id.to_string(), version.to_string());
+        self.persist()
     }
     
-    /// Get a model by ID
+    /// Get the active version of a model by ID
     pub fn get(&self, id: &str) -> Option<&ModelObjective> {
-        self.models.get(id)
+        self.models.get(&self.active_key(id)?)
+    }
+
+    /// Get a specific version of a model
+    pub fn get_version(&self, id: &str, version: &str) -> Option<&ModelObjective> {
+        self.models.get(&(id.to_string(), version.to_string()))
+    }
+
+    /// Active version of a model
+    pub fn active_version(&self, id: &str) -> Option<&str> {
+        self.active_versions.get(id).map(String::as_str)
     }
     
-    /// Get a mutable reference to a model
+    /// Get a mutable reference to the active version of a model. Changes made through it
+    /// are not saved until the next mutation.
     pub fn get_mut(&mut self, id: &str) -> Option<&mut ModelObjective> {
-        self.models.get_mut(id)
+        let key = self.active_key(id)?;
+        self.models.get_mut(&key)
     }
     
-    /// List all models
+    /// List all models, by their active version
     pub fn list(&self) -> Vec<&ModelObjective> {
-        self.models.values().collect()
+        self.active_versions.keys().filter_map(|id| self.get(id)).collect()
+    }
+
+    /// List every version of a model, oldest first
+    pub fn list_versions(&self, id: &str) -> Vec<&ModelObjective> {
+        let mut versions: Vec<&ModelObjective> = self.models
+            .values()
+            .filter(|m| m.id == id)
+            .collect();
+        versions.sort_by(|a, b| (a.created_at, &a.version).cmp(&(b.created_at, &b.version)));
+        versions
     }
     
     /// List models by type
     pub fn list_by_type(&self, model_type: &ModelType) -> Vec<&ModelObjective> {
-        self.models
-            .values()
+        self.list()
+            .into_iter()
             .filter(|m| &m.model_type == model_type)
             .collect()
     }
     
     /// List models by status
     pub fn list_by_status(&self, status: &ModelStatus) -> Vec<&ModelObjective> {
-        self.models
-            .values()
+        self.list()
+            .into_iter()
             .filter(|m| &m.status == status)
             .collect()
     }
     
-    /// Update model metrics
+    /// Update the metrics of a model's active version
     pub fn update_metrics(&mut self, id: &str, metrics: ModelMetrics) -> Result<(), String> {
-        let model = self.models.get_mut(id)
-            .ok_or_else(|| format!("Model '{}' not found", id))?;
+        let version = self.active_version(id)
+            .ok_or_else(|| format!("Model '{}' not found", id))?
+            .to_string();
+        self.update_version_metrics(id, &version, metrics)
+    }
+
+    /// Update the metrics of a specific version of a model
+    pub fn update_version_metrics(&mut self, id: &str, version: &str, metrics: ModelMetrics) -> Result<(), String> {
+        let model = self.models.get_mut(&(id.to_string(), version.to_string()))
+            .ok_or_else(|| format!("Model '{}' has no version '{}'", id, version))?;
         
         model.update_metrics(metrics);
-        Ok(())
+        self.persist()
     }
     
-    /// Update model status
+    /// Update the status of a model's active version
     pub fn update_status(&mut self, id: &str, status: ModelStatus) -> Result<(), String> {
-        let model = self.models.get_mut(id)
+        let model = self.get_mut(id)
             .ok_or_else(|| format!("Model '{}' not found", id))?;
         
         model.update_status(status);
-        Ok(())
+        self.persist()
     }
     
     /// Bind a model to a property
@@ -381,7 +584,7 @@ impl ModelRegistry {
         config: ModelBindingConfig,
     ) -> Result<ModelBinding, String> {
         // Check if model exists
-        if !self.models.contains_key(model_id) {
+        if !self.active_versions.contains_key(model_id) {
             return Err(format!("Model '{}' not found", model_id));
         }
         
@@ -405,11 +608,12 @@ impl ModelRegistry {
         };
         
         // Update model status to Bound
-        self.update_status(model_id, ModelStatus::Bound)?;
+        self.get_mut(model_id).expect("checked above").update_status(ModelStatus::Bound);
         
         // Store binding
         self.bindings.insert(key, binding.clone());
         
+        self.persist()?;
         Ok(binding)
     }
     
@@ -434,10 +638,12 @@ impl ModelRegistry {
         
         // If no other bindings, update status back to Registered
         if !has_other_bindings {
-            self.update_status(model_id, ModelStatus::Registered)?;
+            if let Some(model) = self.get_mut(model_id) {
+                model.update_status(ModelStatus::Registered);
+            }
         }
         
-        Ok(())
+        self.persist()
     }
     
     /// Get binding for a property
@@ -445,6 +651,12 @@ impl ModelRegistry {
         let key = (object_type.to_string(), property_id.to_string());
         self.bindings.get(&key)
     }
+
+    /// The model version a property's binding currently resolves to: the active version
+    /// of the bound model
+    pub fn bound_model(&self, object_type: &str, property_id: &str) -> Option<&ModelObjective> {
+        self.get(&self.get_binding(object_type, property_id)?.model_id)
+    }
     
     /// List all bindings
     pub fn list_bindings(&self) -> Vec<&ModelBinding> {
@@ -459,7 +671,15 @@ impl ModelRegistry {
             .collect()
     }
     
-    /// Compare multiple models by their primary metrics
+    /// List bindings on properties of a specific object type
+    pub fn list_bindings_for_object_type(&self, object_type: &str) -> Vec<&ModelBinding> {
+        self.bindings
+            .values()
+            .filter(|b| b.object_type == object_type)
+            .collect()
+    }
+    
+    /// Compare multiple models by the primary metrics of their active versions
     pub fn compare_models(&self, model_ids: &[String]) -> Result<Vec<ModelComparison>, String> {
         let mut comparisons = Vec::new();
         
@@ -467,21 +687,22 @@ impl ModelRegistry {
             let model = self.get(id)
                 .ok_or_else(|| format!("Model '{}' not found", id))?;
             
-            comparisons.push(ModelComparison {
-                model_id: model.id.clone(),
-                model_name: model.name.clone(),
-                model_type: model.model_type.clone(),
-                version: model.version.clone(),
-                metrics: model.metrics.clone(),
-                primary_metric: model.metrics.primary_metric(&model.model_type),
-                status: model.status.clone(),
-            });
+            comparisons.push(ModelComparison::of(model));
         }
         
         Ok(comparisons)
     }
+
+    /// Compare all versions of one model by their primary metrics, oldest first
+    pub fn compare_versions(&self, id: &str) -> Result<Vec<ModelComparison>, String> {
+        let versions = self.list_versions(id);
+        if versions.is_empty() {
+            return Err(format!("Model '{}' not found", id));
+        }
+        Ok(versions.into_iter().map(ModelComparison::of).collect())
+    }
     
-    /// Delete a model (only if not bound)
+    /// Delete a model with all its versions (only if not bound)
     pub fn delete(&mut self, id: &str) -> Result<(), String> {
         // Check if model has any bindings
         let has_bindings = self.bindings.values()
@@ -494,10 +715,11 @@ impl ModelRegistry {
             ));
         }
         
-        self.models.remove(id)
+        self.active_versions.remove(id)
             .ok_or_else(|| format!("Model '{}' not found", id))?;
+        self.models.retain(|(model_id, _), _| model_id != id);
         
-        Ok(())
+        self.persist()
     }
 }
 
@@ -507,6 +729,56 @@ impl Default for ModelRegistry {
     }
 }
 
+/// Serialized form of the registry used for backups
+#[derive(Serialize, Deserialize)]
+struct ModelRegistrySnapshot {
+    models: Vec<ModelObjective>,
+    bindings: Vec<ModelBinding>,
+    #[serde(default)]
+    active_versions: BTreeMap<String, String>,
+}
+
+/// Registry file written by `save_to_path`; `version` lets later formats migrate old files
+#[derive(Serialize, Deserialize)]
+struct ModelRegistryFile {
+    version: u32,
+    #[serde(flatten)]
+    registry: ModelRegistrySnapshot,
+}
+
+/// Errors loading or saving a persisted model registry
+#[derive(Debug, thiserror::Error)]
+pub enum ModelRegistryError {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Serialization error: {0}")]
+    Serialization(String),
+
+    #[error("Unsupported model registry file version {0}")]
+    UnsupportedVersion(u32),
+}
+
+#[async_trait::async_trait]
+impl BackupComponent for ModelRegistry {
+    fn backup_name(&self) -> String {
+        "model_registry".to_string()
+    }
+
+    async fn export_for_backup(&self) -> Result<Vec<u8>, BackupError> {
+        serde_json::to_vec(&self.snapshot())
+            .map_err(|e| BackupError::Serialization(e.to_string()))
+    }
+
+    async fn import_from_backup(&mut self, data: &[u8]) -> Result<(), BackupError> {
+        let snapshot: ModelRegistrySnapshot = serde_json::from_slice(data)
+            .map_err(|e| BackupError::Serialization(e.to_string()))?;
+
+        self.restore(snapshot);
+        self.persist().map_err(BackupError::Component)
+    }
+}
+
 /// Model comparison result
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ModelComparison {
@@ -519,6 +791,20 @@ pub struct ModelComparison {
     pub status: ModelStatus,
 }
 
+impl ModelComparison {
+    fn of(model: &ModelObjective) -> Self {
+        Self {
+            model_id: model.id.clone(),
+            model_name: model.name.clone(),
+            model_type: model.model_type.clone(),
+            version: model.version.clone(),
+            metrics: model.metrics.clone(),
+            primary_metric: model.metrics.primary_metric(&model.model_type),
+            status: model.status.clone(),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -668,4 +954,193 @@ mod tests {
         assert_eq!(comparison[0].primary_metric, Some(0.85));
         assert_eq!(comparison[1].primary_metric, Some(0.92));
     }
+
+    #[tokio::test]
+    async fn test_registry_backup_round_trip() {
+        let mut registry = ModelRegistry::new();
+        registry.register(ModelObjective::new(
+            "model_1".to_string(),
+            "Demand Forecast".to_string(),
+            ModelType::Regression,
+            "1.0.0".to_string(),
+            "/models/model_1.pkl".to_string(),
+            ModelPlatform::Local {
+                framework: "sklearn".to_string(),
+            },
+        )).unwrap();
+        registry.bind_model(
+            "model_1",
+            "Plant".to_string(),
+            "demand_forecast".to_string(),
+            None,
+            ModelBindingConfig::default(),
+        ).unwrap();
+
+        let data = registry.export_for_backup().await.unwrap();
+
+        let mut restored = ModelRegistry::new();
+        restored.import_from_backup(&data).await.unwrap();
+        assert_eq!(restored.get("model_1").unwrap().status, ModelStatus::Bound);
+        assert_eq!(restored.get_binding("Plant", "demand_forecast").unwrap().model_id, "model_1");
+        assert_eq!(restored.export_for_backup().await.unwrap(), data);
+    }
+
+    fn registry_path() -> PathBuf {
+        std::env::temp_dir().join(format!("model-registry-{}", uuid::Uuid::new_v4())).join("models.json")
+    }
+
+    fn local_model(id: &str) -> ModelObjective {
+        ModelObjective::new(
+            id.to_string(),
+            format!("Model {}", id),
+            ModelType::Regression,
+            "1.0.0".to_string(),
+            format!("/models/{}.pkl", id),
+            ModelPlatform::Local {
+                framework: "sklearn".to_string(),
+            },
+        )
+    }
+
+    #[test]
+    fn test_registry_persists_mutations() {
+        let path = registry_path();
+        let mut registry = ModelRegistry::open(&path).unwrap();
+        registry.register(local_model("model_1")).unwrap();
+        registry.register(local_model("model_2")).unwrap();
+        registry.register(local_model("model_3")).unwrap();
+        registry.bind_model(
+            "model_1",
+            "Plant".to_string(),
+            "demand_forecast".to_string(),
+            Some("analyst".to_string()),
+            ModelBindingConfig::default(),
+        ).unwrap();
+        registry.bind_model("model_2", "Plant".to_string(), "risk".to_string(), None, ModelBindingConfig::default())
+            .unwrap();
+        registry.unbind_model("Plant", "risk").unwrap();
+        let mut metrics = ModelMetrics::new();
+        metrics.r2 = Some(0.8);
+        registry.update_metrics("model_2", metrics).unwrap();
+        registry.update_status("model_2", ModelStatus::Deprecated).unwrap();
+        registry.delete("model_3").unwrap();
+
+        let reopened = ModelRegistry::open(&path).unwrap();
+        assert_eq!(reopened.list().len(), 2);
+        assert!(reopened.get("model_3").is_none());
+        assert_eq!(reopened.get("model_1").unwrap().status, ModelStatus::Bound);
+        let model_2 = reopened.get("model_2").unwrap();
+        assert_eq!(model_2.status, ModelStatus::Deprecated);
+        assert_eq!(model_2.metrics.r2, Some(0.8));
+        let binding = reopened.get_binding("Plant", "demand_forecast").unwrap();
+        assert_eq!(binding.bound_by.as_deref(), Some("analyst"));
+        assert!(reopened.get_binding("Plant", "risk").is_none());
+
+        let file: serde_json::Value = serde_json::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+        assert_eq!(file["version"], MODEL_REGISTRY_FILE_VERSION);
+        std::fs::remove_dir_all(path.parent().unwrap()).ok();
+    }
+
+    #[test]
+    fn test_registry_survives_partial_write() {
+        let path = registry_path();
+        let mut registry = ModelRegistry::open(&path).unwrap();
+        registry.register(local_model("model_1")).unwrap();
+        let saved = std::fs::read(&path).unwrap();
+
+        // A crash mid-save leaves only a truncated temporary file beside the registry
+        std::fs::write(path.with_extension("crashed.tmp"), &saved[..saved.len() / 2]).unwrap();
+        let mut reopened = ModelRegistry::open(&path).unwrap();
+        assert!(reopened.get("model_1").is_some());
+        reopened.register(local_model("model_2")).unwrap();
+        assert_eq!(ModelRegistry::load_from_path(&path).unwrap().list().len(), 2);
+
+        // A truncated registry file is reported rather than silently starting empty
+        std::fs::write(&path, &saved[..saved.len() / 2]).unwrap();
+        assert!(matches!(ModelRegistry::open(&path), Err(ModelRegistryError::Serialization(_))));
+        std::fs::remove_dir_all(path.parent().unwrap()).ok();
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_concurrent_mutations_are_all_saved() {
+        let path = registry_path();
+        let registry = std::sync::Arc::new(tokio::sync::RwLock::new(ModelRegistry::open(&path).unwrap()));
+        let tasks: Vec<_> = (0..16)
+            .map(|i| {
+                let registry = registry.clone();
+                tokio::spawn(async move { registry.write().await.register(local_model(&format!("model_{}", i))) })
+            })
+            .collect();
+        for task in tasks {
+            task.await.unwrap().unwrap();
+        }
+
+        assert_eq!(ModelRegistry::load_from_path(&path).unwrap().list().len(), 16);
+        let leftovers = std::fs::read_dir(path.parent().unwrap()).unwrap().count();
+        assert_eq!(leftovers, 1, "temporary files should be renamed into place");
+        std::fs::remove_dir_all(path.parent().unwrap()).ok();
+    }
+
+    #[test]
+    fn test_model_version_promotion() {
+        let mut registry = ModelRegistry::new();
+        let mut v1 = local_model("churn");
+        v1.metrics.r2 = Some(0.7);
+        registry.register(v1).unwrap();
+        registry.bind_model("churn", "Customer".to_string(), "churn_risk".to_string(), None, ModelBindingConfig::default())
+            .unwrap();
+
+        let mut v2 = local_model("churn");
+        v2.version = "2.0.0".to_string();
+        v2.metrics.r2 = Some(0.9);
+        registry.register_version(v2.clone()).unwrap();
+        assert!(registry.register_version(v2).is_err());
+        assert_eq!(registry.bound_model("Customer", "churn_risk").unwrap().version, "1.0.0");
+        assert_eq!(registry.list().len(), 1);
+        assert_eq!(registry.get_version("churn", "2.0.0").unwrap().status, ModelStatus::Registered);
+
+        let versions: Vec<_> = registry.compare_versions("churn").unwrap()
+            .into_iter()
+            .map(|c| (c.version, c.primary_metric))
+            .collect();
+        assert_eq!(versions, vec![("1.0.0".to_string(), Some(0.7)), ("2.0.0".to_string(), Some(0.9))]);
+
+        assert!(registry.promote_version("churn", "3.0.0").is_err());
+        registry.promote_version("churn", "2.0.0").unwrap();
+        let bound = registry.bound_model("Customer", "churn_risk").unwrap();
+        assert_eq!(bound.version, "2.0.0");
+        assert_eq!(bound.status, ModelStatus::Bound);
+        assert_eq!(registry.get_version("churn", "1.0.0").unwrap().status, ModelStatus::Registered);
+        assert_eq!(registry.get("churn").unwrap().version, "2.0.0");
+
+        registry.unbind_model("Customer", "churn_risk").unwrap();
+        registry.delete("churn").unwrap();
+        assert!(registry.list_versions("churn").is_empty());
+    }
+
+    #[test]
+    fn test_registry_loads_files_without_versions() {
+        let path = registry_path();
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        let file = serde_json::json!({ "version": 1, "models": [local_model("churn")], "bindings": [] });
+        std::fs::write(&path, file.to_string()).unwrap();
+
+        let mut registry = ModelRegistry::open(&path).unwrap();
+        assert_eq!(registry.active_version("churn"), Some("1.0.0"));
+        let mut v2 = local_model("churn");
+        v2.version = "2.0.0".to_string();
+        registry.register_version(v2).unwrap();
+        registry.promote_version("churn", "2.0.0").unwrap();
+        assert_eq!(ModelRegistry::load_from_path(&path).unwrap().get("churn").unwrap().version, "2.0.0");
+        std::fs::remove_dir_all(path.parent().unwrap()).ok();
+    }
+
+    #[test]
+    fn test_registry_rejects_newer_file_versions() {
+        let path = registry_path();
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, r#"{"version": 3, "models": [], "bindings": []}"#).unwrap();
+        assert!(matches!(ModelRegistry::load_from_path(&path), Err(ModelRegistryError::UnsupportedVersion(3))));
+        std::fs::remove_dir_all(path.parent().unwrap()).ok();
+    }
 }
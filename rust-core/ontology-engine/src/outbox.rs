@@ -0,0 +1,446 @@
+//! Durable outbox for action side effects.
+//!
+//! An executor with an outbox does not run side effects inline. It records each one, with
+//! its templates already substituted, in a single write once the action's operations have
+//! succeeded, and returns. An `OutboxDispatcher` then delivers them in the background,
+//! retrying failures with exponential backoff and dead-lettering effects that keep failing.
+//!
+//! An outbox opened on a file saves every change to it. An effect is marked in flight before
+//! its handler runs; one still in flight when the process stopped is delivered again after
+//! the next open, since the dispatcher may have died mid-delivery. Handlers therefore see the
+//! effect's ID under the `effect_id` config key and receivers deduplicate on it.
+
+use crate::action::SideEffectType;
+use crate::property::{PropertyMap, PropertyValue};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+/// Config key under which handlers receive the effect's ID
+pub const EFFECT_ID_CONFIG_KEY: &str = "effect_id";
+
+/// Executes a side effect once; the outbox does the retrying. `SideEffectHandlers` can be
+/// used with `max_retries: 0`.
+pub type OutboxHandler = Arc<dyn Fn(&SideEffectType, &PropertyMap) -> Result<(), String> + Send + Sync>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SideEffectStatus {
+    /// Waiting for its first or next delivery attempt
+    Enqueued,
+    /// A dispatcher is delivering it
+    InFlight,
+    Delivered,
+    /// Failed on every allowed attempt; not retried again
+    DeadLettered,
+}
+
+/// A side effect recorded in the outbox, and how its delivery is going
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SideEffectRecord {
+    pub effect_id: String,
+    pub action_type_id: String,
+    pub effect_type: SideEffectType,
+    /// Config with the action's parameters substituted
+    pub config: PropertyMap,
+    pub status: SideEffectStatus,
+    pub attempts: u32,
+    pub last_error: Option<String>,
+    pub enqueued_at: DateTime<Utc>,
+    /// Earliest time of the next delivery attempt
+    pub next_attempt_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
+impl SideEffectRecord {
+    pub fn new(action_type_id: &str, effect_type: SideEffectType, config: PropertyMap) -> Self {
+        let now = Utc::now();
+        Self {
+            effect_id: uuid::Uuid::new_v4().to_string(),
+            action_type_id: action_type_id.to_string(),
+            effect_type,
+            config,
+            status: SideEffectStatus::Enqueued,
+            attempts: 0,
+            last_error: None,
+            enqueued_at: now,
+            next_attempt_at: now,
+            finished_at: None,
+        }
+    }
+}
+
+/// Errors reading or writing an outbox file
+#[derive(Debug, thiserror::Error)]
+pub enum OutboxError {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Serialization error: {0}")]
+    Serialization(String),
+}
+
+/// Side effects waiting for delivery and those already delivered or dead-lettered, by ID
+#[derive(Clone, Default)]
+pub struct SideEffectOutbox {
+    effects: Arc<RwLock<HashMap<String, SideEffectRecord>>>,
+    path: Option<PathBuf>,
+}
+
+impl SideEffectOutbox {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Outbox persisted to `path`, loading the effects saved there
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self, OutboxError> {
+        let path = path.into();
+        let mut effects = HashMap::new();
+        if path.exists() {
+            let content = std::fs::read_to_string(&path)?;
+            let saved: Vec<SideEffectRecord> = serde_json::from_str(&content)
+                .map_err(|e| OutboxError::Serialization(format!("Invalid outbox {}: {}", path.display(), e)))?;
+            for mut record in saved {
+                if record.status == SideEffectStatus::InFlight {
+                    record.status = SideEffectStatus::Enqueued;
+                    record.last_error = Some("Interrupted by a restart".to_string());
+                }
+                effects.insert(record.effect_id.clone(), record);
+            }
+        }
+        let outbox = Self {
+            effects: Arc::new(RwLock::new(effects)),
+            path: Some(path),
+        };
+        outbox.save()?;
+        Ok(outbox)
+    }
+
+    /// Record effects for delivery in one write; either all of them are saved or none are
+    pub fn enqueue(&self, records: Vec<SideEffectRecord>) -> Result<(), OutboxError> {
+        let ids: Vec<String> = records.iter().map(|record| record.effect_id.clone()).collect();
+        {
+            let mut effects = self.effects.write().unwrap();
+            for record in records {
+                effects.insert(record.effect_id.clone(), record);
+            }
+        }
+        self.save().inspect_err(|_| {
+            let mut effects = self.effects.write().unwrap();
+            for id in &ids {
+                effects.remove(id);
+            }
+        })
+    }
+
+    pub fn side_effect_status(&self, effect_id: &str) -> Option<SideEffectRecord> {
+        self.effects.read().unwrap().get(effect_id).cloned()
+    }
+
+    /// All effects, oldest first
+    pub fn list(&self) -> Vec<SideEffectRecord> {
+        let mut records: Vec<SideEffectRecord> = self.effects.read().unwrap().values().cloned().collect();
+        records.sort_by(|a, b| a.enqueued_at.cmp(&b.enqueued_at).then_with(|| a.effect_id.cmp(&b.effect_id)));
+        records
+    }
+
+    /// Make a waiting or dead-lettered effect due now. A dead-lettered one gets a fresh set of
+    /// attempts. Returns false if the effect is unknown, in flight or delivered.
+    pub fn retry(&self, effect_id: &str) -> bool {
+        let retried = match self.effects.write().unwrap().get_mut(effect_id) {
+            Some(record) if matches!(record.status, SideEffectStatus::Enqueued | SideEffectStatus::DeadLettered) => {
+                if record.status == SideEffectStatus::DeadLettered {
+                    record.attempts = 0;
+                    record.finished_at = None;
+                }
+                record.status = SideEffectStatus::Enqueued;
+                record.next_attempt_at = Utc::now();
+                true
+            }
+            _ => false,
+        };
+        if retried {
+            self.save_or_warn();
+        }
+        retried
+    }
+
+    /// Mark effects due for delivery as in flight and return them, oldest first
+    fn claim_due(&self, now: DateTime<Utc>) -> Vec<SideEffectRecord> {
+        let claimed: Vec<SideEffectRecord> = {
+            let mut effects = self.effects.write().unwrap();
+            let mut due: Vec<&mut SideEffectRecord> = effects
+                .values_mut()
+                .filter(|record| record.status == SideEffectStatus::Enqueued && record.next_attempt_at <= now)
+                .collect();
+            due.sort_by_key(|record| record.enqueued_at);
+            due.into_iter()
+                .map(|record| {
+                    record.status = SideEffectStatus::InFlight;
+                    record.attempts += 1;
+                    record.clone()
+                })
+                .collect()
+        };
+        if !claimed.is_empty() {
+            self.save_or_warn();
+        }
+        claimed
+    }
+
+    /// Record the outcome of a delivery attempt
+    fn finish_attempt(&self, effect_id: &str, outcome: Result<(), String>, policy: &RetryPolicy) {
+        if let Some(record) = self.effects.write().unwrap().get_mut(effect_id) {
+            let now = Utc::now();
+            match outcome {
+                Ok(()) => {
+                    record.status = SideEffectStatus::Delivered;
+                    record.finished_at = Some(now);
+                }
+                Err(e) if record.attempts >= policy.max_attempts => {
+                    record.status = SideEffectStatus::DeadLettered;
+                    record.last_error = Some(e);
+                    record.finished_at = Some(now);
+                }
+                Err(e) => {
+                    record.status = SideEffectStatus::Enqueued;
+                    record.last_error = Some(e);
+                    record.next_attempt_at = now + policy.backoff(record.attempts);
+                }
+            }
+        }
+        self.save_or_warn();
+    }
+
+    fn save(&self) -> Result<(), OutboxError> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let content = serde_json::to_vec_pretty(&self.list()).map_err(|e| OutboxError::Serialization(e.to_string()))?;
+        // Write then rename so a crash never leaves a truncated outbox
+        let partial = path.with_extension("tmp");
+        std::fs::write(&partial, content)?;
+        std::fs::rename(&partial, path)?;
+        Ok(())
+    }
+
+    fn save_or_warn(&self) {
+        if let Err(e) = self.save() {
+            tracing::warn!("failed to save side effect outbox: {}", e);
+        }
+    }
+}
+
+/// How often and how patiently failed deliveries are retried
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Attempts before an effect is dead-lettered, the first one included
+    pub max_attempts: u32,
+    /// Delay after the first failure; it doubles with each further failure
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(300),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Delay before the attempt after `attempts` failed ones
+    fn backoff(&self, attempts: u32) -> chrono::Duration {
+        let factor = 2u32.saturating_pow(attempts.saturating_sub(1));
+        let delay = self.initial_backoff.saturating_mul(factor).min(self.max_backoff);
+        chrono::Duration::from_std(delay).unwrap_or(chrono::Duration::MAX)
+    }
+}
+
+/// Delivers due effects from an outbox through a handler
+#[derive(Clone)]
+pub struct OutboxDispatcher {
+    outbox: SideEffectOutbox,
+    handler: OutboxHandler,
+    policy: RetryPolicy,
+}
+
+impl OutboxDispatcher {
+    pub fn new(outbox: SideEffectOutbox, handler: OutboxHandler) -> Self {
+        Self {
+            outbox,
+            handler,
+            policy: RetryPolicy::default(),
+        }
+    }
+
+    pub fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Attempt every effect that is due now and return how many were attempted
+    pub fn dispatch_due(&self) -> usize {
+        let claimed = self.outbox.claim_due(Utc::now());
+        for record in &claimed {
+            let mut config = record.config.clone();
+            config.insert(EFFECT_ID_CONFIG_KEY.to_string(), PropertyValue::String(record.effect_id.clone()));
+            let outcome = (self.handler)(&record.effect_type, &config);
+            self.outbox.finish_attempt(&record.effect_id, outcome, &self.policy);
+        }
+        claimed.len()
+    }
+
+    /// Dispatch on a background thread every `interval` until the returned handle is stopped.
+    /// Handlers may block (e.g. on a slow SMTP server) without holding up action responses.
+    pub fn spawn(self, interval: Duration) -> DispatcherHandle {
+        let stop = Arc::new(AtomicBool::new(false));
+        let stopped = stop.clone();
+        let thread = std::thread::spawn(move || {
+            while !stopped.load(Ordering::SeqCst) {
+                if self.dispatch_due() == 0 {
+                    std::thread::sleep(interval);
+                }
+            }
+        });
+        DispatcherHandle { stop, thread }
+    }
+}
+
+/// A running background dispatcher
+pub struct DispatcherHandle {
+    stop: Arc<AtomicBool>,
+    thread: JoinHandle<()>,
+}
+
+impl DispatcherHandle {
+    /// Stop after the current round and wait for it. Returns false if the dispatcher had
+    /// already died (a handler panicked).
+    pub fn stop(self) -> bool {
+        self.stop.store(true, Ordering::SeqCst);
+        self.thread.join().is_ok()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.thread.is_finished()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+    use std::sync::Mutex;
+
+    fn webhook(url: &str) -> SideEffectRecord {
+        let mut config = PropertyMap::new();
+        config.insert("url".to_string(), PropertyValue::String(url.to_string()));
+        SideEffectRecord::new("notify", SideEffectType::Webhook, config)
+    }
+
+    fn effect_id(config: &PropertyMap) -> String {
+        match config.get(EFFECT_ID_CONFIG_KEY) {
+            Some(PropertyValue::String(id)) => id.clone(),
+            other => panic!("missing effect id: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_retries_with_backoff_then_dead_letters() {
+        let outbox = SideEffectOutbox::new();
+        let (flaky, broken) = (webhook("https://flaky"), webhook("https://broken"));
+        let (flaky_id, broken_id) = (flaky.effect_id.clone(), broken.effect_id.clone());
+        outbox.enqueue(vec![flaky, broken]).unwrap();
+
+        let calls = Arc::new(Mutex::new(HashMap::<String, u32>::new()));
+        let seen = calls.clone();
+        let handler: OutboxHandler = Arc::new(move |_, config| {
+            let url = config.get("url").unwrap().to_string();
+            let mut calls = seen.lock().unwrap();
+            let count = calls.entry(url.clone()).or_default();
+            *count += 1;
+            match (url.as_str(), *count) {
+                ("https://flaky", 1) => Err("503".to_string()),
+                ("https://flaky", _) => Ok(()),
+                _ => Err("404".to_string()),
+            }
+        });
+        let policy = RetryPolicy { max_attempts: 3, initial_backoff: Duration::from_secs(3600), max_backoff: Duration::from_secs(3600) };
+        let dispatcher = OutboxDispatcher::new(outbox.clone(), handler).with_retry_policy(policy.clone());
+
+        // Both fail once, and back off rather than being retried straight away
+        assert_eq!(dispatcher.dispatch_due(), 2);
+        assert_eq!(dispatcher.dispatch_due(), 0);
+        let flaky = outbox.side_effect_status(&flaky_id).unwrap();
+        assert_eq!((flaky.status, flaky.attempts, flaky.last_error.as_deref()), (SideEffectStatus::Enqueued, 1, Some("503")));
+        assert!(flaky.next_attempt_at > Utc::now() + chrono::Duration::minutes(59));
+
+        let dispatcher = dispatcher.with_retry_policy(RetryPolicy { initial_backoff: Duration::ZERO, ..policy });
+        assert!(outbox.retry(&flaky_id) && outbox.retry(&broken_id));
+        while dispatcher.dispatch_due() > 0 {}
+        assert_eq!(outbox.side_effect_status(&flaky_id).unwrap().status, SideEffectStatus::Delivered);
+        let broken = outbox.side_effect_status(&broken_id).unwrap();
+        assert_eq!((broken.status, broken.attempts), (SideEffectStatus::DeadLettered, 3));
+        assert_eq!(calls.lock().unwrap().get("https://broken"), Some(&3));
+
+        // A dead-lettered effect can be given another round of attempts
+        assert!(!outbox.retry(&flaky_id));
+        assert!(outbox.retry(&broken_id));
+        assert_eq!(dispatcher.dispatch_due(), 1);
+        assert_eq!(outbox.side_effect_status(&broken_id).unwrap().attempts, 1);
+    }
+
+    #[test]
+    fn test_effect_interrupted_by_a_crash_is_delivered_once_after_restart() {
+        let path = std::env::temp_dir().join(format!("outbox-{}.json", uuid::Uuid::new_v4()));
+        let outbox = SideEffectOutbox::open(&path).unwrap();
+        let record = webhook("https://hooks.example/retired");
+        let id = record.effect_id.clone();
+        outbox.enqueue(vec![record]).unwrap();
+
+        // The receiver applies each effect ID once, however often it is delivered
+        let applied = Arc::new(Mutex::new(HashSet::new()));
+        let deliveries = Arc::new(Mutex::new(0));
+        let receiver = |crash: bool| -> OutboxHandler {
+            let (applied, deliveries) = (applied.clone(), deliveries.clone());
+            Arc::new(move |_, config| {
+                *deliveries.lock().unwrap() += 1;
+                applied.lock().unwrap().insert(effect_id(config));
+                if crash {
+                    panic!("dispatcher killed after delivering");
+                }
+                Ok(())
+            })
+        };
+
+        // The dispatcher dies after the delivery but before recording it
+        let handle = OutboxDispatcher::new(outbox, receiver(true)).spawn(Duration::from_millis(5));
+        while !handle.is_finished() {
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        assert!(!handle.stop());
+
+        let outbox = SideEffectOutbox::open(&path).unwrap();
+        let interrupted = outbox.side_effect_status(&id).unwrap();
+        assert_eq!(interrupted.status, SideEffectStatus::Enqueued);
+        assert_eq!(interrupted.last_error.as_deref(), Some("Interrupted by a restart"));
+
+        let handle = OutboxDispatcher::new(outbox.clone(), receiver(false)).spawn(Duration::from_millis(5));
+        while outbox.side_effect_status(&id).unwrap().status != SideEffectStatus::Delivered {
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        assert!(handle.stop());
+        assert_eq!(*deliveries.lock().unwrap(), 2);
+        assert_eq!(applied.lock().unwrap().iter().collect::<Vec<_>>(), vec![&id]);
+        assert_eq!(SideEffectOutbox::open(&path).unwrap().side_effect_status(&id).unwrap().attempts, 2);
+        std::fs::remove_file(&path).ok();
+    }
+}
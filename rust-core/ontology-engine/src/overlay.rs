@@ -0,0 +1,488 @@
+//! Environment overlays layered on a base ontology.
+//!
+//! An overlay lists definitions in the same shape as the ontology itself, under an `overlay`
+//! key. An entry whose ID is new is added as-is. An entry naming an existing definition is
+//! merged into it: its fields replace the definition's fields, and its `properties` are
+//! matched by ID, so new properties are appended and existing ones replaced.
+//!
+//! ```yaml
+//! overlay:
+//!   objectTypes:
+//!     - id: plant
+//!       backingDatasource: plants_staging
+//!       properties:
+//!         - id: capacity
+//!           type: double
+//!           override: true
+//!         - id: fixture_batch
+//!           type: string
+//! ```
+//!
+//! Changing anything a lower layer already defines requires `override: true`, on the
+//! property or on the whole definition; otherwise the overlay fails with a conflict.
+//! Environment-specific fields such as `backingDatasource` can always be set. Overlays apply
+//! in order, each on top of the result of the previous ones.
+
+use crate::load_error::{DefinitionKind, OntologyLoadError, OntologyLoadErrors};
+use crate::meta_model::{ActionTypeDef, FunctionTypeDef, InterfaceDef, LinkTypeDef, ObjectType, OntologyDef};
+use crate::property::Property;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value as JsonValue};
+
+/// Fields any overlay may set without `override: true`
+const ENVIRONMENT_FIELDS: &[&str] = &["backingDatasource"];
+
+const OVERRIDE_KEY: &str = "override";
+
+/// A set of additions and overrides for a base ontology
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OntologyOverlay {
+    /// Where the overlay came from (e.g. its file path), used in errors
+    #[serde(skip)]
+    pub name: String,
+
+    #[serde(rename = "objectTypes")]
+    #[serde(default)]
+    pub object_types: Vec<JsonValue>,
+
+    #[serde(rename = "linkTypes")]
+    #[serde(default)]
+    pub link_types: Vec<JsonValue>,
+
+    #[serde(rename = "actionTypes")]
+    #[serde(default)]
+    pub action_types: Vec<JsonValue>,
+
+    #[serde(default)]
+    pub interfaces: Vec<JsonValue>,
+
+    #[serde(rename = "functionTypes")]
+    #[serde(default)]
+    pub function_types: Vec<JsonValue>,
+}
+
+#[derive(Deserialize)]
+struct OverlayFile {
+    overlay: OntologyOverlay,
+}
+
+impl OntologyOverlay {
+    /// Parse an overlay file; `name` identifies it in errors
+    pub fn from_yaml(name: impl Into<String>, content: &str) -> Result<Self, OntologyLoadErrors> {
+        let file: OverlayFile = serde_yaml::from_str(content)
+            .map_err(|e| OntologyLoadError::from_yaml_error(&e))?;
+        Ok(Self { name: name.into(), ..file.overlay })
+    }
+
+    pub fn from_json(name: impl Into<String>, content: &str) -> Result<Self, OntologyLoadErrors> {
+        let file: OverlayFile = serde_json::from_str(content)
+            .map_err(|e| OntologyLoadError::from_json_error(&e))?;
+        Ok(Self { name: name.into(), ..file.overlay })
+    }
+
+    /// Layer this overlay onto a definition, reporting every conflict. On error the
+    /// definition is left unchanged.
+    pub fn apply(&self, definition: &mut OntologyDef) -> Result<(), OntologyLoadErrors> {
+        let mut draft = definition.clone();
+        let mut errors = Vec::new();
+        apply_entries(&self.name, &mut draft.object_types, &self.object_types, &mut errors);
+        apply_entries(&self.name, &mut draft.link_types, &self.link_types, &mut errors);
+        apply_entries(&self.name, &mut draft.action_types, &self.action_types, &mut errors);
+        apply_entries(&self.name, &mut draft.interfaces, &self.interfaces, &mut errors);
+        apply_entries(&self.name, &mut draft.function_types, &self.function_types, &mut errors);
+        if !errors.is_empty() {
+            return Err(OntologyLoadErrors(errors));
+        }
+        *definition = draft;
+        Ok(())
+    }
+}
+
+/// Apply overlays in order, stopping at the first one that fails
+pub fn apply_overlays(
+    mut definition: OntologyDef,
+    overlays: &[OntologyOverlay],
+) -> Result<OntologyDef, OntologyLoadErrors> {
+    for overlay in overlays {
+        overlay.apply(&mut definition)?;
+    }
+    Ok(definition)
+}
+
+/// A kind of definition an overlay can add or override
+trait LayeredDefinition: Serialize + DeserializeOwned {
+    const KIND: DefinitionKind;
+    /// Whether `properties` holds property definitions merged by ID
+    const MERGES_PROPERTIES: bool;
+
+    fn id(&self) -> &str;
+}
+
+impl LayeredDefinition for ObjectType {
+    const KIND: DefinitionKind = DefinitionKind::ObjectType;
+    const MERGES_PROPERTIES: bool = true;
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+}
+
+impl LayeredDefinition for LinkTypeDef {
+    const KIND: DefinitionKind = DefinitionKind::LinkType;
+    const MERGES_PROPERTIES: bool = true;
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+}
+
+impl LayeredDefinition for ActionTypeDef {
+    const KIND: DefinitionKind = DefinitionKind::ActionType;
+    const MERGES_PROPERTIES: bool = false;
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+}
+
+impl LayeredDefinition for InterfaceDef {
+    const KIND: DefinitionKind = DefinitionKind::Interface;
+    const MERGES_PROPERTIES: bool = true;
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+}
+
+impl LayeredDefinition for FunctionTypeDef {
+    const KIND: DefinitionKind = DefinitionKind::FunctionType;
+    const MERGES_PROPERTIES: bool = false;
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+}
+
+fn apply_entries<T: LayeredDefinition>(
+    overlay: &str,
+    definitions: &mut Vec<T>,
+    entries: &[JsonValue],
+    errors: &mut Vec<OntologyLoadError>,
+) {
+    for entry in entries {
+        let Some((id, mut entry)) = identified_entry(overlay, T::KIND, entry, errors) else {
+            continue;
+        };
+        let overriding = take_override(&mut entry);
+        let position = definitions.iter().position(|definition| definition.id() == id);
+
+        let merged = match position {
+            None => {
+                if T::MERGES_PROPERTIES {
+                    // Flags on the properties of a new definition mean nothing; drop them
+                    if let Some(JsonValue::Array(properties)) = entry.get_mut("properties") {
+                        for property in properties.iter_mut().filter_map(JsonValue::as_object_mut) {
+                            property.remove(OVERRIDE_KEY);
+                        }
+                    }
+                }
+                entry
+            }
+            Some(index) => {
+                let Ok(JsonValue::Object(mut current)) = serde_json::to_value(&definitions[index]) else {
+                    continue;
+                };
+                let before = errors.len();
+                merge_fields::<T>(overlay, &id, &mut current, entry, overriding, errors);
+                if errors.len() > before {
+                    continue;
+                }
+                current
+            }
+        };
+
+        match serde_json::from_value::<T>(JsonValue::Object(merged)) {
+            Ok(definition) => match position {
+                Some(index) => definitions[index] = definition,
+                None => definitions.push(definition),
+            },
+            Err(e) => errors.push(OntologyLoadError::InvalidDefinition {
+                kind: T::KIND,
+                id: id.clone(),
+                message: format!("Overlay '{}' leaves {} '{}' invalid: {}", overlay, T::KIND, id, e),
+            }),
+        }
+    }
+}
+
+/// Merge an overlay entry's fields into an existing definition
+fn merge_fields<T: LayeredDefinition>(
+    overlay: &str,
+    id: &str,
+    current: &mut Map<String, JsonValue>,
+    entry: Map<String, JsonValue>,
+    overriding: bool,
+    errors: &mut Vec<OntologyLoadError>,
+) {
+    for (field, value) in entry {
+        if field == "id" {
+            continue;
+        }
+        if T::MERGES_PROPERTIES && field == "properties" {
+            if let (Some(JsonValue::Array(properties)), JsonValue::Array(entries)) = (current.get_mut("properties"), &value) {
+                merge_properties(overlay, T::KIND, id, properties, entries, overriding, errors);
+                continue;
+            }
+        }
+        if current.get(&field) == Some(&value) {
+            continue;
+        }
+        if overriding || ENVIRONMENT_FIELDS.contains(&field.as_str()) {
+            current.insert(field, value);
+        } else {
+            errors.push(OntologyLoadError::OverlayConflict {
+                overlay: overlay.to_string(),
+                kind: T::KIND,
+                id: id.to_string(),
+                field: format!("field '{}'", field),
+            });
+        }
+    }
+}
+
+/// Append new properties and replace overridden ones, matching by ID
+fn merge_properties(
+    overlay: &str,
+    kind: DefinitionKind,
+    id: &str,
+    properties: &mut Vec<JsonValue>,
+    entries: &[JsonValue],
+    overriding: bool,
+    errors: &mut Vec<OntologyLoadError>,
+) {
+    for entry in entries {
+        let Some((property_id, mut entry)) = identified_entry(overlay, DefinitionKind::Property, entry, errors) else {
+            continue;
+        };
+        let overriding = take_override(&mut entry) || overriding;
+        let entry = JsonValue::Object(entry);
+        let position = properties
+            .iter()
+            .position(|p| p.get("id").and_then(JsonValue::as_str) == Some(property_id.as_str()));
+        let Some(index) = position else {
+            properties.push(entry);
+            continue;
+        };
+
+        // Compare parsed definitions so restating a property with its defaults spelled out
+        // is not a change
+        let unchanged = match (
+            serde_json::from_value::<Property>(properties[index].clone()),
+            serde_json::from_value::<Property>(entry.clone()),
+        ) {
+            (Ok(current), Ok(replacement)) => current == replacement,
+            _ => false,
+        };
+        if unchanged {
+            continue;
+        }
+        if overriding {
+            properties[index] = entry;
+        } else {
+            errors.push(OntologyLoadError::OverlayConflict {
+                overlay: overlay.to_string(),
+                kind,
+                id: id.to_string(),
+                field: format!("property '{}'", property_id),
+            });
+        }
+    }
+}
+
+/// An overlay entry as a mapping with its ID, or an error if it is not one
+fn identified_entry(
+    overlay: &str,
+    kind: DefinitionKind,
+    entry: &JsonValue,
+    errors: &mut Vec<OntologyLoadError>,
+) -> Option<(String, Map<String, JsonValue>)> {
+    let id = entry.get("id").and_then(JsonValue::as_str);
+    match (entry.as_object(), id) {
+        (Some(entry), Some(id)) => Some((id.to_string(), entry.clone())),
+        _ => {
+            errors.push(OntologyLoadError::InvalidDefinition {
+                kind,
+                id: String::new(),
+                message: format!("Overlay '{}' has a {} entry without an 'id'", overlay, kind),
+            });
+            None
+        }
+    }
+}
+
+fn take_override(entry: &mut Map<String, JsonValue>) -> bool {
+    entry.remove(OVERRIDE_KEY).and_then(|v| v.as_bool()).unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::meta_model::{OntologyConfig, OntologyRuntime};
+    use crate::property::PropertyType;
+
+    const BASE: &str = r#"
+ontology:
+  objectTypes:
+    - id: plant
+      displayName: Plant
+      primaryKey: id
+      backingDatasource: plants
+      properties:
+        - id: id
+          type: string
+        - id: capacity
+          type: integer
+  linkTypes: []
+"#;
+
+    fn base() -> OntologyConfig {
+        serde_yaml::from_str(BASE).unwrap()
+    }
+
+    fn overlay(name: &str, yaml: &str) -> OntologyOverlay {
+        OntologyOverlay::from_yaml(name, yaml).unwrap()
+    }
+
+    #[test]
+    fn test_overlay_adds_types_and_properties() {
+        let staging = overlay("staging.yaml", r#"
+overlay:
+  objectTypes:
+    - id: plant
+      backingDatasource: plants_staging
+      properties:
+        - id: fixture_batch
+          type: string
+    - id: fixture
+      displayName: Fixture
+      primaryKey: id
+      properties:
+        - id: id
+          type: string
+          override: true
+"#);
+        let ontology = OntologyRuntime::from_layers(base(), &[staging]).unwrap();
+
+        let plant = ontology.get_object_type("plant").unwrap();
+        assert_eq!(plant.backing_datasource.as_deref(), Some("plants_staging"));
+        let ids: Vec<&str> = plant.properties.iter().map(|p| p.id.as_str()).collect();
+        assert_eq!(ids, vec!["id", "capacity", "fixture_batch"]);
+        assert_eq!(plant.display_name, "Plant");
+        assert!(ontology.get_object_type("fixture").is_some());
+    }
+
+    #[test]
+    fn test_overlay_overrides_flagged_definitions() {
+        let prod = overlay("prod.yaml", r#"
+overlay:
+  objectTypes:
+    - id: plant
+      properties:
+        - id: capacity
+          type: double
+          override: true
+"#);
+        let renamed = overlay("labels.yaml", r#"
+overlay:
+  objectTypes:
+    - id: plant
+      displayName: Power Plant
+      override: true
+"#);
+        let ontology = OntologyRuntime::from_layers(base(), &[prod, renamed]).unwrap();
+
+        let plant = ontology.get_object_type("plant").unwrap();
+        assert_eq!(plant.get_property("capacity").unwrap().property_type, PropertyType::Double);
+        assert_eq!(plant.display_name, "Power Plant");
+        assert_eq!(plant.properties.len(), 2);
+
+        // Restating a property unchanged needs no flag
+        let restated = overlay("restated.yaml", r#"
+overlay:
+  objectTypes:
+    - id: plant
+      properties:
+        - id: capacity
+          type: integer
+"#);
+        assert!(OntologyRuntime::from_layers(base(), &[restated]).is_ok());
+    }
+
+    #[test]
+    fn test_overlay_conflicts_without_override() {
+        let conflicting = overlay("dev.yaml", r#"
+overlay:
+  objectTypes:
+    - id: plant
+      displayName: Dev Plant
+      properties:
+        - id: capacity
+          type: double
+  linkTypes:
+    - id: plant_parts
+"#);
+        let errors = match OntologyRuntime::from_layers(base(), &[conflicting]) {
+            Err(errors) => errors,
+            Ok(_) => panic!("expected conflicts"),
+        };
+        let codes: Vec<&str> = errors.iter().map(|e| e.code()).collect();
+        assert_eq!(codes, vec!["overlay_conflict", "overlay_conflict", "invalid_definition"]);
+        let messages: Vec<String> = errors.iter().map(|e| e.to_string()).collect();
+        assert_eq!(
+            messages[0],
+            "Overlay 'dev.yaml' changes field 'displayName' of object type 'plant' without `override: true`"
+        );
+        assert_eq!(
+            messages[1],
+            "Overlay 'dev.yaml' changes property 'capacity' of object type 'plant' without `override: true`"
+        );
+
+        // A failed overlay leaves the definition untouched
+        let mut definition = base().ontology;
+        let conflicting = overlay("dev.yaml", r#"
+overlay:
+  objectTypes:
+    - id: plant
+      backingDatasource: plants_dev
+      displayName: Dev Plant
+"#);
+        assert!(conflicting.apply(&mut definition).is_err());
+        assert_eq!(definition.object_types[0].backing_datasource.as_deref(), Some("plants"));
+    }
+
+    #[test]
+    fn test_later_overlays_see_earlier_ones() {
+        let first = overlay("first.yaml", r#"
+overlay:
+  objectTypes:
+    - id: plant
+      properties:
+        - id: region
+          type: string
+"#);
+        let second = overlay("second.yaml", r#"
+overlay:
+  objectTypes:
+    - id: plant
+      properties:
+        - id: region
+          type: integer
+"#);
+        let errors = match OntologyRuntime::from_layers(base(), &[first, second]) {
+            Err(errors) => errors,
+            Ok(_) => panic!("expected a conflict"),
+        };
+        assert!(errors.to_string().contains("Overlay 'second.yaml' changes property 'region'"));
+    }
+}
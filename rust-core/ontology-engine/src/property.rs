@@ -2,7 +2,11 @@ use serde::{Deserialize, Serialize, Serializer};
 use serde::ser::SerializeMap;
 use std::collections::HashMap;
 use std::str::FromStr;
+use std::sync::{Arc, OnceLock, RwLock};
 use chrono::{DateTime, Utc};
+use regex::Regex;
+use crate::geo::{self, CoordinateValidation, GeoOptions};
+use crate::reference::ObjectRef;
 
 /// Struct definition for nested object types
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -163,11 +167,16 @@ impl PropertyType {
         )
     }
     
+    /// Check if values of this type have a meaningful ordering for sorting
+    pub fn is_sortable(&self) -> bool {
+        self.is_simple() && !matches!(self, PropertyType::GeoJSON | PropertyType::GeoJSONAlt)
+    }
+    
     /// Get the underlying simple type if this is a simple type variant
     pub fn as_simple(&self) -> Option<Self> {
         match self {
-            PropertyType::String | PropertyType::Int => Some(PropertyType::String),
-            PropertyType::Integer => Some(PropertyType::Integer),
+            PropertyType::String => Some(PropertyType::String),
+            PropertyType::Integer | PropertyType::Int => Some(PropertyType::Integer),
             PropertyType::Double | PropertyType::Float => Some(PropertyType::Double),
             PropertyType::Boolean | PropertyType::Bool => Some(PropertyType::Boolean),
             PropertyType::Date => Some(PropertyType::Date),
@@ -188,6 +197,13 @@ pub struct Property {
     #[serde(default)]
     pub display_name: Option<String>,
     
+    /// Display names by locale (e.g. `fr`, `pt-BR`), used in place of `displayName` when
+    /// rendering for that locale
+    #[serde(rename = "displayNames")]
+    #[serde(default)]
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub display_names: HashMap<String, String>,
+    
     #[serde(rename = "type")]
     #[serde(deserialize_with = "deserialize_property_type")]
     pub property_type: PropertyType,
@@ -233,9 +249,37 @@ pub struct Property {
     #[serde(default)]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub model_binding: Option<String>, // Model ID
+    
+    // Position of this property when rendered by frontends (lower first)
+    #[serde(rename = "displayOrder")]
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub display_order: Option<u32>,
+    
+    // How the search store indexes this property; full indexing when unset
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub indexing: Option<IndexingHint>,
+    
+    // Object type an object reference property points at; bare object IDs are read as this type
+    #[serde(rename = "referenceTarget")]
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reference_target: Option<String>,
+    
+    // CRS and coordinate validation of a GeoJSON property; WGS84, lenient when unset
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub geo: Option<GeoOptions>,
+    
+    // Name of a validator in the `ValidatorRegistry` run after the built-in rules
+    #[serde(rename = "customValidator")]
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub custom_validator: Option<String>,
 }
 
-fn deserialize_property_type<'de, D>(deserializer: D) -> Result<PropertyType, D::Error>
+pub(crate) fn deserialize_property_type<'de, D>(deserializer: D) -> Result<PropertyType, D::Error>
 where
     D: serde::Deserializer<'de>,
 {
@@ -248,37 +292,37 @@ where
             PropertyType::from_str(&s).map_err(D::Error::custom)
         }
         serde_json::Value::Object(mut obj) => {
-            // Check for complex types
+            // Check for complex types; nested types take the same string or object forms
+            let nested = |value: serde_json::Value| deserialize_property_type(value).map_err(D::Error::custom);
             if obj.contains_key("elementType") {
                 let element_type_val = obj.remove("elementType")
                     .ok_or_else(|| D::Error::custom("array type missing elementType"))?;
-                let element_type: PropertyType = serde_json::from_value(element_type_val)
-                    .map_err(D::Error::custom)?;
                 Ok(PropertyType::Array {
-                    element_type: Box::new(element_type),
+                    element_type: Box::new(nested(element_type_val)?),
                 })
             } else if obj.contains_key("keyType") && obj.contains_key("valueType") {
                 let key_type_val = obj.remove("keyType")
                     .ok_or_else(|| D::Error::custom("map type missing keyType"))?;
                 let value_type_val = obj.remove("valueType")
                     .ok_or_else(|| D::Error::custom("map type missing valueType"))?;
-                let key_type: PropertyType = serde_json::from_value(key_type_val)
-                    .map_err(D::Error::custom)?;
-                let value_type: PropertyType = serde_json::from_value(value_type_val)
-                    .map_err(D::Error::custom)?;
                 Ok(PropertyType::Map {
-                    key_type: Box::new(key_type),
-                    value_type: Box::new(value_type),
+                    key_type: Box::new(nested(key_type_val)?),
+                    value_type: Box::new(nested(value_type_val)?),
                 })
             } else if obj.contains_key("types") {
                 let types_val = obj.remove("types")
                     .ok_or_else(|| D::Error::custom("union type missing types"))?;
-                let types: Vec<PropertyType> = serde_json::from_value(types_val)
+                let types: Vec<serde_json::Value> = serde_json::from_value(types_val)
                     .map_err(D::Error::custom)?;
-                Ok(PropertyType::Union { types })
+                Ok(PropertyType::Union {
+                    types: types.into_iter().map(nested).collect::<Result<_, _>>()?,
+                })
+            } else if obj.contains_key("fields") {
+                // A struct, written as its definition: `{id, fields: [...]}`
+                let struct_def: StructDef = serde_json::from_value(serde_json::Value::Object(obj))
+                    .map_err(D::Error::custom)?;
+                Ok(PropertyType::Object(struct_def))
             } else {
-                // Try to deserialize as Object with struct definition
-                // For now, fall back to trying string deserialization
                 Err(D::Error::custom("Unknown property type format"))
             }
         }
@@ -305,6 +349,52 @@ pub enum PropertyFormat {
     },
 }
 
+/// How a property is indexed in the search store. Properties that are never searched
+/// (large text blobs, raw GeoJSON) can skip indexing entirely to keep the index small.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IndexingHint {
+    /// Kept in the stored document but not searchable: no filtering or sorting
+    NotIndexed,
+    /// Exact-match keyword only, without a full-text field
+    KeywordOnly,
+    /// Full-text only, without a keyword field: matchable but not sortable
+    TextOnly,
+    /// Default indexing for the property's type (text plus keyword for strings)
+    #[default]
+    Full,
+}
+
+impl IndexingHint {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            IndexingHint::NotIndexed => "not_indexed",
+            IndexingHint::KeywordOnly => "keyword_only",
+            IndexingHint::TextOnly => "text_only",
+            IndexingHint::Full => "full",
+        }
+    }
+    
+    /// Parse a hint from its serialized name (e.g. a `sys:indexing` annotation)
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "not_indexed" => Some(IndexingHint::NotIndexed),
+            "keyword_only" => Some(IndexingHint::KeywordOnly),
+            "text_only" => Some(IndexingHint::TextOnly),
+            "full" => Some(IndexingHint::Full),
+            _ => None,
+        }
+    }
+    
+    pub fn is_filterable(&self) -> bool {
+        *self != IndexingHint::NotIndexed
+    }
+    
+    pub fn is_sortable(&self) -> bool {
+        matches!(self, IndexingHint::KeywordOnly | IndexingHint::Full)
+    }
+}
+
 /// Deprecation information for properties
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct DeprecationInfo {
@@ -330,14 +420,159 @@ pub struct PropertyValidation {
     #[serde(default)]
     pub max: Option<f64>,
     
+    /// Regex the whole string value must match
     #[serde(default)]
-    pub pattern: Option<String>, // Regex pattern
+    pub pattern: Option<String>,
     
     #[serde(default)]
     pub enum_values: Option<Vec<String>>,
+    
+    /// Earliest allowed date/datetime, as `YYYY-MM-DD` or RFC 3339
+    #[serde(default)]
+    pub min_date: Option<String>,
+    
+    /// Latest allowed date/datetime, as `YYYY-MM-DD` or RFC 3339
+    #[serde(default)]
+    pub max_date: Option<String>,
+    
+    /// GeoJSON geometry types a value may hold (e.g. `Point` and `MultiPoint` for a centroid).
+    /// A feature is checked by its geometry and a feature collection by each feature's.
+    #[serde(default, alias = "geometryTypes")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub geometry_types: Option<Vec<String>>,
+}
+
+impl PropertyValidation {
+    /// Check the rules themselves are usable (the pattern compiles, date bounds parse and
+    /// are in order)
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(pattern) = &self.pattern {
+            pattern_regex(pattern)?;
+        }
+        let min_date = self.min_date.as_deref().map(parse_date_bound).transpose()?;
+        let max_date = self.max_date.as_deref().map(parse_date_bound).transpose()?;
+        if let (Some(min), Some(max)) = (min_date, max_date) {
+            if min > max {
+                return Err(format!(
+                    "min_date {} is after max_date {}",
+                    self.min_date.as_deref().unwrap_or_default(),
+                    self.max_date.as_deref().unwrap_or_default()
+                ));
+            }
+        }
+        for geometry_type in self.geometry_types.iter().flatten() {
+            if !crate::geometry::GEOMETRY_TYPES.contains(&geometry_type.as_str()) {
+                return Err(format!(
+                    "geometry_types has '{}', which is not a GeoJSON geometry type ({})",
+                    geometry_type,
+                    crate::geometry::GEOMETRY_TYPES.join(", ")
+                ));
+            }
+        }
+        Ok(())
+    }
+    
+    /// Whether date bounds are set, which only apply to date and datetime properties
+    pub fn has_date_bounds(&self) -> bool {
+        self.min_date.is_some() || self.max_date.is_some()
+    }
+}
+
+/// Parse an ISO 8601 `YYYY-MM-DD` date value
+pub(crate) fn parse_date(s: &str) -> Result<DateTime<Utc>, String> {
+    chrono::NaiveDate::parse_from_str(s, "%Y-%m-%d")
+        .map(|date| date.and_time(chrono::NaiveTime::MIN).and_utc())
+        .map_err(|e| format!("'{}' is not an ISO 8601 date (YYYY-MM-DD): {}", s, e))
+}
+
+/// Parse an RFC 3339 datetime value
+pub(crate) fn parse_datetime(s: &str) -> Result<DateTime<Utc>, String> {
+    DateTime::parse_from_rfc3339(s)
+        .map(|dt| dt.with_timezone(&Utc))
+        .map_err(|e| format!("'{}' is not an RFC 3339 datetime: {}", s, e))
+}
+
+/// A `min_date`/`max_date` bound: a date (midnight UTC) or an RFC 3339 datetime
+fn parse_date_bound(s: &str) -> Result<DateTime<Utc>, String> {
+    parse_datetime(s)
+        .or_else(|_| parse_date(s))
+        .map_err(|_| format!("date bound '{}' is neither YYYY-MM-DD nor RFC 3339", s))
+}
+
+/// Compiled `pattern` validations, shared so validating many values compiles each pattern once
+static PATTERN_CACHE: OnceLock<RwLock<HashMap<String, Arc<Regex>>>> = OnceLock::new();
+
+/// The regex for a validation pattern, anchored so it must match the whole value
+pub(crate) fn pattern_regex(pattern: &str) -> Result<Arc<Regex>, String> {
+    let cache = PATTERN_CACHE.get_or_init(Default::default);
+    if let Some(regex) = cache.read().unwrap_or_else(|e| e.into_inner()).get(pattern) {
+        return Ok(regex.clone());
+    }
+    let regex = Regex::new(&format!("^(?:{})$", pattern))
+        .map_err(|e| format!("invalid validation pattern '{}': {}", pattern, e))?;
+    let mut cache = cache.write().unwrap_or_else(|e| e.into_inner());
+    Ok(cache.entry(pattern.to_string()).or_insert_with(|| Arc::new(regex)).clone())
+}
+
+/// Display name for a locale: an exact match (`pt-BR`), then the language alone (`pt`)
+pub(crate) fn localized_name<'a>(names: &'a HashMap<String, String>, locale: Option<&str>) -> Option<&'a str> {
+    let locale = locale?;
+    names
+        .get(locale)
+        .or_else(|| names.get(locale.split(['-', '_']).next()?))
+        .map(String::as_str)
 }
 
 impl Property {
+    /// Display name for the given locale, falling back to `displayName` and then the ID
+    pub fn display_name_for(&self, locale: Option<&str>) -> &str {
+        localized_name(&self.display_names, locale)
+            .or(self.display_name.as_deref())
+            .unwrap_or(&self.id)
+    }
+    
+    /// Whether the property holds object references, singly or in an array
+    pub fn holds_references(&self) -> bool {
+        let reference = Some(PropertyType::ObjectReference);
+        match &self.property_type {
+            PropertyType::Array { element_type } => element_type.as_simple() == reference,
+            other => other.as_simple() == reference,
+        }
+    }
+    
+    /// Parse a reference held by this property, reading bare IDs as its `referenceTarget`
+    pub fn parse_reference(&self, reference: &str) -> Result<ObjectRef, String> {
+        ObjectRef::parse(reference, self.reference_target.as_deref())
+    }
+    
+    /// A GeoJSON value reprojected from this property's declared CRS to WGS84; other values,
+    /// and values of properties without a CRS, are returned unchanged
+    pub fn to_wgs84(&self, value: PropertyValue) -> Result<PropertyValue, String> {
+        let Some(crs) = self.geo.as_ref().and_then(GeoOptions::source_crs) else {
+            return Ok(value);
+        };
+        match value {
+            PropertyValue::GeoJSON(gj) => {
+                let mut geometry: serde_json::Value = serde_json::from_str(&gj)
+                    .map_err(|e| format!("Property '{}' contains invalid GeoJSON: {}", self.id, e))?;
+                geo::reproject_to_wgs84(&mut geometry, crs)
+                    .map_err(|e| format!("Property '{}' cannot be reprojected: {}", self.id, e))?;
+                Ok(PropertyValue::GeoJSON(geometry.to_string()))
+            }
+            PropertyValue::Array(items) => items
+                .into_iter()
+                .map(|item| self.to_wgs84(item))
+                .collect::<Result<_, _>>()
+                .map(PropertyValue::Array),
+            other => Ok(other),
+        }
+    }
+    
+    /// Effective indexing hint (`Full` unless configured)
+    pub fn indexing_hint(&self) -> IndexingHint {
+        self.indexing.unwrap_or_default()
+    }
+    
     /// Validate a property value against this property's rules
     pub fn validate_value(&self, value: &PropertyValue) -> Result<(), String> {
         self.validate_value_with_reference_check(value, None)
@@ -358,6 +593,7 @@ impl Property {
                     let element_prop = Property {
                         id: format!("{}[{}]", self.id, idx),
                         display_name: None,
+                        display_names: HashMap::new(),
                         property_type: *element_type.clone(),
                         required: false,
                         default: None,
@@ -371,6 +607,11 @@ impl Property {
                         deprecated: None,
                         statistics: None,
                         model_binding: None,
+                        display_order: None,
+                        indexing: None,
+                        reference_target: self.reference_target.clone(),
+                        geo: self.geo.clone(),
+                        custom_validator: None,
                     };
                     element_prop.validate_value_with_reference_check(item, reference_checker)
                         .map_err(|e| format!("Array element {}: {}", idx, e))?;
@@ -383,6 +624,7 @@ impl Property {
                     let key_prop = Property {
                         id: format!("{}.key", self.id),
                         display_name: None,
+                        display_names: HashMap::new(),
                         property_type: *key_type.clone(),
                         required: false,
                         default: None,
@@ -396,6 +638,11 @@ impl Property {
                         deprecated: None,
                         statistics: None,
                         model_binding: None,
+                        display_order: None,
+                        indexing: None,
+                        reference_target: None,
+                        geo: None,
+                        custom_validator: None,
                     };
                     // Convert key to PropertyValue based on key type
                     let key_value = match key_type.as_ref() {
@@ -414,6 +661,7 @@ impl Property {
                     let val_prop = Property {
                         id: format!("{}.{}", self.id, key),
                         display_name: None,
+                        display_names: HashMap::new(),
                         property_type: *value_type.clone(),
                         required: false,
                         default: None,
@@ -427,6 +675,11 @@ impl Property {
                         deprecated: None,
                         statistics: None,
                         model_binding: None,
+                        display_order: None,
+                        indexing: None,
+                        reference_target: None,
+                        geo: None,
+                        custom_validator: None,
                     };
                     val_prop.validate_value_with_reference_check(val, reference_checker)
                         .map_err(|e| format!("Map value for key '{}': {}", key, e))?;
@@ -465,6 +718,7 @@ impl Property {
                     let union_prop = Property {
                         id: self.id.clone(),
                         display_name: None,
+                        display_names: HashMap::new(),
                         property_type: union_type.clone(),
                         required: false,
                         default: None,
@@ -478,6 +732,11 @@ impl Property {
                         deprecated: None,
                         statistics: None,
                         model_binding: None,
+                        display_order: None,
+                        indexing: None,
+                        reference_target: None,
+                        geo: None,
+                        custom_validator: None,
                     };
                     match union_prop.validate_value_with_reference_check(value, reference_checker) {
                         Ok(()) => {
@@ -502,28 +761,39 @@ impl Property {
             (PropertyType::Integer, PropertyValue::Integer(_)) => {}
             (PropertyType::Double | PropertyType::Float, PropertyValue::Double(_)) => {}
             (PropertyType::Boolean | PropertyType::Bool, PropertyValue::Boolean(_)) => {}
-            (PropertyType::Date, PropertyValue::Date(_)) => {}
-            (PropertyType::DateTime | PropertyType::Timestamp, PropertyValue::DateTime(_)) => {}
+            (PropertyType::Date, PropertyValue::Date(d)) => {
+                parse_date(d).map_err(|e| format!("Property '{}' value {}", self.id, e))?;
+            }
+            (PropertyType::DateTime | PropertyType::Timestamp, PropertyValue::DateTime(dt)) => {
+                parse_datetime(dt).map_err(|e| format!("Property '{}' value {}", self.id, e))?;
+            }
             (PropertyType::ObjectReference | PropertyType::ObjectReferenceAlt, PropertyValue::ObjectReference(ref_id)) => {
-                // If reference checker is provided, validate that the referenced object exists
-                if let Some(checker) = reference_checker {
-                    // Extract object type and ID from reference (format: "object_type:object_id" or just "object_id")
-                    let parts: Vec<&str> = ref_id.split(':').collect();
-                    let (obj_type, obj_id) = if parts.len() == 2 {
-                        (parts[0], parts[1])
-                    } else {
-                        // If no type prefix, we can't validate - this is a limitation
-                        // In a real system, we'd need the object type context
+                // A bare ID is only checkable when the property declares its target type
+                let reference = match self.parse_reference(ref_id) {
+                    Ok(reference) => reference,
+                    Err(_) if reference_checker.is_none() && self.reference_target.is_none() => return Ok(()),
+                    Err(e) => {
+                        return Err(format!(
+                            "Property '{}': {} (declare referenceTarget to allow bare IDs)",
+                            self.id, e
+                        ))
+                    }
+                };
+                if let Some(target) = &self.reference_target {
+                    if &reference.object_type != target {
                         return Err(format!(
-                            "Object reference '{}' must be in format 'object_type:object_id' for validation",
-                            ref_id
+                            "Property '{}' references '{}' objects, not '{}'",
+                            self.id, target, reference
                         ));
-                    };
-                    
-                    if !checker(obj_type, obj_id) {
+                    }
+                }
+                
+                // If reference checker is provided, validate that the referenced object exists
+                if let Some(checker) = reference_checker {
+                    if !checker(&reference.object_type, &reference.object_id) {
                         return Err(format!(
                             "Referenced object '{}' of type '{}' does not exist",
-                            obj_id, obj_type
+                            reference.object_id, reference.object_type
                         ));
                     }
                 }
@@ -536,6 +806,22 @@ impl Property {
                         self.id, e
                     ));
                 }
+                
+                // Stored geometries are WGS84 longitude/latitude
+                let in_range = serde_json::from_str(gj)
+                    .map_err(|e| e.to_string())
+                    .and_then(|geometry| geo::check_coordinates(&geometry));
+                if let Err(e) = in_range {
+                    let validation = self.geo.as_ref().map(|g| g.validation).unwrap_or_default();
+                    match validation {
+                        CoordinateValidation::Strict => {
+                            return Err(format!("Property '{}' {}", self.id, e));
+                        }
+                        CoordinateValidation::Lenient => {
+                            tracing::warn!("property '{}' {}", self.id, e);
+                        }
+                    }
+                }
             }
             _ => {
                 return Err(format!(
@@ -566,9 +852,8 @@ impl Property {
                         }
                     }
                     if let Some(pattern) = &validation.pattern {
-                        // Simple substring matching for now - can be enhanced with regex crate later
-                        // For production, consider using regex crate: regex::Regex::new(pattern)
-                        if !s.contains(pattern) {
+                        let regex = pattern_regex(pattern).map_err(|e| format!("Property '{}' has an {}", self.id, e))?;
+                        if !regex.is_match(s) {
                             return Err(format!(
                                 "Property '{}' value does not match pattern '{}'",
                                 self.id, pattern
@@ -668,6 +953,41 @@ impl Property {
                             self.id, e
                         ));
                     }
+                    if let Some(allowed) = &validation.geometry_types {
+                        let types = crate::geometry::geometry_types(gj)
+                            .map_err(|e| format!("Property '{}' {}", self.id, e))?;
+                        if let Some(rejected) = types.iter().find(|t| !allowed.contains(t)) {
+                            return Err(format!(
+                                "Property '{}' only accepts {} geometries, got {}",
+                                self.id,
+                                allowed.join(" or "),
+                                rejected
+                            ));
+                        }
+                    }
+                }
+                PropertyValue::Date(s) | PropertyValue::DateTime(s) if validation.has_date_bounds() => {
+                    // Type checking already parsed the value
+                    let value = match value {
+                        PropertyValue::Date(_) => parse_date(s)?,
+                        _ => parse_datetime(s)?,
+                    };
+                    if let Some(min) = &validation.min_date {
+                        if value < parse_date_bound(min)? {
+                            return Err(format!(
+                                "Property '{}' value {} is before minimum {}",
+                                self.id, s, min
+                            ));
+                        }
+                    }
+                    if let Some(max) = &validation.max_date {
+                        if value > parse_date_bound(max)? {
+                            return Err(format!(
+                                "Property '{}' value {} is after maximum {}",
+                                self.id, s, max
+                            ));
+                        }
+                    }
                 }
                 _ => {}
             }
@@ -733,6 +1053,10 @@ impl PropertyValue {
     }
 }
 
+/// System-managed property holding an object's [`PropertyMap::content_hash`], stamped at
+/// ingestion so unchanged rows can be skipped on re-ingest
+pub const CONTENT_HASH_PROPERTY: &str = "_content_hash";
+
 /// A collection of property values (object properties at runtime)
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct PropertyMap {
@@ -769,6 +1093,26 @@ impl PropertyMap {
     pub fn is_empty(&self) -> bool {
         self.properties.is_empty()
     }
+    
+    /// Stable hash of the object's content: hex SHA-256 over its values as stored, with keys
+    /// in sorted order at every level. Null values count as absent, and system fields that
+    /// change on every write (the hash itself, materialization timestamps) are left out.
+    pub fn content_hash(&self) -> String {
+        use sha2::{Digest, Sha256};
+        let normalized: serde_json::Map<String, serde_json::Value> = self
+            .properties
+            .iter()
+            .filter(|(key, value)| {
+                !value.is_null()
+                    && key.as_str() != CONTENT_HASH_PROPERTY
+                    && key.as_str() != crate::computed_properties::MATERIALIZED_AT_PROPERTY
+            })
+            .map(|(key, value)| (key.clone(), serde_json::to_value(value).unwrap_or(serde_json::Value::Null)))
+            .collect();
+        // serde_json maps are ordered, so nested maps serialize with sorted keys too
+        let canonical = serde_json::Value::Object(normalized).to_string();
+        format!("{:x}", Sha256::digest(canonical.as_bytes()))
+    }
 }
 
 #[cfg(test)]
@@ -803,11 +1147,25 @@ mod tests {
         assert!(PropertyType::from_str("invalid").is_err());
     }
     
+    #[test]
+    fn test_display_name_for_locale() {
+        let prop: Property = serde_json::from_str(
+            r#"{"id": "name", "type": "string", "displayName": "Name", "displayNames": {"fr": "Nom", "pt-BR": "Nome"}}"#,
+        )
+        .unwrap();
+        assert_eq!(prop.display_name_for(None), "Name");
+        assert_eq!(prop.display_name_for(Some("fr")), "Nom");
+        assert_eq!(prop.display_name_for(Some("fr-CA")), "Nom");
+        assert_eq!(prop.display_name_for(Some("pt-BR")), "Nome");
+        assert_eq!(prop.display_name_for(Some("de")), "Name");
+    }
+    
     #[test]
     fn test_property_validation_string_length() {
         let prop = Property {
             id: "test".to_string(),
             display_name: None,
+            display_names: HashMap::new(),
             property_type: PropertyType::String,
             required: false,
             default: None,
@@ -818,6 +1176,9 @@ mod tests {
                 max: None,
                 pattern: None,
                 enum_values: None,
+                min_date: None,
+                max_date: None,
+                geometry_types: None,
             }),
             description: None,
             annotations: HashMap::new(),
@@ -827,7 +1188,12 @@ mod tests {
             pii: false,
             deprecated: None,
                     statistics: None,
-                    model_binding: None,        };
+                    model_binding: None,
+                    display_order: None,
+                    indexing: None,
+                    reference_target: None,
+                    geo: None,
+                    custom_validator: None,        };
         
         assert!(prop.validate_value(&PropertyValue::String("test".to_string())).is_ok());
         assert!(prop.validate_value(&PropertyValue::String("ab".to_string())).is_err()); // Too short
@@ -839,6 +1205,7 @@ mod tests {
         let prop = Property {
             id: "test".to_string(),
             display_name: None,
+            display_names: HashMap::new(),
             property_type: PropertyType::Integer,
             required: false,
             default: None,
@@ -849,6 +1216,9 @@ mod tests {
                 max: Some(100.0),
                 pattern: None,
                 enum_values: None,
+                min_date: None,
+                max_date: None,
+                geometry_types: None,
             }),
             description: None,
             annotations: HashMap::new(),
@@ -858,7 +1228,12 @@ mod tests {
             pii: false,
             deprecated: None,
                     statistics: None,
-                    model_binding: None,        };
+                    model_binding: None,
+                    display_order: None,
+                    indexing: None,
+                    reference_target: None,
+                    geo: None,
+                    custom_validator: None,        };
         
         assert!(prop.validate_value(&PropertyValue::Integer(50)).is_ok());
         assert!(prop.validate_value(&PropertyValue::Integer(5)).is_err()); // Too small
@@ -870,6 +1245,7 @@ mod tests {
         let prop = Property {
             id: "test".to_string(),
             display_name: None,
+            display_names: HashMap::new(),
             property_type: PropertyType::String,
             required: false,
             default: None,
@@ -880,6 +1256,9 @@ mod tests {
                 max: None,
                 pattern: None,
                 enum_values: Some(vec!["option1".to_string(), "option2".to_string()]),
+                min_date: None,
+                max_date: None,
+                geometry_types: None,
             }),
             description: None,
             annotations: HashMap::new(),
@@ -889,12 +1268,133 @@ mod tests {
             pii: false,
             deprecated: None,
                     statistics: None,
-                    model_binding: None,        };
+                    model_binding: None,
+                    display_order: None,
+                    indexing: None,
+                    reference_target: None,
+                    geo: None,
+                    custom_validator: None,        };
         
         assert!(prop.validate_value(&PropertyValue::String("option1".to_string())).is_ok());
         assert!(prop.validate_value(&PropertyValue::String("invalid".to_string())).is_err());
     }
     
+    #[test]
+    fn test_property_validation_pattern() {
+        let prop = Property {
+            id: "plate".to_string(),
+            display_name: None,
+            display_names: HashMap::new(),
+            property_type: PropertyType::String,
+            required: false,
+            default: None,
+            validation: Some(PropertyValidation {
+                min_length: None,
+                max_length: None,
+                min: None,
+                max: None,
+                pattern: Some(r"[A-Z]{2}-\d{4}".to_string()),
+                enum_values: None,
+                min_date: None,
+                max_date: None,
+                geometry_types: None,
+            }),
+            description: None,
+            annotations: HashMap::new(),
+            unit: None,
+            format: None,
+            sensitivity_tags: Vec::new(),
+            pii: false,
+            deprecated: None,
+            statistics: None,
+            model_binding: None,
+            display_order: None,
+            indexing: None,
+            reference_target: None,
+            geo: None,
+            custom_validator: None,
+        };
+
+        assert!(prop.validate_value(&PropertyValue::String("MA-1234".to_string())).is_ok());
+        // The whole value must match, not just a substring
+        assert!(prop.validate_value(&PropertyValue::String("xMA-1234".to_string())).is_err());
+        assert!(prop.validate_value(&PropertyValue::String("MA-12345".to_string())).is_err());
+        let err = prop.validate_value(&PropertyValue::String("ma-1234".to_string())).unwrap_err();
+        assert!(err.contains("does not match pattern"), "{}", err);
+
+        // Compiled once and reused across values
+        let first = pattern_regex(r"[A-Z]{2}-\d{4}").unwrap();
+        assert!(Arc::ptr_eq(&first, &pattern_regex(r"[A-Z]{2}-\d{4}").unwrap()));
+
+        let invalid = PropertyValidation { pattern: Some("[A-Z".to_string()), ..prop.validation.clone().unwrap() };
+        assert!(invalid.validate().unwrap_err().contains("invalid validation pattern '[A-Z'"));
+    }
+
+    #[test]
+    fn test_property_validation_dates() {
+        let date = |s: &str| PropertyValue::Date(s.to_string());
+        let datetime = |s: &str| PropertyValue::DateTime(s.to_string());
+        let mut opened = Property {
+            id: "opened".to_string(),
+            display_name: None,
+            display_names: HashMap::new(),
+            property_type: PropertyType::Date,
+            required: false,
+            default: None,
+            validation: None,
+            description: None,
+            annotations: HashMap::new(),
+            unit: None,
+            format: None,
+            sensitivity_tags: Vec::new(),
+            pii: false,
+            deprecated: None,
+            statistics: None,
+            model_binding: None,
+            display_order: None,
+            indexing: None,
+            reference_target: None,
+            geo: None,
+            custom_validator: None,
+        };
+        assert!(opened.validate_value(&date("2024-02-29")).is_ok());
+        let err = opened.validate_value(&date("not-a-date")).unwrap_err();
+        assert!(err.contains("'not-a-date' is not an ISO 8601 date"), "{}", err);
+        assert!(opened.validate_value(&date("2023-02-29")).is_err());
+
+        let mut seen_at = Property { id: "seen_at".to_string(), property_type: PropertyType::DateTime, ..opened.clone() };
+        assert!(seen_at.validate_value(&datetime("2024-03-01T12:00:00+02:00")).is_ok());
+        let err = seen_at.validate_value(&datetime("2024-03-01 12:00")).unwrap_err();
+        assert!(err.contains("'2024-03-01 12:00' is not an RFC 3339 datetime"), "{}", err);
+
+        // Bounds compare chronologically, across formats and offsets
+        let bounds = PropertyValidation {
+            min_length: None,
+            max_length: None,
+            min: None,
+            max: None,
+            pattern: None,
+            enum_values: None,
+            min_date: Some("2000-01-01".to_string()),
+            max_date: Some("2024-06-30T23:59:59Z".to_string()),
+            geometry_types: None,
+        };
+        opened.validation = Some(bounds.clone());
+        assert!(opened.validate_value(&date("2000-01-01")).is_ok());
+        assert!(opened.validate_value(&date("2024-06-30")).is_ok());
+        assert!(opened.validate_value(&date("1999-12-31")).unwrap_err().contains("before minimum 2000-01-01"));
+        assert!(opened.validate_value(&date("2024-07-01")).unwrap_err().contains("after maximum"));
+        seen_at.validation = Some(bounds.clone());
+        assert!(seen_at.validate_value(&datetime("2024-07-01T01:00:00+02:00")).is_ok());
+        assert!(seen_at.validate_value(&datetime("1999-12-31T23:00:00-02:00")).is_ok());
+        assert!(seen_at.validate_value(&datetime("1999-12-31T23:00:00Z")).is_err());
+
+        let inverted = PropertyValidation { min_date: Some("2025-01-01".to_string()), ..bounds.clone() };
+        assert!(inverted.validate().unwrap_err().contains("is after max_date"));
+        let unparseable = PropertyValidation { max_date: Some("June 2024".to_string()), ..bounds };
+        assert!(unparseable.validate().unwrap_err().contains("'June 2024'"));
+    }
+
     #[test]
     fn test_property_map() {
         let mut map = PropertyMap::new();
@@ -906,6 +1406,27 @@ mod tests {
         assert_eq!(map.get("key1"), Some(&PropertyValue::String("value1".to_string())));
     }
     
+    #[test]
+    fn test_content_hash_ignores_key_order_and_system_fields() {
+        let nested = |pairs: &[(&str, i64)]| {
+            PropertyValue::Map(pairs.iter().map(|(k, v)| (k.to_string(), PropertyValue::Integer(*v))).collect())
+        };
+        let mut a = PropertyMap::new();
+        a.insert("name".to_string(), PropertyValue::String("Acme".to_string()));
+        a.insert("counts".to_string(), nested(&[("x", 1), ("y", 2)]));
+        let mut b = PropertyMap::new();
+        b.insert("counts".to_string(), nested(&[("y", 2), ("x", 1)]));
+        b.insert("name".to_string(), PropertyValue::String("Acme".to_string()));
+        b.insert("missing".to_string(), PropertyValue::Null);
+        b.insert(CONTENT_HASH_PROPERTY.to_string(), PropertyValue::String("stale".to_string()));
+        assert_eq!(a.content_hash(), b.content_hash());
+        assert_eq!(a.content_hash().len(), 64);
+
+        // Nested values are covered
+        b.insert("counts".to_string(), nested(&[("x", 1), ("y", 3)]));
+        assert_ne!(a.content_hash(), b.content_hash());
+    }
+    
     #[test]
     fn test_property_value_to_string() {
         assert_eq!(PropertyValue::String("test".to_string()).to_string(), "test");
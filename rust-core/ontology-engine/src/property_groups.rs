@@ -1,6 +1,6 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use crate::property::Property;
+use crate::property::{localized_name, Property};
 
 /// Property group - organizes related properties together
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -10,6 +10,13 @@ pub struct PropertyGroup {
     #[serde(rename = "displayName")]
     pub display_name: String,
     
+    /// Display names by locale (e.g. `fr`, `pt-BR`), used in place of `displayName` when
+    /// rendering for that locale
+    #[serde(rename = "displayNames")]
+    #[serde(default)]
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub display_names: HashMap<String, String>,
+    
     #[serde(default)]
     pub description: Option<String>,
     
@@ -25,6 +32,7 @@ pub struct PropertyGroup {
     pub collapsible: bool,
     
     /// Whether this group is collapsed by default
+    #[serde(rename = "collapsedByDefault", alias = "collapsed_by_default")]
     #[serde(default)]
     pub collapsed_by_default: bool,
 }
@@ -34,6 +42,7 @@ impl PropertyGroup {
         Self {
             id,
             display_name,
+            display_names: HashMap::new(),
             description: None,
             properties: Vec::new(),
             order: 0,
@@ -51,8 +60,16 @@ impl PropertyGroup {
         self.order = order;
         self
     }
+    
+    /// Display name for the given locale, falling back to `displayName`
+    pub fn display_name_for(&self, locale: Option<&str>) -> &str {
+        localized_name(&self.display_names, locale).unwrap_or(&self.display_name)
+    }
 }
 
+/// ID of the group `PropertyGroupManager::with_remainder` gathers ungrouped properties in
+pub const UNGROUPED_GROUP_ID: &str = "ungrouped";
+
 /// Manager for property groups on an object type
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PropertyGroupManager {
@@ -95,6 +112,20 @@ impl PropertyGroupManager {
         groups.sort_by_key(|g| g.order);
         groups
     }
+    
+    /// The groups in display order, followed by an `ungrouped` group holding the properties
+    /// no group names, in declaration order. The remainder is left out when it is empty.
+    pub fn with_remainder(&self, all_properties: &[Property]) -> Vec<PropertyGroup> {
+        let mut groups: Vec<PropertyGroup> = self.get_sorted_groups().into_iter().cloned().collect();
+        let ungrouped = self.get_ungrouped_properties(all_properties);
+        if !ungrouped.is_empty() {
+            let mut remainder = PropertyGroup::new(UNGROUPED_GROUP_ID.to_string(), "Other".to_string());
+            remainder.properties = ungrouped.into_iter().map(|p| p.id.clone()).collect();
+            remainder.order = i32::MAX;
+            groups.push(remainder);
+        }
+        groups
+    }
 }
 
 impl Default for PropertyGroupManager {
@@ -102,3 +133,40 @@ impl Default for PropertyGroupManager {
         Self::new()
     }
 }
+
+impl From<Vec<PropertyGroup>> for PropertyGroupManager {
+    fn from(groups: Vec<PropertyGroup>) -> Self {
+        Self { groups }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn property(id: &str) -> Property {
+        serde_json::from_value(serde_json::json!({ "id": id, "type": "string" })).unwrap()
+    }
+    
+    #[test]
+    fn test_with_remainder_appends_ungrouped_properties() {
+        let properties: Vec<Property> = ["geoid", "population", "name", "geometry"].into_iter().map(property).collect();
+        let manager = PropertyGroupManager::from(vec![
+            PropertyGroup::new("geometry".to_string(), "Geometry".to_string())
+                .with_property("geometry".to_string())
+                .with_order(2),
+            PropertyGroup::new("demographics".to_string(), "Demographics".to_string())
+                .with_property("population".to_string())
+                .with_order(1),
+        ]);
+        
+        let groups = manager.with_remainder(&properties);
+        let ids: Vec<&str> = groups.iter().map(|g| g.id.as_str()).collect();
+        assert_eq!(ids, vec!["demographics", "geometry", UNGROUPED_GROUP_ID]);
+        assert_eq!(groups[2].properties, vec!["geoid".to_string(), "name".to_string()]);
+        
+        // Nothing left over, no remainder
+        let everything = vec![property("geometry"), property("population")];
+        assert_eq!(manager.with_remainder(&everything).len(), 2);
+    }
+}
@@ -1,5 +1,69 @@
 use crate::property::{PropertyValue, PropertyMap};
 use crate::link::Link;
+use crate::meta_model::ObjectType;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+
+/// A typed object reference. Written `object_type:object_id`; a bare `object_id` is read
+/// as an object of the referencing property's declared `referenceTarget`.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ObjectRef {
+    pub object_type: String,
+    pub object_id: String,
+}
+
+impl ObjectRef {
+    pub fn new(object_type: impl Into<String>, object_id: impl Into<String>) -> Self {
+        Self {
+            object_type: object_type.into(),
+            object_id: object_id.into(),
+        }
+    }
+
+    /// Parse `object_type:object_id`, or a bare `object_id` of `default_type`. A bare ID is
+    /// an error when there is no default type. The type ends at the first `:`, so IDs may
+    /// contain colons (`document:urn:isbn:0451450523`).
+    pub fn parse(reference: &str, default_type: Option<&str>) -> Result<Self, String> {
+        match reference.split_once(':') {
+            Some((object_type, object_id)) if !object_type.is_empty() && !object_id.is_empty() => {
+                Ok(Self::new(object_type, object_id))
+            }
+            None if !reference.is_empty() => match default_type {
+                Some(object_type) => Ok(Self::new(object_type, reference)),
+                None => Err(format!(
+                    "Object reference '{}' must be in format 'object_type:object_id'",
+                    reference
+                )),
+            },
+            _ => Err(format!("Invalid object reference format: {}", reference)),
+        }
+    }
+}
+
+impl fmt::Display for ObjectRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.object_type, self.object_id)
+    }
+}
+
+impl From<ObjectRef> for PropertyValue {
+    fn from(reference: ObjectRef) -> Self {
+        PropertyValue::ObjectReference(reference.to_string())
+    }
+}
+
+impl Serialize for ObjectRef {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for ObjectRef {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let reference = String::deserialize(deserializer)?;
+        ObjectRef::parse(&reference, None).map_err(serde::de::Error::custom)
+    }
+}
 
 /// Helper for managing object references and auto-creating links
 pub struct ReferenceManager;
@@ -20,21 +84,10 @@ impl ReferenceManager {
         Ok(())
     }
     
-    /// Parse object reference string (format: "object_type:object_id" or just "object_id")
+    /// Parse object reference string (format: "object_type:object_id"); see `ObjectRef::parse`
+    /// for bare IDs
     pub fn parse_reference(ref_str: &str) -> Result<(String, String), String> {
-        let parts: Vec<&str> = ref_str.split(':').collect();
-        if parts.len() == 2 {
-            Ok((parts[0].to_string(), parts[1].to_string()))
-        } else if parts.len() == 1 {
-            // If no type prefix, we need the object type from context
-            // For now, return error - caller should provide full reference
-            Err(format!(
-                "Object reference '{}' must be in format 'object_type:object_id'",
-                ref_str
-            ))
-        } else {
-            Err(format!("Invalid object reference format: {}", ref_str))
-        }
+        ObjectRef::parse(ref_str, None).map(|reference| (reference.object_type, reference.object_id))
     }
     
     /// Create a link automatically when an object reference is set
@@ -46,20 +99,82 @@ impl ReferenceManager {
         target_reference: &str,
         link_type_id: Option<&str>,
     ) -> Result<Link, String> {
-        let (target_type, target_id) = Self::parse_reference(target_reference)?;
+        let target = ObjectRef::parse(target_reference, None)?;
         
         // Generate link type ID if not provided (format: "{source_type}_to_{target_type}")
         let link_type = link_type_id.map(|s| s.to_string())
-            .unwrap_or_else(|| format!("{}_to_{}", source_object_type, target_type));
+            .unwrap_or_else(|| format!("{}_to_{}", source_object_type, target.object_type));
         
         Ok(Link::new(
             uuid::Uuid::new_v4().to_string(),
             link_type,
-            format!("{}:{}", source_object_type, source_object_id),
-            format!("{}:{}", target_type, target_id),
+            ObjectRef::new(source_object_type, source_object_id).to_string(),
+            target.to_string(),
         ))
     }
     
+    /// Rewrite a reference value that points at one of `from_ids` (of `target_type`) to point
+    /// at `to_id` instead, keeping its `object_type:object_id` or bare-ID form. Arrays of
+    /// references are rewritten element-wise. Returns `None` if nothing changed.
+    pub fn repoint_reference(
+        value: &PropertyValue,
+        target_type: &str,
+        from_ids: &[String],
+        to_id: &str,
+    ) -> Option<PropertyValue> {
+        let repoint = |reference: &str| -> Option<String> {
+            match ObjectRef::parse(reference, None) {
+                Ok(parsed) if parsed.object_type == target_type && from_ids.contains(&parsed.object_id) => {
+                    Some(ObjectRef::new(target_type, to_id).to_string())
+                }
+                Ok(_) => None,
+                Err(_) => from_ids.iter().any(|id| id == reference).then(|| to_id.to_string()),
+            }
+        };
+        match value {
+            PropertyValue::String(s) => repoint(s).map(PropertyValue::String),
+            PropertyValue::ObjectReference(s) => repoint(s).map(PropertyValue::ObjectReference),
+            PropertyValue::Array(items) => {
+                let repointed: Vec<Option<PropertyValue>> = items
+                    .iter()
+                    .map(|item| Self::repoint_reference(item, target_type, from_ids, to_id))
+                    .collect();
+                if repointed.iter().all(Option::is_none) {
+                    return None;
+                }
+                Some(PropertyValue::Array(
+                    items
+                        .iter()
+                        .zip(repointed)
+                        .map(|(item, new)| new.unwrap_or_else(|| item.clone()))
+                        .collect(),
+                ))
+            }
+            _ => None,
+        }
+    }
+    
+    /// Object references held by an object's reference properties (single or array-valued),
+    /// as `(property_id, target)` pairs. Values that do not parse as references are skipped.
+    pub fn extract_references(object_type: &ObjectType, properties: &PropertyMap) -> Vec<(String, ObjectRef)> {
+        let mut references = Vec::new();
+        for property in &object_type.properties {
+            if !property.holds_references() {
+                continue;
+            }
+            let mut values = Vec::new();
+            if let Some(value) = properties.get(&property.id) {
+                collect_reference_strings(value, &mut values);
+            }
+            for value in values {
+                if let Ok(target) = property.parse_reference(value) {
+                    references.push((property.id.clone(), target));
+                }
+            }
+        }
+        references
+    }
+    
     /// Get reverse references - find all objects that reference a given object
     pub fn find_reverse_references(
         target_object_type: &str,
@@ -71,7 +186,7 @@ impl ReferenceManager {
         // This would typically query the search store for objects with matching reference values
         // For now, this is a placeholder that uses the checker function
         // In a real implementation, this would query: property_name = "target_type:target_id"
-        let ref_value = format!("{}:{}", target_object_type, target_object_id);
+        let ref_value = ObjectRef::new(target_object_type, target_object_id).to_string();
         
         // The checker function should return source object IDs that have this reference
         // This is a simplified interface - real implementation would be more sophisticated
@@ -79,8 +194,21 @@ impl ReferenceManager {
     }
 }
 
-/// Configuration for cascade delete behavior
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+fn collect_reference_strings<'a>(value: &'a PropertyValue, out: &mut Vec<&'a str>) {
+    match value {
+        PropertyValue::ObjectReference(reference) | PropertyValue::String(reference) => out.push(reference),
+        PropertyValue::Array(items) => {
+            for item in items {
+                collect_reference_strings(item, out);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// What happens to a link type's links when one of their objects is deleted (`onDelete`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub enum CascadeDeleteBehavior {
     /// Delete referenced objects when source is deleted
     Cascade,
@@ -89,12 +217,14 @@ pub enum CascadeDeleteBehavior {
     /// Prevent deletion if references exist
     Restrict,
     /// No special behavior
+    #[default]
     NoAction,
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::property::PropertyType;
     
     #[test]
     fn test_parse_reference() {
@@ -105,7 +235,94 @@ mod tests {
     
     #[test]
     fn test_parse_reference_invalid() {
-        assert!(ReferenceManager::parse_reference("invalid:format:here").is_err());
+        assert!(ReferenceManager::parse_reference(":123").is_err());
+        assert!(ReferenceManager::parse_reference("person:").is_err());
+    }
+    
+    #[test]
+    fn test_object_ref_parse_both_forms() {
+        let prefixed = ObjectRef::parse("person:123", None).unwrap();
+        assert_eq!(prefixed, ObjectRef::new("person", "123"));
+        // The prefix wins over the declared target
+        assert_eq!(ObjectRef::parse("person:123", Some("company")).unwrap(), prefixed);
+        assert_eq!(ObjectRef::parse("123", Some("person")).unwrap(), prefixed);
+        
+        // A bare ID needs a declared target
+        assert!(ObjectRef::parse("123", None).is_err());
+        assert!(ObjectRef::parse("", Some("person")).is_err());
+        assert!(ObjectRef::parse("person:", None).is_err());
+        
+        // Only the first colon separates the type; IDs keep theirs
+        assert_eq!(
+            ObjectRef::parse("document:urn:isbn:0451450523", Some("person")).unwrap(),
+            ObjectRef::new("document", "urn:isbn:0451450523")
+        );
+        let reading = ObjectRef::new("reading", "2024-01-01T00:00:00Z");
+        assert_eq!(ObjectRef::parse(&reading.to_string(), None).unwrap(), reading);
+    }
+    
+    #[test]
+    fn test_object_ref_serialization_is_stable() {
+        let reference = ObjectRef::parse("123", Some("person")).unwrap();
+        assert_eq!(reference.to_string(), "person:123");
+        assert_eq!(ObjectRef::parse(&reference.to_string(), None).unwrap(), reference);
+        
+        let json = serde_json::to_string(&reference).unwrap();
+        assert_eq!(json, "\"person:123\"");
+        assert_eq!(serde_json::from_str::<ObjectRef>(&json).unwrap(), reference);
+        assert_eq!(PropertyValue::from(reference), PropertyValue::ObjectReference("person:123".to_string()));
+    }
+    
+    #[test]
+    fn test_repoint_reference() {
+        let losers = vec!["p2".to_string(), "p3".to_string()];
+        let typed = PropertyValue::String("person:p2".to_string());
+        assert_eq!(
+            ReferenceManager::repoint_reference(&typed, "person", &losers, "p1"),
+            Some(PropertyValue::String("person:p1".to_string()))
+        );
+        let other_type = PropertyValue::String("company:p2".to_string());
+        assert_eq!(ReferenceManager::repoint_reference(&other_type, "person", &losers, "p1"), None);
+        let bare = PropertyValue::Array(vec![
+            PropertyValue::ObjectReference("p3".to_string()),
+            PropertyValue::ObjectReference("p9".to_string()),
+        ]);
+        assert_eq!(
+            ReferenceManager::repoint_reference(&bare, "person", &losers, "p1"),
+            Some(PropertyValue::Array(vec![
+                PropertyValue::ObjectReference("p1".to_string()),
+                PropertyValue::ObjectReference("p9".to_string()),
+            ]))
+        );
+    }
+    
+    #[test]
+    fn test_extract_references() {
+        let mut object_type: ObjectType = serde_json::from_value(serde_json::json!({
+            "id": "order",
+            "displayName": "Order",
+            "primaryKey": "id",
+            "properties": [
+                { "id": "id", "type": "string" },
+                { "id": "plant", "type": "object_reference", "referenceTarget": "plant" },
+                { "id": "suppliers", "type": "string" },
+            ],
+        })).unwrap();
+        object_type.properties[2].property_type = PropertyType::Array {
+            element_type: Box::new(PropertyType::ObjectReference),
+        };
+        let mut properties = PropertyMap::new();
+        properties.insert("id".to_string(), PropertyValue::String("plant:9".to_string()));
+        properties.insert("plant".to_string(), PropertyValue::ObjectReference("123".to_string()));
+        properties.insert("suppliers".to_string(), PropertyValue::Array(vec![
+            PropertyValue::String("company:c1".to_string()),
+            PropertyValue::String(":not a reference".to_string()),
+        ]));
+        
+        assert_eq!(ReferenceManager::extract_references(&object_type, &properties), vec![
+            ("plant".to_string(), ObjectRef::new("plant", "123")),
+            ("suppliers".to_string(), ObjectRef::new("company", "c1")),
+        ]);
     }
     
     #[test]
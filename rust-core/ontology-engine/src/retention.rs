@@ -0,0 +1,78 @@
+//! Retention policies: when objects of a type leave the search index for the archive.
+//!
+//! An archival policy names a year, date or datetime property and an age in years; objects
+//! whose property falls before the cutoff (e.g. `year < current_year - 5`) are moved to the
+//! columnar store by the archive job, where they stay available to analytics.
+
+use crate::meta_model::ObjectType;
+use crate::property::{PropertyType, PropertyValue};
+use chrono::{DateTime, Datelike, Months, Utc};
+use serde::{Deserialize, Serialize};
+
+/// When objects of a type are archived
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ArchivalPolicy {
+    /// Integer year, date or datetime property the age is read from
+    pub property: String,
+
+    /// Objects are archived once `property` is more than this many years in the past
+    #[serde(rename = "olderThanYears")]
+    pub older_than_years: u32,
+}
+
+impl ArchivalPolicy {
+    /// Values of `property` strictly below this are due for archival at `now`.
+    /// Integer properties hold a year; dates and datetimes are compared to the same day
+    /// `older_than_years` ago.
+    pub fn cutoff(&self, property_type: &PropertyType, now: DateTime<Utc>) -> Option<PropertyValue> {
+        let years = self.older_than_years;
+        match property_type {
+            PropertyType::Integer | PropertyType::Int => Some(PropertyValue::Integer(now.year() as i64 - years as i64)),
+            PropertyType::Date => {
+                let date = now.date_naive().checked_sub_months(Months::new(years * 12))?;
+                Some(PropertyValue::Date(date.format("%Y-%m-%d").to_string()))
+            }
+            PropertyType::DateTime | PropertyType::Timestamp => {
+                let cutoff = now.checked_sub_months(Months::new(years * 12))?;
+                Some(PropertyValue::DateTime(cutoff.to_rfc3339()))
+            }
+            _ => None,
+        }
+    }
+
+    /// Validate this policy against the object type it belongs to
+    pub fn validate_for(&self, object_type: &ObjectType) -> Result<(), String> {
+        let property = object_type
+            .get_property(&self.property)
+            .ok_or_else(|| format!("archival property '{}' does not exist", self.property))?;
+        if self.cutoff(&property.property_type, Utc::now()).is_none() {
+            return Err(format!(
+                "archival property '{}' must be an integer year, date or datetime",
+                self.property
+            ));
+        }
+        if !property.indexing_hint().is_sortable() {
+            return Err(format!("archival property '{}' must be indexed for range filters", self.property));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_cutoff_by_property_type() {
+        let policy = ArchivalPolicy { property: "year".to_string(), older_than_years: 5 };
+        let now = Utc.with_ymd_and_hms(2026, 3, 15, 12, 0, 0).unwrap();
+        assert_eq!(policy.cutoff(&PropertyType::Integer, now), Some(PropertyValue::Integer(2021)));
+        assert_eq!(policy.cutoff(&PropertyType::Date, now), Some(PropertyValue::Date("2021-03-15".to_string())));
+        assert_eq!(
+            policy.cutoff(&PropertyType::DateTime, now),
+            Some(PropertyValue::DateTime("2021-03-15T12:00:00+00:00".to_string()))
+        );
+        assert_eq!(policy.cutoff(&PropertyType::String, now), None);
+    }
+}
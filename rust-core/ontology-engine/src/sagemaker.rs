@@ -0,0 +1,385 @@
+//! SageMaker endpoint invocation for `RemoteModelExecutor`. The executor builds the request
+//! and reads the prediction from the response; a `SageMakerRuntime` given to
+//! `RemoteModelExecutor::with_sagemaker_runtime` makes the call. With the `sagemaker`
+//! feature the executor defaults to `AwsSageMakerRuntime`, which signs the call with
+//! credentials from the default AWS provider chain.
+
+use crate::model_executor::{ModelExecutionError, ModelExecutionResult};
+use crate::property::PropertyValue;
+use std::collections::HashMap;
+
+/// Content type of request bodies for endpoints that don't configure one
+pub const DEFAULT_CONTENT_TYPE: &str = "application/json";
+
+/// An `InvokeEndpoint` call
+#[derive(Debug, Clone, PartialEq)]
+pub struct SageMakerRequest {
+    pub endpoint_name: String,
+    pub region: String,
+    pub content_type: String,
+    pub body: Vec<u8>,
+}
+
+impl SageMakerRequest {
+    /// Request sending `inputs` to the endpoint as a JSON object
+    pub fn new(
+        endpoint_name: &str,
+        region: &str,
+        content_type: Option<&str>,
+        inputs: &HashMap<String, PropertyValue>,
+    ) -> Result<Self, ModelExecutionError> {
+        let body = serde_json::to_vec(inputs)
+            .map_err(|e| ModelExecutionError::InvalidInput(format!("Inputs are not serializable: {}", e)))?;
+        Ok(Self {
+            endpoint_name: endpoint_name.to_string(),
+            region: region.to_string(),
+            content_type: content_type.unwrap_or(DEFAULT_CONTENT_TYPE).to_string(),
+            body,
+        })
+    }
+
+    /// The regional runtime URL the request is POSTed to
+    pub fn invocation_url(&self) -> String {
+        format!(
+            "https://runtime.sagemaker.{}.amazonaws.com/endpoints/{}/invocations",
+            self.region, self.endpoint_name
+        )
+    }
+}
+
+/// Why an `InvokeEndpoint` call failed
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum SageMakerError {
+    /// SageMaker is throttling calls to the endpoint
+    #[error("Throttled: {0}")]
+    Throttled(String),
+
+    /// The model container answered with an error, with its message
+    #[error("Model error: {0}")]
+    ModelError(String),
+
+    #[error("Timed out")]
+    Timeout,
+
+    /// Any other error SageMaker answered with
+    #[error("SageMaker error: {0}")]
+    Service(String),
+
+    /// The call never reached SageMaker
+    #[error("Network error: {0}")]
+    Network(String),
+}
+
+impl From<SageMakerError> for ModelExecutionError {
+    fn from(error: SageMakerError) -> Self {
+        match error {
+            SageMakerError::Throttled(_) | SageMakerError::Timeout => ModelExecutionError::Timeout,
+            SageMakerError::ModelError(message) => ModelExecutionError::ExecutionFailed(message),
+            SageMakerError::Service(message) => ModelExecutionError::ExecutionFailed(format!("SageMaker: {}", message)),
+            SageMakerError::Network(message) => ModelExecutionError::NetworkError(message),
+        }
+    }
+}
+
+/// The error in an `InvokeEndpoint` response with HTTP `status`, the `x-amzn-ErrorType`
+/// header and the JSON error `body`
+pub fn invoke_error(status: u16, error_type: Option<&str>, body: &[u8]) -> SageMakerError {
+    let error_code = error_type.map(|value| value.split(':').next().unwrap_or_default());
+    let body: serde_json::Value = serde_json::from_slice(body).unwrap_or_default();
+    let field = |name: &str| body.get(name).and_then(|value| value.as_str()).filter(|value| !value.is_empty());
+    let message = field("message").or_else(|| field("Message")).unwrap_or("no message").to_string();
+    match error_code {
+        Some("ThrottlingException") => SageMakerError::Throttled(message),
+        Some("ModelError") => SageMakerError::ModelError(field("OriginalMessage").map(str::to_string).unwrap_or(message)),
+        _ if status == 429 => SageMakerError::Throttled(message),
+        Some(code) => SageMakerError::Service(format!("{} ({}): {}", code, status, message)),
+        None => SageMakerError::Service(format!("HTTP {}: {}", status, message)),
+    }
+}
+
+/// Makes `InvokeEndpoint` calls, answering with the response body
+#[async_trait::async_trait]
+pub trait SageMakerRuntime: Send + Sync {
+    async fn invoke_endpoint(&self, request: SageMakerRequest) -> Result<Vec<u8>, SageMakerError>;
+}
+
+/// Credentials are fetched again when they expire within this long
+#[cfg(feature = "sagemaker")]
+const CREDENTIALS_REFRESH_MARGIN: std::time::Duration = std::time::Duration::from_secs(300);
+
+/// Credentials provider of a region's AWS config and the credentials it last gave
+#[cfg(feature = "sagemaker")]
+struct RegionCredentials {
+    provider: aws_credential_types::provider::SharedCredentialsProvider,
+    current: Option<aws_credential_types::Credentials>,
+}
+
+/// Calls the SageMaker runtime API over HTTPS, SigV4-signing each request with credentials
+/// from the default AWS provider chain. The chain is resolved once per region and its
+/// credentials are reused until they are about to expire.
+#[cfg(feature = "sagemaker")]
+pub struct AwsSageMakerRuntime {
+    http_client: reqwest::Client,
+    regions: tokio::sync::Mutex<HashMap<String, RegionCredentials>>,
+}
+
+#[cfg(feature = "sagemaker")]
+impl AwsSageMakerRuntime {
+    /// Runtime sending requests through `http_client`, whose timeout bounds each call
+    pub fn new(http_client: reqwest::Client) -> Self {
+        Self {
+            http_client,
+            regions: tokio::sync::Mutex::new(HashMap::new()),
+        }
+    }
+
+    async fn credentials(&self, region: &str) -> Result<aws_credential_types::Credentials, SageMakerError> {
+        use aws_credential_types::provider::ProvideCredentials;
+        use std::collections::hash_map::Entry;
+
+        let mut regions = self.regions.lock().await;
+        let entry = match regions.entry(region.to_string()) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                let config = aws_config::defaults(aws_config::BehaviorVersion::latest())
+                    .region(aws_config::Region::new(region.to_string()))
+                    .load()
+                    .await;
+                let provider = config
+                    .credentials_provider()
+                    .ok_or_else(|| SageMakerError::Service("No AWS credentials provider is configured".to_string()))?;
+                entry.insert(RegionCredentials { provider, current: None })
+            }
+        };
+        let refresh_after = std::time::SystemTime::now() + CREDENTIALS_REFRESH_MARGIN;
+        let current = entry
+            .current
+            .as_ref()
+            .filter(|credentials| credentials.expiry().is_none_or(|expiry| expiry > refresh_after));
+        if let Some(credentials) = current {
+            return Ok(credentials.clone());
+        }
+        let credentials = entry
+            .provider
+            .provide_credentials()
+            .await
+            .map_err(|e| SageMakerError::Service(format!("AWS credentials are unavailable: {}", e)))?;
+        entry.current = Some(credentials.clone());
+        Ok(credentials)
+    }
+}
+
+#[cfg(feature = "sagemaker")]
+#[async_trait::async_trait]
+impl SageMakerRuntime for AwsSageMakerRuntime {
+    async fn invoke_endpoint(&self, request: SageMakerRequest) -> Result<Vec<u8>, SageMakerError> {
+        use aws_sigv4::http_request::{sign, SignableBody, SignableRequest, SigningSettings};
+        use aws_sigv4::sign::v4::SigningParams;
+        use aws_smithy_runtime_api::client::identity::Identity;
+
+        let unsigned = |e: &dyn std::fmt::Display| SageMakerError::Service(format!("Cannot sign the request: {}", e));
+        let identity = Identity::from(self.credentials(&request.region).await?);
+        let params = SigningParams::builder()
+            .identity(&identity)
+            .region(&request.region)
+            .name("sagemaker")
+            .time(std::time::SystemTime::now())
+            .settings(SigningSettings::default())
+            .build()
+            .map_err(|e| unsigned(&e))?
+            .into();
+        let url = request.invocation_url();
+        let headers = [("content-type", request.content_type.as_str()), ("accept", "application/json")];
+        let signable = SignableRequest::new("POST", &url, headers.into_iter(), SignableBody::Bytes(&request.body))
+            .map_err(|e| unsigned(&e))?;
+        let (instructions, _) = sign(signable, &params).map_err(|e| unsigned(&e))?.into_parts();
+
+        let mut builder = self.http_client.post(&url);
+        for (name, value) in headers.into_iter().chain(instructions.headers()) {
+            builder = builder.header(name, value);
+        }
+        let network_error = |e: reqwest::Error| {
+            if e.is_timeout() {
+                SageMakerError::Timeout
+            } else {
+                SageMakerError::Network(e.to_string())
+            }
+        };
+        let response = builder.body(request.body).send().await.map_err(network_error)?;
+        let status = response.status();
+        let error_type = response
+            .headers()
+            .get("x-amzn-errortype")
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
+        let body = response.bytes().await.map_err(network_error)?;
+        if status.is_success() {
+            Ok(body.to_vec())
+        } else {
+            Err(invoke_error(status.as_u16(), error_type.as_deref(), &body))
+        }
+    }
+}
+
+/// The prediction in an endpoint's response body: a bare number, or an object with a
+/// `prediction` and optionally `confidence` and `probabilities`
+pub fn parse_response(body: &[u8]) -> Result<ModelExecutionResult, ModelExecutionError> {
+    let invalid = |reason: String| ModelExecutionError::ExecutionFailed(format!("Invalid prediction response: {}", reason));
+    match serde_json::from_slice(body).map_err(|e| invalid(e.to_string()))? {
+        serde_json::Value::Number(number) => {
+            let prediction = match number.as_i64() {
+                Some(n) => PropertyValue::Integer(n),
+                None => PropertyValue::Double(number.as_f64().unwrap_or_default()),
+            };
+            Ok(ModelExecutionResult {
+                prediction,
+                confidence: None,
+                probabilities: None,
+                metadata: HashMap::new(),
+            })
+        }
+        value @ serde_json::Value::Object(_) => serde_json::from_value(value).map_err(|e| invalid(e.to_string())),
+        other => Err(invalid(format!("expected a number or an object, got {}", other))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model_executor::{ModelExecutor, RemoteModelExecutor};
+    use crate::model_objectives::{ModelObjective, ModelPlatform, ModelType};
+    use std::sync::{Arc, Mutex};
+
+    /// Runtime answering every call with `response`, recording the requests
+    struct MockRuntime {
+        response: Result<Vec<u8>, SageMakerError>,
+        requests: Mutex<Vec<SageMakerRequest>>,
+    }
+
+    #[async_trait::async_trait]
+    impl SageMakerRuntime for MockRuntime {
+        async fn invoke_endpoint(&self, request: SageMakerRequest) -> Result<Vec<u8>, SageMakerError> {
+            self.requests.lock().unwrap().push(request);
+            self.response.clone()
+        }
+    }
+
+    fn mock(response: Result<&str, SageMakerError>) -> Arc<MockRuntime> {
+        Arc::new(MockRuntime {
+            response: response.map(|body| body.as_bytes().to_vec()),
+            requests: Mutex::new(Vec::new()),
+        })
+    }
+
+    fn sagemaker_model(content_type: Option<&str>) -> ModelObjective {
+        ModelObjective::new(
+            "churn".to_string(),
+            "Churn".to_string(),
+            ModelType::Classification,
+            "1.0.0".to_string(),
+            "s3://models/churn.tar.gz".to_string(),
+            ModelPlatform::SageMaker {
+                endpoint_name: "churn-endpoint".to_string(),
+                region: "eu-west-1".to_string(),
+                content_type: content_type.map(str::to_string),
+            },
+        )
+    }
+
+    async fn predict(runtime: &Arc<MockRuntime>, model: &ModelObjective) -> Result<ModelExecutionResult, ModelExecutionError> {
+        let executor = RemoteModelExecutor::new().with_sagemaker_runtime(runtime.clone());
+        let inputs = HashMap::from([("tenure".to_string(), PropertyValue::Integer(3))]);
+        executor.execute(model, inputs).await
+    }
+
+    #[tokio::test]
+    async fn test_sagemaker_request_serialization() {
+        let runtime = mock(Ok("0.25"));
+        predict(&runtime, &sagemaker_model(None)).await.unwrap();
+        predict(&runtime, &sagemaker_model(Some("application/x-json"))).await.unwrap();
+
+        let requests = runtime.requests.lock().unwrap();
+        assert_eq!(
+            requests[0],
+            SageMakerRequest {
+                endpoint_name: "churn-endpoint".to_string(),
+                region: "eu-west-1".to_string(),
+                content_type: "application/json".to_string(),
+                body: br#"{"tenure":3}"#.to_vec(),
+            }
+        );
+        assert_eq!(requests[1].content_type, "application/x-json");
+    }
+
+    #[tokio::test]
+    async fn test_sagemaker_response_parsing() {
+        let model = sagemaker_model(None);
+        let result = predict(&mock(Ok("0.25")), &model).await.unwrap();
+        assert_eq!(result.prediction, PropertyValue::Double(0.25));
+        assert_eq!(result.probabilities, None);
+        let result = predict(&mock(Ok("1")), &model).await.unwrap();
+        assert_eq!(result.prediction, PropertyValue::Integer(1));
+
+        let body = r#"{"prediction": "churn", "probabilities": {"churn": 0.7, "stay": 0.3}}"#;
+        let result = predict(&mock(Ok(body)), &model).await.unwrap();
+        assert_eq!(result.prediction, PropertyValue::String("churn".to_string()));
+        assert_eq!(result.probabilities.unwrap()["stay"], 0.3);
+
+        for body in [r#"["churn"]"#, r#"{"score": 1}"#, "not json"] {
+            let error = predict(&mock(Ok(body)), &model).await.unwrap_err();
+            assert!(
+                matches!(&error, ModelExecutionError::ExecutionFailed(message) if message.starts_with("Invalid prediction response")),
+                "{}",
+                error
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sagemaker_errors() {
+        let model = sagemaker_model(None);
+        let error = predict(&mock(Err(SageMakerError::Throttled("Rate exceeded".to_string()))), &model).await.unwrap_err();
+        assert!(matches!(error, ModelExecutionError::Timeout), "{}", error);
+
+        let model_error = SageMakerError::ModelError("feature 'plan' is missing".to_string());
+        let error = predict(&mock(Err(model_error)), &model).await.unwrap_err();
+        assert!(
+            matches!(&error, ModelExecutionError::ExecutionFailed(message) if message == "feature 'plan' is missing"),
+            "{}",
+            error
+        );
+    }
+
+    #[test]
+    fn test_sagemaker_invocation_url() {
+        let request = SageMakerRequest::new("churn-endpoint", "eu-west-1", None, &HashMap::new()).unwrap();
+        assert_eq!(
+            request.invocation_url(),
+            "https://runtime.sagemaker.eu-west-1.amazonaws.com/endpoints/churn-endpoint/invocations"
+        );
+    }
+
+    #[test]
+    fn test_sagemaker_invoke_errors() {
+        let body = br#"{"message": "Rate exceeded"}"#;
+        assert_eq!(
+            invoke_error(400, Some("ThrottlingException:http://internal.amazon.com/coral/"), body),
+            SageMakerError::Throttled("Rate exceeded".to_string())
+        );
+        assert_eq!(invoke_error(429, None, body), SageMakerError::Throttled("Rate exceeded".to_string()));
+
+        let body = br#"{"ErrorCode": "CLIENT_ERROR_FROM_MODEL", "OriginalStatusCode": 400,
+            "OriginalMessage": "feature 'plan' is missing", "Message": "Received client error (400)"}"#;
+        assert_eq!(
+            invoke_error(424, Some("ModelError:http://internal.amazon.com/coral/"), body),
+            SageMakerError::ModelError("feature 'plan' is missing".to_string())
+        );
+
+        let body = br#"{"message": "Endpoint churn of account 1 not found."}"#;
+        assert_eq!(
+            invoke_error(400, Some("ValidationError"), body),
+            SageMakerError::Service("ValidationError (400): Endpoint churn of account 1 not found.".to_string())
+        );
+        assert_eq!(invoke_error(502, None, b"Bad Gateway"), SageMakerError::Service("HTTP 502: no message".to_string()));
+    }
+}
@@ -0,0 +1,151 @@
+//! JSON Schema (draft 2020-12) documents for object types.
+//!
+//! Consumers outside Rust validate payloads against these before submitting actions or
+//! writes. Each schema describes one object's properties as they travel over the API:
+//! - arrays become `items`, maps `additionalProperties`, structs nested objects and unions
+//!   `anyOf`
+//! - object references are strings (bare IDs or `type:id`) with format `object-reference`
+//!   and an `x-ref` naming the target type's schema file when the target is declared
+//! - dates, datetimes and GeoJSON are strings with the matching `format` (GeoJSON also
+//!   with its media type), described the same way as in form schemas
+//!
+//! `required`, validation rules, descriptions and defaults carry over; deprecated
+//! properties stay in the schema marked `deprecated`, and model-bound and computed
+//! properties are `readOnly`. Strict types reject undeclared properties.
+
+use crate::form_schema::add_constraints;
+use crate::meta_model::ObjectType;
+use crate::property::{Property, PropertyType, StructDef};
+use serde_json::{json, Map, Value as JsonValue};
+
+/// Dialect every exported schema declares in `$schema`
+pub const JSON_SCHEMA_DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// File (and `$id`) of an object type's schema
+pub fn json_schema_file_name(object_type_id: &str) -> String {
+    format!("{}.schema.json", object_type_id)
+}
+
+impl ObjectType {
+    /// JSON Schema of objects of this type
+    pub fn to_json_schema(&self) -> JsonValue {
+        let mut schema = Map::new();
+        schema.insert("$schema".to_string(), json!(JSON_SCHEMA_DIALECT));
+        schema.insert("$id".to_string(), json!(json_schema_file_name(&self.id)));
+        schema.insert("title".to_string(), json!(self.display_name));
+        schema.extend(object_schema(&self.properties));
+
+        for computed in &self.computed_properties {
+            let mut field = type_schema(&computed.property_type);
+            field.insert("title".to_string(), json!(computed.display_name));
+            if let Some(description) = &computed.description {
+                field.insert("description".to_string(), json!(description));
+            }
+            field.insert("readOnly".to_string(), json!(true));
+            if let Some(JsonValue::Object(properties)) = schema.get_mut("properties") {
+                properties.insert(computed.id.clone(), JsonValue::Object(field));
+            }
+        }
+        if self.strict_properties {
+            schema.insert("additionalProperties".to_string(), json!(false));
+        }
+        JsonValue::Object(schema)
+    }
+}
+
+/// `type: object` with `properties` and `required`
+fn object_schema(properties: &[Property]) -> Map<String, JsonValue> {
+    let mut fields = Map::new();
+    let mut required = Vec::new();
+    for property in properties {
+        if property.required {
+            required.push(json!(property.id));
+        }
+        fields.insert(property.id.clone(), JsonValue::Object(property_schema(property)));
+    }
+
+    let mut schema = Map::new();
+    schema.insert("type".to_string(), json!("object"));
+    schema.insert("properties".to_string(), JsonValue::Object(fields));
+    if !required.is_empty() {
+        schema.insert("required".to_string(), JsonValue::Array(required));
+    }
+    schema
+}
+
+fn property_schema(property: &Property) -> Map<String, JsonValue> {
+    let mut schema = type_schema(&property.property_type);
+    if let Some(display_name) = &property.display_name {
+        schema.insert("title".to_string(), json!(display_name));
+    }
+    if let Some(description) = &property.description {
+        schema.insert("description".to_string(), json!(description));
+    }
+    if let Some(default) = &property.default {
+        schema.insert("default".to_string(), serde_json::to_value(default).unwrap_or(JsonValue::Null));
+    }
+    if let Some(validation) = &property.validation {
+        add_constraints(&mut schema, validation, &property.property_type);
+    }
+    if let Some(target) = &property.reference_target {
+        schema.insert("x-ref".to_string(), json!(json_schema_file_name(target)));
+    }
+    if property.model_binding.is_some() {
+        schema.insert("readOnly".to_string(), json!(true));
+    }
+    if property.deprecated.is_some() {
+        schema.insert("deprecated".to_string(), json!(true));
+    }
+    schema
+}
+
+fn type_schema(property_type: &PropertyType) -> Map<String, JsonValue> {
+    value_schema(property_type, &|struct_def| {
+        let mut schema = object_schema(&struct_def.fields);
+        schema.insert("title".to_string(), json!(struct_def.id));
+        schema
+    })
+}
+
+/// Schema of a value of `property_type` as it travels over the API, shared with form
+/// schemas so the two always describe values alike. `struct_schema` describes struct
+/// types, whose fields each document lays out its own way.
+pub(crate) fn value_schema(
+    property_type: &PropertyType,
+    struct_schema: &dyn Fn(&StructDef) -> Map<String, JsonValue>,
+) -> Map<String, JsonValue> {
+    let schema = match property_type {
+        PropertyType::Array { element_type } => {
+            json!({ "type": "array", "items": value_schema(element_type, struct_schema) })
+        }
+        // JSON object keys are always strings, so only the value type is described
+        PropertyType::Map { value_type, .. } => {
+            json!({ "type": "object", "additionalProperties": value_schema(value_type, struct_schema) })
+        }
+        PropertyType::Object(struct_def) => JsonValue::Object(struct_schema(struct_def)),
+        PropertyType::Union { types } => {
+            let variants: Vec<JsonValue> = types
+                .iter()
+                .map(|t| JsonValue::Object(value_schema(t, struct_schema)))
+                .collect();
+            json!({ "anyOf": variants })
+        }
+        simple => match simple.as_simple() {
+            Some(PropertyType::Integer) => json!({ "type": "integer" }),
+            Some(PropertyType::Double) => json!({ "type": "number" }),
+            Some(PropertyType::Boolean) => json!({ "type": "boolean" }),
+            Some(PropertyType::Date) => json!({ "type": "string", "format": "date" }),
+            Some(PropertyType::DateTime) => json!({ "type": "string", "format": "date-time" }),
+            Some(PropertyType::ObjectReference) => json!({ "type": "string", "format": "object-reference" }),
+            // Geometries are sent as GeoJSON text; `format` lets forms pick a map input
+            Some(PropertyType::GeoJSON) => {
+                json!({ "type": "string", "format": "geojson", "contentMediaType": "application/geo+json" })
+            }
+            _ => json!({ "type": "string" }),
+        },
+    };
+    match schema {
+        JsonValue::Object(schema) => schema,
+        _ => Map::new(),
+    }
+}
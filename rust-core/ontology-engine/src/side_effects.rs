@@ -0,0 +1,440 @@
+//! Delivery of action side effects: webhooks are POSTed over HTTP, and emails are sent over
+//! SMTP when the crate is built with the `smtp` feature.
+//!
+//! A webhook side effect names either a configured endpoint (`endpoint`), whose URL and
+//! headers come from `WebhookConfig`, or a URL (`url`). Its whole config, templates
+//! substituted, is the JSON body. An email side effect has `to`, `subject` and `body`.
+//! Failed deliveries are retried with exponential backoff when the failure may be
+//! transient: network errors and 5xx responses, not 4xx ones.
+//!
+//! Endpoint headers and SMTP credentials are secrets: they are redacted from `Debug` output
+//! and never appear in errors, and URLs are reported without their query string.
+
+use crate::action::SideEffectType;
+use crate::action_executor::{default_side_effect, DeliveryOutcome};
+use crate::property::{PropertyMap, PropertyValue};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+/// Config key naming a configured webhook endpoint
+pub const WEBHOOK_ENDPOINT_CONFIG_KEY: &str = "endpoint";
+/// Config key holding a webhook URL, used when no endpoint is named
+pub const WEBHOOK_URL_CONFIG_KEY: &str = "url";
+
+/// Side effect delivery settings
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SideEffectConfig {
+    #[serde(default)]
+    pub webhooks: WebhookConfig,
+    /// Without it email side effects fail
+    #[serde(default)]
+    pub smtp: Option<SmtpConfig>,
+}
+
+/// Webhook endpoints and how patiently they are called
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookConfig {
+    /// Endpoints side effects can name, by name
+    #[serde(default)]
+    pub endpoints: HashMap<String, WebhookEndpoint>,
+    /// Per attempt
+    #[serde(default = "default_timeout_ms")]
+    pub timeout_ms: u64,
+    /// Retries after the first attempt
+    #[serde(default = "default_max_retries")]
+    pub max_retries: u32,
+    /// Delay before the first retry; it doubles with each further one
+    #[serde(default = "default_initial_backoff_ms")]
+    pub initial_backoff_ms: u64,
+}
+
+fn default_timeout_ms() -> u64 {
+    10_000
+}
+
+fn default_max_retries() -> u32 {
+    3
+}
+
+fn default_initial_backoff_ms() -> u64 {
+    500
+}
+
+impl Default for WebhookConfig {
+    fn default() -> Self {
+        Self {
+            endpoints: HashMap::new(),
+            timeout_ms: default_timeout_ms(),
+            max_retries: default_max_retries(),
+            initial_backoff_ms: default_initial_backoff_ms(),
+        }
+    }
+}
+
+impl WebhookConfig {
+    /// Delay before retry `retry` (1 for the first)
+    fn backoff(&self, retry: u32) -> Duration {
+        Duration::from_millis(self.initial_backoff_ms.saturating_mul(2u64.saturating_pow(retry.saturating_sub(1))))
+    }
+}
+
+/// A webhook receiver. `headers` typically carry credentials, so they are never shown.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct WebhookEndpoint {
+    pub url: String,
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+}
+
+impl fmt::Debug for WebhookEndpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WebhookEndpoint")
+            .field("url", &redacted_url(&self.url))
+            .field("headers", &self.headers.keys().map(|name| (name, "***")).collect::<HashMap<_, _>>())
+            .finish()
+    }
+}
+
+/// SMTP relay for email side effects
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SmtpConfig {
+    pub host: String,
+    #[serde(default = "default_smtp_port")]
+    pub port: u16,
+    #[serde(default)]
+    pub username: Option<String>,
+    #[serde(default)]
+    pub password: Option<String>,
+    /// Sender address
+    pub from: String,
+}
+
+fn default_smtp_port() -> u16 {
+    587
+}
+
+impl fmt::Debug for SmtpConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SmtpConfig")
+            .field("host", &self.host)
+            .field("port", &self.port)
+            .field("username", &self.username)
+            .field("password", &self.password.as_ref().map(|_| "***"))
+            .field("from", &self.from)
+            .finish()
+    }
+}
+
+/// Whether a failed attempt is worth retrying
+enum AttemptError {
+    Transient(String),
+    Permanent(String),
+}
+
+/// Delivers side effects according to a `SideEffectConfig`. Delivery blocks, so call it off
+/// async executors (e.g. in `spawn_blocking`) or from an `OutboxDispatcher` thread.
+pub struct SideEffectHandlers {
+    config: SideEffectConfig,
+    /// Created on first use, since blocking clients cannot be created on async executors
+    client: OnceLock<Result<reqwest::blocking::Client, String>>,
+}
+
+impl SideEffectHandlers {
+    pub fn new(config: SideEffectConfig) -> Self {
+        Self { config, client: OnceLock::new() }
+    }
+
+    pub fn config(&self) -> &SideEffectConfig {
+        &self.config
+    }
+
+    fn client(&self) -> Result<&reqwest::blocking::Client, String> {
+        self.client
+            .get_or_init(|| {
+                reqwest::blocking::Client::builder()
+                    .timeout(Duration::from_millis(self.config.webhooks.timeout_ms))
+                    .build()
+                    .map_err(|e| format!("Failed to create webhook client: {}", e))
+            })
+            .as_ref()
+            .map_err(Clone::clone)
+    }
+
+    /// Deliver one side effect. Log and notification effects are handled by
+    /// `default_side_effect`.
+    pub fn deliver(&self, effect_type: &SideEffectType, config: &PropertyMap) -> DeliveryOutcome {
+        match effect_type {
+            SideEffectType::Webhook => self.deliver_webhook(config),
+            SideEffectType::Email => self.deliver_email(config),
+            _ => DeliveryOutcome::from(default_side_effect(effect_type, config)),
+        }
+    }
+
+    /// POST the config as JSON to the webhook it names
+    pub fn deliver_webhook(&self, config: &PropertyMap) -> DeliveryOutcome {
+        let (client, (url, headers)) = match self.client().and_then(|client| Ok((client, self.webhook_target(config)?))) {
+            Ok(target) => target,
+            Err(e) => return DeliveryOutcome::failed(0, e),
+        };
+        let body: serde_json::Map<String, serde_json::Value> = config
+            .iter()
+            .filter(|(key, _)| key.as_str() != WEBHOOK_ENDPOINT_CONFIG_KEY)
+            .map(|(key, value)| (key.clone(), serde_json::to_value(value).unwrap_or(serde_json::Value::Null)))
+            .collect();
+        self.with_retries(|| {
+            let mut request = client.post(url).json(&body);
+            for (name, value) in headers {
+                request = request.header(name, value);
+            }
+            match request.send() {
+                Ok(response) if response.status().is_server_error() => Err(AttemptError::Transient(format!(
+                    "Webhook {} responded {}",
+                    redacted_url(url),
+                    response.status()
+                ))),
+                Ok(response) if response.status().is_client_error() => Err(AttemptError::Permanent(format!(
+                    "Webhook {} responded {}",
+                    redacted_url(url),
+                    response.status()
+                ))),
+                Ok(_) => Ok(()),
+                // reqwest errors can include the full URL
+                Err(e) => Err(AttemptError::Transient(format!(
+                    "Webhook {} failed: {}",
+                    redacted_url(url),
+                    e.without_url()
+                ))),
+            }
+        })
+    }
+
+    /// The URL and headers of the webhook `config` names
+    fn webhook_target<'a>(&'a self, config: &'a PropertyMap) -> Result<(&'a str, &'a HashMap<String, String>), String> {
+        static NO_HEADERS: OnceLock<HashMap<String, String>> = OnceLock::new();
+        match (config.get(WEBHOOK_ENDPOINT_CONFIG_KEY), config.get(WEBHOOK_URL_CONFIG_KEY)) {
+            (Some(PropertyValue::String(name)), _) => self
+                .config
+                .webhooks
+                .endpoints
+                .get(name)
+                .map(|endpoint| (endpoint.url.as_str(), &endpoint.headers))
+                .ok_or_else(|| format!("Webhook endpoint '{}' is not configured", name)),
+            (None, Some(PropertyValue::String(url))) => Ok((url.as_str(), NO_HEADERS.get_or_init(HashMap::new))),
+            _ => Err(format!(
+                "Webhook side effects need a '{}' or '{}' string",
+                WEBHOOK_ENDPOINT_CONFIG_KEY, WEBHOOK_URL_CONFIG_KEY
+            )),
+        }
+    }
+
+    /// Send the email `config` describes through the configured SMTP relay
+    pub fn deliver_email(&self, config: &PropertyMap) -> DeliveryOutcome {
+        let Some(smtp) = &self.config.smtp else {
+            return DeliveryOutcome::failed(0, "No SMTP server is configured".to_string());
+        };
+        let field = |key: &str| match config.get(key) {
+            Some(PropertyValue::String(value)) => Ok(value.clone()),
+            _ => Err(format!("Email side effects need a '{}' string", key)),
+        };
+        let (to, subject, body) = match (field("to"), field("subject"), field("body")) {
+            (Ok(to), Ok(subject), Ok(body)) => (to, subject, body),
+            (Err(e), _, _) | (_, Err(e), _) | (_, _, Err(e)) => return DeliveryOutcome::failed(0, e),
+        };
+        self.send_email(smtp, &to, &subject, body)
+    }
+
+    #[cfg(feature = "smtp")]
+    fn send_email(&self, smtp: &SmtpConfig, to: &str, subject: &str, body: String) -> DeliveryOutcome {
+        use lettre::transport::smtp::authentication::Credentials;
+        use lettre::{Message, SmtpTransport, Transport};
+
+        let message = match (smtp.from.parse(), to.parse()) {
+            (Ok(from), Ok(to)) => Message::builder().from(from).to(to).subject(subject).body(body),
+            _ => return DeliveryOutcome::failed(0, format!("Invalid email address in '{}' or '{}'", smtp.from, to)),
+        };
+        let message = match message {
+            Ok(message) => message,
+            Err(e) => return DeliveryOutcome::failed(0, format!("Invalid email: {}", e)),
+        };
+        let mut transport = match SmtpTransport::starttls_relay(&smtp.host) {
+            Ok(transport) => transport,
+            Err(e) => return DeliveryOutcome::failed(0, format!("Invalid SMTP server '{}': {}", smtp.host, e)),
+        }
+        .port(smtp.port)
+        .timeout(Some(Duration::from_millis(self.config.webhooks.timeout_ms)));
+        if let (Some(username), Some(password)) = (&smtp.username, &smtp.password) {
+            transport = transport.credentials(Credentials::new(username.clone(), password.clone()));
+        }
+        let transport = transport.build();
+        self.with_retries(|| match transport.send(&message) {
+            Ok(_) => Ok(()),
+            Err(e) if e.is_permanent() => Err(AttemptError::Permanent(format!("SMTP server rejected the email: {}", e))),
+            Err(e) => Err(AttemptError::Transient(format!("Sending email failed: {}", e))),
+        })
+    }
+
+    #[cfg(not(feature = "smtp"))]
+    fn send_email(&self, _smtp: &SmtpConfig, _to: &str, _subject: &str, _body: String) -> DeliveryOutcome {
+        DeliveryOutcome::failed(0, "Email side effects need the `smtp` feature".to_string())
+    }
+
+    /// Make attempts until one succeeds, fails permanently, or the retries run out
+    fn with_retries(&self, mut attempt: impl FnMut() -> Result<(), AttemptError>) -> DeliveryOutcome {
+        let mut attempts = 0;
+        loop {
+            attempts += 1;
+            match attempt() {
+                Ok(()) => return DeliveryOutcome::delivered(attempts),
+                Err(AttemptError::Permanent(e)) => return DeliveryOutcome::failed(attempts, e),
+                Err(AttemptError::Transient(e)) if attempts > self.config.webhooks.max_retries => {
+                    return DeliveryOutcome::failed(attempts, e)
+                }
+                Err(AttemptError::Transient(_)) => std::thread::sleep(self.config.webhooks.backoff(attempts)),
+            }
+        }
+    }
+
+    /// Handler for `ActionExecutor::side_effect_handler`
+    pub fn into_handler(self) -> Box<dyn Fn(&SideEffectType, &PropertyMap) -> DeliveryOutcome + Send + Sync> {
+        Box::new(move |effect_type, config| self.deliver(effect_type, config))
+    }
+}
+
+/// `url` without credentials or query string, which may carry tokens
+fn redacted_url(url: &str) -> String {
+    match reqwest::Url::parse(url) {
+        Ok(mut parsed) => {
+            parsed.set_query(None);
+            let _ = parsed.set_username("");
+            let _ = parsed.set_password(None);
+            parsed.to_string()
+        }
+        Err(_) => "<invalid url>".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    /// HTTP server answering the n-th request with `statuses[n]` (the last one repeats),
+    /// returning its URL and the number of requests served so far
+    fn serve(statuses: Vec<u16>) -> (String, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/hook?token=secret", listener.local_addr().unwrap());
+        let served = Arc::new(AtomicUsize::new(0));
+        let counter = served.clone();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut request = Vec::new();
+                let mut buffer = [0; 4096];
+                // Read the headers and the JSON body
+                while !String::from_utf8_lossy(&request).contains("\r\n\r\n")
+                    || !String::from_utf8_lossy(&request).trim_end().ends_with('}')
+                {
+                    let read = stream.read(&mut buffer).unwrap();
+                    if read == 0 {
+                        break;
+                    }
+                    request.extend_from_slice(&buffer[..read]);
+                }
+                let n = counter.fetch_add(1, Ordering::SeqCst);
+                let status = statuses[n.min(statuses.len() - 1)];
+                let response = format!("HTTP/1.1 {} Status\r\ncontent-length: 0\r\nconnection: close\r\n\r\n", status);
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+        });
+        (url, served)
+    }
+
+    fn handlers() -> SideEffectHandlers {
+        SideEffectHandlers::new(SideEffectConfig {
+            webhooks: WebhookConfig { initial_backoff_ms: 1, ..Default::default() },
+            smtp: None,
+        })
+    }
+
+    fn webhook(url: &str) -> PropertyMap {
+        let mut config = PropertyMap::new();
+        config.insert(WEBHOOK_URL_CONFIG_KEY.to_string(), PropertyValue::String(url.to_string()));
+        config.insert("plant".to_string(), PropertyValue::String("p2".to_string()));
+        config
+    }
+
+    #[test]
+    fn test_webhook_retries_server_errors() {
+        let (url, served) = serve(vec![503, 500, 200]);
+        let outcome = handlers().deliver(&SideEffectType::Webhook, &webhook(&url));
+        assert_eq!(outcome.attempts, 3);
+        assert_eq!(outcome.error, None);
+        assert_eq!(served.load(Ordering::SeqCst), 3);
+
+        // Three retries, then it gives up
+        let (url, served) = serve(vec![502]);
+        let outcome = handlers().deliver(&SideEffectType::Webhook, &webhook(&url));
+        assert_eq!(outcome.attempts, 4);
+        assert_eq!(served.load(Ordering::SeqCst), 4);
+        let error = outcome.error.unwrap();
+        assert!(error.contains("502"), "{}", error);
+        assert!(!error.contains("secret"), "{}", error);
+    }
+
+    #[test]
+    fn test_webhook_does_not_retry_client_errors() {
+        let (url, served) = serve(vec![404, 200]);
+        let outcome = handlers().deliver(&SideEffectType::Webhook, &webhook(&url));
+        assert_eq!(outcome.attempts, 1);
+        assert!(outcome.error.unwrap().contains("404"));
+        assert_eq!(served.load(Ordering::SeqCst), 1);
+
+        // Connection failures are retried
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let closed = format!("http://{}/hook", listener.local_addr().unwrap());
+        drop(listener);
+        let outcome = handlers().deliver(&SideEffectType::Webhook, &webhook(&closed));
+        assert_eq!(outcome.attempts, 4);
+        assert!(outcome.error.is_some());
+    }
+
+    #[test]
+    fn test_configured_endpoints_keep_secrets_out_of_output() {
+        let (url, served) = serve(vec![200]);
+        let mut webhooks = WebhookConfig::default();
+        webhooks.endpoints.insert(
+            "alerts".to_string(),
+            WebhookEndpoint {
+                url,
+                headers: HashMap::from([("authorization".to_string(), "Bearer s3cr3t".to_string())]),
+            },
+        );
+        let debug = format!("{:?}", webhooks);
+        assert!(!debug.contains("s3cr3t") && !debug.contains("token=secret"), "{}", debug);
+        let handlers = SideEffectHandlers::new(SideEffectConfig { webhooks, smtp: None });
+
+        let mut config = PropertyMap::new();
+        config.insert(WEBHOOK_ENDPOINT_CONFIG_KEY.to_string(), PropertyValue::String("alerts".to_string()));
+        assert_eq!(handlers.deliver(&SideEffectType::Webhook, &config), DeliveryOutcome::delivered(1));
+        assert_eq!(served.load(Ordering::SeqCst), 1);
+
+        config.insert(WEBHOOK_ENDPOINT_CONFIG_KEY.to_string(), PropertyValue::String("pager".to_string()));
+        let outcome = handlers.deliver(&SideEffectType::Webhook, &config);
+        assert_eq!(outcome, DeliveryOutcome::failed(0, "Webhook endpoint 'pager' is not configured".to_string()));
+
+        let smtp = SmtpConfig {
+            host: "smtp.example.com".to_string(),
+            port: 587,
+            username: Some("mailer".to_string()),
+            password: Some("hunter2".to_string()),
+            from: "ontology@example.com".to_string(),
+        };
+        assert!(!format!("{:?}", smtp).contains("hunter2"));
+    }
+}
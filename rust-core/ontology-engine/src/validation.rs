@@ -51,11 +51,11 @@ pub fn validate_action(
         }
         
         if let Some(value) = action.parameters.get(&param_def.id) {
-            if let Err(e) = param_def.validate_value(value) {
-                return Err(ValidationError::InvalidParameter(format!(
-                    "Parameter '{}': {}",
-                    param_def.id, e
-                )));
+            if let Err(e) = param_def.validate_value_with_siblings(value, &action.parameters) {
+                return Err(ValidationError::InvalidParameterValue {
+                    parameter: param_def.id.clone(),
+                    message: e,
+                });
             }
         }
     }
@@ -115,6 +115,20 @@ fn check_condition(
             condition.property
         )))?;
     
+    if !condition_matches(condition, value)? {
+        return Err(ValidationError::InvalidCondition(format!(
+            "Condition failed: {} {:?} {:?}",
+            condition.property,
+            condition.operator,
+            condition.value
+        )));
+    }
+    
+    Ok(())
+}
+
+/// Whether `value` satisfies a condition
+pub fn condition_matches(condition: &ActionCondition, value: &PropertyValue) -> Result<bool, ValidationError> {
     let matches = match &condition.operator {
         ConditionOperator::Equals => value == &condition.value,
         ConditionOperator::NotEquals => value != &condition.value,
@@ -155,17 +169,7 @@ fn check_condition(
             }
         }
     };
-    
-    if !matches {
-        return Err(ValidationError::InvalidCondition(format!(
-            "Condition failed: {} {:?} {:?}",
-            condition.property,
-            condition.operator,
-            condition.value
-        )));
-    }
-    
-    Ok(())
+    Ok(matches)
 }
 
 /// Compare two property values numerically
@@ -203,8 +207,26 @@ pub enum ValidationError {
     #[error("Invalid parameter: {0}")]
     InvalidParameter(String),
     
+    #[error("Invalid parameter '{parameter}': {message}")]
+    InvalidParameterValue { parameter: String, message: String },
+    
     #[error("Invalid condition: {0}")]
     InvalidCondition(String),
+    
+    /// An operation failed; the result says what ran, and what was rolled back
+    #[error("Action execution failed: {:?}", .0.errors)]
+    ExecutionFailed(Box<crate::action_executor::ActionExecutionResult>),
+}
+
+impl ValidationError {
+    /// The parameter the error is about, if it is about one
+    pub fn parameter(&self) -> Option<&str> {
+        match self {
+            ValidationError::MissingParameter(parameter) => Some(parameter),
+            ValidationError::InvalidParameterValue { parameter, .. } => Some(parameter),
+            _ => None,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -217,10 +239,12 @@ mod tests {
         ActionType {
             id: "test_action".to_string(),
             display_name: "Test Action".to_string(),
+            display_names: std::collections::HashMap::new(),
             parameters: vec![
             Property {
                 id: "required_param".to_string(),
                 display_name: None,
+                display_names: std::collections::HashMap::new(),
                 property_type: PropertyType::String,
                 required: true,
                 default: None,
@@ -233,11 +257,18 @@ mod tests {
                 pii: false,
                 deprecated: None,
                     statistics: None,
-                    model_binding: None,            },
+                    model_binding: None,
+                    display_order: None,
+                    indexing: None,
+                    reference_target: None,
+                    geo: None,
+                    custom_validator: None,            },
             ],
             logic: vec![],
             validation: None,
             side_effects: vec![],
+            target_interface: None,
+            execution_mode: None,
         }
     }
     
@@ -260,6 +291,24 @@ mod tests {
         }
     }
     
+    #[test]
+    fn test_action_validation_names_invalid_parameter() {
+        let action_type = create_test_action_type();
+        let mut params = PropertyMap::new();
+        params.insert("required_param".to_string(), PropertyValue::Integer(7));
+        let action = Action {
+            action_type_id: "test_action".to_string(),
+            parameters: params,
+            executed_by: "user1".to_string(),
+            timestamp: chrono::Utc::now(),
+        };
+        let context = ActionContext::new("user1".to_string());
+        
+        let err = validate_action(&action, &action_type, &context).unwrap_err();
+        assert!(matches!(err, ValidationError::InvalidParameterValue { .. }), "{:?}", err);
+        assert_eq!(err.parameter(), Some("required_param"));
+    }
+    
     #[test]
     fn test_action_validation_with_role_requirement() {
         let mut action_type = create_test_action_type();
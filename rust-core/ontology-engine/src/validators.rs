@@ -0,0 +1,236 @@
+//! Named validation functions for rules the declarative `validation` block cannot express,
+//! such as checksums or lookups against reference data.
+//!
+//! A property opts in with `customValidator: <name>`. The host application registers its
+//! validators before loading the ontology, since a load fails if a property names one that
+//! is not registered. A validator receives the value and the other properties of the
+//! object (or the other parameters of an action or function call) and runs after the
+//! built-in rules pass. `luhn` and `url` are always registered.
+
+use crate::load_error::{DefinitionKind, OntologyLoadError};
+use crate::meta_model::OntologyDef;
+use crate::property::{Property, PropertyMap, PropertyValue};
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock, RwLock};
+
+/// A validator: `Err` carries the message reported for the value
+pub type CustomValidator = Arc<dyn Fn(&PropertyValue, &PropertyMap) -> Result<(), String> + Send + Sync>;
+
+/// Digit strings (card and account numbers, ...) whose Luhn check digit must match
+pub const LUHN_VALIDATOR: &str = "luhn";
+
+/// Absolute URLs with a host
+pub const URL_VALIDATOR: &str = "url";
+
+static VALIDATORS: OnceLock<RwLock<HashMap<String, CustomValidator>>> = OnceLock::new();
+
+fn validators() -> &'static RwLock<HashMap<String, CustomValidator>> {
+    VALIDATORS.get_or_init(|| {
+        let mut validators: HashMap<String, CustomValidator> = HashMap::new();
+        validators.insert(LUHN_VALIDATOR.to_string(), Arc::new(luhn));
+        validators.insert(URL_VALIDATOR.to_string(), Arc::new(url));
+        RwLock::new(validators)
+    })
+}
+
+/// Process-wide registry of named validators
+pub struct ValidatorRegistry;
+
+impl ValidatorRegistry {
+    /// Register `validator` under `name`, replacing any validator already registered there
+    pub fn register<F>(name: impl Into<String>, validator: F)
+    where
+        F: Fn(&PropertyValue, &PropertyMap) -> Result<(), String> + Send + Sync + 'static,
+    {
+        validators().write().unwrap().insert(name.into(), Arc::new(validator));
+    }
+
+    pub fn get(name: &str) -> Option<CustomValidator> {
+        validators().read().unwrap().get(name).cloned()
+    }
+
+    pub fn is_registered(name: &str) -> bool {
+        validators().read().unwrap().contains_key(name)
+    }
+
+    /// Registered validator names, sorted
+    pub fn names() -> Vec<String> {
+        let mut names: Vec<String> = validators().read().unwrap().keys().cloned().collect();
+        names.sort();
+        names
+    }
+}
+
+impl Property {
+    /// Run this property's custom validator, if it names one, on a value. `siblings` are the
+    /// other values of the object or call the value belongs to.
+    pub fn run_custom_validator(&self, value: &PropertyValue, siblings: &PropertyMap) -> Result<(), String> {
+        let Some(name) = &self.custom_validator else {
+            return Ok(());
+        };
+        let validator = ValidatorRegistry::get(name)
+            .ok_or_else(|| format!("Property '{}' uses unregistered validator '{}'", self.id, name))?;
+        validator(value, siblings).map_err(|e| format!("Property '{}': {}", self.id, e))
+    }
+
+    /// Built-in rules, then the custom validator
+    pub fn validate_value_with_siblings(&self, value: &PropertyValue, siblings: &PropertyMap) -> Result<(), String> {
+        self.validate_value(value)?;
+        self.run_custom_validator(value, siblings)
+    }
+}
+
+/// Properties and parameters naming a validator that is not registered
+pub(crate) fn unregistered_validator_errors(ontology_def: &OntologyDef) -> Vec<OntologyLoadError> {
+    let mut owners: Vec<(String, &Vec<Property>)> = Vec::new();
+    for object_type in &ontology_def.object_types {
+        owners.push((format!("object type '{}'", object_type.id), &object_type.properties));
+    }
+    for link_type in &ontology_def.link_types {
+        owners.push((format!("link type '{}'", link_type.id), &link_type.properties));
+    }
+    for action_type in &ontology_def.action_types {
+        owners.push((format!("action type '{}'", action_type.id), &action_type.parameters));
+    }
+    for function_type in &ontology_def.function_types {
+        owners.push((format!("function type '{}'", function_type.id), &function_type.parameters));
+    }
+
+    let mut errors = Vec::new();
+    for (owner, properties) in owners {
+        for property in properties {
+            let Some(name) = &property.custom_validator else {
+                continue;
+            };
+            if !ValidatorRegistry::is_registered(name) {
+                errors.push(OntologyLoadError::UnknownReference {
+                    kind: DefinitionKind::Validator,
+                    id: name.clone(),
+                    referenced_by: format!("Property '{}' of {}", property.id, owner),
+                });
+            }
+        }
+    }
+    errors
+}
+
+fn luhn(value: &PropertyValue, _siblings: &PropertyMap) -> Result<(), String> {
+    let text = match value {
+        PropertyValue::String(s) => s.clone(),
+        PropertyValue::Integer(i) => i.to_string(),
+        other => return Err(format!("Luhn check needs a string or integer, got {}", other.to_string())),
+    };
+    // Spaces and dashes are common separators in printed numbers
+    let digits: Vec<u32> = text
+        .chars()
+        .filter(|c| !matches!(c, ' ' | '-'))
+        .map(|c| c.to_digit(10))
+        .collect::<Option<_>>()
+        .ok_or_else(|| format!("'{}' is not a number", text))?;
+    if digits.len() < 2 {
+        return Err(format!("'{}' is too short for a Luhn check digit", text));
+    }
+    let sum: u32 = digits
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, &d)| match i % 2 {
+            1 if d * 2 > 9 => d * 2 - 9,
+            1 => d * 2,
+            _ => d,
+        })
+        .sum();
+    if sum % 10 == 0 {
+        Ok(())
+    } else {
+        Err(format!("'{}' fails the Luhn checksum", text))
+    }
+}
+
+fn url(value: &PropertyValue, _siblings: &PropertyMap) -> Result<(), String> {
+    let PropertyValue::String(text) = value else {
+        return Err(format!("Expected a URL string, got {}", value.to_string()));
+    };
+    match reqwest::Url::parse(text) {
+        Ok(url) if url.host_str().is_some_and(|host| !host.is_empty()) => Ok(()),
+        Ok(_) => Err(format!("'{}' is not a URL with a host", text)),
+        Err(e) => Err(format!("'{}' is not a valid URL: {}", text, e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_validators() {
+        let siblings = PropertyMap::new();
+        let text = |s: &str| PropertyValue::String(s.to_string());
+        assert!(luhn(&text("4539 1488 0343 6467"), &siblings).is_ok());
+        assert!(luhn(&PropertyValue::Integer(79927398713), &siblings).is_ok());
+        assert!(luhn(&text("4539 1488 0343 6468"), &siblings).unwrap_err().contains("Luhn checksum"));
+        assert!(luhn(&text("12a4"), &siblings).is_err());
+
+        assert!(url(&text("https://example.com/path?q=1"), &siblings).is_ok());
+        assert!(url(&text("example.com"), &siblings).is_err());
+        assert!(url(&text("mailto:someone@example.com"), &siblings).is_err());
+        assert!(ValidatorRegistry::is_registered(LUHN_VALIDATOR));
+        assert!(ValidatorRegistry::is_registered(URL_VALIDATOR));
+    }
+
+    #[test]
+    fn test_unregistered_validator_fails_the_load() {
+        let yaml = r#"
+ontology:
+  objectTypes:
+    - id: company
+      displayName: Company
+      primaryKey: id
+      properties:
+        - id: id
+          type: string
+        - id: naics
+          type: string
+          customValidator: test_never_registered
+        - id: website
+          type: string
+          customValidator: url
+  linkTypes: []
+"#;
+        let Err(errors) = crate::Ontology::from_yaml(yaml) else {
+            panic!("an unregistered validator should fail the load");
+        };
+        assert_eq!(
+            errors.to_string(),
+            "Property 'naics' of object type 'company' references unknown validator 'test_never_registered'"
+        );
+
+        ValidatorRegistry::register("test_registered_late", |_, _| Ok(()));
+        let yaml = yaml.replace("test_never_registered", "test_registered_late");
+        assert!(crate::Ontology::from_yaml(&yaml).is_ok());
+    }
+
+    #[test]
+    fn test_custom_validator_sees_siblings() {
+        // Valid when the code starts with the object's state FIPS prefix
+        ValidatorRegistry::register("test_county_fips", |value, siblings| {
+            let state = siblings.get("state_fips").map(|v| v.to_string()).unwrap_or_default();
+            match value {
+                PropertyValue::String(code) if code.len() == 5 && code.starts_with(&state) => Ok(()),
+                other => Err(format!("'{}' is not a county in state {}", other.to_string(), state)),
+            }
+        });
+        let property: Property = serde_json::from_str(
+            r#"{"id": "county_fips", "type": "string", "customValidator": "test_county_fips"}"#,
+        )
+        .unwrap();
+        let mut siblings = PropertyMap::new();
+        siblings.insert("state_fips".to_string(), PropertyValue::String("25".to_string()));
+
+        assert!(property.validate_value_with_siblings(&PropertyValue::String("25025".to_string()), &siblings).is_ok());
+        let err = property
+            .validate_value_with_siblings(&PropertyValue::String("36061".to_string()), &siblings)
+            .unwrap_err();
+        assert_eq!(err, "Property 'county_fips': '36061' is not a county in state 25");
+    }
+}
@@ -1,4 +1,5 @@
-use ontology_engine::crosswalk::{CrosswalkTraverser, CrosswalkLink};
+use ontology_engine::crosswalk::{ApportionRounding, CrosswalkTraverser, CrosswalkLink};
+use ontology_engine::property::{PropertyMap, PropertyValue};
 use std::collections::HashMap;
 
 #[test]
@@ -35,9 +36,105 @@ fn test_normalize_boundaries() {
     assert!((total - 1000.0).abs() < 0.01);
 }
 
+fn link(source: &str, source_year: i64, target: &str, target_year: i64, overlap: f64) -> CrosswalkLink {
+    CrosswalkLink {
+        source_tract_id: source.to_string(),
+        target_tract_id: target.to_string(),
+        source_year,
+        target_year,
+        overlap_percentage: overlap,
+        allocation_factor: None,
+    }
+}
 
+#[test]
+fn test_resolve_path_composes_weights_across_vintages() {
+    // A splits 60/40 into B and C; C splits again into D and E while B carries over whole
+    let traverser = CrosswalkTraverser::new(vec![
+        link("A", 1990, "B", 2000, 0.6),
+        link("A", 1990, "C", 2000, 0.4),
+        link("B", 2000, "F", 2010, 1.0),
+        link("C", 2000, "D", 2010, 0.75),
+        link("C", 2000, "E", 2010, 0.25),
+    ]);
 
+    assert_eq!(traverser.find_path(1990, 2010).unwrap(), vec![1990, 2000, 2010]);
+    let weights = traverser.resolve_path(1990, 2010, "A").unwrap();
+    let expected = [("D", 0.3), ("E", 0.1), ("F", 0.6)];
+    assert_eq!(weights.len(), expected.len());
+    for ((target, weight), (expected_target, expected_weight)) in weights.iter().zip(expected) {
+        assert_eq!(target, expected_target);
+        assert!((weight - expected_weight).abs() < 1e-9, "{} has weight {}", target, weight);
+    }
+    let total: f64 = weights.iter().map(|(_, weight)| weight).sum();
+    assert!((total - 1.0).abs() < 1e-9);
+}
 
+fn tract(id: &str, property: &str, value: PropertyValue) -> (String, PropertyMap) {
+    let mut properties = PropertyMap::new();
+    properties.insert(property.to_string(), value);
+    (id.to_string(), properties)
+}
 
+#[test]
+fn test_apportion_keeps_the_integer_grand_total() {
+    // Thirds don't divide the populations evenly, and A's split chains across two hops
+    let traverser = CrosswalkTraverser::new(vec![
+        link("A", 1990, "B", 2000, 0.6),
+        link("A", 1990, "C", 2000, 0.4),
+        link("G", 1990, "B", 2000, 1.0),
+        link("H", 1990, "B", 2000, 1.0),
+        link("H", 1990, "C", 2000, 1.0),
+        link("H", 1990, "X", 2000, 1.0),
+        link("B", 2000, "F", 2010, 1.0),
+        link("C", 2000, "D", 2010, 0.75),
+        link("C", 2000, "E", 2010, 0.25),
+        link("X", 2000, "E", 2010, 1.0),
+    ]);
+    let sources = vec![
+        tract("A", "population", PropertyValue::Integer(1001)),
+        tract("G", "population", PropertyValue::Integer(77)),
+        tract("H", "population", PropertyValue::Integer(100)),
+    ];
 
+    let apportioned = traverser
+        .apportion("population", &sources, 1990, 2010, ApportionRounding::LargestRemainder)
+        .unwrap();
+    let total: i64 = apportioned
+        .values()
+        .map(|value| match value {
+            PropertyValue::Integer(value) => *value,
+            other => panic!("expected an integer, got {:?}", other),
+        })
+        .sum();
+    assert_eq!(total, 1001 + 77 + 100);
+    assert_eq!(apportioned.len(), 3);
+}
 
+#[test]
+fn test_apportion_sums_doubles_and_rejects_non_numeric_properties() {
+    let traverser = CrosswalkTraverser::new(vec![
+        link("A", 1990, "B", 2000, 0.5),
+        link("A", 1990, "C", 2000, 0.5),
+        link("D", 1990, "C", 2000, 1.0),
+    ]);
+
+    let sources = vec![
+        tract("A", "income", PropertyValue::Double(10.0)),
+        tract("D", "income", PropertyValue::Integer(3)),
+    ];
+    let apportioned = traverser
+        .apportion("income", &sources, 1990, 2000, ApportionRounding::default())
+        .unwrap();
+    assert_eq!(apportioned["B"], PropertyValue::Double(5.0));
+    assert_eq!(apportioned["C"], PropertyValue::Double(8.0));
+
+    let sources = vec![
+        tract("A", "name", PropertyValue::String("North".to_string())),
+        tract("D", "name", PropertyValue::Boolean(true)),
+    ];
+    let error = traverser
+        .apportion("name", &sources, 1990, 2000, ApportionRounding::default())
+        .unwrap_err();
+    assert_eq!(error, "Property 'name' is not numeric on: A, D");
+}
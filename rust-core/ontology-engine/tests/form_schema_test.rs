@@ -0,0 +1,59 @@
+use ontology_engine::{action_form_schema, object_form_schema, FormSchemaOptions, Ontology};
+use serde_json::{json, Value};
+
+/// Committed form schemas of the fixture ontology. Regenerate with `UPDATE_FORM_SCHEMAS=1`
+/// once a change to the generated schemas is intended.
+const GOLDEN: &str = "tests/fixtures/form_schemas.json";
+
+fn fixture() -> Ontology {
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/form_ontology.yaml");
+    Ontology::from_yaml(&std::fs::read_to_string(path).unwrap()).unwrap()
+}
+
+fn form_schemas(ontology: &Ontology) -> Value {
+    let plant = ontology.get_object_type("plant").unwrap();
+    let action = ontology.get_action_type("schedule_maintenance").unwrap();
+    let french = FormSchemaOptions { locale: Some("fr-CA".to_string()), include_deprecated: true };
+    json!({
+        "plant": object_form_schema(plant, &FormSchemaOptions::default()),
+        "plant_fr_with_deprecated": object_form_schema(plant, &french),
+        "schedule_maintenance": action_form_schema(action, &FormSchemaOptions::default()),
+        "schedule_maintenance_fr": action_form_schema(action, &french),
+    })
+}
+
+#[test]
+fn test_form_schemas_match_golden_file() {
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join(GOLDEN);
+    let current = form_schemas(&fixture());
+    if std::env::var("UPDATE_FORM_SCHEMAS").as_deref() == Ok("1") {
+        let pretty = serde_json::to_string_pretty(&current).unwrap() + "\n";
+        std::fs::write(&path, pretty).expect("Failed to write form schemas");
+        return;
+    }
+    let golden: Value = serde_json::from_str(&std::fs::read_to_string(&path).expect("Form schema golden file missing"))
+        .expect("Form schema golden file is not JSON");
+    // Compared as values, so key order does not matter
+    assert_eq!(current, golden, "Generated form schemas changed; regenerate with UPDATE_FORM_SCHEMAS=1 if intended");
+}
+
+#[test]
+fn test_form_schema_nests_complex_types_and_omits_deprecated() {
+    let ontology = fixture();
+    let plant = ontology.get_object_type("plant").unwrap();
+    let schema = object_form_schema(plant, &FormSchemaOptions::default());
+
+    let order: Vec<&str> = schema["x-order"].as_array().unwrap().iter().map(|id| id.as_str().unwrap()).collect();
+    assert_eq!(&order[..3], ["id", "name", "code"]);
+    assert_eq!(order.last(), Some(&"utilization"));
+    assert!(schema["properties"].get("legacy_code").is_none());
+    assert_eq!(schema["x-sections"][0]["properties"], json!(["name", "code", "status"]));
+
+    let contacts = &schema["properties"]["address"]["properties"]["contacts"];
+    assert_eq!(contacts["type"], "array");
+    assert_eq!(contacts["items"]["required"], json!(["email"]));
+    assert_eq!(schema["properties"]["settings"]["additionalProperties"]["type"], "integer");
+    assert_eq!(schema["properties"]["tags"]["maxItems"], 10);
+    assert_eq!(schema["properties"]["risk_score"]["readOnly"], true);
+    assert_eq!(schema["properties"]["utilization"]["readOnly"], true);
+}
@@ -17,6 +17,7 @@ fn test_geojson_validation() {
     let prop = Property {
         id: "geoshape".to_string(),
         display_name: None,
+        display_names: std::collections::HashMap::new(),
         property_type: PropertyType::GeoJSON,
         required: false,
         default: None,
@@ -30,6 +31,11 @@ fn test_geojson_validation() {
         deprecated: None,
         statistics: None,
         model_binding: None,
+        display_order: None,
+        indexing: None,
+        reference_target: None,
+        geo: None,
+        custom_validator: None,
     };
 
     // Valid GeoJSON
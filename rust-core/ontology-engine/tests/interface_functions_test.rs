@@ -0,0 +1,157 @@
+use ontology_engine::{AggregationType, FunctionDataSource, FunctionExecutor, Ontology, OntologyLoadError, PropertyMap, PropertyValue};
+use std::collections::HashMap;
+
+const ONTOLOGY: &str = r#"
+ontology:
+  interfaces:
+    - id: "Location"
+      displayName: "Location"
+      properties:
+        - id: "area"
+          type: "double"
+  objectTypes:
+    - id: "tract"
+      displayName: "Census Tract"
+      primaryKey: "geoid"
+      implements: ["Location"]
+      interfaceMappings:
+        Location:
+          area: "land_area"
+      properties:
+        - id: "geoid"
+          type: "string"
+          required: true
+        - id: "land_area"
+          type: "double"
+    - id: "county"
+      displayName: "County"
+      primaryKey: "fips"
+      implements: ["Location"]
+      properties:
+        - id: "fips"
+          type: "string"
+          required: true
+        - id: "area"
+          type: "double"
+  linkTypes: []
+  actionTypes:
+    - id: "set_area"
+      displayName: "Set Area"
+      targetInterface: "Location"
+      logic:
+        - operation: "update_object"
+          properties:
+            properties:
+              area: 1.5
+  functionTypes:
+    - id: "location_area"
+      displayName: "Location Area"
+      targetInterface: "Location"
+      parameters:
+        - id: "location"
+          type: "object_reference"
+          required: true
+      returnType:
+        type: "property"
+        property_type: "double"
+      logic:
+        type: "property_access"
+        property: "area"
+"#;
+
+/// Stored objects, keyed by (type, id), using each type's own property names
+struct StoredObjects(HashMap<(String, String), PropertyMap>);
+
+#[async_trait::async_trait]
+impl FunctionDataSource for StoredObjects {
+    async fn get_object_property(
+        &self,
+        object_type: &str,
+        object_id: &str,
+        property: &str,
+    ) -> Result<Option<PropertyValue>, String> {
+        let key = (object_type.to_string(), object_id.to_string());
+        Ok(self.0.get(&key).and_then(|properties| properties.get(property).cloned()))
+    }
+
+    async fn get_linked_objects(&self, _: &str, _: &str, _: &str) -> Result<Vec<String>, String> {
+        Ok(Vec::new())
+    }
+
+    async fn aggregate_linked_properties(
+        &self,
+        _: &str,
+        _: &str,
+        _: &str,
+        _: AggregationType,
+    ) -> Result<Option<PropertyValue>, String> {
+        Ok(None)
+    }
+}
+
+#[tokio::test]
+async fn test_interface_function_runs_against_each_implementer() {
+    let ontology = Ontology::from_yaml(ONTOLOGY).unwrap();
+    let function = ontology.get_function_type("location_area").unwrap();
+
+    let mut objects = HashMap::new();
+    let mut tract = PropertyMap::new();
+    tract.insert("land_area".to_string(), PropertyValue::Double(4.25));
+    objects.insert(("tract".to_string(), "t1".to_string()), tract);
+    let mut county = PropertyMap::new();
+    county.insert("area".to_string(), PropertyValue::Double(912.0));
+    objects.insert(("county".to_string(), "c1".to_string()), county);
+    let objects = StoredObjects(objects);
+
+    for (object_type, object_id, expected) in [("tract", "t1", 4.25), ("county", "c1", 912.0)] {
+        let bound = function.bind_to(ontology.get_object_type(object_type).unwrap()).unwrap();
+        assert!(bound.target_interface.is_none());
+
+        let mut params = PropertyMap::new();
+        params.insert(
+            "location".to_string(),
+            PropertyValue::ObjectReference(format!("{}:{}", object_type, object_id)),
+        );
+        let result = FunctionExecutor::execute(&bound, &params, Some(&objects))
+            .await
+            .unwrap();
+        assert_eq!(result.value, PropertyValue::Double(expected));
+    }
+}
+
+#[test]
+fn test_interface_action_binds_operations_to_implementer() {
+    let ontology = Ontology::from_yaml(ONTOLOGY).unwrap();
+    let action = ontology.get_action_type("set_area").unwrap();
+
+    let bound = action.bind_to(ontology.get_object_type("tract").unwrap()).unwrap();
+    assert_eq!(bound.logic[0].object_type.as_deref(), Some("tract"));
+    assert!(bound.logic[0].properties.contains_key("land_area"));
+    assert!(!bound.logic[0].properties.contains_key("area"));
+
+    let bound = action.bind_to(ontology.get_object_type("county").unwrap()).unwrap();
+    assert_eq!(bound.logic[0].object_type.as_deref(), Some("county"));
+    assert!(bound.logic[0].properties.contains_key("area"));
+}
+
+#[test]
+fn test_interface_bindings_are_validated() {
+    let yaml = ONTOLOGY
+        .replace("property: \"area\"", "property: \"land_area\"")
+        .replace("area: 1.5", "population: 10")
+        .replace("area: \"land_area\"", "perimeter: \"land_area\"");
+    let errors = Ontology::from_yaml(&yaml).err().unwrap();
+
+    let unknown: Vec<(&str, &str)> = errors
+        .iter()
+        .filter_map(|e| match e {
+            OntologyLoadError::UnknownReference { id, referenced_by, .. } => {
+                Some((id.as_str(), referenced_by.as_str()))
+            }
+            _ => None,
+        })
+        .collect();
+    assert!(unknown.iter().any(|(id, by)| *id == "land_area" && by.contains("location_area")));
+    assert!(unknown.iter().any(|(id, by)| *id == "population" && by.contains("set_area")));
+    assert!(unknown.iter().any(|(id, by)| *id == "perimeter" && by.contains("tract")));
+}
@@ -0,0 +1,158 @@
+use ontology_engine::{
+    AggregationType, FunctionDataSource, FunctionExecutor, FunctionLogic, FunctionReturnType, Ontology, PropertyMap,
+    PropertyValue,
+};
+use std::collections::HashMap;
+
+const ONTOLOGY: &str = r#"
+ontology:
+  objectTypes:
+    - id: "region"
+      displayName: "Region"
+      primaryKey: "id"
+      properties:
+        - id: "id"
+          type: "string"
+    - id: "company"
+      displayName: "Company"
+      primaryKey: "id"
+      properties:
+        - id: "id"
+          type: "string"
+    - id: "employee"
+      displayName: "Employee"
+      primaryKey: "id"
+      properties:
+        - id: "id"
+          type: "string"
+        - id: "status"
+          type: "string"
+        - id: "salary"
+          type: "integer"
+  linkTypes:
+    - id: "locatedIn"
+      source: "company"
+      target: "region"
+      cardinality: "MANY_TO_ONE"
+    - id: "worksAt"
+      source: "employee"
+      target: "company"
+      cardinality: "MANY_TO_ONE"
+  functionTypes:
+    - id: "region_payroll"
+      displayName: "Region Payroll"
+      parameters:
+        - id: "region"
+          type: "object_reference"
+          required: true
+      returnType:
+        type: "property"
+        property_type: "integer"
+      logic:
+        type: "pipeline"
+        steps:
+          - type: "link_traversal"
+            linkType: "locatedIn"
+            targetType: "company"
+          - type: "link_traversal"
+            linkType: "worksAt"
+            targetType: "employee"
+          - type: "filter"
+            filters:
+              - property: "status"
+                operator: "=="
+                value: "active"
+          - type: "aggregation"
+            aggregation: "sum"
+            property: "salary"
+"#;
+
+/// Objects keyed by (type, id), and links keyed by (source reference, link type). Links are
+/// stored in the direction a traversal follows them.
+#[derive(Default)]
+struct Graph {
+    objects: HashMap<(String, String), PropertyMap>,
+    links: HashMap<(String, String), Vec<String>>,
+}
+
+impl Graph {
+    fn employee(&mut self, id: &str, company: &str, status: &str, salary: i64) {
+        let mut properties = PropertyMap::new();
+        properties.insert("status".to_string(), PropertyValue::String(status.to_string()));
+        properties.insert("salary".to_string(), PropertyValue::Integer(salary));
+        self.objects.insert(("employee".to_string(), id.to_string()), properties);
+        self.link(&format!("company:{}", company), "worksAt", id);
+    }
+
+    fn link(&mut self, from: &str, link_type: &str, to: &str) {
+        self.links
+            .entry((from.to_string(), link_type.to_string()))
+            .or_default()
+            .push(to.to_string());
+    }
+}
+
+#[async_trait::async_trait]
+impl FunctionDataSource for Graph {
+    async fn get_object_property(
+        &self,
+        object_type: &str,
+        object_id: &str,
+        property: &str,
+    ) -> Result<Option<PropertyValue>, String> {
+        let key = (object_type.to_string(), object_id.to_string());
+        Ok(self.objects.get(&key).and_then(|properties| properties.get(property).cloned()))
+    }
+
+    async fn get_linked_objects(&self, object_id: &str, link_type: &str, _: &str) -> Result<Vec<String>, String> {
+        let key = (object_id.to_string(), link_type.to_string());
+        Ok(self.links.get(&key).cloned().unwrap_or_default())
+    }
+
+    async fn aggregate_linked_properties(
+        &self,
+        _: &str,
+        _: &str,
+        _: &str,
+        _: AggregationType,
+    ) -> Result<Option<PropertyValue>, String> {
+        Err("pipelines aggregate the objects they traversed to".to_string())
+    }
+}
+
+#[tokio::test]
+async fn test_pipeline_traverses_filters_and_aggregates() {
+    let ontology = Ontology::from_yaml(ONTOLOGY).unwrap();
+    let function = ontology.get_function_type("region_payroll").unwrap();
+
+    let mut graph = Graph::default();
+    graph.link("region:east", "locatedIn", "acme");
+    graph.link("region:east", "locatedIn", "globex");
+    graph.link("region:west", "locatedIn", "initech");
+    graph.employee("e1", "acme", "active", 100);
+    graph.employee("e2", "acme", "inactive", 1_000);
+    graph.employee("e3", "globex", "active", 250);
+    graph.employee("e4", "initech", "active", 70);
+
+    let mut params = PropertyMap::new();
+    params.insert("region".to_string(), PropertyValue::ObjectReference("region:east".to_string()));
+    let result = FunctionExecutor::execute(function, &params, Some(&graph)).await.unwrap();
+    assert_eq!(result.value, PropertyValue::Integer(350));
+
+    // Without the aggregation, the pipeline returns the objects it ended on
+    let mut objects_only = function.clone();
+    if let FunctionLogic::Pipeline { steps } = &mut objects_only.logic {
+        steps.pop();
+    }
+    objects_only.return_type = FunctionReturnType::Array {
+        element_type: Box::new(FunctionReturnType::ObjectType { object_type: "employee".to_string() }),
+    };
+    let result = FunctionExecutor::execute(&objects_only, &params, Some(&graph)).await.unwrap();
+    assert_eq!(
+        result.value,
+        PropertyValue::Array(vec![
+            PropertyValue::ObjectReference("e1".to_string()),
+            PropertyValue::ObjectReference("e3".to_string()),
+        ])
+    );
+}
@@ -0,0 +1,124 @@
+use ontology_engine::{object_form_schema, FormSchemaOptions, Ontology, JSON_SCHEMA_DIALECT};
+use serde_json::json;
+
+fn fixture() -> Ontology {
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/form_ontology.yaml");
+    Ontology::from_yaml(&std::fs::read_to_string(path).unwrap()).unwrap()
+}
+
+#[test]
+fn test_object_type_json_schema() {
+    let ontology = fixture();
+    let schema = ontology.get_object_type("plant").unwrap().to_json_schema();
+
+    assert_eq!(schema["$schema"], json!(JSON_SCHEMA_DIALECT));
+    assert_eq!(schema["$id"], json!("plant.schema.json"));
+    assert_eq!(schema["type"], json!("object"));
+    assert_eq!(schema["required"], json!(["id", "name"]));
+    assert!(schema.get("additionalProperties").is_none());
+
+    let properties = &schema["properties"];
+    assert_eq!(properties["name"]["minLength"], json!(2));
+    assert_eq!(properties["name"]["maxLength"], json!(80));
+    assert_eq!(properties["code"]["pattern"], json!("^PL-[0-9]{3}$"));
+    assert_eq!(properties["code"]["description"], json!("Internal site code, e.g. PL-042"));
+    assert_eq!(properties["status"]["enum"], json!(["active", "idle", "closed"]));
+    assert_eq!(properties["status"]["default"], json!("active"));
+    assert_eq!(properties["capacity"]["maximum"], json!(10000.0));
+    assert_eq!(properties["opened_on"]["format"], json!("date"));
+    assert_eq!(
+        properties["operator"],
+        json!({ "type": "string", "format": "object-reference", "x-ref": "company.schema.json" })
+    );
+    assert_eq!(properties["tags"], json!({ "type": "array", "items": { "type": "string" }, "maxItems": 10 }));
+    assert_eq!(
+        properties["settings"],
+        json!({ "type": "object", "additionalProperties": { "type": "integer" } })
+    );
+
+    let address = &properties["address"];
+    assert_eq!(address["title"], json!("address"));
+    assert_eq!(address["required"], json!(["street"]));
+    assert_eq!(address["properties"]["contacts"]["items"]["required"], json!(["email"]));
+
+    assert_eq!(properties["risk_score"]["readOnly"], json!(true));
+    assert_eq!(properties["utilization"]["readOnly"], json!(true));
+    assert_eq!(properties["legacy_code"]["deprecated"], json!(true));
+}
+
+#[test]
+fn test_json_schema_unions_and_strict_types() {
+    let yaml = r#"
+ontology:
+  objectTypes:
+    - id: reading
+      displayName: Reading
+      primaryKey: id
+      strictProperties: true
+      properties:
+        - id: id
+          type: string
+          required: true
+        - id: value
+          type:
+            types: [integer, string]
+  linkTypes: []
+"#;
+    let ontology = Ontology::from_yaml(yaml).unwrap();
+    let schema = ontology.get_object_type("reading").unwrap().to_json_schema();
+
+    assert_eq!(schema["additionalProperties"], json!(false));
+    assert_eq!(
+        schema["properties"]["value"],
+        json!({ "anyOf": [{ "type": "integer" }, { "type": "string" }] })
+    );
+}
+
+#[test]
+fn test_json_schema_and_form_schema_type_values_alike() {
+    let yaml = r#"
+ontology:
+  objectTypes:
+    - id: site
+      displayName: Site
+      primaryKey: id
+      properties:
+        - id: id
+          type: string
+        - id: footprint
+          type: geojson
+        - id: surveyed_on
+          type: date
+        - id: areas
+          type:
+            elementType: double
+        - id: owner
+          type: object_reference
+        - id: readings
+          type:
+            types: [integer, geojson]
+  linkTypes: []
+"#;
+    let ontology = Ontology::from_yaml(yaml).unwrap();
+    let site = ontology.get_object_type("site").unwrap();
+    let json_schema = site.to_json_schema();
+    let form_schema = object_form_schema(site, &FormSchemaOptions::default());
+
+    assert_eq!(
+        json_schema["properties"]["footprint"],
+        json!({ "type": "string", "format": "geojson", "contentMediaType": "application/geo+json" })
+    );
+    // Forms add titles and layout on top; the keywords typing the value are the same
+    let typing = |schema: &serde_json::Value| {
+        let keywords = ["type", "format", "contentMediaType", "items", "additionalProperties", "anyOf"];
+        keywords.map(|keyword| schema.get(keyword).cloned())
+    };
+    for property in &site.properties {
+        assert_eq!(
+            typing(&json_schema["properties"][&property.id]),
+            typing(&form_schema["properties"][&property.id]),
+            "{}",
+            property.id
+        );
+    }
+}
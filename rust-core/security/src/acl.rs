@@ -0,0 +1,390 @@
+use crate::ols::{SecurityContext, SecurityError};
+use ontology_engine::{PropertyMap, PropertyValue};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Reserved system property holding an object's ACL entries
+pub const ACL_PROPERTY: &str = "_acl";
+
+/// Indexed field listing principals allowed to read the object ("*" when unrestricted)
+pub const ACL_READERS_FIELD: &str = "_acl_readers";
+
+/// Indexed field listing principals explicitly denied access to the object
+pub const ACL_DENIED_FIELD: &str = "_acl_denied";
+
+/// Wildcard principal indexed for objects without allow entries
+pub const ACL_PUBLIC_PRINCIPAL: &str = "*";
+
+/// Role that may manage ACLs on any object
+pub const MANAGE_ACL_ROLE: &str = "acl_admin";
+
+/// Permission granted (or denied) by an ACL entry
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AclPermission {
+    Read,
+    Write,
+    Manage,
+    Deny,
+}
+
+impl AclPermission {
+    pub fn from_str(s: &str) -> Result<Self, SecurityError> {
+        match s.to_lowercase().as_str() {
+            "read" => Ok(AclPermission::Read),
+            "write" => Ok(AclPermission::Write),
+            "manage" => Ok(AclPermission::Manage),
+            "deny" => Ok(AclPermission::Deny),
+            other => Err(SecurityError::InvalidContext(format!("Unknown ACL permission: {}", other))),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AclPermission::Read => "read",
+            AclPermission::Write => "write",
+            AclPermission::Manage => "manage",
+            AclPermission::Deny => "deny",
+        }
+    }
+}
+
+/// A single ACL entry: a principal ("user:alice", "role:analyst", ...) and its permission
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AclEntry {
+    pub principal: String,
+    pub permission: AclPermission,
+}
+
+impl AclEntry {
+    pub fn new(principal: String, permission: AclPermission) -> Self {
+        Self { principal, permission }
+    }
+}
+
+/// Outcome of evaluating an object ACL for a caller
+#[derive(Debug, Clone, PartialEq)]
+pub enum AclDecision {
+    /// The caller matches a deny entry
+    Deny,
+    /// The caller matches an allow entry
+    Allow,
+    /// The ACL lists allowed principals and the caller is not one of them
+    NotListed,
+    /// The ACL places no restriction on the caller
+    Unrestricted,
+}
+
+/// Per-object access control list
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ObjectAcl {
+    pub entries: Vec<AclEntry>,
+}
+
+impl ObjectAcl {
+    pub fn new(entries: Vec<AclEntry>) -> Self {
+        Self { entries }
+    }
+
+    /// Read the ACL from an object's reserved `_acl` property
+    pub fn from_properties(properties: &PropertyMap) -> Result<Option<Self>, SecurityError> {
+        match properties.get(ACL_PROPERTY) {
+            None | Some(PropertyValue::Null) => Ok(None),
+            Some(value) => Self::from_property_value(value).map(Some),
+        }
+    }
+
+    /// Parse an ACL from a property value (array of `{principal, permission}` entries)
+    pub fn from_property_value(value: &PropertyValue) -> Result<Self, SecurityError> {
+        let items = match value {
+            PropertyValue::Array(items) => items,
+            _ => return Err(SecurityError::InvalidContext(format!("'{}' must be an array of entries", ACL_PROPERTY))),
+        };
+
+        let mut entries = Vec::new();
+        for item in items {
+            let fields = match item {
+                PropertyValue::Map(fields) | PropertyValue::Object(fields) => fields,
+                _ => return Err(SecurityError::InvalidContext(format!("'{}' entries must be objects", ACL_PROPERTY))),
+            };
+            let principal = match fields.get("principal") {
+                Some(PropertyValue::String(p)) => p.clone(),
+                _ => return Err(SecurityError::InvalidContext("ACL entry is missing 'principal'".to_string())),
+            };
+            let permission = match fields.get("permission") {
+                Some(PropertyValue::String(p)) => AclPermission::from_str(p)?,
+                _ => return Err(SecurityError::InvalidContext("ACL entry is missing 'permission'".to_string())),
+            };
+            entries.push(AclEntry { principal, permission });
+        }
+
+        Ok(Self { entries })
+    }
+
+    /// Convert the ACL into the value stored in the `_acl` property
+    pub fn to_property_value(&self) -> PropertyValue {
+        PropertyValue::Array(
+            self.entries.iter()
+                .map(|entry| {
+                    let mut fields = HashMap::new();
+                    fields.insert("principal".to_string(), PropertyValue::String(entry.principal.clone()));
+                    fields.insert("permission".to_string(), PropertyValue::String(entry.permission.as_str().to_string()));
+                    PropertyValue::Map(fields)
+                })
+                .collect(),
+        )
+    }
+
+    /// Evaluate the ACL for a caller. Explicit deny always wins.
+    pub fn evaluate(&self, context: &SecurityContext) -> AclDecision {
+        let principals = context.principals();
+        let matches = |entry: &&AclEntry| principals.contains(&entry.principal);
+
+        if self.entries.iter().filter(matches).any(|e| e.permission == AclPermission::Deny) {
+            return AclDecision::Deny;
+        }
+        if self.entries.iter().filter(matches).any(|e| e.permission != AclPermission::Deny) {
+            return AclDecision::Allow;
+        }
+        if self.entries.iter().any(|e| e.permission != AclPermission::Deny) {
+            return AclDecision::NotListed;
+        }
+        AclDecision::Unrestricted
+    }
+
+    /// Check whether the caller may change this ACL
+    pub fn can_manage(&self, context: &SecurityContext) -> bool {
+        if context.has_role(MANAGE_ACL_ROLE) {
+            return true;
+        }
+        let principals = context.principals();
+        self.evaluate(context) != AclDecision::Deny
+            && self.entries.iter()
+                .any(|e| e.permission == AclPermission::Manage && principals.contains(&e.principal))
+    }
+
+    /// Check whether the caller may change the object itself. Once any entry grants `write`
+    /// or `manage`, only those principals may; otherwise anyone the ACL lets read it may.
+    /// ACL managers may always write.
+    pub fn can_write(&self, context: &SecurityContext) -> bool {
+        if self.can_manage(context) {
+            return true;
+        }
+        if matches!(self.evaluate(context), AclDecision::Deny | AclDecision::NotListed) {
+            return false;
+        }
+        let principals = context.principals();
+        let mut writers = self.entries.iter()
+            .filter(|e| matches!(e.permission, AclPermission::Write | AclPermission::Manage))
+            .peekable();
+        writers.peek().is_none() || writers.any(|e| principals.contains(&e.principal))
+    }
+
+    /// Principals that may read the object (the public wildcard when no allow entries exist)
+    pub fn readers(&self) -> Vec<String> {
+        let readers: Vec<String> = self.entries.iter()
+            .filter(|e| e.permission != AclPermission::Deny)
+            .map(|e| e.principal.clone())
+            .collect();
+        if readers.is_empty() {
+            vec![ACL_PUBLIC_PRINCIPAL.to_string()]
+        } else {
+            readers
+        }
+    }
+
+    /// Principals explicitly denied access to the object
+    pub fn denied(&self) -> Vec<String> {
+        self.entries.iter()
+            .filter(|e| e.permission == AclPermission::Deny)
+            .map(|e| e.principal.clone())
+            .collect()
+    }
+}
+
+/// Add the flattened ACL fields used to pre-filter searches to an object's properties
+pub fn with_acl_index_fields(properties: &PropertyMap) -> Result<PropertyMap, SecurityError> {
+    let acl = ObjectAcl::from_properties(properties)?.unwrap_or_default();
+    let to_array = |principals: Vec<String>| {
+        PropertyValue::Array(principals.into_iter().map(PropertyValue::String).collect())
+    };
+
+    let mut indexed = properties.clone();
+    indexed.insert(ACL_READERS_FIELD.to_string(), to_array(acl.readers()));
+    indexed.insert(ACL_DENIED_FIELD.to_string(), to_array(acl.denied()));
+    Ok(indexed)
+}
+
+/// Terms pre-filter over the indexed ACL fields for a caller:
+/// `_acl_readers` must contain one of `readers_any_of` and `_acl_denied` none of `denied_none_of`
+#[derive(Debug, Clone, PartialEq)]
+pub struct AclSearchFilter {
+    pub readers_any_of: Vec<String>,
+    pub denied_none_of: Vec<String>,
+}
+
+impl AclSearchFilter {
+    pub fn for_context(context: &SecurityContext) -> Self {
+        let principals = context.principals();
+        let mut readers_any_of = principals.clone();
+        readers_any_of.push(ACL_PUBLIC_PRINCIPAL.to_string());
+        Self {
+            readers_any_of,
+            denied_none_of: principals,
+        }
+    }
+
+    /// Evaluate the filter against indexed properties (as produced by `with_acl_index_fields`)
+    pub fn matches(&self, indexed: &PropertyMap) -> bool {
+        let field_values = |field: &str| -> Vec<String> {
+            match indexed.get(field) {
+                Some(PropertyValue::Array(items)) => items.iter()
+                    .filter_map(|v| match v {
+                        PropertyValue::String(s) => Some(s.clone()),
+                        _ => None,
+                    })
+                    .collect(),
+                _ => Vec::new(),
+            }
+        };
+
+        let readers = field_values(ACL_READERS_FIELD);
+        let denied = field_values(ACL_DENIED_FIELD);
+        (readers.is_empty() || readers.iter().any(|r| self.readers_any_of.contains(r)))
+            && !denied.iter().any(|d| self.denied_none_of.contains(d))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ols::{check_access, ObjectLevelSecurity, ObjectSecurityPolicy};
+
+    fn object_with_acl(entries: Vec<AclEntry>) -> PropertyMap {
+        let mut properties = PropertyMap::new();
+        properties.insert("classification".to_string(), PropertyValue::String("Secret".to_string()));
+        properties.insert(ACL_PROPERTY.to_string(), ObjectAcl::new(entries).to_property_value());
+        properties
+    }
+
+    #[test]
+    fn test_acl_round_trip() {
+        let acl = ObjectAcl::new(vec![
+            AclEntry::new("user:alice".to_string(), AclPermission::Read),
+            AclEntry::new("role:intern".to_string(), AclPermission::Deny),
+        ]);
+        let mut properties = PropertyMap::new();
+        properties.insert(ACL_PROPERTY.to_string(), acl.to_property_value());
+
+        assert_eq!(ObjectAcl::from_properties(&properties).unwrap(), Some(acl));
+    }
+
+    #[test]
+    fn test_allow_override_opens_restricted_object() {
+        let properties = object_with_acl(vec![
+            AclEntry::new("user:alice".to_string(), AclPermission::Read),
+        ]);
+        let alice = SecurityContext::new("alice".to_string());
+        let bob = SecurityContext::new("bob".to_string()).with_clearance("Secret".to_string());
+
+        // Without override, type-level clearance is still required
+        let policy = ObjectLevelSecurity::get_policy_for_object("case", &properties);
+        assert!(check_access(&alice, &policy).is_err());
+
+        // With override, the explicit allow opens the object
+        let policy = policy.with_acl_override(true);
+        assert!(check_access(&alice, &policy).is_ok());
+
+        // Callers not on the case team are restricted even with clearance
+        assert!(check_access(&bob, &policy).is_err());
+    }
+
+    #[test]
+    fn test_deny_override_wins() {
+        let properties = object_with_acl(vec![
+            AclEntry::new("role:analyst".to_string(), AclPermission::Read),
+            AclEntry::new("user:mallory".to_string(), AclPermission::Deny),
+        ]);
+        let policy = ObjectLevelSecurity::get_policy_for_object("case", &properties)
+            .with_acl_override(true);
+
+        let mallory = SecurityContext::new("mallory".to_string())
+            .with_role("analyst".to_string())
+            .with_clearance("Secret".to_string());
+        assert!(check_access(&mallory, &policy).is_err());
+
+        let carol = SecurityContext::new("carol".to_string()).with_role("analyst".to_string());
+        assert!(check_access(&carol, &policy).is_ok());
+    }
+
+    #[test]
+    fn test_can_manage() {
+        let acl = ObjectAcl::new(vec![
+            AclEntry::new("user:lead".to_string(), AclPermission::Manage),
+        ]);
+        assert!(acl.can_manage(&SecurityContext::new("lead".to_string())));
+        assert!(!acl.can_manage(&SecurityContext::new("other".to_string())));
+        assert!(acl.can_manage(&SecurityContext::new("other".to_string()).with_role(MANAGE_ACL_ROLE.to_string())));
+    }
+
+    #[test]
+    fn test_can_write() {
+        let alice = SecurityContext::new("alice".to_string());
+        let bob = SecurityContext::new("bob".to_string()).with_role("analyst".to_string());
+        assert!(ObjectAcl::default().can_write(&alice));
+
+        // Readers may write until some entry grants write access
+        let readers = ObjectAcl::new(vec![
+            AclEntry::new("user:alice".to_string(), AclPermission::Read),
+            AclEntry::new("role:analyst".to_string(), AclPermission::Read),
+        ]);
+        assert!(readers.can_write(&alice));
+        assert!(!readers.can_write(&SecurityContext::new("carol".to_string())));
+
+        let writers = ObjectAcl::new(vec![
+            AclEntry::new("user:alice".to_string(), AclPermission::Read),
+            AclEntry::new("role:analyst".to_string(), AclPermission::Write),
+        ]);
+        assert!(!writers.can_write(&alice));
+        assert!(writers.can_write(&bob));
+        assert!(writers.can_write(&alice.clone().with_role(MANAGE_ACL_ROLE.to_string())));
+
+        let denied = ObjectAcl::new(vec![
+            AclEntry::new("role:analyst".to_string(), AclPermission::Write),
+            AclEntry::new("user:bob".to_string(), AclPermission::Deny),
+        ]);
+        assert!(!denied.can_write(&bob));
+    }
+
+    #[test]
+    fn test_search_filter_matches_acl_evaluation() {
+        let objects = vec![
+            PropertyMap::new(),
+            object_with_acl(vec![AclEntry::new("user:alice".to_string(), AclPermission::Read)]),
+            object_with_acl(vec![AclEntry::new("role:analyst".to_string(), AclPermission::Write)]),
+            object_with_acl(vec![AclEntry::new("user:alice".to_string(), AclPermission::Deny)]),
+            object_with_acl(vec![
+                AclEntry::new("role:analyst".to_string(), AclPermission::Read),
+                AclEntry::new("user:alice".to_string(), AclPermission::Deny),
+            ]),
+        ];
+        let callers = vec![
+            SecurityContext::new("alice".to_string()),
+            SecurityContext::new("alice".to_string()).with_role("analyst".to_string()),
+            SecurityContext::new("bob".to_string()).with_role("analyst".to_string()),
+            SecurityContext::new("eve".to_string()),
+        ];
+
+        for caller in &callers {
+            let filter = AclSearchFilter::for_context(caller);
+            for properties in &objects {
+                let indexed = with_acl_index_fields(properties).unwrap();
+                let visible = match ObjectAcl::from_properties(properties).unwrap() {
+                    Some(acl) => matches!(acl.evaluate(caller), AclDecision::Allow | AclDecision::Unrestricted),
+                    None => true,
+                };
+                assert_eq!(filter.matches(&indexed), visible, "caller {:?}, object {:?}", caller.user_id, properties);
+            }
+        }
+    }
+}
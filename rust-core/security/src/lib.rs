@@ -1,11 +1,17 @@
 pub mod ols;
 pub mod sharing;
+pub mod acl;
+pub mod masking;
+pub mod property_access;
 
 pub use ols::{ObjectLevelSecurity, SecurityContext, SecurityError, check_access};
 pub use sharing::{
     SharingRule, SharingRuleStore, SharingPermission, SharingError,
     InMemorySharingStore, check_sharing_access,
 };
+pub use acl::{AclEntry, AclPermission, AclDecision, AclSearchFilter, ObjectAcl};
+pub use masking::{MaskingPolicy, MaskingProfile, MaskingRule, MaskingStrategy};
+pub use property_access::{PropertyAccessPolicy, PropertyAccessRule, PropertyRedaction};
 
 
 
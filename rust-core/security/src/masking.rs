@@ -0,0 +1,353 @@
+use crate::ols::SecurityContext;
+use hmac::{Hmac, Mac};
+use ontology_engine::{ObjectType, Property, PropertyMap, PropertyValue};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::collections::BTreeMap;
+
+/// Sensitivity tag implied by a property's `pii` flag
+pub const PII_TAG: &str = "pii";
+
+/// Role wildcard: a rule for this role applies to callers with no role-specific rule
+pub const ANY_ROLE: &str = "*";
+
+/// Replacement value for redacted properties
+pub const REDACTED_VALUE: &str = "[REDACTED]";
+
+/// How a sensitive property value is shown to a caller.
+/// Variants are ordered from least to most restrictive.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "strategy")]
+pub enum MaskingStrategy {
+    /// Value is shown unchanged
+    Reveal,
+    /// Only the last `keep_last` characters of string values are shown ("***-**-1234")
+    PartialReveal {
+        #[serde(rename = "keepLast")]
+        keep_last: usize,
+    },
+    /// Value is replaced by a keyed hash, stable per value so joins still work
+    Hash,
+    /// Value is replaced by a fixed placeholder
+    Redact,
+    /// Property is removed entirely
+    Hide,
+}
+
+impl MaskingStrategy {
+    /// Rank used for precedence; higher is more restrictive
+    fn restrictiveness(&self) -> (u8, usize) {
+        match self {
+            MaskingStrategy::Reveal => (0, 0),
+            // Revealing fewer characters is more restrictive
+            MaskingStrategy::PartialReveal { keep_last } => (1, usize::MAX - keep_last),
+            MaskingStrategy::Hash => (2, 0),
+            MaskingStrategy::Redact => (3, 0),
+            MaskingStrategy::Hide => (4, 0),
+        }
+    }
+
+    fn most_restrictive(a: MaskingStrategy, b: MaskingStrategy) -> MaskingStrategy {
+        if b.restrictiveness() > a.restrictiveness() { b } else { a }
+    }
+
+    fn least_restrictive(a: MaskingStrategy, b: MaskingStrategy) -> MaskingStrategy {
+        if b.restrictiveness() < a.restrictiveness() { b } else { a }
+    }
+}
+
+/// Maps a (role, sensitivity tag) pair to a masking strategy
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MaskingRule {
+    pub role: String,
+    pub tag: String,
+    #[serde(flatten)]
+    pub strategy: MaskingStrategy,
+}
+
+/// Masking configuration: the rule table plus the server key used by `Hash`
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct MaskingPolicy {
+    #[serde(default)]
+    pub rules: Vec<MaskingRule>,
+
+    #[serde(skip)]
+    hash_key: Vec<u8>,
+}
+
+/// Strategies resolved for one caller, keyed by sensitivity tag
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MaskingProfile {
+    /// Roles whose rules contributed to this profile, or "default" if only wildcard rules applied
+    pub name: String,
+    pub strategies: BTreeMap<String, MaskingStrategy>,
+}
+
+impl MaskingPolicy {
+    pub fn new(hash_key: impl Into<Vec<u8>>) -> Self {
+        Self {
+            rules: Vec::new(),
+            hash_key: hash_key.into(),
+        }
+    }
+
+    pub fn with_hash_key(mut self, hash_key: impl Into<Vec<u8>>) -> Self {
+        self.hash_key = hash_key.into();
+        self
+    }
+
+    pub fn with_rule(mut self, role: &str, tag: &str, strategy: MaskingStrategy) -> Self {
+        self.rules.push(MaskingRule {
+            role: role.to_string(),
+            tag: tag.to_string(),
+            strategy,
+        });
+        self
+    }
+
+    /// Check the policy can be applied: hashed values are only as secret as the key, so
+    /// `Hash` rules need a non-empty one
+    pub fn validate(&self) -> Result<(), String> {
+        if self.hash_key.is_empty() && self.rules.iter().any(|rule| rule.strategy == MaskingStrategy::Hash) {
+            return Err("Masking rules hash values but no hash key is set".to_string());
+        }
+        Ok(())
+    }
+
+    /// Resolve the profile for a caller. For each tag, the least restrictive strategy among
+    /// the caller's roles wins; wildcard rules only apply when none of the caller's roles has one.
+    pub fn profile_for(&self, context: &SecurityContext) -> MaskingProfile {
+        let mut role_strategies: BTreeMap<String, MaskingStrategy> = BTreeMap::new();
+        let mut wildcard_strategies: BTreeMap<String, MaskingStrategy> = BTreeMap::new();
+        let mut contributing_roles: Vec<String> = Vec::new();
+
+        for rule in &self.rules {
+            let target = if rule.role == ANY_ROLE {
+                &mut wildcard_strategies
+            } else if context.has_role(&rule.role) {
+                if !contributing_roles.contains(&rule.role) {
+                    contributing_roles.push(rule.role.clone());
+                }
+                &mut role_strategies
+            } else {
+                continue;
+            };
+            let strategy = match target.remove(&rule.tag) {
+                Some(existing) => MaskingStrategy::least_restrictive(existing, rule.strategy.clone()),
+                None => rule.strategy.clone(),
+            };
+            target.insert(rule.tag.clone(), strategy);
+        }
+
+        for (tag, strategy) in wildcard_strategies {
+            role_strategies.entry(tag).or_insert(strategy);
+        }
+
+        contributing_roles.sort();
+        MaskingProfile {
+            name: if contributing_roles.is_empty() {
+                "default".to_string()
+            } else {
+                contributing_roles.join("+")
+            },
+            strategies: role_strategies,
+        }
+    }
+
+    /// Apply a profile to an object's properties. Properties without a matching tag are unchanged.
+    pub fn mask_properties(
+        &self,
+        profile: &MaskingProfile,
+        object_type: &ObjectType,
+        properties: &PropertyMap,
+    ) -> PropertyMap {
+        let mut masked = PropertyMap::new();
+        for (key, value) in properties.iter() {
+            let strategy = object_type.get_property(key)
+                .and_then(|property| profile.strategy_for(property));
+            match strategy {
+                Some(strategy) => {
+                    if let Some(value) = self.mask_value(&strategy, value) {
+                        masked.insert(key.clone(), value);
+                    }
+                }
+                None => masked.insert(key.clone(), value.clone()),
+            }
+        }
+        masked
+    }
+
+    /// Mask a single value; `None` means the property should be hidden
+    pub fn mask_value(&self, strategy: &MaskingStrategy, value: &PropertyValue) -> Option<PropertyValue> {
+        match strategy {
+            MaskingStrategy::Reveal => Some(value.clone()),
+            MaskingStrategy::PartialReveal { keep_last } => match value {
+                PropertyValue::String(s) => Some(PropertyValue::String(partial_reveal(s, *keep_last))),
+                // Partial reveal is only meaningful for strings; anything else is redacted
+                PropertyValue::Null => Some(PropertyValue::Null),
+                _ => Some(PropertyValue::String(REDACTED_VALUE.to_string())),
+            },
+            MaskingStrategy::Hash => match value {
+                PropertyValue::Null => Some(PropertyValue::Null),
+                _ => Some(PropertyValue::String(self.hash_value(value))),
+            },
+            MaskingStrategy::Redact => Some(PropertyValue::String(REDACTED_VALUE.to_string())),
+            MaskingStrategy::Hide => None,
+        }
+    }
+
+    /// HMAC-SHA256 of the value under the server key, hex encoded
+    fn hash_value(&self, value: &PropertyValue) -> String {
+        let canonical = match value {
+            PropertyValue::String(s) => s.clone(),
+            other => serde_json::to_string(other).unwrap_or_default(),
+        };
+        let mut mac = Hmac::<Sha256>::new_from_slice(&self.hash_key)
+            .expect("HMAC accepts keys of any length");
+        mac.update(canonical.as_bytes());
+        format!("{:x}", mac.finalize().into_bytes())
+    }
+}
+
+impl std::fmt::Debug for MaskingPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Never print the hash key
+        f.debug_struct("MaskingPolicy")
+            .field("rules", &self.rules)
+            .finish_non_exhaustive()
+    }
+}
+
+impl MaskingProfile {
+    /// Strategy for a property; when several of its tags apply, the most restrictive wins
+    pub fn strategy_for(&self, property: &Property) -> Option<MaskingStrategy> {
+        property_tags(property).into_iter()
+            .filter_map(|tag| self.strategies.get(tag).cloned())
+            .reduce(MaskingStrategy::most_restrictive)
+    }
+}
+
+/// A property's sensitivity tags, plus `pii` if it is flagged as PII
+pub(crate) fn property_tags(property: &Property) -> Vec<&str> {
+    let mut tags: Vec<&str> = property.sensitivity_tags.iter().map(String::as_str).collect();
+    if property.pii {
+        tags.push(PII_TAG);
+    }
+    tags
+}
+
+/// Replace all but the last `keep_last` characters with `*`, keeping separators in place
+fn partial_reveal(value: &str, keep_last: usize) -> String {
+    let chars: Vec<char> = value.chars().collect();
+    let reveal_from = chars.len().saturating_sub(keep_last);
+    chars.iter()
+        .enumerate()
+        .map(|(i, c)| if i >= reveal_from || !c.is_alphanumeric() { *c } else { '*' })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn property(id: &str, tags: &[&str], pii: bool) -> Property {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "type": "string",
+            "sensitivityTags": tags,
+            "pii": pii,
+        }))
+        .unwrap()
+    }
+
+    fn policy() -> MaskingPolicy {
+        MaskingPolicy::new("server-key")
+            .with_rule(ANY_ROLE, "ssn", MaskingStrategy::Redact)
+            .with_rule("analyst", "ssn", MaskingStrategy::PartialReveal { keep_last: 4 })
+            .with_rule("auditor", "ssn", MaskingStrategy::Reveal)
+            .with_rule("analyst", "contact", MaskingStrategy::Hash)
+            .with_rule(ANY_ROLE, "internal", MaskingStrategy::Hide)
+    }
+
+    #[test]
+    fn test_partial_reveal_keeps_separators() {
+        let policy = policy();
+        let ssn = PropertyValue::String("123-45-6789".to_string());
+        let masked = policy.mask_value(&MaskingStrategy::PartialReveal { keep_last: 4 }, &ssn);
+        assert_eq!(masked, Some(PropertyValue::String("***-**-6789".to_string())));
+
+        // Non-string values are redacted rather than partially revealed
+        let masked = policy.mask_value(&MaskingStrategy::PartialReveal { keep_last: 2 }, &PropertyValue::Integer(123456));
+        assert_eq!(masked, Some(PropertyValue::String(REDACTED_VALUE.to_string())));
+    }
+
+    #[test]
+    fn test_hash_is_stable_and_keyed() {
+        let policy = policy();
+        let email = PropertyValue::String("a@example.com".to_string());
+        let first = policy.mask_value(&MaskingStrategy::Hash, &email);
+        let second = policy.mask_value(&MaskingStrategy::Hash, &email);
+        assert_eq!(first, second);
+        assert_ne!(first, Some(email.clone()));
+
+        let other_key = MaskingPolicy::new("another-key");
+        assert_ne!(other_key.mask_value(&MaskingStrategy::Hash, &email), first);
+
+        // Hash rules without a key are rejected
+        assert!(policy.validate().is_ok());
+        assert!(policy.clone().with_hash_key("").validate().is_err());
+        let unhashed = MaskingPolicy::default().with_rule(ANY_ROLE, "ssn", MaskingStrategy::Redact);
+        assert!(unhashed.validate().is_ok());
+    }
+
+    #[test]
+    fn test_redact_and_hide() {
+        let policy = policy();
+        let value = PropertyValue::String("secret".to_string());
+        assert_eq!(
+            policy.mask_value(&MaskingStrategy::Redact, &value),
+            Some(PropertyValue::String(REDACTED_VALUE.to_string()))
+        );
+        assert_eq!(policy.mask_value(&MaskingStrategy::Hide, &value), None);
+        assert_eq!(policy.mask_value(&MaskingStrategy::Reveal, &value), Some(value));
+    }
+
+    #[test]
+    fn test_profile_role_precedence() {
+        let policy = policy();
+        let ssn = property("ssn", &["ssn"], false);
+
+        let analyst = SecurityContext::new("a".to_string()).with_role("analyst".to_string());
+        let profile = policy.profile_for(&analyst);
+        assert_eq!(profile.name, "analyst");
+        assert_eq!(profile.strategy_for(&ssn), Some(MaskingStrategy::PartialReveal { keep_last: 4 }));
+
+        // Auditor rule is the least restrictive of the caller's roles
+        let both = analyst.clone().with_role("auditor".to_string());
+        assert_eq!(policy.profile_for(&both).strategy_for(&ssn), Some(MaskingStrategy::Reveal));
+
+        // Callers without a role-specific rule fall back to the wildcard rule
+        let guest = SecurityContext::new("g".to_string());
+        let profile = policy.profile_for(&guest);
+        assert_eq!(profile.name, "default");
+        assert_eq!(profile.strategy_for(&ssn), Some(MaskingStrategy::Redact));
+    }
+
+    #[test]
+    fn test_most_restrictive_tag_wins() {
+        let policy = policy().with_rule(ANY_ROLE, PII_TAG, MaskingStrategy::Redact);
+        let analyst = SecurityContext::new("a".to_string()).with_role("analyst".to_string());
+        let profile = policy.profile_for(&analyst);
+
+        // Hash (contact) vs Redact (pii): Redact is more restrictive
+        let email = property("email", &["contact"], true);
+        assert_eq!(profile.strategy_for(&email), Some(MaskingStrategy::Redact));
+
+        // PartialReveal (ssn) vs Hide (internal): Hide wins
+        let ssn = property("ssn", &["ssn", "internal"], false);
+        assert_eq!(profile.strategy_for(&ssn), Some(MaskingStrategy::Hide));
+
+        // Untagged properties are unmasked
+        assert_eq!(profile.strategy_for(&property("name", &[], false)), None);
+    }
+}
@@ -1,3 +1,4 @@
+use crate::acl::{AclDecision, ObjectAcl, MANAGE_ACL_ROLE};
 use ontology_engine::{PropertyMap, PropertyValue};
 use std::collections::HashSet;
 
@@ -49,6 +50,14 @@ impl SecurityContext {
     pub fn has_clearance(&self, clearance: &str) -> bool {
         self.clearances.contains(clearance)
     }
+    
+    /// Principals this context acts as, in the form used by object ACLs
+    pub fn principals(&self) -> Vec<String> {
+        let mut principals = vec![format!("user:{}", self.user_id)];
+        principals.extend(self.roles.iter().map(|r| format!("role:{}", r)));
+        principals.extend(self.badges.iter().map(|b| format!("badge:{}", b)));
+        principals
+    }
 }
 
 /// Security requirements for an object
@@ -58,6 +67,10 @@ pub struct ObjectSecurityPolicy {
     pub required_badges: HashSet<String>,
     pub required_clearances: HashSet<String>,
     pub property_level_access: Option<PropertyAccessControl>,
+    /// Object-level ACL, evaluated before type-level rules
+    pub object_acl: Option<ObjectAcl>,
+    /// Whether an explicit ACL allow bypasses the type-level rules
+    pub acl_overrides_type_rules: bool,
 }
 
 /// Property-level access control
@@ -74,6 +87,8 @@ impl ObjectSecurityPolicy {
             required_badges: HashSet::new(),
             required_clearances: HashSet::new(),
             property_level_access: None,
+            object_acl: None,
+            acl_overrides_type_rules: false,
         }
     }
     
@@ -96,6 +111,16 @@ impl ObjectSecurityPolicy {
         self.property_level_access = Some(pac);
         self
     }
+    
+    pub fn with_object_acl(mut self, acl: ObjectAcl) -> Self {
+        self.object_acl = Some(acl);
+        self
+    }
+    
+    pub fn with_acl_override(mut self, overrides: bool) -> Self {
+        self.acl_overrides_type_rules = overrides;
+        self
+    }
 }
 
 /// Check if a user has access to an object
@@ -103,6 +128,26 @@ pub fn check_access(
     context: &SecurityContext,
     policy: &ObjectSecurityPolicy,
 ) -> Result<(), SecurityError> {
+    // Object-level ACL is evaluated first: explicit deny always wins
+    if let Some(ref acl) = policy.object_acl {
+        match acl.evaluate(context) {
+            AclDecision::Deny => {
+                return Err(SecurityError::AccessDenied(format!(
+                    "User '{}' is explicitly denied by the object ACL",
+                    context.user_id
+                )));
+            }
+            AclDecision::NotListed => {
+                return Err(SecurityError::AccessDenied(format!(
+                    "Object is restricted to principals listed in its ACL; user '{}' is not listed",
+                    context.user_id
+                )));
+            }
+            AclDecision::Allow if policy.acl_overrides_type_rules => return Ok(()),
+            AclDecision::Allow | AclDecision::Unrestricted => {}
+        }
+    }
+    
     // Check roles
     if !policy.required_roles.is_empty() {
         let has_role = policy.required_roles.iter()
@@ -205,6 +250,13 @@ impl ObjectLevelSecurity {
             }
         }
         
+        // Attach the object's ACL; a malformed ACL fails closed to ACL admins only
+        match ObjectAcl::from_properties(object_properties) {
+            Ok(Some(acl)) => policy = policy.with_object_acl(acl),
+            Ok(None) => {}
+            Err(_) => policy = policy.with_required_role(MANAGE_ACL_ROLE.to_string()),
+        }
+        
         policy
     }
 }
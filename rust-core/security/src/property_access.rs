@@ -0,0 +1,224 @@
+use crate::masking::property_tags;
+use crate::ols::SecurityContext;
+use ontology_engine::{ObjectType, Property, PropertyMap, PropertyValue};
+use serde::{Deserialize, Serialize};
+
+/// Placeholder shown for property values a caller is not cleared for, unless configured
+pub const DEFAULT_REDACTION_PLACEHOLDER: &str = "***";
+
+/// What a caller sees of a property they may not read
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "mode")]
+pub enum PropertyRedaction {
+    /// Value is replaced by the placeholder
+    Mask {
+        #[serde(default = "default_placeholder")]
+        placeholder: String,
+    },
+    /// Property is removed entirely
+    Remove,
+}
+
+fn default_placeholder() -> String {
+    DEFAULT_REDACTION_PLACEHOLDER.to_string()
+}
+
+impl Default for PropertyRedaction {
+    fn default() -> Self {
+        PropertyRedaction::Mask { placeholder: default_placeholder() }
+    }
+}
+
+/// Clears callers with one of `roles` or `clearances` to read properties tagged `tag`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PropertyAccessRule {
+    pub tag: String,
+    #[serde(default)]
+    pub roles: Vec<String>,
+    #[serde(default)]
+    pub clearances: Vec<String>,
+}
+
+impl PropertyAccessRule {
+    fn admits(&self, context: &SecurityContext) -> bool {
+        self.roles.iter().any(|role| context.has_role(role))
+            || self.clearances.iter().any(|clearance| context.has_clearance(clearance))
+    }
+}
+
+/// Which sensitivity tags a caller must be cleared for to read a property at all. A tag
+/// with rules is restricted to the callers one of its rules admits; tags without rules are
+/// open to everyone. A property is readable only if the caller is cleared for each of its
+/// tags, including `pii` for properties flagged as PII. Unlike `MaskingPolicy`, which
+/// decides how a readable value is shown, this decides whether it is shown.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PropertyAccessPolicy {
+    #[serde(default)]
+    pub rules: Vec<PropertyAccessRule>,
+    #[serde(default)]
+    pub redaction: PropertyRedaction,
+}
+
+impl PropertyAccessPolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Clear callers with `role` for properties tagged `tag`
+    pub fn allow_role(mut self, tag: &str, role: &str) -> Self {
+        self.rule(tag).roles.push(role.to_string());
+        self
+    }
+
+    /// Clear callers with `clearance` for properties tagged `tag`
+    pub fn allow_clearance(mut self, tag: &str, clearance: &str) -> Self {
+        self.rule(tag).clearances.push(clearance.to_string());
+        self
+    }
+
+    pub fn with_redaction(mut self, redaction: PropertyRedaction) -> Self {
+        self.redaction = redaction;
+        self
+    }
+
+    fn rule(&mut self, tag: &str) -> &mut PropertyAccessRule {
+        let index = match self.rules.iter().position(|rule| rule.tag == tag) {
+            Some(index) => index,
+            None => {
+                self.rules.push(PropertyAccessRule { tag: tag.to_string(), roles: Vec::new(), clearances: Vec::new() });
+                self.rules.len() - 1
+            }
+        };
+        &mut self.rules[index]
+    }
+
+    /// Whether the caller may read `property`; `None` is a caller with no roles or clearances
+    pub fn can_read(&self, context: Option<&SecurityContext>, property: &Property) -> bool {
+        property_tags(property).into_iter().all(|tag| {
+            let mut rules = self.rules.iter().filter(|rule| rule.tag == tag).peekable();
+            rules.peek().is_none() || context.is_some_and(|context| rules.any(|rule| rule.admits(context)))
+        })
+    }
+
+    /// Fail if the caller may not read the property `property_id` of `object_type`, e.g.
+    /// before filtering on it. Properties the type does not declare pass.
+    pub fn check_readable(
+        &self,
+        context: Option<&SecurityContext>,
+        object_type: &ObjectType,
+        property_id: &str,
+    ) -> Result<(), String> {
+        match object_type.get_property(property_id) {
+            Some(property) if !self.can_read(context, property) => Err(format!(
+                "Property '{}' of '{}' is not readable by the caller",
+                property_id, object_type.id
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// What replaces an unreadable value; `None` means the property is removed
+    pub fn redacted_value(&self) -> Option<PropertyValue> {
+        match &self.redaction {
+            PropertyRedaction::Mask { placeholder } => Some(PropertyValue::String(placeholder.clone())),
+            PropertyRedaction::Remove => None,
+        }
+    }
+
+    /// Redact the properties the caller may not read. Undeclared properties are unchanged.
+    pub fn redact_properties(
+        &self,
+        context: Option<&SecurityContext>,
+        object_type: &ObjectType,
+        properties: &PropertyMap,
+    ) -> PropertyMap {
+        let mut redacted = PropertyMap::new();
+        for (key, value) in properties.iter() {
+            let readable = object_type
+                .get_property(key)
+                .is_none_or(|property| self.can_read(context, property));
+            if readable {
+                redacted.insert(key.clone(), value.clone());
+            } else if let Some(value) = self.redacted_value() {
+                redacted.insert(key.clone(), value);
+            }
+        }
+        redacted
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn property(id: &str, tags: &[&str], pii: bool) -> Property {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "type": "string",
+            "sensitivityTags": tags,
+            "pii": pii,
+        }))
+        .unwrap()
+    }
+
+    fn policy() -> PropertyAccessPolicy {
+        PropertyAccessPolicy::new()
+            .allow_role("pii", "admin")
+            .allow_clearance("pii", "pii-reader")
+            .allow_role("finance", "admin")
+    }
+
+    #[test]
+    fn test_tags_require_a_clearing_role_or_clearance() {
+        let policy = policy();
+        let email = property("email", &[], true);
+        let name = property("name", &["public"], false);
+
+        let user = SecurityContext::new("u".to_string()).with_role("analyst".to_string());
+        assert!(!policy.can_read(Some(&user), &email));
+        assert!(policy.can_read(Some(&user), &name));
+        assert!(!policy.can_read(None, &email));
+        assert!(policy.can_read(None, &name));
+
+        let admin = SecurityContext::new("a".to_string()).with_role("admin".to_string());
+        assert!(policy.can_read(Some(&admin), &email));
+        let reader = SecurityContext::new("r".to_string()).with_clearance("pii-reader".to_string());
+        assert!(policy.can_read(Some(&reader), &email));
+
+        // Every restricted tag must be cleared
+        let salary = property("salary", &["finance"], true);
+        assert!(policy.can_read(Some(&admin), &salary));
+        assert!(!policy.can_read(Some(&reader), &salary));
+    }
+
+    #[test]
+    fn test_redaction_masks_or_removes() {
+        let object_type: ObjectType = serde_json::from_value(serde_json::json!({
+            "id": "person",
+            "displayName": "Person",
+            "primaryKey": "id",
+            "properties": [
+                { "id": "id", "type": "string" },
+                { "id": "email", "type": "string", "pii": true },
+            ],
+        }))
+        .unwrap();
+        let mut properties = PropertyMap::new();
+        properties.insert("id".to_string(), PropertyValue::String("p1".to_string()));
+        properties.insert("email".to_string(), PropertyValue::String("p@example.com".to_string()));
+        let user = SecurityContext::new("u".to_string());
+
+        let masked = policy().redact_properties(Some(&user), &object_type, &properties);
+        assert_eq!(masked.get("email"), Some(&PropertyValue::String("***".to_string())));
+        assert_eq!(masked.get("id"), properties.get("id"));
+
+        let removed = policy()
+            .with_redaction(PropertyRedaction::Remove)
+            .redact_properties(Some(&user), &object_type, &properties);
+        assert!(!removed.contains_key("email"));
+
+        let err = policy().check_readable(Some(&user), &object_type, "email").unwrap_err();
+        assert_eq!(err, "Property 'email' of 'person' is not readable by the caller");
+        assert!(policy().check_readable(Some(&user), &object_type, "id").is_ok());
+    }
+}
@@ -0,0 +1,38 @@
+//! Event log compaction CLI
+//!
+//! Usage:
+//!   event-log-compact <event_log_dir> <days>
+//!
+//! Rewrites the segments of days more than `<days>` ago into one snapshot per object, which
+//! bounds how many events reconstructing an object replays. Intermediate states within the
+//! compacted days are lost. Run it while no server is appending to the log.
+
+use versioning::FileEventStore;
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let (dir, days) = match args.as_slice() {
+        [dir, days] => match days.parse::<u32>() {
+            Ok(days) => (dir, days),
+            Err(_) => usage(),
+        },
+        _ => usage(),
+    };
+
+    let result = FileEventStore::open(dir).and_then(|mut store| store.compact(days));
+    match result {
+        Ok(report) => println!(
+            "Compacted {} segment(s): {} event(s) -> {}",
+            report.segments_compacted, report.events_before, report.events_after
+        ),
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+fn usage() -> ! {
+    eprintln!("Usage:\n  event-log-compact <event_log_dir> <days>");
+    std::process::exit(2);
+}
@@ -0,0 +1,44 @@
+//! Event log restore CLI
+//!
+//! Usage:
+//!   event-log-restore <backup_root> <backup_id> <event_log_dir>
+//!
+//! Replaces the file-backed event log in `<event_log_dir>` with the `event_log` component
+//! of a backup, after verifying its checksum. The current segments are first copied to a
+//! `.pre-restore` directory beside it. Run it while no server is appending to the log.
+
+use ontology_engine::backup::{read_component_backup, BackupComponent};
+use std::path::Path;
+use versioning::FileEventStore;
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let [root, backup_id, dir] = args.as_slice() else {
+        eprintln!("Usage:\n  event-log-restore <backup_root> <backup_id> <event_log_dir>");
+        std::process::exit(2);
+    };
+
+    if let Err(e) = restore(Path::new(root), backup_id, Path::new(dir)) {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    }
+}
+
+fn restore(root: &Path, backup_id: &str, dir: &Path) -> anyhow::Result<()> {
+    let data = read_component_backup(root, backup_id, "event_log")?;
+    let mut store = FileEventStore::open(dir)?;
+
+    let safety = dir.with_extension("pre-restore");
+    std::fs::create_dir_all(&safety)?;
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if let Some(name) = path.file_name().filter(|_| path.is_file()) {
+            std::fs::copy(&path, safety.join(name))?;
+        }
+    }
+
+    let runtime = tokio::runtime::Builder::new_current_thread().build()?;
+    runtime.block_on(store.import_from_backup(&data))?;
+    println!("Restored the event log in {} from backup '{}'", dir.display(), backup_id);
+    Ok(())
+}
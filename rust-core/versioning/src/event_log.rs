@@ -1,16 +1,22 @@
 use ontology_engine::PropertyMap;
+use ontology_engine::backup::{BackupComponent, BackupError};
+use serde::{Deserialize, Serialize};
 use chrono::{DateTime, Utc};
 use uuid::Uuid;
 use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Receives every event recorded in an `EventLog`, e.g. to keep it in a persistent store
+pub type EventSink = Arc<dyn Fn(&ObjectEvent) + Send + Sync>;
 
 /// Event log for tracking all changes to objects (event sourcing)
 pub struct EventLog {
     events: Vec<ObjectEvent>,
-    // In production, this would be a persistent store (database, event stream, etc.)
+    sink: Option<EventSink>,
 }
 
 /// Event types that can occur on objects
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum EventType {
     ObjectCreated {
         object_type: String,
@@ -33,10 +39,37 @@ pub enum EventType {
         old_value: Option<ontology_engine::PropertyValue>,
         new_value: ontology_engine::PropertyValue,
     },
+    /// Duplicates merged into a surviving object, which now holds `merged_properties`;
+    /// the losers are soft-deleted and redirect to the winner
+    ObjectsMerged {
+        object_type: String,
+        winner_id: String,
+        loser_ids: Vec<String>,
+        merged_properties: PropertyMap,
+    },
+    /// Links are immutable: a change to a link's properties is recorded as its deletion
+    /// and the creation of a replacement, so there is no update event
+    LinkCreated {
+        link_type: String,
+        link_id: String,
+        source_id: String,
+        target_id: String,
+        properties: PropertyMap,
+    },
+    /// Endpoints are repeated so the link's history can be read without its creation event
+    LinkDeleted {
+        link_type: String,
+        link_id: String,
+        source_id: String,
+        target_id: String,
+    },
 }
 
-/// An event in the log
-#[derive(Debug, Clone)]
+/// An event in the log. Events are bitemporal: `timestamp` is transaction time, when the
+/// event was recorded, and `valid_from`/`valid_to` is valid time, when the fact it records
+/// was true. A correction that arrives years later has a late `timestamp` but an early
+/// `valid_from`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ObjectEvent {
     pub event_id: String,
     pub event_type: EventType,
@@ -46,15 +79,49 @@ pub struct ObjectEvent {
     pub valid_to: Option<DateTime<Utc>>, // None means still valid
 }
 
+impl ObjectEvent {
+    /// An event recorded now about a fact true from `valid_from` until `valid_to`
+    pub fn new(
+        event_type: EventType,
+        valid_from: DateTime<Utc>,
+        valid_to: Option<DateTime<Utc>>,
+        user_id: Option<String>,
+    ) -> Self {
+        Self {
+            event_id: Uuid::new_v4().to_string(),
+            event_type,
+            timestamp: Utc::now(),
+            user_id,
+            valid_from,
+            valid_to,
+        }
+    }
+    
+    /// An event recorded now about a fact true from `valid_from` on
+    pub fn at(event_type: EventType, valid_from: DateTime<Utc>, user_id: Option<String>) -> Self {
+        Self::new(event_type, valid_from, None, user_id)
+    }
+}
+
 impl EventLog {
     pub fn new() -> Self {
         Self {
             events: Vec::new(),
+            sink: None,
         }
     }
     
+    /// Pass every event recorded from now on to `sink` as well
+    pub fn with_sink(mut self, sink: EventSink) -> Self {
+        self.sink = Some(sink);
+        self
+    }
+    
     /// Record an event
     pub fn record(&mut self, event: ObjectEvent) {
+        if let Some(sink) = &self.sink {
+            sink(&event);
+        }
         self.events.push(event);
     }
     
@@ -107,6 +174,103 @@ impl EventLog {
         self.record(event);
     }
     
+    /// Record an object deletion event
+    pub fn record_deleted(&mut self, object_type: String, object_id: String, user_id: Option<String>) {
+        self.record_at(EventType::ObjectDeleted { object_type, object_id }, Utc::now(), user_id);
+    }
+    
+    /// Record a merge of duplicate objects into `winner_id`
+    pub fn record_merged(
+        &mut self,
+        object_type: String,
+        winner_id: String,
+        loser_ids: Vec<String>,
+        merged_properties: PropertyMap,
+        user_id: Option<String>,
+    ) {
+        let event = ObjectEvent {
+            event_id: Uuid::new_v4().to_string(),
+            event_type: EventType::ObjectsMerged {
+                object_type,
+                winner_id,
+                loser_ids,
+                merged_properties,
+            },
+            timestamp: Utc::now(),
+            user_id,
+            valid_from: Utc::now(),
+            valid_to: None,
+        };
+        self.record(event);
+    }
+    
+    /// Record a link creation event
+    pub fn record_link_created(
+        &mut self,
+        link_type: String,
+        link_id: String,
+        source_id: String,
+        target_id: String,
+        properties: PropertyMap,
+        user_id: Option<String>,
+    ) {
+        self.record_at(
+            EventType::LinkCreated {
+                link_type,
+                link_id,
+                source_id,
+                target_id,
+                properties,
+            },
+            Utc::now(),
+            user_id,
+        );
+    }
+    
+    /// Record a link deletion event
+    pub fn record_link_deleted(
+        &mut self,
+        link_type: String,
+        link_id: String,
+        source_id: String,
+        target_id: String,
+        user_id: Option<String>,
+    ) {
+        self.record_at(
+            EventType::LinkDeleted {
+                link_type,
+                link_id,
+                source_id,
+                target_id,
+            },
+            Utc::now(),
+            user_id,
+        );
+    }
+    
+    /// Record an event that took effect at `timestamp`, e.g. when backfilling history
+    /// from a source system. It is recorded now.
+    pub fn record_at(&mut self, event_type: EventType, timestamp: DateTime<Utc>, user_id: Option<String>) {
+        self.record(ObjectEvent::at(event_type, timestamp, user_id));
+    }
+    
+    /// Record an event about a fact true from `valid_from` until `valid_to`, e.g. a
+    /// census vintage or a late correction of one
+    pub fn record_valid(
+        &mut self,
+        event_type: EventType,
+        valid_from: DateTime<Utc>,
+        valid_to: Option<DateTime<Utc>>,
+        user_id: Option<String>,
+    ) {
+        self.record(ObjectEvent::new(event_type, valid_from, valid_to, user_id));
+    }
+    
+    /// Every event, in the order recorded
+    pub fn events(&self) -> &[ObjectEvent] {
+        &self.events
+    }
+    
     /// Invalidate previous events for properties that are being updated
     fn invalidate_properties(
         &mut self,
@@ -152,10 +316,50 @@ impl EventLog {
                 EventType::PropertyChanged { object_type: ot, object_id: oid, .. } => {
                     ot == object_type && oid == object_id
                 }
+                EventType::ObjectsMerged { object_type: ot, winner_id, loser_ids, .. } => {
+                    ot == object_type && (winner_id == object_id || loser_ids.iter().any(|id| id == object_id))
+                }
+                EventType::LinkCreated { .. } | EventType::LinkDeleted { .. } => false,
             })
             .collect()
     }
     
+    /// Types of the objects with ID `object_id` that have events, in the order first seen
+    pub fn object_types_for_id(&self, object_id: &str) -> Vec<String> {
+        let mut object_types: Vec<String> = Vec::new();
+        for event in &self.events {
+            let object_type = match &event.event_type {
+                EventType::ObjectCreated { object_type, object_id: oid, .. } |
+                EventType::ObjectUpdated { object_type, object_id: oid, .. } |
+                EventType::ObjectDeleted { object_type, object_id: oid } |
+                EventType::PropertyChanged { object_type, object_id: oid, .. } if oid == object_id => object_type,
+                EventType::ObjectsMerged { object_type, winner_id, loser_ids, .. }
+                    if winner_id == object_id || loser_ids.iter().any(|id| id == object_id) => object_type,
+                _ => continue,
+            };
+            if !object_types.contains(object_type) {
+                object_types.push(object_type.clone());
+            }
+        }
+        object_types
+    }
+    
+    /// Creation and deletion events of links with `object_id` at either end, in the
+    /// order they took effect
+    pub fn get_link_events_for_object(&self, object_id: &str) -> Vec<&ObjectEvent> {
+        let mut events: Vec<&ObjectEvent> = self.events.iter()
+            .filter(|e| match &e.event_type {
+                EventType::LinkCreated { source_id, target_id, .. } |
+                EventType::LinkDeleted { source_id, target_id, .. } => {
+                    source_id == object_id || target_id == object_id
+                }
+                _ => false,
+            })
+            .collect();
+        events.sort_by_key(|e| (e.valid_from, e.timestamp));
+        events
+    }
+    
     /// Get events valid at a specific time
     pub fn get_events_at_time(
         &self,
@@ -192,6 +396,22 @@ impl Default for EventLog {
     }
 }
 
+#[async_trait::async_trait]
+impl BackupComponent for EventLog {
+    fn backup_name(&self) -> String {
+        "event_log".to_string()
+    }
+
+    async fn export_for_backup(&self) -> Result<Vec<u8>, BackupError> {
+        serde_json::to_vec(&self.events).map_err(|e| BackupError::Serialization(e.to_string()))
+    }
+
+    async fn import_from_backup(&mut self, data: &[u8]) -> Result<(), BackupError> {
+        self.events = serde_json::from_slice(data).map_err(|e| BackupError::Serialization(e.to_string()))?;
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -245,4 +465,20 @@ mod tests {
         let events_before = log.get_events_at_time(before - chrono::Duration::seconds(1));
         assert!(events_before.is_empty());
     }
+
+    #[tokio::test]
+    async fn test_backup_round_trip() {
+        let mut log = EventLog::new();
+        let mut props = PropertyMap::new();
+        props.insert("name".to_string(), PropertyValue::String("test".to_string()));
+        log.record_created("test_type".to_string(), "test_id".to_string(), props.clone(), Some("user1".to_string()));
+        log.record_updated("test_type".to_string(), "test_id".to_string(), props, None);
+
+        let data = log.export_for_backup().await.unwrap();
+        let mut restored = EventLog::new();
+        restored.import_from_backup(&data).await.unwrap();
+
+        assert_eq!(restored.events.len(), 2);
+        assert_eq!(restored.export_for_backup().await.unwrap(), data);
+    }
 }
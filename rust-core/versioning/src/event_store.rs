@@ -0,0 +1,706 @@
+use crate::event_log::{EventLog, EventType, ObjectEvent};
+use chrono::{DateTime, NaiveDate, Utc};
+use ontology_engine::backup::{BackupComponent, BackupError};
+use ontology_engine::PropertyMap;
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum EventStoreError {
+    #[error("Event store I/O error on {path}: {source}")]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("Corrupt event in {path} at byte {offset}: {message}")]
+    Corrupt { path: PathBuf, offset: u64, message: String },
+    #[error("Failed to serialize event: {0}")]
+    Serialization(String),
+}
+
+fn io_error(path: &Path) -> impl FnOnce(std::io::Error) -> EventStoreError + '_ {
+    move |source| EventStoreError::Io { path: path.to_path_buf(), source }
+}
+
+/// Where object events are kept. Events come back in the order they took effect.
+pub trait EventStore: Send + Sync {
+    fn append(&mut self, event: ObjectEvent) -> Result<(), EventStoreError>;
+
+    /// Events for an object that were valid at `timestamp`
+    fn get_object_events_at_time(
+        &self,
+        object_type: &str,
+        object_id: &str,
+        timestamp: DateTime<Utc>,
+    ) -> Result<Vec<ObjectEvent>, EventStoreError>;
+
+    /// Events of every object and link that were valid at `timestamp`
+    fn get_events_at_time(&self, timestamp: DateTime<Utc>) -> Result<Vec<ObjectEvent>, EventStoreError>;
+
+    /// Events that took effect in `[from, to)`
+    fn events_in_range(&self, from: DateTime<Utc>, to: DateTime<Utc>) -> Result<Vec<ObjectEvent>, EventStoreError>;
+
+    /// Creation and deletion events of links with `object_id` at either end
+    fn get_link_events_for_object(&self, object_id: &str) -> Result<Vec<ObjectEvent>, EventStoreError>;
+
+    /// Types of the objects with ID `object_id` that have events
+    fn object_types_for_id(&self, object_id: &str) -> Result<Vec<String>, EventStoreError>;
+}
+
+fn valid_at(event: &ObjectEvent, timestamp: DateTime<Utc>) -> bool {
+    event.valid_from <= timestamp && event.valid_to.is_none_or(|to| to > timestamp)
+}
+
+/// The in-memory store; history is lost when the process exits
+impl EventStore for EventLog {
+    fn append(&mut self, event: ObjectEvent) -> Result<(), EventStoreError> {
+        self.record(event);
+        Ok(())
+    }
+
+    fn get_object_events_at_time(
+        &self,
+        object_type: &str,
+        object_id: &str,
+        timestamp: DateTime<Utc>,
+    ) -> Result<Vec<ObjectEvent>, EventStoreError> {
+        Ok(EventLog::get_object_events_at_time(self, object_type, object_id, timestamp).into_iter().cloned().collect())
+    }
+
+    fn get_events_at_time(&self, timestamp: DateTime<Utc>) -> Result<Vec<ObjectEvent>, EventStoreError> {
+        Ok(EventLog::get_events_at_time(self, timestamp).into_iter().cloned().collect())
+    }
+
+    fn events_in_range(&self, from: DateTime<Utc>, to: DateTime<Utc>) -> Result<Vec<ObjectEvent>, EventStoreError> {
+        let mut events: Vec<ObjectEvent> = self
+            .events()
+            .iter()
+            .filter(|event| event.valid_from >= from && event.valid_from < to)
+            .cloned()
+            .collect();
+        events.sort_by_key(|event| (event.valid_from, event.timestamp));
+        Ok(events)
+    }
+
+    fn get_link_events_for_object(&self, object_id: &str) -> Result<Vec<ObjectEvent>, EventStoreError> {
+        Ok(EventLog::get_link_events_for_object(self, object_id).into_iter().cloned().collect())
+    }
+
+    fn object_types_for_id(&self, object_id: &str) -> Result<Vec<String>, EventStoreError> {
+        Ok(EventLog::object_types_for_id(self, object_id))
+    }
+}
+
+/// What an event is looked up by
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum IndexKey {
+    Object { object_type: String, object_id: String },
+    /// An object at either end of a link
+    LinkEnd(String),
+}
+
+fn index_keys(event_type: &EventType) -> Vec<IndexKey> {
+    let object = |object_type: &String, object_id: &String| IndexKey::Object {
+        object_type: object_type.clone(),
+        object_id: object_id.clone(),
+    };
+    match event_type {
+        EventType::ObjectCreated { object_type, object_id, .. }
+        | EventType::ObjectUpdated { object_type, object_id, .. }
+        | EventType::ObjectDeleted { object_type, object_id }
+        | EventType::PropertyChanged { object_type, object_id, .. } => vec![object(object_type, object_id)],
+        EventType::ObjectsMerged { object_type, winner_id, loser_ids, .. } => std::iter::once(winner_id)
+            .chain(loser_ids)
+            .map(|object_id| object(object_type, object_id))
+            .collect(),
+        EventType::LinkCreated { source_id, target_id, .. } | EventType::LinkDeleted { source_id, target_id, .. } => {
+            vec![IndexKey::LinkEnd(source_id.clone()), IndexKey::LinkEnd(target_id.clone())]
+        }
+    }
+}
+
+/// Position of an event: the day of its segment and its byte offset there
+type EventPosition = (NaiveDate, u64);
+
+/// Append-only event log on disk, one JSONL segment per day named `YYYY-MM-DD.jsonl` after
+/// the day events took effect. Lookups by object go through an index of event positions,
+/// built by scanning the segments on first use.
+pub struct FileEventStore {
+    dir: PathBuf,
+    index: OnceLock<HashMap<IndexKey, Vec<EventPosition>>>,
+}
+
+/// What `FileEventStore::compact` did
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CompactionReport {
+    pub segments_compacted: usize,
+    pub events_before: usize,
+    pub events_after: usize,
+}
+
+impl FileEventStore {
+    /// Open the log in `dir`, creating it if needed. A final line cut short by a crash
+    /// mid-write is discarded with a warning.
+    pub fn open(dir: impl AsRef<Path>) -> Result<Self, EventStoreError> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir).map_err(io_error(&dir))?;
+        let store = Self { dir, index: OnceLock::new() };
+        for (_, path) in store.segments()? {
+            repair_tail(&path)?;
+        }
+        Ok(store)
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Whether the log holds no events yet
+    pub fn is_empty(&self) -> Result<bool, EventStoreError> {
+        for (_, path) in self.segments()? {
+            if fs::metadata(&path).map_err(io_error(&path))?.len() > 0 {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    fn segment_path(&self, day: NaiveDate) -> PathBuf {
+        self.dir.join(format!("{}.jsonl", day.format("%Y-%m-%d")))
+    }
+
+    /// Segments in day order
+    fn segments(&self) -> Result<Vec<(NaiveDate, PathBuf)>, EventStoreError> {
+        let mut segments = Vec::new();
+        for entry in fs::read_dir(&self.dir).map_err(io_error(&self.dir))? {
+            let path = entry.map_err(io_error(&self.dir))?.path();
+            if path.extension().is_some_and(|extension| extension == "jsonl") {
+                let day = path
+                    .file_stem()
+                    .and_then(|stem| stem.to_str())
+                    .and_then(|stem| NaiveDate::parse_from_str(stem, "%Y-%m-%d").ok());
+                if let Some(day) = day {
+                    segments.push((day, path));
+                }
+            }
+        }
+        segments.sort();
+        Ok(segments)
+    }
+
+    /// Every event of a segment with its offset
+    fn read_segment(path: &Path) -> Result<Vec<(u64, ObjectEvent)>, EventStoreError> {
+        let mut reader = BufReader::new(File::open(path).map_err(io_error(path))?);
+        let mut events = Vec::new();
+        let mut offset = 0;
+        let mut line = String::new();
+        loop {
+            line.clear();
+            let read = reader.read_line(&mut line).map_err(io_error(path))?;
+            if read == 0 {
+                return Ok(events);
+            }
+            if !line.trim().is_empty() {
+                events.push((offset, parse_event(path, offset, &line)?));
+            }
+            offset += read as u64;
+        }
+    }
+
+    fn index(&self) -> Result<&HashMap<IndexKey, Vec<EventPosition>>, EventStoreError> {
+        if let Some(index) = self.index.get() {
+            return Ok(index);
+        }
+        let mut index: HashMap<IndexKey, Vec<EventPosition>> = HashMap::new();
+        for (day, path) in self.segments()? {
+            for (offset, event) in Self::read_segment(&path)? {
+                for key in index_keys(&event.event_type) {
+                    index.entry(key).or_default().push((day, offset));
+                }
+            }
+        }
+        Ok(self.index.get_or_init(|| index))
+    }
+
+    /// Read the events at `positions`, in the order they took effect
+    fn read_positions(&self, positions: &[EventPosition]) -> Result<Vec<ObjectEvent>, EventStoreError> {
+        let mut positions = positions.to_vec();
+        positions.sort();
+        positions.dedup();
+        let mut events = Vec::with_capacity(positions.len());
+        let mut open: Option<(NaiveDate, PathBuf, BufReader<File>)> = None;
+        for (day, offset) in positions {
+            if open.as_ref().is_none_or(|(open_day, _, _)| *open_day != day) {
+                let path = self.segment_path(day);
+                let file = File::open(&path).map_err(io_error(&path))?;
+                open = Some((day, path, BufReader::new(file)));
+            }
+            let (_, path, reader) = open.as_mut().expect("segment was just opened");
+            reader.seek(SeekFrom::Start(offset)).map_err(io_error(path))?;
+            let mut line = String::new();
+            reader.read_line(&mut line).map_err(io_error(path))?;
+            events.push(parse_event(path, offset, &line)?);
+        }
+        events.sort_by_key(|event| (event.valid_from, event.timestamp));
+        Ok(events)
+    }
+
+    /// Events of the segments for days up to `last_day`
+    fn events_through(&self, last_day: NaiveDate) -> Result<Vec<ObjectEvent>, EventStoreError> {
+        let mut events = Vec::new();
+        for (day, path) in self.segments()? {
+            if day > last_day {
+                break;
+            }
+            events.extend(Self::read_segment(&path)?.into_iter().map(|(_, event)| event));
+        }
+        events.sort_by_key(|event| (event.valid_from, event.timestamp));
+        Ok(events)
+    }
+
+    /// Every event in the log, in the order they took effect
+    pub fn all_events(&self) -> Result<Vec<ObjectEvent>, EventStoreError> {
+        self.events_through(NaiveDate::MAX)
+    }
+
+    /// Replace the whole log with `events`, e.g. when restoring a backup. Run it while no
+    /// server is appending to the log.
+    pub fn replace_all(&mut self, events: Vec<ObjectEvent>) -> Result<(), EventStoreError> {
+        let mut by_day: HashMap<NaiveDate, Vec<ObjectEvent>> = HashMap::new();
+        for event in events {
+            by_day.entry(event.valid_from.date_naive()).or_default().push(event);
+        }
+        for (day, path) in self.segments()? {
+            if !by_day.contains_key(&day) {
+                fs::remove_file(&path).map_err(io_error(&path))?;
+            }
+        }
+        for (day, mut events) in by_day {
+            events.sort_by_key(|event| (event.valid_from, event.timestamp));
+            write_segment(&self.segment_path(day), &events)?;
+        }
+        self.index = OnceLock::new();
+        Ok(())
+    }
+
+    /// Compact the segments of days more than `days` ago; see `compact_before`
+    pub fn compact(&mut self, days: u32) -> Result<CompactionReport, EventStoreError> {
+        self.compact_before(Utc::now().date_naive() - chrono::Duration::days(days as i64))
+    }
+
+    /// Rewrite the segments of days before `cutoff` into one snapshot event per object that
+    /// still exists at the end of them, with its properties as of then, and the creation
+    /// events of links that were not deleted. Later reconstruction replays far fewer events;
+    /// the price is that the compacted days no longer show intermediate states. Snapshots
+    /// keep the object's creation time.
+    pub fn compact_before(&mut self, cutoff: NaiveDate) -> Result<CompactionReport, EventStoreError> {
+        let old: Vec<(NaiveDate, PathBuf)> = self.segments()?.into_iter().filter(|(day, _)| *day < cutoff).collect();
+        let Some(last_day) = old.last().map(|(day, _)| *day) else {
+            return Ok(CompactionReport::default());
+        };
+        let events = self.events_through(last_day)?;
+        let mut report = CompactionReport { segments_compacted: old.len(), events_before: events.len(), events_after: 0 };
+
+        // (object type, object ID) -> snapshot event; link ID -> creation event
+        let mut objects: Vec<((String, String), ObjectEvent)> = Vec::new();
+        let mut links: Vec<(String, ObjectEvent)> = Vec::new();
+        let position = |objects: &[((String, String), ObjectEvent)], object_type: &str, object_id: &str| {
+            objects.iter().position(|((t, id), _)| t == object_type && id == object_id)
+        };
+        for event in events {
+            match &event.event_type {
+                EventType::ObjectCreated { object_type, object_id, .. } => {
+                    let key = (object_type.clone(), object_id.clone());
+                    objects.retain(|(existing, _)| *existing != key);
+                    objects.push((key, event));
+                }
+                EventType::ObjectUpdated { object_type, object_id, changed_properties: changes, .. }
+                | EventType::ObjectsMerged { object_type, winner_id: object_id, merged_properties: changes, .. } => {
+                    if let EventType::ObjectsMerged { loser_ids, .. } = &event.event_type {
+                        objects.retain(|((t, id), _)| !(t == object_type && loser_ids.contains(id)));
+                    }
+                    match position(&objects, object_type, object_id) {
+                        Some(index) => merge_into(&mut objects[index].1, changes),
+                        None => objects.push((
+                            (object_type.clone(), object_id.clone()),
+                            snapshot_event(&event, object_type, object_id, changes.clone()),
+                        )),
+                    }
+                }
+                EventType::PropertyChanged { object_type, object_id, property_name, new_value, .. } => {
+                    let mut changes = PropertyMap::new();
+                    changes.insert(property_name.clone(), new_value.clone());
+                    match position(&objects, object_type, object_id) {
+                        Some(index) => merge_into(&mut objects[index].1, &changes),
+                        None => objects.push((
+                            (object_type.clone(), object_id.clone()),
+                            snapshot_event(&event, object_type, object_id, changes),
+                        )),
+                    }
+                }
+                EventType::ObjectDeleted { object_type, object_id } => {
+                    objects.retain(|((t, id), _)| !(t == object_type && id == object_id));
+                }
+                EventType::LinkCreated { link_id, .. } => links.push((link_id.clone(), event.clone())),
+                EventType::LinkDeleted { link_id, .. } => links.retain(|(id, _)| id != link_id),
+            }
+        }
+
+        let mut compacted: Vec<ObjectEvent> =
+            objects.into_iter().map(|(_, event)| event).chain(links.into_iter().map(|(_, event)| event)).collect();
+        compacted.sort_by_key(|event| event.valid_from);
+        report.events_after = compacted.len();
+        let mut by_day: HashMap<NaiveDate, Vec<ObjectEvent>> = HashMap::new();
+        for event in compacted {
+            by_day.entry(event.valid_from.date_naive()).or_default().push(event);
+        }
+        for (day, path) in old {
+            match by_day.remove(&day) {
+                Some(events) => write_segment(&path, &events)?,
+                None => fs::remove_file(&path).map_err(io_error(&path))?,
+            }
+        }
+        self.index = OnceLock::new();
+        Ok(report)
+    }
+}
+
+/// An `ObjectCreated` event for `object_id` with `properties`, taking effect with `event`
+fn snapshot_event(event: &ObjectEvent, object_type: &str, object_id: &str, properties: PropertyMap) -> ObjectEvent {
+    ObjectEvent {
+        event_type: EventType::ObjectCreated {
+            object_type: object_type.to_string(),
+            object_id: object_id.to_string(),
+            properties,
+        },
+        ..event.clone()
+    }
+}
+
+fn merge_into(snapshot: &mut ObjectEvent, changes: &PropertyMap) {
+    if let EventType::ObjectCreated { properties, .. } = &mut snapshot.event_type {
+        for (key, value) in changes.iter() {
+            properties.insert(key.clone(), value.clone());
+        }
+    }
+}
+
+fn parse_event(path: &Path, offset: u64, line: &str) -> Result<ObjectEvent, EventStoreError> {
+    serde_json::from_str(line).map_err(|e| EventStoreError::Corrupt {
+        path: path.to_path_buf(),
+        offset,
+        message: e.to_string(),
+    })
+}
+
+/// Replace `path` with `events`, through a temporary file so a crash leaves either version
+fn write_segment(path: &Path, events: &[ObjectEvent]) -> Result<(), EventStoreError> {
+    let temporary = path.with_extension("jsonl.tmp");
+    let mut content = String::new();
+    for event in events {
+        content.push_str(&serde_json::to_string(event).map_err(|e| EventStoreError::Serialization(e.to_string()))?);
+        content.push('\n');
+    }
+    fs::write(&temporary, content).map_err(io_error(&temporary))?;
+    fs::rename(&temporary, path).map_err(io_error(path))
+}
+
+/// Finish or drop a final line without its newline. One that parses was written in full
+/// and only lacks the newline; anything else was cut short and is discarded.
+fn repair_tail(path: &Path) -> Result<(), EventStoreError> {
+    let mut file = OpenOptions::new().read(true).write(true).open(path).map_err(io_error(path))?;
+    let mut content = Vec::new();
+    file.read_to_end(&mut content).map_err(io_error(path))?;
+    if content.is_empty() || content.ends_with(b"\n") {
+        return Ok(());
+    }
+    let start = content.iter().rposition(|byte| *byte == b'\n').map_or(0, |newline| newline + 1);
+    let tail = String::from_utf8_lossy(&content[start..]);
+    if serde_json::from_str::<ObjectEvent>(&tail).is_ok() {
+        file.write_all(b"\n").map_err(io_error(path))?;
+    } else {
+        tracing::warn!(
+            "discarding truncated event at the end of {} ({} bytes)",
+            path.display(),
+            content.len() - start
+        );
+        file.set_len(start as u64).map_err(io_error(path))?;
+    }
+    Ok(())
+}
+
+impl EventStore for FileEventStore {
+    fn append(&mut self, event: ObjectEvent) -> Result<(), EventStoreError> {
+        let day = event.valid_from.date_naive();
+        let path = self.segment_path(day);
+        let mut line = serde_json::to_string(&event).map_err(|e| EventStoreError::Serialization(e.to_string()))?;
+        line.push('\n');
+        let mut file = OpenOptions::new().create(true).append(true).open(&path).map_err(io_error(&path))?;
+        let offset = file.metadata().map_err(io_error(&path))?.len();
+        file.write_all(line.as_bytes()).map_err(io_error(&path))?;
+        file.sync_data().map_err(io_error(&path))?;
+        if let Some(index) = self.index.get_mut() {
+            for key in index_keys(&event.event_type) {
+                index.entry(key).or_default().push((day, offset));
+            }
+        }
+        Ok(())
+    }
+
+    fn get_object_events_at_time(
+        &self,
+        object_type: &str,
+        object_id: &str,
+        timestamp: DateTime<Utc>,
+    ) -> Result<Vec<ObjectEvent>, EventStoreError> {
+        let key = IndexKey::Object { object_type: object_type.to_string(), object_id: object_id.to_string() };
+        let positions = self.index()?.get(&key).cloned().unwrap_or_default();
+        let mut events = self.read_positions(&positions)?;
+        events.retain(|event| valid_at(event, timestamp));
+        Ok(events)
+    }
+
+    fn get_events_at_time(&self, timestamp: DateTime<Utc>) -> Result<Vec<ObjectEvent>, EventStoreError> {
+        let mut events = self.events_through(timestamp.date_naive())?;
+        events.retain(|event| valid_at(event, timestamp));
+        Ok(events)
+    }
+
+    fn events_in_range(&self, from: DateTime<Utc>, to: DateTime<Utc>) -> Result<Vec<ObjectEvent>, EventStoreError> {
+        let mut events = Vec::new();
+        for (day, path) in self.segments()? {
+            if day >= from.date_naive() && day <= to.date_naive() {
+                events.extend(Self::read_segment(&path)?.into_iter().map(|(_, event)| event));
+            }
+        }
+        events.retain(|event| event.valid_from >= from && event.valid_from < to);
+        events.sort_by_key(|event| (event.valid_from, event.timestamp));
+        Ok(events)
+    }
+
+    fn get_link_events_for_object(&self, object_id: &str) -> Result<Vec<ObjectEvent>, EventStoreError> {
+        let positions = self.index()?.get(&IndexKey::LinkEnd(object_id.to_string())).cloned().unwrap_or_default();
+        self.read_positions(&positions)
+    }
+
+    fn object_types_for_id(&self, object_id: &str) -> Result<Vec<String>, EventStoreError> {
+        let mut object_types: Vec<String> = self
+            .index()?
+            .keys()
+            .filter_map(|key| match key {
+                IndexKey::Object { object_type, object_id: id } if id == object_id => Some(object_type.clone()),
+                _ => None,
+            })
+            .collect();
+        object_types.sort();
+        Ok(object_types)
+    }
+}
+
+/// Backed up as the same JSON event list as the in-memory `EventLog`. The log is a
+/// directory, so it has no live file for the offline restore; `event-log-restore` writes a
+/// backup back into it.
+#[async_trait::async_trait]
+impl BackupComponent for FileEventStore {
+    fn backup_name(&self) -> String {
+        "event_log".to_string()
+    }
+
+    async fn export_for_backup(&self) -> Result<Vec<u8>, BackupError> {
+        let events = self.all_events().map_err(|e| BackupError::Component(e.to_string()))?;
+        serde_json::to_vec(&events).map_err(|e| BackupError::Serialization(e.to_string()))
+    }
+
+    async fn import_from_backup(&mut self, data: &[u8]) -> Result<(), BackupError> {
+        let events: Vec<ObjectEvent> =
+            serde_json::from_slice(data).map_err(|e| BackupError::Serialization(e.to_string()))?;
+        self.replace_all(events).map_err(|e| BackupError::Component(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event_log::EventSink;
+    use crate::time_query::TimeQuery;
+    use ontology_engine::PropertyValue;
+    use std::sync::Arc;
+
+    fn at(year: i32) -> DateTime<Utc> {
+        chrono::TimeZone::with_ymd_and_hms(&Utc, year, 1, 1, 0, 0, 0).unwrap()
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("event-store-{}-{}", name, uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn created(object_id: &str, status: &str, year: i32) -> ObjectEvent {
+        let mut properties = PropertyMap::new();
+        properties.insert("status".to_string(), PropertyValue::String(status.to_string()));
+        ObjectEvent::at(
+            EventType::ObjectCreated { object_type: "plant".to_string(), object_id: object_id.to_string(), properties },
+            at(year),
+            None,
+        )
+    }
+
+    fn updated(object_id: &str, status: &str, year: i32) -> ObjectEvent {
+        let mut changed_properties = PropertyMap::new();
+        changed_properties.insert("status".to_string(), PropertyValue::String(status.to_string()));
+        ObjectEvent::at(
+            EventType::ObjectUpdated { object_type: "plant".to_string(), object_id: object_id.to_string(), changed_properties },
+            at(year),
+            None,
+        )
+    }
+
+    fn status(time_query: &TimeQuery, object_id: &str, year: i32) -> Option<PropertyValue> {
+        time_query
+            .reconstruct_object("plant", object_id, at(year))
+            .and_then(|object| object.properties.get("status").cloned())
+    }
+
+    #[test]
+    fn test_history_survives_reopen() {
+        let dir = temp_dir("reopen");
+        let mut store = FileEventStore::open(&dir).unwrap();
+        assert!(store.is_empty().unwrap());
+        store.append(created("p1", "planned", 2010)).unwrap();
+        // Index loaded before the next append, which must update it
+        assert_eq!(store.object_types_for_id("p1").unwrap(), vec!["plant".to_string()]);
+        store.append(updated("p1", "operating", 2015)).unwrap();
+        store
+            .append(ObjectEvent::at(
+                EventType::LinkCreated {
+                    link_type: "supplies".to_string(),
+                    link_id: "l1".to_string(),
+                    source_id: "acme".to_string(),
+                    target_id: "p1".to_string(),
+                    properties: PropertyMap::new(),
+                },
+                at(2016),
+                None,
+            ))
+            .unwrap();
+        assert_eq!(store.get_object_events_at_time("plant", "p1", at(2020)).unwrap().len(), 2);
+        drop(store);
+
+        let time_query = TimeQuery::with_store(Box::new(FileEventStore::open(&dir).unwrap()));
+        assert_eq!(status(&time_query, "p1", 2012), Some(PropertyValue::String("planned".to_string())));
+        assert_eq!(status(&time_query, "p1", 2020), Some(PropertyValue::String("operating".to_string())));
+        assert_eq!(time_query.get_links_at_time("p1", at(2020)).len(), 1);
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 3);
+
+        let store = FileEventStore::open(&dir).unwrap();
+        assert!(!store.is_empty().unwrap());
+        assert_eq!(store.events_in_range(at(2011), at(2016)).unwrap().len(), 1);
+        assert_eq!(store.get_events_at_time(at(2015)).unwrap().len(), 2);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_event_log_sink_appends_to_shared_history() {
+        let dir = temp_dir("sink");
+        let time_query = Arc::new(TimeQuery::with_store(Box::new(FileEventStore::open(&dir).unwrap())));
+        let history = time_query.clone();
+        let sink: EventSink = Arc::new(move |event| history.append(event.clone()).unwrap());
+        let mut log = EventLog::new().with_sink(sink);
+        log.record(created("p1", "planned", 2010));
+        log.record(updated("p1", "operating", 2015));
+
+        assert_eq!(status(&time_query, "p1", 2020), Some(PropertyValue::String("operating".to_string())));
+        let store = FileEventStore::open(&dir).unwrap();
+        assert_eq!(store.get_object_events_at_time("plant", "p1", at(2020)).unwrap().len(), 2);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_truncated_final_line_is_discarded() {
+        let dir = temp_dir("truncated");
+        let mut store = FileEventStore::open(&dir).unwrap();
+        store.append(created("p1", "planned", 2010)).unwrap();
+        store.append(created("p2", "planned", 2010)).unwrap();
+        drop(store);
+
+        // A crash mid-write leaves half a line
+        let segment = dir.join("2010-01-01.jsonl");
+        let line = serde_json::to_string(&created("p3", "planned", 2010)).unwrap();
+        let mut file = OpenOptions::new().append(true).open(&segment).unwrap();
+        file.write_all(&line.as_bytes()[..line.len() / 2]).unwrap();
+        drop(file);
+
+        let mut store = FileEventStore::open(&dir).unwrap();
+        assert_eq!(store.events_in_range(at(2010), at(2011)).unwrap().len(), 2);
+        // Later appends start on a fresh line
+        store.append(created("p3", "planned", 2010)).unwrap();
+        let time_query = TimeQuery::with_store(Box::new(store));
+        assert!(status(&time_query, "p2", 2011).is_some());
+        assert!(status(&time_query, "p3", 2011).is_some());
+
+        // A complete final line that only lacks its newline is kept
+        let mut file = OpenOptions::new().append(true).open(&segment).unwrap();
+        file.write_all(serde_json::to_string(&created("p4", "planned", 2010)).unwrap().as_bytes()).unwrap();
+        drop(file);
+        let store = FileEventStore::open(&dir).unwrap();
+        assert_eq!(store.events_in_range(at(2010), at(2011)).unwrap().len(), 4);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_compaction_keeps_final_states() {
+        let dir = temp_dir("compact");
+        let mut store = FileEventStore::open(&dir).unwrap();
+        store.append(created("p1", "planned", 2010)).unwrap();
+        store.append(updated("p1", "operating", 2012)).unwrap();
+        store.append(created("p2", "planned", 2011)).unwrap();
+        store
+            .append(ObjectEvent::at(
+                EventType::ObjectDeleted { object_type: "plant".to_string(), object_id: "p2".to_string() },
+                at(2013),
+                None,
+            ))
+            .unwrap();
+        store.append(updated("p1", "retired", 2020)).unwrap();
+
+        let report = store.compact_before(NaiveDate::from_ymd_opt(2015, 1, 1).unwrap()).unwrap();
+        assert_eq!(report, CompactionReport { segments_compacted: 4, events_before: 4, events_after: 1 });
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 2);
+
+        let time_query = TimeQuery::with_store(Box::new(store));
+        // The compacted days show the state at their end
+        assert_eq!(status(&time_query, "p1", 2011), Some(PropertyValue::String("operating".to_string())));
+        assert_eq!(status(&time_query, "p1", 2021), Some(PropertyValue::String("retired".to_string())));
+        assert!(status(&time_query, "p1", 2009).is_none());
+        assert!(status(&time_query, "p2", 2012).is_none());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_backup_round_trip() {
+        let dir = temp_dir("backup");
+        let mut store = FileEventStore::open(&dir).unwrap();
+        store.append(created("p1", "planned", 2010)).unwrap();
+        store.append(updated("p1", "operating", 2015)).unwrap();
+        let data = store.export_for_backup().await.unwrap();
+
+        // Restoring drops what was written since, including whole days
+        store.append(updated("p1", "retired", 2020)).unwrap();
+        assert_eq!(store.object_types_for_id("p1").unwrap(), vec!["plant".to_string()]);
+        store.import_from_backup(&data).await.unwrap();
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 2);
+        assert_eq!(store.get_object_events_at_time("plant", "p1", at(2021)).unwrap().len(), 2);
+
+        // The payload is the in-memory log's backup format
+        let mut log = EventLog::new();
+        log.import_from_backup(&data).await.unwrap();
+        assert_eq!(log.events().len(), 2);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
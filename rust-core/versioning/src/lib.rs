@@ -1,8 +1,12 @@
 pub mod event_log;
+pub mod event_store;
+pub mod snapshot_cache;
 pub mod time_query;
 
-pub use event_log::{EventLog, ObjectEvent, EventType};
-pub use time_query::{TimeQuery, HistoricalObject, Snapshot};
+pub use event_log::{EventLog, EventSink, ObjectEvent, EventType};
+pub use event_store::{CompactionReport, EventStore, EventStoreError, FileEventStore};
+pub use snapshot_cache::{SnapshotCache, SnapshotCacheConfig};
+pub use time_query::{TimeQuery, HistoricalLink, HistoricalObject, Snapshot};
 
 
 
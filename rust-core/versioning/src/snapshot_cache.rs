@@ -0,0 +1,225 @@
+use crate::event_log::{EventType, ObjectEvent};
+use crate::event_store::{EventStore, EventStoreError};
+use chrono::{DateTime, NaiveTime, Utc};
+use ontology_engine::PropertyMap;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// An object's state as replayed so far
+#[derive(Debug, Clone)]
+pub(crate) struct ObjectState {
+    pub properties: PropertyMap,
+    /// When its first event took effect
+    pub valid_from: DateTime<Utc>,
+    /// When the earliest-ending fact it was built from stops being valid
+    pub valid_to: Option<DateTime<Utc>>,
+    /// Deleted or merged away; it stays gone even if later events mention it
+    pub removed: bool,
+}
+
+/// (object type, object ID) -> state
+pub(crate) type ObjectStates = HashMap<(String, String), ObjectState>;
+
+fn state<'a>(objects: &'a mut ObjectStates, event: &ObjectEvent, object_type: &str, object_id: &str) -> &'a mut ObjectState {
+    let state = objects.entry((object_type.to_string(), object_id.to_string())).or_insert_with(|| ObjectState {
+        properties: PropertyMap::new(),
+        valid_from: event.valid_from,
+        valid_to: None,
+        removed: false,
+    });
+    if let Some(valid_to) = event.valid_to {
+        state.valid_to = Some(state.valid_to.map_or(valid_to, |earlier| earlier.min(valid_to)));
+    }
+    state
+}
+
+fn merge(state: &mut ObjectState, changes: &PropertyMap) {
+    if !state.removed {
+        for (key, value) in changes.iter() {
+            state.properties.insert(key.clone(), value.clone());
+        }
+    }
+}
+
+/// Apply `event` to the states it touches, the way `TimeQuery::reconstruct_object` does
+pub(crate) fn apply_event(objects: &mut ObjectStates, event: &ObjectEvent) {
+    match &event.event_type {
+        EventType::ObjectCreated { object_type, object_id, properties } => {
+            merge(state(objects, event, object_type, object_id), properties)
+        }
+        EventType::ObjectUpdated { object_type, object_id, changed_properties } => {
+            merge(state(objects, event, object_type, object_id), changed_properties)
+        }
+        EventType::PropertyChanged { object_type, object_id, property_name, new_value, .. } => {
+            let mut changes = PropertyMap::new();
+            changes.insert(property_name.clone(), new_value.clone());
+            merge(state(objects, event, object_type, object_id), &changes)
+        }
+        EventType::ObjectDeleted { object_type, object_id } => state(objects, event, object_type, object_id).removed = true,
+        EventType::ObjectsMerged { object_type, winner_id, loser_ids, merged_properties } => {
+            for loser_id in loser_ids {
+                state(objects, event, object_type, loser_id).removed = true;
+            }
+            merge(state(objects, event, object_type, winner_id), merged_properties)
+        }
+        EventType::LinkCreated { .. } | EventType::LinkDeleted { .. } => {}
+    }
+}
+
+/// When `SnapshotCache` places checkpoints and how many it keeps
+#[derive(Debug, Clone)]
+pub struct SnapshotCacheConfig {
+    /// Checkpoint after every this many events
+    pub every_events: Option<usize>,
+    /// Checkpoint at the start of every day with events
+    pub daily: bool,
+    /// Checkpoints kept in memory; the least recently used is dropped first
+    pub max_checkpoints: usize,
+}
+
+impl Default for SnapshotCacheConfig {
+    fn default() -> Self {
+        Self {
+            every_events: Some(10_000),
+            daily: false,
+            max_checkpoints: 16,
+        }
+    }
+}
+
+/// Every object's state as of `at`, from the events that took effect before it
+pub(crate) struct Checkpoint {
+    pub at: DateTime<Utc>,
+    pub objects: ObjectStates,
+}
+
+#[derive(Default)]
+struct CacheState {
+    /// Where checkpoints go, ascending; worked out from a scan of the store on first use
+    boundaries: Option<Vec<DateTime<Utc>>>,
+    /// Events seen while placing boundaries, and the latest time one took effect
+    events_seen: usize,
+    latest: Option<DateTime<Utc>>,
+    /// Whether an event with a `valid_to` was seen, which checkpoints cannot represent
+    bounded_validity: bool,
+    /// Materialized checkpoints, least recently used first
+    checkpoints: Vec<Arc<Checkpoint>>,
+}
+
+/// Materialized object states at checkpoints, so reconstruction at a time only replays the
+/// events after the nearest earlier checkpoint. Checkpoints are placed every N events and/or
+/// at day boundaries and built on first use from the nearest earlier one; at most
+/// `max_checkpoints` are kept. An event appended with an earlier time than one already seen
+/// invalidates the checkpoints after it, since they would miss it.
+///
+/// Checkpoints assume events stay valid once they take effect, so none are used once an
+/// event with a `valid_to` is recorded.
+pub struct SnapshotCache {
+    config: SnapshotCacheConfig,
+    state: Mutex<CacheState>,
+}
+
+impl SnapshotCache {
+    pub fn new(config: SnapshotCacheConfig) -> Self {
+        Self { config, state: Mutex::new(CacheState::default()) }
+    }
+
+    pub fn config(&self) -> &SnapshotCacheConfig {
+        &self.config
+    }
+
+    /// Materialized checkpoints
+    pub fn len(&self) -> usize {
+        self.state.lock().unwrap().checkpoints.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Note an event appended to the store
+    pub fn record_appended(&self, event: &ObjectEvent) {
+        let mut state = self.state.lock().unwrap();
+        // Checkpoints after it would miss it; only an out-of-order event drops any
+        state.checkpoints.retain(|checkpoint| checkpoint.at <= event.valid_from);
+        if state.boundaries.is_none() {
+            return;
+        }
+        if state.latest.is_some_and(|latest| event.valid_from < latest) {
+            // Later boundaries shift, so they are placed again on next use
+            state.boundaries = None;
+            return;
+        }
+        self.place(&mut state, event);
+    }
+
+    /// Count one more event, adding a boundary if due
+    fn place(&self, state: &mut CacheState, event: &ObjectEvent) {
+        let valid_from = event.valid_from;
+        state.bounded_validity |= event.valid_to.is_some();
+        let due = state.events_seen > 0
+            && self.config.every_events.is_some_and(|every| every > 0 && state.events_seen % every == 0);
+        let mut boundary = due.then_some(valid_from);
+        if self.config.daily && state.latest.is_some_and(|latest| latest.date_naive() < valid_from.date_naive()) {
+            let midnight = valid_from.date_naive().and_time(NaiveTime::MIN).and_utc();
+            boundary = Some(boundary.map_or(midnight, |boundary| boundary.min(midnight)));
+        }
+        state.events_seen += 1;
+        state.latest = Some(valid_from);
+        if let (Some(boundary), Some(boundaries)) = (boundary, state.boundaries.as_mut()) {
+            if boundaries.last().is_none_or(|last| *last < boundary) {
+                boundaries.push(boundary);
+            }
+        }
+    }
+
+    /// The checkpoint nearest before `timestamp`, materializing it if needed; `None` if
+    /// `timestamp` is before the first boundary. Events replayed to build it are added to
+    /// `replayed`.
+    pub(crate) fn checkpoint_before(
+        &self,
+        store: &dyn EventStore,
+        timestamp: DateTime<Utc>,
+        replayed: &AtomicUsize,
+    ) -> Result<Option<Arc<Checkpoint>>, EventStoreError> {
+        let mut state = self.state.lock().unwrap();
+        if state.boundaries.is_none() {
+            state.boundaries = Some(Vec::new());
+            state.events_seen = 0;
+            state.latest = None;
+            state.bounded_validity = false;
+            for event in store.events_in_range(DateTime::<Utc>::MIN_UTC, DateTime::<Utc>::MAX_UTC)? {
+                self.place(&mut state, &event);
+            }
+        }
+        if state.bounded_validity {
+            return Ok(None);
+        }
+        let boundaries = state.boundaries.as_deref().unwrap_or_default();
+        let Some(at) = boundaries.iter().rev().find(|boundary| **boundary <= timestamp).copied() else {
+            return Ok(None);
+        };
+
+        if let Some(index) = state.checkpoints.iter().position(|checkpoint| checkpoint.at == at) {
+            let checkpoint = state.checkpoints.remove(index);
+            state.checkpoints.push(checkpoint.clone());
+            return Ok(Some(checkpoint));
+        }
+        let (from, mut objects) = match state.checkpoints.iter().filter(|checkpoint| checkpoint.at < at).max_by_key(|checkpoint| checkpoint.at) {
+            Some(base) => (base.at, base.objects.clone()),
+            None => (DateTime::<Utc>::MIN_UTC, ObjectStates::new()),
+        };
+        let events = store.events_in_range(from, at)?;
+        replayed.fetch_add(events.len(), Ordering::Relaxed);
+        for event in &events {
+            apply_event(&mut objects, event);
+        }
+        let checkpoint = Arc::new(Checkpoint { at, objects });
+        state.checkpoints.push(checkpoint.clone());
+        if state.checkpoints.len() > self.config.max_checkpoints.max(1) {
+            state.checkpoints.remove(0);
+        }
+        Ok(Some(checkpoint))
+    }
+}
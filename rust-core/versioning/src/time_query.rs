@@ -1,11 +1,19 @@
 use crate::event_log::{EventLog, ObjectEvent};
+use crate::event_store::{EventStore, EventStoreError};
+use crate::snapshot_cache::{apply_event, Checkpoint, ObjectState, ObjectStates, SnapshotCache, SnapshotCacheConfig};
 use ontology_engine::PropertyMap;
 use chrono::{DateTime, Utc};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, RwLock};
 
 /// Time query - query objects at a specific point in time
 pub struct TimeQuery {
-    event_log: EventLog,
+    /// Locked so events can be appended while the history is shared with readers
+    store: RwLock<Box<dyn EventStore>>,
+    snapshot_cache: Option<SnapshotCache>,
+    /// Events applied while reconstructing, including those replayed into checkpoints
+    events_replayed: AtomicUsize,
 }
 
 /// Historical representation of an object
@@ -19,6 +27,17 @@ pub struct HistoricalObject {
     pub reconstructed_at: DateTime<Utc>,
 }
 
+/// A link as it existed at a specific time
+#[derive(Debug, Clone)]
+pub struct HistoricalLink {
+    pub link_type: String,
+    pub link_id: String,
+    pub source_id: String,
+    pub target_id: String,
+    pub properties: PropertyMap,
+    pub created_at: DateTime<Utc>,
+}
+
 /// Snapshot of the world at a specific time
 pub struct Snapshot {
     pub timestamp: DateTime<Utc>,
@@ -27,7 +46,61 @@ pub struct Snapshot {
 
 impl TimeQuery {
     pub fn new(event_log: EventLog) -> Self {
-        Self { event_log }
+        Self::with_store(Box::new(event_log))
+    }
+    
+    /// Query history kept in `store`, e.g. a `FileEventStore`
+    pub fn with_store(store: Box<dyn EventStore>) -> Self {
+        Self { store: RwLock::new(store), snapshot_cache: None, events_replayed: AtomicUsize::new(0) }
+    }
+    
+    /// Reconstruct from checkpoints of object state instead of from the first event
+    pub fn with_snapshot_cache(mut self, config: SnapshotCacheConfig) -> Self {
+        self.snapshot_cache = Some(SnapshotCache::new(config));
+        self
+    }
+    
+    pub fn snapshot_cache(&self) -> Option<&SnapshotCache> {
+        self.snapshot_cache.as_ref()
+    }
+    
+    /// Events applied by reconstructions so far
+    pub fn events_replayed(&self) -> usize {
+        self.events_replayed.load(Ordering::Relaxed)
+    }
+    
+    /// Add an event to the store, invalidating checkpoints it precedes
+    pub fn append(&self, event: ObjectEvent) -> Result<(), EventStoreError> {
+        if let Some(cache) = &self.snapshot_cache {
+            cache.record_appended(&event);
+        }
+        self.store.write().unwrap().append(event)
+    }
+    
+    /// The checkpoint nearest before `timestamp`, or `None` without a usable one
+    fn checkpoint_before(&self, timestamp: DateTime<Utc>) -> Option<Arc<Checkpoint>> {
+        let cache = self.snapshot_cache.as_ref()?;
+        self.read(|store| cache.checkpoint_before(store, timestamp, &self.events_replayed))
+    }
+    
+    fn historical_object(object_type: &str, object_id: &str, state: ObjectState) -> Option<HistoricalObject> {
+        (!state.removed).then(|| HistoricalObject {
+            object_type: object_type.to_string(),
+            object_id: object_id.to_string(),
+            properties: state.properties,
+            valid_from: state.valid_from,
+            valid_to: state.valid_to,
+            reconstructed_at: Utc::now(),
+        })
+    }
+    
+    /// Events read from the store by `query`; a failed read is reported and treated as no
+    /// events
+    fn read<T: Default>(&self, query: impl FnOnce(&dyn EventStore) -> Result<T, EventStoreError>) -> T {
+        query(self.store.read().unwrap().as_ref()).unwrap_or_else(|e| {
+            tracing::error!("reading the event store failed: {}", e);
+            T::default()
+        })
     }
     
     /// Reconstruct an object's state at a specific time
@@ -38,15 +111,31 @@ impl TimeQuery {
         timestamp: DateTime<Utc>,
     ) -> Option<HistoricalObject> {
         // Get all events for this object up to the timestamp
-        let events = self.event_log.get_object_events_at_time(
+        let mut events = self.read(|store| store.get_object_events_at_time(
             object_type,
             object_id,
             timestamp,
-        );
+        ));
+        events.sort_by_key(|e| (e.valid_from, e.timestamp));
+        
+        if let Some(checkpoint) = self.checkpoint_before(timestamp) {
+            // Only the events since the checkpoint are replayed
+            let key = (object_type.to_string(), object_id.to_string());
+            let mut objects = ObjectStates::new();
+            if let Some(state) = checkpoint.objects.get(&key) {
+                objects.insert(key.clone(), state.clone());
+            }
+            for event in events.iter().filter(|e| e.valid_from >= checkpoint.at) {
+                self.events_replayed.fetch_add(1, Ordering::Relaxed);
+                apply_event(&mut objects, event);
+            }
+            return objects.remove(&key).and_then(|state| Self::historical_object(object_type, object_id, state));
+        }
         
         if events.is_empty() {
             return None;
         }
+        self.events_replayed.fetch_add(events.len(), Ordering::Relaxed);
         
         // Reconstruct properties by applying events in order
         let mut properties = PropertyMap::new();
@@ -73,6 +162,17 @@ impl TimeQuery {
                     // Object was deleted, return None
                     return None;
                 }
+                crate::event_log::EventType::ObjectsMerged { winner_id, merged_properties, .. } => {
+                    // Losers were merged away; the winner takes the merged properties
+                    if winner_id != object_id {
+                        return None;
+                    }
+                    for (key, value) in merged_properties.iter() {
+                        properties.insert(key.clone(), value.clone());
+                    }
+                }
+                crate::event_log::EventType::LinkCreated { .. } |
+                crate::event_log::EventType::LinkDeleted { .. } => {}
             }
         }
         
@@ -101,8 +201,31 @@ impl TimeQuery {
     ) -> Snapshot {
         let mut objects = HashMap::new();
         
+        if let Some(checkpoint) = self.checkpoint_before(timestamp) {
+            // Start from the checkpoint and replay the events since
+            let selected = |object_type: &String| object_types.is_empty() || object_types.contains(object_type);
+            let mut states: ObjectStates = checkpoint.objects.iter()
+                .filter(|((object_type, _), _)| selected(object_type))
+                .map(|(key, state)| (key.clone(), state.clone()))
+                .collect();
+            let until = timestamp.checked_add_signed(chrono::Duration::nanoseconds(1)).unwrap_or(timestamp);
+            for event in self.read(|store| store.events_in_range(checkpoint.at, until)) {
+                self.events_replayed.fetch_add(1, Ordering::Relaxed);
+                apply_event(&mut states, &event);
+            }
+            for ((object_type, object_id), state) in states {
+                if !selected(&object_type) {
+                    continue;
+                }
+                if let Some(historical) = Self::historical_object(&object_type, &object_id, state) {
+                    objects.insert(format!("{}:{}", object_type, object_id), historical);
+                }
+            }
+            return Snapshot { timestamp, objects };
+        }
+        
         // Get all events at this time
-        let events = self.event_log.get_events_at_time(timestamp);
+        let events = self.read(|store| store.get_events_at_time(timestamp));
         
         // Group events by object
         let mut object_events: HashMap<(String, String), Vec<&ObjectEvent>> = HashMap::new();
@@ -114,6 +237,11 @@ impl TimeQuery {
                 crate::event_log::EventType::PropertyChanged { object_type, object_id, .. } => {
                     (object_type.clone(), object_id.clone())
                 }
+                crate::event_log::EventType::ObjectsMerged { object_type, winner_id, .. } => {
+                    (object_type.clone(), winner_id.clone())
+                }
+                crate::event_log::EventType::LinkCreated { .. } |
+                crate::event_log::EventType::LinkDeleted { .. } => continue,
             };
             
             object_events.entry(key).or_insert_with(Vec::new).push(event);
@@ -136,22 +264,89 @@ impl TimeQuery {
         }
     }
     
-    /// Reconstruct a graph of linked objects at a specific time
+    /// Links with `object_id` at either end as they stood at `timestamp`: created at or
+    /// before it and not deleted by then. An empty `link_types` means every type.
+    pub fn reconstruct_links(
+        &self,
+        object_id: &str,
+        link_types: &[String],
+        timestamp: DateTime<Utc>,
+    ) -> Vec<HistoricalLink> {
+        let mut links: HashMap<String, HistoricalLink> = HashMap::new();
+        for event in self.read(|store| store.get_link_events_for_object(object_id)) {
+            if event.valid_from > timestamp {
+                break;
+            }
+            match &event.event_type {
+                crate::event_log::EventType::LinkCreated { link_type, link_id, source_id, target_id, properties } => {
+                    if link_types.is_empty() || link_types.contains(link_type) {
+                        links.insert(link_id.clone(), HistoricalLink {
+                            link_type: link_type.clone(),
+                            link_id: link_id.clone(),
+                            source_id: source_id.clone(),
+                            target_id: target_id.clone(),
+                            properties: properties.clone(),
+                            created_at: event.valid_from,
+                        });
+                    }
+                }
+                crate::event_log::EventType::LinkDeleted { link_id, .. } => {
+                    links.remove(link_id);
+                }
+                _ => {}
+            }
+        }
+        
+        let mut links: Vec<HistoricalLink> = links.into_values().collect();
+        links.sort_by(|a, b| a.created_at.cmp(&b.created_at).then_with(|| a.link_id.cmp(&b.link_id)));
+        links
+    }
+    
+    /// Every link with `object_id` at either end as it stood at `timestamp`
+    pub fn get_links_at_time(&self, object_id: &str, timestamp: DateTime<Utc>) -> Vec<HistoricalLink> {
+        self.reconstruct_links(object_id, &[], timestamp)
+    }
+    
+    /// Reconstruct a graph of linked objects at a specific time: the start object and the
+    /// objects up to `max_hops` links away, following links of `link_type_ids` (every type if
+    /// empty) in either direction as they stood at `timestamp`. Objects that did not exist
+    /// then are left out and not traversed through. The start object comes first, then the
+    /// others in order of distance.
     pub fn reconstruct_graph(
         &self,
         start_object_type: &str,
         start_object_id: &str,
-        _link_type_ids: &[String],
-        _max_hops: usize,
+        link_type_ids: &[String],
+        max_hops: usize,
         timestamp: DateTime<Utc>,
     ) -> Vec<HistoricalObject> {
-        // This would require integration with the graph store's time-travel capabilities
-        // For now, this is a placeholder that reconstructs just the start object
-        if let Some(obj) = self.reconstruct_object(start_object_type, start_object_id, timestamp) {
-            vec![obj]
-        } else {
-            vec![]
+        let Some(start) = self.reconstruct_object(start_object_type, start_object_id, timestamp) else {
+            return vec![];
+        };
+        let mut visited: HashSet<String> = HashSet::from([start_object_id.to_string()]);
+        let mut frontier = vec![start_object_id.to_string()];
+        let mut objects = vec![start];
+        for _ in 0..max_hops {
+            let mut next = Vec::new();
+            for object_id in &frontier {
+                for link in self.reconstruct_links(object_id, link_type_ids, timestamp) {
+                    let neighbor = if &link.source_id == object_id { link.target_id } else { link.source_id };
+                    if !visited.insert(neighbor.clone()) {
+                        continue;
+                    }
+                    // Link events carry IDs only, so the neighbor's type is looked up
+                    let object = self.read(|store| store.object_types_for_id(&neighbor))
+                        .iter()
+                        .find_map(|object_type| self.reconstruct_object(object_type, &neighbor, timestamp));
+                    if let Some(object) = object {
+                        objects.push(object);
+                        next.push(neighbor);
+                    }
+                }
+            }
+            frontier = next;
         }
+        objects
     }
     
     /// Query objects by year/vintage - filters objects that have a 'year' property matching the criteria
@@ -203,6 +398,32 @@ impl TimeQuery {
             .collect()
     }
     
+    /// Objects of `object_type` as they were at `valid_at`, according to only the events
+    /// recorded on or before `as_known_at`. Corrections recorded later are ignored, so this
+    /// answers "what did we believe at `as_known_at` about `valid_at`".
+    pub fn query_bitemporal(
+        &self,
+        object_type: &str,
+        valid_at: DateTime<Utc>,
+        as_known_at: DateTime<Utc>,
+    ) -> Vec<HistoricalObject> {
+        let mut events: Vec<ObjectEvent> = self.read(|store| store.get_events_at_time(valid_at))
+            .into_iter()
+            .filter(|event| event.timestamp <= as_known_at)
+            .collect();
+        events.sort_by_key(|e| (e.valid_from, e.timestamp));
+        self.events_replayed.fetch_add(events.len(), Ordering::Relaxed);
+        
+        let mut states = ObjectStates::new();
+        for event in &events {
+            apply_event(&mut states, event);
+        }
+        states.into_iter()
+            .filter(|((state_type, _), _)| state_type == object_type)
+            .filter_map(|((object_type, object_id), state)| Self::historical_object(&object_type, &object_id, state))
+            .collect()
+    }
+    
     /// Query objects "as of" a specific date - useful for vintage-specific queries
     pub fn query_as_of_date(
         &self,
@@ -210,12 +431,7 @@ impl TimeQuery {
         as_of_date: DateTime<Utc>,
         year: Option<i64>,
     ) -> Vec<HistoricalObject> {
-        let snapshot = self.create_snapshot(as_of_date, &[object_type.to_string()]);
-        
-        let mut results: Vec<HistoricalObject> = snapshot.get_objects_by_type(object_type)
-            .into_iter()
-            .cloned()
-            .collect();
+        let mut results = self.query_bitemporal(object_type, as_of_date, Utc::now());
         
         // If year filter is provided, filter by year
         if let Some(filter_year) = year {
@@ -307,4 +523,281 @@ mod tests {
         let obj = snapshot.get_object("test_type", "test_id");
         assert!(obj.is_some());
     }
+    
+    #[test]
+    fn test_reconstruct_after_merge() {
+        let mut event_log = EventLog::new();
+        for (id, name) in [("a", "Ada"), ("b", "Ada L.")] {
+            let mut properties = PropertyMap::new();
+            properties.insert("name".to_string(), PropertyValue::String(name.to_string()));
+            event_log.record_created("person".to_string(), id.to_string(), properties, None);
+        }
+        let mut merged = PropertyMap::new();
+        merged.insert("name".to_string(), PropertyValue::String("Ada Lovelace".to_string()));
+        event_log.record_merged("person".to_string(), "a".to_string(), vec!["b".to_string()], merged, None);
+        
+        let time_query = TimeQuery::new(event_log);
+        let winner = time_query.reconstruct_object("person", "a", Utc::now()).unwrap();
+        assert_eq!(winner.properties.get("name"), Some(&PropertyValue::String("Ada Lovelace".to_string())));
+        assert!(time_query.reconstruct_object("person", "b", Utc::now()).is_none());
+    }
+    
+    #[test]
+    fn test_reconstruct_links() {
+        let at = |year: i32| chrono::TimeZone::with_ymd_and_hms(&Utc, year, 1, 1, 0, 0, 0).unwrap();
+        let mut event_log = EventLog::new();
+        let link = |link_id: &str, source_id: &str| crate::event_log::EventType::LinkCreated {
+            link_type: "supplies".to_string(),
+            link_id: link_id.to_string(),
+            source_id: source_id.to_string(),
+            target_id: "plant".to_string(),
+            properties: PropertyMap::new(),
+        };
+        event_log.record_at(link("l1", "acme"), at(2016), None);
+        event_log.record_at(link("l2", "globex"), at(2018), None);
+        event_log.record_at(
+            crate::event_log::EventType::LinkDeleted {
+                link_type: "supplies".to_string(),
+                link_id: "l1".to_string(),
+                source_id: "acme".to_string(),
+                target_id: "plant".to_string(),
+            },
+            at(2019),
+            None,
+        );
+        
+        let time_query = TimeQuery::new(event_log);
+        let ids = |year: i32| -> Vec<String> {
+            time_query.reconstruct_links("plant", &[], at(year)).into_iter().map(|l| l.link_id).collect()
+        };
+        assert!(ids(2015).is_empty());
+        assert_eq!(ids(2017), vec!["l1".to_string()]);
+        assert_eq!(ids(2018), vec!["l1".to_string(), "l2".to_string()]);
+        assert_eq!(ids(2020), vec!["l2".to_string()]);
+        assert!(time_query.reconstruct_links("plant", &["owns".to_string()], at(2020)).is_empty());
+    }
+    
+    #[test]
+    fn test_reconstruct_graph_follows_historical_links() {
+        let at = |year: i32| chrono::TimeZone::with_ymd_and_hms(&Utc, year, 1, 1, 0, 0, 0).unwrap();
+        let mut event_log = EventLog::new();
+        for (object_type, object_id) in [("plant", "p1"), ("supplier", "acme"), ("supplier", "globex"), ("region", "ne")] {
+            event_log.record_at(
+                crate::event_log::EventType::ObjectCreated {
+                    object_type: object_type.to_string(),
+                    object_id: object_id.to_string(),
+                    properties: PropertyMap::new(),
+                },
+                at(2010),
+                None,
+            );
+        }
+        let link = |link_type: &str, link_id: &str, source_id: &str, target_id: &str| crate::event_log::EventType::LinkCreated {
+            link_type: link_type.to_string(),
+            link_id: link_id.to_string(),
+            source_id: source_id.to_string(),
+            target_id: target_id.to_string(),
+            properties: PropertyMap::new(),
+        };
+        event_log.record_at(link("supplies", "l1", "acme", "p1"), at(2016), None);
+        event_log.record_at(link("supplies", "l2", "globex", "p1"), at(2017), None);
+        event_log.record_at(link("located_in", "l3", "globex", "ne"), at(2017), None);
+        event_log.record_at(
+            crate::event_log::EventType::LinkDeleted {
+                link_type: "supplies".to_string(),
+                link_id: "l1".to_string(),
+                source_id: "acme".to_string(),
+                target_id: "p1".to_string(),
+            },
+            at(2019),
+            None,
+        );
+        
+        let time_query = TimeQuery::new(event_log);
+        let ids = |max_hops: usize, link_types: &[String], year: i32| -> Vec<String> {
+            time_query
+                .reconstruct_graph("plant", "p1", link_types, max_hops, at(year))
+                .into_iter()
+                .map(|object| format!("{}:{}", object.object_type, object.object_id))
+                .collect()
+        };
+        // Between the creations and the deletion both links are present
+        assert_eq!(ids(1, &[], 2018), vec!["plant:p1", "supplier:acme", "supplier:globex"]);
+        assert_eq!(ids(2, &[], 2018), vec!["plant:p1", "supplier:acme", "supplier:globex", "region:ne"]);
+        assert_eq!(ids(2, &["supplies".to_string()], 2018), vec!["plant:p1", "supplier:acme", "supplier:globex"]);
+        assert_eq!(ids(1, &[], 2020), vec!["plant:p1", "supplier:globex"]);
+        assert_eq!(ids(0, &[], 2018), vec!["plant:p1"]);
+        assert_eq!(time_query.get_links_at_time("p1", at(2018)).len(), 2);
+        assert_eq!(time_query.get_links_at_time("p1", at(2020)).len(), 1);
+        assert!(ids(1, &[], 2005).is_empty());
+    }
+    
+    /// Plants created, updated, merged and deleted over a month, one event per hour
+    fn plant_history() -> EventLog {
+        let start = chrono::TimeZone::with_ymd_and_hms(&Utc, 2020, 1, 1, 0, 0, 0).unwrap();
+        let mut event_log = EventLog::new();
+        for i in 0..720i64 {
+            let object_id = format!("p{}", i % 40);
+            let mut properties = PropertyMap::new();
+            properties.insert("output".to_string(), PropertyValue::Integer(i));
+            let event_type = match i {
+                i if i < 40 => crate::event_log::EventType::ObjectCreated { object_type: "plant".to_string(), object_id, properties },
+                i if i % 97 == 0 => crate::event_log::EventType::ObjectDeleted { object_type: "plant".to_string(), object_id },
+                i if i % 131 == 0 => crate::event_log::EventType::ObjectsMerged {
+                    object_type: "plant".to_string(),
+                    winner_id: object_id,
+                    loser_ids: vec![format!("p{}", (i + 1) % 40)],
+                    merged_properties: properties,
+                },
+                i if i % 5 == 0 => crate::event_log::EventType::PropertyChanged {
+                    object_type: "plant".to_string(),
+                    object_id,
+                    property_name: "status".to_string(),
+                    old_value: None,
+                    new_value: PropertyValue::String(format!("s{}", i)),
+                },
+                _ => crate::event_log::EventType::ObjectUpdated { object_type: "plant".to_string(), object_id, changed_properties: properties },
+            };
+            event_log.record_at(event_type, start + chrono::Duration::hours(i), None);
+        }
+        event_log
+    }
+    
+    fn assert_same_objects(a: Option<HistoricalObject>, b: Option<HistoricalObject>) {
+        let comparable = |object: Option<HistoricalObject>| {
+            object.map(|o| (o.object_id, o.valid_from, serde_json::to_value(&o.properties).unwrap()))
+        };
+        assert_eq!(comparable(a), comparable(b));
+    }
+    
+    #[test]
+    fn test_checkpointed_reconstruction_agrees() {
+        let config = SnapshotCacheConfig { every_events: Some(97), daily: true, max_checkpoints: 3 };
+        let mut plain = TimeQuery::new(plant_history());
+        let mut checkpointed = TimeQuery::new(plant_history()).with_snapshot_cache(config);
+        let start = chrono::TimeZone::with_ymd_and_hms(&Utc, 2020, 1, 1, 0, 0, 0).unwrap();
+        
+        let compare = |plain: &TimeQuery, checkpointed: &TimeQuery| {
+            for hours in [0, 30, 97, 250, 251, 500, 719, 800] {
+                let at = start + chrono::Duration::hours(hours) + chrono::Duration::minutes(30);
+                for i in 0..40 {
+                    let object_id = format!("p{}", i);
+                    assert_same_objects(
+                        plain.reconstruct_object("plant", &object_id, at),
+                        checkpointed.reconstruct_object("plant", &object_id, at),
+                    );
+                }
+                let expected = plain.create_snapshot(at, &[]);
+                let actual = checkpointed.create_snapshot(at, &["plant".to_string()]);
+                assert_eq!(expected.objects.len(), actual.objects.len(), "at {}", at);
+                for (key, object) in expected.objects {
+                    assert_same_objects(Some(object), actual.objects.get(&key).cloned());
+                }
+            }
+        };
+        compare(&plain, &checkpointed);
+        assert_eq!(checkpointed.snapshot_cache().unwrap().len(), 3);
+        
+        // An event inserted before existing checkpoints invalidates them
+        let mut properties = PropertyMap::new();
+        properties.insert("output".to_string(), PropertyValue::Integer(-1));
+        let late = crate::event_log::ObjectEvent::at(
+            crate::event_log::EventType::ObjectUpdated { object_type: "plant".to_string(), object_id: "p3".to_string(), changed_properties: properties },
+            start + chrono::Duration::hours(45),
+            None,
+        );
+        plain.append(late.clone()).unwrap();
+        checkpointed.append(late).unwrap();
+        assert!(checkpointed.snapshot_cache().unwrap().len() < 3);
+        compare(&plain, &checkpointed);
+    }
+    
+    #[test]
+    fn test_checkpoints_bound_replay() {
+        // 100 plants with 1,000 events each
+        let start = chrono::TimeZone::with_ymd_and_hms(&Utc, 2020, 1, 1, 0, 0, 0).unwrap();
+        let history = || {
+            let mut event_log = EventLog::new();
+            for i in 0..100_000i64 {
+                let object_id = format!("p{}", i % 100);
+                let mut properties = PropertyMap::new();
+                properties.insert("output".to_string(), PropertyValue::Integer(i));
+                let event_type = if i < 100 {
+                    crate::event_log::EventType::ObjectCreated { object_type: "plant".to_string(), object_id, properties }
+                } else {
+                    crate::event_log::EventType::ObjectUpdated { object_type: "plant".to_string(), object_id, changed_properties: properties }
+                };
+                event_log.record_at(event_type, start + chrono::Duration::seconds(i), None);
+            }
+            event_log
+        };
+        let plain = TimeQuery::new(history());
+        let config = SnapshotCacheConfig { every_events: Some(1_000), ..Default::default() };
+        let checkpointed = TimeQuery::new(history()).with_snapshot_cache(config);
+        
+        let times: Vec<DateTime<Utc>> = (0..5).map(|i| start + chrono::Duration::seconds(99_000 + i * 150)).collect();
+        for at in &times {
+            let expected = plain.create_snapshot(*at, &[]);
+            let actual = checkpointed.create_snapshot(*at, &[]);
+            assert_eq!(expected.objects.len(), 100);
+            let output = |snapshot: &Snapshot| snapshot.get_object("plant", "p7").unwrap().properties.get("output").cloned();
+            assert_eq!(output(&expected), output(&actual));
+        }
+        // The first query builds the checkpoint; after that each replays fewer than 1,000
+        // events instead of about 100,000
+        assert!(plain.events_replayed() > 5 * 99_000);
+        assert!(checkpointed.events_replayed() < 100_000 + 5 * 1_000, "{}", checkpointed.events_replayed());
+        
+        let before = checkpointed.events_replayed();
+        checkpointed.reconstruct_object("plant", "p7", times[4]).unwrap();
+        assert!(checkpointed.events_replayed() - before <= 20);
+        let before = plain.events_replayed();
+        plain.reconstruct_object("plant", "p7", times[4]).unwrap();
+        assert_eq!(plain.events_replayed() - before, 996);
+    }
+    
+    #[test]
+    fn test_bitemporal_query_ignores_later_corrections() {
+        use crate::event_log::EventType;
+        use chrono::TimeZone;
+        
+        let census_day = Utc.with_ymd_and_hms(2020, 4, 1, 0, 0, 0).unwrap();
+        let published = Utc.with_ymd_and_hms(2021, 8, 12, 0, 0, 0).unwrap();
+        let corrected = Utc.with_ymd_and_hms(2023, 3, 1, 0, 0, 0).unwrap();
+        let population = |value| {
+            let mut properties = PropertyMap::new();
+            properties.insert("population".to_string(), PropertyValue::Integer(value));
+            properties
+        };
+        
+        let mut original = ObjectEvent::at(
+            EventType::ObjectCreated { object_type: "county".to_string(), object_id: "06001".to_string(), properties: population(1_682_353) },
+            census_day,
+            None,
+        );
+        original.timestamp = published;
+        let mut correction = ObjectEvent::at(
+            EventType::ObjectUpdated { object_type: "county".to_string(), object_id: "06001".to_string(), changed_properties: population(1_682_400) },
+            census_day,
+            None,
+        );
+        correction.timestamp = corrected;
+        let mut event_log = EventLog::new();
+        // The correction is recorded before the original is replayed, to check ordering
+        event_log.record(correction);
+        event_log.record(original);
+        let time_query = TimeQuery::new(event_log);
+        
+        let population_of = |objects: Vec<HistoricalObject>| match objects.as_slice() {
+            [county] => county.properties.get("population").cloned(),
+            other => panic!("expected one county, got {}", other.len()),
+        };
+        let valid_at = Utc.with_ymd_and_hms(2022, 1, 1, 0, 0, 0).unwrap();
+        let believed_then = time_query.query_bitemporal("county", valid_at, Utc.with_ymd_and_hms(2022, 1, 1, 0, 0, 0).unwrap());
+        assert_eq!(population_of(believed_then), Some(PropertyValue::Integer(1_682_353)));
+        let believed_now = time_query.query_bitemporal("county", valid_at, Utc::now());
+        assert_eq!(population_of(believed_now), Some(PropertyValue::Integer(1_682_400)));
+        assert!(time_query.query_bitemporal("county", valid_at, Utc.with_ymd_and_hms(2021, 1, 1, 0, 0, 0).unwrap()).is_empty());
+        assert_eq!(population_of(time_query.query_as_of_date("county", valid_at, None)), Some(PropertyValue::Integer(1_682_400)));
+    }
 }
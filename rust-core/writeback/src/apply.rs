@@ -0,0 +1,229 @@
+use crate::merge::{merge_and_materialize, ConflictResolutionStrategy, MergeResult};
+use crate::queue::UserEdit;
+use indexing::read_modify_write;
+use indexing::store::{SearchStore, StoreError};
+use ontology_engine::ObjectType;
+use std::collections::BTreeSet;
+
+/// Outcome of applying edits to the stored object
+#[derive(Debug, Clone)]
+pub struct AppliedEdits {
+    /// Revision of the object after the write
+    pub revision: u64,
+    pub merge: MergeResult,
+}
+
+/// Apply user edits to the object in the search store.
+///
+/// The edits are merged over the stored properties and write-time computed properties are
+/// recomputed. Edits that conflict with the stored source values are settled by `strategy`;
+/// with `Manual` they are left out and returned as pending in `merge`. Edited values must pass their property's rules, custom validators included,
+/// or nothing is written (`StoreError::Validation`). The result is written only if the
+/// object is still at the revision that was read. If a sync refresh (or another apply) wrote the object in between, this returns
+/// `StoreError::Conflict` with the refreshed object and nothing is written, so the caller
+/// can rebase the edits onto the new source values and apply again.
+pub async fn apply_edits(
+    search: &dyn SearchStore,
+    object_type: &ObjectType,
+    object_id: &str,
+    edits: &[UserEdit],
+    strategy: ConflictResolutionStrategy,
+) -> Result<AppliedEdits, StoreError> {
+    let mut merge = None;
+    let written = read_modify_write(search, &object_type.id, object_id, |current| {
+        let current = current.ok_or_else(|| {
+            StoreError::NotFound(format!("{} '{}'", object_type.id, object_id))
+        })?;
+        let result = merge_and_materialize(object_type, &current.properties, edits, strategy);
+        let properties = result.merged_properties.clone();
+        let errors = invalid_edits(object_type, edits, &result);
+        if !errors.is_empty() {
+            return Err(StoreError::Validation(format!(
+                "Invalid edits to {} '{}': {}",
+                object_type.id,
+                object_id,
+                errors.join("; ")
+            )));
+        }
+        merge = Some(result);
+        Ok(properties)
+    })
+    .await?;
+
+    Ok(AppliedEdits {
+        revision: written.revision,
+        merge: merge.expect("merge runs before every successful write"),
+    })
+}
+
+/// Problems with the merged values of applied edits to properties the object type declares
+fn invalid_edits(object_type: &ObjectType, edits: &[UserEdit], result: &MergeResult) -> Vec<String> {
+    let merged = &result.merged_properties;
+    let edited: BTreeSet<&str> = edits
+        .iter()
+        .filter(|e| !e.deleted && result.overridden_properties.contains(&e.property_name))
+        .map(|e| e.property_name.as_str())
+        .collect();
+    edited
+        .into_iter()
+        .filter_map(|name| {
+            let property = object_type.get_property(name)?;
+            let value = merged.get(name).filter(|v| !v.is_null())?;
+            property.validate_value_with_siblings(value, merged).err()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use crate::queue::EditStatus;
+    use chrono::Utc;
+    use indexing::store::{Filter, IndexedObject, SearchQuery};
+    use indexing::InMemorySearchStore;
+    use ontology_engine::{PropertyMap, PropertyValue};
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    /// Search store where a sync refresh lands right after the first read
+    #[derive(Default)]
+    struct RacingStore {
+        inner: InMemorySearchStore,
+        raced: AtomicBool,
+    }
+
+    #[async_trait]
+    impl SearchStore for RacingStore {
+        async fn index_object(
+            &self,
+            object_type: &str,
+            object_id: &str,
+            properties: &PropertyMap,
+            expected_revision: Option<u64>,
+        ) -> Result<u64, StoreError> {
+            self.inner.index_object(object_type, object_id, properties, expected_revision).await
+        }
+
+        async fn search(&self, object_type: &str, query: &SearchQuery) -> Result<Vec<IndexedObject>, StoreError> {
+            self.inner.search(object_type, query).await
+        }
+
+        async fn get_object(&self, object_type: &str, object_id: &str) -> Result<Option<IndexedObject>, StoreError> {
+            let object = self.inner.get_object(object_type, object_id).await?;
+            if let Some(read) = &object {
+                if !self.raced.swap(true, Ordering::SeqCst) {
+                    let mut refreshed = read.properties.clone();
+                    refreshed.insert("population".to_string(), PropertyValue::Integer(2000));
+                    self.inner.index_object(object_type, object_id, &refreshed, None).await?;
+                }
+            }
+            Ok(object)
+        }
+
+        async fn bulk_index(&self, objects: Vec<IndexedObject>) -> Result<(), StoreError> {
+            self.inner.bulk_index(objects).await
+        }
+
+        async fn delete_object(&self, object_type: &str, object_id: &str) -> Result<(), StoreError> {
+            self.inner.delete_object(object_type, object_id).await
+        }
+
+        async fn count_objects(&self, object_type: &str, filters: Option<&[Filter]>) -> Result<u64, StoreError> {
+            self.inner.count_objects(object_type, filters).await
+        }
+    }
+
+    fn city() -> ObjectType {
+        serde_json::from_value(serde_json::json!({
+            "id": "city",
+            "displayName": "City",
+            "primaryKey": "id",
+            "properties": [
+                { "id": "id", "type": "string" },
+                { "id": "name", "type": "string" },
+                { "id": "population", "type": "integer" }
+            ]
+        }))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_apply_edits_conflicts_with_concurrent_refresh() {
+        let store = RacingStore::default();
+        let mut source = PropertyMap::new();
+        source.insert("name".to_string(), PropertyValue::String("Springfield".to_string()));
+        source.insert("population".to_string(), PropertyValue::Integer(1000));
+        store.inner.index_object("city", "c1", &source, None).await.unwrap();
+
+        let edit = UserEdit {
+            edit_id: "edit1".to_string(),
+            object_type: "city".to_string(),
+            object_id: "c1".to_string(),
+            property_name: "name".to_string(),
+            property_value: PropertyValue::String("Shelbyville".to_string()),
+            user_id: "user1".to_string(),
+            timestamp: Utc::now(),
+            deleted: false,
+            base_value: Some(PropertyValue::String("Springfield".to_string())),
+            comment: None,
+            status: EditStatus::Pending,
+            resolved_at: None,
+        };
+
+        // The refresh wins; the apply sees the refreshed object in the conflict
+        match apply_edits(&store, &city(), "c1", std::slice::from_ref(&edit), ConflictResolutionStrategy::EditWins).await {
+            Err(StoreError::Conflict(conflict)) => {
+                assert_eq!(conflict.expected_revision, 1);
+                assert_eq!(conflict.current_revision, 2);
+                let current = conflict.current.unwrap();
+                assert_eq!(current.properties.get("population"), Some(&PropertyValue::Integer(2000)));
+                assert_eq!(current.properties.get("name"), Some(&PropertyValue::String("Springfield".to_string())));
+            }
+            other => panic!("expected a conflict, got {:?}", other),
+        }
+
+        // Rebasing onto the refreshed object keeps both changes
+        let applied = apply_edits(&store, &city(), "c1", &[edit], ConflictResolutionStrategy::EditWins).await.unwrap();
+        assert_eq!(applied.revision, 3);
+        let stored = store.get_object("city", "c1").await.unwrap().unwrap();
+        assert_eq!(stored.properties.get("name"), Some(&PropertyValue::String("Shelbyville".to_string())));
+        assert_eq!(stored.properties.get("population"), Some(&PropertyValue::Integer(2000)));
+    }
+
+    #[tokio::test]
+    async fn test_apply_edits_rejects_values_failing_a_custom_validator() {
+        let store = InMemorySearchStore::new();
+        let mut source = PropertyMap::new();
+        source.insert("name".to_string(), PropertyValue::String("Springfield".to_string()));
+        store.index_object("city", "c1", &source, None).await.unwrap();
+
+        let mut city = city();
+        let mut website: ontology_engine::Property =
+            serde_json::from_value(serde_json::json!({ "id": "website", "type": "string" })).unwrap();
+        website.custom_validator = Some(ontology_engine::URL_VALIDATOR.to_string());
+        city.properties.push(website);
+        let edit = |value: &str| UserEdit {
+            edit_id: "edit1".to_string(),
+            object_type: "city".to_string(),
+            object_id: "c1".to_string(),
+            property_name: "website".to_string(),
+            property_value: PropertyValue::String(value.to_string()),
+            user_id: "user1".to_string(),
+            timestamp: Utc::now(),
+            deleted: false,
+            base_value: None,
+            comment: None,
+            status: EditStatus::Pending,
+            resolved_at: None,
+        };
+
+        match apply_edits(&store, &city, "c1", &[edit("springfield dot gov")], ConflictResolutionStrategy::EditWins).await {
+            Err(StoreError::Validation(message)) => assert!(message.contains("Property 'website'"), "{}", message),
+            other => panic!("expected a validation error, got {:?}", other),
+        }
+        assert_eq!(store.get_object("city", "c1").await.unwrap().unwrap().revision, 1);
+
+        let applied = apply_edits(&store, &city, "c1", &[edit("https://springfield.gov")], ConflictResolutionStrategy::EditWins).await.unwrap();
+        assert_eq!(applied.revision, 2);
+    }
+}
@@ -0,0 +1,56 @@
+use crate::merge::{MergeResult, PropertyConflict};
+use std::sync::Mutex;
+
+/// Conflicts waiting for manual resolution, keyed by edit ID. Merges with
+/// `ConflictResolutionStrategy::Manual` add theirs with `record`; a resolver takes one out
+/// with `take` and applies or drops its edit.
+#[derive(Default)]
+pub struct PendingConflicts {
+    conflicts: Mutex<Vec<PropertyConflict>>,
+}
+
+impl PendingConflicts {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the pending conflicts of a merge, replacing any earlier conflict of the same edit
+    pub fn record(&self, merge: &MergeResult) {
+        let mut conflicts = self.conflicts.lock().unwrap();
+        for conflict in merge.pending_conflicts() {
+            conflicts.retain(|pending| pending.edit.edit_id != conflict.edit.edit_id);
+            conflicts.push(conflict.clone());
+        }
+    }
+
+    /// Pending conflicts, oldest first, optionally only those of one object type
+    pub fn list(&self, object_type: Option<&str>) -> Vec<PropertyConflict> {
+        self.conflicts
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|conflict| object_type.is_none_or(|object_type| conflict.edit.object_type == object_type))
+            .cloned()
+            .collect()
+    }
+
+    /// The conflict of an edit
+    pub fn get(&self, edit_id: &str) -> Option<PropertyConflict> {
+        self.conflicts.lock().unwrap().iter().find(|conflict| conflict.edit.edit_id == edit_id).cloned()
+    }
+
+    /// Remove and return the conflict of an edit
+    pub fn take(&self, edit_id: &str) -> Option<PropertyConflict> {
+        let mut conflicts = self.conflicts.lock().unwrap();
+        let index = conflicts.iter().position(|conflict| conflict.edit.edit_id == edit_id)?;
+        Some(conflicts.remove(index))
+    }
+
+    pub fn len(&self) -> usize {
+        self.conflicts.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
@@ -1,8 +1,15 @@
 pub mod queue;
 pub mod merge;
-
-pub use queue::{WriteBackQueue, UserEdit};
-pub use merge::{merge_source_and_edits, MergeResult};
+pub mod apply;
+pub mod conflicts;
+
+pub use queue::{EditQueue, EditQueueError, EditStatus, InMemoryEditQueue, UserEdit, WriteBackQueue};
+pub use merge::{
+    merge_and_materialize, merge_source_and_edits, ConflictKind, ConflictResolution, ConflictResolutionStrategy,
+    MergeResult, PropertyConflict,
+};
+pub use apply::{apply_edits, AppliedEdits};
+pub use conflicts::PendingConflicts;
 
 
 
@@ -1,58 +1,105 @@
 use crate::queue::UserEdit;
-use ontology_engine::PropertyMap;
+use ontology_engine::{ComputedPropertyMaterializer, ObjectType, PropertyMap, PropertyValue};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
 
-/// Merge source data with user edits (overlay architecture)
-pub fn merge_source_and_edits(source_properties: &PropertyMap, edits: &[UserEdit]) -> MergeResult {
+/// How a conflict between a user edit and the source is settled
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictResolutionStrategy {
+    /// Keep the refreshed source value and drop the edit
+    SourceWins,
+    /// Apply the edit over the refreshed source value
+    #[default]
+    EditWins,
+    /// Keep the source value and leave the conflict pending until someone resolves it
+    Manual,
+}
+
+/// Why an edit conflicts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictKind {
+    /// The source changed (or removed) the property since the value the user edited
+    SourceChanged,
+    /// Users edited the property without seeing each other's edits
+    ConcurrentEdits,
+}
+
+/// What became of a conflicting edit
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictResolution {
+    SourceKept,
+    EditApplied,
+    /// Waiting for `ConflictResolutionStrategy::Manual` resolution; the source value is kept meanwhile
+    Pending,
+}
+
+/// The value an edit sets; `None` if it deletes the property
+fn edited_value(edit: &UserEdit) -> Option<&PropertyValue> {
+    (!edit.deleted).then_some(&edit.property_value)
+}
+
+/// Merge source data with user edits (overlay architecture).
+///
+/// The latest edit to each property is applied unless it conflicts: the source changed the
+/// property from the value the first edit was based on to something other than the edited
+/// value, or a user edited it without seeing another user's earlier edit. Conflicts are settled
+/// by `strategy`; among concurrent edits the last writer wins unless `strategy` is `Manual`.
+pub fn merge_source_and_edits(
+    source_properties: &PropertyMap,
+    edits: &[UserEdit],
+    strategy: ConflictResolutionStrategy,
+) -> MergeResult {
     let mut merged = source_properties.clone();
-    let mut overridden_properties = std::collections::HashSet::new();
+    let mut overridden_properties = HashSet::new();
     let mut conflicts = Vec::new();
 
-    // Apply edits in chronological order (oldest first)
-    // In practice, we'd want the most recent edit for each property
-    let mut edits_by_property: std::collections::HashMap<String, &UserEdit> =
-        std::collections::HashMap::new();
+    let mut edits_by_property: BTreeMap<&str, Vec<&UserEdit>> = BTreeMap::new();
     for edit in edits {
-        if edit.deleted {
-            // Mark property as deleted
-            // Would need a way to track deleted properties; no-op for now
-            overridden_properties.insert(edit.property_name.clone());
-            edits_by_property.insert(edit.property_name.clone(), edit);
-        } else {
-            // Use the most recent edit for each property
-            let existing = edits_by_property.get(&edit.property_name);
-            if existing.map_or(true, |e| edit.timestamp > e.timestamp) {
-                edits_by_property.insert(edit.property_name.clone(), edit);
-            }
-        }
+        edits_by_property.entry(&edit.property_name).or_default().push(edit);
     }
 
-    // Apply the most recent edit for each property
-    for (property_name, edit) in edits_by_property {
-        if edit.deleted {
-            // Remove the property (in a real implementation, we'd track this differently)
-            // For now, we'll leave it but mark as overridden
-            overridden_properties.insert(property_name.clone());
+    for (property_name, mut property_edits) in edits_by_property {
+        // Oldest first; the latest edit is the one applied
+        property_edits.sort_by_key(|edit| edit.timestamp);
+        let latest = *property_edits.last().expect("grouped edits are non-empty");
+        let source_value = source_properties.get(property_name);
+
+        let source_changed = source_value != property_edits[0].base_value.as_ref() && source_value != edited_value(latest);
+        let concurrent = property_edits.windows(2).any(|pair| {
+            pair[0].user_id != pair[1].user_id && pair[1].base_value.as_ref() != edited_value(pair[0])
+        });
+        let kind = if source_changed {
+            Some(ConflictKind::SourceChanged)
+        } else if concurrent {
+            Some(ConflictKind::ConcurrentEdits)
         } else {
-            // Check if source had this property
-            let had_source_value = source_properties.contains_key(&property_name);
-
-            // Apply the edit
-            merged.insert(property_name.clone(), edit.property_value.clone());
-            overridden_properties.insert(property_name.clone());
-
-            // If source had a different value, record as conflict
-            if had_source_value {
-                if let Some(source_value) = source_properties.get(&property_name) {
-                    if source_value != &edit.property_value {
-                        conflicts.push(PropertyConflict {
-                            property_name: property_name.clone(),
-                            source_value: source_value.clone(),
-                            edited_value: edit.property_value.clone(),
-                            edit_timestamp: edit.timestamp,
-                        });
-                    }
-                }
+            None
+        };
+        let resolution = match (kind, strategy) {
+            (None, _) => ConflictResolution::EditApplied,
+            (Some(_), ConflictResolutionStrategy::Manual) => ConflictResolution::Pending,
+            (Some(ConflictKind::SourceChanged), ConflictResolutionStrategy::SourceWins) => ConflictResolution::SourceKept,
+            (Some(_), _) => ConflictResolution::EditApplied,
+        };
+
+        if resolution == ConflictResolution::EditApplied {
+            // A deleted property stays in the merged map; it is only marked as overridden
+            if !latest.deleted {
+                merged.insert(property_name.to_string(), latest.property_value.clone());
             }
+            overridden_properties.insert(property_name.to_string());
+        }
+        if let Some(kind) = kind {
+            conflicts.push(PropertyConflict {
+                property_name: property_name.to_string(),
+                kind,
+                source_value: source_value.cloned(),
+                edit: latest.clone(),
+                resolution,
+            });
         }
     }
 
@@ -63,28 +110,73 @@ pub fn merge_source_and_edits(source_properties: &PropertyMap, edits: &[UserEdit
     }
 }
 
+/// Merge source data with user edits, then recompute the object type's write-time
+/// computed properties so stored values reflect the edited inputs
+pub fn merge_and_materialize(
+    object_type: &ObjectType,
+    source_properties: &PropertyMap,
+    edits: &[UserEdit],
+    strategy: ConflictResolutionStrategy,
+) -> MergeResult {
+    let mut result = merge_source_and_edits(source_properties, edits, strategy);
+    for (property, error) in ComputedPropertyMaterializer::materialize(
+        object_type,
+        &mut result.merged_properties,
+        chrono::Utc::now(),
+    ) {
+        tracing::error!(
+            "Failed to materialize computed property '{}' on '{}': {}",
+            property, object_type.id, error
+        );
+    }
+    result
+}
+
 /// Result of merging source data with edits
 #[derive(Debug, Clone)]
 pub struct MergeResult {
     pub merged_properties: PropertyMap,
-    pub overridden_properties: std::collections::HashSet<String>,
+    pub overridden_properties: HashSet<String>,
+    /// Every conflicting edit, however it was resolved
     pub conflicts: Vec<PropertyConflict>,
 }
 
-/// A conflict between source value and edited value
-#[derive(Debug, Clone)]
+impl MergeResult {
+    /// Whether every edit merged without a conflict
+    pub fn is_clean(&self) -> bool {
+        self.conflicts.is_empty()
+    }
+
+    /// Conflicts left for manual resolution
+    pub fn pending_conflicts(&self) -> impl Iterator<Item = &PropertyConflict> {
+        self.conflicts.iter().filter(|conflict| conflict.is_pending())
+    }
+}
+
+/// A user edit that conflicts with the source or with another user's edit
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PropertyConflict {
     pub property_name: String,
-    pub source_value: ontology_engine::PropertyValue,
-    pub edited_value: ontology_engine::PropertyValue,
-    pub edit_timestamp: chrono::DateTime<chrono::Utc>,
+    pub kind: ConflictKind,
+    /// The refreshed source value; `None` if the source no longer has the property
+    pub source_value: Option<PropertyValue>,
+    /// The latest edit to the property
+    pub edit: UserEdit,
+    pub resolution: ConflictResolution,
+}
+
+impl PropertyConflict {
+    /// Whether the conflict still waits for manual resolution
+    pub fn is_pending(&self) -> bool {
+        self.resolution == ConflictResolution::Pending
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::queue::EditStatus;
     use chrono::Utc;
-    use ontology_engine::PropertyValue;
 
     #[test]
     fn test_merge_source_and_edits() {
@@ -107,9 +199,14 @@ mod tests {
             user_id: "user1".to_string(),
             timestamp: Utc::now(),
             deleted: false,
+            base_value: Some(PropertyValue::String("source_value".to_string())),
+            comment: None,
+            status: EditStatus::Pending,
+            resolved_at: None,
         };
 
-        let result = merge_source_and_edits(&source, &[edit]);
+        let result = merge_source_and_edits(&source, &[edit], ConflictResolutionStrategy::Manual);
+        assert!(result.is_clean());
 
         // Check that prop1 was overridden
         assert!(result.overridden_properties.contains("prop1"));
@@ -127,29 +224,146 @@ mod tests {
         );
     }
 
+    fn edit(edit_id: &str, user_id: &str, value: Option<&str>, base_value: Option<&str>) -> UserEdit {
+        let string = |value: &str| PropertyValue::String(value.to_string());
+        UserEdit {
+            edit_id: edit_id.to_string(),
+            object_type: "test".to_string(),
+            object_id: "test_id".to_string(),
+            property_name: "prop1".to_string(),
+            property_value: value.map_or(PropertyValue::Null, string),
+            user_id: user_id.to_string(),
+            timestamp: Utc::now(),
+            deleted: value.is_none(),
+            base_value: base_value.map(string),
+            comment: None,
+            status: EditStatus::Pending,
+            resolved_at: None,
+        }
+    }
+
+    fn source(value: Option<&str>) -> PropertyMap {
+        let mut source = PropertyMap::new();
+        if let Some(value) = value {
+            source.insert("prop1".to_string(), PropertyValue::String(value.to_string()));
+        }
+        source
+    }
+
     #[test]
     fn test_merge_with_conflict() {
+        // The source moved from what the user saw to something else
+        let edits = [edit("edit1", "user1", Some("edited_value"), Some("base_value"))];
+        let prop1 = |result: &MergeResult| result.merged_properties.get("prop1").cloned();
+
+        let result = merge_source_and_edits(&source(Some("source_value")), &edits, ConflictResolutionStrategy::EditWins);
+        assert!(!result.is_clean());
+        assert_eq!(result.conflicts[0].property_name, "prop1");
+        assert_eq!(result.conflicts[0].kind, ConflictKind::SourceChanged);
+        assert_eq!(result.conflicts[0].resolution, ConflictResolution::EditApplied);
+        assert_eq!(prop1(&result), Some(PropertyValue::String("edited_value".to_string())));
+
+        let result = merge_source_and_edits(&source(Some("source_value")), &edits, ConflictResolutionStrategy::SourceWins);
+        assert_eq!(result.conflicts[0].resolution, ConflictResolution::SourceKept);
+        assert_eq!(prop1(&result), Some(PropertyValue::String("source_value".to_string())));
+        assert!(!result.overridden_properties.contains("prop1"));
+
+        let result = merge_source_and_edits(&source(Some("source_value")), &edits, ConflictResolutionStrategy::Manual);
+        assert_eq!(result.pending_conflicts().count(), 1);
+        assert_eq!(prop1(&result), Some(PropertyValue::String("source_value".to_string())));
+
+        // A source that caught up with the edit, or a source that did not change, is clean
+        for value in ["edited_value", "base_value"] {
+            let result = merge_source_and_edits(&source(Some(value)), &edits, ConflictResolutionStrategy::Manual);
+            assert!(result.is_clean());
+            assert_eq!(prop1(&result), Some(PropertyValue::String("edited_value".to_string())));
+        }
+    }
+
+    #[test]
+    fn test_merge_edit_to_property_deleted_from_source() {
+        let edits = [edit("edit1", "user1", Some("edited_value"), Some("base_value"))];
+        let result = merge_source_and_edits(&source(None), &edits, ConflictResolutionStrategy::Manual);
+        let conflict = result.pending_conflicts().next().unwrap();
+        assert_eq!(conflict.kind, ConflictKind::SourceChanged);
+        assert_eq!(conflict.source_value, None);
+        assert!(result.merged_properties.get("prop1").is_none());
+
+        // A property added by an edit is clean while the source still lacks it
+        let edits = [edit("edit1", "user1", Some("edited_value"), None)];
+        assert!(merge_source_and_edits(&source(None), &edits, ConflictResolutionStrategy::Manual).is_clean());
+    }
+
+    #[test]
+    fn test_merge_concurrent_edits_from_different_users() {
+        let first = edit("edit1", "user1", Some("first"), Some("source_value"));
+        let mut second = edit("edit2", "user2", Some("second"), Some("source_value"));
+        second.timestamp = first.timestamp + chrono::Duration::seconds(1);
+        let edits = [second.clone(), first.clone()];
+
+        // The last writer wins unless conflicts are resolved by hand
+        let result = merge_source_and_edits(&source(Some("source_value")), &edits, ConflictResolutionStrategy::SourceWins);
+        assert_eq!(result.conflicts[0].kind, ConflictKind::ConcurrentEdits);
+        assert_eq!(result.conflicts[0].edit.edit_id, "edit2");
+        assert_eq!(result.merged_properties.get("prop1"), Some(&PropertyValue::String("second".to_string())));
+
+        let result = merge_source_and_edits(&source(Some("source_value")), &edits, ConflictResolutionStrategy::Manual);
+        assert_eq!(result.pending_conflicts().count(), 1);
+        assert_eq!(result.merged_properties.get("prop1"), Some(&PropertyValue::String("source_value".to_string())));
+
+        // An edit made after seeing the other user's edit is not concurrent
+        second.base_value = Some(PropertyValue::String("first".to_string()));
+        let result = merge_source_and_edits(&source(Some("source_value")), &[first, second], ConflictResolutionStrategy::Manual);
+        assert!(result.is_clean());
+        assert_eq!(result.merged_properties.get("prop1"), Some(&PropertyValue::String("second".to_string())));
+    }
+
+    #[test]
+    fn test_merge_and_materialize_recomputes_stored_value() {
+        let object_type: ObjectType = serde_json::from_value(serde_json::json!({
+            "id": "city",
+            "displayName": "City",
+            "primaryKey": "id",
+            "properties": [
+                { "id": "id", "type": "string" },
+                { "id": "population", "type": "integer" },
+                { "id": "area", "type": "double" }
+            ],
+            "computedProperties": [{
+                "id": "density",
+                "displayName": "Density",
+                "type": "double",
+                "expression": { "type": "arithmetic", "expression": "population / area" },
+                "dependencies": ["population", "area"],
+                "materialization": "on_write"
+            }]
+        }))
+        .unwrap();
+
         let mut source = PropertyMap::new();
-        source.insert(
-            "prop1".to_string(),
-            PropertyValue::String("source_value".to_string()),
-        );
+        source.insert("population".to_string(), PropertyValue::Integer(1000));
+        source.insert("area".to_string(), PropertyValue::Double(50.0));
+        source.insert("density".to_string(), PropertyValue::Double(20.0));
 
         let edit = UserEdit {
             edit_id: "edit1".to_string(),
-            object_type: "test".to_string(),
-            object_id: "test_id".to_string(),
-            property_name: "prop1".to_string(),
-            property_value: PropertyValue::String("edited_value".to_string()),
+            object_type: "city".to_string(),
+            object_id: "c1".to_string(),
+            property_name: "population".to_string(),
+            property_value: PropertyValue::Integer(5000),
             user_id: "user1".to_string(),
             timestamp: Utc::now(),
             deleted: false,
+            base_value: Some(PropertyValue::Integer(1000)),
+            comment: None,
+            status: EditStatus::Pending,
+            resolved_at: None,
         };
 
-        let result = merge_source_and_edits(&source, &[edit]);
-
-        // Should have a conflict
-        assert!(!result.conflicts.is_empty());
-        assert_eq!(result.conflicts[0].property_name, "prop1");
+        let result = merge_and_materialize(&object_type, &source, &[edit], ConflictResolutionStrategy::EditWins);
+        assert_eq!(
+            result.merged_properties.get("density"),
+            Some(&PropertyValue::Double(100.0))
+        );
     }
 }
@@ -2,14 +2,61 @@ use ontology_engine::PropertyMap;
 use chrono::{DateTime, Utc};
 use uuid::Uuid;
 use sqlx::PgPool;
+use ontology_engine::backup::{BackupComponent, BackupError};
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use thiserror::Error;
 
-/// Write-back queue - stores user edits that overlay source data
+/// Write-back queue - stores user edits that overlay source data. Clones share the
+/// connection pool.
+#[derive(Clone)]
 pub struct WriteBackQueue {
     pool: PgPool,
 }
 
+/// Where a user edit is in its lifecycle. Edits start `Pending` and move once to one of
+/// the other states.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EditStatus {
+    #[default]
+    Pending,
+    /// Written to the object
+    Applied,
+    /// Not written: the source value or a later edit of the property won
+    Rejected,
+    /// Taken back by the user, or replaced by their newer edit of the property
+    Withdrawn,
+}
+
+impl EditStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EditStatus::Pending => "pending",
+            EditStatus::Applied => "applied",
+            EditStatus::Rejected => "rejected",
+            EditStatus::Withdrawn => "withdrawn",
+        }
+    }
+
+    fn parse(status: &str) -> Self {
+        match status {
+            "applied" => EditStatus::Applied,
+            "rejected" => EditStatus::Rejected,
+            "withdrawn" => EditStatus::Withdrawn,
+            _ => EditStatus::Pending,
+        }
+    }
+}
+
+impl std::fmt::Display for EditStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
 /// A user edit record
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UserEdit {
     pub edit_id: String,
     pub object_type: String,
@@ -19,13 +66,181 @@ pub struct UserEdit {
     pub user_id: String,
     pub timestamp: DateTime<Utc>,
     pub deleted: bool, // True if this edit deletes the property
+    /// The value the user saw when editing; `None` if the property was unset. A sync that
+    /// changes the property away from it conflicts with the edit.
+    #[serde(default)]
+    pub base_value: Option<ontology_engine::PropertyValue>,
+    /// Why the user made the edit
+    #[serde(default)]
+    pub comment: Option<String>,
+    #[serde(default)]
+    pub status: EditStatus,
+    /// When the edit left `Pending`
+    #[serde(default)]
+    pub resolved_at: Option<DateTime<Utc>>,
+}
+
+impl UserEdit {
+    /// A pending edit made now
+    pub fn new(
+        object_type: &str,
+        object_id: &str,
+        property_name: &str,
+        property_value: ontology_engine::PropertyValue,
+        base_value: Option<ontology_engine::PropertyValue>,
+        user_id: &str,
+    ) -> Self {
+        Self {
+            edit_id: Uuid::new_v4().to_string(),
+            object_type: object_type.to_string(),
+            object_id: object_id.to_string(),
+            property_name: property_name.to_string(),
+            property_value,
+            user_id: user_id.to_string(),
+            timestamp: Utc::now(),
+            deleted: false,
+            base_value,
+            comment: None,
+            status: EditStatus::Pending,
+            resolved_at: None,
+        }
+    }
+
+    pub fn with_comment(mut self, comment: Option<String>) -> Self {
+        self.comment = comment;
+        self
+    }
+
+    /// Whether `other` is an earlier pending edit this one replaces
+    fn replaces(&self, other: &UserEdit) -> bool {
+        other.status == EditStatus::Pending
+            && other.edit_id != self.edit_id
+            && other.object_type == self.object_type
+            && other.object_id == self.object_id
+            && other.property_name == self.property_name
+            && other.user_id == self.user_id
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum EditQueueError {
+    #[error("Edit '{0}' not found")]
+    NotFound(String),
+    #[error("Edit '{edit_id}' is already {from} and cannot become {to}")]
+    InvalidTransition { edit_id: String, from: EditStatus, to: EditStatus },
+    #[error("Edit queue storage error: {0}")]
+    Storage(String),
+}
+
+impl From<sqlx::Error> for EditQueueError {
+    fn from(e: sqlx::Error) -> Self {
+        EditQueueError::Storage(e.to_string())
+    }
+}
+
+/// Where user edits wait to be applied, and their history once they are settled
+#[async_trait::async_trait]
+pub trait EditQueue: Send + Sync {
+    /// Queue a pending edit. A pending edit of the same property by the same user is
+    /// withdrawn, so each user has at most one edit of a property waiting.
+    async fn submit(&self, edit: UserEdit) -> Result<(), EditQueueError>;
+
+    async fn get_edit(&self, edit_id: &str) -> Result<Option<UserEdit>, EditQueueError>;
+
+    /// Pending edits of an object, oldest first
+    async fn pending_edits(&self, object_type: &str, object_id: &str) -> Result<Vec<UserEdit>, EditQueueError>;
+
+    /// Every edit of objects with ID `object_id`, whatever its status, oldest first
+    async fn edit_history(&self, object_id: &str) -> Result<Vec<UserEdit>, EditQueueError>;
+
+    /// Move a pending edit to `status` and stamp when it was settled
+    async fn resolve(&self, edit_id: &str, status: EditStatus) -> Result<UserEdit, EditQueueError>;
+}
+
+fn check_transition(edit: &UserEdit, status: EditStatus) -> Result<(), EditQueueError> {
+    if edit.status != EditStatus::Pending || status == EditStatus::Pending {
+        return Err(EditQueueError::InvalidTransition {
+            edit_id: edit.edit_id.clone(),
+            from: edit.status,
+            to: status,
+        });
+    }
+    Ok(())
+}
+
+/// Edit queue held in memory; edits are lost when the process exits
+#[derive(Default)]
+pub struct InMemoryEditQueue {
+    edits: Mutex<Vec<UserEdit>>,
+}
+
+impl InMemoryEditQueue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait::async_trait]
+impl EditQueue for InMemoryEditQueue {
+    async fn submit(&self, edit: UserEdit) -> Result<(), EditQueueError> {
+        let mut edits = self.edits.lock().unwrap();
+        for replaced in edits.iter_mut().filter(|other| edit.replaces(other)) {
+            replaced.status = EditStatus::Withdrawn;
+            replaced.resolved_at = Some(edit.timestamp);
+        }
+        edits.push(edit);
+        Ok(())
+    }
+
+    async fn get_edit(&self, edit_id: &str) -> Result<Option<UserEdit>, EditQueueError> {
+        Ok(self.edits.lock().unwrap().iter().find(|edit| edit.edit_id == edit_id).cloned())
+    }
+
+    async fn pending_edits(&self, object_type: &str, object_id: &str) -> Result<Vec<UserEdit>, EditQueueError> {
+        Ok(self
+            .edits
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|edit| {
+                edit.status == EditStatus::Pending && edit.object_type == object_type && edit.object_id == object_id
+            })
+            .cloned()
+            .collect())
+    }
+
+    async fn edit_history(&self, object_id: &str) -> Result<Vec<UserEdit>, EditQueueError> {
+        Ok(self.edits.lock().unwrap().iter().filter(|edit| edit.object_id == object_id).cloned().collect())
+    }
+
+    async fn resolve(&self, edit_id: &str, status: EditStatus) -> Result<UserEdit, EditQueueError> {
+        let mut edits = self.edits.lock().unwrap();
+        let edit = edits
+            .iter_mut()
+            .find(|edit| edit.edit_id == edit_id)
+            .ok_or_else(|| EditQueueError::NotFound(edit_id.to_string()))?;
+        check_transition(edit, status)?;
+        edit.status = status;
+        edit.resolved_at = Some(Utc::now());
+        Ok(edit.clone())
+    }
 }
 
+const EDIT_COLUMNS: &str =
+    "edit_id, object_type, object_id, property_name, property_value, user_id, timestamp, deleted, base_value, comment, status, resolved_at";
+
 impl WriteBackQueue {
     pub fn new(pool: PgPool) -> Self {
         Self { pool }
     }
-    
+
+    /// Connect to the database at `url` and create the schema if needed
+    pub async fn connect(url: &str) -> Result<Self, sqlx::Error> {
+        let queue = Self::new(PgPool::connect(url).await?);
+        queue.initialize().await?;
+        Ok(queue)
+    }
+
     /// Initialize the database schema
     pub async fn initialize(&self) -> Result<(), sqlx::Error> {
         sqlx::query(
@@ -39,121 +254,133 @@ impl WriteBackQueue {
                 user_id TEXT NOT NULL,
                 timestamp TIMESTAMPTZ NOT NULL,
                 deleted BOOLEAN NOT NULL DEFAULT FALSE,
-                UNIQUE(object_type, object_id, property_name)
+                base_value JSONB,
+                comment TEXT,
+                status TEXT NOT NULL DEFAULT 'pending',
+                resolved_at TIMESTAMPTZ
             );
+            ALTER TABLE user_edits ADD COLUMN IF NOT EXISTS base_value JSONB;
+            ALTER TABLE user_edits ADD COLUMN IF NOT EXISTS comment TEXT;
+            ALTER TABLE user_edits ADD COLUMN IF NOT EXISTS status TEXT NOT NULL DEFAULT 'pending';
+            ALTER TABLE user_edits ADD COLUMN IF NOT EXISTS resolved_at TIMESTAMPTZ;
+            -- Settled edits are kept as history, so a property can have many edits
+            ALTER TABLE user_edits DROP CONSTRAINT IF EXISTS user_edits_object_type_object_id_property_name_key;
             CREATE INDEX IF NOT EXISTS idx_user_edits_object ON user_edits(object_type, object_id);
+            CREATE INDEX IF NOT EXISTS idx_user_edits_object_id ON user_edits(object_id);
             CREATE INDEX IF NOT EXISTS idx_user_edits_timestamp ON user_edits(timestamp);
             "#,
         )
         .execute(&self.pool)
         .await?;
-        
+
         Ok(())
     }
-    
-    /// Record a user edit
+
+    /// Record a user edit. `base_value` is the value the user saw, `None` if the property was
+    /// unset. The user's pending edit of the property, if any, is withdrawn.
     pub async fn record_edit(
         &self,
         object_type: &str,
         object_id: &str,
         property_name: &str,
         property_value: &ontology_engine::PropertyValue,
+        base_value: Option<&ontology_engine::PropertyValue>,
         user_id: &str,
     ) -> Result<String, sqlx::Error> {
-        let edit_id = Uuid::new_v4().to_string();
-        let timestamp = Utc::now();
-        
-        let json_value = serde_json::to_value(property_value)
-            .map_err(|e| sqlx::Error::Decode(Box::new(std::io::Error::new(
-                std::io::ErrorKind::InvalidData,
-                format!("Failed to serialize property value: {}", e)
-            ))))?;
-        
-        sqlx::query(
-            r#"
-            INSERT INTO user_edits (edit_id, object_type, object_id, property_name, property_value, user_id, timestamp, deleted)
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
-            ON CONFLICT (object_type, object_id, property_name)
-            DO UPDATE SET
-                property_value = EXCLUDED.property_value,
-                user_id = EXCLUDED.user_id,
-                timestamp = EXCLUDED.timestamp,
-                deleted = EXCLUDED.deleted
-            "#,
-        )
-        .bind(&edit_id)
-        .bind(object_type)
-        .bind(object_id)
-        .bind(property_name)
-        .bind(json_value)
-        .bind(user_id)
-        .bind(timestamp)
-        .bind(false)
-        .execute(&self.pool)
-        .await?;
-        
+        let edit = UserEdit::new(object_type, object_id, property_name, property_value.clone(), base_value.cloned(), user_id);
+        let edit_id = edit.edit_id.clone();
+        self.insert_edit(edit).await?;
         Ok(edit_id)
     }
-    
-    /// Delete a property (mark as deleted)
+
+    /// Delete a property (mark as deleted); `base_value` is as for `record_edit`
     pub async fn delete_property(
         &self,
         object_type: &str,
         object_id: &str,
         property_name: &str,
+        base_value: Option<&ontology_engine::PropertyValue>,
         user_id: &str,
     ) -> Result<(), sqlx::Error> {
-        let edit_id = Uuid::new_v4().to_string();
-        let timestamp = Utc::now();
-        let null_value = serde_json::Value::Null;
-        
+        let mut edit = UserEdit::new(
+            object_type,
+            object_id,
+            property_name,
+            ontology_engine::PropertyValue::Null,
+            base_value.cloned(),
+            user_id,
+        );
+        edit.deleted = true;
+        self.insert_edit(edit).await
+    }
+
+    /// Insert a pending edit, withdrawing the one it replaces
+    async fn insert_edit(&self, edit: UserEdit) -> Result<(), sqlx::Error> {
+        let json_value = to_json(&edit.property_value)?;
+        let base_json = edit.base_value.as_ref().map(to_json).transpose()?;
+
+        let mut tx = self.pool.begin().await?;
         sqlx::query(
             r#"
-            INSERT INTO user_edits (edit_id, object_type, object_id, property_name, property_value, user_id, timestamp, deleted)
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
-            ON CONFLICT (object_type, object_id, property_name)
-            DO UPDATE SET
-                user_id = EXCLUDED.user_id,
-                timestamp = EXCLUDED.timestamp,
-                deleted = TRUE
+            UPDATE user_edits SET status = 'withdrawn', resolved_at = $5
+            WHERE object_type = $1 AND object_id = $2 AND property_name = $3 AND user_id = $4 AND status = 'pending'
             "#,
         )
-        .bind(&edit_id)
-        .bind(object_type)
-        .bind(object_id)
-        .bind(property_name)
-        .bind(null_value)
-        .bind(user_id)
-        .bind(timestamp)
-        .bind(true)
-        .execute(&self.pool)
+        .bind(&edit.object_type)
+        .bind(&edit.object_id)
+        .bind(&edit.property_name)
+        .bind(&edit.user_id)
+        .bind(edit.timestamp)
+        .execute(&mut *tx)
         .await?;
-        
-        Ok(())
+
+        sqlx::query(
+            r#"
+            INSERT INTO user_edits (edit_id, object_type, object_id, property_name, property_value, user_id, timestamp, deleted, base_value, comment, status, resolved_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+            "#,
+        )
+        .bind(&edit.edit_id)
+        .bind(&edit.object_type)
+        .bind(&edit.object_id)
+        .bind(&edit.property_name)
+        .bind(json_value)
+        .bind(&edit.user_id)
+        .bind(edit.timestamp)
+        .bind(edit.deleted)
+        .bind(base_json)
+        .bind(&edit.comment)
+        .bind(edit.status.as_str())
+        .bind(edit.resolved_at)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await
     }
-    
-    /// Get all edits for an object
+
+    /// Get all pending edits for an object, newest first
     pub async fn get_edits_for_object(
         &self,
         object_type: &str,
         object_id: &str,
     ) -> Result<Vec<UserEdit>, sqlx::Error> {
-        let rows = sqlx::query_as::<_, EditRow>(
+        let rows = sqlx::query_as::<_, EditRow>(&format!(
             r#"
-            SELECT edit_id, object_type, object_id, property_name, property_value, user_id, timestamp, deleted
+            SELECT {}
             FROM user_edits
-            WHERE object_type = $1 AND object_id = $2 AND deleted = FALSE
+            WHERE object_type = $1 AND object_id = $2 AND deleted = FALSE AND status = 'pending'
             ORDER BY timestamp DESC
             "#,
-        )
+            EDIT_COLUMNS
+        ))
         .bind(object_type)
         .bind(object_id)
         .fetch_all(&self.pool)
         .await?;
-        
+
         Ok(rows.into_iter().map(|r| r.into()).collect())
     }
-    
+
     /// Get all active (non-deleted) edits for an object
     pub async fn get_active_edits(
         &self,
@@ -162,7 +389,7 @@ impl WriteBackQueue {
     ) -> Result<Vec<UserEdit>, sqlx::Error> {
         self.get_edits_for_object(object_type, object_id).await
     }
-    
+
     /// Revert an edit (delete it)
     pub async fn revert_edit(
         &self,
@@ -174,11 +401,152 @@ impl WriteBackQueue {
         .bind(edit_id)
         .execute(&self.pool)
         .await?;
-        
+
         Ok(())
     }
 }
 
+#[async_trait::async_trait]
+impl EditQueue for WriteBackQueue {
+    async fn submit(&self, edit: UserEdit) -> Result<(), EditQueueError> {
+        Ok(self.insert_edit(edit).await?)
+    }
+
+    async fn get_edit(&self, edit_id: &str) -> Result<Option<UserEdit>, EditQueueError> {
+        let row = sqlx::query_as::<_, EditRow>(&format!("SELECT {} FROM user_edits WHERE edit_id = $1", EDIT_COLUMNS))
+            .bind(edit_id)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(row.map(UserEdit::from))
+    }
+
+    async fn pending_edits(&self, object_type: &str, object_id: &str) -> Result<Vec<UserEdit>, EditQueueError> {
+        let rows = sqlx::query_as::<_, EditRow>(&format!(
+            r#"
+            SELECT {}
+            FROM user_edits
+            WHERE object_type = $1 AND object_id = $2 AND status = 'pending'
+            ORDER BY timestamp, edit_id
+            "#,
+            EDIT_COLUMNS
+        ))
+        .bind(object_type)
+        .bind(object_id)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows.into_iter().map(UserEdit::from).collect())
+    }
+
+    async fn edit_history(&self, object_id: &str) -> Result<Vec<UserEdit>, EditQueueError> {
+        let rows = sqlx::query_as::<_, EditRow>(&format!(
+            "SELECT {} FROM user_edits WHERE object_id = $1 ORDER BY timestamp, edit_id",
+            EDIT_COLUMNS
+        ))
+        .bind(object_id)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows.into_iter().map(UserEdit::from).collect())
+    }
+
+    async fn resolve(&self, edit_id: &str, status: EditStatus) -> Result<UserEdit, EditQueueError> {
+        let mut tx = self.pool.begin().await?;
+        let row = sqlx::query_as::<_, EditRow>(&format!(
+            "SELECT {} FROM user_edits WHERE edit_id = $1 FOR UPDATE",
+            EDIT_COLUMNS
+        ))
+        .bind(edit_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+        let mut edit = UserEdit::from(row.ok_or_else(|| EditQueueError::NotFound(edit_id.to_string()))?);
+        check_transition(&edit, status)?;
+        edit.status = status;
+        edit.resolved_at = Some(Utc::now());
+        sqlx::query("UPDATE user_edits SET status = $2, resolved_at = $3 WHERE edit_id = $1")
+            .bind(edit_id)
+            .bind(status.as_str())
+            .bind(edit.resolved_at)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok(edit)
+    }
+}
+
+fn to_json(value: &ontology_engine::PropertyValue) -> Result<serde_json::Value, sqlx::Error> {
+    serde_json::to_value(value)
+        .map_err(|e| sqlx::Error::Decode(Box::new(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("Failed to serialize property value: {}", e)
+        ))))
+}
+
+#[async_trait::async_trait]
+impl BackupComponent for WriteBackQueue {
+    fn backup_name(&self) -> String {
+        "writeback_queue".to_string()
+    }
+
+    async fn export_for_backup(&self) -> Result<Vec<u8>, BackupError> {
+        let rows = sqlx::query_as::<_, EditRow>(&format!(
+            r#"
+            SELECT {}
+            FROM user_edits
+            ORDER BY timestamp, edit_id
+            "#,
+            EDIT_COLUMNS
+        ))
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| BackupError::Component(e.to_string()))?;
+
+        let edits: Vec<UserEdit> = rows.into_iter().map(|r| r.into()).collect();
+        serde_json::to_vec(&edits).map_err(|e| BackupError::Serialization(e.to_string()))
+    }
+
+    async fn import_from_backup(&mut self, data: &[u8]) -> Result<(), BackupError> {
+        let edits: Vec<UserEdit> = serde_json::from_slice(data)
+            .map_err(|e| BackupError::Serialization(e.to_string()))?;
+        let db_err = |e: sqlx::Error| BackupError::Component(e.to_string());
+
+        // Replace the table contents in a single transaction so a failed restore leaves it untouched
+        let mut tx = self.pool.begin().await.map_err(db_err)?;
+        sqlx::query("DELETE FROM user_edits")
+            .execute(&mut *tx)
+            .await
+            .map_err(db_err)?;
+
+        for edit in edits {
+            let json_value = serde_json::to_value(&edit.property_value)
+                .map_err(|e| BackupError::Serialization(e.to_string()))?;
+            let base_json = edit.base_value.as_ref().map(serde_json::to_value).transpose()
+                .map_err(|e| BackupError::Serialization(e.to_string()))?;
+            sqlx::query(
+                r#"
+                INSERT INTO user_edits (edit_id, object_type, object_id, property_name, property_value, user_id, timestamp, deleted, base_value, comment, status, resolved_at)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+                "#,
+            )
+            .bind(&edit.edit_id)
+            .bind(&edit.object_type)
+            .bind(&edit.object_id)
+            .bind(&edit.property_name)
+            .bind(json_value)
+            .bind(&edit.user_id)
+            .bind(edit.timestamp)
+            .bind(edit.deleted)
+            .bind(base_json)
+            .bind(&edit.comment)
+            .bind(edit.status.as_str())
+            .bind(edit.resolved_at)
+            .execute(&mut *tx)
+            .await
+            .map_err(db_err)?;
+        }
+
+        tx.commit().await.map_err(db_err)
+    }
+}
+
 #[derive(sqlx::FromRow)]
 struct EditRow {
     edit_id: String,
@@ -194,6 +562,10 @@ struct EditRow {
     user_id: String,
     timestamp: DateTime<Utc>,
     deleted: bool,
+    base_value: Option<sqlx::types::Json<serde_json::Value>>,
+    comment: Option<String>,
+    status: String,
+    resolved_at: Option<DateTime<Utc>>,
 }
 
 impl From<EditRow> for UserEdit {
@@ -201,7 +573,7 @@ impl From<EditRow> for UserEdit {
         // Try to deserialize property_value back to PropertyValue
         let property_value: ontology_engine::PropertyValue = serde_json::from_value(row.property_value.0)
             .unwrap_or(ontology_engine::PropertyValue::Null);
-        
+
         Self {
             edit_id: row.edit_id,
             object_type: row.object_type,
@@ -211,7 +583,44 @@ impl From<EditRow> for UserEdit {
             user_id: row.user_id,
             timestamp: row.timestamp,
             deleted: row.deleted,
+            base_value: row.base_value.and_then(|value| serde_json::from_value(value.0).ok()),
+            comment: row.comment,
+            status: EditStatus::parse(&row.status),
+            resolved_at: row.resolved_at,
         }
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ontology_engine::PropertyValue;
+
+    #[tokio::test]
+    async fn test_in_memory_queue_lifecycle() {
+        let queue = InMemoryEditQueue::new();
+        let edit = |value: i64, user_id: &str| {
+            UserEdit::new("city", "c1", "population", PropertyValue::Integer(value), None, user_id)
+        };
+        let first = edit(1000, "alice");
+        let replacement = edit(1100, "alice");
+        let other = edit(1200, "bob");
+        for edit in [first.clone(), replacement.clone(), other.clone()] {
+            queue.submit(edit).await.unwrap();
+        }
+
+        // A user's newer edit of a property replaces their pending one
+        let pending: Vec<String> = queue.pending_edits("city", "c1").await.unwrap().into_iter().map(|e| e.edit_id).collect();
+        assert_eq!(pending, vec![replacement.edit_id.clone(), other.edit_id.clone()]);
+        assert_eq!(queue.get_edit(&first.edit_id).await.unwrap().unwrap().status, EditStatus::Withdrawn);
+
+        let applied = queue.resolve(&replacement.edit_id, EditStatus::Applied).await.unwrap();
+        assert!(applied.resolved_at.is_some());
+        match queue.resolve(&replacement.edit_id, EditStatus::Withdrawn).await {
+            Err(EditQueueError::InvalidTransition { from: EditStatus::Applied, to: EditStatus::Withdrawn, .. }) => {}
+            other => panic!("expected an invalid transition, got {:?}", other),
+        }
+        assert!(matches!(queue.resolve("missing", EditStatus::Rejected).await, Err(EditQueueError::NotFound(_))));
+        assert_eq!(queue.edit_history("c1").await.unwrap().len(), 3);
+    }
+}